@@ -0,0 +1,22 @@
+//! Build script for otl-api
+//!
+//! Compiles the gRPC protocol definitions when the `grpc` feature is enabled.
+//! A vendored `protoc` is used so no system installation is required.
+//!
+//! Author: hephaex@gmail.com
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/otl.proto");
+
+    #[cfg(feature = "grpc")]
+    {
+        if std::env::var_os("PROTOC").is_none() {
+            std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+        }
+        tonic_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/otl.proto"], &["proto"])?;
+    }
+
+    Ok(())
+}
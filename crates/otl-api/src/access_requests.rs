@@ -0,0 +1,781 @@
+//! Document access requests
+//!
+//! A user the document ACL denies can ask for access instead of stopping at
+//! a 403. The request goes to the people who may decide it: the document
+//! owner and the editors of the owning department (admins when there are
+//! none), who are notified through the configured webhooks and the
+//! notification channels. Admins can decide any request.
+//!
+//! Approving adds the user to the document's `allowed_users`, optionally
+//! until a given time. Access checks read the list through
+//! [`LIVE_ALLOWED_USERS`], so a grant ends at its expiry; a background job
+//! later removes it from the list.
+//! Access is only ever requested for Confidential and Restricted documents,
+//! the levels that honor `allowed_users`.
+//!
+//! Answers from the RAG pipeline filter on the ACL stored with the vectors
+//! at indexing time, which carries no user list; grants apply to the
+//! document, chunk, graph and export endpoints.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::audit::{audit_log, AuditEvent};
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::AppError;
+use crate::handlers::documents::parse_access_level;
+use crate::notifications::{self, Audience, NotificationEvent, LINK_VAR};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Default seconds between checks for expired grants (hourly)
+const DEFAULT_EXPIRY_CHECK_INTERVAL_SECS: u64 = 3600;
+
+/// Seconds to wait for a webhook to accept a notification
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+const REQUEST_SELECT: &str = "SELECT r.id, r.document_id, d.title AS document_title, \
+     r.requester_id, u.email AS requester_email, u.name AS requester_name, r.reason, \
+     r.requested_until, r.status, r.decided_by, r.decided_at, r.decision_note, \
+     r.access_expires_at, r.created_at \
+     FROM access_requests r \
+     JOIN documents d ON d.id = r.document_id \
+     JOIN users u ON u.id = r.requester_id";
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// Who hears about access requests and how often grants are expired
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessRequestPolicy {
+    /// URLs receiving new requests and decisions as JSON `POST`s
+    pub webhook_urls: Vec<String>,
+    /// Time between expiry checks (`None` never withdraws grants)
+    pub expiry_check_interval: Option<Duration>,
+}
+
+impl Default for AccessRequestPolicy {
+    fn default() -> Self {
+        Self {
+            webhook_urls: Vec::new(),
+            expiry_check_interval: Some(Duration::from_secs(DEFAULT_EXPIRY_CHECK_INTERVAL_SECS)),
+        }
+    }
+}
+
+impl AccessRequestPolicy {
+    /// Policy from `ACCESS_REQUEST_WEBHOOK_URLS` (comma-separated) and
+    /// `ACCESS_GRANT_EXPIRY_CHECK_INTERVAL_SECS` (0 disables the job)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(urls) = std::env::var("ACCESS_REQUEST_WEBHOOK_URLS") {
+            policy.webhook_urls = urls
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Ok(secs) = std::env::var("ACCESS_GRANT_EXPIRY_CHECK_INTERVAL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(0) => policy.expiry_check_interval = None,
+                Ok(secs) => policy.expiry_check_interval = Some(Duration::from_secs(secs)),
+                Err(_) => tracing::warn!(
+                    "Ignoring invalid ACCESS_GRANT_EXPIRY_CHECK_INTERVAL_SECS: {}",
+                    secs
+                ),
+            }
+        }
+        policy
+    }
+}
+
+// ============================================================================
+// Requests
+// ============================================================================
+
+/// Where a request stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessRequestStatus {
+    /// Waiting for a decision
+    Pending,
+    /// Access granted
+    Approved,
+    /// Access refused
+    Denied,
+    /// Access was granted and has run out
+    Expired,
+}
+
+impl AccessRequestStatus {
+    /// Name stored in `access_requests.status`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Denied => "denied",
+            Self::Expired => "expired",
+        }
+    }
+
+    /// Parse a stored status
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "approved" => Some(Self::Approved),
+            "denied" => Some(Self::Denied),
+            "expired" => Some(Self::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// Request for access to a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AccessRequest {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub document_title: String,
+    pub requester_id: Uuid,
+    pub requester_email: String,
+    pub requester_name: String,
+    /// Why the requester needs the document
+    pub reason: Option<String>,
+    /// Access end the requester asked for
+    pub requested_until: Option<DateTime<Utc>>,
+    pub status: AccessRequestStatus,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub decision_note: Option<String>,
+    /// When an approved grant is withdrawn (`None`: never)
+    pub access_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RequestRow {
+    id: Uuid,
+    document_id: Uuid,
+    document_title: String,
+    requester_id: Uuid,
+    requester_email: String,
+    requester_name: String,
+    reason: Option<String>,
+    requested_until: Option<DateTime<Utc>>,
+    status: String,
+    decided_by: Option<Uuid>,
+    decided_at: Option<DateTime<Utc>>,
+    decision_note: Option<String>,
+    access_expires_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl RequestRow {
+    fn into_request(self) -> Option<AccessRequest> {
+        let Some(status) = AccessRequestStatus::parse(&self.status) else {
+            tracing::warn!(
+                "Skipping access request {} with status {}",
+                self.id,
+                self.status
+            );
+            return None;
+        };
+        Some(AccessRequest {
+            id: self.id,
+            document_id: self.document_id,
+            document_title: self.document_title,
+            requester_id: self.requester_id,
+            requester_email: self.requester_email,
+            requester_name: self.requester_name,
+            reason: self.reason,
+            requested_until: self.requested_until,
+            status,
+            decided_by: self.decided_by,
+            decided_at: self.decided_at,
+            decision_note: self.decision_note,
+            access_expires_at: self.access_expires_at,
+            created_at: self.created_at,
+        })
+    }
+}
+
+/// User told about a request
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Approver {
+    pub id: Uuid,
+    pub email: String,
+    pub name: String,
+}
+
+/// ACL and ownership of a requested document
+#[derive(sqlx::FromRow)]
+struct DocumentRow {
+    access_level: String,
+    owner_id: Option<String>,
+    department: Option<String>,
+    required_roles: Option<Vec<String>>,
+    allowed_users: Option<Vec<String>>,
+}
+
+impl DocumentRow {
+    fn acl(&self) -> otl_core::DocumentAcl {
+        otl_core::DocumentAcl {
+            access_level: parse_access_level(&self.access_level),
+            owner_id: self.owner_id.clone(),
+            department: self.department.clone(),
+            required_roles: self.required_roles.clone().unwrap_or_default(),
+            allowed_users: self.allowed_users.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Whether a user may decide requests for a document
+///
+/// Admins decide any request; the owner and the editors of the owning
+/// department decide those for their documents.
+pub fn may_decide(
+    owner_id: Option<&str>,
+    department: Option<&str>,
+    user: &AuthenticatedUser,
+) -> bool {
+    user.is_admin()
+        || owner_id == Some(user.user_id.to_string().as_str())
+        || (user.is_editor_or_higher()
+            && department.is_some()
+            && department == user.department.as_deref())
+}
+
+async fn fetch_request(state: &AppState, id: Uuid) -> Result<AccessRequest, AppError> {
+    let row: Option<RequestRow> = sqlx::query_as(&format!("{REQUEST_SELECT} WHERE r.id = $1"))
+        .bind(id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch access request: {e}")))?;
+    row.and_then(RequestRow::into_request)
+        .ok_or_else(|| AppError::NotFound(format!("Access request {id} not found")))
+}
+
+/// Ask for access to a document the user cannot read
+///
+/// `requested_until` is the access end the user asks for; approvers may
+/// change it. A user has at most one pending request per document.
+pub async fn request_access(
+    state: &AppState,
+    document_id: Uuid,
+    user: &AuthenticatedUser,
+    reason: Option<String>,
+    requested_until: Option<DateTime<Utc>>,
+    ip_address: Option<String>,
+) -> Result<AccessRequest, AppError> {
+    let document: DocumentRow = sqlx::query_as(&format!(
+        "SELECT d.access_level::text, d.owner_id, d.department, d.required_roles, \
+         {LIVE_ALLOWED_USERS} AS allowed_users \
+         FROM documents d WHERE d.id = $1 AND d.deleted_at IS NULL"
+    ))
+    .bind(document_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?
+    .ok_or_else(|| AppError::NotFound(format!("Document {document_id} not found")))?;
+
+    if document.acl().can_access(&user.to_acl_user()) {
+        return Err(AppError::BadRequest(
+            "You already have access to this document".to_string(),
+        ));
+    }
+    if requested_until.is_some_and(|until| until <= Utc::now()) {
+        return Err(AppError::BadRequest(
+            "requested_until must be in the future".to_string(),
+        ));
+    }
+
+    let id: Option<Uuid> = sqlx::query_scalar(
+        "INSERT INTO access_requests (document_id, requester_id, reason, requested_until) \
+         VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (document_id, requester_id) WHERE status = 'pending' DO NOTHING \
+         RETURNING id",
+    )
+    .bind(document_id)
+    .bind(user.user_id)
+    .bind(reason.filter(|r| !r.trim().is_empty()))
+    .bind(requested_until)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to create access request: {e}")))?;
+    let id = id.ok_or_else(|| {
+        AppError::BadRequest("An access request for this document is already pending".to_string())
+    })?;
+
+    audit_log(&AuditEvent::AccessRequested {
+        request_id: id,
+        document_id,
+        user_id: user.user_id,
+        ip_address,
+    });
+
+    let request = fetch_request(state, id).await?;
+    let approvers = approvers(state, &document).await?;
+    notifications::notify(
+        state,
+        NotificationEvent::AccessRequested,
+        vec![
+            ("requester", request.requester_name.clone()),
+            ("document", request.document_title.clone()),
+            (
+                "reason",
+                request.reason.clone().unwrap_or_else(|| "-".to_string()),
+            ),
+            ("until", format_until(request.requested_until)),
+            (LINK_VAR, "/api/v1/access-requests/pending".to_string()),
+        ],
+        Audience::Users(approvers.iter().map(|a| a.id).collect()),
+    );
+    notify(
+        &state.access_requests,
+        "access_request.created",
+        &request,
+        approvers,
+    );
+    Ok(request)
+}
+
+/// Active users who decide requests for a document: its owner and the
+/// editors of its department, or the admins if there are none
+async fn approvers(state: &AppState, document: &DocumentRow) -> Result<Vec<Approver>, AppError> {
+    let approvers: Vec<Approver> = sqlx::query_as(
+        "SELECT id, email, name FROM users WHERE is_active \
+         AND (id::text = $1 OR (role = 'editor' AND department = $2)) ORDER BY email",
+    )
+    .bind(document.owner_id.as_deref())
+    .bind(document.department.as_deref())
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to find approvers: {e}")))?;
+    if !approvers.is_empty() {
+        return Ok(approvers);
+    }
+
+    sqlx::query_as(
+        "SELECT id, email, name FROM users WHERE is_active AND role = 'admin' ORDER BY email",
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to find approvers: {e}")))
+}
+
+/// Requests made by a user, newest first
+pub async fn list_own(
+    state: &AppState,
+    user: &AuthenticatedUser,
+) -> Result<Vec<AccessRequest>, AppError> {
+    let rows: Vec<RequestRow> = sqlx::query_as(&format!(
+        "{REQUEST_SELECT} WHERE r.requester_id = $1 ORDER BY r.created_at DESC"
+    ))
+    .bind(user.user_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list access requests: {e}")))?;
+    Ok(rows
+        .into_iter()
+        .filter_map(RequestRow::into_request)
+        .collect())
+}
+
+/// Pending requests the user may decide, oldest first
+pub async fn list_pending(
+    state: &AppState,
+    user: &AuthenticatedUser,
+) -> Result<Vec<AccessRequest>, AppError> {
+    let editor_department = user
+        .department
+        .as_deref()
+        .filter(|_| user.is_editor_or_higher());
+    let rows: Vec<RequestRow> = sqlx::query_as(&format!(
+        "{REQUEST_SELECT} WHERE r.status = 'pending' AND r.requester_id <> $1 \
+         AND ($2 OR d.owner_id = $3 OR d.department = $4) ORDER BY r.created_at"
+    ))
+    .bind(user.user_id)
+    .bind(user.is_admin())
+    .bind(user.user_id.to_string())
+    .bind(editor_department)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list access requests: {e}")))?;
+    Ok(rows
+        .into_iter()
+        .filter_map(RequestRow::into_request)
+        .collect())
+}
+
+/// Decision on a pending request
+#[derive(Debug, Clone, Default)]
+pub struct Decision {
+    pub approve: bool,
+    /// Access end of an approval; defaults to the one requested
+    pub expires_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+}
+
+/// Approve or deny a pending request
+///
+/// Approving adds the requester to the document's `allowed_users`.
+pub async fn decide(
+    state: &AppState,
+    id: Uuid,
+    decider: &AuthenticatedUser,
+    decision: Decision,
+    ip_address: Option<String>,
+) -> Result<AccessRequest, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct PendingRow {
+        document_id: Uuid,
+        requester_id: Uuid,
+        requested_until: Option<DateTime<Utc>>,
+        status: String,
+        owner_id: Option<String>,
+        department: Option<String>,
+    }
+
+    let mut tx = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let row: PendingRow = sqlx::query_as(
+        "SELECT r.document_id, r.requester_id, r.requested_until, r.status, \
+         d.owner_id, d.department \
+         FROM access_requests r JOIN documents d ON d.id = r.document_id \
+         WHERE r.id = $1 FOR UPDATE OF r",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch access request: {e}")))?
+    .ok_or_else(|| AppError::NotFound(format!("Access request {id} not found")))?;
+
+    if !may_decide(row.owner_id.as_deref(), row.department.as_deref(), decider) {
+        return Err(AppError::Forbidden(
+            "Only the document owner, an editor of its department or an admin can decide this request"
+                .to_string(),
+        ));
+    }
+    if row.status != AccessRequestStatus::Pending.as_str() {
+        return Err(AppError::BadRequest(format!(
+            "Access request is already {}",
+            row.status
+        )));
+    }
+
+    let (status, expires_at) = if decision.approve {
+        let expires_at = decision.expires_at.or(row.requested_until);
+        if expires_at.is_some_and(|at| at <= Utc::now()) {
+            return Err(AppError::BadRequest(
+                "expires_at must be in the future".to_string(),
+            ));
+        }
+        sqlx::query(
+            "UPDATE documents \
+             SET allowed_users = array_append(COALESCE(allowed_users, '{}'), $2), updated_at = NOW() \
+             WHERE id = $1 AND NOT ($2 = ANY(COALESCE(allowed_users, '{}')))",
+        )
+        .bind(row.document_id)
+        .bind(row.requester_id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to grant access: {e}")))?;
+        (AccessRequestStatus::Approved, expires_at)
+    } else {
+        (AccessRequestStatus::Denied, None)
+    };
+
+    sqlx::query(
+        "UPDATE access_requests SET status = $2, decided_by = $3, decided_at = NOW(), \
+         decision_note = $4, access_expires_at = $5 WHERE id = $1",
+    )
+    .bind(id)
+    .bind(status.as_str())
+    .bind(decider.user_id)
+    .bind(decision.note.filter(|n| !n.trim().is_empty()))
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to record decision: {e}")))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to commit decision: {e}")))?;
+
+    audit_log(&AuditEvent::AccessRequestDecided {
+        request_id: id,
+        document_id: row.document_id,
+        user_id: row.requester_id,
+        approved: decision.approve,
+        access_expires_at: expires_at,
+        decided_by: decider.user_id,
+        ip_address,
+    });
+
+    let request = fetch_request(state, id).await?;
+    let requester = Approver {
+        id: request.requester_id,
+        email: request.requester_email.clone(),
+        name: request.requester_name.clone(),
+    };
+    let event = if decision.approve {
+        "access_request.approved"
+    } else {
+        "access_request.denied"
+    };
+    notifications::notify(
+        state,
+        NotificationEvent::AccessDecided,
+        vec![
+            ("document", request.document_title.clone()),
+            ("decision", request.status.as_str().to_string()),
+            ("until", format_until(request.access_expires_at)),
+            (
+                "note",
+                request
+                    .decision_note
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            (
+                LINK_VAR,
+                format!("/api/v1/documents/{}", request.document_id),
+            ),
+        ],
+        Audience::Users(vec![request.requester_id]),
+    );
+    notify(&state.access_requests, event, &request, vec![requester]);
+    Ok(request)
+}
+
+/// Access end as shown in notifications
+fn format_until(until: Option<DateTime<Utc>>) -> String {
+    until.map_or_else(
+        || "no end date".to_string(),
+        |at| at.format("%Y-%m-%d %H:%M UTC").to_string(),
+    )
+}
+
+// ============================================================================
+// Expiry
+// ============================================================================
+
+/// SQL expression for the `allowed_users` of document `d` without users
+/// whose approved requests have all run out
+///
+/// Select it instead of the bare column wherever the ACL decides access.
+pub(crate) const LIVE_ALLOWED_USERS: &str =
+    "ARRAY(SELECT u FROM unnest(COALESCE(d.allowed_users, '{}')) AS u \
+     WHERE NOT EXISTS (SELECT 1 FROM access_requests r WHERE r.document_id = d.id \
+         AND r.requester_id::text = u AND r.status = 'approved' AND r.access_expires_at <= NOW()) \
+     OR EXISTS (SELECT 1 FROM access_requests r WHERE r.document_id = d.id \
+         AND r.requester_id::text = u AND r.status = 'approved' \
+         AND (r.access_expires_at IS NULL OR r.access_expires_at > NOW())))";
+
+/// Mark approved requests that ran out as expired and remove their users
+/// from `allowed_users`; returns the number expired
+///
+/// Cleanup only: [`LIVE_ALLOWED_USERS`] already denies expired grants. A
+/// user with another live grant on the document stays listed.
+pub async fn expire_grants(state: &AppState) -> Result<usize, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct ExpiredRow {
+        id: Uuid,
+        document_id: Uuid,
+        requester_id: Uuid,
+    }
+
+    let mut tx = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let expired: Vec<ExpiredRow> = sqlx::query_as(
+        "UPDATE access_requests SET status = 'expired' \
+         WHERE status = 'approved' AND access_expires_at <= NOW() \
+         RETURNING id, document_id, requester_id",
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to expire access grants: {e}")))?;
+
+    for grant in &expired {
+        sqlx::query(
+            "UPDATE documents SET allowed_users = array_remove(allowed_users, $2), \
+             updated_at = NOW() WHERE id = $1 AND NOT EXISTS (\
+                 SELECT 1 FROM access_requests r WHERE r.document_id = $1 \
+                 AND r.requester_id = $3 AND r.status = 'approved' \
+                 AND (r.access_expires_at IS NULL OR r.access_expires_at > NOW()))",
+        )
+        .bind(grant.document_id)
+        .bind(grant.requester_id.to_string())
+        .bind(grant.requester_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to withdraw access: {e}")))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to commit expiry: {e}")))?;
+
+    for grant in &expired {
+        audit_log(&AuditEvent::AccessGrantExpired {
+            request_id: grant.id,
+            document_id: grant.document_id,
+            user_id: grant.requester_id,
+        });
+    }
+    Ok(expired.len())
+}
+
+/// Run [`expire_grants`] periodically in the background
+///
+/// Does nothing if the policy has no check interval.
+pub fn spawn_expiry_job(state: Arc<AppState>, policy: AccessRequestPolicy) {
+    let Some(interval) = policy.expiry_check_interval else {
+        tracing::info!("Access grant expiry job disabled");
+        return;
+    };
+    tracing::info!("Expiring access grants every {}s", interval.as_secs());
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match expire_grants(&state).await {
+                Ok(0) => {}
+                Ok(expired) => tracing::info!("Withdrew {} expired access grants", expired),
+                Err(e) => tracing::warn!("Access grant expiry failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Notification
+// ============================================================================
+
+/// Webhook body announcing a request or decision
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    event: &'a str,
+    request: &'a AccessRequest,
+    /// Users to tell: the approvers of a new request, the requester of a
+    /// decision
+    recipients: &'a [Approver],
+}
+
+/// Post an event to every webhook in the background
+fn notify(
+    policy: &AccessRequestPolicy,
+    event: &'static str,
+    request: &AccessRequest,
+    recipients: Vec<Approver>,
+) {
+    if policy.webhook_urls.is_empty() {
+        return;
+    }
+    let urls = policy.webhook_urls.clone();
+    let request = request.clone();
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("Cannot build webhook client: {}", e);
+                return;
+            }
+        };
+        let payload = WebhookPayload {
+            event,
+            request: &request,
+            recipients: &recipients,
+        };
+        for url in &urls {
+            let result = client
+                .post(url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            if let Err(e) = result {
+                tracing::warn!("Access request webhook {} failed: {}", url, e);
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(role: &str, department: Option<&str>) -> AuthenticatedUser {
+        AuthenticatedUser {
+            user_id: Uuid::new_v4(),
+            email: "kim@example.com".to_string(),
+            name: "김인사".to_string(),
+            role: role.to_string(),
+            department: department.map(str::to_string),
+            jti: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [
+            AccessRequestStatus::Pending,
+            AccessRequestStatus::Approved,
+            AccessRequestStatus::Denied,
+            AccessRequestStatus::Expired,
+        ] {
+            assert_eq!(AccessRequestStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(AccessRequestStatus::parse("revoked"), None);
+    }
+
+    #[test]
+    fn test_who_may_decide() {
+        let owner = user("viewer", None);
+        let owner_id = owner.user_id.to_string();
+        let owner_id = Some(owner_id.as_str());
+        let hr = Some("인사팀");
+
+        assert!(may_decide(owner_id, hr, &owner));
+        assert!(may_decide(owner_id, hr, &user("admin", None)));
+        assert!(may_decide(owner_id, hr, &user("editor", hr)));
+        // Viewers of the department and editors of another cannot
+        assert!(!may_decide(owner_id, hr, &user("viewer", hr)));
+        assert!(!may_decide(owner_id, hr, &user("editor", Some("재무팀"))));
+        // Without an owning department only the owner and admins can
+        assert!(!may_decide(None, None, &user("editor", None)));
+    }
+
+    #[test]
+    fn test_default_policy_expires_hourly() {
+        let policy = AccessRequestPolicy::default();
+        assert!(policy.webhook_urls.is_empty());
+        assert_eq!(
+            policy.expiry_check_interval,
+            Some(Duration::from_secs(DEFAULT_EXPIRY_CHECK_INTERVAL_SECS))
+        );
+    }
+}
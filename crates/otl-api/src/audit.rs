@@ -151,6 +151,105 @@ pub enum AuditEvent {
         unlocked_by: Option<Uuid>,
         ip_address: Option<String>,
     },
+
+    /// Role changed by an admin
+    UserRoleChanged {
+        user_id: Uuid,
+        email: String,
+        old_role: String,
+        new_role: String,
+        changed_by: Uuid,
+        ip_address: Option<String>,
+    },
+
+    /// Department changed by an admin
+    UserDepartmentChanged {
+        user_id: Uuid,
+        email: String,
+        old_department: Option<String>,
+        new_department: Option<String>,
+        changed_by: Uuid,
+        ip_address: Option<String>,
+    },
+
+    /// Account deactivated or reactivated by an admin
+    AccountStatusChanged {
+        user_id: Uuid,
+        email: String,
+        is_active: bool,
+        changed_by: Uuid,
+        ip_address: Option<String>,
+    },
+
+    /// Password reset token issued by an admin
+    PasswordResetIssued {
+        user_id: Uuid,
+        email: String,
+        requested_by: Uuid,
+        expires_at: DateTime<Utc>,
+        ip_address: Option<String>,
+    },
+
+    /// Session revoked by its owner
+    SessionRevoked {
+        user_id: Uuid,
+        session_id: Uuid,
+        ip_address: Option<String>,
+    },
+
+    /// User signed out of all sessions by an admin
+    ForcedLogout {
+        user_id: Uuid,
+        email: String,
+        revoked_sessions: u64,
+        forced_by: Uuid,
+        ip_address: Option<String>,
+    },
+
+    /// Signed link to a document issued
+    DocumentShared {
+        document_id: Uuid,
+        scope: String,
+        expires_at: DateTime<Utc>,
+        shared_by: Uuid,
+        recipient: Option<String>,
+        ip_address: Option<String>,
+    },
+
+    /// Document opened through a signed link
+    SharedDocumentAccessed {
+        document_id: Uuid,
+        scope: String,
+        shared_by: Uuid,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    },
+
+    /// Access to a document requested by a user its ACL denies
+    AccessRequested {
+        request_id: Uuid,
+        document_id: Uuid,
+        user_id: Uuid,
+        ip_address: Option<String>,
+    },
+
+    /// Access request approved or denied
+    AccessRequestDecided {
+        request_id: Uuid,
+        document_id: Uuid,
+        user_id: Uuid,
+        approved: bool,
+        access_expires_at: Option<DateTime<Utc>>,
+        decided_by: Uuid,
+        ip_address: Option<String>,
+    },
+
+    /// Access granted by an approved request ran out
+    AccessGrantExpired {
+        request_id: Uuid,
+        document_id: Uuid,
+        user_id: Uuid,
+    },
 }
 
 /// Audit log context containing metadata about the request
@@ -443,6 +542,216 @@ pub fn audit_log(event: &AuditEvent) {
                 "Account unlocked"
             );
         }
+        AuditEvent::UserRoleChanged {
+            user_id,
+            email,
+            old_role,
+            new_role,
+            changed_by,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                user_id = %user_id,
+                email = %email,
+                old_role = %old_role,
+                new_role = %new_role,
+                changed_by = %changed_by,
+                ip_address = ?ip_address,
+                "User role changed"
+            );
+        }
+        AuditEvent::UserDepartmentChanged {
+            user_id,
+            email,
+            old_department,
+            new_department,
+            changed_by,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                user_id = %user_id,
+                email = %email,
+                old_department = ?old_department,
+                new_department = ?new_department,
+                changed_by = %changed_by,
+                ip_address = ?ip_address,
+                "User department changed"
+            );
+        }
+        AuditEvent::AccountStatusChanged {
+            user_id,
+            email,
+            is_active,
+            changed_by,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                user_id = %user_id,
+                email = %email,
+                is_active = %is_active,
+                changed_by = %changed_by,
+                ip_address = ?ip_address,
+                "{}",
+                if *is_active { "Account reactivated" } else { "Account deactivated" }
+            );
+        }
+        AuditEvent::PasswordResetIssued {
+            user_id,
+            email,
+            requested_by,
+            expires_at,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                user_id = %user_id,
+                email = %email,
+                requested_by = %requested_by,
+                expires_at = %expires_at,
+                ip_address = ?ip_address,
+                "Password reset issued"
+            );
+        }
+        AuditEvent::SessionRevoked {
+            user_id,
+            session_id,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                user_id = %user_id,
+                session_id = %session_id,
+                ip_address = ?ip_address,
+                "Session revoked"
+            );
+        }
+        AuditEvent::ForcedLogout {
+            user_id,
+            email,
+            revoked_sessions,
+            forced_by,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                user_id = %user_id,
+                email = %email,
+                revoked_sessions = %revoked_sessions,
+                forced_by = %forced_by,
+                ip_address = ?ip_address,
+                "Forced logout"
+            );
+        }
+        AuditEvent::DocumentShared {
+            document_id,
+            scope,
+            expires_at,
+            shared_by,
+            recipient,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                document_id = %document_id,
+                scope = %scope,
+                expires_at = %expires_at,
+                shared_by = %shared_by,
+                recipient = ?recipient,
+                ip_address = ?ip_address,
+                "Document shared"
+            );
+        }
+        AuditEvent::SharedDocumentAccessed {
+            document_id,
+            scope,
+            shared_by,
+            ip_address,
+            user_agent,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                document_id = %document_id,
+                scope = %scope,
+                shared_by = %shared_by,
+                ip_address = ?ip_address,
+                user_agent = ?user_agent,
+                "Shared document accessed"
+            );
+        }
+        AuditEvent::AccessRequested {
+            request_id,
+            document_id,
+            user_id,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                request_id = %request_id,
+                document_id = %document_id,
+                user_id = %user_id,
+                ip_address = ?ip_address,
+                "Document access requested"
+            );
+        }
+        AuditEvent::AccessRequestDecided {
+            request_id,
+            document_id,
+            user_id,
+            approved,
+            access_expires_at,
+            decided_by,
+            ip_address,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                request_id = %request_id,
+                document_id = %document_id,
+                user_id = %user_id,
+                approved = %approved,
+                access_expires_at = ?access_expires_at,
+                decided_by = %decided_by,
+                ip_address = ?ip_address,
+                "Document access request decided"
+            );
+        }
+        AuditEvent::AccessGrantExpired {
+            request_id,
+            document_id,
+            user_id,
+        } => {
+            info!(
+                target: "audit",
+                timestamp = %timestamp,
+                event = %event_json,
+                request_id = %request_id,
+                document_id = %document_id,
+                user_id = %user_id,
+                "Document access grant expired"
+            );
+        }
     }
 }
 
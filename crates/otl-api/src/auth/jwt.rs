@@ -53,6 +53,9 @@ pub enum JwtError {
     SystemTimeError(#[from] std::time::SystemTimeError),
 }
 
+/// Signing key used when `JWT_SECRET` is not set (refused in production)
+pub const DEV_SECRET: &str = "development-secret-key-change-in-production";
+
 /// JWT Configuration
 ///
 /// Contains settings for token generation and validation
@@ -69,7 +72,7 @@ pub struct JwtConfig {
 impl Default for JwtConfig {
     fn default() -> Self {
         Self {
-            secret: "development-secret-key-change-in-production".to_string(),
+            secret: DEV_SECRET.to_string(),
             access_expiration_secs: 3600, // 1 hour
             issuer: "otl-api".to_string(),
         }
@@ -80,8 +83,7 @@ impl JwtConfig {
     /// Create a new JWT configuration from environment variables
     pub fn from_env() -> Self {
         Self {
-            secret: std::env::var("JWT_SECRET")
-                .unwrap_or_else(|_| "development-secret-key-change-in-production".to_string()),
+            secret: std::env::var("JWT_SECRET").unwrap_or_else(|_| DEV_SECRET.to_string()),
             access_expiration_secs: std::env::var("JWT_ACCESS_EXPIRATION_SECS")
                 .ok()
                 .and_then(|v| v.parse().ok())
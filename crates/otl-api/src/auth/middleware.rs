@@ -4,15 +4,16 @@
 /// On success, adds authenticated user information to request extensions.
 use super::jwt::{validate_access_token, Claims, JwtConfig, JwtError};
 use crate::audit::{audit_log, extract_ip_address, extract_user_agent, AuditEvent};
+use crate::error::{ApiError, ErrorCode};
 use axum::{
     body::Body,
     extract::Request,
-    http::{header, StatusCode},
+    http::header,
     middleware::Next,
     response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use thiserror::Error;
 use uuid::Uuid;
@@ -27,6 +28,15 @@ use uuid::Uuid;
 /// Uses Mutex to ensure thread-safe access across async tasks.
 static TOKEN_BLACKLIST: Mutex<Option<HashSet<String>>> = Mutex::new(None);
 
+/// Per-user cutoffs for forced logout: access tokens of a user issued at or
+/// before the cutoff (Unix seconds) are rejected
+///
+/// A cache of `users.tokens_revoked_at`: [`crate::sessions`] stores every
+/// cutoff there and reloads them periodically, so a forced logout reaches
+/// all instances and survives restarts. Cutoffs older than the access
+/// token lifetime are dropped, since every token they reject has expired.
+static USER_TOKEN_CUTOFFS: Mutex<Option<HashMap<Uuid, u64>>> = Mutex::new(None);
+
 /// Authenticated user information extracted from JWT
 ///
 /// This is added to request extensions by the auth middleware
@@ -58,10 +68,26 @@ impl AuthenticatedUser {
         matches!(self.role.as_str(), "admin" | "editor")
     }
 
+    /// Check if user may request retrieval traces (`?debug=true` on queries)
+    pub fn can_debug_queries(&self) -> bool {
+        matches!(self.role.as_str(), "admin" | "developer")
+    }
+
     /// Check if user can access a specific department
     pub fn can_access_department(&self, dept: &str) -> bool {
         self.is_admin() || self.department.as_deref() == Some(dept)
     }
+
+    /// Convert to the core ACL user used by document and RAG filtering
+    pub fn to_acl_user(&self) -> otl_core::User {
+        otl_core::User {
+            user_id: self.user_id.to_string(),
+            email: Some(self.email.clone()),
+            roles: vec![self.role.to_uppercase()],
+            departments: self.department.iter().cloned().collect(),
+            is_internal: true,
+        }
+    }
 }
 
 impl From<Claims> for AuthenticatedUser {
@@ -101,28 +127,23 @@ pub enum AuthError {
 
 impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
+        let (code, message) = match self {
             AuthError::MissingAuthHeader => {
-                (StatusCode::UNAUTHORIZED, "Missing Authorization header")
+                (ErrorCode::Unauthorized, "Missing Authorization header")
             }
             AuthError::InvalidAuthHeader => (
-                StatusCode::UNAUTHORIZED,
+                ErrorCode::Unauthorized,
                 "Invalid Authorization header format",
             ),
-            AuthError::InvalidToken(_) => (StatusCode::UNAUTHORIZED, "Invalid or expired token"),
-            AuthError::TokenRevoked => (StatusCode::UNAUTHORIZED, "Token has been revoked"),
+            AuthError::InvalidToken(_) => (ErrorCode::InvalidToken, "Invalid or expired token"),
+            AuthError::TokenRevoked => (ErrorCode::InvalidToken, "Token has been revoked"),
             AuthError::InsufficientPermissions => {
-                (StatusCode::FORBIDDEN, "Insufficient permissions")
+                (ErrorCode::Forbidden, "Insufficient permissions")
             }
-            AuthError::AccessDenied(_) => (StatusCode::FORBIDDEN, "Access denied"),
+            AuthError::AccessDenied(_) => (ErrorCode::AclDenied, "Access denied"),
         };
 
-        let body = serde_json::json!({
-            "error": message,
-            "status": status.as_u16(),
-        });
-
-        (status, axum::Json(body)).into_response()
+        ApiError::new(code, message).into_response()
     }
 }
 
@@ -195,11 +216,13 @@ pub async fn auth_middleware(
         }
     };
 
+    let issued_at = claims.iat;
+
     // Convert claims to AuthenticatedUser
     let user = AuthenticatedUser::from(claims);
 
-    // Check token blacklist (for logout support)
-    if is_token_revoked(&user.jti) {
+    // Check token blacklist (for logout support) and forced logouts
+    if is_token_revoked(&user.jti) || is_user_token_revoked(user.user_id, issued_at) {
         // Log revoked token attempt
         audit_log(&AuditEvent::InvalidToken {
             ip_address,
@@ -209,7 +232,8 @@ pub async fn auth_middleware(
         return Err(AuthError::TokenRevoked);
     }
 
-    // Add user to request extensions
+    // Add user to request extensions and the request's log span
+    tracing::Span::current().record("user_id", tracing::field::display(user.user_id));
     request.extensions_mut().insert(user);
 
     // Continue to the next middleware/handler
@@ -231,6 +255,8 @@ pub async fn optional_auth_middleware(mut request: Request<Body>, next: Next) ->
                 let config = JwtConfig::from_env();
                 if let Ok(claims) = validate_access_token(&config, token) {
                     let user = AuthenticatedUser::from(claims);
+                    tracing::Span::current()
+                        .record("user_id", tracing::field::display(user.user_id));
                     request.extensions_mut().insert(user);
                 }
             }
@@ -423,6 +449,46 @@ pub fn clear_blacklist() {
     }
 }
 
+/// Revoke every access token issued to a user so far
+///
+/// Used for forced logout, together with revoking the user's refresh
+/// tokens. Tokens issued afterwards are accepted. Only this instance learns
+/// of it; `sessions::revoke_access_tokens` also stores the cutoff for the
+/// others.
+pub fn revoke_user_tokens(user_id: Uuid) {
+    merge_user_token_cutoffs([(user_id, unix_now())]);
+}
+
+/// Add cutoffs loaded from the database, keeping the later one per user,
+/// and drop those older than the access token lifetime
+pub fn merge_user_token_cutoffs(cutoffs: impl IntoIterator<Item = (Uuid, u64)>) {
+    let oldest = unix_now().saturating_sub(JwtConfig::from_env().access_expiration_secs);
+    let mut guard = USER_TOKEN_CUTOFFS.lock().unwrap();
+    let map = guard.get_or_insert_with(HashMap::new);
+    for (user_id, cutoff) in cutoffs {
+        let entry = map.entry(user_id).or_insert(cutoff);
+        *entry = (*entry).max(cutoff);
+    }
+    map.retain(|_, cutoff| *cutoff >= oldest);
+}
+
+/// Check if an access token of `user_id` issued at `issued_at` was revoked
+/// by [`revoke_user_tokens`]
+pub fn is_user_token_revoked(user_id: Uuid, issued_at: u64) -> bool {
+    let cutoffs = USER_TOKEN_CUTOFFS.lock().unwrap();
+    cutoffs
+        .as_ref()
+        .and_then(|map| map.get(&user_id))
+        .is_some_and(|cutoff| issued_at <= *cutoff)
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -491,6 +557,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_can_debug_queries() {
+        let roles = vec![
+            ("admin", true),
+            ("developer", true),
+            ("editor", false),
+            ("viewer", false),
+        ];
+
+        for (role, expected) in roles {
+            let user = AuthenticatedUser {
+                user_id: Uuid::new_v4(),
+                email: "test@example.com".to_string(),
+                name: "Test".to_string(),
+                role: role.to_string(),
+                department: None,
+                jti: Uuid::new_v4().to_string(),
+            };
+
+            assert_eq!(user.can_debug_queries(), expected, "Role: {}", role);
+        }
+    }
+
     #[test]
     fn test_can_access_department() {
         let admin = AuthenticatedUser {
@@ -561,4 +650,33 @@ mod tests {
         // Should still be revoked
         assert!(is_token_revoked(&jti));
     }
+
+    #[test]
+    fn test_revoke_user_tokens() {
+        let user_id = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        assert!(!is_user_token_revoked(user_id, 1000));
+
+        revoke_user_tokens(user_id);
+        assert!(is_user_token_revoked(user_id, 1000));
+        assert!(!is_user_token_revoked(other, 1000));
+        // Tokens issued after the forced logout are accepted
+        assert!(!is_user_token_revoked(user_id, u64::MAX));
+    }
+
+    #[test]
+    fn test_merge_user_token_cutoffs() {
+        let (recent, expired) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = unix_now();
+        merge_user_token_cutoffs([(recent, now - 60), (expired, now - 30 * 24 * 3600)]);
+        assert!(is_user_token_revoked(recent, now - 120));
+        assert!(!is_user_token_revoked(recent, now));
+        // Every token the old cutoff could reject has expired
+        assert!(!is_user_token_revoked(expired, now - 31 * 24 * 3600));
+
+        // A stale reload does not undo a later local revocation
+        revoke_user_tokens(recent);
+        merge_user_token_cutoffs([(recent, now - 60)]);
+        assert!(is_user_token_revoked(recent, now - 30));
+    }
 }
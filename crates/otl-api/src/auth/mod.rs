@@ -17,17 +17,17 @@ pub mod service;
 
 pub use jwt::{generate_access_token, validate_access_token, Claims, JwtConfig};
 pub use middleware::{
-    auth_middleware, clear_blacklist, is_token_revoked, optional_auth_middleware, revoke_token,
-    AuthError, AuthenticatedUser,
+    auth_middleware, clear_blacklist, is_token_revoked, is_user_token_revoked,
+    optional_auth_middleware, revoke_token, revoke_user_tokens, AuthError, AuthenticatedUser,
 };
 pub use models::{
     CreateUserRequest, RefreshToken, TokenBlacklist, UpdateUserRequest, User, UserPublic, UserRole,
 };
-pub use password::{hash_password, validate_password_strength, verify_password};
+pub use password::{hash_password, validate_password_strength, verify_password, PasswordPolicy};
 pub use repository::{
     RefreshTokenRepository, RepositoryError, TokenBlacklistRepository, UserRepository,
 };
 pub use service::{
-    AuthResponse, AuthService, LoginRequest, LogoutRequest, RefreshRequest, RegisterRequest,
-    UserInfo,
+    AuthResponse, AuthService, LockoutPolicy, LoginRequest, LogoutRequest, RefreshRequest,
+    RegisterRequest, SessionClient, UserInfo,
 };
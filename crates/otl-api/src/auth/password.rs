@@ -173,7 +173,132 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, PasswordError
     }
 }
 
-/// Validate password strength
+/// Password strength rules
+///
+/// Read from the environment by [`PasswordPolicy::from_env`]:
+///
+/// - `AUTH_PASSWORD_MIN_LENGTH` - minimum length in characters (default: 8)
+/// - `AUTH_PASSWORD_REQUIRE_UPPERCASE`, `AUTH_PASSWORD_REQUIRE_LOWERCASE`,
+///   `AUTH_PASSWORD_REQUIRE_DIGIT`, `AUTH_PASSWORD_REQUIRE_SPECIAL` -
+///   required character classes (default: all `true`)
+/// - `AUTH_BREACHED_PASSWORD_URL` - range API of breached password hashes
+///   in the Have I Been Pwned format, e.g.
+///   `https://api.pwnedpasswords.com/range` (default: no check)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordPolicy {
+    /// Minimum length in characters
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_special: bool,
+    /// Range API of breached password hashes; `None` skips the check
+    pub breached_password_url: Option<String>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_special: true,
+            breached_password_url: None,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Create a policy from environment variables
+    pub fn from_env() -> Self {
+        let flag = |name: &str, default: bool| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            min_length: std::env::var("AUTH_PASSWORD_MIN_LENGTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.min_length),
+            require_uppercase: flag(
+                "AUTH_PASSWORD_REQUIRE_UPPERCASE",
+                defaults.require_uppercase,
+            ),
+            require_lowercase: flag(
+                "AUTH_PASSWORD_REQUIRE_LOWERCASE",
+                defaults.require_lowercase,
+            ),
+            require_digit: flag("AUTH_PASSWORD_REQUIRE_DIGIT", defaults.require_digit),
+            require_special: flag("AUTH_PASSWORD_REQUIRE_SPECIAL", defaults.require_special),
+            breached_password_url: std::env::var("AUTH_BREACHED_PASSWORD_URL")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
+        }
+    }
+
+    /// Check the length and character class rules
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Password meets the rules
+    /// * `Err(String)` - Description of the first rule it breaks
+    pub fn validate(&self, password: &str) -> Result<(), String> {
+        if password.chars().count() < self.min_length {
+            return Err(format!(
+                "Password must be at least {} characters long",
+                self.min_length
+            ));
+        }
+
+        if self.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            return Err("Password must contain at least one uppercase letter".to_string());
+        }
+
+        if self.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            return Err("Password must contain at least one lowercase letter".to_string());
+        }
+
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            return Err("Password must contain at least one digit".to_string());
+        }
+
+        if self.require_special && !password.chars().any(|c| !c.is_alphanumeric()) {
+            return Err("Password must contain at least one special character".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Check the rules, then whether the password appears in a breach
+    ///
+    /// Only the first five hex digits of the password's SHA-1 hash are sent
+    /// to the breached password service (k-anonymity). If the service
+    /// cannot be reached the password is accepted, so an outage does not
+    /// block registrations.
+    pub async fn check(&self, password: &str) -> Result<(), String> {
+        self.validate(password)?;
+
+        let Some(url) = &self.breached_password_url else {
+            return Ok(());
+        };
+        match is_breached(url, password).await {
+            Ok(true) => {
+                Err("Password appears in a known data breach; choose a different one".to_string())
+            }
+            Ok(false) => Ok(()),
+            Err(e) => {
+                tracing::warn!("Breached password check failed, skipping it: {e}");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Validate password strength against the default [`PasswordPolicy`]
 ///
 /// Checks if a password meets minimum security requirements:
 /// - At least 8 characters
@@ -200,27 +325,36 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, PasswordError
 /// assert!(validate_password_strength("weak").is_err());
 /// ```
 pub fn validate_password_strength(password: &str) -> Result<(), String> {
-    if password.len() < 8 {
-        return Err("Password must be at least 8 characters long".to_string());
-    }
-
-    if !password.chars().any(|c| c.is_uppercase()) {
-        return Err("Password must contain at least one uppercase letter".to_string());
-    }
-
-    if !password.chars().any(|c| c.is_lowercase()) {
-        return Err("Password must contain at least one lowercase letter".to_string());
-    }
-
-    if !password.chars().any(|c| c.is_ascii_digit()) {
-        return Err("Password must contain at least one digit".to_string());
-    }
+    PasswordPolicy::default().validate(password)
+}
 
-    if !password.chars().any(|c| !c.is_alphanumeric()) {
-        return Err("Password must contain at least one special character".to_string());
-    }
+/// Look a password up in a breached password range API
+async fn is_breached(url: &str, password: &str) -> Result<bool, String> {
+    use sha1::{Digest, Sha1};
+    let hash = format!("{:X}", Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = hash.split_at(5);
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/{prefix}", url.trim_end_matches('/')))
+        .header("Add-Padding", "true")
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    Ok(range_contains(&body, suffix))
+}
 
-    Ok(())
+/// Whether a range API response lists `suffix` with a non-zero count
+///
+/// Each line is `SUFFIX:COUNT`; padding entries have a count of 0.
+fn range_contains(body: &str, suffix: &str) -> bool {
+    body.lines().any(|line| {
+        line.trim().split_once(':').is_some_and(|(s, count)| {
+            s.eq_ignore_ascii_case(suffix) && count.trim().parse::<u64>().unwrap_or(0) > 0
+        })
+    })
 }
 
 #[cfg(test)]
@@ -313,4 +447,53 @@ mod tests {
         assert!(hash.contains("t=2"));
         assert!(hash.contains("p=2"));
     }
+
+    #[test]
+    fn test_password_policy_configurable() {
+        let policy = PasswordPolicy {
+            min_length: 12,
+            require_uppercase: false,
+            require_special: false,
+            ..Default::default()
+        };
+        assert!(policy.validate("longpassword1").is_ok());
+        assert!(policy.validate("short1").is_err());
+        assert!(policy.validate("longpassword").is_err()); // no digit
+
+        // Length counts characters, not bytes
+        let korean = PasswordPolicy {
+            min_length: 8,
+            require_uppercase: false,
+            require_lowercase: false,
+            ..Default::default()
+        };
+        assert!(korean.validate("비밀번호1!").is_err());
+        assert!(korean.validate("비밀번호입니다1!").is_ok());
+    }
+
+    #[test]
+    fn test_range_contains() {
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n\
+                    00D4F6E8FA6EECAD2A3AA415EEC418D38EC:0\r\n\
+                    011053FD0102E94D6AE2F8B83D76FAF94F6:3";
+        assert!(range_contains(body, "0018a45c4d1def81644b54ab7f969b88d65"));
+        assert!(range_contains(body, "011053FD0102E94D6AE2F8B83D76FAF94F6"));
+        // Padding entries have a count of 0
+        assert!(!range_contains(body, "00D4F6E8FA6EECAD2A3AA415EEC418D38EC"));
+        assert!(!range_contains(body, "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"));
+    }
+
+    #[tokio::test]
+    async fn test_check_without_breach_service() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.check("SecureP@ssw0rd!").await.is_ok());
+        assert!(policy.check("weak").await.is_err());
+
+        // An unreachable service does not block the password
+        let unreachable = PasswordPolicy {
+            breached_password_url: Some("http://127.0.0.1:1/range".to_string()),
+            ..Default::default()
+        };
+        assert!(unreachable.check("SecureP@ssw0rd!").await.is_ok());
+    }
 }
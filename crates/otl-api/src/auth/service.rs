@@ -4,7 +4,7 @@
 //! Integrates with database for user storage and session management.
 
 use super::jwt::{generate_access_token, JwtConfig};
-use super::password::{hash_password, validate_password_strength, verify_password};
+use super::password::{hash_password, verify_password, PasswordPolicy};
 use crate::audit::{audit_log, AuditEvent};
 use crate::error::AppError;
 use base64::Engine;
@@ -44,6 +44,25 @@ pub struct LogoutRequest {
     pub logout_all_devices: Option<bool>,
 }
 
+/// Client a session is started or refreshed from
+///
+/// Recorded on the session so users can tell their devices apart.
+#[derive(Debug, Clone, Default)]
+pub struct SessionClient {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl SessionClient {
+    /// IP address if it parses, so it can be stored as `INET`
+    fn ip(&self) -> Option<String> {
+        self.ip_address
+            .as_deref()
+            .and_then(|ip| ip.trim().parse::<std::net::IpAddr>().ok())
+            .map(|ip| ip.to_string())
+    }
+}
+
 /// Authentication response with tokens
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AuthResponse {
@@ -88,18 +107,74 @@ struct UserRecord {
 struct RefreshTokenRecord {
     id: Uuid,
     user_id: Uuid,
-    _token_hash: String,
     expires_at: DateTime<Utc>,
     revoked_at: Option<DateTime<Utc>>,
 }
 
+/// Account lockout after repeated failed logins
+///
+/// The account is locked for `base_duration_mins` once `max_attempts`
+/// logins in a row have failed. Every further failure after the lock
+/// expires doubles the lock, up to `max_duration_mins`; a successful login
+/// starts over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockoutPolicy {
+    pub max_attempts: i32,
+    pub base_duration_mins: i64,
+    pub max_duration_mins: i64,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_duration_mins: 15,
+            max_duration_mins: 24 * 60,
+        }
+    }
+}
+
+impl LockoutPolicy {
+    /// Create a policy from `AUTH_MAX_LOGIN_ATTEMPTS`,
+    /// `AUTH_LOCKOUT_DURATION_MINS` and `AUTH_LOCKOUT_MAX_DURATION_MINS`
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+        let defaults = Self::default();
+        Self {
+            max_attempts: var("AUTH_MAX_LOGIN_ATTEMPTS", defaults.max_attempts).max(1),
+            base_duration_mins: var("AUTH_LOCKOUT_DURATION_MINS", defaults.base_duration_mins),
+            max_duration_mins: var("AUTH_LOCKOUT_MAX_DURATION_MINS", defaults.max_duration_mins),
+        }
+    }
+
+    /// How long to lock an account after `failed_attempts` failed logins in
+    /// a row; `None` below the threshold
+    pub fn lockout_for(&self, failed_attempts: i32) -> Option<Duration> {
+        let excess = failed_attempts.saturating_sub(self.max_attempts);
+        if excess < 0 {
+            return None;
+        }
+        let factor = 1i64 << excess.min(32);
+        let minutes = self
+            .base_duration_mins
+            .saturating_mul(factor)
+            .min(self.max_duration_mins.max(self.base_duration_mins));
+        Some(Duration::minutes(minutes))
+    }
+}
+
 /// Authentication service
 pub struct AuthService {
     db_pool: PgPool,
     jwt_config: JwtConfig,
     refresh_token_expiry_days: i64,
-    max_failed_attempts: i32,
-    lockout_duration_mins: i64,
+    lockout: LockoutPolicy,
+    password_policy: PasswordPolicy,
 }
 
 impl AuthService {
@@ -110,21 +185,13 @@ impl AuthService {
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(7);
-        let max_failed_attempts = std::env::var("AUTH_MAX_LOGIN_ATTEMPTS")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(5);
-        let lockout_duration_mins = std::env::var("AUTH_LOCKOUT_DURATION_MINS")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(15);
 
         Self {
             db_pool,
             jwt_config,
             refresh_token_expiry_days,
-            max_failed_attempts,
-            lockout_duration_mins,
+            lockout: LockoutPolicy::from_env(),
+            password_policy: PasswordPolicy::from_env(),
         }
     }
 
@@ -144,8 +211,10 @@ impl AuthService {
             return Err(AppError::BadRequest("Invalid email format".to_string()));
         }
 
-        // Validate password strength
-        validate_password_strength(&request.password)
+        // Validate password against the policy
+        self.password_policy
+            .check(&request.password)
+            .await
             .map_err(|e| AppError::BadRequest(format!("Password validation failed: {e}")))?;
 
         // Check if email already exists
@@ -197,12 +266,17 @@ impl AuthService {
     /// # Arguments
     ///
     /// * `request` - Login credentials
+    /// * `client` - Device the new session is started from
     ///
     /// # Returns
     ///
     /// * `Ok(AuthResponse)` - Access token, refresh token, and user info
     /// * `Err(AppError)` - If login fails
-    pub async fn login(&self, request: LoginRequest) -> Result<AuthResponse, AppError> {
+    pub async fn login(
+        &self,
+        request: LoginRequest,
+        client: &SessionClient,
+    ) -> Result<AuthResponse, AppError> {
         // Fetch user by email
         let user = sqlx::query_as::<_, UserRecord>(
             "SELECT id, email, password_hash, name, role, department, is_active, email_verified, failed_login_attempts, locked_until, created_at FROM users WHERE email = $1",
@@ -233,12 +307,12 @@ impl AuthService {
 
         if !password_valid {
             // Increment failed login attempts
+            // Lock progressively longer the more often it keeps failing
             let failed_attempts = user.failed_login_attempts + 1;
-            let locked_until = if failed_attempts >= self.max_failed_attempts {
-                Some(Utc::now() + Duration::minutes(self.lockout_duration_mins))
-            } else {
-                None
-            };
+            let locked_until = self
+                .lockout
+                .lockout_for(failed_attempts)
+                .map(|duration| Utc::now() + duration);
 
             sqlx::query(
                 "UPDATE users SET failed_login_attempts = $1, locked_until = $2 WHERE id = $3",
@@ -257,7 +331,7 @@ impl AuthService {
                     email: user.email.clone(),
                     failed_attempts,
                     locked_until: locked_time,
-                    ip_address: None, // IP address logged at handler level
+                    ip_address: client.ip_address.clone(),
                 });
             }
 
@@ -283,17 +357,19 @@ impl AuthService {
         .map_err(|e| AppError::Internal(format!("Failed to generate access token: {e}")))?;
 
         // Generate refresh token
-        let refresh_token = self.generate_refresh_token();
-        let refresh_token_hash = self.hash_token(&refresh_token);
+        let refresh_token = random_token();
+        let refresh_token_hash = hash_token(&refresh_token);
         let expires_at = Utc::now() + Duration::days(self.refresh_token_expiry_days);
 
-        // Store refresh token
+        // Store refresh token; its row is the session
         sqlx::query(
-            "INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, created_at) VALUES ($1, $2, $3, $4, NOW())",
+            "INSERT INTO refresh_tokens (id, user_id, token_hash, device_info, ip_address, expires_at, created_at, last_used_at) VALUES ($1, $2, $3, $4, $5::inet, $6, NOW(), NOW())",
         )
         .bind(Uuid::new_v4())
         .bind(user.id)
         .bind(&refresh_token_hash)
+        .bind(&client.user_agent)
+        .bind(client.ip())
         .bind(expires_at)
         .execute(&self.db_pool)
         .await
@@ -322,17 +398,22 @@ impl AuthService {
     /// # Arguments
     ///
     /// * `request` - Refresh token
+    /// * `client` - Device the session is refreshed from
     ///
     /// # Returns
     ///
     /// * `Ok(AuthResponse)` - New access token and rotated refresh token
     /// * `Err(AppError)` - If refresh fails
-    pub async fn refresh(&self, request: RefreshRequest) -> Result<AuthResponse, AppError> {
-        let token_hash = self.hash_token(&request.refresh_token);
+    pub async fn refresh(
+        &self,
+        request: RefreshRequest,
+        client: &SessionClient,
+    ) -> Result<AuthResponse, AppError> {
+        let token_hash = hash_token(&request.refresh_token);
 
         // Fetch refresh token record
         let token_record = sqlx::query_as::<_, RefreshTokenRecord>(
-            "SELECT id, user_id, token_hash, expires_at, revoked_at FROM refresh_tokens WHERE token_hash = $1",
+            "SELECT id, user_id, expires_at, revoked_at FROM refresh_tokens WHERE token_hash = $1",
         )
         .bind(&token_hash)
         .fetch_optional(&self.db_pool)
@@ -365,13 +446,6 @@ impl AuthService {
             return Err(AppError::Forbidden("Account is deactivated".to_string()));
         }
 
-        // Revoke old refresh token (token rotation)
-        sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE id = $1")
-            .bind(token_record.id)
-            .execute(&self.db_pool)
-            .await
-            .ok(); // Ignore errors
-
         // Generate new access token
         let access_token = generate_access_token(
             &self.jwt_config,
@@ -383,22 +457,35 @@ impl AuthService {
         )
         .map_err(|e| AppError::Internal(format!("Failed to generate access token: {e}")))?;
 
-        // Generate new refresh token
-        let new_refresh_token = self.generate_refresh_token();
-        let new_token_hash = self.hash_token(&new_refresh_token);
+        // Rotate the refresh token in place so the session keeps its id;
+        // a concurrent refresh with the same token loses the race
+        let new_refresh_token = random_token();
+        let new_token_hash = hash_token(&new_refresh_token);
         let expires_at = Utc::now() + Duration::days(self.refresh_token_expiry_days);
 
-        // Store new refresh token
-        sqlx::query(
-            "INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, created_at) VALUES ($1, $2, $3, $4, NOW())",
+        let rotated = sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET
+                token_hash = $3,
+                expires_at = $4,
+                last_used_at = NOW(),
+                device_info = COALESCE($5, device_info),
+                ip_address = COALESCE($6::inet, ip_address)
+            WHERE id = $1 AND token_hash = $2 AND revoked_at IS NULL
+            "#,
         )
-        .bind(Uuid::new_v4())
-        .bind(user.id)
+        .bind(token_record.id)
+        .bind(&token_hash)
         .bind(&new_token_hash)
         .bind(expires_at)
+        .bind(&client.user_agent)
+        .bind(client.ip())
         .execute(&self.db_pool)
         .await
-        .map_err(|e| AppError::Database(format!("Failed to store refresh token: {e}")))?;
+        .map_err(|e| AppError::Database(format!("Failed to rotate refresh token: {e}")))?;
+        if rotated.rows_affected() == 0 {
+            return Err(AppError::Unauthorized);
+        }
 
         Ok(AuthResponse {
             access_token,
@@ -438,7 +525,7 @@ impl AuthService {
     ) -> Result<(), AppError> {
         // If refresh token is provided, revoke it
         if let Some(refresh_token) = request.refresh_token {
-            let token_hash = self.hash_token(&refresh_token);
+            let token_hash = hash_token(&refresh_token);
             sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE token_hash = $1 AND user_id = $2")
                 .bind(&token_hash)
                 .bind(user_id)
@@ -524,21 +611,21 @@ impl AuthService {
 
         Ok(count > 0)
     }
+}
 
-    /// Generate a cryptographically secure refresh token
-    fn generate_refresh_token(&self) -> String {
-        let mut rng = rand::thread_rng();
-        let token_bytes: [u8; 32] = rng.gen();
-        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(token_bytes)
-    }
+/// Generate a cryptographically secure opaque token (refresh or password reset)
+pub(crate) fn random_token() -> String {
+    let mut rng = rand::thread_rng();
+    let token_bytes: [u8; 32] = rng.gen();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(token_bytes)
+}
 
-    /// Hash a token for storage (simple SHA-256)
-    fn hash_token(&self, token: &str) -> String {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(token.as_bytes());
-        format!("{:x}", hasher.finalize())
-    }
+/// Hash a token for storage (simple SHA-256)
+pub(crate) fn hash_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    format!("{:x}", hasher.finalize())
 }
 
 impl Clone for AuthService {
@@ -547,8 +634,30 @@ impl Clone for AuthService {
             db_pool: self.db_pool.clone(),
             jwt_config: self.jwt_config.clone(),
             refresh_token_expiry_days: self.refresh_token_expiry_days,
-            max_failed_attempts: self.max_failed_attempts,
-            lockout_duration_mins: self.lockout_duration_mins,
+            lockout: self.lockout.clone(),
+            password_policy: self.password_policy.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_is_progressive() {
+        let policy = LockoutPolicy {
+            max_attempts: 5,
+            base_duration_mins: 15,
+            max_duration_mins: 120,
+        };
+        assert_eq!(policy.lockout_for(1), None);
+        assert_eq!(policy.lockout_for(4), None);
+        assert_eq!(policy.lockout_for(5), Some(Duration::minutes(15)));
+        assert_eq!(policy.lockout_for(6), Some(Duration::minutes(30)));
+        assert_eq!(policy.lockout_for(7), Some(Duration::minutes(60)));
+        // Capped
+        assert_eq!(policy.lockout_for(8), Some(Duration::minutes(120)));
+        assert_eq!(policy.lockout_for(500), Some(Duration::minutes(120)));
+    }
+}
@@ -0,0 +1,588 @@
+//! Auto-approval policy engine
+//!
+//! Pending extractions are auto-approved by rules matched on the entity
+//! type or relation predicate of each extracted item, the access level of
+//! the document and the extractor that produced the extraction (`rule`,
+//! `llm`, `table` for mapped spreadsheet rows or `form` for recognized
+//! forms). The first matching rule gives the confidence an item needs, or
+//! excludes it from auto-approval; items no rule matches need the global
+//! threshold kept by the quality audit. An extraction is auto-approved when its confidence meets what
+//! every one of its items needs.
+//!
+//! Policies are versioned in `auto_approve_policies`; the newest one is in
+//! effect. A proposed policy can be simulated against recent extractions
+//! to see which ones it would have classified differently.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Known extractor sources
+const EXTRACTORS: [&str; 4] = [
+    "rule",
+    "llm",
+    otl_extractor::tabular::TABLE_EXTRACTOR,
+    otl_extractor::forms::FORM_EXTRACTOR,
+];
+
+/// Known document access levels
+const ACCESS_LEVELS: [&str; 4] = ["public", "internal", "confidential", "restricted"];
+
+/// Most pending extractions evaluated per run
+const MAX_CANDIDATES: i64 = 5_000;
+
+/// Most recent extractions evaluated by a simulation
+const MAX_SIMULATED: i64 = 20_000;
+
+/// Reclassified extractions listed by a simulation
+const MAX_EXAMPLES: usize = 20;
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+/// Auto-approval rule
+///
+/// Unset conditions match anything. A rule with `entity_type` only matches
+/// entities and one with `predicate` only matches relations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ApprovalRule {
+    /// Entity type the rule applies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "LeaveType")]
+    pub entity_type: Option<String>,
+    /// Relation predicate the rule applies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub predicate: Option<String>,
+    /// Document access level the rule applies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "internal")]
+    pub access_level: Option<String>,
+    /// Extractor the rule applies to (`rule`, `llm`, `table` or `form`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "rule")]
+    pub extractor: Option<String>,
+    /// Confidence the item needs; unset: never auto-approved
+    #[schema(example = 0.9)]
+    pub min_confidence: Option<f32>,
+}
+
+/// Extracted item a rule is matched against
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Entity(String),
+    Relation(String),
+}
+
+impl ApprovalRule {
+    fn matches(&self, item: Option<&Item>, access_level: &str, extractor: Option<&str>) -> bool {
+        let kind = match (&self.entity_type, &self.predicate, item) {
+            (Some(t), None, Some(Item::Entity(e))) => t.eq_ignore_ascii_case(e),
+            (None, Some(p), Some(Item::Relation(r))) => p.eq_ignore_ascii_case(r),
+            (None, None, _) => true,
+            _ => false,
+        };
+        kind && self
+            .access_level
+            .as_deref()
+            .map_or(true, |level| level == access_level)
+            && self
+                .extractor
+                .as_deref()
+                .map_or(true, |source| Some(source) == extractor)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.entity_type.is_some() && self.predicate.is_some() {
+            return Err("A rule can match an entity type or a predicate, not both".to_string());
+        }
+        if let Some(level) = &self.access_level {
+            if !ACCESS_LEVELS.contains(&level.as_str()) {
+                return Err(format!("Unknown access level: {level}"));
+            }
+        }
+        if let Some(source) = &self.extractor {
+            if !EXTRACTORS.contains(&source.as_str()) {
+                return Err(format!(
+                    "Unknown extractor: {source} (expected rule, llm, table or form)"
+                ));
+            }
+        }
+        if let Some(confidence) = self.min_confidence {
+            if !(0.0..=1.0).contains(&confidence) {
+                return Err(format!(
+                    "min_confidence must be between 0 and 1: {confidence}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ordered auto-approval rules; the first matching rule applies
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ApprovalPolicy {
+    pub rules: Vec<ApprovalRule>,
+}
+
+/// Pending extraction the policy is evaluated for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub confidence: Option<f32>,
+    pub access_level: String,
+    pub extractor: Option<String>,
+    pub items: Vec<Item>,
+}
+
+impl ApprovalPolicy {
+    /// Check every rule, naming the first invalid one
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            rule.validate()
+                .map_err(|e| format!("Rule {}: {e}", i + 1))?;
+        }
+        Ok(())
+    }
+
+    /// Confidence an item needs, or `None` if it is never auto-approved
+    fn required(
+        &self,
+        item: Option<&Item>,
+        access_level: &str,
+        extractor: Option<&str>,
+        default_threshold: f32,
+    ) -> Option<f32> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(item, access_level, extractor))
+            .map_or(Some(default_threshold), |rule| rule.min_confidence)
+    }
+
+    /// Whether the extraction is auto-approved
+    pub fn approves(&self, candidate: &Candidate, default_threshold: f32) -> bool {
+        let Some(confidence) = candidate.confidence else {
+            return false;
+        };
+        let extractor = candidate.extractor.as_deref();
+        let required = if candidate.items.is_empty() {
+            self.required(None, &candidate.access_level, extractor, default_threshold)
+        } else {
+            candidate.items.iter().try_fold(0.0f32, |max, item| {
+                self.required(
+                    Some(item),
+                    &candidate.access_level,
+                    extractor,
+                    default_threshold,
+                )
+                .map(|r| max.max(r))
+            })
+        };
+        required.is_some_and(|required| confidence >= required)
+    }
+}
+
+/// Entity types and relation predicates of extracted content
+pub fn items(entities: &serde_json::Value, relations: &serde_json::Value) -> Vec<Item> {
+    let field = |value: &serde_json::Value, key: &str| -> Vec<String> {
+        value
+            .as_array()
+            .map(|list| {
+                list.iter()
+                    .filter_map(|item| item.get(key)?.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    field(entities, "entity_type")
+        .into_iter()
+        .map(Item::Entity)
+        .chain(
+            field(relations, "predicate")
+                .into_iter()
+                .map(Item::Relation),
+        )
+        .collect()
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+/// Stored auto-approval policy
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PolicyVersion {
+    pub rules: Vec<ApprovalRule>,
+    /// Admin who set it (absent before any policy was set)
+    pub changed_by: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Policy in effect (no rules before any policy was set)
+pub async fn current_policy(state: &AppState) -> Result<PolicyVersion, AppError> {
+    let row: Option<(serde_json::Value, Option<String>, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT rules, changed_by, created_at
+        FROM auto_approve_policies
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch auto-approve policy: {e}")))?;
+
+    Ok(match row {
+        Some((rules, changed_by, created_at)) => PolicyVersion {
+            rules: serde_json::from_value(rules).map_err(|e| {
+                AppError::Internal(format!("Invalid stored auto-approve policy: {e}"))
+            })?,
+            changed_by,
+            created_at: Some(created_at),
+        },
+        None => PolicyVersion {
+            rules: Vec::new(),
+            changed_by: None,
+            created_at: None,
+        },
+    })
+}
+
+/// Store a validated policy as the one in effect
+pub async fn set_policy(
+    state: &AppState,
+    policy: &ApprovalPolicy,
+    changed_by: &str,
+) -> Result<(), AppError> {
+    policy.validate().map_err(AppError::BadRequest)?;
+    let rules = serde_json::to_value(&policy.rules)
+        .map_err(|e| AppError::Internal(format!("Failed to encode auto-approve policy: {e}")))?;
+    sqlx::query("INSERT INTO auto_approve_policies (rules, changed_by) VALUES ($1, $2)")
+        .bind(rules)
+        .bind(changed_by)
+        .execute(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to store auto-approve policy: {e}")))?;
+    Ok(())
+}
+
+#[derive(sqlx::FromRow)]
+struct CandidateRow {
+    id: Uuid,
+    extracted_entities: serde_json::Value,
+    extracted_relations: serde_json::Value,
+    confidence_score: Option<f32>,
+    extractor: Option<String>,
+    access_level: String,
+    status: String,
+}
+
+impl CandidateRow {
+    fn candidate(&self) -> Candidate {
+        Candidate {
+            confidence: self.confidence_score,
+            access_level: self.access_level.clone(),
+            extractor: self.extractor.clone(),
+            items: items(&self.extracted_entities, &self.extracted_relations),
+        }
+    }
+}
+
+/// Access levels whose extractions need several approvals and are never
+/// auto-approved
+fn multi_review_levels(state: &AppState) -> Vec<String> {
+    state
+        .review
+        .required_approvals
+        .iter()
+        .filter(|(_, n)| **n > 1)
+        .map(|(level, _)| level.clone())
+        .collect()
+}
+
+// ============================================================================
+// Auto-approval
+// ============================================================================
+
+/// Approve the pending extractions the policy in effect approves
+///
+/// Extractions already decided by a reviewer and those of documents whose
+/// access level needs several approvals are left for review.
+pub async fn approve_pending(state: &AppState, default_threshold: f32) -> Result<u64, AppError> {
+    let policy = ApprovalPolicy {
+        rules: current_policy(state).await?.rules,
+    };
+
+    let rows: Vec<CandidateRow> = sqlx::query_as(
+        r#"
+        SELECT eq.id, eq.extracted_entities, eq.extracted_relations, eq.confidence_score,
+               eq.extractor, d.access_level::text AS access_level, eq.status::text AS status
+        FROM extraction_queue eq
+        JOIN documents d ON eq.document_id = d.id
+        WHERE eq.status = 'pending'
+          AND eq.confidence_score IS NOT NULL
+          AND NOT (d.access_level::text = ANY($1))
+          AND NOT EXISTS (SELECT 1 FROM extraction_reviews r WHERE r.extraction_id = eq.id)
+        ORDER BY eq.confidence_score DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(multi_review_levels(state))
+    .bind(MAX_CANDIDATES)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch auto-approval candidates: {e}")))?;
+
+    let approved: Vec<Uuid> = rows
+        .iter()
+        .filter(|row| policy.approves(&row.candidate(), default_threshold))
+        .map(|row| row.id)
+        .collect();
+    if approved.is_empty() {
+        return Ok(0);
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE extraction_queue
+        SET status = 'approved', auto_approved = TRUE, reviewed_at = NOW()
+        WHERE id = ANY($1) AND status = 'pending'
+        "#,
+    )
+    .bind(&approved)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to auto-approve extractions: {e}")))?;
+    Ok(result.rows_affected())
+}
+
+// ============================================================================
+// Simulation
+// ============================================================================
+
+/// Extraction a proposed policy classifies differently
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Reclassified {
+    pub extraction_id: Uuid,
+    pub confidence: Option<f32>,
+    /// Current verification status
+    #[schema(example = "rejected")]
+    pub status: String,
+    /// Whether the policy in effect auto-approves it
+    pub current: bool,
+    /// Whether the proposed policy auto-approves it
+    pub proposed: bool,
+}
+
+/// How a proposed policy would have classified recent extractions
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SimulationReport {
+    pub days: u32,
+    /// Global threshold used for items no rule matches
+    pub default_threshold: f32,
+    /// Extractions evaluated (multi-review access levels excluded)
+    pub evaluated: u64,
+    /// Auto-approved by the policy in effect
+    pub current_auto_approved: u64,
+    /// Auto-approved by the proposed policy
+    pub proposed_auto_approved: u64,
+    /// Auto-approved by the proposed policy only
+    pub newly_auto_approved: u64,
+    /// Auto-approved by the policy in effect only
+    pub no_longer_auto_approved: u64,
+    /// Newly auto-approved extractions that reviewers or audits rejected
+    pub newly_auto_approved_rejected: u64,
+    /// Reclassified extractions, rejected ones first
+    pub examples: Vec<Reclassified>,
+}
+
+/// Compare the policy in effect with a proposed one over the extractions of
+/// the last `days` days, whatever their status now
+pub async fn simulate(
+    state: &AppState,
+    proposed: &ApprovalPolicy,
+    default_threshold: f32,
+    days: u32,
+) -> Result<SimulationReport, AppError> {
+    proposed.validate().map_err(AppError::BadRequest)?;
+    let current = ApprovalPolicy {
+        rules: current_policy(state).await?.rules,
+    };
+    let since = Utc::now() - chrono::Duration::days(i64::from(days));
+
+    let rows: Vec<CandidateRow> = sqlx::query_as(
+        r#"
+        SELECT eq.id, eq.extracted_entities, eq.extracted_relations, eq.confidence_score,
+               eq.extractor, d.access_level::text AS access_level, eq.status::text AS status
+        FROM extraction_queue eq
+        JOIN documents d ON eq.document_id = d.id
+        WHERE eq.created_at >= $1
+          AND NOT (d.access_level::text = ANY($2))
+        ORDER BY eq.created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(since)
+    .bind(multi_review_levels(state))
+    .bind(MAX_SIMULATED)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch recent extractions: {e}")))?;
+
+    let mut report = SimulationReport {
+        days,
+        default_threshold,
+        ..Default::default()
+    };
+    for row in &rows {
+        let candidate = row.candidate();
+        let before = current.approves(&candidate, default_threshold);
+        let after = proposed.approves(&candidate, default_threshold);
+        report.evaluated += 1;
+        report.current_auto_approved += u64::from(before);
+        report.proposed_auto_approved += u64::from(after);
+        if before == after {
+            continue;
+        }
+        if after {
+            report.newly_auto_approved += 1;
+            if row.status == "rejected" {
+                report.newly_auto_approved_rejected += 1;
+            }
+        } else {
+            report.no_longer_auto_approved += 1;
+        }
+        report.examples.push(Reclassified {
+            extraction_id: row.id,
+            confidence: row.confidence_score,
+            status: row.status.clone(),
+            current: before,
+            proposed: after,
+        });
+    }
+    report
+        .examples
+        .sort_by_key(|example| example.status != "rejected");
+    report.examples.truncate(MAX_EXAMPLES);
+
+    Ok(report)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(confidence: f32, extractor: Option<&str>, items: Vec<Item>) -> Candidate {
+        Candidate {
+            confidence: Some(confidence),
+            access_level: "internal".to_string(),
+            extractor: extractor.map(str::to_string),
+            items,
+        }
+    }
+
+    #[test]
+    fn test_policy_rules() {
+        let policy = ApprovalPolicy {
+            rules: vec![
+                ApprovalRule {
+                    predicate: Some("requires".to_string()),
+                    min_confidence: None,
+                    ..Default::default()
+                },
+                ApprovalRule {
+                    entity_type: Some("LeaveType".to_string()),
+                    extractor: Some("rule".to_string()),
+                    min_confidence: Some(0.8),
+                    ..Default::default()
+                },
+            ],
+        };
+        let leave = || Item::Entity("LeaveType".to_string());
+
+        // Rule extractor gets the lower threshold, LLM the global one
+        assert!(policy.approves(&candidate(0.85, Some("rule"), vec![leave()]), 0.95));
+        assert!(!policy.approves(&candidate(0.85, Some("llm"), vec![leave()]), 0.95));
+        assert!(policy.approves(&candidate(0.96, Some("llm"), vec![leave()]), 0.95));
+
+        // Every item must pass; an excluded predicate blocks the extraction
+        let mixed = vec![leave(), Item::Entity("Department".to_string())];
+        assert!(!policy.approves(&candidate(0.85, Some("rule"), mixed), 0.95));
+        let excluded = vec![leave(), Item::Relation("requires".to_string())];
+        assert!(!policy.approves(&candidate(0.99, Some("rule"), excluded), 0.95));
+
+        // Empty extractions and access-level rules
+        assert!(policy.approves(&candidate(0.95, None, vec![]), 0.95));
+        let restricted = ApprovalPolicy {
+            rules: vec![ApprovalRule {
+                access_level: Some("confidential".to_string()),
+                min_confidence: None,
+                ..Default::default()
+            }],
+        };
+        let mut secret = candidate(0.99, None, vec![leave()]);
+        assert!(restricted.approves(&secret, 0.95));
+        secret.access_level = "confidential".to_string();
+        assert!(!restricted.approves(&secret, 0.95));
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        let invalid = |rule: ApprovalRule| ApprovalPolicy { rules: vec![rule] }.validate();
+
+        assert!(invalid(ApprovalRule {
+            entity_type: Some("LeaveType".to_string()),
+            predicate: Some("requires".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(invalid(ApprovalRule {
+            extractor: Some("manual".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(invalid(ApprovalRule {
+            min_confidence: Some(1.5),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(invalid(ApprovalRule {
+            access_level: Some("secret".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(invalid(ApprovalRule {
+            access_level: Some("restricted".to_string()),
+            extractor: Some("llm".to_string()),
+            min_confidence: Some(0.97),
+            ..Default::default()
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_items_from_content() {
+        let entities = serde_json::json!([
+            {"text": "연차", "entity_type": "LeaveType", "start": 0, "end": 6},
+            {"text": "broken"}
+        ]);
+        let relations = serde_json::json!([
+            {"subject": "연차", "predicate": "requires", "object": "신청서"}
+        ]);
+        assert_eq!(
+            items(&entities, &relations),
+            vec![
+                Item::Entity("LeaveType".to_string()),
+                Item::Relation("requires".to_string())
+            ]
+        );
+    }
+}
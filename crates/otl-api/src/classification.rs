@@ -0,0 +1,667 @@
+//! Access level suggestions at ingest
+//!
+//! Uploaders often label documents with the wrong sensitivity. Each new
+//! document is classified from its text: rules look for classification
+//! markers (대외비, 사내한정, ...), sensitive subjects and personal
+//! information, and for keywords of the departments that own documents.
+//! When the rules are not confident enough, the LLM is asked as well; it
+//! never lowers a level the rules found evidence for.
+//!
+//! The suggestion is returned with the upload response and stored in
+//! `document_classifications`. A suggested level that differs from the
+//! declared one with enough confidence flags the document for admin
+//! review. Suggestions are advisory: the declared ACL stays in force until
+//! someone changes it.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::handlers::documents::parse_access_level;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use otl_core::AccessLevel;
+use otl_parser::pii::{PiiConfig, PiiKind, PiiRedactor};
+use otl_rag::{detect_language, PromptTemplate};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// Confidence when no rule matched (the default level is a guess)
+const NO_EVIDENCE_CONFIDENCE: f32 = 0.3;
+
+/// Confidence added by each further signal for the suggested level
+const CORROBORATION_BONUS: f32 = 0.05;
+
+/// Highest confidence the rules claim
+const MAX_RULE_CONFIDENCE: f32 = 0.99;
+
+/// Department keyword occurrences needed to suggest a department
+const MIN_DEPARTMENT_HITS: usize = 2;
+
+/// Characters of the document shown to the LLM
+const LLM_EXCERPT_CHARS: usize = 3000;
+
+/// Confidence assumed when the LLM does not give one
+const DEFAULT_LLM_CONFIDENCE: f32 = 0.5;
+
+/// Phrases that mark or imply a level, with the confidence they carry
+///
+/// Matched case-insensitively with whitespace removed, so `사내 한정`
+/// matches `사내한정`.
+const LEVEL_MARKERS: &[(&str, AccessLevel, f32)] = &[
+    ("극비", AccessLevel::Restricted, 0.95),
+    ("비밀문서", AccessLevel::Restricted, 0.9),
+    ("strictly confidential", AccessLevel::Restricted, 0.9),
+    ("대외비", AccessLevel::Confidential, 0.95),
+    ("기밀", AccessLevel::Confidential, 0.8),
+    ("confidential", AccessLevel::Confidential, 0.85),
+    ("급여명세", AccessLevel::Confidential, 0.7),
+    ("인사평가", AccessLevel::Confidential, 0.7),
+    ("연봉", AccessLevel::Confidential, 0.6),
+    ("징계", AccessLevel::Confidential, 0.6),
+    ("사내한정", AccessLevel::Internal, 0.9),
+    ("내부용", AccessLevel::Internal, 0.85),
+    ("internal use only", AccessLevel::Internal, 0.9),
+    ("보도자료", AccessLevel::Public, 0.85),
+    ("press release", AccessLevel::Public, 0.85),
+];
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// Keywords of the documents a department owns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepartmentKeywords {
+    pub department: String,
+    pub keywords: Vec<String>,
+}
+
+/// How documents are classified and when a mismatch is flagged
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassificationPolicy {
+    /// Classify uploaded documents
+    pub enabled: bool,
+    /// Ask the LLM when the rules are not confident enough
+    pub use_llm: bool,
+    /// Rule confidence below which the LLM is asked
+    pub llm_below: f32,
+    /// Smallest confidence at which a differing level is flagged
+    pub flag_min_confidence: f32,
+    /// Departments that can be suggested, with their keywords
+    pub departments: Vec<DepartmentKeywords>,
+}
+
+impl Default for ClassificationPolicy {
+    fn default() -> Self {
+        let department = |name: &str, keywords: &[&str]| DepartmentKeywords {
+            department: name.to_string(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        };
+        Self {
+            enabled: true,
+            use_llm: true,
+            llm_below: 0.8,
+            flag_min_confidence: 0.6,
+            departments: vec![
+                department(
+                    "인사팀",
+                    &["인사", "채용", "급여", "연봉", "휴가", "근태", "복리후생"],
+                ),
+                department("재무팀", &["예산", "회계", "결산", "세금", "비용", "매출"]),
+                department("총무팀", &["총무", "시설", "비품", "자산관리", "차량"]),
+                department("영업팀", &["영업", "고객", "견적", "거래처", "수주"]),
+                department("법무팀", &["법무", "계약서", "소송", "약관", "준법"]),
+            ],
+        }
+    }
+}
+
+impl ClassificationPolicy {
+    /// Policy from `CLASSIFIER_ENABLED`, `CLASSIFIER_USE_LLM`,
+    /// `CLASSIFIER_LLM_BELOW_CONFIDENCE`, `CLASSIFIER_FLAG_MIN_CONFIDENCE`
+    /// and `CLASSIFIER_DEPARTMENTS` (JSON object of department to keywords)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        let flag = |name: &str, value: &mut bool| {
+            if let Ok(raw) = std::env::var(name) {
+                match raw.parse::<bool>() {
+                    Ok(parsed) => *value = parsed,
+                    Err(_) => tracing::warn!("Ignoring invalid {}: {}", name, raw),
+                }
+            }
+        };
+        flag("CLASSIFIER_ENABLED", &mut policy.enabled);
+        flag("CLASSIFIER_USE_LLM", &mut policy.use_llm);
+        let confidence = |name: &str, value: &mut f32| {
+            if let Ok(raw) = std::env::var(name) {
+                match raw.parse::<f32>() {
+                    Ok(parsed) if (0.0..=1.0).contains(&parsed) => *value = parsed,
+                    _ => tracing::warn!("Ignoring invalid {}: {}", name, raw),
+                }
+            }
+        };
+        confidence("CLASSIFIER_LLM_BELOW_CONFIDENCE", &mut policy.llm_below);
+        confidence(
+            "CLASSIFIER_FLAG_MIN_CONFIDENCE",
+            &mut policy.flag_min_confidence,
+        );
+        if let Ok(json) = std::env::var("CLASSIFIER_DEPARTMENTS") {
+            match serde_json::from_str::<std::collections::BTreeMap<String, Vec<String>>>(&json) {
+                Ok(departments) => {
+                    policy.departments = departments
+                        .into_iter()
+                        .map(|(department, keywords)| DepartmentKeywords {
+                            department,
+                            keywords,
+                        })
+                        .collect()
+                }
+                Err(e) => tracing::warn!("Ignoring invalid CLASSIFIER_DEPARTMENTS: {}", e),
+            }
+        }
+        policy
+    }
+
+    fn department_names(&self) -> Vec<String> {
+        self.departments
+            .iter()
+            .map(|d| d.department.clone())
+            .collect()
+    }
+}
+
+// ============================================================================
+// Classification
+// ============================================================================
+
+/// What produced a suggestion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionSource {
+    Rules,
+    /// The LLM, on top of the rules
+    Llm,
+}
+
+impl SuggestionSource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rules => "rules",
+            Self::Llm => "llm",
+        }
+    }
+}
+
+/// Suggested access level and owning department of a document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassificationSuggestion {
+    pub access_level: AccessLevel,
+    pub department: Option<String>,
+    /// Confidence in the access level (0.0-1.0)
+    pub confidence: f32,
+    pub source: SuggestionSource,
+    /// Evidence for the suggestion
+    pub reasons: Vec<String>,
+    /// The declared level differs and the document awaits admin review
+    pub mismatch: bool,
+}
+
+impl ClassificationSuggestion {
+    /// Whether a document declared at `declared` should be reviewed
+    pub fn flags(&self, declared: AccessLevel, policy: &ClassificationPolicy) -> bool {
+        self.access_level != declared && self.confidence >= policy.flag_min_confidence
+    }
+}
+
+/// Text with whitespace removed and lowercased, for phrase matching
+fn compact(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Redactors counting each kind of personal information separately
+fn pii_detectors() -> &'static [(PiiKind, PiiRedactor)] {
+    static DETECTORS: OnceLock<Vec<(PiiKind, PiiRedactor)>> = OnceLock::new();
+    DETECTORS.get_or_init(|| {
+        PiiKind::ALL
+            .iter()
+            .map(|kind| {
+                let config = PiiConfig {
+                    kinds: vec![*kind],
+                    ..Default::default()
+                };
+                (*kind, PiiRedactor::new(&config))
+            })
+            .collect()
+    })
+}
+
+/// Classify a document from its text with the rules alone
+pub fn classify_rules(text: &str, policy: &ClassificationPolicy) -> ClassificationSuggestion {
+    let compacted = compact(text);
+    let mut signals: Vec<(AccessLevel, f32, String)> = LEVEL_MARKERS
+        .iter()
+        .filter(|(phrase, _, _)| compacted.contains(&compact(phrase)))
+        .map(|(phrase, level, confidence)| (*level, *confidence, format!("marker '{phrase}'")))
+        .collect();
+    for (kind, detector) in pii_detectors() {
+        let (level, confidence, label) = match kind {
+            PiiKind::ResidentId => (
+                AccessLevel::Restricted,
+                0.85,
+                "resident registration numbers",
+            ),
+            PiiKind::CardNumber => (AccessLevel::Confidential, 0.7, "card numbers"),
+            // Contact details appear in ordinary notices
+            PiiKind::Phone | PiiKind::Email => continue,
+        };
+        let (_, count) = detector.redact(text);
+        if count > 0 {
+            signals.push((level, confidence, format!("{count} {label}")));
+        }
+    }
+
+    let (access_level, confidence) = match signals.iter().map(|(level, ..)| *level).max() {
+        Some(level) => {
+            let mut at_level: Vec<f32> = signals
+                .iter()
+                .filter(|(l, ..)| *l == level)
+                .map(|(_, confidence, _)| *confidence)
+                .collect();
+            at_level.sort_by(|a, b| b.total_cmp(a));
+            let bonus = CORROBORATION_BONUS * (at_level.len() - 1) as f32;
+            (level, (at_level[0] + bonus).min(MAX_RULE_CONFIDENCE))
+        }
+        None => (AccessLevel::default(), NO_EVIDENCE_CONFIDENCE),
+    };
+
+    let mut reasons: Vec<String> = signals.into_iter().map(|(_, _, reason)| reason).collect();
+    let department = suggest_department(&compacted, policy).map(|(department, keywords)| {
+        reasons.push(format!("keywords of {department}: {}", keywords.join(", ")));
+        department
+    });
+    ClassificationSuggestion {
+        access_level,
+        department,
+        confidence,
+        source: SuggestionSource::Rules,
+        reasons,
+        mismatch: false,
+    }
+}
+
+/// Department whose keywords occur most often, with the keywords found
+fn suggest_department<'a>(
+    compacted: &str,
+    policy: &'a ClassificationPolicy,
+) -> Option<(String, Vec<&'a str>)> {
+    let mut best: Option<(usize, &DepartmentKeywords, Vec<&str>)> = None;
+    for department in &policy.departments {
+        let mut hits = 0;
+        let mut found = Vec::new();
+        for keyword in &department.keywords {
+            let count = compacted.matches(&compact(keyword)).count();
+            if count > 0 {
+                hits += count;
+                found.push(keyword.as_str());
+            }
+        }
+        if hits >= MIN_DEPARTMENT_HITS && best.as_ref().map_or(true, |(most, ..)| hits > *most) {
+            best = Some((hits, department, found));
+        }
+    }
+    best.map(|(_, department, found)| (department.department.clone(), found))
+}
+
+/// Verdict the LLM replies with
+#[derive(Debug, Deserialize)]
+struct LlmVerdict {
+    access_level: AccessLevel,
+    #[serde(default)]
+    department: Option<String>,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// The JSON object in an LLM reply, which may be wrapped in prose or a
+/// code fence
+fn parse_verdict(reply: &str) -> Option<LlmVerdict> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+/// Combine the rule suggestion with the LLM's verdict
+///
+/// The LLM may raise the level or settle a level the rules had no evidence
+/// for, but not lower a level the rules found evidence for. Departments
+/// outside the policy's list are ignored.
+fn merge_verdict(
+    rules: ClassificationSuggestion,
+    verdict: LlmVerdict,
+    policy: &ClassificationPolicy,
+) -> ClassificationSuggestion {
+    let has_evidence = rules.confidence > NO_EVIDENCE_CONFIDENCE;
+    let llm_confidence = verdict
+        .confidence
+        .unwrap_or(DEFAULT_LLM_CONFIDENCE)
+        .clamp(0.0, 1.0);
+    let (access_level, confidence) = if has_evidence && rules.access_level > verdict.access_level {
+        (rules.access_level, rules.confidence)
+    } else if has_evidence && rules.access_level == verdict.access_level {
+        (rules.access_level, rules.confidence.max(llm_confidence))
+    } else {
+        (verdict.access_level, llm_confidence)
+    };
+    let department = verdict
+        .department
+        .filter(|d| policy.departments.iter().any(|k| &k.department == d))
+        .or(rules.department);
+    let mut reasons = rules.reasons;
+    if let Some(reason) = verdict.reason.filter(|r| !r.trim().is_empty()) {
+        reasons.push(format!("LLM: {}", reason.trim()));
+    }
+    ClassificationSuggestion {
+        access_level,
+        department,
+        confidence,
+        source: SuggestionSource::Llm,
+        reasons,
+        mismatch: false,
+    }
+}
+
+/// Classify a document, asking the LLM when the rules are unsure
+///
+/// Personal information is redacted from the excerpt the LLM sees.
+pub async fn classify(state: &AppState, text: &str) -> ClassificationSuggestion {
+    let policy = &state.classification;
+    let rules = classify_rules(text, policy);
+    if !policy.use_llm || rules.confidence >= policy.llm_below {
+        return rules;
+    }
+    let Some(llm) = state.llm_client.read().await.clone() else {
+        return rules;
+    };
+
+    let excerpt: String = text.chars().take(LLM_EXCERPT_CHARS).collect();
+    let (excerpt, _) = PiiRedactor::new(&PiiConfig::default()).redact(&excerpt);
+    let template = PromptTemplate::for_language(detect_language(&excerpt));
+    let prompt = template.classification_prompt(&excerpt, &policy.department_names());
+    match llm.generate(&prompt).await {
+        Ok(reply) => match parse_verdict(&reply) {
+            Some(verdict) => merge_verdict(rules, verdict, policy),
+            None => {
+                tracing::warn!("Unparseable classification reply: {}", reply);
+                rules
+            }
+        },
+        Err(e) => {
+            tracing::warn!("Document classification by LLM failed: {}", e);
+            rules
+        }
+    }
+}
+
+/// Classify an uploaded document and record the suggestion
+///
+/// Returns `None` when classification is disabled. Failing to record the
+/// suggestion is logged, not returned.
+pub async fn classify_upload(
+    state: &AppState,
+    document_id: Uuid,
+    text: &str,
+    declared_level: AccessLevel,
+    declared_department: Option<&str>,
+) -> Option<ClassificationSuggestion> {
+    if !state.classification.enabled {
+        return None;
+    }
+    let mut suggestion = classify(state, text).await;
+    suggestion.mismatch = suggestion.flags(declared_level, &state.classification);
+    if suggestion.mismatch {
+        tracing::info!(
+            "Document {document_id} declared {declared_level} but looks {} ({:.2})",
+            suggestion.access_level,
+            suggestion.confidence
+        );
+    }
+
+    if let Err(e) = sqlx::query(
+        "INSERT INTO document_classifications
+            (document_id, declared_access_level, declared_department, suggested_access_level,
+             suggested_department, confidence, source, reasons, mismatch)
+         VALUES ($1, $2::access_level, $3, $4::access_level, $5, $6, $7, $8, $9)
+         ON CONFLICT (document_id) DO UPDATE SET
+            declared_access_level = EXCLUDED.declared_access_level,
+            declared_department = EXCLUDED.declared_department,
+            suggested_access_level = EXCLUDED.suggested_access_level,
+            suggested_department = EXCLUDED.suggested_department,
+            confidence = EXCLUDED.confidence, source = EXCLUDED.source,
+            reasons = EXCLUDED.reasons, mismatch = EXCLUDED.mismatch,
+            reviewed_at = NULL, reviewed_by = NULL, review_note = NULL, created_at = NOW()",
+    )
+    .bind(document_id)
+    .bind(declared_level.to_string())
+    .bind(declared_department)
+    .bind(suggestion.access_level.to_string())
+    .bind(&suggestion.department)
+    .bind(suggestion.confidence)
+    .bind(suggestion.source.as_str())
+    .bind(&suggestion.reasons)
+    .bind(suggestion.mismatch)
+    .execute(&state.db_pool)
+    .await
+    {
+        tracing::warn!("Failed to record classification of document {document_id}: {e}");
+    }
+    Some(suggestion)
+}
+
+// ============================================================================
+// Review
+// ============================================================================
+
+/// Document whose declared access level differs from the suggestion
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassificationReview {
+    pub document_id: Uuid,
+    pub document_title: String,
+    pub declared_access_level: AccessLevel,
+    pub declared_department: Option<String>,
+    pub suggested_access_level: AccessLevel,
+    pub suggested_department: Option<String>,
+    pub confidence: f32,
+    pub source: String,
+    pub reasons: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub reviewed_by: Option<Uuid>,
+    pub review_note: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ReviewRow {
+    document_id: Uuid,
+    document_title: String,
+    declared_access_level: String,
+    declared_department: Option<String>,
+    suggested_access_level: String,
+    suggested_department: Option<String>,
+    confidence: f32,
+    source: String,
+    reasons: Vec<String>,
+    created_at: DateTime<Utc>,
+    reviewed_at: Option<DateTime<Utc>>,
+    reviewed_by: Option<Uuid>,
+    review_note: Option<String>,
+}
+
+impl From<ReviewRow> for ClassificationReview {
+    fn from(row: ReviewRow) -> Self {
+        Self {
+            document_id: row.document_id,
+            document_title: row.document_title,
+            declared_access_level: parse_access_level(&row.declared_access_level),
+            declared_department: row.declared_department,
+            suggested_access_level: parse_access_level(&row.suggested_access_level),
+            suggested_department: row.suggested_department,
+            confidence: row.confidence,
+            source: row.source,
+            reasons: row.reasons,
+            created_at: row.created_at,
+            reviewed_at: row.reviewed_at,
+            reviewed_by: row.reviewed_by,
+            review_note: row.review_note,
+        }
+    }
+}
+
+const REVIEW_COLUMNS: &str = "c.document_id, d.title AS document_title, \
+     c.declared_access_level::text AS declared_access_level, c.declared_department, \
+     c.suggested_access_level::text AS suggested_access_level, c.suggested_department, \
+     c.confidence, c.source, c.reasons, c.created_at, c.reviewed_at, c.reviewed_by, \
+     c.review_note";
+
+/// Flagged documents, newest first; reviewed ones only with
+/// `include_reviewed`
+pub async fn list_flagged(
+    pool: &sqlx::PgPool,
+    include_reviewed: bool,
+    limit: i64,
+) -> Result<Vec<ClassificationReview>, sqlx::Error> {
+    let rows: Vec<ReviewRow> = sqlx::query_as(&format!(
+        "SELECT {REVIEW_COLUMNS} FROM document_classifications c
+         JOIN documents d ON d.id = c.document_id
+         WHERE c.mismatch AND d.deleted_at IS NULL AND ($1 OR c.reviewed_at IS NULL)
+         ORDER BY c.created_at DESC
+         LIMIT $2"
+    ))
+    .bind(include_reviewed)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(ClassificationReview::from).collect())
+}
+
+/// Mark a flagged document reviewed; `None` if it is not flagged
+pub async fn resolve(
+    pool: &sqlx::PgPool,
+    document_id: Uuid,
+    reviewer: Uuid,
+    note: Option<&str>,
+) -> Result<Option<ClassificationReview>, sqlx::Error> {
+    let updated = sqlx::query(
+        "UPDATE document_classifications
+         SET reviewed_at = NOW(), reviewed_by = $2, review_note = $3
+         WHERE document_id = $1 AND mismatch",
+    )
+    .bind(document_id)
+    .bind(reviewer)
+    .bind(note)
+    .execute(pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    let row: Option<ReviewRow> = sqlx::query_as(&format!(
+        "SELECT {REVIEW_COLUMNS} FROM document_classifications c
+         JOIN documents d ON d.id = c.document_id
+         WHERE c.document_id = $1"
+    ))
+    .bind(document_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(ClassificationReview::from))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markers_suggest_levels() {
+        let policy = ClassificationPolicy::default();
+
+        let doc = classify_rules("[대외비] 2026년 사업 계획", &policy);
+        assert_eq!(doc.access_level, AccessLevel::Confidential);
+        assert!(doc.confidence >= 0.9);
+
+        let doc = classify_rules("사내 한정 배포. 사내 동호회 안내", &policy);
+        assert_eq!(doc.access_level, AccessLevel::Internal);
+
+        let doc = classify_rules("보도자료: 신제품 출시", &policy);
+        assert_eq!(doc.access_level, AccessLevel::Public);
+
+        let doc = classify_rules("[극비] 대외비 자료보다 엄격히 관리", &policy);
+        assert_eq!(doc.access_level, AccessLevel::Restricted);
+    }
+
+    #[test]
+    fn test_personal_information_raises_the_level() {
+        let policy = ClassificationPolicy::default();
+        let doc = classify_rules("홍길동 주민등록번호 900101-1234567", &policy);
+        assert_eq!(doc.access_level, AccessLevel::Restricted);
+        assert!(doc.reasons.iter().any(|r| r.contains("resident")));
+
+        let doc = classify_rules("문의: 02-123-4567, hr@example.com", &policy);
+        assert_eq!(doc.access_level, AccessLevel::Internal);
+        assert_eq!(doc.confidence, NO_EVIDENCE_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_department_needs_repeated_keywords() {
+        let policy = ClassificationPolicy::default();
+        let doc = classify_rules("연봉 조정 및 급여 지급일 안내", &policy);
+        assert_eq!(doc.department.as_deref(), Some("인사팀"));
+
+        let doc = classify_rules("예산 안내", &policy);
+        assert_eq!(doc.department, None);
+    }
+
+    #[test]
+    fn test_llm_cannot_lower_a_level_with_evidence() {
+        let policy = ClassificationPolicy::default();
+        let rules = classify_rules("징계 위원회 회의록", &policy);
+        let verdict = parse_verdict(
+            "```json\n{\"access_level\": \"public\", \"department\": \"인사팀\", \"confidence\": 0.9}\n```",
+        )
+        .unwrap();
+        let merged = merge_verdict(rules, verdict, &policy);
+        assert_eq!(merged.access_level, AccessLevel::Confidential);
+        assert_eq!(merged.department.as_deref(), Some("인사팀"));
+        assert_eq!(merged.source, SuggestionSource::Llm);
+
+        let rules = classify_rules("회의실 예약 방법", &policy);
+        let verdict = parse_verdict(
+            "{\"access_level\": \"public\", \"department\": \"마케팅팀\", \"confidence\": 0.7, \"reason\": \"일반 안내\"}",
+        )
+        .unwrap();
+        let merged = merge_verdict(rules, verdict, &policy);
+        assert_eq!(merged.access_level, AccessLevel::Public);
+        assert_eq!(merged.department, None);
+        assert_eq!(merged.reasons, vec!["LLM: 일반 안내".to_string()]);
+
+        assert!(parse_verdict("잘 모르겠습니다").is_none());
+    }
+
+    #[test]
+    fn test_mismatch_needs_confidence() {
+        let policy = ClassificationPolicy::default();
+        let doc = classify_rules("[대외비] 인수 합병 검토", &policy);
+        assert!(doc.flags(AccessLevel::Public, &policy));
+        assert!(!doc.flags(AccessLevel::Confidential, &policy));
+
+        let guess = classify_rules("회의실 예약 방법", &policy);
+        assert!(!guess.flags(AccessLevel::Public, &policy));
+    }
+}
@@ -0,0 +1,747 @@
+//! Document comparison
+//!
+//! Compares two documents, typically an old and a new revision of the same
+//! regulation. Chunks are regrouped into sections by their section name,
+//! sections of the two documents are aligned by heading and content
+//! (embedding similarity when both sides have vectors, word overlap
+//! otherwise), and every aligned pair gets a word-level diff. The result is
+//! served by `POST /api/v1/documents/compare`, optionally with an LLM-written
+//! change summary that cites both versions.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_rag::PromptTemplate;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Default similarity two sections need to be aligned without a shared heading
+pub const DEFAULT_MIN_SIMILARITY: f32 = 0.5;
+
+/// Word pairs above which the diff of a section pair is not computed in detail
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Characters of each section quoted in the summary prompt
+const MAX_SUMMARY_SECTION_CHARS: usize = 1500;
+
+/// Changed sections quoted in the summary prompt
+pub const MAX_SUMMARY_CHANGES: usize = 20;
+
+// ============================================================================
+// Sections
+// ============================================================================
+
+/// Stored chunk, as read for comparison
+#[derive(Debug, Clone)]
+pub struct CompareChunk {
+    pub chunk_index: u32,
+    pub content: String,
+    pub start_offset: Option<u32>,
+    pub end_offset: Option<u32>,
+    pub page: Option<u32>,
+    pub section: Option<String>,
+}
+
+/// Consecutive chunks of a document sharing a section name
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub title: Option<String>,
+    pub page: Option<u32>,
+    pub text: String,
+    /// Mean embedding of the section's chunks, when they are embedded
+    pub vector: Option<Vec<f32>>,
+}
+
+/// Group chunks (in document order) into sections
+///
+/// Chunks without a section name each form their own section. Text repeated
+/// by chunk overlap is dropped using the chunk offsets. `vectors` maps chunk
+/// indexes to embeddings.
+pub fn group_sections(chunks: &[CompareChunk], vectors: &HashMap<u32, Vec<f32>>) -> Vec<Section> {
+    struct Builder {
+        section: Section,
+        end_offset: Option<u32>,
+        vectors: Vec<Vec<f32>>,
+    }
+
+    let mut builders: Vec<Builder> = Vec::new();
+    for chunk in chunks {
+        let continues = chunk.section.is_some()
+            && builders
+                .last()
+                .is_some_and(|b| b.section.title == chunk.section);
+
+        if !continues {
+            builders.push(Builder {
+                section: Section {
+                    title: chunk.section.clone(),
+                    page: chunk.page,
+                    text: String::new(),
+                    vector: None,
+                },
+                end_offset: None,
+                vectors: Vec::new(),
+            });
+        }
+        let builder = builders.last_mut().expect("a section was just pushed");
+
+        let overlap = match (builder.end_offset, chunk.start_offset) {
+            (Some(end), Some(start)) if start < end => (end - start) as usize,
+            _ => 0,
+        };
+        let text: String = chunk.content.chars().skip(overlap).collect();
+        let text = text.trim();
+        if !text.is_empty() {
+            if !builder.section.text.is_empty() {
+                builder.section.text.push(' ');
+            }
+            builder.section.text.push_str(text);
+        }
+        builder.end_offset = chunk.end_offset.or(builder.end_offset);
+        if let Some(vector) = vectors.get(&chunk.chunk_index) {
+            builder.vectors.push(vector.clone());
+        }
+    }
+
+    builders
+        .into_iter()
+        .map(|mut b| {
+            b.section.vector = mean_vector(&b.vectors);
+            b.section
+        })
+        .collect()
+}
+
+fn mean_vector(vectors: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let dimension = vectors.first()?.len();
+    let mut mean = vec![0.0f32; dimension];
+    for vector in vectors.iter().filter(|v| v.len() == dimension) {
+        for (m, x) in mean.iter_mut().zip(vector) {
+            *m += x;
+        }
+    }
+    let count = vectors.len() as f32;
+    mean.iter_mut().for_each(|m| *m /= count);
+    Some(mean)
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Heading compared ignoring case and whitespace (`제3조 (연차휴가)` matches
+/// `제3조(연차휴가)`)
+fn heading_key(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn word_set(text: &str) -> HashSet<&str> {
+    text.split_whitespace().collect()
+}
+
+fn jaccard(a: &HashSet<&str>, b: &HashSet<&str>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f32 / union as f32
+}
+
+// ============================================================================
+// Alignment
+// ============================================================================
+
+/// Sections paired across the two documents
+///
+/// Each entry holds an old and/or a new section index; entries are ordered
+/// by the new document, with removed sections placed after the section that
+/// preceded them in the old document.
+pub fn align_sections(old: &[Section], new: &[Section], min_similarity: f32) -> Vec<Alignment> {
+    let old_words: Vec<_> = old.iter().map(|s| word_set(&s.text)).collect();
+    let new_words: Vec<_> = new.iter().map(|s| word_set(&s.text)).collect();
+
+    // (score, old, new); a shared heading ranks above any content match
+    let mut candidates: Vec<(f32, usize, usize, Option<f32>)> = Vec::new();
+    for (i, o) in old.iter().enumerate() {
+        for (j, n) in new.iter().enumerate() {
+            let semantic = match (&o.vector, &n.vector) {
+                (Some(a), Some(b)) => Some(cosine_similarity(a, b)),
+                _ => None,
+            };
+            let content = semantic.unwrap_or_else(|| jaccard(&old_words[i], &new_words[j]));
+            let same_heading = match (&o.title, &n.title) {
+                (Some(a), Some(b)) => heading_key(a) == heading_key(b),
+                _ => false,
+            };
+            if same_heading {
+                candidates.push((1.0 + content, i, j, semantic));
+            } else if content >= min_similarity {
+                candidates.push((content, i, j, semantic));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    let mut old_match: Vec<Option<(usize, Option<f32>)>> = vec![None; old.len()];
+    let mut new_taken = vec![false; new.len()];
+    for (_, i, j, semantic) in candidates {
+        if old_match[i].is_none() && !new_taken[j] {
+            old_match[i] = Some((j, semantic));
+            new_taken[j] = true;
+        }
+    }
+
+    let mut by_new: HashMap<usize, (usize, Option<f32>)> = HashMap::new();
+    for (i, matched) in old_match.iter().enumerate() {
+        if let Some((j, semantic)) = matched {
+            by_new.insert(*j, (i, *semantic));
+        }
+    }
+
+    // Removed sections follow the nearest preceding old section that was kept
+    let mut removed_after: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    let mut anchor: Option<usize> = None;
+    for (i, matched) in old_match.iter().enumerate() {
+        match matched {
+            Some((j, _)) => anchor = Some(*j),
+            None => removed_after.entry(anchor).or_default().push(i),
+        }
+    }
+
+    let removed = |alignments: &mut Vec<Alignment>, key: Option<usize>| {
+        for &i in removed_after.get(&key).into_iter().flatten() {
+            alignments.push(Alignment {
+                old: Some(i),
+                new: None,
+                semantic_similarity: None,
+            });
+        }
+    };
+
+    let mut alignments = Vec::new();
+    removed(&mut alignments, None);
+    for j in 0..new.len() {
+        let (old_index, semantic) = match by_new.get(&j) {
+            Some((i, semantic)) => (Some(*i), *semantic),
+            None => (None, None),
+        };
+        alignments.push(Alignment {
+            old: old_index,
+            new: Some(j),
+            semantic_similarity: semantic,
+        });
+        removed(&mut alignments, Some(j));
+    }
+    alignments
+}
+
+/// Old and new section indexes of one aligned pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alignment {
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+    /// Cosine similarity of the section embeddings, when both are embedded
+    pub semantic_similarity: Option<f32>,
+}
+
+// ============================================================================
+// Textual diff
+// ============================================================================
+
+/// Kind of a diff run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    /// Words present in both versions
+    Equal,
+    /// Words only in the new version
+    Insert,
+    /// Words only in the old version
+    Delete,
+}
+
+/// Run of words with the same diff kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TextChange {
+    pub op: DiffOp,
+    /// Words of the run, separated by single spaces
+    #[schema(example = "15일")]
+    pub text: String,
+}
+
+/// Word-level diff of two texts
+///
+/// Common leading and trailing words are matched directly; the rest is
+/// aligned by longest common subsequence, or reported as one deletion and one
+/// insertion when it is too long to align.
+pub fn diff_words(old: &str, new: &str) -> Vec<TextChange> {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<(DiffOp, &str)> = old[..prefix].iter().map(|w| (DiffOp::Equal, *w)).collect();
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        ops.extend(old_mid.iter().map(|w| (DiffOp::Delete, *w)));
+        ops.extend(new_mid.iter().map(|w| (DiffOp::Insert, *w)));
+    } else {
+        ops.extend(lcs_diff(old_mid, new_mid));
+    }
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|w| (DiffOp::Equal, *w)),
+    );
+
+    let mut changes: Vec<TextChange> = Vec::new();
+    for (op, word) in ops {
+        match changes.last_mut() {
+            Some(last) if last.op == op => {
+                last.text.push(' ');
+                last.text.push_str(word);
+            }
+            _ => changes.push(TextChange {
+                op,
+                text: word.to_string(),
+            }),
+        }
+    }
+    changes
+}
+
+fn lcs_diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(DiffOp, &'a str)> {
+    let (n, m) = (old.len(), new.len());
+    // lengths[i][j]: LCS length of old[i..] and new[j..]
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[at(i, j)] = if old[i] == new[j] {
+                lengths[at(i + 1, j + 1)] + 1
+            } else {
+                lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            ops.push((DiffOp::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[at(i + 1, j)] >= lengths[at(i, j + 1)] {
+            ops.push((DiffOp::Delete, old[i]));
+            i += 1;
+        } else {
+            ops.push((DiffOp::Insert, new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|w| (DiffOp::Delete, *w)));
+    ops.extend(new[j..].iter().map(|w| (DiffOp::Insert, *w)));
+    ops
+}
+
+/// Share of words (0-1) the diff keeps unchanged
+pub fn textual_similarity(changes: &[TextChange]) -> f32 {
+    let words = |op: DiffOp| -> usize {
+        changes
+            .iter()
+            .filter(|c| c.op == op)
+            .map(|c| c.text.split(' ').count())
+            .sum()
+    };
+    let equal = words(DiffOp::Equal);
+    let total = 2 * equal + words(DiffOp::Insert) + words(DiffOp::Delete);
+    if total == 0 {
+        1.0
+    } else {
+        (2 * equal) as f32 / total as f32
+    }
+}
+
+// ============================================================================
+// Comparison
+// ============================================================================
+
+/// How a section changed between the two documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionChange {
+    Unchanged,
+    Modified,
+    Added,
+    Removed,
+}
+
+/// One side of a section comparison
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SectionSide {
+    /// Section name
+    #[schema(example = "제3조 (연차휴가)")]
+    pub title: Option<String>,
+    /// Page the section starts on
+    pub page: Option<u32>,
+    /// Section text
+    pub text: String,
+}
+
+impl From<&Section> for SectionSide {
+    fn from(section: &Section) -> Self {
+        Self {
+            title: section.title.clone(),
+            page: section.page,
+            text: section.text.clone(),
+        }
+    }
+}
+
+/// Comparison of one aligned section pair
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SectionDiff {
+    pub change: SectionChange,
+    /// Section in the old document
+    pub old: Option<SectionSide>,
+    /// Section in the new document
+    pub new: Option<SectionSide>,
+    /// Share of words kept unchanged (0-1)
+    #[schema(example = 0.93)]
+    pub textual_similarity: f32,
+    /// Cosine similarity of the section embeddings, when both are embedded
+    #[schema(example = 0.97)]
+    pub semantic_similarity: Option<f32>,
+    /// Word-level diff; empty for unchanged sections
+    pub changes: Vec<TextChange>,
+}
+
+/// Number of sections by change
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct CompareStats {
+    pub unchanged: usize,
+    pub modified: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Compare the sections of two documents
+pub fn compare_sections(
+    old: &[Section],
+    new: &[Section],
+    min_similarity: f32,
+) -> (Vec<SectionDiff>, CompareStats) {
+    let mut stats = CompareStats::default();
+    let diffs: Vec<SectionDiff> = align_sections(old, new, min_similarity)
+        .into_iter()
+        .map(|alignment| {
+            let old = alignment.old.map(|i| &old[i]);
+            let new = alignment.new.map(|j| &new[j]);
+            let changes = diff_words(
+                old.map(|s| s.text.as_str()).unwrap_or_default(),
+                new.map(|s| s.text.as_str()).unwrap_or_default(),
+            );
+            let change = match (old, new) {
+                (Some(_), None) => SectionChange::Removed,
+                (None, _) => SectionChange::Added,
+                _ if changes.iter().all(|c| c.op == DiffOp::Equal) => SectionChange::Unchanged,
+                _ => SectionChange::Modified,
+            };
+            match change {
+                SectionChange::Unchanged => stats.unchanged += 1,
+                SectionChange::Modified => stats.modified += 1,
+                SectionChange::Added => stats.added += 1,
+                SectionChange::Removed => stats.removed += 1,
+            }
+            SectionDiff {
+                change,
+                old: old.map(SectionSide::from),
+                new: new.map(SectionSide::from),
+                textual_similarity: textual_similarity(&changes),
+                semantic_similarity: alignment.semantic_similarity,
+                changes: if change == SectionChange::Unchanged {
+                    Vec::new()
+                } else {
+                    changes
+                },
+            }
+        })
+        .collect();
+    (diffs, stats)
+}
+
+// ============================================================================
+// Change summary
+// ============================================================================
+
+/// Version a summary citation points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentVersion {
+    Old,
+    New,
+}
+
+/// Source quoted in the change summary prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CompareCitation {
+    /// Number of the `[출처: N]` marker in the summary
+    #[schema(example = 1)]
+    pub index: u32,
+    pub version: DocumentVersion,
+    pub document_id: Uuid,
+    #[schema(example = "인사규정_2023.pdf")]
+    pub document_title: String,
+    #[schema(example = "제3조 (연차휴가)")]
+    pub section: Option<String>,
+    pub page: Option<u32>,
+}
+
+/// Compared document
+#[derive(Debug, Clone)]
+pub struct CompareDocument {
+    pub id: Uuid,
+    pub title: String,
+}
+
+/// Summary prompt over the changed sections, with the sources it cites in
+/// marker order (unnumbered)
+pub fn summary_prompt(
+    template: &PromptTemplate,
+    old_document: &CompareDocument,
+    new_document: &CompareDocument,
+    diffs: &[SectionDiff],
+) -> (String, Vec<CompareCitation>) {
+    let mut sources = Vec::new();
+    let mut citations = Vec::new();
+    let changed = diffs
+        .iter()
+        .filter(|d| d.change != SectionChange::Unchanged)
+        .take(MAX_SUMMARY_CHANGES);
+    for diff in changed {
+        let sides = [
+            (DocumentVersion::Old, old_document, &diff.old),
+            (DocumentVersion::New, new_document, &diff.new),
+        ];
+        for (version, document, side) in sides {
+            let Some(side) = side else { continue };
+            let label = match version {
+                DocumentVersion::Old => template.old_version_label,
+                DocumentVersion::New => template.new_version_label,
+            };
+            let heading = match &side.title {
+                Some(title) => format!("{label} · {} · {title}", document.title),
+                None => format!("{label} · {}", document.title),
+            };
+            let text: String = side.text.chars().take(MAX_SUMMARY_SECTION_CHARS).collect();
+            sources.push((heading, text));
+            citations.push(CompareCitation {
+                index: citations.len() as u32 + 1,
+                version,
+                document_id: document.id,
+                document_title: document.title.clone(),
+                section: side.title.clone(),
+                page: side.page,
+            });
+        }
+    }
+    (template.compare_summary_prompt(&sources), citations)
+}
+
+/// Check the summary's citation markers against the quoted sources,
+/// renumbering them by first appearance
+pub fn cite_summary(summary: &str, sources: &[CompareCitation]) -> (String, Vec<CompareCitation>) {
+    let checked = otl_rag::citations::verify(summary, sources.len());
+    let citations = checked
+        .cited
+        .iter()
+        .map(|&(index, position)| CompareCitation {
+            index,
+            ..sources[position].clone()
+        })
+        .collect();
+    (checked.answer.trim().to_string(), citations)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(title: &str, text: &str) -> Section {
+        Section {
+            title: Some(title.to_string()),
+            page: Some(1),
+            text: text.to_string(),
+            vector: None,
+        }
+    }
+
+    fn chunk(
+        index: u32,
+        section: Option<&str>,
+        content: &str,
+        offsets: (u32, u32),
+    ) -> CompareChunk {
+        CompareChunk {
+            chunk_index: index,
+            content: content.to_string(),
+            start_offset: Some(offsets.0),
+            end_offset: Some(offsets.1),
+            page: Some(1),
+            section: section.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_group_sections_merges_chunks_and_drops_overlap() {
+        let chunks = vec![
+            chunk(0, Some("제1조"), "목적은 다음과 같다.", (0, 11)),
+            chunk(1, Some("제1조"), "같다. 이 규정은", (8, 17)),
+            chunk(2, None, "부칙 하나", (17, 22)),
+            chunk(3, None, "부칙 둘", (22, 26)),
+        ];
+        let vectors = HashMap::from([(0, vec![1.0, 0.0]), (1, vec![0.0, 1.0])]);
+
+        let sections = group_sections(&chunks, &vectors);
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].text, "목적은 다음과 같다. 이 규정은");
+        assert_eq!(sections[0].vector, Some(vec![0.5, 0.5]));
+        assert_eq!(sections[1].text, "부칙 하나");
+        assert!(sections[2].vector.is_none());
+    }
+
+    #[test]
+    fn test_diff_words() {
+        let changes = diff_words("연차휴가는 연 15일 부여한다", "연차휴가는 연 20일 부여한다");
+        assert_eq!(
+            changes,
+            vec![
+                TextChange {
+                    op: DiffOp::Equal,
+                    text: "연차휴가는 연".to_string()
+                },
+                TextChange {
+                    op: DiffOp::Delete,
+                    text: "15일".to_string()
+                },
+                TextChange {
+                    op: DiffOp::Insert,
+                    text: "20일".to_string()
+                },
+                TextChange {
+                    op: DiffOp::Equal,
+                    text: "부여한다".to_string()
+                },
+            ]
+        );
+        assert!((textual_similarity(&changes) - 0.75).abs() < 1e-6);
+        assert_eq!(textual_similarity(&diff_words("", "")), 1.0);
+    }
+
+    #[test]
+    fn test_compare_sections() {
+        let old = vec![
+            section("제1조 (목적)", "이 규정은 휴가에 관한 사항을 정한다"),
+            section("제2조 (연차휴가)", "연차휴가는 연 15일 부여한다"),
+            section("제3조 (포상휴가)", "포상휴가는 대표이사가 정한다"),
+        ];
+        let new = vec![
+            section("제1조(목적)", "이 규정은 휴가에 관한 사항을 정한다"),
+            section("제2조 (연차휴가)", "연차휴가는 연 20일 부여한다"),
+            section("제3조 (병가)", "병가는 연 60일 이내로 한다"),
+        ];
+
+        let (diffs, stats) = compare_sections(&old, &new, DEFAULT_MIN_SIMILARITY);
+        let changes: Vec<_> = diffs.iter().map(|d| d.change).collect();
+        assert_eq!(
+            changes,
+            vec![
+                SectionChange::Unchanged,
+                SectionChange::Modified,
+                SectionChange::Removed,
+                SectionChange::Added,
+            ]
+        );
+        assert_eq!(
+            stats,
+            CompareStats {
+                unchanged: 1,
+                modified: 1,
+                added: 1,
+                removed: 1
+            }
+        );
+        assert!(diffs[0].changes.is_empty());
+        assert_eq!(
+            diffs[2].old.as_ref().unwrap().title.as_deref(),
+            Some("제3조 (포상휴가)")
+        );
+    }
+
+    #[test]
+    fn test_align_sections_by_content() {
+        let mut old = section("제5조", "출장비는 실비로 정산한다");
+        let mut new = section("제7조", "출장비는 실비로 정산한다");
+        old.vector = Some(vec![1.0, 0.0]);
+        new.vector = Some(vec![0.9, 0.1]);
+
+        let alignments = align_sections(&[old], &[new], DEFAULT_MIN_SIMILARITY);
+        assert_eq!(alignments.len(), 1);
+        assert_eq!(alignments[0].old, Some(0));
+        assert!(alignments[0].semantic_similarity.unwrap() > 0.9);
+    }
+
+    #[test]
+    fn test_summary_citations() {
+        let template = PromptTemplate::for_language(otl_core::Language::Korean);
+        let old_document = CompareDocument {
+            id: Uuid::new_v4(),
+            title: "인사규정_2023.pdf".to_string(),
+        };
+        let new_document = CompareDocument {
+            id: Uuid::new_v4(),
+            title: "인사규정_2024.pdf".to_string(),
+        };
+        let (diffs, _) = compare_sections(
+            &[section("제2조", "연차휴가는 연 15일 부여한다")],
+            &[section("제2조", "연차휴가는 연 20일 부여한다")],
+            DEFAULT_MIN_SIMILARITY,
+        );
+
+        let (prompt, sources) = summary_prompt(template, &old_document, &new_document, &diffs);
+        assert_eq!(sources.len(), 2);
+        assert!(prompt.contains("[출처: 2]"));
+
+        let (summary, cited) =
+            cite_summary("연차휴가가 20일로 늘었다 [출처: 2] [출처: 7].", &sources);
+        assert_eq!(summary, "연차휴가가 20일로 늘었다 [출처: 1].");
+        assert_eq!(cited.len(), 1);
+        assert_eq!(cited[0].index, 1);
+        assert_eq!(cited[0].version, DocumentVersion::New);
+        assert_eq!(cited[0].document_id, new_document.id);
+    }
+}
@@ -0,0 +1,423 @@
+//! Content gap triage
+//!
+//! A question that retrieves nothing, or only weakly related passages,
+//! points at missing documentation. Such queries are logged to the
+//! `content_gaps` table with their keywords. The admin API groups open gaps
+//! into topics by keyword overlap, and admins mark gaps resolved once
+//! documents covering them have been ingested.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Default answer confidence below which retrieval counts as weak
+const DEFAULT_MIN_CONFIDENCE: f32 = 0.3;
+
+/// Default keyword overlap (Jaccard) for two gaps to share a topic
+pub const DEFAULT_CLUSTER_SIMILARITY: f32 = 0.4;
+
+/// Sample questions returned per topic
+const SAMPLE_QUESTIONS: usize = 5;
+
+/// Keywords used as the topic label
+const TOPIC_KEYWORDS: usize = 3;
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// When a query is logged as a content gap
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentGapPolicy {
+    /// Log queries at all
+    pub enabled: bool,
+    /// Answers below this confidence are logged as low-confidence gaps
+    pub min_confidence: f32,
+}
+
+impl Default for ContentGapPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+}
+
+impl ContentGapPolicy {
+    /// Policy from `CONTENT_GAP_MIN_CONFIDENCE` (a negative value logs only
+    /// queries without results) and `CONTENT_GAP_LOGGING` (`false` disables)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(value) = std::env::var("CONTENT_GAP_MIN_CONFIDENCE") {
+            match value.parse::<f32>() {
+                Ok(min) => policy.min_confidence = min,
+                Err(_) => tracing::warn!("Ignoring invalid CONTENT_GAP_MIN_CONFIDENCE: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("CONTENT_GAP_LOGGING") {
+            policy.enabled = !matches!(value.to_lowercase().as_str(), "false" | "0" | "off");
+        }
+        policy
+    }
+
+    /// Why an answer counts as a content gap, if it does
+    pub fn classify(&self, citations: usize, confidence: f32) -> Option<GapReason> {
+        if !self.enabled {
+            None
+        } else if citations == 0 {
+            Some(GapReason::NoResults)
+        } else if confidence < self.min_confidence {
+            Some(GapReason::LowConfidence)
+        } else {
+            None
+        }
+    }
+}
+
+/// Why a query was logged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapReason {
+    /// Retrieval returned no passages
+    NoResults,
+    /// Passages were found but the answer confidence was low
+    LowConfidence,
+}
+
+impl GapReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoResults => "no_results",
+            Self::LowConfidence => "low_confidence",
+        }
+    }
+}
+
+impl std::str::FromStr for GapReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "no_results" => Ok(Self::NoResults),
+            "low_confidence" => Ok(Self::LowConfidence),
+            other => Err(format!("Unknown gap reason: {other}")),
+        }
+    }
+}
+
+// ============================================================================
+// Records
+// ============================================================================
+
+/// A logged query
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentGap {
+    pub id: Uuid,
+    pub question: String,
+    pub keywords: Vec<String>,
+    pub reason: GapReason,
+    pub confidence: f32,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct GapRow {
+    id: Uuid,
+    question: String,
+    keywords: Vec<String>,
+    reason: String,
+    confidence: f32,
+    created_at: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+}
+
+impl From<GapRow> for ContentGap {
+    fn from(row: GapRow) -> Self {
+        Self {
+            id: row.id,
+            question: row.question,
+            keywords: row.keywords,
+            reason: row.reason.parse().unwrap_or(GapReason::LowConfidence),
+            confidence: row.confidence,
+            created_at: row.created_at,
+            resolved_at: row.resolved_at,
+        }
+    }
+}
+
+/// Log an answered query in the background if its retrieval was weak
+///
+/// Called by the REST, GraphQL and gRPC query paths. The response does not
+/// wait for the insert; failures are only logged.
+pub(crate) fn log_if_gap(
+    state: &Arc<AppState>,
+    question: &str,
+    citations: usize,
+    confidence: f32,
+    user_id: Uuid,
+) {
+    let Some(reason) = state.content_gaps.classify(citations, confidence) else {
+        return;
+    };
+    let keywords = state.analyzer.keywords(question);
+    let question = question.trim().to_string();
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        let result = sqlx::query(
+            "INSERT INTO content_gaps (question, keywords, reason, confidence, user_id)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&question)
+        .bind(&keywords)
+        .bind(reason.as_str())
+        .bind(confidence)
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to log content gap: {}", e);
+        }
+    });
+}
+
+/// Gaps, newest first
+pub(crate) async fn fetch(
+    pool: &sqlx::PgPool,
+    include_resolved: bool,
+    since: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<ContentGap>, sqlx::Error> {
+    let rows: Vec<GapRow> = sqlx::query_as(
+        "SELECT id, question, keywords, reason, confidence, created_at, resolved_at
+         FROM content_gaps
+         WHERE ($1 OR resolved_at IS NULL)
+           AND ($2::timestamptz IS NULL OR created_at >= $2)
+         ORDER BY created_at DESC
+         LIMIT $3",
+    )
+    .bind(include_resolved)
+    .bind(since)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(ContentGap::from).collect())
+}
+
+/// Mark gaps resolved, returning how many were still open
+pub(crate) async fn resolve(
+    pool: &sqlx::PgPool,
+    ids: &[Uuid],
+    resolved_by: Uuid,
+    document_ids: &[Uuid],
+    note: Option<&str>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE content_gaps
+         SET resolved_at = NOW(), resolved_by = $2, resolved_documents = $3, resolution_note = $4
+         WHERE id = ANY($1) AND resolved_at IS NULL",
+    )
+    .bind(ids)
+    .bind(resolved_by)
+    .bind(document_ids)
+    .bind(note)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// ============================================================================
+// Topic clustering
+// ============================================================================
+
+/// Gaps sharing a topic
+#[derive(Debug, Clone, Serialize)]
+pub struct GapCluster {
+    /// Most frequent keywords of the cluster
+    pub topic: String,
+    pub keywords: Vec<String>,
+    pub count: usize,
+    pub open: usize,
+    /// Gaps per reason
+    pub reasons: BTreeMap<GapReason, usize>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Most recent questions
+    pub sample_questions: Vec<String>,
+    /// All gaps of the cluster (pass to the resolve endpoint)
+    pub gap_ids: Vec<Uuid>,
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f32 / union as f32
+}
+
+/// Keyword set of a gap, falling back to the normalized question
+fn gap_terms(gap: &ContentGap) -> HashSet<String> {
+    if gap.keywords.is_empty() {
+        HashSet::from([gap.question.trim().to_lowercase()])
+    } else {
+        gap.keywords.iter().map(|k| k.to_lowercase()).collect()
+    }
+}
+
+/// Group items by keyword overlap
+///
+/// Each item joins the first group whose seed (the terms of its first
+/// item) overlaps at least `min_similarity`, otherwise it seeds a new
+/// group. Groups keep the input order.
+pub(crate) fn seed_clusters<T>(
+    items: &[T],
+    terms: impl Fn(&T) -> HashSet<String>,
+    min_similarity: f32,
+) -> Vec<Vec<&T>> {
+    let mut seeds: Vec<HashSet<String>> = Vec::new();
+    let mut members: Vec<Vec<&T>> = Vec::new();
+
+    for item in items {
+        let item_terms = terms(item);
+        match seeds
+            .iter()
+            .position(|seed| jaccard(seed, &item_terms) >= min_similarity)
+        {
+            Some(i) => members[i].push(item),
+            None => {
+                seeds.push(item_terms);
+                members.push(vec![item]);
+            }
+        }
+    }
+    members
+}
+
+/// Group gaps into topics by keyword overlap
+///
+/// See [`seed_clusters`]. Topics are ordered by size, then by most recent
+/// gap.
+pub fn cluster_gaps(gaps: &[ContentGap], min_similarity: f32) -> Vec<GapCluster> {
+    let mut clusters: Vec<GapCluster> = seed_clusters(gaps, gap_terms, min_similarity)
+        .into_iter()
+        .map(build_cluster)
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| b.last_seen.cmp(&a.last_seen))
+    });
+    clusters
+}
+
+fn build_cluster(mut gaps: Vec<&ContentGap>) -> GapCluster {
+    gaps.sort_by_key(|g| std::cmp::Reverse(g.created_at));
+
+    let mut frequency: BTreeMap<String, usize> = BTreeMap::new();
+    let mut reasons = BTreeMap::new();
+    for gap in &gaps {
+        for term in gap_terms(gap) {
+            *frequency.entry(term).or_default() += 1;
+        }
+        *reasons.entry(gap.reason).or_default() += 1;
+    }
+    let mut keywords: Vec<(String, usize)> = frequency.into_iter().collect();
+    keywords.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let keywords: Vec<String> = keywords.into_iter().map(|(k, _)| k).collect();
+
+    let mut seen = HashSet::new();
+    let sample_questions = gaps
+        .iter()
+        .filter(|g| seen.insert(g.question.to_lowercase()))
+        .take(SAMPLE_QUESTIONS)
+        .map(|g| g.question.clone())
+        .collect();
+
+    GapCluster {
+        topic: keywords
+            .iter()
+            .take(TOPIC_KEYWORDS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" "),
+        count: gaps.len(),
+        open: gaps.iter().filter(|g| g.resolved_at.is_none()).count(),
+        reasons,
+        first_seen: gaps.last().map(|g| g.created_at).unwrap_or_default(),
+        last_seen: gaps.first().map(|g| g.created_at).unwrap_or_default(),
+        sample_questions,
+        gap_ids: gaps.iter().map(|g| g.id).collect(),
+        keywords,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn gap(question: &str, keywords: &[&str], minutes_ago: i64) -> ContentGap {
+        ContentGap {
+            id: Uuid::new_v4(),
+            question: question.to_string(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            reason: GapReason::NoResults,
+            confidence: 0.0,
+            created_at: Utc::now() - Duration::minutes(minutes_ago),
+            resolved_at: None,
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let policy = ContentGapPolicy::default();
+        assert_eq!(policy.classify(0, 0.9), Some(GapReason::NoResults));
+        assert_eq!(policy.classify(3, 0.1), Some(GapReason::LowConfidence));
+        assert_eq!(policy.classify(3, 0.8), None);
+
+        let disabled = ContentGapPolicy {
+            enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(disabled.classify(0, 0.0), None);
+    }
+
+    #[test]
+    fn test_cluster_gaps_by_keyword_overlap() {
+        let gaps = vec![
+            gap("육아휴직 신청 방법", &["육아휴직", "신청"], 1),
+            gap("출장비 정산 기한", &["출장비", "정산"], 2),
+            gap("육아휴직 신청 서류", &["육아휴직", "신청", "서류"], 3),
+            gap("육아휴직 급여", &["육아휴직", "급여"], 4),
+        ];
+
+        let clusters = cluster_gaps(&gaps, 0.3);
+        assert_eq!(clusters.len(), 2);
+
+        let leave = &clusters[0];
+        assert_eq!(leave.count, 3);
+        assert_eq!(leave.keywords[0], "육아휴직");
+        assert!(leave.topic.starts_with("육아휴직 신청"));
+        assert_eq!(leave.sample_questions[0], "육아휴직 신청 방법");
+        assert_eq!(leave.reasons[&GapReason::NoResults], 3);
+        assert!(leave.first_seen < leave.last_seen);
+
+        assert_eq!(clusters[1].count, 1);
+        assert_eq!(clusters[1].topic, "정산 출장비");
+    }
+}
@@ -0,0 +1,410 @@
+//! Chunk-level content deduplication
+//!
+//! When a document's chunks are stored, each gets a SimHash fingerprint
+//! (see [`otl_core::dedup`]) and is linked to the first earlier chunk it
+//! near-duplicates, in the same document or in another one, through
+//! `document_chunks.canonical_chunk_id`. Links always point at a canonical
+//! chunk, never at another duplicate.
+//!
+//! A chunk is only linked to a chunk whose document every reader of its own
+//! document can read: a public document, or one with the same ACL.
+//! Restricted documents are never fingerprinted, so nothing about their
+//! content is compared.
+//!
+//! With `DEDUP_SKIP_EMBEDDING`, duplicates whose canonical chunk is embedded
+//! are not embedded themselves: retrieval finds the canonical chunk, and the
+//! duplicate keeps its document, position and link, so a citation can still
+//! name every document the text appears in. Deleting a document releases
+//! the duplicates of its chunks, which are then embedded in their own
+//! documents.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use crate::state::AppState;
+use otl_core::dedup;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// Whether and how strictly duplicate chunks are detected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DedupPolicy {
+    /// Fingerprint and link chunks when they are stored
+    pub enabled: bool,
+    /// Largest Hamming distance between near-duplicate fingerprints
+    pub max_distance: u32,
+    /// Shortest normalized chunk text that is fingerprinted
+    pub min_chars: usize,
+    /// Leave duplicates of embedded chunks out of the vector store
+    pub skip_embedding: bool,
+}
+
+impl Default for DedupPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_distance: dedup::DEFAULT_MAX_DISTANCE,
+            min_chars: dedup::DEFAULT_MIN_CHARS,
+            skip_embedding: false,
+        }
+    }
+}
+
+impl DedupPolicy {
+    /// Policy from `DEDUP_ENABLED`, `DEDUP_MAX_DISTANCE` (below 4),
+    /// `DEDUP_MIN_CHARS` and `DEDUP_SKIP_EMBEDDING`
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(enabled) = std::env::var("DEDUP_ENABLED") {
+            match enabled.parse::<bool>() {
+                Ok(enabled) => policy.enabled = enabled,
+                Err(_) => tracing::warn!("Ignoring invalid DEDUP_ENABLED: {}", enabled),
+            }
+        }
+        if let Ok(distance) = std::env::var("DEDUP_MAX_DISTANCE") {
+            match distance.parse::<u32>() {
+                Ok(distance) if (distance as usize) < dedup::BANDS => {
+                    policy.max_distance = distance
+                }
+                _ => tracing::warn!("Ignoring invalid DEDUP_MAX_DISTANCE: {}", distance),
+            }
+        }
+        if let Ok(chars) = std::env::var("DEDUP_MIN_CHARS") {
+            match chars.parse::<usize>() {
+                Ok(chars) if chars > 0 => policy.min_chars = chars,
+                _ => tracing::warn!("Ignoring invalid DEDUP_MIN_CHARS: {}", chars),
+            }
+        }
+        if let Ok(skip) = std::env::var("DEDUP_SKIP_EMBEDDING") {
+            match skip.parse::<bool>() {
+                Ok(skip) => policy.skip_embedding = skip,
+                Err(_) => tracing::warn!("Ignoring invalid DEDUP_SKIP_EMBEDDING: {}", skip),
+            }
+        }
+        policy
+    }
+}
+
+// ============================================================================
+// Linking
+// ============================================================================
+
+/// Outcome of linking the chunks of one document
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DedupReport {
+    /// Chunks long enough to fingerprint
+    pub fingerprinted: usize,
+    /// Chunks duplicating an earlier chunk of the same document
+    pub within_document: usize,
+    /// Chunks duplicating a chunk of another document
+    pub across_documents: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct DocumentScope {
+    access_level: String,
+    department: Option<String>,
+    owner_id: Option<String>,
+    required_roles: Vec<String>,
+    allowed_users: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ChunkRow {
+    id: Uuid,
+    chunk_index: i32,
+    content: String,
+}
+
+/// Canonical chunk candidate of another document
+#[derive(sqlx::FromRow)]
+struct Candidate {
+    /// Position of the chunk looking for a canonical copy
+    position: i32,
+    id: Uuid,
+    simhash: i64,
+}
+
+/// Canonical chunk of each chunk: `links[i]` is the position of an earlier
+/// chunk of the same document or the ID of another document's chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Link {
+    Earlier(usize),
+    Other(Uuid),
+}
+
+/// Resolve the links of a document's chunks to canonical chunk IDs
+///
+/// A chunk duplicating an earlier chunk that itself links elsewhere points
+/// at that chunk's canonical copy, so links are always one hop.
+pub fn resolve_links(ids: &[Uuid], links: &[Option<Link>]) -> Vec<Option<Uuid>> {
+    let mut canonical: Vec<Option<Uuid>> = Vec::with_capacity(links.len());
+    for link in links {
+        let resolved = match link {
+            None => None,
+            Some(Link::Other(id)) => Some(*id),
+            Some(Link::Earlier(i)) => Some(canonical[*i].unwrap_or(ids[*i])),
+        };
+        canonical.push(resolved);
+    }
+    canonical
+}
+
+/// Fingerprint the stored chunks of a document and link the duplicates to
+/// their canonical chunks
+///
+/// Logs instead of failing the ingestion.
+pub async fn link_chunks(state: &AppState, document_id: Uuid) -> DedupReport {
+    if !state.dedup.enabled {
+        return DedupReport::default();
+    }
+    match try_link_chunks(state, document_id).await {
+        Ok(report) => {
+            if report.within_document + report.across_documents > 0 {
+                tracing::info!("Document {document_id} duplicate chunks: {:?}", report);
+            }
+            report
+        }
+        Err(e) => {
+            tracing::warn!("Failed to deduplicate chunks of document {document_id}: {e:?}");
+            DedupReport::default()
+        }
+    }
+}
+
+async fn try_link_chunks(state: &AppState, document_id: Uuid) -> Result<DedupReport, AppError> {
+    let policy = &state.dedup;
+    let Some(scope): Option<DocumentScope> = sqlx::query_as(
+        "SELECT access_level::text, department, owner_id, \
+         COALESCE(required_roles, '{}') AS required_roles, \
+         COALESCE(allowed_users, '{}') AS allowed_users \
+         FROM documents WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(document_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?
+    else {
+        return Ok(DedupReport::default());
+    };
+    if scope.access_level == "restricted" {
+        return Ok(DedupReport::default());
+    }
+
+    let mut chunks: Vec<ChunkRow> = sqlx::query_as(
+        "SELECT id, chunk_index, content FROM document_chunks \
+         WHERE document_id = $1 ORDER BY chunk_index",
+    )
+    .bind(document_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch chunks: {e}")))?;
+    state
+        .keyring
+        .open_chunks(
+            &state.db_pool,
+            document_id,
+            chunks
+                .iter_mut()
+                .map(|c| (c.chunk_index.max(0) as u32, &mut c.content)),
+        )
+        .await?;
+
+    let fingerprints: Vec<Option<u64>> = chunks
+        .iter()
+        .map(|c| dedup::fingerprint(&c.content, policy.min_chars))
+        .collect();
+    let mut links: Vec<Option<Link>> = dedup::first_duplicates(&fingerprints, policy.max_distance)
+        .into_iter()
+        .map(|earlier| earlier.map(Link::Earlier))
+        .collect();
+    let mut report = DedupReport {
+        fingerprinted: fingerprints.iter().flatten().count(),
+        within_document: links.iter().flatten().count(),
+        across_documents: 0,
+    };
+
+    // Chunks that are first in their document look for a copy elsewhere
+    let lookups: Vec<(i32, u64)> = fingerprints
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| links[*i].is_none())
+        .filter_map(|(i, f)| Some((i as i32, (*f)?)))
+        .collect();
+    if !lookups.is_empty() {
+        for (position, id) in find_canonical(state, document_id, &scope, &lookups).await? {
+            links[position] = Some(Link::Other(id));
+            report.across_documents += 1;
+        }
+    }
+
+    let ids: Vec<Uuid> = chunks.iter().map(|c| c.id).collect();
+    let canonical = resolve_links(&ids, &links);
+    let stored: Vec<Option<i64>> = fingerprints
+        .iter()
+        .map(|f| f.map(dedup::to_stored))
+        .collect();
+    sqlx::query(
+        "UPDATE document_chunks SET simhash = u.simhash, canonical_chunk_id = u.canonical \
+         FROM UNNEST($1::uuid[], $2::bigint[], $3::uuid[]) AS u(id, simhash, canonical) \
+         WHERE document_chunks.id = u.id",
+    )
+    .bind(&ids)
+    .bind(&stored)
+    .bind(&canonical)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to link duplicate chunks: {e}")))?;
+    Ok(report)
+}
+
+/// Earliest canonical chunk of another document each fingerprint
+/// near-duplicates, as `(position, chunk ID)`
+async fn find_canonical(
+    state: &AppState,
+    document_id: Uuid,
+    scope: &DocumentScope,
+    lookups: &[(i32, u64)],
+) -> Result<Vec<(usize, Uuid)>, AppError> {
+    let positions: Vec<i32> = lookups.iter().map(|(p, _)| *p).collect();
+    let band = |b: usize| -> Vec<i32> {
+        lookups
+            .iter()
+            .map(|(_, f)| i32::from(dedup::bands(*f)[b]))
+            .collect()
+    };
+    let candidates: Vec<Candidate> = sqlx::query_as(
+        "SELECT q.position, c.id, c.simhash \
+         FROM UNNEST($2::int[], $3::int[], $4::int[], $5::int[], $6::int[]) \
+             AS q(position, b0, b1, b2, b3) \
+         JOIN document_chunks c ON ( \
+             ((c.simhash >> 48) & 65535) = q.b0 OR ((c.simhash >> 32) & 65535) = q.b1 \
+             OR ((c.simhash >> 16) & 65535) = q.b2 OR (c.simhash & 65535) = q.b3) \
+         JOIN documents d ON d.id = c.document_id \
+         WHERE c.document_id <> $1 AND c.canonical_chunk_id IS NULL AND NOT c.corrupted \
+         AND d.deleted_at IS NULL \
+         AND (d.access_level = 'public' OR (d.access_level::text = $7 \
+             AND ($7 = 'internal' OR (d.department IS NOT DISTINCT FROM $8 \
+                 AND d.owner_id IS NOT DISTINCT FROM $9 \
+                 AND COALESCE(d.required_roles, '{}') = $10 \
+                 AND COALESCE(d.allowed_users, '{}') = $11)))) \
+         ORDER BY q.position, d.created_at, c.chunk_index",
+    )
+    .bind(document_id)
+    .bind(&positions)
+    .bind(band(0))
+    .bind(band(1))
+    .bind(band(2))
+    .bind(band(3))
+    .bind(&scope.access_level)
+    .bind(&scope.department)
+    .bind(&scope.owner_id)
+    .bind(&scope.required_roles)
+    .bind(&scope.allowed_users)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to find duplicate chunks: {e}")))?;
+
+    let fingerprints: HashMap<i32, u64> = lookups.iter().copied().collect();
+    let mut found: Vec<(usize, Uuid)> = Vec::new();
+    for candidate in candidates {
+        if found
+            .last()
+            .is_some_and(|(p, _)| *p == candidate.position as usize)
+        {
+            continue;
+        }
+        let distance = dedup::hamming_distance(
+            fingerprints[&candidate.position],
+            dedup::from_stored(candidate.simhash),
+        );
+        if distance <= state.dedup.max_distance {
+            found.push((candidate.position as usize, candidate.id));
+        }
+    }
+    Ok(found)
+}
+
+/// Chunks of a document not to embed, by chunk index, with their canonical
+/// chunk
+///
+/// Empty unless duplicates skip embedding; only duplicates whose canonical
+/// chunk is embedded are skipped.
+pub async fn skipped_chunks(
+    state: &AppState,
+    document_id: Uuid,
+) -> Result<HashMap<u32, Uuid>, AppError> {
+    if !state.dedup.skip_embedding {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<(i32, Uuid)> = sqlx::query_as(
+        "SELECT c.chunk_index, c.canonical_chunk_id FROM document_chunks c \
+         JOIN document_chunks canonical ON canonical.id = c.canonical_chunk_id \
+         WHERE c.document_id = $1 AND COALESCE(canonical.vector_id, '') <> ''",
+    )
+    .bind(document_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch duplicate chunks: {e}")))?;
+    Ok(rows
+        .into_iter()
+        .map(|(index, canonical)| (index.max(0) as u32, canonical))
+        .collect())
+}
+
+/// Unlink the duplicates of a deleted document's chunks; returns the
+/// documents whose chunks were unlinked
+///
+/// The caller re-embeds those documents when duplicates skip embedding.
+pub async fn release(state: &AppState, document_id: Uuid) -> Result<Vec<Uuid>, AppError> {
+    sqlx::query_scalar(
+        "WITH released AS ( \
+             UPDATE document_chunks c SET canonical_chunk_id = NULL \
+             FROM document_chunks canonical \
+             WHERE canonical.id = c.canonical_chunk_id AND canonical.document_id = $1 \
+             AND c.document_id <> $1 \
+             RETURNING c.document_id) \
+         SELECT DISTINCT document_id FROM released",
+    )
+    .bind(document_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to release duplicate chunks: {e}")))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links_resolve_to_one_hop() {
+        let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        let elsewhere = Uuid::new_v4();
+        let links = [
+            Some(Link::Other(elsewhere)),
+            None,
+            Some(Link::Earlier(0)),
+            Some(Link::Earlier(1)),
+        ];
+        assert_eq!(
+            resolve_links(&ids, &links),
+            vec![Some(elsewhere), None, Some(elsewhere), Some(ids[1])]
+        );
+    }
+
+    #[test]
+    fn test_default_distance_is_found_by_band_lookup() {
+        let policy = DedupPolicy::default();
+        assert!((policy.max_distance as usize) < dedup::BANDS);
+        assert!(policy.enabled);
+        assert!(!policy.skip_embedding);
+    }
+}
@@ -0,0 +1,831 @@
+//! Embedding model migration
+//!
+//! Changing the embedding model makes every stored vector incomparable
+//! with new query embeddings. A migration re-embeds the active collection
+//! into a new one in the background while searches keep using the old
+//! index:
+//!
+//! 1. The target collection is created and attached as the backend's
+//!    shadow index, so documents indexed or deleted meanwhile reach both.
+//! 2. Every point of the active collection is re-embedded with the new
+//!    model and stored under the same ID and payload; progress is kept in
+//!    `embedding_migrations`.
+//! 3. Recent user queries are run against both indexes (shadow test); the
+//!    overlap of their results shows whether retrieval changed drastically.
+//! 4. The backend switches to the target in one step, either on request or
+//!    automatically when the shadow test passed. The old collection is kept
+//!    for rollback.
+//!
+//! The last switched migration decides the collection and model used at
+//! startup.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::{AppError, ErrorCode};
+use crate::notifications::{self, Audience, NotificationEvent, LINK_VAR};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use otl_core::config::AppConfig;
+use otl_core::SearchResult;
+use otl_rag::CachedEmbeddingClient;
+use otl_vector::embedding::create_embedding_client;
+use otl_vector::{EmbeddingClient, VectorIndex, VectorSearchBackend};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Default number of recent queries replayed by the shadow test
+const DEFAULT_SHADOW_QUERIES: i64 = 50;
+
+/// Default number of results compared per shadow query
+const DEFAULT_SHADOW_TOP_K: usize = 10;
+
+/// Default minimum mean result overlap for a migration to switch
+const DEFAULT_MIN_OVERLAP: f32 = 0.5;
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// How migrations are shadow-tested
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingMigrationPolicy {
+    /// Recent distinct queries replayed against both indexes
+    pub shadow_queries: i64,
+    /// Results compared per query
+    pub shadow_top_k: usize,
+    /// Mean overlap (0-1) the target needs to switch without `force`
+    pub min_overlap: f32,
+}
+
+impl Default for EmbeddingMigrationPolicy {
+    fn default() -> Self {
+        Self {
+            shadow_queries: DEFAULT_SHADOW_QUERIES,
+            shadow_top_k: DEFAULT_SHADOW_TOP_K,
+            min_overlap: DEFAULT_MIN_OVERLAP,
+        }
+    }
+}
+
+impl EmbeddingMigrationPolicy {
+    /// Policy from `EMBEDDING_MIGRATION_SHADOW_QUERIES`,
+    /// `EMBEDDING_MIGRATION_SHADOW_TOP_K` and `EMBEDDING_MIGRATION_MIN_OVERLAP`
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(value) = std::env::var("EMBEDDING_MIGRATION_SHADOW_QUERIES") {
+            match value.parse::<i64>() {
+                Ok(queries) if queries >= 0 => policy.shadow_queries = queries,
+                _ => tracing::warn!(
+                    "Ignoring invalid EMBEDDING_MIGRATION_SHADOW_QUERIES: {}",
+                    value
+                ),
+            }
+        }
+        if let Ok(value) = std::env::var("EMBEDDING_MIGRATION_SHADOW_TOP_K") {
+            match value.parse::<usize>() {
+                Ok(k) if k > 0 => policy.shadow_top_k = k,
+                _ => tracing::warn!(
+                    "Ignoring invalid EMBEDDING_MIGRATION_SHADOW_TOP_K: {}",
+                    value
+                ),
+            }
+        }
+        if let Ok(value) = std::env::var("EMBEDDING_MIGRATION_MIN_OVERLAP") {
+            match value.parse::<f32>() {
+                Ok(overlap) if (0.0..=1.0).contains(&overlap) => policy.min_overlap = overlap,
+                _ => tracing::warn!(
+                    "Ignoring invalid EMBEDDING_MIGRATION_MIN_OVERLAP: {}",
+                    value
+                ),
+            }
+        }
+        policy
+    }
+}
+
+// ============================================================================
+// Migrations
+// ============================================================================
+
+/// Stage of a migration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStatus {
+    /// Re-embedding the active collection
+    Running,
+    /// Replaying recent queries against both indexes
+    ShadowTesting,
+    /// Complete, waiting for the switch
+    Ready,
+    /// Serving searches
+    Switched,
+    /// Stopped by an error or a restart
+    Failed,
+}
+
+impl MigrationStatus {
+    /// Label stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::ShadowTesting => "shadow_testing",
+            Self::Ready => "ready",
+            Self::Switched => "switched",
+            Self::Failed => "failed",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "running" => Self::Running,
+            "shadow_testing" => Self::ShadowTesting,
+            "ready" => Self::Ready,
+            "switched" => Self::Switched,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// Retrieval of the target index compared with the active one
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, Serialize)]
+pub struct ShadowReport {
+    /// Queries replayed
+    pub queries: usize,
+    /// Results compared per query
+    pub top_k: usize,
+    /// Mean share of the active top-k also in the target top-k
+    pub mean_overlap: f32,
+    /// Share of queries whose first result is the same
+    pub top1_agreement: f32,
+    /// Mean best score of the active index
+    pub mean_active_score: f32,
+    /// Mean best score of the target index
+    pub mean_target_score: f32,
+    /// Whether `mean_overlap` reached the policy minimum
+    pub passed: bool,
+}
+
+/// Progress and outcome of a migration
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingMigration {
+    pub id: Uuid,
+    pub source_collection: String,
+    pub target_collection: String,
+    pub embedding_model: String,
+    pub dimension: i32,
+    pub status: MigrationStatus,
+    /// Points in the source collection when the migration started
+    pub total_points: i64,
+    pub embedded_points: i64,
+    /// Points without content or whose embedding failed
+    pub failed_points: i64,
+    pub auto_switch: bool,
+    pub shadow_report: Option<ShadowReport>,
+    pub error: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub switched_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct MigrationRow {
+    id: Uuid,
+    source_collection: String,
+    target_collection: String,
+    embedding_model: String,
+    dimension: i32,
+    status: String,
+    total_points: i64,
+    embedded_points: i64,
+    failed_points: i64,
+    auto_switch: bool,
+    shadow_report: Option<serde_json::Value>,
+    error: Option<String>,
+    created_by: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    switched_at: Option<DateTime<Utc>>,
+}
+
+impl From<MigrationRow> for EmbeddingMigration {
+    fn from(row: MigrationRow) -> Self {
+        Self {
+            id: row.id,
+            source_collection: row.source_collection,
+            target_collection: row.target_collection,
+            embedding_model: row.embedding_model,
+            dimension: row.dimension,
+            status: MigrationStatus::parse(&row.status),
+            total_points: row.total_points,
+            embedded_points: row.embedded_points,
+            failed_points: row.failed_points,
+            auto_switch: row.auto_switch,
+            shadow_report: row
+                .shadow_report
+                .and_then(|report| serde_json::from_value(report).ok()),
+            error: row.error,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            completed_at: row.completed_at,
+            switched_at: row.switched_at,
+        }
+    }
+}
+
+const MIGRATION_COLUMNS: &str = "id, source_collection, target_collection, embedding_model, \
+     dimension, status, total_points, embedded_points, failed_points, auto_switch, \
+     shadow_report, error, created_by, created_at, completed_at, switched_at";
+
+fn db_error(action: &str) -> impl Fn(sqlx::Error) -> AppError + '_ {
+    move |e| AppError::Database(format!("Failed to {action}: {e}"))
+}
+
+/// Migrations, newest first
+pub async fn list_migrations(pool: &sqlx::PgPool) -> Result<Vec<EmbeddingMigration>, AppError> {
+    let rows: Vec<MigrationRow> = sqlx::query_as(&format!(
+        "SELECT {MIGRATION_COLUMNS} FROM embedding_migrations ORDER BY created_at DESC"
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(db_error("fetch embedding migrations"))?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+/// One migration
+pub async fn get_migration(pool: &sqlx::PgPool, id: Uuid) -> Result<EmbeddingMigration, AppError> {
+    let row: Option<MigrationRow> = sqlx::query_as(&format!(
+        "SELECT {MIGRATION_COLUMNS} FROM embedding_migrations WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(db_error("fetch embedding migration"))?;
+    row.map(Into::into)
+        .ok_or_else(|| AppError::NotFound(format!("Embedding migration {id} not found")))
+}
+
+async fn set_status(
+    pool: &sqlx::PgPool,
+    id: Uuid,
+    status: MigrationStatus,
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE embedding_migrations SET status = $2 WHERE id = $1")
+        .bind(id)
+        .bind(status.as_str())
+        .execute(pool)
+        .await
+        .map_err(db_error("update embedding migration"))?;
+    Ok(())
+}
+
+async fn mark_failed(pool: &sqlx::PgPool, id: Uuid, error: &str) {
+    let result = sqlx::query(
+        "UPDATE embedding_migrations SET status = 'failed', error = $2, completed_at = NOW()
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(error)
+    .execute(pool)
+    .await;
+    if let Err(e) = result {
+        tracing::warn!("Cannot record failure of embedding migration {}: {}", id, e);
+    }
+}
+
+// ============================================================================
+// Start
+// ============================================================================
+
+/// Target of a new migration
+#[derive(Debug, Clone)]
+pub struct MigrationRequest {
+    pub embedding_model: String,
+    /// Target collection (derived from the active one and the model if unset)
+    pub collection: Option<String>,
+    /// Switch as soon as the shadow test passes
+    pub auto_switch: bool,
+}
+
+/// Collection name for `model` next to `active`
+fn target_collection_name(active: &str, model: &str) -> String {
+    let base = active.split("__").next().unwrap_or(active);
+    let slug: String = model
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{base}__{}", slug.trim_matches('_'))
+}
+
+/// Embedding client for `model`, cached apart from other models
+fn embedding_client_for(
+    state: &AppState,
+    model: &str,
+) -> Result<Arc<dyn EmbeddingClient>, AppError> {
+    let mut llm = state.config.llm.clone();
+    llm.embedding_model = model.to_string();
+    let client = create_embedding_client(&llm).map_err(|e| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            format!("Cannot create embedding client for {model}: {e}"),
+        )
+    })?;
+    Ok(Arc::new(
+        CachedEmbeddingClient::new(Arc::from(client), state.rag_cache.embedding.clone())
+            .with_namespace(model),
+    ))
+}
+
+async fn vector_backend(state: &AppState) -> Result<Arc<VectorSearchBackend>, AppError> {
+    state.vector_backend.read().await.clone().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Vector store not initialized".to_string(),
+        )
+    })
+}
+
+/// Create the target collection and start re-embedding in the background
+pub async fn start_migration(
+    state: Arc<AppState>,
+    request: MigrationRequest,
+    user_id: Uuid,
+) -> Result<EmbeddingMigration, AppError> {
+    let model = request.embedding_model.trim().to_string();
+    if model.is_empty() {
+        return Err(AppError::BadRequest(
+            "embedding_model must not be empty".to_string(),
+        ));
+    }
+    let Ok(running) = state.embedding_migration.clone().try_lock_owned() else {
+        return Err(AppError::BadRequest(
+            "An embedding migration is already running".to_string(),
+        ));
+    };
+
+    let backend = vector_backend(&state).await?;
+    let active = backend.active();
+    let collection = request
+        .collection
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| target_collection_name(active.collection(), &model));
+    if collection == active.collection() {
+        return Err(AppError::BadRequest(format!(
+            "{collection} is already the active collection"
+        )));
+    }
+
+    let client = embedding_client_for(&state, &model)?;
+    let target = Arc::new(VectorIndex::new(
+        active.store().with_collection(&collection),
+        client,
+    ));
+    target.init().await?;
+    let total = active.store().count().await?;
+
+    let row: MigrationRow = sqlx::query_as(&format!(
+        "INSERT INTO embedding_migrations
+             (source_collection, target_collection, embedding_model, dimension, status,
+              total_points, auto_switch, created_by)
+         VALUES ($1, $2, $3, $4, 'running', $5, $6, $7)
+         RETURNING {MIGRATION_COLUMNS}"
+    ))
+    .bind(active.collection())
+    .bind(&collection)
+    .bind(&model)
+    .bind(target.embedding_client().dimension() as i32)
+    .bind(total as i64)
+    .bind(request.auto_switch)
+    .bind(user_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(db_error("create embedding migration"))?;
+    let migration = EmbeddingMigration::from(row);
+
+    // Writes from now on reach both indexes
+    backend.set_shadow(Some(target.clone()));
+    tracing::info!(
+        "Re-embedding {} points of {} into {} with {}",
+        total,
+        active.collection(),
+        collection,
+        model
+    );
+
+    let id = migration.id;
+    tokio::spawn(async move {
+        let _running = running;
+        if let Err(e) = run_migration(&state, &backend, active, target, id).await {
+            tracing::warn!("Embedding migration {} failed: {:?}", id, e);
+            backend.set_shadow(None);
+            let error = format!("{e:?}");
+            mark_failed(&state.db_pool, id, &error).await;
+            notifications::notify(
+                &state,
+                NotificationEvent::JobFailed,
+                vec![
+                    ("job", "Embedding migration".to_string()),
+                    ("job_id", id.to_string()),
+                    ("error", error),
+                    (
+                        LINK_VAR,
+                        format!("/api/v1/admin/embeddings/migrations/{id}"),
+                    ),
+                ],
+                Audience::Users(vec![user_id]),
+            );
+        }
+    });
+    Ok(migration)
+}
+
+/// Re-embed, shadow-test and optionally switch
+async fn run_migration(
+    state: &Arc<AppState>,
+    backend: &VectorSearchBackend,
+    source: Arc<VectorIndex>,
+    target: Arc<VectorIndex>,
+    id: Uuid,
+) -> Result<(), AppError> {
+    let mut offset: Option<String> = None;
+    loop {
+        let page = target
+            .reembed_from(source.store(), offset.as_deref())
+            .await?;
+        sqlx::query(
+            "UPDATE embedding_migrations
+             SET embedded_points = embedded_points + $2, failed_points = failed_points + $3
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(page.embedded as i64)
+        .bind(page.failed as i64)
+        .execute(&state.db_pool)
+        .await
+        .map_err(db_error("update embedding migration"))?;
+
+        match page.next_offset {
+            Some(next) => offset = Some(next),
+            None => break,
+        }
+    }
+
+    set_status(&state.db_pool, id, MigrationStatus::ShadowTesting).await?;
+    let policy = &state.embedding_migration_policy;
+    let report = shadow_test(state, &source, &target, policy).await?;
+    tracing::info!("Shadow test of embedding migration {}: {:?}", id, report);
+
+    let report_json = serde_json::to_value(&report).unwrap_or_default();
+    let migration: MigrationRow = sqlx::query_as(&format!(
+        "UPDATE embedding_migrations
+         SET status = 'ready', shadow_report = $2, completed_at = NOW()
+         WHERE id = $1
+         RETURNING {MIGRATION_COLUMNS}"
+    ))
+    .bind(id)
+    .bind(report_json)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(db_error("update embedding migration"))?;
+
+    let migration = EmbeddingMigration::from(migration);
+    if migration.auto_switch {
+        if report.passed && migration.failed_points == 0 {
+            switch_to_target(state, backend, &migration, target).await?;
+        } else {
+            tracing::warn!(
+                "Embedding migration {} left for review (overlap {:.2}, {} failed points)",
+                id,
+                report.mean_overlap,
+                migration.failed_points
+            );
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Shadow test
+// ============================================================================
+
+/// Identity of a search result across indexes
+fn result_key(result: &SearchResult) -> (Uuid, &str) {
+    (result.source.document_id, result.content.as_str())
+}
+
+/// Share of `active`'s top `k` results present in `target`'s top `k`, and
+/// whether both rank the same result first
+fn compare_results(active: &[SearchResult], target: &[SearchResult], k: usize) -> (f32, bool) {
+    let active = &active[..active.len().min(k)];
+    let target = &target[..target.len().min(k)];
+    if active.is_empty() {
+        return (if target.is_empty() { 1.0 } else { 0.0 }, target.is_empty());
+    }
+    let target_keys: HashSet<_> = target.iter().map(result_key).collect();
+    let shared = active
+        .iter()
+        .filter(|r| target_keys.contains(&result_key(r)))
+        .count();
+    let same_top = target
+        .first()
+        .is_some_and(|t| result_key(t) == result_key(&active[0]));
+    (shared as f32 / active.len() as f32, same_top)
+}
+
+/// Summarize per-query comparisons
+fn summarize(
+    comparisons: &[(f32, bool, f32, f32)],
+    top_k: usize,
+    min_overlap: f32,
+) -> ShadowReport {
+    let queries = comparisons.len();
+    if queries == 0 {
+        // Nothing to compare against: do not block the switch
+        return ShadowReport {
+            top_k,
+            passed: true,
+            ..Default::default()
+        };
+    }
+    let n = queries as f32;
+    let mean_overlap = comparisons.iter().map(|c| c.0).sum::<f32>() / n;
+    ShadowReport {
+        queries,
+        top_k,
+        mean_overlap,
+        top1_agreement: comparisons.iter().filter(|c| c.1).count() as f32 / n,
+        mean_active_score: comparisons.iter().map(|c| c.2).sum::<f32>() / n,
+        mean_target_score: comparisons.iter().map(|c| c.3).sum::<f32>() / n,
+        passed: mean_overlap >= min_overlap,
+    }
+}
+
+/// Replay recent queries against both indexes
+async fn shadow_test(
+    state: &AppState,
+    active: &VectorIndex,
+    target: &VectorIndex,
+    policy: &EmbeddingMigrationPolicy,
+) -> Result<ShadowReport, AppError> {
+    let queries: Vec<String> = sqlx::query_scalar(
+        "SELECT query_text FROM query_stats
+         GROUP BY query_text
+         ORDER BY MAX(created_at) DESC
+         LIMIT $1",
+    )
+    .bind(policy.shadow_queries)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(db_error("fetch recent queries"))?;
+
+    let k = policy.shadow_top_k;
+    let mut comparisons = Vec::with_capacity(queries.len());
+    for query in &queries {
+        let active_results = active.search(query, k).await?;
+        let target_results = target.search(query, k).await?;
+        let (overlap, same_top) = compare_results(&active_results, &target_results, k);
+        let best = |results: &[SearchResult]| results.first().map(|r| r.score).unwrap_or(0.0);
+        comparisons.push((
+            overlap,
+            same_top,
+            best(&active_results),
+            best(&target_results),
+        ));
+    }
+    Ok(summarize(&comparisons, k, policy.min_overlap))
+}
+
+// ============================================================================
+// Switch
+// ============================================================================
+
+async fn switch_to_target(
+    state: &AppState,
+    backend: &VectorSearchBackend,
+    migration: &EmbeddingMigration,
+    target: Arc<VectorIndex>,
+) -> Result<(), AppError> {
+    let previous = backend.switch_to(target);
+    sqlx::query(
+        "UPDATE embedding_migrations SET status = 'switched', switched_at = NOW() WHERE id = $1",
+    )
+    .bind(migration.id)
+    .execute(&state.db_pool)
+    .await
+    .map_err(db_error("update embedding migration"))?;
+
+    // Results retrieved with the old index no longer match fresh searches
+    state.rag_cache.query.clear().await;
+    state.rag_cache.answer.clear().await;
+    tracing::info!(
+        "Vector search switched from {} to {} ({})",
+        previous.collection(),
+        migration.target_collection,
+        migration.embedding_model
+    );
+    Ok(())
+}
+
+/// Serve searches from a ready migration's collection
+///
+/// Without `force`, the shadow test must have passed and every point must
+/// have been re-embedded.
+pub async fn switch_migration(
+    state: &AppState,
+    id: Uuid,
+    force: bool,
+) -> Result<EmbeddingMigration, AppError> {
+    let migration = get_migration(&state.db_pool, id).await?;
+    if migration.status != MigrationStatus::Ready {
+        return Err(AppError::BadRequest(format!(
+            "Embedding migration is {}, not ready",
+            migration.status.as_str()
+        )));
+    }
+    if !force {
+        if migration.failed_points > 0 {
+            return Err(AppError::BadRequest(format!(
+                "{} points were not re-embedded; pass force to switch anyway",
+                migration.failed_points
+            )));
+        }
+        if let Some(report) = migration.shadow_report.as_ref().filter(|r| !r.passed) {
+            return Err(AppError::BadRequest(format!(
+                "Shadow test overlap {:.2} is below the minimum; pass force to switch anyway",
+                report.mean_overlap
+            )));
+        }
+    }
+
+    let backend = vector_backend(state).await?;
+    let target = match backend
+        .shadow()
+        .filter(|s| s.collection() == migration.target_collection)
+    {
+        Some(target) => target,
+        None => Arc::new(VectorIndex::new(
+            backend
+                .active()
+                .store()
+                .with_collection(&migration.target_collection),
+            embedding_client_for(state, &migration.embedding_model)?,
+        )),
+    };
+    switch_to_target(state, &backend, &migration, target).await?;
+    get_migration(&state.db_pool, id).await
+}
+
+// ============================================================================
+// Startup
+// ============================================================================
+
+/// Point `config` at the collection and model of the last switched
+/// migration
+///
+/// Migrations interrupted by the restart are marked failed. Returns the
+/// model name when a migration applied, so its embeddings are cached apart.
+pub async fn apply_active_index(pool: &sqlx::PgPool, config: &mut AppConfig) -> Option<String> {
+    let interrupted = sqlx::query(
+        "UPDATE embedding_migrations
+         SET status = 'failed', error = 'Interrupted by a server restart', completed_at = NOW()
+         WHERE status IN ('running', 'shadow_testing')",
+    )
+    .execute(pool)
+    .await;
+    match interrupted {
+        Ok(result) if result.rows_affected() > 0 => tracing::warn!(
+            "{} embedding migrations were interrupted and must be restarted",
+            result.rows_affected()
+        ),
+        Ok(_) => {}
+        Err(e) => {
+            tracing::warn!("Cannot read embedding migrations: {}", e);
+            return None;
+        }
+    }
+
+    let active: Option<(String, String)> = sqlx::query_as(
+        "SELECT target_collection, embedding_model FROM embedding_migrations
+         WHERE status = 'switched'
+         ORDER BY switched_at DESC
+         LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+    let (collection, model) = active?;
+    tracing::info!("Using vector collection {} ({})", collection, model);
+    config.database.qdrant_collection = collection;
+    config.llm.embedding_model = model.clone();
+    Some(model)
+}
+
+/// Re-attach the target of a ready migration as the shadow index, so
+/// writes after a restart reach it before the switch
+pub async fn resume_shadow(state: &AppState) {
+    let ready: Option<(String, String)> = sqlx::query_as(
+        "SELECT target_collection, embedding_model FROM embedding_migrations
+         WHERE status = 'ready'
+         ORDER BY completed_at DESC
+         LIMIT 1",
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .ok()
+    .flatten();
+    let (Some((collection, model)), Some(backend)) =
+        (ready, state.vector_backend.read().await.clone())
+    else {
+        return;
+    };
+    match embedding_client_for(state, &model) {
+        Ok(client) => {
+            let store = backend.active().store().with_collection(&collection);
+            backend.set_shadow(Some(Arc::new(VectorIndex::new(store, client))));
+            tracing::info!("Keeping {} in sync until it is switched to", collection);
+        }
+        Err(e) => tracing::warn!("Cannot resume shadow index {}: {:?}", collection, e),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{SearchResultType, SourceReference};
+
+    fn result(document_id: Uuid, content: &str, score: f32) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score,
+            source: SourceReference::new(document_id),
+            acl: Default::default(),
+            result_type: SearchResultType::Vector,
+        }
+    }
+
+    #[test]
+    fn test_target_collection_name() {
+        assert_eq!(
+            target_collection_name("otl_chunks", "text-embedding-3-large"),
+            "otl_chunks__text_embedding_3_large"
+        );
+        // Migrating again starts from the base name
+        assert_eq!(
+            target_collection_name("otl_chunks__text_embedding_3_large", "bge-m3"),
+            "otl_chunks__bge_m3"
+        );
+    }
+
+    #[test]
+    fn test_compare_results() {
+        let doc = Uuid::new_v4();
+        let active = vec![
+            result(doc, "연차 신청", 0.9),
+            result(doc, "병가 신청", 0.8),
+            result(doc, "출장 정산", 0.7),
+            result(doc, "경조사 휴가", 0.6),
+        ];
+        let target = vec![
+            result(doc, "연차 신청", 0.7),
+            result(doc, "출장 정산", 0.6),
+            result(doc, "보안 서약", 0.5),
+        ];
+
+        let (overlap, same_top) = compare_results(&active, &target, 4);
+        assert_eq!(overlap, 0.5);
+        assert!(same_top);
+
+        let (overlap, same_top) = compare_results(&active, &target[1..], 3);
+        assert!((overlap - 1.0 / 3.0).abs() < 1e-6);
+        assert!(!same_top);
+
+        assert_eq!(compare_results(&[], &[], 10), (1.0, true));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let report = summarize(&[(1.0, true, 0.9, 0.8), (0.2, false, 0.7, 0.6)], 10, 0.5);
+        assert_eq!(report.queries, 2);
+        assert!((report.mean_overlap - 0.6).abs() < 1e-6);
+        assert_eq!(report.top1_agreement, 0.5);
+        assert!((report.mean_target_score - 0.7).abs() < 1e-6);
+        assert!(report.passed);
+
+        assert!(!summarize(&[(0.2, false, 0.7, 0.6)], 10, 0.5).passed);
+        assert!(summarize(&[], 10, 0.5).passed);
+    }
+}
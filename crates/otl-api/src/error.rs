@@ -1,7 +1,13 @@
 //! API error handling
 //!
+//! Every error response has the same body: a stable [`ErrorCode`] that
+//! clients can branch on, a human-readable message, optional details and the
+//! request ID of the failed request (also sent as the `x-request-id` header),
+//! which ties the response to the server logs.
+//!
 //! Author: hephaex@gmail.com
 
+use crate::middleware::request_id::current_request_id;
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -10,24 +16,156 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+/// Machine-readable error codes
+///
+/// Codes are part of the API contract: new codes may be added, existing
+/// ones are never renamed or given a different HTTP status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// Malformed or invalid request (400)
+    BadRequest,
+    /// Missing or invalid credentials (401)
+    Unauthorized,
+    /// Expired, revoked or malformed access token (401)
+    InvalidToken,
+    /// The user's role does not allow the operation (403)
+    Forbidden,
+    /// The document's access control list denies the user (403)
+    AclDenied,
+    /// Resource does not exist (404)
+    NotFound,
+    /// The extraction is locked to another reviewer (409)
+    ReviewLocked,
+    /// The change would leave no active admin (409)
+    LastAdmin,
+    /// A deleted document is past its retention period and can no longer
+    /// be restored (410)
+    RestoreWindowExpired,
+    /// A signed document link is past its expiry (410)
+    ShareLinkExpired,
+    /// Uploaded document exceeds the size limit (413)
+    DocTooLarge,
+    /// Uploaded document cannot be read as its declared file type (422)
+    DocUnreadable,
+    /// The language model did not answer in time (504)
+    LlmTimeout,
+    /// The language model request failed (502)
+    LlmUnavailable,
+    /// The vector, keyword or graph search failed (502)
+    SearchFailed,
+    /// A document needs OCR but no OCR engine is installed (503)
+    OcrEngineMissing,
+    /// A required component (RAG pipeline, vector store, graph database) is
+    /// not initialized (503)
+    ServiceUnavailable,
+    /// Database operation failed (500)
+    DatabaseError,
+    /// Server misconfiguration (500)
+    ConfigError,
+    /// Unexpected server error (500)
+    InternalError,
+}
+
+impl ErrorCode {
+    /// All codes, in documentation order
+    pub const ALL: [ErrorCode; 20] = [
+        Self::BadRequest,
+        Self::Unauthorized,
+        Self::InvalidToken,
+        Self::Forbidden,
+        Self::AclDenied,
+        Self::NotFound,
+        Self::ReviewLocked,
+        Self::LastAdmin,
+        Self::RestoreWindowExpired,
+        Self::ShareLinkExpired,
+        Self::DocTooLarge,
+        Self::DocUnreadable,
+        Self::LlmTimeout,
+        Self::LlmUnavailable,
+        Self::SearchFailed,
+        Self::OcrEngineMissing,
+        Self::ServiceUnavailable,
+        Self::DatabaseError,
+        Self::ConfigError,
+        Self::InternalError,
+    ];
+
+    /// HTTP status returned with this code
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BadRequest => StatusCode::BAD_REQUEST,
+            Self::Unauthorized | Self::InvalidToken => StatusCode::UNAUTHORIZED,
+            Self::Forbidden | Self::AclDenied => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::ReviewLocked | Self::LastAdmin => StatusCode::CONFLICT,
+            Self::RestoreWindowExpired | Self::ShareLinkExpired => StatusCode::GONE,
+            Self::DocTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::DocUnreadable => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::LlmTimeout => StatusCode::GATEWAY_TIMEOUT,
+            Self::LlmUnavailable | Self::SearchFailed => StatusCode::BAD_GATEWAY,
+            Self::OcrEngineMissing | Self::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Self::DatabaseError | Self::ConfigError | Self::InternalError => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    /// Wire name of the code (e.g. `DOC_TOO_LARGE`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BadRequest => "BAD_REQUEST",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::InvalidToken => "INVALID_TOKEN",
+            Self::Forbidden => "FORBIDDEN",
+            Self::AclDenied => "ACL_DENIED",
+            Self::NotFound => "NOT_FOUND",
+            Self::ReviewLocked => "REVIEW_LOCKED",
+            Self::LastAdmin => "LAST_ADMIN",
+            Self::RestoreWindowExpired => "RESTORE_WINDOW_EXPIRED",
+            Self::ShareLinkExpired => "SHARE_LINK_EXPIRED",
+            Self::DocTooLarge => "DOC_TOO_LARGE",
+            Self::DocUnreadable => "DOC_UNREADABLE",
+            Self::LlmTimeout => "LLM_TIMEOUT",
+            Self::LlmUnavailable => "LLM_UNAVAILABLE",
+            Self::SearchFailed => "SEARCH_FAILED",
+            Self::OcrEngineMissing => "OCR_ENGINE_MISSING",
+            Self::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            Self::DatabaseError => "DATABASE_ERROR",
+            Self::ConfigError => "CONFIG_ERROR",
+            Self::InternalError => "INTERNAL_ERROR",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// API error response
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ApiError {
     /// Error code
-    pub code: String,
+    pub code: ErrorCode,
     /// Human-readable message
     pub message: String,
     /// Additional details
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
+    /// ID of the failed request, for correlating with server logs
+    pub request_id: String,
 }
 
 impl ApiError {
-    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
         Self {
-            code: code.into(),
+            code,
             message: message.into(),
             details: None,
+            request_id: current_request_id(),
         }
     }
 
@@ -37,23 +175,39 @@ impl ApiError {
     }
 
     pub fn not_found(resource: &str) -> Self {
-        Self::new("NOT_FOUND", format!("{resource} not found"))
+        Self::new(ErrorCode::NotFound, format!("{resource} not found"))
     }
 
     pub fn bad_request(message: impl Into<String>) -> Self {
-        Self::new("BAD_REQUEST", message)
+        Self::new(ErrorCode::BadRequest, message)
     }
 
     pub fn unauthorized() -> Self {
-        Self::new("UNAUTHORIZED", "Authentication required")
+        Self::new(ErrorCode::Unauthorized, "Authentication required")
     }
 
     pub fn forbidden() -> Self {
-        Self::new("FORBIDDEN", "Access denied")
+        Self::new(ErrorCode::Forbidden, "Access denied")
     }
 
     pub fn internal_error() -> Self {
-        Self::new("INTERNAL_ERROR", "Internal server error")
+        Self::new(ErrorCode::InternalError, "Internal server error")
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.code.status();
+        if status.is_server_error() {
+            tracing::error!(
+                request_id = %self.request_id,
+                code = %self.code,
+                "{}: {}",
+                self.message,
+                self.details.as_deref().unwrap_or_default()
+            );
+        }
+        (status, Json(self)).into_response()
     }
 }
 
@@ -66,26 +220,46 @@ pub enum AppError {
     Forbidden(String),
     Internal(String),
     Database(String),
+    /// Error with a specific code
+    Coded(ErrorCode, String),
+}
+
+impl AppError {
+    /// Error with a specific code and message
+    pub fn coded(code: ErrorCode, message: impl Into<String>) -> Self {
+        AppError::Coded(code, message.into())
+    }
+
+    /// Code reported for this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::BadRequest(_) => ErrorCode::BadRequest,
+            AppError::Unauthorized => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::Internal(_) => ErrorCode::InternalError,
+            AppError::Database(_) => ErrorCode::DatabaseError,
+            AppError::Coded(code, _) => *code,
+        }
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error) = match self {
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, ApiError::not_found(&msg)),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, ApiError::bad_request(msg)),
-            AppError::Unauthorized => (StatusCode::UNAUTHORIZED, ApiError::unauthorized()),
-            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, ApiError::new("FORBIDDEN", msg)),
-            AppError::Internal(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ApiError::internal_error().with_details(msg),
-            ),
-            AppError::Database(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ApiError::new("DATABASE_ERROR", "Database operation failed").with_details(msg),
-            ),
+        let code = self.code();
+        let error = match self {
+            AppError::NotFound(msg) => ApiError::not_found(&msg),
+            AppError::BadRequest(msg) => ApiError::bad_request(msg),
+            AppError::Unauthorized => ApiError::unauthorized(),
+            AppError::Forbidden(msg) => ApiError::new(code, msg),
+            AppError::Internal(msg) => ApiError::internal_error().with_details(msg),
+            AppError::Database(msg) => {
+                ApiError::new(code, "Database operation failed").with_details(msg)
+            }
+            AppError::Coded(code, msg) => ApiError::new(code, msg),
         };
 
-        (status, Json(error)).into_response()
+        error.into_response()
     }
 }
 
@@ -101,16 +275,90 @@ impl From<otl_core::OtlError> for AppError {
 
         match err {
             OtlError::NotFound(msg) => AppError::NotFound(msg),
-            OtlError::AccessDenied { reason } => AppError::Forbidden(reason),
+            OtlError::AccessDenied { reason } => AppError::coded(ErrorCode::AclDenied, reason),
             OtlError::InvalidOntology(msg) => {
                 AppError::BadRequest(format!("Invalid ontology: {msg}"))
             }
             OtlError::ValidationError(msg) => AppError::BadRequest(msg),
             OtlError::DatabaseError(msg) => AppError::Database(msg),
-            OtlError::SearchError(msg) => AppError::Internal(format!("Search error: {msg}")),
-            OtlError::LlmError(msg) => AppError::Internal(format!("LLM error: {msg}")),
-            OtlError::ConfigError(msg) => AppError::Internal(format!("Configuration error: {msg}")),
+            OtlError::SearchError(msg) => {
+                AppError::coded(ErrorCode::SearchFailed, format!("Search error: {msg}"))
+            }
+            OtlError::LlmError(msg) if is_timeout(&msg) => {
+                AppError::coded(ErrorCode::LlmTimeout, format!("LLM timed out: {msg}"))
+            }
+            OtlError::LlmError(msg) => {
+                AppError::coded(ErrorCode::LlmUnavailable, format!("LLM error: {msg}"))
+            }
+            OtlError::ConfigError(msg) => AppError::coded(
+                ErrorCode::ConfigError,
+                format!("Configuration error: {msg}"),
+            ),
+            OtlError::EncryptionError(msg) => {
+                AppError::Internal(format!("Encryption error: {msg}"))
+            }
             OtlError::Other(err) => AppError::Internal(err.to_string()),
         }
     }
 }
+
+/// Whether an LLM client error reports a timeout (reqwest: "operation timed
+/// out")
+fn is_timeout(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("timed out") || message.contains("timeout")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_serialize_to_their_wire_names() {
+        for code in ErrorCode::ALL {
+            assert_eq!(
+                serde_json::to_value(code).unwrap(),
+                serde_json::json!(code.as_str())
+            );
+        }
+        assert_eq!(ErrorCode::DocTooLarge.as_str(), "DOC_TOO_LARGE");
+        assert_eq!(
+            ErrorCode::DocTooLarge.status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(ErrorCode::AclDenied.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_core_errors_map_to_specific_codes() {
+        use otl_core::OtlError;
+
+        let timeout = AppError::from(OtlError::LlmError(
+            "Request failed: operation timed out".to_string(),
+        ));
+        assert_eq!(timeout.code(), ErrorCode::LlmTimeout);
+        let failed = AppError::from(OtlError::LlmError("OpenAI error: 500".to_string()));
+        assert_eq!(failed.code(), ErrorCode::LlmUnavailable);
+        let denied = AppError::from(OtlError::AccessDenied {
+            reason: "department".to_string(),
+        });
+        assert_eq!(denied.code(), ErrorCode::AclDenied);
+    }
+
+    #[tokio::test]
+    async fn test_error_body_carries_code_and_request_id() {
+        let response = AppError::coded(ErrorCode::DocTooLarge, "too large").into_response();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: ApiError = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.code, ErrorCode::DocTooLarge);
+        assert!(!error.request_id.is_empty());
+    }
+}
@@ -0,0 +1,494 @@
+//! Document data export
+//!
+//! Builds a zip bundle with the processed artifacts of one document: its
+//! chunks, graph entities, triples (with provenance) and chunk embeddings,
+//! one JSON object per line, plus a `manifest.json` describing the bundle.
+//! Large documents are exported by background jobs kept in [`ExportJobs`].
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::{AppError, ErrorCode};
+use crate::notifications::{self, Audience, NotificationEvent, LINK_VAR};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Documents with more chunks than this are exported by a background job
+pub const SYNC_EXPORT_MAX_CHUNKS: i64 = 1000;
+
+/// How long a finished export job and its bundle are kept
+const JOB_TTL: Duration = Duration::from_secs(3600);
+
+// ============================================================================
+// Bundle contents
+// ============================================================================
+
+/// Artifact included in an export bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportPart {
+    Chunks,
+    Entities,
+    Triples,
+    Embeddings,
+}
+
+impl ExportPart {
+    pub const ALL: [ExportPart; 4] = [
+        Self::Chunks,
+        Self::Entities,
+        Self::Triples,
+        Self::Embeddings,
+    ];
+
+    /// Parse a comma-separated `include` list; `None` or empty selects all
+    pub fn parse_list(include: Option<&str>) -> Result<Vec<ExportPart>, AppError> {
+        let Some(include) = include.filter(|s| !s.trim().is_empty()) else {
+            return Ok(Self::ALL.to_vec());
+        };
+
+        let mut parts = Vec::new();
+        for name in include.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let part = match name {
+                "chunks" => Self::Chunks,
+                "entities" => Self::Entities,
+                "triples" => Self::Triples,
+                "embeddings" => Self::Embeddings,
+                other => {
+                    return Err(AppError::BadRequest(format!(
+                        "Unknown export part '{other}' (expected chunks, entities, triples or embeddings)"
+                    )))
+                }
+            };
+            if !parts.contains(&part) {
+                parts.push(part);
+            }
+        }
+        parts.sort();
+        Ok(parts)
+    }
+
+    /// File name of the part inside the bundle
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::Chunks => "chunks.jsonl",
+            Self::Entities => "entities.jsonl",
+            Self::Triples => "triples.jsonl",
+            Self::Embeddings => "embeddings.jsonl",
+        }
+    }
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub document_id: Uuid,
+    pub exported_at: DateTime<Utc>,
+    /// Lines per file
+    pub files: BTreeMap<String, usize>,
+    /// Model that produced the embeddings, if exported
+    pub embedding_model: Option<String>,
+}
+
+/// Chunk line of `chunks.jsonl`
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct ChunkLine {
+    id: Uuid,
+    chunk_index: i32,
+    content: String,
+    content_hash: Option<String>,
+    page_number: Option<i32>,
+    section_name: Option<String>,
+    start_offset: Option<i32>,
+    end_offset: Option<i32>,
+    vector_id: Option<String>,
+}
+
+/// Line of `triples.jsonl`: an edge with the evidence behind it
+#[derive(Debug, Serialize)]
+struct TripleLine {
+    #[serde(flatten)]
+    edge: otl_graph::snapshot::EdgeSnapshot,
+    provenance: Vec<otl_graph::snapshot::ProvenanceSnapshot>,
+}
+
+/// Line of `embeddings.jsonl`
+#[derive(Debug, Serialize)]
+struct EmbeddingLine {
+    vector_id: String,
+    chunk_index: Option<u32>,
+    dimension: usize,
+    vector: Vec<f32>,
+}
+
+/// Serialize records as JSON lines
+fn to_jsonl<T: Serialize>(records: &[T]) -> Result<Vec<u8>, AppError> {
+    let mut out = Vec::new();
+    for record in records {
+        serde_json::to_writer(&mut out, record)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize export: {e}")))?;
+        out.push(b'\n');
+    }
+    Ok(out)
+}
+
+/// Zip the manifest and part files
+pub fn write_bundle(
+    manifest: &ExportManifest,
+    files: &[(&str, Vec<u8>)],
+) -> Result<Vec<u8>, AppError> {
+    let zip_error = |e: zip::result::ZipError| AppError::Internal(format!("Failed to zip: {e}"));
+    let io_error = |e: std::io::Error| AppError::Internal(format!("Failed to zip: {e}"));
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("manifest.json", options)
+        .map_err(zip_error)?;
+    let manifest = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize manifest: {e}")))?;
+    zip.write_all(&manifest).map_err(io_error)?;
+
+    for (name, content) in files {
+        zip.start_file(*name, options).map_err(zip_error)?;
+        zip.write_all(content).map_err(io_error)?;
+    }
+
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+/// Build the export bundle of a document
+///
+/// Access must have been checked by the caller.
+pub async fn build_bundle(
+    state: &AppState,
+    document_id: Uuid,
+    parts: &[ExportPart],
+) -> Result<Vec<u8>, AppError> {
+    let mut files: Vec<(&str, Vec<u8>)> = Vec::new();
+    let mut counts = BTreeMap::new();
+    let mut embedding_model = None;
+
+    let needs_graph = parts.contains(&ExportPart::Entities) || parts.contains(&ExportPart::Triples);
+    let graph = if needs_graph {
+        let graph_db = state.graph_db.read().await.clone().ok_or_else(|| {
+            AppError::coded(
+                ErrorCode::ServiceUnavailable,
+                "Graph database not initialized",
+            )
+        })?;
+        Some(graph_db.document_snapshot(document_id).await?)
+    } else {
+        None
+    };
+
+    for part in parts {
+        let (lines, content) = match part {
+            ExportPart::Chunks => {
+                let mut chunks: Vec<ChunkLine> = sqlx::query_as(
+                    "SELECT id, chunk_index, content, content_hash, page_number, section_name,
+                            start_offset, end_offset, vector_id
+                     FROM document_chunks WHERE document_id = $1 ORDER BY chunk_index",
+                )
+                .bind(document_id)
+                .fetch_all(&state.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to fetch chunks: {e}")))?;
+                state
+                    .keyring
+                    .open_chunks(
+                        &state.db_pool,
+                        document_id,
+                        chunks
+                            .iter_mut()
+                            .map(|c| (c.chunk_index.max(0) as u32, &mut c.content)),
+                    )
+                    .await?;
+                (chunks.len(), to_jsonl(&chunks)?)
+            }
+            ExportPart::Entities => {
+                let entities = graph.as_ref().map(|g| g.entities.as_slice()).unwrap_or(&[]);
+                (entities.len(), to_jsonl(entities)?)
+            }
+            ExportPart::Triples => {
+                let triples = graph.as_ref().map(triple_lines).unwrap_or_default();
+                (triples.len(), to_jsonl(&triples)?)
+            }
+            ExportPart::Embeddings => {
+                let backend = state.vector_backend.read().await.clone().ok_or_else(|| {
+                    AppError::coded(
+                        ErrorCode::ServiceUnavailable,
+                        "Vector store not initialized",
+                    )
+                })?;
+                let mut vectors = backend.document_vectors(document_id).await?;
+                vectors.sort_by_key(|v| v.chunk_index);
+                let lines: Vec<EmbeddingLine> = vectors
+                    .into_iter()
+                    .map(|v| EmbeddingLine {
+                        vector_id: v.vector_id,
+                        chunk_index: v.chunk_index,
+                        dimension: v.vector.len(),
+                        vector: v.vector,
+                    })
+                    .collect();
+                embedding_model = Some(state.config.llm.embedding_model.clone());
+                (lines.len(), to_jsonl(&lines)?)
+            }
+        };
+        counts.insert(part.file_name().to_string(), lines);
+        files.push((part.file_name(), content));
+    }
+
+    let manifest = ExportManifest {
+        document_id,
+        exported_at: Utc::now(),
+        files: counts,
+        embedding_model,
+    };
+    write_bundle(&manifest, &files)
+}
+
+/// Group provenance records under their triples
+fn triple_lines(snapshot: &otl_graph::snapshot::GraphSnapshot) -> Vec<TripleLine> {
+    let mut provenance: HashMap<&str, Vec<_>> = HashMap::new();
+    for record in &snapshot.provenance {
+        provenance
+            .entry(record.triple_id.as_str())
+            .or_default()
+            .push(record.clone());
+    }
+    snapshot
+        .edges
+        .iter()
+        .map(|edge| TripleLine {
+            edge: edge.clone(),
+            provenance: edge
+                .triple_id
+                .as_deref()
+                .and_then(|id| provenance.get(id).cloned())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+// ============================================================================
+// Background jobs
+// ============================================================================
+
+/// State of an export job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportJobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Export job as reported to clients
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ExportJobInfo {
+    pub job_id: Uuid,
+    pub document_id: Uuid,
+    pub status: ExportJobStatus,
+    pub parts: Vec<ExportPart>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Where to download the bundle once completed
+    pub download_url: Option<String>,
+    /// Failure reason
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+struct ExportJob {
+    info: ExportJobInfo,
+    /// User who started the job; only they can read it
+    owner: Uuid,
+    bundle: Option<PathBuf>,
+}
+
+/// Export jobs of this API instance
+#[derive(Debug, Default)]
+pub struct ExportJobs {
+    jobs: RwLock<HashMap<Uuid, ExportJob>>,
+}
+
+impl ExportJobs {
+    /// Start exporting a document in the background
+    pub async fn start(
+        self: &Arc<Self>,
+        state: Arc<AppState>,
+        owner: Uuid,
+        document_id: Uuid,
+        parts: Vec<ExportPart>,
+    ) -> ExportJobInfo {
+        self.remove_expired().await;
+
+        let info = ExportJobInfo {
+            job_id: Uuid::new_v4(),
+            document_id,
+            status: ExportJobStatus::Running,
+            parts: parts.clone(),
+            created_at: Utc::now(),
+            finished_at: None,
+            download_url: None,
+            error: None,
+        };
+        let job_id = info.job_id;
+        self.jobs.write().await.insert(
+            job_id,
+            ExportJob {
+                info: info.clone(),
+                owner,
+                bundle: None,
+            },
+        );
+
+        let jobs = self.clone();
+        tokio::spawn(async move {
+            let result = match build_bundle(&state, document_id, &parts).await {
+                Ok(bundle) => {
+                    let path = std::env::temp_dir().join(format!("otl-export-{job_id}.zip"));
+                    tokio::fs::write(&path, bundle)
+                        .await
+                        .map(|()| path)
+                        .map_err(|e| format!("Failed to store bundle: {e}"))
+                }
+                Err(e) => Err(format!("{e:?}")),
+            };
+            if let Err(error) = &result {
+                notifications::notify(
+                    &state,
+                    NotificationEvent::JobFailed,
+                    vec![
+                        ("job", "Document export".to_string()),
+                        ("job_id", job_id.to_string()),
+                        ("error", error.clone()),
+                        (LINK_VAR, format!("/api/v1/exports/{job_id}")),
+                    ],
+                    Audience::Users(vec![owner]),
+                );
+            }
+            jobs.finish(job_id, result).await;
+        });
+
+        info
+    }
+
+    async fn finish(&self, job_id: Uuid, result: Result<PathBuf, String>) {
+        let mut jobs = self.jobs.write().await;
+        let Some(job) = jobs.get_mut(&job_id) else {
+            return;
+        };
+        job.info.finished_at = Some(Utc::now());
+        match result {
+            Ok(path) => {
+                job.info.status = ExportJobStatus::Completed;
+                job.info.download_url = Some(format!("/api/v1/exports/{job_id}/download"));
+                job.bundle = Some(path);
+            }
+            Err(error) => {
+                tracing::warn!("Export job {job_id} failed: {error}");
+                job.info.status = ExportJobStatus::Failed;
+                job.info.error = Some(error);
+            }
+        }
+    }
+
+    /// Job info, if the job exists and belongs to `owner`
+    pub async fn get(&self, job_id: Uuid, owner: Uuid) -> Option<ExportJobInfo> {
+        let jobs = self.jobs.read().await;
+        jobs.get(&job_id)
+            .filter(|job| job.owner == owner)
+            .map(|job| job.info.clone())
+    }
+
+    /// Bundle file of a completed job owned by `owner`
+    pub async fn bundle(&self, job_id: Uuid, owner: Uuid) -> Option<(ExportJobInfo, PathBuf)> {
+        let jobs = self.jobs.read().await;
+        let job = jobs.get(&job_id).filter(|job| job.owner == owner)?;
+        Some((job.info.clone(), job.bundle.clone()?))
+    }
+
+    /// Drop finished jobs older than the TTL and their bundle files
+    async fn remove_expired(&self) {
+        let cutoff = Utc::now() - chrono::Duration::from_std(JOB_TTL).unwrap_or_default();
+        let mut jobs = self.jobs.write().await;
+        let expired: Vec<Uuid> = jobs
+            .iter()
+            .filter(|(_, job)| job.info.finished_at.is_some_and(|at| at < cutoff))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            if let Some(path) = jobs.remove(&id).and_then(|job| job.bundle) {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_parse_include_list() {
+        assert_eq!(ExportPart::parse_list(None).unwrap(), ExportPart::ALL);
+        assert_eq!(
+            ExportPart::parse_list(Some("embeddings, chunks,chunks")).unwrap(),
+            vec![ExportPart::Chunks, ExportPart::Embeddings]
+        );
+        assert!(ExportPart::parse_list(Some("chunks,images")).is_err());
+    }
+
+    #[test]
+    fn test_bundle_contains_manifest_and_jsonl_files() {
+        let manifest = ExportManifest {
+            document_id: Uuid::new_v4(),
+            exported_at: Utc::now(),
+            files: BTreeMap::from([("chunks.jsonl".to_string(), 2)]),
+            embedding_model: None,
+        };
+        let lines = to_jsonl(&[
+            serde_json::json!({ "chunk_index": 0, "content": "연차휴가" }),
+            serde_json::json!({ "chunk_index": 1, "content": "병가" }),
+        ])
+        .unwrap();
+        let bundle = write_bundle(&manifest, &[("chunks.jsonl", lines)]).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bundle)).unwrap();
+        assert_eq!(archive.len(), 2);
+
+        let mut chunks = String::new();
+        archive
+            .by_name("chunks.jsonl")
+            .unwrap()
+            .read_to_string(&mut chunks)
+            .unwrap();
+        let parsed: Vec<serde_json::Value> = chunks
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1]["content"], "병가");
+
+        let manifest: ExportManifest =
+            serde_json::from_reader(archive.by_name("manifest.json").unwrap()).unwrap();
+        assert_eq!(manifest.files["chunks.jsonl"], 2);
+    }
+}
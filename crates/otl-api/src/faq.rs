@@ -0,0 +1,514 @@
+//! FAQ generation from the query history
+//!
+//! Answered queries are recorded in `query_stats` with their keywords. The
+//! generation job clusters recent questions by keyword overlap; for every
+//! cluster asked often enough it has the LLM phrase one canonical question,
+//! answers it through the RAG pipeline and queues the cited answer for
+//! review. Approved entries are listed by `GET /api/v1/faq` and retrieved by
+//! the RAG pipeline ahead of document passages.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::content_gaps::{self, DEFAULT_CLUSTER_SIMILARITY};
+use crate::error::{AppError, ErrorCode};
+use crate::handlers::documents::fetch_document_acls;
+use crate::state::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use otl_core::faq::keyword_overlap;
+use otl_core::{DocumentAcl, FaqEntry, FaqRepository, FaqStore, LlmClient, RagQuery, RagResponse};
+use otl_rag::{detect_language, PromptTemplate};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Default questions a cluster needs before an entry is generated
+const DEFAULT_MIN_QUERIES: usize = 5;
+
+/// Default days of query history considered
+const DEFAULT_LOOKBACK_DAYS: u32 = 30;
+
+/// Default answer confidence needed to queue an entry
+const DEFAULT_MIN_CONFIDENCE: f32 = 0.5;
+
+/// Default entries generated per run
+const DEFAULT_MAX_PER_RUN: usize = 20;
+
+/// Most recent queries clustered per run
+const MAX_HISTORY: i64 = 10_000;
+
+/// Questions shown to the LLM and kept with the entry
+const SAMPLE_QUESTIONS: usize = 5;
+
+/// Longest canonical question accepted from the LLM (in characters)
+const MAX_QUESTION_CHARS: usize = 200;
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// Query history recording and FAQ generation settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaqPolicy {
+    /// Record answered queries in the history
+    pub history: bool,
+    /// Questions a cluster needs before an entry is generated
+    pub min_queries: usize,
+    /// Days of history considered
+    pub lookback_days: u32,
+    /// Answer confidence needed to queue an entry
+    pub min_confidence: f32,
+    /// Entries generated per run
+    pub max_per_run: usize,
+    /// How often the job runs (`None` = only on demand)
+    pub interval: Option<Duration>,
+}
+
+impl Default for FaqPolicy {
+    fn default() -> Self {
+        Self {
+            history: true,
+            min_queries: DEFAULT_MIN_QUERIES,
+            lookback_days: DEFAULT_LOOKBACK_DAYS,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+            max_per_run: DEFAULT_MAX_PER_RUN,
+            interval: None,
+        }
+    }
+}
+
+impl FaqPolicy {
+    /// Policy from `FAQ_QUERY_HISTORY`, `FAQ_MIN_QUERIES`,
+    /// `FAQ_LOOKBACK_DAYS`, `FAQ_MIN_CONFIDENCE`, `FAQ_MAX_PER_RUN` and
+    /// `FAQ_GENERATION_INTERVAL_SECS` (0 or unset: on demand only)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(value) = std::env::var("FAQ_QUERY_HISTORY") {
+            policy.history = !matches!(value.to_lowercase().as_str(), "false" | "0" | "off");
+        }
+        if let Ok(value) = std::env::var("FAQ_MIN_QUERIES") {
+            match value.parse::<usize>() {
+                Ok(min) if min > 0 => policy.min_queries = min,
+                _ => tracing::warn!("Ignoring invalid FAQ_MIN_QUERIES: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("FAQ_LOOKBACK_DAYS") {
+            match value.parse::<u32>() {
+                Ok(days) if days > 0 => policy.lookback_days = days,
+                _ => tracing::warn!("Ignoring invalid FAQ_LOOKBACK_DAYS: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("FAQ_MIN_CONFIDENCE") {
+            match value.parse::<f32>() {
+                Ok(min) if (0.0..=1.0).contains(&min) => policy.min_confidence = min,
+                _ => tracing::warn!("Ignoring invalid FAQ_MIN_CONFIDENCE: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("FAQ_MAX_PER_RUN") {
+            match value.parse::<usize>() {
+                Ok(max) => policy.max_per_run = max,
+                Err(_) => tracing::warn!("Ignoring invalid FAQ_MAX_PER_RUN: {}", value),
+            }
+        }
+        if let Ok(secs) = std::env::var("FAQ_GENERATION_INTERVAL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(0) => policy.interval = None,
+                Ok(secs) => policy.interval = Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    tracing::warn!("Ignoring invalid FAQ_GENERATION_INTERVAL_SECS: {}", secs)
+                }
+            }
+        }
+        policy
+    }
+}
+
+// ============================================================================
+// Query history
+// ============================================================================
+
+/// Record an answered query in the background
+///
+/// Called by the REST, GraphQL and gRPC query paths next to content gap
+/// logging. Failures are only logged.
+pub(crate) fn record_query(
+    state: &Arc<AppState>,
+    question: &str,
+    response: &RagResponse,
+    user_id: Uuid,
+) {
+    if !state.faq.history {
+        return;
+    }
+    let keywords = state.analyzer.keywords(question);
+    let question = question.trim().to_string();
+    let citations = response.citations.len() as i32;
+    let confidence = response.confidence;
+    let total_time_ms = i32::try_from(response.processing_time_ms).unwrap_or(i32::MAX);
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        let result = sqlx::query(
+            "INSERT INTO query_stats (user_id, query_text, keywords, total_time_ms, num_results, confidence)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(user_id.to_string())
+        .bind(&question)
+        .bind(&keywords)
+        .bind(total_time_ms)
+        .bind(citations)
+        .bind(confidence)
+        .execute(&state.db_pool)
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to record query: {}", e);
+        }
+    });
+}
+
+/// A recorded question
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct HistoryQuery {
+    pub question: String,
+    pub keywords: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Answered questions recorded since `since`, newest first
+async fn fetch_history(
+    pool: &sqlx::PgPool,
+    since: DateTime<Utc>,
+) -> Result<Vec<HistoryQuery>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT query_text AS question, keywords, created_at
+         FROM query_stats
+         WHERE created_at >= $1 AND num_results > 0
+         ORDER BY created_at DESC
+         LIMIT $2",
+    )
+    .bind(since)
+    .bind(MAX_HISTORY)
+    .fetch_all(pool)
+    .await
+}
+
+// ============================================================================
+// Clustering
+// ============================================================================
+
+/// Questions asking the same thing
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryCluster {
+    /// Keywords of at least half the questions, most frequent first
+    pub keywords: Vec<String>,
+    /// Questions in the cluster
+    pub count: usize,
+    /// Distinct questions, most asked first
+    pub questions: Vec<String>,
+}
+
+fn query_terms(query: &HistoryQuery) -> HashSet<String> {
+    if query.keywords.is_empty() {
+        HashSet::from([query.question.trim().to_lowercase()])
+    } else {
+        query.keywords.iter().map(|k| k.to_lowercase()).collect()
+    }
+}
+
+/// Group recorded questions by keyword overlap, largest cluster first
+pub fn cluster_queries(queries: &[HistoryQuery], min_similarity: f32) -> Vec<QueryCluster> {
+    let mut clusters: Vec<QueryCluster> =
+        content_gaps::seed_clusters(queries, query_terms, min_similarity)
+            .into_iter()
+            .map(build_cluster)
+            .collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.count));
+    clusters
+}
+
+fn build_cluster(queries: Vec<&HistoryQuery>) -> QueryCluster {
+    let mut frequency: BTreeMap<String, usize> = BTreeMap::new();
+    for query in &queries {
+        for term in query_terms(query) {
+            *frequency.entry(term).or_default() += 1;
+        }
+    }
+    let mut keywords: Vec<(String, usize)> = frequency.into_iter().collect();
+    keywords.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let top = keywords.first().map(|(_, n)| *n).unwrap_or(0);
+    let keywords = keywords
+        .into_iter()
+        .filter(|(_, n)| *n == top || n * 2 >= queries.len())
+        .map(|(k, _)| k)
+        .collect();
+
+    // Distinct questions by frequency; `queries` is newest first, so ties
+    // keep the most recent wording
+    let mut asked: Vec<(String, usize)> = Vec::new();
+    for query in &queries {
+        let question = query.question.trim();
+        match asked
+            .iter_mut()
+            .find(|(q, _)| q.to_lowercase() == question.to_lowercase())
+        {
+            Some((_, n)) => *n += 1,
+            None => asked.push((question.to_string(), 1)),
+        }
+    }
+    asked.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+
+    QueryCluster {
+        keywords,
+        count: queries.len(),
+        questions: asked.into_iter().map(|(q, _)| q).collect(),
+    }
+}
+
+// ============================================================================
+// Generation
+// ============================================================================
+
+/// Outcome of one generation run
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct FaqRunReport {
+    /// Recorded questions considered
+    pub queries: usize,
+    /// Clusters asked at least `min_queries` times
+    pub clusters: usize,
+    /// Entries queued for review
+    pub generated: usize,
+    /// Clusters already covered by an entry (of any status)
+    pub existing: usize,
+    /// Clusters whose answer had no citations or too little confidence
+    pub unanswered: usize,
+}
+
+/// Generate FAQ entries for frequently asked clusters and queue them for
+/// review
+///
+/// Callers hold `state.faq_generation` so runs do not overlap.
+pub async fn generate_faqs(
+    state: &Arc<AppState>,
+    policy: &FaqPolicy,
+) -> Result<FaqRunReport, AppError> {
+    let rag = state.get_rag().await.ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "RAG pipeline not initialized",
+        )
+    })?;
+    let llm = state.llm_client.read().await.clone();
+    let store = FaqStore::from_pool(state.db_pool.clone());
+
+    let since = Utc::now() - ChronoDuration::days(i64::from(policy.lookback_days));
+    let history = fetch_history(&state.db_pool, since)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch query history: {e}")))?;
+    let clusters: Vec<QueryCluster> = cluster_queries(&history, DEFAULT_CLUSTER_SIMILARITY)
+        .into_iter()
+        .filter(|c| c.count >= policy.min_queries)
+        .collect();
+    let mut existing = store.list(None, MAX_HISTORY, 0).await?;
+
+    let mut report = FaqRunReport {
+        queries: history.len(),
+        clusters: clusters.len(),
+        ..Default::default()
+    };
+    let user = state.get_default_user(Some("faq-generator"));
+    for cluster in clusters {
+        if report.generated >= policy.max_per_run {
+            break;
+        }
+        if existing
+            .iter()
+            .any(|e| keyword_overlap(&e.keywords, &cluster.keywords) >= DEFAULT_CLUSTER_SIMILARITY)
+        {
+            report.existing += 1;
+            continue;
+        }
+
+        let question = canonical_question(llm.as_deref(), &cluster).await;
+        let response = match rag.query(&RagQuery::new(&question), &user).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("FAQ answer for '{}' failed: {}", question, e);
+                report.unanswered += 1;
+                continue;
+            }
+        };
+        if response.citations.is_empty()
+            || response.moderation.is_some()
+            || response.confidence < policy.min_confidence
+        {
+            report.unanswered += 1;
+            continue;
+        }
+
+        let document_ids: Vec<Uuid> = response
+            .citations
+            .iter()
+            .map(|c| c.source.document_id)
+            .collect();
+        let acls = fetch_document_acls(state, &document_ids).await?;
+
+        let mut entry = FaqEntry::new(question, response.answer);
+        entry.acl = most_restrictive(&acls);
+        entry.citations = response.citations;
+        entry.confidence = response.confidence;
+        entry.keywords = cluster.keywords;
+        entry.query_count = i32::try_from(cluster.count).unwrap_or(i32::MAX);
+        entry.source_questions = cluster
+            .questions
+            .into_iter()
+            .take(SAMPLE_QUESTIONS)
+            .collect();
+        store.upsert(&entry).await?;
+
+        tracing::info!(
+            "Queued FAQ '{}' ({} questions)",
+            entry.question,
+            entry.query_count
+        );
+        existing.push(entry);
+        report.generated += 1;
+    }
+
+    Ok(report)
+}
+
+/// The ACL of the most restrictive cited document
+fn most_restrictive(acls: &HashMap<Uuid, DocumentAcl>) -> DocumentAcl {
+    acls.values()
+        .max_by_key(|acl| acl.access_level)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// One question representing the cluster
+///
+/// The LLM rewrites the most asked questions into one; without an LLM, or
+/// when its reply is unusable, the most asked question is used as is.
+async fn canonical_question(llm: Option<&dyn LlmClient>, cluster: &QueryCluster) -> String {
+    let fallback = cluster.questions.first().cloned().unwrap_or_default();
+    let Some(llm) = llm.filter(|_| cluster.questions.len() > 1) else {
+        return fallback;
+    };
+
+    let samples: Vec<String> = cluster
+        .questions
+        .iter()
+        .take(SAMPLE_QUESTIONS)
+        .cloned()
+        .collect();
+    let template = PromptTemplate::for_language(detect_language(&fallback));
+    match llm.generate(&template.faq_question_prompt(&samples)).await {
+        Ok(reply) => {
+            let question = reply
+                .lines()
+                .map(|l| l.trim().trim_matches(|c| c == '"' || c == '\''))
+                .find(|l| !l.is_empty())
+                .unwrap_or_default();
+            if question.is_empty() || question.chars().count() > MAX_QUESTION_CHARS {
+                fallback
+            } else {
+                question.to_string()
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Canonical FAQ question failed: {}", e);
+            fallback
+        }
+    }
+}
+
+/// Run [`generate_faqs`] periodically in the background
+///
+/// Does nothing if the policy has no interval. A run is skipped while an
+/// on-demand run is in progress.
+pub fn spawn_generation_job(state: Arc<AppState>, policy: FaqPolicy) {
+    let Some(interval) = policy.interval else {
+        tracing::info!("FAQ generation job disabled");
+        return;
+    };
+    tracing::info!("Generating FAQ entries every {}s", interval.as_secs());
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let Ok(_running) = state.faq_generation.try_lock() else {
+                continue;
+            };
+            match generate_faqs(&state, &policy).await {
+                Ok(report) if report.generated > 0 => tracing::info!(
+                    "Queued {} FAQ entries from {} clusters",
+                    report.generated,
+                    report.clusters
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("FAQ generation failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(question: &str, keywords: &[&str], minutes_ago: i64) -> HistoryQuery {
+        HistoryQuery {
+            question: question.to_string(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            created_at: Utc::now() - ChronoDuration::minutes(minutes_ago),
+        }
+    }
+
+    #[test]
+    fn test_cluster_queries() {
+        let queries = vec![
+            query("연차휴가 신청 방법은?", &["연차휴가", "신청", "방법"], 1),
+            query("연차휴가 신청은 어떻게 하나요?", &["연차휴가", "신청"], 2),
+            query("연차휴가 신청 방법은?", &["연차휴가", "신청", "방법"], 3),
+            query("법인카드 한도는?", &["법인카드", "한도"], 4),
+        ];
+
+        let clusters = cluster_queries(&queries, DEFAULT_CLUSTER_SIMILARITY);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].count, 3);
+        assert_eq!(clusters[0].keywords, vec!["신청", "연차휴가", "방법"]);
+        assert_eq!(
+            clusters[0].questions,
+            vec!["연차휴가 신청 방법은?", "연차휴가 신청은 어떻게 하나요?"]
+        );
+        assert_eq!(clusters[1].questions, vec!["법인카드 한도는?"]);
+    }
+
+    #[test]
+    fn test_most_restrictive_acl() {
+        let mut acls = HashMap::new();
+        assert_eq!(
+            most_restrictive(&acls).access_level,
+            otl_core::AccessLevel::Internal
+        );
+        acls.insert(Uuid::new_v4(), DocumentAcl::default());
+        acls.insert(
+            Uuid::new_v4(),
+            DocumentAcl {
+                access_level: otl_core::AccessLevel::Confidential,
+                department: Some("HR".to_string()),
+                ..Default::default()
+            },
+        );
+        let acl = most_restrictive(&acls);
+        assert_eq!(acl.access_level, otl_core::AccessLevel::Confidential);
+        assert_eq!(acl.department.as_deref(), Some("HR"));
+    }
+}
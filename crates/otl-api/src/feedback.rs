@@ -0,0 +1,129 @@
+//! Citation feedback
+//!
+//! Users mark the citations of an answer helpful or unhelpful through
+//! `POST /api/v1/query/:id/feedback`. Votes are stored in the
+//! `citation_feedback` table and folded into the shared
+//! [`FeedbackPriors`](otl_rag::FeedbackPriors), which boost popular
+//! documents and chunks in the fusion stage. The priors are rebuilt from the
+//! table at startup.
+//!
+//! Each user has one vote per cited chunk of a query: repeating a vote
+//! changes nothing, and changing it replaces the earlier one both in the
+//! priors and in the table.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::state::{AppState, VoteBook};
+use chrono::{DateTime, Utc};
+use otl_rag::FeedbackVote;
+use std::collections::hash_map::Entry;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Votes older than this many half-lives are not loaded (weight < 0.1%)
+const LOADED_HALF_LIVES: f32 = 10.0;
+
+/// Apply new or changed votes to the priors and store them in the
+/// background; returns how many there were
+pub(crate) async fn record_votes(
+    state: &Arc<AppState>,
+    query_id: Uuid,
+    user_id: Uuid,
+    votes: Vec<FeedbackVote>,
+) -> usize {
+    let votes: Vec<FeedbackVote> = {
+        let mut books = state.votes.write().await;
+        if books.get(&query_id).is_none() {
+            books.insert(query_id, VoteBook::new());
+        }
+        let Some(book) = books.get_mut(&query_id) else {
+            // The store keeps no queries
+            return 0;
+        };
+        let mut changed = Vec::new();
+        for vote in votes {
+            match book.entry((user_id, vote.document_id, vote.chunk_index)) {
+                Entry::Occupied(earlier) if earlier.get().helpful == vote.helpful => continue,
+                Entry::Occupied(mut earlier) => state.feedback.withdraw(&earlier.insert(vote)),
+                Entry::Vacant(entry) => {
+                    entry.insert(vote);
+                }
+            }
+            changed.push(vote);
+        }
+        changed
+    };
+    for vote in &votes {
+        state.feedback.record(vote);
+    }
+    let recorded = votes.len();
+
+    let state = state.clone();
+    tokio::spawn(async move {
+        for vote in votes {
+            let result = sqlx::query(
+                "INSERT INTO citation_feedback
+                     (query_id, user_id, document_id, chunk_index, helpful, created_at)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (query_id, user_id, document_id, chunk_index)
+                 DO UPDATE SET helpful = EXCLUDED.helpful, created_at = EXCLUDED.created_at",
+            )
+            .bind(query_id)
+            .bind(user_id)
+            .bind(vote.document_id)
+            .bind(vote.chunk_index.map(|index| index as i32))
+            .bind(vote.helpful)
+            .bind(vote.at)
+            .execute(&state.db_pool)
+            .await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to store citation feedback: {}", e);
+                return;
+            }
+        }
+    });
+    recorded
+}
+
+/// Rebuild the feedback priors from stored votes in the background
+pub fn spawn_load(state: Arc<AppState>) {
+    let config = state.feedback.config().clone();
+    if !config.enabled {
+        tracing::info!("Feedback priors disabled");
+        return;
+    }
+    let max_age_days = (config.half_life_days > 0.0)
+        .then(|| (config.half_life_days * LOADED_HALF_LIVES).ceil() as i32);
+
+    tokio::spawn(async move {
+        let rows: Result<Vec<(Uuid, Option<i32>, bool, DateTime<Utc>)>, _> = sqlx::query_as(
+            "SELECT document_id, chunk_index, helpful, created_at
+             FROM citation_feedback
+             WHERE $1::int IS NULL OR created_at >= NOW() - make_interval(days => $1)
+             ORDER BY created_at",
+        )
+        .bind(max_age_days)
+        .fetch_all(&state.db_pool)
+        .await;
+
+        match rows {
+            Ok(rows) => {
+                let votes = rows.len();
+                for (document_id, chunk_index, helpful, at) in rows {
+                    state.feedback.record(&FeedbackVote {
+                        document_id,
+                        chunk_index: chunk_index.and_then(|index| u32::try_from(index).ok()),
+                        helpful,
+                        at,
+                    });
+                }
+                tracing::info!(
+                    "Loaded {} citation votes on {} documents",
+                    votes,
+                    state.feedback.len()
+                );
+            }
+            Err(e) => tracing::warn!("Failed to load citation feedback: {}", e),
+        }
+    });
+}
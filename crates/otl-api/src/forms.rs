@@ -0,0 +1,109 @@
+//! Form template registry
+//!
+//! Admins define the layout of application forms (휴가신청서, ...) as
+//! [`FormTemplate`]s, kept in `form_templates` by name. Templates are
+//! checked against the ontology when saved; the registry built from them
+//! recognizes which form a document follows and pulls its field values.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use otl_extractor::forms::{FormRegistry, FormTemplate};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Longest accepted template name
+const MAX_NAME_LEN: usize = 100;
+
+/// Stored form template
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StoredTemplate {
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub template: FormTemplate,
+    /// Admin who last saved it
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// All templates, by name
+pub async fn list_templates(state: &AppState) -> Result<Vec<StoredTemplate>, AppError> {
+    let rows: Vec<(serde_json::Value, Option<String>, DateTime<Utc>)> =
+        sqlx::query_as("SELECT template, updated_by, updated_at FROM form_templates ORDER BY name")
+            .fetch_all(&state.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch form templates: {e}")))?;
+
+    rows.into_iter()
+        .map(|(template, updated_by, updated_at)| {
+            Ok(StoredTemplate {
+                template: serde_json::from_value(template).map_err(|e| {
+                    AppError::Internal(format!("Invalid stored form template: {e}"))
+                })?,
+                updated_by,
+                updated_at,
+            })
+        })
+        .collect()
+}
+
+/// Registry of all templates; ties in detection go to the first by name
+pub async fn registry(state: &AppState) -> Result<FormRegistry, AppError> {
+    let templates = list_templates(state).await?;
+    Ok(FormRegistry::new(
+        templates.into_iter().map(|t| t.template).collect(),
+    ))
+}
+
+/// Create or replace a template after checking it against the ontology
+pub async fn save_template(
+    state: &AppState,
+    template: FormTemplate,
+    updated_by: &str,
+) -> Result<StoredTemplate, AppError> {
+    if template.name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Template name is longer than {MAX_NAME_LEN} characters"
+        )));
+    }
+    let classes = crate::handlers::graph::default_ontology().to_core_classes();
+    template.validate(&classes)?;
+
+    let value = serde_json::to_value(&template)
+        .map_err(|e| AppError::Internal(format!("Failed to encode form template: {e}")))?;
+    let updated_at: DateTime<Utc> = sqlx::query_scalar(
+        r#"
+        INSERT INTO form_templates (name, template, updated_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (name) DO UPDATE
+        SET template = EXCLUDED.template,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        RETURNING updated_at
+        "#,
+    )
+    .bind(&template.name)
+    .bind(value)
+    .bind(updated_by)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to store form template: {e}")))?;
+
+    Ok(StoredTemplate {
+        template,
+        updated_by: Some(updated_by.to_string()),
+        updated_at,
+    })
+}
+
+/// Remove a template; false if there was none by that name
+pub async fn delete_template(state: &AppState, name: &str) -> Result<bool, AppError> {
+    let result = sqlx::query("DELETE FROM form_templates WHERE name = $1")
+        .bind(name)
+        .execute(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete form template: {e}")))?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,566 @@
+//! Knowledge freshness checks
+//!
+//! A scheduled job walks the document metadata and flags documents that
+//! are past their review date or superseded by a newer version (see
+//! [`otl_core::freshness`]). When a newer version of the same
+//! `document_group` exists, the older document's `superseded_by` is set so
+//! that ranking and citation warnings see it without recomputing groups;
+//! a `superseded_by` pointing at a deleted document is cleared.
+//!
+//! Vectors carry the document's version and superseded state in their
+//! payload; they are re-tagged whenever `superseded_by` changes.
+//!
+//! Each stale condition opens one row in `freshness_alerts`. New alerts are
+//! posted to the configured webhooks and sent to the admins through the
+//! notification channels; alerts whose condition cleared are resolved on
+//! the next run.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use crate::notifications::{self, Audience, NotificationEvent, LINK_VAR};
+use crate::state::AppState;
+use chrono::{DateTime, NaiveDate, Utc};
+use otl_core::freshness::{self, SUPERSEDED_BY_KEY};
+use otl_core::{DocumentMetadata, MetadataRepository, MetadataStore, StaleReason};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Default seconds between freshness checks (daily)
+const DEFAULT_CHECK_INTERVAL_SECS: u64 = 86_400;
+
+/// Documents loaded per database round trip
+const CHECK_BATCH_SIZE: i64 = 500;
+
+/// Seconds to wait for a webhook to accept an alert
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// When freshness is checked and who is told about stale documents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreshnessPolicy {
+    /// Time between checks (`None` runs checks only on demand)
+    pub check_interval: Option<Duration>,
+    /// URLs receiving new alerts as JSON `POST`s
+    pub webhook_urls: Vec<String>,
+}
+
+impl Default for FreshnessPolicy {
+    fn default() -> Self {
+        Self {
+            check_interval: Some(Duration::from_secs(DEFAULT_CHECK_INTERVAL_SECS)),
+            webhook_urls: Vec::new(),
+        }
+    }
+}
+
+impl FreshnessPolicy {
+    /// Policy from `FRESHNESS_CHECK_INTERVAL_SECS` (0 disables the job) and
+    /// `FRESHNESS_WEBHOOK_URLS` (comma-separated)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(secs) = std::env::var("FRESHNESS_CHECK_INTERVAL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(0) => policy.check_interval = None,
+                Ok(secs) => policy.check_interval = Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    tracing::warn!("Ignoring invalid FRESHNESS_CHECK_INTERVAL_SECS: {}", secs)
+                }
+            }
+        }
+        if let Ok(urls) = std::env::var("FRESHNESS_WEBHOOK_URLS") {
+            policy.webhook_urls = urls
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        policy
+    }
+}
+
+// ============================================================================
+// Alerts
+// ============================================================================
+
+/// A stale condition of one document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FreshnessAlert {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub document_title: String,
+    pub reason: StaleReason,
+    /// Review date that had passed when the alert was raised
+    pub review_date: Option<NaiveDate>,
+    /// Newer version that superseded the document
+    pub superseded_by: Option<Uuid>,
+    pub detected_at: DateTime<Utc>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub acknowledged_by: Option<Uuid>,
+    /// When the condition cleared (review date moved, newer version removed)
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AlertRow {
+    id: Uuid,
+    document_id: Uuid,
+    document_title: String,
+    reason: String,
+    review_date: Option<NaiveDate>,
+    superseded_by: Option<Uuid>,
+    detected_at: DateTime<Utc>,
+    acknowledged_at: Option<DateTime<Utc>>,
+    acknowledged_by: Option<Uuid>,
+    resolved_at: Option<DateTime<Utc>>,
+}
+
+impl AlertRow {
+    fn into_alert(self) -> Option<FreshnessAlert> {
+        let reason = match self.reason.parse() {
+            Ok(reason) => reason,
+            Err(e) => {
+                tracing::warn!("Skipping freshness alert {}: {}", self.id, e);
+                return None;
+            }
+        };
+        Some(FreshnessAlert {
+            id: self.id,
+            document_id: self.document_id,
+            document_title: self.document_title,
+            reason,
+            review_date: self.review_date,
+            superseded_by: self.superseded_by,
+            detected_at: self.detected_at,
+            acknowledged_at: self.acknowledged_at,
+            acknowledged_by: self.acknowledged_by,
+            resolved_at: self.resolved_at,
+        })
+    }
+}
+
+const ALERT_COLUMNS: &str = "a.id, a.document_id, d.title AS document_title, a.reason, \
+     a.review_date, a.superseded_by, a.detected_at, a.acknowledged_at, a.acknowledged_by, \
+     a.resolved_at";
+
+/// Alerts, newest first; resolved ones only with `include_resolved`
+pub async fn list_alerts(
+    pool: &sqlx::PgPool,
+    include_resolved: bool,
+    limit: i64,
+) -> Result<Vec<FreshnessAlert>, sqlx::Error> {
+    let rows: Vec<AlertRow> = sqlx::query_as(&format!(
+        "SELECT {ALERT_COLUMNS} FROM freshness_alerts a
+         JOIN documents d ON d.id = a.document_id
+         WHERE ($1 OR a.resolved_at IS NULL)
+         ORDER BY a.detected_at DESC
+         LIMIT $2"
+    ))
+    .bind(include_resolved)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().filter_map(AlertRow::into_alert).collect())
+}
+
+/// Mark an alert seen by an admin; `None` if it does not exist
+pub async fn acknowledge(
+    pool: &sqlx::PgPool,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<FreshnessAlert>, sqlx::Error> {
+    let updated = sqlx::query(
+        "UPDATE freshness_alerts SET acknowledged_at = NOW(), acknowledged_by = $2
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    let row: Option<AlertRow> = sqlx::query_as(&format!(
+        "SELECT {ALERT_COLUMNS} FROM freshness_alerts a
+         JOIN documents d ON d.id = a.document_id
+         WHERE a.id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.and_then(AlertRow::into_alert))
+}
+
+// ============================================================================
+// Check
+// ============================================================================
+
+/// What a freshness check found
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FreshnessReport {
+    /// Documents examined
+    pub documents: usize,
+    /// Documents whose `superseded_by` was set to a newer version
+    pub marked_superseded: usize,
+    /// `superseded_by` values cleared because the newer document is gone
+    pub cleared_superseded: usize,
+    /// Alerts opened by this run
+    pub new_alerts: usize,
+    /// Alerts resolved because their condition cleared
+    pub resolved_alerts: usize,
+    /// Webhooks that did not accept the new alerts
+    pub webhook_failures: usize,
+}
+
+/// Change to a document's `superseded_by`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SupersededUpdate {
+    Set(Uuid),
+    Clear,
+}
+
+/// `superseded_by` changes needed for the current set of documents
+///
+/// Documents that are not the newest of their version group point at the
+/// newest one unless they already name a replacement; names of documents
+/// that no longer exist are cleared.
+fn superseded_updates(documents: &[DocumentMetadata]) -> Vec<(Uuid, SupersededUpdate)> {
+    let ids: HashSet<Uuid> = documents.iter().map(|d| d.id).collect();
+    let latest = freshness::latest_versions(documents);
+
+    let mut updates = Vec::new();
+    for doc in documents {
+        match freshness::superseded_by(doc) {
+            Some(newer) if ids.contains(&newer) => {}
+            Some(_) => updates.push((doc.id, SupersededUpdate::Clear)),
+            None => {
+                if let Some(newest) = latest.get(&freshness::version_group(doc)) {
+                    if *newest != doc.id {
+                        updates.push((doc.id, SupersededUpdate::Set(*newest)));
+                    }
+                }
+            }
+        }
+    }
+    updates
+}
+
+/// Tag the vectors of a document with its version and superseded state
+///
+/// Failures are logged; retrieval still excludes superseded documents by
+/// their metadata.
+pub(crate) async fn tag_vectors(state: &AppState, doc: &DocumentMetadata) {
+    let Some(backend) = state.vector_backend.read().await.clone() else {
+        return;
+    };
+    let superseded = freshness::superseded_by(doc).is_some();
+    if let Err(e) = backend
+        .tag_version(doc.id, freshness::version(doc), superseded)
+        .await
+    {
+        tracing::warn!("Failed to tag vectors of document {}: {}", doc.id, e);
+    }
+}
+
+/// Load every live document
+async fn load_documents(state: &AppState) -> Result<Vec<DocumentMetadata>, AppError> {
+    let store = MetadataStore::from_pool(state.db_pool.clone());
+    let mut documents = Vec::new();
+    loop {
+        let batch = store
+            .list_documents(CHECK_BATCH_SIZE, documents.len() as i64)
+            .await?;
+        let done = (batch.len() as i64) < CHECK_BATCH_SIZE;
+        documents.extend(batch);
+        if done {
+            return Ok(documents);
+        }
+    }
+}
+
+/// Flag stale documents, open and resolve alerts and notify webhooks
+pub async fn check_freshness(
+    state: &AppState,
+    policy: &FreshnessPolicy,
+) -> Result<FreshnessReport, AppError> {
+    let mut documents = load_documents(state).await?;
+    let mut report = FreshnessReport {
+        documents: documents.len(),
+        ..Default::default()
+    };
+
+    let updates: HashMap<Uuid, SupersededUpdate> =
+        superseded_updates(&documents).into_iter().collect();
+    for doc in &mut documents {
+        let Some(update) = updates.get(&doc.id) else {
+            continue;
+        };
+        let query = match update {
+            SupersededUpdate::Set(newer) => {
+                doc.extra.insert(
+                    SUPERSEDED_BY_KEY.to_string(),
+                    serde_json::Value::String(newer.to_string()),
+                );
+                report.marked_superseded += 1;
+                sqlx::query(
+                    "UPDATE documents SET metadata = COALESCE(metadata, '{}') || jsonb_build_object($2::text, $3::text)
+                     WHERE id = $1",
+                )
+                .bind(doc.id)
+                .bind(SUPERSEDED_BY_KEY)
+                .bind(newer.to_string())
+            }
+            SupersededUpdate::Clear => {
+                doc.extra.remove(SUPERSEDED_BY_KEY);
+                report.cleared_superseded += 1;
+                sqlx::query("UPDATE documents SET metadata = metadata - $2 WHERE id = $1")
+                    .bind(doc.id)
+                    .bind(SUPERSEDED_BY_KEY)
+            }
+        };
+        query
+            .execute(&state.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update document metadata: {e}")))?;
+        tag_vectors(state, doc).await;
+    }
+
+    // Stale conditions as of today
+    let today = Utc::now().date_naive();
+    let mut current: HashMap<(Uuid, StaleReason), (NaiveDate, Option<Uuid>)> = HashMap::new();
+    let mut review_dates: HashMap<Uuid, Option<NaiveDate>> = HashMap::new();
+    for doc in &documents {
+        let Some(warning) = freshness::assess(doc, today) else {
+            continue;
+        };
+        review_dates.insert(doc.id, warning.review_date);
+        for reason in &warning.reasons {
+            current.insert((doc.id, *reason), (today, warning.superseded_by));
+        }
+    }
+
+    let open: Vec<(Uuid, Uuid, String)> = sqlx::query_as(
+        "SELECT id, document_id, reason FROM freshness_alerts WHERE resolved_at IS NULL",
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch freshness alerts: {e}")))?;
+
+    let mut open_keys = HashSet::new();
+    let mut cleared = Vec::new();
+    for (id, document_id, reason) in open {
+        let key = reason.parse().ok().map(|reason| (document_id, reason));
+        match key {
+            Some(key) if current.contains_key(&key) => {
+                open_keys.insert(key);
+            }
+            _ => cleared.push(id),
+        }
+    }
+    if !cleared.is_empty() {
+        report.resolved_alerts =
+            sqlx::query("UPDATE freshness_alerts SET resolved_at = NOW() WHERE id = ANY($1)")
+                .bind(&cleared)
+                .execute(&state.db_pool)
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to resolve freshness alerts: {e}"))
+                })?
+                .rows_affected() as usize;
+    }
+
+    let titles: HashMap<Uuid, &str> = documents.iter().map(|d| (d.id, d.title.as_str())).collect();
+    let mut new_alerts = Vec::new();
+    for ((document_id, reason), (_, superseded_by)) in &current {
+        if open_keys.contains(&(*document_id, *reason)) {
+            continue;
+        }
+        let review_date = match reason {
+            StaleReason::ReviewOverdue => review_dates.get(document_id).copied().flatten(),
+            StaleReason::Superseded => None,
+        };
+        let superseded_by = match reason {
+            StaleReason::Superseded => *superseded_by,
+            StaleReason::ReviewOverdue => None,
+        };
+        let (id, detected_at): (Uuid, DateTime<Utc>) = sqlx::query_as(
+            "INSERT INTO freshness_alerts (document_id, reason, review_date, superseded_by)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id, detected_at",
+        )
+        .bind(document_id)
+        .bind(reason.as_str())
+        .bind(review_date)
+        .bind(superseded_by)
+        .fetch_one(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to store freshness alert: {e}")))?;
+
+        new_alerts.push(FreshnessAlert {
+            id,
+            document_id: *document_id,
+            document_title: titles.get(document_id).unwrap_or(&"").to_string(),
+            reason: *reason,
+            review_date,
+            superseded_by,
+            detected_at,
+            acknowledged_at: None,
+            acknowledged_by: None,
+            resolved_at: None,
+        });
+    }
+    new_alerts.sort_by_key(|a| (a.document_title.clone(), a.reason));
+    report.new_alerts = new_alerts.len();
+
+    if !new_alerts.is_empty() {
+        report.webhook_failures = notify(&policy.webhook_urls, &new_alerts).await;
+        let alerts: Vec<String> = new_alerts
+            .iter()
+            .map(|a| format!("- {}: {}", a.document_title, a.reason.as_str()))
+            .collect();
+        notifications::notify(
+            state,
+            NotificationEvent::DocumentsStale,
+            vec![
+                ("count", new_alerts.len().to_string()),
+                ("alerts", alerts.join("\n")),
+                (LINK_VAR, "/api/v1/admin/freshness/alerts".to_string()),
+            ],
+            Audience::Admins,
+        );
+    }
+    Ok(report)
+}
+
+/// Webhook body announcing new alerts
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    event: &'static str,
+    alerts: &'a [FreshnessAlert],
+}
+
+/// Post new alerts to every webhook; returns the number that failed
+async fn notify(urls: &[String], alerts: &[FreshnessAlert]) -> usize {
+    if urls.is_empty() {
+        return 0;
+    }
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!("Cannot build webhook client: {}", e);
+            return urls.len();
+        }
+    };
+    let payload = WebhookPayload {
+        event: "documents.stale",
+        alerts,
+    };
+
+    let mut failures = 0;
+    for url in urls {
+        let result = client
+            .post(url)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!("Freshness webhook {} failed: {}", url, e);
+            failures += 1;
+        }
+    }
+    failures
+}
+
+/// Run [`check_freshness`] periodically in the background
+///
+/// Does nothing if the policy has no check interval.
+pub fn spawn_check_job(state: Arc<AppState>, policy: FreshnessPolicy) {
+    let Some(interval) = policy.check_interval else {
+        tracing::info!("Document freshness job disabled");
+        return;
+    };
+    tracing::info!(
+        "Checking document freshness every {}s ({} webhooks)",
+        interval.as_secs(),
+        policy.webhook_urls.len()
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match check_freshness(&state, &policy).await {
+                Ok(report) if report.new_alerts > 0 || report.resolved_alerts > 0 => {
+                    tracing::info!("Freshness check: {:?}", report)
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Freshness check failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document(title: &str, version: u32) -> DocumentMetadata {
+        let mut doc = DocumentMetadata::new(title, format!("/docs/{title}.pdf"), "pdf");
+        doc.extra.insert("version".to_string(), json!(version));
+        doc
+    }
+
+    #[test]
+    fn test_superseded_updates() {
+        let v1 = document("취업규칙", 1);
+        let v2 = document("취업규칙", 2);
+        let v3 = document("취업규칙", 3);
+        let mut manual = document("출장 규정", 1);
+        manual
+            .extra
+            .insert(SUPERSEDED_BY_KEY.to_string(), json!(v3.id.to_string()));
+        let mut dangling = document("보안 규정", 1);
+        dangling.extra.insert(
+            SUPERSEDED_BY_KEY.to_string(),
+            json!(Uuid::new_v4().to_string()),
+        );
+
+        let documents = vec![v1.clone(), v2.clone(), v3.clone(), manual, dangling.clone()];
+        let mut updates = superseded_updates(&documents);
+        updates.sort_by_key(|(id, _)| documents.iter().position(|d| d.id == *id));
+
+        assert_eq!(
+            updates,
+            vec![
+                (v1.id, SupersededUpdate::Set(v3.id)),
+                (v2.id, SupersededUpdate::Set(v3.id)),
+                (dangling.id, SupersededUpdate::Clear),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_policy_defaults_to_daily_checks() {
+        let policy = FreshnessPolicy::default();
+        assert_eq!(policy.check_interval, Some(Duration::from_secs(86_400)));
+        assert!(policy.webhook_urls.is_empty());
+    }
+}
@@ -0,0 +1,731 @@
+//! GraphQL API surface
+//!
+//! Exposes documents, graph entities, the verification queue and RAG queries
+//! through a single async-graphql schema. Nested resolvers allow clients to
+//! walk document → chunks → entities → relations in one round trip.
+//!
+//! The schema shares `AppState` with the REST layer and is mounted behind the
+//! same JWT middleware; the authenticated user is attached to each request's
+//! context data and used for ACL filtering.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::access_requests::LIVE_ALLOWED_USERS;
+use crate::auth::middleware::AuthenticatedUser;
+use crate::handlers::graph::extract_entity_name;
+use crate::handlers::query::stream_answer;
+use crate::handlers::verify::{self, RejectAction, VerifyAction};
+use crate::state::AppState;
+use async_graphql::{ComplexObject, Context, Json, Object, Schema, SimpleObject, Subscription, ID};
+use axum::{
+    extract::{Path, State},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use futures::stream::{Stream, StreamExt};
+use otl_core::{AccessLevel, DocumentAcl, RagQuery};
+use otl_graph::GraphStore;
+use std::convert::Infallible;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Full OTL GraphQL schema
+pub type OtlSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
+
+/// Maximum page size accepted by list resolvers
+const MAX_PAGE_SIZE: i32 = 100;
+
+static SCHEMA: OnceLock<OtlSchema> = OnceLock::new();
+
+/// Build the schema (state and user are injected per request)
+pub fn build_schema() -> OtlSchema {
+    Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+        .limit_depth(10)
+        .finish()
+}
+
+/// Get the process-wide schema instance
+pub fn schema() -> &'static OtlSchema {
+    SCHEMA.get_or_init(build_schema)
+}
+
+// ============================================================================
+// Context helpers
+// ============================================================================
+
+fn app_state<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Arc<AppState>> {
+    ctx.data::<Arc<AppState>>()
+}
+
+fn current_user<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a AuthenticatedUser> {
+    ctx.data::<AuthenticatedUser>()
+        .map_err(|_| async_graphql::Error::new("Authentication required"))
+}
+
+fn clamp_limit(limit: Option<i32>, default: i32) -> i64 {
+    limit.unwrap_or(default).clamp(1, MAX_PAGE_SIZE) as i64
+}
+
+fn parse_access_level(level: &str) -> AccessLevel {
+    match level {
+        "public" => AccessLevel::Public,
+        "confidential" => AccessLevel::Confidential,
+        "restricted" => AccessLevel::Restricted,
+        _ => AccessLevel::Internal,
+    }
+}
+
+// ============================================================================
+// Object types
+// ============================================================================
+
+/// Database row for documents including ACL columns
+#[derive(sqlx::FromRow)]
+struct DocumentAclRow {
+    id: Uuid,
+    title: String,
+    file_type: String,
+    access_level: String,
+    department: Option<String>,
+    owner_id: Option<String>,
+    required_roles: Option<Vec<String>>,
+    allowed_users: Option<Vec<String>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl DocumentAclRow {
+    fn acl(&self) -> DocumentAcl {
+        DocumentAcl {
+            access_level: parse_access_level(&self.access_level),
+            owner_id: self.owner_id.clone(),
+            department: self.department.clone(),
+            required_roles: self.required_roles.clone().unwrap_or_default(),
+            allowed_users: self.allowed_users.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Columns of [`DocumentAclRow`] except `allowed_users`, which is selected
+/// as [`LIVE_ALLOWED_USERS`]
+const DOCUMENT_COLUMNS: &str = "d.id, d.title, d.file_type::text, d.access_level::text, \
+     d.department, d.owner_id, d.required_roles, d.created_at, d.updated_at";
+
+/// Document node
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Document {
+    pub id: Uuid,
+    pub title: String,
+    pub file_type: String,
+    pub access_level: String,
+    pub department: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<DocumentAclRow> for Document {
+    fn from(row: DocumentAclRow) -> Self {
+        Self {
+            id: row.id,
+            title: row.title,
+            file_type: row.file_type,
+            access_level: row.access_level,
+            department: row.department,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[ComplexObject]
+impl Document {
+    /// Chunks of this document in reading order
+    async fn chunks(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<Chunk>> {
+        let state = app_state(ctx)?;
+        let mut rows = sqlx::query_as::<_, Chunk>(
+            "SELECT id, document_id, chunk_index, content, page_number, section_name
+             FROM document_chunks
+             WHERE document_id = $1
+             ORDER BY chunk_index
+             LIMIT $2 OFFSET $3",
+        )
+        .bind(self.id)
+        .bind(clamp_limit(limit, 20))
+        .bind(offset.unwrap_or(0).max(0) as i64)
+        .fetch_all(&state.db_pool)
+        .await?;
+        state
+            .keyring
+            .open_chunks(
+                &state.db_pool,
+                self.id,
+                rows.iter_mut()
+                    .map(|c| (c.chunk_index.max(0) as u32, &mut c.content)),
+            )
+            .await?;
+        Ok(rows)
+    }
+
+    /// Graph entities extracted from this document
+    async fn entities(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+    ) -> async_graphql::Result<Vec<GraphEntity>> {
+        let state = app_state(ctx)?;
+        let Some(graph_db) = state.graph_db.read().await.clone() else {
+            return Ok(Vec::new());
+        };
+
+        let entities = graph_db
+            .query(&format!(
+                "SELECT * FROM entity WHERE source.document_id = '{}' LIMIT {}",
+                self.id,
+                clamp_limit(limit, 50)
+            ))
+            .await?;
+
+        Ok(entities.into_iter().map(GraphEntity::from).collect())
+    }
+}
+
+/// Document chunk node
+#[derive(SimpleObject, sqlx::FromRow)]
+pub struct Chunk {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub chunk_index: i32,
+    pub content: String,
+    pub page_number: Option<i32>,
+    pub section_name: Option<String>,
+}
+
+/// Knowledge graph entity node
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct GraphEntity {
+    pub id: Uuid,
+    pub class: String,
+    pub name: String,
+    pub properties: Json<serde_json::Value>,
+    pub document_id: Uuid,
+}
+
+impl From<otl_core::Entity> for GraphEntity {
+    fn from(entity: otl_core::Entity) -> Self {
+        Self {
+            id: entity.id,
+            name: extract_entity_name(&entity.properties),
+            properties: Json(serde_json::to_value(&entity.properties).unwrap_or_default()),
+            document_id: entity.source.document_id,
+            class: entity.class,
+        }
+    }
+}
+
+#[ComplexObject]
+impl GraphEntity {
+    /// Entities reachable from this one within `depth` hops
+    async fn related(
+        &self,
+        ctx: &Context<'_>,
+        depth: Option<i32>,
+    ) -> async_graphql::Result<Vec<GraphEntity>> {
+        let state = app_state(ctx)?;
+        let Some(graph_db) = state.graph_db.read().await.clone() else {
+            return Ok(Vec::new());
+        };
+
+        let depth = depth.unwrap_or(1).clamp(1, 3) as u32;
+        let related = graph_db.traverse(self.id, depth).await?;
+        Ok(related.into_iter().map(GraphEntity::from).collect())
+    }
+}
+
+/// Database row for pending extractions
+#[derive(sqlx::FromRow)]
+struct PendingExtractionRow {
+    id: Uuid,
+    document_id: Uuid,
+    document_title: String,
+    extracted_entities: serde_json::Value,
+    extracted_relations: serde_json::Value,
+    source_text: Option<String>,
+    confidence_score: f32,
+    status: String,
+    created_at: DateTime<Utc>,
+}
+
+/// Pending extraction in the HITL queue
+#[derive(SimpleObject)]
+pub struct PendingExtraction {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub document_title: String,
+    pub extracted_entities: Json<serde_json::Value>,
+    pub extracted_relations: Json<serde_json::Value>,
+    pub source_text: Option<String>,
+    pub confidence_score: f32,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<PendingExtractionRow> for PendingExtraction {
+    fn from(row: PendingExtractionRow) -> Self {
+        Self {
+            id: row.id,
+            document_id: row.document_id,
+            document_title: row.document_title,
+            extracted_entities: Json(row.extracted_entities),
+            extracted_relations: Json(row.extracted_relations),
+            source_text: row.source_text,
+            confidence_score: row.confidence_score,
+            status: row.status,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// Result of a verification mutation
+#[derive(SimpleObject)]
+pub struct VerificationResult {
+    pub id: Uuid,
+    pub status: String,
+}
+
+/// Citation attached to a RAG answer
+#[derive(SimpleObject)]
+pub struct AnswerCitation {
+    pub index: i32,
+    pub document_id: Uuid,
+    pub document_title: String,
+    pub page: Option<i32>,
+    pub section: Option<String>,
+    pub text: String,
+    /// Why the cited document may be out of date (`review_overdue`, `superseded`)
+    pub stale_reasons: Vec<String>,
+    /// Document replacing the cited one
+    pub superseded_by: Option<Uuid>,
+}
+
+/// RAG answer
+#[derive(SimpleObject)]
+pub struct Answer {
+    pub answer: String,
+    pub citations: Vec<AnswerCitation>,
+    pub confidence: f32,
+    pub processing_time_ms: u64,
+    pub suggestions: Vec<String>,
+    pub structured_answer: Option<Json<serde_json::Value>>,
+    /// Answer layout (`prose`, `steps`, `table` or `yes_no`)
+    pub format: String,
+    /// Degradations the answer was produced under (e.g. skipped backends)
+    pub warnings: Vec<String>,
+    /// Model that generated the answer
+    pub model: Option<String>,
+    /// Knowledge spaces the answer was retrieved from, ending with `global`
+    /// after a fallback (empty for global search)
+    pub searched_spaces: Vec<String>,
+}
+
+// ============================================================================
+// Roots
+// ============================================================================
+
+/// Query root
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Single document by ID (ACL-checked)
+    async fn document(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Option<Document>> {
+        let state = app_state(ctx)?;
+        let user = current_user(ctx)?.to_acl_user();
+        let id = Uuid::parse_str(&id)?;
+
+        let row = sqlx::query_as::<_, DocumentAclRow>(&format!(
+            "SELECT {DOCUMENT_COLUMNS}, {LIVE_ALLOWED_USERS} AS allowed_users FROM documents d WHERE d.id = $1 AND d.deleted_at IS NULL"
+        ))
+        .bind(id)
+        .fetch_optional(&state.db_pool)
+        .await?;
+
+        Ok(row
+            .filter(|row| row.acl().can_access(&user))
+            .map(Document::from))
+    }
+
+    /// Documents visible to the current user, newest first
+    async fn documents(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+        search: Option<String>,
+    ) -> async_graphql::Result<Vec<Document>> {
+        let state = app_state(ctx)?;
+        let user = current_user(ctx)?.to_acl_user();
+
+        let rows = sqlx::query_as::<_, DocumentAclRow>(&format!(
+            "SELECT {DOCUMENT_COLUMNS}, {LIVE_ALLOWED_USERS} AS allowed_users FROM documents d
+             WHERE d.deleted_at IS NULL AND ($1::text IS NULL OR d.title ILIKE $1)
+             ORDER BY d.created_at DESC
+             LIMIT $2 OFFSET $3"
+        ))
+        .bind(search.map(|s| format!("%{s}%")))
+        .bind(clamp_limit(limit, 20))
+        .bind(offset.unwrap_or(0).max(0) as i64)
+        .fetch_all(&state.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| row.acl().can_access(&user))
+            .map(Document::from)
+            .collect())
+    }
+
+    /// Single graph entity by ID
+    async fn entity(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> async_graphql::Result<Option<GraphEntity>> {
+        let state = app_state(ctx)?;
+        let graph_db = state
+            .graph_db
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| async_graphql::Error::new("Graph database not initialized"))?;
+
+        let entity = graph_db.get_entity(Uuid::parse_str(&id)?).await?;
+        Ok(entity.map(GraphEntity::from))
+    }
+
+    /// Graph entities, optionally filtered by ontology class
+    async fn entities(
+        &self,
+        ctx: &Context<'_>,
+        class: Option<String>,
+        limit: Option<i32>,
+    ) -> async_graphql::Result<Vec<GraphEntity>> {
+        let state = app_state(ctx)?;
+        let graph_db = state
+            .graph_db
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| async_graphql::Error::new("Graph database not initialized"))?;
+
+        let limit = clamp_limit(limit, 50);
+        let entities = match class {
+            Some(class) => graph_db.find_by_class(&class, limit as usize).await?,
+            None => {
+                graph_db
+                    .query(&format!("SELECT * FROM entity LIMIT {limit}"))
+                    .await?
+            }
+        };
+
+        Ok(entities.into_iter().map(GraphEntity::from).collect())
+    }
+
+    /// Pending extractions awaiting review
+    async fn pending_extractions(
+        &self,
+        ctx: &Context<'_>,
+        document_id: Option<ID>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<PendingExtraction>> {
+        let state = app_state(ctx)?;
+        current_user(ctx)?;
+        let document_id = document_id.map(|id| Uuid::parse_str(&id)).transpose()?;
+
+        let rows = sqlx::query_as::<_, PendingExtractionRow>(
+            "SELECT eq.id, eq.document_id, d.title AS document_title,
+                    eq.extracted_entities, eq.extracted_relations, eq.source_text,
+                    eq.confidence_score, eq.status::text, eq.created_at
+             FROM extraction_queue eq
+             JOIN documents d ON eq.document_id = d.id
+             WHERE eq.status = 'pending' AND ($1::uuid IS NULL OR eq.document_id = $1)
+             ORDER BY eq.priority, eq.created_at
+             LIMIT $2 OFFSET $3",
+        )
+        .bind(document_id)
+        .bind(clamp_limit(limit, 20))
+        .bind(offset.unwrap_or(0).max(0) as i64)
+        .fetch_all(&state.db_pool)
+        .await?;
+
+        Ok(rows.into_iter().map(PendingExtraction::from).collect())
+    }
+}
+
+/// Mutation root
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Ask a question through the hybrid RAG pipeline
+    async fn query(
+        &self,
+        ctx: &Context<'_>,
+        question: String,
+        top_k: Option<i32>,
+        strict: Option<bool>,
+        include_superseded: Option<bool>,
+        space: Option<String>,
+    ) -> async_graphql::Result<Answer> {
+        let state = app_state(ctx)?;
+        let caller = current_user(ctx)?;
+        let user = caller.to_acl_user();
+
+        if question.trim().is_empty() {
+            return Err(async_graphql::Error::new("Question cannot be empty"));
+        }
+
+        let rag = state
+            .get_rag()
+            .await
+            .ok_or_else(|| async_graphql::Error::new("RAG pipeline not initialized"))?;
+
+        state.increment_requests();
+        let top_k = top_k.unwrap_or(5).clamp(1, MAX_PAGE_SIZE) as usize;
+        let (scope, persona) =
+            state.scope_and_persona(space.as_deref(), &question, caller.department.as_deref())?;
+        let response = rag
+            .query(
+                &RagQuery::new(&question)
+                    .with_top_k(top_k)
+                    .with_strict(strict.unwrap_or(false))
+                    .with_include_superseded(include_superseded.unwrap_or(false))
+                    .with_persona(persona)
+                    .with_scope(scope),
+                &user,
+            )
+            .await?;
+        crate::content_gaps::log_if_gap(
+            state,
+            &question,
+            response.citations.len(),
+            response.confidence,
+            caller.user_id,
+        );
+        crate::faq::record_query(state, &question, &response, caller.user_id);
+
+        Ok(Answer {
+            answer: response.answer,
+            citations: response
+                .citations
+                .into_iter()
+                .map(|c| AnswerCitation {
+                    index: c.index as i32,
+                    document_id: c.source.document_id,
+                    document_title: c.document_title,
+                    page: c.source.page.map(|p| p as i32),
+                    section: c.source.section,
+                    text: c.text,
+                    stale_reasons: c
+                        .freshness
+                        .iter()
+                        .flat_map(|f| f.reasons.iter().map(|r| r.as_str().to_string()))
+                        .collect(),
+                    superseded_by: c.freshness.and_then(|f| f.superseded_by),
+                })
+                .collect(),
+            confidence: response.confidence,
+            processing_time_ms: response.processing_time_ms,
+            suggestions: response.suggestions,
+            format: response.format.as_str().to_string(),
+            structured_answer: response
+                .structured_answer
+                .and_then(|a| serde_json::to_value(a).ok())
+                .map(Json),
+            warnings: response.warnings,
+            model: response.model,
+            searched_spaces: response.searched_spaces,
+        })
+    }
+
+    /// Approve a pending extraction
+    async fn approve_extraction(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        notes: Option<String>,
+    ) -> async_graphql::Result<VerificationResult> {
+        let state = app_state(ctx)?.clone();
+        let user = current_user(ctx)?.clone();
+        let id = Uuid::parse_str(&id)?;
+
+        verify::approve_extraction(
+            State(state),
+            Extension(user),
+            Path(id),
+            axum::Json(VerifyAction {
+                correction: None,
+                notes,
+            }),
+        )
+        .await
+        .map_err(|e| async_graphql::Error::new(format!("{e:?}")))?;
+
+        Ok(VerificationResult {
+            id,
+            status: "approved".to_string(),
+        })
+    }
+
+    /// Reject a pending extraction
+    async fn reject_extraction(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        reason: String,
+        notes: Option<String>,
+    ) -> async_graphql::Result<VerificationResult> {
+        let state = app_state(ctx)?.clone();
+        let user = current_user(ctx)?.clone();
+        let id = Uuid::parse_str(&id)?;
+
+        verify::reject_extraction(
+            State(state),
+            Extension(user),
+            Path(id),
+            axum::Json(RejectAction { reason, notes }),
+        )
+        .await
+        .map_err(|e| async_graphql::Error::new(format!("{e:?}")))?;
+
+        Ok(VerificationResult {
+            id,
+            status: "rejected".to_string(),
+        })
+    }
+}
+
+/// Subscription root
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Stream answer tokens for a question as they are generated
+    async fn query_stream(
+        &self,
+        ctx: &Context<'_>,
+        question: String,
+        top_k: Option<i32>,
+    ) -> async_graphql::Result<impl Stream<Item = String>> {
+        let state = app_state(ctx)?.clone();
+        let caller = current_user(ctx)?;
+        let persona = state.prompts.select(caller.department.as_deref());
+
+        if question.trim().is_empty() {
+            return Err(async_graphql::Error::new("Question cannot be empty"));
+        }
+
+        state.increment_requests();
+        let top_k = top_k.unwrap_or(5).clamp(1, MAX_PAGE_SIZE) as usize;
+        let tokens = stream_answer(
+            &state,
+            &caller.to_acl_user(),
+            &question,
+            top_k,
+            None,
+            persona.as_ref(),
+        )
+        .await;
+
+        Ok(tokens.filter_map(|chunk| async move {
+            match chunk {
+                Ok(text) => Some(text),
+                Err(e) => {
+                    tracing::error!("GraphQL stream chunk error: {}", e);
+                    None
+                }
+            }
+        }))
+    }
+}
+
+// ============================================================================
+// HTTP handlers
+// ============================================================================
+
+/// Execute a GraphQL query or mutation
+pub async fn graphql_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    axum::Json(request): axum::Json<async_graphql::Request>,
+) -> impl IntoResponse {
+    let request = request.data(state).data(user);
+    axum::Json(schema().execute(request).await)
+}
+
+/// Execute a GraphQL subscription, delivering each response as an SSE event
+pub async fn graphql_stream_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    axum::Json(request): axum::Json<async_graphql::Request>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let request = request.data(state).data(user);
+    let responses = schema().execute_stream(request).map(|response| {
+        let event = Event::default()
+            .event("next")
+            .json_data(&response)
+            .unwrap_or_else(|_| Event::default().event("error").data("serialization failed"));
+        Ok(event)
+    });
+
+    Sse::new(responses).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text("keep-alive"),
+    )
+}
+
+/// Schema definition language for the GraphQL API
+pub async fn graphql_sdl() -> impl IntoResponse {
+    schema().sdl()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_exposes_roots() {
+        let sdl = build_schema().sdl();
+        assert!(sdl.contains("documents("));
+        assert!(sdl.contains("pendingExtractions("));
+        assert!(sdl.contains("approveExtraction("));
+        assert!(sdl.contains("queryStream("));
+    }
+
+    #[test]
+    fn test_clamp_limit() {
+        assert_eq!(clamp_limit(None, 20), 20);
+        assert_eq!(clamp_limit(Some(0), 20), 1);
+        assert_eq!(clamp_limit(Some(1000), 20), MAX_PAGE_SIZE as i64);
+    }
+
+    #[tokio::test]
+    async fn test_request_without_context_fails() {
+        let request = async_graphql::Request::new("{ documents { id } }");
+        let response = build_schema().execute(request).await;
+        assert!(!response.errors.is_empty());
+    }
+}
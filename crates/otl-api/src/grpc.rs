@@ -0,0 +1,605 @@
+//! gRPC services (feature `grpc`)
+//!
+//! Tonic implementations of the Ingest, Query and Graph services defined in
+//! `proto/otl.proto`. They share `AppState` — and therefore the RAG
+//! orchestrator, vector backend and graph store — with the REST layer, and
+//! authenticate callers with the same JWT access tokens passed in the
+//! `authorization` metadata entry.
+//!
+//! Author: hephaex@gmail.com
+
+// `tonic::Status` is the error type mandated by the generated service traits.
+#![allow(clippy::result_large_err)]
+
+use crate::auth::jwt::{validate_access_token, JwtConfig};
+use crate::auth::middleware::{is_token_revoked, is_user_token_revoked, AuthenticatedUser};
+use crate::error::{AppError, ErrorCode};
+use crate::handlers::documents::{
+    chunk_document_text, extract_document_text, ingestion_chunk_config, parse_access_level,
+    record_ingest_report, record_upload, replace_chunks, store_structure_graph, store_vector_ids,
+    UploadedFile,
+};
+use crate::handlers::graph::extract_entity_name;
+use crate::handlers::query::stream_answer;
+use crate::lineage::{DocumentLineage, ParserLineage};
+use crate::state::AppState;
+use futures::stream::{self, Stream, StreamExt};
+use otl_core::RagQuery;
+use otl_graph::GraphStore;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+/// Generated protobuf types and service traits
+#[allow(clippy::all)]
+pub mod pb {
+    tonic::include_proto!("otl.v1");
+}
+
+use pb::graph_service_server::{GraphService, GraphServiceServer};
+use pb::ingest_service_server::{IngestService, IngestServiceServer};
+use pb::query_service_server::{QueryService, QueryServiceServer};
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Maximum number of entities returned by streaming graph calls
+const MAX_ENTITY_STREAM: u32 = 1000;
+
+// ============================================================================
+// Authentication
+// ============================================================================
+
+/// Validate the bearer token carried in request metadata
+///
+/// Installed as a tonic interceptor on every service; the authenticated user
+/// is stored in the request extensions for the handlers.
+pub fn auth_interceptor(mut request: Request<()>) -> Result<Request<()>, Status> {
+    let token = request
+        .metadata()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or_else(|| Status::unauthenticated("Missing or invalid authorization metadata"))?;
+
+    let claims = validate_access_token(&JwtConfig::from_env(), token)
+        .map_err(|e| Status::unauthenticated(e.to_string()))?;
+    let issued_at = claims.iat;
+    let user = AuthenticatedUser::from(claims);
+
+    // Logout, and forced logouts including deactivation and role changes
+    if is_token_revoked(&user.jti) || is_user_token_revoked(user.user_id, issued_at) {
+        return Err(Status::unauthenticated("Token has been revoked"));
+    }
+
+    request.extensions_mut().insert(user);
+    Ok(request)
+}
+
+fn authenticated_user<T>(request: &Request<T>) -> Result<AuthenticatedUser, Status> {
+    request
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .cloned()
+        .ok_or_else(|| Status::unauthenticated("Authentication required"))
+}
+
+fn parse_uuid(value: &str, field: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(value).map_err(|_| Status::invalid_argument(format!("Invalid {field}")))
+}
+
+impl From<AppError> for Status {
+    fn from(err: AppError) -> Self {
+        match err {
+            AppError::NotFound(msg) => Status::not_found(msg),
+            AppError::BadRequest(msg) => Status::invalid_argument(msg),
+            AppError::Unauthorized => Status::unauthenticated("Authentication required"),
+            AppError::Forbidden(msg) => Status::permission_denied(msg),
+            AppError::Internal(msg) | AppError::Database(msg) => Status::internal(msg),
+            AppError::Coded(code, msg) => match code {
+                ErrorCode::BadRequest | ErrorCode::DocUnreadable => Status::invalid_argument(msg),
+                ErrorCode::Unauthorized | ErrorCode::InvalidToken => Status::unauthenticated(msg),
+                ErrorCode::Forbidden | ErrorCode::AclDenied => Status::permission_denied(msg),
+                ErrorCode::NotFound => Status::not_found(msg),
+                ErrorCode::ReviewLocked => Status::aborted(msg),
+                ErrorCode::LastAdmin
+                | ErrorCode::RestoreWindowExpired
+                | ErrorCode::ShareLinkExpired => Status::failed_precondition(msg),
+                ErrorCode::DocTooLarge => Status::resource_exhausted(msg),
+                ErrorCode::LlmTimeout => Status::deadline_exceeded(msg),
+                ErrorCode::LlmUnavailable
+                | ErrorCode::SearchFailed
+                | ErrorCode::OcrEngineMissing
+                | ErrorCode::ServiceUnavailable => Status::unavailable(msg),
+                ErrorCode::DatabaseError | ErrorCode::ConfigError | ErrorCode::InternalError => {
+                    Status::internal(msg)
+                }
+            },
+        }
+    }
+}
+
+impl From<otl_core::Entity> for pb::Entity {
+    fn from(entity: otl_core::Entity) -> Self {
+        Self {
+            id: entity.id.to_string(),
+            name: extract_entity_name(&entity.properties),
+            properties_json: serde_json::to_string(&entity.properties).unwrap_or_default(),
+            document_id: entity.source.document_id.to_string(),
+            class: entity.class,
+        }
+    }
+}
+
+// ============================================================================
+// Ingest service
+// ============================================================================
+
+/// Document ingestion over gRPC
+pub struct IngestServiceImpl {
+    state: Arc<AppState>,
+}
+
+#[tonic::async_trait]
+impl IngestService for IngestServiceImpl {
+    type IngestDocumentStream = ResponseStream<pb::IngestProgress>;
+
+    async fn ingest_document(
+        &self,
+        request: Request<pb::IngestRequest>,
+    ) -> Result<Response<Self::IngestDocumentStream>, Status> {
+        let user = authenticated_user(&request)?;
+        if !user.is_editor_or_higher() {
+            return Err(Status::permission_denied("Editor role required to ingest"));
+        }
+
+        let req = request.into_inner();
+        if req.title.trim().is_empty() {
+            return Err(Status::invalid_argument("Title cannot be empty"));
+        }
+        if req.content.is_empty() {
+            return Err(Status::invalid_argument("Content cannot be empty"));
+        }
+
+        self.state.increment_requests();
+
+        let text = extract_document_text(req.content.clone(), &req.file_type)?;
+        let chunks = chunk_document_text(&text);
+        let total_chunks = chunks.len() as u32;
+        let doc_id = Uuid::new_v4();
+        let file = UploadedFile {
+            id: doc_id,
+            title: &req.title,
+            file_type: &req.file_type,
+            access_level: req.access_level.as_deref(),
+            department: req.department.as_deref(),
+            owner_id: Some(user.user_id.to_string()),
+        };
+        record_upload(&self.state, &file, req.content).await?;
+        record_ingest_report(&self.state, doc_id, &chunks).await;
+        let access_level = parse_access_level(req.access_level.as_deref().unwrap_or("internal"));
+        crate::classification::classify_upload(
+            &self.state,
+            doc_id,
+            &text,
+            access_level,
+            req.department.as_deref(),
+        )
+        .await;
+        replace_chunks(&self.state, doc_id, &chunks, access_level).await?;
+        crate::dedup::link_chunks(&self.state, doc_id).await;
+        let skipped = Arc::new(crate::dedup::skipped_chunks(&self.state, doc_id).await?);
+
+        tracing::info!(
+            "gRPC ingest: {} (id: {}, {} chunks)",
+            req.title,
+            doc_id,
+            total_chunks
+        );
+
+        store_structure_graph(
+            &self.state,
+            doc_id,
+            &req.title,
+            &chunks,
+            &Default::default(),
+        )
+        .await;
+
+        let backend = self
+            .state
+            .vector_backend
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| Status::unavailable("Vector store not available for indexing"))?;
+
+        let lineage = DocumentLineage::new(
+            doc_id,
+            ParserLineage::for_file_type(&req.file_type),
+            &ingestion_chunk_config(),
+            &chunks,
+        );
+        crate::lineage::record(&self.state.db_pool, &lineage).await;
+
+        // Chunks are indexed one at a time; the lineage and the stored chunks
+        // are completed with their vector IDs once the last one is done.
+        // Duplicates of embedded chunks are reported with their canonical
+        // chunk instead of being indexed.
+        let vector_ids = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let state = self.state.clone();
+        let progress = stream::iter(chunks.into_iter().enumerate()).then(move |(index, chunk)| {
+            let backend = backend.clone();
+            let vector_ids = vector_ids.clone();
+            let state = state.clone();
+            let lineage = lineage.clone();
+            let duplicate_of = skipped.get(&(index as u32)).copied();
+            async move {
+                let result = match duplicate_of {
+                    Some(_) => Ok(None),
+                    None => backend
+                        .index_text(doc_id, index as u32, &chunk)
+                        .await
+                        .map(Some),
+                };
+                if let Ok(Some(vector_id)) = &result {
+                    vector_ids
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(index, vector_id.to_string());
+                }
+                if index as u32 + 1 == total_chunks {
+                    let vector_ids = vector_ids.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                    if let Err(e) = store_vector_ids(&state, doc_id, &vector_ids).await {
+                        tracing::warn!("Failed to record vector IDs of document {doc_id}: {e:?}");
+                    }
+                    let lineage =
+                        lineage.with_indexed_chunks(&state.config.llm.embedding_model, &vector_ids);
+                    crate::lineage::record(&state.db_pool, &lineage).await;
+                }
+                Ok(pb::IngestProgress {
+                    document_id: doc_id.to_string(),
+                    chunk_index: index as u32,
+                    total_chunks,
+                    indexed: matches!(result, Ok(Some(_))),
+                    error: result.err().map(|e| e.to_string()),
+                    duplicate_of: duplicate_of.map(|id| id.to_string()),
+                })
+            }
+        });
+
+        Ok(Response::new(Box::pin(progress)))
+    }
+}
+
+// ============================================================================
+// Query service
+// ============================================================================
+
+/// RAG queries over gRPC
+pub struct QueryServiceImpl {
+    state: Arc<AppState>,
+}
+
+#[tonic::async_trait]
+impl QueryService for QueryServiceImpl {
+    type QueryStreamStream = ResponseStream<pb::QueryChunk>;
+
+    async fn query(
+        &self,
+        request: Request<pb::QueryRequest>,
+    ) -> Result<Response<pb::QueryResponse>, Status> {
+        let caller = authenticated_user(&request)?;
+        let user = caller.to_acl_user();
+        let req = request.into_inner();
+
+        if req.question.trim().is_empty() {
+            return Err(Status::invalid_argument("Question cannot be empty"));
+        }
+
+        self.state.increment_requests();
+
+        let rag = self
+            .state
+            .get_rag()
+            .await
+            .ok_or_else(|| Status::unavailable("RAG pipeline not initialized"))?;
+
+        let top_k = if req.top_k == 0 {
+            5
+        } else {
+            req.top_k as usize
+        };
+        let (scope, persona) = self
+            .state
+            .scope_and_persona(
+                req.space.as_deref(),
+                &req.question,
+                caller.department.as_deref(),
+            )
+            .map_err(AppError::from)?;
+        let response = rag
+            .query(
+                &RagQuery::new(&req.question)
+                    .with_top_k(top_k)
+                    .with_strict(req.strict)
+                    .with_include_superseded(req.include_superseded)
+                    .with_persona(persona)
+                    .with_scope(scope),
+                &user,
+            )
+            .await
+            .map_err(|e| Status::internal(format!("RAG query failed: {e}")))?;
+        crate::content_gaps::log_if_gap(
+            &self.state,
+            &req.question,
+            response.citations.len(),
+            response.confidence,
+            caller.user_id,
+        );
+        crate::faq::record_query(&self.state, &req.question, &response, caller.user_id);
+
+        Ok(Response::new(pb::QueryResponse {
+            answer: response.answer,
+            citations: response
+                .citations
+                .into_iter()
+                .map(|c| pb::Citation {
+                    index: c.index,
+                    document_id: c.source.document_id.to_string(),
+                    document_title: c.document_title,
+                    page: c.source.page,
+                    section: c.source.section,
+                    text: c.text,
+                    stale_reasons: c
+                        .freshness
+                        .iter()
+                        .flat_map(|f| f.reasons.iter().map(|r| r.as_str().to_string()))
+                        .collect(),
+                    superseded_by: c
+                        .freshness
+                        .and_then(|f| f.superseded_by)
+                        .map(|id| id.to_string()),
+                })
+                .collect(),
+            confidence: response.confidence,
+            processing_time_ms: response.processing_time_ms,
+            suggestions: response.suggestions,
+            format: response.format.as_str().to_string(),
+            structured_answer_json: response
+                .structured_answer
+                .and_then(|a| serde_json::to_string(&a).ok()),
+            warnings: response.warnings,
+            model: response.model,
+            searched_spaces: response.searched_spaces,
+        }))
+    }
+
+    async fn query_stream(
+        &self,
+        request: Request<pb::QueryRequest>,
+    ) -> Result<Response<Self::QueryStreamStream>, Status> {
+        let caller = authenticated_user(&request)?;
+        let req = request.into_inner();
+
+        if req.question.trim().is_empty() {
+            return Err(Status::invalid_argument("Question cannot be empty"));
+        }
+
+        self.state.increment_requests();
+
+        let top_k = if req.top_k == 0 {
+            5
+        } else {
+            req.top_k as usize
+        };
+        let persona = self.state.prompts.select(caller.department.as_deref());
+        let tokens = stream_answer(
+            &self.state,
+            &caller.to_acl_user(),
+            &req.question,
+            top_k,
+            None,
+            persona.as_ref(),
+        )
+        .await
+        .map(|chunk| chunk.map_err(|e| Status::internal(e.to_string())));
+
+        let chunks = tokens.enumerate().map(|(sequence, token)| {
+            token.map(|text| pb::QueryChunk {
+                text,
+                sequence: sequence as u64,
+            })
+        });
+
+        Ok(Response::new(Box::pin(chunks)))
+    }
+}
+
+// ============================================================================
+// Graph service
+// ============================================================================
+
+/// Knowledge graph access over gRPC
+pub struct GraphServiceImpl {
+    state: Arc<AppState>,
+}
+
+impl GraphServiceImpl {
+    async fn graph_db(&self) -> Result<Arc<otl_graph::SurrealDbStore>, Status> {
+        self.state
+            .graph_db
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| Status::unavailable("Graph database not initialized"))
+    }
+}
+
+fn entity_stream(entities: Vec<otl_core::Entity>) -> ResponseStream<pb::Entity> {
+    Box::pin(stream::iter(
+        entities.into_iter().map(|e| Ok(pb::Entity::from(e))),
+    ))
+}
+
+#[tonic::async_trait]
+impl GraphService for GraphServiceImpl {
+    type ListEntitiesStream = ResponseStream<pb::Entity>;
+    type TraverseStream = ResponseStream<pb::Entity>;
+
+    async fn get_entity(
+        &self,
+        request: Request<pb::EntityRequest>,
+    ) -> Result<Response<pb::Entity>, Status> {
+        authenticated_user(&request)?;
+        let id = parse_uuid(&request.get_ref().id, "entity id")?;
+        self.state.increment_requests();
+
+        let entity = self
+            .graph_db()
+            .await?
+            .get_entity(id)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found(format!("Entity {id} not found")))?;
+
+        Ok(Response::new(entity.into()))
+    }
+
+    async fn list_entities(
+        &self,
+        request: Request<pb::ListEntitiesRequest>,
+    ) -> Result<Response<Self::ListEntitiesStream>, Status> {
+        authenticated_user(&request)?;
+        let req = request.into_inner();
+        self.state.increment_requests();
+
+        let limit = if req.limit == 0 {
+            100
+        } else {
+            req.limit.min(MAX_ENTITY_STREAM)
+        };
+        let entities = self
+            .graph_db()
+            .await?
+            .find_by_class(&req.class, limit as usize)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(entity_stream(entities)))
+    }
+
+    async fn traverse(
+        &self,
+        request: Request<pb::TraverseRequest>,
+    ) -> Result<Response<Self::TraverseStream>, Status> {
+        authenticated_user(&request)?;
+        let start_id = parse_uuid(&request.get_ref().start_id, "start id")?;
+        let depth = request.get_ref().depth.clamp(1, 5);
+        self.state.increment_requests();
+
+        let entities = self
+            .graph_db()
+            .await?
+            .traverse(start_id, depth)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(entity_stream(entities)))
+    }
+}
+
+// ============================================================================
+// Server
+// ============================================================================
+
+/// Build the gRPC router with all services behind the auth interceptor
+pub fn grpc_router(state: Arc<AppState>) -> tonic::transport::server::Router {
+    tonic::transport::Server::builder()
+        .add_service(IngestServiceServer::with_interceptor(
+            IngestServiceImpl {
+                state: state.clone(),
+            },
+            auth_interceptor,
+        ))
+        .add_service(QueryServiceServer::with_interceptor(
+            QueryServiceImpl {
+                state: state.clone(),
+            },
+            auth_interceptor,
+        ))
+        .add_service(GraphServiceServer::with_interceptor(
+            GraphServiceImpl { state },
+            auth_interceptor,
+        ))
+}
+
+/// Serve the gRPC API on the given address
+pub async fn serve(state: Arc<AppState>, addr: SocketAddr) -> anyhow::Result<()> {
+    tracing::info!("OTL gRPC server starting on {}", addr);
+    grpc_router(state).serve(addr).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interceptor_rejects_missing_token() {
+        let status = auth_interceptor(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_interceptor_rejects_forced_logout() {
+        use crate::auth::jwt::generate_access_token;
+        use crate::auth::middleware::revoke_user_tokens;
+
+        let user_id = Uuid::new_v4();
+        let token = generate_access_token(
+            &JwtConfig::from_env(),
+            user_id,
+            "김인사",
+            "kim@example.com",
+            "admin",
+            Some("인사팀"),
+        )
+        .unwrap();
+        let request = || {
+            let mut request = Request::new(());
+            request
+                .metadata_mut()
+                .insert("authorization", format!("Bearer {token}").parse().unwrap());
+            request
+        };
+        assert!(auth_interceptor(request()).is_ok());
+
+        revoke_user_tokens(user_id);
+        let status = auth_interceptor(request()).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_app_error_maps_to_status() {
+        let status = Status::from(AppError::NotFound("doc".to_string()));
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let status = Status::from(AppError::BadRequest("bad".to_string()));
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_entity_conversion() {
+        let source = otl_core::SourceReference::new(Uuid::new_v4());
+        let entity = otl_core::Entity::new("LeaveType", source)
+            .with_property("text", serde_json::json!("연차휴가"));
+
+        let pb_entity = pb::Entity::from(entity);
+        assert_eq!(pb_entity.class, "LeaveType");
+        assert_eq!(pb_entity.name, "연차휴가");
+        assert!(pb_entity.properties_json.contains("연차휴가"));
+    }
+}
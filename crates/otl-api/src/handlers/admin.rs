@@ -0,0 +1,1822 @@
+//! Admin handlers
+//!
+//! Author: hephaex@gmail.com
+
+use crate::audit::{audit_log, extract_ip_address, AuditEvent};
+use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::UserRole;
+use crate::classification::{self, ClassificationReview};
+use crate::content_gaps::{self, GapCluster};
+use crate::embedding_migration::{self, EmbeddingMigration, MigrationRequest};
+use crate::error::{AppError, ErrorCode};
+use crate::faq;
+use crate::forms::{self, StoredTemplate};
+use crate::freshness::{self, FreshnessAlert, FreshnessReport};
+use crate::sessions::{self, Session};
+use crate::slo;
+use crate::state::{analyzer_settings_from_env, AppState};
+use crate::users::{self, AdminUser, PasswordResetTicket, UserChange, UserFilter, UserPage};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use otl_core::calibration::{reliability_curve, CalibrationCurve};
+use otl_core::{
+    AccessLevel, AnalyzerSettings, CalibrationMethod, CalibrationSample, Calibrator, DocumentAcl,
+    DocumentMetadata, FaqEntry, FaqRepository, FaqStatus, FaqStore, GlossaryEntry,
+    GlossaryRepository, GlossaryStatus, GlossaryStore, KnowledgeSpace, MetadataRepository,
+    MetadataStore, Persona, RagQuery, SynonymGroup,
+};
+use otl_extractor::forms::FormTemplate;
+use otl_rag::{CacheBackendKind, CacheStatsReport};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Default number of reliability curve bins
+const DEFAULT_BINS: usize = 10;
+
+/// Maximum number of texts and queries accepted by one cache warm-up
+const MAX_WARM_ITEMS: usize = 500;
+
+/// Query parameters for the calibration report
+#[derive(Debug, Deserialize)]
+pub struct CalibrationQuery {
+    /// Fitting method (platt or isotonic)
+    pub method: Option<String>,
+
+    /// Number of reliability curve bins
+    pub bins: Option<usize>,
+}
+
+/// Calibration of one confidence source
+#[derive(Debug, Serialize)]
+pub struct CalibrationReport {
+    /// Number of human-reviewed samples
+    pub samples: usize,
+
+    /// Calibrator fitted from the samples
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calibrator: Option<Calibrator>,
+
+    /// Why no calibrator could be fitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fit_error: Option<String>,
+
+    /// Reliability of the raw confidences
+    pub raw: CalibrationCurve,
+
+    /// Reliability after applying the fitted calibrator
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calibrated: Option<CalibrationCurve>,
+}
+
+/// Calibration report response
+#[derive(Debug, Serialize)]
+pub struct CalibrationResponse {
+    pub method: CalibrationMethod,
+    pub entities: CalibrationReport,
+    pub relations: CalibrationReport,
+
+    /// Calibrator currently applied to RAG answer confidence
+    pub rag_calibrator: Option<Calibrator>,
+}
+
+/// Fit a calibrator and compare reliability before and after
+pub(crate) fn calibration_report(
+    samples: &[CalibrationSample],
+    method: CalibrationMethod,
+    bins: usize,
+) -> CalibrationReport {
+    let raw = reliability_curve(samples, &Calibrator::Identity, bins);
+    match Calibrator::fit(method, samples) {
+        Ok(calibrator) => CalibrationReport {
+            samples: samples.len(),
+            calibrated: Some(reliability_curve(samples, &calibrator, bins)),
+            calibrator: Some(calibrator),
+            fit_error: None,
+            raw,
+        },
+        Err(e) => CalibrationReport {
+            samples: samples.len(),
+            calibrator: None,
+            fit_error: Some(e.to_string()),
+            raw,
+            calibrated: None,
+        },
+    }
+}
+
+/// Confidence calibration curves fitted from HITL review outcomes
+pub async fn get_calibration(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<CalibrationQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let method = match params.method.as_deref() {
+        Some(m) => m
+            .parse::<CalibrationMethod>()
+            .map_err(|e| AppError::BadRequest(e.to_string()))?,
+        None => CalibrationMethod::default(),
+    };
+    let bins = params.bins.unwrap_or(DEFAULT_BINS).clamp(1, 100);
+
+    #[derive(sqlx::FromRow)]
+    struct ReviewedRow {
+        confidence_score: Option<f32>,
+        status: String,
+        entity_count: i32,
+        relation_count: i32,
+    }
+
+    let rows: Vec<ReviewedRow> = sqlx::query_as(
+        r#"
+        SELECT
+            confidence_score,
+            status::text,
+            jsonb_array_length(extracted_entities) as entity_count,
+            jsonb_array_length(extracted_relations) as relation_count
+        FROM extraction_queue
+        WHERE status IN ('approved', 'rejected')
+        "#,
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch review outcomes: {e}")))?;
+
+    let mut entity_samples = Vec::new();
+    let mut relation_samples = Vec::new();
+    for row in rows {
+        let sample = CalibrationSample::new(
+            row.confidence_score.unwrap_or(0.0),
+            row.status == "approved",
+        );
+        if row.entity_count > 0 {
+            entity_samples.push(sample);
+        }
+        if row.relation_count > 0 {
+            relation_samples.push(sample);
+        }
+    }
+
+    let rag_calibrator = state
+        .get_rag()
+        .await
+        .map(|rag| rag.config().confidence_calibrator.clone());
+
+    Ok(Json(CalibrationResponse {
+        method,
+        entities: calibration_report(&entity_samples, method, bins),
+        relations: calibration_report(&relation_samples, method, bins),
+        rag_calibrator,
+    }))
+}
+
+/// Registered synonym groups
+#[derive(Debug, Serialize)]
+pub struct SynonymListResponse {
+    pub groups: Vec<SynonymGroup>,
+    pub total: usize,
+}
+
+/// List the synonym groups used for query expansion
+pub async fn list_synonyms(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let groups = state.synonyms.groups();
+    Ok(Json(SynonymListResponse {
+        total: groups.len(),
+        groups,
+    }))
+}
+
+/// Add a synonym group, merging it into an existing group with the same
+/// canonical term
+///
+/// Changes apply to subsequent queries immediately and last until restart.
+pub async fn add_synonyms(
+    State(state): State<Arc<AppState>>,
+    Json(group): Json<SynonymGroup>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if group.canonical.trim().is_empty() {
+        return Err(AppError::BadRequest(
+            "canonical term must not be empty".to_string(),
+        ));
+    }
+    if group.aliases.iter().all(|a| a.trim().is_empty()) {
+        return Err(AppError::BadRequest(
+            "at least one alias is required".to_string(),
+        ));
+    }
+
+    let canonical = group.canonical.trim().to_string();
+    state.synonyms.add(group);
+    let merged = state
+        .synonyms
+        .groups()
+        .into_iter()
+        .find(|g| g.canonical == canonical)
+        .ok_or_else(|| AppError::Internal("synonym group was not stored".to_string()))?;
+
+    tracing::info!(
+        "Synonym group '{}' now has {} aliases",
+        merged.canonical,
+        merged.aliases.len()
+    );
+    Ok((StatusCode::CREATED, Json(merged)))
+}
+
+/// Remove a term; removing a canonical term drops its whole group
+pub async fn delete_synonym(
+    State(state): State<Arc<AppState>>,
+    Path(term): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !state.synonyms.remove(&term) {
+        return Err(AppError::NotFound(format!("Synonym '{term}' not found")));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Personas
+// ============================================================================
+
+/// Registered answer personas
+#[derive(Debug, Serialize)]
+pub struct PersonaListResponse {
+    pub personas: Vec<Persona>,
+    pub total: usize,
+}
+
+/// List the answer personas
+pub async fn list_personas(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let personas = state.prompts.personas();
+    Ok(Json(PersonaListResponse {
+        total: personas.len(),
+        personas,
+    }))
+}
+
+/// Create or replace the persona named in the path
+///
+/// Changes apply to subsequent queries immediately and last until restart;
+/// answers cached under the previous persona are not reused.
+pub async fn put_persona(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(persona): Json<Persona>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let persona = Persona {
+        name: name.trim().to_string(),
+        ..persona
+    };
+    if persona.name.is_empty() {
+        return Err(AppError::BadRequest(
+            "persona name must not be empty".to_string(),
+        ));
+    }
+    let has_role = persona
+        .role
+        .as_deref()
+        .is_some_and(|r| !r.trim().is_empty());
+    if !has_role && persona.rules().next().is_none() {
+        return Err(AppError::BadRequest(
+            "a persona needs a role, tone, refusal or source_signature".to_string(),
+        ));
+    }
+
+    let status = match state.prompts.upsert(persona.clone()) {
+        Some(_) => StatusCode::OK,
+        None => StatusCode::CREATED,
+    };
+    tracing::info!(
+        "Persona '{}' stored for {}",
+        persona.name,
+        if persona.is_default() {
+            "users without a matching department".to_string()
+        } else {
+            persona.departments.join(", ")
+        }
+    );
+    Ok((status, Json(persona)))
+}
+
+/// Remove a persona
+pub async fn delete_persona(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !state.prompts.remove(&name) {
+        return Err(AppError::NotFound(format!("Persona '{name}' not found")));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Knowledge spaces
+// ============================================================================
+
+/// Registered knowledge spaces
+#[derive(Debug, Serialize)]
+pub struct SpaceListResponse {
+    pub spaces: Vec<KnowledgeSpace>,
+    pub total: usize,
+}
+
+/// List the knowledge spaces
+pub async fn list_spaces(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let spaces = state.spaces.spaces();
+    Ok(Json(SpaceListResponse {
+        total: spaces.len(),
+        spaces,
+    }))
+}
+
+/// Create or replace the knowledge space named in the path
+///
+/// Changes apply to subsequent queries immediately and last until restart.
+pub async fn put_space(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(space): Json<KnowledgeSpace>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let space = KnowledgeSpace {
+        name: name.trim().to_string(),
+        ..space
+    };
+    if space.name.is_empty()
+        || space
+            .name
+            .eq_ignore_ascii_case(otl_core::space::GLOBAL_SPACE)
+    {
+        return Err(AppError::BadRequest(format!(
+            "space name must not be empty or '{}'",
+            otl_core::space::GLOBAL_SPACE
+        )));
+    }
+    if space.departments.iter().all(|d| d.trim().is_empty()) && !space.include_unassigned {
+        return Err(AppError::BadRequest(
+            "a space needs departments or include_unassigned".to_string(),
+        ));
+    }
+    if let Some(persona) = space.persona.as_deref() {
+        if state.prompts.get(persona).is_none() {
+            return Err(AppError::BadRequest(format!(
+                "persona '{persona}' is not registered"
+            )));
+        }
+    }
+
+    let status = match state.spaces.upsert(space.clone()) {
+        Some(_) => StatusCode::OK,
+        None => StatusCode::CREATED,
+    };
+    tracing::info!(
+        "Knowledge space '{}' stored for {}",
+        space.name,
+        space.departments.join(", ")
+    );
+    Ok((status, Json(space)))
+}
+
+/// Remove a knowledge space
+pub async fn delete_space(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !state.spaces.remove(&name) {
+        return Err(AppError::NotFound(format!(
+            "Knowledge space '{name}' not found"
+        )));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Analyzer settings
+// ============================================================================
+
+/// Current per-language analyzer settings (stopwords, minimum keyword
+/// length, normalization, protected nouns)
+pub async fn get_analyzer_settings(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    Ok(Json(state.analyzer.settings()))
+}
+
+/// Replace the analyzer settings
+///
+/// Query analysis and graph keyword search use the new settings from the
+/// next request; cached query results and answers are dropped because their
+/// keywords may differ. Changes last until restart or reload.
+pub async fn update_analyzer_settings(
+    State(state): State<Arc<AppState>>,
+    Json(settings): Json<AnalyzerSettings>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    apply_analyzer_settings(&state, settings).await;
+    Ok(Json(state.analyzer.settings()))
+}
+
+/// Reload the analyzer settings from `RAG_ANALYZER_CONFIG`
+pub async fn reload_analyzer_settings(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if std::env::var("RAG_ANALYZER_CONFIG").is_err() {
+        return Err(AppError::BadRequest(
+            "RAG_ANALYZER_CONFIG is not set".to_string(),
+        ));
+    }
+    let settings = analyzer_settings_from_env().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    apply_analyzer_settings(&state, settings).await;
+    Ok(Json(state.analyzer.settings()))
+}
+
+async fn apply_analyzer_settings(state: &AppState, settings: AnalyzerSettings) {
+    state.analyzer.update(settings);
+    state.rag_cache.query.clear().await;
+    state.rag_cache.answer.clear().await;
+    tracing::info!("Analyzer settings updated");
+}
+
+// ============================================================================
+// Cache administration
+// ============================================================================
+
+/// Cache statistics
+#[derive(Debug, Serialize)]
+pub struct CacheStatsResponse {
+    /// Storage backend (`memory` or `redis`)
+    pub backend: &'static str,
+    pub caches: Vec<CacheStatsReport>,
+    /// Embeddings held on disk, when persistence is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persisted_embeddings: Option<usize>,
+}
+
+/// Hit/miss statistics of the embedding, query and answer caches
+pub async fn get_cache_stats(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let cache = &state.rag_cache;
+    let backend = match cache.config().backend {
+        CacheBackendKind::Memory => "memory",
+        CacheBackendKind::Redis { .. } => "redis",
+    };
+    Ok(Json(CacheStatsResponse {
+        backend,
+        caches: cache.all_stats(),
+        persisted_embeddings: cache.embedding.store().map(|store| store.len()),
+    }))
+}
+
+/// Cache selected by a clear request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheName {
+    Embedding,
+    Query,
+    Answer,
+    Llm,
+    #[default]
+    All,
+}
+
+/// Request to clear one cache or all of them
+#[derive(Debug, Default, Deserialize)]
+pub struct ClearCacheRequest {
+    #[serde(default)]
+    pub cache: CacheName,
+}
+
+/// Caches that were cleared
+#[derive(Debug, Serialize)]
+pub struct ClearCacheResponse {
+    pub cleared: Vec<CacheName>,
+}
+
+/// Clear a cache; `{"cache": "all"}` (the default) clears every cache,
+/// including persisted embeddings
+pub async fn clear_cache(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ClearCacheRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let cache = &state.rag_cache;
+    let cleared = match request.cache {
+        CacheName::Embedding => {
+            cache.embedding.clear().await;
+            vec![CacheName::Embedding]
+        }
+        CacheName::Query => {
+            cache.query.clear().await;
+            vec![CacheName::Query]
+        }
+        CacheName::Answer => {
+            cache.answer.clear().await;
+            vec![CacheName::Answer]
+        }
+        CacheName::Llm => {
+            cache.llm.clear().await;
+            vec![CacheName::Llm]
+        }
+        CacheName::All => {
+            cache.clear_all().await;
+            vec![
+                CacheName::Embedding,
+                CacheName::Query,
+                CacheName::Answer,
+                CacheName::Llm,
+            ]
+        }
+    };
+
+    tracing::info!("Cleared caches: {:?}", cleared);
+    Ok(Json(ClearCacheResponse { cleared }))
+}
+
+/// Texts to embed and questions to answer ahead of time
+#[derive(Debug, Deserialize)]
+pub struct WarmCacheRequest {
+    /// Texts whose embeddings are precomputed
+    #[serde(default)]
+    pub texts: Vec<String>,
+    /// Common questions answered through the RAG pipeline
+    #[serde(default)]
+    pub queries: Vec<String>,
+}
+
+/// Accepted warm-up work
+#[derive(Debug, Serialize)]
+pub struct WarmCacheResponse {
+    pub texts: usize,
+    pub queries: usize,
+}
+
+/// Precompute embeddings and answers in the background
+///
+/// Questions are answered as the default API user, so they fill the answer
+/// cache for the same contexts regular queries retrieve. Returns 202 once
+/// the work is scheduled.
+pub async fn warm_cache(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<WarmCacheRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let texts: Vec<String> = request
+        .texts
+        .into_iter()
+        .filter(|t| !t.trim().is_empty())
+        .collect();
+    let queries: Vec<String> = request
+        .queries
+        .into_iter()
+        .filter(|q| !q.trim().is_empty())
+        .collect();
+    if texts.is_empty() && queries.is_empty() {
+        return Err(AppError::BadRequest(
+            "texts or queries must not be empty".to_string(),
+        ));
+    }
+    if texts.len() + queries.len() > MAX_WARM_ITEMS {
+        return Err(AppError::BadRequest(format!(
+            "at most {MAX_WARM_ITEMS} texts and queries per request"
+        )));
+    }
+
+    let embedder = match state.vector_backend.read().await.as_ref() {
+        Some(backend) => Some(backend.embedding_client()),
+        None if texts.is_empty() => None,
+        None => {
+            return Err(AppError::coded(
+                ErrorCode::ServiceUnavailable,
+                "Embedding client not initialized",
+            ))
+        }
+    };
+    let rag = match state.get_rag().await {
+        Some(rag) => Some(rag),
+        None if queries.is_empty() => None,
+        None => {
+            return Err(AppError::coded(
+                ErrorCode::ServiceUnavailable,
+                "RAG pipeline not initialized",
+            ))
+        }
+    };
+
+    let response = WarmCacheResponse {
+        texts: texts.len(),
+        queries: queries.len(),
+    };
+    let user = state.get_default_user(None);
+    let cache = state.rag_cache.clone();
+    tokio::spawn(async move {
+        if let Some(embedder) = embedder {
+            let embed = |text: String| {
+                let embedder = embedder.clone();
+                async move { embedder.embed(&text).await }
+            };
+            if let Err(e) = cache.warm_embedding_cache(texts, embed).await {
+                tracing::warn!("Embedding cache warm-up failed: {}", e);
+            }
+        }
+        if let Some(rag) = rag {
+            let total = queries.len();
+            let mut answered = 0;
+            for question in queries {
+                match rag.query(&RagQuery::new(&question), &user).await {
+                    Ok(_) => answered += 1,
+                    Err(e) => tracing::warn!("Warm-up query '{}' failed: {}", question, e),
+                }
+            }
+            tracing::info!("Answer cache warmed with {}/{} queries", answered, total);
+        }
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// Gaps clustered into topics by default
+const DEFAULT_GAP_LIMIT: i64 = 1000;
+
+/// Query parameters for the content gap report
+#[derive(Debug, Deserialize)]
+pub struct ContentGapQuery {
+    /// `open` (default) or `all`
+    pub status: Option<String>,
+
+    /// Only gaps logged in the last N days
+    pub days: Option<u32>,
+
+    /// Most recent gaps to cluster (default 1000, at most 10000)
+    pub limit: Option<i64>,
+
+    /// Keyword overlap (0-1) for two questions to share a topic
+    pub similarity: Option<f32>,
+}
+
+/// Content gaps grouped by topic
+#[derive(Debug, Serialize)]
+pub struct ContentGapsResponse {
+    pub total_gaps: usize,
+    pub open_gaps: usize,
+    pub topics: Vec<GapCluster>,
+}
+
+/// Queries with empty or weak retrieval, grouped by topic
+pub async fn list_content_gaps(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ContentGapQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let include_resolved = match params.status.as_deref() {
+        None | Some("open") => false,
+        Some("all") => true,
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "status must be open or all, got {other}"
+            )))
+        }
+    };
+    let since = params
+        .days
+        .map(|days| chrono::Utc::now() - chrono::Duration::days(i64::from(days)));
+    let limit = params.limit.unwrap_or(DEFAULT_GAP_LIMIT).clamp(1, 10_000);
+    let similarity = params
+        .similarity
+        .unwrap_or(content_gaps::DEFAULT_CLUSTER_SIMILARITY)
+        .clamp(0.0, 1.0);
+
+    let gaps = content_gaps::fetch(&state.db_pool, include_resolved, since, limit)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch content gaps: {e}")))?;
+
+    Ok(Json(ContentGapsResponse {
+        total_gaps: gaps.len(),
+        open_gaps: gaps.iter().filter(|g| g.resolved_at.is_none()).count(),
+        topics: content_gaps::cluster_gaps(&gaps, similarity),
+    }))
+}
+
+/// Request to mark content gaps resolved
+#[derive(Debug, Deserialize)]
+pub struct ResolveContentGapsRequest {
+    /// Gaps to resolve (e.g. the `gap_ids` of a topic)
+    pub gap_ids: Vec<Uuid>,
+
+    /// Documents ingested to cover the gaps
+    #[serde(default)]
+    pub document_ids: Vec<Uuid>,
+
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Number of gaps that were resolved
+#[derive(Debug, Serialize)]
+pub struct ResolveContentGapsResponse {
+    pub resolved: u64,
+}
+
+/// Mark content gaps resolved once covering documents are ingested
+pub async fn resolve_content_gaps(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<ResolveContentGapsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if request.gap_ids.is_empty() {
+        return Err(AppError::BadRequest(
+            "gap_ids must not be empty".to_string(),
+        ));
+    }
+
+    let resolved = content_gaps::resolve(
+        &state.db_pool,
+        &request.gap_ids,
+        user.user_id,
+        &request.document_ids,
+        request.note.as_deref(),
+    )
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to resolve content gaps: {e}")))?;
+
+    tracing::info!("{} resolved {} content gaps", user.email, resolved);
+    Ok(Json(ResolveContentGapsResponse { resolved }))
+}
+
+// ============================================================================
+// Service level objectives
+// ============================================================================
+
+/// Objectives with their event counts and burn rates on this instance
+pub async fn get_slo_status(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+    Ok(Json(state.slo.snapshot()))
+}
+
+/// Prometheus rule file with burn-rate alerts for every objective
+pub async fn get_slo_alert_rules(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/yaml; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"otl-slo-alerts.yml\"",
+            ),
+        ],
+        slo::alert_rules(state.slo.policy()),
+    ))
+}
+
+// ============================================================================
+// Glossary
+// ============================================================================
+
+/// Entries returned by one glossary page by default
+const DEFAULT_GLOSSARY_LIMIT: i64 = 100;
+
+/// Query parameters for listing glossary entries
+#[derive(Debug, Deserialize)]
+pub struct GlossaryListQuery {
+    /// `approved` or `candidate` (all entries when omitted)
+    pub status: Option<String>,
+
+    /// Page size (default 100, at most 1000)
+    pub limit: Option<i64>,
+
+    pub offset: Option<i64>,
+}
+
+/// Glossary entry as created or replaced by an admin
+#[derive(Debug, Deserialize)]
+pub struct GlossaryEntryRequest {
+    pub term: String,
+
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
+    pub definition: String,
+
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// Document cited when the entry answers a question
+    #[serde(default)]
+    pub document_id: Option<Uuid>,
+
+    #[serde(default)]
+    pub access_level: AccessLevel,
+
+    #[serde(default)]
+    pub department: Option<String>,
+
+    #[serde(default)]
+    pub required_roles: Vec<String>,
+
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+
+    /// Defaults to `approved`
+    #[serde(default)]
+    pub status: GlossaryStatus,
+}
+
+impl GlossaryEntryRequest {
+    /// Copy the request onto an entry, keeping its ID and creation time
+    fn apply(self, entry: &mut GlossaryEntry) -> Result<(), AppError> {
+        if self.term.trim().is_empty() || self.definition.trim().is_empty() {
+            return Err(AppError::BadRequest(
+                "term and definition must not be empty".to_string(),
+            ));
+        }
+        entry.term = self.term.trim().to_string();
+        entry.aliases = self.aliases;
+        entry.definition = self.definition.trim().to_string();
+        entry.source = self.source;
+        entry.document_id = self.document_id;
+        entry.acl = DocumentAcl {
+            access_level: self.access_level,
+            owner_id: None,
+            department: self.department,
+            required_roles: self.required_roles,
+            allowed_users: self.allowed_users,
+        };
+        entry.status = self.status;
+        Ok(())
+    }
+}
+
+/// Glossary entries, optionally filtered by review status
+pub async fn list_glossary(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<GlossaryListQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let status = params
+        .status
+        .as_deref()
+        .map(str::parse::<GlossaryStatus>)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_GLOSSARY_LIMIT)
+        .clamp(1, 1000);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let store = GlossaryStore::from_pool(state.db_pool.clone());
+    Ok(Json(store.list(status, limit, offset).await?))
+}
+
+/// Add a glossary entry
+pub async fn create_glossary_entry(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<GlossaryEntryRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let mut entry = GlossaryEntry::new(String::new(), String::new());
+    request.apply(&mut entry)?;
+
+    let store = GlossaryStore::from_pool(state.db_pool.clone());
+    if let Some(existing) = store
+        .find_by_term(&entry.term)
+        .await?
+        .into_iter()
+        .find(|e| e.status == GlossaryStatus::Approved)
+    {
+        return Err(AppError::BadRequest(format!(
+            "'{}' is already defined by entry {}",
+            entry.term, existing.id
+        )));
+    }
+    store.upsert(&entry).await?;
+
+    tracing::info!("{} added glossary entry '{}'", user.email, entry.term);
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/// One glossary entry
+pub async fn get_glossary_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let store = GlossaryStore::from_pool(state.db_pool.clone());
+    let entry = store
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Glossary entry {id} not found")))?;
+    Ok(Json(entry))
+}
+
+/// Replace a glossary entry
+pub async fn update_glossary_entry(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<GlossaryEntryRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let store = GlossaryStore::from_pool(state.db_pool.clone());
+    let mut entry = store
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Glossary entry {id} not found")))?;
+    request.apply(&mut entry)?;
+    store.upsert(&entry).await?;
+    // Cached answers may predate the change
+    state.rag_cache.answer.clear().await;
+
+    tracing::info!("{} updated glossary entry '{}'", user.email, entry.term);
+    Ok(Json(entry))
+}
+
+/// Approve a candidate entry so it answers definitional questions
+pub async fn approve_glossary_entry(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let store = GlossaryStore::from_pool(state.db_pool.clone());
+    let mut entry = store
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Glossary entry {id} not found")))?;
+    entry.status = GlossaryStatus::Approved;
+    store.upsert(&entry).await?;
+    state.rag_cache.answer.clear().await;
+
+    tracing::info!("{} approved glossary entry '{}'", user.email, entry.term);
+    Ok(Json(entry))
+}
+
+/// Delete a glossary entry
+pub async fn delete_glossary_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let store = GlossaryStore::from_pool(state.db_pool.clone());
+    if !store.delete(id).await? {
+        return Err(AppError::NotFound(format!("Glossary entry {id} not found")));
+    }
+    state.rag_cache.answer.clear().await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// FAQ review
+// ============================================================================
+
+/// Entries returned by one FAQ page by default
+const DEFAULT_FAQ_LIMIT: i64 = 100;
+
+/// Query parameters for the FAQ review list
+#[derive(Debug, Deserialize)]
+pub struct FaqReviewQuery {
+    /// `pending`, `approved` or `rejected` (all entries when omitted)
+    pub status: Option<String>,
+
+    /// Page size (default 100, at most 1000)
+    pub limit: Option<i64>,
+
+    pub offset: Option<i64>,
+}
+
+/// FAQ entries awaiting or past review, most asked first
+pub async fn list_faq_entries(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<FaqReviewQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let status = params
+        .status
+        .as_deref()
+        .map(str::parse::<FaqStatus>)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let limit = params.limit.unwrap_or(DEFAULT_FAQ_LIMIT).clamp(1, 1000);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let store = FaqStore::from_pool(state.db_pool.clone());
+    Ok(Json(store.list(status, limit, offset).await?))
+}
+
+/// Start a FAQ generation run over the query history
+///
+/// The run continues in the background; its report is logged.
+pub async fn generate_faq(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if state.get_rag().await.is_none() {
+        return Err(AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "RAG pipeline not initialized",
+        ));
+    }
+    let Ok(running) = state.faq_generation.clone().try_lock_owned() else {
+        return Err(AppError::BadRequest(
+            "FAQ generation is already running".to_string(),
+        ));
+    };
+
+    tracing::info!("{} started FAQ generation", user.email);
+    let policy = state.faq.clone();
+    tokio::spawn(async move {
+        let _running = running;
+        match faq::generate_faqs(&state, &policy).await {
+            Ok(report) => tracing::info!("FAQ generation finished: {:?}", report),
+            Err(e) => tracing::warn!("FAQ generation failed: {:?}", e),
+        }
+    });
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Reviewer edits to a FAQ entry
+#[derive(Debug, Deserialize)]
+pub struct FaqEditRequest {
+    #[serde(default)]
+    pub question: Option<String>,
+
+    /// New answer; keep its `[출처: N]` markers in line with the citations
+    #[serde(default)]
+    pub answer: Option<String>,
+}
+
+async fn faq_entry(store: &FaqStore, id: Uuid) -> Result<FaqEntry, AppError> {
+    store
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("FAQ entry {id} not found")))
+}
+
+/// Edit the question or answer of a FAQ entry
+pub async fn update_faq_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<FaqEditRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let store = FaqStore::from_pool(state.db_pool.clone());
+    let mut entry = faq_entry(&store, id).await?;
+    for (field, value) in [
+        (&mut entry.question, request.question),
+        (&mut entry.answer, request.answer),
+    ] {
+        if let Some(value) = value {
+            if value.trim().is_empty() {
+                return Err(AppError::BadRequest(
+                    "question and answer must not be empty".to_string(),
+                ));
+            }
+            *field = value.trim().to_string();
+        }
+    }
+    store.upsert(&entry).await?;
+    if entry.status == FaqStatus::Approved {
+        state.rag_cache.answer.clear().await;
+    }
+    Ok(Json(entry))
+}
+
+/// Record a review decision on a FAQ entry
+async fn review_faq_entry(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    id: Uuid,
+    status: FaqStatus,
+) -> Result<FaqEntry, AppError> {
+    let store = FaqStore::from_pool(state.db_pool.clone());
+    let mut entry = faq_entry(&store, id).await?;
+    entry.status = status;
+    entry.reviewed_by = Some(user.user_id);
+    entry.reviewed_at = Some(chrono::Utc::now());
+    store.upsert(&entry).await?;
+    // Answers cached before the decision may include or miss the entry
+    state.rag_cache.answer.clear().await;
+
+    tracing::info!(
+        "{} marked FAQ '{}' {}",
+        user.email,
+        entry.question,
+        status.as_str()
+    );
+    Ok(entry)
+}
+
+/// Approve a FAQ entry for the FAQ list and retrieval
+pub async fn approve_faq_entry(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    Ok(Json(
+        review_faq_entry(&state, &user, id, FaqStatus::Approved).await?,
+    ))
+}
+
+/// Reject a FAQ entry; its topic is not generated again
+pub async fn reject_faq_entry(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    Ok(Json(
+        review_faq_entry(&state, &user, id, FaqStatus::Rejected).await?,
+    ))
+}
+
+/// Delete a FAQ entry so its topic can be generated again
+pub async fn delete_faq_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let store = FaqStore::from_pool(state.db_pool.clone());
+    if !store.delete(id).await? {
+        return Err(AppError::NotFound(format!("FAQ entry {id} not found")));
+    }
+    state.rag_cache.answer.clear().await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Document freshness
+// ============================================================================
+
+/// Alerts returned by one page by default
+const DEFAULT_ALERT_LIMIT: i64 = 100;
+
+/// Query parameters for the freshness alert list
+#[derive(Debug, Deserialize)]
+pub struct FreshnessAlertQuery {
+    /// `open` (default) or `all`, which includes resolved alerts
+    pub status: Option<String>,
+
+    pub limit: Option<i64>,
+}
+
+/// Stale document alerts, newest first
+pub async fn list_freshness_alerts(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<FreshnessAlertQuery>,
+) -> Result<Json<Vec<FreshnessAlert>>, AppError> {
+    state.increment_requests();
+
+    let include_resolved = match params.status.as_deref() {
+        None | Some("open") => false,
+        Some("all") => true,
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Unknown alert status '{other}' (expected open or all)"
+            )))
+        }
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_ALERT_LIMIT).clamp(1, 1000);
+
+    let alerts = freshness::list_alerts(&state.db_pool, include_resolved, limit)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch freshness alerts: {e}")))?;
+    Ok(Json(alerts))
+}
+
+/// Run a freshness check now instead of waiting for the scheduled one
+pub async fn run_freshness_check(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<FreshnessReport>, AppError> {
+    state.increment_requests();
+
+    tracing::info!("{} started a freshness check", user.email);
+    let report = freshness::check_freshness(&state, &state.freshness).await?;
+    Ok(Json(report))
+}
+
+/// Mark a freshness alert as seen
+pub async fn acknowledge_freshness_alert(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FreshnessAlert>, AppError> {
+    state.increment_requests();
+
+    freshness::acknowledge(&state.db_pool, id, user.user_id)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to acknowledge alert: {e}")))?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Freshness alert {id} not found")))
+}
+
+// ============================================================================
+// Access level suggestions
+// ============================================================================
+
+/// Query parameters for the classification review list
+#[derive(Debug, Deserialize)]
+pub struct ClassificationReviewQuery {
+    /// `open` (default) or `all`, which includes reviewed documents
+    pub status: Option<String>,
+
+    pub limit: Option<i64>,
+}
+
+/// Documents whose declared access level differs from the suggested one,
+/// newest first
+pub async fn list_classification_reviews(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ClassificationReviewQuery>,
+) -> Result<Json<Vec<ClassificationReview>>, AppError> {
+    state.increment_requests();
+
+    let include_reviewed = match params.status.as_deref() {
+        None | Some("open") => false,
+        Some("all") => true,
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Unknown review status '{other}' (expected open or all)"
+            )))
+        }
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_ALERT_LIMIT).clamp(1, 1000);
+
+    let reviews = classification::list_flagged(&state.db_pool, include_reviewed, limit)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch classification reviews: {e}")))?;
+    Ok(Json(reviews))
+}
+
+/// Outcome of a classification review
+#[derive(Debug, Default, Deserialize)]
+pub struct ResolveClassificationRequest {
+    /// What was decided, e.g. that the declared level is correct
+    pub note: Option<String>,
+}
+
+/// Mark a flagged document reviewed
+///
+/// Does not change the document's ACL; the declared level stays in force
+/// unless it is changed separately.
+pub async fn resolve_classification_review(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(document_id): Path<Uuid>,
+    Json(request): Json<ResolveClassificationRequest>,
+) -> Result<Json<ClassificationReview>, AppError> {
+    state.increment_requests();
+
+    let note = request
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty());
+    classification::resolve(&state.db_pool, document_id, user.user_id, note)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to resolve classification review: {e}")))?
+        .map(Json)
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "No classification review for document {document_id}"
+            ))
+        })
+}
+
+/// Freshness dates of a document
+///
+/// Absent fields are left unchanged; `null` or an empty string removes
+/// the field.
+#[derive(Debug, Deserialize)]
+pub struct DocumentFreshnessRequest {
+    /// Date the document takes effect (`YYYY-MM-DD`)
+    #[serde(default, deserialize_with = "deserialize_clearable")]
+    pub effective_date: Option<Option<String>>,
+
+    /// Date by which the document must be reviewed (`YYYY-MM-DD`)
+    #[serde(default, deserialize_with = "deserialize_clearable")]
+    pub review_date: Option<Option<String>>,
+
+    /// Document replacing this one
+    #[serde(default, deserialize_with = "deserialize_clearable")]
+    pub superseded_by: Option<Option<Uuid>>,
+}
+
+/// Tell an absent field (`None`) from an explicit `null` (`Some(None)`)
+fn deserialize_clearable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Apply one date field of a [`DocumentFreshnessRequest`]
+fn set_date_field(
+    doc: &mut DocumentMetadata,
+    key: &str,
+    value: Option<Option<String>>,
+) -> Result<(), AppError> {
+    match value.map(|v| v.filter(|s| !s.trim().is_empty())) {
+        None => {}
+        Some(None) => {
+            doc.extra.remove(key);
+        }
+        Some(Some(value)) => {
+            let date = otl_core::freshness::parse_date(&value).ok_or_else(|| {
+                AppError::BadRequest(format!("{key} must be a YYYY-MM-DD date, got '{value}'"))
+            })?;
+            doc.extra.insert(
+                key.to_string(),
+                serde_json::Value::String(date.format("%Y-%m-%d").to_string()),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Set the effective date, review date or replacement of a document
+pub async fn update_document_freshness(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<DocumentFreshnessRequest>,
+) -> Result<Json<DocumentMetadata>, AppError> {
+    state.increment_requests();
+
+    let store = MetadataStore::from_pool(state.db_pool.clone());
+    let mut doc = store
+        .get_document(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Document {id} not found")))?;
+
+    set_date_field(
+        &mut doc,
+        otl_core::freshness::EFFECTIVE_DATE_KEY,
+        request.effective_date,
+    )?;
+    set_date_field(
+        &mut doc,
+        otl_core::freshness::REVIEW_DATE_KEY,
+        request.review_date,
+    )?;
+    match request.superseded_by {
+        None => {}
+        Some(None) => {
+            doc.extra.remove(otl_core::freshness::SUPERSEDED_BY_KEY);
+        }
+        Some(Some(newer)) => {
+            if newer == id {
+                return Err(AppError::BadRequest(
+                    "A document cannot supersede itself".to_string(),
+                ));
+            }
+            if store.get_document(newer).await?.is_none() {
+                return Err(AppError::NotFound(format!("Document {newer} not found")));
+            }
+            doc.extra.insert(
+                otl_core::freshness::SUPERSEDED_BY_KEY.to_string(),
+                serde_json::Value::String(newer.to_string()),
+            );
+        }
+    }
+
+    store.update_document(&doc).await?;
+    freshness::tag_vectors(&state, &doc).await;
+    // Cached answers carry the old citation warnings
+    state.rag_cache.answer.clear().await;
+    Ok(Json(doc))
+}
+
+// ============================================================================
+// Embedding migrations
+// ============================================================================
+
+/// Request to re-embed the active collection with another model
+#[derive(Debug, Deserialize)]
+pub struct StartEmbeddingMigrationRequest {
+    /// Embedding model of the configured provider
+    pub embedding_model: String,
+
+    /// Target collection (default: active collection name plus the model)
+    #[serde(default)]
+    pub collection: Option<String>,
+
+    /// Switch as soon as the shadow test passes
+    #[serde(default)]
+    pub auto_switch: bool,
+}
+
+/// Embedding migrations, newest first
+pub async fn list_embedding_migrations(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<EmbeddingMigration>>, AppError> {
+    state.increment_requests();
+
+    Ok(Json(
+        embedding_migration::list_migrations(&state.db_pool).await?,
+    ))
+}
+
+/// Start re-embedding into a new collection
+pub async fn start_embedding_migration(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<StartEmbeddingMigrationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    tracing::info!(
+        "{} started an embedding migration to {}",
+        user.email,
+        request.embedding_model
+    );
+    let migration = embedding_migration::start_migration(
+        state.clone(),
+        MigrationRequest {
+            embedding_model: request.embedding_model,
+            collection: request.collection,
+            auto_switch: request.auto_switch,
+        },
+        user.user_id,
+    )
+    .await?;
+    Ok((StatusCode::ACCEPTED, Json(migration)))
+}
+
+/// Progress of an embedding migration
+pub async fn get_embedding_migration(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EmbeddingMigration>, AppError> {
+    state.increment_requests();
+
+    Ok(Json(
+        embedding_migration::get_migration(&state.db_pool, id).await?,
+    ))
+}
+
+/// Switch options
+#[derive(Debug, Default, Deserialize)]
+pub struct SwitchEmbeddingMigrationRequest {
+    /// Switch despite a failed shadow test or missing points
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Serve vector search from a ready migration's collection
+pub async fn switch_embedding_migration(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    request: Option<Json<SwitchEmbeddingMigrationRequest>>,
+) -> Result<Json<EmbeddingMigration>, AppError> {
+    state.increment_requests();
+
+    let force = request.map(|Json(r)| r.force).unwrap_or(false);
+    let migration = embedding_migration::switch_migration(&state, id, force).await?;
+    tracing::info!(
+        "{} switched vector search to {}",
+        user.email,
+        migration.target_collection
+    );
+    Ok(Json(migration))
+}
+
+// ============================================================================
+// Form templates
+// ============================================================================
+
+/// Form templates, by name
+pub async fn list_form_templates(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<StoredTemplate>>, AppError> {
+    state.increment_requests();
+
+    Ok(Json(forms::list_templates(&state).await?))
+}
+
+/// Create or replace a form template
+///
+/// The template's name must match the path. Fields, patterns and relation
+/// fields are checked against the ontology before the template is saved.
+pub async fn put_form_template(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(name): Path<String>,
+    Json(template): Json<FormTemplate>,
+) -> Result<Json<StoredTemplate>, AppError> {
+    state.increment_requests();
+
+    if template.name != name {
+        return Err(AppError::BadRequest(format!(
+            "Template name '{}' does not match the path '{name}'",
+            template.name
+        )));
+    }
+    let stored = forms::save_template(&state, template, &user.email).await?;
+    tracing::info!("{} saved form template '{name}'", user.email);
+    Ok(Json(stored))
+}
+
+/// Remove a form template
+pub async fn delete_form_template(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !forms::delete_template(&state, &name).await? {
+        return Err(AppError::NotFound(format!(
+            "Form template '{name}' not found"
+        )));
+    }
+    tracing::info!("{} deleted form template '{name}'", user.email);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Users
+// ============================================================================
+
+/// Query parameters for the user list
+#[derive(Debug, Deserialize)]
+pub struct UserListQuery {
+    /// Part of the email or name
+    pub q: Option<String>,
+
+    pub role: Option<UserRole>,
+
+    pub department: Option<String>,
+
+    /// `true` for active accounts only, `false` for deactivated ones
+    pub active: Option<bool>,
+
+    pub page: Option<u32>,
+
+    pub page_size: Option<u32>,
+}
+
+/// Users, newest first
+pub async fn list_users(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<UserListQuery>,
+) -> Result<Json<UserPage>, AppError> {
+    state.increment_requests();
+
+    let filter = UserFilter {
+        search: params.q,
+        role: params.role,
+        department: params.department,
+        is_active: params.active,
+    };
+    let page = users::list_users(
+        &state,
+        &filter,
+        params.page.unwrap_or(1),
+        params.page_size.unwrap_or(20),
+    )
+    .await?;
+    Ok(Json(page))
+}
+
+/// One user
+pub async fn get_user(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AdminUser>, AppError> {
+    state.increment_requests();
+
+    Ok(Json(users::get_user(&state, id).await?))
+}
+
+/// Role and department of a user
+///
+/// Absent fields are left unchanged; `null` or an empty string removes the
+/// department.
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRequest {
+    pub role: Option<UserRole>,
+
+    #[serde(default, deserialize_with = "deserialize_clearable")]
+    pub department: Option<Option<String>>,
+}
+
+/// Change the role or department of a user
+pub async fn update_user(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateUserRequest>,
+) -> Result<Json<AdminUser>, AppError> {
+    state.increment_requests();
+
+    let change = UserChange {
+        role: request.role,
+        department: request.department,
+        is_active: None,
+    };
+    apply_user_change(&state, &user, id, &change, &headers).await
+}
+
+/// Deactivate an account and revoke its sessions and access tokens
+pub async fn deactivate_user(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Json<AdminUser>, AppError> {
+    state.increment_requests();
+
+    let change = UserChange {
+        is_active: Some(false),
+        ..Default::default()
+    };
+    apply_user_change(&state, &user, id, &change, &headers).await
+}
+
+/// Reactivate a deactivated account
+pub async fn activate_user(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Json<AdminUser>, AppError> {
+    state.increment_requests();
+
+    let change = UserChange {
+        is_active: Some(true),
+        ..Default::default()
+    };
+    apply_user_change(&state, &user, id, &change, &headers).await
+}
+
+/// Update a user and audit log what changed
+async fn apply_user_change(
+    state: &AppState,
+    actor: &AuthenticatedUser,
+    id: Uuid,
+    change: &UserChange,
+    headers: &HeaderMap,
+) -> Result<Json<AdminUser>, AppError> {
+    let (before, after) = users::update_user(state, id, change).await?;
+    for event in users::change_events(&before, &after, actor.user_id, extract_ip_address(headers)) {
+        audit_log(&event);
+    }
+    Ok(Json(after))
+}
+
+/// Issue a one-time password reset token for a user
+pub async fn reset_user_password(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Json<PasswordResetTicket>, AppError> {
+    state.increment_requests();
+
+    let (target, ticket) = users::issue_password_reset(&state, id, user.user_id).await?;
+    audit_log(&AuditEvent::PasswordResetIssued {
+        user_id: target.id,
+        email: target.email,
+        requested_by: user.user_id,
+        expires_at: ticket.expires_at,
+        ip_address: extract_ip_address(&headers),
+    });
+    Ok(Json(ticket))
+}
+
+/// Active sessions of a user
+pub async fn list_user_sessions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Session>>, AppError> {
+    state.increment_requests();
+
+    users::get_user(&state, id).await?;
+    Ok(Json(sessions::list_sessions(&state, id).await?))
+}
+
+/// Result of a forced logout
+#[derive(Debug, Serialize)]
+pub struct ForcedLogoutResponse {
+    pub user_id: Uuid,
+    pub revoked_sessions: u64,
+}
+
+/// Sign a user out of all sessions, including issued access tokens
+pub async fn force_logout_user(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Json<ForcedLogoutResponse>, AppError> {
+    state.increment_requests();
+
+    let target = users::get_user(&state, id).await?;
+    let revoked_sessions = sessions::force_logout(&state, id).await?;
+    audit_log(&AuditEvent::ForcedLogout {
+        user_id: target.id,
+        email: target.email,
+        revoked_sessions,
+        forced_by: user.user_id,
+        ip_address: extract_ip_address(&headers),
+    });
+    Ok(Json(ForcedLogoutResponse {
+        user_id: id,
+        revoked_sessions,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibration_report() {
+        let samples: Vec<CalibrationSample> = (0..40)
+            .map(|i| CalibrationSample::new(0.9, i % 2 == 0))
+            .collect();
+
+        let report = calibration_report(&samples, CalibrationMethod::Isotonic, 10);
+        assert_eq!(report.samples, 40);
+        assert!(report.fit_error.is_none());
+        assert!(
+            report.calibrated.unwrap().expected_calibration_error
+                < report.raw.expected_calibration_error
+        );
+
+        let report = calibration_report(&samples[..5], CalibrationMethod::Platt, 10);
+        assert!(report.calibrator.is_none());
+        assert!(report.fit_error.is_some());
+        assert_eq!(report.raw.bins[9].count, 5);
+    }
+
+    #[test]
+    fn test_clear_cache_request_defaults_to_all() {
+        let request: ClearCacheRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(request.cache, CacheName::All);
+        let request: ClearCacheRequest = serde_json::from_str(r#"{"cache":"answer"}"#).unwrap();
+        assert_eq!(request.cache, CacheName::Answer);
+        assert!(serde_json::from_str::<ClearCacheRequest>(r#"{"cache":"bogus"}"#).is_err());
+    }
+}
@@ -7,18 +7,22 @@
 use crate::audit::{audit_log, extract_ip_address, extract_user_agent, AuditEvent};
 use crate::auth::{
     AuthService, AuthenticatedUser, LoginRequest, LogoutRequest, RefreshRequest, RegisterRequest,
+    SessionClient,
 };
 use crate::error::AppError;
+use crate::sessions;
 use crate::state::AppState;
+use crate::users;
 use axum::{
-    extract::State,
-    http::HeaderMap,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use utoipa::ToSchema;
+use uuid::Uuid;
 
 /// Registration response
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -36,6 +40,27 @@ pub struct LogoutResponse {
     pub message: String,
 }
 
+/// Password reset request
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PasswordResetRequest {
+    /// One-time token issued by an admin
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Password reset response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PasswordResetResponse {
+    pub message: String,
+}
+
+/// Token introspection request
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IntrospectRequest {
+    /// Access or refresh token
+    pub token: String,
+}
+
 /// Register a new user account
 ///
 /// Creates a new user with the provided email, password, and profile information.
@@ -118,7 +143,9 @@ pub async fn register_handler(
 /// Login with email and password
 ///
 /// Authenticates a user and returns JWT access and refresh tokens.
-/// Failed login attempts are tracked and account will be locked after 5 failed attempts.
+/// Failed login attempts are tracked and the account is locked after
+/// `AUTH_MAX_LOGIN_ATTEMPTS` (default 5) failures in a row, for longer each
+/// time it keeps failing.
 ///
 /// # Request Body
 ///
@@ -156,7 +183,11 @@ pub async fn login_handler(
     let auth_service = AuthService::new(state.db_pool.clone());
 
     // Attempt login
-    let result = auth_service.login(request).await;
+    let client = SessionClient {
+        ip_address: ip_address.clone(),
+        user_agent: user_agent.clone(),
+    };
+    let result = auth_service.login(request, &client).await;
 
     match result {
         Ok(response) => {
@@ -230,7 +261,11 @@ pub async fn refresh_handler(
     let user_agent = extract_user_agent(&headers);
 
     let auth_service = AuthService::new(state.db_pool.clone());
-    let result = auth_service.refresh(request).await;
+    let client = SessionClient {
+        ip_address: ip_address.clone(),
+        user_agent: user_agent.clone(),
+    };
+    let result = auth_service.refresh(request, &client).await;
 
     match result {
         Ok(response) => {
@@ -350,6 +385,163 @@ pub async fn me_handler(
     Ok(Json(user_info))
 }
 
+/// Set a new password with a reset token
+///
+/// Admins issue the one-time token through
+/// `POST /api/v1/admin/users/:id/password-reset`. The token expires after
+/// 24 hours. A successful reset unlocks the account and signs it out of all
+/// devices.
+///
+/// # Request Body
+///
+/// * `token` - Password reset token
+/// * `new_password` - Must meet security requirements
+///
+/// # Responses
+///
+/// * `200 OK` - Password changed
+/// * `400 Bad Request` - Invalid, used or expired token, or weak password
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password-reset",
+    tag = "auth",
+    request_body = PasswordResetRequest,
+    responses(
+        (status = 200, description = "Password changed", body = PasswordResetResponse),
+        (status = 400, description = "Invalid token or weak password", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    )
+)]
+pub async fn password_reset_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<PasswordResetRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = users::reset_password(&state, &request.token, &request.new_password).await?;
+
+    audit_log(&AuditEvent::PasswordChange {
+        user_id: user.id,
+        email: user.email,
+        ip_address: extract_ip_address(&headers),
+        user_agent: extract_user_agent(&headers),
+    });
+
+    Ok(Json(PasswordResetResponse {
+        message: "Password changed; sign in with the new password".to_string(),
+    }))
+}
+
+/// List active sessions
+///
+/// Returns the sessions of the authenticated user: one per login, with the
+/// device and IP it was last used from. Refreshing a token keeps its
+/// session.
+///
+/// # Responses
+///
+/// * `200 OK` - Sessions, most recently used first
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/sessions",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Active sessions", body = Vec<crate::sessions::Session>),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_sessions_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(sessions::list_sessions(&state, user.user_id).await?))
+}
+
+/// Revoke a session
+///
+/// Its refresh token stops working. Access tokens already issued to it stay
+/// valid until they expire.
+///
+/// # Responses
+///
+/// * `204 No Content` - Session revoked
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `404 Not Found` - No such active session of the user
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/sessions/{id}",
+    tag = "auth",
+    params(("id" = Uuid, Path, description = "Session ID")),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 404, description = "Session not found", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_session_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    sessions::revoke_session(&state, user.user_id, id).await?;
+
+    audit_log(&AuditEvent::SessionRevoked {
+        user_id: user.user_id,
+        session_id: id,
+        ip_address: extract_ip_address(&headers),
+    });
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Introspect a token
+///
+/// Reports whether an access or refresh token is active and whom it belongs
+/// to, in the manner of RFC 7662. Invalid, expired and revoked tokens only
+/// report `active: false`.
+///
+/// # Request Body
+///
+/// * `token` - Access or refresh token
+///
+/// # Responses
+///
+/// * `200 OK` - Introspection result
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/introspect",
+    tag = "auth",
+    request_body = IntrospectRequest,
+    responses(
+        (status = 200, description = "Introspection result", body = crate::sessions::TokenIntrospection),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn introspect_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<IntrospectRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(sessions::introspect(&state, &request.token).await?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
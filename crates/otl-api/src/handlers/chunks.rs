@@ -0,0 +1,857 @@
+//! Chunk inspection handlers
+//!
+//! Lets operators see how a document was chunked and which chunks sit next
+//! to each other in embedding space when debugging retrieval.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::{AppError, ErrorCode};
+use crate::lineage::DocumentLineage;
+use crate::state::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use otl_core::encryption::{chunk_context, is_sealed_text};
+use otl_core::integrity::{self, ChunkIntegrity, ChunkStatus, StoredChunk};
+use otl_core::{DocumentAcl, OtlError, User};
+use otl_vector::NeighborChunk;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Maximum number of neighbors returned by the similarity endpoint
+const MAX_SIMILAR: usize = 50;
+
+/// Database row for chunk queries
+#[derive(sqlx::FromRow)]
+struct ChunkRow {
+    id: Uuid,
+    document_id: Uuid,
+    chunk_index: i32,
+    content: String,
+    start_offset: Option<i32>,
+    end_offset: Option<i32>,
+    page_number: Option<i32>,
+    section_name: Option<String>,
+    vector_id: Option<String>,
+    corrupted: bool,
+    canonical_chunk_id: Option<Uuid>,
+}
+
+/// Whether a chunk has been embedded into the vector store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingStatus {
+    /// The chunk has a vector in the vector store
+    Embedded,
+    /// The chunk is stored but not (yet) embedded
+    Pending,
+    /// The chunk is not embedded; retrieval finds its canonical copy
+    Duplicate,
+}
+
+impl EmbeddingStatus {
+    fn of(vector_id: Option<&str>, canonical_chunk_id: Option<Uuid>) -> Self {
+        match (vector_id, canonical_chunk_id) {
+            (Some(id), _) if !id.is_empty() => Self::Embedded,
+            (_, Some(_)) => Self::Duplicate,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// Chunk of a document
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChunkInfo {
+    /// Chunk UUID
+    pub id: Uuid,
+
+    /// Position of the chunk within the document
+    #[schema(example = 3)]
+    pub chunk_index: u32,
+
+    /// Chunk text
+    pub content: String,
+
+    /// Character offset where the chunk starts in the document text
+    pub start_offset: Option<u32>,
+
+    /// Character offset where the chunk ends in the document text
+    pub end_offset: Option<u32>,
+
+    /// Page number
+    #[schema(example = 12)]
+    pub page: Option<u32>,
+
+    /// Section heading
+    #[schema(example = "제3장 휴가")]
+    pub section: Option<String>,
+
+    /// Vector store point ID
+    pub vector_id: Option<String>,
+
+    /// Embedding status
+    pub embedding_status: EmbeddingStatus,
+
+    /// Found corrupted by the last integrity check (never cited)
+    pub corrupted: bool,
+
+    /// Earlier chunk this chunk near-duplicates, possibly in another document
+    pub canonical_chunk_id: Option<Uuid>,
+}
+
+impl From<ChunkRow> for ChunkInfo {
+    fn from(row: ChunkRow) -> Self {
+        let to_u32 = |v: Option<i32>| v.and_then(|n| u32::try_from(n).ok());
+        Self {
+            id: row.id,
+            chunk_index: row.chunk_index.max(0) as u32,
+            content: row.content,
+            start_offset: to_u32(row.start_offset),
+            end_offset: to_u32(row.end_offset),
+            page: to_u32(row.page_number),
+            section: row.section_name,
+            embedding_status: EmbeddingStatus::of(row.vector_id.as_deref(), row.canonical_chunk_id),
+            vector_id: row.vector_id,
+            corrupted: row.corrupted,
+            canonical_chunk_id: row.canonical_chunk_id,
+        }
+    }
+}
+
+/// Chunk list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChunkListResponse {
+    /// Document UUID
+    pub document_id: Uuid,
+
+    /// Chunks in document order
+    pub chunks: Vec<ChunkInfo>,
+
+    /// Total number of chunks in the document
+    pub total: u32,
+
+    /// Number of chunks with an embedding
+    pub embedded: u32,
+
+    /// Current page
+    pub page: u32,
+
+    /// Page size
+    pub page_size: u32,
+}
+
+/// Query parameters for chunk listing
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListChunksQuery {
+    /// Page number (1-indexed)
+    #[param(default = 1)]
+    pub page: Option<u32>,
+
+    /// Items per page
+    #[param(default = 50)]
+    pub page_size: Option<u32>,
+}
+
+/// Query parameters for the similarity endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SimilarChunksQuery {
+    /// Number of neighbors to return
+    #[param(default = 10)]
+    pub limit: Option<usize>,
+}
+
+/// Chunk close to the inspected chunk
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SimilarChunk {
+    /// Chunk UUID, when the neighbor is tracked in the metadata store
+    pub chunk_id: Option<Uuid>,
+
+    /// Vector store point ID
+    pub vector_id: String,
+
+    /// Document the neighbor belongs to
+    pub document_id: Uuid,
+
+    /// Position of the neighbor within its document
+    pub chunk_index: Option<u32>,
+
+    /// Neighbor text
+    pub content: String,
+
+    /// Cosine similarity to the inspected chunk
+    #[schema(example = 0.87)]
+    pub score: f32,
+}
+
+/// Similar chunks response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SimilarChunksResponse {
+    /// Inspected chunk
+    pub chunk: ChunkInfo,
+
+    /// Nearest neighbors, most similar first
+    pub neighbors: Vec<SimilarChunk>,
+
+    /// Neighbors hidden because the user cannot access their documents
+    pub hidden: usize,
+}
+
+/// Copy of a chunk's text in some document
+#[derive(Debug, Serialize, ToSchema, sqlx::FromRow)]
+pub struct ChunkCopy {
+    /// Chunk UUID
+    pub chunk_id: Uuid,
+
+    /// Document the copy belongs to
+    pub document_id: Uuid,
+
+    /// Document title
+    #[schema(example = "취업규칙_2026.pdf")]
+    pub title: String,
+
+    /// Position of the copy within its document
+    pub chunk_index: i32,
+
+    /// Whether this copy is the canonical chunk
+    pub canonical: bool,
+}
+
+/// Chunk duplicates response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChunkDuplicatesResponse {
+    /// Inspected chunk
+    pub chunk_id: Uuid,
+
+    /// Canonical chunk of the text, absent if the text is unique
+    pub canonical_chunk_id: Option<Uuid>,
+
+    /// Every copy of the text, canonical first, then oldest document first
+    pub copies: Vec<ChunkCopy>,
+
+    /// Copies hidden because the user cannot access their documents
+    pub hidden: usize,
+}
+
+/// Load a document's ACL and check the user may read it
+pub(crate) async fn authorize_document(
+    state: &AppState,
+    document_id: Uuid,
+    user: &User,
+) -> Result<(), AppError> {
+    let acls = super::documents::fetch_document_acls(state, &[document_id]).await?;
+    let acl = acls
+        .get(&document_id)
+        .ok_or_else(|| AppError::NotFound(format!("Document {document_id} not found")))?;
+    if !acl.can_access(user) {
+        return Err(AppError::coded(
+            ErrorCode::AclDenied,
+            "You don't have permission to access this document",
+        ));
+    }
+    Ok(())
+}
+
+/// Keep the neighbors whose documents the user may read
+///
+/// Documents missing from the metadata store fall back to the ACL stored in
+/// the vector payload.
+pub(crate) fn filter_neighbors_by_acl(
+    neighbors: Vec<NeighborChunk>,
+    document_acls: &HashMap<Uuid, DocumentAcl>,
+    user: &User,
+) -> (Vec<NeighborChunk>, usize) {
+    let total = neighbors.len();
+    let visible: Vec<_> = neighbors
+        .into_iter()
+        .filter(|n| {
+            document_acls
+                .get(&n.result.source.document_id)
+                .unwrap_or(&n.result.acl)
+                .can_access(user)
+        })
+        .collect();
+    let hidden = total - visible.len();
+    (visible, hidden)
+}
+
+/// List the chunks of a document
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/chunks",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID"),
+        ListChunksQuery
+    ),
+    responses(
+        (status = 200, description = "Document chunks", body = ChunkListResponse),
+        (status = 403, description = "Access denied", body = crate::error::ApiError),
+        (status = 404, description = "Document not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn list_document_chunks(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ListChunksQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    authorize_document(&state, id, &user.to_acl_user()).await?;
+
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = params.page_size.unwrap_or(50).clamp(1, 200);
+    let offset = ((page - 1) * page_size) as i64;
+
+    let (total, embedded): (i64, i64) = sqlx::query_as(
+        "SELECT COUNT(*), COUNT(NULLIF(vector_id, '')) FROM document_chunks WHERE document_id = $1",
+    )
+    .bind(id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to count chunks: {e}")))?;
+
+    let mut rows: Vec<ChunkRow> = sqlx::query_as(
+        "SELECT id, document_id, chunk_index, content, start_offset, end_offset, page_number, \
+         section_name, vector_id, corrupted, canonical_chunk_id FROM document_chunks WHERE document_id = $1 \
+         ORDER BY chunk_index LIMIT $2 OFFSET $3",
+    )
+    .bind(id)
+    .bind(page_size as i64)
+    .bind(offset)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch chunks: {e}")))?;
+    state
+        .keyring
+        .open_chunks(
+            &state.db_pool,
+            id,
+            rows.iter_mut()
+                .map(|r| (r.chunk_index.max(0) as u32, &mut r.content)),
+        )
+        .await?;
+
+    Ok(Json(ChunkListResponse {
+        document_id: id,
+        chunks: rows.into_iter().map(ChunkInfo::from).collect(),
+        total: total as u32,
+        embedded: embedded as u32,
+        page,
+        page_size,
+    }))
+}
+
+/// Find the chunks nearest to a chunk in embedding space
+#[utoipa::path(
+    get,
+    path = "/api/v1/chunks/{id}/similar",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Chunk UUID"),
+        SimilarChunksQuery
+    ),
+    responses(
+        (status = 200, description = "Nearest neighbors", body = SimilarChunksResponse),
+        (status = 400, description = "Chunk has no embedding", body = crate::error::ApiError),
+        (status = 403, description = "Access denied", body = crate::error::ApiError),
+        (status = 404, description = "Chunk not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn similar_chunks(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<SimilarChunksQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let mut row: ChunkRow = sqlx::query_as(
+        "SELECT id, document_id, chunk_index, content, start_offset, end_offset, page_number, \
+         section_name, vector_id, corrupted, canonical_chunk_id FROM document_chunks WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch chunk: {e}")))?
+    .ok_or_else(|| AppError::NotFound(format!("Chunk {id} not found")))?;
+
+    let acl_user = user.to_acl_user();
+    authorize_document(&state, row.document_id, &acl_user).await?;
+    state
+        .keyring
+        .open_chunks(
+            &state.db_pool,
+            row.document_id,
+            [(row.chunk_index.max(0) as u32, &mut row.content)],
+        )
+        .await?;
+
+    let chunk = ChunkInfo::from(row);
+    let vector_id = match (&chunk.vector_id, chunk.embedding_status) {
+        (Some(vector_id), EmbeddingStatus::Embedded) => vector_id.clone(),
+        _ => return Err(AppError::BadRequest(format!("Chunk {id} has no embedding"))),
+    };
+
+    let backend = state.vector_backend.read().await.clone().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Vector store not initialized",
+        )
+    })?;
+
+    let limit = params.limit.unwrap_or(10).clamp(1, MAX_SIMILAR);
+    let neighbors = backend
+        .neighbors(&vector_id, limit)
+        .await
+        .map_err(AppError::from)?;
+
+    let mut document_ids: Vec<Uuid> = neighbors
+        .iter()
+        .map(|n| n.result.source.document_id)
+        .collect();
+    document_ids.sort();
+    document_ids.dedup();
+    let document_acls = super::documents::fetch_document_acls(&state, &document_ids).await?;
+    let (neighbors, hidden) = filter_neighbors_by_acl(neighbors, &document_acls, &acl_user);
+
+    let vector_ids: Vec<String> = neighbors.iter().map(|n| n.vector_id.clone()).collect();
+    let chunk_ids: HashMap<String, Uuid> = sqlx::query_as::<_, (String, Uuid)>(
+        "SELECT vector_id, id FROM document_chunks WHERE vector_id = ANY($1)",
+    )
+    .bind(&vector_ids)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to resolve neighbor chunks: {e}")))?
+    .into_iter()
+    .collect();
+
+    let neighbors = neighbors
+        .into_iter()
+        .map(|n| SimilarChunk {
+            chunk_id: chunk_ids.get(&n.vector_id).copied(),
+            document_id: n.result.source.document_id,
+            chunk_index: n.chunk_index,
+            content: n.result.content,
+            score: n.result.score,
+            vector_id: n.vector_id,
+        })
+        .collect();
+
+    Ok(Json(SimilarChunksResponse {
+        chunk,
+        neighbors,
+        hidden,
+    }))
+}
+
+/// List the documents a chunk's text appears in
+///
+/// Near-duplicate chunks (boilerplate, repeated clauses) are linked to one
+/// canonical chunk when they are ingested; duplicates may not be embedded
+/// themselves, so a citation of the canonical chunk stands for every copy.
+#[utoipa::path(
+    get,
+    path = "/api/v1/chunks/{id}/duplicates",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Chunk UUID")
+    ),
+    responses(
+        (status = 200, description = "Copies of the chunk", body = ChunkDuplicatesResponse),
+        (status = 403, description = "Access denied", body = crate::error::ApiError),
+        (status = 404, description = "Chunk not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn chunk_duplicates(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let (document_id, canonical): (Uuid, Option<Uuid>) =
+        sqlx::query_as("SELECT document_id, canonical_chunk_id FROM document_chunks WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch chunk: {e}")))?
+            .ok_or_else(|| AppError::NotFound(format!("Chunk {id} not found")))?;
+
+    let acl_user = user.to_acl_user();
+    authorize_document(&state, document_id, &acl_user).await?;
+
+    let root = canonical.unwrap_or(id);
+    let mut copies: Vec<ChunkCopy> = sqlx::query_as(
+        "SELECT c.id AS chunk_id, c.document_id, d.title, c.chunk_index, \
+         c.canonical_chunk_id IS NULL AS canonical \
+         FROM document_chunks c JOIN documents d ON d.id = c.document_id \
+         WHERE (c.id = $1 OR c.canonical_chunk_id = $1) AND d.deleted_at IS NULL \
+         ORDER BY c.canonical_chunk_id IS NOT NULL, d.created_at, c.chunk_index",
+    )
+    .bind(root)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch duplicate chunks: {e}")))?;
+    let canonical_chunk_id = (copies.len() > 1).then_some(root);
+
+    let mut document_ids: Vec<Uuid> = copies.iter().map(|c| c.document_id).collect();
+    document_ids.sort();
+    document_ids.dedup();
+    let document_acls = super::documents::fetch_document_acls(&state, &document_ids).await?;
+    let total = copies.len();
+    copies.retain(|c| {
+        document_acls
+            .get(&c.document_id)
+            .is_some_and(|acl| acl.can_access(&acl_user))
+    });
+
+    Ok(Json(ChunkDuplicatesResponse {
+        chunk_id: id,
+        canonical_chunk_id,
+        hidden: total - copies.len(),
+        copies,
+    }))
+}
+
+// ============================================================================
+// Integrity
+// ============================================================================
+
+/// Content integrity of a document's chunks
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IntegrityReport {
+    /// Document UUID
+    pub document_id: Uuid,
+
+    /// Whether chunks were re-derived from the original document
+    pub original_checked: bool,
+
+    /// Why the original could not be used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_error: Option<String>,
+
+    /// Chunks checked (stored or derived from the original)
+    pub checked: usize,
+
+    /// Chunks matching every reference hash
+    pub ok: usize,
+
+    /// Stored chunks whose text no longer matches
+    pub corrupted: usize,
+
+    /// Chunks of the original that are not stored
+    pub missing: usize,
+
+    /// Chunks with neither a recorded hash nor an original to compare with
+    pub unverified: usize,
+
+    /// Chunks that are not ok, by index
+    #[schema(value_type = Vec<Object>)]
+    pub mismatches: Vec<ChunkIntegrity>,
+
+    /// When the check ran
+    pub checked_at: DateTime<Utc>,
+}
+
+impl IntegrityReport {
+    fn new(document_id: Uuid, chunks: Vec<ChunkIntegrity>, original_error: Option<String>) -> Self {
+        let count = |status| chunks.iter().filter(|c| c.status == status).count();
+        Self {
+            document_id,
+            original_checked: original_error.is_none(),
+            original_error,
+            checked: chunks.len(),
+            ok: count(ChunkStatus::Ok),
+            corrupted: count(ChunkStatus::Corrupted),
+            missing: count(ChunkStatus::Missing),
+            unverified: count(ChunkStatus::Unverified),
+            checked_at: Utc::now(),
+            mismatches: chunks
+                .into_iter()
+                .filter(|c| c.status != ChunkStatus::Ok)
+                .collect(),
+        }
+    }
+}
+
+/// Verify that a document's stored chunks still match the original
+///
+/// Hashes the stored chunk text and compares it with the hash recorded when
+/// the chunk was written (or in the document's lineage) and with the chunks
+/// re-derived from the original file using the chunker settings recorded in
+/// the lineage. Corrupted chunks are flagged so that answers no longer cite
+/// them; a later check that finds them intact clears the flag.
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/integrity",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    responses(
+        (status = 200, description = "Integrity report", body = IntegrityReport),
+        (status = 403, description = "Access denied", body = crate::error::ApiError),
+        (status = 404, description = "Document not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn check_document_integrity(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    authorize_document(&state, id, &user.to_acl_user()).await?;
+
+    let rows: Vec<(i32, String, Option<String>)> = sqlx::query_as(
+        "SELECT chunk_index, content, content_hash FROM document_chunks \
+         WHERE document_id = $1 ORDER BY chunk_index",
+    )
+    .bind(id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch chunks: {e}")))?;
+
+    let lineage = crate::lineage::fetch(&state.db_pool, id).await?;
+    let recorded: HashMap<u32, String> = lineage
+        .iter()
+        .flat_map(|l| &l.chunks)
+        .map(|c| (c.chunk_index, c.content_hash.clone()))
+        .collect();
+    // Sealed chunks that fail to open were tampered with; keeping them
+    // sealed makes their hash mismatch
+    let data_key = if rows.iter().any(|(_, content, _)| is_sealed_text(content)) {
+        state.keyring.document_key(&state.db_pool, id).await?
+    } else {
+        None
+    };
+    let stored: Vec<StoredChunk> = rows
+        .into_iter()
+        .map(|(index, content, hash)| {
+            let chunk_index = index.max(0) as u32;
+            let content = match &data_key {
+                Some(key) if is_sealed_text(&content) => key
+                    .open_text(&content, &chunk_context(id, chunk_index))
+                    .unwrap_or(content),
+                _ => content,
+            };
+            StoredChunk {
+                chunk_index,
+                content,
+                recorded_hash: hash.or_else(|| recorded.get(&chunk_index).cloned()),
+            }
+        })
+        .collect();
+
+    let original = original_chunks(&state, id, lineage.as_ref()).await;
+    let chunks = integrity::check_chunks(&stored, original.as_deref().ok());
+    record_integrity(&state, id, &chunks).await?;
+
+    let report = IntegrityReport::new(id, chunks, original.err());
+    if report.corrupted > 0 {
+        tracing::warn!(
+            "Integrity check of document {id}: {} of {} chunks corrupted",
+            report.corrupted,
+            report.checked
+        );
+    }
+    Ok(Json(report))
+}
+
+/// Re-derive a document's chunks from its original file
+///
+/// Needs the chunker settings recorded in the document's lineage, since
+/// re-chunking with other settings would not reproduce the stored chunks.
+async fn original_chunks(
+    state: &AppState,
+    document_id: Uuid,
+    lineage: Option<&DocumentLineage>,
+) -> Result<Vec<String>, String> {
+    let lineage = lineage.ok_or("No lineage recorded; chunker settings are unknown")?;
+    let (file_path, file_type): (String, String) =
+        sqlx::query_as("SELECT file_path, file_type::text FROM documents WHERE id = $1")
+            .bind(document_id)
+            .fetch_one(&state.db_pool)
+            .await
+            .map_err(|e| format!("Failed to fetch document: {e}"))?;
+
+    // Documents ingested before originals were kept in the blob store may
+    // still have theirs on local disk
+    let bytes = match super::documents::read_original(state, document_id, &file_path).await {
+        Ok(bytes) => bytes,
+        Err(OtlError::NotFound(_) | OtlError::ValidationError(_)) => tokio::fs::read(&file_path)
+            .await
+            .map_err(|e| format!("Original {file_path} is not readable: {e}"))?,
+        Err(e) => return Err(format!("Original {file_path} is not readable: {e}")),
+    };
+    let text = super::documents::extract_document_text(bytes, &file_type)
+        .map_err(|e| format!("Original {file_path} cannot be parsed: {e:?}"))?;
+    let config = otl_parser::ChunkConfig::from(&lineage.chunker);
+    Ok(super::documents::chunk_text_simple(&text, &config))
+}
+
+/// Flag the corrupted chunks and clear the flag on the others
+///
+/// Chunks verified against their original also get the hash they were
+/// missing.
+async fn record_integrity(
+    state: &AppState,
+    document_id: Uuid,
+    chunks: &[ChunkIntegrity],
+) -> Result<(), AppError> {
+    let corrupted: Vec<i32> = chunks
+        .iter()
+        .filter(|c| c.status == ChunkStatus::Corrupted)
+        .map(|c| c.chunk_index as i32)
+        .collect();
+    let (verified, hashes): (Vec<i32>, Vec<String>) = chunks
+        .iter()
+        .filter(|c| c.status == ChunkStatus::Ok && c.recorded_hash.is_none())
+        .filter_map(|c| Some((c.chunk_index as i32, c.original_hash.clone()?)))
+        .unzip();
+
+    let mut tx = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to start transaction: {e}")))?;
+    sqlx::query(
+        "UPDATE document_chunks \
+         SET corrupted = (chunk_index = ANY($2)), integrity_checked_at = NOW() \
+         WHERE document_id = $1",
+    )
+    .bind(document_id)
+    .bind(&corrupted)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to record chunk integrity: {e}")))?;
+    sqlx::query(
+        "UPDATE document_chunks c SET content_hash = v.hash \
+         FROM UNNEST($2::int[], $3::text[]) AS v(chunk_index, hash) \
+         WHERE c.document_id = $1 AND c.chunk_index = v.chunk_index",
+    )
+    .bind(document_id)
+    .bind(&verified)
+    .bind(&hashes)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to record chunk hashes: {e}")))?;
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to commit chunk integrity: {e}")))?;
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{AccessLevel, SearchResult, SearchResultType, SourceReference};
+
+    fn neighbor(document_id: Uuid, access_level: AccessLevel) -> NeighborChunk {
+        NeighborChunk {
+            vector_id: Uuid::new_v4().to_string(),
+            chunk_index: Some(0),
+            result: SearchResult {
+                content: "연차휴가는 15일입니다.".to_string(),
+                score: 0.9,
+                source: SourceReference::new(document_id),
+                acl: DocumentAcl {
+                    access_level,
+                    ..Default::default()
+                },
+                result_type: SearchResultType::Vector,
+            },
+        }
+    }
+
+    #[test]
+    fn test_integrity_report_lists_mismatches() {
+        let stored = [StoredChunk {
+            chunk_index: 0,
+            content: "연차휴가는 20일".to_string(),
+            recorded_hash: Some(integrity::content_hash("연차휴가는 15일")),
+        }];
+        let original = vec!["연차휴가는 15일".to_string(), "병가".to_string()];
+        let chunks = integrity::check_chunks(&stored, Some(&original));
+
+        let report = IntegrityReport::new(Uuid::new_v4(), chunks, None);
+        assert!(report.original_checked);
+        assert_eq!((report.checked, report.ok), (2, 0));
+        assert_eq!((report.corrupted, report.missing), (1, 1));
+        assert_eq!(report.mismatches[1].chunk_index, 1);
+
+        let report = IntegrityReport::new(Uuid::new_v4(), Vec::new(), Some("gone".into()));
+        assert!(!report.original_checked);
+        assert!(report.mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_embedding_status() {
+        let canonical = Some(Uuid::new_v4());
+        assert_eq!(
+            EmbeddingStatus::of(Some("abc"), None),
+            EmbeddingStatus::Embedded
+        );
+        assert_eq!(
+            EmbeddingStatus::of(Some(""), None),
+            EmbeddingStatus::Pending
+        );
+        assert_eq!(EmbeddingStatus::of(None, None), EmbeddingStatus::Pending);
+        assert_eq!(
+            EmbeddingStatus::of(Some("abc"), canonical),
+            EmbeddingStatus::Embedded
+        );
+        assert_eq!(
+            EmbeddingStatus::of(None, canonical),
+            EmbeddingStatus::Duplicate
+        );
+    }
+
+    #[test]
+    fn test_filter_neighbors_by_acl() {
+        let public_doc = Uuid::new_v4();
+        let restricted_doc = Uuid::new_v4();
+        let untracked_doc = Uuid::new_v4();
+
+        let mut acls = HashMap::new();
+        acls.insert(
+            public_doc,
+            DocumentAcl {
+                access_level: AccessLevel::Public,
+                ..Default::default()
+            },
+        );
+        acls.insert(
+            restricted_doc,
+            DocumentAcl {
+                access_level: AccessLevel::Restricted,
+                ..Default::default()
+            },
+        );
+
+        let neighbors = vec![
+            neighbor(public_doc, AccessLevel::Public),
+            // The metadata store wins over the payload
+            neighbor(restricted_doc, AccessLevel::Public),
+            neighbor(untracked_doc, AccessLevel::Restricted),
+        ];
+        let user = User::internal("emp-1", vec!["EMPLOYEE".to_string()]);
+
+        let (visible, hidden) = filter_neighbors_by_acl(neighbors, &acls, &user);
+        assert_eq!(hidden, 2);
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].result.source.document_id, public_doc);
+    }
+}
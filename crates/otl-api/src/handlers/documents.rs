@@ -2,22 +2,46 @@
 //!
 //! Author: hephaex@gmail.com
 
-use crate::error::AppError;
+use crate::access_requests::{self, AccessRequest, LIVE_ALLOWED_USERS};
+use crate::audit::{audit_log, extract_ip_address, extract_user_agent, AuditEvent};
+use crate::auth::middleware::AuthenticatedUser;
+use crate::classification::ClassificationSuggestion;
+use crate::compare::{self, CompareCitation, CompareStats, SectionChange, SectionDiff};
+use crate::error::{AppError, ErrorCode};
+use crate::lineage::{DocumentLineage, ParserLineage};
+use crate::share::{ShareGrant, ShareScope};
 use crate::state::AppState;
+use crate::users;
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use base64::Engine;
 use chrono::{DateTime, Utc};
 use futures::stream::{self, StreamExt};
+use otl_core::encryption::chunk_context;
+use otl_core::integrity::content_hash;
+use otl_core::{blob, DocumentChunk, MetadataRepository, MetadataStore};
+use otl_extractor::forms::{FormRegistry, FormTemplate, FORM_EXTRACTOR};
+use otl_extractor::tabular::{MappedEntity, TableMapping, TABLE_CONFIDENCE, TABLE_EXTRACTOR};
+use otl_extractor::ExtractedRelation;
+use otl_graph::DocumentGraph;
+use otl_rag::{detect_language, PromptTemplate};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+/// Largest accepted upload (50MB)
+const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
+
+/// Most data rows ingested from one table
+const MAX_TABLE_ROWS: usize = 50_000;
+
 /// Database row for document queries
 #[derive(sqlx::FromRow)]
 struct DocumentRow {
@@ -144,14 +168,18 @@ pub async fn list_documents(
         conditions.push("d.access_level = 'public'".to_string());
     } else {
         // Internal users: apply ACL logic
-        // Can see: public, internal, confidential (if dept/role match), restricted (if allowed)
+        // Can see: public, internal, confidential (if dept/role match or allowed),
+        // restricted (if allowed)
         let acl_filter = format!(
             "(d.access_level = 'public' OR d.access_level = 'internal' \
-             OR (d.access_level = 'confidential' AND (d.department = ${} OR d.required_roles && ${{{}}})) \
-             OR (d.access_level = 'restricted' AND (d.owner_id = ${} OR ${} = ANY(d.allowed_users))))",
+             OR (d.access_level = 'confidential' AND (d.department = ${} OR d.required_roles && ${{{}}} \
+                 OR d.owner_id = ${} OR ${} = ANY({LIVE_ALLOWED_USERS}))) \
+             OR (d.access_level = 'restricted' AND (d.owner_id = ${} OR ${} = ANY({LIVE_ALLOWED_USERS}))))",
             param_count,
             param_count + 1,
             param_count + 2,
+            param_count + 2,
+            param_count + 2,
             param_count + 2
         );
         conditions.push(acl_filter);
@@ -285,6 +313,7 @@ pub async fn list_documents(
     ),
     responses(
         (status = 200, description = "Document details", body = DocumentInfo),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
         (status = 404, description = "Document not found", body = crate::error::ApiError)
     )
 )]
@@ -323,8 +352,9 @@ pub async fn get_document(
     };
 
     if !acl.can_access(&user) {
-        return Err(AppError::Forbidden(
-            "You don't have permission to access this document".to_string(),
+        return Err(AppError::coded(
+            ErrorCode::AclDenied,
+            "You don't have permission to access this document",
         ));
     }
 
@@ -342,6 +372,158 @@ pub async fn get_document(
     Ok((StatusCode::OK, Json(doc)))
 }
 
+/// Get the processing lineage of a document
+///
+/// Shows the parser, OCR engine, chunker settings, embedding model and
+/// extraction model that produced the document and each of its chunks.
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/lineage",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    responses(
+        (status = 200, description = "Document lineage", body = DocumentLineage),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "No lineage recorded for this document", body = crate::error::ApiError)
+    )
+)]
+pub async fn get_document_lineage(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let user = state.get_default_user(None);
+
+    #[derive(sqlx::FromRow)]
+    struct AclRow {
+        access_level: String,
+        owner_id: Option<String>,
+        department: Option<String>,
+    }
+
+    // Documents ingested without a metadata row have no ACL to check
+    let row: Option<AclRow> = sqlx::query_as(
+        "SELECT access_level::text, owner_id, department FROM documents WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?;
+
+    if let Some(row) = row {
+        let acl = otl_core::DocumentAcl {
+            access_level: parse_access_level(&row.access_level),
+            owner_id: row.owner_id,
+            department: row.department,
+            required_roles: Vec::new(),
+            allowed_users: Vec::new(),
+        };
+        if !acl.can_access(&user) {
+            return Err(AppError::coded(
+                ErrorCode::AclDenied,
+                "You don't have permission to access this document",
+            ));
+        }
+    }
+
+    let lineage = crate::lineage::fetch(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Lineage of document {id}")))?;
+
+    Ok((StatusCode::OK, Json(lineage)))
+}
+
+/// Get the ingest report of a document
+///
+/// Chunk count, token histogram against the embedding model's input limit,
+/// over-limit chunks and warnings, recorded when the document was last
+/// ingested or reprocessed.
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/ingest-report",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    responses(
+        (status = 200, description = "Ingest report", body = Object),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "No ingest report recorded for this document", body = crate::error::ApiError)
+    )
+)]
+pub async fn get_ingest_report(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    super::chunks::authorize_document(&state, id, &user.to_acl_user()).await?;
+    let report: Option<serde_json::Value> = sqlx::query_scalar(
+        "SELECT metadata -> 'ingest_report' FROM documents WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?
+    .flatten();
+
+    let report = report.ok_or_else(|| {
+        AppError::NotFound(format!("No ingest report recorded for document {id}"))
+    })?;
+    Ok(Json(report))
+}
+
+/// Get the pages of a document that need re-scanning
+///
+/// Scanned pages whose OCR confidence was below 0.6 at ingest, with whether
+/// they were left out of the index. A document without such pages has an
+/// empty list.
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/rescan",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    responses(
+        (status = 200, description = "Pages to re-scan", body = Object),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "Document not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn get_rescan_report(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    super::chunks::authorize_document(&state, id, &user.to_acl_user()).await?;
+    let pages: Option<Option<serde_json::Value>> = sqlx::query_scalar(
+        "SELECT metadata -> 'ingest_report' -> 'rescan' FROM documents \
+         WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?;
+
+    let pages = pages.ok_or_else(|| AppError::NotFound(format!("Document {id}")))?;
+    let pages: Vec<otl_parser::report::RescanPage> = match pages {
+        Some(pages) => serde_json::from_value(pages)
+            .map_err(|e| AppError::Internal(format!("Invalid re-scan pages: {e}")))?,
+        None => Vec::new(),
+    };
+    Ok(Json(serde_json::json!({
+        "document_id": id,
+        "pages": pages,
+    })))
+}
+
 /// Upload document request
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UploadDocumentRequest {
@@ -371,6 +553,10 @@ pub struct UploadDocumentResponse {
     pub id: Uuid,
     pub message: String,
     pub chunk_count: u32,
+    /// Suggested access level and department, flagged for admin review
+    /// when the declared level differs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub classification: Option<ClassificationSuggestion>,
 }
 
 /// Upload a new document
@@ -381,7 +567,9 @@ pub struct UploadDocumentResponse {
     request_body = UploadDocumentRequest,
     responses(
         (status = 201, description = "Document uploaded successfully"),
-        (status = 400, description = "Invalid request", body = crate::error::ApiError)
+        (status = 400, description = "Invalid request", body = crate::error::ApiError),
+        (status = 413, description = "File exceeds 50MB (DOC_TOO_LARGE)", body = crate::error::ApiError),
+        (status = 422, description = "File cannot be read as its file type (DOC_UNREADABLE)", body = crate::error::ApiError)
     )
 )]
 pub async fn upload_document(
@@ -399,66 +587,16 @@ pub async fn upload_document(
         return Err(AppError::BadRequest("Content cannot be empty".to_string()));
     }
 
-    // Generate document ID
+    // Generate document ID and log it with the request
     let doc_id = Uuid::new_v4();
+    tracing::Span::current().record("document_id", tracing::field::display(doc_id));
 
     // Decode base64 content
     let decoded_bytes = base64::engine::general_purpose::STANDARD
         .decode(&req.content)
         .map_err(|e| AppError::BadRequest(format!("Invalid base64 content: {e}")))?;
 
-    // Validate file size (max 50MB)
-    const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
-    if decoded_bytes.len() > MAX_FILE_SIZE {
-        return Err(AppError::BadRequest(format!(
-            "File size exceeds maximum allowed size of 50MB (actual: {} bytes)",
-            decoded_bytes.len()
-        )));
-    }
-
-    // Validate magic bytes for file type
-    match req.file_type.to_lowercase().as_str() {
-        "pdf" => {
-            if !decoded_bytes.starts_with(b"%PDF-") {
-                return Err(AppError::BadRequest(
-                    "Invalid PDF file: magic bytes do not match".to_string(),
-                ));
-            }
-        }
-        "docx" => {
-            // DOCX files are ZIP archives starting with PK signature
-            if !decoded_bytes.starts_with(&[0x50, 0x4B, 0x03, 0x04]) {
-                return Err(AppError::BadRequest(
-                    "Invalid DOCX file: magic bytes do not match (expected ZIP signature)"
-                        .to_string(),
-                ));
-            }
-        }
-        _ => {
-            // For text files, no magic bytes validation needed
-        }
-    }
-
-    // Extract text content based on file type
-    let text_content = match req.file_type.to_lowercase().as_str() {
-        "pdf" => {
-            // Use PDF parser to extract text
-            extract_text_from_pdf(&decoded_bytes).map_err(|e| {
-                AppError::BadRequest(format!("Failed to extract text from PDF: {e}"))
-            })?
-        }
-        "docx" => {
-            // Use DOCX parser to extract text
-            extract_text_from_docx(&decoded_bytes).map_err(|e| {
-                AppError::BadRequest(format!("Failed to extract text from DOCX: {e}"))
-            })?
-        }
-        _ => {
-            // Assume plain text (txt, md, etc.)
-            String::from_utf8(decoded_bytes)
-                .map_err(|e| AppError::BadRequest(format!("Content is not valid UTF-8: {e}")))?
-        }
-    };
+    let text_content = extract_document_text(decoded_bytes.clone(), &req.file_type)?;
 
     tracing::info!(
         "Processing document upload: {} (id: {}, type: {}, size: {} bytes)",
@@ -468,20 +606,48 @@ pub async fn upload_document(
         text_content.len()
     );
 
-    // Chunk the document
-    let chunk_config = otl_parser::ChunkConfig {
-        chunk_size: 1000,
-        overlap: 200,
-        min_chunk_size: 100,
-        respect_sections: true,
-        respect_paragraphs: true,
-    };
+    record_upload(
+        &state,
+        &UploadedFile {
+            id: doc_id,
+            title: &req.title,
+            file_type: &req.file_type,
+            access_level: req.access_level.as_deref(),
+            department: req.department.as_deref(),
+            owner_id: None,
+        },
+        decoded_bytes,
+    )
+    .await?;
+
+    let access_level = parse_access_level(req.access_level.as_deref().unwrap_or("internal"));
+    let classification = crate::classification::classify_upload(
+        &state,
+        doc_id,
+        &text_content,
+        access_level,
+        req.department.as_deref(),
+    )
+    .await;
 
-    let chunks = chunk_text_simple(&text_content, &chunk_config);
+    // Chunk the document
+    let chunks = chunk_document_text(&text_content);
     let chunk_count = chunks.len() as u32;
+    record_ingest_report(&state, doc_id, &chunks).await;
+    let lineage = DocumentLineage::new(
+        doc_id,
+        ParserLineage::for_file_type(&req.file_type),
+        &ingestion_chunk_config(),
+        &chunks,
+    );
 
     tracing::info!("Document {} split into {} chunks", doc_id, chunk_count);
 
+    // Store the chunks so near-duplicates are linked to their canonical copies
+    replace_chunks(&state, doc_id, &chunks, access_level).await?;
+    crate::dedup::link_chunks(&state, doc_id).await;
+    let skipped = crate::dedup::skipped_chunks(&state, doc_id).await?;
+
     // Get vector backend and process chunks
     let vector_backend_guard = state.vector_backend.read().await;
     if let Some(vector_backend) = vector_backend_guard.as_ref() {
@@ -492,7 +658,13 @@ pub async fn upload_document(
         // Process chunks in parallel using buffer_unordered for better performance
         const PARALLEL_LIMIT: usize = 4;
 
-        let indexing_results: Vec<_> = stream::iter(chunks.into_iter().enumerate())
+        let to_index: Vec<(usize, String)> = chunks
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(index, _)| !skipped.contains_key(&(*index as u32)))
+            .collect();
+        let indexing_results: Vec<_> = stream::iter(to_index)
             .map(|(index, chunk_text)| {
                 let backend = backend.clone();
                 async move {
@@ -506,6 +678,7 @@ pub async fn upload_document(
 
         // Process results and count successes
         let mut processed_count = 0;
+        let mut vector_ids = HashMap::new();
         for (index, result) in indexing_results {
             match result {
                 Ok(vector_id) => {
@@ -516,6 +689,7 @@ pub async fn upload_document(
                         doc_id,
                         vector_id
                     );
+                    vector_ids.insert(index, vector_id.to_string());
                 }
                 Err(e) => {
                     tracing::warn!(
@@ -529,18 +703,33 @@ pub async fn upload_document(
         }
 
         tracing::info!(
-            "Successfully indexed {}/{} chunks for document {}",
+            "Successfully indexed {}/{} chunks for document {} ({} duplicates skipped)",
             processed_count,
             chunk_count,
-            doc_id
+            doc_id,
+            skipped.len()
         );
+        store_vector_ids(&state, doc_id, &vector_ids).await?;
+
+        store_structure_graph(&state, doc_id, &req.title, &chunks, &vector_ids).await;
+        let lineage = lineage.with_indexed_chunks(&state.config.llm.embedding_model, &vector_ids);
+        crate::lineage::record(&state.db_pool, &lineage).await;
 
         let response = UploadDocumentResponse {
             id: doc_id,
-            message: format!(
-                "Document uploaded and processed: {processed_count}/{chunk_count} chunks indexed"
-            ),
+            message: if skipped.is_empty() {
+                format!(
+                    "Document uploaded and processed: {processed_count}/{chunk_count} chunks indexed"
+                )
+            } else {
+                format!(
+                    "Document uploaded and processed: {processed_count}/{chunk_count} chunks \
+                     indexed, {} duplicates of existing chunks",
+                    skipped.len()
+                )
+            },
             chunk_count: processed_count,
+            classification,
         };
 
         Ok((StatusCode::CREATED, Json(response)))
@@ -548,216 +737,2106 @@ pub async fn upload_document(
         // Vector backend not initialized
         tracing::warn!("Vector backend not initialized, document upload not processed");
 
+        store_structure_graph(&state, doc_id, &req.title, &chunks, &HashMap::new()).await;
+        crate::lineage::record(&state.db_pool, &lineage).await;
+
         let response = UploadDocumentResponse {
             id: doc_id,
             message: "Document received but vector store not available for indexing".to_string(),
             chunk_count: 0,
+            classification,
         };
 
         Ok((StatusCode::CREATED, Json(response)))
     }
 }
 
-/// Simple text chunking function with proper UTF-8 handling
-fn chunk_text_simple(text: &str, config: &otl_parser::ChunkConfig) -> Vec<String> {
-    let mut chunks = Vec::new();
-
-    if text.len() <= config.chunk_size {
-        chunks.push(text.to_string());
-        return chunks;
-    }
-
-    let mut start = 0;
-    while start < text.len() {
-        // Calculate target end position (ensuring char boundary)
-        let target_end = (start + config.chunk_size).min(text.len());
-        let end = find_char_boundary(text, target_end);
-
-        // Find a good break point (sentence or paragraph boundary)
-        let actual_end = find_chunk_boundary(text, start, end, config.respect_paragraphs);
+/// Tabular ingestion request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TabularIngestRequest {
+    /// Document title
+    #[schema(example = "인사마스터_2026.xlsx")]
+    pub title: String,
 
-        // Ensure start is on char boundary
-        let safe_start = find_char_boundary(text, start);
-        let chunk_text = &text[safe_start..actual_end];
+    /// Base64 encoded file content
+    pub content: String,
 
-        if chunk_text.len() >= config.min_chunk_size {
-            chunks.push(chunk_text.to_string());
-        }
+    /// File type: `xlsx`, `xls` or `csv`
+    #[schema(example = "xlsx")]
+    pub file_type: String,
 
-        if actual_end >= text.len() {
-            break;
-        }
+    /// Access level
+    #[schema(example = "confidential")]
+    pub access_level: Option<String>,
 
-        // Move start with overlap (ensuring char boundary)
-        let overlap_pos = if actual_end > config.overlap {
-            actual_end - config.overlap
-        } else {
-            actual_end
-        };
-        start = find_char_boundary(text, overlap_pos);
-    }
+    /// Owner department
+    #[schema(example = "인사팀")]
+    pub department: Option<String>,
 
-    chunks
-}
+    /// Column to class, property and relation mapping
+    #[schema(value_type = Object)]
+    pub mapping: TableMapping,
 
-/// Find the nearest valid UTF-8 character boundary at or before the given position
-fn find_char_boundary(text: &str, pos: usize) -> usize {
-    if pos >= text.len() {
-        return text.len();
-    }
-    let mut boundary = pos;
-    while boundary > 0 && !text.is_char_boundary(boundary) {
-        boundary -= 1;
-    }
-    boundary
+    /// Only check the file against the mapping, store nothing
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
-/// Find a good boundary for chunking (respecting sentence/paragraph boundaries)
-fn find_chunk_boundary(
-    text: &str,
-    _start: usize,
-    target: usize,
-    respect_paragraphs: bool,
-) -> usize {
-    if target >= text.len() {
-        return text.len();
-    }
-
-    // Ensure target is on a valid char boundary
-    let target = find_char_boundary(text, target);
-
-    if !respect_paragraphs {
-        return target;
-    }
-
-    // Search window around target position (ensure boundaries are valid)
-    let search_start = find_char_boundary(text, target.saturating_sub(100));
-    let search_end = find_char_boundary(text, (target + 100).min(text.len()));
-    let search_text = &text[search_start..search_end];
-
-    // Look for paragraph break (double newline)
-    if let Some(pos) = search_text.rfind("\n\n") {
-        return (search_start + pos + 2).min(text.len());
-    }
-
-    // Look for sentence endings
-    for pattern in [". ", "。", "! ", "? ", ".\n", "。\n", "!\n", "?\n"] {
-        if let Some(pos) = search_text.rfind(pattern) {
-            return (search_start + pos + pattern.len()).min(text.len());
-        }
-    }
-
-    // Look for single newline
-    if let Some(pos) = search_text.rfind('\n') {
-        return (search_start + pos + 1).min(text.len());
-    }
-
-    // Fall back to target position
-    target
+/// Rejected table row
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TableRowError {
+    /// Row number in the file (the header is row 1)
+    pub row: usize,
+    #[schema(example = "'사번' is empty")]
+    pub message: String,
 }
 
-/// Delete document response
-#[derive(Debug, Serialize)]
-pub struct DeleteDocumentResponse {
-    pub message: String,
+/// Tabular ingestion result
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TabularIngestResponse {
+    /// Created document (absent for dry runs and rejected files)
+    pub document_id: Option<Uuid>,
+    /// Rows converted
+    pub rows: usize,
+    pub entities: usize,
+    pub relations: usize,
+    /// Extractions added to the verification queue
+    pub queued: usize,
+    /// Rejected rows; when there are any, nothing is stored
+    pub errors: Vec<TableRowError>,
 }
 
-/// Delete a document
+/// Ingest a spreadsheet of structured data as entities and relations
+///
+/// Maps each row of an Excel or CSV file onto the ontology with the given
+/// column mapping instead of running NER. Converted rows are queued for
+/// verification with full confidence as `table` extractions, one per row,
+/// so auto-approval rules and audit sampling decide which ones reviewers
+/// see. The file is rejected as a whole if any row is invalid.
 #[utoipa::path(
-    delete,
-    path = "/api/v1/documents/{id}",
+    post,
+    path = "/api/v1/documents/tabular",
     tag = "documents",
-    params(
-        ("id" = Uuid, Path, description = "Document UUID")
-    ),
+    request_body = TabularIngestRequest,
     responses(
-        (status = 200, description = "Document deleted"),
-        (status = 404, description = "Document not found", body = crate::error::ApiError)
+        (status = 201, description = "Rows queued for verification", body = TabularIngestResponse),
+        (status = 200, description = "Dry run: every row maps cleanly", body = TabularIngestResponse),
+        (status = 400, description = "Invalid file type or mapping", body = crate::error::ApiError),
+        (status = 403, description = "Editor role required", body = crate::error::ApiError),
+        (status = 422, description = "Invalid rows, nothing stored", body = TabularIngestResponse)
     )
 )]
-pub async fn delete_document(
+pub async fn ingest_tabular(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<Uuid>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(req): Json<TabularIngestRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required for tabular ingestion".to_string(),
+        ));
+    }
+    if req.title.trim().is_empty() {
+        return Err(AppError::BadRequest("Title cannot be empty".to_string()));
+    }
+    let access_level = parse_access_level(req.access_level.as_deref().unwrap_or("internal"));
+
+    let decoded_bytes = base64::engine::general_purpose::STANDARD
+        .decode(&req.content)
+        .map_err(|e| AppError::BadRequest(format!("Invalid base64 content: {e}")))?;
+    let table = read_table(&decoded_bytes, &req.file_type, req.mapping.sheet.as_deref())?;
+    if table.rows.len() > MAX_TABLE_ROWS {
+        return Err(AppError::BadRequest(format!(
+            "Table has {} rows; at most {MAX_TABLE_ROWS} are ingested at once",
+            table.rows.len()
+        )));
+    }
+
+    let classes = super::graph::default_ontology().to_core_classes();
+    req.mapping.validate(&table.headers, &classes)?;
+    let extraction = req.mapping.extract(&table.headers, &table.rows);
+
+    let mut response = TabularIngestResponse {
+        document_id: None,
+        rows: extraction.rows.len(),
+        entities: extraction.entity_count(),
+        relations: extraction.relation_count(),
+        queued: 0,
+        errors: extraction
+            .errors
+            .iter()
+            .map(|e| TableRowError {
+                row: e.row,
+                message: e.message.clone(),
+            })
+            .collect(),
+    };
+    if !response.errors.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(response)));
+    }
+    if req.dry_run {
+        return Ok((StatusCode::OK, Json(response)));
+    }
+
+    let doc_id = Uuid::new_v4();
+    tracing::Span::current().record("document_id", tracing::field::display(doc_id));
+    let file_size = decoded_bytes.len() as i64;
+    let file_path =
+        store_original(&state, doc_id, decoded_bytes, &req.file_type, access_level).await?;
+    let document = IngestedDocument {
+        id: doc_id,
+        title: &req.title,
+        file_path,
+        file_type: stored_file_type(&req.file_type),
+        file_size,
+        access_level,
+        department: req.department.as_deref(),
+        metadata: serde_json::json!({
+            "ingestion": "table",
+            "table_mapping": req.mapping,
+        }),
+    };
+    let rows = extraction
+        .rows
+        .iter()
+        .map(|row| {
+            QueuedExtraction::new(
+                &row.entities,
+                &row.relations,
+                row.source_text.clone(),
+                TABLE_CONFIDENCE,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let queued = queue_extractions(&state, &document, &user, TABLE_EXTRACTOR, rows).await?;
+    response.document_id = Some(doc_id);
+    response.queued = queued;
+
+    tracing::info!(
+        "Tabular ingestion of '{}' by {}: {} rows, {} entities, {} relations queued as document {}",
+        req.title,
+        user.email,
+        response.rows,
+        response.entities,
+        response.relations,
+        doc_id
+    );
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Read the table to ingest from an Excel workbook or a CSV file
+///
+/// Workbooks are read from `sheet`, or else from their first sheet.
+fn read_table(
+    bytes: &[u8],
+    file_type: &str,
+    sheet: Option<&str>,
+) -> Result<otl_parser::Table, AppError> {
+    if bytes.len() > MAX_FILE_SIZE {
+        return Err(AppError::coded(
+            ErrorCode::DocTooLarge,
+            format!(
+                "File size exceeds maximum allowed size of 50MB (actual: {} bytes)",
+                bytes.len()
+            ),
+        ));
+    }
+    let unreadable =
+        |e: otl_parser::ParserError| AppError::coded(ErrorCode::DocUnreadable, e.to_string());
+
+    match file_type.to_lowercase().as_str() {
+        "csv" => {
+            let text = std::str::from_utf8(bytes).map_err(|e| {
+                AppError::coded(
+                    ErrorCode::DocUnreadable,
+                    format!("Content is not valid UTF-8: {e}"),
+                )
+            })?;
+            otl_parser::csv::parse_csv(text).map_err(unreadable)
+        }
+        "xlsx" | "xls" => {
+            let tables = otl_parser::ExcelParser::new()
+                .read_tables(bytes)
+                .map_err(unreadable)?;
+            match sheet {
+                Some(sheet) => tables
+                    .into_iter()
+                    .find(|t| t.caption.as_deref() == Some(sheet))
+                    .ok_or_else(|| AppError::BadRequest(format!("Sheet '{sheet}' not found"))),
+                None => tables.into_iter().next().ok_or_else(|| {
+                    AppError::coded(ErrorCode::DocUnreadable, "Workbook has no readable sheet")
+                }),
+            }
+        }
+        other => Err(AppError::BadRequest(format!(
+            "Unsupported file type for tabular ingestion: {other} (expected xlsx, xls or csv)"
+        ))),
+    }
+}
+
+/// Form ingestion request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FormIngestRequest {
+    /// Document title
+    #[schema(example = "휴가신청서_김철수.pdf")]
+    pub title: String,
+
+    /// Base64 encoded file content
+    pub content: String,
+
+    /// File type (pdf, docx, txt, md)
+    #[schema(example = "pdf")]
+    pub file_type: String,
+
+    /// Access level
+    #[schema(example = "internal")]
+    pub access_level: Option<String>,
+
+    /// Owner department
+    #[schema(example = "인사팀")]
+    pub department: Option<String>,
+
+    /// Template to read the form with; recognized from the text if absent
+    #[schema(example = "leave_request")]
+    pub template: Option<String>,
+
+    /// Only extract the fields, store nothing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Form ingestion result
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FormIngestResponse {
+    /// Created document (absent for dry runs)
+    pub document_id: Option<Uuid>,
+    /// Template the form was read with
+    #[schema(example = "leave_request")]
+    pub template: String,
+    /// Share of the template's markers and labels found in the text
+    pub score: f32,
+    /// Literal field values by property
+    pub fields: BTreeMap<String, String>,
+    pub entities: usize,
+    pub relations: usize,
+    /// Required fields not found
+    pub missing: Vec<String>,
+    /// Confidence given to the extraction
+    pub confidence: f32,
+    /// Extractions added to the verification queue
+    pub queued: usize,
+}
+
+/// Recognize an application form and extract its fields
+///
+/// Reads the document with the named template, or with the registered
+/// template whose markers and labels it matches best. The field values
+/// become an entity of the template's class, plus related entities for
+/// relation fields, queued for verification as one `form` extraction. A
+/// complete form gets a high confidence; one missing required fields gets
+/// a low one and is left to reviewers.
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/form",
+    tag = "documents",
+    request_body = FormIngestRequest,
+    responses(
+        (status = 201, description = "Form fields queued for verification", body = FormIngestResponse),
+        (status = 200, description = "Dry run: extracted fields", body = FormIngestResponse),
+        (status = 400, description = "Invalid content or no template matches", body = crate::error::ApiError),
+        (status = 403, description = "Editor role required", body = crate::error::ApiError),
+        (status = 404, description = "Template not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn ingest_form(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(req): Json<FormIngestRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required for form ingestion".to_string(),
+        ));
+    }
+    if req.title.trim().is_empty() {
+        return Err(AppError::BadRequest("Title cannot be empty".to_string()));
+    }
+    let access_level = parse_access_level(req.access_level.as_deref().unwrap_or("internal"));
+
+    let decoded_bytes = base64::engine::general_purpose::STANDARD
+        .decode(&req.content)
+        .map_err(|e| AppError::BadRequest(format!("Invalid base64 content: {e}")))?;
+    let file_size = decoded_bytes.len() as i64;
+    let text = extract_document_text(decoded_bytes.clone(), &req.file_type)?;
+
+    let registry = crate::forms::registry(&state).await?;
+    let (template, score) = match_form(&registry, req.template.as_deref(), &text)?;
+    let form = template.extract(&text);
+
+    let mut response = FormIngestResponse {
+        document_id: None,
+        template: form.template.clone(),
+        score,
+        fields: form.fields().clone(),
+        entities: form.entities.len(),
+        relations: form.relations.len(),
+        missing: form.missing.clone(),
+        confidence: form.confidence,
+        queued: 0,
+    };
+    if req.dry_run {
+        return Ok((StatusCode::OK, Json(response)));
+    }
+
+    let doc_id = Uuid::new_v4();
+    tracing::Span::current().record("document_id", tracing::field::display(doc_id));
+    let file_path =
+        store_original(&state, doc_id, decoded_bytes, &req.file_type, access_level).await?;
+    let document = IngestedDocument {
+        id: doc_id,
+        title: &req.title,
+        file_path,
+        file_type: stored_file_type(&req.file_type),
+        file_size,
+        access_level,
+        department: req.department.as_deref(),
+        metadata: serde_json::json!({
+            "ingestion": "form",
+            "form_template": form.template,
+        }),
+    };
+    let extraction = QueuedExtraction::new(&form.entities, &form.relations, text, form.confidence)?;
+    let queued =
+        queue_extractions(&state, &document, &user, FORM_EXTRACTOR, vec![extraction]).await?;
+    response.document_id = Some(doc_id);
+    response.queued = queued;
+
+    tracing::info!(
+        "Form ingestion of '{}' by {}: read as '{}', {} missing fields, queued as document {}",
+        req.title,
+        user.email,
+        response.template,
+        response.missing.len(),
+        doc_id
+    );
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Template to read a form with, and its detection score
+fn match_form<'a>(
+    registry: &'a FormRegistry,
+    name: Option<&str>,
+    text: &str,
+) -> Result<(&'a FormTemplate, f32), AppError> {
+    match name {
+        Some(name) => registry
+            .get(name)
+            .map(|template| (template, template.score(text)))
+            .ok_or_else(|| AppError::NotFound(format!("Form template '{name}' not found"))),
+        None => registry
+            .detect(text)
+            .map(|m| (m.template, m.score))
+            .ok_or_else(|| {
+                AppError::BadRequest("Document does not match any form template".to_string())
+            }),
+    }
+}
+
+/// Document created by structured ingestion
+struct IngestedDocument<'a> {
+    id: Uuid,
+    title: &'a str,
+    /// Key of the stored original
+    file_path: String,
+    file_type: &'a str,
+    file_size: i64,
+    access_level: otl_core::AccessLevel,
+    department: Option<&'a str>,
+    metadata: serde_json::Value,
+}
+
+/// Extraction added to the verification queue
+struct QueuedExtraction {
+    entities: serde_json::Value,
+    relations: serde_json::Value,
+    source_text: String,
+    confidence: f32,
+}
+
+impl QueuedExtraction {
+    fn new(
+        entities: &[MappedEntity],
+        relations: &[ExtractedRelation],
+        source_text: String,
+        confidence: f32,
+    ) -> Result<Self, AppError> {
+        let entities = serde_json::to_value(entities)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize entities: {e}")))?;
+        let relations = relations
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "subject": r.subject.text,
+                    "subject_type": r.subject.entity_type,
+                    "predicate": r.predicate,
+                    "object": r.object.text,
+                    "object_type": r.object.entity_type,
+                    "confidence": r.confidence,
+                })
+            })
+            .collect();
+        Ok(Self {
+            entities,
+            relations,
+            source_text,
+            confidence,
+        })
+    }
+}
+
+/// Record the ingested file and queue its extractions in one transaction
+///
+/// Structured extractions are not reviewed by default: auto-approval and
+/// audit sampling decide which ones reach a reviewer.
+async fn queue_extractions(
+    state: &AppState,
+    document: &IngestedDocument<'_>,
+    user: &AuthenticatedUser,
+    extractor: &str,
+    extractions: Vec<QueuedExtraction>,
+) -> Result<usize, AppError> {
+    let mut entities = Vec::with_capacity(extractions.len());
+    let mut relations = Vec::with_capacity(extractions.len());
+    let mut source_texts = Vec::with_capacity(extractions.len());
+    let mut confidences = Vec::with_capacity(extractions.len());
+    for extraction in extractions {
+        entities.push(extraction.entities);
+        relations.push(extraction.relations);
+        source_texts.push(extraction.source_text);
+        confidences.push(extraction.confidence);
+    }
+
+    let mut tx = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to start transaction: {e}")))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO documents
+            (id, title, file_path, file_type, file_size, access_level, owner_id, department, metadata)
+        VALUES ($1, $2, $3, $4::file_type, $5, $6::access_level, $7, $8, $9)
+        "#,
+    )
+    .bind(document.id)
+    .bind(document.title)
+    .bind(&document.file_path)
+    .bind(document.file_type)
+    .bind(document.file_size)
+    .bind(document.access_level.to_string())
+    .bind(user.user_id.to_string())
+    .bind(document.department)
+    .bind(&document.metadata)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to create document: {e}")))?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO extraction_queue
+            (document_id, extracted_entities, extracted_relations, source_text, extractor, confidence_score)
+        SELECT $1, u.entities, u.relations, u.source_text, $6, u.confidence
+        FROM UNNEST($2::jsonb[], $3::jsonb[], $4::text[], $5::real[])
+            AS u(entities, relations, source_text, confidence)
+        "#,
+    )
+    .bind(document.id)
+    .bind(&entities)
+    .bind(&relations)
+    .bind(&source_texts)
+    .bind(&confidences)
+    .bind(extractor)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to queue extractions: {e}")))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to commit extractions: {e}")))?;
+    Ok(result.rows_affected() as usize)
+}
+
+/// `file_type` enum value stored for an ingested file
+fn stored_file_type(file_type: &str) -> &'static str {
+    match file_type.to_lowercase().as_str() {
+        "pdf" => "pdf",
+        "docx" => "docx",
+        "xlsx" => "xlsx",
+        "pptx" => "pptx",
+        "md" | "markdown" => "markdown",
+        "txt" | "text" => "text",
+        "html" | "htm" => "html",
+        _ => "other",
+    }
+}
+
+/// Build the document structure graph for uploaded chunks
+///
+/// Markdown headings inside the chunks open sections; a chunk belongs to the
+/// heading it starts with, or else to the last heading seen before it.
+pub(crate) fn build_structure_graph(
+    doc_id: Uuid,
+    title: &str,
+    chunks: &[String],
+    vector_ids: &HashMap<usize, String>,
+) -> DocumentGraph {
+    let mut graph = DocumentGraph::new(doc_id, title);
+    let mut current: Option<String> = None;
+
+    for (index, text) in chunks.iter().enumerate() {
+        let headings: Vec<(u32, &str)> = text.lines().filter_map(markdown_heading).collect();
+        let starts_with_heading = text
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .and_then(markdown_heading)
+            .is_some();
+
+        let section = if starts_with_heading {
+            headings.first().map(|(_, t)| t.to_string())
+        } else {
+            current.clone()
+        };
+        for (level, heading) in &headings {
+            graph.add_section(heading, *level, None);
+        }
+        if let Some((_, last)) = headings.last() {
+            current = Some(last.to_string());
+        }
+
+        let mut chunk = DocumentChunk::new(doc_id, index as u32, text.as_str());
+        chunk.section_name = section;
+        chunk.vector_id = vector_ids.get(&index).cloned();
+        graph.add_chunk(&chunk);
+    }
+
+    graph
+}
+
+/// Parse a markdown ATX heading line into (level, title)
+fn markdown_heading(line: &str) -> Option<(u32, &str)> {
+    let line = line.trim();
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let title = line[level..]
+        .strip_prefix(' ')?
+        .trim()
+        .trim_end_matches('#')
+        .trim();
+    (!title.is_empty()).then_some((level as u32, title))
+}
+
+/// Store the structure graph, logging instead of failing the upload
+pub(crate) async fn store_structure_graph(
+    state: &AppState,
+    doc_id: Uuid,
+    title: &str,
+    chunks: &[String],
+    vector_ids: &HashMap<usize, String>,
+) {
+    let Some(graph_db) = state.graph_db.read().await.clone() else {
+        tracing::debug!("Graph database not initialized, skipping structure graph");
+        return;
+    };
+
+    let graph = build_structure_graph(doc_id, title, chunks, vector_ids);
+    match graph.store(&*graph_db).await {
+        Ok(()) => tracing::info!(
+            "Stored structure graph for document {}: {} sections, {} chunks",
+            doc_id,
+            graph.sections().len(),
+            graph.chunks().len()
+        ),
+        Err(e) => tracing::warn!("Failed to store structure graph for document {doc_id}: {e}"),
+    }
+}
+
+/// Media type of an original file
+pub(crate) fn media_type(file_type: &str) -> &'static str {
+    match file_type.to_lowercase().as_str() {
+        "pdf" => "application/pdf",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "xls" => "application/vnd.ms-excel",
+        "csv" => "text/csv; charset=utf-8",
+        "md" | "markdown" => "text/markdown; charset=utf-8",
+        "txt" | "text" => "text/plain; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Keep an ingested file as the document's original; returns its key
+///
+/// Originals of documents with a data key (Restricted ones) are sealed
+/// before they reach the blob store.
+pub(crate) async fn store_original(
+    state: &AppState,
+    document_id: Uuid,
+    bytes: Vec<u8>,
+    file_type: &str,
+    access_level: otl_core::AccessLevel,
+) -> Result<String, AppError> {
+    let key = blob::original_key(document_id);
+    let bytes = match state
+        .keyring
+        .content_key(&state.db_pool, document_id, access_level)
+        .await?
+    {
+        Some(data_key) => data_key.seal_blob(&bytes, &key)?,
+        None => bytes,
+    };
+    blob::put_bytes(
+        state.blob_store.as_ref(),
+        &key,
+        bytes,
+        Some(media_type(file_type)),
+    )
+    .await
+    .map_err(|e| {
+        AppError::Internal(format!(
+            "Failed to store original of document {document_id}: {e}"
+        ))
+    })?;
+    Ok(key)
+}
+
+/// Read a stored original, opening it if it was sealed
+pub(crate) async fn read_original(
+    state: &AppState,
+    document_id: Uuid,
+    key: &str,
+) -> otl_core::Result<Vec<u8>> {
+    let bytes = blob::get_bytes(state.blob_store.as_ref(), key).await?;
+    if !otl_core::encryption::is_sealed_blob(&bytes) {
+        return Ok(bytes);
+    }
+    let data_key = state
+        .keyring
+        .document_key(&state.db_pool, document_id)
+        .await?
+        .ok_or_else(|| {
+            otl_core::OtlError::EncryptionError(format!(
+                "Original of document {document_id} is sealed but it has no data key"
+            ))
+        })?;
+    data_key.open_blob(bytes, key)
+}
+
+/// Uploaded file to record as a document
+pub(crate) struct UploadedFile<'a> {
+    pub id: Uuid,
+    pub title: &'a str,
+    pub file_type: &'a str,
+    pub access_level: Option<&'a str>,
+    pub department: Option<&'a str>,
+    pub owner_id: Option<String>,
+}
+
+/// Store an uploaded file as the original of a new document
+pub(crate) async fn record_upload(
+    state: &AppState,
+    file: &UploadedFile<'_>,
+    bytes: Vec<u8>,
+) -> Result<(), AppError> {
+    let file_size = bytes.len() as i64;
+    let access_level = parse_access_level(file.access_level.unwrap_or("internal"));
+    let file_path = store_original(state, file.id, bytes, file.file_type, access_level).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO documents
+            (id, title, file_path, file_type, file_size, access_level, owner_id, department)
+        VALUES ($1, $2, $3, $4::file_type, $5, $6::access_level, $7, $8)
+        "#,
+    )
+    .bind(file.id)
+    .bind(file.title)
+    .bind(&file_path)
+    .bind(stored_file_type(file.file_type))
+    .bind(file_size)
+    .bind(access_level.to_string())
+    .bind(file.owner_id.as_deref())
+    .bind(file.department)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to create document: {e}")))?;
+    Ok(())
+}
+
+/// Validate raw upload bytes and extract their text content
+///
+/// Shared by the REST upload handler and the gRPC ingest service.
+pub(crate) fn extract_document_text(
+    decoded_bytes: Vec<u8>,
+    file_type: &str,
+) -> Result<String, AppError> {
+    // Validate file size (max 50MB)
+    if decoded_bytes.len() > MAX_FILE_SIZE {
+        return Err(AppError::coded(
+            ErrorCode::DocTooLarge,
+            format!(
+                "File size exceeds maximum allowed size of 50MB (actual: {} bytes)",
+                decoded_bytes.len()
+            ),
+        ));
+    }
+
+    // Validate magic bytes for file type
+    match file_type.to_lowercase().as_str() {
+        "pdf" if !decoded_bytes.starts_with(b"%PDF-") => {
+            return Err(AppError::coded(
+                ErrorCode::DocUnreadable,
+                "Invalid PDF file: magic bytes do not match",
+            ));
+        }
+        // DOCX files are ZIP archives starting with PK signature
+        "docx" if !decoded_bytes.starts_with(&[0x50, 0x4B, 0x03, 0x04]) => {
+            return Err(AppError::coded(
+                ErrorCode::DocUnreadable,
+                "Invalid DOCX file: magic bytes do not match (expected ZIP signature)",
+            ));
+        }
+        _ => {
+            // For text files, no magic bytes validation needed
+        }
+    }
+
+    // Extract text content based on file type
+    let text_content = match file_type.to_lowercase().as_str() {
+        "pdf" => {
+            // Use PDF parser to extract text
+            extract_text_from_pdf(&decoded_bytes).map_err(|e| {
+                AppError::coded(
+                    ErrorCode::DocUnreadable,
+                    format!("Failed to extract text from PDF: {e}"),
+                )
+            })?
+        }
+        "docx" => {
+            // Use DOCX parser to extract text
+            extract_text_from_docx(&decoded_bytes).map_err(|e| {
+                AppError::coded(
+                    ErrorCode::DocUnreadable,
+                    format!("Failed to extract text from DOCX: {e}"),
+                )
+            })?
+        }
+        _ => {
+            // Assume plain text (txt, md, etc.)
+            String::from_utf8(decoded_bytes).map_err(|e| {
+                AppError::coded(
+                    ErrorCode::DocUnreadable,
+                    format!("Content is not valid UTF-8: {e}"),
+                )
+            })?
+        }
+    };
+
+    Ok(text_content)
+}
+
+/// Chunker settings used when ingesting uploads
+pub(crate) fn ingestion_chunk_config() -> otl_parser::ChunkConfig {
+    otl_parser::ChunkConfig {
+        chunk_size: 1000,
+        overlap: 200,
+        min_chunk_size: 100,
+        respect_sections: true,
+        respect_paragraphs: true,
+    }
+}
+
+/// Split extracted text into chunks using the default ingestion settings
+pub(crate) fn chunk_document_text(text: &str) -> Vec<String> {
+    chunk_text_simple(text, &ingestion_chunk_config())
+}
+
+/// Store the ingest report of a document's chunks in its metadata
+///
+/// Token counts are estimated for the configured embedding model. Logs
+/// instead of failing the ingestion.
+pub(crate) async fn record_ingest_report(state: &AppState, id: Uuid, chunks: &[String]) {
+    let counter = otl_vector::TokenCounter::for_config(&state.config.llm);
+    let report = otl_parser::IngestReport::build(
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i as u32, c.as_str(), None)),
+        counter.max_tokens,
+        |text| counter.count(text),
+    );
+    if !report.over_limit.is_empty() {
+        tracing::warn!(
+            "Document {id}: {} of {} chunks exceed the {} token input limit of {}",
+            report.over_limit.len(),
+            report.chunk_count,
+            report.token_limit,
+            state.config.llm.embedding_model
+        );
+    }
+
+    let result = sqlx::query(
+        "UPDATE documents \
+         SET metadata = COALESCE(metadata, '{}'::jsonb) || jsonb_build_object('ingest_report', $2::jsonb) \
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(sqlx::types::Json(&report))
+    .execute(&state.db_pool)
+    .await;
+    if let Err(e) = result {
+        tracing::warn!("Failed to record ingest report of document {id}: {e}");
+    }
+}
+
+/// Simple text chunking function with proper UTF-8 handling
+pub(crate) fn chunk_text_simple(text: &str, config: &otl_parser::ChunkConfig) -> Vec<String> {
+    let mut chunks = Vec::new();
+
+    if text.len() <= config.chunk_size {
+        chunks.push(text.to_string());
+        return chunks;
+    }
+
+    let mut start = 0;
+    while start < text.len() {
+        // Calculate target end position (ensuring char boundary)
+        let target_end = (start + config.chunk_size).min(text.len());
+        let end = find_char_boundary(text, target_end);
+
+        // Find a good break point (sentence or paragraph boundary)
+        let actual_end = find_chunk_boundary(text, start, end, config.respect_paragraphs);
+
+        // Ensure start is on char boundary
+        let safe_start = find_char_boundary(text, start);
+        let chunk_text = &text[safe_start..actual_end];
+
+        if chunk_text.len() >= config.min_chunk_size {
+            chunks.push(chunk_text.to_string());
+        }
+
+        if actual_end >= text.len() {
+            break;
+        }
+
+        // Move start with overlap (ensuring char boundary)
+        let overlap_pos = if actual_end > config.overlap {
+            actual_end - config.overlap
+        } else {
+            actual_end
+        };
+        start = find_char_boundary(text, overlap_pos);
+    }
+
+    chunks
+}
+
+/// Find the nearest valid UTF-8 character boundary at or before the given position
+fn find_char_boundary(text: &str, pos: usize) -> usize {
+    if pos >= text.len() {
+        return text.len();
+    }
+    let mut boundary = pos;
+    while boundary > 0 && !text.is_char_boundary(boundary) {
+        boundary -= 1;
+    }
+    boundary
+}
+
+/// Find a good boundary for chunking (respecting sentence/paragraph boundaries)
+fn find_chunk_boundary(
+    text: &str,
+    _start: usize,
+    target: usize,
+    respect_paragraphs: bool,
+) -> usize {
+    if target >= text.len() {
+        return text.len();
+    }
+
+    // Ensure target is on a valid char boundary
+    let target = find_char_boundary(text, target);
+
+    if !respect_paragraphs {
+        return target;
+    }
+
+    // Search window around target position (ensure boundaries are valid)
+    let search_start = find_char_boundary(text, target.saturating_sub(100));
+    let search_end = find_char_boundary(text, (target + 100).min(text.len()));
+    let search_text = &text[search_start..search_end];
+
+    // Look for paragraph break (double newline)
+    if let Some(pos) = search_text.rfind("\n\n") {
+        return (search_start + pos + 2).min(text.len());
+    }
+
+    // Look for sentence endings
+    for pattern in [". ", "。", "! ", "? ", ".\n", "。\n", "!\n", "?\n"] {
+        if let Some(pos) = search_text.rfind(pattern) {
+            return (search_start + pos + pattern.len()).min(text.len());
+        }
+    }
+
+    // Look for single newline
+    if let Some(pos) = search_text.rfind('\n') {
+        return (search_start + pos + 1).min(text.len());
+    }
+
+    // Fall back to target position
+    target
+}
+
+/// Delete document response
+#[derive(Debug, Serialize)]
+pub struct DeleteDocumentResponse {
+    pub message: String,
+}
+
+/// Delete a document
+///
+/// The document leaves search at once but can be restored until its
+/// retention period has passed (see [`crate::retention`]).
+#[utoipa::path(
+    delete,
+    path = "/api/v1/documents/{id}",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    responses(
+        (status = 200, description = "Document deleted"),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "Document not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn delete_document(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    // Get user context
+    let user = state.get_default_user(None);
+
+    // First, check if document exists and user has permission
+    #[derive(sqlx::FromRow)]
+    struct DocCheck {
+        #[allow(dead_code)]
+        id: Uuid,
+        access_level: String,
+        owner_id: Option<String>,
+        department: Option<String>,
+    }
+
+    let doc: Option<DocCheck> = sqlx::query_as(
+        "SELECT id, access_level::text, owner_id, department
+         FROM documents
+         WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?;
+
+    let doc = doc.ok_or_else(|| AppError::NotFound(format!("Document {id} not found")))?;
+
+    // Check ACL permissions
+    let acl = otl_core::DocumentAcl {
+        access_level: parse_access_level(&doc.access_level),
+        owner_id: doc.owner_id.clone(),
+        department: doc.department.clone(),
+        required_roles: Vec::new(),
+        allowed_users: Vec::new(),
+    };
+
+    if !acl.can_access(&user) {
+        return Err(AppError::coded(
+            ErrorCode::AclDenied,
+            "You don't have permission to delete this document",
+        ));
+    }
+
+    tracing::info!("Deleting document: {id}");
+
+    // Delete from vector store if available (use document-level deletion)
+    let vector_backend_guard = state.vector_backend.read().await;
+    if let Some(vector_backend) = vector_backend_guard.as_ref() {
+        let backend = vector_backend.clone();
+        drop(vector_backend_guard);
+
+        match backend.delete_by_document(id).await {
+            Ok(count) => {
+                tracing::info!("Deleted {count} vectors from vector store for document {id}");
+            }
+            Err(e) => {
+                tracing::warn!("Failed to delete vectors for document {id}: {e}");
+            }
+        }
+    }
+
+    // Soft delete the document (cascade will handle chunks via ON DELETE CASCADE)
+    let result = sqlx::query("UPDATE documents SET deleted_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete document: {e}")))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+
+    tracing::info!("Document {id} soft deleted successfully");
+
+    // Duplicates of this document's chunks become canonical copies again
+    match crate::dedup::release(&state, id).await {
+        Ok(released) if state.dedup.skip_embedding => {
+            for document_id in released {
+                let indexed = reindex_chunks(&state, document_id).await?.len();
+                tracing::info!("Re-indexed {indexed} chunks of document {document_id}");
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to release duplicates of document {id}: {e:?}"),
+    }
+
+    state.rag_cache.invalidate_document(id).await;
+
+    Ok((
+        StatusCode::OK,
+        Json(DeleteDocumentResponse {
+            message: format!("Document {id} deleted successfully"),
+        }),
+    ))
+}
+
+/// Restore document response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RestoreDocumentResponse {
+    pub id: Uuid,
+    pub message: String,
+    /// Chunks indexed again into the vector store
+    pub chunk_count: u32,
+}
+
+/// Restore a deleted document
+///
+/// Possible until the retention period after deletion has passed. The
+/// document's chunks are indexed into the vector store again.
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/{id}/restore",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    responses(
+        (status = 200, description = "Document restored", body = RestoreDocumentResponse),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "No deleted document with this ID", body = crate::error::ApiError),
+        (status = 410, description = "Retention period has passed (RESTORE_WINDOW_EXPIRED)", body = crate::error::ApiError)
+    )
+)]
+pub async fn restore_document(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, AppError> {
     state.increment_requests();
 
-    // Get user context
     let user = state.get_default_user(None);
 
-    // First, check if document exists and user has permission
-    #[derive(sqlx::FromRow)]
-    struct DocCheck {
-        #[allow(dead_code)]
-        id: Uuid,
-        access_level: String,
-        owner_id: Option<String>,
-        department: Option<String>,
-    }
+    #[derive(sqlx::FromRow)]
+    struct DeletedDoc {
+        access_level: String,
+        owner_id: Option<String>,
+        department: Option<String>,
+        deleted_at: DateTime<Utc>,
+    }
+
+    let doc: Option<DeletedDoc> = sqlx::query_as(
+        "SELECT access_level::text, owner_id, department, deleted_at
+         FROM documents
+         WHERE id = $1 AND deleted_at IS NOT NULL",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?;
+
+    let doc = doc.ok_or_else(|| AppError::NotFound(format!("Deleted document {id} not found")))?;
+
+    let acl = otl_core::DocumentAcl {
+        access_level: parse_access_level(&doc.access_level),
+        owner_id: doc.owner_id.clone(),
+        department: doc.department.clone(),
+        required_roles: Vec::new(),
+        allowed_users: Vec::new(),
+    };
+    if !acl.can_access(&user) {
+        return Err(AppError::coded(
+            ErrorCode::AclDenied,
+            "You don't have permission to restore this document",
+        ));
+    }
+    if !state.retention.is_restorable(doc.deleted_at, Utc::now()) {
+        return Err(AppError::coded(
+            ErrorCode::RestoreWindowExpired,
+            format!(
+                "Document {id} was deleted more than {} days ago",
+                state.retention.retention_days
+            ),
+        ));
+    }
+
+    let result = sqlx::query(
+        "UPDATE documents SET deleted_at = NULL, updated_at = NOW()
+         WHERE id = $1 AND deleted_at IS NOT NULL",
+    )
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to restore document: {e}")))?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!(
+            "Deleted document {id} not found"
+        )));
+    }
+
+    crate::dedup::link_chunks(&state, id).await;
+    let chunk_count = reindex_chunks(&state, id).await?.len() as u32;
+    tracing::info!("Document {id} restored, {chunk_count} chunks indexed");
+
+    Ok((
+        StatusCode::OK,
+        Json(RestoreDocumentResponse {
+            id,
+            message: format!("Document {id} restored"),
+            chunk_count,
+        }),
+    ))
+}
+
+/// Download the original file of a document
+///
+/// Streams the file as it was uploaded from the blob store.
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/original",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    responses(
+        (status = 200, description = "Original file", content_type = "application/octet-stream"),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "Document or its original not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn download_original(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    super::chunks::authorize_document(&state, id, &user.to_acl_user()).await?;
+    original_response(&state, id, "attachment").await
+}
+
+/// The original of a document as a response with `disposition`
+async fn original_response(
+    state: &AppState,
+    id: Uuid,
+    disposition: &'static str,
+) -> Result<impl IntoResponse, AppError> {
+    let file_type: String = sqlx::query_scalar(
+        "SELECT file_type::text FROM documents WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?
+    .ok_or_else(|| AppError::NotFound(format!("Document {id} not found")))?;
+
+    // Sealed originals are opened in memory; others are streamed through
+    let key = blob::original_key(id);
+    let sealed = state.keyring.is_enabled()
+        && state
+            .keyring
+            .document_key(&state.db_pool, id)
+            .await?
+            .is_some();
+    let body = if sealed {
+        read_original(state, id, &key).await.map(Body::from)
+    } else {
+        state.blob_store.get(&key).await.map(Body::from_stream)
+    };
+    let body = match body {
+        Ok(body) => body,
+        Err(otl_core::OtlError::NotFound(_)) => {
+            return Err(AppError::NotFound(format!(
+                "Original of document {id} is not stored"
+            )))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, media_type(&file_type)),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        ],
+        body,
+    ))
+}
+
+/// Share document request
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ShareDocumentRequest {
+    /// `download` (default) or `preview`
+    #[serde(default)]
+    pub scope: ShareScope,
+
+    /// Lifetime of the link in seconds (default `SHARE_URL_TTL_SECS`, 24
+    /// hours)
+    pub expires_in_secs: Option<i64>,
+
+    /// Who the link is for, recorded in the audit log
+    pub recipient: Option<String>,
+}
+
+/// Share document response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ShareDocumentResponse {
+    /// Link that works without authentication until `expires_at`
+    pub url: String,
+    pub scope: ShareScope,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Create a temporary link to a document
+///
+/// The link gives anyone who has it access to the original without an
+/// account, until it expires. It is signed, so its document, scope and
+/// expiry cannot be changed, and it stops working when the issuing user
+/// loses access to the document.
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/{id}/share",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    request_body = ShareDocumentRequest,
+    responses(
+        (status = 200, description = "Signed link", body = ShareDocumentResponse),
+        (status = 400, description = "Lifetime out of range", body = crate::error::ApiError),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "Document not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn share_document(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<ShareDocumentRequest>,
+) -> Result<Json<ShareDocumentResponse>, AppError> {
+    state.increment_requests();
+
+    super::chunks::authorize_document(&state, id, &user.to_acl_user()).await?;
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM documents WHERE id = $1 AND deleted_at IS NULL)",
+    )
+    .bind(id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?;
+    if !exists {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+
+    let grant = state
+        .share
+        .grant(id, request.scope, request.expires_in_secs, user.user_id)?;
+    let expires_at = DateTime::from_timestamp(grant.expires, 0).unwrap_or_else(Utc::now);
+    audit_log(&AuditEvent::DocumentShared {
+        document_id: id,
+        scope: grant.scope.as_str().to_string(),
+        expires_at,
+        shared_by: user.user_id,
+        recipient: request.recipient,
+        ip_address: extract_ip_address(&headers),
+    });
+
+    Ok(Json(ShareDocumentResponse {
+        url: state.share.url(&grant),
+        scope: grant.scope,
+        expires_at,
+    }))
+}
+
+/// Query parameters of a signed link
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SharedDocumentQuery {
+    pub scope: ShareScope,
+    /// Expiry, Unix seconds
+    pub expires: i64,
+    /// Issuing user
+    pub by: Uuid,
+    /// HMAC-SHA256 signature
+    pub sig: String,
+}
+
+/// Open a document through a signed link
+///
+/// Needs no authentication. Serves the original as an attachment
+/// (`download`) or inline (`preview`).
+#[utoipa::path(
+    get,
+    path = "/api/v1/shared/documents/{id}",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID"),
+        SharedDocumentQuery
+    ),
+    responses(
+        (status = 200, description = "Original file", content_type = "application/octet-stream"),
+        (status = 403, description = "Invalid link, or the issuer lost access to the document", body = crate::error::ApiError),
+        (status = 404, description = "Document or its original not found", body = crate::error::ApiError),
+        (status = 410, description = "Link expired (SHARE_LINK_EXPIRED)", body = crate::error::ApiError)
+    )
+)]
+pub async fn open_shared_document(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<SharedDocumentQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let ip_address = extract_ip_address(&headers);
+    let user_agent = extract_user_agent(&headers);
+    let grant = ShareGrant {
+        document_id: id,
+        scope: params.scope,
+        expires: params.expires,
+        issued_by: params.by,
+    };
+    if let Err(e) = state.share.verify(&grant, &params.sig, Utc::now()) {
+        audit_log(&AuditEvent::InvalidToken {
+            ip_address,
+            user_agent,
+            reason: format!("Invalid or expired share link for document {id}"),
+        });
+        return Err(e);
+    }
+
+    // The link carries the access of its issuer, as it is now
+    let issuer = match users::get_user(&state, grant.issued_by).await {
+        Ok(issuer) if issuer.is_active => issuer,
+        Ok(_) | Err(AppError::NotFound(_)) => {
+            return Err(AppError::Forbidden(
+                "Share link is no longer valid".to_string(),
+            ))
+        }
+        Err(e) => return Err(e),
+    };
+    super::chunks::authorize_document(&state, id, &issuer.to_acl_user()).await?;
+
+    let disposition = match grant.scope {
+        ShareScope::Download => "attachment",
+        ShareScope::Preview => "inline",
+    };
+    let response = original_response(&state, id, disposition).await?;
+    audit_log(&AuditEvent::SharedDocumentAccessed {
+        document_id: id,
+        scope: grant.scope.as_str().to_string(),
+        shared_by: grant.issued_by,
+        ip_address,
+        user_agent,
+    });
+    Ok(response)
+}
+
+/// Request for access to a document
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct AccessRequestBody {
+    /// Why the document is needed
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Access end to ask for (approvers may change it)
+    #[serde(default)]
+    pub requested_until: Option<DateTime<Utc>>,
+}
+
+/// Ask for access to a document the ACL denies
+///
+/// The document owner and the editors of its department (or the admins)
+/// are notified and decide the request.
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/{id}/access-requests",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    request_body = AccessRequestBody,
+    responses(
+        (status = 201, description = "Access requested", body = AccessRequest),
+        (status = 400, description = "Already has access, or a request is pending", body = crate::error::ApiError),
+        (status = 404, description = "Document not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn request_document_access(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<AccessRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let access_request = access_requests::request_access(
+        &state,
+        id,
+        &user,
+        request.reason,
+        request.requested_until,
+        extract_ip_address(&headers),
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(access_request)))
+}
+
+/// List the caller's access requests, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/access-requests",
+    tag = "documents",
+    responses(
+        (status = 200, description = "Access requests made by the caller", body = Vec<AccessRequest>)
+    )
+)]
+pub async fn list_access_requests(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<Vec<AccessRequest>>, AppError> {
+    state.increment_requests();
+    Ok(Json(access_requests::list_own(&state, &user).await?))
+}
+
+/// List the pending access requests the caller may decide, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/access-requests/pending",
+    tag = "documents",
+    responses(
+        (status = 200, description = "Requests awaiting the caller's decision", body = Vec<AccessRequest>)
+    )
+)]
+pub async fn list_pending_access_requests(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<Vec<AccessRequest>>, AppError> {
+    state.increment_requests();
+    Ok(Json(access_requests::list_pending(&state, &user).await?))
+}
 
-    let doc: Option<DocCheck> = sqlx::query_as(
-        "SELECT id, access_level::text, owner_id, department
-         FROM documents
+/// Decision on an access request
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct AccessDecisionBody {
+    /// Access end of an approval; defaults to the one requested, none
+    /// grants access until withdrawn
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Note for the requester
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Approve an access request
+///
+/// Adds the requester to the document's `allowed_users`, until
+/// `expires_at` if set.
+#[utoipa::path(
+    post,
+    path = "/api/v1/access-requests/{id}/approve",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Access request UUID")
+    ),
+    request_body = AccessDecisionBody,
+    responses(
+        (status = 200, description = "Access granted", body = AccessRequest),
+        (status = 400, description = "Request already decided, or expiry in the past", body = crate::error::ApiError),
+        (status = 403, description = "Not the document owner, an editor of its department or an admin", body = crate::error::ApiError),
+        (status = 404, description = "Access request not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn approve_access_request(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Option<Json<AccessDecisionBody>>,
+) -> Result<Json<AccessRequest>, AppError> {
+    state.increment_requests();
+
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let decision = access_requests::Decision {
+        approve: true,
+        expires_at: body.expires_at,
+        note: body.note,
+    };
+    let request =
+        access_requests::decide(&state, id, &user, decision, extract_ip_address(&headers)).await?;
+    Ok(Json(request))
+}
+
+/// Deny an access request
+#[utoipa::path(
+    post,
+    path = "/api/v1/access-requests/{id}/deny",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Access request UUID")
+    ),
+    request_body = AccessDecisionBody,
+    responses(
+        (status = 200, description = "Access refused", body = AccessRequest),
+        (status = 400, description = "Request already decided", body = crate::error::ApiError),
+        (status = 403, description = "Not the document owner, an editor of its department or an admin", body = crate::error::ApiError),
+        (status = 404, description = "Access request not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn deny_access_request(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Option<Json<AccessDecisionBody>>,
+) -> Result<Json<AccessRequest>, AppError> {
+    state.increment_requests();
+
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let decision = access_requests::Decision {
+        approve: false,
+        expires_at: None,
+        note: body.note,
+    };
+    let request =
+        access_requests::decide(&state, id, &user, decision, extract_ip_address(&headers)).await?;
+    Ok(Json(request))
+}
+
+/// Reprocess document response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReprocessDocumentResponse {
+    pub id: Uuid,
+    pub message: String,
+    /// Chunks derived from the original
+    pub chunk_count: u32,
+    /// Chunks indexed into the vector store
+    pub indexed: u32,
+}
+
+/// Rebuild a document from its stored original
+///
+/// Extracts the text of the original again and chunks it with the current
+/// settings, replaces the stored chunks and their vectors, and records new
+/// lineage. Used after parser or chunker changes and to repair chunks an
+/// integrity check found corrupted.
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/{id}/reprocess",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID")
+    ),
+    responses(
+        (status = 200, description = "Document reprocessed", body = ReprocessDocumentResponse),
+        (status = 403, description = "Editor role required, or denied by the document ACL", body = crate::error::ApiError),
+        (status = 404, description = "Document or its original not found", body = crate::error::ApiError),
+        (status = 422, description = "Original cannot be read as its file type (DOC_UNREADABLE)", body = crate::error::ApiError)
+    )
+)]
+pub async fn reprocess_document(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required to reprocess documents".to_string(),
+        ));
+    }
+    super::chunks::authorize_document(&state, id, &user.to_acl_user()).await?;
+    let (file_type, access_level): (String, String) = sqlx::query_as(
+        "SELECT file_type::text, access_level::text FROM documents \
          WHERE id = $1 AND deleted_at IS NULL",
     )
     .bind(id)
     .fetch_optional(&state.db_pool)
     .await
-    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?;
+    .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?
+    .ok_or_else(|| AppError::NotFound(format!("Document {id} not found")))?;
+
+    let bytes = match read_original(&state, id, &blob::original_key(id)).await {
+        Ok(bytes) => bytes,
+        Err(otl_core::OtlError::NotFound(_)) => {
+            return Err(AppError::NotFound(format!(
+                "Original of document {id} is not stored"
+            )))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let text = extract_document_text(bytes, &file_type)?;
+    let chunks = chunk_document_text(&text);
+    let chunk_count = chunks.len() as u32;
+    record_ingest_report(&state, id, &chunks).await;
+
+    replace_chunks(&state, id, &chunks, parse_access_level(&access_level)).await?;
+    crate::dedup::link_chunks(&state, id).await;
+    let vector_ids = reindex_chunks(&state, id).await?;
+    let lineage = DocumentLineage::new(
+        id,
+        ParserLineage::for_file_type(&file_type),
+        &ingestion_chunk_config(),
+        &chunks,
+    )
+    .with_indexed_chunks(&state.config.llm.embedding_model, &vector_ids);
+    crate::lineage::record(&state.db_pool, &lineage).await;
 
-    let doc = doc.ok_or_else(|| AppError::NotFound(format!("Document {id} not found")))?;
+    tracing::info!(
+        "Document {id} reprocessed by {}: {chunk_count} chunks, {} indexed",
+        user.email,
+        vector_ids.len()
+    );
 
-    // Check ACL permissions
-    let acl = otl_core::DocumentAcl {
-        access_level: parse_access_level(&doc.access_level),
-        owner_id: doc.owner_id.clone(),
-        department: doc.department.clone(),
-        required_roles: Vec::new(),
-        allowed_users: Vec::new(),
+    Ok(Json(ReprocessDocumentResponse {
+        id,
+        message: format!("Document {id} reprocessed from its original"),
+        chunk_count,
+        indexed: vector_ids.len() as u32,
+    }))
+}
+
+/// Replace the stored chunks of a document, recording their hashes
+///
+/// Hashes are taken over the plaintext; content is sealed with the
+/// document's data key if it has (or, being Restricted, needs) one.
+pub(crate) async fn replace_chunks(
+    state: &AppState,
+    id: Uuid,
+    chunks: &[String],
+    access_level: otl_core::AccessLevel,
+) -> Result<(), AppError> {
+    let indices: Vec<i32> = (0..chunks.len() as i32).collect();
+    let hashes: Vec<String> = chunks.iter().map(|c| content_hash(c)).collect();
+    let contents: Vec<String> = match state
+        .keyring
+        .content_key(&state.db_pool, id, access_level)
+        .await?
+    {
+        Some(key) => chunks
+            .iter()
+            .enumerate()
+            .map(|(i, c)| key.seal_text(c, &chunk_context(id, i as u32)))
+            .collect::<otl_core::Result<_>>()?,
+        None => chunks.to_vec(),
     };
 
-    if !acl.can_access(&user) {
-        return Err(AppError::Forbidden(
-            "You don't have permission to delete this document".to_string(),
-        ));
+    let mut tx = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to start transaction: {e}")))?;
+    sqlx::query("DELETE FROM document_chunks WHERE document_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete chunks: {e}")))?;
+    sqlx::query(
+        r#"
+        INSERT INTO document_chunks (document_id, chunk_index, content, content_hash)
+        SELECT $1, c.chunk_index, c.content, c.content_hash
+        FROM UNNEST($2::int[], $3::text[], $4::text[]) AS c(chunk_index, content, content_hash)
+        "#,
+    )
+    .bind(id)
+    .bind(&indices)
+    .bind(&contents)
+    .bind(&hashes)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to store chunks: {e}")))?;
+    sqlx::query("UPDATE documents SET updated_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update document: {e}")))?;
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to commit chunks: {e}")))?;
+    Ok(())
+}
+
+/// Record the vector IDs of indexed chunks, by chunk index
+pub(crate) async fn store_vector_ids(
+    state: &AppState,
+    id: Uuid,
+    vector_ids: &HashMap<usize, String>,
+) -> Result<(), AppError> {
+    if vector_ids.is_empty() {
+        return Ok(());
     }
+    let (indices, ids): (Vec<i32>, Vec<String>) = vector_ids
+        .iter()
+        .map(|(index, vector_id)| (*index as i32, vector_id.clone()))
+        .unzip();
+    sqlx::query(
+        "UPDATE document_chunks SET vector_id = v.vector_id \
+         FROM UNNEST($2::int[], $3::text[]) AS v(chunk_index, vector_id) \
+         WHERE document_chunks.document_id = $1 AND document_chunks.chunk_index = v.chunk_index",
+    )
+    .bind(id)
+    .bind(&indices)
+    .bind(&ids)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to update chunks: {e}")))?;
+    Ok(())
+}
 
-    tracing::info!("Deleting document: {id}");
+/// Index the stored chunks of a document into the vector store
+///
+/// Duplicates of embedded chunks are left out when the dedup policy skips
+/// them. Returns the vector IDs of the chunks indexed, by chunk index (none
+/// without a vector store).
+pub(crate) async fn reindex_chunks(
+    state: &AppState,
+    id: Uuid,
+) -> Result<HashMap<usize, String>, AppError> {
+    let Some(backend) = state.vector_backend.read().await.clone() else {
+        tracing::warn!("Vector backend not initialized, document {id} not indexed");
+        return Ok(HashMap::new());
+    };
 
-    // Delete from vector store if available (use document-level deletion)
-    let vector_backend_guard = state.vector_backend.read().await;
-    if let Some(vector_backend) = vector_backend_guard.as_ref() {
-        let backend = vector_backend.clone();
-        drop(vector_backend_guard);
+    #[derive(sqlx::FromRow)]
+    struct ChunkRow {
+        id: Uuid,
+        chunk_index: i32,
+        content: String,
+    }
 
-        match backend.delete_by_document(id).await {
-            Ok(count) => {
-                tracing::info!("Deleted {count} vectors from vector store for document {id}");
-            }
-            Err(e) => {
-                tracing::warn!("Failed to delete vectors for document {id}: {e}");
+    let mut chunks: Vec<ChunkRow> = sqlx::query_as(
+        "SELECT id, chunk_index, content FROM document_chunks
+         WHERE document_id = $1 ORDER BY chunk_index",
+    )
+    .bind(id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch chunks: {e}")))?;
+    state
+        .keyring
+        .open_chunks(
+            &state.db_pool,
+            id,
+            chunks
+                .iter_mut()
+                .map(|c| (c.chunk_index.max(0) as u32, &mut c.content)),
+        )
+        .await?;
+
+    // Vectors may survive a failed delete; avoid indexing them twice
+    if let Err(e) = backend.delete_by_document(id).await {
+        tracing::warn!("Failed to clear old vectors of document {id}: {e}");
+    }
+
+    let skipped = crate::dedup::skipped_chunks(state, id).await?;
+    let mut indexed = HashMap::new();
+    for chunk in chunks {
+        if skipped.contains_key(&(chunk.chunk_index.max(0) as u32)) {
+            sqlx::query("UPDATE document_chunks SET vector_id = NULL WHERE id = $1")
+                .bind(chunk.id)
+                .execute(&state.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to update chunk: {e}")))?;
+            continue;
+        }
+        match backend
+            .index_text(id, chunk.chunk_index as u32, &chunk.content)
+            .await
+        {
+            Ok(vector_id) => {
+                let vector_id = vector_id.to_string();
+                sqlx::query("UPDATE document_chunks SET vector_id = $2 WHERE id = $1")
+                    .bind(chunk.id)
+                    .bind(&vector_id)
+                    .execute(&state.db_pool)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to update chunk: {e}")))?;
+                indexed.insert(chunk.chunk_index.max(0) as usize, vector_id);
             }
+            Err(e) => tracing::warn!(
+                "Failed to index chunk {} of document {}: {}",
+                chunk.chunk_index,
+                id,
+                e
+            ),
         }
     }
 
-    // Soft delete the document (cascade will handle chunks via ON DELETE CASCADE)
-    let result = sqlx::query("UPDATE documents SET deleted_at = NOW() WHERE id = $1")
-        .bind(id)
-        .execute(&state.db_pool)
-        .await
-        .map_err(|e| AppError::Database(format!("Failed to delete document: {e}")))?;
+    // New vectors start untagged
+    if !indexed.is_empty() {
+        match MetadataStore::from_pool(state.db_pool.clone())
+            .get_document(id)
+            .await
+        {
+            Ok(Some(doc)) => crate::freshness::tag_vectors(state, &doc).await,
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to tag vectors of document {id}: {e}"),
+        }
+    }
+    Ok(indexed)
+}
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound(format!("Document {id} not found")));
+/// Compare documents request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CompareDocumentsRequest {
+    /// Older version
+    pub old_document_id: Uuid,
+
+    /// Newer version
+    pub new_document_id: Uuid,
+
+    /// Ask the LLM for a change summary citing both versions
+    #[serde(default)]
+    pub summarize: bool,
+
+    /// Similarity (0-1) two sections with different headings need to be aligned
+    #[schema(example = 0.5)]
+    pub min_similarity: Option<f32>,
+}
+
+/// Document taking part in a comparison
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ComparedDocument {
+    /// Document UUID
+    pub id: Uuid,
+
+    /// Document title
+    #[schema(example = "인사규정_2024.pdf")]
+    pub title: String,
+
+    /// Number of sections the document was split into
+    pub sections: usize,
+}
+
+/// Document comparison response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CompareDocumentsResponse {
+    pub old_document: ComparedDocument,
+    pub new_document: ComparedDocument,
+
+    /// Sections by change
+    pub stats: CompareStats,
+
+    /// Aligned sections in the order of the new document
+    pub sections: Vec<SectionDiff>,
+
+    /// Change summary, when requested and there are changes
+    pub summary: Option<String>,
+
+    /// Sources cited by the summary
+    pub citations: Vec<CompareCitation>,
+}
+
+/// Compare two documents section by section
+///
+/// Sections are aligned by heading and content, then diffed word by word.
+/// With `summarize`, the LLM describes the changes and cites the old and new
+/// sections it relied on.
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/compare",
+    tag = "documents",
+    request_body = CompareDocumentsRequest,
+    responses(
+        (status = 200, description = "Section-level comparison", body = CompareDocumentsResponse),
+        (status = 400, description = "Invalid request", body = crate::error::ApiError),
+        (status = 403, description = "Denied by a document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "Document not found", body = crate::error::ApiError),
+        (status = 503, description = "Summary requested but no LLM is configured", body = crate::error::ApiError)
+    )
+)]
+pub async fn compare_documents(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthenticatedUser>,
+    Json(req): Json<CompareDocumentsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if req.old_document_id == req.new_document_id {
+        return Err(AppError::BadRequest(
+            "old_document_id and new_document_id must differ".to_string(),
+        ));
+    }
+    let min_similarity = req
+        .min_similarity
+        .unwrap_or(compare::DEFAULT_MIN_SIMILARITY);
+    if !(0.0..=1.0).contains(&min_similarity) {
+        return Err(AppError::BadRequest(
+            "min_similarity must be between 0 and 1".to_string(),
+        ));
     }
 
-    tracing::info!("Document {id} soft deleted successfully");
+    let user = caller.to_acl_user();
+    for id in [req.old_document_id, req.new_document_id] {
+        super::chunks::authorize_document(&state, id, &user).await?;
+    }
 
-    Ok((
-        StatusCode::OK,
-        Json(DeleteDocumentResponse {
-            message: format!("Document {id} deleted successfully"),
-        }),
-    ))
+    let old_document = load_compare_document(&state, req.old_document_id).await?;
+    let new_document = load_compare_document(&state, req.new_document_id).await?;
+    let old_sections = load_sections(&state, req.old_document_id).await?;
+    let new_sections = load_sections(&state, req.new_document_id).await?;
+
+    let (sections, stats) = compare::compare_sections(&old_sections, &new_sections, min_similarity);
+
+    let mut summary = None;
+    let mut citations = Vec::new();
+    let first_change = sections
+        .iter()
+        .find(|s| s.change != SectionChange::Unchanged)
+        .and_then(|s| s.new.as_ref().or(s.old.as_ref()));
+    if let (true, Some(first_change)) = (req.summarize, first_change) {
+        let llm =
+            state.llm_client.read().await.clone().ok_or_else(|| {
+                AppError::coded(ErrorCode::ServiceUnavailable, "LLM not initialized")
+            })?;
+        let template = PromptTemplate::for_language(detect_language(&first_change.text));
+        let (prompt, sources) =
+            compare::summary_prompt(template, &old_document, &new_document, &sections);
+        let answer = llm.generate(&prompt).await?;
+        let (text, cited) = compare::cite_summary(&answer, &sources);
+        summary = Some(text);
+        citations = cited;
+    }
+
+    Ok(Json(CompareDocumentsResponse {
+        old_document: ComparedDocument {
+            id: old_document.id,
+            title: old_document.title,
+            sections: old_sections.len(),
+        },
+        new_document: ComparedDocument {
+            id: new_document.id,
+            title: new_document.title,
+            sections: new_sections.len(),
+        },
+        stats,
+        sections,
+        summary,
+        citations,
+    }))
+}
+
+async fn load_compare_document(
+    state: &AppState,
+    id: Uuid,
+) -> Result<compare::CompareDocument, AppError> {
+    let title: String =
+        sqlx::query_scalar("SELECT title FROM documents WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch document: {e}")))?
+            .ok_or_else(|| AppError::NotFound(format!("Document {id} not found")))?;
+    Ok(compare::CompareDocument { id, title })
+}
+
+/// Sections of a stored document, with chunk embeddings when the vector
+/// store is available
+async fn load_sections(state: &AppState, id: Uuid) -> Result<Vec<compare::Section>, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        chunk_index: i32,
+        content: String,
+        start_offset: Option<i32>,
+        end_offset: Option<i32>,
+        page_number: Option<i32>,
+        section_name: Option<String>,
+    }
+
+    let mut rows: Vec<Row> = sqlx::query_as(
+        "SELECT chunk_index, content, start_offset, end_offset, page_number, section_name \
+         FROM document_chunks WHERE document_id = $1 ORDER BY chunk_index",
+    )
+    .bind(id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch chunks: {e}")))?;
+    state
+        .keyring
+        .open_chunks(
+            &state.db_pool,
+            id,
+            rows.iter_mut()
+                .map(|r| (r.chunk_index.max(0) as u32, &mut r.content)),
+        )
+        .await?;
+
+    let to_u32 = |v: Option<i32>| v.and_then(|n| u32::try_from(n).ok());
+    let chunks: Vec<compare::CompareChunk> = rows
+        .into_iter()
+        .map(|row| compare::CompareChunk {
+            chunk_index: row.chunk_index.max(0) as u32,
+            content: row.content,
+            start_offset: to_u32(row.start_offset),
+            end_offset: to_u32(row.end_offset),
+            page: to_u32(row.page_number),
+            section: row.section_name,
+        })
+        .collect();
+
+    let mut vectors = HashMap::new();
+    if let Some(backend) = state.vector_backend.read().await.clone() {
+        match backend.document_vectors(id).await {
+            Ok(stored) => {
+                vectors = stored
+                    .into_iter()
+                    .filter_map(|v| Some((v.chunk_index?, v.vector)))
+                    .collect();
+            }
+            Err(e) => tracing::warn!("Comparing document {} without embeddings: {}", id, e),
+        }
+    }
+
+    Ok(compare::group_sections(&chunks, &vectors))
 }
 
 // ============================================================================
@@ -765,7 +2844,49 @@ pub async fn delete_document(
 // ============================================================================
 
 /// Parse access level string to enum
-fn parse_access_level(level: &str) -> otl_core::AccessLevel {
+/// Fetch the ACLs of non-deleted documents, keyed by document ID
+pub(crate) async fn fetch_document_acls(
+    state: &AppState,
+    document_ids: &[Uuid],
+) -> Result<HashMap<Uuid, otl_core::DocumentAcl>, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct AclRow {
+        id: Uuid,
+        access_level: String,
+        department: Option<String>,
+        owner_id: Option<String>,
+        required_roles: Option<Vec<String>>,
+        allowed_users: Option<Vec<String>>,
+    }
+
+    let rows: Vec<AclRow> = sqlx::query_as(&format!(
+        "SELECT d.id, d.access_level::text, d.department, d.owner_id, d.required_roles, \
+         {LIVE_ALLOWED_USERS} AS allowed_users \
+         FROM documents d WHERE d.id = ANY($1) AND d.deleted_at IS NULL"
+    ))
+    .bind(document_ids)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch document ACLs: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.id,
+                otl_core::DocumentAcl {
+                    access_level: parse_access_level(&row.access_level),
+                    owner_id: row.owner_id,
+                    department: row.department,
+                    required_roles: row.required_roles.unwrap_or_default(),
+                    allowed_users: row.allowed_users.unwrap_or_default(),
+                },
+            )
+        })
+        .collect())
+}
+
+pub(crate) fn parse_access_level(level: &str) -> otl_core::AccessLevel {
     match level.to_lowercase().as_str() {
         "public" => otl_core::AccessLevel::Public,
         "internal" => otl_core::AccessLevel::Internal,
@@ -847,3 +2968,130 @@ fn extract_text_node(child: docx_rs::RunChild) -> Option<String> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_heading() {
+        assert_eq!(
+            markdown_heading("## 제5조 연차휴가"),
+            Some((2, "제5조 연차휴가"))
+        );
+        assert_eq!(markdown_heading("# 총칙 #"), Some((1, "총칙")));
+        assert_eq!(markdown_heading("#태그"), None);
+        assert_eq!(markdown_heading("####### 너무 깊음"), None);
+        assert_eq!(markdown_heading("본문"), None);
+    }
+
+    #[test]
+    fn test_build_structure_graph() {
+        let doc_id = Uuid::new_v4();
+        let chunks = vec![
+            "서문입니다.".to_string(),
+            "# 제2장 휴가\n## 제5조 연차휴가\n연차휴가는 15일입니다.".to_string(),
+            "연차휴가 신청은 팀장 승인이 필요합니다.".to_string(),
+        ];
+        let vector_ids = HashMap::from([(1, "v1".to_string())]);
+
+        let graph = build_structure_graph(doc_id, "인사규정", &chunks, &vector_ids);
+
+        assert_eq!(graph.sections().len(), 2);
+        assert_eq!(graph.chunks().len(), 3);
+        let chunk_ids: Vec<Uuid> = graph.chunks().iter().map(|c| c.id).collect();
+        assert!(graph.section_of(chunk_ids[0]).is_none());
+        assert_eq!(
+            graph.section_of(chunk_ids[1]).unwrap().properties["name"],
+            "제2장 휴가"
+        );
+        // Continuation chunks inherit the last heading
+        assert_eq!(
+            graph.section_of(chunk_ids[2]).unwrap().properties["name"],
+            "제5조 연차휴가"
+        );
+        assert_eq!(graph.chunks()[1].properties["vector_id"], "v1");
+    }
+
+    #[test]
+    fn test_read_table_against_default_ontology() {
+        let csv = "사번,성명,부서,담당부서\nE001,김철수,인사팀,\"인사팀,총무팀\"\n";
+        let table = read_table(csv.as_bytes(), "CSV", None).unwrap();
+        let mapping: TableMapping = serde_json::from_value(serde_json::json!({
+            "class": "Employee",
+            "key": "성명",
+            "properties": [{"column": "사번", "property": "employeeId", "required": true}],
+            "relations": [
+                {"column": "부서", "predicate": "belongsTo", "class": "Team"},
+                {"column": "담당부서", "predicate": "manages", "class": "Department", "separator": ","}
+            ]
+        }))
+        .unwrap();
+
+        let classes = crate::handlers::graph::default_ontology().to_core_classes();
+        mapping.validate(&table.headers, &classes).unwrap();
+        let extraction = mapping.extract(&table.headers, &table.rows);
+        assert!(extraction.errors.is_empty());
+        assert_eq!(extraction.relation_count(), 3);
+
+        // belongsTo is functional, so its cells cannot be split
+        let mut split = mapping.clone();
+        split.relations[0].separator = Some(",".to_string());
+        assert!(split.validate(&table.headers, &classes).is_err());
+
+        assert!(matches!(
+            read_table(b"a,b", "pdf", None),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_match_leave_request_form() {
+        let template: FormTemplate = serde_json::from_value(serde_json::json!({
+            "name": "leave_request",
+            "class": "LeaveRequest",
+            "markers": ["휴가신청서"],
+            "fields": [
+                {"property": "applicant", "label": "성명", "required": true,
+                 "relation": {"predicate": "submittedBy", "class": "Employee"}},
+                {"property": "leaveType", "label": "휴가종류"}
+            ]
+        }))
+        .unwrap();
+        let classes = crate::handlers::graph::default_ontology().to_core_classes();
+        template.validate(&classes).unwrap();
+
+        let registry = FormRegistry::new(vec![template]);
+        let text = "휴 가 신 청 서\n성 명: 김철수\n휴가종류: 연차\n";
+        let (matched, score) = match_form(&registry, None, text).unwrap();
+        assert_eq!(matched.name, "leave_request");
+        assert!((score - 1.0).abs() < f32::EPSILON);
+
+        let form = matched.extract(text);
+        assert!(form.missing.is_empty());
+        assert_eq!(form.relations[0].object.text, "김철수");
+        assert_eq!(form.fields()["leaveType"], "연차");
+
+        assert!(matches!(
+            match_form(&registry, None, "경비청구서"),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            match_form(&registry, Some("expense_claim"), text),
+            Err(AppError::NotFound(_))
+        ));
+        assert_eq!(stored_file_type("MD"), "markdown");
+    }
+
+    #[test]
+    fn test_media_type_of_stored_file_types() {
+        for file_type in ["pdf", "docx", "xlsx", "pptx", "markdown", "text", "html"] {
+            assert_ne!(media_type(file_type), "application/octet-stream");
+            assert_eq!(
+                media_type(stored_file_type(file_type)),
+                media_type(file_type)
+            );
+        }
+        assert_eq!(media_type("other"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,167 @@
+//! Document export handlers
+//!
+//! Downloads the processed artifacts of a document as a zipped JSONL
+//! bundle (see [`crate::export`]). Small documents are exported in the
+//! request; large ones, or any with `mode=async`, by a background job.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::AppError;
+use crate::export::{self, ExportJobStatus, ExportPart};
+use crate::state::AppState;
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+/// Query parameters for document export
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExportQuery {
+    /// Comma-separated parts: `chunks`, `entities`, `triples`, `embeddings`
+    /// (default: all)
+    #[param(example = "chunks,entities,triples,embeddings")]
+    pub include: Option<String>,
+
+    /// `async` to always export in a background job
+    #[param(example = "async")]
+    pub mode: Option<String>,
+}
+
+fn zip_response(document_id: Uuid, bundle: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"document-{document_id}.zip\""),
+            ),
+        ],
+        Body::from(bundle),
+    )
+        .into_response()
+}
+
+/// Export a document's chunks, entities, triples and embeddings
+///
+/// Returns the zip bundle directly, or `202 Accepted` with an export job
+/// for documents over the synchronous size limit or with `mode=async`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/export",
+    tag = "documents",
+    params(
+        ("id" = Uuid, Path, description = "Document UUID"),
+        ExportQuery
+    ),
+    responses(
+        (status = 200, description = "Zip bundle of JSONL files", content_type = "application/zip"),
+        (status = 202, description = "Export job started", body = export::ExportJobInfo),
+        (status = 400, description = "Unknown export part", body = crate::error::ApiError),
+        (status = 403, description = "Denied by the document ACL (ACL_DENIED)", body = crate::error::ApiError),
+        (status = 404, description = "Document not found", body = crate::error::ApiError),
+        (status = 503, description = "Graph or vector store not initialized", body = crate::error::ApiError)
+    )
+)]
+pub async fn export_document(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    state.increment_requests();
+
+    super::chunks::authorize_document(&state, id, &user.to_acl_user()).await?;
+    let parts = ExportPart::parse_list(params.include.as_deref())?;
+
+    let chunk_count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM document_chunks WHERE document_id = $1")
+            .bind(id)
+            .fetch_one(&state.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count chunks: {e}")))?;
+
+    let force_async = params.mode.as_deref() == Some("async");
+    if force_async || chunk_count > export::SYNC_EXPORT_MAX_CHUNKS {
+        let job = state
+            .export_jobs
+            .start(state.clone(), user.user_id, id, parts)
+            .await;
+        tracing::info!("Export job {} started for document {}", job.job_id, id);
+        return Ok((StatusCode::ACCEPTED, Json(job)).into_response());
+    }
+
+    let bundle = export::build_bundle(&state, id, &parts).await?;
+    Ok(zip_response(id, bundle))
+}
+
+/// Get the status of an export job
+#[utoipa::path(
+    get,
+    path = "/api/v1/exports/{job_id}",
+    tag = "documents",
+    params(
+        ("job_id" = Uuid, Path, description = "Export job ID")
+    ),
+    responses(
+        (status = 200, description = "Export job", body = export::ExportJobInfo),
+        (status = 404, description = "Job not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn get_export_job(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(job_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let job = state
+        .export_jobs
+        .get(job_id, user.user_id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("Export job {job_id}")))?;
+    Ok(Json(job))
+}
+
+/// Download the bundle of a completed export job
+#[utoipa::path(
+    get,
+    path = "/api/v1/exports/{job_id}/download",
+    tag = "documents",
+    params(
+        ("job_id" = Uuid, Path, description = "Export job ID")
+    ),
+    responses(
+        (status = 200, description = "Zip bundle of JSONL files", content_type = "application/zip"),
+        (status = 400, description = "Job has not completed", body = crate::error::ApiError),
+        (status = 404, description = "Job not found or expired", body = crate::error::ApiError)
+    )
+)]
+pub async fn download_export(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let Some((job, path)) = state.export_jobs.bundle(job_id, user.user_id).await else {
+        let job = state
+            .export_jobs
+            .get(job_id, user.user_id)
+            .await
+            .ok_or_else(|| AppError::NotFound(format!("Export job {job_id}")))?;
+        let reason = match job.status {
+            ExportJobStatus::Failed => job.error.unwrap_or_default(),
+            _ => "Export is still running".to_string(),
+        };
+        return Err(AppError::BadRequest(reason));
+    };
+
+    let bundle = tokio::fs::read(&path)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to read export bundle: {e}")))?;
+    Ok(zip_response(job.document_id, bundle))
+}
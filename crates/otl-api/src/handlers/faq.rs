@@ -0,0 +1,120 @@
+//! FAQ handlers
+//!
+//! Author: hephaex@gmail.com
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::AppError;
+use crate::handlers::query::Citation;
+use crate::state::AppState;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use otl_core::{FaqEntry, FaqRepository, FaqStatus, FaqStore};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Approved entries loaded before ACL filtering
+const MAX_APPROVED: i64 = 1000;
+
+/// Query parameters for the FAQ list
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FaqQuery {
+    /// Maximum number of entries
+    #[param(default = 50)]
+    pub limit: Option<usize>,
+}
+
+/// Approved question and answer
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FaqItem {
+    /// FAQ entry UUID
+    pub id: Uuid,
+
+    /// Canonical question
+    #[schema(example = "연차휴가는 어떻게 신청하나요?")]
+    pub question: String,
+
+    /// Answer with `[출처: N]` citation markers
+    #[schema(example = "연차휴가는 인사 시스템에서 3일 전까지 신청합니다 [출처: 1].")]
+    pub answer: String,
+
+    /// Cited documents, in marker order
+    pub citations: Vec<Citation>,
+
+    /// Times the question was asked when the entry was generated
+    #[schema(example = 42)]
+    pub query_count: i32,
+
+    /// Last review or edit
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<FaqEntry> for FaqItem {
+    fn from(entry: FaqEntry) -> Self {
+        Self {
+            id: entry.id,
+            question: entry.question,
+            answer: entry.answer,
+            citations: entry
+                .citations
+                .into_iter()
+                .map(|c| Citation {
+                    source: c.document_title,
+                    page: c.source.page,
+                    section: c.source.section,
+                    relevance: c.source.confidence,
+                    freshness_warning: c.freshness.map(Into::into),
+                })
+                .collect(),
+            query_count: entry.query_count,
+            updated_at: entry.updated_at,
+        }
+    }
+}
+
+/// FAQ list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FaqListResponse {
+    pub faqs: Vec<FaqItem>,
+    pub total: usize,
+}
+
+/// List approved FAQ entries the caller may read, most asked first
+#[utoipa::path(
+    get,
+    path = "/api/v1/faq",
+    tag = "faq",
+    params(FaqQuery),
+    responses(
+        (status = 200, description = "Approved FAQ entries", body = FaqListResponse),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn list_faq(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthenticatedUser>,
+    Query(params): Query<FaqQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let user = caller.to_acl_user();
+    let faqs: Vec<FaqItem> = FaqStore::from_pool(state.db_pool.clone())
+        .list(Some(FaqStatus::Approved), MAX_APPROVED, 0)
+        .await?
+        .into_iter()
+        .filter(|entry| entry.acl.can_access(&user))
+        .take(limit)
+        .map(FaqItem::from)
+        .collect();
+
+    Ok(Json(FaqListResponse {
+        total: faqs.len(),
+        faqs,
+    }))
+}
@@ -3,7 +3,7 @@
 //! Author: hephaex@gmail.com
 
 use crate::auth::middleware::AuthenticatedUser;
-use crate::error::AppError;
+use crate::error::{AppError, ErrorCode};
 use crate::state::AppState;
 use axum::{
     extract::{Path, Query, State},
@@ -115,9 +115,12 @@ pub async fn list_entities(
 
     // Get graph database connection
     let graph_db = state.graph_db.read().await;
-    let graph_db = graph_db
-        .as_ref()
-        .ok_or_else(|| AppError::Internal("Graph database not initialized".to_string()))?;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
 
     // Determine query parameters
     let limit = params.limit.unwrap_or(100).min(1000); // Cap at 1000
@@ -174,7 +177,7 @@ pub async fn list_entities(
 }
 
 /// Extract entity name from properties
-fn extract_entity_name(properties: &HashMap<String, serde_json::Value>) -> String {
+pub(crate) fn extract_entity_name(properties: &HashMap<String, serde_json::Value>) -> String {
     properties
         .get("text")
         .and_then(|v| v.as_str())
@@ -215,9 +218,12 @@ pub async fn get_entity(
 
     // Get graph database connection
     let graph_db = state.graph_db.read().await;
-    let graph_db = graph_db
-        .as_ref()
-        .ok_or_else(|| AppError::Internal("Graph database not initialized".to_string()))?;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
 
     // Get the entity
     let entity_opt = graph_db
@@ -257,6 +263,940 @@ pub async fn get_entity(
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Section citing an entity
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CitingSection {
+    /// Section node UUID
+    pub id: Uuid,
+
+    /// Document the section belongs to
+    pub document_id: Option<Uuid>,
+
+    /// Section heading
+    #[schema(example = "제5조 연차휴가")]
+    pub title: String,
+
+    /// Heading level (1 = top level)
+    pub level: Option<u32>,
+
+    /// Page the section starts on
+    pub page: Option<u32>,
+}
+
+impl From<&otl_core::Entity> for CitingSection {
+    fn from(section: &otl_core::Entity) -> Self {
+        let get_u32 = |key: &str| {
+            section
+                .properties
+                .get(key)
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u32)
+        };
+        Self {
+            id: section.id,
+            document_id: section
+                .properties
+                .get("document_id")
+                .and_then(|v| v.as_str())
+                .and_then(|v| Uuid::parse_str(v).ok()),
+            title: extract_entity_name(&section.properties),
+            level: get_u32("level"),
+            page: get_u32("page"),
+        }
+    }
+}
+
+/// Sections whose chunks mention an entity
+#[utoipa::path(
+    get,
+    path = "/api/v1/graph/entities/{id}/sections",
+    tag = "graph",
+    params(
+        ("id" = Uuid, Path, description = "Entity UUID")
+    ),
+    responses(
+        (status = 200, description = "Sections citing the entity", body = [CitingSection]),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn get_entity_sections(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = state.graph_db.read().await;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
+
+    let sections = graph_db
+        .find_citing_sections(id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to find citing sections: {e}")))?;
+
+    let sections: Vec<CitingSection> = sections.iter().map(CitingSection::from).collect();
+    Ok(Json(sections))
+}
+
+/// Event in an entity's history
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimelineEntry {
+    pub at: chrono::DateTime<chrono::Utc>,
+
+    /// `entity_created`, `entity_updated`, `relation_added`,
+    /// `relation_confirmed` or `relation_superseded`
+    #[schema(example = "relation_added")]
+    pub kind: String,
+
+    /// Document the event comes from
+    pub document_id: Option<Uuid>,
+    #[schema(example = "인사규정_2026.pdf")]
+    pub document_title: Option<String>,
+
+    pub triple_id: Option<Uuid>,
+    #[schema(example = "requiresDocument")]
+    pub predicate: Option<String>,
+
+    /// `outgoing` when the entity is the subject, `incoming` otherwise
+    pub direction: Option<String>,
+
+    /// Entity at the other end of the relation
+    pub related_id: Option<Uuid>,
+    pub related_name: Option<String>,
+
+    pub confidence: Option<f32>,
+
+    /// Newer version of the superseded source document
+    pub superseded_by: Option<Uuid>,
+    pub superseded_by_title: Option<String>,
+}
+
+/// Entity timeline
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EntityTimelineResponse {
+    pub entity_id: Uuid,
+    #[schema(example = "육아휴직")]
+    pub name: String,
+    pub entity_type: String,
+    /// Events, oldest first
+    pub events: Vec<TimelineEntry>,
+}
+
+/// How an entity's properties and relations changed as documents were
+/// ingested
+///
+/// Lists the entity's creation, the first extraction of each relation, its
+/// confirmation by further documents and its supersession once every
+/// supporting document was replaced by a newer version. Events from
+/// documents the caller may not read are left out.
+#[utoipa::path(
+    get,
+    path = "/api/v1/graph/entities/{id}/timeline",
+    tag = "graph",
+    params(
+        ("id" = Uuid, Path, description = "Entity UUID")
+    ),
+    responses(
+        (status = 200, description = "Entity timeline", body = EntityTimelineResponse),
+        (status = 404, description = "Entity not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn get_entity_timeline(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = state.graph_db.read().await;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
+
+    let snapshot = graph_db
+        .entity_snapshot(id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to load entity history: {e}")))?;
+    let entity_key = id.to_string();
+    let entity = snapshot
+        .entities
+        .iter()
+        .find(|e| e.id == entity_key)
+        .ok_or_else(|| AppError::NotFound(format!("Entity {id} not found")))?;
+
+    #[derive(sqlx::FromRow)]
+    struct SourceRow {
+        id: Uuid,
+        title: String,
+        superseded_by: Option<String>,
+        superseded_by_title: Option<String>,
+        superseded_at: chrono::DateTime<chrono::Utc>,
+    }
+
+    let mut document_ids: Vec<Uuid> = snapshot
+        .provenance
+        .iter()
+        .map(|p| p.document_id.as_str())
+        .chain(
+            snapshot
+                .edges
+                .iter()
+                .filter_map(|e| e.document_id.as_deref()),
+        )
+        .chain(entity.source.get("document_id").and_then(|v| v.as_str()))
+        .filter_map(|doc| Uuid::parse_str(doc).ok())
+        .collect();
+    document_ids.sort_unstable();
+    document_ids.dedup();
+
+    // Supersession is dated by its freshness alert, or else by the last
+    // change of the document's metadata
+    let sources: Vec<SourceRow> = sqlx::query_as(
+        r#"
+        SELECT
+            d.id,
+            d.title,
+            d.metadata->>'superseded_by' AS superseded_by,
+            n.title AS superseded_by_title,
+            COALESCE(
+                (SELECT MIN(a.detected_at) FROM freshness_alerts a
+                 WHERE a.document_id = d.id AND a.reason = 'superseded'),
+                d.updated_at
+            ) AS superseded_at
+        FROM documents d
+        LEFT JOIN documents n ON n.id::text = d.metadata->>'superseded_by'
+        WHERE d.id = ANY($1) AND d.deleted_at IS NULL
+        "#,
+    )
+    .bind(&document_ids)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch source documents: {e}")))?;
+
+    let superseded: HashMap<String, otl_graph::timeline::Supersession> = sources
+        .iter()
+        .filter_map(|row| {
+            let newer = row.superseded_by.clone()?;
+            Some((
+                row.id.to_string(),
+                otl_graph::timeline::Supersession {
+                    superseded_by: newer,
+                    at: row.superseded_at,
+                },
+            ))
+        })
+        .collect();
+    let titles: HashMap<Uuid, &str> = sources
+        .iter()
+        .map(|row| (row.id, row.title.as_str()))
+        .chain(sources.iter().filter_map(|row| {
+            let newer = Uuid::parse_str(row.superseded_by.as_deref()?).ok()?;
+            Some((newer, row.superseded_by_title.as_deref()?))
+        }))
+        .collect();
+
+    let acl_ids: Vec<Uuid> = titles.keys().copied().collect();
+    let document_acls = super::documents::fetch_document_acls(&state, &acl_ids).await?;
+    let default_acl = otl_core::DocumentAcl::default();
+    let acl_user = user.to_acl_user();
+    let visible = |doc: Option<Uuid>| {
+        doc.map_or(true, |doc| {
+            document_acls
+                .get(&doc)
+                .unwrap_or(&default_acl)
+                .can_access(&acl_user)
+        })
+    };
+
+    let names: HashMap<&str, String> = snapshot
+        .entities
+        .iter()
+        .map(|e| {
+            let properties: HashMap<String, serde_json::Value> =
+                serde_json::from_value(e.properties.clone()).unwrap_or_default();
+            (e.id.as_str(), extract_entity_name(&properties))
+        })
+        .collect();
+    let parse = |id: &Option<String>| id.as_deref().and_then(|id| Uuid::parse_str(id).ok());
+
+    let events: Vec<TimelineEntry> =
+        otl_graph::timeline::timeline(&entity_key, &snapshot, &superseded)
+            .into_iter()
+            .map(|event| {
+                let document_id = parse(&event.document_id);
+                let superseded_by = parse(&event.superseded_by);
+                TimelineEntry {
+                    at: event.at,
+                    kind: event.kind.as_str().to_string(),
+                    document_id,
+                    document_title: document_id
+                        .and_then(|doc| titles.get(&doc))
+                        .map(|t| t.to_string()),
+                    triple_id: parse(&event.triple_id),
+                    predicate: event.predicate,
+                    direction: event.direction.map(str::to_string),
+                    related_id: parse(&event.related_id),
+                    related_name: event
+                        .related_id
+                        .as_deref()
+                        .and_then(|id| names.get(id))
+                        .cloned(),
+                    confidence: event.confidence,
+                    superseded_by,
+                    superseded_by_title: superseded_by
+                        .and_then(|doc| titles.get(&doc))
+                        .map(|t| t.to_string()),
+                }
+            })
+            .filter(|entry| visible(entry.document_id) && visible(entry.superseded_by))
+            .collect();
+
+    let properties: HashMap<String, serde_json::Value> =
+        serde_json::from_value(entity.properties.clone()).unwrap_or_default();
+    let response = EntityTimelineResponse {
+        entity_id: id,
+        name: extract_entity_name(&properties),
+        entity_type: entity.class.clone(),
+        events,
+    };
+
+    Ok(Json(response))
+}
+
+/// Evidence supporting a triple
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProvenanceInfo {
+    /// Source document
+    pub document_id: Uuid,
+
+    /// Page number (if known)
+    pub page: Option<u32>,
+
+    /// Section heading (if known)
+    pub section: Option<String>,
+
+    /// Character offset in the document
+    pub offset: Option<usize>,
+
+    /// Supporting text snippet
+    #[schema(example = "병가 신청 시 진단서를 제출한다.")]
+    pub snippet: Option<String>,
+
+    /// Extractor that produced the evidence
+    pub extractor: Option<String>,
+
+    /// Extraction confidence
+    pub confidence: f32,
+
+    /// When the evidence was recorded
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&otl_core::Provenance> for ProvenanceInfo {
+    fn from(p: &otl_core::Provenance) -> Self {
+        Self {
+            document_id: p.source.document_id,
+            page: p.source.page,
+            section: p.source.section.clone(),
+            offset: p.source.offset,
+            snippet: p.snippet.clone(),
+            extractor: p.extractor.clone(),
+            confidence: p.confidence,
+            recorded_at: p.recorded_at,
+        }
+    }
+}
+
+/// Point in a triple's confidence history
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfidencePoint {
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+
+    /// Confidence of the evidence recorded at this point
+    pub confidence: f32,
+
+    /// Highest confidence seen so far (what the edge carries)
+    pub max_confidence: f32,
+
+    pub extractor: Option<String>,
+}
+
+/// Triple provenance response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TripleProvenanceResponse {
+    pub triple: RelationInfo,
+
+    /// Every recorded piece of evidence, oldest first
+    pub sources: Vec<ProvenanceInfo>,
+
+    /// Number of distinct documents supporting the triple
+    pub document_count: usize,
+
+    pub confidence_history: Vec<ConfidencePoint>,
+}
+
+/// Assemble a provenance response from a triple and its evidence
+pub(crate) fn triple_provenance_response(
+    triple: RelationInfo,
+    provenance: &[otl_core::Provenance],
+) -> TripleProvenanceResponse {
+    let mut records: Vec<&otl_core::Provenance> = provenance.iter().collect();
+    records.sort_by_key(|p| p.recorded_at);
+
+    let mut documents: Vec<Uuid> = records.iter().map(|p| p.source.document_id).collect();
+    documents.sort();
+    documents.dedup();
+
+    let mut max_confidence = 0.0f32;
+    let confidence_history = records
+        .iter()
+        .map(|p| {
+            max_confidence = max_confidence.max(p.confidence);
+            ConfidencePoint {
+                recorded_at: p.recorded_at,
+                confidence: p.confidence,
+                max_confidence,
+                extractor: p.extractor.clone(),
+            }
+        })
+        .collect();
+
+    TripleProvenanceResponse {
+        triple,
+        sources: records.into_iter().map(ProvenanceInfo::from).collect(),
+        document_count: documents.len(),
+        confidence_history,
+    }
+}
+
+/// Every source snippet supporting a triple
+#[utoipa::path(
+    get,
+    path = "/api/v1/graph/triples/{id}/provenance",
+    tag = "graph",
+    params(
+        ("id" = Uuid, Path, description = "Triple UUID")
+    ),
+    responses(
+        (status = 200, description = "Triple provenance", body = TripleProvenanceResponse),
+        (status = 404, description = "Triple not found")
+    )
+)]
+pub async fn get_triple_provenance(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = state.graph_db.read().await;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
+
+    let triple = graph_db
+        .get_triple(id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to get triple: {e}")))?
+        .ok_or_else(|| AppError::NotFound(format!("Triple {id} not found")))?;
+
+    let provenance = graph_db
+        .triple_provenance(id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to get provenance: {e}")))?;
+
+    let name_of = |entity: Option<otl_core::Entity>| {
+        entity
+            .map(|e| extract_entity_name(&e.properties))
+            .unwrap_or_else(|| "Unknown".to_string())
+    };
+    let source_name = name_of(graph_db.get_entity(triple.subject).await.ok().flatten());
+    let target_name = name_of(graph_db.get_entity(triple.object).await.ok().flatten());
+
+    let relation = RelationInfo {
+        id: triple.id,
+        relation_type: triple.predicate,
+        source_id: triple.subject,
+        source_name,
+        target_id: triple.object,
+        target_name,
+        confidence: triple.confidence,
+    };
+
+    Ok(Json(triple_provenance_response(relation, &provenance)))
+}
+
+/// Query parameters for graph analytics
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AnalyticsQuery {
+    /// Number of most central entities to return
+    #[param(default = 10)]
+    pub top: Option<usize>,
+}
+
+/// Knowledge graph health analytics
+#[utoipa::path(
+    get,
+    path = "/api/v1/graph/analytics",
+    tag = "graph",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Node/edge counts, orphans, degree distribution and document coverage"),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn get_graph_analytics(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AnalyticsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = state.graph_db.read().await;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
+
+    let top = params
+        .top
+        .unwrap_or(otl_graph::analytics::DEFAULT_TOP_N)
+        .min(100);
+    let analytics = graph_db
+        .analytics(top)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to compute graph analytics: {e}")))?;
+
+    Ok(Json(analytics))
+}
+
+/// Query parameters for graph visualization
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct VisualizeQuery {
+    /// Entity the neighborhood is centered on
+    pub center: Uuid,
+
+    /// Number of hops around the center (1-4)
+    #[param(default = 2)]
+    pub depth: Option<u32>,
+
+    /// Most nodes to return (up to 1000)
+    #[param(default = 200)]
+    pub max_nodes: Option<usize>,
+}
+
+/// Render-ready neighborhood of an entity
+///
+/// Returns nodes with class-based colors, community cluster IDs and hop
+/// distances, plus the edges between them. Entities and relations from
+/// documents the caller may not read are left out; `truncated` tells
+/// whether the node cap cut the neighborhood short.
+#[utoipa::path(
+    get,
+    path = "/api/v1/graph/visualize",
+    tag = "graph",
+    params(VisualizeQuery),
+    responses(
+        (status = 200, description = "Nodes, edges, clusters, legend and truncation metadata"),
+        (status = 404, description = "Entity not found", body = crate::error::ApiError)
+    )
+)]
+pub async fn visualize_graph(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<VisualizeQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = state.graph_db.read().await;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
+
+    let depth = params
+        .depth
+        .unwrap_or(otl_graph::visualize::DEFAULT_DEPTH)
+        .clamp(1, otl_graph::visualize::MAX_DEPTH);
+    let max_nodes = params
+        .max_nodes
+        .unwrap_or(otl_graph::visualize::DEFAULT_MAX_NODES)
+        .clamp(1, otl_graph::visualize::MAX_NODES);
+    let mut neighborhood = graph_db
+        .neighborhood(params.center, depth, max_nodes)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to load neighborhood: {e}")))?;
+
+    let source_document = |entity: &otl_graph::snapshot::EntitySnapshot| {
+        entity
+            .source
+            .get("document_id")
+            .and_then(|v| v.as_str())
+            .and_then(|doc| Uuid::parse_str(doc).ok())
+    };
+    let mut document_ids: Vec<Uuid> = neighborhood
+        .snapshot
+        .entities
+        .iter()
+        .filter_map(source_document)
+        .chain(
+            neighborhood
+                .snapshot
+                .edges
+                .iter()
+                .filter_map(|e| e.document_id.as_deref())
+                .filter_map(|doc| Uuid::parse_str(doc).ok()),
+        )
+        .collect();
+    document_ids.sort_unstable();
+    document_ids.dedup();
+
+    let document_acls = super::documents::fetch_document_acls(&state, &document_ids).await?;
+    let default_acl = otl_core::DocumentAcl::default();
+    let acl_user = user.to_acl_user();
+    let visible = |doc: Option<Uuid>| {
+        doc.map_or(true, |doc| {
+            document_acls
+                .get(&doc)
+                .unwrap_or(&default_acl)
+                .can_access(&acl_user)
+        })
+    };
+
+    let snapshot = &mut neighborhood.snapshot;
+    snapshot.entities.retain(|e| visible(source_document(e)));
+    let kept: std::collections::HashSet<String> =
+        snapshot.entities.iter().map(|e| e.id.clone()).collect();
+    if !kept.contains(&neighborhood.center) {
+        return Err(AppError::NotFound(format!(
+            "Entity {} not found",
+            params.center
+        )));
+    }
+    snapshot.edges.retain(|e| {
+        kept.contains(&e.subject)
+            && kept.contains(&e.object)
+            && visible(
+                e.document_id
+                    .as_deref()
+                    .and_then(|doc| Uuid::parse_str(doc).ok()),
+            )
+    });
+    neighborhood.hops.retain(|id, _| kept.contains(id));
+
+    Ok(Json(otl_graph::visualize::render(&neighborhood)))
+}
+
+/// SPARQL-lite query request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SparqlRequest {
+    /// Query text (BGP + FILTER + LIMIT subset)
+    #[schema(
+        example = "SELECT ?leave ?doc WHERE { ?leave a otl:SickLeave . ?leave otl:requiresDocument ?doc } LIMIT 10"
+    )]
+    pub query: String,
+}
+
+/// Drop solutions that bind an entity from a document the user cannot read
+///
+/// Entities whose source document has no ACL row fall back to the default
+/// (internal) ACL. Returns the visible solutions and the number removed.
+pub(crate) fn filter_solutions_by_acl(
+    solutions: Vec<otl_graph::sparql::Solution>,
+    entity_documents: &HashMap<Uuid, Uuid>,
+    document_acls: &HashMap<Uuid, otl_core::DocumentAcl>,
+    user: &otl_core::User,
+) -> (Vec<otl_graph::sparql::Solution>, usize) {
+    let default_acl = otl_core::DocumentAcl::default();
+    let visible = |entity: &Uuid| {
+        entity_documents
+            .get(entity)
+            .and_then(|doc| document_acls.get(doc))
+            .unwrap_or(&default_acl)
+            .can_access(user)
+    };
+
+    let total = solutions.len();
+    let kept: Vec<_> = solutions
+        .into_iter()
+        .filter(|solution| {
+            solution.values().all(|value| match value {
+                otl_graph::sparql::SparqlValue::Entity(id) => visible(id),
+                otl_graph::sparql::SparqlValue::Literal(_) => true,
+            })
+        })
+        .collect();
+    let filtered = total - kept.len();
+    (kept, filtered)
+}
+
+/// Run a SPARQL-lite query over the knowledge graph
+///
+/// Results use the SPARQL JSON results layout. Solutions binding entities
+/// extracted from documents the caller may not read are removed before
+/// LIMIT is applied.
+#[utoipa::path(
+    post,
+    path = "/api/v1/graph/sparql",
+    tag = "graph",
+    request_body = SparqlRequest,
+    responses(
+        (status = 200, description = "SPARQL JSON results"),
+        (status = 400, description = "Unsupported or malformed query", body = crate::error::ApiError),
+        (status = 401, description = "Unauthorized")
+    )
+)]
+pub async fn sparql_query(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(req): Json<SparqlRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let query =
+        otl_graph::sparql::parse(&req.query).map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let graph_db = state.graph_db.read().await;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
+
+    let solutions = graph_db
+        .sparql_solutions(&query)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to evaluate SPARQL query: {e}")))?;
+    let entity_documents = graph_db
+        .entity_documents(&otl_graph::sparql::entity_ids(&solutions))
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to resolve entity sources: {e}")))?;
+
+    let mut document_ids: Vec<Uuid> = entity_documents.values().copied().collect();
+    document_ids.sort();
+    document_ids.dedup();
+    let document_acls = super::documents::fetch_document_acls(&state, &document_ids).await?;
+
+    let (solutions, filtered) = filter_solutions_by_acl(
+        solutions,
+        &entity_documents,
+        &document_acls,
+        &user.to_acl_user(),
+    );
+    if filtered > 0 {
+        tracing::debug!("SPARQL query: {filtered} solutions hidden by document ACLs");
+    }
+
+    Ok(Json(otl_graph::sparql::project(&query, solutions)))
+}
+
+/// Named graph snapshot
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GraphSnapshotInfo {
+    #[schema(example = "before-bulk-load")]
+    pub name: String,
+    pub entities: u64,
+    pub edges: u64,
+    pub provenance: u64,
+    pub note: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<otl_graph::snapshot::SnapshotInfo> for GraphSnapshotInfo {
+    fn from(info: otl_graph::snapshot::SnapshotInfo) -> Self {
+        Self {
+            name: info.name,
+            entities: info.counts.entities,
+            edges: info.counts.edges,
+            provenance: info.counts.provenance,
+            note: info.note,
+            created_at: info.created_at,
+        }
+    }
+}
+
+/// Snapshot creation request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateSnapshotRequest {
+    /// 1-64 letters, digits, `_` or `-`
+    #[schema(example = "before-bulk-load")]
+    pub name: String,
+
+    /// Why the snapshot was taken
+    pub note: Option<String>,
+}
+
+/// Live graph database, for snapshot operations by admins
+async fn snapshot_graph_db(
+    state: &AppState,
+    user: &AuthenticatedUser,
+) -> Result<Arc<otl_graph::SurrealDbStore>, AppError> {
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required for graph snapshots".to_string(),
+        ));
+    }
+    state.graph_db.read().await.clone().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })
+}
+
+/// List named graph snapshots, newest first (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/v1/graph/snapshots",
+    tag = "graph",
+    responses(
+        (status = 200, description = "Snapshots", body = Vec<GraphSnapshotInfo>),
+        (status = 403, description = "Admin role required")
+    )
+)]
+pub async fn list_snapshots(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = snapshot_graph_db(&state, &user).await?;
+    let snapshots: Vec<GraphSnapshotInfo> = otl_graph::snapshot::list(&graph_db)
+        .await?
+        .into_iter()
+        .map(GraphSnapshotInfo::from)
+        .collect();
+
+    Ok(Json(snapshots))
+}
+
+/// Copy the live graph into a named snapshot (admin only)
+///
+/// Take one before bulk-loading triples to have a restore point. Queries
+/// can be answered from it with `as_of` on `POST /api/v1/query`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/graph/snapshots",
+    tag = "graph",
+    request_body = CreateSnapshotRequest,
+    responses(
+        (status = 201, description = "Snapshot created", body = GraphSnapshotInfo),
+        (status = 400, description = "Invalid or existing name", body = crate::error::ApiError),
+        (status = 403, description = "Admin role required")
+    )
+)]
+pub async fn create_snapshot(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(req): Json<CreateSnapshotRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = snapshot_graph_db(&state, &user).await?;
+    let info = otl_graph::snapshot::create(
+        &graph_db,
+        &state.config.database,
+        &req.name,
+        req.note.as_deref(),
+    )
+    .await?;
+    tracing::info!(
+        "Graph snapshot '{}' created by {} ({} entities, {} edges)",
+        info.name,
+        user.email,
+        info.counts.entities,
+        info.counts.edges
+    );
+
+    Ok((StatusCode::CREATED, Json(GraphSnapshotInfo::from(info))))
+}
+
+/// Replace the live graph with a named snapshot (admin only)
+///
+/// The snapshot is kept. Cached answers are cleared.
+#[utoipa::path(
+    post,
+    path = "/api/v1/graph/snapshots/{name}/restore",
+    tag = "graph",
+    params(
+        ("name" = String, Path, description = "Snapshot name")
+    ),
+    responses(
+        (status = 200, description = "Snapshot restored"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Unknown snapshot", body = crate::error::ApiError)
+    )
+)]
+pub async fn restore_snapshot(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = snapshot_graph_db(&state, &user).await?;
+    let snapshot = otl_graph::snapshot::restore(&graph_db, &state.config.database, &name).await?;
+    state.rag_cache.clear_all().await;
+    let counts = snapshot.counts();
+    tracing::warn!(
+        "Graph restored from snapshot '{}' by {} ({} entities, {} edges)",
+        name,
+        user.email,
+        counts.entities,
+        counts.edges
+    );
+
+    Ok(Json(serde_json::json!({
+        "name": name,
+        "entities": counts.entities,
+        "edges": counts.edges,
+        "provenance": counts.provenance,
+    })))
+}
+
+/// Delete a named graph snapshot (admin only)
+#[utoipa::path(
+    delete,
+    path = "/api/v1/graph/snapshots/{name}",
+    tag = "graph",
+    params(
+        ("name" = String, Path, description = "Snapshot name")
+    ),
+    responses(
+        (status = 204, description = "Snapshot deleted"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Unknown snapshot", body = crate::error::ApiError)
+    )
+)]
+pub async fn delete_snapshot(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = snapshot_graph_db(&state, &user).await?;
+    if !otl_graph::snapshot::delete(&graph_db, &state.config.database, &name).await? {
+        return Err(AppError::NotFound(format!("Graph snapshot '{name}'")));
+    }
+    tracing::info!("Graph snapshot '{}' deleted by {}", name, user.email);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Get incoming and outgoing relations for an entity
 async fn get_entity_relations(
     graph_db: &dyn GraphStore,
@@ -367,9 +1307,12 @@ pub async fn search_graph(
 
     // Get graph database connection
     let graph_db = state.graph_db.read().await;
-    let graph_db = graph_db
-        .as_ref()
-        .ok_or_else(|| AppError::Internal("Graph database not initialized".to_string()))?;
+    let graph_db = graph_db.as_ref().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
 
     // Search for matching entities using keyword search
     let initial_entities = graph_db
@@ -499,6 +1442,18 @@ pub struct OntologyProperty {
     pub label: String,
     pub domain: String,
     pub range: String,
+    /// `a p b` and `b p c` imply `a p c`
+    #[serde(default)]
+    pub transitive: bool,
+    /// Property holding in the opposite direction
+    #[serde(default)]
+    pub inverse_of: Option<String>,
+    /// At most one object per subject
+    #[serde(default)]
+    pub functional: bool,
+    /// At most this many objects per subject
+    #[serde(default)]
+    pub max_cardinality: Option<usize>,
 }
 
 /// Get ontology schema
@@ -509,7 +1464,12 @@ pub async fn get_ontology(
 
     // Query ontology from database or use default schema
     // For now, return the HR ontology schema from Sprint 0
-    let response = OntologyResponse {
+    Ok((StatusCode::OK, Json(default_ontology())))
+}
+
+/// Default HR ontology schema
+pub(crate) fn default_ontology() -> OntologyResponse {
+    OntologyResponse {
         classes: vec![
             OntologyClass {
                 name: "Employee".to_string(),
@@ -521,6 +1481,11 @@ pub async fn get_ontology(
                 label: "부서".to_string(),
                 parent: None,
             },
+            OntologyClass {
+                name: "Team".to_string(),
+                label: "팀".to_string(),
+                parent: Some("Department".to_string()),
+            },
             OntologyClass {
                 name: "Position".to_string(),
                 label: "직위".to_string(),
@@ -551,6 +1516,11 @@ pub async fn get_ontology(
                 label: "규정".to_string(),
                 parent: None,
             },
+            OntologyClass {
+                name: "LeaveRequest".to_string(),
+                label: "휴가신청".to_string(),
+                parent: None,
+            },
         ],
         properties: vec![
             OntologyProperty {
@@ -558,36 +1528,152 @@ pub async fn get_ontology(
                 label: "소속".to_string(),
                 domain: "Employee".to_string(),
                 range: "Department".to_string(),
+                transitive: false,
+                inverse_of: None,
+                functional: true,
+                max_cardinality: None,
             },
             OntologyProperty {
                 name: "manages".to_string(),
                 label: "관리".to_string(),
                 domain: "Employee".to_string(),
                 range: "Department".to_string(),
+                transitive: false,
+                inverse_of: None,
+                functional: false,
+                max_cardinality: None,
+            },
+            OntologyProperty {
+                name: "managedBy".to_string(),
+                label: "관리자".to_string(),
+                domain: "Department".to_string(),
+                range: "Employee".to_string(),
+                transitive: false,
+                inverse_of: Some("manages".to_string()),
+                functional: true,
+                max_cardinality: None,
+            },
+            OntologyProperty {
+                name: "partOf".to_string(),
+                label: "상위조직".to_string(),
+                domain: "Department".to_string(),
+                range: "Department".to_string(),
+                transitive: true,
+                inverse_of: None,
+                functional: false,
+                max_cardinality: None,
             },
             OntologyProperty {
                 name: "requires".to_string(),
                 label: "필요".to_string(),
                 domain: "LeaveType".to_string(),
                 range: "ApprovalProcess".to_string(),
+                transitive: false,
+                inverse_of: None,
+                functional: false,
+                max_cardinality: None,
             },
             OntologyProperty {
                 name: "references".to_string(),
                 label: "참조".to_string(),
                 domain: "Policy".to_string(),
                 range: "Regulation".to_string(),
+                transitive: false,
+                inverse_of: None,
+                functional: false,
+                max_cardinality: None,
             },
             OntologyProperty {
                 name: "appliesTo".to_string(),
                 label: "적용대상".to_string(),
                 domain: "Policy".to_string(),
                 range: "Employee".to_string(),
+                transitive: false,
+                inverse_of: None,
+                functional: false,
+                max_cardinality: None,
+            },
+            OntologyProperty {
+                name: "submittedBy".to_string(),
+                label: "신청인".to_string(),
+                domain: "LeaveRequest".to_string(),
+                range: "Employee".to_string(),
+                transitive: false,
+                inverse_of: None,
+                functional: true,
+                max_cardinality: None,
             },
         ],
         version: "1.0.0".to_string(),
-    };
+    }
+}
 
-    Ok((StatusCode::OK, Json(response)))
+impl OntologyResponse {
+    /// Convert to core ontology classes, attaching each property to its domain
+    pub(crate) fn to_core_classes(&self) -> Vec<otl_core::OntologyClass> {
+        self.classes
+            .iter()
+            .map(|class| otl_core::OntologyClass {
+                id: class.name.clone(),
+                label: class.label.clone(),
+                description: None,
+                parent: class.parent.clone(),
+                properties: self
+                    .properties
+                    .iter()
+                    .filter(|p| p.domain == class.name)
+                    .map(|p| otl_core::PropertyDefinition {
+                        name: p.name.clone(),
+                        data_type: otl_core::DataType::ObjectReference(p.range.clone()),
+                        cardinality: if p.functional || p.max_cardinality == Some(1) {
+                            otl_core::Cardinality::ZeroOrOne
+                        } else {
+                            otl_core::Cardinality::Many
+                        },
+                        range: Some(p.range.clone()),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Inference rules declared by the ontology
+    ///
+    /// Transitive and inverse properties become rules; class parents and
+    /// property domains and ranges scope them, so a property declared on a
+    /// class also applies to its subclasses.
+    pub(crate) fn to_inference_rules(&self) -> otl_graph::InferenceRules {
+        let mut rules = otl_graph::InferenceRules::new();
+        for class in &self.classes {
+            if let Some(parent) = &class.parent {
+                rules = rules.with_subclass(&class.name, parent);
+            }
+        }
+        for property in &self.properties {
+            rules = rules.with_signature(&property.name, &property.domain, &property.range);
+            if property.transitive {
+                rules = rules.with_transitive(&property.name);
+            }
+            if let Some(inverse) = &property.inverse_of {
+                rules = rules.with_inverse(&property.name, inverse);
+            }
+        }
+        rules
+    }
+
+    /// Cardinality constraints declared by the ontology
+    pub(crate) fn to_constraint_rules(&self) -> otl_graph::ConstraintRules {
+        let mut rules = otl_graph::ConstraintRules::new();
+        for property in &self.properties {
+            if property.functional {
+                rules = rules.with_functional(&property.name);
+            }
+            if let Some(max) = property.max_cardinality {
+                rules = rules.with_max_cardinality(&property.name, max);
+            }
+        }
+        rules
+    }
 }
 
 /// Update ontology request
@@ -649,6 +1735,20 @@ pub async fn update_ontology(
                     "Invalid property definition: all fields are required".to_string(),
                 ));
             }
+            if prop.max_cardinality == Some(0) {
+                return Err(AppError::BadRequest(format!(
+                    "Property '{}' must allow at least one object",
+                    prop.name
+                )));
+            }
+            if let Some(inverse) = &prop.inverse_of {
+                if !properties.iter().any(|p| &p.name == inverse) {
+                    return Err(AppError::BadRequest(format!(
+                        "Property '{}' is declared the inverse of unknown property '{}'",
+                        prop.name, inverse
+                    )));
+                }
+            }
         }
     }
 
@@ -672,3 +1772,491 @@ pub async fn update_ontology(
         })),
     ))
 }
+
+/// Materialize the triples inferred from the ontology rules (admin only)
+///
+/// Replaces the inferred edges of the previous run. Inferred edges are
+/// flagged `inferred = true` and are not sent for review.
+#[utoipa::path(
+    post,
+    path = "/api/v1/graph/inference/run",
+    tag = "graph",
+    responses(
+        (status = 200, description = "Inference report with asserted, inferred and removed counts per rule"),
+        (status = 403, description = "Admin role required"),
+        (status = 503, description = "Graph database not initialized", body = crate::error::ApiError)
+    )
+)]
+pub async fn run_inference(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required to run inference".to_string(),
+        ));
+    }
+    let graph_db = state.graph_db.read().await.clone().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
+
+    let rules = default_ontology().to_inference_rules();
+    let report = graph_db.materialize_inferences(&rules).await?;
+    state.rag_cache.clear_all().await;
+    tracing::info!(
+        "Inference run by {}: {} inferred from {} asserted triples ({} replaced){}",
+        user.email,
+        report.inferred,
+        report.asserted,
+        report.removed,
+        if report.truncated { ", truncated" } else { "" }
+    );
+
+    Ok(Json(report))
+}
+
+/// Curated triple to load into the graph
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateTripleRequest {
+    pub subject_id: Uuid,
+    /// Property declared by the ontology
+    #[schema(example = "belongsTo")]
+    pub predicate: String,
+    pub object_id: Uuid,
+    /// Document stating the fact
+    pub document_id: Uuid,
+    /// Passage stating the fact
+    pub snippet: Option<String>,
+    #[schema(example = 1.0)]
+    pub confidence: Option<f32>,
+}
+
+/// Load a curated triple, checked against the ontology constraints (editor or admin)
+///
+/// Returns 201 with the triple ID when stored, or 202 with the queued
+/// conflict when the triple would break a functional or cardinality
+/// constraint.
+#[utoipa::path(
+    post,
+    path = "/api/v1/graph/triples",
+    tag = "graph",
+    request_body = CreateTripleRequest,
+    responses(
+        (status = 201, description = "Triple stored"),
+        (status = 202, description = "Triple queued as a conflict"),
+        (status = 400, description = "Undeclared predicate or invalid confidence", body = crate::error::ApiError),
+        (status = 403, description = "Editor role required")
+    )
+)]
+pub async fn create_triple(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(req): Json<CreateTripleRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    use otl_graph::constraints::TripleLoad;
+
+    state.increment_requests();
+
+    let graph_db = conflict_graph_db(&state, &user).await?;
+    let ontology = default_ontology();
+    if !ontology.properties.iter().any(|p| p.name == req.predicate) {
+        return Err(AppError::BadRequest(format!(
+            "Predicate '{}' is not declared by the ontology",
+            req.predicate
+        )));
+    }
+    let confidence = req.confidence.unwrap_or(1.0);
+    if !(0.0..=1.0).contains(&confidence) {
+        return Err(AppError::BadRequest(
+            "Confidence must be between 0 and 1".to_string(),
+        ));
+    }
+    let acl = super::documents::fetch_document_acls(&state, std::slice::from_ref(&req.document_id))
+        .await?
+        .remove(&req.document_id)
+        .unwrap_or_default();
+    if !acl.can_access(&user.to_acl_user()) {
+        return Err(AppError::NotFound(format!("Document {}", req.document_id)));
+    }
+
+    let source = otl_core::SourceReference::new(req.document_id).with_confidence(confidence);
+    let triple = otl_core::Triple::new(
+        req.subject_id,
+        &req.predicate,
+        req.object_id,
+        source,
+        confidence,
+    );
+    let mut provenance = triple.provenance().with_extractor("manual");
+    if let Some(snippet) = &req.snippet {
+        provenance = provenance.with_snippet(snippet);
+    }
+
+    let load = otl_graph::constraints::load_triple(
+        &graph_db,
+        &ontology.to_constraint_rules(),
+        &triple,
+        &[provenance],
+    )
+    .await?;
+    match load {
+        TripleLoad::Stored(triple_id) => {
+            state.rag_cache.clear_all().await;
+            Ok((
+                StatusCode::CREATED,
+                Json(serde_json::json!({ "triple_id": triple_id })),
+            ))
+        }
+        TripleLoad::Queued(conflict) => {
+            tracing::info!(
+                "Triple from {} queued as conflict {}: {}",
+                user.email,
+                conflict.id,
+                conflict.message
+            );
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({ "conflict": conflict })),
+            ))
+        }
+    }
+}
+
+/// Query parameters for the graph conflict queue
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ConflictsQuery {
+    /// `open` (default), `resolved` or `all`
+    pub status: Option<String>,
+
+    /// Limit results
+    #[param(default = 100)]
+    pub limit: Option<usize>,
+}
+
+/// Triples held back by ontology constraints (editor or admin)
+///
+/// A triple that would give a functional property a second object, or
+/// exceed a property's maximum cardinality, is queued here instead of
+/// being stored. Conflicts from documents the caller cannot read are
+/// omitted.
+#[utoipa::path(
+    get,
+    path = "/api/v1/graph/conflicts",
+    tag = "graph",
+    params(ConflictsQuery),
+    responses(
+        (status = 200, description = "Queued conflicts, oldest first, with the incoming triple, its evidence and the edges it contradicts"),
+        (status = 400, description = "Invalid status", body = crate::error::ApiError),
+        (status = 403, description = "Editor role required")
+    )
+)]
+pub async fn list_conflicts(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<ConflictsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    use otl_graph::constraints::ConflictStatus;
+
+    state.increment_requests();
+
+    let graph_db = conflict_graph_db(&state, &user).await?;
+    let status = match params.status.as_deref().unwrap_or("open") {
+        "open" => Some(ConflictStatus::Open),
+        "resolved" => Some(ConflictStatus::Resolved),
+        "all" => None,
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid status '{other}': use open, resolved or all"
+            )))
+        }
+    };
+    let conflicts =
+        otl_graph::constraints::list(&graph_db, status, params.limit.unwrap_or(100)).await?;
+
+    let document_ids: Vec<Uuid> = conflicts
+        .iter()
+        .map(|c| c.triple.source.document_id)
+        .collect();
+    let document_acls = super::documents::fetch_document_acls(&state, &document_ids).await?;
+    let default_acl = otl_core::DocumentAcl::default();
+    let acl_user = user.to_acl_user();
+    let conflicts: Vec<_> = conflicts
+        .into_iter()
+        .filter(|c| {
+            document_acls
+                .get(&c.triple.source.document_id)
+                .unwrap_or(&default_acl)
+                .can_access(&acl_user)
+        })
+        .collect();
+
+    Ok(Json(conflicts))
+}
+
+/// Resolve conflict request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResolveConflictRequest {
+    /// `keep_existing` discards the incoming triple, `replace` deletes the
+    /// contradicted edges and stores it, `keep_both` stores it as well
+    #[schema(value_type = String, example = "replace")]
+    pub resolution: otl_graph::constraints::Resolution,
+    pub notes: Option<String>,
+}
+
+/// Settle a queued graph conflict (editor or admin)
+#[utoipa::path(
+    post,
+    path = "/api/v1/graph/conflicts/{id}/resolve",
+    tag = "graph",
+    params(
+        ("id" = Uuid, Path, description = "Conflict ID")
+    ),
+    request_body = ResolveConflictRequest,
+    responses(
+        (status = 200, description = "Resolved conflict"),
+        (status = 400, description = "Conflict already resolved", body = crate::error::ApiError),
+        (status = 403, description = "Editor role required"),
+        (status = 404, description = "Unknown conflict", body = crate::error::ApiError)
+    )
+)]
+pub async fn resolve_conflict(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ResolveConflictRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let graph_db = conflict_graph_db(&state, &user).await?;
+    let conflict = otl_graph::constraints::find(&graph_db, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Graph conflict {id}")))?;
+    let acl = super::documents::fetch_document_acls(
+        &state,
+        std::slice::from_ref(&conflict.triple.source.document_id),
+    )
+    .await?
+    .remove(&conflict.triple.source.document_id)
+    .unwrap_or_default();
+    if !acl.can_access(&user.to_acl_user()) {
+        return Err(AppError::NotFound(format!("Graph conflict {id}")));
+    }
+
+    let conflict = otl_graph::constraints::resolve(
+        &graph_db,
+        id,
+        req.resolution,
+        &user.email,
+        req.notes.as_deref(),
+    )
+    .await?;
+    if req.resolution != otl_graph::constraints::Resolution::KeepExisting {
+        state.rag_cache.clear_all().await;
+    }
+    tracing::info!(
+        "Graph conflict {} on '{}' resolved by {} ({:?})",
+        id,
+        conflict.violation.predicate,
+        user.email,
+        req.resolution
+    );
+
+    Ok(Json(conflict))
+}
+
+/// Graph store for the conflict queue, checking the caller is an editor
+async fn conflict_graph_db(
+    state: &AppState,
+    user: &AuthenticatedUser,
+) -> Result<Arc<otl_graph::SurrealDbStore>, AppError> {
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required for graph conflicts".to_string(),
+        ));
+    }
+    state.graph_db.read().await.clone().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use otl_core::{Provenance, SourceReference};
+
+    #[test]
+    fn test_triple_provenance_response() {
+        let triple_id = Uuid::new_v4();
+        let doc_a = Uuid::new_v4();
+        let doc_b = Uuid::new_v4();
+        let now = Utc::now();
+
+        let record = |doc, confidence, minutes: i64, snippet: &str| {
+            let mut p = Provenance::new(
+                triple_id,
+                SourceReference::new(doc).with_confidence(confidence),
+            )
+            .with_snippet(snippet);
+            p.recorded_at = now + Duration::minutes(minutes);
+            p
+        };
+        // Deliberately out of order
+        let provenance = vec![
+            record(doc_b, 0.7, 10, "병가는 진단서가 필요하다."),
+            record(doc_a, 0.9, 0, "병가 신청 시 진단서를 제출한다."),
+            record(doc_a, 0.6, 5, "진단서 없는 병가는 불가하다."),
+        ];
+
+        let relation = RelationInfo {
+            id: triple_id,
+            relation_type: "requiresDocument".to_string(),
+            source_id: Uuid::new_v4(),
+            source_name: "병가".to_string(),
+            target_id: Uuid::new_v4(),
+            target_name: "진단서".to_string(),
+            confidence: 0.9,
+        };
+        let response = triple_provenance_response(relation, &provenance);
+
+        assert_eq!(response.sources.len(), 3);
+        assert_eq!(response.document_count, 2);
+        assert_eq!(
+            response.sources[0].snippet.as_deref(),
+            Some("병가 신청 시 진단서를 제출한다.")
+        );
+        let history: Vec<(f32, f32)> = response
+            .confidence_history
+            .iter()
+            .map(|p| (p.confidence, p.max_confidence))
+            .collect();
+        assert_eq!(history, vec![(0.9, 0.9), (0.6, 0.9), (0.7, 0.9)]);
+    }
+
+    #[test]
+    fn test_default_ontology_inference_rules() {
+        use otl_graph::snapshot::EdgeSnapshot;
+
+        let ontology = default_ontology();
+        for property in &ontology.properties {
+            if let Some(inverse) = &property.inverse_of {
+                assert!(ontology.properties.iter().any(|p| &p.name == inverse));
+            }
+        }
+
+        let rules = ontology.to_inference_rules();
+        assert!(rules.is_a("Team", "Department"));
+        let classes = HashMap::from([
+            ("kim".to_string(), "Employee".to_string()),
+            ("recruiting".to_string(), "Team".to_string()),
+        ]);
+        let edges = vec![EdgeSnapshot {
+            subject: "kim".to_string(),
+            object: "recruiting".to_string(),
+            triple_id: Some("t1".to_string()),
+            predicate: Some("manages".to_string()),
+            confidence: Some(0.9),
+            document_id: None,
+            inferred: false,
+        }];
+        let inference = rules.infer(&classes, &edges);
+        assert_eq!(inference.triples.len(), 1);
+        assert_eq!(inference.triples[0].predicate, "managedBy");
+        assert_eq!(inference.triples[0].subject, "recruiting");
+    }
+
+    #[test]
+    fn test_default_ontology_constraint_rules() {
+        let ontology = default_ontology();
+        let rules = ontology.to_constraint_rules();
+        assert_eq!(rules.max_objects("belongsTo"), Some(1));
+        assert_eq!(rules.max_objects("manages"), None);
+
+        let employee = ontology
+            .to_core_classes()
+            .into_iter()
+            .find(|c| c.id == "Employee")
+            .unwrap();
+        let belongs_to = employee
+            .properties
+            .iter()
+            .find(|p| p.name == "belongsTo")
+            .unwrap();
+        assert!(matches!(
+            belongs_to.cardinality,
+            otl_core::Cardinality::ZeroOrOne
+        ));
+    }
+
+    #[test]
+    fn test_filter_solutions_by_acl() {
+        use otl_core::{AccessLevel, DocumentAcl, User};
+        use otl_graph::sparql::{Solution, SparqlValue};
+
+        let public_doc = Uuid::new_v4();
+        let secret_doc = Uuid::new_v4();
+        let public_entity = Uuid::new_v4();
+        let secret_entity = Uuid::new_v4();
+        let unknown_entity = Uuid::new_v4();
+
+        let entity_documents =
+            HashMap::from([(public_entity, public_doc), (secret_entity, secret_doc)]);
+        let document_acls = HashMap::from([
+            (
+                public_doc,
+                DocumentAcl {
+                    access_level: AccessLevel::Public,
+                    ..Default::default()
+                },
+            ),
+            (
+                secret_doc,
+                DocumentAcl {
+                    access_level: AccessLevel::Restricted,
+                    owner_id: Some("owner".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let solution = |ids: &[Uuid]| -> Solution {
+            ids.iter()
+                .enumerate()
+                .map(|(i, id)| (format!("v{i}"), SparqlValue::Entity(*id)))
+                .chain([("name".to_string(), SparqlValue::Literal("병가".into()))])
+                .collect()
+        };
+        let solutions = vec![
+            solution(&[public_entity]),
+            solution(&[public_entity, secret_entity]),
+            solution(&[unknown_entity]),
+        ];
+
+        let (kept, filtered) = filter_solutions_by_acl(
+            solutions.clone(),
+            &entity_documents,
+            &document_acls,
+            &User::anonymous(),
+        );
+        assert_eq!(kept, vec![solution(&[public_entity])]);
+        assert_eq!(filtered, 2);
+
+        let owner = User {
+            user_id: "owner".to_string(),
+            is_internal: true,
+            ..User::anonymous()
+        };
+        let (kept, filtered) =
+            filter_solutions_by_acl(solutions, &entity_documents, &document_acls, &owner);
+        assert_eq!(kept.len(), 3);
+        assert_eq!(filtered, 0);
+    }
+}
@@ -127,6 +127,11 @@ pub async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl Into
     let has_rag = state.has_rag().await;
     let (cache_hits, cache_misses) = state.get_cache_stats();
 
+    let rag_timeouts = match state.get_rag().await {
+        Some(rag) => rag.timeout_metrics().snapshot(),
+        None => Vec::new(),
+    };
+
     // Database pool metrics
     let db_pool = &state.db_pool;
     let pool_size = db_pool.size();
@@ -146,7 +151,10 @@ pub async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl Into
 
     output.push_str("# HELP otl_rag_enabled Whether RAG is initialized\n");
     output.push_str("# TYPE otl_rag_enabled gauge\n");
-    output.push_str(&format!("otl_rag_enabled {}\n\n", if has_rag { 1 } else { 0 }));
+    output.push_str(&format!(
+        "otl_rag_enabled {}\n\n",
+        if has_rag { 1 } else { 0 }
+    ));
 
     output.push_str("# HELP otl_build_info Build information\n");
     output.push_str("# TYPE otl_build_info gauge\n");
@@ -172,6 +180,21 @@ pub async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl Into
         output.push_str(&format!("otl_cache_hit_rate {hit_rate:.4}\n\n"));
     }
 
+    // RAG stage budget overruns
+    if !rag_timeouts.is_empty() {
+        output.push_str(
+            "# HELP otl_rag_timeouts_total RAG pipeline stages that exceeded their time budget\n",
+        );
+        output.push_str("# TYPE otl_rag_timeouts_total counter\n");
+        for (stage, count) in &rag_timeouts {
+            output.push_str(&format!(
+                "otl_rag_timeouts_total{{stage=\"{}\"}} {count}\n",
+                stage.as_str()
+            ));
+        }
+        output.push('\n');
+    }
+
     // Database pool metrics
     output.push_str("# HELP otl_db_pool_connections_active Active database connections\n");
     output.push_str("# TYPE otl_db_pool_connections_active gauge\n");
@@ -234,8 +257,7 @@ pub async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl Into
             ));
 
             // Sum and count
-            let total_sum_s =
-                (endpoint_metrics.total_latency_us as f64) / 1_000_000.0;
+            let total_sum_s = (endpoint_metrics.total_latency_us as f64) / 1_000_000.0;
             output.push_str(&format!(
                 "otl_http_request_duration_seconds_sum{{endpoint=\"{endpoint}\"}} {total_sum_s:.6}\n"
             ));
@@ -248,7 +270,9 @@ pub async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl Into
     output.push('\n');
 
     // Latency quantiles (approximated from buckets)
-    output.push_str("# HELP otl_http_request_duration_seconds_summary HTTP request latency summary\n");
+    output.push_str(
+        "# HELP otl_http_request_duration_seconds_summary HTTP request latency summary\n",
+    );
     output.push_str("# TYPE otl_http_request_duration_seconds_summary summary\n");
     for (endpoint, endpoint_metrics) in metrics.iter() {
         if endpoint_metrics.latency_count > 0 {
@@ -264,7 +288,12 @@ pub async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl Into
             let p90_threshold = (total * 9) / 10;
             let p99_threshold = (total * 99) / 100;
 
-            let (p50, p90, p99) = calculate_percentiles(endpoint_metrics, p50_threshold, p90_threshold, p99_threshold);
+            let (p50, p90, p99) = calculate_percentiles(
+                endpoint_metrics,
+                p50_threshold,
+                p90_threshold,
+                p99_threshold,
+            );
 
             output.push_str(&format!(
                 "otl_http_request_duration_seconds_summary{{endpoint=\"{endpoint}\",quantile=\"0.5\"}} {p50:.6}\n"
@@ -278,6 +307,55 @@ pub async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl Into
         }
     }
 
+    output.push('\n');
+
+    // Service level objectives
+    let slos = state.slo.snapshot();
+    output.push_str("# HELP otl_slo_objective Target share of good events\n");
+    output.push_str("# TYPE otl_slo_objective gauge\n");
+    for slo in &slos {
+        output.push_str(&format!(
+            "otl_slo_objective{{slo=\"{}\",class=\"{}\"}} {}\n",
+            slo.objective.name,
+            slo.objective.class.as_str(),
+            slo.objective.target
+        ));
+    }
+    output.push('\n');
+
+    output.push_str("# HELP otl_slo_events_total Requests counted for an objective\n");
+    output.push_str("# TYPE otl_slo_events_total counter\n");
+    for slo in &slos {
+        output.push_str(&format!(
+            "otl_slo_events_total{{slo=\"{}\"}} {}\n",
+            slo.objective.name, slo.events
+        ));
+    }
+    output.push('\n');
+
+    output.push_str("# HELP otl_slo_bad_events_total Requests that missed an objective\n");
+    output.push_str("# TYPE otl_slo_bad_events_total counter\n");
+    for slo in &slos {
+        output.push_str(&format!(
+            "otl_slo_bad_events_total{{slo=\"{}\"}} {}\n",
+            slo.objective.name, slo.bad_events
+        ));
+    }
+    output.push('\n');
+
+    output.push_str(
+        "# HELP otl_slo_burn_rate Error budget burn rate of this instance (1 spends it over the SLO period)\n",
+    );
+    output.push_str("# TYPE otl_slo_burn_rate gauge\n");
+    for slo in &slos {
+        for window in &slo.windows {
+            output.push_str(&format!(
+                "otl_slo_burn_rate{{slo=\"{}\",window=\"{}\"}} {:.4}\n",
+                slo.objective.name, window.window, window.burn_rate
+            ));
+        }
+    }
+
     (
         StatusCode::OK,
         [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
@@ -2,9 +2,15 @@
 //!
 //! Author: hephaex@gmail.com
 
+pub mod admin;
 pub mod auth;
+pub mod chunks;
 pub mod documents;
+pub mod export;
+pub mod faq;
 pub mod graph;
 pub mod health;
+pub mod notifications;
 pub mod query;
+pub mod saved_searches;
 pub mod verify;
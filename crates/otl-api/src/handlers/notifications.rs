@@ -0,0 +1,274 @@
+//! Notification center and preference handlers
+//!
+//! Author: hephaex@gmail.com
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::AppError;
+use crate::notification_center::{self, UserNotification};
+use crate::notifications::{self, NotificationPreferences};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Default notifications per page
+const DEFAULT_LIMIT: i64 = 50;
+
+/// Most notifications per page
+const MAX_LIMIT: i64 = 200;
+
+/// Query parameters for the notification list
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct NotificationListQuery {
+    /// Only unread notifications
+    #[serde(default)]
+    pub unread: bool,
+    /// Only notifications created before this time (the `created_at` of
+    /// the last one of the previous page)
+    pub before: Option<DateTime<Utc>>,
+    /// Maximum number of notifications
+    #[param(default = 50, maximum = 200)]
+    pub limit: Option<i64>,
+}
+
+/// Notifications of the user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotificationListResponse {
+    /// Newest first
+    pub notifications: Vec<UserNotification>,
+    /// Unread notifications in total, not only on this page
+    #[schema(example = 3)]
+    pub unread_count: i64,
+}
+
+/// Unread notification count
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UnreadCountResponse {
+    #[schema(example = 3)]
+    pub unread_count: i64,
+}
+
+/// Mark-all-read result
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MarkAllReadResponse {
+    /// Notifications that were unread
+    pub marked: u64,
+}
+
+/// List notifications
+///
+/// Returns the in-app notifications of the authenticated user with the
+/// unread count, so a notification bell needs one request.
+///
+/// # Responses
+///
+/// * `200 OK` - Notifications, newest first
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications",
+    tag = "notifications",
+    params(NotificationListQuery),
+    responses(
+        (status = 200, description = "Notifications", body = NotificationListResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_notifications(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<NotificationListQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let notifications = notification_center::list(
+        &state.db_pool,
+        user.user_id,
+        query.unread,
+        query.before,
+        limit,
+    )
+    .await?;
+    let unread_count = notification_center::unread_count(&state.db_pool, user.user_id).await?;
+    Ok(Json(NotificationListResponse {
+        notifications,
+        unread_count,
+    }))
+}
+
+/// Count unread notifications
+///
+/// # Responses
+///
+/// * `200 OK` - Unread count
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications/unread-count",
+    tag = "notifications",
+    responses(
+        (status = 200, description = "Unread count", body = UnreadCountResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn unread_count(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(UnreadCountResponse {
+        unread_count: notification_center::unread_count(&state.db_pool, user.user_id).await?,
+    }))
+}
+
+/// Mark a notification read
+///
+/// # Responses
+///
+/// * `204 No Content` - Marked read (also if it already was)
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `404 Not Found` - No such notification of the user
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    post,
+    path = "/api/v1/notifications/{id}/read",
+    tag = "notifications",
+    params(("id" = Uuid, Path, description = "Notification ID")),
+    responses(
+        (status = 204, description = "Marked read"),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 404, description = "Notification not found", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn mark_read(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    notification_center::mark_read(&state.db_pool, user.user_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Mark all notifications read
+///
+/// # Responses
+///
+/// * `200 OK` - Number of notifications marked read
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    post,
+    path = "/api/v1/notifications/read-all",
+    tag = "notifications",
+    responses(
+        (status = 200, description = "Marked read", body = MarkAllReadResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn mark_all_read(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(MarkAllReadResponse {
+        marked: notification_center::mark_all_read(&state.db_pool, user.user_id).await?,
+    }))
+}
+
+/// Get notification preferences
+///
+/// Returns how the authenticated user is notified. Users who never saved
+/// preferences get every event by email and Slack.
+///
+/// # Responses
+///
+/// * `200 OK` - Preferences
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications/preferences",
+    tag = "notifications",
+    responses(
+        (status = 200, description = "Notification preferences", body = NotificationPreferences),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        notifications::get_preferences(&state.db_pool, user.user_id).await?,
+    ))
+}
+
+/// Update notification preferences
+///
+/// Chooses the channels (email, Slack) the authenticated user is notified
+/// through, the Slack member ID to mention and the events to mute.
+///
+/// # Responses
+///
+/// * `200 OK` - Saved preferences
+/// * `400 Bad Request` - Invalid Slack member ID
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    put,
+    path = "/api/v1/notifications/preferences",
+    tag = "notifications",
+    request_body = NotificationPreferences,
+    responses(
+        (status = 200, description = "Saved preferences", body = NotificationPreferences),
+        (status = 400, description = "Invalid preferences", body = crate::error::ApiError),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(preferences): Json<NotificationPreferences>,
+) -> Result<impl IntoResponse, AppError> {
+    let member_id = preferences.slack_member_id.as_deref().map(str::trim);
+    if member_id.is_some_and(|id| !id.chars().all(|c| c.is_ascii_alphanumeric())) {
+        return Err(AppError::BadRequest(
+            "slack_member_id must be a Slack member ID such as U024BE7LH".to_string(),
+        ));
+    }
+    notifications::set_preferences(&state.db_pool, user.user_id, &preferences).await?;
+    Ok(Json(
+        notifications::get_preferences(&state.db_pool, user.user_id).await?,
+    ))
+}
@@ -2,24 +2,37 @@
 //!
 //! Author: hephaex@gmail.com
 
-use crate::error::AppError;
+use crate::auth::middleware::AuthenticatedUser;
+use crate::content_gaps;
+use crate::error::{AppError, ErrorCode};
+use crate::faq;
+use crate::feedback;
 use crate::state::AppState;
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{
         sse::{Event, Sse},
         IntoResponse,
     },
-    Json,
+    Extension, Json,
+};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use otl_core::{
+    AnswerFormat, AnswerMode, BackendHealth, Language, Persona, QueryRecording, RagQuery,
+    RagResponse, SearchResult, User,
+};
+use otl_graph::GraphSearchBackend;
+use otl_rag::{
+    detect_language, AnswerPath, HybridRagOrchestrator, ModerationConfig, Moderator,
+    PromptTemplate, QueryEstimate,
 };
-use futures::stream::{self, Stream, StreamExt};
-use otl_core::RagQuery;
 use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
 use std::sync::Arc;
 use std::time::Duration;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
 /// Query request body
 #[derive(Debug, Deserialize, ToSchema)]
@@ -38,9 +51,156 @@ pub struct QueryRequest {
     #[schema(default = true)]
     pub include_citations: bool,
 
-    /// User ID for ACL filtering
+    /// Ignored: ACL filtering uses the authenticated caller (kept so older
+    /// clients still parse)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_id: Option<String>,
+
+    /// Answer mode (`extractive` skips the LLM)
+    #[serde(default)]
+    pub mode: QueryMode,
+
+    /// Answer language (`ko` or `en`); detected from the question when omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "en")]
+    pub response_language: Option<String>,
+
+    /// Fail with `SEARCH_FAILED` instead of answering without an
+    /// unavailable retrieval backend
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Deadline in milliseconds; only shortens the server's `RAG_TIMEOUTS`
+    /// deadline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 10000)]
+    pub timeout_ms: Option<u64>,
+
+    /// Answer from this named graph snapshot instead of the live graph
+    /// (admin and developer roles)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "before-bulk-load")]
+    pub as_of: Option<String>,
+
+    /// Run reproducibly: the LLM samples with temperature 0 and this seed,
+    /// and with `?debug=true` the trace carries a `recording` that
+    /// `POST /api/v1/query/replay` can re-run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 42)]
+    pub seed: Option<u64>,
+
+    /// Also retrieve from document versions replaced by a newer one, for
+    /// questions about past rules
+    #[serde(default)]
+    #[schema(default = false)]
+    pub include_superseded: bool,
+
+    /// Knowledge space to search (`global` searches every readable
+    /// document); when omitted the question is routed to spaces by topic,
+    /// then to the default space of the caller's department
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "hr")]
+    pub space: Option<String>,
+}
+
+/// Replay request body
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReplayRequest {
+    /// `debug.recording` of a seeded query
+    #[schema(value_type = Object)]
+    pub recording: QueryRecording,
+
+    /// Ignored: ACL filtering uses the authenticated caller (kept so older
+    /// clients still parse)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+}
+
+/// Query string options for the query endpoint
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct QueryOptions {
+    /// Include the retrieval trace in the response (admin and developer roles)
+    #[serde(default)]
+    #[param(default = false)]
+    pub debug: bool,
+}
+
+impl QueryRequest {
+    /// Parse the requested answer language
+    fn language(&self) -> Result<Option<Language>, AppError> {
+        self.response_language
+            .as_deref()
+            .map(|l| l.parse::<Language>())
+            .transpose()
+            .map_err(|e| AppError::BadRequest(e.to_string()))
+    }
+
+    /// Pipeline query for this request, answered with `persona`
+    fn rag_query(&self, debug: bool, persona: Option<Persona>) -> Result<RagQuery, AppError> {
+        if self.question.trim().is_empty() {
+            return Err(AppError::BadRequest("Question cannot be empty".to_string()));
+        }
+        let mut rag_query = RagQuery::new(&self.question)
+            .with_top_k(self.top_k)
+            .with_answer_mode(self.mode.into())
+            .with_debug(debug)
+            .with_strict(self.strict)
+            .with_include_superseded(self.include_superseded)
+            .with_persona(persona);
+        if let Some(language) = self.language()? {
+            rag_query = rag_query.with_response_language(language);
+        }
+        if let Some(timeout_ms) = self.timeout_ms {
+            rag_query = rag_query.with_timeout_ms(timeout_ms);
+        }
+        if let Some(seed) = self.seed {
+            rag_query = rag_query.with_seed(seed);
+        }
+        Ok(rag_query)
+    }
+}
+
+/// How the answer is produced
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMode {
+    /// LLM-generated answer
+    #[default]
+    Generative,
+    /// Top passages with highlighted sentences, no LLM
+    Extractive,
+}
+
+impl From<QueryMode> for AnswerMode {
+    fn from(mode: QueryMode) -> Self {
+        match mode {
+            QueryMode::Generative => AnswerMode::Generative,
+            QueryMode::Extractive => AnswerMode::Extractive,
+        }
+    }
+}
+
+/// Copy of the orchestrator retrieving from a named graph snapshot
+async fn snapshot_rag(
+    state: &AppState,
+    rag: &HybridRagOrchestrator,
+    name: &str,
+) -> Result<HybridRagOrchestrator, AppError> {
+    let live = state.graph_db.read().await.clone().ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "Graph database not initialized",
+        )
+    })?;
+    let snapshot = otl_graph::snapshot::open(&live, &state.config.database, name).await?;
+    let search = GraphSearchBackend::new(&otl_graph::snapshot::snapshot_config(
+        &state.config.database,
+        name,
+    ))
+    .await?
+    .with_synonyms(state.synonyms.clone())
+    .with_analyzer(state.analyzer.clone());
+    Ok(rag.on_graph(Arc::new(search), Some(Arc::new(snapshot))))
 }
 
 fn default_top_k() -> usize {
@@ -69,11 +229,163 @@ pub struct Citation {
     /// Relevance score
     #[schema(example = 0.92)]
     pub relevance: f32,
+
+    /// Set when the cited document is past its review date or superseded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freshness_warning: Option<FreshnessWarning>,
+}
+
+/// Why a cited document may be out of date
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FreshnessWarning {
+    /// `review_overdue` and/or `superseded`
+    #[schema(example = json!(["superseded"]))]
+    pub reasons: Vec<String>,
+
+    /// Review date that has passed (`YYYY-MM-DD`)
+    #[schema(example = "2026-06-30")]
+    pub review_date: Option<String>,
+
+    /// Document replacing the cited one
+    pub superseded_by: Option<Uuid>,
+}
+
+impl From<otl_core::FreshnessWarning> for FreshnessWarning {
+    fn from(warning: otl_core::FreshnessWarning) -> Self {
+        Self {
+            reasons: warning
+                .reasons
+                .iter()
+                .map(|r| r.as_str().to_string())
+                .collect(),
+            review_date: warning.review_date.map(|d| d.to_string()),
+            superseded_by: warning.superseded_by,
+        }
+    }
+}
+
+/// Sentence highlighted within a passage
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PassageHighlight {
+    /// Byte offset of the sentence start in the passage text
+    pub start: usize,
+
+    /// Byte offset of the sentence end in the passage text
+    pub end: usize,
+
+    /// Sentence relevance score
+    #[schema(example = 0.75)]
+    pub score: f32,
+}
+
+/// Source passage returned in extractive mode
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Passage {
+    /// Citation index
+    #[schema(example = 1)]
+    pub index: u32,
+
+    /// Source document ID
+    pub document_id: Uuid,
+
+    /// Page number if applicable
+    pub page: Option<u32>,
+
+    /// Section title
+    pub section: Option<String>,
+
+    /// Passage text
+    pub text: String,
+
+    /// Passage relevance score
+    pub score: f32,
+
+    /// Best matching sentences
+    pub highlights: Vec<PassageHighlight>,
+}
+
+/// Dry-run estimate of a query
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EstimateResponse {
+    /// How the answer would be produced (`generation`, `extractive` or
+    /// `glossary`)
+    #[schema(value_type = String, example = "generation")]
+    pub path: AnswerPath,
+
+    /// Model that would generate the answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "gpt-4o-mini")]
+    pub model: Option<String>,
+
+    /// Availability and candidate count of each retrieval backend
+    #[schema(value_type = Vec<Object>)]
+    pub backends: Vec<BackendHealth>,
+
+    /// Candidates removed by ACL filtering
+    pub acl_filtered: usize,
+
+    /// Candidates after fusion
+    pub fused: usize,
+
+    /// Contexts selected for answering
+    #[schema(example = 5)]
+    pub contexts: usize,
+
+    /// Contexts fitting the prompt budget
+    #[schema(example = 5)]
+    pub prompt_contexts: usize,
+
+    /// Prompt length in characters
+    pub prompt_chars: usize,
+
+    /// Estimated prompt tokens
+    #[schema(example = 2100)]
+    pub prompt_tokens: usize,
+
+    /// Expected answer tokens
+    #[schema(example = 500)]
+    pub completion_tokens: usize,
+
+    /// Estimated cost of the generation call in USD (unpriced models have
+    /// none)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 0.000615)]
+    pub cost_usd: Option<f64>,
+
+    /// Time spent retrieving and packing the context, in milliseconds
+    #[schema(example = 180)]
+    pub retrieval_ms: u64,
+
+    /// Degradations the answer would be produced under
+    pub warnings: Vec<String>,
+}
+
+impl From<QueryEstimate> for EstimateResponse {
+    fn from(estimate: QueryEstimate) -> Self {
+        Self {
+            path: estimate.path,
+            model: estimate.model,
+            backends: estimate.backends,
+            acl_filtered: estimate.acl_filtered,
+            fused: estimate.fused,
+            contexts: estimate.contexts,
+            prompt_contexts: estimate.prompt_contexts,
+            prompt_chars: estimate.prompt_chars,
+            prompt_tokens: estimate.prompt_tokens,
+            completion_tokens: estimate.completion_tokens,
+            cost_usd: estimate.cost_usd,
+            retrieval_ms: estimate.retrieval_ms,
+            warnings: estimate.warnings,
+        }
+    }
 }
 
 /// Query response body
 #[derive(Debug, Serialize, ToSchema)]
 pub struct QueryResponse {
+    /// Query ID (used to fetch suggestions later)
+    pub id: Uuid,
+
     /// Generated answer
     #[schema(example = "연차휴가 신청은 다음 절차를 따릅니다...")]
     pub answer: String,
@@ -88,6 +400,157 @@ pub struct QueryResponse {
     /// Processing time in milliseconds
     #[schema(example = 1250)]
     pub processing_time_ms: u64,
+
+    /// Suggested follow-up questions
+    #[schema(example = json!(["연차휴가 처리에는 얼마나 걸리나요?"]))]
+    pub suggestions: Vec<String>,
+
+    /// Source passages with highlights (extractive mode only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub passages: Vec<Passage>,
+
+    /// Answer layout: `prose`, `steps` (numbered procedure), `table`
+    /// (markdown comparison) or `yes_no` (verdict, then rationale)
+    #[schema(example = "steps")]
+    pub format: String,
+
+    /// Machine-readable answer for list/fact questions (`type`: `list` or `fact`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub structured_answer: Option<serde_json::Value>,
+
+    /// Moderation applied to the answer (`action`, `rules`, `detector`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub moderation: Option<serde_json::Value>,
+
+    /// Degradations the answer was produced under, such as a retrieval
+    /// backend that was skipped because it was unavailable
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[schema(example = json!(["graph search unavailable; the answer may be incomplete"]))]
+    pub warnings: Vec<String>,
+
+    /// Sensitive topics whose values were masked as "[권한 필요]" for lack
+    /// of clearance (`rule`, `description`, `values`, `access_request`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[schema(value_type = Vec<Object>)]
+    pub withheld: Vec<serde_json::Value>,
+
+    /// Model that generated the answer; questions are routed to model tiers
+    /// by intent and length (absent for extractive and glossary answers)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "gpt-4o-mini")]
+    pub model: Option<String>,
+
+    /// Knowledge spaces the answer was retrieved from, ending with
+    /// `global` when they held nothing relevant (absent for global search)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[schema(example = json!(["hr"]))]
+    pub searched_spaces: Vec<String>,
+
+    /// Retrieval trace: per-backend candidates and health, ACL-filtered
+    /// items, RRF and rerank scores, the prompt and per-stage timings
+    /// (`?debug=true` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub debug: Option<serde_json::Value>,
+}
+
+impl QueryResponse {
+    /// Response to an answered query
+    fn new(id: Uuid, rag_response: RagResponse) -> Self {
+        Self {
+            id,
+            answer: rag_response.answer,
+            citations: rag_response
+                .citations
+                .into_iter()
+                .map(|c| Citation {
+                    source: c.document_title,
+                    page: c.source.page,
+                    section: c.source.section,
+                    relevance: c.source.confidence,
+                    freshness_warning: c.freshness.map(FreshnessWarning::from),
+                })
+                .collect(),
+            confidence: rag_response.confidence,
+            processing_time_ms: rag_response.processing_time_ms,
+            suggestions: rag_response.suggestions,
+            format: rag_response.format.as_str().to_string(),
+            structured_answer: rag_response
+                .structured_answer
+                .and_then(|a| serde_json::to_value(a).ok()),
+            moderation: rag_response
+                .moderation
+                .and_then(|m| serde_json::to_value(m).ok()),
+            warnings: rag_response.warnings,
+            withheld: rag_response
+                .withheld
+                .into_iter()
+                .filter_map(|w| serde_json::to_value(w).ok())
+                .collect(),
+            model: rag_response.model,
+            searched_spaces: rag_response.searched_spaces,
+            debug: rag_response
+                .trace
+                .and_then(|t| serde_json::to_value(t).ok()),
+            passages: rag_response
+                .passages
+                .into_iter()
+                .map(|p| Passage {
+                    index: p.index,
+                    document_id: p.source.document_id,
+                    page: p.source.page,
+                    section: p.source.section,
+                    text: p.text,
+                    score: p.score,
+                    highlights: p
+                        .highlights
+                        .into_iter()
+                        .map(|h| PassageHighlight {
+                            start: h.start,
+                            end: h.end,
+                            score: h.score,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Follow-up suggestions for an answered query
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SuggestionsResponse {
+    /// Query ID
+    pub query_id: Uuid,
+
+    /// Suggested follow-up questions
+    pub suggestions: Vec<String>,
+}
+
+/// Verdicts on the citations of an answered query
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FeedbackRequest {
+    /// Citations that helped (1-based positions in `citations`)
+    #[serde(default)]
+    #[schema(example = json!([1]))]
+    pub helpful: Vec<usize>,
+
+    /// Citations that did not help (1-based positions in `citations`)
+    #[serde(default)]
+    #[schema(example = json!([3]))]
+    pub unhelpful: Vec<usize>,
+}
+
+/// Recorded citation feedback
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedbackResponse {
+    /// Query ID
+    pub query_id: Uuid,
+
+    /// Number of new or changed votes; repeated votes are not counted
+    pub recorded: usize,
 }
 
 /// Handle RAG query requests
@@ -95,60 +558,120 @@ pub struct QueryResponse {
     post,
     path = "/api/v1/query",
     tag = "query",
+    params(QueryOptions),
     request_body = QueryRequest,
     responses(
         (status = 200, description = "Query successful", body = QueryResponse),
         (status = 400, description = "Invalid request", body = crate::error::ApiError),
+        (status = 403, description = "Debug mode requires the admin or developer role", body = crate::error::ApiError),
         (status = 500, description = "Internal error", body = crate::error::ApiError)
     )
 )]
 pub async fn query_handler(
     State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthenticatedUser>,
+    Query(options): Query<QueryOptions>,
     Json(req): Json<QueryRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     state.increment_requests();
 
+    if options.debug && !caller.can_debug_queries() {
+        return Err(AppError::Forbidden(
+            "Debug mode requires the admin or developer role".to_string(),
+        ));
+    }
+
+    if req.as_of.is_some() && !caller.can_debug_queries() {
+        return Err(AppError::Forbidden(
+            "Snapshot queries require the admin or developer role".to_string(),
+        ));
+    }
+
     let start = std::time::Instant::now();
 
     // Validate request
-    if req.question.trim().is_empty() {
-        return Err(AppError::BadRequest("Question cannot be empty".to_string()));
-    }
+    let (scope, persona) = state.scope_and_persona(
+        req.space.as_deref(),
+        &req.question,
+        caller.department.as_deref(),
+    )?;
+    let rag_query = req.rag_query(options.debug, persona)?.with_scope(scope);
 
-    // Try to use actual RAG orchestrator if available
-    if let Some(rag) = state.get_rag().await {
-        let user = state.get_default_user(req.user_id.as_deref());
-        let rag_query = RagQuery::new(&req.question).with_top_k(req.top_k);
+    let rag = match (state.get_rag().await, req.as_of.as_deref()) {
+        (Some(rag), Some(name)) => Some(Arc::new(snapshot_rag(&state, &rag, name).await?)),
+        (None, Some(_)) => {
+            return Err(AppError::coded(
+                ErrorCode::ServiceUnavailable,
+                "RAG pipeline not initialized",
+            ))
+        }
+        (rag, None) => rag,
+    };
 
+    // Try to use actual RAG orchestrator if available
+    if let Some(rag) = rag {
+        // ACL and masking of restricted values follow the caller, never a
+        // user named in the body
+        let user = caller.to_acl_user();
         match rag.query(&rag_query, &user).await {
-            Ok(rag_response) => {
-                let response = QueryResponse {
-                    answer: rag_response.answer,
-                    citations: rag_response
-                        .citations
-                        .into_iter()
-                        .map(|c| Citation {
-                            source: c.document_title,
-                            page: c.source.page,
-                            section: c.source.section,
-                            relevance: c.source.confidence,
-                        })
-                        .collect(),
-                    confidence: rag_response.confidence,
-                    processing_time_ms: rag_response.processing_time_ms,
-                };
+            Ok(mut rag_response) => {
+                let id = Uuid::new_v4();
+                // Snapshot answers say nothing about the live knowledge base
+                if let Some(name) = &req.as_of {
+                    rag_response
+                        .warnings
+                        .push(format!("answered from graph snapshot '{name}'"));
+                } else {
+                    content_gaps::log_if_gap(
+                        &state,
+                        &req.question,
+                        rag_response.citations.len(),
+                        rag_response.confidence,
+                        caller.user_id,
+                    );
+                    faq::record_query(&state, &req.question, &rag_response, caller.user_id);
+                }
+                state
+                    .store_suggestions(id, rag_response.suggestions.clone())
+                    .await;
+                state
+                    .store_citations(
+                        id,
+                        rag_response
+                            .citations
+                            .iter()
+                            .map(|c| c.source.clone())
+                            .collect(),
+                    )
+                    .await;
+
+                let mut response = QueryResponse::new(id, rag_response);
+                // Seeded queries are traced; only debug callers see it
+                if !options.debug {
+                    response.debug = None;
+                }
                 return Ok((StatusCode::OK, Json(response)));
             }
             Err(e) => {
                 tracing::error!("RAG query failed: {}", e);
-                return Err(AppError::Internal(format!("RAG query failed: {e}")));
+                // Keeps the specific code (LLM_TIMEOUT, SEARCH_FAILED, ACL_DENIED)
+                return Err(e.into());
             }
         }
     }
 
     // Fallback to mock response when RAG is not initialized
     tracing::warn!("RAG not initialized, returning mock response");
+    let id = Uuid::new_v4();
+    let suggestions = vec![
+        "연차휴가 처리에는 얼마나 걸리나요?".to_string(),
+        "연차휴가 신청 시 필요한 서류는 무엇인가요?".to_string(),
+        "휴가신청 매뉴얼에는 어떤 내용이 있나요?".to_string(),
+    ];
+    state.store_suggestions(id, suggestions.clone()).await;
+
     let response = QueryResponse {
+        id,
         answer: format!(
             "귀하의 질문 \"{}\"에 대한 답변입니다.\n\n\
              연차휴가 신청은 사내 인사시스템을 통해 진행됩니다. \
@@ -163,21 +686,234 @@ pub async fn query_handler(
                 page: Some(15),
                 section: Some("제3장 휴가".to_string()),
                 relevance: 0.92,
+                freshness_warning: None,
             },
             Citation {
                 source: "휴가신청_매뉴얼.docx".to_string(),
                 page: Some(3),
                 section: Some("신청 절차".to_string()),
                 relevance: 0.85,
+                freshness_warning: None,
             },
         ],
         confidence: 0.87,
         processing_time_ms: start.elapsed().as_millis() as u64,
+        suggestions,
+        passages: Vec::new(),
+        format: AnswerFormat::Prose.as_str().to_string(),
+        structured_answer: None,
+        moderation: None,
+        warnings: Vec::new(),
+        withheld: Vec::new(),
+        model: None,
+        searched_spaces: Vec::new(),
+        debug: None,
     };
 
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Estimate the prompt size and cost of a query without answering it
+///
+/// Runs retrieval and context packing like `POST /api/v1/query`, then
+/// skips generation. Model prices come from `RAG_COST_MODEL`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/query/estimate",
+    tag = "query",
+    request_body = QueryRequest,
+    responses(
+        (status = 200, description = "Query estimated", body = EstimateResponse),
+        (status = 400, description = "Invalid request", body = crate::error::ApiError),
+        (status = 403, description = "Estimates require the admin or developer role", body = crate::error::ApiError),
+        (status = 503, description = "RAG pipeline not initialized", body = crate::error::ApiError)
+    )
+)]
+pub async fn estimate_query(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthenticatedUser>,
+    Json(req): Json<QueryRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !caller.can_debug_queries() {
+        return Err(AppError::Forbidden(
+            "Query estimates require the admin or developer role".to_string(),
+        ));
+    }
+    let (scope, persona) = state.scope_and_persona(
+        req.space.as_deref(),
+        &req.question,
+        caller.department.as_deref(),
+    )?;
+    let rag_query = req.rag_query(false, persona)?.with_scope(scope);
+
+    let rag = state.get_rag().await.ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "RAG pipeline not initialized",
+        )
+    })?;
+    let rag = match req.as_of.as_deref() {
+        Some(name) => Arc::new(snapshot_rag(&state, &rag, name).await?),
+        None => rag,
+    };
+    let estimate = rag.estimate(&rag_query, &caller.to_acl_user()).await?;
+
+    Ok((StatusCode::OK, Json(EstimateResponse::from(estimate))))
+}
+
+/// Re-run a recorded seeded query without calling any backend
+///
+/// Search results, LLM responses, embeddings and document metadata come
+/// from the recording; the response carries the new trace and warns where
+/// the prompts or the answer differ from the recorded run.
+#[utoipa::path(
+    post,
+    path = "/api/v1/query/replay",
+    tag = "query",
+    request_body = ReplayRequest,
+    responses(
+        (status = 200, description = "Query replayed", body = QueryResponse),
+        (status = 400, description = "Recording without retrieval", body = crate::error::ApiError),
+        (status = 403, description = "Replay requires the admin or developer role", body = crate::error::ApiError),
+        (status = 503, description = "RAG pipeline not initialized", body = crate::error::ApiError)
+    )
+)]
+pub async fn replay_query(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthenticatedUser>,
+    Json(req): Json<ReplayRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !caller.can_debug_queries() {
+        return Err(AppError::Forbidden(
+            "Replaying queries requires the admin or developer role".to_string(),
+        ));
+    }
+
+    let rag = state.get_rag().await.ok_or_else(|| {
+        AppError::coded(
+            ErrorCode::ServiceUnavailable,
+            "RAG pipeline not initialized",
+        )
+    })?;
+    let rag_response = rag.replay(&req.recording, &caller.to_acl_user()).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(QueryResponse::new(Uuid::new_v4(), rag_response)),
+    ))
+}
+
+/// Get follow-up question suggestions for a previous query
+#[utoipa::path(
+    get,
+    path = "/api/v1/query/{id}/suggestions",
+    tag = "query",
+    params(
+        ("id" = Uuid, Path, description = "Query ID returned by the query endpoint")
+    ),
+    responses(
+        (status = 200, description = "Suggestions found", body = SuggestionsResponse),
+        (status = 404, description = "Unknown or expired query", body = crate::error::ApiError)
+    )
+)]
+pub async fn get_query_suggestions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuggestionsResponse>, AppError> {
+    state.increment_requests();
+
+    let suggestions = state
+        .get_suggestions(&id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("No suggestions for query {id}")))?;
+
+    Ok(Json(SuggestionsResponse {
+        query_id: id,
+        suggestions,
+    }))
+}
+
+/// Mark the citations of a previous query helpful or unhelpful
+///
+/// Votes become popularity priors that slightly boost (or demote) the cited
+/// chunks and documents in later rankings; older votes count less.
+#[utoipa::path(
+    post,
+    path = "/api/v1/query/{id}/feedback",
+    tag = "query",
+    params(
+        ("id" = Uuid, Path, description = "Query ID returned by the query endpoint")
+    ),
+    request_body = FeedbackRequest,
+    responses(
+        (status = 200, description = "Feedback recorded", body = FeedbackResponse),
+        (status = 400, description = "No citations marked or unknown citation", body = crate::error::ApiError),
+        (status = 404, description = "Unknown or expired query", body = crate::error::ApiError)
+    )
+)]
+pub async fn submit_feedback(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<FeedbackRequest>,
+) -> Result<Json<FeedbackResponse>, AppError> {
+    state.increment_requests();
+
+    if req.helpful.is_empty() && req.unhelpful.is_empty() {
+        return Err(AppError::BadRequest("No citations marked".to_string()));
+    }
+    if let Some(n) = req.helpful.iter().find(|n| req.unhelpful.contains(n)) {
+        return Err(AppError::BadRequest(format!(
+            "Citation {n} is marked both helpful and unhelpful"
+        )));
+    }
+    let citations = state
+        .get_citations(&id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("No citations for query {id}")))?;
+
+    let mut marked: Vec<(usize, bool)> = req
+        .helpful
+        .iter()
+        .map(|&n| (n, true))
+        .chain(req.unhelpful.iter().map(|&n| (n, false)))
+        .collect();
+    marked.sort_unstable();
+    marked.dedup();
+    let at = chrono::Utc::now();
+    let votes = marked
+        .into_iter()
+        .map(|(n, helpful)| {
+            let source = n
+                .checked_sub(1)
+                .and_then(|i| citations.get(i))
+                .ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "Citation {n} is not one of the {} citations of query {id}",
+                        citations.len()
+                    ))
+                })?;
+            Ok(otl_rag::FeedbackVote {
+                document_id: source.document_id,
+                chunk_index: source.chunk_index,
+                helpful,
+                at,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    let recorded = feedback::record_votes(&state, id, caller.user_id, votes).await;
+
+    Ok(Json(FeedbackResponse {
+        query_id: id,
+        recorded,
+    }))
+}
+
 /// Handle streaming RAG query requests with true streaming
 #[utoipa::path(
     post,
@@ -191,6 +927,7 @@ pub async fn query_handler(
 )]
 pub async fn query_stream_handler(
     State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthenticatedUser>,
     Json(req): Json<QueryRequest>,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -201,23 +938,124 @@ pub async fn query_stream_handler(
         return Err(AppError::BadRequest("Question cannot be empty".to_string()));
     }
 
+    let language = req.language()?;
+    let persona = state.prompts.select(caller.department.as_deref());
+    let tokens = stream_answer(
+        &state,
+        &caller.to_acl_user(),
+        &req.question,
+        req.top_k,
+        language,
+        persona.as_ref(),
+    )
+    .await;
+
+    // Use atomic counter for event IDs
+    let counter = Arc::new(AtomicUsize::new(0));
+    let stream = tokens.map(move |result| {
+        let id = counter.fetch_add(1, Ordering::SeqCst);
+        match result {
+            Ok(chunk) => Ok(Event::default()
+                .data(chunk)
+                .id(id.to_string())
+                .event("message")),
+            Err(e) => {
+                tracing::error!("Stream chunk error: {}", e);
+                Ok(Event::default()
+                    .data("[스트리밍 오류]")
+                    .id(id.to_string())
+                    .event("error"))
+            }
+        }
+    });
+
+    Ok(Sse::new(stream).keep_alive(
+        axum::response::sse::KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text("keep-alive"),
+    ))
+}
+
+/// Stream the answer to a question as `user` may see it
+///
+/// Shared by the SSE endpoint, the GraphQL `queryStream` subscription and
+/// the gRPC `QueryStream` call. Answers are moderated like non-streaming
+/// ones: if a sensitive-topic rule applies to the user, the answer is
+/// generated in full, moderated and sent as one chunk, since streamed text
+/// cannot be taken back. Without an LLM, a mock answer is streamed.
+pub(crate) async fn stream_answer(
+    state: &AppState,
+    user: &User,
+    question: &str,
+    top_k: usize,
+    language: Option<Language>,
+    persona: Option<&Persona>,
+) -> BoxStream<'static, otl_core::Result<String>> {
+    let template =
+        PromptTemplate::for_language(language.unwrap_or_else(|| detect_language(question)));
+    let moderator = stream_moderator(state).await;
+    let prompt =
+        build_stream_prompt(state, &moderator, user, question, top_k, template, persona).await;
+
+    let Some(llm) = state.llm_client.read().await.clone() else {
+        tracing::warn!("LLM not initialized, returning mock streaming response");
+        return stream::iter(get_mock_chunks().into_iter().map(Ok)).boxed();
+    };
+    let tokens = match llm.generate_stream(&prompt).await {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            tracing::error!("LLM stream failed: {}", e);
+            return stream::iter(get_mock_chunks().into_iter().map(Ok)).boxed();
+        }
+    };
+    if !moderator.applies_to(user) {
+        return tokens;
+    }
+
+    // A failed chunk ends the answer; what arrived before it is still moderated
+    let mut answer = String::new();
+    let mut failure = None;
+    let mut tokens = tokens;
+    while let Some(token) = tokens.next().await {
+        match token {
+            Ok(text) => answer.push_str(&text),
+            Err(e) => {
+                failure = Some(e);
+                break;
+            }
+        }
+    }
+    let moderation = moderator
+        .moderate(&answer, user, llm.as_ref(), template)
+        .await;
+    if let Some(decision) = &moderation.decision {
+        tracing::info!(
+            target: "audit",
+            user_id = %user.user_id,
+            action = ?decision.action,
+            rules = ?decision.rules,
+            detector = ?decision.detector,
+            redacted_sentences = decision.redacted_sentences,
+            "Streamed answer moderated"
+        );
+    }
+    stream::iter(std::iter::once(Ok(moderation.answer)).chain(failure.map(Err))).boxed()
+}
+
+/// Build the streaming prompt from vector-store context `user` may read
+async fn build_stream_prompt(
+    state: &AppState,
+    moderator: &Moderator,
+    user: &User,
+    question: &str,
+    top_k: usize,
+    template: &PromptTemplate,
+    persona: Option<&Persona>,
+) -> String {
     // First, search for relevant context from vector store (this part must complete before streaming)
     let context = if let Some(vector_store) = state.vector_store.read().await.clone() {
-        match vector_store.search(&req.question, req.top_k).await {
-            Ok(results) => {
-                if results.is_empty() {
-                    tracing::info!("No relevant documents found for query");
-                    String::new()
-                } else {
-                    tracing::info!("Found {} relevant documents", results.len());
-                    results
-                        .iter()
-                        .enumerate()
-                        .map(|(i, r)| format!("[문서 {}] {}", i + 1, r.content))
-                        .collect::<Vec<_>>()
-                        .join("\n\n")
-                }
-            }
+        match vector_store.search(question, top_k).await {
+            Ok(results) => stream_context(template, moderator, results, user),
             Err(e) => {
                 tracing::warn!("Vector search failed: {}", e);
                 String::new()
@@ -227,83 +1065,46 @@ pub async fn query_stream_handler(
         String::new()
     };
 
-    // Build the prompt
-    let prompt = if context.is_empty() {
-        format!(
-            "당신은 조직의 지식 전문가입니다.\n\
-             질문에 대해 간결하고 정확하게 답변하세요.\n\n\
-             질문: {}\n\n답변:",
-            req.question
-        )
-    } else {
-        format!(
-            "당신은 조직의 지식 전문가입니다.\n\
-             아래 제공된 문서를 참고하여 질문에 답변하세요.\n\
-             문서에 없는 내용은 추측하지 마세요.\n\n\
-             === 참고 문서 ===\n{}\n\n\
-             === 질문 ===\n{}\n\n답변:",
-            context, req.question
-        )
-    };
-
-    // Get LLM client
-    let llm_client = state.llm_client.read().await.clone();
-
-    // Create a true streaming SSE response
-    let stream: std::pin::Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>> =
-        if let Some(llm) = llm_client {
-            match llm.generate_stream(&prompt).await {
-                Ok(llm_stream) => {
-                    // Use atomic counter for event IDs
-                    let counter = Arc::new(AtomicUsize::new(0));
-
-                    // Transform LLM stream directly to SSE events without buffering
-                    let sse_stream = llm_stream.map(move |result| {
-                        let id = counter.fetch_add(1, Ordering::SeqCst);
-                        match result {
-                            Ok(chunk) => Ok(Event::default()
-                                .data(chunk)
-                                .id(id.to_string())
-                                .event("message")),
-                            Err(e) => {
-                                tracing::error!("Stream chunk error: {}", e);
-                                Ok(Event::default()
-                                    .data("[스트리밍 오류]")
-                                    .id(id.to_string())
-                                    .event("error"))
-                            }
-                        }
-                    });
-
-                    Box::pin(sse_stream)
-                }
-                Err(e) => {
-                    tracing::error!("LLM stream failed: {}", e);
-                    // Fallback to mock streaming
-                    Box::pin(create_mock_stream())
-                }
-            }
-        } else {
-            tracing::warn!("LLM not initialized, returning mock streaming response");
-            Box::pin(create_mock_stream())
-        };
+    template.stream_prompt(&context, question, persona)
+}
 
-    Ok(Sse::new(stream).keep_alive(
-        axum::response::sse::KeepAlive::new()
-            .interval(Duration::from_secs(15))
-            .text("keep-alive"),
-    ))
+/// Moderator of the RAG pipeline, or the built-in rules before it is up
+async fn stream_moderator(state: &AppState) -> Moderator {
+    match state.get_rag().await {
+        Some(rag) => rag.moderator().clone(),
+        None => Moderator::new(&ModerationConfig::default())
+            .expect("built-in moderation rules are valid"),
+    }
 }
 
-/// Create a mock streaming response for fallback
-fn create_mock_stream() -> impl Stream<Item = Result<Event, Infallible>> {
-    let chunks = get_mock_chunks();
-    stream::iter(chunks.into_iter().enumerate().map(|(i, chunk)| {
-        Ok(Event::default()
-            .data(chunk)
-            .id(i.to_string())
-            .event("message"))
-    }))
+/// Numbered context of the search results `user` may read, with restricted
+/// values masked as in non-streaming answers
+fn stream_context(
+    template: &PromptTemplate,
+    moderator: &Moderator,
+    results: Vec<SearchResult>,
+    user: &User,
+) -> String {
+    let (readable, denied): (Vec<_>, Vec<_>) =
+        results.into_iter().partition(|r| r.acl.can_access(user));
+    if !denied.is_empty() {
+        tracing::debug!("Filtered {} results by ACL", denied.len());
+    }
+    if readable.is_empty() {
+        tracing::info!("No relevant documents found for query");
+        return String::new();
+    }
+
+    tracing::info!("Found {} relevant documents", readable.len());
+    readable
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let content = moderator.mask(&r.content, user, template.restricted_marker);
+            format!("[{} {}] {}", template.document_label, i + 1, content.text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
 }
 
 /// Get mock chunks for fallback streaming response
@@ -318,3 +1119,101 @@ fn get_mock_chunks() -> Vec<String> {
         "(주의: LLM이 초기화되지 않아 Mock 응답입니다)".to_string(),
     ]
 }
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{AccessLevel, DocumentAcl, SearchResultType, SourceReference};
+
+    fn result(content: &str, acl: DocumentAcl) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score: 0.9,
+            source: SourceReference::new(Uuid::new_v4()),
+            acl,
+            result_type: SearchResultType::Vector,
+        }
+    }
+
+    #[test]
+    fn test_stream_context_only_uses_readable_documents() {
+        let template = PromptTemplate::for_language(Language::Korean);
+        let salaries = DocumentAcl {
+            access_level: AccessLevel::Confidential,
+            department: Some("인사팀".to_string()),
+            ..Default::default()
+        };
+        let results = vec![
+            result("연차휴가는 15일입니다.", DocumentAcl::default()),
+            result("2026년 임원 연봉표", salaries),
+        ];
+
+        let mut viewer = User::internal("lee", vec!["viewer".to_string()]);
+        viewer.departments = vec!["총무팀".to_string()];
+        let moderator = Moderator::new(&ModerationConfig::default()).unwrap();
+        let context = stream_context(template, &moderator, results.clone(), &viewer);
+        assert_eq!(context, "[문서 1] 연차휴가는 15일입니다.");
+
+        let mut hr = viewer.clone();
+        hr.departments = vec!["인사팀".to_string()];
+        assert!(stream_context(template, &moderator, results.clone(), &hr)
+            .contains("[문서 2] 2026년 임원 연봉표"));
+
+        assert!(stream_context(template, &moderator, results, &User::anonymous()).is_empty());
+    }
+
+    #[test]
+    fn test_stream_context_masks_restricted_values() {
+        let template = PromptTemplate::for_language(Language::Korean);
+        let moderator = Moderator::new(&ModerationConfig {
+            mask_context: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let results = vec![result(
+            "신입사원 연봉은 4,000만원입니다.",
+            DocumentAcl::default(),
+        )];
+
+        let viewer = User::internal("lee", vec!["VIEWER".to_string()]);
+        let context = stream_context(template, &moderator, results.clone(), &viewer);
+        assert!(context.contains("[권한 필요]"));
+        assert!(!context.contains("4,000"));
+
+        let hr = User::internal("kim", vec!["HR_MANAGER".to_string()]);
+        assert!(stream_context(template, &moderator, results, &hr).contains("4,000만원"));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_answer_is_moderated() {
+        let state = crate::create_test_state();
+        *state.llm_client.write().await = Some(Arc::new(otl_core::testing::MockLlmClient::new(
+            "홍길동 대리의 연봉은 5,000만원입니다. 연차는 15일입니다.",
+        )));
+        let answer = |user: User| {
+            let state = state.clone();
+            async move {
+                stream_answer(&state, &user, "홍길동 연봉은?", 5, None, None)
+                    .await
+                    .map(Result::unwrap)
+                    .collect::<Vec<_>>()
+                    .await
+            }
+        };
+
+        let chunks = answer(User::internal("lee", vec!["VIEWER".to_string()])).await;
+        assert_eq!(chunks.len(), 1);
+        assert!(!chunks[0].contains("5,000"));
+        assert!(chunks[0].contains("[비공개 정보]"));
+        assert!(chunks[0].contains("연차는 15일입니다."));
+
+        // Exempt users get the tokens as generated
+        let chunks = answer(User::internal("kim", vec!["HR_MANAGER".to_string()])).await;
+        assert!(chunks.len() > 1);
+        assert!(chunks.concat().contains("5,000만원"));
+    }
+}
@@ -0,0 +1,298 @@
+//! Saved search handlers
+//!
+//! Author: hephaex@gmail.com
+
+use crate::auth::middleware::AuthenticatedUser;
+use crate::error::AppError;
+use crate::saved_searches::{self, SavedSearch, SavedSearchMatch, SearchSpec};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Default matches per page
+const DEFAULT_LIMIT: i64 = 50;
+
+/// Most matches per page
+const MAX_LIMIT: i64 = 200;
+
+/// Saved search to create or replace
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SavedSearchRequest {
+    /// Display name (defaults to the query)
+    #[schema(example = "육아휴직 소식")]
+    #[serde(default)]
+    pub name: String,
+    /// Query new documents are compared with
+    #[schema(example = "육아휴직 신청 절차")]
+    pub query: String,
+    /// Words a matching chunk should contain (defaults to the words of the
+    /// query)
+    #[schema(example = json!(["육아휴직"]))]
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Match new documents against the search
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl From<SavedSearchRequest> for SearchSpec {
+    fn from(req: SavedSearchRequest) -> Self {
+        Self {
+            name: req.name,
+            query: req.query,
+            keywords: req.keywords,
+            enabled: req.enabled,
+        }
+    }
+}
+
+/// Saved searches of the user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SavedSearchListResponse {
+    pub saved_searches: Vec<SavedSearch>,
+}
+
+/// Query parameters for the match list
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SavedSearchMatchQuery {
+    /// Maximum number of matches
+    #[param(default = 50, maximum = 200)]
+    pub limit: Option<i64>,
+}
+
+/// Documents that matched a saved search
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SavedSearchMatchesResponse {
+    pub saved_search_id: Uuid,
+    /// Newest first
+    pub matches: Vec<SavedSearchMatch>,
+}
+
+/// List saved searches
+///
+/// # Responses
+///
+/// * `200 OK` - Saved searches of the authenticated user, newest first
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    get,
+    path = "/api/v1/saved-searches",
+    tag = "saved-searches",
+    responses(
+        (status = 200, description = "Saved searches", body = SavedSearchListResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_saved_searches(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(SavedSearchListResponse {
+        saved_searches: saved_searches::list(&state.db_pool, user.user_id).await?,
+    }))
+}
+
+/// Save a search
+///
+/// New documents are matched against the query in the background; the user
+/// is notified of every readable document that matches, once.
+///
+/// # Responses
+///
+/// * `201 Created` - Saved search
+/// * `400 Bad Request` - Empty query or too many saved searches
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    post,
+    path = "/api/v1/saved-searches",
+    tag = "saved-searches",
+    request_body = SavedSearchRequest,
+    responses(
+        (status = 201, description = "Saved search", body = SavedSearch),
+        (status = 400, description = "Invalid saved search", body = crate::error::ApiError),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_saved_search(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(req): Json<SavedSearchRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let search = saved_searches::create(&state, user.user_id, req.into()).await?;
+    Ok((StatusCode::CREATED, Json(search)))
+}
+
+/// Get a saved search
+///
+/// # Responses
+///
+/// * `200 OK` - Saved search
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `404 Not Found` - No such saved search of the user
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    get,
+    path = "/api/v1/saved-searches/{id}",
+    tag = "saved-searches",
+    params(("id" = Uuid, Path, description = "Saved search ID")),
+    responses(
+        (status = 200, description = "Saved search", body = SavedSearch),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 404, description = "Saved search not found", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_saved_search(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        saved_searches::get(&state.db_pool, user.user_id, id).await?,
+    ))
+}
+
+/// Replace a saved search
+///
+/// Documents that already matched are not matched again.
+///
+/// # Responses
+///
+/// * `200 OK` - Saved search
+/// * `400 Bad Request` - Empty query
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `404 Not Found` - No such saved search of the user
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    put,
+    path = "/api/v1/saved-searches/{id}",
+    tag = "saved-searches",
+    params(("id" = Uuid, Path, description = "Saved search ID")),
+    request_body = SavedSearchRequest,
+    responses(
+        (status = 200, description = "Saved search", body = SavedSearch),
+        (status = 400, description = "Invalid saved search", body = crate::error::ApiError),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 404, description = "Saved search not found", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_saved_search(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SavedSearchRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        saved_searches::update(&state.db_pool, user.user_id, id, req.into()).await?,
+    ))
+}
+
+/// Delete a saved search
+///
+/// # Responses
+///
+/// * `204 No Content` - Deleted with its matches
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `404 Not Found` - No such saved search of the user
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    delete,
+    path = "/api/v1/saved-searches/{id}",
+    tag = "saved-searches",
+    params(("id" = Uuid, Path, description = "Saved search ID")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 404, description = "Saved search not found", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_saved_search(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    saved_searches::delete(&state.db_pool, user.user_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List documents that matched a saved search
+///
+/// Documents the user can no longer read are left out.
+///
+/// # Responses
+///
+/// * `200 OK` - Matches, newest first
+/// * `401 Unauthorized` - Invalid or missing authentication
+/// * `404 Not Found` - No such saved search of the user
+/// * `500 Internal Server Error` - Server error
+#[utoipa::path(
+    get,
+    path = "/api/v1/saved-searches/{id}/matches",
+    tag = "saved-searches",
+    params(
+        ("id" = Uuid, Path, description = "Saved search ID"),
+        SavedSearchMatchQuery
+    ),
+    responses(
+        (status = 200, description = "Matching documents", body = SavedSearchMatchesResponse),
+        (status = 401, description = "Unauthorized", body = crate::error::ApiError),
+        (status = 404, description = "Saved search not found", body = crate::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::error::ApiError),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_saved_search_matches(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SavedSearchMatchQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let mut matches = saved_searches::matches(&state.db_pool, user.user_id, id, limit).await?;
+    let document_ids: Vec<Uuid> = matches.iter().map(|m| m.document_id).collect();
+    let acls = super::documents::fetch_document_acls(&state, &document_ids).await?;
+    let acl_user = user.to_acl_user();
+    matches.retain(|m| {
+        acls.get(&m.document_id)
+            .is_some_and(|acl| acl.can_access(&acl_user))
+    });
+    Ok(Json(SavedSearchMatchesResponse {
+        saved_search_id: id,
+        matches,
+    }))
+}
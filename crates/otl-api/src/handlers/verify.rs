@@ -3,7 +3,10 @@
 //! Author: hephaex@gmail.com
 
 use crate::auth::middleware::AuthenticatedUser;
+use crate::auto_approve;
 use crate::error::AppError;
+use crate::quality_audit;
+use crate::review;
 use crate::state::AppState;
 use axum::{
     extract::{Path, Query, State},
@@ -12,6 +15,7 @@ use axum::{
     Extension, Json,
 };
 use chrono::{DateTime, Utc};
+use otl_extractor::offsets;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use utoipa::{IntoParams, ToSchema};
@@ -60,8 +64,19 @@ pub enum ExtractedContent {
     Entity {
         text: String,
         entity_type: String,
+        /// Byte offsets into the context
         start: usize,
         end: usize,
+        /// Character offsets into the context, for highlighting
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        char_start: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        char_end: Option<usize>,
+        /// UTF-16 code unit offsets into the context, for JavaScript clients
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        utf16_start: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        utf16_end: Option<usize>,
     },
     Relation {
         subject: String,
@@ -70,6 +85,35 @@ pub enum ExtractedContent {
     },
 }
 
+impl ExtractedContent {
+    /// Derive character and UTF-16 offsets from the byte offsets
+    ///
+    /// Offsets stored before character offsets existed are filled in here;
+    /// spans that do not fit the context keep whatever was stored.
+    fn with_offsets(mut self, context: &str) -> Self {
+        if let Self::Entity {
+            start,
+            end,
+            char_start,
+            char_end,
+            utf16_start,
+            utf16_end,
+            ..
+        } = &mut self
+        {
+            if let Some((s, e)) = offsets::byte_span_to_char(context, *start, *end) {
+                *char_start = Some(s);
+                *char_end = Some(e);
+            }
+            if let Some((s, e)) = offsets::byte_span_to_utf16(context, *start, *end) {
+                *utf16_start = Some(s);
+                *utf16_end = Some(e);
+            }
+        }
+        self
+    }
+}
+
 /// Pending extractions list response
 #[derive(Debug, Serialize)]
 pub struct PendingListResponse {
@@ -117,6 +161,11 @@ pub async fn list_pending(
 ) -> Result<impl IntoResponse, AppError> {
     state.increment_requests();
 
+    // Items whose review lock expired are pending again
+    if let Err(e) = review::release_expired_locks(&state).await {
+        tracing::warn!("Failed to release expired review locks: {:?}", e);
+    }
+
     let page = params.page.unwrap_or(1).max(1);
     let page_size = params.page_size.unwrap_or(20).min(100);
     let offset = ((page - 1) * page_size) as i64;
@@ -254,6 +303,7 @@ pub async fn list_pending(
                     else {
                         continue;
                     };
+                    let content = content.with_offsets(&context);
 
                     results.push(PendingExtraction {
                         id: row.id,
@@ -311,249 +361,1775 @@ pub async fn list_pending(
     Ok((StatusCode::OK, Json(response)))
 }
 
-/// Verification action
-#[derive(Debug, Deserialize, ToSchema)]
-pub struct VerifyAction {
-    /// Optional correction for the extraction
-    pub correction: Option<ExtractedContent>,
+/// Characters of document text shown on each side of the passage by default
+const DEFAULT_WINDOW_CHARS: usize = 500;
 
-    /// Reviewer notes
-    pub notes: Option<String>,
+/// Most approved triples returned with an extraction
+const MAX_APPROVED_TRIPLES: usize = 20;
+
+/// Document text around the passage an extraction was made from
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContextWindow {
+    /// Text before the passage
+    pub before: String,
+
+    /// The passage itself
+    #[schema(example = "연차휴가는 팀장의 사전 승인을 받아야 한다.")]
+    pub text: String,
+
+    /// Text after the passage
+    pub after: String,
+
+    /// Page of the passage
+    pub page: Option<i32>,
+
+    /// Section of the passage
+    #[schema(example = "제3장 휴가")]
+    pub section: Option<String>,
 }
 
-/// Verification response
-#[derive(Debug, Serialize)]
-pub struct VerifyResponse {
+/// Source document of an extraction
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReviewDocument {
+    pub id: Uuid,
+
+    #[schema(example = "인사규정_2024.pdf")]
+    pub title: String,
+
+    #[schema(example = "pdf")]
+    pub file_type: String,
+
+    #[schema(example = "internal")]
+    pub access_level: String,
+
+    pub department: Option<String>,
+
+    /// Document metadata (freshness, lineage, custom fields)
+    pub metadata: serde_json::Value,
+
+    pub created_at: String,
+}
+
+/// Approved relation of the same document that shares an entity with the
+/// extraction under review
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ApprovedTriple {
+    /// Extraction the relation was approved in
+    pub extraction_id: Uuid,
+
+    #[schema(example = "연차휴가")]
+    pub subject: String,
+
+    #[schema(example = "requires")]
+    pub predicate: String,
+
+    #[schema(example = "팀장 승인")]
+    pub object: String,
+}
+
+/// Extraction with everything needed to review it in one view
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExtractionDetail {
+    /// Extraction UUID
     pub id: Uuid,
+
+    #[schema(example = "pending")]
     pub status: String,
-    pub message: String,
+
+    #[schema(example = 0.65)]
+    pub confidence: f32,
+
+    /// Review order (lower first)
+    #[schema(example = 100)]
+    pub priority: i32,
+
+    /// Reviewer the extraction is assigned to
+    pub assigned_to: Option<String>,
+
+    /// Reviewer holding the review lock
+    pub locked_by: Option<String>,
+
+    /// When the review lock expires
+    pub lock_expires_at: Option<String>,
+
+    pub created_at: String,
+
+    /// Extracted entities, with offsets into `context`
+    pub entities: Vec<ExtractedContent>,
+
+    /// Extracted relations
+    pub relations: Vec<ExtractedContent>,
+
+    /// Passage the extraction was made from
+    #[schema(example = "연차휴가는 팀장의 사전 승인을 받아야 한다.")]
+    pub context: String,
+
+    /// Surrounding document text (absent when the passage is not found in
+    /// the stored chunks)
+    pub window: Option<ContextWindow>,
+
+    /// Source document
+    pub document: ReviewDocument,
+
+    /// Approved relations of the document sharing an entity with this one
+    pub approved_triples: Vec<ApprovedTriple>,
+
+    /// Decisions recorded so far (several for documents needing multiple
+    /// approvals)
+    pub reviews: Vec<ReviewRecord>,
 }
 
-/// Approve an extraction
-#[utoipa::path(
-    post,
-    path = "/api/v1/verify/{id}/approve",
-    tag = "verify",
-    params(
-        ("id" = Uuid, Path, description = "Extraction UUID")
-    ),
-    request_body = VerifyAction,
-    responses(
-        (status = 200, description = "Extraction approved"),
-        (status = 404, description = "Extraction not found")
-    )
-)]
-pub async fn approve_extraction(
-    State(state): State<Arc<AppState>>,
-    Extension(user): Extension<AuthenticatedUser>,
-    Path(id): Path<Uuid>,
-    Json(action): Json<VerifyAction>,
-) -> Result<impl IntoResponse, AppError> {
-    state.increment_requests();
+/// Decision of one reviewer on an extraction
+#[derive(Debug, Serialize, ToSchema, sqlx::FromRow)]
+pub struct ReviewRecord {
+    pub reviewer_id: String,
 
-    // Start database transaction
-    let mut tx = state
-        .db_pool
-        .begin()
+    /// `approve` or `reject`
+    #[schema(example = "approve")]
+    pub decision: String,
+
+    /// Correction proposed with an approval
+    pub correction: Option<serde_json::Value>,
+
+    pub notes: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters for an extraction
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExtractionDetailQuery {
+    /// Characters of surrounding text on each side of the passage
+    #[param(default = 500)]
+    pub window: Option<usize>,
+}
+
+/// Up to `chars` characters before and after `passage` in `text`
+fn context_window(text: &str, passage: &str, chars: usize) -> Option<(String, String)> {
+    if passage.is_empty() {
+        return None;
+    }
+    let start = text.find(passage)?;
+    let before: Vec<char> = text[..start].chars().rev().take(chars).collect();
+    let after = text[start + passage.len()..].chars().take(chars).collect();
+    Some((before.into_iter().rev().collect(), after))
+}
+
+/// Entity names an extraction mentions
+fn mentioned_terms(entities: &[ExtractedContent], relations: &[ExtractedContent]) -> Vec<String> {
+    let mut terms = Vec::new();
+    for content in entities.iter().chain(relations) {
+        match content {
+            ExtractedContent::Entity { text, .. } => terms.push(text.clone()),
+            ExtractedContent::Relation {
+                subject, object, ..
+            } => {
+                terms.push(subject.clone());
+                terms.push(object.clone());
+            }
+        }
+    }
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Approved relations whose subject or object is one of `terms`
+fn neighboring_triples(
+    terms: &[String],
+    approved: Vec<(Uuid, serde_json::Value)>,
+) -> Vec<ApprovedTriple> {
+    approved
+        .into_iter()
+        .flat_map(|(extraction_id, relations)| {
+            serde_json::from_value::<Vec<serde_json::Value>>(relations)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(move |relation| {
+                    match serde_json::from_value::<ExtractedContent>(relation).ok()? {
+                        ExtractedContent::Relation {
+                            subject,
+                            predicate,
+                            object,
+                        } => Some(ApprovedTriple {
+                            extraction_id,
+                            subject,
+                            predicate,
+                            object,
+                        }),
+                        ExtractedContent::Entity { .. } => None,
+                    }
+                })
+        })
+        .filter(|t| terms.contains(&t.subject) || terms.contains(&t.object))
+        .take(MAX_APPROVED_TRIPLES)
+        .collect()
+}
+
+/// Parse stored extraction content, skipping malformed items
+fn parse_contents(value: serde_json::Value, context: &str) -> Vec<ExtractedContent> {
+    serde_json::from_value::<Vec<serde_json::Value>>(value)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| serde_json::from_value::<ExtractedContent>(item).ok())
+        .map(|content| content.with_offsets(context))
+        .collect()
+}
+
+/// Chunk holding a passage, or one of its neighbors
+#[derive(sqlx::FromRow)]
+struct ContextChunk {
+    chunk_index: i32,
+    content: String,
+    page_number: Option<i32>,
+    section_name: Option<String>,
+}
+
+/// First chunk of a document holding the passage, joined with its neighbors
+///
+/// Sealed (Restricted) chunks cannot be searched in SQL, so those documents
+/// are opened and searched here.
+async fn context_chunks(
+    state: &AppState,
+    document_id: Uuid,
+    context: &str,
+) -> Result<Vec<ContextChunk>, AppError> {
+    let sealed = state.keyring.is_enabled()
+        && state
+            .keyring
+            .document_key(&state.db_pool, document_id)
+            .await?
+            .is_some();
+    if !sealed {
+        return sqlx::query_as(
+            r#"
+            WITH hit AS (
+                SELECT chunk_index
+                FROM document_chunks
+                WHERE document_id = $1 AND strpos(content, $2) > 0
+                ORDER BY chunk_index
+                LIMIT 1
+            )
+            SELECT c.chunk_index, c.content, c.page_number, c.section_name
+            FROM document_chunks c, hit
+            WHERE c.document_id = $1
+              AND c.chunk_index BETWEEN hit.chunk_index - 1 AND hit.chunk_index + 1
+            ORDER BY c.chunk_index
+            "#,
+        )
+        .bind(document_id)
+        .bind(context)
+        .fetch_all(&state.db_pool)
         .await
-        .map_err(|e| AppError::Internal(format!("Failed to start transaction: {e}")))?;
+        .map_err(|e| AppError::Internal(format!("Failed to fetch document chunks: {e}")));
+    }
 
-    // Verify extraction exists and is pending
-    let extraction: Option<(String, serde_json::Value, serde_json::Value)> = sqlx::query_as(
+    let mut chunks: Vec<ContextChunk> = sqlx::query_as(
+        "SELECT chunk_index, content, page_number, section_name \
+         FROM document_chunks WHERE document_id = $1 ORDER BY chunk_index",
+    )
+    .bind(document_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch document chunks: {e}")))?;
+    state
+        .keyring
+        .open_chunks(
+            &state.db_pool,
+            document_id,
+            chunks
+                .iter_mut()
+                .map(|c| (c.chunk_index.max(0) as u32, &mut c.content)),
+        )
+        .await?;
+    let Some(hit) = chunks
+        .iter()
+        .find(|c| c.content.contains(context))
+        .map(|c| c.chunk_index)
+    else {
+        return Ok(Vec::new());
+    };
+    chunks.retain(|c| (hit - 1..=hit + 1).contains(&c.chunk_index));
+    Ok(chunks)
+}
+
+/// Load an extraction with its context window, document and neighboring
+/// approved triples
+async fn load_detail(
+    state: &AppState,
+    id: Uuid,
+    window_chars: usize,
+) -> Result<Option<ExtractionDetail>, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct DetailRow {
+        id: Uuid,
+        document_id: Uuid,
+        extracted_entities: serde_json::Value,
+        extracted_relations: serde_json::Value,
+        source_text: Option<String>,
+        confidence_score: Option<f32>,
+        status: String,
+        priority: Option<i32>,
+        assigned_to: Option<String>,
+        locked_by: Option<String>,
+        lock_expires_at: Option<DateTime<Utc>>,
+        created_at: DateTime<Utc>,
+        title: String,
+        file_type: String,
+        access_level: String,
+        department: Option<String>,
+        metadata: Option<serde_json::Value>,
+        document_created_at: DateTime<Utc>,
+    }
+
+    let row: Option<DetailRow> = sqlx::query_as(
         r#"
-        SELECT status::text, extracted_entities, extracted_relations
-        FROM extraction_queue
-        WHERE id = $1
+        SELECT
+            eq.id,
+            eq.document_id,
+            eq.extracted_entities,
+            eq.extracted_relations,
+            eq.source_text,
+            eq.confidence_score,
+            eq.status::text AS status,
+            eq.priority,
+            eq.assigned_to,
+            eq.locked_by,
+            eq.lock_expires_at,
+            eq.created_at,
+            d.title,
+            d.file_type::text AS file_type,
+            d.access_level::text AS access_level,
+            d.department,
+            d.metadata,
+            d.created_at AS document_created_at
+        FROM extraction_queue eq
+        JOIN documents d ON eq.document_id = d.id
+        WHERE eq.id = $1
         "#,
     )
     .bind(id)
-    .fetch_optional(&mut *tx)
+    .fetch_optional(&state.db_pool)
     .await
     .map_err(|e| AppError::Internal(format!("Failed to fetch extraction: {e}")))?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
 
-    let (current_status, mut entities, mut relations) =
-        extraction.ok_or_else(|| AppError::NotFound(format!("Extraction {id} not found")))?;
-
-    if current_status != "pending" {
-        return Err(AppError::BadRequest(format!(
-            "Cannot approve extraction in status: {current_status}"
-        )));
-    }
-
-    // If correction provided, update the extraction content
-    if let Some(correction) = action.correction {
-        match correction {
-            ExtractedContent::Entity { .. } => {
-                // Replace entities with corrected version
-                entities = serde_json::to_value(vec![correction]).unwrap_or(serde_json::json!([]));
-            }
-            ExtractedContent::Relation { .. } => {
-                // Replace relations with corrected version
-                relations = serde_json::to_value(vec![correction]).unwrap_or(serde_json::json!([]));
-            }
+    let context = row.source_text.unwrap_or_default();
+    let entities = parse_contents(row.extracted_entities, &context);
+    let relations = parse_contents(row.extracted_relations, &context);
+
+    let mut window = None;
+    if !context.is_empty() {
+        let chunks = context_chunks(state, row.document_id, &context).await?;
+        let text = chunks
+            .iter()
+            .map(|c| c.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let hit = chunks.iter().find(|c| c.content.contains(context.as_str()));
+        if let (Some((before, after)), Some(hit)) =
+            (context_window(&text, &context, window_chars), hit)
+        {
+            tracing::debug!("Context of extraction {} in chunk {}", id, hit.chunk_index);
+            window = Some(ContextWindow {
+                before,
+                text: context.clone(),
+                after,
+                page: hit.page_number,
+                section: hit.section_name.clone(),
+            });
         }
     }
 
-    // Update extraction status to approved
-    let now = Utc::now();
-    let notes_for_log = action.notes.clone();
-    let notes_value = action.notes.clone(); // Clone before bind
-    let result = sqlx::query(
+    let approved: Vec<(Uuid, serde_json::Value)> = sqlx::query_as(
         r#"
-        UPDATE extraction_queue
-        SET status = 'approved',
-            reviewer_id = $1,
-            review_notes = $2,
-            reviewed_at = $3,
-            extracted_entities = $4,
-            extracted_relations = $5
-        WHERE id = $6
+        SELECT id, extracted_relations
+        FROM extraction_queue
+        WHERE document_id = $1 AND status = 'approved' AND id <> $2
+        ORDER BY reviewed_at DESC NULLS LAST
+        LIMIT 200
         "#,
     )
-    .bind(user.user_id.to_string())
-    .bind(notes_value)
-    .bind(now)
-    .bind(entities)
-    .bind(relations)
+    .bind(row.document_id)
     .bind(id)
-    .execute(&mut *tx)
+    .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| AppError::Internal(format!("Failed to update extraction: {e}")))?;
+    .map_err(|e| AppError::Internal(format!("Failed to fetch approved extractions: {e}")))?;
+    let approved_triples = neighboring_triples(&mentioned_terms(&entities, &relations), approved);
 
-    if result.rows_affected() == 0 {
-        tx.rollback().await.ok();
-        return Err(AppError::NotFound(format!("Extraction {id} not found")));
-    }
+    let reviews: Vec<ReviewRecord> = sqlx::query_as(
+        r#"
+        SELECT reviewer_id, decision, correction, notes, created_at
+        FROM extraction_reviews
+        WHERE extraction_id = $1
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch reviews: {e}")))?;
+
+    Ok(Some(ExtractionDetail {
+        id: row.id,
+        status: row.status,
+        confidence: row.confidence_score.unwrap_or(0.0),
+        priority: row.priority.unwrap_or(100),
+        assigned_to: row.assigned_to,
+        locked_by: row.locked_by,
+        lock_expires_at: row.lock_expires_at.map(|at| at.to_rfc3339()),
+        created_at: row.created_at.to_rfc3339(),
+        entities,
+        relations,
+        context,
+        window,
+        document: ReviewDocument {
+            id: row.document_id,
+            title: row.title,
+            file_type: row.file_type,
+            access_level: row.access_level,
+            department: row.department,
+            metadata: row.metadata.unwrap_or_else(|| serde_json::json!({})),
+            created_at: row.document_created_at.to_rfc3339(),
+        },
+        approved_triples,
+        reviews,
+    }))
+}
 
-    // Commit transaction
-    tx.commit()
-        .await
-        .map_err(|e| AppError::Internal(format!("Failed to commit transaction: {e}")))?;
+/// Get an extraction with its review context
+///
+/// Returns the surrounding document text, the document metadata and the
+/// approved relations of the same document that share an entity with it,
+/// so reviewers see the evidence side by side without refetching.
+#[utoipa::path(
+    get,
+    path = "/api/v1/verify/{id}",
+    tag = "verify",
+    params(
+        ("id" = Uuid, Path, description = "Extraction UUID"),
+        ExtractionDetailQuery
+    ),
+    responses(
+        (status = 200, description = "Extraction with review context", body = ExtractionDetail),
+        (status = 404, description = "Extraction not found")
+    )
+)]
+pub async fn get_extraction(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ExtractionDetailQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
 
-    tracing::info!("Approved extraction {} with notes: {:?}", id, notes_for_log);
+    let window = params.window.unwrap_or(DEFAULT_WINDOW_CHARS).min(5000);
+    let detail = load_detail(&state, id, window)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Extraction {id} not found")))?;
 
-    let response = VerifyResponse {
-        id,
-        status: "approved".to_string(),
-        message: "Extraction approved and queued for graph loading".to_string(),
-    };
+    Ok((StatusCode::OK, Json(detail)))
+}
 
-    Ok((StatusCode::OK, Json(response)))
+/// Query parameters for the next extraction to review
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct NextExtractionQuery {
+    /// Only extractions with entities (`entity`) or relations (`relation`)
+    pub extraction_type: Option<String>,
+
+    /// Filter by document ID
+    pub document_id: Option<Uuid>,
+
+    /// Maximum confidence to include
+    pub max_confidence: Option<f32>,
+
+    /// Only extractions assigned to the caller
+    #[param(default = false)]
+    pub mine: Option<bool>,
+
+    /// Queue to work through: `pending` or `adjudication`
+    #[param(default = "pending")]
+    pub status: Option<String>,
+
+    /// Extraction reviewed last; the next one in review order follows it
+    pub after: Option<Uuid>,
+
+    /// Characters of surrounding text on each side of the passage
+    #[param(default = 500)]
+    pub window: Option<usize>,
 }
 
-/// Reject action
-#[derive(Debug, Deserialize)]
-pub struct RejectAction {
-    /// Reason for rejection
-    pub reason: String,
+/// Next extraction of a review session
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NextExtractionResponse {
+    /// Next extraction, if any is left
+    pub extraction: Option<ExtractionDetail>,
 
-    /// Reviewer notes
-    pub notes: Option<String>,
+    /// Extractions matching the filter after this one
+    pub remaining: i64,
 }
 
-/// Reject an extraction
+/// Get the next pending extraction to review
+///
+/// Pending extractions are reviewed in priority order. Passing the last
+/// reviewed (or skipped) extraction as `after` moves through the queue one
+/// item at a time, so a keyboard-driven session needs no list paging.
 #[utoipa::path(
-    post,
-    path = "/api/v1/verify/{id}/reject",
+    get,
+    path = "/api/v1/verify/next",
     tag = "verify",
-    params(
-        ("id" = Uuid, Path, description = "Extraction UUID")
-    ),
+    params(NextExtractionQuery),
     responses(
-        (status = 200, description = "Extraction rejected"),
-        (status = 404, description = "Extraction not found")
+        (status = 200, description = "Next extraction to review", body = NextExtractionResponse),
+        (status = 400, description = "Invalid extraction type")
     )
 )]
-pub async fn reject_extraction(
+pub async fn next_extraction(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthenticatedUser>,
-    Path(id): Path<Uuid>,
-    Json(action): Json<RejectAction>,
+    Query(params): Query<NextExtractionQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     state.increment_requests();
 
-    // Start database transaction
-    let mut tx = state
-        .db_pool
-        .begin()
-        .await
-        .map_err(|e| AppError::Internal(format!("Failed to start transaction: {e}")))?;
-
-    // Verify extraction exists and is pending
-    let current_status: Option<String> = sqlx::query_scalar(
-        r#"
-        SELECT status::text
-        FROM extraction_queue
-        WHERE id = $1
-        "#,
-    )
-    .bind(id)
-    .fetch_optional(&mut *tx)
-    .await
-    .map_err(|e| AppError::Internal(format!("Failed to fetch extraction: {e}")))?;
-
-    let status =
-        current_status.ok_or_else(|| AppError::NotFound(format!("Extraction {id} not found")))?;
+    if let Some(kind) = params.extraction_type.as_deref() {
+        if kind != "entity" && kind != "relation" {
+            return Err(AppError::BadRequest(format!(
+                "Unknown extraction type: {kind} (expected entity or relation)"
+            )));
+        }
+    }
 
-    if status != "pending" {
+    let status = params.status.as_deref().unwrap_or("pending");
+    if status != "pending" && status != "adjudication" {
         return Err(AppError::BadRequest(format!(
-            "Cannot reject extraction in status: {status}"
+            "Unknown review queue: {status} (expected pending or adjudication)"
         )));
     }
 
-    // Update extraction status to rejected
-    let now = Utc::now();
-    let review_notes = format!(
-        "REJECTED: {}\n{}",
+    // Items whose review lock expired are pending again
+    if let Err(e) = review::release_expired_locks(&state).await {
+        tracing::warn!("Failed to release expired review locks: {:?}", e);
+    }
+
+    // Extractions matching the filter, after the cursor; pending ones the
+    // caller already decided wait for other reviewers
+    let ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT eq.id
+        FROM extraction_queue eq
+        WHERE eq.status::text = $6
+          AND ($6 <> 'pending' OR NOT EXISTS (
+                SELECT 1 FROM extraction_reviews r
+                WHERE r.extraction_id = eq.id AND r.reviewer_id = $7))
+          AND ($1::uuid IS NULL OR eq.document_id = $1)
+          AND ($2::real IS NULL OR eq.confidence_score <= $2)
+          AND ($3::text IS NULL
+               OR ($3 = 'entity' AND jsonb_array_length(eq.extracted_entities) > 0)
+               OR ($3 = 'relation' AND jsonb_array_length(eq.extracted_relations) > 0))
+          AND ($4::uuid IS NULL
+               OR (COALESCE(eq.priority, 100), eq.created_at, eq.id) > (
+                   SELECT COALESCE(priority, 100), created_at, id
+                   FROM extraction_queue
+                   WHERE id = $4))
+          AND ($5::text IS NULL OR eq.assigned_to = $5)
+        ORDER BY COALESCE(eq.priority, 100), eq.created_at, eq.id
+        "#,
+    )
+    .bind(params.document_id)
+    .bind(params.max_confidence)
+    .bind(params.extraction_type.as_deref())
+    .bind(params.after)
+    .bind(
+        params
+            .mine
+            .unwrap_or(false)
+            .then(|| user.user_id.to_string()),
+    )
+    .bind(status)
+    .bind(user.user_id.to_string())
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch pending extractions: {e}")))?;
+
+    let window = params.window.unwrap_or(DEFAULT_WINDOW_CHARS).min(5000);
+    let extraction = match ids.first() {
+        Some(&id) => load_detail(&state, id, window).await?,
+        None => None,
+    };
+
+    let response = NextExtractionResponse {
+        extraction,
+        remaining: ids.len().saturating_sub(1) as i64,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Verification action
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VerifyAction {
+    /// Optional correction for the extraction
+    pub correction: Option<ExtractedContent>,
+
+    /// Reviewer notes
+    pub notes: Option<String>,
+}
+
+/// Verification response
+#[derive(Debug, Serialize)]
+pub struct VerifyResponse {
+    pub id: Uuid,
+    pub status: String,
+    pub message: String,
+}
+
+/// Apply a reviewer's correction to the extracted content
+fn apply_correction(
+    entities: &mut serde_json::Value,
+    relations: &mut serde_json::Value,
+    correction: ExtractedContent,
+) {
+    match correction {
+        ExtractedContent::Entity { .. } => {
+            // Replace entities with corrected version
+            *entities = serde_json::to_value(vec![correction]).unwrap_or(serde_json::json!([]));
+        }
+        ExtractedContent::Relation { .. } => {
+            // Replace relations with corrected version
+            *relations = serde_json::to_value(vec![correction]).unwrap_or(serde_json::json!([]));
+        }
+    }
+}
+
+/// Record a reviewer's decision and settle the extraction once enough
+/// reviewers agree
+///
+/// Extractions from documents whose access level needs more than one
+/// approval stay pending (unlocked and unassigned, for the next reviewer)
+/// until enough decisions agree, and go to adjudication when two differ.
+async fn decide(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    id: Uuid,
+    decision: review::Decision,
+    correction: Option<ExtractedContent>,
+    notes: Option<String>,
+) -> Result<review::ReviewOutcome, AppError> {
+    // Start database transaction
+    let mut tx = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to start transaction: {e}")))?;
+
+    // Verify extraction exists and is pending or locked to this reviewer
+    #[allow(clippy::type_complexity)]
+    let extraction: Option<(
+        String,
+        serde_json::Value,
+        serde_json::Value,
+        Option<String>,
+        Option<DateTime<Utc>>,
+        String,
+    )> = sqlx::query_as(
+        r#"
+        SELECT
+            eq.status::text,
+            eq.extracted_entities,
+            eq.extracted_relations,
+            eq.locked_by,
+            eq.lock_expires_at,
+            d.access_level::text
+        FROM extraction_queue eq
+        JOIN documents d ON eq.document_id = d.id
+        WHERE eq.id = $1
+        FOR UPDATE OF eq
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch extraction: {e}")))?;
+
+    let (status, mut entities, mut relations, locked_by, lock_expires_at, access_level) =
+        extraction.ok_or_else(|| AppError::NotFound(format!("Extraction {id} not found")))?;
+
+    let reviewer = user.user_id.to_string();
+    review::check_reviewable(
+        id,
+        &status,
+        locked_by.as_deref(),
+        lock_expires_at,
+        &reviewer,
+        Utc::now(),
+    )?;
+
+    // Record the decision; each reviewer decides an extraction once
+    let correction_value = correction
+        .as_ref()
+        .and_then(|c| serde_json::to_value(c).ok());
+    let recorded = sqlx::query(
+        r#"
+        INSERT INTO extraction_reviews (extraction_id, reviewer_id, decision, correction, notes)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (extraction_id, reviewer_id) DO NOTHING
+        "#,
+    )
+    .bind(id)
+    .bind(&reviewer)
+    .bind(decision.as_str())
+    .bind(&correction_value)
+    .bind(&notes)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to record review: {e}")))?;
+    if recorded.rows_affected() == 0 {
+        return Err(AppError::BadRequest(format!(
+            "You already reviewed extraction {id}"
+        )));
+    }
+
+    let decisions: Vec<(String, Option<serde_json::Value>)> = sqlx::query_as(
+        r#"
+        SELECT decision, correction
+        FROM extraction_reviews
+        WHERE extraction_id = $1
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch reviews: {e}")))?;
+    let decisions: Vec<_> = decisions
+        .into_iter()
+        .filter_map(|(d, c)| Some((review::Decision::parse(&d)?, c)))
+        .collect();
+
+    let required = state.review.required_approvals(&access_level);
+    let outcome = review::tally(&decisions, required);
+
+    let now = Utc::now();
+    let result = match &outcome {
+        review::ReviewOutcome::Approved | review::ReviewOutcome::Rejected => {
+            // All decisions agree, so this one's correction is everyone's
+            if let Some(correction) = correction {
+                apply_correction(&mut entities, &mut relations, correction);
+            }
+            let status = if outcome == review::ReviewOutcome::Approved {
+                "approved"
+            } else {
+                "rejected"
+            };
+            sqlx::query(
+                r#"
+                UPDATE extraction_queue
+                SET status = $1::verification_status,
+                    reviewer_id = $2,
+                    review_notes = $3,
+                    reviewed_at = $4,
+                    extracted_entities = $5,
+                    extracted_relations = $6,
+                    locked_by = NULL,
+                    lock_expires_at = NULL
+                WHERE id = $7
+                "#,
+            )
+            .bind(status)
+            .bind(&reviewer)
+            .bind(&notes)
+            .bind(now)
+            .bind(entities)
+            .bind(relations)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        }
+        review::ReviewOutcome::Pending { .. } | review::ReviewOutcome::Adjudication => {
+            let status = if outcome == review::ReviewOutcome::Adjudication {
+                "adjudication"
+            } else {
+                "pending"
+            };
+            sqlx::query(
+                r#"
+                UPDATE extraction_queue
+                SET status = $1::verification_status,
+                    assigned_to = NULL,
+                    assigned_at = NULL,
+                    locked_by = NULL,
+                    lock_expires_at = NULL
+                WHERE id = $2
+                "#,
+            )
+            .bind(status)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        }
+    }
+    .map_err(|e| AppError::Internal(format!("Failed to update extraction: {e}")))?;
+
+    if result.rows_affected() == 0 {
+        tx.rollback().await.ok();
+        return Err(AppError::NotFound(format!("Extraction {id} not found")));
+    }
+
+    // Commit transaction
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to commit transaction: {e}")))?;
+
+    Ok(outcome)
+}
+
+/// Response for a decision that did not settle the extraction
+fn unsettled_response(id: Uuid, outcome: &review::ReviewOutcome) -> Option<VerifyResponse> {
+    match outcome {
+        review::ReviewOutcome::Pending { decided, required } => Some(VerifyResponse {
+            id,
+            status: "pending".to_string(),
+            message: format!("Decision recorded ({decided} of {required} reviews)"),
+        }),
+        review::ReviewOutcome::Adjudication => Some(VerifyResponse {
+            id,
+            status: "adjudication".to_string(),
+            message: "Reviewers disagree; extraction sent to adjudication".to_string(),
+        }),
+        review::ReviewOutcome::Approved | review::ReviewOutcome::Rejected => None,
+    }
+}
+
+/// Approve an extraction
+///
+/// Extractions from documents whose access level needs several approvals
+/// (`VERIFY_REQUIRED_APPROVALS`) are approved once enough reviewers agree.
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/{id}/approve",
+    tag = "verify",
+    params(
+        ("id" = Uuid, Path, description = "Extraction UUID")
+    ),
+    request_body = VerifyAction,
+    responses(
+        (status = 200, description = "Extraction approved, or approval recorded"),
+        (status = 400, description = "Already reviewed by the caller or not pending"),
+        (status = 404, description = "Extraction not found"),
+        (status = 409, description = "Locked to another reviewer (REVIEW_LOCKED)", body = crate::error::ApiError)
+    )
+)]
+pub async fn approve_extraction(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(action): Json<VerifyAction>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let notes_for_log = action.notes.clone();
+    let outcome = decide(
+        &state,
+        &user,
+        id,
+        review::Decision::Approve,
+        action.correction,
+        action.notes,
+    )
+    .await?;
+
+    tracing::info!(
+        "Approval of extraction {} ({:?}) with notes: {:?}",
+        id,
+        outcome,
+        notes_for_log
+    );
+
+    let response = unsettled_response(id, &outcome).unwrap_or_else(|| VerifyResponse {
+        id,
+        status: "approved".to_string(),
+        message: "Extraction approved and queued for graph loading".to_string(),
+    });
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Reject action
+#[derive(Debug, Deserialize)]
+pub struct RejectAction {
+    /// Reason for rejection
+    pub reason: String,
+
+    /// Reviewer notes
+    pub notes: Option<String>,
+}
+
+/// Reject an extraction
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/{id}/reject",
+    tag = "verify",
+    params(
+        ("id" = Uuid, Path, description = "Extraction UUID")
+    ),
+    responses(
+        (status = 200, description = "Extraction rejected, or rejection recorded"),
+        (status = 400, description = "Already reviewed by the caller or not pending"),
+        (status = 404, description = "Extraction not found"),
+        (status = 409, description = "Locked to another reviewer (REVIEW_LOCKED)", body = crate::error::ApiError)
+    )
+)]
+pub async fn reject_extraction(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(action): Json<RejectAction>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let review_notes = format!(
+        "REJECTED: {}\n{}",
         action.reason,
         action.notes.unwrap_or_default()
     );
+    let outcome = decide(
+        &state,
+        &user,
+        id,
+        review::Decision::Reject,
+        None,
+        Some(review_notes),
+    )
+    .await?;
+
+    tracing::info!(
+        "Rejection of extraction {} ({:?}) with reason: {}",
+        id,
+        outcome,
+        action.reason
+    );
+
+    let response = unsettled_response(id, &outcome).unwrap_or_else(|| VerifyResponse {
+        id,
+        status: "rejected".to_string(),
+        message: format!("Extraction rejected: {}", action.reason),
+    });
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Adjudication of an extraction reviewers disagreed on
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdjudicateAction {
+    /// Approve (`true`) or reject (`false`) the extraction
+    pub approve: bool,
+
+    /// Optional correction applied when approving
+    pub correction: Option<ExtractedContent>,
+
+    /// Adjudicator notes
+    pub notes: Option<String>,
+}
+
+/// Decide an extraction reviewers disagreed on (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/{id}/adjudicate",
+    tag = "verify",
+    params(
+        ("id" = Uuid, Path, description = "Extraction UUID")
+    ),
+    request_body = AdjudicateAction,
+    responses(
+        (status = 200, description = "Extraction approved or rejected"),
+        (status = 400, description = "Extraction is not in adjudication"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Extraction not found")
+    )
+)]
+pub async fn adjudicate_extraction(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(action): Json<AdjudicateAction>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required for adjudication".to_string(),
+        ));
+    }
+
+    let mut tx = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to start transaction: {e}")))?;
+
+    let extraction: Option<(String, serde_json::Value, serde_json::Value)> = sqlx::query_as(
+        r#"
+        SELECT status::text, extracted_entities, extracted_relations
+        FROM extraction_queue
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch extraction: {e}")))?;
+
+    let (status, mut entities, mut relations) =
+        extraction.ok_or_else(|| AppError::NotFound(format!("Extraction {id} not found")))?;
+    if status != "adjudication" {
+        return Err(AppError::BadRequest(format!(
+            "Cannot adjudicate extraction in status: {status}"
+        )));
+    }
+
+    let status = if action.approve {
+        if let Some(correction) = action.correction {
+            apply_correction(&mut entities, &mut relations, correction);
+        }
+        "approved"
+    } else {
+        "rejected"
+    };
+    let review_notes = format!("ADJUDICATED: {}", action.notes.unwrap_or_default());
+
+    sqlx::query(
+        r#"
+        UPDATE extraction_queue
+        SET status = $1::verification_status,
+            reviewer_id = $2,
+            review_notes = $3,
+            reviewed_at = $4,
+            extracted_entities = $5,
+            extracted_relations = $6
+        WHERE id = $7
+        "#,
+    )
+    .bind(status)
+    .bind(user.user_id.to_string())
+    .bind(review_notes)
+    .bind(Utc::now())
+    .bind(entities)
+    .bind(relations)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to update extraction: {e}")))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to commit transaction: {e}")))?;
+
+    tracing::info!("Adjudicated extraction {}: {}", id, status);
+
+    let response = VerifyResponse {
+        id,
+        status: status.to_string(),
+        message: format!("Extraction {status} by adjudication"),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Query parameters for review agreement
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AgreementQuery {
+    /// Days of reviews counted
+    #[param(default = 30)]
+    pub days: Option<u32>,
+}
+
+/// Get agreement between reviewers of multiply reviewed extractions
+///
+/// Reports Cohen's kappa between the first two decisions on each
+/// extraction, with the number of extractions in adjudication.
+#[utoipa::path(
+    get,
+    path = "/api/v1/verify/agreement",
+    tag = "verify",
+    params(AgreementQuery),
+    responses(
+        (status = 200, description = "Inter-reviewer agreement", body = review::AgreementReport),
+        (status = 403, description = "Editor role required")
+    )
+)]
+pub async fn get_agreement(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<AgreementQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required for review agreement".to_string(),
+        ));
+    }
+
+    let days = params.days.unwrap_or(30).clamp(1, 365);
+    let report = review::agreement_report(&state, days).await?;
+
+    Ok((StatusCode::OK, Json(report)))
+}
+
+/// Manual assignment request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignRequest {
+    /// User ID of the reviewer (an active admin or editor)
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub reviewer_id: String,
+}
+
+/// Review lock held on an extraction
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClaimResponse {
+    pub id: Uuid,
+
+    /// Reviewer holding the lock
+    pub locked_by: String,
+
+    /// When the lock expires unless the item is claimed again or decided
+    pub lock_expires_at: String,
+}
+
+/// Assign unassigned pending extractions to reviewers (admin only)
+///
+/// Reviewers of the document's department take turns; extractions of a
+/// department without reviewers are shared by all reviewers.
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/assign",
+    tag = "verify",
+    responses(
+        (status = 200, description = "Extractions assigned", body = review::AssignmentReport),
+        (status = 403, description = "Admin role required")
+    )
+)]
+pub async fn auto_assign(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required to assign reviewers".to_string(),
+        ));
+    }
+
+    let report = review::assign_pending(&state).await?;
+    tracing::info!("Assigned {} extractions", report.assigned);
+
+    Ok((StatusCode::OK, Json(report)))
+}
+
+/// Assign an extraction to a reviewer (admin only)
+#[utoipa::path(
+    put,
+    path = "/api/v1/verify/{id}/assign",
+    tag = "verify",
+    params(
+        ("id" = Uuid, Path, description = "Extraction UUID")
+    ),
+    request_body = AssignRequest,
+    responses(
+        (status = 200, description = "Extraction assigned"),
+        (status = 400, description = "Not a reviewer or extraction already decided"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Extraction not found")
+    )
+)]
+pub async fn assign_extraction(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AssignRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required to assign reviewers".to_string(),
+        ));
+    }
+
+    review::assign_to(&state, id, &request.reviewer_id).await?;
+    tracing::info!("Assigned extraction {} to {}", id, request.reviewer_id);
+
+    let response = VerifyResponse {
+        id,
+        status: "assigned".to_string(),
+        message: format!("Extraction assigned to {}", request.reviewer_id),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Claim an extraction for review
+///
+/// Locks the extraction to the caller (`in_review`) until the lock
+/// expires; claiming again extends the lock.
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/{id}/claim",
+    tag = "verify",
+    params(
+        ("id" = Uuid, Path, description = "Extraction UUID")
+    ),
+    responses(
+        (status = 200, description = "Extraction locked to the caller", body = ClaimResponse),
+        (status = 404, description = "Extraction not found"),
+        (status = 409, description = "Locked to another reviewer (REVIEW_LOCKED)", body = crate::error::ApiError)
+    )
+)]
+pub async fn claim_extraction(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    let reviewer = user.user_id.to_string();
+    let expires_at = review::claim(&state, id, &reviewer, state.review.lock_ttl).await?;
+
+    let response = ClaimResponse {
+        id,
+        locked_by: reviewer,
+        lock_expires_at: expires_at.to_rfc3339(),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Release the review lock on an extraction
+///
+/// The extraction returns to the pending queue. Admins can release any
+/// reviewer's lock.
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/{id}/release",
+    tag = "verify",
+    params(
+        ("id" = Uuid, Path, description = "Extraction UUID")
+    ),
+    responses(
+        (status = 200, description = "Lock released"),
+        (status = 400, description = "Extraction is not in review"),
+        (status = 404, description = "Extraction not found"),
+        (status = 409, description = "Locked to another reviewer (REVIEW_LOCKED)", body = crate::error::ApiError)
+    )
+)]
+pub async fn release_extraction(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    review::release(&state, id, &user.user_id.to_string(), user.is_admin()).await?;
+
+    let response = VerifyResponse {
+        id,
+        status: "pending".to_string(),
+        message: "Review lock released".to_string(),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Query parameters for reviewer statistics
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ReviewerStatsQuery {
+    /// Days of decisions counted
+    #[param(default = 30)]
+    pub days: Option<u32>,
+}
+
+/// Get workload and throughput per reviewer (editor or admin)
+#[utoipa::path(
+    get,
+    path = "/api/v1/verify/reviewers/stats",
+    tag = "verify",
+    params(ReviewerStatsQuery),
+    responses(
+        (status = 200, description = "Reviewer statistics", body = Vec<review::ReviewerStats>),
+        (status = 403, description = "Editor role required")
+    )
+)]
+pub async fn get_reviewer_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<ReviewerStatsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required for reviewer statistics".to_string(),
+        ));
+    }
+
+    let days = params.days.unwrap_or(30).clamp(1, 365);
+    let stats = review::reviewer_stats(&state, days).await?;
+
+    Ok((StatusCode::OK, Json(stats)))
+}
+
+/// Auto-approve pending extractions and sample auto-approved ones for
+/// audit now (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/audit/run",
+    tag = "verify",
+    responses(
+        (status = 200, description = "Run finished", body = quality_audit::AuditRun),
+        (status = 403, description = "Admin role required")
+    )
+)]
+pub async fn run_audit(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required to run auto-approval".to_string(),
+        ));
+    }
+
+    let report = quality_audit::run(&state, &state.quality_audit).await?;
+    tracing::info!(
+        "Auto-approved {} extractions, sampled {} for audit",
+        report.auto_approved,
+        report.sampled
+    );
+
+    Ok((StatusCode::OK, Json(report)))
+}
+
+/// Query parameters for the audit queue
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuditListQuery {
+    /// Page number (1-indexed)
+    #[param(default = 1)]
+    pub page: Option<usize>,
+
+    /// Items per page
+    #[param(default = 20)]
+    pub page_size: Option<usize>,
+}
+
+/// Auto-approved extraction waiting for audit
+#[derive(Debug, Serialize, ToSchema, sqlx::FromRow)]
+pub struct AuditItem {
+    pub extraction_id: Uuid,
+    pub document_id: Uuid,
+    pub document_title: String,
+    pub confidence: Option<f32>,
+    pub extracted_entities: serde_json::Value,
+    pub extracted_relations: serde_json::Value,
+    pub source_text: Option<String>,
+    pub sampled_at: DateTime<Utc>,
+}
+
+/// Audit queue page
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditListResponse {
+    /// Extractions waiting for audit
+    pub total: i64,
+    pub items: Vec<AuditItem>,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+/// List sampled auto-approved extractions waiting for audit (editor or
+/// admin)
+#[utoipa::path(
+    get,
+    path = "/api/v1/verify/audit",
+    tag = "verify",
+    params(AuditListQuery),
+    responses(
+        (status = 200, description = "Audit queue", body = AuditListResponse),
+        (status = 403, description = "Editor role required")
+    )
+)]
+pub async fn list_audit(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<AuditListQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required for audits".to_string(),
+        ));
+    }
+
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = params.page_size.unwrap_or(20).clamp(1, 100);
 
-    let result = sqlx::query(
+    let items: Vec<AuditItem> = sqlx::query_as(
         r#"
-        UPDATE extraction_queue
-        SET status = 'rejected',
-            reviewer_id = $1,
-            review_notes = $2,
-            reviewed_at = $3
-        WHERE id = $4
+        SELECT
+            a.extraction_id,
+            eq.document_id,
+            d.title AS document_title,
+            a.confidence,
+            eq.extracted_entities,
+            eq.extracted_relations,
+            eq.source_text,
+            a.sampled_at
+        FROM extraction_audits a
+        JOIN extraction_queue eq ON a.extraction_id = eq.id
+        JOIN documents d ON eq.document_id = d.id
+        WHERE a.status = 'pending'
+        ORDER BY a.sampled_at, a.id
+        LIMIT $1 OFFSET $2
         "#,
     )
-    .bind(user.user_id.to_string())
-    .bind(review_notes)
-    .bind(now)
-    .bind(id)
-    .execute(&mut *tx)
+    .bind(page_size as i64)
+    .bind(((page - 1) * page_size) as i64)
+    .fetch_all(&state.db_pool)
     .await
-    .map_err(|e| AppError::Internal(format!("Failed to update extraction: {e}")))?;
+    .map_err(|e| AppError::Internal(format!("Failed to fetch audit queue: {e}")))?;
 
-    if result.rows_affected() == 0 {
-        tx.rollback().await.ok();
-        return Err(AppError::NotFound(format!("Extraction {id} not found")));
-    }
+    let total: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM extraction_audits WHERE status = 'pending'")
+            .fetch_one(&state.db_pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to count audit queue: {e}")))?;
 
-    // Commit transaction
-    tx.commit()
-        .await
-        .map_err(|e| AppError::Internal(format!("Failed to commit transaction: {e}")))?;
+    let response = AuditListResponse {
+        total,
+        items,
+        page,
+        page_size,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
 
-    tracing::info!("Rejected extraction {} with reason: {}", id, action.reason);
+/// Auditor's verdict on an auto-approved extraction
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AuditVerdict {
+    /// Whether the auto-approval was correct
+    pub correct: bool,
 
-    let response = VerifyResponse {
+    /// Auditor notes
+    pub notes: Option<String>,
+}
+
+/// Result of an audit
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditResponse {
+    pub extraction_id: Uuid,
+
+    /// `correct` or `incorrect` (incorrect extractions are rejected)
+    #[schema(example = "incorrect")]
+    pub status: String,
+
+    /// New auto-approve threshold, if the audit raised it
+    pub threshold_raised_to: Option<f32>,
+}
+
+/// Record the audit of a sampled auto-approved extraction (editor or
+/// admin)
+///
+/// An incorrect extraction is rejected. When the error rate of the audits
+/// since the last threshold change exceeds `AUDIT_MAX_ERROR_RATE`, the
+/// auto-approve threshold is raised.
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/audit/{id}",
+    tag = "verify",
+    params(
+        ("id" = Uuid, Path, description = "Extraction UUID")
+    ),
+    request_body = AuditVerdict,
+    responses(
+        (status = 200, description = "Audit recorded", body = AuditResponse),
+        (status = 400, description = "Already audited"),
+        (status = 403, description = "Editor role required"),
+        (status = 404, description = "Extraction not in the audit queue")
+    )
+)]
+pub async fn record_audit(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(verdict): Json<AuditVerdict>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required for audits".to_string(),
+        ));
+    }
+
+    let raised = quality_audit::record_audit(
+        &state,
+        &state.quality_audit,
         id,
-        status: "rejected".to_string(),
-        message: format!("Extraction rejected: {}", action.reason),
+        &user.user_id.to_string(),
+        verdict.correct,
+        verdict.notes.as_deref(),
+    )
+    .await?;
+
+    let response = AuditResponse {
+        extraction_id: id,
+        status: if verdict.correct {
+            "correct"
+        } else {
+            "incorrect"
+        }
+        .to_string(),
+        threshold_raised_to: raised,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Get auto-approval audit statistics (editor or admin)
+///
+/// Estimates the error rate of auto-approval from the audited sample, with
+/// a 95% confidence interval and the threshold history.
+#[utoipa::path(
+    get,
+    path = "/api/v1/verify/audit/stats",
+    tag = "verify",
+    responses(
+        (status = 200, description = "Audit statistics", body = quality_audit::AuditStats),
+        (status = 403, description = "Editor role required")
+    )
+)]
+pub async fn get_audit_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required for audits".to_string(),
+        ));
+    }
+
+    let stats = quality_audit::audit_stats(&state, &state.quality_audit).await?;
+
+    Ok((StatusCode::OK, Json(stats)))
+}
+
+/// Auto-approve threshold update
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ThresholdUpdate {
+    /// Confidence at which pending extractions are auto-approved (0-1)
+    #[schema(example = 0.95)]
+    pub threshold: f32,
+
+    /// Why the threshold changes
+    pub reason: Option<String>,
+}
+
+/// Set the auto-approve threshold (admin only)
+#[utoipa::path(
+    put,
+    path = "/api/v1/verify/audit/threshold",
+    tag = "verify",
+    request_body = ThresholdUpdate,
+    responses(
+        (status = 200, description = "Threshold set"),
+        (status = 400, description = "Threshold outside 0-1"),
+        (status = 403, description = "Admin role required")
+    )
+)]
+pub async fn set_auto_approve_threshold(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(update): Json<ThresholdUpdate>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required to set the auto-approve threshold".to_string(),
+        ));
+    }
+    if !(0.0..=1.0).contains(&update.threshold) {
+        return Err(AppError::BadRequest(format!(
+            "Threshold must be between 0 and 1: {}",
+            update.threshold
+        )));
+    }
+
+    let reason = update.reason.unwrap_or_else(|| "Set by admin".to_string());
+    quality_audit::set_threshold(
+        &state,
+        update.threshold,
+        &reason,
+        Some(&user.user_id.to_string()),
+    )
+    .await?;
+    tracing::info!(
+        "Auto-approve threshold set to {:.2} by {}",
+        update.threshold,
+        user.email
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({ "threshold": update.threshold, "reason": reason })),
+    ))
+}
+
+/// Auto-approval policy in effect with the global threshold
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AutoApprovePolicyResponse {
+    /// Threshold for items no rule matches
+    pub default_threshold: f32,
+    pub policy: auto_approve::PolicyVersion,
+}
+
+/// Get the auto-approval policy (editor or admin)
+#[utoipa::path(
+    get,
+    path = "/api/v1/verify/auto-approve/policy",
+    tag = "verify",
+    responses(
+        (status = 200, description = "Policy in effect", body = AutoApprovePolicyResponse),
+        (status = 403, description = "Editor role required")
+    )
+)]
+pub async fn get_auto_approve_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_editor_or_higher() {
+        return Err(AppError::Forbidden(
+            "Editor role required to view the auto-approval policy".to_string(),
+        ));
+    }
+
+    let response = AutoApprovePolicyResponse {
+        default_threshold: quality_audit::current_threshold(&state, &state.quality_audit).await?,
+        policy: auto_approve::current_policy(&state).await?,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Replace the auto-approval policy (admin only)
+///
+/// Rules are matched in order; the first rule matching an extracted item
+/// gives the confidence it needs.
+#[utoipa::path(
+    put,
+    path = "/api/v1/verify/auto-approve/policy",
+    tag = "verify",
+    request_body = auto_approve::ApprovalPolicy,
+    responses(
+        (status = 200, description = "Policy set", body = AutoApprovePolicyResponse),
+        (status = 400, description = "Invalid rule"),
+        (status = 403, description = "Admin role required")
+    )
+)]
+pub async fn set_auto_approve_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(policy): Json<auto_approve::ApprovalPolicy>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required to set the auto-approval policy".to_string(),
+        ));
+    }
+
+    auto_approve::set_policy(&state, &policy, &user.user_id.to_string()).await?;
+    tracing::info!(
+        "Auto-approval policy set to {} rules by {}",
+        policy.rules.len(),
+        user.email
+    );
+
+    let response = AutoApprovePolicyResponse {
+        default_threshold: quality_audit::current_threshold(&state, &state.quality_audit).await?,
+        policy: auto_approve::current_policy(&state).await?,
     };
 
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Proposed auto-approval policy to simulate
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SimulateRequest {
+    pub rules: Vec<auto_approve::ApprovalRule>,
+
+    /// Days of extractions to replay (default 7, at most 90)
+    #[schema(example = 7)]
+    pub days: Option<u32>,
+}
+
+/// Dry-run a proposed auto-approval policy (admin only)
+///
+/// Shows how the proposed rules would have classified the extractions of
+/// the last `days` days compared with the policy in effect. Nothing is
+/// changed.
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify/auto-approve/simulate",
+    tag = "verify",
+    request_body = SimulateRequest,
+    responses(
+        (status = 200, description = "Simulation result", body = auto_approve::SimulationReport),
+        (status = 400, description = "Invalid rule"),
+        (status = 403, description = "Admin role required")
+    )
+)]
+pub async fn simulate_auto_approve_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<SimulateRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state.increment_requests();
+
+    if !user.is_admin() {
+        return Err(AppError::Forbidden(
+            "Admin role required to simulate the auto-approval policy".to_string(),
+        ));
+    }
+
+    let proposed = auto_approve::ApprovalPolicy {
+        rules: request.rules,
+    };
+    let days = request.days.unwrap_or(7).clamp(1, 90);
+    let threshold = quality_audit::current_threshold(&state, &state.quality_audit).await?;
+    let report = auto_approve::simulate(&state, &proposed, threshold, days).await?;
+
+    Ok((StatusCode::OK, Json(report)))
+}
+
 /// Verification statistics
 #[derive(Debug, Serialize)]
 pub struct VerifyStats {
     pub total_pending: u32,
     pub total_approved: u32,
     pub total_rejected: u32,
+    /// Extractions waiting for adjudication after reviewers disagreed
+    pub total_adjudication: u32,
     pub entities: EntityStats,
     pub relations: RelationStats,
 }
@@ -604,12 +2180,14 @@ pub async fn get_stats(State(state): State<Arc<AppState>>) -> Result<impl IntoRe
     let mut total_pending = 0;
     let mut total_approved = 0;
     let mut total_rejected = 0;
+    let mut total_adjudication = 0;
 
     for stat in status_counts {
         match stat.status.as_str() {
             "pending" | "in_review" => total_pending += stat.count as u32,
             "approved" => total_approved += stat.count as u32,
             "rejected" => total_rejected += stat.count as u32,
+            "adjudication" => total_adjudication += stat.count as u32,
             _ => {}
         }
     }
@@ -697,13 +2275,13 @@ pub async fn get_stats(State(state): State<Arc<AppState>>) -> Result<impl IntoRe
         0.0
     };
 
-    // Auto-approved count (confidence >= 0.9)
+    // Auto-approved count (approved without review)
     let entity_auto_approved: i64 = sqlx::query_scalar(
         r#"
         SELECT COUNT(*)
         FROM extraction_queue
         WHERE status = 'approved'
-            AND confidence_score >= 0.9
+            AND auto_approved
             AND jsonb_array_length(extracted_entities) > 0
         "#,
     )
@@ -716,7 +2294,7 @@ pub async fn get_stats(State(state): State<Arc<AppState>>) -> Result<impl IntoRe
         SELECT COUNT(*)
         FROM extraction_queue
         WHERE status = 'approved'
-            AND confidence_score >= 0.9
+            AND auto_approved
             AND jsonb_array_length(extracted_relations) > 0
         "#,
     )
@@ -728,6 +2306,7 @@ pub async fn get_stats(State(state): State<Arc<AppState>>) -> Result<impl IntoRe
         total_pending,
         total_approved,
         total_rejected,
+        total_adjudication,
         entities: EntityStats {
             pending: entity_pending,
             approved: entity_approved,
@@ -746,3 +2325,79 @@ pub async fn get_stats(State(state): State<Arc<AppState>>) -> Result<impl IntoRe
 
     Ok((StatusCode::OK, Json(stats)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_window() {
+        let text =
+            "제15조 휴가\n연차휴가는 팀장의 사전 승인을 받아야 한다.\n병가는 진단서가 필요하다.";
+        let passage = "연차휴가는 팀장의 사전 승인을 받아야 한다.";
+
+        let (before, after) = context_window(text, passage, 5).unwrap();
+        assert_eq!(before, "조 휴가\n");
+        assert_eq!(after, "\n병가는 ");
+
+        let (before, after) = context_window(text, passage, 1000).unwrap();
+        assert_eq!(before, "제15조 휴가\n");
+        assert_eq!(after, "\n병가는 진단서가 필요하다.");
+
+        assert!(context_window(text, "없는 문장", 10).is_none());
+    }
+
+    #[test]
+    fn test_neighboring_triples_share_an_entity() {
+        let item = vec![ExtractedContent::Relation {
+            subject: "연차휴가".to_string(),
+            predicate: "requires".to_string(),
+            object: "팀장 승인".to_string(),
+        }];
+        let terms = mentioned_terms(&[], &item);
+        let approved_id = Uuid::new_v4();
+        let approved = vec![(
+            approved_id,
+            serde_json::json!([
+                {"subject": "연차휴가", "predicate": "hasDuration", "object": "15일"},
+                {"subject": "병가", "predicate": "requires", "object": "진단서"},
+                {"text": "팀장", "entity_type": "Role", "start": 0, "end": 6}
+            ]),
+        )];
+
+        let triples = neighboring_triples(&terms, approved);
+
+        assert_eq!(triples.len(), 1);
+        assert_eq!(triples[0].extraction_id, approved_id);
+        assert_eq!(triples[0].object, "15일");
+    }
+
+    #[test]
+    fn test_entity_offsets_from_context() {
+        let context = "연차휴가는 팀장의 사전 승인을 받아야 한다.";
+        let start = context.find("팀장").unwrap();
+        let stored = serde_json::json!({
+            "text": "팀장",
+            "entity_type": "Manager",
+            "start": start,
+            "end": start + "팀장".len(),
+        });
+
+        let content = serde_json::from_value::<ExtractedContent>(stored)
+            .unwrap()
+            .with_offsets(context);
+        let ExtractedContent::Entity {
+            char_start,
+            char_end,
+            utf16_start,
+            utf16_end,
+            ..
+        } = content
+        else {
+            panic!("expected entity");
+        };
+
+        assert_eq!((char_start, char_end), (Some(6), Some(8)));
+        assert_eq!((utf16_start, utf16_end), (Some(6), Some(8)));
+    }
+}
@@ -5,17 +5,47 @@
 //! - Document management
 //! - Knowledge graph operations
 //! - HITL verification
+//! - GraphQL access to the same resources
+//! - gRPC Ingest/Query/Graph services (feature `grpc`)
 //! - Authentication and authorization
 //!
 //! Author: hephaex@gmail.com
 
+pub mod access_requests;
 pub mod audit;
 pub mod auth;
+pub mod auto_approve;
+pub mod classification;
+pub mod compare;
+pub mod config_check;
+pub mod content_gaps;
+pub mod dedup;
+pub mod embedding_migration;
 pub mod error;
+pub mod export;
+pub mod faq;
+pub mod feedback;
+pub mod forms;
+pub mod freshness;
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod handlers;
+pub mod lineage;
+pub mod logging;
 pub mod middleware;
+pub mod notification_center;
+pub mod notifications;
+pub mod quality_audit;
+pub mod retention;
+pub mod review;
 pub mod routes;
+pub mod saved_searches;
+pub mod sessions;
+pub mod share;
+pub mod slo;
 pub mod state;
+pub mod users;
 
 use axum::{middleware as axum_middleware, Router};
 use state::AppState;
@@ -34,18 +64,93 @@ use utoipa_swagger_ui::SwaggerUi;
         handlers::auth::refresh_handler,
         handlers::auth::logout_handler,
         handlers::auth::me_handler,
+        handlers::auth::password_reset_handler,
+        handlers::auth::list_sessions_handler,
+        handlers::auth::revoke_session_handler,
+        handlers::auth::introspect_handler,
         handlers::query::query_handler,
+        handlers::query::estimate_query,
+        handlers::query::replay_query,
         handlers::query::query_stream_handler,
+        handlers::query::get_query_suggestions,
+        handlers::query::submit_feedback,
         handlers::documents::list_documents,
         handlers::documents::get_document,
         handlers::documents::upload_document,
+        handlers::documents::ingest_tabular,
+        handlers::documents::ingest_form,
         handlers::documents::delete_document,
+        handlers::documents::restore_document,
+        handlers::documents::reprocess_document,
+        handlers::documents::download_original,
+        handlers::documents::share_document,
+        handlers::documents::open_shared_document,
+        handlers::documents::request_document_access,
+        handlers::documents::list_access_requests,
+        handlers::documents::list_pending_access_requests,
+        handlers::documents::approve_access_request,
+        handlers::documents::deny_access_request,
+        handlers::documents::get_document_lineage,
+        handlers::documents::get_ingest_report,
+        handlers::documents::get_rescan_report,
+        handlers::documents::compare_documents,
+        handlers::export::export_document,
+        handlers::export::get_export_job,
+        handlers::export::download_export,
+        handlers::faq::list_faq,
+        handlers::chunks::list_document_chunks,
+        handlers::chunks::check_document_integrity,
+        handlers::chunks::similar_chunks,
+        handlers::chunks::chunk_duplicates,
         handlers::graph::list_entities,
         handlers::graph::get_entity,
+        handlers::graph::get_entity_sections,
+        handlers::graph::get_entity_timeline,
+        handlers::graph::get_triple_provenance,
+        handlers::graph::get_graph_analytics,
+        handlers::graph::visualize_graph,
         handlers::graph::search_graph,
+        handlers::graph::sparql_query,
+        handlers::graph::list_snapshots,
+        handlers::graph::create_snapshot,
+        handlers::graph::restore_snapshot,
+        handlers::graph::delete_snapshot,
+        handlers::graph::run_inference,
+        handlers::graph::create_triple,
+        handlers::graph::list_conflicts,
+        handlers::graph::resolve_conflict,
         handlers::verify::list_pending,
         handlers::verify::approve_extraction,
         handlers::verify::reject_extraction,
+        handlers::verify::get_extraction,
+        handlers::verify::next_extraction,
+        handlers::verify::auto_assign,
+        handlers::verify::assign_extraction,
+        handlers::verify::claim_extraction,
+        handlers::verify::release_extraction,
+        handlers::verify::get_reviewer_stats,
+        handlers::verify::adjudicate_extraction,
+        handlers::verify::get_agreement,
+        handlers::verify::run_audit,
+        handlers::verify::list_audit,
+        handlers::verify::record_audit,
+        handlers::verify::get_audit_stats,
+        handlers::verify::set_auto_approve_threshold,
+        handlers::verify::get_auto_approve_policy,
+        handlers::verify::set_auto_approve_policy,
+        handlers::verify::simulate_auto_approve_policy,
+        handlers::notifications::list_notifications,
+        handlers::notifications::unread_count,
+        handlers::notifications::mark_read,
+        handlers::notifications::mark_all_read,
+        handlers::notifications::get_preferences,
+        handlers::notifications::update_preferences,
+        handlers::saved_searches::list_saved_searches,
+        handlers::saved_searches::create_saved_search,
+        handlers::saved_searches::get_saved_search,
+        handlers::saved_searches::update_saved_search,
+        handlers::saved_searches::delete_saved_search,
+        handlers::saved_searches::list_saved_search_matches,
         handlers::health::health_check,
         handlers::health::readiness_check,
     ),
@@ -59,19 +164,125 @@ use utoipa_swagger_ui::SwaggerUi;
             auth::UserInfo,
             handlers::auth::RegisterResponse,
             handlers::auth::LogoutResponse,
+            handlers::auth::PasswordResetRequest,
+            handlers::auth::PasswordResetResponse,
+            handlers::auth::IntrospectRequest,
+            sessions::Session,
+            sessions::TokenIntrospection,
             handlers::query::QueryRequest,
+            handlers::query::ReplayRequest,
+            handlers::query::EstimateResponse,
             handlers::query::QueryResponse,
             handlers::query::Citation,
+            handlers::query::SuggestionsResponse,
+            handlers::query::FeedbackRequest,
+            handlers::query::FeedbackResponse,
+            handlers::query::QueryMode,
+            handlers::query::Passage,
+            handlers::query::PassageHighlight,
             handlers::documents::DocumentInfo,
             handlers::documents::DocumentListResponse,
             handlers::documents::UploadDocumentRequest,
+            handlers::documents::TabularIngestRequest,
+            handlers::documents::TabularIngestResponse,
+            handlers::documents::FormIngestRequest,
+            handlers::documents::FormIngestResponse,
+            handlers::documents::TableRowError,
+            handlers::documents::RestoreDocumentResponse,
+            handlers::documents::ReprocessDocumentResponse,
+            handlers::documents::ShareDocumentRequest,
+            handlers::documents::ShareDocumentResponse,
+            share::ShareScope,
+            handlers::documents::AccessRequestBody,
+            handlers::documents::AccessDecisionBody,
+            access_requests::AccessRequest,
+            access_requests::AccessRequestStatus,
+            handlers::documents::CompareDocumentsRequest,
+            handlers::documents::CompareDocumentsResponse,
+            handlers::documents::ComparedDocument,
+            compare::SectionDiff,
+            compare::SectionSide,
+            compare::SectionChange,
+            compare::TextChange,
+            compare::DiffOp,
+            compare::CompareStats,
+            compare::CompareCitation,
+            compare::DocumentVersion,
+            lineage::DocumentLineage,
+            lineage::ChunkLineage,
+            lineage::ChunkerLineage,
+            lineage::ParserLineage,
+            export::ExportJobInfo,
+            export::ExportJobStatus,
+            export::ExportPart,
+            handlers::faq::FaqItem,
+            handlers::faq::FaqListResponse,
+            handlers::chunks::ChunkCopy,
+            handlers::chunks::ChunkDuplicatesResponse,
+            handlers::chunks::ChunkInfo,
+            handlers::chunks::ChunkListResponse,
+            handlers::chunks::EmbeddingStatus,
+            handlers::chunks::IntegrityReport,
+            handlers::chunks::SimilarChunk,
+            handlers::chunks::SimilarChunksResponse,
             handlers::graph::EntityInfo,
             handlers::graph::RelationInfo,
+            handlers::graph::CitingSection,
+            handlers::graph::ProvenanceInfo,
+            handlers::graph::ConfidencePoint,
+            handlers::graph::TripleProvenanceResponse,
             handlers::graph::GraphSearchRequest,
             handlers::graph::GraphSearchResponse,
+            handlers::graph::SparqlRequest,
+            handlers::graph::TimelineEntry,
+            handlers::graph::EntityTimelineResponse,
+            handlers::graph::GraphSnapshotInfo,
+            handlers::graph::CreateSnapshotRequest,
+            handlers::graph::CreateTripleRequest,
+            handlers::graph::ResolveConflictRequest,
             handlers::verify::PendingExtraction,
             handlers::verify::VerifyAction,
+            handlers::verify::ExtractionDetail,
+            handlers::verify::ContextWindow,
+            handlers::verify::ReviewDocument,
+            handlers::verify::ApprovedTriple,
+            handlers::verify::NextExtractionResponse,
+            handlers::verify::AssignRequest,
+            handlers::verify::ClaimResponse,
+            review::AssignmentReport,
+            review::ReviewerStats,
+            review::Agreement,
+            review::AgreementReport,
+            handlers::verify::AdjudicateAction,
+            handlers::verify::ReviewRecord,
+            handlers::verify::AuditItem,
+            handlers::verify::AuditListResponse,
+            handlers::verify::AuditVerdict,
+            handlers::verify::AuditResponse,
+            handlers::verify::ThresholdUpdate,
+            quality_audit::AuditRun,
+            quality_audit::AuditStats,
+            quality_audit::ThresholdChange,
+            handlers::verify::AutoApprovePolicyResponse,
+            handlers::verify::SimulateRequest,
+            auto_approve::ApprovalRule,
+            auto_approve::ApprovalPolicy,
+            auto_approve::PolicyVersion,
+            auto_approve::Reclassified,
+            auto_approve::SimulationReport,
+            handlers::notifications::NotificationListResponse,
+            handlers::notifications::UnreadCountResponse,
+            handlers::notifications::MarkAllReadResponse,
+            notification_center::UserNotification,
+            notifications::NotificationPreferences,
+            notifications::NotificationEvent,
+            handlers::saved_searches::SavedSearchRequest,
+            handlers::saved_searches::SavedSearchListResponse,
+            handlers::saved_searches::SavedSearchMatchesResponse,
+            saved_searches::SavedSearch,
+            saved_searches::SavedSearchMatch,
             error::ApiError,
+            error::ErrorCode,
         )
     ),
     tags(
@@ -80,6 +291,9 @@ use utoipa_swagger_ui::SwaggerUi;
         (name = "documents", description = "Document management"),
         (name = "graph", description = "Knowledge graph operations"),
         (name = "verify", description = "HITL verification"),
+        (name = "faq", description = "Frequently asked questions"),
+        (name = "notifications", description = "In-app notifications and preferences"),
+        (name = "saved-searches", description = "Saved searches and alerts on new matching documents"),
         (name = "health", description = "Health checks"),
     ),
     modifiers(&SecurityAddon),
@@ -163,6 +377,7 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .layer(axum_middleware::from_fn(
             middleware::security_headers_middleware,
         ))
+        .layer(axum_middleware::from_fn(middleware::request_id_middleware))
         .layer(TraceLayer::new_for_http())
         .layer(cors)
         .with_state(state)
@@ -203,3 +418,28 @@ pub fn create_test_state() -> Arc<AppState> {
 pub fn create_router_for_testing() -> Router {
     create_router(create_test_state())
 }
+
+/// Create a router whose RAG pipeline runs on in-memory fakes
+///
+/// Vector search reads `vector`, graph search finds nothing and `llm`
+/// answers every prompt, so query endpoints can be tested without Qdrant,
+/// SurrealDB or an LLM endpoint.
+#[cfg(feature = "test-utils")]
+pub async fn create_router_with_rag(
+    vector: otl_core::testing::InMemorySearchBackend,
+    llm: otl_core::testing::MockLlmClient,
+) -> Router {
+    use otl_core::testing::InMemorySearchBackend;
+    use otl_core::SearchResultType;
+
+    let state = create_test_state();
+    let rag = otl_rag::HybridRagOrchestrator::new(
+        Arc::new(vector),
+        Arc::new(InMemorySearchBackend::new("graph", SearchResultType::Graph)),
+        Arc::new(llm),
+        otl_rag::RagConfig::default(),
+    )
+    .with_feedback(state.feedback.clone());
+    *state.rag.write().await = Some(Arc::new(rag));
+    create_router(state)
+}
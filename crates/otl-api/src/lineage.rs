@@ -0,0 +1,322 @@
+//! Document lineage
+//!
+//! Records which pipeline components produced a document's chunks: the
+//! parser and its version, the OCR engine (if any), the chunker settings and
+//! their hash, the embedding model and the extraction model. Lineage is
+//! written when a document is ingested and served by
+//! `GET /api/v1/documents/:id/lineage`, so a chunk can be traced back to the
+//! exact configuration that created it.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Version of the ingestion pipeline (this crate)
+pub const PIPELINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Parser that turned the uploaded bytes into text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ParserLineage {
+    /// Parser name
+    #[schema(example = "pdf-extract")]
+    pub name: String,
+    /// Parser library version
+    #[schema(example = "0.7")]
+    pub version: String,
+}
+
+impl ParserLineage {
+    /// Parser used by the upload path for a file type
+    ///
+    /// Versions follow the workspace dependency requirements.
+    pub fn for_file_type(file_type: &str) -> Self {
+        let (name, version) = match file_type.to_lowercase().as_str() {
+            "pdf" => ("pdf-extract", "0.7"),
+            "docx" => ("docx-rs", "0.4"),
+            _ => ("utf8-text", PIPELINE_VERSION),
+        };
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+        }
+    }
+}
+
+/// Chunker settings a document was split with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ChunkerLineage {
+    pub chunk_size: usize,
+    pub overlap: usize,
+    pub min_chunk_size: usize,
+    pub respect_sections: bool,
+    pub respect_paragraphs: bool,
+    /// Stable hash of the settings above; equal hashes mean equal chunking
+    #[schema(example = "5f0c2e1a9b7d3c44")]
+    pub config_hash: String,
+}
+
+impl From<&otl_parser::ChunkConfig> for ChunkerLineage {
+    fn from(config: &otl_parser::ChunkConfig) -> Self {
+        let canonical = format!(
+            "chunk_size={};overlap={};min_chunk_size={};respect_sections={};respect_paragraphs={}",
+            config.chunk_size,
+            config.overlap,
+            config.min_chunk_size,
+            config.respect_sections,
+            config.respect_paragraphs
+        );
+        let mut config_hash = sha256_hex(&canonical);
+        config_hash.truncate(16);
+
+        Self {
+            chunk_size: config.chunk_size,
+            overlap: config.overlap,
+            min_chunk_size: config.min_chunk_size,
+            respect_sections: config.respect_sections,
+            respect_paragraphs: config.respect_paragraphs,
+            config_hash,
+        }
+    }
+}
+
+impl From<&ChunkerLineage> for otl_parser::ChunkConfig {
+    fn from(lineage: &ChunkerLineage) -> Self {
+        Self {
+            chunk_size: lineage.chunk_size,
+            overlap: lineage.overlap,
+            min_chunk_size: lineage.min_chunk_size,
+            respect_sections: lineage.respect_sections,
+            respect_paragraphs: lineage.respect_paragraphs,
+        }
+    }
+}
+
+/// Lineage of one chunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ChunkLineage {
+    pub chunk_index: u32,
+    /// SHA-256 of the chunk text
+    pub content_hash: String,
+    /// Vector store point, if the chunk was indexed
+    pub vector_id: Option<String>,
+    /// Model that embedded the chunk, if it was indexed
+    pub embedding_model: Option<String>,
+    /// Hash of the chunker settings that produced the chunk
+    pub chunker_config_hash: String,
+}
+
+/// Lineage of a document and its chunks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DocumentLineage {
+    pub document_id: Uuid,
+    /// Ingestion pipeline version
+    #[schema(example = "0.1.0")]
+    pub pipeline_version: String,
+    pub parser: ParserLineage,
+    /// OCR engine used for scanned pages (`None` when no OCR ran)
+    pub ocr_engine: Option<String>,
+    pub chunker: ChunkerLineage,
+    /// Embedding model (`None` when the document was not indexed)
+    #[schema(example = "text-embedding-3-small")]
+    pub embedding_model: Option<String>,
+    /// Model used for entity and relation extraction (`None` when no
+    /// extraction ran)
+    pub extraction_model: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+    pub chunks: Vec<ChunkLineage>,
+}
+
+impl DocumentLineage {
+    /// Lineage of a freshly parsed and chunked document
+    ///
+    /// Chunk embedding details are filled in by [`Self::with_indexed_chunks`].
+    pub fn new(
+        document_id: Uuid,
+        parser: ParserLineage,
+        chunk_config: &otl_parser::ChunkConfig,
+        chunks: &[String],
+    ) -> Self {
+        let chunker = ChunkerLineage::from(chunk_config);
+        let chunks = chunks
+            .iter()
+            .enumerate()
+            .map(|(index, text)| ChunkLineage {
+                chunk_index: index as u32,
+                content_hash: otl_core::integrity::content_hash(text),
+                vector_id: None,
+                embedding_model: None,
+                chunker_config_hash: chunker.config_hash.clone(),
+            })
+            .collect();
+
+        Self {
+            document_id,
+            pipeline_version: PIPELINE_VERSION.to_string(),
+            parser,
+            ocr_engine: None,
+            chunker,
+            embedding_model: None,
+            extraction_model: None,
+            recorded_at: Utc::now(),
+            chunks,
+        }
+    }
+
+    pub fn with_ocr_engine(mut self, engine: impl Into<String>) -> Self {
+        self.ocr_engine = Some(engine.into());
+        self
+    }
+
+    pub fn with_extraction_model(mut self, model: impl Into<String>) -> Self {
+        self.extraction_model = Some(model.into());
+        self
+    }
+
+    /// Record the chunks indexed with `embedding_model`, keyed by chunk index
+    pub fn with_indexed_chunks(
+        mut self,
+        embedding_model: &str,
+        vector_ids: &std::collections::HashMap<usize, String>,
+    ) -> Self {
+        for (index, chunk) in self.chunks.iter_mut().enumerate() {
+            if let Some(vector_id) = vector_ids.get(&index) {
+                chunk.vector_id = Some(vector_id.clone());
+                chunk.embedding_model = Some(embedding_model.to_string());
+            }
+        }
+        if !vector_ids.is_empty() {
+            self.embedding_model = Some(embedding_model.to_string());
+        }
+        self
+    }
+}
+
+fn sha256_hex(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+/// Store a document's lineage, replacing any earlier record
+pub async fn store(pool: &PgPool, lineage: &DocumentLineage) -> Result<(), AppError> {
+    let value = serde_json::to_value(lineage)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize lineage: {e}")))?;
+
+    sqlx::query(
+        "INSERT INTO document_lineage (document_id, lineage, recorded_at)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (document_id)
+         DO UPDATE SET lineage = EXCLUDED.lineage, recorded_at = EXCLUDED.recorded_at",
+    )
+    .bind(lineage.document_id)
+    .bind(value)
+    .bind(lineage.recorded_at)
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to store lineage: {e}")))?;
+    Ok(())
+}
+
+/// Lineage recorded for a document, if any
+pub async fn fetch(pool: &PgPool, document_id: Uuid) -> Result<Option<DocumentLineage>, AppError> {
+    let value: Option<serde_json::Value> =
+        sqlx::query_scalar("SELECT lineage FROM document_lineage WHERE document_id = $1")
+            .bind(document_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch lineage: {e}")))?;
+
+    value
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| AppError::Internal(format!("Stored lineage is invalid: {e}")))
+}
+
+/// Store lineage, logging instead of failing the ingestion
+pub(crate) async fn record(pool: &PgPool, lineage: &DocumentLineage) {
+    if let Err(e) = store(pool, lineage).await {
+        tracing::warn!(
+            "Failed to record lineage of document {}: {:?}",
+            lineage.document_id,
+            e
+        );
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(chunk_size: usize) -> otl_parser::ChunkConfig {
+        otl_parser::ChunkConfig {
+            chunk_size,
+            overlap: 200,
+            min_chunk_size: 100,
+            respect_sections: true,
+            respect_paragraphs: true,
+        }
+    }
+
+    #[test]
+    fn test_chunker_hash_is_stable_and_tracks_settings() {
+        let a = ChunkerLineage::from(&config(1000));
+        let b = ChunkerLineage::from(&config(1000));
+        let c = ChunkerLineage::from(&config(500));
+        assert_eq!(a.config_hash, b.config_hash);
+        assert_ne!(a.config_hash, c.config_hash);
+        assert_eq!(a.config_hash.len(), 16);
+    }
+
+    #[test]
+    fn test_chunker_lineage_restores_its_settings() {
+        let lineage = ChunkerLineage::from(&config(800));
+        let restored = otl_parser::ChunkConfig::from(&lineage);
+        assert_eq!(ChunkerLineage::from(&restored), lineage);
+    }
+
+    #[test]
+    fn test_indexed_chunks_carry_the_embedding_model() {
+        let chunks = vec!["첫 번째 청크".to_string(), "second chunk".to_string()];
+        let vector_ids = HashMap::from([(1, "v-1".to_string())]);
+        let lineage = DocumentLineage::new(
+            Uuid::new_v4(),
+            ParserLineage::for_file_type("PDF"),
+            &config(1000),
+            &chunks,
+        )
+        .with_indexed_chunks("text-embedding-3-small", &vector_ids);
+
+        assert_eq!(lineage.parser.name, "pdf-extract");
+        assert_eq!(lineage.ocr_engine, None);
+        assert_eq!(
+            lineage.embedding_model.as_deref(),
+            Some("text-embedding-3-small")
+        );
+        assert_eq!(lineage.chunks[0].embedding_model, None);
+        assert_eq!(lineage.chunks[1].vector_id.as_deref(), Some("v-1"));
+        assert_eq!(lineage.chunks[1].content_hash, sha256_hex("second chunk"));
+        assert_eq!(
+            lineage.chunks[0].chunker_config_hash,
+            lineage.chunker.config_hash
+        );
+
+        let json = serde_json::to_value(&lineage).unwrap();
+        let parsed: DocumentLineage = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, lineage);
+    }
+}
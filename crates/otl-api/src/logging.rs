@@ -0,0 +1,295 @@
+//! Log output and request correlation
+//!
+//! Logs are written as text (the default) or, with `LOG_FORMAT=json`, as
+//! one JSON object per line. Every event logged while a request is handled
+//! carries the request's correlation fields, whichever component logs it:
+//! the request span opened by the request ID middleware holds
+//! `request_id`, `trace_id`, `tenant` and `document_id`, and the auth
+//! middleware adds `user_id`. In JSON output these fields are top-level
+//! keys, so one query can be followed across the API, retrieval and
+//! generation by filtering on a single key.
+//!
+//! Author: hephaex@gmail.com
+
+use chrono::{SecondsFormat, Utc};
+use otl_core::config::LoggingConfig;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Span fields copied onto every event logged inside the span
+pub const CORRELATION_FIELDS: [&str; 5] =
+    ["request_id", "user_id", "tenant", "document_id", "trace_id"];
+
+/// Install the global subscriber for the configured format and level
+///
+/// `RUST_LOG`, when set, overrides the level.
+pub fn init(config: &LoggingConfig) {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| format!("otl_api={0},tower_http={0}", config.level).into());
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(CorrelationLayer);
+
+    if config.json_format {
+        registry
+            .with(tracing_subscriber::fmt::layer().event_format(JsonFormat::new(config)))
+            .init();
+    } else {
+        registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_file(config.include_location)
+                    .with_line_number(config.include_location),
+            )
+            .init();
+    }
+}
+
+// ============================================================================
+// Correlation
+// ============================================================================
+
+/// Correlation fields recorded on a span
+#[derive(Debug, Default, Clone)]
+struct Correlation(BTreeMap<&'static str, String>);
+
+impl Visit for Correlation {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if let Some(name) = CORRELATION_FIELDS
+            .iter()
+            .find(|name| **name == field.name())
+        {
+            self.0.insert(name, value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_str(field, &format!("{value:?}"));
+    }
+}
+
+/// Keeps the correlation fields of every span, including fields recorded
+/// after the span was opened
+pub struct CorrelationLayer;
+
+impl<S> Layer<S> for CorrelationLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut correlation = Correlation::default();
+        attrs.record(&mut correlation);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(correlation);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(correlation) = span.extensions_mut().get_mut::<Correlation>() {
+                values.record(correlation);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// JSON output
+// ============================================================================
+
+/// Event fields as JSON values
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
+
+/// One JSON object per event with the correlation fields of its spans
+///
+/// Fields of inner spans override those of outer spans, and fields of the
+/// event itself override both.
+pub struct JsonFormat {
+    tenant: Option<String>,
+    include_location: bool,
+}
+
+impl JsonFormat {
+    pub fn new(config: &LoggingConfig) -> Self {
+        Self {
+            tenant: config.tenant.clone(),
+            include_location: config.include_location,
+        }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".to_string(),
+            Utc::now()
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+                .into(),
+        );
+        line.insert("level".to_string(), metadata.level().as_str().into());
+        line.insert("target".to_string(), metadata.target().into());
+        if self.include_location {
+            if let Some(file) = metadata.file() {
+                line.insert("file".to_string(), file.into());
+            }
+            if let Some(number) = metadata.line() {
+                line.insert("line".to_string(), number.into());
+            }
+        }
+
+        if let Some(tenant) = &self.tenant {
+            line.insert("tenant".to_string(), tenant.clone().into());
+        }
+        let spans = ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root());
+        for correlation in spans.filter_map(|span| span.extensions().get::<Correlation>().cloned())
+        {
+            line.extend(
+                correlation
+                    .0
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value.into())),
+            );
+        }
+        event.record(&mut JsonFields(&mut line));
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn json_subscriber(config: &LoggingConfig, buffer: &Buffer) -> impl Subscriber {
+        let writer = buffer.clone();
+        tracing_subscriber::registry().with(CorrelationLayer).with(
+            tracing_subscriber::fmt::layer()
+                .event_format(JsonFormat::new(config))
+                .with_writer(move || writer.clone()),
+        )
+    }
+
+    #[test]
+    fn test_json_events_carry_correlation_fields() {
+        let buffer = Buffer::default();
+        let config = LoggingConfig {
+            json_format: true,
+            tenant: Some("acme".to_string()),
+            ..LoggingConfig::default()
+        };
+
+        tracing::subscriber::with_default(json_subscriber(&config, &buffer), || {
+            let request = tracing::info_span!(
+                "request",
+                request_id = %"req-1",
+                trace_id = "4bf92f3577b34da6a3ce929d0e0e4736",
+                user_id = tracing::field::Empty,
+                document_id = tracing::field::Empty,
+            );
+            let _entered = request.enter();
+            request.record("user_id", "u-42");
+            tracing::info_span!("retrieve", document_id = %"doc-7").in_scope(|| {
+                tracing::info!(chunks = 3, "연차휴가 규정 검색 완료");
+            });
+            tracing::warn!(document_id = "doc-9", "문서 없음");
+        });
+        tracing::subscriber::with_default(
+            json_subscriber(&LoggingConfig::default(), &buffer),
+            || tracing::info!("background"),
+        );
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+
+        let retrieved = &lines[0];
+        assert_eq!(retrieved["message"], "연차휴가 규정 검색 완료");
+        assert_eq!(retrieved["level"], "INFO");
+        assert_eq!(retrieved["chunks"], 3);
+        assert_eq!(retrieved["request_id"], "req-1");
+        assert_eq!(retrieved["trace_id"], "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(retrieved["user_id"], "u-42");
+        assert_eq!(retrieved["tenant"], "acme");
+        assert_eq!(retrieved["document_id"], "doc-7");
+
+        assert_eq!(lines[1]["document_id"], "doc-9");
+        assert_eq!(lines[1]["user_id"], "u-42");
+
+        assert_eq!(lines[2]["message"], "background");
+        assert!(lines[2].get("request_id").is_none());
+        assert!(lines[2].get("tenant").is_none());
+    }
+}
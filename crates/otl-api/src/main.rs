@@ -4,27 +4,42 @@
 //!
 //! Author: hephaex@gmail.com
 
+use otl_api::config_check::{self, CheckPolicy, Problem};
 use otl_api::{create_router, state::AppState};
 use otl_core::config::AppConfig;
+use otl_core::{LlmClient, LlmProvider};
 use otl_graph::{GraphSearchBackend, SurrealDbStore};
-use otl_rag::llm::create_llm_client;
+use otl_rag::llm::{
+    create_llm_client, llm_cache_identity, warm_up_ollama, CachedLlmClient, DisabledLlmClient,
+};
+use otl_rag::CachedEmbeddingClient;
 use otl_vector::embedding::create_embedding_client;
-use otl_vector::VectorSearchBackend;
+use otl_vector::{EmbeddingClient, VectorSearchBackend};
 use sqlx::postgres::PgPoolOptions;
 use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // Load configuration
+    let (mut config, config_error) = match AppConfig::from_env() {
+        Ok(config) => (config, None),
+        Err(e) => (AppConfig::default(), Some(e)),
+    };
+
     // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "otl_api=info,tower_http=info".into()),
-        )
-        .init();
+    otl_api::logging::init(&config.logging);
+    if let Some(e) = &config_error {
+        tracing::warn!("Using default configuration: {}", e);
+    }
+
+    // Check the configuration before connecting to anything
+    let check_policy = CheckPolicy::from_env()?;
+    let mut problems = config_check::check_env(&config, check_policy.environment);
+    if let Some(e) = &config_error {
+        problems.insert(0, Problem::from(e));
+    }
+    check_policy.enforce(problems)?;
 
-    // Load configuration
-    let config = AppConfig::from_env().unwrap_or_default();
     let host = std::env::var("API_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
     let port = std::env::var("API_PORT")
         .ok()
@@ -51,6 +66,10 @@ async fn main() -> anyhow::Result<()> {
         config.database.postgres_pool_size
     );
 
+    // Search the collection of the last embedding migration, if any
+    let migrated_model =
+        otl_api::embedding_migration::apply_active_index(&db_pool, &mut config).await;
+
     // Create application state
     let state = Arc::new(AppState::new(config.clone(), db_pool));
 
@@ -65,7 +84,16 @@ async fn main() -> anyhow::Result<()> {
                 config.llm.provider,
                 config.llm.model
             );
-            Some(Arc::from(client))
+            let client: Arc<dyn LlmClient> = Arc::from(client);
+            if state.rag_cache.config().llm_ttl_seconds > 0 {
+                Some(Arc::new(CachedLlmClient::new(
+                    client,
+                    state.rag_cache.llm.clone(),
+                    llm_cache_identity(&config.llm),
+                )) as Arc<dyn LlmClient>)
+            } else {
+                Some(client)
+            }
         }
         Err(e) => {
             tracing::warn!("Failed to initialize LLM client: {}", e);
@@ -73,14 +101,26 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
-    // 2. Initialize Embedding client
+    // Load local models in the background so the first query is not slowed
+    if config.llm.provider == LlmProvider::Ollama {
+        let llm_config = config.llm.clone();
+        tokio::spawn(async move { warm_up_ollama(&llm_config).await });
+    }
+
+    // 2. Initialize Embedding client, served from the embedding cache
+    state.rag_cache.embedding.warm_load().await;
     let embedding_client = match create_embedding_client(&config.llm) {
         Ok(client) => {
             tracing::info!(
                 "Embedding client initialized with dimension {}",
                 client.dimension()
             );
-            Some(Arc::from(client))
+            let mut cached =
+                CachedEmbeddingClient::new(Arc::from(client), state.rag_cache.embedding.clone());
+            if let Some(model) = &migrated_model {
+                cached = cached.with_namespace(model);
+            }
+            Some(Arc::new(cached) as Arc<dyn EmbeddingClient>)
         }
         Err(e) => {
             tracing::warn!("Failed to initialize embedding client: {}", e);
@@ -88,6 +128,9 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    // Keep a handle for extractive sentence scoring
+    let sentence_embedder = embedding_client.clone();
+
     // 3. Initialize Vector Store (Qdrant)
     let vector_store = if let Some(emb_client) = embedding_client {
         match VectorSearchBackend::from_config(&config.database, emb_client).await {
@@ -102,6 +145,7 @@ async fn main() -> anyhow::Result<()> {
 
                 // Set the concrete backend for document indexing
                 state.set_vector_backend(store_arc.clone()).await;
+                otl_api::embedding_migration::resume_shadow(&state).await;
 
                 Some(store_arc as Arc<dyn otl_core::SearchBackend>)
             }
@@ -127,6 +171,9 @@ async fn main() -> anyhow::Result<()> {
             match GraphSearchBackend::new(&config.database).await {
                 Ok(search_backend) => {
                     tracing::info!("Graph search backend initialized");
+                    let search_backend = search_backend
+                        .with_synonyms(state.synonyms.clone())
+                        .with_analyzer(state.analyzer.clone());
                     (
                         Some(Arc::new(search_backend) as Arc<dyn otl_core::SearchBackend>),
                         Some(db_arc),
@@ -144,23 +191,54 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
-    // 5. Initialize full RAG pipeline if all components are available
-    if let (Some(llm), Some(vs), Some(gs)) = (
-        llm_client.clone(),
-        vector_store.clone(),
-        graph_store.clone(),
-    ) {
-        state.initialize_rag(vs, gs, llm).await;
+    // 5. Initialize RAG pipeline if the search backends are available
+    if let (Some(vs), Some(gs)) = (vector_store.clone(), graph_store.clone()) {
+        let llm = match llm_client {
+            Some(llm) => {
+                tracing::info!("RAG pipeline fully initialized");
+                llm
+            }
+            None => {
+                tracing::warn!("RAG pipeline initialized without LLM (extractive mode only)");
+                Arc::new(DisabledLlmClient) as Arc<dyn otl_core::LlmClient>
+            }
+        };
+        state.initialize_rag(vs, gs, llm, sentence_embedder).await;
         rag_initialized = true;
-        tracing::info!("RAG pipeline fully initialized");
-    } else {
+    } else if let Some(llm) = llm_client {
         // At least set LLM client for streaming
-        if let Some(llm) = llm_client {
-            *state.llm_client.write().await = Some(llm);
-            tracing::info!("LLM client set for streaming (RAG not fully initialized)");
-        }
+        *state.llm_client.write().await = Some(llm);
+        tracing::info!("LLM client set for streaming (RAG not fully initialized)");
     }
 
+    // Start gRPC server alongside REST
+    #[cfg(feature = "grpc")]
+    {
+        let grpc_port = std::env::var("GRPC_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(50051u16);
+        let grpc_addr: std::net::SocketAddr = format!("{host}:{grpc_port}").parse()?;
+        let grpc_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = otl_api::grpc::serve(grpc_state, grpc_addr).await {
+                tracing::error!("gRPC server failed: {}", e);
+            }
+        });
+    }
+
+    otl_api::retention::spawn_purge_job(state.clone(), state.retention.clone());
+    otl_api::faq::spawn_generation_job(state.clone(), state.faq.clone());
+    otl_api::feedback::spawn_load(state.clone());
+    otl_api::freshness::spawn_check_job(state.clone(), state.freshness.clone());
+    otl_api::review::spawn_assignment_job(state.clone(), state.review.clone());
+    otl_api::quality_audit::spawn_audit_job(state.clone(), state.quality_audit.clone());
+    otl_api::access_requests::spawn_expiry_job(state.clone(), state.access_requests.clone());
+    otl_api::notifications::spawn_pending_job(state.clone());
+    otl_api::notification_center::spawn_purge_job(state.clone());
+    otl_api::sessions::spawn_cutoff_sync_job(state.clone());
+    otl_api::saved_searches::spawn_match_job(state.clone(), state.saved_searches.clone());
+
     // Create router
     let app = create_router(state);
 
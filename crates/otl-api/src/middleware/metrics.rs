@@ -19,12 +19,14 @@ use std::time::Instant;
 /// - Request count per endpoint
 /// - Request latency distribution
 /// - Response status codes
+/// - Events of the service level objectives
 pub async fn metrics_middleware(
     State(state): State<Arc<AppState>>,
     request: Request,
     next: Next,
 ) -> Response {
     let start = Instant::now();
+    let method = request.method().clone();
     let path = request.uri().path().to_string();
 
     // Normalize the path for metrics (remove IDs)
@@ -36,6 +38,9 @@ pub async fn metrics_middleware(
     // Record metrics
     let latency_us = start.elapsed().as_micros() as u64;
     let status = response.status();
+    state
+        .slo
+        .record(&method, &endpoint, status.as_u16(), latency_us);
 
     // Record asynchronously to avoid blocking
     let state_clone = state.clone();
@@ -72,12 +77,10 @@ fn normalize_endpoint(path: &str) -> String {
 /// Check if a string looks like a UUID
 fn is_uuid(s: &str) -> bool {
     s.len() == 36
-        && s.chars()
-            .enumerate()
-            .all(|(i, c)| match i {
-                8 | 13 | 18 | 23 => c == '-',
-                _ => c.is_ascii_hexdigit(),
-            })
+        && s.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
 }
 
 /// Check if a string is numeric (likely an ID)
@@ -99,14 +102,8 @@ mod tests {
             normalize_endpoint("/api/v1/documents/550e8400-e29b-41d4-a716-446655440000"),
             "/api/v1/documents/:id"
         );
-        assert_eq!(
-            normalize_endpoint("/api/v1/query"),
-            "/api/v1/query"
-        );
-        assert_eq!(
-            normalize_endpoint("/health"),
-            "/health"
-        );
+        assert_eq!(normalize_endpoint("/api/v1/query"), "/api/v1/query");
+        assert_eq!(normalize_endpoint("/health"), "/health");
     }
 
     #[test]
@@ -6,18 +6,20 @@
 // pub mod rate_limit;
 
 pub mod metrics;
+pub mod request_id;
 pub mod security_headers;
 
 pub use metrics::metrics_middleware;
+pub use request_id::request_id_middleware;
 pub use security_headers::security_headers_middleware;
 
+use crate::error::{ApiError, ErrorCode};
 use axum::{
     body::Body,
     extract::Request,
-    http::{header, StatusCode},
+    http::header,
     middleware::Next,
     response::{IntoResponse, Response},
-    Json,
 };
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
@@ -92,12 +94,9 @@ pub async fn auth_middleware(
     let token = match auth_header {
         Some(h) if h.starts_with("Bearer ") => &h[7..],
         _ => {
-            return Err((
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::json!({
-                    "code": "UNAUTHORIZED",
-                    "message": "Missing or invalid authorization header"
-                })),
+            return Err(ApiError::new(
+                ErrorCode::Unauthorized,
+                "Missing or invalid authorization header",
             ));
         }
     };
@@ -116,12 +115,9 @@ pub async fn auth_middleware(
             request.extensions_mut().insert(AuthUser::from(data.claims));
             Ok(next.run(request).await)
         }
-        Err(e) => Err((
-            StatusCode::UNAUTHORIZED,
-            Json(serde_json::json!({
-                "code": "INVALID_TOKEN",
-                "message": format!("Invalid token: {e}")
-            })),
+        Err(e) => Err(ApiError::new(
+            ErrorCode::InvalidToken,
+            format!("Invalid token: {e}"),
         )),
     }
 }
@@ -154,26 +150,14 @@ pub async fn optional_auth_middleware(mut request: Request<Body>, next: Next) ->
 }
 
 /// Check if user has required role
-pub fn check_role(
-    user: Option<&AuthUser>,
-    required_role: &str,
-) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+pub fn check_role(user: Option<&AuthUser>, required_role: &str) -> Result<(), ApiError> {
     match user {
         Some(u) if u.roles.contains(&required_role.to_string()) => Ok(()),
-        Some(_) => Err((
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({
-                "code": "FORBIDDEN",
-                "message": format!("Required role: {required_role}")
-            })),
-        )),
-        None => Err((
-            StatusCode::UNAUTHORIZED,
-            Json(serde_json::json!({
-                "code": "UNAUTHORIZED",
-                "message": "Authentication required"
-            })),
+        Some(_) => Err(ApiError::new(
+            ErrorCode::Forbidden,
+            format!("Required role: {required_role}"),
         )),
+        None => Err(ApiError::unauthorized()),
     }
 }
 
@@ -0,0 +1,174 @@
+//! Request ID middleware
+//!
+//! Gives every request a correlation ID: the client's `x-request-id` header
+//! when it is a reasonable token, a new UUID otherwise. The ID is echoed in
+//! the response header, recorded on the request's log span and included in
+//! every error body.
+//!
+//! The span also carries the trace ID of a W3C `traceparent` header (a new
+//! one otherwise), the tenant of an `x-tenant-id` header and the document
+//! addressed by the path; the auth middleware records the user (see
+//! [`crate::logging`]).
+//!
+//! Author: hephaex@gmail.com
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+/// Header carrying the request ID
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// W3C trace context header
+static TRACEPARENT_HEADER: HeaderName = HeaderName::from_static("traceparent");
+
+/// Header naming the tenant a request is made for
+static TENANT_HEADER: HeaderName = HeaderName::from_static("x-tenant-id");
+
+/// Longest client-supplied request ID that is accepted
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// ID of the request being handled by the current task
+///
+/// Outside a request (e.g. in background tasks) a new ID is generated so
+/// that error bodies always carry one.
+pub fn current_request_id() -> String {
+    REQUEST_ID
+        .try_with(Clone::clone)
+        .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string())
+}
+
+/// Assign a request ID and make it available to handlers and error bodies
+pub async fn request_id_middleware(request: Request<Body>, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    // Owned copies in their own scope: a borrow of the request must not be
+    // held across `next.run`, or the middleware future is not `Send`
+    let (trace_id, tenant, document_id) = {
+        let headers = request.headers();
+        let header = |name: &HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        (
+            header(&TRACEPARENT_HEADER)
+                .and_then(|traceparent| traceparent_trace_id(&traceparent))
+                .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string()),
+            header(&TENANT_HEADER).filter(|id| is_valid_request_id(id)),
+            path_document_id(request.uri().path()).map(str::to_string),
+        )
+    };
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        trace_id = %trace_id,
+        tenant = tracing::field::Empty,
+        user_id = tracing::field::Empty,
+        document_id = tracing::field::Empty,
+    );
+    if let Some(tenant) = &tenant {
+        span.record("tenant", tenant.as_str());
+    }
+    if let Some(document_id) = &document_id {
+        span.record("document_id", document_id.as_str());
+    }
+
+    let mut response = REQUEST_ID
+        .scope(request_id.clone(), next.run(request).instrument(span))
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
+
+/// Trace ID of a `traceparent` header (`00-<trace id>-<parent id>-<flags>`)
+fn traceparent_trace_id(traceparent: &str) -> Option<String> {
+    let mut parts = traceparent.trim().split('-');
+    let (_version, trace_id) = (parts.next()?, parts.next()?);
+    let valid = trace_id.len() == 32
+        && trace_id.chars().all(|c| c.is_ascii_hexdigit())
+        && trace_id.chars().any(|c| c != '0');
+    valid.then(|| trace_id.to_ascii_lowercase())
+}
+
+/// Document ID of a `/documents/:id/...` path
+fn path_document_id(path: &str) -> Option<&str> {
+    let mut segments = path.split('/');
+    segments.find(|segment| *segment == "documents")?;
+    segments
+        .next()
+        .filter(|id| uuid::Uuid::parse_str(id).is_ok())
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_id_validation() {
+        assert!(is_valid_request_id("3f2c-ab_12.x"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("bad id"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[test]
+    fn test_trace_and_document_ids() {
+        assert_eq!(
+            traceparent_trace_id("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01")
+                .as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+        assert!(
+            traceparent_trace_id("00-00000000000000000000000000000000-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(traceparent_trace_id("not-a-trace").is_none());
+
+        let id = "550e8400-e29b-41d4-a716-446655440000";
+        assert_eq!(
+            path_document_id(&format!("/api/v1/documents/{id}/chunks")),
+            Some(id)
+        );
+        assert_eq!(path_document_id("/api/v1/documents/compare"), None);
+        assert_eq!(path_document_id("/api/v1/query"), None);
+    }
+
+    #[tokio::test]
+    async fn test_current_request_id_inside_scope() {
+        let id = REQUEST_ID
+            .scope("req-1".to_string(), async { current_request_id() })
+            .await;
+        assert_eq!(id, "req-1");
+        assert_ne!(current_request_id(), current_request_id());
+    }
+}
@@ -0,0 +1,236 @@
+//! In-app notification center
+//!
+//! Every notification sent by [`crate::notifications`] is also stored per
+//! recipient in `user_notifications`, so the web UI can show a bell with an
+//! unread count and a list to read from, with one endpoint instead of
+//! polling the review queue, access requests and job endpoints. Users mark
+//! notifications read one at a time or all at once; read notifications are
+//! removed after the retention period.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use crate::notifications::{Message, NotificationEvent};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Seconds between purges of old read notifications (daily)
+const PURGE_INTERVAL_SECS: u64 = 86_400;
+
+/// Notification in a user's inbox
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct UserNotification {
+    pub id: Uuid,
+    pub event: NotificationEvent,
+    pub subject: String,
+    pub body: String,
+    /// API path of what the notification is about
+    pub link: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the user read it (`None`: unread)
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct NotificationRow {
+    id: Uuid,
+    event: String,
+    subject: String,
+    body: String,
+    link: Option<String>,
+    created_at: DateTime<Utc>,
+    read_at: Option<DateTime<Utc>>,
+}
+
+impl NotificationRow {
+    fn into_notification(self) -> Option<UserNotification> {
+        let Some(event) = NotificationEvent::parse(&self.event) else {
+            tracing::warn!(
+                "Skipping notification {} with event {}",
+                self.id,
+                self.event
+            );
+            return None;
+        };
+        Some(UserNotification {
+            id: self.id,
+            event,
+            subject: self.subject,
+            body: self.body,
+            link: self.link,
+            created_at: self.created_at,
+            read_at: self.read_at,
+        })
+    }
+}
+
+fn db_error(action: &str) -> impl Fn(sqlx::Error) -> AppError + '_ {
+    move |e| AppError::Database(format!("Failed to {action}: {e}"))
+}
+
+/// Put a message into the inbox of each user
+pub async fn store(
+    pool: &sqlx::PgPool,
+    message: &Message,
+    user_ids: &[Uuid],
+) -> Result<(), AppError> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO user_notifications (user_id, event, subject, body, link) \
+         SELECT u, $2, $3, $4, $5 FROM UNNEST($1::uuid[]) AS u",
+    )
+    .bind(user_ids)
+    .bind(message.event.as_str())
+    .bind(&message.subject)
+    .bind(&message.body)
+    .bind(&message.link)
+    .execute(pool)
+    .await
+    .map_err(db_error("store notifications"))?;
+    Ok(())
+}
+
+/// Notifications of a user, newest first
+///
+/// `before` pages backwards from a notification's `created_at`.
+pub async fn list(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    unread_only: bool,
+    before: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<UserNotification>, AppError> {
+    let rows: Vec<NotificationRow> = sqlx::query_as(
+        "SELECT id, event, subject, body, link, created_at, read_at \
+         FROM user_notifications \
+         WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL) \
+         AND ($3::timestamptz IS NULL OR created_at < $3) \
+         ORDER BY created_at DESC LIMIT $4",
+    )
+    .bind(user_id)
+    .bind(unread_only)
+    .bind(before)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error("list notifications"))?;
+    Ok(rows
+        .into_iter()
+        .filter_map(NotificationRow::into_notification)
+        .collect())
+}
+
+/// Unread notifications of a user
+pub async fn unread_count(pool: &sqlx::PgPool, user_id: Uuid) -> Result<i64, AppError> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM user_notifications WHERE user_id = $1 AND read_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .map_err(db_error("count notifications"))
+}
+
+/// Mark one notification of a user read
+pub async fn mark_read(pool: &sqlx::PgPool, user_id: Uuid, id: Uuid) -> Result<(), AppError> {
+    let result = sqlx::query(
+        "UPDATE user_notifications SET read_at = COALESCE(read_at, NOW()) \
+         WHERE id = $1 AND user_id = $2",
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .map_err(db_error("mark notification read"))?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Notification {id} not found")));
+    }
+    Ok(())
+}
+
+/// Mark every unread notification of a user read; returns how many
+pub async fn mark_all_read(pool: &sqlx::PgPool, user_id: Uuid) -> Result<u64, AppError> {
+    Ok(sqlx::query(
+        "UPDATE user_notifications SET read_at = NOW() WHERE user_id = $1 AND read_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .map_err(db_error("mark notifications read"))?
+    .rows_affected())
+}
+
+/// Delete notifications read more than `days` ago; returns how many
+pub async fn purge_read(pool: &sqlx::PgPool, days: u32) -> Result<u64, AppError> {
+    Ok(sqlx::query(
+        "DELETE FROM user_notifications WHERE read_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(days as i32)
+    .execute(pool)
+    .await
+    .map_err(db_error("purge notifications"))?
+    .rows_affected())
+}
+
+/// Purge old read notifications daily in the background
+///
+/// Does nothing if read notifications are kept forever.
+pub fn spawn_purge_job(state: Arc<AppState>) {
+    let Some(days) = state.notifications.policy().read_retention_days else {
+        tracing::info!("Read notifications are kept forever");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(PURGE_INTERVAL_SECS));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match purge_read(&state.db_pool, days).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} read notifications", purged),
+                Err(e) => tracing::warn!("Notification purge failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(event: &str) -> NotificationRow {
+        NotificationRow {
+            id: Uuid::new_v4(),
+            event: event.to_string(),
+            subject: "[OTL] Access requested: 취업규칙".to_string(),
+            body: "김인사 asked for access".to_string(),
+            link: Some("/api/v1/access-requests/pending".to_string()),
+            created_at: Utc::now(),
+            read_at: None,
+        }
+    }
+
+    #[test]
+    fn test_rows_with_unknown_events_are_skipped() {
+        let notification = row("access_requested").into_notification().unwrap();
+        assert_eq!(notification.event, NotificationEvent::AccessRequested);
+        assert_eq!(
+            notification.link.as_deref(),
+            Some("/api/v1/access-requests/pending")
+        );
+
+        assert!(row("document_deleted").into_notification().is_none());
+    }
+}
@@ -0,0 +1,1022 @@
+//! Notifications by email and Slack
+//!
+//! Tells people about work waiting for them: new extractions in the review
+//! queue (editors and admins), access requests (their approvers) and
+//! decisions (the requester), failed background jobs (the user who started
+//! the job, else the admins), newly stale documents (admins) and new
+//! documents matching a saved search (its owner).
+//!
+//! Each event has a message template with `{name}` placeholders, which
+//! `NOTIFY_TEMPLATES` can override. A notification goes out through every
+//! configured channel: SMTP mails each recipient, the Slack webhook posts
+//! once to its channel and mentions the recipients who gave a Slack member
+//! ID. Every notification is also kept in the recipient's in-app inbox
+//! (see [`crate::notification_center`]), with or without channels. Users
+//! pick their channels and mute events in their preferences. Delivery runs
+//! in the background; failures are logged and never fail the request that
+//! caused the notification.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use crate::notification_center;
+use crate::state::AppState;
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Default seconds between checks for new pending extractions
+const DEFAULT_PENDING_CHECK_INTERVAL_SECS: u64 = 900;
+
+/// Seconds to wait for the Slack webhook to accept a message
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Default days read in-app notifications are kept
+const DEFAULT_READ_RETENTION_DAYS: u32 = 30;
+
+/// Document titles named in a pending extraction notification
+const MAX_LISTED_DOCUMENTS: usize = 5;
+
+// ============================================================================
+// Events and templates
+// ============================================================================
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// New extractions are waiting for review
+    ExtractionsPending,
+    /// A user asked for access to a document
+    AccessRequested,
+    /// An access request was approved or denied
+    AccessDecided,
+    /// A background job failed
+    JobFailed,
+    /// Documents became stale (review overdue or superseded)
+    DocumentsStale,
+    /// New documents match a saved search
+    SavedSearchMatched,
+}
+
+impl NotificationEvent {
+    pub const ALL: [Self; 6] = [
+        Self::ExtractionsPending,
+        Self::AccessRequested,
+        Self::AccessDecided,
+        Self::JobFailed,
+        Self::DocumentsStale,
+        Self::SavedSearchMatched,
+    ];
+
+    /// Name used in templates and preferences
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ExtractionsPending => "extractions_pending",
+            Self::AccessRequested => "access_requested",
+            Self::AccessDecided => "access_decided",
+            Self::JobFailed => "job_failed",
+            Self::DocumentsStale => "documents_stale",
+            Self::SavedSearchMatched => "saved_search_matched",
+        }
+    }
+
+    /// Parse an event name
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.as_str() == value)
+    }
+}
+
+/// Subject and body of a message, with `{name}` placeholders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+    pub subject: String,
+    pub body: String,
+}
+
+impl Template {
+    fn new(subject: &str, body: &str) -> Self {
+        Self {
+            subject: subject.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Built-in template of an event
+    pub fn default_for(event: NotificationEvent) -> Self {
+        match event {
+            NotificationEvent::ExtractionsPending => Self::new(
+                "[OTL] {count} new extractions to review",
+                "{count} new extractions are waiting in the review queue.\n\
+                 Documents: {documents}",
+            ),
+            NotificationEvent::AccessRequested => Self::new(
+                "[OTL] Access requested: {document}",
+                "{requester} asked for access to \"{document}\".\n\
+                 Reason: {reason}\n\
+                 Requested until: {until}",
+            ),
+            NotificationEvent::AccessDecided => Self::new(
+                "[OTL] Access request {decision}: {document}",
+                "Your request for access to \"{document}\" was {decision}.\n\
+                 Access until: {until}\n\
+                 Note: {note}",
+            ),
+            NotificationEvent::JobFailed => {
+                Self::new("[OTL] {job} failed", "{job} {job_id} failed: {error}")
+            }
+            NotificationEvent::DocumentsStale => Self::new(
+                "[OTL] {count} documents need attention",
+                "The freshness check raised {count} alerts.\n{alerts}",
+            ),
+            NotificationEvent::SavedSearchMatched => Self::new(
+                "[OTL] New documents for \"{search}\"",
+                "{count} new documents match your saved search \"{search}\" ({query}).\n\
+                 {documents}",
+            ),
+        }
+    }
+}
+
+/// Variable holding the API path a notification points to
+pub const LINK_VAR: &str = "link";
+
+/// Replace `{name}` placeholders with their values
+///
+/// Placeholders without a value are left as they are.
+pub fn render(template: &str, vars: &[(&str, String)]) -> String {
+    let mut out = template.to_string();
+    for (name, value) in vars {
+        out = out.replace(&format!("{{{name}}}"), value);
+    }
+    out
+}
+
+/// A rendered notification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Message {
+    pub event: NotificationEvent,
+    pub subject: String,
+    pub body: String,
+    /// API path of what the notification is about (the `link` variable)
+    pub link: Option<String>,
+}
+
+// ============================================================================
+// Preferences
+// ============================================================================
+
+/// Delivery channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    Email,
+    Slack,
+}
+
+/// How a user wants to be notified
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferences {
+    /// Receive notifications by email
+    pub email: bool,
+    /// Be mentioned in Slack notifications
+    pub slack: bool,
+    /// Slack member ID to mention, e.g. `U024BE7LH`
+    pub slack_member_id: Option<String>,
+    /// Events the user does not want to hear about
+    pub muted_events: Vec<NotificationEvent>,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            email: true,
+            slack: true,
+            slack_member_id: None,
+            muted_events: Vec::new(),
+        }
+    }
+}
+
+impl NotificationPreferences {
+    /// Whether the user wants `event` through `channel`
+    pub fn wants(&self, event: NotificationEvent, channel: ChannelKind) -> bool {
+        let enabled = match channel {
+            ChannelKind::Email => self.email,
+            ChannelKind::Slack => self.slack,
+        };
+        enabled && !self.muted_events.contains(&event)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PreferencesRow {
+    email_enabled: Option<bool>,
+    slack_enabled: Option<bool>,
+    slack_member_id: Option<String>,
+    muted_events: Option<Vec<String>>,
+}
+
+impl From<PreferencesRow> for NotificationPreferences {
+    fn from(row: PreferencesRow) -> Self {
+        let defaults = Self::default();
+        Self {
+            email: row.email_enabled.unwrap_or(defaults.email),
+            slack: row.slack_enabled.unwrap_or(defaults.slack),
+            slack_member_id: row.slack_member_id,
+            muted_events: row
+                .muted_events
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|name| NotificationEvent::parse(name))
+                .collect(),
+        }
+    }
+}
+
+/// Preferences of a user (defaults if never saved)
+pub async fn get_preferences(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+) -> Result<NotificationPreferences, AppError> {
+    let row: Option<PreferencesRow> = sqlx::query_as(
+        "SELECT email_enabled, slack_enabled, slack_member_id, muted_events \
+         FROM notification_preferences WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch notification preferences: {e}")))?;
+    Ok(row.map(Into::into).unwrap_or_default())
+}
+
+/// Save the preferences of a user
+pub async fn set_preferences(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    preferences: &NotificationPreferences,
+) -> Result<(), AppError> {
+    let muted: Vec<&str> = preferences
+        .muted_events
+        .iter()
+        .map(NotificationEvent::as_str)
+        .collect();
+    let member_id = preferences
+        .slack_member_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty());
+    sqlx::query(
+        "INSERT INTO notification_preferences \
+             (user_id, email_enabled, slack_enabled, slack_member_id, muted_events) \
+         VALUES ($1, $2, $3, $4, $5) \
+         ON CONFLICT (user_id) DO UPDATE SET email_enabled = $2, slack_enabled = $3, \
+             slack_member_id = $4, muted_events = $5, updated_at = NOW()",
+    )
+    .bind(user_id)
+    .bind(preferences.email)
+    .bind(preferences.slack)
+    .bind(member_id)
+    .bind(&muted)
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to save notification preferences: {e}")))?;
+    Ok(())
+}
+
+// ============================================================================
+// Channels
+// ============================================================================
+
+/// User a notification is delivered to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipient {
+    pub id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub preferences: NotificationPreferences,
+}
+
+/// Way of delivering notifications
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn kind(&self) -> ChannelKind;
+
+    /// Deliver a message to the recipients that want it on this channel
+    async fn deliver(&self, message: &Message, recipients: &[Recipient]) -> Result<(), String>;
+}
+
+/// Mails each recipient through an SMTP relay
+pub struct SmtpChannel {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpChannel {
+    pub fn new(settings: &SmtpSettings) -> Result<Self, String> {
+        let from: Mailbox = settings
+            .from
+            .parse()
+            .map_err(|e| format!("Invalid sender {}: {e}", settings.from))?;
+        let mut builder = match settings.tls {
+            SmtpTls::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+                    .map_err(|e| format!("Invalid SMTP relay {}: {e}", settings.host))?
+            }
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host)
+                .map_err(|e| format!("Invalid SMTP relay {}: {e}", settings.host))?,
+            SmtpTls::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+            }
+        };
+        if let Some(port) = settings.port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SmtpChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Email
+    }
+
+    async fn deliver(&self, message: &Message, recipients: &[Recipient]) -> Result<(), String> {
+        let mut failures = Vec::new();
+        for recipient in recipients {
+            let to = match recipient.email.parse::<Address>() {
+                Ok(address) => Mailbox::new(Some(recipient.name.clone()), address),
+                Err(e) => {
+                    failures.push(format!("{}: {e}", recipient.email));
+                    continue;
+                }
+            };
+            let email = lettre::Message::builder()
+                .from(self.from.clone())
+                .to(to)
+                .subject(&message.subject)
+                .header(ContentType::TEXT_PLAIN)
+                .body(message.body.clone())
+                .map_err(|e| format!("Cannot build mail: {e}"))?;
+            if let Err(e) = self.transport.send(email).await {
+                failures.push(format!("{}: {e}", recipient.email));
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures.join("; "))
+        }
+    }
+}
+
+/// Posts to a Slack incoming webhook
+pub struct SlackChannel {
+    webhook_url: String,
+    client: reqwest::Client,
+}
+
+impl SlackChannel {
+    pub fn new(webhook_url: impl Into<String>) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| format!("Cannot build webhook client: {e}"))?;
+        Ok(Self {
+            webhook_url: webhook_url.into(),
+            client,
+        })
+    }
+}
+
+/// Slack message text: bold subject, body and mentions of the recipients
+/// with a member ID
+fn slack_text(message: &Message, recipients: &[Recipient]) -> String {
+    let mut text = format!("*{}*\n{}", message.subject, message.body);
+    let mentions: Vec<String> = recipients
+        .iter()
+        .filter_map(|r| r.preferences.slack_member_id.as_deref())
+        .map(|id| format!("<@{id}>"))
+        .collect();
+    if !mentions.is_empty() {
+        text.push('\n');
+        text.push_str(&mentions.join(" "));
+    }
+    text
+}
+
+#[async_trait]
+impl NotificationChannel for SlackChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Slack
+    }
+
+    async fn deliver(&self, message: &Message, recipients: &[Recipient]) -> Result<(), String> {
+        self.client
+            .post(&self.webhook_url)
+            .json(&serde_json::json!({ "text": slack_text(message, recipients) }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// How mail is encrypted on the way to the relay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Upgrade a plain connection (port 587)
+    #[default]
+    StartTls,
+    /// TLS from the start (port 465)
+    Tls,
+    /// Unencrypted, for local relays only
+    None,
+}
+
+/// SMTP relay settings
+#[derive(Clone, PartialEq, Eq)]
+pub struct SmtpSettings {
+    pub host: String,
+    /// Defaults to the standard port of the TLS mode
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Sender, e.g. `OTL <otl@example.com>`
+    pub from: String,
+    pub tls: SmtpTls,
+}
+
+impl std::fmt::Debug for SmtpSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpSettings")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("from", &self.from)
+            .field("tls", &self.tls)
+            .finish()
+    }
+}
+
+/// Channels, templates and schedule of notifications
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationPolicy {
+    pub smtp: Option<SmtpSettings>,
+    pub slack_webhook_url: Option<String>,
+    /// Templates replacing the built-in ones
+    pub templates: HashMap<NotificationEvent, Template>,
+    /// Time between checks for new pending extractions (`None` disables
+    /// reviewer notifications)
+    pub pending_check_interval: Option<Duration>,
+    /// Days read in-app notifications are kept (`None`: forever)
+    pub read_retention_days: Option<u32>,
+}
+
+impl Default for NotificationPolicy {
+    fn default() -> Self {
+        Self {
+            smtp: None,
+            slack_webhook_url: None,
+            templates: HashMap::new(),
+            pending_check_interval: Some(Duration::from_secs(DEFAULT_PENDING_CHECK_INTERVAL_SECS)),
+            read_retention_days: Some(DEFAULT_READ_RETENTION_DAYS),
+        }
+    }
+}
+
+impl NotificationPolicy {
+    /// Policy from `NOTIFY_SMTP_HOST`, `NOTIFY_SMTP_PORT`,
+    /// `NOTIFY_SMTP_USERNAME`, `NOTIFY_SMTP_PASSWORD`, `NOTIFY_SMTP_FROM`,
+    /// `NOTIFY_SMTP_TLS` (`starttls`, `tls` or `none`),
+    /// `NOTIFY_SLACK_WEBHOOK_URL`, `NOTIFY_TEMPLATES` (JSON object of event
+    /// name to `{subject, body}`), `NOTIFY_PENDING_CHECK_INTERVAL_SECS`
+    /// (0 disables the check) and `NOTIFY_READ_RETENTION_DAYS` (0 keeps read
+    /// notifications forever)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        if let Some(host) = var("NOTIFY_SMTP_HOST") {
+            let tls = match var("NOTIFY_SMTP_TLS").as_deref() {
+                None | Some("starttls") => SmtpTls::StartTls,
+                Some("tls") => SmtpTls::Tls,
+                Some("none") => SmtpTls::None,
+                Some(other) => {
+                    tracing::warn!("Ignoring invalid NOTIFY_SMTP_TLS: {}", other);
+                    SmtpTls::StartTls
+                }
+            };
+            let port = var("NOTIFY_SMTP_PORT").and_then(|port| match port.parse() {
+                Ok(port) => Some(port),
+                Err(_) => {
+                    tracing::warn!("Ignoring invalid NOTIFY_SMTP_PORT: {}", port);
+                    None
+                }
+            });
+            policy.smtp = Some(SmtpSettings {
+                from: var("NOTIFY_SMTP_FROM").unwrap_or_else(|| format!("OTL <otl@{host}>")),
+                host,
+                port,
+                username: var("NOTIFY_SMTP_USERNAME"),
+                password: var("NOTIFY_SMTP_PASSWORD"),
+                tls,
+            });
+        }
+        policy.slack_webhook_url = var("NOTIFY_SLACK_WEBHOOK_URL");
+
+        if let Some(json) = var("NOTIFY_TEMPLATES") {
+            policy.templates = parse_templates(&json);
+        }
+        if let Some(secs) = var("NOTIFY_PENDING_CHECK_INTERVAL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(0) => policy.pending_check_interval = None,
+                Ok(secs) => policy.pending_check_interval = Some(Duration::from_secs(secs)),
+                Err(_) => tracing::warn!(
+                    "Ignoring invalid NOTIFY_PENDING_CHECK_INTERVAL_SECS: {}",
+                    secs
+                ),
+            }
+        }
+        if let Some(days) = var("NOTIFY_READ_RETENTION_DAYS") {
+            match days.parse::<u32>() {
+                Ok(0) => policy.read_retention_days = None,
+                Ok(days) => policy.read_retention_days = Some(days),
+                Err(_) => tracing::warn!("Ignoring invalid NOTIFY_READ_RETENTION_DAYS: {}", days),
+            }
+        }
+        policy
+    }
+}
+
+/// Templates of `NOTIFY_TEMPLATES`, skipping unknown events (none if the
+/// JSON is invalid)
+fn parse_templates(json: &str) -> HashMap<NotificationEvent, Template> {
+    let templates = match serde_json::from_str::<HashMap<String, Template>>(json) {
+        Ok(templates) => templates,
+        Err(e) => {
+            tracing::warn!("Ignoring invalid NOTIFY_TEMPLATES: {}", e);
+            return HashMap::new();
+        }
+    };
+    let mut parsed = HashMap::new();
+    for (name, template) in templates {
+        let Some(event) = NotificationEvent::parse(&name) else {
+            tracing::warn!("Ignoring template of unknown event {}", name);
+            continue;
+        };
+        parsed.insert(event, template);
+    }
+    parsed
+}
+
+// ============================================================================
+// Notifier
+// ============================================================================
+
+/// Who a notification is for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Audience {
+    /// Specific users
+    Users(Vec<Uuid>),
+    /// Editors and admins, who review extractions
+    Reviewers,
+    /// Admins
+    Admins,
+}
+
+/// Renders notifications and hands them to the channels
+pub struct Notifier {
+    policy: NotificationPolicy,
+    channels: Vec<Arc<dyn NotificationChannel>>,
+}
+
+impl std::fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let channels: Vec<ChannelKind> = self.channels.iter().map(|c| c.kind()).collect();
+        f.debug_struct("Notifier")
+            .field("policy", &self.policy)
+            .field("channels", &channels)
+            .finish()
+    }
+}
+
+impl Notifier {
+    /// Notifier with the channels configured in the policy
+    ///
+    /// Channels that cannot be set up are left out with a warning.
+    pub fn from_policy(policy: NotificationPolicy) -> Self {
+        let mut channels: Vec<Arc<dyn NotificationChannel>> = Vec::new();
+        if let Some(smtp) = &policy.smtp {
+            match SmtpChannel::new(smtp) {
+                Ok(channel) => channels.push(Arc::new(channel)),
+                Err(e) => tracing::warn!("Email notifications disabled: {}", e),
+            }
+        }
+        if let Some(url) = &policy.slack_webhook_url {
+            match SlackChannel::new(url.clone()) {
+                Ok(channel) => channels.push(Arc::new(channel)),
+                Err(e) => tracing::warn!("Slack notifications disabled: {}", e),
+            }
+        }
+        Self { policy, channels }
+    }
+
+    /// Add a channel
+    pub fn with_channel(mut self, channel: Arc<dyn NotificationChannel>) -> Self {
+        self.channels.push(channel);
+        self
+    }
+
+    pub fn policy(&self) -> &NotificationPolicy {
+        &self.policy
+    }
+
+    /// Whether any channel is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.channels.is_empty()
+    }
+
+    /// Render the message of an event
+    pub fn message(&self, event: NotificationEvent, vars: &[(&str, String)]) -> Message {
+        let template = self
+            .policy
+            .templates
+            .get(&event)
+            .cloned()
+            .unwrap_or_else(|| Template::default_for(event));
+        Message {
+            event,
+            subject: render(&template.subject, vars),
+            body: render(&template.body, vars),
+            link: vars
+                .iter()
+                .find(|(name, _)| *name == LINK_VAR)
+                .map(|(_, link)| link.clone()),
+        }
+    }
+
+    /// Deliver a message through every channel to the recipients that want
+    /// it there
+    pub async fn deliver(&self, message: &Message, recipients: &[Recipient]) {
+        for channel in &self.channels {
+            let kind = channel.kind();
+            let wanting: Vec<Recipient> = recipients
+                .iter()
+                .filter(|r| r.preferences.wants(message.event, kind))
+                .cloned()
+                .collect();
+            if wanting.is_empty() {
+                continue;
+            }
+            if let Err(e) = channel.deliver(message, &wanting).await {
+                tracing::warn!(
+                    "{:?} notification {} failed: {}",
+                    kind,
+                    message.event.as_str(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RecipientRow {
+    id: Uuid,
+    email: String,
+    name: String,
+    #[sqlx(flatten)]
+    preferences: PreferencesRow,
+}
+
+/// Active users of an audience with their preferences
+async fn recipients(
+    pool: &sqlx::PgPool,
+    audience: &Audience,
+) -> Result<Vec<Recipient>, sqlx::Error> {
+    let filter = match audience {
+        Audience::Users(_) => "u.id = ANY($1)",
+        Audience::Reviewers => "u.role IN ('admin', 'editor')",
+        Audience::Admins => "u.role = 'admin'",
+    };
+    let sql = format!(
+        "SELECT u.id, u.email, u.name, p.email_enabled, p.slack_enabled, p.slack_member_id, \
+         p.muted_events \
+         FROM users u LEFT JOIN notification_preferences p ON p.user_id = u.id \
+         WHERE u.is_active AND {filter} ORDER BY u.email"
+    );
+    let mut query = sqlx::query_as::<_, RecipientRow>(&sql);
+    if let Audience::Users(ids) = audience {
+        query = query.bind(ids.clone());
+    }
+    let rows = query.fetch_all(pool).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| Recipient {
+            id: row.id,
+            email: row.email,
+            name: row.name,
+            preferences: row.preferences.into(),
+        })
+        .collect())
+}
+
+/// Notify an audience of an event in the background
+///
+/// The message is stored in the inbox of every recipient who has not muted
+/// the event and delivered through the configured channels.
+pub fn notify(
+    state: &AppState,
+    event: NotificationEvent,
+    vars: Vec<(&'static str, String)>,
+    audience: Audience,
+) {
+    let notifier = state.notifications.clone();
+    if matches!(&audience, Audience::Users(ids) if ids.is_empty()) {
+        return;
+    }
+    let pool = state.db_pool.clone();
+    tokio::spawn(async move {
+        let recipients = match recipients(&pool, &audience).await {
+            Ok(recipients) => recipients,
+            Err(e) => {
+                tracing::warn!("Cannot find recipients of {}: {}", event.as_str(), e);
+                return;
+            }
+        };
+        if recipients.is_empty() {
+            return;
+        }
+        let message = notifier.message(event, &vars);
+        let inbox: Vec<Uuid> = recipients
+            .iter()
+            .filter(|r| !r.preferences.muted_events.contains(&event))
+            .map(|r| r.id)
+            .collect();
+        if let Err(e) = notification_center::store(&pool, &message, &inbox).await {
+            tracing::warn!("Cannot store notification {}: {:?}", event.as_str(), e);
+        }
+        notifier.deliver(&message, &recipients).await;
+    });
+}
+
+// ============================================================================
+// Pending extractions
+// ============================================================================
+
+/// Notify reviewers of extractions queued since `since`; returns the time
+/// to check from next
+pub async fn notify_pending_extractions(
+    state: &AppState,
+    since: chrono::DateTime<chrono::Utc>,
+) -> Result<chrono::DateTime<chrono::Utc>, AppError> {
+    let now: chrono::DateTime<chrono::Utc> = sqlx::query_scalar("SELECT NOW()")
+        .fetch_one(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to read database time: {e}")))?;
+    let documents: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT d.title, COUNT(*) FROM extraction_queue eq \
+         JOIN documents d ON d.id = eq.document_id \
+         WHERE eq.status = 'pending' AND eq.created_at > $1 AND eq.created_at <= $2 \
+         GROUP BY d.title ORDER BY COUNT(*) DESC, d.title",
+    )
+    .bind(since)
+    .bind(now)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to count pending extractions: {e}")))?;
+
+    let count: i64 = documents.iter().map(|(_, n)| n).sum();
+    if count > 0 {
+        notify(
+            state,
+            NotificationEvent::ExtractionsPending,
+            vec![
+                ("count", count.to_string()),
+                ("documents", list_titles(&documents)),
+                (LINK_VAR, "/api/v1/verify/pending".to_string()),
+            ],
+            Audience::Reviewers,
+        );
+    }
+    Ok(now)
+}
+
+/// Comma-separated titles, the rest summarized as "and N more"
+fn list_titles(documents: &[(String, i64)]) -> String {
+    let mut titles: Vec<String> = documents
+        .iter()
+        .take(MAX_LISTED_DOCUMENTS)
+        .map(|(title, n)| format!("{title} ({n})"))
+        .collect();
+    if documents.len() > MAX_LISTED_DOCUMENTS {
+        titles.push(format!(
+            "and {} more",
+            documents.len() - MAX_LISTED_DOCUMENTS
+        ));
+    }
+    titles.join(", ")
+}
+
+/// Periodically tell reviewers about new pending extractions
+///
+/// Does nothing without a check interval.
+pub fn spawn_pending_job(state: Arc<AppState>) {
+    let Some(interval) = state.notifications.policy().pending_check_interval else {
+        tracing::info!("Pending extraction notifications disabled");
+        return;
+    };
+    tracing::info!(
+        "Notifying reviewers of new extractions every {}s",
+        interval.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let mut since = chrono::Utc::now();
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match notify_pending_extractions(&state, since).await {
+                Ok(next) => since = next,
+                Err(e) => tracing::warn!("Pending extraction notification failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Channel recording what it was asked to deliver
+    #[derive(Default)]
+    struct RecordingChannel {
+        delivered: Mutex<Vec<(String, Vec<Uuid>)>>,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for RecordingChannel {
+        fn kind(&self) -> ChannelKind {
+            ChannelKind::Slack
+        }
+
+        async fn deliver(&self, message: &Message, recipients: &[Recipient]) -> Result<(), String> {
+            self.delivered.lock().unwrap().push((
+                message.subject.clone(),
+                recipients.iter().map(|r| r.id).collect(),
+            ));
+            Ok(())
+        }
+    }
+
+    fn recipient(preferences: NotificationPreferences) -> Recipient {
+        Recipient {
+            id: Uuid::new_v4(),
+            email: "kim@example.com".to_string(),
+            name: "김인사".to_string(),
+            preferences,
+        }
+    }
+
+    #[test]
+    fn test_event_names_round_trip() {
+        for event in NotificationEvent::ALL {
+            assert_eq!(NotificationEvent::parse(event.as_str()), Some(event));
+            assert_eq!(
+                serde_json::to_value(event).unwrap(),
+                serde_json::json!(event.as_str())
+            );
+        }
+        assert_eq!(NotificationEvent::parse("document_deleted"), None);
+    }
+
+    #[test]
+    fn test_render_leaves_unknown_placeholders() {
+        let text = render(
+            "{requester} asked for \"{document}\" ({reason})",
+            &[
+                ("requester", "김인사".to_string()),
+                ("document", "취업규칙".to_string()),
+            ],
+        );
+        assert_eq!(text, "김인사 asked for \"취업규칙\" ({reason})");
+    }
+
+    #[test]
+    fn test_templates_override_defaults() {
+        let mut policy = NotificationPolicy::default();
+        policy.templates.insert(
+            NotificationEvent::JobFailed,
+            Template::new("작업 실패: {job}", "{error}"),
+        );
+        let notifier = Notifier::from_policy(policy);
+        let vars = [
+            ("job", "Export".to_string()),
+            ("error", "disk full".to_string()),
+            (LINK_VAR, "/api/v1/exports/42".to_string()),
+        ];
+
+        let message = notifier.message(NotificationEvent::JobFailed, &vars);
+        assert_eq!(message.subject, "작업 실패: Export");
+        assert_eq!(message.body, "disk full");
+        assert_eq!(message.link.as_deref(), Some("/api/v1/exports/42"));
+
+        let message = notifier.message(NotificationEvent::DocumentsStale, &[]);
+        assert_eq!(message.subject, "[OTL] {count} documents need attention");
+    }
+
+    #[test]
+    fn test_preferences_filter_channels_and_events() {
+        let prefs = NotificationPreferences {
+            email: false,
+            muted_events: vec![NotificationEvent::DocumentsStale],
+            ..Default::default()
+        };
+        assert!(!prefs.wants(NotificationEvent::JobFailed, ChannelKind::Email));
+        assert!(prefs.wants(NotificationEvent::JobFailed, ChannelKind::Slack));
+        assert!(!prefs.wants(NotificationEvent::DocumentsStale, ChannelKind::Slack));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_skips_recipients_without_the_channel() {
+        let channel = Arc::new(RecordingChannel::default());
+        let notifier =
+            Notifier::from_policy(NotificationPolicy::default()).with_channel(channel.clone());
+        let slack = recipient(NotificationPreferences::default());
+        let email_only = recipient(NotificationPreferences {
+            slack: false,
+            ..Default::default()
+        });
+
+        let message = notifier.message(NotificationEvent::AccessRequested, &[]);
+        notifier
+            .deliver(&message, &[slack.clone(), email_only.clone()])
+            .await;
+        notifier.deliver(&message, &[email_only]).await;
+
+        let delivered = channel.delivered.lock().unwrap();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].1, vec![slack.id]);
+    }
+
+    #[test]
+    fn test_slack_text_mentions_members() {
+        let message = Message {
+            event: NotificationEvent::ExtractionsPending,
+            subject: "3 new extractions".to_string(),
+            body: "취업규칙 (3)".to_string(),
+            link: None,
+        };
+        let member = recipient(NotificationPreferences {
+            slack_member_id: Some("U024BE7LH".to_string()),
+            ..Default::default()
+        });
+        let anonymous = recipient(NotificationPreferences::default());
+
+        assert_eq!(
+            slack_text(&message, &[member, anonymous]),
+            "*3 new extractions*\n취업규칙 (3)\n<@U024BE7LH>"
+        );
+    }
+
+    #[test]
+    fn test_list_titles_summarizes_the_rest() {
+        let documents: Vec<(String, i64)> = (1..=7).map(|i| (format!("규정 {i}"), 1)).collect();
+        assert_eq!(
+            list_titles(&documents),
+            "규정 1 (1), 규정 2 (1), 규정 3 (1), 규정 4 (1), 규정 5 (1), and 2 more"
+        );
+    }
+}
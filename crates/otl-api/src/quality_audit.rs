@@ -0,0 +1,544 @@
+//! Quality audit of auto-approved extractions
+//!
+//! Pending extractions are approved without review by the auto-approval
+//! policy (see [`crate::auto_approve`]), which falls back to the threshold
+//! kept here. A share of these auto-approved items is sampled into an
+//! audit queue, where auditors mark them correct or incorrect; incorrect
+//! ones are rejected. The audited share estimates the error rate of
+//! auto-approval. When the error rate of the audits since the last
+//! threshold change exceeds the limit, the threshold is raised one step so
+//! fewer items are auto-approved.
+//!
+//! Sampling is decided by a hash of the extraction ID, so repeated runs
+//! never sample an item twice and the sampled share stays at the rate.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::auto_approve;
+use crate::error::AppError;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Default confidence at which pending extractions are auto-approved
+const DEFAULT_THRESHOLD: f32 = 0.95;
+
+/// Highest threshold automatic tightening goes to
+const MAX_THRESHOLD: f32 = 0.99;
+
+/// Default share of auto-approved extractions sampled for audit
+const DEFAULT_SAMPLE_RATE: f64 = 0.05;
+
+/// Default error rate above which the threshold is raised
+const DEFAULT_MAX_ERROR_RATE: f64 = 0.05;
+
+/// Default audits needed before the threshold is adjusted
+const DEFAULT_MIN_AUDITS: i64 = 20;
+
+/// Default threshold increase per adjustment
+const DEFAULT_THRESHOLD_STEP: f32 = 0.01;
+
+/// Default days of auto-approvals considered
+const DEFAULT_WINDOW_DAYS: u32 = 30;
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// Auto-approval and audit settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityAuditPolicy {
+    /// Threshold used until one is recorded in `auto_approve_thresholds`
+    pub threshold: f32,
+    /// Share of auto-approved extractions sampled for audit
+    pub sample_rate: f64,
+    /// Error rate above which the threshold is raised
+    pub max_error_rate: f64,
+    /// Audits since the last threshold change needed to adjust it
+    pub min_audits: i64,
+    /// Threshold increase per adjustment
+    pub threshold_step: f32,
+    /// Days of auto-approvals sampled and reported
+    pub window_days: u32,
+    /// How often auto-approval and sampling run (`None` = only on demand)
+    pub interval: Option<Duration>,
+}
+
+impl Default for QualityAuditPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            max_error_rate: DEFAULT_MAX_ERROR_RATE,
+            min_audits: DEFAULT_MIN_AUDITS,
+            threshold_step: DEFAULT_THRESHOLD_STEP,
+            window_days: DEFAULT_WINDOW_DAYS,
+            interval: None,
+        }
+    }
+}
+
+impl QualityAuditPolicy {
+    /// Policy from `VERIFY_AUTO_APPROVE_THRESHOLD`, `AUDIT_SAMPLE_RATE`,
+    /// `AUDIT_MAX_ERROR_RATE`, `AUDIT_MIN_SAMPLES`, `AUDIT_THRESHOLD_STEP`,
+    /// `AUDIT_WINDOW_DAYS` and `AUDIT_INTERVAL_SECS` (0 or unset: on demand
+    /// only)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(value) = std::env::var("VERIFY_AUTO_APPROVE_THRESHOLD") {
+            match value.parse::<f32>() {
+                Ok(t) if (0.0..=1.0).contains(&t) => policy.threshold = t,
+                _ => tracing::warn!("Ignoring invalid VERIFY_AUTO_APPROVE_THRESHOLD: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("AUDIT_SAMPLE_RATE") {
+            match value.parse::<f64>() {
+                Ok(rate) if (0.0..=1.0).contains(&rate) => policy.sample_rate = rate,
+                _ => tracing::warn!("Ignoring invalid AUDIT_SAMPLE_RATE: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("AUDIT_MAX_ERROR_RATE") {
+            match value.parse::<f64>() {
+                Ok(rate) if (0.0..=1.0).contains(&rate) => policy.max_error_rate = rate,
+                _ => tracing::warn!("Ignoring invalid AUDIT_MAX_ERROR_RATE: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("AUDIT_MIN_SAMPLES") {
+            match value.parse::<i64>() {
+                Ok(min) if min > 0 => policy.min_audits = min,
+                _ => tracing::warn!("Ignoring invalid AUDIT_MIN_SAMPLES: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("AUDIT_THRESHOLD_STEP") {
+            match value.parse::<f32>() {
+                Ok(step) if step > 0.0 && step < 1.0 => policy.threshold_step = step,
+                _ => tracing::warn!("Ignoring invalid AUDIT_THRESHOLD_STEP: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("AUDIT_WINDOW_DAYS") {
+            match value.parse::<u32>() {
+                Ok(days) if days > 0 => policy.window_days = days,
+                _ => tracing::warn!("Ignoring invalid AUDIT_WINDOW_DAYS: {}", value),
+            }
+        }
+        if let Ok(secs) = std::env::var("AUDIT_INTERVAL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(0) => policy.interval = None,
+                Ok(secs) => policy.interval = Some(Duration::from_secs(secs)),
+                Err(_) => tracing::warn!("Ignoring invalid AUDIT_INTERVAL_SECS: {}", secs),
+            }
+        }
+        policy
+    }
+
+    /// Threshold after audits found `incorrect` errors in `audited` items,
+    /// if it should be raised
+    pub fn tightened_threshold(&self, current: f32, audited: i64, incorrect: i64) -> Option<f32> {
+        if audited < self.min_audits || current >= MAX_THRESHOLD {
+            return None;
+        }
+        let error_rate = incorrect as f64 / audited as f64;
+        (error_rate > self.max_error_rate)
+            .then(|| (current + self.threshold_step).min(MAX_THRESHOLD))
+    }
+}
+
+/// 95% Wilson score interval of a proportion of `hits` in `n`
+pub fn wilson_interval(hits: i64, n: i64) -> Option<(f64, f64)> {
+    if n <= 0 {
+        return None;
+    }
+    const Z: f64 = 1.96;
+    let n = n as f64;
+    let p = hits as f64 / n;
+    let denominator = 1.0 + Z * Z / n;
+    let center = p + Z * Z / (2.0 * n);
+    let margin = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt();
+    Some((
+        ((center - margin) / denominator).max(0.0),
+        ((center + margin) / denominator).min(1.0),
+    ))
+}
+
+// ============================================================================
+// Threshold
+// ============================================================================
+
+/// Change of the auto-approve threshold
+#[derive(Debug, Clone, Serialize, ToSchema, sqlx::FromRow)]
+pub struct ThresholdChange {
+    #[schema(example = 0.96)]
+    pub threshold: f32,
+    /// Why it changed
+    #[schema(example = "Audit error rate 8.0% over 25 audits exceeds 5.0%")]
+    pub reason: Option<String>,
+    /// Admin who set it (absent for automatic changes)
+    pub changed_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Threshold changes, newest first
+pub async fn threshold_history(state: &AppState) -> Result<Vec<ThresholdChange>, AppError> {
+    sqlx::query_as(
+        r#"
+        SELECT threshold, reason, changed_by, created_at
+        FROM auto_approve_thresholds
+        ORDER BY created_at DESC
+        LIMIT 50
+        "#,
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch threshold history: {e}")))
+}
+
+/// Auto-approve threshold in effect
+pub async fn current_threshold(
+    state: &AppState,
+    policy: &QualityAuditPolicy,
+) -> Result<f32, AppError> {
+    let threshold: Option<f32> = sqlx::query_scalar(
+        "SELECT threshold FROM auto_approve_thresholds ORDER BY created_at DESC LIMIT 1",
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch auto-approve threshold: {e}")))?;
+    Ok(threshold.unwrap_or(policy.threshold))
+}
+
+/// Record a new auto-approve threshold
+pub async fn set_threshold(
+    state: &AppState,
+    threshold: f32,
+    reason: &str,
+    changed_by: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO auto_approve_thresholds (threshold, reason, changed_by)
+        VALUES ($1, $2, $3)
+        "#,
+    )
+    .bind(threshold)
+    .bind(reason)
+    .bind(changed_by)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to record auto-approve threshold: {e}")))?;
+    Ok(())
+}
+
+/// Raise the threshold if the audits since its last change found too many
+/// errors; returns the new threshold
+pub async fn adjust_threshold(
+    state: &AppState,
+    policy: &QualityAuditPolicy,
+) -> Result<Option<f32>, AppError> {
+    let current = current_threshold(state, policy).await?;
+    let (audited, incorrect): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE status <> 'pending'),
+            COUNT(*) FILTER (WHERE status = 'incorrect')
+        FROM extraction_audits
+        WHERE audited_at > COALESCE(
+            (SELECT MAX(created_at) FROM auto_approve_thresholds),
+            '-infinity'::timestamptz
+        )
+        "#,
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to count audits: {e}")))?;
+
+    let Some(raised) = policy.tightened_threshold(current, audited, incorrect) else {
+        return Ok(None);
+    };
+    let reason = format!(
+        "Audit error rate {:.1}% over {} audits exceeds {:.1}%",
+        incorrect as f64 / audited as f64 * 100.0,
+        audited,
+        policy.max_error_rate * 100.0
+    );
+    set_threshold(state, raised, &reason, None).await?;
+    tracing::warn!(
+        "Raised auto-approve threshold from {:.2} to {:.2}: {}",
+        current,
+        raised,
+        reason
+    );
+    Ok(Some(raised))
+}
+
+// ============================================================================
+// Auto-approval and sampling
+// ============================================================================
+
+/// What an auto-approval and sampling run did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct AuditRun {
+    /// Threshold used for items no auto-approval rule matches
+    pub threshold: f32,
+    /// Pending extractions auto-approved
+    pub auto_approved: u64,
+    /// Auto-approved extractions added to the audit queue
+    pub sampled: u64,
+}
+
+/// Add the sampled share of recent auto-approved extractions to the audit
+/// queue
+pub async fn sample_auto_approved(
+    state: &AppState,
+    policy: &QualityAuditPolicy,
+) -> Result<u64, AppError> {
+    let since = Utc::now() - chrono::Duration::days(i64::from(policy.window_days));
+    // First 32 bits of md5(id) as a uniform number in [0, 1)
+    let result = sqlx::query(
+        r#"
+        INSERT INTO extraction_audits (extraction_id, confidence)
+        SELECT eq.id, eq.confidence_score
+        FROM extraction_queue eq
+        WHERE eq.auto_approved
+          AND eq.status = 'approved'
+          AND eq.reviewed_at >= $1
+          AND ('x' || substr(md5(eq.id::text), 1, 8))::bit(32)::bigint / 4294967296.0 < $2
+        ON CONFLICT (extraction_id) DO NOTHING
+        "#,
+    )
+    .bind(since)
+    .bind(policy.sample_rate)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to sample auto-approved extractions: {e}")))?;
+    Ok(result.rows_affected())
+}
+
+/// Auto-approve pending extractions, then sample auto-approved ones
+pub async fn run(state: &AppState, policy: &QualityAuditPolicy) -> Result<AuditRun, AppError> {
+    let threshold = current_threshold(state, policy).await?;
+    let auto_approved = auto_approve::approve_pending(state, threshold).await?;
+    let sampled = sample_auto_approved(state, policy).await?;
+    Ok(AuditRun {
+        threshold,
+        auto_approved,
+        sampled,
+    })
+}
+
+/// Run [`run`] periodically in the background
+///
+/// Does nothing if the policy has no interval.
+pub fn spawn_audit_job(state: Arc<AppState>, policy: QualityAuditPolicy) {
+    let Some(interval) = policy.interval else {
+        tracing::info!("Auto-approval and audit sampling job disabled");
+        return;
+    };
+    tracing::info!(
+        "Auto-approving and sampling extractions every {}s",
+        interval.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match run(&state, &policy).await {
+                Ok(report) if report.auto_approved > 0 || report.sampled > 0 => tracing::info!(
+                    "Auto-approved {} extractions at {:.2}, sampled {} for audit",
+                    report.auto_approved,
+                    report.threshold,
+                    report.sampled
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Auto-approval run failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Audit results
+// ============================================================================
+
+/// Record an auditor's verdict on a sampled extraction
+///
+/// An incorrect auto-approval is rejected. The threshold is adjusted
+/// afterwards; returns the new threshold if it was raised.
+pub async fn record_audit(
+    state: &AppState,
+    policy: &QualityAuditPolicy,
+    extraction_id: uuid::Uuid,
+    auditor_id: &str,
+    correct: bool,
+    notes: Option<&str>,
+) -> Result<Option<f32>, AppError> {
+    let mut tx = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to start transaction: {e}")))?;
+
+    let status: Option<String> = sqlx::query_scalar(
+        "SELECT status FROM extraction_audits WHERE extraction_id = $1 FOR UPDATE",
+    )
+    .bind(extraction_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch audit: {e}")))?;
+    let status = status.ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Extraction {extraction_id} is not in the audit queue"
+        ))
+    })?;
+    if status != "pending" {
+        return Err(AppError::BadRequest(format!(
+            "Extraction {extraction_id} was already audited ({status})"
+        )));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE extraction_audits
+        SET status = $1, auditor_id = $2, notes = $3, audited_at = NOW()
+        WHERE extraction_id = $4
+        "#,
+    )
+    .bind(if correct { "correct" } else { "incorrect" })
+    .bind(auditor_id)
+    .bind(notes)
+    .bind(extraction_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to record audit: {e}")))?;
+
+    if !correct {
+        sqlx::query(
+            r#"
+            UPDATE extraction_queue
+            SET status = 'rejected',
+                reviewer_id = $1,
+                review_notes = $2,
+                reviewed_at = NOW()
+            WHERE id = $3
+            "#,
+        )
+        .bind(auditor_id)
+        .bind(format!("AUDIT: {}", notes.unwrap_or_default()))
+        .bind(extraction_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to reject extraction: {e}")))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to commit transaction: {e}")))?;
+
+    adjust_threshold(state, policy).await
+}
+
+/// Auto-approval error estimate from the audits of a period
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuditStats {
+    /// Days of auto-approvals counted
+    pub window_days: u32,
+    /// Auto-approve threshold in effect
+    pub threshold: f32,
+    /// Extractions auto-approved in the period
+    pub auto_approved: i64,
+    /// Auto-approved extractions sampled for audit
+    pub sampled: i64,
+    /// Sampled extractions audited
+    pub audited: i64,
+    /// Audited extractions found incorrect
+    pub incorrect: i64,
+    /// Share of audited extractions found incorrect
+    pub error_rate: Option<f64>,
+    /// 95% confidence interval of the error rate
+    pub error_rate_interval: Option<(f64, f64)>,
+    /// Estimated incorrect auto-approvals in the period
+    pub estimated_errors: Option<f64>,
+    /// Threshold changes, newest first
+    pub threshold_history: Vec<ThresholdChange>,
+}
+
+/// Audit statistics of the policy window
+pub async fn audit_stats(
+    state: &AppState,
+    policy: &QualityAuditPolicy,
+) -> Result<AuditStats, AppError> {
+    let since = Utc::now() - chrono::Duration::days(i64::from(policy.window_days));
+    let (auto_approved, sampled, audited, incorrect): (i64, i64, i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            COUNT(*),
+            COUNT(a.id),
+            COUNT(a.id) FILTER (WHERE a.status <> 'pending'),
+            COUNT(a.id) FILTER (WHERE a.status = 'incorrect')
+        FROM extraction_queue eq
+        LEFT JOIN extraction_audits a ON a.extraction_id = eq.id
+        WHERE eq.auto_approved AND eq.reviewed_at >= $1
+        "#,
+    )
+    .bind(since)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch audit statistics: {e}")))?;
+
+    let error_rate = (audited > 0).then(|| incorrect as f64 / audited as f64);
+    Ok(AuditStats {
+        window_days: policy.window_days,
+        threshold: current_threshold(state, policy).await?,
+        auto_approved,
+        sampled,
+        audited,
+        incorrect,
+        error_rate,
+        error_rate_interval: wilson_interval(incorrect, audited),
+        estimated_errors: error_rate.map(|rate| rate * auto_approved as f64),
+        threshold_history: threshold_history(state).await?,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tightened_threshold() {
+        let policy = QualityAuditPolicy::default();
+
+        // Too few audits, or error rate within the limit
+        assert_eq!(policy.tightened_threshold(0.95, 10, 5), None);
+        assert_eq!(policy.tightened_threshold(0.95, 40, 2), None);
+
+        let raised = policy.tightened_threshold(0.95, 40, 3).unwrap();
+        assert!((raised - 0.96).abs() < 1e-6);
+        assert_eq!(
+            policy.tightened_threshold(0.985, 40, 10),
+            Some(MAX_THRESHOLD)
+        );
+        assert_eq!(policy.tightened_threshold(MAX_THRESHOLD, 40, 10), None);
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        assert_eq!(wilson_interval(0, 0), None);
+
+        let (low, high) = wilson_interval(2, 40).unwrap();
+        assert!(low > 0.0 && low < 0.05);
+        assert!(high > 0.05 && high < 0.2);
+
+        let (low, high) = wilson_interval(0, 20).unwrap();
+        assert_eq!(low, 0.0);
+        assert!(high > 0.1 && high < 0.2);
+    }
+}
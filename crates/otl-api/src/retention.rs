@@ -0,0 +1,300 @@
+//! Soft-delete retention and purge job
+//!
+//! Deleting a document only sets `deleted_at` (its vectors are removed at
+//! once so it drops out of search). During the retention window it can be
+//! restored; afterwards the purge job removes it for good: the database row
+//! (chunks and extraction queue entries cascade), any vectors left over from
+//! a failed delete, the stored original (in the blob store or, for older
+//! documents, the storage directory), the recorded lineage, the data key of
+//! encrypted content and the document's graph provenance.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use crate::state::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use otl_core::blob;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Default days a deleted document can be restored
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// Default seconds between purge runs
+const DEFAULT_PURGE_INTERVAL_SECS: u64 = 3600;
+
+/// Documents purged per database round trip
+const PURGE_BATCH_SIZE: i64 = 100;
+
+/// How long soft-deleted documents are kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Days a deleted document stays restorable before it is purged
+    pub retention_days: u32,
+    /// Time between purge runs (`None` disables the purge job)
+    pub purge_interval: Option<Duration>,
+    /// Directory holding stored document files; files outside it are never
+    /// removed (`None` leaves all files in place)
+    pub storage_dir: Option<PathBuf>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            retention_days: DEFAULT_RETENTION_DAYS,
+            purge_interval: Some(Duration::from_secs(DEFAULT_PURGE_INTERVAL_SECS)),
+            storage_dir: None,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Policy from `DOCUMENT_RETENTION_DAYS`, `DOCUMENT_PURGE_INTERVAL_SECS`
+    /// (0 disables the purge job) and `DOCUMENT_STORAGE_DIR`
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(days) = std::env::var("DOCUMENT_RETENTION_DAYS") {
+            match days.parse::<u32>() {
+                Ok(days) => policy.retention_days = days,
+                Err(_) => tracing::warn!("Ignoring invalid DOCUMENT_RETENTION_DAYS: {}", days),
+            }
+        }
+        if let Ok(secs) = std::env::var("DOCUMENT_PURGE_INTERVAL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(0) => policy.purge_interval = None,
+                Ok(secs) => policy.purge_interval = Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    tracing::warn!("Ignoring invalid DOCUMENT_PURGE_INTERVAL_SECS: {}", secs)
+                }
+            }
+        }
+        if let Ok(dir) = std::env::var("DOCUMENT_STORAGE_DIR") {
+            policy.storage_dir = Some(dir.into());
+        }
+        policy
+    }
+
+    /// Documents deleted before this time are past retention
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - ChronoDuration::days(i64::from(self.retention_days))
+    }
+
+    /// Whether a document deleted at `deleted_at` can still be restored
+    pub fn is_restorable(&self, deleted_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        deleted_at > self.cutoff(now)
+    }
+
+    /// The stored file at `file_path`, if it lies inside the storage directory
+    fn stored_file(&self, file_path: &str) -> Option<PathBuf> {
+        let dir = self.storage_dir.as_ref()?.canonicalize().ok()?;
+        let path = Path::new(file_path);
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            dir.join(path)
+        };
+        let path = path.canonicalize().ok()?;
+        (path.starts_with(&dir) && path.is_file()).then_some(path)
+    }
+}
+
+/// What a purge run removed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PurgeReport {
+    /// Documents removed from the database
+    pub documents: usize,
+    /// Vectors that were still stored
+    pub vectors: u64,
+    /// Stored files removed
+    pub files: usize,
+    /// Graph records (provenance, orphaned triples, structure nodes) removed
+    pub graph_records: usize,
+}
+
+/// Permanently remove documents deleted before the retention cutoff
+///
+/// A document whose vectors or graph records cannot be removed is kept and
+/// retried on the next run.
+pub async fn purge_expired(
+    state: &AppState,
+    policy: &RetentionPolicy,
+) -> Result<PurgeReport, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct ExpiredDocument {
+        id: Uuid,
+        file_path: String,
+    }
+
+    let cutoff = policy.cutoff(Utc::now());
+    let vector_backend = state.vector_backend.read().await.clone();
+    let graph_db = state.graph_db.read().await.clone();
+    let mut report = PurgeReport::default();
+    let mut failed: Vec<Uuid> = Vec::new();
+
+    loop {
+        let expired: Vec<ExpiredDocument> = sqlx::query_as(
+            "SELECT id, file_path FROM documents
+             WHERE deleted_at IS NOT NULL AND deleted_at < $1 AND NOT (id = ANY($2))
+             ORDER BY deleted_at
+             LIMIT $3",
+        )
+        .bind(cutoff)
+        .bind(&failed)
+        .bind(PURGE_BATCH_SIZE)
+        .fetch_all(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch expired documents: {e}")))?;
+        if expired.is_empty() {
+            break;
+        }
+
+        for doc in expired {
+            if let Some(backend) = &vector_backend {
+                match backend.delete_by_document(doc.id).await {
+                    Ok(count) => report.vectors += count,
+                    Err(e) => {
+                        tracing::warn!("Keeping document {} for the next purge: {}", doc.id, e);
+                        failed.push(doc.id);
+                        continue;
+                    }
+                }
+            }
+            if let Some(graph_db) = &graph_db {
+                match graph_db.purge_document(doc.id).await {
+                    Ok(count) => report.graph_records += count,
+                    Err(e) => {
+                        tracing::warn!("Keeping document {} for the next purge: {}", doc.id, e);
+                        failed.push(doc.id);
+                        continue;
+                    }
+                }
+            }
+
+            let result = sqlx::query("DELETE FROM documents WHERE id = $1 AND deleted_at < $2")
+                .bind(doc.id)
+                .bind(cutoff)
+                .execute(&state.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to purge document: {e}")))?;
+            if result.rows_affected() == 0 {
+                // Restored meanwhile
+                continue;
+            }
+            report.documents += 1;
+
+            sqlx::query("DELETE FROM document_lineage WHERE document_id = $1")
+                .bind(doc.id)
+                .execute(&state.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to purge lineage: {e}")))?;
+            // Dropping the data key also shreds any sealed copy left behind
+            sqlx::query("DELETE FROM document_keys WHERE document_id = $1")
+                .bind(doc.id)
+                .execute(&state.db_pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to purge data key: {e}")))?;
+
+            match remove_original(state, doc.id).await {
+                Ok(removed) => report.files += usize::from(removed),
+                Err(e) => tracing::warn!("Failed to remove original of {}: {}", doc.id, e),
+            }
+            if let Some(path) = policy.stored_file(&doc.file_path) {
+                match tokio::fs::remove_file(&path).await {
+                    Ok(()) => report.files += 1,
+                    Err(e) => tracing::warn!("Failed to remove {}: {}", path.display(), e),
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Remove a document's original from the blob store; false if none was stored
+async fn remove_original(state: &AppState, id: Uuid) -> otl_core::Result<bool> {
+    let key = blob::original_key(id);
+    if !state.blob_store.exists(&key).await? {
+        return Ok(false);
+    }
+    state.blob_store.delete(&key).await?;
+    Ok(true)
+}
+
+/// Run [`purge_expired`] periodically in the background
+///
+/// Does nothing if the policy has no purge interval.
+pub fn spawn_purge_job(state: Arc<AppState>, policy: RetentionPolicy) {
+    let Some(interval) = policy.purge_interval else {
+        tracing::info!("Document purge job disabled");
+        return;
+    };
+    tracing::info!(
+        "Purging deleted documents after {} days, checking every {}s",
+        policy.retention_days,
+        interval.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match purge_expired(&state, &policy).await {
+                Ok(report) if report.documents > 0 => tracing::info!(
+                    "Purged {} documents ({} vectors, {} files, {} graph records)",
+                    report.documents,
+                    report.vectors,
+                    report.files,
+                    report.graph_records
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Document purge failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_window() {
+        let policy = RetentionPolicy {
+            retention_days: 7,
+            ..Default::default()
+        };
+        let now = Utc::now();
+        assert_eq!(policy.cutoff(now), now - ChronoDuration::days(7));
+        assert!(policy.is_restorable(now - ChronoDuration::days(6), now));
+        assert!(!policy.is_restorable(now - ChronoDuration::days(8), now));
+    }
+
+    #[test]
+    fn test_only_files_inside_the_storage_dir_are_removed() {
+        let root = std::env::temp_dir().join(format!("otl-retention-{}", Uuid::new_v4()));
+        let storage = root.join("storage");
+        std::fs::create_dir_all(&storage).unwrap();
+        std::fs::write(storage.join("doc.pdf"), b"%PDF-").unwrap();
+        std::fs::write(root.join("other.pdf"), b"%PDF-").unwrap();
+
+        let mut policy = RetentionPolicy::default();
+        assert_eq!(policy.stored_file("doc.pdf"), None);
+
+        policy.storage_dir = Some(storage.clone());
+        assert!(policy.stored_file("doc.pdf").is_some());
+        assert!(policy
+            .stored_file(storage.join("doc.pdf").to_str().unwrap())
+            .is_some());
+        assert_eq!(policy.stored_file("../other.pdf"), None);
+        assert_eq!(policy.stored_file("missing.pdf"), None);
+
+        std::fs::remove_dir_all(root).ok();
+    }
+}
@@ -0,0 +1,973 @@
+//! Reviewer assignment and review locks for the verification queue
+//!
+//! Pending extractions are assigned to reviewers (active admins and
+//! editors) in turn within the department of their document; items of a
+//! department without reviewers are shared by all reviewers. Assignment
+//! runs on demand (`POST /api/v1/verify/assign`) or periodically, and an
+//! admin can reassign single items.
+//!
+//! A reviewer claims an item before reviewing it: the item moves to
+//! `in_review`, locked to the reviewer until the lock expires, so two
+//! reviewers never decide the same item. Expired locks are released and
+//! the item becomes pending again.
+//!
+//! Extractions from documents of some classifications (access levels) can
+//! require several independent approvals. Each decision is recorded in
+//! `extraction_reviews`; the item stays pending until enough reviewers
+//! agree, and goes to adjudication by an admin as soon as two decisions
+//! differ. Agreement between the first two reviewers of each item is
+//! reported as Cohen's kappa.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::{AppError, ErrorCode};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Default seconds a claimed item stays locked to its reviewer
+const DEFAULT_LOCK_TTL_SECS: u64 = 900;
+
+/// Most items assigned per run
+const MAX_ASSIGN_PER_RUN: i64 = 1000;
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// Review lock and assignment settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewPolicy {
+    /// How long a claimed item stays locked to its reviewer
+    pub lock_ttl: Duration,
+    /// How often unassigned items are assigned (`None` = only on demand)
+    pub assign_interval: Option<Duration>,
+    /// Approvals required per document access level (1 when not listed)
+    pub required_approvals: BTreeMap<String, u32>,
+}
+
+impl Default for ReviewPolicy {
+    fn default() -> Self {
+        Self {
+            lock_ttl: Duration::from_secs(DEFAULT_LOCK_TTL_SECS),
+            assign_interval: None,
+            required_approvals: BTreeMap::new(),
+        }
+    }
+}
+
+impl ReviewPolicy {
+    /// Policy from `VERIFY_LOCK_TTL_SECS`, `VERIFY_ASSIGN_INTERVAL_SECS`
+    /// (0 or unset: on demand only) and `VERIFY_REQUIRED_APPROVALS` (JSON
+    /// object of access level to approvals, e.g. `{"restricted":2}`)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(secs) = std::env::var("VERIFY_LOCK_TTL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(secs) if secs > 0 => policy.lock_ttl = Duration::from_secs(secs),
+                _ => tracing::warn!("Ignoring invalid VERIFY_LOCK_TTL_SECS: {}", secs),
+            }
+        }
+        if let Ok(secs) = std::env::var("VERIFY_ASSIGN_INTERVAL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(0) => policy.assign_interval = None,
+                Ok(secs) => policy.assign_interval = Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    tracing::warn!("Ignoring invalid VERIFY_ASSIGN_INTERVAL_SECS: {}", secs)
+                }
+            }
+        }
+        if let Ok(value) = std::env::var("VERIFY_REQUIRED_APPROVALS") {
+            match serde_json::from_str::<BTreeMap<String, u32>>(&value) {
+                Ok(levels) if levels.values().all(|n| *n > 0) => policy.required_approvals = levels,
+                _ => tracing::warn!("Ignoring invalid VERIFY_REQUIRED_APPROVALS: {}", value),
+            }
+        }
+        policy
+    }
+
+    /// Approvals an extraction from a document of `access_level` needs
+    pub fn required_approvals(&self, access_level: &str) -> u32 {
+        self.required_approvals
+            .get(access_level)
+            .copied()
+            .unwrap_or(1)
+    }
+}
+
+// ============================================================================
+// Assignment
+// ============================================================================
+
+/// User who can review extractions
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct Reviewer {
+    pub id: String,
+    pub department: Option<String>,
+}
+
+/// Unassigned pending item, in review order
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct UnassignedItem {
+    pub id: Uuid,
+    /// Department of the item's document
+    pub department: Option<String>,
+    /// Reviewers who already decided the item (never assigned it again)
+    pub reviewed_by: Vec<String>,
+}
+
+/// Assign `items` to `reviewers` in turn
+///
+/// Reviewers of an item's department take turns; items of a department
+/// without reviewers go to all reviewers in turn. Each rotation continues
+/// after the reviewer it reached last: `recent` lists earlier assignments
+/// (department, reviewer) from oldest to newest. An item is never given
+/// to a reviewer who already decided it; it stays unassigned if no
+/// reviewer of its rotation is left.
+pub fn round_robin(
+    items: &[UnassignedItem],
+    reviewers: &[Reviewer],
+    recent: &[(Option<String>, String)],
+) -> Vec<(Uuid, String)> {
+    let mut all: Vec<&str> = reviewers.iter().map(|r| r.id.as_str()).collect();
+    all.sort_unstable();
+    all.dedup();
+    if all.is_empty() {
+        return Vec::new();
+    }
+
+    let mut by_department: HashMap<&str, Vec<&str>> = HashMap::new();
+    for reviewer in reviewers {
+        if let Some(department) = reviewer.department.as_deref() {
+            by_department
+                .entry(department)
+                .or_default()
+                .push(reviewer.id.as_str());
+        }
+    }
+    for ids in by_department.values_mut() {
+        ids.sort_unstable();
+        ids.dedup();
+    }
+
+    // Rotation of a department: its own reviewers, or everyone
+    let rotation = |department: Option<&str>| {
+        department.and_then(|d| by_department.get_key_value(d).map(|(key, _)| *key))
+    };
+    let members = |key: Option<&str>| -> &Vec<&str> {
+        key.and_then(|d| by_department.get(d)).unwrap_or(&all)
+    };
+
+    let mut next: HashMap<Option<&str>, usize> = HashMap::new();
+    for (department, reviewer) in recent {
+        let key = rotation(department.as_deref());
+        if let Some(pos) = members(key).iter().position(|id| id == reviewer) {
+            next.insert(key, pos + 1);
+        }
+    }
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let key = rotation(item.department.as_deref());
+            let ids = members(key);
+            let turn = next.entry(key).or_insert(0);
+            let offset = (0..ids.len()).find(|i| {
+                !item
+                    .reviewed_by
+                    .iter()
+                    .any(|r| r == ids[(*turn + i) % ids.len()])
+            })?;
+            let reviewer = ids[(*turn + offset) % ids.len()];
+            *turn = (*turn + offset + 1) % ids.len();
+            Some((item.id, reviewer.to_string()))
+        })
+        .collect()
+}
+
+/// Items assigned by a run, per reviewer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct AssignmentReport {
+    /// Items assigned
+    pub assigned: usize,
+    /// Items assigned to each reviewer
+    pub per_reviewer: BTreeMap<String, usize>,
+}
+
+/// Active users who can review extractions
+pub async fn fetch_reviewers(state: &AppState) -> Result<Vec<Reviewer>, AppError> {
+    sqlx::query_as(
+        r#"
+        SELECT id::text AS id, department
+        FROM users
+        WHERE is_active AND role IN ('admin', 'editor')
+        "#,
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch reviewers: {e}")))
+}
+
+/// Assign unassigned pending items in turn
+pub async fn assign_pending(state: &AppState) -> Result<AssignmentReport, AppError> {
+    let reviewers = fetch_reviewers(state).await?;
+    if reviewers.is_empty() {
+        return Ok(AssignmentReport::default());
+    }
+
+    let items: Vec<UnassignedItem> = sqlx::query_as(
+        r#"
+        SELECT
+            eq.id,
+            d.department,
+            ARRAY(
+                SELECT r.reviewer_id FROM extraction_reviews r WHERE r.extraction_id = eq.id
+            ) AS reviewed_by
+        FROM extraction_queue eq
+        JOIN documents d ON eq.document_id = d.id
+        WHERE eq.status IN ('pending', 'in_review') AND eq.assigned_to IS NULL
+        ORDER BY eq.priority, eq.created_at, eq.id
+        LIMIT $1
+        "#,
+    )
+    .bind(MAX_ASSIGN_PER_RUN)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch unassigned extractions: {e}")))?;
+    if items.is_empty() {
+        return Ok(AssignmentReport::default());
+    }
+
+    // Last assignment of each department, oldest first
+    let recent: Vec<(Option<String>, String)> = sqlx::query_as(
+        r#"
+        SELECT department, assigned_to
+        FROM (
+            SELECT DISTINCT ON (d.department) d.department, eq.assigned_to, eq.assigned_at
+            FROM extraction_queue eq
+            JOIN documents d ON eq.document_id = d.id
+            WHERE eq.assigned_to IS NOT NULL AND eq.assigned_at IS NOT NULL
+            ORDER BY d.department, eq.assigned_at DESC
+        ) last
+        ORDER BY assigned_at
+        "#,
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch recent assignments: {e}")))?;
+
+    let mut report = AssignmentReport::default();
+    for (id, reviewer) in round_robin(&items, &reviewers, &recent) {
+        let result = sqlx::query(
+            r#"
+            UPDATE extraction_queue
+            SET assigned_to = $1, assigned_at = NOW()
+            WHERE id = $2 AND assigned_to IS NULL
+            "#,
+        )
+        .bind(&reviewer)
+        .bind(id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to assign extraction: {e}")))?;
+        if result.rows_affected() > 0 {
+            report.assigned += 1;
+            *report.per_reviewer.entry(reviewer).or_default() += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// Assign an item to a reviewer, replacing any earlier assignment
+pub async fn assign_to(state: &AppState, id: Uuid, reviewer_id: &str) -> Result<(), AppError> {
+    let reviewers = fetch_reviewers(state).await?;
+    if !reviewers.iter().any(|r| r.id == reviewer_id) {
+        return Err(AppError::BadRequest(format!(
+            "User {reviewer_id} is not an active reviewer"
+        )));
+    }
+
+    let status: Option<String> =
+        sqlx::query_scalar("SELECT status::text FROM extraction_queue WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.db_pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to fetch extraction: {e}")))?;
+    let status = status.ok_or_else(|| AppError::NotFound(format!("Extraction {id} not found")))?;
+    if status != "pending" && status != "in_review" {
+        return Err(AppError::BadRequest(format!(
+            "Cannot assign extraction in status: {status}"
+        )));
+    }
+
+    let reviewed: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM extraction_reviews WHERE extraction_id = $1 AND reviewer_id = $2)",
+    )
+    .bind(id)
+    .bind(reviewer_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch reviews: {e}")))?;
+    if reviewed {
+        return Err(AppError::BadRequest(format!(
+            "User {reviewer_id} already reviewed extraction {id}"
+        )));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE extraction_queue
+        SET assigned_to = $1, assigned_at = NOW()
+        WHERE id = $2
+        "#,
+    )
+    .bind(reviewer_id)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to assign extraction: {e}")))?;
+    Ok(())
+}
+
+/// Run [`assign_pending`] periodically in the background
+///
+/// Does nothing if the policy has no interval.
+pub fn spawn_assignment_job(state: Arc<AppState>, policy: ReviewPolicy) {
+    let Some(interval) = policy.assign_interval else {
+        tracing::info!("Reviewer assignment job disabled");
+        return;
+    };
+    tracing::info!(
+        "Assigning pending extractions every {}s",
+        interval.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match assign_pending(&state).await {
+                Ok(report) if report.assigned > 0 => tracing::info!(
+                    "Assigned {} extractions to {} reviewers",
+                    report.assigned,
+                    report.per_reviewer.len()
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Reviewer assignment failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Locks
+// ============================================================================
+
+/// Return items whose review lock has expired to the pending queue
+pub async fn release_expired_locks(state: &AppState) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE extraction_queue
+        SET status = 'pending', locked_by = NULL, lock_expires_at = NULL
+        WHERE status = 'in_review' AND lock_expires_at <= NOW()
+        "#,
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to release expired locks: {e}")))?;
+    Ok(result.rows_affected())
+}
+
+/// Check that `user_id` may decide an item in `status`
+///
+/// Pending items and items locked to the user can be decided; an item
+/// locked to someone else can be decided once the lock has expired.
+pub fn check_reviewable(
+    id: Uuid,
+    status: &str,
+    locked_by: Option<&str>,
+    lock_expires_at: Option<DateTime<Utc>>,
+    user_id: &str,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    match status {
+        "pending" => Ok(()),
+        "in_review" => match locked_by {
+            Some(owner) if owner != user_id && lock_expires_at.is_some_and(|at| at > now) => {
+                Err(AppError::coded(
+                    ErrorCode::ReviewLocked,
+                    format!("Extraction {id} is being reviewed by {owner}"),
+                ))
+            }
+            _ => Ok(()),
+        },
+        _ => Err(AppError::BadRequest(format!(
+            "Cannot review extraction in status: {status}"
+        ))),
+    }
+}
+
+/// Lock an item to a reviewer; returns when the lock expires
+///
+/// Claiming an item the reviewer already holds extends the lock.
+pub async fn claim(
+    state: &AppState,
+    id: Uuid,
+    user_id: &str,
+    ttl: Duration,
+) -> Result<DateTime<Utc>, AppError> {
+    let expires_at = Utc::now()
+        + chrono::Duration::from_std(ttl).unwrap_or_else(|_| chrono::Duration::seconds(900));
+
+    let claimed: Option<DateTime<Utc>> = sqlx::query_scalar(
+        r#"
+        UPDATE extraction_queue
+        SET status = 'in_review', locked_by = $2, lock_expires_at = $3
+        WHERE id = $1
+          AND (status = 'pending'
+               OR (status = 'in_review'
+                   AND (locked_by = $2 OR locked_by IS NULL OR lock_expires_at <= NOW())))
+        RETURNING lock_expires_at
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(expires_at)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to claim extraction: {e}")))?;
+    if let Some(expires_at) = claimed {
+        return Ok(expires_at);
+    }
+
+    // Not claimable: report why
+    let row: Option<(String, Option<String>, Option<DateTime<Utc>>)> = sqlx::query_as(
+        "SELECT status::text, locked_by, lock_expires_at FROM extraction_queue WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch extraction: {e}")))?;
+    let (status, locked_by, lock_expires_at) =
+        row.ok_or_else(|| AppError::NotFound(format!("Extraction {id} not found")))?;
+    check_reviewable(
+        id,
+        &status,
+        locked_by.as_deref(),
+        lock_expires_at,
+        user_id,
+        Utc::now(),
+    )?;
+    Err(AppError::coded(
+        ErrorCode::ReviewLocked,
+        format!("Extraction {id} was claimed concurrently"),
+    ))
+}
+
+/// Give up the lock on an item, returning it to the pending queue
+///
+/// Admins can release any reviewer's lock.
+pub async fn release(
+    state: &AppState,
+    id: Uuid,
+    user_id: &str,
+    is_admin: bool,
+) -> Result<(), AppError> {
+    let row: Option<(String, Option<String>, Option<DateTime<Utc>>)> = sqlx::query_as(
+        "SELECT status::text, locked_by, lock_expires_at FROM extraction_queue WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch extraction: {e}")))?;
+    let (status, locked_by, lock_expires_at) =
+        row.ok_or_else(|| AppError::NotFound(format!("Extraction {id} not found")))?;
+    if status != "in_review" {
+        return Err(AppError::BadRequest(format!(
+            "Extraction {id} is not in review (status: {status})"
+        )));
+    }
+    if !is_admin {
+        check_reviewable(
+            id,
+            &status,
+            locked_by.as_deref(),
+            lock_expires_at,
+            user_id,
+            Utc::now(),
+        )?;
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE extraction_queue
+        SET status = 'pending', locked_by = NULL, lock_expires_at = NULL
+        WHERE id = $1 AND status = 'in_review'
+        "#,
+    )
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to release extraction: {e}")))?;
+    Ok(())
+}
+
+// ============================================================================
+// Multiple review
+// ============================================================================
+
+/// Decision of one reviewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Approve,
+    Reject,
+}
+
+impl Decision {
+    /// Name stored in `extraction_reviews.decision`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approve => "approve",
+            Self::Reject => "reject",
+        }
+    }
+
+    /// Parse a stored decision
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "approve" => Some(Self::Approve),
+            "reject" => Some(Self::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// Where the decisions recorded for an item leave it
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReviewOutcome {
+    /// More agreeing decisions are needed
+    Pending { decided: usize, required: u32 },
+    /// Enough reviewers approved (with the same correction, if any)
+    Approved,
+    /// Enough reviewers rejected
+    Rejected,
+    /// Reviewers disagree; an admin decides
+    Adjudication,
+}
+
+/// Outcome of the decisions on an item needing `required` agreeing ones
+///
+/// Approvals agree only if they carry the same correction.
+pub fn tally(decisions: &[(Decision, Option<serde_json::Value>)], required: u32) -> ReviewOutcome {
+    let key = |(decision, correction): &(Decision, Option<serde_json::Value>)| match decision {
+        Decision::Approve => (Decision::Approve, correction.clone()),
+        Decision::Reject => (Decision::Reject, None),
+    };
+    let Some(first) = decisions.first() else {
+        return ReviewOutcome::Pending {
+            decided: 0,
+            required,
+        };
+    };
+    let first = key(first);
+    if decisions.iter().any(|d| key(d) != first) {
+        return ReviewOutcome::Adjudication;
+    }
+    if decisions.len() < required as usize {
+        return ReviewOutcome::Pending {
+            decided: decisions.len(),
+            required,
+        };
+    }
+    match first.0 {
+        Decision::Approve => ReviewOutcome::Approved,
+        Decision::Reject => ReviewOutcome::Rejected,
+    }
+}
+
+/// Agreement between the first two reviewers of each item
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Agreement {
+    /// Items decided by at least two reviewers
+    pub pairs: usize,
+    /// Share of pairs with the same decision
+    pub observed_agreement: f64,
+    /// Agreement expected by chance from each position's approval rate
+    pub expected_agreement: f64,
+    /// Cohen's kappa (absent without pairs or when chance agreement is
+    /// already perfect)
+    pub kappa: Option<f64>,
+}
+
+/// Cohen's kappa of (first, second) decision pairs
+pub fn cohens_kappa(pairs: &[(Decision, Decision)]) -> Agreement {
+    let n = pairs.len() as f64;
+    if pairs.is_empty() {
+        return Agreement {
+            pairs: 0,
+            observed_agreement: 0.0,
+            expected_agreement: 0.0,
+            kappa: None,
+        };
+    }
+    let agree = pairs.iter().filter(|(a, b)| a == b).count() as f64;
+    let first_approve = pairs
+        .iter()
+        .filter(|(a, _)| *a == Decision::Approve)
+        .count() as f64;
+    let second_approve = pairs
+        .iter()
+        .filter(|(_, b)| *b == Decision::Approve)
+        .count() as f64;
+
+    let observed = agree / n;
+    let expected = (first_approve / n) * (second_approve / n)
+        + (1.0 - first_approve / n) * (1.0 - second_approve / n);
+    let kappa = (expected < 1.0).then(|| (observed - expected) / (1.0 - expected));
+    Agreement {
+        pairs: pairs.len(),
+        observed_agreement: observed,
+        expected_agreement: expected,
+        kappa,
+    }
+}
+
+/// Inter-reviewer agreement over a period
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AgreementReport {
+    /// Days of reviews counted
+    pub days: u32,
+    #[serde(flatten)]
+    pub agreement: Agreement,
+    /// Items waiting for an admin decision
+    pub in_adjudication: i64,
+    /// Pending items with some but not enough approvals
+    pub awaiting_review: i64,
+}
+
+/// Agreement of items first reviewed in the last `days` days
+pub async fn agreement_report(state: &AppState, days: u32) -> Result<AgreementReport, AppError> {
+    let since = Utc::now() - chrono::Duration::days(i64::from(days));
+    let rows: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        SELECT extraction_id, decision
+        FROM extraction_reviews
+        WHERE extraction_id IN (
+            SELECT extraction_id
+            FROM extraction_reviews
+            GROUP BY extraction_id
+            HAVING COUNT(*) >= 2 AND MIN(created_at) >= $1
+        )
+        ORDER BY extraction_id, created_at, id
+        "#,
+    )
+    .bind(since)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch reviews: {e}")))?;
+
+    let mut pairs = Vec::new();
+    let mut current: Option<(Uuid, Vec<Decision>)> = None;
+    for (id, decision) in rows {
+        let Some(decision) = Decision::parse(&decision) else {
+            continue;
+        };
+        match current.as_mut() {
+            Some((item, decisions)) if *item == id => decisions.push(decision),
+            _ => {
+                if let Some((_, decisions)) = current.take() {
+                    if decisions.len() >= 2 {
+                        pairs.push((decisions[0], decisions[1]));
+                    }
+                }
+                current = Some((id, vec![decision]));
+            }
+        }
+    }
+    if let Some((_, decisions)) = current {
+        if decisions.len() >= 2 {
+            pairs.push((decisions[0], decisions[1]));
+        }
+    }
+
+    let (in_adjudication, awaiting_review): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE status = 'adjudication'),
+            COUNT(*) FILTER (
+                WHERE status IN ('pending', 'in_review')
+                  AND EXISTS (SELECT 1 FROM extraction_reviews r WHERE r.extraction_id = eq.id)
+            )
+        FROM extraction_queue eq
+        "#,
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to count reviews: {e}")))?;
+
+    Ok(AgreementReport {
+        days,
+        agreement: cohens_kappa(&pairs),
+        in_adjudication,
+        awaiting_review,
+    })
+}
+
+// ============================================================================
+// Throughput
+// ============================================================================
+
+/// Workload and throughput of a reviewer
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReviewerStats {
+    /// Reviewer user ID
+    pub reviewer_id: String,
+    #[schema(example = "홍길동")]
+    pub name: String,
+    pub department: Option<String>,
+    /// Assigned items still pending or in review
+    pub open_assigned: i64,
+    /// Items currently locked to the reviewer
+    pub in_review: i64,
+    /// Items approved in the period
+    pub approved: i64,
+    /// Items rejected in the period
+    pub rejected: i64,
+    /// Items decided per day over the period
+    pub reviewed_per_day: f64,
+    /// Mean seconds from assignment to decision
+    pub avg_turnaround_secs: Option<f64>,
+}
+
+/// Workload and throughput of every reviewer over the last `days` days
+pub async fn reviewer_stats(state: &AppState, days: u32) -> Result<Vec<ReviewerStats>, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct StatsRow {
+        reviewer_id: String,
+        name: String,
+        department: Option<String>,
+        open_assigned: i64,
+        in_review: i64,
+        approved: i64,
+        rejected: i64,
+        avg_turnaround_secs: Option<f64>,
+    }
+
+    let since = Utc::now() - chrono::Duration::days(i64::from(days));
+    let rows: Vec<StatsRow> = sqlx::query_as(
+        r#"
+        SELECT
+            u.id::text AS reviewer_id,
+            u.name,
+            u.department,
+            COUNT(eq.id) FILTER (
+                WHERE eq.assigned_to = u.id::text AND eq.status IN ('pending', 'in_review')
+            ) AS open_assigned,
+            COUNT(eq.id) FILTER (
+                WHERE eq.locked_by = u.id::text AND eq.status = 'in_review'
+                  AND eq.lock_expires_at > NOW()
+            ) AS in_review,
+            COUNT(eq.id) FILTER (
+                WHERE eq.reviewer_id = u.id::text AND eq.status = 'approved'
+                  AND eq.reviewed_at >= $1
+            ) AS approved,
+            COUNT(eq.id) FILTER (
+                WHERE eq.reviewer_id = u.id::text AND eq.status = 'rejected'
+                  AND eq.reviewed_at >= $1
+            ) AS rejected,
+            (AVG(EXTRACT(EPOCH FROM eq.reviewed_at - eq.assigned_at)) FILTER (
+                WHERE eq.reviewer_id = u.id::text AND eq.reviewed_at >= $1
+                  AND eq.assigned_at IS NOT NULL
+            ))::float8 AS avg_turnaround_secs
+        FROM users u
+        LEFT JOIN extraction_queue eq
+            ON eq.assigned_to = u.id::text
+            OR eq.locked_by = u.id::text
+            OR eq.reviewer_id = u.id::text
+        WHERE u.is_active AND u.role IN ('admin', 'editor')
+        GROUP BY u.id, u.name, u.department
+        ORDER BY u.name
+        "#,
+    )
+    .bind(since)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch reviewer statistics: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ReviewerStats {
+            reviewed_per_day: (row.approved + row.rejected) as f64 / f64::from(days.max(1)),
+            reviewer_id: row.reviewer_id,
+            name: row.name,
+            department: row.department,
+            open_assigned: row.open_assigned,
+            in_review: row.in_review,
+            approved: row.approved,
+            rejected: row.rejected,
+            avg_turnaround_secs: row.avg_turnaround_secs,
+        })
+        .collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reviewer(id: &str, department: Option<&str>) -> Reviewer {
+        Reviewer {
+            id: id.to_string(),
+            department: department.map(str::to_string),
+        }
+    }
+
+    fn item(department: Option<&str>) -> UnassignedItem {
+        UnassignedItem {
+            id: Uuid::new_v4(),
+            department: department.map(str::to_string),
+            reviewed_by: Vec::new(),
+        }
+    }
+
+    fn assignees(assignments: &[(Uuid, String)]) -> Vec<&str> {
+        assignments.iter().map(|(_, r)| r.as_str()).collect()
+    }
+
+    #[test]
+    fn test_round_robin_by_department() {
+        let reviewers = vec![
+            reviewer("hr-1", Some("HR")),
+            reviewer("hr-2", Some("HR")),
+            reviewer("it-1", Some("IT")),
+        ];
+        let items = vec![
+            item(Some("HR")),
+            item(Some("IT")),
+            item(Some("HR")),
+            item(Some("HR")),
+            item(Some("Finance")),
+            item(None),
+        ];
+
+        let assignments = round_robin(&items, &reviewers, &[]);
+
+        assert_eq!(
+            assignees(&assignments),
+            vec!["hr-1", "it-1", "hr-2", "hr-1", "hr-1", "hr-2"]
+        );
+        assert_eq!(assignments[0].0, items[0].id);
+    }
+
+    #[test]
+    fn test_round_robin_continues_after_recent_assignment() {
+        let reviewers = vec![reviewer("hr-1", Some("HR")), reviewer("hr-2", Some("HR"))];
+        let items = vec![item(Some("HR")), item(Some("HR"))];
+        let recent = vec![
+            (Some("HR".to_string()), "hr-2".to_string()),
+            (Some("HR".to_string()), "hr-1".to_string()),
+        ];
+
+        let assignments = round_robin(&items, &reviewers, &recent);
+
+        assert_eq!(assignees(&assignments), vec!["hr-2", "hr-1"]);
+        assert!(round_robin(&items, &[], &recent).is_empty());
+    }
+
+    #[test]
+    fn test_round_robin_skips_earlier_reviewers() {
+        let reviewers = vec![reviewer("hr-1", Some("HR")), reviewer("hr-2", Some("HR"))];
+        let mut second = item(Some("HR"));
+        second.reviewed_by = vec!["hr-1".to_string()];
+        let mut done = item(Some("HR"));
+        done.reviewed_by = vec!["hr-1".to_string(), "hr-2".to_string()];
+        let items = vec![second, done, item(Some("HR"))];
+
+        let assignments = round_robin(&items, &reviewers, &[]);
+
+        assert_eq!(assignees(&assignments), vec!["hr-2", "hr-1"]);
+        assert_eq!(assignments[1].0, items[2].id);
+    }
+
+    #[test]
+    fn test_tally_requires_agreeing_decisions() {
+        let correction = Some(serde_json::json!({"text": "연차휴가"}));
+        let approve = (Decision::Approve, None);
+        let reject = (Decision::Reject, None);
+
+        assert_eq!(
+            tally(std::slice::from_ref(&approve), 2),
+            ReviewOutcome::Pending {
+                decided: 1,
+                required: 2
+            }
+        );
+        assert_eq!(
+            tally(std::slice::from_ref(&approve), 1),
+            ReviewOutcome::Approved
+        );
+        assert_eq!(
+            tally(&[approve.clone(), approve.clone()], 2),
+            ReviewOutcome::Approved
+        );
+        assert_eq!(
+            tally(&[reject.clone(), reject.clone()], 2),
+            ReviewOutcome::Rejected
+        );
+        assert_eq!(
+            tally(&[approve.clone(), reject], 2),
+            ReviewOutcome::Adjudication
+        );
+        assert_eq!(
+            tally(&[approve, (Decision::Approve, correction)], 2),
+            ReviewOutcome::Adjudication
+        );
+    }
+
+    #[test]
+    fn test_cohens_kappa() {
+        use Decision::{Approve as A, Reject as R};
+
+        // 20 pairs: 10 both approve, 5 both reject, 3 A/R, 2 R/A
+        let mut pairs = vec![(A, A); 10];
+        pairs.extend(vec![(R, R); 5]);
+        pairs.extend(vec![(A, R); 3]);
+        pairs.extend(vec![(R, A); 2]);
+
+        let agreement = cohens_kappa(&pairs);
+        assert_eq!(agreement.pairs, 20);
+        assert!((agreement.observed_agreement - 0.75).abs() < 1e-9);
+        // Chance: 0.65 * 0.6 + 0.35 * 0.4 = 0.53
+        assert!((agreement.expected_agreement - 0.53).abs() < 1e-9);
+        assert!((agreement.kappa.unwrap() - 0.22 / 0.47).abs() < 1e-9);
+
+        assert_eq!(cohens_kappa(&[(A, A), (A, A)]).kappa, None);
+        assert_eq!(cohens_kappa(&[]).kappa, None);
+    }
+
+    #[test]
+    fn test_check_reviewable_respects_live_locks() {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let later = Some(now + chrono::Duration::minutes(5));
+        let earlier = Some(now - chrono::Duration::minutes(5));
+
+        assert!(check_reviewable(id, "pending", None, None, "a", now).is_ok());
+        assert!(check_reviewable(id, "in_review", Some("a"), later, "a", now).is_ok());
+        assert!(check_reviewable(id, "in_review", Some("b"), earlier, "a", now).is_ok());
+
+        let err = check_reviewable(id, "in_review", Some("b"), later, "a", now).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ReviewLocked);
+        let err = check_reviewable(id, "approved", None, None, "a", now).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::BadRequest);
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! Author: hephaex@gmail.com
 
-use crate::auth::middleware::auth_middleware;
-use crate::handlers::{auth, documents, graph, query, verify};
+use crate::auth::middleware::{auth_middleware, require_role};
+use crate::graphql;
+use crate::handlers::{
+    admin, auth, chunks, documents, export, faq, graph, notifications, query, saved_searches,
+    verify,
+};
 // TODO: Re-enable rate limiting once tower_governor is updated to 0.8+
 // use crate::middleware::rate_limit;
 use crate::state::AppState;
@@ -21,13 +25,21 @@ pub fn api_routes() -> Router<Arc<AppState>> {
     let auth_routes = Router::new()
         .route("/auth/register", post(auth::register_handler))
         .route("/auth/login", post(auth::login_handler))
-        .route("/auth/refresh", post(auth::refresh_handler));
+        .route("/auth/refresh", post(auth::refresh_handler))
+        .route("/auth/password-reset", post(auth::password_reset_handler));
     // .layer(rate_limit::auth_rate_limit());
 
+    // Signed document links (the signature is the authorization)
+    let shared_routes = Router::new().route(
+        "/shared/documents/:id",
+        get(documents::open_shared_document),
+    );
+
     // Streaming endpoints (authentication required)
     // TODO: Add rate limiting - 10 requests per minute per IP due to high resource usage
     let streaming_routes = Router::new()
         .route("/query/stream", post(query::query_stream_handler))
+        .route("/graphql/stream", post(graphql::graphql_stream_handler))
         .layer(middleware::from_fn(auth_middleware));
     // .layer(rate_limit::streaming_rate_limit());
 
@@ -36,17 +48,128 @@ pub fn api_routes() -> Router<Arc<AppState>> {
     let protected_routes = Router::new()
         .route("/auth/logout", post(auth::logout_handler))
         .route("/auth/me", get(auth::me_handler))
+        .route("/auth/sessions", get(auth::list_sessions_handler))
+        .route("/auth/sessions/:id", delete(auth::revoke_session_handler))
+        .route("/auth/introspect", post(auth::introspect_handler))
         // Query endpoints
         .route("/query", post(query::query_handler))
+        .route("/query/estimate", post(query::estimate_query))
+        .route("/query/replay", post(query::replay_query))
+        .route("/query/:id/suggestions", get(query::get_query_suggestions))
+        .route("/query/:id/feedback", post(query::submit_feedback))
+        .route("/faq", get(faq::list_faq))
+        .route("/notifications", get(notifications::list_notifications))
+        .route(
+            "/notifications/unread-count",
+            get(notifications::unread_count),
+        )
+        .route(
+            "/notifications/read-all",
+            post(notifications::mark_all_read),
+        )
+        .route("/notifications/:id/read", post(notifications::mark_read))
+        .route(
+            "/notifications/preferences",
+            get(notifications::get_preferences).put(notifications::update_preferences),
+        )
+        .route(
+            "/saved-searches",
+            get(saved_searches::list_saved_searches).post(saved_searches::create_saved_search),
+        )
+        .route(
+            "/saved-searches/:id",
+            get(saved_searches::get_saved_search)
+                .put(saved_searches::update_saved_search)
+                .delete(saved_searches::delete_saved_search),
+        )
+        .route(
+            "/saved-searches/:id/matches",
+            get(saved_searches::list_saved_search_matches),
+        )
         // Document endpoints
         .route("/documents", get(documents::list_documents))
         .route("/documents", post(documents::upload_document))
+        .route("/documents/tabular", post(documents::ingest_tabular))
+        .route("/documents/form", post(documents::ingest_form))
+        .route("/documents/compare", post(documents::compare_documents))
         .route("/documents/:id", get(documents::get_document))
         .route("/documents/:id", delete(documents::delete_document))
+        .route("/documents/:id/restore", post(documents::restore_document))
+        .route(
+            "/documents/:id/reprocess",
+            post(documents::reprocess_document),
+        )
+        .route("/documents/:id/original", get(documents::download_original))
+        .route("/documents/:id/share", post(documents::share_document))
+        .route(
+            "/documents/:id/access-requests",
+            post(documents::request_document_access),
+        )
+        .route("/access-requests", get(documents::list_access_requests))
+        .route(
+            "/access-requests/pending",
+            get(documents::list_pending_access_requests),
+        )
+        .route(
+            "/access-requests/:id/approve",
+            post(documents::approve_access_request),
+        )
+        .route(
+            "/access-requests/:id/deny",
+            post(documents::deny_access_request),
+        )
+        .route(
+            "/documents/:id/lineage",
+            get(documents::get_document_lineage),
+        )
+        .route(
+            "/documents/:id/ingest-report",
+            get(documents::get_ingest_report),
+        )
+        .route("/documents/:id/rescan", get(documents::get_rescan_report))
+        .route("/documents/:id/export", get(export::export_document))
+        .route("/exports/:job_id", get(export::get_export_job))
+        .route("/exports/:job_id/download", get(export::download_export))
+        .route("/documents/:id/chunks", get(chunks::list_document_chunks))
+        .route(
+            "/documents/:id/integrity",
+            get(chunks::check_document_integrity),
+        )
+        .route("/chunks/:id/similar", get(chunks::similar_chunks))
+        .route("/chunks/:id/duplicates", get(chunks::chunk_duplicates))
         // Graph endpoints
         .route("/graph/entities", get(graph::list_entities))
         .route("/graph/entities/:id", get(graph::get_entity))
+        .route(
+            "/graph/entities/:id/sections",
+            get(graph::get_entity_sections),
+        )
+        .route(
+            "/graph/entities/:id/timeline",
+            get(graph::get_entity_timeline),
+        )
+        .route(
+            "/graph/triples/:id/provenance",
+            get(graph::get_triple_provenance),
+        )
+        .route("/graph/analytics", get(graph::get_graph_analytics))
+        .route("/graph/visualize", get(graph::visualize_graph))
         .route("/graph/search", post(graph::search_graph))
+        .route("/graph/sparql", post(graph::sparql_query))
+        .route("/graph/snapshots", get(graph::list_snapshots))
+        .route("/graph/snapshots", post(graph::create_snapshot))
+        .route("/graph/snapshots/:name", delete(graph::delete_snapshot))
+        .route(
+            "/graph/snapshots/:name/restore",
+            post(graph::restore_snapshot),
+        )
+        .route("/graph/inference/run", post(graph::run_inference))
+        .route("/graph/triples", post(graph::create_triple))
+        .route("/graph/conflicts", get(graph::list_conflicts))
+        .route(
+            "/graph/conflicts/:id/resolve",
+            post(graph::resolve_conflict),
+        )
         // Ontology endpoints
         .route("/ontology", get(graph::get_ontology))
         .route("/ontology", put(graph::update_ontology))
@@ -55,12 +178,145 @@ pub fn api_routes() -> Router<Arc<AppState>> {
         .route("/verify/:id/approve", post(verify::approve_extraction))
         .route("/verify/:id/reject", post(verify::reject_extraction))
         .route("/verify/stats", get(verify::get_stats))
+        .route("/verify/next", get(verify::next_extraction))
+        .route("/verify/assign", post(verify::auto_assign))
+        .route("/verify/reviewers/stats", get(verify::get_reviewer_stats))
+        .route("/verify/agreement", get(verify::get_agreement))
+        .route("/verify/audit", get(verify::list_audit))
+        .route("/verify/audit/run", post(verify::run_audit))
+        .route("/verify/audit/stats", get(verify::get_audit_stats))
+        .route(
+            "/verify/audit/threshold",
+            put(verify::set_auto_approve_threshold),
+        )
+        .route("/verify/audit/:id", post(verify::record_audit))
+        .route(
+            "/verify/auto-approve/policy",
+            get(verify::get_auto_approve_policy),
+        )
+        .route(
+            "/verify/auto-approve/policy",
+            put(verify::set_auto_approve_policy),
+        )
+        .route(
+            "/verify/auto-approve/simulate",
+            post(verify::simulate_auto_approve_policy),
+        )
+        .route(
+            "/verify/:id/adjudicate",
+            post(verify::adjudicate_extraction),
+        )
+        .route("/verify/:id/assign", put(verify::assign_extraction))
+        .route("/verify/:id/claim", post(verify::claim_extraction))
+        .route("/verify/:id/release", post(verify::release_extraction))
+        .route("/verify/:id", get(verify::get_extraction))
+        // GraphQL endpoint
+        .route(
+            "/graphql",
+            get(graphql::graphql_sdl).post(graphql::graphql_handler),
+        )
         .layer(middleware::from_fn(auth_middleware));
     // .layer(rate_limit::api_rate_limit());
 
+    // Admin routes (admin role required)
+    let admin_routes = Router::new()
+        .route("/admin/calibration", get(admin::get_calibration))
+        .route("/admin/synonyms", get(admin::list_synonyms))
+        .route("/admin/synonyms", post(admin::add_synonyms))
+        .route("/admin/synonyms/:term", delete(admin::delete_synonym))
+        .route("/admin/personas", get(admin::list_personas))
+        .route("/admin/personas/:name", put(admin::put_persona))
+        .route("/admin/personas/:name", delete(admin::delete_persona))
+        .route("/admin/spaces", get(admin::list_spaces))
+        .route("/admin/spaces/:name", put(admin::put_space))
+        .route("/admin/spaces/:name", delete(admin::delete_space))
+        .route("/admin/cache/stats", get(admin::get_cache_stats))
+        .route("/admin/cache/clear", post(admin::clear_cache))
+        .route("/admin/cache/warm", post(admin::warm_cache))
+        .route("/admin/analyzer", get(admin::get_analyzer_settings))
+        .route("/admin/analyzer", put(admin::update_analyzer_settings))
+        .route(
+            "/admin/analyzer/reload",
+            post(admin::reload_analyzer_settings),
+        )
+        .route("/admin/slo", get(admin::get_slo_status))
+        .route("/admin/slo/alert-rules", get(admin::get_slo_alert_rules))
+        .route("/admin/content-gaps", get(admin::list_content_gaps))
+        .route(
+            "/admin/content-gaps/resolve",
+            post(admin::resolve_content_gaps),
+        )
+        .route("/admin/glossary", get(admin::list_glossary))
+        .route("/admin/glossary", post(admin::create_glossary_entry))
+        .route("/admin/glossary/:id", get(admin::get_glossary_entry))
+        .route("/admin/glossary/:id", put(admin::update_glossary_entry))
+        .route("/admin/glossary/:id", delete(admin::delete_glossary_entry))
+        .route(
+            "/admin/glossary/:id/approve",
+            post(admin::approve_glossary_entry),
+        )
+        .route("/admin/faq", get(admin::list_faq_entries))
+        .route("/admin/faq/generate", post(admin::generate_faq))
+        .route("/admin/faq/:id", put(admin::update_faq_entry))
+        .route("/admin/faq/:id", delete(admin::delete_faq_entry))
+        .route("/admin/faq/:id/approve", post(admin::approve_faq_entry))
+        .route("/admin/faq/:id/reject", post(admin::reject_faq_entry))
+        .route("/admin/freshness/alerts", get(admin::list_freshness_alerts))
+        .route("/admin/freshness/check", post(admin::run_freshness_check))
+        .route(
+            "/admin/freshness/alerts/:id/acknowledge",
+            post(admin::acknowledge_freshness_alert),
+        )
+        .route(
+            "/admin/documents/:id/freshness",
+            put(admin::update_document_freshness),
+        )
+        .route(
+            "/admin/classification-reviews",
+            get(admin::list_classification_reviews),
+        )
+        .route(
+            "/admin/classification-reviews/:document_id/resolve",
+            post(admin::resolve_classification_review),
+        )
+        .route(
+            "/admin/embeddings/migrations",
+            get(admin::list_embedding_migrations).post(admin::start_embedding_migration),
+        )
+        .route(
+            "/admin/embeddings/migrations/:id",
+            get(admin::get_embedding_migration),
+        )
+        .route(
+            "/admin/embeddings/migrations/:id/switch",
+            post(admin::switch_embedding_migration),
+        )
+        .route("/admin/forms", get(admin::list_form_templates))
+        .route(
+            "/admin/forms/:name",
+            put(admin::put_form_template).delete(admin::delete_form_template),
+        )
+        .route("/admin/users", get(admin::list_users))
+        .route(
+            "/admin/users/:id",
+            get(admin::get_user).put(admin::update_user),
+        )
+        .route("/admin/users/:id/deactivate", post(admin::deactivate_user))
+        .route("/admin/users/:id/activate", post(admin::activate_user))
+        .route(
+            "/admin/users/:id/password-reset",
+            post(admin::reset_user_password),
+        )
+        .route("/admin/users/:id/sessions", get(admin::list_user_sessions))
+        .route("/admin/users/:id/logout", post(admin::force_logout_user))
+        .route_layer(middleware::from_fn(require_role("admin")))
+        .route_layer(middleware::from_fn(auth_middleware));
+
     // Combine routes
     Router::new()
         .merge(auth_routes)
+        .merge(shared_routes)
         .merge(streaming_routes)
         .merge(protected_routes)
+        .merge(admin_routes)
 }
@@ -0,0 +1,878 @@
+//! Saved searches and alerts on new matching documents
+//!
+//! Users save a query ("육아휴직") and are told when new documents about it
+//! arrive. A background job picks up the documents ingested since its last
+//! run, through the API or the CLI, and scores their chunks against every
+//! enabled saved search. A chunk's score blends the cosine similarity of its
+//! embedding to the query's with the share of the search's keywords it
+//! contains; without a vector store, or for chunks without a vector, only
+//! keywords count. A document matches when its best chunk reaches the
+//! minimum score and the owner of the search may read it.
+//!
+//! Each match is recorded once in `saved_search_matches`, so a document
+//! never triggers the same search twice, and the owner is notified through
+//! [`crate::notifications`] with a link to the document (or to the match
+//! list when several documents matched in one run).
+//!
+//! Author: hephaex@gmail.com
+
+use crate::error::AppError;
+use crate::handlers::documents::fetch_document_acls;
+use crate::notifications::{self, Audience, NotificationEvent, LINK_VAR};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Default seconds between checks for new documents
+const DEFAULT_CHECK_INTERVAL_SECS: u64 = 300;
+
+/// Default minimum chunk score for a document to match
+const DEFAULT_MIN_SCORE: f32 = 0.6;
+
+/// Default share of the score coming from keywords when a chunk has a vector
+const DEFAULT_KEYWORD_WEIGHT: f32 = 0.4;
+
+/// Default saved searches per user
+const DEFAULT_MAX_PER_USER: usize = 20;
+
+/// Seconds a new document is left alone so chunking and indexing can finish
+const INGEST_SETTLE_SECS: i64 = 120;
+
+/// Document titles listed in one notification
+const MAX_LISTED_DOCUMENTS: usize = 5;
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// How often new documents are matched and how strict matching is
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSearchPolicy {
+    /// Time between checks (`None` disables saved search alerts)
+    pub check_interval: Option<Duration>,
+    /// Minimum chunk score (0.0 to 1.0) for a document to match
+    pub min_score: f32,
+    /// Share of the score (0.0 to 1.0) coming from keywords; the rest is
+    /// embedding similarity
+    pub keyword_weight: f32,
+    /// Saved searches a user may have
+    pub max_per_user: usize,
+}
+
+impl Default for SavedSearchPolicy {
+    fn default() -> Self {
+        Self {
+            check_interval: Some(Duration::from_secs(DEFAULT_CHECK_INTERVAL_SECS)),
+            min_score: DEFAULT_MIN_SCORE,
+            keyword_weight: DEFAULT_KEYWORD_WEIGHT,
+            max_per_user: DEFAULT_MAX_PER_USER,
+        }
+    }
+}
+
+impl SavedSearchPolicy {
+    /// Policy from `SAVED_SEARCH_CHECK_INTERVAL_SECS` (0 disables the job),
+    /// `SAVED_SEARCH_MIN_SCORE`, `SAVED_SEARCH_KEYWORD_WEIGHT` and
+    /// `SAVED_SEARCH_MAX_PER_USER`
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(secs) = std::env::var("SAVED_SEARCH_CHECK_INTERVAL_SECS") {
+            match secs.parse::<u64>() {
+                Ok(0) => policy.check_interval = None,
+                Ok(secs) => policy.check_interval = Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    tracing::warn!(
+                        "Ignoring invalid SAVED_SEARCH_CHECK_INTERVAL_SECS: {}",
+                        secs
+                    )
+                }
+            }
+        }
+        if let Ok(score) = std::env::var("SAVED_SEARCH_MIN_SCORE") {
+            match score.parse::<f32>() {
+                Ok(score) if (0.0..=1.0).contains(&score) => policy.min_score = score,
+                _ => tracing::warn!("Ignoring invalid SAVED_SEARCH_MIN_SCORE: {}", score),
+            }
+        }
+        if let Ok(weight) = std::env::var("SAVED_SEARCH_KEYWORD_WEIGHT") {
+            match weight.parse::<f32>() {
+                Ok(weight) if (0.0..=1.0).contains(&weight) => policy.keyword_weight = weight,
+                _ => tracing::warn!("Ignoring invalid SAVED_SEARCH_KEYWORD_WEIGHT: {}", weight),
+            }
+        }
+        if let Ok(max) = std::env::var("SAVED_SEARCH_MAX_PER_USER") {
+            match max.parse::<usize>() {
+                Ok(max) if max > 0 => policy.max_per_user = max,
+                _ => tracing::warn!("Ignoring invalid SAVED_SEARCH_MAX_PER_USER: {}", max),
+            }
+        }
+        policy
+    }
+
+    /// Score of a chunk from its embedding similarity (if it has a vector)
+    /// and keyword coverage
+    pub fn chunk_score(&self, similarity: Option<f32>, keywords: f32) -> f32 {
+        match similarity {
+            Some(similarity) => {
+                self.keyword_weight * keywords
+                    + (1.0 - self.keyword_weight) * similarity.clamp(0.0, 1.0)
+            }
+            None => keywords,
+        }
+    }
+}
+
+// ============================================================================
+// Saved searches
+// ============================================================================
+
+/// A saved search of a user
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SavedSearch {
+    pub id: Uuid,
+    #[schema(example = "육아휴직 소식")]
+    pub name: String,
+    /// Query new documents are compared with
+    #[schema(example = "육아휴직 신청 절차")]
+    pub query: String,
+    /// Words a matching chunk should contain
+    #[schema(example = json!(["육아휴직"]))]
+    pub keywords: Vec<String>,
+    /// Whether new documents are matched against it
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    /// When a document last matched
+    pub last_matched_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SavedSearchRow {
+    id: Uuid,
+    name: String,
+    query: String,
+    keywords: Vec<String>,
+    enabled: bool,
+    created_at: DateTime<Utc>,
+    last_matched_at: Option<DateTime<Utc>>,
+}
+
+impl From<SavedSearchRow> for SavedSearch {
+    fn from(row: SavedSearchRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            query: row.query,
+            keywords: row.keywords,
+            enabled: row.enabled,
+            created_at: row.created_at,
+            last_matched_at: row.last_matched_at,
+        }
+    }
+}
+
+/// Name, query and keywords of a saved search
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSpec {
+    pub name: String,
+    pub query: String,
+    /// Keywords; derived from the query when empty
+    pub keywords: Vec<String>,
+    pub enabled: bool,
+}
+
+impl SearchSpec {
+    /// Trimmed, validated spec
+    pub fn normalized(self) -> Result<Self, AppError> {
+        let query = self.query.trim().to_string();
+        if query.is_empty() {
+            return Err(AppError::BadRequest("Query cannot be empty".to_string()));
+        }
+        let name = match self.name.trim() {
+            "" => query.clone(),
+            name => name.to_string(),
+        };
+        let mut keywords = normalize_keywords(self.keywords.iter().map(String::as_str));
+        if keywords.is_empty() {
+            keywords = query_keywords(&query);
+        }
+        if keywords.is_empty() {
+            return Err(AppError::BadRequest(
+                "Query has no keywords; give at least one word of two or more characters"
+                    .to_string(),
+            ));
+        }
+        Ok(Self {
+            name,
+            query,
+            keywords,
+            enabled: self.enabled,
+        })
+    }
+}
+
+const SAVED_SEARCH_COLUMNS: &str =
+    "id, name, query, keywords, enabled, created_at, last_matched_at";
+
+/// Saved searches of a user, newest first
+pub async fn list(pool: &sqlx::PgPool, user_id: Uuid) -> Result<Vec<SavedSearch>, AppError> {
+    let rows: Vec<SavedSearchRow> = sqlx::query_as(&format!(
+        "SELECT {SAVED_SEARCH_COLUMNS} FROM saved_searches WHERE user_id = $1 \
+         ORDER BY created_at DESC"
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list saved searches: {e}")))?;
+    Ok(rows.into_iter().map(SavedSearch::from).collect())
+}
+
+/// One saved search of a user
+pub async fn get(pool: &sqlx::PgPool, user_id: Uuid, id: Uuid) -> Result<SavedSearch, AppError> {
+    let row: Option<SavedSearchRow> = sqlx::query_as(&format!(
+        "SELECT {SAVED_SEARCH_COLUMNS} FROM saved_searches WHERE id = $1 AND user_id = $2"
+    ))
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to fetch saved search: {e}")))?;
+    row.map(SavedSearch::from)
+        .ok_or_else(|| AppError::NotFound(format!("Saved search {id} not found")))
+}
+
+/// Save a search for a user
+pub async fn create(
+    state: &AppState,
+    user_id: Uuid,
+    spec: SearchSpec,
+) -> Result<SavedSearch, AppError> {
+    let spec = spec.normalized()?;
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM saved_searches WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to count saved searches: {e}")))?;
+    if count as usize >= state.saved_searches.max_per_user {
+        return Err(AppError::BadRequest(format!(
+            "At most {} saved searches are allowed; delete one first",
+            state.saved_searches.max_per_user
+        )));
+    }
+
+    let row: SavedSearchRow = sqlx::query_as(&format!(
+        "INSERT INTO saved_searches (user_id, name, query, keywords, enabled) \
+         VALUES ($1, $2, $3, $4, $5) RETURNING {SAVED_SEARCH_COLUMNS}"
+    ))
+    .bind(user_id)
+    .bind(&spec.name)
+    .bind(&spec.query)
+    .bind(&spec.keywords)
+    .bind(spec.enabled)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to save search: {e}")))?;
+    Ok(row.into())
+}
+
+/// Replace the name, query, keywords and enabled state of a saved search
+pub async fn update(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    spec: SearchSpec,
+) -> Result<SavedSearch, AppError> {
+    let spec = spec.normalized()?;
+    let row: Option<SavedSearchRow> = sqlx::query_as(&format!(
+        "UPDATE saved_searches SET name = $3, query = $4, keywords = $5, enabled = $6, \
+         updated_at = NOW() WHERE id = $1 AND user_id = $2 RETURNING {SAVED_SEARCH_COLUMNS}"
+    ))
+    .bind(id)
+    .bind(user_id)
+    .bind(&spec.name)
+    .bind(&spec.query)
+    .bind(&spec.keywords)
+    .bind(spec.enabled)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to update saved search: {e}")))?;
+    row.map(SavedSearch::from)
+        .ok_or_else(|| AppError::NotFound(format!("Saved search {id} not found")))
+}
+
+/// Delete a saved search and its matches
+pub async fn delete(pool: &sqlx::PgPool, user_id: Uuid, id: Uuid) -> Result<(), AppError> {
+    let result = sqlx::query("DELETE FROM saved_searches WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete saved search: {e}")))?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Saved search {id} not found")));
+    }
+    Ok(())
+}
+
+/// A document that matched a saved search
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SavedSearchMatch {
+    pub document_id: Uuid,
+    #[schema(example = "육아휴직 운영지침 2026")]
+    pub document_title: String,
+    /// Score of the best matching chunk
+    #[schema(example = 0.82)]
+    pub score: f32,
+    /// Index of the best matching chunk
+    pub chunk_index: i32,
+    pub matched_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct MatchRow {
+    document_id: Uuid,
+    document_title: String,
+    score: f32,
+    chunk_index: i32,
+    matched_at: DateTime<Utc>,
+}
+
+/// Documents that matched a saved search of a user, newest first
+///
+/// Deleted documents are left out.
+pub async fn matches(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    limit: i64,
+) -> Result<Vec<SavedSearchMatch>, AppError> {
+    get(pool, user_id, id).await?;
+    let rows: Vec<MatchRow> = sqlx::query_as(
+        "SELECT m.document_id, d.title AS document_title, m.score, m.chunk_index, m.matched_at \
+         FROM saved_search_matches m JOIN documents d ON d.id = m.document_id \
+         WHERE m.saved_search_id = $1 AND d.deleted_at IS NULL \
+         ORDER BY m.matched_at DESC, m.score DESC LIMIT $2",
+    )
+    .bind(id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list saved search matches: {e}")))?;
+    Ok(rows
+        .into_iter()
+        .map(|row| SavedSearchMatch {
+            document_id: row.document_id,
+            document_title: row.document_title,
+            score: row.score,
+            chunk_index: row.chunk_index,
+            matched_at: row.matched_at,
+        })
+        .collect())
+}
+
+// ============================================================================
+// Scoring
+// ============================================================================
+
+/// Lowercased, deduplicated keywords of at least two characters
+fn normalize_keywords<'a>(words: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for word in words {
+        let word = word
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        if word.chars().count() >= 2 && !keywords.contains(&word) {
+            keywords.push(word);
+        }
+    }
+    keywords
+}
+
+/// Keywords of a query: its words of two or more characters
+pub fn query_keywords(query: &str) -> Vec<String> {
+    normalize_keywords(query.split_whitespace())
+}
+
+/// Share of the keywords found in a text
+///
+/// Keywords match inside words, so `육아휴직` is found in `육아휴직을`.
+pub fn keyword_coverage(keywords: &[String], text: &str) -> f32 {
+    if keywords.is_empty() {
+        return 0.0;
+    }
+    let text = text.to_lowercase();
+    let found = keywords
+        .iter()
+        .filter(|k| text.contains(k.as_str()))
+        .count();
+    found as f32 / keywords.len() as f32
+}
+
+/// Cosine similarity over the dimensions both vectors have
+///
+/// Stored vectors may be truncated (Matryoshka embeddings) while query
+/// embeddings are not; comparing the shared prefix handles both.
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// A chunk of a new document
+#[derive(Debug, Clone)]
+pub struct CandidateChunk {
+    pub chunk_index: i32,
+    pub content: String,
+    pub vector: Option<Vec<f32>>,
+}
+
+/// Best scoring chunk of a document as `(chunk_index, score)`, if it reaches
+/// the minimum score
+pub fn best_chunk(
+    policy: &SavedSearchPolicy,
+    keywords: &[String],
+    query_vector: Option<&[f32]>,
+    chunks: &[CandidateChunk],
+) -> Option<(i32, f32)> {
+    chunks
+        .iter()
+        .map(|chunk| {
+            let similarity = query_vector
+                .zip(chunk.vector.as_deref())
+                .map(|(query, vector)| cosine_similarity(query, vector));
+            let score = policy.chunk_score(similarity, keyword_coverage(keywords, &chunk.content));
+            (chunk.chunk_index, score)
+        })
+        .filter(|(_, score)| *score >= policy.min_score)
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct ActiveSearchRow {
+    id: Uuid,
+    user_id: Uuid,
+    name: String,
+    query: String,
+    keywords: Vec<String>,
+    email: String,
+    role: String,
+    department: Option<String>,
+}
+
+impl ActiveSearchRow {
+    /// ACL user of the search owner
+    fn acl_user(&self) -> otl_core::User {
+        otl_core::User {
+            user_id: self.user_id.to_string(),
+            email: Some(self.email.clone()),
+            roles: vec![self.role.to_uppercase()],
+            departments: self.department.iter().cloned().collect(),
+            is_internal: true,
+        }
+    }
+}
+
+/// Result of one matching run
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct MatchReport {
+    pub documents: usize,
+    pub searches: usize,
+    pub matches: usize,
+}
+
+/// Embeddings of the queries, `None` without a vector store or when
+/// embedding fails
+async fn query_vectors(state: &AppState, queries: &[String]) -> Option<Vec<Vec<f32>>> {
+    let backend = state.vector_backend.read().await.clone()?;
+    match backend.embedding_client().embed_batch(queries).await {
+        Ok(vectors) if vectors.len() == queries.len() => Some(vectors),
+        Ok(_) => {
+            tracing::warn!("Matching saved searches by keywords: embedding count mismatch");
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Matching saved searches by keywords: {}", e);
+            None
+        }
+    }
+}
+
+/// Opened chunks of a document with their vectors where the vector store
+/// has them
+async fn candidate_chunks(
+    state: &AppState,
+    document_id: Uuid,
+) -> Result<Vec<CandidateChunk>, AppError> {
+    let mut rows: Vec<(i32, String)> = sqlx::query_as(
+        "SELECT chunk_index, content FROM document_chunks \
+         WHERE document_id = $1 AND NOT corrupted ORDER BY chunk_index",
+    )
+    .bind(document_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to load chunks: {e}")))?;
+    state
+        .keyring
+        .open_chunks(
+            &state.db_pool,
+            document_id,
+            rows.iter_mut()
+                .map(|(chunk_index, content)| ((*chunk_index).max(0) as u32, content)),
+        )
+        .await?;
+
+    let mut vectors: HashMap<u32, Vec<f32>> = HashMap::new();
+    if let Some(backend) = state.vector_backend.read().await.clone() {
+        match backend.document_vectors(document_id).await {
+            Ok(stored) => {
+                vectors = stored
+                    .into_iter()
+                    .filter_map(|v| Some((v.chunk_index?, v.vector)))
+                    .collect();
+            }
+            Err(e) => tracing::warn!("Matching document {} by keywords only: {}", document_id, e),
+        }
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|(chunk_index, content)| CandidateChunk {
+            vector: vectors.remove(&(chunk_index.max(0) as u32)),
+            chunk_index,
+            content,
+        })
+        .collect())
+}
+
+/// Match documents ingested since `since` against the enabled saved
+/// searches and notify their owners; returns the report and the time to
+/// check from next
+///
+/// Documents younger than a couple of minutes are left for the next run so
+/// their chunks and vectors are complete.
+pub async fn match_new_documents(
+    state: &AppState,
+    since: DateTime<Utc>,
+) -> Result<(MatchReport, DateTime<Utc>), AppError> {
+    let until: DateTime<Utc> = sqlx::query_scalar(&format!(
+        "SELECT NOW() - INTERVAL '{INGEST_SETTLE_SECS} seconds'"
+    ))
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to read database time: {e}")))?;
+    if until <= since {
+        return Ok((MatchReport::default(), since));
+    }
+
+    let documents: Vec<(Uuid, String)> = sqlx::query_as(
+        "SELECT id, title FROM documents \
+         WHERE created_at > $1 AND created_at <= $2 AND deleted_at IS NULL \
+         ORDER BY created_at",
+    )
+    .bind(since)
+    .bind(until)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list new documents: {e}")))?;
+    let mut report = MatchReport {
+        documents: documents.len(),
+        ..Default::default()
+    };
+    if documents.is_empty() {
+        return Ok((report, until));
+    }
+
+    let searches: Vec<ActiveSearchRow> = sqlx::query_as(
+        "SELECT s.id, s.user_id, s.name, s.query, s.keywords, u.email, u.role, u.department \
+         FROM saved_searches s JOIN users u ON u.id = s.user_id \
+         WHERE s.enabled AND u.is_active ORDER BY s.created_at",
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list saved searches: {e}")))?;
+    report.searches = searches.len();
+    if searches.is_empty() {
+        return Ok((report, until));
+    }
+
+    let queries: Vec<String> = searches.iter().map(|s| s.query.clone()).collect();
+    let query_vectors = query_vectors(state, &queries).await;
+    let document_ids: Vec<Uuid> = documents.iter().map(|(id, _)| *id).collect();
+    let acls = fetch_document_acls(state, &document_ids).await?;
+    let acl_users: Vec<otl_core::User> = searches.iter().map(ActiveSearchRow::acl_user).collect();
+
+    // Saved search index -> matched (document ID, title)
+    let mut matched: HashMap<usize, Vec<(Uuid, String)>> = HashMap::new();
+    for (document_id, title) in &documents {
+        let Some(acl) = acls.get(document_id) else {
+            continue;
+        };
+        let chunks = candidate_chunks(state, *document_id).await?;
+        if chunks.is_empty() {
+            continue;
+        }
+        for (i, search) in searches.iter().enumerate() {
+            if !acl.can_access(&acl_users[i]) {
+                continue;
+            }
+            let query_vector = query_vectors.as_ref().map(|v| v[i].as_slice());
+            let Some((chunk_index, score)) = best_chunk(
+                &state.saved_searches,
+                &search.keywords,
+                query_vector,
+                &chunks,
+            ) else {
+                continue;
+            };
+            if record_match(state, search.id, *document_id, chunk_index, score).await? {
+                matched
+                    .entry(i)
+                    .or_default()
+                    .push((*document_id, title.clone()));
+            }
+        }
+    }
+
+    for (i, documents) in matched {
+        let search = &searches[i];
+        report.matches += documents.len();
+        notify_owner(state, search, &documents);
+    }
+    Ok((report, until))
+}
+
+/// Record a match; false if the document already matched the search
+async fn record_match(
+    state: &AppState,
+    search_id: Uuid,
+    document_id: Uuid,
+    chunk_index: i32,
+    score: f32,
+) -> Result<bool, AppError> {
+    let inserted = sqlx::query(
+        "INSERT INTO saved_search_matches (saved_search_id, document_id, chunk_index, score) \
+         VALUES ($1, $2, $3, $4) ON CONFLICT (saved_search_id, document_id) DO NOTHING",
+    )
+    .bind(search_id)
+    .bind(document_id)
+    .bind(chunk_index)
+    .bind(score)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to record saved search match: {e}")))?
+    .rows_affected()
+        > 0;
+    if inserted {
+        sqlx::query("UPDATE saved_searches SET last_matched_at = NOW() WHERE id = $1")
+            .bind(search_id)
+            .execute(&state.db_pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update saved search: {e}")))?;
+    }
+    Ok(inserted)
+}
+
+fn notify_owner(state: &AppState, search: &ActiveSearchRow, documents: &[(Uuid, String)]) {
+    let link = match documents {
+        [(document_id, _)] => format!("/api/v1/documents/{document_id}"),
+        _ => format!("/api/v1/saved-searches/{}/matches", search.id),
+    };
+    notifications::notify(
+        state,
+        NotificationEvent::SavedSearchMatched,
+        vec![
+            ("search", search.name.clone()),
+            ("query", search.query.clone()),
+            ("count", documents.len().to_string()),
+            ("documents", list_documents(documents)),
+            (LINK_VAR, link),
+        ],
+        Audience::Users(vec![search.user_id]),
+    );
+}
+
+/// One "- title (/api/v1/documents/id)" line per document, the rest
+/// summarized as "and N more"
+fn list_documents(documents: &[(Uuid, String)]) -> String {
+    let mut lines: Vec<String> = documents
+        .iter()
+        .take(MAX_LISTED_DOCUMENTS)
+        .map(|(id, title)| format!("- {title} (/api/v1/documents/{id})"))
+        .collect();
+    if documents.len() > MAX_LISTED_DOCUMENTS {
+        lines.push(format!(
+            "and {} more",
+            documents.len() - MAX_LISTED_DOCUMENTS
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Periodically match new documents against saved searches
+///
+/// Does nothing without a check interval.
+pub fn spawn_match_job(state: Arc<AppState>, policy: SavedSearchPolicy) {
+    let Some(interval) = policy.check_interval else {
+        tracing::info!("Saved search alerts disabled");
+        return;
+    };
+    tracing::info!(
+        "Matching new documents against saved searches every {}s",
+        interval.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let mut since = Utc::now() - chrono::Duration::seconds(INGEST_SETTLE_SECS);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match match_new_documents(&state, since).await {
+                Ok((report, next)) => {
+                    if report.matches > 0 {
+                        tracing::info!("Saved search matching: {:?}", report);
+                    }
+                    since = next;
+                }
+                Err(e) => tracing::warn!("Saved search matching failed: {:?}", e),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_index: i32, content: &str, vector: Option<Vec<f32>>) -> CandidateChunk {
+        CandidateChunk {
+            chunk_index,
+            content: content.to_string(),
+            vector,
+        }
+    }
+
+    fn spec(query: &str, keywords: &[&str]) -> SearchSpec {
+        SearchSpec {
+            name: String::new(),
+            query: query.to_string(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_spec_derives_name_and_keywords_from_query() {
+        let spec = spec("  육아휴직 신청 절차, 및 요건 ", &[])
+            .normalized()
+            .unwrap();
+        assert_eq!(spec.name, "육아휴직 신청 절차, 및 요건");
+        assert_eq!(spec.keywords, vec!["육아휴직", "신청", "절차", "요건"]);
+
+        let spec = SearchSpec {
+            name: "육아휴직 소식".to_string(),
+            ..self::spec("육아휴직", &["육아휴직", " 육아휴직 ", "Parental"])
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(spec.name, "육아휴직 소식");
+        assert_eq!(spec.keywords, vec!["육아휴직", "parental"]);
+
+        assert!(spec_is_rejected("   "));
+        assert!(spec_is_rejected("및 a"));
+    }
+
+    fn spec_is_rejected(query: &str) -> bool {
+        matches!(spec(query, &[]).normalized(), Err(AppError::BadRequest(_)))
+    }
+
+    #[test]
+    fn test_keyword_coverage_matches_inside_words() {
+        let keywords = query_keywords("육아휴직 급여");
+        assert_eq!(
+            keyword_coverage(&keywords, "육아휴직을 신청한 직원은 급여를 받는다"),
+            1.0
+        );
+        assert_eq!(keyword_coverage(&keywords, "육아휴직은 1년이다"), 0.5);
+        assert_eq!(keyword_coverage(&keywords, "연차휴가 사용 기준"), 0.0);
+        assert_eq!(keyword_coverage(&[], "육아휴직"), 0.0);
+    }
+
+    #[test]
+    fn test_cosine_similarity_compares_shared_prefix() {
+        assert!((cosine_similarity(&[1.0, 0.0, 5.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_best_chunk_blends_similarity_and_keywords() {
+        let policy = SavedSearchPolicy::default();
+        let keywords = query_keywords("육아휴직");
+        let query = [1.0, 0.0];
+        let chunks = vec![
+            chunk(0, "연차휴가 사용 기준", Some(vec![0.0, 1.0])),
+            chunk(1, "육아휴직 신청 절차", Some(vec![0.8, 0.6])),
+            chunk(2, "휴직 중 급여", Some(vec![1.0, 0.1])),
+        ];
+
+        // 0.4 * 1.0 + 0.6 * 0.8 beats 0.4 * 0.0 + 0.6 * 0.995
+        let (index, score) = best_chunk(&policy, &keywords, Some(&query), &chunks).unwrap();
+        assert_eq!(index, 1);
+        assert!((score - 0.88).abs() < 1e-3);
+
+        // Without embeddings only keywords count
+        let (index, score) = best_chunk(&policy, &keywords, None, &chunks).unwrap();
+        assert_eq!((index, score), (1, 1.0));
+
+        let unrelated = vec![chunk(0, "연차휴가 사용 기준", Some(vec![0.0, 1.0]))];
+        assert_eq!(
+            best_chunk(&policy, &keywords, Some(&query), &unrelated),
+            None
+        );
+    }
+
+    #[test]
+    fn test_chunk_score_ignores_negative_similarity() {
+        let policy = SavedSearchPolicy {
+            keyword_weight: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(policy.chunk_score(Some(-0.7), 1.0), 0.5);
+        assert_eq!(policy.chunk_score(None, 0.5), 0.5);
+    }
+
+    #[test]
+    fn test_list_documents_caps_lines() {
+        let documents: Vec<(Uuid, String)> = (0..7)
+            .map(|i| (Uuid::nil(), format!("육아휴직 지침 {i}")))
+            .collect();
+        let text = list_documents(&documents);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), MAX_LISTED_DOCUMENTS + 1);
+        assert_eq!(
+            lines[0],
+            format!("- 육아휴직 지침 0 (/api/v1/documents/{})", Uuid::nil())
+        );
+        assert_eq!(lines[MAX_LISTED_DOCUMENTS], "and 2 more");
+    }
+}
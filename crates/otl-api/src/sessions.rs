@@ -0,0 +1,289 @@
+//! Login sessions and token introspection
+//!
+//! Every login starts a session: one row in `refresh_tokens` holding the
+//! device and IP it was started from. Refreshing rotates the token in
+//! place, so a session keeps its id and records when it was last used.
+//! Users list and revoke their own sessions; admins can force a user out
+//! of all of them, which also rejects the access tokens already issued to
+//! that user. That cutoff is stored in `users.tokens_revoked_at` and
+//! reloaded by every instance every [`TOKEN_CUTOFF_SYNC_SECS`], so the
+//! other instances reject the tokens within that time.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::auth::jwt::{validate_access_token, JwtConfig};
+use crate::auth::middleware::{
+    is_token_revoked, is_user_token_revoked, merge_user_token_cutoffs, revoke_user_tokens,
+};
+use crate::auth::service::hash_token;
+use crate::error::AppError;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// How often forced logouts of other instances are loaded
+pub const TOKEN_CUTOFF_SYNC_SECS: u64 = 15;
+
+const SESSION_COLUMNS: &str =
+    "id, device_info, host(ip_address) AS ip_address, created_at, last_used_at, expires_at";
+
+/// Active login session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Session {
+    pub id: Uuid,
+    /// User agent the session was last used from
+    pub device_info: Option<String>,
+    pub ip_address: Option<String>,
+    /// When the session was started by logging in
+    pub created_at: DateTime<Utc>,
+    /// Last login or token refresh
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// What a token is and whom it belongs to
+///
+/// Modelled on OAuth 2.0 token introspection (RFC 7662): an invalid,
+/// expired or revoked token only reports `active: false`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TokenIntrospection {
+    pub active: bool,
+    /// `access_token` or `refresh_token`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// User ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub department: Option<String>,
+    /// Access token ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// Session of a refresh token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<Uuid>,
+    /// Issued at, Unix seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    /// Expires at, Unix seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+}
+
+/// Active refresh token joined with its user
+#[derive(sqlx::FromRow)]
+struct RefreshTokenOwner {
+    session_id: Uuid,
+    user_id: Uuid,
+    email: String,
+    role: String,
+    department: Option<String>,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+/// Active sessions of a user, most recently used first
+pub async fn list_sessions(state: &AppState, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+    sqlx::query_as(&format!(
+        "SELECT {SESSION_COLUMNS} FROM refresh_tokens \
+         WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW() \
+         ORDER BY COALESCE(last_used_at, created_at) DESC"
+    ))
+    .bind(user_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list sessions: {e}")))
+}
+
+/// Revoke one session of a user
+///
+/// Sessions of other users are reported as not found.
+pub async fn revoke_session(state: &AppState, user_id: Uuid, id: Uuid) -> Result<(), AppError> {
+    let revoked = sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = NOW() \
+         WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to revoke session: {e}")))?;
+
+    if revoked.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Session {id} not found")));
+    }
+    Ok(())
+}
+
+/// Sign a user out everywhere; returns the number of sessions revoked
+///
+/// Access tokens issued so far are rejected too, instead of staying valid
+/// until they expire.
+pub async fn force_logout(state: &AppState, user_id: Uuid) -> Result<u64, AppError> {
+    let revoked = sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to revoke sessions: {e}")))?;
+
+    revoke_access_tokens(state, user_id).await?;
+    Ok(revoked.rows_affected())
+}
+
+/// Reject the access tokens issued to a user so far, on every instance
+pub async fn revoke_access_tokens(state: &AppState, user_id: Uuid) -> Result<(), AppError> {
+    revoke_user_tokens(user_id);
+    sqlx::query("UPDATE users SET tokens_revoked_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to revoke access tokens: {e}")))?;
+    Ok(())
+}
+
+/// Load the forced logouts that can still reject unexpired tokens
+async fn load_token_cutoffs(state: &AppState) -> Result<(), AppError> {
+    let lifetime = JwtConfig::from_env().access_expiration_secs;
+    let rows: Vec<(Uuid, i64)> = sqlx::query_as(
+        "SELECT id, EXTRACT(EPOCH FROM tokens_revoked_at)::BIGINT FROM users \
+         WHERE tokens_revoked_at > NOW() - make_interval(secs => $1)",
+    )
+    .bind(lifetime as f64)
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to load token cutoffs: {e}")))?;
+
+    merge_user_token_cutoffs(
+        rows.into_iter()
+            .filter_map(|(user_id, cutoff)| Some((user_id, u64::try_from(cutoff).ok()?))),
+    );
+    Ok(())
+}
+
+/// Keep the forced logout cutoffs of this instance in sync with the
+/// database, starting with those stored before a restart
+pub fn spawn_cutoff_sync_job(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(TOKEN_CUTOFF_SYNC_SECS));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = load_token_cutoffs(&state).await {
+                tracing::warn!("Token cutoff sync failed: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Introspect an access or refresh token
+pub async fn introspect(state: &AppState, token: &str) -> Result<TokenIntrospection, AppError> {
+    if let Some(access) = introspect_access_token(&JwtConfig::from_env(), token) {
+        return Ok(access);
+    }
+
+    let owner: Option<RefreshTokenOwner> = sqlx::query_as(
+        r#"
+        SELECT t.id AS session_id, u.id AS user_id, u.email, u.role, u.department,
+               t.created_at, t.expires_at
+        FROM refresh_tokens t JOIN users u ON u.id = t.user_id
+        WHERE t.token_hash = $1 AND t.revoked_at IS NULL AND t.expires_at > NOW()
+          AND u.is_active
+        "#,
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to introspect token: {e}")))?;
+
+    Ok(match owner {
+        Some(owner) => TokenIntrospection {
+            active: true,
+            token_type: Some("refresh_token".to_string()),
+            sub: Some(owner.user_id.to_string()),
+            email: Some(owner.email),
+            role: Some(owner.role),
+            department: owner.department,
+            session_id: Some(owner.session_id),
+            iat: Some(owner.created_at.timestamp()),
+            exp: Some(owner.expires_at.timestamp()),
+            ..Default::default()
+        },
+        None => TokenIntrospection::default(),
+    })
+}
+
+/// Introspect a token as an access token; `None` if it is not a valid one
+fn introspect_access_token(config: &JwtConfig, token: &str) -> Option<TokenIntrospection> {
+    let claims = validate_access_token(config, token).ok()?;
+    let user_id = Uuid::parse_str(&claims.sub).ok()?;
+    if is_token_revoked(&claims.jti) || is_user_token_revoked(user_id, claims.iat) {
+        return None;
+    }
+
+    Some(TokenIntrospection {
+        active: true,
+        token_type: Some("access_token".to_string()),
+        sub: Some(claims.sub),
+        email: Some(claims.email),
+        role: Some(claims.role),
+        department: claims.department,
+        jti: Some(claims.jti),
+        iat: i64::try_from(claims.iat).ok(),
+        exp: i64::try_from(claims.exp).ok(),
+        ..Default::default()
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::jwt::generate_access_token;
+
+    #[test]
+    fn test_introspect_access_token() {
+        let config = JwtConfig::default();
+        let user_id = Uuid::new_v4();
+        let token = generate_access_token(
+            &config,
+            user_id,
+            "김인사",
+            "kim@example.com",
+            "editor",
+            Some("인사팀"),
+        )
+        .unwrap();
+
+        let info = introspect_access_token(&config, &token).unwrap();
+        assert!(info.active);
+        assert_eq!(info.token_type.as_deref(), Some("access_token"));
+        assert_eq!(info.sub, Some(user_id.to_string()));
+        assert_eq!(info.department.as_deref(), Some("인사팀"));
+        assert!(info.exp > info.iat);
+
+        assert!(introspect_access_token(&config, "not-a-jwt").is_none());
+
+        // A forced logout deactivates the tokens issued before it
+        revoke_user_tokens(user_id);
+        assert!(introspect_access_token(&config, &token).is_none());
+    }
+
+    #[test]
+    fn test_inactive_introspection_only_reports_active() {
+        let json = serde_json::to_value(TokenIntrospection::default()).unwrap();
+        assert_eq!(json, serde_json::json!({ "active": false }));
+    }
+}
@@ -0,0 +1,360 @@
+//! Signed URLs for temporary document access
+//!
+//! Lets a user hand a document to someone without an account, such as an
+//! external auditor. `POST /api/v1/documents/:id/share` returns a link
+//!
+//! ```text
+//! /api/v1/shared/documents/<id>?scope=download&expires=<unix>&by=<user>&sig=<hmac>
+//! ```
+//!
+//! that works without authentication until it expires. The signature is an
+//! HMAC-SHA256 over the document, scope, expiry and issuing user, so none of
+//! them can be changed. Each access checks the document ACL again as the
+//! issuing user: a link stops working when that user is deactivated or
+//! loses access to the document.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::auth::jwt::DEV_SECRET;
+use crate::config_check::Environment;
+use crate::error::{AppError, ErrorCode};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Default lifetime of a link
+const DEFAULT_TTL_SECS: i64 = 24 * 3600;
+
+/// Longest lifetime a link may be given
+const DEFAULT_MAX_TTL_SECS: i64 = 7 * 24 * 3600;
+
+/// What a link allows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareScope {
+    /// Download the original as an attachment
+    #[default]
+    Download,
+    /// Open the original inline in the browser
+    Preview,
+}
+
+impl ShareScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Download => "download",
+            Self::Preview => "preview",
+        }
+    }
+}
+
+/// The document access a link grants
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareGrant {
+    pub document_id: Uuid,
+    pub scope: ShareScope,
+    /// Expiry, Unix seconds
+    pub expires: i64,
+    /// User who issued the link
+    pub issued_by: Uuid,
+}
+
+impl ShareGrant {
+    fn message(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.document_id,
+            self.scope.as_str(),
+            self.expires,
+            self.issued_by
+        )
+    }
+}
+
+/// Signing key and link lifetimes
+#[derive(Clone)]
+pub struct SharePolicy {
+    /// `None` disables sharing
+    secret: Option<String>,
+    /// Prepended to link paths, e.g. `https://otl.example.com`
+    pub base_url: Option<String>,
+    pub default_ttl_secs: i64,
+    pub max_ttl_secs: i64,
+}
+
+impl std::fmt::Debug for SharePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharePolicy")
+            .field("secret", &"<redacted>")
+            .field("base_url", &self.base_url)
+            .field("default_ttl_secs", &self.default_ttl_secs)
+            .field("max_ttl_secs", &self.max_ttl_secs)
+            .finish()
+    }
+}
+
+impl SharePolicy {
+    /// Policy signing with `secret`
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: Some(secret.into()),
+            base_url: None,
+            default_ttl_secs: DEFAULT_TTL_SECS,
+            max_ttl_secs: DEFAULT_MAX_TTL_SECS,
+        }
+    }
+
+    /// Policy from `SHARE_URL_SECRET` (falls back to `JWT_SECRET`),
+    /// `SHARE_BASE_URL`, `SHARE_URL_TTL_SECS` and `SHARE_URL_MAX_TTL_SECS`
+    pub fn from_env() -> Self {
+        Self::from_lookup(&|key| std::env::var(key).ok())
+    }
+
+    /// Policy from settings that `env` looks up by name
+    ///
+    /// Empty secrets count as unset. Without one, links are signed with the
+    /// public development key only when `OTL_ENV` is development; anywhere
+    /// else sharing is disabled.
+    fn from_lookup(env: &dyn Fn(&str) -> Option<String>) -> Self {
+        let var = |key: &str| env(key).filter(|value| !value.trim().is_empty());
+        let development = env("OTL_ENV")
+            .map_or(Ok(Environment::Development), |value| value.parse())
+            .is_ok_and(|environment| environment == Environment::Development);
+        let secret = var("SHARE_URL_SECRET")
+            .or_else(|| var("JWT_SECRET"))
+            .or_else(|| development.then(|| DEV_SECRET.to_string()));
+        if secret.is_none() {
+            tracing::warn!("Share links disabled: SHARE_URL_SECRET and JWT_SECRET are not set");
+        }
+        let mut policy = Self {
+            secret,
+            ..Self::new(String::new())
+        };
+        policy.base_url = env("SHARE_BASE_URL")
+            .map(|url| url.trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty());
+        for (name, target) in [
+            ("SHARE_URL_TTL_SECS", &mut policy.default_ttl_secs),
+            ("SHARE_URL_MAX_TTL_SECS", &mut policy.max_ttl_secs),
+        ] {
+            if let Some(value) = env(name) {
+                match value.parse::<i64>() {
+                    Ok(secs) if secs > 0 => *target = secs,
+                    _ => tracing::warn!("Ignoring invalid {}: {}", name, value),
+                }
+            }
+        }
+        policy.default_ttl_secs = policy.default_ttl_secs.min(policy.max_ttl_secs);
+        policy
+    }
+
+    /// Grant access to a document until `ttl_secs` from now (the default
+    /// lifetime if `None`)
+    pub fn grant(
+        &self,
+        document_id: Uuid,
+        scope: ShareScope,
+        ttl_secs: Option<i64>,
+        issued_by: Uuid,
+    ) -> Result<ShareGrant, AppError> {
+        if self.secret.is_none() {
+            return Err(AppError::coded(
+                ErrorCode::ServiceUnavailable,
+                "Share links are disabled: SHARE_URL_SECRET is not set",
+            ));
+        }
+        let ttl = ttl_secs.unwrap_or(self.default_ttl_secs);
+        if ttl <= 0 || ttl > self.max_ttl_secs {
+            return Err(AppError::BadRequest(format!(
+                "expires_in_secs must be between 1 and {}",
+                self.max_ttl_secs
+            )));
+        }
+        Ok(ShareGrant {
+            document_id,
+            scope,
+            expires: Utc::now().timestamp() + ttl,
+            issued_by,
+        })
+    }
+
+    fn mac(&self, grant: &ShareGrant) -> Hmac<Sha256> {
+        let secret = self
+            .secret
+            .as_deref()
+            .expect("grant and verify refuse a policy without a secret");
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(grant.message().as_bytes());
+        mac
+    }
+
+    /// Hex signature of a grant
+    pub fn sign(&self, grant: &ShareGrant) -> String {
+        hex::encode(self.mac(grant).finalize().into_bytes())
+    }
+
+    /// Link for a grant
+    pub fn url(&self, grant: &ShareGrant) -> String {
+        format!(
+            "{}/api/v1/shared/documents/{}?scope={}&expires={}&by={}&sig={}",
+            self.base_url.as_deref().unwrap_or(""),
+            grant.document_id,
+            grant.scope.as_str(),
+            grant.expires,
+            grant.issued_by,
+            self.sign(grant)
+        )
+    }
+
+    /// Check the signature and expiry of a grant at `now`
+    pub fn verify(
+        &self,
+        grant: &ShareGrant,
+        signature: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        if self.secret.is_none() {
+            return Err(AppError::Forbidden("Invalid share link".to_string()));
+        }
+        let valid = hex::decode(signature)
+            .map(|sig| self.mac(grant).verify_slice(&sig).is_ok())
+            .unwrap_or(false);
+        if !valid {
+            return Err(AppError::Forbidden("Invalid share link".to_string()));
+        }
+        if grant.expires <= now.timestamp() {
+            return Err(AppError::coded(
+                ErrorCode::ShareLinkExpired,
+                "Share link has expired",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grant(policy: &SharePolicy) -> ShareGrant {
+        policy
+            .grant(
+                Uuid::new_v4(),
+                ShareScope::Download,
+                Some(3600),
+                Uuid::new_v4(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_signed_grant_verifies() {
+        let policy = SharePolicy::new("secret");
+        let grant = grant(&policy);
+        let sig = policy.sign(&grant);
+
+        assert!(policy.verify(&grant, &sig, Utc::now()).is_ok());
+        // Another key does not accept it
+        assert!(SharePolicy::new("other")
+            .verify(&grant, &sig, Utc::now())
+            .is_err());
+    }
+
+    #[test]
+    fn test_tampered_grant_is_rejected() {
+        let policy = SharePolicy::new("secret");
+        let grant = grant(&policy);
+        let sig = policy.sign(&grant);
+
+        let mut preview = grant.clone();
+        preview.scope = ShareScope::Preview;
+        let mut longer = grant.clone();
+        longer.expires += 3600;
+        let mut other_document = grant.clone();
+        other_document.document_id = Uuid::new_v4();
+        for tampered in [preview, longer, other_document] {
+            let err = policy.verify(&tampered, &sig, Utc::now()).unwrap_err();
+            assert_eq!(err.code(), ErrorCode::Forbidden);
+        }
+        assert!(policy.verify(&grant, "not-hex", Utc::now()).is_err());
+    }
+
+    #[test]
+    fn test_expired_grant_is_gone() {
+        let policy = SharePolicy::new("secret");
+        let grant = grant(&policy);
+        let sig = policy.sign(&grant);
+
+        let later = Utc::now() + chrono::Duration::hours(2);
+        let err = policy.verify(&grant, &sig, later).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ShareLinkExpired);
+    }
+
+    #[test]
+    fn test_lifetime_is_bounded() {
+        let policy = SharePolicy::new("secret");
+        let id = Uuid::new_v4();
+        assert!(policy
+            .grant(id, ShareScope::Preview, Some(DEFAULT_MAX_TTL_SECS + 1), id)
+            .is_err());
+        assert!(policy.grant(id, ShareScope::Preview, Some(0), id).is_err());
+
+        let default = policy.grant(id, ShareScope::Preview, None, id).unwrap();
+        assert!(default.expires - Utc::now().timestamp() <= DEFAULT_TTL_SECS);
+    }
+
+    #[test]
+    fn test_secret_from_env() {
+        let policy = |vars: &[(&str, &str)]| {
+            let vars: Vec<(String, String)> = vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            SharePolicy::from_lookup(&move |key| {
+                vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+            })
+        };
+        let id = Uuid::new_v4();
+
+        // An empty secret falls through to the next one
+        let signed = policy(&[("SHARE_URL_SECRET", ""), ("JWT_SECRET", "jwt-secret")]);
+        assert_eq!(signed.secret.as_deref(), Some("jwt-secret"));
+
+        // The development key only signs in development
+        let development = policy(&[("SHARE_URL_SECRET", " ")]);
+        assert_eq!(development.secret.as_deref(), Some(DEV_SECRET));
+        let production = policy(&[("OTL_ENV", "production"), ("JWT_SECRET", "")]);
+        assert!(production.secret.is_none());
+        let err = production
+            .grant(id, ShareScope::Download, None, id)
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ServiceUnavailable);
+
+        // Links signed with the development key do not open there either
+        let grant = grant(&development);
+        let sig = development.sign(&grant);
+        assert!(production.verify(&grant, &sig, Utc::now()).is_err());
+    }
+
+    #[test]
+    fn test_url_carries_grant() {
+        let mut policy = SharePolicy::new("secret");
+        policy.base_url = Some("https://otl.example.com".to_string());
+        let grant = grant(&policy);
+
+        let url = policy.url(&grant);
+        assert!(url.starts_with(&format!(
+            "https://otl.example.com/api/v1/shared/documents/{}?scope=download",
+            grant.document_id
+        )));
+        assert!(url.ends_with(&format!("&sig={}", policy.sign(&grant))));
+    }
+}
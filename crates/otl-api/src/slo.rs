@@ -0,0 +1,516 @@
+//! Service level objectives and burn-rate alerting
+//!
+//! Requests of two endpoint classes are held to objectives: queries must
+//! answer fast and succeed, ingests must succeed. Each request of a class
+//! is an event of its objectives and a bad event when it misses them (a
+//! server error, or a successful query slower than the threshold).
+//!
+//! The tracker counts events in one-minute buckets and reports burn rates
+//! (bad-event ratio over the error budget) for windows of up to six hours;
+//! a burn rate of 1 spends the budget exactly over the SLO period. The
+//! same counts are exported as Prometheus counters, from which
+//! [`alert_rules`] builds multi-window burn-rate alerts that aggregate
+//! over all API replicas.
+//!
+//! Author: hephaex@gmail.com
+
+use axum::http::Method;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Default latency threshold of queries in milliseconds
+const DEFAULT_QUERY_LATENCY_MS: u64 = 5000;
+
+/// Default share of queries answered within the threshold
+const DEFAULT_QUERY_LATENCY_TARGET: f64 = 0.95;
+
+/// Default share of queries that succeed
+const DEFAULT_QUERY_AVAILABILITY_TARGET: f64 = 0.995;
+
+/// Default share of ingests that succeed
+const DEFAULT_INGEST_SUCCESS_TARGET: f64 = 0.99;
+
+/// Seconds per counting bucket
+const BUCKET_SECS: i64 = 60;
+
+/// Burn-rate windows reported by the tracker (label, seconds)
+pub const WINDOWS: [(&str, i64); 4] = [("5m", 300), ("30m", 1800), ("1h", 3600), ("6h", 21600)];
+
+/// Multi-window burn-rate alerts: name suffix, long and short window,
+/// burn-rate factor, severity and pending duration
+///
+/// A factor of 14.4 over one hour spends 2% of a 30-day budget, 6 over
+/// six hours 5%, and 1 over three days 10%.
+const BURN_ALERTS: [(&str, &str, &str, f64, &str, &str); 3] = [
+    ("FastBurn", "1h", "5m", 14.4, "page", "2m"),
+    ("SlowBurn", "6h", "30m", 6.0, "page", "15m"),
+    ("BudgetBurn", "3d", "6h", 1.0, "ticket", "1h"),
+];
+
+// ============================================================================
+// Objectives
+// ============================================================================
+
+/// Endpoints held to the same objectives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointClass {
+    /// RAG queries, streamed or not
+    Query,
+    /// Document uploads and reprocessing
+    Ingest,
+}
+
+impl EndpointClass {
+    /// Class of a request to a normalized endpoint, if any
+    pub fn of(method: &Method, endpoint: &str) -> Option<Self> {
+        if method != Method::POST {
+            return None;
+        }
+        match endpoint {
+            "/api/v1/query" | "/api/v1/query/stream" => Some(Self::Query),
+            "/api/v1/documents"
+            | "/api/v1/documents/tabular"
+            | "/api/v1/documents/form"
+            | "/api/v1/documents/:id/reprocess" => Some(Self::Ingest),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Ingest => "ingest",
+        }
+    }
+}
+
+/// What makes an event of an objective bad
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Indicator {
+    /// Successful requests slower than the threshold; failed requests are
+    /// left to the success objective
+    Latency { threshold_ms: u64 },
+    /// Server errors (5xx)
+    Success,
+}
+
+/// Target share of good events among the requests of a class
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Objective {
+    /// Value of the `slo` metric label
+    pub name: &'static str,
+    pub class: EndpointClass,
+    pub indicator: Indicator,
+    /// Share of good events, below 1
+    pub target: f64,
+}
+
+impl Objective {
+    /// Share of events allowed to be bad
+    pub fn error_budget(&self) -> f64 {
+        1.0 - self.target
+    }
+
+    /// Whether a request counts for the objective, and whether it is bad
+    fn classify(&self, status: u16, latency_us: u64) -> Option<bool> {
+        let failed = status >= 500;
+        match self.indicator {
+            Indicator::Latency { .. } if failed => None,
+            Indicator::Latency { threshold_ms } => Some(latency_us > threshold_ms * 1000),
+            Indicator::Success => Some(failed),
+        }
+    }
+
+    /// One-line statement of the objective
+    pub fn description(&self) -> String {
+        let share = format!("{}%", (self.target * 1000.0).round() / 10.0);
+        match self.indicator {
+            Indicator::Latency { threshold_ms } => format!(
+                "{share} of {} requests answer within {threshold_ms} ms",
+                self.class.as_str()
+            ),
+            Indicator::Success => format!("{share} of {} requests succeed", self.class.as_str()),
+        }
+    }
+}
+
+/// Objectives the API is held to
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloPolicy {
+    pub objectives: Vec<Objective>,
+}
+
+impl Default for SloPolicy {
+    fn default() -> Self {
+        Self {
+            objectives: vec![
+                Objective {
+                    name: "query_latency",
+                    class: EndpointClass::Query,
+                    indicator: Indicator::Latency {
+                        threshold_ms: DEFAULT_QUERY_LATENCY_MS,
+                    },
+                    target: DEFAULT_QUERY_LATENCY_TARGET,
+                },
+                Objective {
+                    name: "query_availability",
+                    class: EndpointClass::Query,
+                    indicator: Indicator::Success,
+                    target: DEFAULT_QUERY_AVAILABILITY_TARGET,
+                },
+                Objective {
+                    name: "ingest_success",
+                    class: EndpointClass::Ingest,
+                    indicator: Indicator::Success,
+                    target: DEFAULT_INGEST_SUCCESS_TARGET,
+                },
+            ],
+        }
+    }
+}
+
+impl SloPolicy {
+    /// Policy from `SLO_QUERY_LATENCY_MS`, `SLO_QUERY_LATENCY_TARGET`,
+    /// `SLO_QUERY_AVAILABILITY_TARGET` and `SLO_INGEST_SUCCESS_TARGET`
+    /// (targets between 0 and 1, exclusive)
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        for objective in &mut policy.objectives {
+            let var = format!("SLO_{}_TARGET", objective.name.to_uppercase());
+            if let Ok(target) = std::env::var(&var) {
+                match target.parse::<f64>() {
+                    Ok(target) if target > 0.0 && target < 1.0 => objective.target = target,
+                    _ => tracing::warn!("Ignoring invalid {}: {}", var, target),
+                }
+            }
+            if let Indicator::Latency { threshold_ms } = &mut objective.indicator {
+                if let Ok(ms) = std::env::var("SLO_QUERY_LATENCY_MS") {
+                    match ms.parse::<u64>() {
+                        Ok(ms) if ms > 0 => *threshold_ms = ms,
+                        _ => tracing::warn!("Ignoring invalid SLO_QUERY_LATENCY_MS: {}", ms),
+                    }
+                }
+            }
+        }
+        policy
+    }
+}
+
+// ============================================================================
+// Tracking
+// ============================================================================
+
+/// Events of one bucket
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    /// Bucket start (Unix seconds divided by [`BUCKET_SECS`])
+    index: i64,
+    events: u64,
+    bad: u64,
+}
+
+/// Event counts of one objective
+#[derive(Debug, Default)]
+struct Series {
+    /// Buckets of the longest window, oldest first
+    buckets: VecDeque<Bucket>,
+    events: u64,
+    bad: u64,
+}
+
+/// Burn rate of an objective over one window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowBurn {
+    pub window: &'static str,
+    pub events: u64,
+    pub bad_events: u64,
+    /// Bad-event ratio over the error budget (0 without events)
+    pub burn_rate: f64,
+}
+
+/// State of one objective
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloStatus {
+    #[serde(flatten)]
+    pub objective: Objective,
+    pub description: String,
+    /// Events since the server started
+    pub events: u64,
+    pub bad_events: u64,
+    pub windows: Vec<WindowBurn>,
+}
+
+/// Counts the events of every objective
+#[derive(Debug)]
+pub struct SloTracker {
+    policy: SloPolicy,
+    series: Mutex<Vec<Series>>,
+}
+
+impl SloTracker {
+    pub fn new(policy: SloPolicy) -> Self {
+        let series = policy
+            .objectives
+            .iter()
+            .map(|_| Series::default())
+            .collect();
+        Self {
+            policy,
+            series: Mutex::new(series),
+        }
+    }
+
+    pub fn policy(&self) -> &SloPolicy {
+        &self.policy
+    }
+
+    /// Record a finished request to a normalized endpoint
+    pub fn record(&self, method: &Method, endpoint: &str, status: u16, latency_us: u64) {
+        if let Some(class) = EndpointClass::of(method, endpoint) {
+            self.record_at(chrono::Utc::now().timestamp(), class, status, latency_us);
+        }
+    }
+
+    fn record_at(&self, now: i64, class: EndpointClass, status: u16, latency_us: u64) {
+        let index = now.div_euclid(BUCKET_SECS);
+        let oldest = index - longest_window() / BUCKET_SECS;
+        let mut series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        for (objective, series) in self.policy.objectives.iter().zip(series.iter_mut()) {
+            if objective.class != class {
+                continue;
+            }
+            let Some(bad) = objective.classify(status, latency_us) else {
+                continue;
+            };
+            while series.buckets.front().is_some_and(|b| b.index <= oldest) {
+                series.buckets.pop_front();
+            }
+            if series.buckets.back().map(|b| b.index) != Some(index) {
+                series.buckets.push_back(Bucket {
+                    index,
+                    ..Bucket::default()
+                });
+            }
+            if let Some(bucket) = series.buckets.back_mut() {
+                bucket.events += 1;
+                bucket.bad += u64::from(bad);
+            }
+            series.events += 1;
+            series.bad += u64::from(bad);
+        }
+    }
+
+    /// State of every objective
+    pub fn snapshot(&self) -> Vec<SloStatus> {
+        self.snapshot_at(chrono::Utc::now().timestamp())
+    }
+
+    fn snapshot_at(&self, now: i64) -> Vec<SloStatus> {
+        let index = now.div_euclid(BUCKET_SECS);
+        let series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        self.policy
+            .objectives
+            .iter()
+            .zip(series.iter())
+            .map(|(objective, series)| SloStatus {
+                objective: objective.clone(),
+                description: objective.description(),
+                events: series.events,
+                bad_events: series.bad,
+                windows: WINDOWS
+                    .iter()
+                    .map(|&(window, secs)| {
+                        let oldest = index - secs / BUCKET_SECS;
+                        let (events, bad_events) = series
+                            .buckets
+                            .iter()
+                            .filter(|b| b.index > oldest)
+                            .fold((0, 0), |(e, b), bucket| (e + bucket.events, b + bucket.bad));
+                        let burn_rate = if events == 0 {
+                            0.0
+                        } else {
+                            bad_events as f64 / events as f64 / objective.error_budget()
+                        };
+                        WindowBurn {
+                            window,
+                            events,
+                            bad_events,
+                            burn_rate,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+fn longest_window() -> i64 {
+    WINDOWS.iter().map(|&(_, secs)| secs).max().unwrap_or(0)
+}
+
+// ============================================================================
+// Alert rules
+// ============================================================================
+
+/// `QueryLatency` for `query_latency`
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn error_ratio(name: &str, window: &str) -> String {
+    format!(
+        "sum(rate(otl_slo_bad_events_total{{slo=\"{name}\"}}[{window}])) / \
+         sum(rate(otl_slo_events_total{{slo=\"{name}\"}}[{window}]))"
+    )
+}
+
+/// Prometheus rule file with burn-rate alerts for every objective
+///
+/// Each objective gets a fast and a slow paging alert and a ticket for a
+/// steady burn; an alert fires when both of its windows burn too fast, so
+/// it resolves soon after the burn stops.
+pub fn alert_rules(policy: &SloPolicy) -> String {
+    let mut rules = String::from(
+        "# OTL service level objective alerts\n\
+         # Generated from the SLO policy of the API server; load into Prometheus\n\
+         # and route on the severity label in Alertmanager.\n\
+         groups:\n  - name: otl-slo\n    rules:\n",
+    );
+    for objective in &policy.objectives {
+        let threshold = objective.error_budget();
+        for (suffix, long, short, factor, severity, pending) in BURN_ALERTS {
+            let lines = [
+                format!(
+                    "      - alert: OtlSlo{}{suffix}",
+                    camel_case(objective.name)
+                ),
+                "        expr: |".to_string(),
+                format!(
+                    "          ({}) > ({factor} * {threshold:.4})",
+                    error_ratio(objective.name, long)
+                ),
+                "          and".to_string(),
+                format!(
+                    "          ({}) > ({factor} * {threshold:.4})",
+                    error_ratio(objective.name, short)
+                ),
+                format!("        for: {pending}"),
+                "        labels:".to_string(),
+                format!("          severity: {severity}"),
+                format!("          slo: {}", objective.name),
+                format!("          class: {}", objective.class.as_str()),
+                "        annotations:".to_string(),
+                format!(
+                    "          summary: \"{} error budget burning {factor}x too fast\"",
+                    objective.name
+                ),
+                format!(
+                    "          description: \"Objective: {}. Over {long} and {short} the \
+                     bad-event ratio exceeds {factor} times the error budget.\"",
+                    objective.description()
+                ),
+            ];
+            for line in lines {
+                rules.push_str(&line);
+                rules.push('\n');
+            }
+        }
+    }
+    rules
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_classes() {
+        assert_eq!(
+            EndpointClass::of(&Method::POST, "/api/v1/query"),
+            Some(EndpointClass::Query)
+        );
+        assert_eq!(
+            EndpointClass::of(&Method::POST, "/api/v1/documents/:id/reprocess"),
+            Some(EndpointClass::Ingest)
+        );
+        assert_eq!(EndpointClass::of(&Method::GET, "/api/v1/documents"), None);
+        assert_eq!(
+            EndpointClass::of(&Method::POST, "/api/v1/query/estimate"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_burn_rates_per_window() {
+        let tracker = SloTracker::new(SloPolicy::default());
+        let now = 1_700_000_000;
+
+        // Two hours ago: 100 fast queries, 10 failed
+        for i in 0..100 {
+            let status = if i < 10 { 500 } else { 200 };
+            tracker.record_at(now - 7200, EndpointClass::Query, status, 1_000_000);
+        }
+        // Last minute: 20 queries, 5 slower than 5 s
+        for i in 0..20 {
+            let latency = if i < 5 { 8_000_000 } else { 2_000_000 };
+            tracker.record_at(now, EndpointClass::Query, 200, latency);
+        }
+        tracker.record_at(now, EndpointClass::Ingest, 422, 30_000_000);
+
+        let status = tracker.snapshot_at(now);
+        let latency = &status[0];
+        assert_eq!((latency.events, latency.bad_events), (110, 5));
+        let burn = |s: &SloStatus, window: &str| {
+            s.windows
+                .iter()
+                .find(|w| w.window == window)
+                .map(|w| w.burn_rate)
+                .unwrap()
+        };
+        // 25% slow over a 5% budget
+        assert!((burn(latency, "5m") - 5.0).abs() < 1e-9);
+        assert!((burn(latency, "6h") - 5.0 / 110.0 / 0.05).abs() < 1e-9);
+
+        let availability = &status[1];
+        assert_eq!(burn(availability, "1h"), 0.0);
+        assert!((burn(availability, "6h") - 10.0 / 120.0 / 0.005).abs() < 1e-9);
+
+        let ingest = &status[2];
+        assert_eq!((ingest.events, ingest.bad_events), (1, 0));
+
+        // Buckets older than the longest window are dropped
+        tracker.record_at(now + 6 * 3600, EndpointClass::Query, 500, 1_000);
+        let later = tracker.snapshot_at(now + 6 * 3600);
+        assert_eq!(later[1].windows[3].events, 1);
+        assert_eq!(later[1].events, 121);
+    }
+
+    #[test]
+    fn test_alert_rules_cover_every_objective() {
+        let rules = alert_rules(&SloPolicy::default());
+        assert!(rules.starts_with("# OTL service level objective alerts"));
+        assert_eq!(rules.matches("      - alert: OtlSlo").count(), 9);
+        assert!(rules.contains("- alert: OtlSloQueryLatencyFastBurn"));
+        assert!(rules.contains(
+            "(sum(rate(otl_slo_bad_events_total{slo=\"ingest_success\"}[3d])) / \
+             sum(rate(otl_slo_events_total{slo=\"ingest_success\"}[3d]))) > (1 * 0.0100)"
+        ));
+        assert!(rules.contains("severity: ticket"));
+        assert!(rules.contains("95% of query requests answer within 5000 ms"));
+    }
+}
@@ -2,17 +2,44 @@
 //!
 //! Author: hephaex@gmail.com
 
+use crate::access_requests::AccessRequestPolicy;
+use crate::classification::ClassificationPolicy;
+use crate::content_gaps::ContentGapPolicy;
+use crate::dedup::DedupPolicy;
+use crate::embedding_migration::EmbeddingMigrationPolicy;
+use crate::export::ExportJobs;
+use crate::faq::FaqPolicy;
+use crate::freshness::FreshnessPolicy;
+use crate::notifications::{NotificationPolicy, Notifier};
+use crate::quality_audit::QualityAuditPolicy;
+use crate::retention::RetentionPolicy;
+use crate::review::ReviewPolicy;
+use crate::saved_searches::SavedSearchPolicy;
+use crate::share::SharePolicy;
+use crate::slo::{SloPolicy, SloTracker};
 use otl_core::config::AppConfig;
-use otl_core::{LlmClient, SearchBackend, User};
+use otl_core::{
+    AnalyzerSettings, BlobStore, FaqStore, FsBlobStore, GlossaryStore, Keyring, KnowledgeSpace,
+    LlmClient, MetadataStore, OtlError, Persona, PromptRegistry, RetrievalScope, SearchBackend,
+    SharedAnalyzer, SourceReference, SpaceRegistry, SynonymRegistry, User,
+};
 use otl_graph::SurrealDbStore;
-use otl_rag::{HybridRagOrchestrator, RagConfig as OtlRagConfig};
-use otl_vector::VectorSearchBackend;
+use otl_rag::{
+    CacheConfig, FeedbackConfig, FeedbackPriors, FeedbackVote, HybridRagOrchestrator,
+    RagCacheManager, RagConfig as OtlRagConfig,
+};
+use otl_vector::{EmbeddingClient, VectorSearchBackend};
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Maximum number of answered queries whose suggestions and citations are
+/// kept
+const MAX_STORED_QUERIES: usize = 1000;
 
 /// Application state shared across handlers
 pub struct AppState {
@@ -44,6 +71,254 @@ pub struct AppState {
     pub cache_hits: AtomicU64,
     /// Cache miss counter (if cache is enabled)
     pub cache_misses: AtomicU64,
+    /// Follow-up suggestions of recently answered queries
+    pub suggestions: RwLock<SuggestionStore>,
+    /// Cited chunks of recently answered queries, for citation feedback
+    pub citations: RwLock<QueryStore<Vec<SourceReference>>>,
+    /// Citation votes cast on recently answered queries, so a repeated vote
+    /// is counted once
+    pub votes: RwLock<QueryStore<VoteBook>>,
+    /// Popularity priors learned from citation feedback
+    pub feedback: Arc<FeedbackPriors>,
+    /// Synonyms shared by the search backends (seeded from the NER dictionary)
+    pub synonyms: Arc<SynonymRegistry>,
+    /// Answer personas, selected by the department of the caller
+    pub prompts: Arc<PromptRegistry>,
+    /// Knowledge spaces scoping retrieval, by request, topic or caller
+    /// department
+    pub spaces: Arc<SpaceRegistry>,
+    /// Embedding, query and answer caches
+    pub rag_cache: Arc<RagCacheManager>,
+    /// Keyword analyzer shared by query analysis and graph keyword search
+    pub analyzer: Arc<SharedAnalyzer>,
+    /// Retention of soft-deleted documents
+    pub retention: RetentionPolicy,
+    /// Storage of original documents
+    pub blob_store: Arc<dyn BlobStore>,
+    /// Data keys of encrypted (Restricted) document content
+    pub keyring: Arc<Keyring>,
+    /// Background document export jobs
+    pub export_jobs: Arc<ExportJobs>,
+    /// Which queries are logged as content gaps
+    pub content_gaps: ContentGapPolicy,
+    /// Query history recording and FAQ generation
+    pub faq: FaqPolicy,
+    /// Held while a FAQ generation run is in progress
+    pub faq_generation: Arc<tokio::sync::Mutex<()>>,
+    /// Stale document checks and alert webhooks
+    pub freshness: FreshnessPolicy,
+    /// Shadow testing of embedding migrations
+    pub embedding_migration_policy: EmbeddingMigrationPolicy,
+    /// Held while an embedding migration is re-embedding
+    pub embedding_migration: Arc<tokio::sync::Mutex<()>>,
+    /// Review locks and reviewer assignment
+    pub review: ReviewPolicy,
+    /// Auto-approval of extractions and audit sampling
+    pub quality_audit: QualityAuditPolicy,
+    /// Signing key and lifetimes of signed document links
+    pub share: SharePolicy,
+    /// Notification and expiry of document access requests
+    pub access_requests: AccessRequestPolicy,
+    /// Email and Slack notification channels and templates
+    pub notifications: Arc<Notifier>,
+    /// Saved search matching of new documents
+    pub saved_searches: SavedSearchPolicy,
+    /// Near-duplicate chunk detection at ingest
+    pub dedup: DedupPolicy,
+    /// Access level suggestions for uploaded documents
+    pub classification: ClassificationPolicy,
+    /// Service level objectives of query and ingest endpoints
+    pub slo: SloTracker,
+}
+
+/// Bounded store of per-query data keyed by query ID
+///
+/// Oldest entries are evicted first once the capacity is reached.
+#[derive(Debug)]
+pub struct QueryStore<T> {
+    capacity: usize,
+    order: VecDeque<Uuid>,
+    entries: HashMap<Uuid, T>,
+}
+
+/// Follow-up suggestions keyed by query ID
+pub type SuggestionStore = QueryStore<Vec<String>>;
+
+/// Latest vote of each user on each cited chunk of one query, keyed by
+/// user, document and chunk
+pub type VoteBook = HashMap<(Uuid, Uuid, Option<u32>), FeedbackVote>;
+
+impl<T> QueryStore<T> {
+    /// Create a store holding at most `capacity` queries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Store data for a query, evicting the oldest entry if full
+    pub fn insert(&mut self, query_id: Uuid, value: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(query_id, value).is_none() {
+            self.order.push_back(query_id);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    /// Get the data of a query
+    pub fn get(&self, query_id: &Uuid) -> Option<&T> {
+        self.entries.get(query_id)
+    }
+
+    /// Get the data of a query for changing it
+    pub fn get_mut(&mut self, query_id: &Uuid) -> Option<&mut T> {
+        self.entries.get_mut(query_id)
+    }
+
+    /// Number of stored queries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Cache configuration from `RAG_*CACHE*` environment variables
+fn cache_config_from_env() -> CacheConfig {
+    let mut config = CacheConfig::default();
+    if let Ok(ttl) = std::env::var("RAG_ANSWER_CACHE_TTL_SECS") {
+        match ttl.parse::<u64>() {
+            Ok(ttl) => config.answer_ttl_seconds = ttl,
+            Err(_) => tracing::warn!("Ignoring invalid RAG_ANSWER_CACHE_TTL_SECS: {}", ttl),
+        }
+    }
+    if let Ok(url) = std::env::var("RAG_CACHE_REDIS_URL") {
+        config.backend = otl_rag::CacheBackendKind::Redis {
+            url,
+            key_prefix: std::env::var("RAG_CACHE_KEY_PREFIX").unwrap_or_else(|_| "otl".to_string()),
+        };
+    }
+    if let Ok(path) = std::env::var("RAG_EMBEDDING_CACHE_PATH") {
+        config.embedding_store_path = Some(path.into());
+    }
+    if let Ok(max) = std::env::var("RAG_EMBEDDING_CACHE_MAX_ENTRIES") {
+        match max.parse::<usize>() {
+            Ok(max) => config.embedding_store_max_entries = max,
+            Err(_) => tracing::warn!("Ignoring invalid RAG_EMBEDDING_CACHE_MAX_ENTRIES: {}", max),
+        }
+    }
+    if let Ok(ttl) = std::env::var("RAG_LLM_CACHE_TTL_SECS") {
+        match ttl.parse::<u64>() {
+            Ok(ttl) => config.llm_ttl_seconds = ttl,
+            Err(_) => tracing::warn!("Ignoring invalid RAG_LLM_CACHE_TTL_SECS: {}", ttl),
+        }
+    }
+    if let Ok(max) = std::env::var("RAG_LLM_CACHE_MAX_ENTRIES") {
+        match max.parse::<u64>() {
+            Ok(max) => config.llm_max_capacity = max,
+            Err(_) => tracing::warn!("Ignoring invalid RAG_LLM_CACHE_MAX_ENTRIES: {}", max),
+        }
+    }
+    config
+}
+
+/// Feedback prior settings from `RAG_FEEDBACK` (defaults when unset or
+/// invalid)
+fn feedback_config_from_env() -> FeedbackConfig {
+    let Ok(json) = std::env::var("RAG_FEEDBACK") else {
+        return FeedbackConfig::default();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring invalid RAG_FEEDBACK: {}", e);
+        FeedbackConfig::default()
+    })
+}
+
+/// Personas from the JSON array in `RAG_PERSONAS` (none when unset or
+/// invalid)
+fn personas_from_env() -> PromptRegistry {
+    let Ok(json) = std::env::var("RAG_PERSONAS") else {
+        return PromptRegistry::new();
+    };
+    match serde_json::from_str::<Vec<Persona>>(&json) {
+        Ok(personas) => PromptRegistry::from_personas(personas.into_iter().filter(|p| {
+            let named = !p.name.trim().is_empty();
+            if !named {
+                tracing::warn!("Ignoring persona without a name in RAG_PERSONAS");
+            }
+            named
+        })),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid RAG_PERSONAS: {}", e);
+            PromptRegistry::new()
+        }
+    }
+}
+
+/// Knowledge spaces from the JSON array in `RAG_KNOWLEDGE_SPACES` (none
+/// when unset or invalid)
+fn spaces_from_env() -> SpaceRegistry {
+    let Ok(json) = std::env::var("RAG_KNOWLEDGE_SPACES") else {
+        return SpaceRegistry::new();
+    };
+    match serde_json::from_str::<Vec<KnowledgeSpace>>(&json) {
+        Ok(spaces) => SpaceRegistry::from_spaces(spaces.into_iter().filter(|s| {
+            let valid = !s.name.trim().is_empty()
+                && !s.name.eq_ignore_ascii_case(otl_core::space::GLOBAL_SPACE);
+            if !valid {
+                tracing::warn!("Ignoring unnamed or reserved space in RAG_KNOWLEDGE_SPACES");
+            }
+            valid
+        })),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid RAG_KNOWLEDGE_SPACES: {}", e);
+            SpaceRegistry::new()
+        }
+    }
+}
+
+/// Analyzer settings from the JSON file at `RAG_ANALYZER_CONFIG` (built-in
+/// defaults when unset), with the nouns of `RAG_KEYWORD_NOUNS` added to the
+/// Korean settings
+pub fn analyzer_settings_from_env() -> otl_core::Result<AnalyzerSettings> {
+    let mut settings = match std::env::var("RAG_ANALYZER_CONFIG") {
+        Ok(path) => {
+            let json = std::fs::read_to_string(&path).map_err(|e| {
+                OtlError::ConfigError(format!("Failed to read analyzer config {path}: {e}"))
+            })?;
+            serde_json::from_str(&json).map_err(|e| {
+                OtlError::ConfigError(format!("Invalid analyzer config {path}: {e}"))
+            })?
+        }
+        Err(_) => AnalyzerSettings::default(),
+    };
+    if let Ok(nouns) = std::env::var("RAG_KEYWORD_NOUNS") {
+        settings.ko.nouns.extend(
+            nouns
+                .split(',')
+                .map(str::trim)
+                .filter(|noun| !noun.is_empty())
+                .map(str::to_string),
+        );
+    }
+    Ok(settings)
+}
+
+impl<T> Default for QueryStore<T> {
+    fn default() -> Self {
+        Self::new(MAX_STORED_QUERIES)
+    }
 }
 
 /// Metrics for a specific endpoint
@@ -85,6 +360,15 @@ pub struct LatencyBuckets {
 impl AppState {
     /// Create new application state with config and database pool
     pub fn new(config: AppConfig, db_pool: PgPool) -> Self {
+        let blob_store = otl_core::blob::from_config(&config.storage).unwrap_or_else(|e| {
+            tracing::warn!("Storing originals on the filesystem: {}", e);
+            Arc::new(FsBlobStore::new(&config.storage.path))
+        });
+        tracing::info!("Original documents stored in {}", blob_store.name());
+        let keyring = Keyring::from_config(&config.encryption).unwrap_or_else(|e| {
+            tracing::warn!("Content encryption disabled: {}", e);
+            Keyring::new(None)
+        });
         Self {
             config,
             db_pool,
@@ -100,6 +384,41 @@ impl AppState {
             metrics: RwLock::new(HashMap::new()),
             cache_hits: AtomicU64::new(0),
             cache_misses: AtomicU64::new(0),
+            suggestions: RwLock::new(SuggestionStore::default()),
+            citations: RwLock::new(QueryStore::default()),
+            votes: RwLock::new(QueryStore::default()),
+            feedback: Arc::new(FeedbackPriors::new(feedback_config_from_env())),
+            synonyms: Arc::new(SynonymRegistry::from_groups(
+                otl_extractor::ner::RuleBasedNer::new().synonym_groups(),
+            )),
+            prompts: Arc::new(personas_from_env()),
+            spaces: Arc::new(spaces_from_env()),
+            rag_cache: Arc::new(RagCacheManager::with_config(&cache_config_from_env())),
+            analyzer: Arc::new(SharedAnalyzer::new(
+                analyzer_settings_from_env().unwrap_or_else(|e| {
+                    tracing::warn!("Using default analyzer settings: {}", e);
+                    AnalyzerSettings::default()
+                }),
+            )),
+            retention: RetentionPolicy::from_env(),
+            blob_store,
+            keyring: Arc::new(keyring),
+            export_jobs: Arc::new(ExportJobs::default()),
+            content_gaps: ContentGapPolicy::from_env(),
+            faq: FaqPolicy::from_env(),
+            faq_generation: Arc::new(tokio::sync::Mutex::new(())),
+            freshness: FreshnessPolicy::from_env(),
+            embedding_migration_policy: EmbeddingMigrationPolicy::from_env(),
+            embedding_migration: Arc::new(tokio::sync::Mutex::new(())),
+            review: ReviewPolicy::from_env(),
+            quality_audit: QualityAuditPolicy::from_env(),
+            share: SharePolicy::from_env(),
+            access_requests: AccessRequestPolicy::from_env(),
+            notifications: Arc::new(Notifier::from_policy(NotificationPolicy::from_env())),
+            saved_searches: SavedSearchPolicy::from_env(),
+            dedup: DedupPolicy::from_env(),
+            classification: ClassificationPolicy::from_env(),
+            slo: SloTracker::new(SloPolicy::from_env()),
         }
     }
 
@@ -129,19 +448,159 @@ impl AppState {
     }
 
     /// Initialize RAG orchestrator with provided backends
+    ///
+    /// The embedding client, when given, scores sentences for extractive answers.
+    /// A graph database set beforehand enables entity-aware graph retrieval.
     pub async fn initialize_rag(
         &self,
         vector_store: Arc<dyn SearchBackend>,
         graph_store: Arc<dyn SearchBackend>,
         llm_client: Arc<dyn LlmClient>,
+        embedding_client: Option<Arc<dyn EmbeddingClient>>,
     ) {
-        let rag_config = OtlRagConfig::default();
-        let orchestrator = HybridRagOrchestrator::new(
+        let mut rag_config = OtlRagConfig::default();
+        if let Ok(json) = std::env::var("RAG_CONFIDENCE_CALIBRATOR") {
+            match serde_json::from_str(&json) {
+                Ok(calibrator) => rag_config.confidence_calibrator = calibrator,
+                Err(e) => tracing::warn!("Ignoring invalid RAG_CONFIDENCE_CALIBRATOR: {}", e),
+            }
+        }
+        if let Ok(ratio) = std::env::var("RAG_COMPRESSION_RATIO") {
+            match ratio.parse::<f32>() {
+                Ok(ratio) if ratio > 0.0 && ratio <= 1.0 => rag_config.compression_ratio = ratio,
+                _ => tracing::warn!("Ignoring invalid RAG_COMPRESSION_RATIO: {}", ratio),
+            }
+        }
+        if let Ok(lambda) = std::env::var("RAG_MMR_LAMBDA") {
+            match lambda.parse::<f32>() {
+                Ok(lambda) if (0.0..=1.0).contains(&lambda) => rag_config.diversity.lambda = lambda,
+                _ => tracing::warn!("Ignoring invalid RAG_MMR_LAMBDA: {}", lambda),
+            }
+        }
+        if let Ok(repair) = std::env::var("RAG_REPAIR_CITATIONS") {
+            match repair.parse::<bool>() {
+                Ok(repair) => rag_config.repair_dangling_citations = repair,
+                Err(_) => tracing::warn!("Ignoring invalid RAG_REPAIR_CITATIONS: {}", repair),
+            }
+        }
+        if let Ok(confidence) = std::env::var("RAG_GLOSSARY_CANDIDATE_MIN_CONFIDENCE") {
+            match confidence.parse::<f32>() {
+                Ok(confidence) if (0.0..=1.0).contains(&confidence) => {
+                    rag_config.glossary_candidate_min_confidence = confidence
+                }
+                _ => tracing::warn!(
+                    "Ignoring invalid RAG_GLOSSARY_CANDIDATE_MIN_CONFIDENCE: {}",
+                    confidence
+                ),
+            }
+        }
+        if let Ok(similarity) = std::env::var("RAG_FAQ_MIN_SIMILARITY") {
+            match similarity.parse::<f32>() {
+                Ok(similarity) if (0.0..=1.0).contains(&similarity) => {
+                    rag_config.faq_min_similarity = similarity
+                }
+                _ => tracing::warn!("Ignoring invalid RAG_FAQ_MIN_SIMILARITY: {}", similarity),
+            }
+        }
+        if let Ok(strict) = std::env::var("RAG_STRICT_BACKENDS") {
+            match strict.parse::<bool>() {
+                Ok(strict) => rag_config.strict_backends = strict,
+                Err(_) => tracing::warn!("Ignoring invalid RAG_STRICT_BACKENDS: {}", strict),
+            }
+        }
+        if let Ok(json) = std::env::var("RAG_TIMEOUTS") {
+            match serde_json::from_str::<otl_rag::TimeoutBudgets>(&json) {
+                Ok(timeouts) if timeouts.total_ms > 0 => rag_config.timeouts = timeouts,
+                Ok(_) => tracing::warn!("Ignoring invalid RAG_TIMEOUTS: total_ms must be positive"),
+                Err(e) => tracing::warn!("Ignoring invalid RAG_TIMEOUTS: {}", e),
+            }
+        }
+        if let Ok(json) = std::env::var("RAG_MODEL_ROUTING") {
+            match serde_json::from_str::<otl_rag::ModelRouting>(&json) {
+                Ok(routing) => match routing.validate() {
+                    Ok(()) => rag_config.model_routing = routing,
+                    Err(e) => tracing::warn!("Ignoring invalid RAG_MODEL_ROUTING: {}", e),
+                },
+                Err(e) => tracing::warn!("Ignoring invalid RAG_MODEL_ROUTING: {}", e),
+            }
+        }
+        if let Ok(json) = std::env::var("RAG_COST_MODEL") {
+            match serde_json::from_str(&json) {
+                Ok(cost) => rag_config.cost = cost,
+                Err(e) => tracing::warn!("Ignoring invalid RAG_COST_MODEL: {}", e),
+            }
+        }
+        if let Ok(json) = std::env::var("RAG_RANKING_BOOSTS") {
+            match serde_json::from_str(&json) {
+                Ok(boosts) => rag_config.ranking = boosts,
+                Err(e) => tracing::warn!("Ignoring invalid RAG_RANKING_BOOSTS: {}", e),
+            }
+        }
+        if let Ok(json) = std::env::var("RAG_CONTEXT_EXPANSION") {
+            match serde_json::from_str(&json) {
+                Ok(expansion) => rag_config.context_expansion = expansion,
+                Err(e) => tracing::warn!("Ignoring invalid RAG_CONTEXT_EXPANSION: {}", e),
+            }
+        }
+        if let Ok(moderation) = std::env::var("RAG_MODERATION") {
+            match serde_json::from_str::<otl_rag::ModerationConfig>(&moderation) {
+                Ok(config) => match otl_rag::Moderator::new(&config) {
+                    Ok(_) => rag_config.moderation = config,
+                    Err(e) => tracing::warn!("Ignoring invalid RAG_MODERATION: {}", e),
+                },
+                Err(e) => tracing::warn!("Ignoring invalid RAG_MODERATION: {}", e),
+            }
+        }
+        let tiers = rag_config.model_routing.tiers.clone();
+        let mut orchestrator = HybridRagOrchestrator::new(
             vector_store.clone(),
             graph_store.clone(),
             llm_client.clone(),
             rag_config,
-        );
+        )
+        .with_model_name(self.config.llm.model.clone());
+        for (name, tier) in tiers {
+            match otl_rag::create_llm_client(&tier.llm_config(&self.config.llm)) {
+                Ok(client) => {
+                    tracing::info!("Model tier {} uses {}", name, tier.model);
+                    orchestrator = orchestrator.with_model_tier(name, Arc::from(client));
+                }
+                Err(e) => tracing::warn!("Model tier {} is unavailable: {}", name, e),
+            }
+        }
+        if let Some(client) = embedding_client {
+            orchestrator = orchestrator.with_embedding_client(client);
+        }
+        orchestrator = orchestrator
+            .with_synonyms(self.synonyms.clone())
+            .with_feedback(self.feedback.clone())
+            .with_metadata_store(Arc::new(
+                MetadataStore::from_pool(self.db_pool.clone()).with_keyring(self.keyring.clone()),
+            ))
+            .with_glossary(Arc::new(GlossaryStore::from_pool(self.db_pool.clone())))
+            .with_faq(Arc::new(FaqStore::from_pool(self.db_pool.clone())));
+        orchestrator = orchestrator
+            .with_ontology_classes(crate::handlers::graph::default_ontology().to_core_classes());
+        if let Some(graph_db) = self.graph_db.read().await.clone() {
+            orchestrator = orchestrator.with_graph_context(graph_db);
+        }
+        if self.rag_cache.config().answer_ttl_seconds > 0 {
+            orchestrator = orchestrator.with_cache(self.rag_cache.clone());
+        }
+        orchestrator = orchestrator.with_keyword_analyzer(self.analyzer.clone());
+        if let Ok(path) = std::env::var("RAG_INTENT_TRAINING_DATA") {
+            match otl_rag::NaiveBayesIntentClassifier::from_csv_file(&path) {
+                Ok(classifier) => {
+                    tracing::info!(
+                        "Intent classifier trained on {} examples from {}",
+                        classifier.examples(),
+                        path
+                    );
+                    orchestrator = orchestrator.with_intent_classifier(Arc::new(classifier));
+                }
+                Err(e) => tracing::warn!("Using rule-based intent detection: {}", e),
+            }
+        }
 
         *self.vector_store.write().await = Some(vector_store);
         *self.graph_store.write().await = Some(graph_store);
@@ -169,6 +628,26 @@ impl AppState {
         self.rag.read().await.is_some()
     }
 
+    /// Retrieval scope of a query and the persona answering it
+    ///
+    /// The scope covers the requested space, else the spaces the question
+    /// is routed to by topic, else the default space of the caller's
+    /// department. A space naming a registered persona replaces the persona
+    /// selected by department.
+    pub fn scope_and_persona(
+        &self,
+        requested: Option<&str>,
+        question: &str,
+        department: Option<&str>,
+    ) -> otl_core::Result<(Option<RetrievalScope>, Option<Persona>)> {
+        let route = self.spaces.resolve(requested, question, department)?;
+        let persona = route
+            .persona()
+            .and_then(|name| self.prompts.get(name))
+            .or_else(|| self.prompts.select(department));
+        Ok((route.scope(), persona))
+    }
+
     /// Get default user for API requests (can be extended with auth)
     pub fn get_default_user(&self, user_id: Option<&str>) -> User {
         match user_id {
@@ -212,6 +691,26 @@ impl AppState {
         }
     }
 
+    /// Remember follow-up suggestions for an answered query
+    pub async fn store_suggestions(&self, query_id: Uuid, suggestions: Vec<String>) {
+        self.suggestions.write().await.insert(query_id, suggestions);
+    }
+
+    /// Get follow-up suggestions for a previously answered query
+    pub async fn get_suggestions(&self, query_id: &Uuid) -> Option<Vec<String>> {
+        self.suggestions.read().await.get(query_id).cloned()
+    }
+
+    /// Remember the cited chunks of an answered query
+    pub async fn store_citations(&self, query_id: Uuid, citations: Vec<SourceReference>) {
+        self.citations.write().await.insert(query_id, citations);
+    }
+
+    /// Get the cited chunks of a previously answered query
+    pub async fn get_citations(&self, query_id: &Uuid) -> Option<Vec<SourceReference>> {
+        self.citations.read().await.get(query_id).cloned()
+    }
+
     /// Record a cache hit
     pub fn record_cache_hit(&self) {
         self.cache_hits.fetch_add(1, Ordering::SeqCst);
@@ -229,3 +728,34 @@ impl AppState {
         (hits, misses)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestion_store_evicts_oldest() {
+        let mut store = SuggestionStore::new(2);
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        store.insert(a, vec!["a".to_string()]);
+        store.insert(b, vec!["b".to_string()]);
+        store.insert(c, vec!["c".to_string()]);
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&a).is_none());
+        assert_eq!(store.get(&c), Some(&vec!["c".to_string()]));
+    }
+
+    #[test]
+    fn test_suggestion_store_overwrite_keeps_single_entry() {
+        let mut store = SuggestionStore::new(2);
+        let id = Uuid::new_v4();
+
+        store.insert(id, vec!["first".to_string()]);
+        store.insert(id, vec!["second".to_string()]);
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&id), Some(&vec!["second".to_string()]));
+    }
+}
@@ -0,0 +1,558 @@
+//! User administration
+//!
+//! Admins list and search accounts, change their role and department,
+//! deactivate and reactivate them and issue password resets; every change
+//! is audit logged. Deactivating an account or changing its role or
+//! department also rejects the access tokens already issued to it, which
+//! carry the old role and department. The last active admin can be
+//! neither demoted nor deactivated, so someone can always administer the
+//! server.
+//!
+//! A password reset sends nothing: the admin receives a one-time token,
+//! valid for [`RESET_TOKEN_TTL_HOURS`], to hand to the user, who sets a new
+//! password with `POST /api/v1/auth/password-reset`. Only a hash of the
+//! token is stored.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::audit::AuditEvent;
+use crate::auth::middleware::revoke_user_tokens;
+use crate::auth::password::{hash_password, PasswordPolicy};
+use crate::auth::service::{hash_token, random_token};
+use crate::auth::UserRole;
+use crate::error::{AppError, ErrorCode};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Hours a password reset token stays valid
+pub const RESET_TOKEN_TTL_HOURS: i64 = 24;
+
+/// Largest page of the user list
+const MAX_PAGE_SIZE: u32 = 100;
+
+const USER_COLUMNS: &str = "id, email, name, role, department, is_active, email_verified, \
+     failed_login_attempts, locked_until, last_login, created_at, updated_at";
+
+/// Account as seen by admins
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct AdminUser {
+    pub id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub role: String,
+    pub department: Option<String>,
+    pub is_active: bool,
+    pub email_verified: bool,
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_login: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AdminUser {
+    fn is_active_admin(&self) -> bool {
+        self.is_active && self.role == UserRole::Admin.as_str()
+    }
+
+    /// Core ACL user, as for a request the user signed in to
+    pub fn to_acl_user(&self) -> otl_core::User {
+        otl_core::User {
+            user_id: self.id.to_string(),
+            email: Some(self.email.clone()),
+            roles: vec![self.role.to_uppercase()],
+            departments: self.department.iter().cloned().collect(),
+            is_internal: true,
+        }
+    }
+}
+
+/// Filters of the user list
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    /// Part of the email or name, case-insensitive
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+    pub department: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// One page of the user list
+#[derive(Debug, Serialize)]
+pub struct UserPage {
+    pub users: Vec<AdminUser>,
+    /// Users matching the filters
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+/// Change to an account; absent fields are left as they are
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserChange {
+    pub role: Option<UserRole>,
+    /// `Some(None)` removes the department
+    pub department: Option<Option<String>>,
+    pub is_active: Option<bool>,
+}
+
+/// Password reset issued to an admin
+#[derive(Debug, Serialize)]
+pub struct PasswordResetTicket {
+    pub user_id: Uuid,
+    /// One-time token for `POST /api/v1/auth/password-reset`
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Users matching `filter`, newest first
+pub async fn list_users(
+    state: &AppState,
+    filter: &UserFilter,
+    page: u32,
+    page_size: u32,
+) -> Result<UserPage, AppError> {
+    let page = page.max(1);
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+    let conditions = r#"
+        ($1::text IS NULL OR position(lower($1) in lower(email)) > 0
+                          OR position(lower($1) in lower(name)) > 0)
+        AND ($2::text IS NULL OR role = $2)
+        AND ($3::text IS NULL OR department = $3)
+        AND ($4::bool IS NULL OR is_active = $4)
+    "#;
+    let search = filter
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let role = filter.role.as_ref().map(UserRole::as_str);
+
+    let users: Vec<AdminUser> = sqlx::query_as(&format!(
+        "SELECT {USER_COLUMNS} FROM users WHERE {conditions} \
+         ORDER BY created_at DESC, id LIMIT $5 OFFSET $6"
+    ))
+    .bind(search)
+    .bind(role)
+    .bind(filter.department.as_deref())
+    .bind(filter.is_active)
+    .bind(i64::from(page_size))
+    .bind(i64::from((page - 1) * page_size))
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list users: {e}")))?;
+
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM users WHERE {conditions}"))
+        .bind(search)
+        .bind(role)
+        .bind(filter.department.as_deref())
+        .bind(filter.is_active)
+        .fetch_one(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to count users: {e}")))?;
+
+    Ok(UserPage {
+        users,
+        total,
+        page,
+        page_size,
+    })
+}
+
+pub async fn get_user(state: &AppState, id: Uuid) -> Result<AdminUser, AppError> {
+    sqlx::query_as(&format!("SELECT {USER_COLUMNS} FROM users WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch user: {e}")))?
+        .ok_or_else(|| AppError::NotFound(format!("User {id} not found")))
+}
+
+/// Apply `change` to a user; returns the account before and after
+///
+/// Deactivating an account also revokes its refresh tokens.
+pub async fn update_user(
+    state: &AppState,
+    id: Uuid,
+    change: &UserChange,
+) -> Result<(AdminUser, AdminUser), AppError> {
+    let db = |e: sqlx::Error| AppError::Database(format!("Failed to update user: {e}"));
+    let mut tx = state.db_pool.begin().await.map_err(db)?;
+
+    let before: AdminUser = sqlx::query_as(&format!(
+        "SELECT {USER_COLUMNS} FROM users WHERE id = $1 FOR UPDATE"
+    ))
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db)?
+    .ok_or_else(|| AppError::NotFound(format!("User {id} not found")))?;
+
+    if before.is_active_admin() {
+        // Lock the other admins so two admins cannot demote each other at once
+        let others: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM users WHERE role = 'admin' AND is_active AND id <> $1 FOR UPDATE",
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db)?;
+        check_last_admin(&before, change, others.len())?;
+    }
+
+    let department = change
+        .department
+        .clone()
+        .map(|d| d.map(|d| d.trim().to_string()).filter(|d| !d.is_empty()));
+    let after: AdminUser = sqlx::query_as(&format!(
+        r#"
+        UPDATE users SET
+            role = COALESCE($2, role),
+            department = CASE WHEN $3 THEN $4 ELSE department END,
+            is_active = COALESCE($5, is_active),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING {USER_COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(change.role.as_ref().map(UserRole::as_str))
+    .bind(department.is_some())
+    .bind(department.flatten())
+    .bind(change.is_active)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db)?;
+
+    if before.is_active && !after.is_active {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db)?;
+    }
+    let revoke_tokens = invalidates_tokens(&before, &after);
+    if revoke_tokens {
+        // Stored for the other instances (see `sessions`)
+        sqlx::query("UPDATE users SET tokens_revoked_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db)?;
+    }
+
+    tx.commit().await.map_err(db)?;
+    if revoke_tokens {
+        revoke_user_tokens(id);
+    }
+    Ok((before, after))
+}
+
+/// Whether a change makes the access tokens issued before it wrong: the
+/// account was deactivated, or the role or department in their claims
+/// changed
+fn invalidates_tokens(before: &AdminUser, after: &AdminUser) -> bool {
+    (before.is_active && !after.is_active)
+        || before.role != after.role
+        || before.department != after.department
+}
+
+/// Refuse a change that leaves no active admin
+///
+/// `other_admins` counts the active admins besides `user`.
+fn check_last_admin(
+    user: &AdminUser,
+    change: &UserChange,
+    other_admins: usize,
+) -> Result<(), AppError> {
+    let demoted = change.role.as_ref().is_some_and(|r| *r != UserRole::Admin);
+    let deactivated = change.is_active == Some(false);
+    if user.is_active_admin() && (demoted || deactivated) && other_admins == 0 {
+        return Err(AppError::coded(
+            ErrorCode::LastAdmin,
+            format!("{} is the last active admin", user.email),
+        ));
+    }
+    Ok(())
+}
+
+/// Audit events of a change made by `actor`
+pub fn change_events(
+    before: &AdminUser,
+    after: &AdminUser,
+    actor: Uuid,
+    ip_address: Option<String>,
+) -> Vec<AuditEvent> {
+    let mut events = Vec::new();
+    if before.role != after.role {
+        events.push(AuditEvent::UserRoleChanged {
+            user_id: after.id,
+            email: after.email.clone(),
+            old_role: before.role.clone(),
+            new_role: after.role.clone(),
+            changed_by: actor,
+            ip_address: ip_address.clone(),
+        });
+    }
+    if before.department != after.department {
+        events.push(AuditEvent::UserDepartmentChanged {
+            user_id: after.id,
+            email: after.email.clone(),
+            old_department: before.department.clone(),
+            new_department: after.department.clone(),
+            changed_by: actor,
+            ip_address: ip_address.clone(),
+        });
+    }
+    if before.is_active != after.is_active {
+        events.push(AuditEvent::AccountStatusChanged {
+            user_id: after.id,
+            email: after.email.clone(),
+            is_active: after.is_active,
+            changed_by: actor,
+            ip_address,
+        });
+    }
+    events
+}
+
+/// Issue a password reset token for a user, replacing unused ones
+pub async fn issue_password_reset(
+    state: &AppState,
+    id: Uuid,
+    requested_by: Uuid,
+) -> Result<(AdminUser, PasswordResetTicket), AppError> {
+    let user = get_user(state, id).await?;
+    let token = random_token();
+    let expires_at = Utc::now() + Duration::hours(RESET_TOKEN_TTL_HOURS);
+
+    let db = |e: sqlx::Error| AppError::Database(format!("Failed to issue password reset: {e}"));
+    let mut tx = state.db_pool.begin().await.map_err(db)?;
+    sqlx::query("DELETE FROM password_resets WHERE user_id = $1 AND used_at IS NULL")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db)?;
+    sqlx::query(
+        "INSERT INTO password_resets (user_id, token_hash, requested_by, expires_at) \
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(id)
+    .bind(hash_token(&token))
+    .bind(requested_by)
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await
+    .map_err(db)?;
+    tx.commit().await.map_err(db)?;
+
+    Ok((
+        user,
+        PasswordResetTicket {
+            user_id: id,
+            token,
+            expires_at,
+        },
+    ))
+}
+
+/// Set a new password with a reset token; returns the account
+///
+/// The token is used up, the account is unlocked and all its refresh
+/// tokens are revoked.
+pub async fn reset_password(
+    state: &AppState,
+    token: &str,
+    new_password: &str,
+) -> Result<AdminUser, AppError> {
+    PasswordPolicy::from_env()
+        .check(new_password)
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Password validation failed: {e}")))?;
+    let password_hash = hash_password(new_password)
+        .map_err(|e| AppError::Internal(format!("Failed to hash password: {e}")))?;
+
+    let db = |e: sqlx::Error| AppError::Database(format!("Failed to reset password: {e}"));
+    let mut tx = state.db_pool.begin().await.map_err(db)?;
+    let user_id: Uuid = sqlx::query_scalar(
+        r#"
+        UPDATE password_resets SET used_at = NOW()
+        WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+        RETURNING user_id
+        "#,
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db)?
+    .ok_or_else(|| AppError::BadRequest("Invalid or expired reset token".to_string()))?;
+
+    let user: AdminUser = sqlx::query_as(&format!(
+        r#"
+        UPDATE users SET
+            password_hash = $2,
+            password_changed_at = NOW(),
+            failed_login_attempts = 0,
+            locked_until = NULL,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING {USER_COLUMNS}
+        "#
+    ))
+    .bind(user_id)
+    .bind(&password_hash)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db)?;
+    sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(db)?;
+    tx.commit().await.map_err(db)?;
+
+    Ok(user)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(role: &str, is_active: bool) -> AdminUser {
+        let now = Utc::now();
+        AdminUser {
+            id: Uuid::new_v4(),
+            email: "kim@example.com".to_string(),
+            name: "김인사".to_string(),
+            role: role.to_string(),
+            department: Some("인사팀".to_string()),
+            is_active,
+            email_verified: true,
+            failed_login_attempts: 0,
+            locked_until: None,
+            last_login: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_last_admin_cannot_be_demoted_or_deactivated() {
+        let admin = user("admin", true);
+        let demote = UserChange {
+            role: Some(UserRole::Editor),
+            ..Default::default()
+        };
+        let deactivate = UserChange {
+            is_active: Some(false),
+            ..Default::default()
+        };
+
+        let err = check_last_admin(&admin, &demote, 0).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LastAdmin);
+        assert!(check_last_admin(&admin, &deactivate, 0).is_err());
+        // Another admin remains
+        assert!(check_last_admin(&admin, &demote, 1).is_ok());
+        // Department changes and re-granting admin are always allowed
+        let move_department = UserChange {
+            role: Some(UserRole::Admin),
+            department: Some(None),
+            ..Default::default()
+        };
+        assert!(check_last_admin(&admin, &move_department, 0).is_ok());
+        // Inactive admins do not count
+        assert!(check_last_admin(&user("admin", false), &demote, 0).is_ok());
+    }
+
+    #[test]
+    fn test_token_invalidating_changes() {
+        let before = user("admin", true);
+        let mut deactivated = before.clone();
+        deactivated.is_active = false;
+        let mut demoted = before.clone();
+        demoted.role = "viewer".to_string();
+        let mut moved = before.clone();
+        moved.department = Some("총무팀".to_string());
+
+        assert!(invalidates_tokens(&before, &deactivated));
+        assert!(invalidates_tokens(&before, &demoted));
+        assert!(invalidates_tokens(&before, &moved));
+        // Reactivation and no-op changes keep issued tokens
+        assert!(!invalidates_tokens(&deactivated, &before));
+        assert!(!invalidates_tokens(&before, &before));
+    }
+
+    #[tokio::test]
+    async fn test_demoted_admin_access_token_is_rejected() {
+        use crate::auth::jwt::{generate_access_token, JwtConfig};
+        use crate::auth::middleware::auth_middleware;
+        use axum::{body::Body, http::Request, http::StatusCode, routing::get, Router};
+        use tower::ServiceExt;
+
+        let admin = user("admin", true);
+        let token = generate_access_token(
+            &JwtConfig::from_env(),
+            admin.id,
+            &admin.name,
+            &admin.email,
+            &admin.role,
+            admin.department.as_deref(),
+        )
+        .unwrap();
+        let app = Router::new()
+            .route("/admin/users", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(auth_middleware));
+        let call = |app: Router| {
+            let request = Request::builder()
+                .uri("/admin/users")
+                .header("Authorization", format!("Bearer {token}"))
+                .body(Body::empty())
+                .unwrap();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+        assert_eq!(call(app.clone()).await, StatusCode::OK);
+
+        // What `update_user` does after the role change is committed
+        let mut demoted = admin.clone();
+        demoted.role = "viewer".to_string();
+        assert!(invalidates_tokens(&admin, &demoted));
+        revoke_user_tokens(admin.id);
+
+        assert_eq!(call(app).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_change_events() {
+        let before = user("viewer", true);
+        let mut after = before.clone();
+        after.role = "editor".to_string();
+        after.is_active = false;
+        let actor = Uuid::new_v4();
+
+        let events = change_events(&before, &after, actor, None);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            AuditEvent::UserRoleChanged { old_role, new_role, changed_by, .. }
+                if old_role == "viewer" && new_role == "editor" && *changed_by == actor
+        ));
+        assert!(matches!(
+            &events[1],
+            AuditEvent::AccountStatusChanged {
+                is_active: false,
+                ..
+            }
+        ));
+        assert!(change_events(&before, &before, actor, None).is_empty());
+    }
+}
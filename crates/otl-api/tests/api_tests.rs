@@ -2,6 +2,7 @@
 //!
 //! Note: Tests marked with #[ignore] require a real database connection.
 //! To run them, set up a test database and run: cargo test -- --ignored
+//! Query tests use the in-memory fakes and run without services.
 //!
 //! Author: hephaex@gmail.com
 
@@ -9,9 +10,13 @@ use axum::{
     body::Body,
     http::{Request, StatusCode},
 };
-use otl_api::create_router_for_testing;
+use otl_api::auth::jwt::{generate_access_token, JwtConfig};
+use otl_api::{create_router_for_testing, create_router_with_rag};
+use otl_core::testing::{InMemorySearchBackend, MockLlmClient};
+use otl_core::{SearchResultType, SourceReference};
 use serde_json::{json, Value};
 use tower::ServiceExt;
+use uuid::Uuid;
 
 /// Helper to create a test request
 fn create_json_request(method: &str, uri: &str, body: Option<Value>) -> Request<Body> {
@@ -110,20 +115,57 @@ async fn test_metrics_endpoint() {
 // =============================================================================
 // Query API Tests
 // =============================================================================
+// These run the RAG pipeline on the in-memory fakes from `test-utils`
+
+/// Bearer token for an employee, signed with the configured JWT secret
+fn bearer_token() -> String {
+    bearer_token_with_role("viewer")
+}
+
+/// Bearer token for an employee with `role`
+fn bearer_token_with_role(role: &str) -> String {
+    let token = generate_access_token(
+        &JwtConfig::from_env(),
+        Uuid::new_v4(),
+        "테스트 사용자",
+        "tester@example.com",
+        role,
+        Some("인사팀"),
+    )
+    .unwrap();
+    format!("Bearer {token}")
+}
+
+/// Helper to create an authenticated query request
+fn create_query_request(body: Value) -> Request<Body> {
+    let mut request = create_json_request("POST", "/api/v1/query", Some(body));
+    request
+        .headers_mut()
+        .insert("Authorization", bearer_token().parse().unwrap());
+    request
+}
+
+/// Router answering from one leave-policy passage
+async fn create_router_with_leave_policy() -> axum::Router {
+    let vector = InMemorySearchBackend::new("vector", SearchResultType::Vector).with_passage(
+        "연차휴가 신청 절차: 휴가 3일 전까지 부서장 승인을 받는다.",
+        SourceReference::new(Uuid::new_v4()).with_section("제5조 연차휴가"),
+    );
+    let llm = MockLlmClient::new("관련 내용을 찾지 못했습니다.").with_response(
+        "부서장 승인",
+        "휴가 3일 전까지 부서장 승인을 받아야 합니다 [출처: 1]",
+    );
+    create_router_with_rag(vector, llm).await
+}
 
 #[tokio::test]
-#[ignore = "requires database and authentication"]
 async fn test_query_endpoint_success() {
-    let app = create_router_for_testing();
+    let app = create_router_with_leave_policy().await;
 
-    let request = create_json_request(
-        "POST",
-        "/api/v1/query",
-        Some(json!({
-            "question": "연차휴가 신청 절차가 어떻게 되나요?",
-            "top_k": 5
-        })),
-    );
+    let request = create_query_request(json!({
+        "question": "연차휴가 신청 절차가 어떻게 되나요?",
+        "top_k": 5
+    }));
 
     let response = app.oneshot(request).await.unwrap();
 
@@ -134,25 +176,193 @@ async fn test_query_endpoint_success() {
         .unwrap();
     let json: Value = serde_json::from_slice(&body).unwrap();
 
-    assert!(json["answer"].is_string());
-    assert!(json["citations"].is_array());
+    assert!(json["answer"].as_str().unwrap().contains("부서장 승인"));
+    assert_eq!(json["citations"].as_array().unwrap().len(), 1);
+    assert_eq!(json["citations"][0]["section"], "제5조 연차휴가");
     assert!(json["confidence"].is_number());
     assert!(json["processing_time_ms"].is_number());
 }
 
 #[tokio::test]
-#[ignore = "requires database and authentication"]
-async fn test_query_endpoint_empty_question() {
-    let app = create_router_for_testing();
+async fn test_seeded_query_hides_recording_without_debug() {
+    let app = create_router_with_leave_policy().await;
 
-    let request = create_json_request(
-        "POST",
-        "/api/v1/query",
+    let request = create_query_request(json!({
+        "question": "연차휴가 신청 절차가 어떻게 되나요?",
+        "seed": 42
+    }));
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert!(json["answer"].as_str().unwrap().contains("부서장 승인"));
+    assert!(json.get("debug").is_none());
+}
+
+#[tokio::test]
+async fn test_estimate_endpoint_requires_developer_role() {
+    let body = json!({"question": "연차휴가 신청 절차가 어떻게 되나요?"});
+
+    let mut request = create_json_request("POST", "/api/v1/query/estimate", Some(body.clone()));
+    request
+        .headers_mut()
+        .insert("Authorization", bearer_token().parse().unwrap());
+    let app = create_router_with_leave_policy().await;
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let mut request = create_json_request("POST", "/api/v1/query/estimate", Some(body));
+    request.headers_mut().insert(
+        "Authorization",
+        bearer_token_with_role("developer").parse().unwrap(),
+    );
+    let app = create_router_with_leave_policy().await;
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["path"], "generation");
+    assert_eq!(json["contexts"], 1);
+    assert!(json["prompt_tokens"].as_u64().unwrap() > 0);
+    assert_eq!(json["backends"].as_array().unwrap().len(), 4);
+    assert!(json.get("answer").is_none());
+}
+
+#[tokio::test]
+async fn test_query_uses_persona_of_caller_department() {
+    let vector = InMemorySearchBackend::new("vector", SearchResultType::Vector).with_passage(
+        "연차휴가 신청 절차: 휴가 3일 전까지 부서장 승인을 받는다.",
+        SourceReference::new(Uuid::new_v4()),
+    );
+    let llm = MockLlmClient::new("관련 내용을 찾지 못했습니다.").with_response(
+        "인사팀 규정 안내 담당자",
+        "부서장 승인을 받으세요 [출처: 1] — 인사팀 규정 안내",
+    );
+    let app = create_router_with_rag(vector, llm).await;
+
+    let mut request = create_json_request(
+        "PUT",
+        "/api/v1/admin/personas/hr",
         Some(json!({
-            "question": "",
-            "top_k": 5
+            "departments": ["인사팀"],
+            "role": "당신은 인사팀 규정 안내 담당자입니다.",
+            "source_signature": "답변 끝에 '— 인사팀 규정 안내'라고 서명하세요."
         })),
     );
+    request.headers_mut().insert(
+        "Authorization",
+        bearer_token_with_role("admin").parse().unwrap(),
+    );
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // The viewer token carries the 인사팀 department claim
+    let request = create_query_request(json!({
+        "question": "연차휴가 신청 절차가 어떻게 되나요?"
+    }));
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert!(json["answer"]
+        .as_str()
+        .unwrap()
+        .ends_with("— 인사팀 규정 안내"));
+}
+
+#[tokio::test]
+async fn test_citation_feedback_on_answered_query() {
+    let app = create_router_with_leave_policy().await;
+    // Every vote comes from the same user
+    let token = bearer_token();
+    let feedback = |id: String, body: Value| {
+        let mut request =
+            create_json_request("POST", &format!("/api/v1/query/{id}/feedback"), Some(body));
+        request
+            .headers_mut()
+            .insert("Authorization", token.parse().unwrap());
+        request
+    };
+
+    let request = create_query_request(json!({
+        "question": "연차휴가 신청 절차가 어떻게 되나요?"
+    }));
+    let response = app.clone().oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    let id = json["id"].as_str().unwrap().to_string();
+    assert_eq!(json["citations"].as_array().unwrap().len(), 1);
+
+    let response = app
+        .clone()
+        .oneshot(feedback(id.clone(), json!({"helpful": [1]})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["recorded"], 1);
+
+    // A repeated vote is not counted again; a changed one is
+    for (body, recorded) in [
+        (json!({"helpful": [1]}), 0),
+        (json!({"unhelpful": [1]}), 1),
+        (json!({"unhelpful": [1]}), 0),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(feedback(id.clone(), body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["recorded"], recorded);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(feedback(id.clone(), json!({"unhelpful": [2]})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(feedback(
+            Uuid::new_v4().to_string(),
+            json!({"helpful": [1]}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_query_endpoint_empty_question() {
+    let app = create_router_with_leave_policy().await;
+
+    let request = create_query_request(json!({
+        "question": "",
+        "top_k": 5
+    }));
 
     let response = app.oneshot(request).await.unwrap();
 
@@ -167,18 +377,13 @@ async fn test_query_endpoint_empty_question() {
 }
 
 #[tokio::test]
-#[ignore = "requires database and authentication"]
 async fn test_query_endpoint_whitespace_question() {
-    let app = create_router_for_testing();
+    let app = create_router_with_leave_policy().await;
 
-    let request = create_json_request(
-        "POST",
-        "/api/v1/query",
-        Some(json!({
-            "question": "   ",
-            "top_k": 5
-        })),
-    );
+    let request = create_query_request(json!({
+        "question": "   ",
+        "top_k": 5
+    }));
 
     let response = app.oneshot(request).await.unwrap();
 
@@ -994,6 +1199,63 @@ async fn test_protected_verify_endpoint_without_auth() {
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
 
+#[tokio::test]
+#[ignore = "requires database"]
+async fn test_protected_graphql_endpoint_without_auth() {
+    let app = create_router_for_testing();
+
+    let request = create_json_request(
+        "POST",
+        "/api/v1/graphql",
+        Some(json!({ "query": "{ documents { id title } }" })),
+    );
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[ignore = "requires database"]
+async fn test_query_suggestions_without_auth() {
+    let app = create_router_for_testing();
+
+    let request = create_json_request(
+        "GET",
+        &format!("/api/v1/query/{}/suggestions", uuid::Uuid::new_v4()),
+        None,
+    );
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[ignore = "requires database"]
+async fn test_notifications_without_auth() {
+    let app = create_router_for_testing();
+
+    let request = create_json_request("GET", "/api/v1/notifications", None);
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[ignore = "requires database"]
+async fn test_saved_searches_without_auth() {
+    let app = create_router_for_testing();
+
+    let body = json!({"query": "육아휴직"});
+    let request = create_json_request("POST", "/api/v1/saved-searches", Some(body));
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
 // =============================================================================
 // OpenAPI/Swagger Tests
 // =============================================================================
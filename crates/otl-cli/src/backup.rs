@@ -0,0 +1,573 @@
+//! Knowledge base backup and restore
+//!
+//! `otl backup create` writes one `.tar.gz` archive holding
+//! - `postgres.dump`: the metadata database (`pg_dump` custom format),
+//! - `qdrant.snapshot`: a Qdrant collection snapshot,
+//! - `graph.json`: a SurrealDB [`GraphSnapshot`],
+//! - `manifest.json`: checksums and record counts of the three parts.
+//!
+//! `otl backup restore` checks the archive against its manifest before
+//! touching any store, restores each part and then compares the record
+//! counts of the live stores with the manifest. The stores are snapshotted
+//! one after another, so ingestion should be paused while a backup runs.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+
+use otl_core::AppConfig;
+use otl_graph::snapshot::GraphSnapshot;
+use otl_graph::SurrealDbStore;
+
+/// Archive format written by this version
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const POSTGRES_FILE: &str = "postgres.dump";
+const QDRANT_FILE: &str = "qdrant.snapshot";
+const GRAPH_FILE: &str = "graph.json";
+
+// ============================================================================
+// Manifest
+// ============================================================================
+
+/// Store a backup component was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    Postgres,
+    Qdrant,
+    Graph,
+}
+
+/// One file of the archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentManifest {
+    pub kind: ComponentKind,
+    /// File name inside the archive
+    pub file: String,
+    /// SHA-256 of the file
+    pub sha256: String,
+    pub size: u64,
+    /// Record counts at backup time (table or record type -> count)
+    pub counts: BTreeMap<String, u64>,
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub otl_version: String,
+    pub created_at: DateTime<Utc>,
+    /// Qdrant collection the snapshot was taken from
+    pub qdrant_collection: String,
+    pub components: Vec<ComponentManifest>,
+}
+
+impl BackupManifest {
+    fn component(&self, kind: ComponentKind) -> anyhow::Result<&ComponentManifest> {
+        self.components
+            .iter()
+            .find(|c| c.kind == kind)
+            .with_context(|| format!("Manifest has no {kind:?} component"))
+    }
+
+    /// Check every component file in `dir` against its checksum
+    pub fn verify_files(&self, dir: &Path) -> anyhow::Result<()> {
+        if self.format_version > BACKUP_FORMAT_VERSION {
+            bail!(
+                "Backup format {} is newer than supported ({})",
+                self.format_version,
+                BACKUP_FORMAT_VERSION
+            );
+        }
+        for component in &self.components {
+            let path = dir.join(&component.file);
+            let (sha256, size) = file_digest(&path)
+                .with_context(|| format!("Missing backup file {}", component.file))?;
+            if sha256 != component.sha256 || size != component.size {
+                bail!("Checksum mismatch for {}", component.file);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Differences between expected and actual record counts
+pub fn count_mismatches(
+    kind: ComponentKind,
+    expected: &BTreeMap<String, u64>,
+    actual: &BTreeMap<String, u64>,
+) -> Vec<String> {
+    expected
+        .iter()
+        .filter_map(|(name, count)| {
+            let found = actual.get(name).copied().unwrap_or_default();
+            (found != *count).then(|| format!("{kind:?} {name}: expected {count}, found {found}"))
+        })
+        .collect()
+}
+
+fn file_digest(path: &Path) -> anyhow::Result<(String, u64)> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+fn component(
+    kind: ComponentKind,
+    dir: &Path,
+    file: &str,
+    counts: BTreeMap<String, u64>,
+) -> anyhow::Result<ComponentManifest> {
+    let (sha256, size) = file_digest(&dir.join(file))?;
+    Ok(ComponentManifest {
+        kind,
+        file: file.to_string(),
+        sha256,
+        size,
+        counts,
+    })
+}
+
+// ============================================================================
+// Archive
+// ============================================================================
+
+/// Pack the manifest and component files of `dir` into a gzipped tarball
+pub fn write_archive(dir: &Path, manifest: &BackupManifest, output: &Path) -> anyhow::Result<()> {
+    std::fs::write(
+        dir.join(MANIFEST_FILE),
+        serde_json::to_vec_pretty(manifest)?,
+    )?;
+
+    let file = File::create(output)
+        .with_context(|| format!("Cannot create archive {}", output.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    archive.append_path_with_name(dir.join(MANIFEST_FILE), MANIFEST_FILE)?;
+    for component in &manifest.components {
+        archive.append_path_with_name(dir.join(&component.file), &component.file)?;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Unpack an archive into `dir` and read its manifest
+pub fn read_archive(archive: &Path, dir: &Path) -> anyhow::Result<BackupManifest> {
+    let file = File::open(archive).with_context(|| format!("Cannot open {}", archive.display()))?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(dir)
+        .context("Archive is not a valid backup")?;
+
+    let manifest = std::fs::read(dir.join(MANIFEST_FILE)).context("Archive has no manifest")?;
+    Ok(serde_json::from_slice(&manifest)?)
+}
+
+/// Temporary working directory, removed on drop
+struct WorkDir(PathBuf);
+
+impl WorkDir {
+    fn new() -> anyhow::Result<Self> {
+        let dir = std::env::temp_dir().join(format!("otl-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// ============================================================================
+// Postgres
+// ============================================================================
+
+async fn connect_postgres(config: &AppConfig) -> anyhow::Result<PgPool> {
+    PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&config.database.postgres_url)
+        .await
+        .context("PostgreSQL connection failed")
+}
+
+/// Row count of every table in the public schema
+async fn postgres_counts(pool: &PgPool) -> anyhow::Result<BTreeMap<String, u64>> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT table_name::text FROM information_schema.tables
+         WHERE table_schema = 'public' AND table_type = 'BASE TABLE'
+         ORDER BY table_name",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut counts = BTreeMap::new();
+    for table in tables {
+        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{table}\""))
+            .fetch_one(pool)
+            .await?;
+        counts.insert(table, count as u64);
+    }
+    Ok(counts)
+}
+
+fn run_tool(program: &str, args: &[&str]) -> anyhow::Result<()> {
+    let status = std::process::Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("Failed to run {program} (is it installed?)"))?;
+    if !status.success() {
+        bail!("{program} failed with {status}");
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Qdrant
+// ============================================================================
+
+/// Qdrant REST endpoint
+///
+/// `QDRANT_HTTP_URL` if set, otherwise `QDRANT_URL` with the gRPC port
+/// (6334) replaced by the REST port (6333).
+fn qdrant_http_url(config: &AppConfig) -> String {
+    std::env::var("QDRANT_HTTP_URL")
+        .unwrap_or_else(|_| config.database.qdrant_url.replace(":6334", ":6333"))
+        .trim_end_matches('/')
+        .to_string()
+}
+
+#[derive(Deserialize)]
+struct QdrantResponse<T> {
+    result: T,
+}
+
+#[derive(Deserialize)]
+struct SnapshotDescription {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CollectionInfo {
+    points_count: Option<u64>,
+}
+
+async fn qdrant_points(
+    client: &reqwest::Client,
+    base: &str,
+    collection: &str,
+) -> anyhow::Result<u64> {
+    let info: QdrantResponse<CollectionInfo> = client
+        .get(format!("{base}/collections/{collection}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(info.result.points_count.unwrap_or_default())
+}
+
+async fn qdrant_snapshot(
+    client: &reqwest::Client,
+    base: &str,
+    collection: &str,
+    output: &Path,
+) -> anyhow::Result<()> {
+    let created: QdrantResponse<SnapshotDescription> = client
+        .post(format!(
+            "{base}/collections/{collection}/snapshots?wait=true"
+        ))
+        .send()
+        .await?
+        .error_for_status()
+        .context("Qdrant snapshot creation failed")?
+        .json()
+        .await?;
+    let snapshot_url = format!(
+        "{base}/collections/{collection}/snapshots/{}",
+        created.result.name
+    );
+
+    let bytes = client
+        .get(&snapshot_url)
+        .send()
+        .await?
+        .error_for_status()
+        .context("Qdrant snapshot download failed")?
+        .bytes()
+        .await?;
+    std::fs::write(output, &bytes)?;
+
+    // The snapshot stays on the Qdrant node otherwise
+    if let Err(e) = client.delete(&snapshot_url).send().await {
+        tracing::warn!("Failed to remove snapshot from Qdrant: {e}");
+    }
+    Ok(())
+}
+
+async fn qdrant_restore(
+    client: &reqwest::Client,
+    base: &str,
+    collection: &str,
+    snapshot: &Path,
+) -> anyhow::Result<()> {
+    let part = reqwest::multipart::Part::bytes(std::fs::read(snapshot)?).file_name(QDRANT_FILE);
+    client
+        .post(format!(
+            "{base}/collections/{collection}/snapshots/upload?wait=true&priority=snapshot"
+        ))
+        .multipart(reqwest::multipart::Form::new().part("snapshot", part))
+        .send()
+        .await?
+        .error_for_status()
+        .context("Qdrant snapshot restore failed")?;
+    Ok(())
+}
+
+fn graph_counts(snapshot_counts: otl_graph::snapshot::GraphCounts) -> BTreeMap<String, u64> {
+    BTreeMap::from([
+        ("entities".to_string(), snapshot_counts.entities),
+        ("edges".to_string(), snapshot_counts.edges),
+        ("provenance".to_string(), snapshot_counts.provenance),
+    ])
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Back up Postgres, Qdrant and the graph into `output`
+pub async fn create(output: &Path) -> anyhow::Result<BackupManifest> {
+    let config = AppConfig::from_env()?;
+    let work = WorkDir::new()?;
+    let collection = config.database.qdrant_collection.clone();
+    let mut components = Vec::new();
+
+    println!("Backing up PostgreSQL...");
+    let pool = connect_postgres(&config).await?;
+    let dump = work.0.join(POSTGRES_FILE);
+    run_tool(
+        "pg_dump",
+        &[
+            "--format=custom",
+            "--no-owner",
+            "--file",
+            &dump.to_string_lossy(),
+            &config.database.postgres_url,
+        ],
+    )?;
+    let counts = postgres_counts(&pool).await?;
+    components.push(component(
+        ComponentKind::Postgres,
+        &work.0,
+        POSTGRES_FILE,
+        counts,
+    )?);
+
+    println!("Backing up Qdrant collection '{collection}'...");
+    let client = reqwest::Client::new();
+    let base = qdrant_http_url(&config);
+    let points = qdrant_points(&client, &base, &collection).await?;
+    qdrant_snapshot(&client, &base, &collection, &work.0.join(QDRANT_FILE)).await?;
+    components.push(component(
+        ComponentKind::Qdrant,
+        &work.0,
+        QDRANT_FILE,
+        BTreeMap::from([("points".to_string(), points)]),
+    )?);
+
+    println!("Backing up knowledge graph...");
+    let store = SurrealDbStore::new(&config.database).await?;
+    let snapshot = store.export_snapshot().await?;
+    let problems = snapshot.problems();
+    if !problems.is_empty() {
+        println!(
+            "  warning: graph has {} dangling references",
+            problems.len()
+        );
+    }
+    std::fs::write(work.0.join(GRAPH_FILE), serde_json::to_vec(&snapshot)?)?;
+    components.push(component(
+        ComponentKind::Graph,
+        &work.0,
+        GRAPH_FILE,
+        graph_counts(snapshot.counts()),
+    )?);
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        otl_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        qdrant_collection: collection,
+        components,
+    };
+    write_archive(&work.0, &manifest, output)?;
+    Ok(manifest)
+}
+
+/// Restore a backup archive, or only verify it when `dry_run` is set
+///
+/// Returns the count mismatches found after restoring (empty on success).
+pub async fn restore(archive: &Path, dry_run: bool) -> anyhow::Result<Vec<String>> {
+    let work = WorkDir::new()?;
+    let manifest = read_archive(archive, &work.0)?;
+    manifest.verify_files(&work.0)?;
+
+    let graph_component = manifest.component(ComponentKind::Graph)?;
+    let snapshot: GraphSnapshot =
+        serde_json::from_slice(&std::fs::read(work.0.join(&graph_component.file))?)?;
+    if graph_counts(snapshot.counts()) != graph_component.counts {
+        bail!("Graph snapshot does not match the manifest counts");
+    }
+    let problems = snapshot.problems();
+    if !problems.is_empty() {
+        println!(
+            "  warning: graph snapshot has {} dangling references",
+            problems.len()
+        );
+    }
+    println!(
+        "Archive verified: created {} by otl {}",
+        manifest.created_at, manifest.otl_version
+    );
+    if dry_run {
+        return Ok(Vec::new());
+    }
+
+    let config = AppConfig::from_env()?;
+    let mut mismatches = Vec::new();
+
+    println!("Restoring PostgreSQL...");
+    let postgres = manifest.component(ComponentKind::Postgres)?;
+    run_tool(
+        "pg_restore",
+        &[
+            "--clean",
+            "--if-exists",
+            "--no-owner",
+            "--single-transaction",
+            "--dbname",
+            &config.database.postgres_url,
+            &work.0.join(&postgres.file).to_string_lossy(),
+        ],
+    )?;
+    let pool = connect_postgres(&config).await?;
+    mismatches.extend(count_mismatches(
+        ComponentKind::Postgres,
+        &postgres.counts,
+        &postgres_counts(&pool).await?,
+    ));
+
+    println!(
+        "Restoring Qdrant collection '{}'...",
+        manifest.qdrant_collection
+    );
+    let qdrant = manifest.component(ComponentKind::Qdrant)?;
+    let client = reqwest::Client::new();
+    let base = qdrant_http_url(&config);
+    qdrant_restore(
+        &client,
+        &base,
+        &manifest.qdrant_collection,
+        &work.0.join(&qdrant.file),
+    )
+    .await?;
+    let points = qdrant_points(&client, &base, &manifest.qdrant_collection).await?;
+    mismatches.extend(count_mismatches(
+        ComponentKind::Qdrant,
+        &qdrant.counts,
+        &BTreeMap::from([("points".to_string(), points)]),
+    ));
+
+    println!("Restoring knowledge graph...");
+    let store = SurrealDbStore::new(&config.database).await?;
+    store.import_snapshot(&snapshot).await?;
+    mismatches.extend(count_mismatches(
+        ComponentKind::Graph,
+        &graph_component.counts,
+        &graph_counts(store.counts().await?),
+    ));
+
+    Ok(mismatches)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_for(dir: &Path) -> BackupManifest {
+        std::fs::write(dir.join(POSTGRES_FILE), b"PGDMP").unwrap();
+        std::fs::write(dir.join(GRAPH_FILE), b"{}").unwrap();
+        BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            otl_version: "0.1.0".to_string(),
+            created_at: Utc::now(),
+            qdrant_collection: "otl_chunks".to_string(),
+            components: vec![
+                component(
+                    ComponentKind::Postgres,
+                    dir,
+                    POSTGRES_FILE,
+                    BTreeMap::from([("documents".to_string(), 3)]),
+                )
+                .unwrap(),
+                component(ComponentKind::Graph, dir, GRAPH_FILE, BTreeMap::new()).unwrap(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_archive_round_trip_verifies_checksums() {
+        let source = WorkDir::new().unwrap();
+        let manifest = manifest_for(&source.0);
+        let output = source.0.join("backup.tar.gz");
+        write_archive(&source.0, &manifest, &output).unwrap();
+
+        let target = WorkDir::new().unwrap();
+        let restored = read_archive(&output, &target.0).unwrap();
+        assert_eq!(restored, manifest);
+        restored.verify_files(&target.0).unwrap();
+
+        std::fs::write(target.0.join(GRAPH_FILE), b"{\"tampered\":1}").unwrap();
+        assert!(restored.verify_files(&target.0).is_err());
+    }
+
+    #[test]
+    fn test_count_mismatches() {
+        let expected = BTreeMap::from([("documents".to_string(), 3), ("users".to_string(), 1)]);
+        let actual = BTreeMap::from([("documents".to_string(), 2), ("users".to_string(), 1)]);
+        assert_eq!(
+            count_mismatches(ComponentKind::Postgres, &expected, &actual),
+            vec!["Postgres documents: expected 3, found 2".to_string()]
+        );
+        assert!(count_mismatches(ComponentKind::Postgres, &expected, &expected).is_empty());
+    }
+}
@@ -0,0 +1,735 @@
+//! Static knowledge site export
+//!
+//! `otl export site` renders the corpus into a directory of static pages
+//! that can be served by any web server or browsed from disk:
+//! - `index.html` / `index.md`: the exported documents grouped by department,
+//! - `documents/<id>.html` / `.md`: one page per document with a lead
+//!   summary and the full text, headed by section and page,
+//! - `glossary.html` / `glossary.md`: the approved glossary terms,
+//! - `manifest.json`: the export scope and the documents on the site.
+//!
+//! The site is scoped to a reader: content is exported only if a reader at
+//! the chosen access level (and, for Confidential, of the chosen
+//! department) may see it. Restricted documents are never exported, since
+//! their chunks are sealed. Documents are read in pages, so exporting a
+//! large corpus keeps memory flat; on re-runs into the same directory only
+//! documents updated since the last export are re-rendered and pages of
+//! documents that left the scope are removed.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use otl_core::{
+    AccessLevel, AppConfig, DocumentAcl, GlossaryEntry, GlossaryRepository, GlossaryStatus,
+    GlossaryStore, User,
+};
+
+/// Documents (and glossary entries) read per query
+const PAGE_SIZE: i64 = 100;
+
+/// Length the lead summary grows to before it stops at a sentence end
+const SUMMARY_CHARS: usize = 240;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const DOCUMENTS_DIR: &str = "documents";
+
+/// Department heading of documents without a department
+const NO_DEPARTMENT: &str = "General";
+
+// ============================================================================
+// Scope
+// ============================================================================
+
+/// Highest access level of exported content
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SiteLevel {
+    Public,
+    Internal,
+    Confidential,
+}
+
+impl SiteLevel {
+    fn access_level(self) -> AccessLevel {
+        match self {
+            Self::Public => AccessLevel::Public,
+            Self::Internal => AccessLevel::Internal,
+            Self::Confidential => AccessLevel::Confidential,
+        }
+    }
+}
+
+/// Audience of an exported site
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiteScope {
+    pub level: AccessLevel,
+    /// Department whose Confidential content is exported
+    pub department: Option<String>,
+}
+
+impl SiteScope {
+    pub fn new(level: SiteLevel, department: Option<&str>) -> Self {
+        Self {
+            level: level.access_level(),
+            department: department.map(String::from),
+        }
+    }
+
+    /// Reader the site is exported for
+    fn reader(&self) -> User {
+        User {
+            user_id: "site-export".to_string(),
+            email: None,
+            roles: Vec::new(),
+            departments: self.department.iter().cloned().collect(),
+            is_internal: self.level >= AccessLevel::Internal,
+        }
+    }
+
+    /// Whether content with this ACL belongs on the site
+    pub fn admits(&self, acl: &DocumentAcl) -> bool {
+        acl.access_level <= self.level
+            && acl.access_level != AccessLevel::Restricted
+            && acl.can_access(&self.reader())
+    }
+}
+
+// ============================================================================
+// Manifest
+// ============================================================================
+
+/// A document on the site
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageEntry {
+    pub title: String,
+    pub department: Option<String>,
+    /// `documents.updated_at` when the page was rendered
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteManifest {
+    pub scope: SiteScope,
+    pub generated_at: DateTime<Utc>,
+    pub glossary_terms: usize,
+    pub documents: BTreeMap<Uuid, PageEntry>,
+}
+
+impl SiteManifest {
+    fn read(out: &Path) -> Option<Self> {
+        let bytes = std::fs::read(out.join(MANIFEST_FILE)).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// Whether the page of a document rendered by a previous export is current
+pub fn is_current(
+    previous: Option<&SiteManifest>,
+    scope: &SiteScope,
+    id: Uuid,
+    updated_at: DateTime<Utc>,
+) -> bool {
+    previous
+        .filter(|manifest| &manifest.scope == scope)
+        .and_then(|manifest| manifest.documents.get(&id))
+        .is_some_and(|entry| entry.updated_at == updated_at)
+}
+
+/// Documents of a previous export that are no longer on the site
+pub fn stale_documents(
+    previous: Option<&SiteManifest>,
+    documents: &BTreeMap<Uuid, PageEntry>,
+) -> Vec<Uuid> {
+    previous
+        .map(|manifest| {
+            manifest
+                .documents
+                .keys()
+                .filter(|id| !documents.contains_key(id))
+                .copied()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Corpus
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow)]
+struct DocumentRow {
+    id: Uuid,
+    title: String,
+    file_type: String,
+    access_level: String,
+    owner_id: Option<String>,
+    department: Option<String>,
+    required_roles: Vec<String>,
+    allowed_users: Vec<String>,
+    updated_at: DateTime<Utc>,
+}
+
+impl DocumentRow {
+    fn acl(&self) -> DocumentAcl {
+        DocumentAcl {
+            access_level: match self.access_level.as_str() {
+                "public" => AccessLevel::Public,
+                "confidential" => AccessLevel::Confidential,
+                "restricted" => AccessLevel::Restricted,
+                _ => AccessLevel::Internal,
+            },
+            owner_id: self.owner_id.clone(),
+            department: self.department.clone(),
+            required_roles: self.required_roles.clone(),
+            allowed_users: self.allowed_users.clone(),
+        }
+    }
+}
+
+/// A chunk of document text
+#[derive(Debug, Clone, FromRow)]
+pub struct SiteChunk {
+    pub content: String,
+    pub page_number: Option<i32>,
+    pub section_name: Option<String>,
+}
+
+/// Documents after `after` in ID order
+async fn document_page(pool: &PgPool, after: Uuid) -> anyhow::Result<Vec<DocumentRow>> {
+    sqlx::query_as(
+        "SELECT id, title, file_type::text AS file_type, access_level::text AS access_level,
+                owner_id, department, COALESCE(required_roles, '{}') AS required_roles,
+                COALESCE(allowed_users, '{}') AS allowed_users, updated_at
+         FROM documents
+         WHERE deleted_at IS NULL AND id > $1
+         ORDER BY id
+         LIMIT $2",
+    )
+    .bind(after)
+    .bind(PAGE_SIZE)
+    .fetch_all(pool)
+    .await
+    .context("Failed to read documents")
+}
+
+/// Intact chunks of a document in order
+async fn document_chunks(pool: &PgPool, document_id: Uuid) -> anyhow::Result<Vec<SiteChunk>> {
+    sqlx::query_as(
+        "SELECT content, page_number, section_name
+         FROM document_chunks
+         WHERE document_id = $1 AND NOT corrupted
+         ORDER BY chunk_index",
+    )
+    .bind(document_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to read document chunks")
+}
+
+/// Approved glossary entries visible in the scope, ordered by term
+async fn glossary_entries(pool: &PgPool, scope: &SiteScope) -> anyhow::Result<Vec<GlossaryEntry>> {
+    let store = GlossaryStore::from_pool(pool.clone());
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = store
+            .list(Some(GlossaryStatus::Approved), PAGE_SIZE, offset)
+            .await?;
+        let done = (page.len() as i64) < PAGE_SIZE;
+        offset += page.len() as i64;
+        entries.extend(page.into_iter().filter(|entry| scope.admits(&entry.acl)));
+        if done {
+            return Ok(entries);
+        }
+    }
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Escape text for HTML element content and attribute values
+pub fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape text used as markdown link text
+fn markdown_label(text: &str) -> String {
+    text.replace('[', "\\[").replace(']', "\\]")
+}
+
+/// Opening sentences of a document, about [`SUMMARY_CHARS`] long
+pub fn lead_summary(chunks: &[SiteChunk]) -> String {
+    let mut summary = String::new();
+    let text = chunks
+        .iter()
+        .flat_map(|chunk| chunk.content.split_whitespace());
+    for word in text {
+        if !summary.is_empty() {
+            summary.push(' ');
+        }
+        summary.push_str(word);
+        let length = summary.chars().count();
+        if length >= SUMMARY_CHARS && word.ends_with(['.', '!', '?', '。']) {
+            break;
+        }
+        if length >= SUMMARY_CHARS * 2 {
+            summary.push('…');
+            break;
+        }
+    }
+    summary
+}
+
+fn html_page(title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"ko\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n</head>\n<body>\n\
+         <nav><a href=\"{root}index.html\">Documents</a> · \
+         <a href=\"{root}glossary.html\">Glossary</a></nav>\n\
+         {body}</body>\n</html>\n",
+        title = html_escape(title),
+    )
+}
+
+/// Part of a rendered document body
+enum Block<'a> {
+    Section(&'a str),
+    Page(i32),
+    Paragraph(&'a str),
+}
+
+/// Body of a document as paragraphs, with a heading whenever the section
+/// changes and a marker whenever the page changes
+fn blocks(chunks: &[SiteChunk]) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let (mut section, mut page) = (None, None);
+    for chunk in chunks {
+        if chunk.section_name.is_some() && chunk.section_name != section {
+            section = chunk.section_name.clone();
+            blocks.push(Block::Section(
+                chunk.section_name.as_deref().unwrap_or_default(),
+            ));
+        }
+        if let Some(number) = chunk.page_number.filter(|_| chunk.page_number != page) {
+            page = chunk.page_number;
+            blocks.push(Block::Page(number));
+        }
+        blocks.extend(
+            chunk
+                .content
+                .split("\n\n")
+                .map(str::trim)
+                .filter(|paragraph| !paragraph.is_empty())
+                .map(Block::Paragraph),
+        );
+    }
+    blocks
+}
+
+/// Page of one document
+pub fn document_html(entry: &PageEntry, file_type: &str, chunks: &[SiteChunk]) -> String {
+    let mut body = format!(
+        "<h1>{}</h1>\n<p class=\"meta\">{} · {} · updated {}</p>\n",
+        html_escape(&entry.title),
+        html_escape(entry.department.as_deref().unwrap_or(NO_DEPARTMENT)),
+        html_escape(file_type),
+        entry.updated_at.format("%Y-%m-%d"),
+    );
+    let summary = lead_summary(chunks);
+    if !summary.is_empty() {
+        body.push_str(&format!(
+            "<blockquote class=\"summary\">{}</blockquote>\n",
+            html_escape(&summary)
+        ));
+    }
+    for block in blocks(chunks) {
+        body.push_str(&match block {
+            Block::Section(name) => format!("<h2>{}</h2>\n", html_escape(name)),
+            Block::Page(number) => format!("<p class=\"page\">p. {number}</p>\n"),
+            Block::Paragraph(text) => {
+                format!("<p>{}</p>\n", html_escape(text).replace('\n', "<br>\n"))
+            }
+        });
+    }
+    html_page(&entry.title, "../", &body)
+}
+
+/// Markdown page of one document
+pub fn document_markdown(entry: &PageEntry, file_type: &str, chunks: &[SiteChunk]) -> String {
+    let mut page = format!(
+        "# {}\n\n{} · {} · updated {}\n\n",
+        entry.title,
+        entry.department.as_deref().unwrap_or(NO_DEPARTMENT),
+        file_type,
+        entry.updated_at.format("%Y-%m-%d"),
+    );
+    let summary = lead_summary(chunks);
+    if !summary.is_empty() {
+        page.push_str(&format!("> {summary}\n\n"));
+    }
+    for block in blocks(chunks) {
+        page.push_str(&match block {
+            Block::Section(name) => format!("## {name}\n\n"),
+            Block::Page(number) => format!("*p. {number}*\n\n"),
+            Block::Paragraph(text) => format!("{text}\n\n"),
+        });
+    }
+    page
+}
+
+/// Documents grouped by department, each group ordered by title
+fn by_department(documents: &BTreeMap<Uuid, PageEntry>) -> BTreeMap<&str, Vec<(Uuid, &str)>> {
+    let mut groups: BTreeMap<&str, Vec<(Uuid, &str)>> = BTreeMap::new();
+    for (id, entry) in documents {
+        groups
+            .entry(entry.department.as_deref().unwrap_or(NO_DEPARTMENT))
+            .or_default()
+            .push((*id, &entry.title));
+    }
+    for documents in groups.values_mut() {
+        documents.sort_by(|a, b| a.1.cmp(b.1));
+    }
+    groups
+}
+
+/// Index page listing every document
+pub fn index_html(documents: &BTreeMap<Uuid, PageEntry>) -> String {
+    let mut body = format!("<h1>Documents ({})</h1>\n", documents.len());
+    for (department, documents) in by_department(documents) {
+        body.push_str(&format!("<h2>{}</h2>\n<ul>\n", html_escape(department)));
+        for (id, title) in documents {
+            body.push_str(&format!(
+                "<li><a href=\"{DOCUMENTS_DIR}/{id}.html\">{}</a></li>\n",
+                html_escape(title)
+            ));
+        }
+        body.push_str("</ul>\n");
+    }
+    html_page("Documents", "", &body)
+}
+
+/// Markdown index listing every document
+pub fn index_markdown(documents: &BTreeMap<Uuid, PageEntry>) -> String {
+    let mut page = format!("# Documents ({})\n\n", documents.len());
+    for (department, documents) in by_department(documents) {
+        page.push_str(&format!("## {department}\n\n"));
+        for (id, title) in documents {
+            page.push_str(&format!(
+                "- [{}]({DOCUMENTS_DIR}/{id}.md)\n",
+                markdown_label(title)
+            ));
+        }
+        page.push('\n');
+    }
+    page
+}
+
+/// Glossary page; sources on the site are linked
+pub fn glossary_html(entries: &[GlossaryEntry], documents: &BTreeMap<Uuid, PageEntry>) -> String {
+    let mut body = format!("<h1>Glossary ({})</h1>\n<dl>\n", entries.len());
+    for entry in entries {
+        body.push_str(&format!("<dt>{}", html_escape(&entry.term)));
+        if !entry.aliases.is_empty() {
+            body.push_str(&format!(" ({})", html_escape(&entry.aliases.join(", "))));
+        }
+        body.push_str(&format!("</dt>\n<dd>{}", html_escape(&entry.definition)));
+        match entry.document_id.filter(|id| documents.contains_key(id)) {
+            Some(id) => body.push_str(&format!(
+                " <a href=\"{DOCUMENTS_DIR}/{id}.html\">[{}]</a>",
+                html_escape(&documents[&id].title)
+            )),
+            None => {
+                if let Some(source) = &entry.source {
+                    body.push_str(&format!(" [{}]", html_escape(source)));
+                }
+            }
+        }
+        body.push_str("</dd>\n");
+    }
+    body.push_str("</dl>\n");
+    html_page("Glossary", "", &body)
+}
+
+/// Markdown glossary
+pub fn glossary_markdown(
+    entries: &[GlossaryEntry],
+    documents: &BTreeMap<Uuid, PageEntry>,
+) -> String {
+    let mut page = format!("# Glossary ({})\n\n", entries.len());
+    for entry in entries {
+        page.push_str(&format!("**{}**", entry.term));
+        if !entry.aliases.is_empty() {
+            page.push_str(&format!(" ({})", entry.aliases.join(", ")));
+        }
+        page.push_str(&format!(": {}", entry.definition));
+        match entry.document_id.filter(|id| documents.contains_key(id)) {
+            Some(id) => page.push_str(&format!(
+                " [{}]({DOCUMENTS_DIR}/{id}.md)",
+                markdown_label(&documents[&id].title)
+            )),
+            None => {
+                if let Some(source) = &entry.source {
+                    page.push_str(&format!(" \\[{}\\]", markdown_label(source)));
+                }
+            }
+        }
+        page.push_str("\n\n");
+    }
+    page
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Outcome of a site export
+#[derive(Debug, Clone, Default)]
+pub struct SiteReport {
+    /// Document pages rendered by this run
+    pub rendered: usize,
+    /// Document pages kept from the previous run
+    pub unchanged: usize,
+    /// Document pages removed because the document left the scope
+    pub removed: usize,
+    pub glossary_terms: usize,
+}
+
+/// Export the documents and glossary visible in `scope` into `out`
+pub async fn site(out: &Path, scope: &SiteScope) -> anyhow::Result<SiteReport> {
+    let config = AppConfig::from_env()?;
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&config.database.postgres_url)
+        .await
+        .context("PostgreSQL connection failed")?;
+
+    let pages = out.join(DOCUMENTS_DIR);
+    std::fs::create_dir_all(&pages)
+        .with_context(|| format!("Cannot create {}", pages.display()))?;
+    let previous = SiteManifest::read(out);
+    let mut report = SiteReport::default();
+    let mut documents = BTreeMap::new();
+
+    let mut after = Uuid::nil();
+    loop {
+        let rows = document_page(&pool, after).await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after = last.id;
+
+        for row in rows.iter().filter(|row| scope.admits(&row.acl())) {
+            let entry = PageEntry {
+                title: row.title.clone(),
+                department: row.department.clone(),
+                updated_at: row.updated_at,
+            };
+            let html = pages.join(format!("{}.html", row.id));
+            let markdown = pages.join(format!("{}.md", row.id));
+            if is_current(previous.as_ref(), scope, row.id, row.updated_at)
+                && html.exists()
+                && markdown.exists()
+            {
+                report.unchanged += 1;
+            } else {
+                let chunks = document_chunks(&pool, row.id).await?;
+                std::fs::write(&html, document_html(&entry, &row.file_type, &chunks))?;
+                std::fs::write(
+                    &markdown,
+                    document_markdown(&entry, &row.file_type, &chunks),
+                )?;
+                report.rendered += 1;
+            }
+            documents.insert(row.id, entry);
+        }
+        println!(
+            "  {} documents exported ({} rendered, {} unchanged)",
+            documents.len(),
+            report.rendered,
+            report.unchanged
+        );
+    }
+
+    for id in stale_documents(previous.as_ref(), &documents) {
+        for extension in ["html", "md"] {
+            let _ = std::fs::remove_file(pages.join(format!("{id}.{extension}")));
+        }
+        report.removed += 1;
+    }
+
+    let glossary = glossary_entries(&pool, scope).await?;
+    report.glossary_terms = glossary.len();
+    std::fs::write(out.join("index.html"), index_html(&documents))?;
+    std::fs::write(out.join("index.md"), index_markdown(&documents))?;
+    std::fs::write(
+        out.join("glossary.html"),
+        glossary_html(&glossary, &documents),
+    )?;
+    std::fs::write(
+        out.join("glossary.md"),
+        glossary_markdown(&glossary, &documents),
+    )?;
+
+    let manifest = SiteManifest {
+        scope: scope.clone(),
+        generated_at: Utc::now(),
+        glossary_terms: glossary.len(),
+        documents,
+    };
+    std::fs::write(
+        out.join(MANIFEST_FILE),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    Ok(report)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acl(access_level: AccessLevel, department: Option<&str>) -> DocumentAcl {
+        DocumentAcl {
+            access_level,
+            owner_id: None,
+            department: department.map(String::from),
+            required_roles: Vec::new(),
+            allowed_users: Vec::new(),
+        }
+    }
+
+    fn chunk(content: &str, page: Option<i32>, section: Option<&str>) -> SiteChunk {
+        SiteChunk {
+            content: content.to_string(),
+            page_number: page,
+            section_name: section.map(String::from),
+        }
+    }
+
+    fn entry(title: &str, department: Option<&str>) -> PageEntry {
+        PageEntry {
+            title: title.to_string(),
+            department: department.map(String::from),
+            updated_at: "2024-03-01T09:00:00Z".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_scope_follows_acl() {
+        let public = SiteScope::new(SiteLevel::Public, None);
+        assert!(public.admits(&acl(AccessLevel::Public, None)));
+        assert!(!public.admits(&acl(AccessLevel::Internal, None)));
+
+        let internal = SiteScope::new(SiteLevel::Internal, Some("인사팀"));
+        assert!(internal.admits(&acl(AccessLevel::Internal, Some("재무팀"))));
+        assert!(!internal.admits(&acl(AccessLevel::Confidential, Some("인사팀"))));
+
+        let confidential = SiteScope::new(SiteLevel::Confidential, Some("인사팀"));
+        assert!(confidential.admits(&acl(AccessLevel::Confidential, Some("인사팀"))));
+        assert!(!confidential.admits(&acl(AccessLevel::Confidential, Some("재무팀"))));
+        assert!(!confidential.admits(&acl(AccessLevel::Restricted, Some("인사팀"))));
+    }
+
+    #[test]
+    fn test_document_pages_escape_and_structure_text() {
+        let chunks = vec![
+            chunk(
+                "연차휴가는 15일입니다.\n\n<script>alert(1)</script>",
+                Some(1),
+                Some("제1장 총칙"),
+            ),
+            chunk(
+                "병가는 60일까지 사용할 수 있습니다.",
+                Some(2),
+                Some("제1장 총칙"),
+            ),
+            chunk("육아휴직은 1년입니다.", Some(2), Some("제2장 휴직")),
+        ];
+        let page = entry("휴가 규정 <2024>", Some("인사팀"));
+
+        let html = document_html(&page, "pdf", &chunks);
+        assert!(html.contains("<title>휴가 규정 &lt;2024&gt;</title>"));
+        assert!(html.contains("<p>&lt;script&gt;alert(1)&lt;/script&gt;</p>"));
+        assert!(!html.contains("<script>"));
+        assert_eq!(html.matches("<h2>제1장 총칙</h2>").count(), 1);
+        assert_eq!(html.matches("<p class=\"page\">p. 2</p>").count(), 1);
+        assert!(html.contains("<blockquote class=\"summary\">연차휴가는 15일입니다."));
+
+        let markdown = document_markdown(&page, "pdf", &chunks);
+        assert!(markdown.starts_with("# 휴가 규정 <2024>\n\n인사팀 · pdf · updated 2024-03-01"));
+        assert!(markdown.contains("## 제2장 휴직\n\n육아휴직은 1년입니다."));
+    }
+
+    #[test]
+    fn test_lead_summary_stops_at_sentence_end() {
+        let sentence =
+            "임직원은 매년 15일의 연차휴가를 사용할 수 있으며 미사용 연차는 다음 해로 이월됩니다. ";
+        let chunks = vec![chunk(&sentence.repeat(10), None, None)];
+        let summary = lead_summary(&chunks);
+        assert!(summary.ends_with("이월됩니다."));
+        assert!(summary.chars().count() >= SUMMARY_CHARS);
+        assert!(summary.chars().count() < SUMMARY_CHARS + sentence.chars().count());
+
+        let run_on = vec![chunk(&"가나다 ".repeat(400), None, None)];
+        assert!(lead_summary(&run_on).ends_with('…'));
+        assert_eq!(lead_summary(&[]), "");
+    }
+
+    #[test]
+    fn test_rerun_skips_current_and_removes_stale_pages() {
+        let scope = SiteScope::new(SiteLevel::Internal, None);
+        let (kept, updated, dropped) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let previous = SiteManifest {
+            scope: scope.clone(),
+            generated_at: Utc::now(),
+            glossary_terms: 0,
+            documents: BTreeMap::from([
+                (kept, entry("취업규칙", Some("인사팀"))),
+                (updated, entry("출장비 규정", Some("재무팀"))),
+                (dropped, entry("폐지된 규정", None)),
+            ]),
+        };
+        let at = previous.documents[&kept].updated_at;
+
+        assert!(is_current(Some(&previous), &scope, kept, at));
+        assert!(!is_current(Some(&previous), &scope, updated, Utc::now()));
+        assert!(!is_current(None, &scope, kept, at));
+        let wider = SiteScope::new(SiteLevel::Confidential, Some("인사팀"));
+        assert!(!is_current(Some(&previous), &wider, kept, at));
+
+        let current = BTreeMap::from([
+            (kept, entry("취업규칙", Some("인사팀"))),
+            (updated, entry("출장비 규정", Some("재무팀"))),
+        ]);
+        assert_eq!(stale_documents(Some(&previous), &current), vec![dropped]);
+        assert!(stale_documents(None, &current).is_empty());
+
+        let index = index_markdown(&current);
+        assert!(index.contains(&format!("## 인사팀\n\n- [취업규칙](documents/{kept}.md)")));
+    }
+}
@@ -0,0 +1,322 @@
+//! Batch extraction over a corpus
+//!
+//! `otl extract-batch <dir>` parses every document below a directory, runs
+//! the entity and relation extractors (the rule-based ones and those of
+//! plugins, as the `extract` ingest stage does) over its chunks and writes
+//! one JSON line per chunk with entities. Each line carries its provenance:
+//! the file, the chunk index, page and section, and the character offset of
+//! the chunk in the document, to which the entity offsets are relative.
+//!
+//! Documents are parsed and extracted on at most `concurrency` blocking
+//! threads at once, and `rate` caps how many documents are started per
+//! second, for plugin extractors backed by a rate-limited service. Lines
+//! are written in file order whatever the order documents finish in.
+//!
+//! With `queue`, the extractions of documents that were already ingested
+//! (found by their `file://` path in `documents`) are added to the
+//! verification queue, as ingestion adds them; documents not ingested yet
+//! are only written out, since queued extractions belong to a document.
+//!
+//! Author: hephaex@gmail.com
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use futures::StreamExt;
+use serde::Serialize;
+use sqlx::postgres::PgPoolOptions;
+use uuid::Uuid;
+
+use otl_core::AppConfig;
+use otl_extractor::ner::RuleBasedNer;
+use otl_extractor::plugin::{ChainedEntityExtractor, ChainedRelationExtractor, PluginHost};
+use otl_extractor::relation::RuleBasedRe;
+use otl_extractor::{EntityExtractor, ExtractedEntity, ExtractedRelation, RelationExtractor};
+use otl_ocr::OcrManager;
+use otl_parser::{chunk_document, ChunkConfig, ParserRegistry, PdfParser};
+
+use crate::import::{queue_extractions, QueuedExtraction};
+use crate::ingest::{collect_files, SkippedFile};
+
+/// How to run a batch
+#[derive(Debug)]
+pub struct BatchOptions<'a> {
+    /// JSONL file to write; standard output if `None`
+    pub out: Option<&'a Path>,
+    /// Documents processed at once
+    pub concurrency: usize,
+    /// Most documents started per second
+    pub rate: Option<f64>,
+    /// Entities below this confidence are left out
+    pub min_confidence: f32,
+    /// Add the extractions of ingested documents to the verification queue
+    pub queue: bool,
+}
+
+/// Extractions of one chunk, as written to the output
+#[derive(Debug, Serialize)]
+pub struct ExtractionRecord {
+    pub file: PathBuf,
+    /// Ingested document the extractions were queued for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_id: Option<Uuid>,
+    pub chunk_index: u32,
+    pub page: Option<u32>,
+    pub section: Option<String>,
+    /// Character offset of the chunk in the document text
+    pub offset: usize,
+    pub entities: Vec<ExtractedEntity>,
+    pub relations: Vec<ExtractedRelation>,
+}
+
+/// Outcome of a batch
+#[derive(Debug, Default)]
+pub struct BatchSummary {
+    pub documents: usize,
+    pub records: usize,
+    pub entities: usize,
+    pub relations: usize,
+    /// Documents whose extractions were queued for review
+    pub queued: usize,
+    /// Documents that could not be queued because they are not ingested
+    pub not_ingested: Vec<PathBuf>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Extractions of one file
+struct FileExtractions {
+    records: Vec<ExtractionRecord>,
+    /// The same extractions with their chunk text, for the queue
+    queued: Vec<QueuedExtraction>,
+}
+
+/// Parser and extractors shared by the worker threads
+struct Extractor {
+    registry: ParserRegistry,
+    ner: ChainedEntityExtractor,
+    re: ChainedRelationExtractor,
+    chunking: ChunkConfig,
+    min_confidence: f32,
+}
+
+impl Extractor {
+    fn extract_file(&self, path: &Path) -> anyhow::Result<FileExtractions> {
+        let doc = self.registry.parse(path)?;
+        let mut extractions = FileExtractions {
+            records: Vec::new(),
+            queued: Vec::new(),
+        };
+        for chunk in chunk_document(&doc, &self.chunking) {
+            if chunk.content.trim().is_empty() {
+                continue;
+            }
+            let entities: Vec<_> = self
+                .ner
+                .extract(&chunk.content)?
+                .into_iter()
+                .filter(|e| e.confidence >= self.min_confidence)
+                .collect();
+            if entities.is_empty() {
+                continue;
+            }
+            let relations = self.re.extract(&chunk.content, &entities)?;
+            extractions.queued.push(QueuedExtraction::new(
+                &entities,
+                &relations,
+                chunk.content.clone(),
+            )?);
+            extractions.records.push(ExtractionRecord {
+                file: path.to_path_buf(),
+                document_id: None,
+                chunk_index: chunk.index,
+                page: chunk.page,
+                section: chunk.section,
+                offset: chunk.start_offset,
+                entities,
+                relations,
+            });
+        }
+        Ok(extractions)
+    }
+}
+
+/// Extract entities and relations from the documents at `path`
+///
+/// Files that cannot be parsed are skipped; failing to write the output or
+/// to queue extractions stops the batch.
+pub async fn extract_batch(
+    path: &Path,
+    options: &BatchOptions<'_>,
+) -> anyhow::Result<BatchSummary> {
+    anyhow::ensure!(options.concurrency > 0, "--concurrency must be at least 1");
+    if let Some(rate) = options.rate {
+        anyhow::ensure!(rate > 0.0, "--rate must be positive");
+    }
+    let config = AppConfig::from_env()?;
+    let pool = if options.queue {
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&config.database.postgres_url)
+            .await
+            .context("PostgreSQL connection failed")?;
+        Some(pool)
+    } else {
+        None
+    };
+    let plugins = PluginHost::discover(config.plugins.dir.as_deref())?;
+    let mut registry = plugins.parser_registry();
+    let ocr = Arc::new(OcrManager::from_config(&config.ocr));
+    if ocr.can_read_pdf_pages() {
+        registry.register_first(PdfParser::new().with_ocr(ocr));
+    }
+    let extractor = Arc::new(Extractor {
+        registry,
+        ner: plugins.entity_extractor(RuleBasedNer::new()),
+        re: plugins.relation_extractor(RuleBasedRe::new()),
+        chunking: ChunkConfig::default(),
+        min_confidence: options.min_confidence,
+    });
+
+    let mut out: Box<dyn Write> = match options.out {
+        Some(file) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(file)
+                .with_context(|| format!("Failed to create {}", file.display()))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    let ticker = options.rate.map(|rate| {
+        let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        Arc::new(tokio::sync::Mutex::new(interval))
+    });
+    let results = futures::stream::iter(collect_files(path)?)
+        .then(|file| {
+            let ticker = ticker.clone();
+            async move {
+                if let Some(ticker) = ticker {
+                    ticker.lock().await.tick().await;
+                }
+                file
+            }
+        })
+        .map(|file| {
+            let extractor = extractor.clone();
+            async move {
+                let result = tokio::task::spawn_blocking({
+                    let file = file.clone();
+                    move || extractor.extract_file(&file)
+                })
+                .await;
+                (file, result)
+            }
+        })
+        .buffered(options.concurrency);
+    let mut results = std::pin::pin!(results);
+
+    let mut summary = BatchSummary::default();
+    while let Some((file, result)) = results.next().await {
+        let mut extractions = match result.context("Extraction thread failed")? {
+            Ok(extractions) => extractions,
+            Err(e) => {
+                summary.skipped.push(SkippedFile {
+                    path: file,
+                    reason: format!("{e:#}"),
+                });
+                continue;
+            }
+        };
+        summary.documents += 1;
+        if let (Some(pool), false) = (&pool, extractions.queued.is_empty()) {
+            match queue_for_file(pool, &file, &extractions.queued).await? {
+                Some(document_id) => {
+                    for record in &mut extractions.records {
+                        record.document_id = Some(document_id);
+                    }
+                    summary.queued += 1;
+                }
+                None => summary.not_ingested.push(file.clone()),
+            }
+        }
+        for record in &extractions.records {
+            summary.records += 1;
+            summary.entities += record.entities.len();
+            summary.relations += record.relations.len();
+            serde_json::to_writer(&mut out, record)?;
+            out.write_all(b"\n")?;
+        }
+    }
+    out.flush()?;
+    Ok(summary)
+}
+
+/// Queue the extractions of a file for review under its ingested document;
+/// `None` if the file was not ingested
+async fn queue_for_file(
+    pool: &sqlx::PgPool,
+    file: &Path,
+    extractions: &[QueuedExtraction],
+) -> anyhow::Result<Option<Uuid>> {
+    let source = format!("file://{}", file.display());
+    let document_id: Option<Uuid> = sqlx::query_scalar(
+        "SELECT id FROM documents
+         WHERE file_path = $1 AND deleted_at IS NULL
+         ORDER BY created_at DESC
+         LIMIT 1",
+    )
+    .bind(&source)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to look up the document")?;
+    let Some(document_id) = document_id else {
+        return Ok(None);
+    };
+
+    let mut tx = pool.begin().await?;
+    queue_extractions(&mut tx, document_id, extractions).await?;
+    tx.commit().await?;
+    Ok(Some(document_id))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_file_records_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("leave.md");
+        std::fs::write(
+            &file,
+            "# 제1장 총칙\n\n연차휴가는 15일이 기본 부여됩니다.\n",
+        )
+        .unwrap();
+        let plugins = PluginHost::default();
+        let extractor = Extractor {
+            registry: plugins.parser_registry(),
+            ner: plugins.entity_extractor(RuleBasedNer::new()),
+            re: plugins.relation_extractor(RuleBasedRe::new()),
+            chunking: ChunkConfig::default(),
+            min_confidence: 0.0,
+        };
+
+        let extractions = extractor.extract_file(&file).unwrap();
+        assert!(!extractions.records.is_empty());
+        assert_eq!(extractions.records.len(), extractions.queued.len());
+        let record = &extractions.records[0];
+        assert_eq!(record.file, file);
+        assert!(record.document_id.is_none());
+        assert!(record.entities.iter().any(|e| e.text == "15일"));
+
+        let line = serde_json::to_value(record).unwrap();
+        assert!(line.get("document_id").is_none());
+        assert_eq!(line["chunk_index"], 0);
+    }
+}
@@ -0,0 +1,501 @@
+//! Import of pre-chunked, pre-embedded corpora
+//!
+//! `otl import jsonl` loads chunks that were split and embedded outside OTL.
+//! Each line of the input is one [`ImportRecord`]. The whole file is
+//! validated before any store is touched: every embedding must have the
+//! dimension of the configured embedding model, since imported vectors are
+//! searched with query embeddings from that model.
+//!
+//! Valid records are written to the `documents` and `document_chunks`
+//! tables and to the Qdrant collection, one document per transaction.
+//! Re-importing a chunk (same document ID and chunk index) replaces it in
+//! place and keeps its vector ID. Chunks of Restricted documents are sealed
+//! with the document's data key (see `otl_core::encryption`).
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::BufRead;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::postgres::PgPoolOptions;
+use uuid::Uuid;
+
+use otl_core::encryption::chunk_context;
+use otl_core::freshness;
+use otl_core::integrity::content_hash;
+use otl_core::{AccessLevel, AppConfig, DocumentAcl, Keyring};
+use otl_extractor::{ExtractedEntity, ExtractedRelation};
+use otl_vector::embedding::embedding_dimension;
+use otl_vector::{ChunkPoint, EmbeddingVector, QdrantStore};
+
+/// Points written to Qdrant per request
+const UPSERT_BATCH: usize = 256;
+
+/// `extraction_queue.extractor` of extractions queued at ingest
+const RULE_EXTRACTOR: &str = "rule";
+
+// ============================================================================
+// Records
+// ============================================================================
+
+/// One chunk of the import file
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportRecord {
+    pub document_id: Uuid,
+    pub chunk_index: u32,
+    pub content: String,
+    pub embedding: Vec<f32>,
+
+    /// Document title (first non-empty title of the document wins)
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub page: Option<u32>,
+    #[serde(default)]
+    pub section: Option<String>,
+
+    /// Document metadata, stored in `documents.metadata`
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+
+    #[serde(default)]
+    pub access_level: AccessLevel,
+    #[serde(default)]
+    pub department: Option<String>,
+    #[serde(default)]
+    pub required_roles: Vec<String>,
+}
+
+/// A rejected line of the import file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    /// 1-based line number
+    pub line: usize,
+    pub message: String,
+}
+
+/// Outcome of reading an import file
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Valid records grouped by document
+    pub documents: BTreeMap<Uuid, Vec<ImportRecord>>,
+    pub errors: Vec<LineError>,
+}
+
+impl ImportReport {
+    /// Number of valid chunk records
+    pub fn chunk_count(&self) -> usize {
+        self.documents.values().map(Vec::len).sum()
+    }
+}
+
+/// Check one record against the expected embedding dimension
+fn validate(record: &ImportRecord, dimension: usize) -> Result<(), String> {
+    if record.embedding.len() != dimension {
+        return Err(format!(
+            "embedding has {} dimensions, the configured model produces {}",
+            record.embedding.len(),
+            dimension
+        ));
+    }
+    if record.embedding.iter().any(|v| !v.is_finite()) {
+        return Err("embedding contains NaN or infinite values".to_string());
+    }
+    if record.content.trim().is_empty() {
+        return Err("content is empty".to_string());
+    }
+    Ok(())
+}
+
+/// Parse and validate JSONL records
+///
+/// Blank lines are skipped. A chunk index repeated within a document is
+/// reported on its second occurrence.
+pub fn read_records(reader: impl BufRead, dimension: usize) -> anyhow::Result<ImportReport> {
+    let mut report = ImportReport::default();
+    let mut seen = HashSet::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.context("Failed to read import file")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = serde_json::from_str::<ImportRecord>(&line)
+            .map_err(|e| format!("invalid record: {e}"))
+            .and_then(|record| validate(&record, dimension).map(|_| record))
+            .and_then(|record| {
+                if seen.insert((record.document_id, record.chunk_index)) {
+                    Ok(record)
+                } else {
+                    Err(format!(
+                        "duplicate chunk {} of document {}",
+                        record.chunk_index, record.document_id
+                    ))
+                }
+            });
+
+        match result {
+            Ok(record) => report
+                .documents
+                .entry(record.document_id)
+                .or_default()
+                .push(record),
+            Err(message) => report.errors.push(LineError {
+                line: i + 1,
+                message,
+            }),
+        }
+    }
+
+    for records in report.documents.values_mut() {
+        records.sort_by_key(|r| r.chunk_index);
+    }
+    Ok(report)
+}
+
+// ============================================================================
+// Loading
+// ============================================================================
+
+/// Validate `path` and, unless `dry_run` or a line was rejected, load it
+///
+/// Returns the report of the file; the caller decides how to present
+/// rejected lines.
+pub async fn import_jsonl(path: &Path, dry_run: bool) -> anyhow::Result<ImportReport> {
+    let mut config = AppConfig::from_env()?;
+    let dimension = embedding_dimension(&config.llm);
+
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let report = read_records(std::io::BufReader::new(file), dimension)?;
+    if dry_run || !report.errors.is_empty() {
+        return Ok(report);
+    }
+
+    config.database.vector_dimension = dimension;
+    let loader = Loader::connect(&config).await?;
+
+    let source = format!(
+        "import://{}",
+        path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    );
+    for (document_id, records) in &report.documents {
+        loader
+            .load_document(&source, *document_id, records, &[])
+            .await
+            .with_context(|| format!("Failed to import document {document_id}"))?;
+    }
+
+    Ok(report)
+}
+
+/// Stores documents are loaded into
+pub(crate) struct Loader {
+    pool: sqlx::PgPool,
+    store: QdrantStore,
+    keyring: Keyring,
+}
+
+impl Loader {
+    /// Connect to PostgreSQL and the Qdrant collection of `config`
+    ///
+    /// The collection is created with `config.database.vector_dimension` if
+    /// it does not exist yet.
+    pub(crate) async fn connect(config: &AppConfig) -> anyhow::Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&config.database.postgres_url)
+            .await
+            .context("PostgreSQL connection failed")?;
+        let store = QdrantStore::new(&config.database).await?;
+        store.init_collection().await?;
+        let keyring = Keyring::from_config(&config.encryption)?;
+        Ok(Self {
+            pool,
+            store,
+            keyring,
+        })
+    }
+
+    /// Write one document's rows and vectors, and queue its extractions for
+    /// review
+    ///
+    /// The transaction is committed only after the vectors were stored, so a
+    /// failed upsert leaves no chunk rows pointing at missing vectors.
+    pub(crate) async fn load_document(
+        &self,
+        source: &str,
+        document_id: Uuid,
+        records: &[ImportRecord],
+        extractions: &[QueuedExtraction],
+    ) -> anyhow::Result<()> {
+        let (pool, store, keyring) = (&self.pool, &self.store, &self.keyring);
+        let first = &records[0];
+        let title = records
+            .iter()
+            .find_map(|r| r.title.as_deref().filter(|t| !t.trim().is_empty()))
+            .map(str::to_string)
+            .unwrap_or_else(|| document_id.to_string());
+        let metadata = records
+            .iter()
+            .find_map(|r| r.metadata.clone())
+            .unwrap_or_else(|| serde_json::json!({}));
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO documents
+                (id, title, file_path, file_type, access_level, department, required_roles, metadata)
+             VALUES ($1, $2, $3, 'other', $4::access_level, $5, $6, $7)
+             ON CONFLICT (id) DO UPDATE SET
+                title = EXCLUDED.title,
+                access_level = EXCLUDED.access_level,
+                department = EXCLUDED.department,
+                required_roles = EXCLUDED.required_roles,
+                metadata = EXCLUDED.metadata,
+                updated_at = NOW()",
+        )
+        .bind(document_id)
+        .bind(&title)
+        .bind(source)
+        .bind(first.access_level.to_string())
+        .bind(&first.department)
+        .bind(&first.required_roles)
+        .bind(&metadata)
+        .execute(&mut *tx)
+        .await?;
+
+        let existing: HashMap<i32, String> = sqlx::query_as::<_, (i32, String)>(
+            "SELECT chunk_index, vector_id FROM document_chunks
+             WHERE document_id = $1 AND vector_id IS NOT NULL",
+        )
+        .bind(document_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+
+        // Restricted content is sealed before it reaches Postgres
+        let data_key = keyring
+            .content_key(&mut *tx, document_id, first.access_level)
+            .await?;
+
+        let acl = DocumentAcl {
+            access_level: first.access_level,
+            department: first.department.clone(),
+            required_roles: first.required_roles.clone(),
+            ..Default::default()
+        };
+        let version = metadata.get("version").and_then(freshness::parse_version);
+        let superseded = metadata
+            .get(freshness::SUPERSEDED_BY_KEY)
+            .is_some_and(|v| !v.is_null());
+
+        let mut points = Vec::with_capacity(records.len());
+        for record in records {
+            let index = record.chunk_index as i32;
+            let vector_id = existing
+                .get(&index)
+                .and_then(|id| Uuid::parse_str(id).ok())
+                .unwrap_or_else(Uuid::new_v4);
+            let content = match &data_key {
+                Some(key) => key.seal_text(
+                    &record.content,
+                    &chunk_context(document_id, record.chunk_index),
+                )?,
+                None => record.content.clone(),
+            };
+
+            sqlx::query(
+                "INSERT INTO document_chunks
+                    (document_id, chunk_index, content, content_hash, page_number, section_name, vector_id)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (document_id, chunk_index) DO UPDATE SET
+                    content = EXCLUDED.content,
+                    content_hash = EXCLUDED.content_hash,
+                    page_number = EXCLUDED.page_number,
+                    section_name = EXCLUDED.section_name,
+                    vector_id = EXCLUDED.vector_id,
+                    corrupted = FALSE",
+            )
+            .bind(document_id)
+            .bind(index)
+            .bind(&content)
+            .bind(content_hash(&record.content))
+            .bind(record.page.map(|p| p as i32))
+            .bind(&record.section)
+            .bind(vector_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+            points.push(ChunkPoint {
+                embedding: EmbeddingVector {
+                    id: vector_id,
+                    vector: record.embedding.clone(),
+                    document_id,
+                    chunk_index: record.chunk_index,
+                    content: record.content.clone(),
+                },
+                page: record.page,
+                section: record.section.clone(),
+                acl: acl.clone(),
+                version,
+                superseded,
+            });
+        }
+
+        if !extractions.is_empty() {
+            queue_extractions(&mut tx, document_id, extractions).await?;
+        }
+
+        for batch in points.chunks(UPSERT_BATCH) {
+            store.store_points(batch).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Extraction of one chunk, to be reviewed in the verification queue
+#[derive(Debug, Clone)]
+pub(crate) struct QueuedExtraction {
+    entities: serde_json::Value,
+    relations: serde_json::Value,
+    source_text: String,
+    confidence: f32,
+}
+
+impl QueuedExtraction {
+    /// Extraction of `source_text`, as confident as its entities on average
+    pub(crate) fn new(
+        entities: &[ExtractedEntity],
+        relations: &[ExtractedRelation],
+        source_text: String,
+    ) -> otl_core::Result<Self> {
+        let confidence = if entities.is_empty() {
+            0.0
+        } else {
+            entities.iter().map(|e| e.confidence).sum::<f32>() / entities.len() as f32
+        };
+        let relations = relations
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "subject": r.subject.text,
+                    "subject_type": r.subject.entity_type,
+                    "predicate": r.predicate,
+                    "object": r.object.text,
+                    "object_type": r.object.entity_type,
+                    "confidence": r.confidence,
+                })
+            })
+            .collect();
+        Ok(Self {
+            entities: serde_json::to_value(entities).map_err(anyhow::Error::from)?,
+            relations,
+            source_text,
+            confidence,
+        })
+    }
+}
+
+pub(crate) async fn queue_extractions(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    document_id: Uuid,
+    extractions: &[QueuedExtraction],
+) -> anyhow::Result<()> {
+    let entities: Vec<&serde_json::Value> = extractions.iter().map(|e| &e.entities).collect();
+    let relations: Vec<&serde_json::Value> = extractions.iter().map(|e| &e.relations).collect();
+    let source_texts: Vec<&str> = extractions.iter().map(|e| e.source_text.as_str()).collect();
+    let confidences: Vec<f32> = extractions.iter().map(|e| e.confidence).collect();
+
+    sqlx::query(
+        "INSERT INTO extraction_queue
+            (document_id, extracted_entities, extracted_relations, source_text, extractor, confidence_score)
+         SELECT $1, u.entities, u.relations, u.source_text, $6, u.confidence
+         FROM UNNEST($2::jsonb[], $3::jsonb[], $4::text[], $5::real[])
+            AS u(entities, relations, source_text, confidence)",
+    )
+    .bind(document_id)
+    .bind(&entities)
+    .bind(&relations)
+    .bind(&source_texts)
+    .bind(&confidences)
+    .bind(RULE_EXTRACTOR)
+    .execute(&mut **tx)
+    .await
+    .context("Failed to queue extractions")?;
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(document_id: &str, chunk_index: u32, embedding: &[f32]) -> String {
+        serde_json::json!({
+            "document_id": document_id,
+            "chunk_index": chunk_index,
+            "content": format!("chunk {chunk_index}"),
+            "embedding": embedding,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_read_records_groups_by_document() {
+        let doc_a = "550e8400-e29b-41d4-a716-446655440000";
+        let doc_b = "7c9e6679-7425-40de-944b-e07fc1f90ae7";
+        let input = [
+            line(doc_a, 1, &[0.1, 0.2, 0.3]),
+            String::new(),
+            line(doc_b, 0, &[0.4, 0.5, 0.6]),
+            line(doc_a, 0, &[0.7, 0.8, 0.9]),
+        ]
+        .join("\n");
+
+        let report = read_records(input.as_bytes(), 3).unwrap();
+        assert!(report.errors.is_empty());
+        assert_eq!(report.documents.len(), 2);
+        assert_eq!(report.chunk_count(), 3);
+
+        let chunks = &report.documents[&Uuid::parse_str(doc_a).unwrap()];
+        assert_eq!(chunks[0].chunk_index, 0);
+        assert_eq!(chunks[1].chunk_index, 1);
+        assert_eq!(chunks[0].access_level, AccessLevel::Internal);
+    }
+
+    #[test]
+    fn test_read_records_reports_invalid_lines() {
+        let doc = "550e8400-e29b-41d4-a716-446655440000";
+        let input = [
+            line(doc, 0, &[0.1, 0.2, 0.3]),
+            line(doc, 1, &[0.1, 0.2]),
+            line(doc, 0, &[0.1, 0.2, 0.3]),
+            "{not json".to_string(),
+            r#"{"document_id": "550e8400-e29b-41d4-a716-446655440000", "chunk_index": 2,
+                "content": "x", "embedding": [0, 0, 0], "access_level": "secret"}"#
+                .replace('\n', " "),
+        ]
+        .join("\n");
+
+        let report = read_records(input.as_bytes(), 3).unwrap();
+        assert_eq!(report.chunk_count(), 1);
+
+        let lines: Vec<usize> = report.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 3, 4, 5]);
+        assert!(report.errors[0].message.contains("2 dimensions"));
+        assert!(report.errors[1].message.contains("duplicate chunk 0"));
+    }
+}
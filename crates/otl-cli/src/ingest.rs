@@ -0,0 +1,284 @@
+//! Ingestion of local files
+//!
+//! `otl ingest <path>` runs each file at `path` (a file, or a directory
+//! searched recursively) through an ingestion pipeline: an ordered list of
+//! stages such as parse, ocr, pii-redact, chunk, extract, embed and index
+//! (see `otl_core::pipeline` and [`crate::stages`]). The pipeline is chosen
+//! by the file's extension and the `--collection` it is ingested into, from
+//! the YAML file given with `--pipelines`, or from the built-in pipelines:
+//! images are read with OCR, everything else is parsed, and both are
+//! chunked with the default chunker settings, embedded and indexed. When
+//! Tesseract and `pdftoppm` are installed, the PDF parser reads the scanned
+//! pages of a PDF with OCR as well.
+//!
+//! Every file gets an [`IngestReport`]: the token distribution of its chunks
+//! against the embedding model's input limit, chunks over that limit, empty
+//! sections and the OCR confidence of scanned pages. The report is stored in
+//! `documents.metadata.ingest_report`; `--dry-run` leaves out the embed and
+//! index stages and only builds the reports.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context;
+use serde::Serialize;
+use uuid::Uuid;
+
+use otl_core::pipeline::{PipelineRunner, PipelineSet};
+use otl_core::AppConfig;
+use otl_extractor::plugin::PluginHost;
+use otl_ocr::OcrManager;
+use otl_parser::{IngestReport, PdfParser};
+use otl_vector::{create_embedding_client, embedding_dimension, TokenCounter};
+
+use crate::import::Loader;
+use crate::stages::{build_stage, FileContext, StageResources};
+
+/// How to ingest a path
+#[derive(Debug, Default)]
+pub struct IngestOptions<'a> {
+    /// Only report on the files
+    pub dry_run: bool,
+    /// Collection the files belong to, for pipeline selection
+    pub collection: Option<&'a str>,
+    /// YAML file with pipeline definitions (built-in pipelines if `None`)
+    pub pipelines: Option<&'a Path>,
+}
+
+/// A file that was ingested (or, in a dry run, would have been)
+#[derive(Debug, Serialize)]
+pub struct IngestedFile {
+    pub path: PathBuf,
+    pub document_id: Uuid,
+    pub title: String,
+    /// Pipeline the file went through
+    pub pipeline: String,
+    pub report: IngestReport,
+    /// Personal information replaced by the pii-redact stage
+    pub redactions: usize,
+    /// Extractions queued for review by the extract stage
+    pub extractions: usize,
+}
+
+/// A file that could not be read
+#[derive(Debug, Serialize)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// Outcome of ingesting a path
+#[derive(Debug, Default, Serialize)]
+pub struct IngestOutcome {
+    pub documents: Vec<IngestedFile>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Ingest the files at `path`
+///
+/// Files that cannot be read or match no pipeline are skipped; a failure
+/// after a file was read stops the ingest.
+pub async fn ingest(path: &Path, options: &IngestOptions<'_>) -> anyhow::Result<IngestOutcome> {
+    let pipelines = match options.pipelines {
+        Some(file) => PipelineSet::from_file(file)?,
+        None => PipelineSet::default(),
+    };
+    let mut config = AppConfig::from_env()?;
+    let stores = if options.dry_run {
+        None
+    } else {
+        config.database.vector_dimension = embedding_dimension(&config.llm);
+        let embedder = create_embedding_client(&config.llm)?;
+        Some((embedder, Loader::connect(&config).await?))
+    };
+    let plugins = PluginHost::discover(config.plugins.dir.as_deref())?;
+    let ocr = Arc::new(OcrManager::from_config(&config.ocr));
+    let mut registry = plugins.parser_registry();
+    if ocr.can_read_pdf_pages() {
+        // Scanned pages of PDFs are read with OCR
+        registry.register_first(PdfParser::new().with_ocr(ocr.clone()));
+    }
+    let resources = Arc::new(StageResources {
+        registry,
+        plugins,
+        ocr,
+        counter: TokenCounter::for_config(&config.llm),
+        stores,
+    });
+
+    // Stage configs are checked before any file is read
+    let mut runners = HashMap::new();
+    for definition in &pipelines.pipelines {
+        let runner = PipelineRunner::build(definition, |stage| build_stage(&resources, stage))?;
+        runners.insert(definition.name.clone(), runner);
+    }
+
+    let mut outcome = IngestOutcome::default();
+    for file in collect_files(path)? {
+        let file_type = file
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        let Some(definition) = pipelines.select(file_type, options.collection) else {
+            outcome.skipped.push(SkippedFile {
+                reason: "No pipeline matches the file".to_string(),
+                path: file,
+            });
+            continue;
+        };
+
+        let mut context = FileContext::new(file, &definition.name, options.collection);
+        if let Err(e) = runners[&definition.name].run(&mut context).await {
+            if context.document.is_some() {
+                return Err(anyhow::Error::new(e)
+                    .context(format!("Failed to ingest {}", context.path.display())));
+            }
+            outcome.skipped.push(SkippedFile {
+                path: context.path,
+                reason: format!("{:#}", anyhow::Error::new(e)),
+            });
+            continue;
+        }
+        outcome.documents.push(ingested_file(context)?);
+    }
+    Ok(outcome)
+}
+
+fn ingested_file(context: FileContext) -> anyhow::Result<IngestedFile> {
+    let report = context
+        .report
+        .with_context(|| format!("Pipeline {} has no chunk stage", context.pipeline))?;
+    let title = context
+        .document
+        .and_then(|d| d.metadata.title)
+        .unwrap_or_else(|| file_title(&context.path));
+    Ok(IngestedFile {
+        title,
+        document_id: context.document_id,
+        pipeline: context.pipeline,
+        report,
+        redactions: context.redactions,
+        extractions: context.extractions.len(),
+        path: context.path,
+    })
+}
+
+/// Files at a path, in name order; hidden files are skipped
+pub(crate) fn collect_files(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        anyhow::ensure!(path.exists(), "{} does not exist", path.display());
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries =
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+        for entry in entries {
+            let entry_path = entry?.path();
+            let hidden = entry_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if entry_path.is_dir() {
+                dirs.push(entry_path);
+            } else {
+                files.push(entry_path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Title of a document without one: its file name without extension
+pub(crate) fn file_title(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::pipeline::StageKind;
+
+    #[test]
+    fn test_collect_files_recursively() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("규정")).unwrap();
+        std::fs::write(dir.path().join("b.txt"), "b").unwrap();
+        std::fs::write(dir.path().join("규정/a.md"), "a").unwrap();
+        std::fs::write(dir.path().join(".hidden"), "").unwrap();
+
+        let files = collect_files(dir.path()).unwrap();
+        assert_eq!(
+            files,
+            vec![dir.path().join("b.txt"), dir.path().join("규정/a.md")]
+        );
+        assert_eq!(file_title(&files[1]), "a");
+        assert!(collect_files(&dir.path().join("missing")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_redacts_and_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("인사기록.txt");
+        std::fs::write(
+            &path,
+            "홍길동 과장(010-1234-5678)은 인사팀 소속이며 연차휴가 15일을 신청했다.",
+        )
+        .unwrap();
+
+        let pipelines = PipelineSet::from_yaml(
+            r#"
+pipelines:
+  - name: personnel
+    stages:
+      - stage: parse
+      - stage: pii-redact
+      - stage: chunk
+      - stage: extract
+      - stage: embed
+      - stage: index
+"#,
+        )
+        .unwrap();
+        let plugins = PluginHost::new();
+        let resources = Arc::new(StageResources {
+            registry: plugins.parser_registry(),
+            plugins,
+            ocr: Arc::new(OcrManager::default()),
+            counter: TokenCounter::new(otl_vector::TokenizerFamily::WordPiece, 8),
+            stores: None,
+        });
+        let runner = PipelineRunner::build(&pipelines.pipelines[0], |stage| {
+            build_stage(&resources, stage)
+        })
+        .unwrap();
+        // Without stores the writing stages are left out
+        assert!(!runner.stages().any(|s| s == StageKind::Index));
+
+        let mut context = FileContext::new(path, "personnel", None);
+        runner.run(&mut context).await.unwrap();
+        assert_eq!(context.redactions, 1);
+        assert!(context.chunks[0].content.contains("[개인정보]"));
+        assert!(!context.extractions.is_empty());
+
+        let file = ingested_file(context).unwrap();
+        assert_eq!(file.title, "인사기록");
+        assert_eq!(file.report.chunk_count, 1);
+        assert_eq!(file.report.over_limit.len(), 1);
+    }
+}
@@ -2,23 +2,47 @@
 //!
 //! Usage:
 //! ```text
-//!   otl ingest <path>
+//!   otl ingest <path> [--dry-run] [--report text|json] [--pipelines <yaml>] [--collection <name>]
 //!   otl query <question>
 //!   otl verify list
 //!   otl verify approve <id>
 //!   otl verify reject <id> [reason]
 //!   otl verify stats
+//!   otl verify review [--api-url <url>] [--token <jwt>] [--max-confidence <c>]
 //!   otl extract <path>
+//!   otl extract-batch <dir> [--out <jsonl>] [--concurrency <n>] [--rate <docs/s>] [--queue]
+//!   otl graph stats
+//!   otl graph snapshot create <name> [--note <text>]
+//!   otl graph snapshot list
+//!   otl graph snapshot restore <name> [--dry-run]
+//!   otl graph snapshot delete <name>
+//!   otl backup create [--output <archive>]
+//!   otl backup restore <archive> [--dry-run]
+//!   otl export site --out <dir> [--level public|internal|confidential] [--department <name>]
+//!   otl import jsonl <file> [--dry-run]
+//!   otl vector bench [--sample <n>] [--queries <n>] [--top-k <k>]
+//!   otl plugins [--json]
+//!   otl ocr <path> [--lang kor+eng] [--engine tesseract|paddle] [--format text|hocr|json] [--output <dir>]
 //! ```
 //!
 //! Author: hephaex@gmail.com
 
 #![allow(clippy::uninlined_format_args)]
 
+mod backup;
+mod export;
+mod extract_batch;
+mod import;
+mod ingest;
+mod ocr;
+mod review;
+mod stages;
+mod vector;
+
 use std::io::{self, Write};
 use std::sync::Mutex;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use futures::StreamExt;
 use once_cell::sync::Lazy;
 use uuid::Uuid;
@@ -26,8 +50,10 @@ use uuid::Uuid;
 use otl_core::LlmClient;
 use otl_extractor::hitl::VerificationQueue;
 use otl_extractor::ner::RuleBasedNer;
+use otl_extractor::plugin::{PluginHost, PluginSource};
 use otl_extractor::relation::RuleBasedRe;
 use otl_extractor::{EntityExtractor, RelationExtractor};
+use otl_graph::SurrealDbStore;
 use otl_rag::OllamaClient;
 
 // Global verification queue (in production, this would be backed by a database)
@@ -47,8 +73,20 @@ struct Cli {
 enum Commands {
     /// Ingest documents into the knowledge base
     Ingest {
-        /// Path to documents
+        /// Path to documents (a file or a directory)
         path: String,
+        /// Only parse and chunk the files and report on them
+        #[arg(long)]
+        dry_run: bool,
+        /// Format of the per-document ingest report
+        #[arg(long, value_enum, default_value = "text")]
+        report: ReportFormat,
+        /// YAML file with ingestion pipeline definitions
+        #[arg(long)]
+        pipelines: Option<String>,
+        /// Collection the documents belong to (selects the pipeline)
+        #[arg(long)]
+        collection: Option<String>,
     },
     /// Query the knowledge base using RAG
     Query {
@@ -80,6 +118,190 @@ enum Commands {
         #[arg(long)]
         relations_only: bool,
     },
+    /// Extract entities and relations from every document below a directory
+    ExtractBatch {
+        /// Path to documents (a file or a directory)
+        path: String,
+        /// JSONL file to write (default: standard output)
+        #[arg(long)]
+        out: Option<String>,
+        /// Documents processed at once
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+        /// Most documents started per second (default: no limit)
+        #[arg(long)]
+        rate: Option<f64>,
+        /// Leave out entities below this confidence
+        #[arg(long, default_value = "0.0")]
+        min_confidence: f32,
+        /// Add the extractions of ingested documents to the verification queue
+        #[arg(long)]
+        queue: bool,
+    },
+    /// Inspect the knowledge graph
+    Graph {
+        #[command(subcommand)]
+        action: GraphAction,
+    },
+    /// Back up or restore the whole knowledge base
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    /// Export the knowledge base for use outside OTL
+    Export {
+        #[command(subcommand)]
+        action: ExportAction,
+    },
+    /// Import pre-chunked, pre-embedded data
+    Import {
+        #[command(subcommand)]
+        action: ImportAction,
+    },
+    /// Inspect vector storage
+    Vector {
+        #[command(subcommand)]
+        action: VectorAction,
+    },
+    /// List the models pulled into the configured Ollama server
+    Models,
+    /// List the parser and extractor plugins loaded at startup
+    Plugins {
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run OCR on image files and PDFs and report per-page confidence
+    Ocr {
+        /// Path to scans (a file or a directory)
+        path: String,
+        /// Tesseract language code(s)
+        #[arg(long, default_value = "kor+eng")]
+        lang: String,
+        /// OCR engine
+        #[arg(long, value_enum, default_value = "tesseract")]
+        engine: ocr::EngineKind,
+        /// Format of the recognized text
+        #[arg(long, value_enum, default_value = "text")]
+        format: ocr::OutputFormat,
+        /// Save the results to this directory instead of printing them
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
+
+/// Output format of reports
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum VectorAction {
+    /// Compare recall and memory of truncation and quantization settings
+    Bench {
+        /// Stored vectors to sample
+        #[arg(long, default_value = "2000")]
+        sample: usize,
+        /// Sampled vectors used as queries
+        #[arg(long, default_value = "100")]
+        queries: usize,
+        /// Results per query
+        #[arg(short = 'k', long, default_value = "10")]
+        top_k: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportAction {
+    /// Load chunk records with embeddings from a JSONL file
+    Jsonl {
+        /// One record per line: document_id, chunk_index, content, embedding
+        file: String,
+        /// Only validate the file, do not load it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupAction {
+    /// Snapshot PostgreSQL, Qdrant and the graph into one archive
+    Create {
+        /// Archive path (default: otl-backup-<timestamp>.tar.gz)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Restore an archive and verify the restored stores
+    Restore {
+        /// Archive created by `otl backup create`
+        archive: String,
+        /// Only verify the archive, do not restore
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportAction {
+    /// Render documents and the glossary into a static HTML/Markdown site
+    Site {
+        /// Site directory (re-runs only re-render updated documents)
+        #[arg(long)]
+        out: String,
+        /// Highest access level of exported content
+        #[arg(long, value_enum, default_value = "internal")]
+        level: export::SiteLevel,
+        /// Department whose Confidential documents are exported
+        #[arg(long)]
+        department: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GraphAction {
+    /// Show graph health statistics
+    Stats {
+        /// Number of most central entities to show
+        #[arg(short, long, default_value = "10")]
+        top: usize,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage named graph snapshots (restore points)
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotAction {
+    /// Copy the live graph into a named snapshot
+    Create {
+        /// Snapshot name (letters, digits, '_' or '-')
+        name: String,
+        /// Why the snapshot is taken
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// List named snapshots
+    List,
+    /// Replace the live graph with a named snapshot
+    Restore {
+        /// Snapshot name
+        name: String,
+        /// Only compare the snapshot with the live graph
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Delete a named snapshot
+    Delete {
+        /// Snapshot name
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -116,6 +338,18 @@ enum VerifyAction {
     },
     /// Show verification statistics
     Stats,
+    /// Review the API server's queue interactively
+    Review {
+        /// API server URL [env: OTL_API_URL, default: http://localhost:8080]
+        #[arg(long)]
+        api_url: Option<String>,
+        /// JWT of the reviewer [env: OTL_API_TOKEN]
+        #[arg(long)]
+        token: Option<String>,
+        /// Review only extractions at or below this confidence
+        #[arg(long)]
+        max_confidence: Option<f32>,
+    },
     /// Load demo data for testing
     Demo,
 }
@@ -127,9 +361,19 @@ async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Ingest { path } => {
-            println!("Ingesting documents from: {path}");
-            // TODO: Implement ingestion
+        Commands::Ingest {
+            path,
+            dry_run,
+            report,
+            pipelines,
+            collection,
+        } => {
+            let options = ingest::IngestOptions {
+                dry_run,
+                collection: collection.as_deref(),
+                pipelines: pipelines.as_deref().map(std::path::Path::new),
+            };
+            cmd_ingest(&path, &options, report).await?;
         }
         Commands::Query {
             question,
@@ -146,6 +390,23 @@ async fn main() -> anyhow::Result<()> {
         } => {
             cmd_extract(&input, entities_only, relations_only)?;
         }
+        Commands::ExtractBatch {
+            path,
+            out,
+            concurrency,
+            rate,
+            min_confidence,
+            queue,
+        } => {
+            let options = extract_batch::BatchOptions {
+                out: out.as_deref().map(std::path::Path::new),
+                concurrency,
+                rate,
+                min_confidence,
+                queue,
+            };
+            cmd_extract_batch(&path, &options).await?;
+        }
         Commands::Verify { action } => match action {
             VerifyAction::List { item_type, limit } => {
                 cmd_verify_list(item_type.as_deref(), limit)?;
@@ -162,10 +423,84 @@ async fn main() -> anyhow::Result<()> {
             VerifyAction::Stats => {
                 cmd_verify_stats()?;
             }
+            VerifyAction::Review {
+                api_url,
+                token,
+                max_confidence,
+            } => {
+                let options = review::ReviewOptions {
+                    api_url: api_url
+                        .or_else(|| std::env::var("OTL_API_URL").ok())
+                        .unwrap_or_else(|| "http://localhost:8080".to_string()),
+                    token: token.or_else(|| std::env::var("OTL_API_TOKEN").ok()),
+                    max_confidence,
+                };
+                cmd_verify_review(&options).await?;
+            }
             VerifyAction::Demo => {
                 cmd_verify_demo()?;
             }
         },
+        Commands::Graph { action } => match action {
+            GraphAction::Stats { top, json } => {
+                cmd_graph_stats(top, json).await?;
+            }
+            GraphAction::Snapshot { action } => {
+                cmd_graph_snapshot(action).await?;
+            }
+        },
+        Commands::Backup { action } => match action {
+            BackupAction::Create { output } => {
+                cmd_backup_create(output.as_deref()).await?;
+            }
+            BackupAction::Restore { archive, dry_run } => {
+                cmd_backup_restore(&archive, dry_run).await?;
+            }
+        },
+        Commands::Export { action } => match action {
+            ExportAction::Site {
+                out,
+                level,
+                department,
+            } => {
+                cmd_export_site(&out, level, department.as_deref()).await?;
+            }
+        },
+        Commands::Import { action } => match action {
+            ImportAction::Jsonl { file, dry_run } => {
+                cmd_import_jsonl(&file, dry_run).await?;
+            }
+        },
+        Commands::Vector { action } => match action {
+            VectorAction::Bench {
+                sample,
+                queries,
+                top_k,
+            } => {
+                cmd_vector_bench(sample, queries, top_k).await?;
+            }
+        },
+        Commands::Models => {
+            cmd_models().await?;
+        }
+        Commands::Plugins { json } => {
+            cmd_plugins(json)?;
+        }
+        Commands::Ocr {
+            path,
+            lang,
+            engine,
+            format,
+            output,
+        } => {
+            let options = ocr::OcrOptions {
+                language: &lang,
+                engine,
+                format,
+                output: output.as_deref().map(std::path::Path::new),
+            };
+            cmd_ocr(&path, &options)?;
+        }
     }
 
     Ok(())
@@ -173,8 +508,10 @@ async fn main() -> anyhow::Result<()> {
 
 /// Extract entities and relations from text
 fn cmd_extract(input: &str, entities_only: bool, relations_only: bool) -> anyhow::Result<()> {
-    let ner = RuleBasedNer::new();
-    let re = RuleBasedRe::new();
+    let config = otl_core::AppConfig::from_env()?;
+    let plugins = PluginHost::discover(config.plugins.dir.as_deref())?;
+    let ner = plugins.entity_extractor(RuleBasedNer::new());
+    let re = plugins.relation_extractor(RuleBasedRe::new());
 
     let entities = ner.extract(input)?;
     let relations = re.extract(input, &entities)?;
@@ -187,7 +524,11 @@ fn cmd_extract(input: &str, entities_only: bool, relations_only: bool) -> anyhow
             for entity in &entities {
                 println!(
                     "  [{:.2}] {}: \"{}\" @ {}..{}",
-                    entity.confidence, entity.entity_type, entity.text, entity.start, entity.end
+                    entity.confidence,
+                    entity.entity_type,
+                    entity.text,
+                    entity.char_start,
+                    entity.char_end
                 );
             }
         }
@@ -213,12 +554,45 @@ fn cmd_extract(input: &str, entities_only: bool, relations_only: bool) -> anyhow
     Ok(())
 }
 
+/// Extract entities and relations from a corpus
+async fn cmd_extract_batch(
+    path: &str,
+    options: &extract_batch::BatchOptions<'_>,
+) -> anyhow::Result<()> {
+    let summary = extract_batch::extract_batch(std::path::Path::new(path), options).await?;
+
+    // The JSONL may be on stdout, so the summary goes to stderr
+    eprintln!(
+        "Extracted {} entities and {} relations from {} chunks of {} documents",
+        summary.entities, summary.relations, summary.records, summary.documents
+    );
+    if options.queue {
+        eprintln!(
+            "Queued the extractions of {} documents for review",
+            summary.queued
+        );
+        if !summary.not_ingested.is_empty() {
+            eprintln!("Not ingested, so not queued:");
+            for file in &summary.not_ingested {
+                eprintln!("  {}", file.display());
+            }
+        }
+    }
+    if !summary.skipped.is_empty() {
+        eprintln!("Skipped {} files:", summary.skipped.len());
+        for skipped in &summary.skipped {
+            eprintln!("  {}: {}", skipped.path.display(), skipped.reason);
+        }
+    }
+    Ok(())
+}
+
 /// List pending extractions
 fn cmd_verify_list(item_type: Option<&str>, limit: usize) -> anyhow::Result<()> {
     let queue = VERIFICATION_QUEUE.lock().unwrap();
 
-    let show_entities = item_type.is_none_or(|t| t == "entity" || t == "entities");
-    let show_relations = item_type.is_none_or(|t| t == "relation" || t == "relations");
+    let show_entities = item_type.map_or(true, |t| t == "entity" || t == "entities");
+    let show_relations = item_type.map_or(true, |t| t == "relation" || t == "relations");
 
     if show_entities {
         let pending = queue.pending_entities();
@@ -277,7 +651,7 @@ fn cmd_verify_show(id: &str) -> anyhow::Result<()> {
         println!("  Type:       {}", entity.entity.entity_type);
         println!(
             "  Position:   {}..{}",
-            entity.entity.start, entity.entity.end
+            entity.entity.char_start, entity.entity.char_end
         );
         println!("  Confidence: {:.2}", entity.entity.confidence);
         println!("  Status:     {}", entity.status);
@@ -387,6 +761,20 @@ fn cmd_verify_stats() -> anyhow::Result<()> {
 }
 
 /// Load demo data for testing
+/// Review the storage-backed queue in a terminal interface
+async fn cmd_verify_review(options: &review::ReviewOptions) -> anyhow::Result<()> {
+    let session = review::run(options).await?;
+    println!(
+        "Reviewed {} extractions: {} approved, {} corrected, {} rejected ({} skipped)",
+        session.approved + session.corrected + session.rejected,
+        session.approved,
+        session.corrected,
+        session.rejected,
+        session.skipped
+    );
+    Ok(())
+}
+
 fn cmd_verify_demo() -> anyhow::Result<()> {
     let mut queue = VERIFICATION_QUEUE.lock().unwrap();
     let doc_id = Uuid::new_v4();
@@ -442,6 +830,436 @@ fn cmd_verify_demo() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Show knowledge graph analytics
+async fn cmd_graph_stats(top: usize, json: bool) -> anyhow::Result<()> {
+    let config = otl_core::AppConfig::from_env()?;
+    let store = SurrealDbStore::new(&config.database).await?;
+    let report = store.analytics(top).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("\n=== Knowledge Graph Statistics ===\n");
+    println!("  Nodes: {}", report.node_count);
+    for (class, count) in &report.nodes_by_class {
+        println!("    {:<24} {}", class, count);
+    }
+    println!("\n  Edges: {}", report.edge_count);
+    for (predicate, count) in &report.edges_by_predicate {
+        println!("    {:<24} {}", predicate, count);
+    }
+
+    println!("\n  Degree:");
+    println!("    Average:       {:.2}", report.average_degree);
+    println!("    Max:           {}", report.max_degree);
+    for (degree, count) in &report.degree_distribution {
+        println!("    {:>4} edges:    {}", degree, count);
+    }
+
+    println!("\n  Most central:");
+    if report.most_central.is_empty() {
+        println!("    (no relations)");
+    }
+    for node in &report.most_central {
+        println!(
+            "    [{:.3}] {} ({}) degree {}",
+            node.centrality, node.id, node.class, node.degree
+        );
+    }
+
+    println!("\n  Orphaned entities: {}", report.orphan_count);
+    for id in report.orphans.iter().take(top) {
+        println!("    {}", id);
+    }
+
+    let coverage = &report.coverage;
+    println!("\n  Document coverage:");
+    println!(
+        "    {}/{} documents with triples ({:.1}%)",
+        coverage.documents_with_triples,
+        coverage.total_documents,
+        coverage.coverage * 100.0
+    );
+    for id in coverage.uncovered.iter().take(top) {
+        println!("    no triples: {}", id);
+    }
+
+    Ok(())
+}
+
+/// Create, list, restore or delete named graph snapshots
+async fn cmd_graph_snapshot(action: SnapshotAction) -> anyhow::Result<()> {
+    use otl_graph::snapshot;
+
+    let config = otl_core::AppConfig::from_env()?;
+    let store = SurrealDbStore::new(&config.database).await?;
+
+    match action {
+        SnapshotAction::Create { name, note } => {
+            println!("Copying knowledge graph into snapshot '{}'...", name);
+            let info = snapshot::create(&store, &config.database, &name, note.as_deref()).await?;
+            println!("\n=== Snapshot Created ===\n");
+            println!("  Name:       {}", info.name);
+            println!("  Database:   {}", info.database);
+            println!("  Entities:   {}", info.counts.entities);
+            println!("  Edges:      {}", info.counts.edges);
+            println!("  Provenance: {}", info.counts.provenance);
+        }
+        SnapshotAction::List => {
+            let snapshots = snapshot::list(&store).await?;
+            if snapshots.is_empty() {
+                println!("No graph snapshots.");
+            }
+            for info in snapshots {
+                println!(
+                    "  {:<24} {}  {:>8} entities {:>8} edges  {}",
+                    info.name,
+                    info.created_at.format("%Y-%m-%d %H:%M"),
+                    info.counts.entities,
+                    info.counts.edges,
+                    info.note.as_deref().unwrap_or("")
+                );
+            }
+        }
+        SnapshotAction::Restore { name, dry_run } => {
+            if dry_run {
+                let saved = snapshot::open(&store, &config.database, &name)
+                    .await?
+                    .counts()
+                    .await?;
+                let live = store.counts().await?;
+                println!("\n=== Restore '{}' (dry run) ===\n", name);
+                println!("  {:<12} {:>10} {:>10}", "", "live", "snapshot");
+                println!(
+                    "  {:<12} {:>10} {:>10}",
+                    "Entities", live.entities, saved.entities
+                );
+                println!("  {:<12} {:>10} {:>10}", "Edges", live.edges, saved.edges);
+                println!(
+                    "  {:<12} {:>10} {:>10}",
+                    "Provenance", live.provenance, saved.provenance
+                );
+                return Ok(());
+            }
+            println!("Restoring knowledge graph from snapshot '{}'...", name);
+            let counts = snapshot::restore(&store, &config.database, &name)
+                .await?
+                .counts();
+            println!(
+                "Restored {} entities, {} edges and {} provenance records.",
+                counts.entities, counts.edges, counts.provenance
+            );
+            println!(
+                "Note: restart the API server or wait for cache expiry to drop cached answers."
+            );
+        }
+        SnapshotAction::Delete { name } => {
+            if snapshot::delete(&store, &config.database, &name).await? {
+                println!("Deleted graph snapshot '{}'.", name);
+            } else {
+                anyhow::bail!("No graph snapshot named '{}'", name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn cmd_backup_create(output: Option<&str>) -> anyhow::Result<()> {
+    let output = output.map(String::from).unwrap_or_else(|| {
+        format!(
+            "otl-backup-{}.tar.gz",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        )
+    });
+
+    let manifest = backup::create(std::path::Path::new(&output)).await?;
+
+    println!("\n=== Backup Created ===\n");
+    println!("  Archive: {}", output);
+    for component in &manifest.components {
+        println!(
+            "  {:<10} {:>12} bytes  sha256 {}",
+            format!("{:?}", component.kind),
+            component.size,
+            &component.sha256[..16]
+        );
+        for (name, count) in &component.counts {
+            println!("    {:<24} {}", name, count);
+        }
+    }
+
+    Ok(())
+}
+
+async fn cmd_backup_restore(archive: &str, dry_run: bool) -> anyhow::Result<()> {
+    let mismatches = backup::restore(std::path::Path::new(archive), dry_run).await?;
+    if dry_run {
+        return Ok(());
+    }
+
+    if !mismatches.is_empty() {
+        println!("\nRestored data does not match the backup:");
+        for mismatch in &mismatches {
+            println!("  {}", mismatch);
+        }
+        anyhow::bail!("Consistency check failed ({} mismatches)", mismatches.len());
+    }
+
+    println!("\nRestore complete; record counts match the backup manifest.");
+    Ok(())
+}
+
+async fn cmd_export_site(
+    out: &str,
+    level: export::SiteLevel,
+    department: Option<&str>,
+) -> anyhow::Result<()> {
+    if level == export::SiteLevel::Confidential && department.is_none() {
+        anyhow::bail!("--level confidential needs --department");
+    }
+
+    let scope = export::SiteScope::new(level, department);
+    println!("Exporting site to {} ({} content)...", out, scope.level);
+    let report = export::site(std::path::Path::new(out), &scope).await?;
+
+    println!("\n=== Site Exported ===\n");
+    println!("  Rendered:       {}", report.rendered);
+    println!("  Unchanged:      {}", report.unchanged);
+    println!("  Removed:        {}", report.removed);
+    println!("  Glossary terms: {}", report.glossary_terms);
+    println!("  Open {}/index.html", out.trim_end_matches('/'));
+    Ok(())
+}
+
+async fn cmd_ingest(
+    path: &str,
+    options: &ingest::IngestOptions<'_>,
+    format: ReportFormat,
+) -> anyhow::Result<()> {
+    let outcome = ingest::ingest(std::path::Path::new(path), options).await?;
+
+    if format == ReportFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&outcome)?);
+        return Ok(());
+    }
+
+    for file in &outcome.documents {
+        let report = &file.report;
+        println!(
+            "\n{} ({}, pipeline {})",
+            file.path.display(),
+            file.document_id,
+            file.pipeline
+        );
+        println!(
+            "  {} chunks, tokens mean {:.0} / p95 {} / max {} (limit {})",
+            report.chunk_count,
+            report.tokens.mean,
+            report.tokens.p95,
+            report.tokens.max,
+            report.token_limit
+        );
+        for chunk in &report.over_limit {
+            println!(
+                "  over limit: chunk {} with {} tokens{}",
+                chunk.chunk_index,
+                chunk.tokens,
+                chunk
+                    .section
+                    .as_deref()
+                    .map(|s| format!(" in \"{s}\""))
+                    .unwrap_or_default()
+            );
+        }
+        if let Some(ocr) = &report.ocr {
+            println!(
+                "  OCR: {} pages, confidence mean {:.2} / min {:.2}",
+                ocr.pages, ocr.mean, ocr.min
+            );
+        }
+        for page in &report.rescan {
+            println!(
+                "  needs re-scan: page {} with confidence {:.2}{}",
+                page.page,
+                page.confidence,
+                if page.excluded {
+                    ", left out of the index"
+                } else {
+                    ""
+                }
+            );
+        }
+        if file.redactions > 0 {
+            println!(
+                "  redacted {} items of personal information",
+                file.redactions
+            );
+        }
+        if file.extractions > 0 {
+            println!("  {} chunks with extractions to review", file.extractions);
+        }
+        for warning in &report.warnings {
+            println!("  warning: {}", serde_json::to_string(warning)?);
+        }
+    }
+    for skipped in &outcome.skipped {
+        println!("\nSkipped {}: {}", skipped.path.display(), skipped.reason);
+    }
+
+    let verb = if options.dry_run {
+        "Reported on"
+    } else {
+        "Ingested"
+    };
+    println!(
+        "\n{} {} documents from {} ({} skipped)",
+        verb,
+        outcome.documents.len(),
+        path,
+        outcome.skipped.len()
+    );
+    Ok(())
+}
+
+async fn cmd_import_jsonl(file: &str, dry_run: bool) -> anyhow::Result<()> {
+    let report = import::import_jsonl(std::path::Path::new(file), dry_run).await?;
+
+    if !report.errors.is_empty() {
+        println!("\nRejected records:");
+        for error in report.errors.iter().take(20) {
+            println!("  line {}: {}", error.line, error.message);
+        }
+        if report.errors.len() > 20 {
+            println!("  ... and {} more", report.errors.len() - 20);
+        }
+        anyhow::bail!("{} invalid records, nothing imported", report.errors.len());
+    }
+
+    let verb = if dry_run { "Validated" } else { "Imported" };
+    println!(
+        "{} {} chunks of {} documents from {}",
+        verb,
+        report.chunk_count(),
+        report.documents.len(),
+        file
+    );
+    Ok(())
+}
+
+async fn cmd_vector_bench(sample: usize, queries: usize, top_k: usize) -> anyhow::Result<()> {
+    let report = vector::bench(sample, queries, top_k).await?;
+
+    println!(
+        "\nCollection '{}': {} points; {} queries against {} sampled vectors, top {}",
+        report.collection, report.point_count, report.query_count, report.base_size, report.top_k
+    );
+    println!(
+        "{:>9}  {:<12}  {:>7}  {:>8}  {:>9}  {:>10}",
+        "dimension", "quantization", "recall", "rescored", "bytes/vec", "est. RAM"
+    );
+    for result in &report.results {
+        println!(
+            "{:>9}  {:<12}  {:>6.1}%  {:>7.1}%  {:>9}  {:>10}",
+            result.case.dimension,
+            result.case.quantization.to_string(),
+            result.recall * 100.0,
+            result.rescored_recall * 100.0,
+            result.bytes_per_vector,
+            vector::format_bytes(result.bytes_per_vector as u64 * report.point_count)
+        );
+    }
+    println!(
+        "\nRescored: {}x candidates rescored with the unquantized vectors, as searches do",
+        otl_vector::quantization::QUANTIZATION_OVERSAMPLING
+    );
+    Ok(())
+}
+
+/// List Ollama models and whether the configured ones are pulled
+async fn cmd_models() -> anyhow::Result<()> {
+    let config = otl_core::AppConfig::from_env()?;
+    let models = OllamaClient::from_config(&config.llm).list_models().await?;
+
+    println!("\nModels at {}:", config.llm.ollama_url);
+    for model in &models {
+        println!(
+            "  {:<40} {:>10}",
+            model.name,
+            vector::format_bytes(model.size)
+        );
+    }
+    println!();
+    for (role, wanted) in [
+        ("LLM_MODEL", &config.llm.model),
+        ("EMBEDDING_MODEL", &config.llm.embedding_model),
+    ] {
+        let pulled = models
+            .iter()
+            .any(|m| otl_rag::llm::ollama_model_matches(&m.name, wanted));
+        if pulled {
+            println!("{role} {wanted}: pulled");
+        } else {
+            println!("{role} {wanted}: missing, run `ollama pull {wanted}`");
+        }
+    }
+    Ok(())
+}
+
+/// List the loaded plugins
+fn cmd_plugins(json: bool) -> anyhow::Result<()> {
+    let config = otl_core::AppConfig::from_env()?;
+    let plugins = PluginHost::discover(config.plugins.dir.as_deref())?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(plugins.plugins())?);
+        return Ok(());
+    }
+
+    if plugins.plugins().is_empty() {
+        println!("No plugins loaded");
+        return Ok(());
+    }
+    println!("\n=== Plugins ===\n");
+    for plugin in plugins.plugins() {
+        let source = match &plugin.source {
+            PluginSource::Native => "native".to_string(),
+            PluginSource::Wasm { path } => format!("wasm {}", path.display()),
+        };
+        println!("  {} ({})", plugin.name, source);
+        if !plugin.parses.is_empty() {
+            println!("    parses:              {}", plugin.parses.join(", "));
+        }
+        println!("    entity extractors:   {}", plugin.entity_extractors);
+        println!("    relation extractors: {}", plugin.relation_extractors);
+    }
+    Ok(())
+}
+
+/// Run OCR standalone
+fn cmd_ocr(path: &str, options: &ocr::OcrOptions<'_>) -> anyhow::Result<()> {
+    let summary = ocr::run(std::path::Path::new(path), options)?;
+
+    let mut line = format!(
+        "Read {} files, {} pages; {} pages below confidence {:.2} need re-scan",
+        summary.files,
+        summary.pages,
+        summary.low_pages,
+        otl_parser::report::LOW_OCR_CONFIDENCE
+    );
+    if summary.skipped > 0 {
+        line.push_str(&format!(" ({} other files skipped)", summary.skipped));
+    }
+    if let Some(dir) = options.output {
+        println!("{line}\nResults saved to {}", dir.display());
+    } else {
+        eprintln!("{line}");
+    }
+    Ok(())
+}
+
 /// Query the knowledge base using RAG
 async fn cmd_query(
     question: &str,
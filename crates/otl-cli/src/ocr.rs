@@ -0,0 +1,350 @@
+//! Standalone OCR
+//!
+//! `otl ocr` reads image files and scanned PDFs outside ingestion, to check
+//! the quality of a batch of scans before it is ingested. The text of each
+//! file is printed or saved to an output directory (as text, hOCR or JSON),
+//! and the confidence of every page is reported, with the pages below
+//! [`LOW_OCR_CONFIDENCE`] flagged for re-scan as in the ingest report.
+//!
+//! Author: hephaex@gmail.com
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use otl_ocr::{OcrEngine, OcrManager, OcrResult, PaddleEngine, TesseractConfig, TesseractEngine};
+use otl_parser::pdf::PdfParser;
+use otl_parser::report::LOW_OCR_CONFIDENCE;
+
+use crate::ingest::collect_files;
+
+/// Image files read by the OCR engines
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "webp", "pnm",
+];
+
+/// OCR engine to run
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EngineKind {
+    Tesseract,
+    Paddle,
+}
+
+/// Format of the recognized text
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    /// Tesseract's hOCR (HTML with word boxes); image files only
+    Hocr,
+    Json,
+}
+
+impl EngineKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Tesseract => "tesseract",
+            Self::Paddle => "paddle",
+        }
+    }
+}
+
+impl OutputFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Hocr => "hocr",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Settings of an `otl ocr` run
+pub struct OcrOptions<'a> {
+    pub language: &'a str,
+    pub engine: EngineKind,
+    pub format: OutputFormat,
+    /// Directory the results are saved to; without it they are printed
+    pub output: Option<&'a Path>,
+}
+
+/// Outcome of a run
+#[derive(Debug, Default)]
+pub struct OcrSummary {
+    pub files: usize,
+    pub pages: usize,
+    /// Pages below [`LOW_OCR_CONFIDENCE`]
+    pub low_pages: usize,
+    /// Files in a directory that are neither images nor PDFs
+    pub skipped: usize,
+}
+
+/// One file as read
+struct FileOcr {
+    pages: Vec<OcrResult>,
+    hocr: Option<String>,
+}
+
+#[derive(Serialize)]
+struct FileJson<'a> {
+    file: String,
+    engine: &'a str,
+    language: &'a str,
+    pages: Vec<PageJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct PageJson<'a> {
+    page: u32,
+    confidence: f32,
+    text: &'a str,
+    /// Recognized tables as markdown
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tables: Vec<String>,
+}
+
+/// OCR the image files and PDFs at `path`, writing the results and the
+/// confidence report
+///
+/// The report goes to stderr while the results are printed, to keep
+/// stdout to the results.
+pub fn run(path: &Path, options: &OcrOptions<'_>) -> anyhow::Result<OcrSummary> {
+    anyhow::ensure!(
+        options.format != OutputFormat::Hocr || options.engine == EngineKind::Tesseract,
+        "hOCR output needs the tesseract engine"
+    );
+    let config = otl_core::AppConfig::from_env()?;
+    let tesseract_config = TesseractConfig::default()
+        .with_language(options.language)
+        .with_tables(config.ocr.tables);
+    let tesseract = TesseractEngine::with_config(tesseract_config.clone());
+    let manager = match options.engine {
+        EngineKind::Tesseract => available(TesseractEngine::with_config(tesseract_config))?,
+        EngineKind::Paddle => available(PaddleEngine::new(options.language))?,
+    };
+    if let Some(dir) = options.output {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    let mut report: Box<dyn Write> = match options.output {
+        Some(_) => Box::new(std::io::stdout()),
+        None => Box::new(std::io::stderr()),
+    };
+    let mut summary = OcrSummary::default();
+    let single = !path.is_dir();
+    for file in collect_files(path)? {
+        let Some(kind) = kind(&file) else {
+            anyhow::ensure!(!single, "{} is neither an image nor a PDF", file.display());
+            summary.skipped += 1;
+            continue;
+        };
+        let read = match (kind, options.format) {
+            (FileKind::Image, OutputFormat::Hocr) => {
+                let (hocr, pages) = tesseract.extract_hocr(&file)?;
+                FileOcr {
+                    pages,
+                    hocr: Some(hocr),
+                }
+            }
+            (FileKind::Image, _) => FileOcr {
+                pages: manager.extract_pages(&file)?,
+                hocr: None,
+            },
+            (FileKind::Pdf, OutputFormat::Hocr) => {
+                anyhow::bail!(
+                    "hOCR output is only available for image files, not {}",
+                    file.display()
+                )
+            }
+            (FileKind::Pdf, _) => FileOcr {
+                pages: read_pdf(&manager, &file)?,
+                hocr: None,
+            },
+        };
+
+        let name = relative_name(path, &file);
+        write_result(&name, &read, options)?;
+        summary.files += 1;
+        summary.pages += read.pages.len();
+        summary.low_pages += report_file(&mut report, &name, &read.pages)?;
+    }
+    Ok(summary)
+}
+
+/// A manager of `engine`, if it is installed
+fn available<E: OcrEngine + 'static>(engine: E) -> anyhow::Result<OcrManager> {
+    anyhow::ensure!(engine.is_available(), "{} is not installed", engine.name());
+    Ok(OcrManager::with_engine(engine))
+}
+
+#[derive(Clone, Copy)]
+enum FileKind {
+    Image,
+    Pdf,
+}
+
+fn kind(path: &Path) -> Option<FileKind> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    if extension == "pdf" {
+        Some(FileKind::Pdf)
+    } else if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        Some(FileKind::Image)
+    } else {
+        None
+    }
+}
+
+/// Every page of a PDF file, rendered and read
+fn read_pdf(manager: &OcrManager, path: &Path) -> anyhow::Result<Vec<OcrResult>> {
+    anyhow::ensure!(
+        manager.can_read_pdf_pages(),
+        "pdftoppm (poppler-utils) is needed to read {}",
+        path.display()
+    );
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let page_count = PdfParser::new()
+        .parse_bytes(&bytes, &path.display().to_string())?
+        .metadata
+        .page_count
+        .unwrap_or(0);
+    let pages: Vec<u32> = (1..=page_count).collect();
+    Ok(manager.extract_pdf_pages(&bytes, &pages)?)
+}
+
+/// Path of `file` below the `root` given on the command line, or its file
+/// name when the root is the file itself
+fn relative_name(root: &Path, file: &Path) -> PathBuf {
+    match file.strip_prefix(root) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative.to_path_buf(),
+        _ => file.file_name().map(PathBuf::from).unwrap_or_default(),
+    }
+}
+
+/// Print the result of a file, or save it below the output directory
+fn write_result(name: &Path, read: &FileOcr, options: &OcrOptions<'_>) -> anyhow::Result<()> {
+    let rendered = match (options.format, &read.hocr) {
+        (OutputFormat::Hocr, Some(hocr)) => hocr.clone(),
+        (OutputFormat::Json, _) => {
+            let json = FileJson {
+                file: name.display().to_string(),
+                engine: options.engine.name(),
+                language: options.language,
+                pages: read
+                    .pages
+                    .iter()
+                    .map(|p| PageJson {
+                        page: p.page,
+                        confidence: p.confidence,
+                        text: &p.text,
+                        tables: p.tables.iter().map(|t| t.to_markdown()).collect(),
+                    })
+                    .collect(),
+            };
+            if options.output.is_some() {
+                serde_json::to_string_pretty(&json)?
+            } else {
+                // One line per file on stdout
+                serde_json::to_string(&json)?
+            }
+        }
+        // Pages separated by form feeds, as PDF text is
+        _ => read
+            .pages
+            .iter()
+            .map(|p| p.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\x0C"),
+    };
+
+    let Some(dir) = options.output else {
+        if options.format == OutputFormat::Json {
+            println!("{rendered}");
+        } else {
+            println!("==> {} <==\n{rendered}\n", name.display());
+        }
+        return Ok(());
+    };
+    let target = dir.join(name).with_extension(options.format.extension());
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&target, rendered)
+        .with_context(|| format!("Failed to write {}", target.display()))?;
+    Ok(())
+}
+
+/// Report the confidence of each page of a file; returns the number of
+/// pages below [`LOW_OCR_CONFIDENCE`]
+fn report_file(report: &mut dyn Write, name: &Path, pages: &[OcrResult]) -> anyhow::Result<usize> {
+    let low = pages
+        .iter()
+        .filter(|p| p.confidence < LOW_OCR_CONFIDENCE)
+        .count();
+    writeln!(
+        report,
+        "{}: {} pages, {} need re-scan",
+        name.display(),
+        pages.len(),
+        low
+    )?;
+    for page in pages {
+        let flag = if page.confidence < LOW_OCR_CONFIDENCE {
+            "  needs re-scan"
+        } else {
+            ""
+        };
+        writeln!(
+            report,
+            "  page {}: {:.2}{}",
+            page.page, page.confidence, flag
+        )?;
+    }
+    Ok(low)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_kinds_and_names() {
+        assert!(matches!(kind(Path::new("a/scan.PDF")), Some(FileKind::Pdf)));
+        assert!(matches!(
+            kind(Path::new("a/form.tiff")),
+            Some(FileKind::Image)
+        ));
+        assert!(kind(Path::new("a/notes.docx")).is_none());
+
+        let root = Path::new("scans");
+        assert_eq!(
+            relative_name(root, Path::new("scans/2024/leave.png")),
+            PathBuf::from("2024/leave.png")
+        );
+        let file = Path::new("scans/leave.png");
+        assert_eq!(relative_name(file, file), PathBuf::from("leave.png"));
+    }
+
+    #[test]
+    fn test_report_flags_low_pages() {
+        let pages = vec![
+            OcrResult::new("연차").with_confidence(0.9).with_page(1),
+            OcrResult::new("x#7").with_confidence(0.3).with_page(2),
+        ];
+        let mut out = Vec::new();
+        let low = report_file(&mut out, Path::new("leave.pdf"), &pages).unwrap();
+
+        assert_eq!(low, 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "leave.pdf: 2 pages, 1 need re-scan\n  page 1: 0.90\n  page 2: 0.30  needs re-scan\n"
+        );
+    }
+}
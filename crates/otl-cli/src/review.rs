@@ -0,0 +1,945 @@
+//! Interactive review of the verification queue
+//!
+//! `otl verify review` is a terminal interface to the review queue of the
+//! API server, so reviewers work through pending extractions without a
+//! browser and under the same rules as the web UI: review locks, approvals
+//! by several reviewers and adjudication are enforced by the server. The
+//! pending extractions are listed next to the document text of the selected
+//! one, with its entities highlighted, and single keys approve, reject,
+//! correct or skip it while the queue statistics update.
+//!
+//! Author: hephaex@gmail.com
+
+use anyhow::Context as _;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Pending extractions fetched per refresh (the API's largest page)
+const PAGE_SIZE: u32 = 100;
+
+/// Characters of document text shown on each side of an extraction
+const WINDOW_CHARS: usize = 300;
+
+/// Keys listed in the footer
+const HELP: &str = "j/k move  a approve  r reject  e edit  s skip  g refresh  q quit";
+
+/// Extracted content, as the API serializes it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Content {
+    Entity {
+        text: String,
+        entity_type: String,
+        /// Byte offsets into the context
+        start: usize,
+        end: usize,
+        /// Character offsets into the context, filled in by the server
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        char_start: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        char_end: Option<usize>,
+    },
+    Relation {
+        subject: String,
+        predicate: String,
+        object: String,
+    },
+}
+
+impl Content {
+    /// One-line form, which is also how corrections are typed
+    pub fn label(&self) -> String {
+        match self {
+            Self::Entity {
+                text, entity_type, ..
+            } => format!("{entity_type}: {text}"),
+            Self::Relation {
+                subject,
+                predicate,
+                object,
+            } => format!("{subject} | {predicate} | {object}"),
+        }
+    }
+}
+
+/// A pending extraction of the queue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub id: Uuid,
+    pub document_title: String,
+    pub confidence: f32,
+    pub contents: Vec<Content>,
+}
+
+#[derive(Deserialize)]
+struct PendingList {
+    extractions: Vec<PendingRow>,
+    total: usize,
+}
+
+/// One entity or relation of a pending extraction
+#[derive(Deserialize)]
+struct PendingRow {
+    id: Uuid,
+    document_title: String,
+    content: Content,
+    confidence: f32,
+}
+
+/// An extraction with its review context
+#[derive(Debug, Clone, Deserialize)]
+pub struct Detail {
+    pub id: Uuid,
+    pub status: String,
+    pub confidence: f32,
+    #[serde(default)]
+    pub locked_by: Option<String>,
+    pub entities: Vec<Content>,
+    pub relations: Vec<Content>,
+    /// Passage the extraction was made from
+    pub context: String,
+    pub window: Option<Window>,
+    pub document: DocumentRef,
+}
+
+/// Document text around the passage
+#[derive(Debug, Clone, Deserialize)]
+pub struct Window {
+    pub before: String,
+    pub after: String,
+    pub page: Option<i32>,
+    pub section: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DocumentRef {
+    pub title: String,
+}
+
+/// Queue totals
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Stats {
+    pub total_pending: u32,
+    pub total_approved: u32,
+    pub total_rejected: u32,
+    pub total_adjudication: u32,
+}
+
+#[derive(Deserialize)]
+struct Decided {
+    message: String,
+}
+
+/// Client of the verification endpoints of the API server
+pub struct ReviewClient {
+    http: reqwest::Client,
+    base: String,
+    token: Option<String>,
+}
+
+impl ReviewClient {
+    /// Client of the server at `api_url`, authenticated with a JWT
+    pub fn new(api_url: &str, token: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base: format!("{}/api/v1/verify", api_url.trim_end_matches('/')),
+            token,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self.http.request(method, format!("{}{path}", self.base));
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(builder: reqwest::RequestBuilder) -> anyhow::Result<T> {
+        let response = builder.send().await.context("API server unreachable")?;
+        let status = response.status();
+        if !status.is_success() {
+            // Errors carry an `ApiError` body
+            let body: serde_json::Value = response.json().await.unwrap_or_default();
+            let message = body["message"]
+                .as_str()
+                .or(status.canonical_reason())
+                .unwrap_or("request failed");
+            anyhow::bail!("{message} ({status})");
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Pending extractions, up to [`PAGE_SIZE`], and the size of the queue
+    pub async fn pending(&self, max_confidence: Option<f32>) -> anyhow::Result<(Vec<Item>, usize)> {
+        let mut query = vec![("page_size", PAGE_SIZE.to_string())];
+        if let Some(max) = max_confidence {
+            query.push(("max_confidence", max.to_string()));
+        }
+        let list: PendingList =
+            Self::send(self.request(reqwest::Method::GET, "/pending").query(&query)).await?;
+        Ok((group(list.extractions), list.total))
+    }
+
+    pub async fn detail(&self, id: Uuid) -> anyhow::Result<Detail> {
+        let path = format!("/{id}?window={WINDOW_CHARS}");
+        Self::send(self.request(reqwest::Method::GET, &path)).await
+    }
+
+    /// Lock an extraction to the caller while it is being decided
+    pub async fn claim(&self, id: Uuid) -> anyhow::Result<()> {
+        let path = format!("/{id}/claim");
+        Self::send::<serde_json::Value>(self.request(reqwest::Method::POST, &path)).await?;
+        Ok(())
+    }
+
+    pub async fn release(&self, id: Uuid) -> anyhow::Result<()> {
+        let path = format!("/{id}/release");
+        Self::send::<serde_json::Value>(self.request(reqwest::Method::POST, &path)).await?;
+        Ok(())
+    }
+
+    /// Approve an extraction, replacing its entities (or relations) by
+    /// `correction`; returns the server's message
+    pub async fn approve(&self, id: Uuid, correction: Option<Content>) -> anyhow::Result<String> {
+        let path = format!("/{id}/approve");
+        let body = serde_json::json!({ "correction": correction });
+        let decided: Decided =
+            Self::send(self.request(reqwest::Method::POST, &path).json(&body)).await?;
+        Ok(decided.message)
+    }
+
+    pub async fn reject(&self, id: Uuid, reason: &str) -> anyhow::Result<String> {
+        let path = format!("/{id}/reject");
+        let body = serde_json::json!({ "reason": reason });
+        let decided: Decided =
+            Self::send(self.request(reqwest::Method::POST, &path).json(&body)).await?;
+        Ok(decided.message)
+    }
+
+    pub async fn stats(&self) -> anyhow::Result<Stats> {
+        Self::send(self.request(reqwest::Method::GET, "/stats")).await
+    }
+}
+
+/// Gather the entities and relations the API lists one by one into their
+/// extractions
+fn group(rows: Vec<PendingRow>) -> Vec<Item> {
+    let mut items: Vec<Item> = Vec::new();
+    for row in rows {
+        match items.iter_mut().find(|item| item.id == row.id) {
+            Some(item) => item.contents.push(row.content),
+            None => items.push(Item {
+                id: row.id,
+                document_title: row.document_title,
+                confidence: row.confidence,
+                contents: vec![row.content],
+            }),
+        }
+    }
+    items
+}
+
+/// Parse a correction typed as `Type: text` or `subject | predicate | object`
+///
+/// An entity's span is where its text first occurs in `context` (empty
+/// when it does not occur).
+pub fn parse_correction(input: &str, context: &str) -> Result<Content, String> {
+    const USAGE: &str = "Type a correction as \"Type: text\" or \"subject | predicate | object\"";
+
+    let parts: Vec<&str> = input.split('|').map(str::trim).collect();
+    match parts.as_slice() {
+        [subject, predicate, object] => {
+            if subject.is_empty() || predicate.is_empty() || object.is_empty() {
+                return Err(USAGE.to_string());
+            }
+            Ok(Content::Relation {
+                subject: subject.to_string(),
+                predicate: predicate.to_string(),
+                object: object.to_string(),
+            })
+        }
+        [entity] => {
+            let (entity_type, text) = entity.split_once(':').ok_or(USAGE)?;
+            let (entity_type, text) = (entity_type.trim(), text.trim());
+            if entity_type.is_empty() || text.is_empty() {
+                return Err(USAGE.to_string());
+            }
+            let (start, end) = context
+                .find(text)
+                .map_or((0, 0), |start| (start, start + text.len()));
+            Ok(Content::Entity {
+                text: text.to_string(),
+                entity_type: entity_type.to_string(),
+                start,
+                end,
+                char_start: None,
+                char_end: None,
+            })
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Split `context` into runs of text inside and outside the character
+/// `spans`, as `(text, highlighted)`
+pub fn highlight(context: &str, spans: &[(usize, usize)]) -> Vec<(String, bool)> {
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    for (i, c) in context.chars().enumerate() {
+        let inside = spans.iter().any(|&(start, end)| start <= i && i < end);
+        match runs.last_mut() {
+            Some((text, highlighted)) if *highlighted == inside => text.push(c),
+            _ => runs.push((c.to_string(), inside)),
+        }
+    }
+    runs
+}
+
+/// What the interface is doing with the keyboard
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    Browse,
+    /// Typing the reason of a rejection
+    Reject(String),
+    /// Typing a correction
+    Edit(String),
+}
+
+/// Request to the server, or the end of the review
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Load(Uuid),
+    Claim(Uuid),
+    Release(Uuid),
+    Approve(Uuid, Option<Content>),
+    Reject(Uuid, String),
+    Refresh,
+    Quit,
+}
+
+/// How an extraction was decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Approved,
+    Corrected,
+    Rejected,
+}
+
+/// Decisions made in this review
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Session {
+    pub approved: usize,
+    pub corrected: usize,
+    pub rejected: usize,
+    pub skipped: usize,
+}
+
+/// State of the interface
+pub struct App {
+    items: Vec<Item>,
+    /// Pending extractions in the queue, listed or not
+    total: usize,
+    list: ListState,
+    detail: Option<Detail>,
+    mode: Mode,
+    stats: Stats,
+    session: Session,
+    status: String,
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            total: 0,
+            list: ListState::default(),
+            detail: None,
+            mode: Mode::Browse,
+            stats: Stats::default(),
+            session: Session::default(),
+            status: String::new(),
+        }
+    }
+
+    /// Replace the listed extractions, keeping the selected one if it is
+    /// still pending
+    pub fn set_items(&mut self, items: Vec<Item>, total: usize) {
+        let selected = self
+            .selected_id()
+            .and_then(|id| items.iter().position(|item| item.id == id))
+            .or_else(|| (!items.is_empty()).then_some(0));
+        self.items = items;
+        self.total = total;
+        self.list.select(selected);
+    }
+
+    pub fn selected_id(&self) -> Option<Uuid> {
+        self.list
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .map(|item| item.id)
+    }
+
+    /// Extraction locked to the reviewer while a decision is typed
+    fn claimed(&self) -> Option<Uuid> {
+        match self.mode {
+            Mode::Browse => None,
+            Mode::Reject(_) | Mode::Edit(_) => self.selected_id(),
+        }
+    }
+
+    /// Handle a key press; returns what to ask of the server
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Command> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Command::Quit);
+        }
+        match self.mode {
+            Mode::Browse => self.browse_key(key.code),
+            Mode::Reject(_) | Mode::Edit(_) => self.input_key(key.code),
+        }
+    }
+
+    fn browse_key(&mut self, code: KeyCode) -> Option<Command> {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => Some(Command::Quit),
+            KeyCode::Char('j') | KeyCode::Down => self.select(1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(-1),
+            KeyCode::Char('g') => Some(Command::Refresh),
+            KeyCode::Char('s') => {
+                let command = self.select(1);
+                if command.is_some() {
+                    self.session.skipped += 1;
+                }
+                command
+            }
+            KeyCode::Char('a') => self.selected_id().map(|id| Command::Approve(id, None)),
+            KeyCode::Char('r') => {
+                let id = self.selected_id()?;
+                self.mode = Mode::Reject(String::new());
+                Some(Command::Claim(id))
+            }
+            KeyCode::Char('e') => {
+                let id = self.selected_id()?;
+                let current = self
+                    .detail
+                    .as_ref()
+                    .filter(|detail| detail.id == id)
+                    .and_then(|detail| detail.entities.first().or(detail.relations.first()))
+                    .map(Content::label)
+                    .unwrap_or_default();
+                self.mode = Mode::Edit(current);
+                Some(Command::Claim(id))
+            }
+            _ => None,
+        }
+    }
+
+    fn input_key(&mut self, code: KeyCode) -> Option<Command> {
+        if let Mode::Reject(input) | Mode::Edit(input) = &mut self.mode {
+            match code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                _ => {}
+            }
+        }
+        match code {
+            KeyCode::Esc => {
+                let id = self.claimed();
+                self.mode = Mode::Browse;
+                self.status = "Cancelled".to_string();
+                id.map(Command::Release)
+            }
+            KeyCode::Enter => self.submit(),
+            _ => None,
+        }
+    }
+
+    fn submit(&mut self) -> Option<Command> {
+        let id = self.selected_id()?;
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Reject(reason) if reason.trim().is_empty() => {
+                self.status = "A rejection needs a reason".to_string();
+                self.mode = Mode::Reject(reason);
+                None
+            }
+            Mode::Reject(reason) => Some(Command::Reject(id, reason.trim().to_string())),
+            Mode::Edit(input) => {
+                let context = self.detail.as_ref().map_or("", |d| d.context.as_str());
+                match parse_correction(&input, context) {
+                    Ok(correction) => Some(Command::Approve(id, Some(correction))),
+                    Err(usage) => {
+                        self.status = usage;
+                        self.mode = Mode::Edit(input);
+                        None
+                    }
+                }
+            }
+            Mode::Browse => None,
+        }
+    }
+
+    /// Move the selection by `delta`; returns the load of the newly
+    /// selected extraction
+    fn select(&mut self, delta: isize) -> Option<Command> {
+        let current = self.list.selected()?;
+        let last = self.items.len().checked_sub(1)?;
+        let next = current.saturating_add_signed(delta).min(last);
+        if next == current {
+            return None;
+        }
+        self.list.select(Some(next));
+        self.detail = None;
+        Some(Command::Load(self.items[next].id))
+    }
+
+    /// Record the decision on `id` and drop it from the list
+    pub fn decided(&mut self, id: Uuid, outcome: Outcome, message: String) {
+        match outcome {
+            Outcome::Approved => self.session.approved += 1,
+            Outcome::Corrected => self.session.corrected += 1,
+            Outcome::Rejected => self.session.rejected += 1,
+        }
+        if let Some(index) = self.items.iter().position(|item| item.id == id) {
+            self.items.remove(index);
+            self.total = self.total.saturating_sub(1);
+        }
+        let selected = self
+            .list
+            .selected()
+            .map(|i| i.min(self.items.len().saturating_sub(1)))
+            .filter(|_| !self.items.is_empty());
+        self.list.select(selected);
+        self.detail = None;
+        self.status = message;
+    }
+}
+
+/// Settings of an `otl verify review` session
+pub struct ReviewOptions {
+    pub api_url: String,
+    pub token: Option<String>,
+    /// Review only extractions at or below this confidence
+    pub max_confidence: Option<f32>,
+}
+
+/// Run the interface until the reviewer quits; returns their decisions
+pub async fn run(options: &ReviewOptions) -> anyhow::Result<Session> {
+    let client = ReviewClient::new(&options.api_url, options.token.clone());
+    let mut app = App::new();
+    // Fail before taking over the terminal when the server is unreachable
+    refresh(&client, &mut app, options.max_confidence)
+        .await
+        .with_context(|| format!("Failed to read the review queue at {}", options.api_url))?;
+
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &client, &mut app, options).await;
+    ratatui::restore();
+    result.map(|()| app.session)
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    client: &ReviewClient,
+    app: &mut App,
+    options: &ReviewOptions,
+) -> anyhow::Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, app))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match app.handle_key(key) {
+            Some(Command::Quit) => {
+                if let Some(id) = app.claimed() {
+                    // The lock also expires on its own
+                    let _ = client.release(id).await;
+                }
+                return Ok(());
+            }
+            Some(command) => {
+                if let Err(e) = execute(client, app, command, options.max_confidence).await {
+                    app.mode = Mode::Browse;
+                    app.status = format!("{e:#}");
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+async fn execute(
+    client: &ReviewClient,
+    app: &mut App,
+    command: Command,
+    max_confidence: Option<f32>,
+) -> anyhow::Result<()> {
+    let decided = match command {
+        Command::Load(id) => {
+            app.detail = Some(client.detail(id).await?);
+            return Ok(());
+        }
+        Command::Claim(id) => return client.claim(id).await,
+        Command::Release(id) => return client.release(id).await,
+        Command::Refresh => {
+            refresh(client, app, max_confidence).await?;
+            app.status = "Refreshed".to_string();
+            return Ok(());
+        }
+        Command::Quit => return Ok(()),
+        Command::Approve(id, None) => (id, Outcome::Approved, client.approve(id, None).await?),
+        Command::Approve(id, correction) => (
+            id,
+            Outcome::Corrected,
+            client.approve(id, correction).await?,
+        ),
+        Command::Reject(id, reason) => (id, Outcome::Rejected, client.reject(id, &reason).await?),
+    };
+
+    let (id, outcome, message) = decided;
+    app.decided(id, outcome, message);
+    app.stats = client.stats().await?;
+    if let Some(id) = app.selected_id() {
+        app.detail = Some(client.detail(id).await?);
+    }
+    Ok(())
+}
+
+async fn refresh(
+    client: &ReviewClient,
+    app: &mut App,
+    max_confidence: Option<f32>,
+) -> anyhow::Result<()> {
+    let (items, total) = client.pending(max_confidence).await?;
+    app.stats = client.stats().await?;
+    app.set_items(items, total);
+    app.detail = match app.selected_id() {
+        Some(id) => Some(client.detail(id).await?),
+        None => None,
+    };
+    Ok(())
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(5),
+        Constraint::Length(3),
+    ])
+    .areas(frame.area());
+    let [list_area, detail_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(body);
+
+    let stats = app.stats;
+    let session = app.session;
+    let summary = format!(
+        "Queue: {} pending · {} approved · {} rejected · {} in adjudication    \
+         This session: {} approved · {} corrected · {} rejected · {} skipped",
+        stats.total_pending,
+        stats.total_approved,
+        stats.total_rejected,
+        stats.total_adjudication,
+        session.approved,
+        session.corrected,
+        session.rejected,
+        session.skipped
+    );
+    frame.render_widget(
+        Paragraph::new(summary).block(Block::default().borders(Borders::ALL).title("OTL review")),
+        header,
+    );
+
+    let items: Vec<ListItem> = app.items.iter().map(list_item).collect();
+    let title = format!("Pending ({} of {})", app.items.len(), app.total);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, list_area, &mut app.list);
+
+    let detail = app.detail.as_ref().map(detail_lines).unwrap_or_default();
+    frame.render_widget(
+        Paragraph::new(detail)
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL).title("Extraction")),
+        detail_area,
+    );
+
+    let prompt = match &app.mode {
+        Mode::Browse => HELP.to_string(),
+        Mode::Reject(input) => format!("Reason: {input}_   (Enter reject, Esc cancel)"),
+        Mode::Edit(input) => format!("Correction: {input}_   (Enter approve, Esc cancel)"),
+    };
+    frame.render_widget(
+        Paragraph::new(vec![Line::from(app.status.as_str()), Line::from(prompt)])
+            .block(Block::default().borders(Borders::TOP)),
+        footer,
+    );
+}
+
+fn list_item(item: &Item) -> ListItem<'static> {
+    let first = item
+        .contents
+        .first()
+        .map(Content::label)
+        .unwrap_or_default();
+    let more = match item.contents.len() {
+        0 | 1 => String::new(),
+        n => format!(" (+{})", n - 1),
+    };
+    ListItem::new(format!(
+        "{:.2}  {}{more}  [{}]",
+        item.confidence, first, item.document_title
+    ))
+}
+
+fn detail_lines(detail: &Detail) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let mark = Style::default()
+        .fg(Color::Black)
+        .bg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+
+    let mut place = detail.document.title.clone();
+    if let Some(window) = &detail.window {
+        if let Some(page) = window.page {
+            place.push_str(&format!(" · p.{page}"));
+        }
+        if let Some(section) = &window.section {
+            place.push_str(&format!(" · {section}"));
+        }
+    }
+    let mut state = format!("{} · confidence {:.2}", detail.status, detail.confidence);
+    if let Some(reviewer) = &detail.locked_by {
+        state.push_str(&format!(" · locked by {reviewer}"));
+    }
+
+    let spans: Vec<(usize, usize)> = detail
+        .entities
+        .iter()
+        .filter_map(|entity| match entity {
+            Content::Entity {
+                char_start: Some(start),
+                char_end: Some(end),
+                ..
+            } => Some((*start, *end)),
+            _ => None,
+        })
+        .collect();
+    let mut pieces: Vec<(String, Style)> = Vec::new();
+    if let Some(window) = &detail.window {
+        pieces.push((window.before.clone(), dim));
+    }
+    for (text, highlighted) in highlight(&detail.context, &spans) {
+        pieces.push((text, if highlighted { mark } else { Style::default() }));
+    }
+    if let Some(window) = &detail.window {
+        pieces.push((window.after.clone(), dim));
+    }
+
+    let mut lines = vec![
+        Line::styled(place, Style::default().add_modifier(Modifier::BOLD)),
+        Line::from(state),
+        Line::default(),
+    ];
+    lines.extend(styled_lines(pieces));
+    lines.push(Line::default());
+    for (heading, contents) in [
+        ("Entities", &detail.entities),
+        ("Relations", &detail.relations),
+    ] {
+        if contents.is_empty() {
+            continue;
+        }
+        lines.push(Line::styled(
+            heading,
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        lines.extend(
+            contents
+                .iter()
+                .map(|c| Line::from(format!("  {}", c.label()))),
+        );
+    }
+    lines
+}
+
+/// Lay styled text out in lines, breaking at its newlines
+fn styled_lines(pieces: Vec<(String, Style)>) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::default()];
+    for (text, style) in pieces {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Line::default());
+            }
+            if !part.is_empty() {
+                if let Some(line) = lines.last_mut() {
+                    line.push_span(Span::styled(part.to_string(), style));
+                }
+            }
+        }
+    }
+    lines
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(text: &str, entity_type: &str) -> Content {
+        Content::Entity {
+            text: text.to_string(),
+            entity_type: entity_type.to_string(),
+            start: 0,
+            end: text.len(),
+            char_start: None,
+            char_end: None,
+        }
+    }
+
+    fn app_with(ids: &[Uuid]) -> App {
+        let mut app = App::new();
+        let items = ids
+            .iter()
+            .map(|&id| Item {
+                id,
+                document_title: "인사규정.pdf".to_string(),
+                confidence: 0.6,
+                contents: vec![entity("연차휴가", "LeaveType")],
+            })
+            .collect();
+        app.set_items(items, ids.len());
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Option<Command> {
+        app.handle_key(KeyEvent::from(code))
+    }
+
+    #[test]
+    fn test_pending_rows_grouped_by_extraction() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let row = |id, content| PendingRow {
+            id,
+            document_title: "인사규정.pdf".to_string(),
+            content,
+            confidence: 0.7,
+        };
+        let relation = Content::Relation {
+            subject: "연차휴가".to_string(),
+            predicate: "requires".to_string(),
+            object: "팀장 승인".to_string(),
+        };
+        let items = group(vec![
+            row(a, entity("연차휴가", "LeaveType")),
+            row(b, entity("팀장", "Role")),
+            row(a, relation.clone()),
+        ]);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].id, a);
+        assert_eq!(
+            items[0].contents,
+            vec![entity("연차휴가", "LeaveType"), relation]
+        );
+    }
+
+    #[test]
+    fn test_keys_reject_with_reason() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut app = app_with(&[a, b]);
+
+        assert_eq!(press(&mut app, KeyCode::Char('r')), Some(Command::Claim(a)));
+        // A rejection needs a reason
+        assert_eq!(press(&mut app, KeyCode::Enter), None);
+        assert_eq!(app.mode, Mode::Reject(String::new()));
+        for c in "오추출".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(
+            press(&mut app, KeyCode::Enter),
+            Some(Command::Reject(a, "오추출".to_string()))
+        );
+        assert_eq!(app.mode, Mode::Browse);
+
+        app.decided(a, Outcome::Rejected, "Extraction rejected".to_string());
+        assert_eq!(app.selected_id(), Some(b));
+        assert_eq!(app.session.rejected, 1);
+
+        // Escape releases the lock taken for a decision
+        assert_eq!(press(&mut app, KeyCode::Char('e')), Some(Command::Claim(b)));
+        assert_eq!(press(&mut app, KeyCode::Esc), Some(Command::Release(b)));
+        assert_eq!(press(&mut app, KeyCode::Char('q')), Some(Command::Quit));
+    }
+
+    #[test]
+    fn test_keys_move_and_skip() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut app = app_with(&[a, b]);
+
+        assert_eq!(press(&mut app, KeyCode::Up), None);
+        assert_eq!(press(&mut app, KeyCode::Char('s')), Some(Command::Load(b)));
+        // Nothing after the last extraction
+        assert_eq!(press(&mut app, KeyCode::Char('s')), None);
+        assert_eq!(app.session.skipped, 1);
+        assert_eq!(
+            press(&mut app, KeyCode::Char('a')),
+            Some(Command::Approve(b, None))
+        );
+    }
+
+    #[test]
+    fn test_parse_correction() {
+        let context = "연차휴가는 팀장의 사전 승인을 받아야 한다.";
+
+        assert_eq!(
+            parse_correction("Role: 팀장", context),
+            Ok(Content::Entity {
+                text: "팀장".to_string(),
+                entity_type: "Role".to_string(),
+                start: "연차휴가는 ".len(),
+                end: "연차휴가는 팀장".len(),
+                char_start: None,
+                char_end: None,
+            })
+        );
+        assert_eq!(
+            parse_correction(" 연차휴가 | requires | 팀장 승인 ", context),
+            Ok(Content::Relation {
+                subject: "연차휴가".to_string(),
+                predicate: "requires".to_string(),
+                object: "팀장 승인".to_string(),
+            })
+        );
+        assert!(parse_correction("팀장", context).is_err());
+        assert!(parse_correction("a | | c", context).is_err());
+    }
+
+    #[test]
+    fn test_highlight_character_spans() {
+        let runs = highlight("연차휴가는 팀장의 승인", &[(0, 4), (6, 8)]);
+        assert_eq!(
+            runs,
+            vec![
+                ("연차휴가".to_string(), true),
+                ("는 ".to_string(), false),
+                ("팀장".to_string(), true),
+                ("의 승인".to_string(), false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Stages of the ingestion pipelines
+//!
+//! Implementations of the stages named in pipeline definitions (see
+//! `otl_core::pipeline`), over the [`FileContext`] of one ingested file.
+//! Each stage reads what earlier stages left in the context and adds its
+//! own output; `index` writes everything in one transaction.
+//!
+//! Author: hephaex@gmail.com
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use otl_core::pipeline::{Stage, StageDefinition, StageKind};
+use otl_core::{AccessLevel, OtlError, Result};
+use otl_extractor::ner::RuleBasedNer;
+use otl_extractor::plugin::{ChainedEntityExtractor, ChainedRelationExtractor, PluginHost};
+use otl_extractor::relation::RuleBasedRe;
+use otl_extractor::{EntityExtractor, RelationExtractor};
+use otl_ocr::OcrManager;
+use otl_parser::pii::{PiiConfig, PiiRedactor};
+use otl_parser::report::LOW_OCR_CONFIDENCE;
+use otl_parser::{
+    chunk_document, ChunkConfig, DocumentSection, FileType, IngestReport, ParsedDocument,
+    ParserRegistry, TextChunk,
+};
+use otl_vector::{EmbeddingClient, TokenCounter};
+
+use crate::import::{ImportRecord, Loader, QueuedExtraction};
+
+/// State of one file as it moves through its pipeline
+pub(crate) struct FileContext {
+    pub path: PathBuf,
+    pub document_id: Uuid,
+    pub pipeline: String,
+    pub collection: Option<String>,
+    pub document: Option<ParsedDocument>,
+    pub chunks: Vec<TextChunk>,
+    pub report: Option<IngestReport>,
+    /// Embeddings of the non-empty chunks, in chunk order
+    pub embeddings: Vec<Vec<f32>>,
+    pub extractions: Vec<QueuedExtraction>,
+    pub redactions: usize,
+}
+
+impl FileContext {
+    pub fn new(path: PathBuf, pipeline: &str, collection: Option<&str>) -> Self {
+        Self {
+            path,
+            document_id: Uuid::new_v4(),
+            pipeline: pipeline.to_string(),
+            collection: collection.map(str::to_string),
+            document: None,
+            chunks: Vec::new(),
+            report: None,
+            embeddings: Vec::new(),
+            extractions: Vec::new(),
+            redactions: 0,
+        }
+    }
+
+    fn document(&mut self) -> Result<&mut ParsedDocument> {
+        self.document
+            .as_mut()
+            .ok_or_else(|| OtlError::ValidationError("No document was read".to_string()))
+    }
+
+    /// Chunks that can be embedded; empty chunks stay in the report only
+    fn embeddable_chunks(&self) -> impl Iterator<Item = &TextChunk> {
+        self.chunks.iter().filter(|c| !c.content.trim().is_empty())
+    }
+}
+
+/// Shared resources of the stages
+pub(crate) struct StageResources {
+    /// Plugin parsers ahead of the built-in ones
+    pub registry: ParserRegistry,
+    pub plugins: PluginHost,
+    pub ocr: Arc<OcrManager>,
+    pub counter: TokenCounter,
+    /// Embedding client and stores; `None` in a dry run
+    pub stores: Option<(Box<dyn EmbeddingClient>, Loader)>,
+}
+
+/// Implementation of a stage definition
+///
+/// Embedding and indexing are left out when there are no stores (a dry
+/// run).
+pub(crate) fn build_stage(
+    resources: &Arc<StageResources>,
+    definition: &StageDefinition,
+) -> Result<Option<Box<dyn Stage<FileContext>>>> {
+    let resources = resources.clone();
+    let writes = matches!(definition.stage, StageKind::Embed | StageKind::Index);
+    if writes && resources.stores.is_none() {
+        return Ok(None);
+    }
+    let stage: Box<dyn Stage<FileContext>> = match definition.stage {
+        StageKind::Parse => Box::new(ParseStage { resources }),
+        StageKind::Ocr => Box::new(OcrStage {
+            settings: definition.settings()?,
+            resources,
+        }),
+        StageKind::PiiRedact => Box::new(PiiRedactStage {
+            redactor: PiiRedactor::new(&definition.settings::<PiiConfig>()?),
+        }),
+        StageKind::Chunk => Box::new(ChunkStage {
+            config: definition.settings::<ChunkConfig>()?,
+            resources,
+        }),
+        StageKind::Extract => Box::new(ExtractStage {
+            settings: definition.settings()?,
+            ner: resources.plugins.entity_extractor(RuleBasedNer::new()),
+            re: resources.plugins.relation_extractor(RuleBasedRe::new()),
+        }),
+        StageKind::Embed => Box::new(EmbedStage {
+            settings: definition.settings()?,
+            resources,
+        }),
+        StageKind::Index => Box::new(IndexStage {
+            settings: definition.settings()?,
+            resources,
+        }),
+    };
+    Ok(Some(stage))
+}
+
+fn failed(e: impl Into<anyhow::Error>) -> OtlError {
+    OtlError::Other(e.into())
+}
+
+// ============================================================================
+// Reading
+// ============================================================================
+
+struct ParseStage {
+    resources: Arc<StageResources>,
+}
+
+#[async_trait]
+impl Stage<FileContext> for ParseStage {
+    async fn run(&self, context: &mut FileContext) -> Result<()> {
+        let doc = self
+            .resources
+            .registry
+            .parse(&context.path)
+            .map_err(failed)?;
+        context.document = Some(doc);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OcrSettings {
+    /// Leave pages below `LOW_OCR_CONFIDENCE` out of the text, so garbled
+    /// pages are not embedded; they stay listed for re-scanning
+    exclude_low_confidence: bool,
+}
+
+/// Reads the file with OCR unless an earlier stage found text in it
+///
+/// Each page with text becomes a section starting on that page, so chunks
+/// keep their page numbers.
+struct OcrStage {
+    settings: OcrSettings,
+    resources: Arc<StageResources>,
+}
+
+#[async_trait]
+impl Stage<FileContext> for OcrStage {
+    async fn run(&self, context: &mut FileContext) -> Result<()> {
+        let has_text = context
+            .document
+            .as_ref()
+            .is_some_and(|d| !d.content.trim().is_empty());
+        if has_text {
+            return Ok(());
+        }
+
+        let pages = self
+            .resources
+            .ocr
+            .extract_pages(&context.path)
+            .map_err(failed)?;
+        let doc = context.document.get_or_insert_with(|| {
+            ParsedDocument::new(context.path.display().to_string(), FileType::Unknown)
+        });
+        doc.sections.clear();
+        doc.metadata.ocr_applied = true;
+        doc.metadata.ocr_confidence = pages.iter().map(|p| p.confidence).collect();
+        doc.metadata.ocr_pages.clear();
+        doc.metadata.ocr_excluded_pages.clear();
+        doc.tables.clear();
+        for page in pages {
+            if self.settings.exclude_low_confidence && page.confidence < LOW_OCR_CONFIDENCE {
+                doc.metadata.ocr_excluded_pages.push(page.page);
+                continue;
+            }
+            doc.tables.extend(page.tables);
+            if !page.text.trim().is_empty() {
+                doc.sections
+                    .push(DocumentSection::new(page.text).with_start_page(page.page));
+            }
+        }
+        doc.content = doc
+            .sections
+            .iter()
+            .map(|s| s.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        doc.metadata.page_count = Some(doc.metadata.ocr_confidence.len() as u32);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Processing
+// ============================================================================
+
+struct PiiRedactStage {
+    redactor: PiiRedactor,
+}
+
+#[async_trait]
+impl Stage<FileContext> for PiiRedactStage {
+    async fn run(&self, context: &mut FileContext) -> Result<()> {
+        let redactions = self.redactor.redact_document(context.document()?);
+        context.redactions += redactions;
+        Ok(())
+    }
+}
+
+/// Chunks the document and reports on the chunks
+struct ChunkStage {
+    config: ChunkConfig,
+    resources: Arc<StageResources>,
+}
+
+#[async_trait]
+impl Stage<FileContext> for ChunkStage {
+    async fn run(&self, context: &mut FileContext) -> Result<()> {
+        let counter = self.resources.counter;
+        let doc = context.document()?;
+        let chunks = chunk_document(doc, &self.config);
+        let report = IngestReport::for_document(doc, &chunks, counter.max_tokens, |text| {
+            counter.count(text)
+        });
+        context.chunks = chunks;
+        context.report = Some(report);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ExtractSettings {
+    /// Entities below this confidence are not queued
+    min_confidence: f32,
+}
+
+/// Extracts entities and relations of each chunk with the rule-based
+/// extractors and those of plugins; `index` queues them for review
+struct ExtractStage {
+    settings: ExtractSettings,
+    ner: ChainedEntityExtractor,
+    re: ChainedRelationExtractor,
+}
+
+#[async_trait]
+impl Stage<FileContext> for ExtractStage {
+    async fn run(&self, context: &mut FileContext) -> Result<()> {
+        let mut extractions = Vec::new();
+        for chunk in context.embeddable_chunks() {
+            let entities: Vec<_> = self
+                .ner
+                .extract(&chunk.content)?
+                .into_iter()
+                .filter(|e| e.confidence >= self.settings.min_confidence)
+                .collect();
+            if entities.is_empty() {
+                continue;
+            }
+            let relations = self.re.extract(&chunk.content, &entities)?;
+            extractions.push(QueuedExtraction::new(
+                &entities,
+                &relations,
+                chunk.content.clone(),
+            )?);
+        }
+        context.extractions = extractions;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Storing
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct EmbedSettings {
+    /// Chunks embedded per request
+    batch_size: usize,
+}
+
+impl Default for EmbedSettings {
+    fn default() -> Self {
+        Self { batch_size: 32 }
+    }
+}
+
+struct EmbedStage {
+    settings: EmbedSettings,
+    resources: Arc<StageResources>,
+}
+
+#[async_trait]
+impl Stage<FileContext> for EmbedStage {
+    async fn run(&self, context: &mut FileContext) -> Result<()> {
+        let Some((embedder, _)) = &self.resources.stores else {
+            return Ok(());
+        };
+        let texts: Vec<String> = context
+            .embeddable_chunks()
+            .map(|c| c.content.clone())
+            .collect();
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.settings.batch_size.max(1)) {
+            embeddings.extend(embedder.embed_batch(batch).await?);
+        }
+        context.embeddings = embeddings;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct IndexSettings {
+    access_level: AccessLevel,
+    department: Option<String>,
+    required_roles: Vec<String>,
+}
+
+/// Stores the document with its chunks, vectors, report and queued
+/// extractions
+struct IndexStage {
+    settings: IndexSettings,
+    resources: Arc<StageResources>,
+}
+
+#[async_trait]
+impl Stage<FileContext> for IndexStage {
+    async fn run(&self, context: &mut FileContext) -> Result<()> {
+        let Some((_, loader)) = &self.resources.stores else {
+            return Ok(());
+        };
+        let Some(doc) = &context.document else {
+            return Err(OtlError::ValidationError(
+                "No document was read".to_string(),
+            ));
+        };
+        if context.embeddings.is_empty() {
+            return Ok(());
+        }
+
+        let title = doc
+            .metadata
+            .title
+            .clone()
+            .unwrap_or_else(|| crate::ingest::file_title(&context.path));
+        let metadata = serde_json::json!({
+            "source_file": context.path.display().to_string(),
+            "file_type": doc.file_type.to_string(),
+            "pipeline": context.pipeline,
+            "collection": context.collection,
+            "pii_redactions": context.redactions,
+            "ingest_report": context.report,
+        });
+        let records: Vec<ImportRecord> = context
+            .embeddable_chunks()
+            .zip(&context.embeddings)
+            .map(|(chunk, embedding)| ImportRecord {
+                document_id: context.document_id,
+                chunk_index: chunk.index,
+                content: chunk.content.clone(),
+                embedding: embedding.clone(),
+                title: Some(title.clone()),
+                page: chunk.page,
+                section: chunk.section.clone(),
+                metadata: Some(metadata.clone()),
+                access_level: self.settings.access_level,
+                department: self.settings.department.clone(),
+                required_roles: self.settings.required_roles.clone(),
+            })
+            .collect();
+
+        let source = format!("file://{}", context.path.display());
+        loader
+            .load_document(&source, context.document_id, &records, &context.extractions)
+            .await
+            .map_err(OtlError::Other)
+    }
+}
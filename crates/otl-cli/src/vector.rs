@@ -0,0 +1,133 @@
+//! Vector storage benchmark
+//!
+//! `otl vector bench` samples stored vectors from the configured Qdrant
+//! collection and compares recall and memory of dimension truncation and
+//! quantization settings on them. Part of the sample is used as queries and
+//! searched against the rest, so no embedding model is called. The results
+//! help choose `VECTOR_TRUNCATE_DIM` and `VECTOR_QUANTIZATION` before
+//! changing a collection.
+//!
+//! Author: hephaex@gmail.com
+
+use anyhow::bail;
+
+use otl_core::AppConfig;
+use otl_vector::quantization::{self, BenchmarkResult};
+use otl_vector::QdrantStore;
+
+/// Outcome of `otl vector bench`
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub collection: String,
+    /// Points in the collection, used to estimate memory
+    pub point_count: u64,
+    /// Sampled vectors searched against
+    pub base_size: usize,
+    /// Sampled vectors used as queries
+    pub query_count: usize,
+    pub top_k: usize,
+    pub results: Vec<BenchmarkResult>,
+}
+
+/// Split `sample` into queries (every `n`-th vector) and base vectors
+pub fn split_sample(sample: Vec<Vec<f32>>, queries: usize) -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
+    let step = (sample.len() / queries.max(1)).max(2);
+    let mut query_vectors = Vec::with_capacity(queries);
+    let mut base = Vec::with_capacity(sample.len());
+    for (i, vector) in sample.into_iter().enumerate() {
+        if i % step == 0 && query_vectors.len() < queries {
+            query_vectors.push(vector);
+        } else {
+            base.push(vector);
+        }
+    }
+    (query_vectors, base)
+}
+
+/// Human-readable byte count
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Sample `sample` vectors and benchmark the default cases
+pub async fn bench(sample: usize, queries: usize, top_k: usize) -> anyhow::Result<BenchReport> {
+    if queries == 0 || top_k == 0 {
+        bail!("--queries and --top-k must be positive");
+    }
+    let config = AppConfig::from_env()?;
+    let store = QdrantStore::new(&config.database).await?;
+    let point_count = store.count().await?;
+
+    println!(
+        "Sampling {} of {} vectors from '{}'...",
+        sample.min(point_count as usize),
+        point_count,
+        store.collection()
+    );
+    let vectors = store.sample_vectors(sample).await?;
+    if vectors.len() < queries + top_k {
+        bail!(
+            "{} vectors sampled, need at least {} for {} queries and top {}",
+            vectors.len(),
+            queries + top_k,
+            queries,
+            top_k
+        );
+    }
+    let dimension = vectors[0].len();
+    let (query_vectors, base) = split_sample(vectors, queries);
+
+    println!(
+        "Benchmarking {} queries against {} vectors...",
+        query_vectors.len(),
+        base.len()
+    );
+    let cases = quantization::default_cases(dimension);
+    let results = quantization::benchmark(&base, &query_vectors, top_k, &cases);
+
+    Ok(BenchReport {
+        collection: store.collection().to_string(),
+        point_count,
+        base_size: base.len(),
+        query_count: query_vectors.len(),
+        top_k,
+        results,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_sample() {
+        let sample: Vec<Vec<f32>> = (0..10).map(|i| vec![i as f32]).collect();
+        let (queries, base) = split_sample(sample, 3);
+
+        assert_eq!(queries, vec![vec![0.0], vec![3.0], vec![6.0]]);
+        assert_eq!(base.len(), 7);
+        assert!(!base.contains(&vec![3.0]));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,367 @@
+//! Confidence calibration
+//!
+//! Extractor and RAG confidences are heuristic scores, not probabilities.
+//! A [`Calibrator`] maps a raw score to the observed probability of being
+//! correct, fitted from labelled outcomes such as HITL approve/reject
+//! decisions. Two methods are supported: Platt scaling (a logistic fit,
+//! smooth and robust with few samples) and isotonic regression (monotone
+//! step fit, more flexible with many samples).
+//!
+//! Author: hephaex@gmail.com
+
+use crate::{OtlError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Minimum number of labelled samples required to fit a calibrator
+pub const MIN_CALIBRATION_SAMPLES: usize = 20;
+
+/// A raw confidence paired with whether the prediction was correct
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationSample {
+    pub confidence: f32,
+    pub correct: bool,
+}
+
+impl CalibrationSample {
+    /// Create a labelled sample
+    pub fn new(confidence: f32, correct: bool) -> Self {
+        Self {
+            confidence,
+            correct,
+        }
+    }
+}
+
+/// Calibration fitting method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalibrationMethod {
+    #[default]
+    Platt,
+    Isotonic,
+}
+
+impl std::str::FromStr for CalibrationMethod {
+    type Err = OtlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "platt" => Ok(Self::Platt),
+            "isotonic" => Ok(Self::Isotonic),
+            other => Err(OtlError::ValidationError(format!(
+                "Unknown calibration method: {other}"
+            ))),
+        }
+    }
+}
+
+/// Maps raw confidences to calibrated probabilities
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+pub enum Calibrator {
+    /// Leave confidences unchanged
+    #[default]
+    Identity,
+    /// `1 / (1 + exp(-(a * x + b)))`
+    Platt { a: f32, b: f32 },
+    /// Piecewise-linear interpolation through monotone `(x, y)` points
+    Isotonic { points: Vec<(f32, f32)> },
+}
+
+impl Calibrator {
+    /// Fit a calibrator with the given method
+    pub fn fit(method: CalibrationMethod, samples: &[CalibrationSample]) -> Result<Self> {
+        if samples.len() < MIN_CALIBRATION_SAMPLES {
+            return Err(OtlError::ValidationError(format!(
+                "At least {MIN_CALIBRATION_SAMPLES} samples are required, got {}",
+                samples.len()
+            )));
+        }
+        let positives = samples.iter().filter(|s| s.correct).count();
+        if positives == 0 || positives == samples.len() {
+            return Err(OtlError::ValidationError(
+                "Samples must contain both correct and incorrect outcomes".to_string(),
+            ));
+        }
+
+        Ok(match method {
+            CalibrationMethod::Platt => fit_platt(samples),
+            CalibrationMethod::Isotonic => fit_isotonic(samples),
+        })
+    }
+
+    /// Calibrated confidence for a raw score
+    pub fn apply(&self, confidence: f32) -> f32 {
+        match self {
+            Self::Identity => confidence,
+            Self::Platt { a, b } => sigmoid(*a as f64 * confidence as f64 + *b as f64) as f32,
+            Self::Isotonic { points } => interpolate(points, confidence),
+        }
+    }
+
+    /// Whether this calibrator leaves confidences unchanged
+    pub fn is_identity(&self) -> bool {
+        matches!(self, Self::Identity)
+    }
+}
+
+/// One bin of a reliability curve
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationBin {
+    pub lower: f32,
+    pub upper: f32,
+    pub count: usize,
+    /// Mean (calibrated) confidence of the samples in the bin
+    pub mean_confidence: f32,
+    /// Fraction of correct samples in the bin
+    pub accuracy: f32,
+}
+
+/// Reliability curve of a calibrator over labelled samples
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationCurve {
+    pub bins: Vec<CalibrationBin>,
+    /// Sample-weighted mean gap between confidence and accuracy
+    pub expected_calibration_error: f32,
+}
+
+/// Bin samples by calibrated confidence and compare against accuracy
+pub fn reliability_curve(
+    samples: &[CalibrationSample],
+    calibrator: &Calibrator,
+    num_bins: usize,
+) -> CalibrationCurve {
+    let num_bins = num_bins.max(1);
+    let mut counts = vec![0usize; num_bins];
+    let mut confidence_sums = vec![0.0f64; num_bins];
+    let mut correct = vec![0usize; num_bins];
+
+    for sample in samples {
+        let confidence = calibrator.apply(sample.confidence).clamp(0.0, 1.0);
+        let bin = ((confidence * num_bins as f32) as usize).min(num_bins - 1);
+        counts[bin] += 1;
+        confidence_sums[bin] += confidence as f64;
+        correct[bin] += sample.correct as usize;
+    }
+
+    let mut ece = 0.0f64;
+    let bins = (0..num_bins)
+        .map(|i| {
+            let (mean_confidence, accuracy) = if counts[i] == 0 {
+                (0.0, 0.0)
+            } else {
+                let n = counts[i] as f64;
+                (confidence_sums[i] / n, correct[i] as f64 / n)
+            };
+            if !samples.is_empty() {
+                ece += counts[i] as f64 / samples.len() as f64 * (accuracy - mean_confidence).abs();
+            }
+            CalibrationBin {
+                lower: i as f32 / num_bins as f32,
+                upper: (i + 1) as f32 / num_bins as f32,
+                count: counts[i],
+                mean_confidence: mean_confidence as f32,
+                accuracy: accuracy as f32,
+            }
+        })
+        .collect();
+
+    CalibrationCurve {
+        bins,
+        expected_calibration_error: ece as f32,
+    }
+}
+
+// ============================================================================
+// Fitting
+// ============================================================================
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// Logistic fit with Platt's smoothed targets, solved by Newton's method
+fn fit_platt(samples: &[CalibrationSample]) -> Calibrator {
+    let positives = samples.iter().filter(|s| s.correct).count() as f64;
+    let negatives = samples.len() as f64 - positives;
+    let target_pos = (positives + 1.0) / (positives + 2.0);
+    let target_neg = 1.0 / (negatives + 2.0);
+
+    let (mut a, mut b) = (1.0f64, 0.0f64);
+    for _ in 0..100 {
+        let (mut g_a, mut g_b) = (0.0, 0.0);
+        let (mut h_aa, mut h_ab, mut h_bb) = (1e-9, 0.0, 1e-9);
+
+        for sample in samples {
+            let x = sample.confidence as f64;
+            let p = sigmoid(a * x + b);
+            let t = if sample.correct {
+                target_pos
+            } else {
+                target_neg
+            };
+            let w = (p * (1.0 - p)).max(1e-12);
+            g_a += (p - t) * x;
+            g_b += p - t;
+            h_aa += w * x * x;
+            h_ab += w * x;
+            h_bb += w;
+        }
+
+        let det = h_aa * h_bb - h_ab * h_ab;
+        if det.abs() < 1e-18 {
+            break;
+        }
+        let step_a = (h_bb * g_a - h_ab * g_b) / det;
+        let step_b = (h_aa * g_b - h_ab * g_a) / det;
+        a -= step_a;
+        b -= step_b;
+
+        if step_a.abs() < 1e-8 && step_b.abs() < 1e-8 {
+            break;
+        }
+    }
+
+    Calibrator::Platt {
+        a: a as f32,
+        b: b as f32,
+    }
+}
+
+/// Isotonic regression by pool-adjacent-violators
+fn fit_isotonic(samples: &[CalibrationSample]) -> Calibrator {
+    let mut sorted: Vec<&CalibrationSample> = samples.iter().collect();
+    sorted.sort_by(|a, b| a.confidence.total_cmp(&b.confidence));
+
+    // (sum of x, sum of y, weight)
+    let mut blocks: Vec<(f64, f64, f64)> = Vec::with_capacity(sorted.len());
+    for sample in sorted {
+        blocks.push((sample.confidence as f64, sample.correct as u8 as f64, 1.0));
+        while blocks.len() > 1 {
+            let (x2, y2, w2) = blocks[blocks.len() - 1];
+            let (x1, y1, w1) = blocks[blocks.len() - 2];
+            if y1 / w1 <= y2 / w2 {
+                break;
+            }
+            blocks.pop();
+            *blocks.last_mut().expect("two blocks present") = (x1 + x2, y1 + y2, w1 + w2);
+        }
+    }
+
+    Calibrator::Isotonic {
+        points: blocks
+            .into_iter()
+            .map(|(x, y, w)| ((x / w) as f32, (y / w) as f32))
+            .collect(),
+    }
+}
+
+fn interpolate(points: &[(f32, f32)], x: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return x;
+    };
+    if x <= first.0 {
+        return first.1;
+    }
+    if x >= last.0 {
+        return last.1;
+    }
+
+    let upper = points.partition_point(|p| p.0 <= x);
+    let (x0, y0) = points[upper - 1];
+    let (x1, y1) = points[upper];
+    if x1 <= x0 {
+        return y1;
+    }
+    y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scores that are overconfident: a score of `s` is correct with
+    /// probability roughly `s - 0.3`
+    fn overconfident_samples() -> Vec<CalibrationSample> {
+        let mut samples = Vec::new();
+        for i in 0..10 {
+            let score = 0.5 + i as f32 * 0.05;
+            let correct_out_of_ten = ((score - 0.3) * 10.0).round() as usize;
+            for j in 0..10 {
+                samples.push(CalibrationSample::new(score, j < correct_out_of_ten));
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_fit_requires_enough_mixed_samples() {
+        let few = vec![CalibrationSample::new(0.9, true); 5];
+        assert!(Calibrator::fit(CalibrationMethod::Platt, &few).is_err());
+
+        let all_correct = vec![CalibrationSample::new(0.9, true); 30];
+        assert!(Calibrator::fit(CalibrationMethod::Isotonic, &all_correct).is_err());
+    }
+
+    #[test]
+    fn test_platt_reduces_calibration_error() {
+        let samples = overconfident_samples();
+        let calibrator = Calibrator::fit(CalibrationMethod::Platt, &samples).unwrap();
+
+        let raw = reliability_curve(&samples, &Calibrator::Identity, 10);
+        let calibrated = reliability_curve(&samples, &calibrator, 10);
+        assert!(calibrated.expected_calibration_error < raw.expected_calibration_error);
+
+        // Monotone and pulled down towards observed accuracy
+        assert!(calibrator.apply(0.6) < calibrator.apply(0.9));
+        assert!((calibrator.apply(0.9) - 0.6).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_isotonic_is_monotone() {
+        let samples = overconfident_samples();
+        let calibrator = Calibrator::fit(CalibrationMethod::Isotonic, &samples).unwrap();
+
+        let values: Vec<f32> = (0..=20)
+            .map(|i| calibrator.apply(i as f32 / 20.0))
+            .collect();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        assert!((calibrator.apply(0.75) - 0.45).abs() < 0.1);
+        assert_eq!(calibrator.apply(0.0), calibrator.apply(0.5));
+    }
+
+    #[test]
+    fn test_reliability_curve_bins() {
+        let samples = vec![
+            CalibrationSample::new(0.95, true),
+            CalibrationSample::new(0.95, false),
+            CalibrationSample::new(0.15, false),
+        ];
+        let curve = reliability_curve(&samples, &Calibrator::Identity, 10);
+
+        assert_eq!(curve.bins.len(), 10);
+        assert_eq!(curve.bins[9].count, 2);
+        assert_eq!(curve.bins[9].accuracy, 0.5);
+        assert_eq!(curve.bins[1].count, 1);
+        assert!((curve.expected_calibration_error - 0.35).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_calibrator_serde() {
+        let calibrator = Calibrator::Platt { a: 2.0, b: -1.0 };
+        let json = serde_json::to_value(&calibrator).unwrap();
+        assert_eq!(json["method"], "platt");
+        assert_eq!(
+            serde_json::from_value::<Calibrator>(json).unwrap(),
+            calibrator
+        );
+        assert_eq!(
+            "Isotonic".parse::<CalibrationMethod>().unwrap(),
+            CalibrationMethod::Isotonic
+        );
+    }
+}
@@ -23,6 +23,22 @@ pub struct AppConfig {
 
     /// Logging configuration
     pub logging: LoggingConfig,
+
+    /// Object storage for original documents
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    /// At-rest encryption of Restricted documents
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+
+    /// Parser and extractor plugins
+    #[serde(default)]
+    pub plugins: PluginConfig,
+
+    /// OCR of scanned documents
+    #[serde(default)]
+    pub ocr: OcrConfig,
 }
 
 impl AppConfig {
@@ -61,6 +77,21 @@ impl AppConfig {
         if let Ok(url) = std::env::var("QDRANT_URL") {
             config.database.qdrant_url = url;
         }
+        if let Ok(quantization) = std::env::var("VECTOR_QUANTIZATION") {
+            config.database.vector_quantization = quantization.parse()?;
+        }
+        if let Ok(dim) = std::env::var("VECTOR_TRUNCATE_DIM") {
+            config.database.vector_truncate_dim = match dim.parse::<usize>() {
+                Ok(0) => None,
+                Ok(dim) => Some(dim),
+                Err(_) => {
+                    return Err(ConfigError::InvalidValue {
+                        key: "VECTOR_TRUNCATE_DIM".to_string(),
+                        value: dim,
+                    })
+                }
+            };
+        }
 
         // LLM
         if let Ok(provider) = std::env::var("LLM_PROVIDER") {
@@ -72,6 +103,23 @@ impl AppConfig {
         if let Ok(url) = std::env::var("OLLAMA_URL") {
             config.llm.ollama_url = url;
         }
+        if let Ok(url) = std::env::var("VLLM_URL") {
+            config.llm.vllm_url = url;
+        }
+        if let Ok(key) = std::env::var("VLLM_API_KEY") {
+            config.llm.vllm_api_key = Some(key);
+        }
+        if let Ok(size) = std::env::var("LLM_BATCH_SIZE") {
+            config.llm.batch_size = match size.parse::<usize>() {
+                Ok(size) if size > 0 => size,
+                _ => {
+                    return Err(ConfigError::InvalidValue {
+                        key: "LLM_BATCH_SIZE".to_string(),
+                        value: size,
+                    })
+                }
+            };
+        }
         if let Ok(model) = std::env::var("LLM_MODEL") {
             config.llm.model = model;
         }
@@ -92,6 +140,81 @@ impl AppConfig {
         if let Ok(level) = std::env::var("LOG_LEVEL") {
             config.logging.level = level;
         }
+        if let Ok(format) = std::env::var("LOG_FORMAT") {
+            config.logging.json_format = match format.trim().to_lowercase().as_str() {
+                "json" => true,
+                "text" | "" => false,
+                _ => {
+                    return Err(ConfigError::InvalidValue {
+                        key: "LOG_FORMAT".to_string(),
+                        value: format,
+                    })
+                }
+            };
+        }
+        if let Ok(location) = std::env::var("LOG_INCLUDE_LOCATION") {
+            config.logging.include_location =
+                location.parse().map_err(|_| ConfigError::InvalidValue {
+                    key: "LOG_INCLUDE_LOCATION".to_string(),
+                    value: location,
+                })?;
+        }
+        if let Ok(tenant) = std::env::var("LOG_TENANT") {
+            config.logging.tenant = Some(tenant).filter(|t| !t.trim().is_empty());
+        }
+
+        // Object storage
+        if let Ok(backend) = std::env::var("BLOB_STORE") {
+            config.storage.backend = backend.parse()?;
+        }
+        if let Ok(path) = std::env::var("BLOB_STORE_PATH") {
+            config.storage.path = path.into();
+        }
+        config.storage.s3.apply_env()?;
+
+        // Encryption
+        if let Ok(key) = std::env::var("ENCRYPTION_MASTER_KEY") {
+            config.encryption.master_key = Some(key);
+        }
+        if let Ok(id) = std::env::var("ENCRYPTION_MASTER_KEY_ID") {
+            config.encryption.master_key_id = id;
+        }
+
+        // Plugins
+        if let Ok(dir) = std::env::var("PLUGIN_DIR") {
+            config.plugins.dir = Some(dir.into());
+        }
+
+        // OCR
+        if let Ok(tables) = std::env::var("OCR_TABLES") {
+            config.ocr.tables = tables.parse().map_err(|_| ConfigError::InvalidValue {
+                key: "OCR_TABLES".to_string(),
+                value: tables,
+            })?;
+        }
+        if let Ok(url) = std::env::var("HTR_URL") {
+            config.ocr.htr_url = Some(url);
+        }
+        if let Ok(key) = std::env::var("HTR_API_KEY") {
+            config.ocr.htr_api_key = Some(key);
+        }
+        if let Ok(secs) = std::env::var("HTR_TIMEOUT_SECS") {
+            config.ocr.htr_timeout_secs = secs.parse().map_err(|_| ConfigError::InvalidValue {
+                key: "HTR_TIMEOUT_SECS".to_string(),
+                value: secs,
+            })?;
+        }
+        if let Ok(threshold) = std::env::var("HTR_CONFIDENCE_THRESHOLD") {
+            config.ocr.handwriting_below = match threshold.parse::<f32>() {
+                Ok(t) if (0.0..=1.0).contains(&t) => t,
+                _ => {
+                    return Err(ConfigError::InvalidValue {
+                        key: "HTR_CONFIDENCE_THRESHOLD".to_string(),
+                        value: threshold,
+                    })
+                }
+            };
+        }
 
         Ok(config)
     }
@@ -199,6 +322,16 @@ pub struct DatabaseConfig {
 
     /// Vector dimension (must match embedding model)
     pub vector_dimension: usize,
+
+    /// Quantization of stored vectors (applied when a collection is created
+    /// or initialized)
+    #[serde(default)]
+    pub vector_quantization: VectorQuantization,
+
+    /// Keep only the first N dimensions of each embedding (Matryoshka
+    /// models), renormalized; `None` stores full vectors
+    #[serde(default)]
+    pub vector_truncate_dim: Option<usize>,
 }
 
 impl Default for DatabaseConfig {
@@ -214,6 +347,86 @@ impl Default for DatabaseConfig {
             qdrant_url: "http://localhost:6334".to_string(),
             qdrant_collection: "otl_chunks".to_string(),
             vector_dimension: 1536, // OpenAI text-embedding-3-small
+            vector_quantization: VectorQuantization::None,
+            vector_truncate_dim: None,
+        }
+    }
+}
+
+/// In-memory compression of stored vectors
+///
+/// Quantized vectors are searched first and the best candidates rescored
+/// with the original vectors, which Qdrant keeps on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VectorQuantization {
+    /// Full `f32` vectors
+    #[default]
+    None,
+    /// One `i8` per dimension (4x smaller)
+    Scalar,
+    /// Product quantization, `compression` times smaller (4, 8, 16, 32 or 64)
+    Product { compression: u32 },
+}
+
+impl VectorQuantization {
+    /// Compression ratios supported by product quantization
+    pub const PRODUCT_COMPRESSIONS: [u32; 5] = [4, 8, 16, 32, 64];
+
+    /// Compression used by `product` without a ratio
+    pub const DEFAULT_PRODUCT_COMPRESSION: u32 = 16;
+
+    /// In-memory bytes of one quantized vector of `dimension`
+    pub fn bytes_per_vector(&self, dimension: usize) -> usize {
+        match self {
+            Self::None => dimension * 4,
+            Self::Scalar => dimension,
+            Self::Product { compression } => (dimension * 4).div_ceil(*compression as usize),
+        }
+    }
+}
+
+impl std::fmt::Display for VectorQuantization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Scalar => write!(f, "scalar"),
+            Self::Product { compression } => write!(f, "product:x{compression}"),
+        }
+    }
+}
+
+impl std::str::FromStr for VectorQuantization {
+    type Err = ConfigError;
+
+    /// Parse `none`, `scalar`, `product` or `product:x16`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ConfigError::InvalidValue {
+            key: "VECTOR_QUANTIZATION".to_string(),
+            value: s.to_string(),
+        };
+        let value = s.trim().to_lowercase();
+        match value.split_once(':') {
+            None => match value.as_str() {
+                "none" | "" => Ok(Self::None),
+                "scalar" | "int8" => Ok(Self::Scalar),
+                "product" | "pq" => Ok(Self::Product {
+                    compression: Self::DEFAULT_PRODUCT_COMPRESSION,
+                }),
+                _ => Err(invalid()),
+            },
+            Some(("product" | "pq", ratio)) => {
+                let compression: u32 = ratio
+                    .trim_start_matches('x')
+                    .parse()
+                    .map_err(|_| invalid())?;
+                if Self::PRODUCT_COMPRESSIONS.contains(&compression) {
+                    Ok(Self::Product { compression })
+                } else {
+                    Err(invalid())
+                }
+            }
+            Some(_) => Err(invalid()),
         }
     }
 }
@@ -233,6 +446,14 @@ pub struct LlmConfig {
     /// Ollama server URL
     pub ollama_url: String,
 
+    /// Base URL of the vLLM (or other OpenAI-compatible) server
+    #[serde(default = "default_vllm_url")]
+    pub vllm_url: String,
+
+    /// API key of the vLLM server, if it requires one
+    #[serde(default)]
+    pub vllm_api_key: Option<String>,
+
     /// Model name to use
     pub model: String,
 
@@ -247,6 +468,18 @@ pub struct LlmConfig {
 
     /// Request timeout in seconds
     pub timeout_secs: u64,
+
+    /// Requests in flight at once for batched generation
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+}
+
+fn default_vllm_url() -> String {
+    "http://localhost:8000/v1".to_string()
+}
+
+fn default_batch_size() -> usize {
+    8
 }
 
 impl Default for LlmConfig {
@@ -256,11 +489,14 @@ impl Default for LlmConfig {
             openai_api_key: None,
             openai_base_url: None,
             ollama_url: "http://localhost:11434".to_string(),
+            vllm_url: default_vllm_url(),
+            vllm_api_key: None,
             model: "gpt-4o-mini".to_string(),
             embedding_model: "text-embedding-3-small".to_string(),
             max_tokens: 2048,
             temperature: 0.1,
             timeout_secs: 60,
+            batch_size: default_batch_size(),
         }
     }
 }
@@ -272,6 +508,8 @@ pub enum LlmProvider {
     OpenAI,
     Ollama,
     Azure,
+    /// vLLM or another server with an OpenAI-compatible API
+    Vllm,
 }
 
 impl std::str::FromStr for LlmProvider {
@@ -282,6 +520,7 @@ impl std::str::FromStr for LlmProvider {
             "openai" => Ok(Self::OpenAI),
             "ollama" => Ok(Self::Ollama),
             "azure" => Ok(Self::Azure),
+            "vllm" => Ok(Self::Vllm),
             _ => Err(ConfigError::InvalidValue {
                 key: "LLM_PROVIDER".to_string(),
                 value: s.to_string(),
@@ -352,6 +591,10 @@ pub struct LoggingConfig {
 
     /// Include file/line in logs
     pub include_location: bool,
+
+    /// Tenant logged with requests that name none (`x-tenant-id` header)
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
 impl Default for LoggingConfig {
@@ -360,6 +603,262 @@ impl Default for LoggingConfig {
             level: "info".to_string(),
             json_format: false,
             include_location: false,
+            tenant: None,
+        }
+    }
+}
+
+/// Object storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// Where blobs are kept
+    #[serde(default)]
+    pub backend: BlobBackend,
+
+    /// Root directory of the filesystem backend
+    #[serde(default = "default_blob_path")]
+    pub path: PathBuf,
+
+    /// Bucket settings of the S3 backend
+    #[serde(default)]
+    pub s3: S3Config,
+}
+
+fn default_blob_path() -> PathBuf {
+    PathBuf::from("./data/blobs")
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: BlobBackend::Filesystem,
+            path: default_blob_path(),
+            s3: S3Config::default(),
+        }
+    }
+}
+
+/// Blob store backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlobBackend {
+    /// Files in a local directory
+    #[default]
+    Filesystem,
+    /// S3 or an S3-compatible server such as MinIO
+    S3,
+}
+
+impl std::str::FromStr for BlobBackend {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fs" | "filesystem" | "local" => Ok(Self::Filesystem),
+            "s3" | "minio" => Ok(Self::S3),
+            _ => Err(ConfigError::InvalidValue {
+                key: "BLOB_STORE".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// S3 / MinIO bucket configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct S3Config {
+    /// Endpoint URL (`None` uses AWS S3 in `region`)
+    pub endpoint: Option<String>,
+
+    /// Bucket holding the blobs
+    pub bucket: String,
+
+    /// Region requests are signed for
+    pub region: String,
+
+    /// Access key ID
+    pub access_key_id: Option<String>,
+
+    /// Secret access key
+    pub secret_access_key: Option<String>,
+
+    /// Address the bucket in the path instead of the host name (MinIO)
+    pub path_style: bool,
+
+    /// Encryption of stored objects
+    pub server_side_encryption: ServerSideEncryption,
+
+    /// Bodies larger than this are uploaded in parts of this size (at least
+    /// 5 MiB)
+    pub part_size: usize,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            bucket: "otl-documents".to_string(),
+            region: "us-east-1".to_string(),
+            access_key_id: None,
+            secret_access_key: None,
+            path_style: false,
+            server_side_encryption: ServerSideEncryption::None,
+            part_size: 16 * 1024 * 1024, // 16MB
+        }
+    }
+}
+
+impl S3Config {
+    /// Override settings from `S3_*` environment variables
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        if let Ok(endpoint) = std::env::var("S3_ENDPOINT") {
+            self.endpoint = Some(endpoint);
+        }
+        if let Ok(bucket) = std::env::var("S3_BUCKET") {
+            self.bucket = bucket;
+        }
+        if let Ok(region) = std::env::var("S3_REGION") {
+            self.region = region;
+        }
+        if let Ok(key) = std::env::var("S3_ACCESS_KEY_ID") {
+            self.access_key_id = Some(key);
+        }
+        if let Ok(secret) = std::env::var("S3_SECRET_ACCESS_KEY") {
+            self.secret_access_key = Some(secret);
+        }
+        if let Ok(path_style) = std::env::var("S3_PATH_STYLE") {
+            self.path_style = path_style.parse().map_err(|_| ConfigError::InvalidValue {
+                key: "S3_PATH_STYLE".to_string(),
+                value: path_style,
+            })?;
+        }
+        if let Ok(sse) = std::env::var("S3_SSE") {
+            self.server_side_encryption = sse.parse()?;
+        }
+        if let Ok(key_id) = std::env::var("S3_SSE_KMS_KEY_ID") {
+            if let ServerSideEncryption::Kms { key_id: id } = &mut self.server_side_encryption {
+                *id = Some(key_id);
+            }
+        }
+        if let Ok(size) = std::env::var("S3_PART_SIZE_MB") {
+            self.part_size = match size.parse::<usize>() {
+                Ok(mb) if mb >= 5 => mb * 1024 * 1024,
+                _ => {
+                    return Err(ConfigError::InvalidValue {
+                        key: "S3_PART_SIZE_MB".to_string(),
+                        value: size,
+                    })
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Server-side encryption of stored objects
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerSideEncryption {
+    /// Bucket default
+    #[default]
+    None,
+    /// Keys managed by the storage server (SSE-S3)
+    Aes256,
+    /// Keys in AWS KMS (SSE-KMS); `None` uses the account's default key
+    Kms { key_id: Option<String> },
+}
+
+impl std::str::FromStr for ServerSideEncryption {
+    type Err = ConfigError;
+
+    /// Parse `none`, `aes256` or `kms`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "" => Ok(Self::None),
+            "aes256" | "sse-s3" => Ok(Self::Aes256),
+            "kms" | "aws:kms" | "sse-kms" => Ok(Self::Kms { key_id: None }),
+            _ => Err(ConfigError::InvalidValue {
+                key: "S3_SSE".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// At-rest encryption configuration
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// Master key wrapping per-document data keys: 32 bytes, base64
+    /// encoded. Restricted documents cannot be stored without one.
+    #[serde(skip_serializing)]
+    pub master_key: Option<String>,
+
+    /// ID recorded with every data key the master key wraps
+    pub master_key_id: String,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            master_key: None,
+            master_key_id: "local".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Debug for EncryptionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionConfig")
+            .field(
+                "master_key",
+                &self.master_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field("master_key_id", &self.master_key_id)
+            .finish()
+    }
+}
+
+/// Plugin configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginConfig {
+    /// Directory of WASM plugin modules (`*.wasm`); native plugins are
+    /// linked into the binary and need no configuration
+    pub dir: Option<PathBuf>,
+}
+
+/// OCR configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrConfig {
+    /// Recover the structure of tables on scanned pages
+    pub tables: bool,
+
+    /// Handwritten text recognition (HTR) service; without it handwriting
+    /// is read by Tesseract like print
+    pub htr_url: Option<String>,
+
+    /// Bearer token for the HTR service
+    pub htr_api_key: Option<String>,
+
+    /// Timeout of one HTR request
+    pub htr_timeout_secs: u64,
+
+    /// Tesseract confidence (0.0 - 1.0) below which a text block is taken
+    /// to be handwritten and sent to the HTR service
+    pub handwriting_below: f32,
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            tables: false,
+            htr_url: None,
+            htr_api_key: None,
+            htr_timeout_secs: 30,
+            handwriting_below: 0.5,
         }
     }
 }
@@ -405,6 +904,59 @@ mod tests {
             "ollama".parse::<LlmProvider>().unwrap(),
             LlmProvider::Ollama
         );
+        assert_eq!("vLLM".parse::<LlmProvider>().unwrap(), LlmProvider::Vllm);
         assert!("invalid".parse::<LlmProvider>().is_err());
     }
+
+    #[test]
+    fn test_vector_quantization_parse() {
+        assert_eq!(
+            "none".parse::<VectorQuantization>().unwrap(),
+            VectorQuantization::None
+        );
+        assert_eq!(
+            "Scalar".parse::<VectorQuantization>().unwrap(),
+            VectorQuantization::Scalar
+        );
+        assert_eq!(
+            "product".parse::<VectorQuantization>().unwrap(),
+            VectorQuantization::Product { compression: 16 }
+        );
+        assert_eq!(
+            "product:x32".parse::<VectorQuantization>().unwrap(),
+            VectorQuantization::Product { compression: 32 }
+        );
+        assert!("product:x3".parse::<VectorQuantization>().is_err());
+        assert!("binary".parse::<VectorQuantization>().is_err());
+
+        let pq = VectorQuantization::Product { compression: 16 };
+        assert_eq!(pq.to_string(), "product:x16");
+        assert_eq!(pq.bytes_per_vector(1536), 384);
+        assert_eq!(VectorQuantization::Scalar.bytes_per_vector(1536), 1536);
+    }
+
+    #[test]
+    fn test_storage_config_parse() {
+        assert_eq!("MinIO".parse::<BlobBackend>().unwrap(), BlobBackend::S3);
+        assert_eq!(
+            "fs".parse::<BlobBackend>().unwrap(),
+            BlobBackend::Filesystem
+        );
+        assert!("gcs".parse::<BlobBackend>().is_err());
+        assert_eq!(
+            "AES256".parse::<ServerSideEncryption>().unwrap(),
+            ServerSideEncryption::Aes256
+        );
+        assert_eq!(
+            "aws:kms".parse::<ServerSideEncryption>().unwrap(),
+            ServerSideEncryption::Kms { key_id: None }
+        );
+        assert!("sse-c".parse::<ServerSideEncryption>().is_err());
+
+        let storage: StorageConfig =
+            toml::from_str("backend = \"s3\"\n[s3]\nbucket = \"hr\"").unwrap();
+        assert_eq!(storage.path, PathBuf::from("./data/blobs"));
+        assert_eq!(storage.s3.bucket, "hr");
+        assert_eq!(storage.s3.region, "us-east-1");
+    }
 }
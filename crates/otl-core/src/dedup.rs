@@ -0,0 +1,214 @@
+//! Near-duplicate chunk detection
+//!
+//! Boilerplate headers and repeated legal clauses show up in many
+//! documents. Each chunk gets a 64-bit SimHash of the character shingles of
+//! its normalized text; chunks whose fingerprints differ in at most a few
+//! bits are near-duplicates. The fingerprint is split into four 16-bit
+//! bands: two fingerprints within three bits of each other share at least
+//! one band exactly, so candidates can be found with an equality lookup per
+//! band before the Hamming distance is checked.
+//!
+//! Author: hephaex@gmail.com
+
+/// Characters per shingle
+const SHINGLE_CHARS: usize = 4;
+
+/// Bands a fingerprint is split into for candidate lookup
+pub const BANDS: usize = 4;
+
+/// Default largest Hamming distance between near-duplicates
+///
+/// Must stay below [`BANDS`] for the band lookup to find every candidate.
+pub const DEFAULT_MAX_DISTANCE: u32 = 3;
+
+/// Default shortest normalized text that gets a fingerprint
+pub const DEFAULT_MIN_CHARS: usize = 80;
+
+/// Lowercase the text and reduce every run of characters that are not
+/// letters or digits to one space
+pub fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pending_space = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            if pending_space && !out.is_empty() {
+                out.push(' ');
+            }
+            pending_space = false;
+            out.extend(c.to_lowercase());
+        } else {
+            pending_space = true;
+        }
+    }
+    out
+}
+
+/// 64-bit FNV-1a, stable across builds and platforms
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// SimHash of the character shingles of already normalized text
+pub fn simhash(normalized: &str) -> u64 {
+    let chars: Vec<char> = normalized.chars().collect();
+    let mut weights = [0i64; 64];
+    let mut add = |shingle: &[char]| {
+        let shingle: String = shingle.iter().collect();
+        let hash = fnv1a(shingle.as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if (hash >> bit) & 1 == 1 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    };
+    if chars.len() <= SHINGLE_CHARS {
+        add(&chars);
+    } else {
+        chars.windows(SHINGLE_CHARS).for_each(&mut add);
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0, |hash, (bit, _)| hash | (1 << bit))
+}
+
+/// Fingerprint of a chunk, `None` if its normalized text is shorter than
+/// `min_chars` (too short to call anything a duplicate of it)
+pub fn fingerprint(text: &str, min_chars: usize) -> Option<u64> {
+    let normalized = normalize(text);
+    (normalized.chars().count() >= min_chars).then(|| simhash(&normalized))
+}
+
+/// Number of differing bits
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// The 16-bit bands of a fingerprint, most significant first
+pub fn bands(fingerprint: u64) -> [u16; BANDS] {
+    let mut bands = [0; BANDS];
+    for (i, band) in bands.iter_mut().enumerate() {
+        *band = (fingerprint >> (48 - 16 * i)) as u16;
+    }
+    bands
+}
+
+/// Fingerprint as stored in a signed 64-bit database column
+pub fn to_stored(fingerprint: u64) -> i64 {
+    fingerprint as i64
+}
+
+/// Fingerprint from a signed 64-bit database column
+pub fn from_stored(stored: i64) -> u64 {
+    stored as u64
+}
+
+/// Index of the first earlier chunk each chunk near-duplicates, within one
+/// document
+///
+/// `None` entries have no fingerprint and never match.
+pub fn first_duplicates(fingerprints: &[Option<u64>], max_distance: u32) -> Vec<Option<usize>> {
+    fingerprints
+        .iter()
+        .enumerate()
+        .map(|(i, fingerprint)| {
+            let fingerprint = (*fingerprint)?;
+            fingerprints[..i].iter().position(|earlier| {
+                earlier
+                    .is_some_and(|earlier| hamming_distance(earlier, fingerprint) <= max_distance)
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAUSES: &str = "제5조 (비밀유지) 직원은 재직 중은 물론 퇴직 후에도 업무상 알게 된 \
+        회사의 기밀을 누설하여서는 아니 되며, 이를 위반한 경우 관련 법령 및 취업규칙에 따라 \
+        징계 및 손해배상 책임을 진다. 제6조 (겸직금지) 직원은 회사의 허가 없이 다른 직무를 \
+        겸하거나 영리를 목적으로 하는 업무에 종사하여서는 아니 된다. 제7조 (출근) 직원은 \
+        근무시간 시작 전까지 출근하여 업무를 준비하여야 하며, 지각하는 경우 사전에 소속 \
+        부서장에게 알려야 한다. 제8조 (퇴근) 직원은 근무시간이 끝난 후 업무를 정리하고 \
+        퇴근한다. 다만 업무상 필요한 경우 부서장의 지시에 따라 연장근로를 할 수 있다. \
+        제9조 (결근) 직원이 질병이나 그 밖의 부득이한 사유로 결근하고자 할 때에는 사전에 \
+        부서장의 승인을 받아야 한다. 제10조 (휴게) 회사는 근로시간이 4시간인 경우 30분 \
+        이상, 8시간인 경우 1시간 이상의 휴게시간을 근로시간 도중에 주어야 한다.";
+
+    #[test]
+    fn test_normalize_ignores_case_punctuation_and_spacing() {
+        assert_eq!(
+            normalize("  제5조 (비밀유지)\n\n직원은…  Rules! "),
+            "제5조 비밀유지 직원은 rules"
+        );
+        assert_eq!(normalize("---"), "");
+    }
+
+    #[test]
+    fn test_reformatted_text_has_the_same_fingerprint() {
+        let reflowed = CLAUSES.replace(' ', "\n  ").replace('(', "[ ");
+        assert_eq!(fingerprint(CLAUSES, 10), fingerprint(&reflowed, 10));
+    }
+
+    #[test]
+    fn test_small_edits_stay_within_the_distance() {
+        let a = fingerprint(CLAUSES, DEFAULT_MIN_CHARS).unwrap();
+        for edited in [
+            CLAUSES.replace("제5조", "제15조"),
+            CLAUSES.replace("회사의 기밀", "당사의 기밀"),
+            CLAUSES
+                .replace("30분", "40분")
+                .replace("부득이한", "불가피한"),
+        ] {
+            let b = fingerprint(&edited, DEFAULT_MIN_CHARS).unwrap();
+            assert!(hamming_distance(a, b) <= DEFAULT_MAX_DISTANCE);
+        }
+
+        let reworded = CLAUSES
+            .replace("직원", "근로자")
+            .replace("회사", "사업주")
+            .replace("부서장", "관리자");
+        let b = fingerprint(&reworded, DEFAULT_MIN_CHARS).unwrap();
+        assert!(hamming_distance(a, b) > DEFAULT_MAX_DISTANCE);
+    }
+
+    #[test]
+    fn test_short_text_has_no_fingerprint() {
+        assert_eq!(fingerprint("별첨 1. 서식", DEFAULT_MIN_CHARS), None);
+        assert!(fingerprint(CLAUSES, DEFAULT_MIN_CHARS).is_some());
+    }
+
+    #[test]
+    fn test_close_fingerprints_share_a_band() {
+        let a: u64 = 0x1234_5678_9abc_def0;
+        let b = a ^ (1 << 3) ^ (1 << 20) ^ (1 << 40);
+        assert_eq!(hamming_distance(a, b), 3);
+        assert!(bands(a).iter().zip(bands(b)).any(|(x, y)| *x == y));
+        assert_eq!(bands(a), [0x1234, 0x5678, 0x9abc, 0xdef0]);
+        assert_eq!(from_stored(to_stored(u64::MAX)), u64::MAX);
+    }
+
+    #[test]
+    fn test_first_duplicates_points_at_the_earliest_copy() {
+        let a = Some(0b1111);
+        let near_a = Some(0b0111);
+        let far = Some(u64::MAX);
+        assert_eq!(
+            first_duplicates(&[a, far, near_a, None, a], 3),
+            vec![None, None, Some(0), None, Some(0)]
+        );
+    }
+}
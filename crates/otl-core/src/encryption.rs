@@ -0,0 +1,466 @@
+//! Envelope encryption of Restricted content
+//!
+//! Each Restricted document gets its own random data key. Chunk text and
+//! the stored original are sealed with it (AES-256-GCM), and the data key
+//! itself is stored only wrapped by a key-encryption key: the master key
+//! from configuration, or another [`KeyEncryptionKey`] such as a KMS.
+//! Rotating the master key therefore means re-wrapping data keys, not
+//! re-encrypting content.
+//!
+//! Sealed values carry the context they were sealed for (document, chunk
+//! or blob key) as associated data, so ciphertext moved to another chunk
+//! or document fails to open.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::config::EncryptionConfig;
+use crate::{AccessLevel, OtlError, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use std::sync::Arc;
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// Algorithm of data keys and wrapped keys
+pub const ALGORITHM: &str = "AES-256-GCM";
+
+/// Prefix of sealed text
+const TEXT_PREFIX: &str = "enc:v1:";
+
+/// Header of sealed blobs
+const BLOB_HEADER: &[u8] = b"OTLENC1\n";
+
+const KEY_LEN: usize = 32;
+
+/// Whether text was sealed by [`DataKey::seal_text`]
+pub fn is_sealed_text(text: &str) -> bool {
+    text.starts_with(TEXT_PREFIX)
+}
+
+/// Whether a blob was sealed by [`DataKey::seal_blob`]
+pub fn is_sealed_blob(bytes: &[u8]) -> bool {
+    bytes.starts_with(BLOB_HEADER)
+}
+
+/// Associated data of a chunk's text
+pub fn chunk_context(document_id: Uuid, chunk_index: u32) -> String {
+    format!("chunk:{document_id}/{chunk_index}")
+}
+
+/// Per-document key encrypting content
+pub struct DataKey([u8; KEY_LEN]);
+
+impl DataKey {
+    /// New random key
+    pub fn generate() -> Result<Self> {
+        let mut key = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| encryption_error("No randomness for a data key"))?;
+        Ok(Self(key))
+    }
+
+    fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let key: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| encryption_error("Data key has the wrong length"))?;
+        Ok(Self(key))
+    }
+
+    /// Nonce followed by ciphertext and tag
+    pub fn seal(&self, plaintext: &[u8], context: &str) -> Result<Vec<u8>> {
+        seal(&self.0, plaintext, context)
+    }
+
+    /// Open what [`seal`](Self::seal) produced for the same context
+    pub fn open(&self, sealed: &[u8], context: &str) -> Result<Vec<u8>> {
+        open(&self.0, sealed, context)
+    }
+
+    /// Seal text for a text column
+    pub fn seal_text(&self, text: &str, context: &str) -> Result<String> {
+        let sealed = self.seal(text.as_bytes(), context)?;
+        Ok(format!(
+            "{TEXT_PREFIX}{}",
+            base64::engine::general_purpose::STANDARD.encode(sealed)
+        ))
+    }
+
+    /// Open sealed text; text that is not sealed is returned as is
+    pub fn open_text(&self, text: &str, context: &str) -> Result<String> {
+        let Some(encoded) = text.strip_prefix(TEXT_PREFIX) else {
+            return Ok(text.to_string());
+        };
+        let sealed = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| encryption_error(format!("Sealed text is not base64: {e}")))?;
+        String::from_utf8(self.open(&sealed, context)?)
+            .map_err(|e| encryption_error(format!("Opened text is not UTF-8: {e}")))
+    }
+
+    /// Seal a blob, marking it with a header
+    pub fn seal_blob(&self, bytes: &[u8], context: &str) -> Result<Vec<u8>> {
+        let mut sealed = BLOB_HEADER.to_vec();
+        sealed.extend(self.seal(bytes, context)?);
+        Ok(sealed)
+    }
+
+    /// Open a sealed blob; a blob without the header is returned as is
+    pub fn open_blob(&self, bytes: Vec<u8>, context: &str) -> Result<Vec<u8>> {
+        match bytes.strip_prefix(BLOB_HEADER) {
+            Some(sealed) => self.open(sealed, context),
+            None => Ok(bytes),
+        }
+    }
+}
+
+impl Drop for DataKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl std::fmt::Debug for DataKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DataKey(..)")
+    }
+}
+
+/// Data key encrypted by a key-encryption key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrappedKey {
+    /// ID of the key-encryption key that wrapped it
+    pub key_id: String,
+    /// Sealed data key, base64 encoded
+    pub ciphertext: String,
+}
+
+/// Key protecting data keys (master key, KMS key)
+#[async_trait]
+pub trait KeyEncryptionKey: Send + Sync {
+    /// ID recorded with every key it wraps
+    fn key_id(&self) -> &str;
+
+    /// Wrap a data key for `context` (the document it belongs to)
+    async fn wrap(&self, key: &DataKey, context: &str) -> Result<WrappedKey>;
+
+    /// Unwrap a data key wrapped for `context`
+    async fn unwrap(&self, wrapped: &WrappedKey, context: &str) -> Result<DataKey>;
+}
+
+/// Key-encryption key held in configuration
+pub struct MasterKey {
+    id: String,
+    key: [u8; KEY_LEN],
+}
+
+impl MasterKey {
+    /// Master key from 32 base64-encoded bytes
+    pub fn from_base64(id: impl Into<String>, encoded: &str) -> Result<Self> {
+        let mut bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| OtlError::ConfigError(format!("Master key is not base64: {e}")))?;
+        let key = bytes.as_slice().try_into().map_err(|_| {
+            OtlError::ConfigError(format!(
+                "Master key must be {KEY_LEN} bytes, got {}",
+                bytes.len()
+            ))
+        });
+        bytes.zeroize();
+        Ok(Self {
+            id: id.into(),
+            key: key?,
+        })
+    }
+}
+
+impl Drop for MasterKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+#[async_trait]
+impl KeyEncryptionKey for MasterKey {
+    fn key_id(&self) -> &str {
+        &self.id
+    }
+
+    async fn wrap(&self, key: &DataKey, context: &str) -> Result<WrappedKey> {
+        let sealed = seal(&self.key, &key.0, context)?;
+        Ok(WrappedKey {
+            key_id: self.id.clone(),
+            ciphertext: base64::engine::general_purpose::STANDARD.encode(sealed),
+        })
+    }
+
+    async fn unwrap(&self, wrapped: &WrappedKey, context: &str) -> Result<DataKey> {
+        if wrapped.key_id != self.id {
+            return Err(encryption_error(format!(
+                "Data key was wrapped by key '{}', not '{}'",
+                wrapped.key_id, self.id
+            )));
+        }
+        let sealed = base64::engine::general_purpose::STANDARD
+            .decode(&wrapped.ciphertext)
+            .map_err(|e| encryption_error(format!("Wrapped key is not base64: {e}")))?;
+        let mut bytes = open(&self.key, &sealed, context)?;
+        let key = DataKey::from_slice(&bytes);
+        bytes.zeroize();
+        key
+    }
+}
+
+// ============================================================================
+// Keyring
+// ============================================================================
+
+/// Data keys of documents, kept wrapped in `document_keys`
+///
+/// A document has a data key if and only if its content is encrypted: keys
+/// are created for Restricted documents when their content is first
+/// written, and kept if the document is later reclassified so that its
+/// sealed content stays readable.
+pub struct Keyring {
+    kek: Option<Arc<dyn KeyEncryptionKey>>,
+}
+
+impl Keyring {
+    pub fn new(kek: Option<Arc<dyn KeyEncryptionKey>>) -> Self {
+        Self { kek }
+    }
+
+    /// Keyring wrapping data keys with the configured master key
+    pub fn from_config(config: &EncryptionConfig) -> Result<Self> {
+        let kek = match &config.master_key {
+            Some(key) => Some(
+                Arc::new(MasterKey::from_base64(&config.master_key_id, key)?)
+                    as Arc<dyn KeyEncryptionKey>,
+            ),
+            None => None,
+        };
+        Ok(Self::new(kek))
+    }
+
+    /// Whether a key-encryption key is configured
+    pub fn is_enabled(&self) -> bool {
+        self.kek.is_some()
+    }
+
+    fn kek(&self) -> Result<&dyn KeyEncryptionKey> {
+        self.kek
+            .as_deref()
+            .ok_or_else(|| encryption_error("No master key configured"))
+    }
+
+    /// Data key of a document, or `None` if its content is not encrypted
+    pub async fn document_key<'e>(
+        &self,
+        executor: impl PgExecutor<'e>,
+        document_id: Uuid,
+    ) -> Result<Option<DataKey>> {
+        let row: Option<(String, String)> =
+            sqlx::query_as("SELECT key_id, wrapped_key FROM document_keys WHERE document_id = $1")
+                .bind(document_id)
+                .fetch_optional(executor)
+                .await
+                .map_err(|e| OtlError::DatabaseError(format!("Failed to fetch data key: {e}")))?;
+        match row {
+            Some((key_id, ciphertext)) => {
+                let wrapped = WrappedKey { key_id, ciphertext };
+                Ok(Some(
+                    self.kek()?
+                        .unwrap(&wrapped, &document_id.to_string())
+                        .await?,
+                ))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Data key of a document, created if it has none
+    pub async fn create_key<'e>(
+        &self,
+        executor: impl PgExecutor<'e>,
+        document_id: Uuid,
+    ) -> Result<DataKey> {
+        let kek = self.kek()?;
+        let context = document_id.to_string();
+        let wrapped = kek.wrap(&DataKey::generate()?, &context).await?;
+        // A concurrent writer may have created one first; use whichever won
+        let (key_id, ciphertext): (String, String) = sqlx::query_as(
+            r#"
+            INSERT INTO document_keys (document_id, key_id, algorithm, wrapped_key)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (document_id) DO UPDATE SET document_id = EXCLUDED.document_id
+            RETURNING key_id, wrapped_key
+            "#,
+        )
+        .bind(document_id)
+        .bind(&wrapped.key_id)
+        .bind(ALGORITHM)
+        .bind(&wrapped.ciphertext)
+        .fetch_one(executor)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to store data key: {e}")))?;
+        kek.unwrap(&WrappedKey { key_id, ciphertext }, &context)
+            .await
+    }
+
+    /// Key to seal new content of a document with
+    ///
+    /// Restricted documents get a key (created on first use); others keep
+    /// using theirs if they have one and are otherwise stored in clear.
+    pub async fn content_key<'e>(
+        &self,
+        executor: impl PgExecutor<'e>,
+        document_id: Uuid,
+        access_level: AccessLevel,
+    ) -> Result<Option<DataKey>> {
+        if access_level == AccessLevel::Restricted {
+            self.create_key(executor, document_id).await.map(Some)
+        } else {
+            self.document_key(executor, document_id).await
+        }
+    }
+
+    /// Open sealed chunk texts of one document in place
+    ///
+    /// The data key is only fetched if one of the texts is sealed.
+    pub async fn open_chunks<'a>(
+        &self,
+        pool: &PgPool,
+        document_id: Uuid,
+        chunks: impl IntoIterator<Item = (u32, &'a mut String)>,
+    ) -> Result<()> {
+        let sealed: Vec<_> = chunks
+            .into_iter()
+            .filter(|(_, text)| is_sealed_text(text))
+            .collect();
+        if sealed.is_empty() {
+            return Ok(());
+        }
+        let key = self.document_key(pool, document_id).await?.ok_or_else(|| {
+            encryption_error(format!(
+                "Document {document_id} has sealed chunks but no data key"
+            ))
+        })?;
+        for (chunk_index, text) in sealed {
+            *text = key.open_text(text, &chunk_context(document_id, chunk_index))?;
+        }
+        Ok(())
+    }
+}
+
+fn cipher(key: &[u8; KEY_LEN]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&AES_256_GCM, key).map_err(|_| encryption_error("Invalid key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn seal(key: &[u8; KEY_LEN], plaintext: &[u8], context: &str) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| encryption_error("No randomness for a nonce"))?;
+    let mut sealed = plaintext.to_vec();
+    cipher(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(context.as_bytes()),
+            &mut sealed,
+        )
+        .map_err(|_| encryption_error("Sealing failed"))?;
+    let mut out = nonce.to_vec();
+    out.extend(sealed);
+    Ok(out)
+}
+
+fn open(key: &[u8; KEY_LEN], sealed: &[u8], context: &str) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN + AES_256_GCM.tag_len() {
+        return Err(encryption_error("Sealed value is truncated"));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce =
+        Nonce::try_assume_unique_for_key(nonce).map_err(|_| encryption_error("Invalid nonce"))?;
+    let mut buffer = ciphertext.to_vec();
+    let plaintext_len = cipher(key)?
+        .open_in_place(nonce, Aad::from(context.as_bytes()), &mut buffer)
+        .map_err(|_| encryption_error("Sealed value does not open with this key and context"))?
+        .len();
+    buffer.truncate(plaintext_len);
+    Ok(buffer)
+}
+
+fn encryption_error(message: impl Into<String>) -> OtlError {
+    OtlError::EncryptionError(message.into())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn master_key(id: &str, byte: u8) -> MasterKey {
+        let encoded = base64::engine::general_purpose::STANDARD.encode([byte; KEY_LEN]);
+        MasterKey::from_base64(id, &encoded).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_envelope_round_trip() {
+        let master = master_key("local-1", 7);
+        let document = Uuid::new_v4();
+        let data_key = DataKey::generate().unwrap();
+        let wrapped = master.wrap(&data_key, &document.to_string()).await.unwrap();
+        assert_eq!(wrapped.key_id, "local-1");
+
+        let context = chunk_context(document, 3);
+        let sealed = data_key.seal_text("임원 보수 규정", &context).unwrap();
+        assert!(is_sealed_text(&sealed));
+        assert!(!sealed.contains("임원"));
+
+        let unwrapped = master
+            .unwrap(&wrapped, &document.to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            unwrapped.open_text(&sealed, &context).unwrap(),
+            "임원 보수 규정"
+        );
+        assert_eq!(unwrapped.open_text("plain", &context).unwrap(), "plain");
+
+        let blob = unwrapped.seal_blob(b"%PDF-1.7", "originals/x").unwrap();
+        assert!(is_sealed_blob(&blob));
+        assert_eq!(
+            unwrapped.open_blob(blob, "originals/x").unwrap(),
+            b"%PDF-1.7"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_envelope_rejects_wrong_key_or_context() {
+        let master = master_key("local-1", 7);
+        let data_key = DataKey::generate().unwrap();
+        let wrapped = master.wrap(&data_key, "doc-a").await.unwrap();
+
+        assert!(master.unwrap(&wrapped, "doc-b").await.is_err());
+        assert!(master_key("local-1", 8)
+            .unwrap(&wrapped, "doc-a")
+            .await
+            .is_err());
+        assert!(master_key("local-2", 7)
+            .unwrap(&wrapped, "doc-a")
+            .await
+            .is_err());
+
+        let sealed = data_key.seal_text("급여", "chunk:a/0").unwrap();
+        assert!(data_key.open_text(&sealed, "chunk:a/1").is_err());
+        assert!(MasterKey::from_base64("short", "c2hvcnQ=").is_err());
+    }
+}
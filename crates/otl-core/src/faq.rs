@@ -0,0 +1,361 @@
+//! Frequently asked questions
+//!
+//! FAQ entries are generated from clusters of similar questions in the
+//! query history and wait in a review queue. Approved entries are listed by
+//! the FAQ endpoint and retrieved ahead of document passages; like glossary
+//! entries, each carries its own ACL.
+//!
+//! Author: hephaex@gmail.com
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+use sqlx::FromRow;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::{AccessLevel, Citation, DocumentAcl, OtlError, Result};
+
+/// Review state of a FAQ entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FaqStatus {
+    /// Generated; awaiting review
+    #[default]
+    Pending,
+    /// Published and used for retrieval
+    Approved,
+    /// Rejected by a reviewer; kept so the topic is not generated again
+    Rejected,
+}
+
+impl FaqStatus {
+    /// Label stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+impl std::str::FromStr for FaqStatus {
+    type Err = OtlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "approved" => Ok(Self::Approved),
+            "rejected" => Ok(Self::Rejected),
+            other => Err(OtlError::ValidationError(format!(
+                "Unknown FAQ status: {other}"
+            ))),
+        }
+    }
+}
+
+/// A canonical question and its cited answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaqEntry {
+    pub id: Uuid,
+    pub question: String,
+    pub answer: String,
+    pub citations: Vec<Citation>,
+    /// Keywords of the clustered questions, used for matching queries
+    pub keywords: Vec<String>,
+    /// Most recent questions of the cluster the entry was generated from
+    pub source_questions: Vec<String>,
+    /// Questions in the cluster when the entry was generated
+    pub query_count: i32,
+    /// Confidence of the generated answer
+    pub confidence: f32,
+    pub acl: DocumentAcl,
+    pub status: FaqStatus,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FaqEntry {
+    /// New pending entry readable by internal users
+    pub fn new(question: impl Into<String>, answer: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            question: question.into(),
+            answer: answer.into(),
+            citations: Vec::new(),
+            keywords: Vec::new(),
+            source_questions: Vec::new(),
+            query_count: 0,
+            confidence: 0.0,
+            acl: DocumentAcl::default(),
+            status: FaqStatus::Pending,
+            reviewed_by: None,
+            reviewed_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Jaccard overlap (0-1) of two keyword lists, ignoring case
+pub fn keyword_overlap(a: &[String], b: &[String]) -> f32 {
+    let a: HashSet<String> = a.iter().map(|k| k.to_lowercase()).collect();
+    let b: HashSet<String> = b.iter().map(|k| k.to_lowercase()).collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+// ============================================================================
+// Repository
+// ============================================================================
+
+/// FAQ storage
+#[async_trait]
+pub trait FaqRepository: Send + Sync {
+    /// Approved entries sharing at least one keyword with `keywords`
+    async fn find_approved(&self, keywords: &[String]) -> Result<Vec<FaqEntry>>;
+
+    /// Entries, optionally of one status, most asked first
+    async fn list(
+        &self,
+        status: Option<FaqStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<FaqEntry>>;
+
+    /// Entry by ID
+    async fn get(&self, id: Uuid) -> Result<Option<FaqEntry>>;
+
+    /// Insert or replace an entry
+    async fn upsert(&self, entry: &FaqEntry) -> Result<()>;
+
+    /// Delete an entry, returning whether it existed
+    async fn delete(&self, id: Uuid) -> Result<bool>;
+}
+
+/// PostgreSQL FAQ store (`faq_entries` table)
+pub struct FaqStore {
+    pool: PgPool,
+}
+
+impl FaqStore {
+    /// Create from an existing pool
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct FaqRow {
+    id: Uuid,
+    question: String,
+    answer: String,
+    citations: serde_json::Value,
+    keywords: Vec<String>,
+    source_questions: Vec<String>,
+    query_count: i32,
+    confidence: f32,
+    access_level: String,
+    department: Option<String>,
+    required_roles: Vec<String>,
+    allowed_users: Vec<String>,
+    status: String,
+    reviewed_by: Option<Uuid>,
+    reviewed_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<FaqRow> for FaqEntry {
+    fn from(row: FaqRow) -> Self {
+        Self {
+            id: row.id,
+            question: row.question,
+            answer: row.answer,
+            citations: serde_json::from_value(row.citations).unwrap_or_default(),
+            keywords: row.keywords,
+            source_questions: row.source_questions,
+            query_count: row.query_count,
+            confidence: row.confidence,
+            acl: DocumentAcl {
+                access_level: match row.access_level.as_str() {
+                    "public" => AccessLevel::Public,
+                    "confidential" => AccessLevel::Confidential,
+                    "restricted" => AccessLevel::Restricted,
+                    _ => AccessLevel::Internal,
+                },
+                owner_id: None,
+                department: row.department,
+                required_roles: row.required_roles,
+                allowed_users: row.allowed_users,
+            },
+            status: row.status.parse().unwrap_or_default(),
+            reviewed_by: row.reviewed_by,
+            reviewed_at: row.reviewed_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+const SELECT_ENTRY: &str = r#"
+    SELECT id, question, answer, citations, keywords, source_questions, query_count,
+           confidence, access_level::text, department, required_roles, allowed_users,
+           status, reviewed_by, reviewed_at, created_at, updated_at
+    FROM faq_entries
+"#;
+
+#[async_trait]
+impl FaqRepository for FaqStore {
+    async fn find_approved(&self, keywords: &[String]) -> Result<Vec<FaqEntry>> {
+        let keywords: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
+        let rows: Vec<FaqRow> = sqlx::query_as(&format!(
+            "{SELECT_ENTRY} WHERE status = 'approved' AND keywords && $1"
+        ))
+        .bind(&keywords)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("FAQ lookup failed: {e}")))?;
+
+        Ok(rows.into_iter().map(FaqEntry::from).collect())
+    }
+
+    async fn list(
+        &self,
+        status: Option<FaqStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<FaqEntry>> {
+        let rows: Vec<FaqRow> = sqlx::query_as(&format!(
+            "{SELECT_ENTRY} WHERE ($1::text IS NULL OR status = $1)
+             ORDER BY query_count DESC, created_at DESC LIMIT $2 OFFSET $3"
+        ))
+        .bind(status.map(|s| s.as_str()))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to list FAQ: {e}")))?;
+
+        Ok(rows.into_iter().map(FaqEntry::from).collect())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<FaqEntry>> {
+        let row: Option<FaqRow> = sqlx::query_as(&format!("{SELECT_ENTRY} WHERE id = $1"))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to get FAQ entry: {e}")))?;
+
+        Ok(row.map(FaqEntry::from))
+    }
+
+    async fn upsert(&self, entry: &FaqEntry) -> Result<()> {
+        let citations = serde_json::to_value(&entry.citations)
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to encode FAQ citations: {e}")))?;
+        let keywords: Vec<String> = entry.keywords.iter().map(|k| k.to_lowercase()).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO faq_entries (
+                id, question, answer, citations, keywords, source_questions, query_count,
+                confidence, access_level, department, required_roles, allowed_users,
+                status, reviewed_by, reviewed_at, created_at, updated_at
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7,
+                $8, $9::access_level, $10, $11, $12,
+                $13, $14, $15, $16, NOW()
+            )
+            ON CONFLICT (id) DO UPDATE SET
+                question = EXCLUDED.question,
+                answer = EXCLUDED.answer,
+                citations = EXCLUDED.citations,
+                keywords = EXCLUDED.keywords,
+                source_questions = EXCLUDED.source_questions,
+                query_count = EXCLUDED.query_count,
+                confidence = EXCLUDED.confidence,
+                access_level = EXCLUDED.access_level,
+                department = EXCLUDED.department,
+                required_roles = EXCLUDED.required_roles,
+                allowed_users = EXCLUDED.allowed_users,
+                status = EXCLUDED.status,
+                reviewed_by = EXCLUDED.reviewed_by,
+                reviewed_at = EXCLUDED.reviewed_at,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(entry.id)
+        .bind(&entry.question)
+        .bind(&entry.answer)
+        .bind(citations)
+        .bind(&keywords)
+        .bind(&entry.source_questions)
+        .bind(entry.query_count)
+        .bind(entry.confidence)
+        .bind(entry.acl.access_level.to_string())
+        .bind(&entry.acl.department)
+        .bind(&entry.acl.required_roles)
+        .bind(&entry.acl.allowed_users)
+        .bind(entry.status.as_str())
+        .bind(entry.reviewed_by)
+        .bind(entry.reviewed_at)
+        .bind(entry.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to save FAQ entry: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM faq_entries WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to delete FAQ entry: {e}")))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_overlap() {
+        let a = vec!["연차휴가".to_string(), "신청".to_string()];
+        let b = vec![
+            "연차휴가".to_string(),
+            "신청".to_string(),
+            "방법".to_string(),
+        ];
+        assert!((keyword_overlap(&a, &b) - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(keyword_overlap(&a, &[]), 0.0);
+        assert_eq!(keyword_overlap(&[], &[]), 0.0);
+        assert_eq!(
+            keyword_overlap(&["KPI".to_string()], &["kpi".to_string()]),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [FaqStatus::Pending, FaqStatus::Approved, FaqStatus::Rejected] {
+            assert_eq!(status.as_str().parse::<FaqStatus>().unwrap(), status);
+        }
+        assert!("published".parse::<FaqStatus>().is_err());
+    }
+}
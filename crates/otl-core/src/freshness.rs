@@ -0,0 +1,309 @@
+//! Document freshness
+//!
+//! Regulations take effect on a date, must be reviewed by another and are
+//! eventually replaced by a newer version. These facts live in the
+//! document's custom metadata, next to the version fields used for ranking:
+//!
+//! - `effective_date`: date the document takes effect (`YYYY-MM-DD`)
+//! - `review_date`: date by which the document must be reviewed
+//! - `superseded_by`: UUID of the document replacing this one, set by hand
+//!   or by the freshness checker when a newer version of the same
+//!   `document_group` exists
+//!
+//! A document past its review date or superseded is stale; citations of
+//! stale documents carry a [`FreshnessWarning`].
+//!
+//! Author: hephaex@gmail.com
+
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::{DocumentMetadata, OtlError, Result};
+
+/// Metadata key of the date the document takes effect
+pub const EFFECTIVE_DATE_KEY: &str = "effective_date";
+
+/// Metadata key of the date by which the document must be reviewed
+pub const REVIEW_DATE_KEY: &str = "review_date";
+
+/// Metadata key of the document replacing this one
+pub const SUPERSEDED_BY_KEY: &str = "superseded_by";
+
+/// Why a document is stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleReason {
+    /// The review date has passed
+    ReviewOverdue,
+    /// A newer version of the document exists
+    Superseded,
+}
+
+impl StaleReason {
+    /// Label stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReviewOverdue => "review_overdue",
+            Self::Superseded => "superseded",
+        }
+    }
+}
+
+impl std::str::FromStr for StaleReason {
+    type Err = OtlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "review_overdue" => Ok(Self::ReviewOverdue),
+            "superseded" => Ok(Self::Superseded),
+            other => Err(OtlError::ValidationError(format!(
+                "Unknown stale reason: {other}"
+            ))),
+        }
+    }
+}
+
+/// Warning attached to citations of a stale document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreshnessWarning {
+    pub reasons: Vec<StaleReason>,
+
+    /// Review date that has passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_date: Option<NaiveDate>,
+
+    /// Document replacing the cited one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<Uuid>,
+}
+
+/// Date the document takes effect
+pub fn effective_date(doc: &DocumentMetadata) -> Option<NaiveDate> {
+    date_field(doc, EFFECTIVE_DATE_KEY)
+}
+
+/// Date by which the document must be reviewed
+pub fn review_date(doc: &DocumentMetadata) -> Option<NaiveDate> {
+    date_field(doc, REVIEW_DATE_KEY)
+}
+
+/// Document replacing this one
+pub fn superseded_by(doc: &DocumentMetadata) -> Option<Uuid> {
+    doc.extra
+        .get(SUPERSEDED_BY_KEY)?
+        .as_str()?
+        .parse()
+        .ok()
+        .filter(|id| *id != doc.id)
+}
+
+/// Parse a metadata date given as `YYYY-MM-DD` or an RFC 3339 timestamp
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|t| t.date_naive())
+        })
+}
+
+fn date_field(doc: &DocumentMetadata, key: &str) -> Option<NaiveDate> {
+    parse_date(doc.extra.get(key)?.as_str()?)
+}
+
+/// Key shared by all versions of a document (`document_group`, the title
+/// otherwise)
+pub fn version_group(doc: &DocumentMetadata) -> String {
+    doc.extra
+        .get("document_group")
+        .and_then(|v| v.as_str())
+        .unwrap_or(&doc.title)
+        .trim()
+        .to_lowercase()
+}
+
+/// Version number from the custom metadata (`3`, `"3"` or `"v3"`)
+pub fn version(doc: &DocumentMetadata) -> Option<f64> {
+    parse_version(doc.extra.get("version")?)
+}
+
+/// Parse a metadata version given as `3`, `"3"` or `"v3"`
+pub fn parse_version(value: &serde_json::Value) -> Option<f64> {
+    value.as_f64().or_else(|| {
+        value
+            .as_str()
+            .and_then(|s| s.trim().trim_start_matches('v').parse().ok())
+    })
+}
+
+/// Newest document of each version group that has more than one version
+///
+/// Versions compare by `version`, then by effective date, then by update
+/// time.
+pub fn latest_versions<'a>(
+    documents: impl IntoIterator<Item = &'a DocumentMetadata>,
+) -> HashMap<String, Uuid> {
+    let mut groups: HashMap<String, Vec<&DocumentMetadata>> = HashMap::new();
+    for doc in documents {
+        groups.entry(version_group(doc)).or_default().push(doc);
+    }
+    groups
+        .into_iter()
+        .filter(|(_, docs)| docs.len() > 1)
+        .filter_map(|(key, docs)| {
+            let newest = docs.into_iter().max_by(|a, b| {
+                version(a)
+                    .partial_cmp(&version(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(effective_date(a).cmp(&effective_date(b)))
+                    .then(a.updated_at.cmp(&b.updated_at))
+            })?;
+            Some((key, newest.id))
+        })
+        .collect()
+}
+
+/// Documents superseded by a newer version: those naming a replacement and
+/// those older than another version of their group among `documents`
+pub fn superseded<'a>(
+    documents: impl IntoIterator<Item = &'a DocumentMetadata> + Clone,
+) -> HashSet<Uuid> {
+    let latest = latest_versions(documents.clone());
+    documents
+        .into_iter()
+        .filter(|doc| {
+            superseded_by(doc).is_some()
+                || latest
+                    .get(&version_group(doc))
+                    .is_some_and(|newest| *newest != doc.id)
+        })
+        .map(|doc| doc.id)
+        .collect()
+}
+
+/// Freshness of a document on `today`; `None` when it is current
+pub fn assess(doc: &DocumentMetadata, today: NaiveDate) -> Option<FreshnessWarning> {
+    let review_date = review_date(doc).filter(|date| *date < today);
+    let superseded_by = superseded_by(doc);
+
+    let mut reasons = Vec::new();
+    if review_date.is_some() {
+        reasons.push(StaleReason::ReviewOverdue);
+    }
+    if superseded_by.is_some() {
+        reasons.push(StaleReason::Superseded);
+    }
+    if reasons.is_empty() {
+        return None;
+    }
+    Some(FreshnessWarning {
+        reasons,
+        review_date,
+        superseded_by,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document(title: &str, fields: &[(&str, serde_json::Value)]) -> DocumentMetadata {
+        let mut doc = DocumentMetadata::new(title, format!("/docs/{title}.pdf"), "pdf");
+        for (key, value) in fields {
+            doc.extra.insert(key.to_string(), value.clone());
+        }
+        doc
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2024-03-01"), Some(date("2024-03-01")));
+        assert_eq!(
+            parse_date("2024-03-01T09:00:00+09:00"),
+            Some(date("2024-03-01"))
+        );
+        assert_eq!(parse_date("next year"), None);
+    }
+
+    #[test]
+    fn test_assess() {
+        let today = date("2026-10-17");
+        let current = document("휴가 규정", &[(REVIEW_DATE_KEY, json!("2027-01-01"))]);
+        assert_eq!(assess(&current, today), None);
+
+        let newer = Uuid::new_v4();
+        let stale = document(
+            "출장 규정",
+            &[
+                (REVIEW_DATE_KEY, json!("2026-06-30")),
+                (SUPERSEDED_BY_KEY, json!(newer.to_string())),
+            ],
+        );
+        let warning = assess(&stale, today).unwrap();
+        assert_eq!(
+            warning.reasons,
+            vec![StaleReason::ReviewOverdue, StaleReason::Superseded]
+        );
+        assert_eq!(warning.review_date, Some(date("2026-06-30")));
+        assert_eq!(warning.superseded_by, Some(newer));
+
+        let mut own = document("a", &[]);
+        own.extra
+            .insert(SUPERSEDED_BY_KEY.to_string(), json!(own.id.to_string()));
+        assert_eq!(assess(&own, today), None);
+    }
+
+    #[test]
+    fn test_latest_versions_orders_by_version_then_effective_date() {
+        let v1 = document("취업규칙", &[("version", json!(1))]);
+        let v2 = document("취업규칙", &[("version", json!("v2"))]);
+        let older = document(
+            "복무 규정",
+            &[
+                ("document_group", json!("복무")),
+                (EFFECTIVE_DATE_KEY, json!("2023-01-01")),
+            ],
+        );
+        let newer = document(
+            "복무 규정 (개정)",
+            &[
+                ("document_group", json!("복무")),
+                (EFFECTIVE_DATE_KEY, json!("2024-01-01")),
+            ],
+        );
+        let single = document("보안 규정", &[]);
+
+        let latest = latest_versions([&v1, &v2, &newer, &older, &single]);
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[&version_group(&v1)], v2.id);
+        assert_eq!(latest["복무"], newer.id);
+    }
+
+    #[test]
+    fn test_superseded_by_group_or_replacement() {
+        let v1 = document("취업규칙", &[("version", json!(1))]);
+        let v2 = document("취업규칙", &[("version", json!(" v2 "))]);
+        let replaced = document(
+            "출장 규정",
+            &[(SUPERSEDED_BY_KEY, json!(Uuid::new_v4().to_string()))],
+        );
+        let single = document("보안 규정", &[]);
+
+        let superseded = superseded([&v1, &v2, &replaced, &single]);
+        assert_eq!(superseded, HashSet::from([v1.id, replaced.id]));
+        assert_eq!(version(&v2), Some(2.0));
+    }
+}
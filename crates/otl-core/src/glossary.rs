@@ -0,0 +1,356 @@
+//! Glossary of curated term definitions
+//!
+//! Entries are either approved by an admin or candidates proposed from
+//! answers to definitional questions. Only approved entries are served by
+//! the RAG fast path; each carries its own ACL like a document.
+//!
+//! Author: hephaex@gmail.com
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::{AccessLevel, DocumentAcl, OtlError, Result};
+
+/// Review state of a glossary entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlossaryStatus {
+    /// Curated; answers definitional questions directly
+    #[default]
+    Approved,
+    /// Proposed from a generated answer; awaiting review
+    Candidate,
+}
+
+impl GlossaryStatus {
+    /// Label stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approved => "approved",
+            Self::Candidate => "candidate",
+        }
+    }
+}
+
+impl std::str::FromStr for GlossaryStatus {
+    type Err = OtlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "approved" => Ok(Self::Approved),
+            "candidate" => Ok(Self::Candidate),
+            other => Err(OtlError::ValidationError(format!(
+                "Unknown glossary status: {other}"
+            ))),
+        }
+    }
+}
+
+/// A term and its definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub id: Uuid,
+    pub term: String,
+    /// Other spellings that resolve to this entry
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub definition: String,
+    /// Where the definition comes from (document title, regulation, URL)
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Source document, cited when the entry answers a question
+    #[serde(default)]
+    pub document_id: Option<Uuid>,
+    #[serde(default)]
+    pub acl: DocumentAcl,
+    #[serde(default)]
+    pub status: GlossaryStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl GlossaryEntry {
+    /// New approved entry readable by internal users
+    pub fn new(term: impl Into<String>, definition: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            term: term.into(),
+            aliases: Vec::new(),
+            definition: definition.into(),
+            source: None,
+            document_id: None,
+            acl: DocumentAcl::default(),
+            status: GlossaryStatus::Approved,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Normalized term and aliases, used for lookups
+    pub fn lookup_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = std::iter::once(&self.term)
+            .chain(&self.aliases)
+            .map(|t| normalize_term(t))
+            .filter(|t| !t.is_empty())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+/// Lookup form of a term: lowercase, single spaces, no surrounding
+/// punctuation or quotes
+pub fn normalize_term(term: &str) -> String {
+    term.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+// ============================================================================
+// Repository
+// ============================================================================
+
+/// Glossary storage
+#[async_trait]
+pub trait GlossaryRepository: Send + Sync {
+    /// Entries whose term or alias normalizes to `term`, of any status
+    async fn find_by_term(&self, term: &str) -> Result<Vec<GlossaryEntry>>;
+
+    /// Entries, optionally of one status, ordered by term
+    async fn list(
+        &self,
+        status: Option<GlossaryStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<GlossaryEntry>>;
+
+    /// Entry by ID
+    async fn get(&self, id: Uuid) -> Result<Option<GlossaryEntry>>;
+
+    /// Insert or replace an entry
+    async fn upsert(&self, entry: &GlossaryEntry) -> Result<()>;
+
+    /// Delete an entry, returning whether it existed
+    async fn delete(&self, id: Uuid) -> Result<bool>;
+
+    /// Add a candidate unless an entry with the same term exists
+    ///
+    /// Returns whether the candidate was added.
+    async fn propose(&self, candidate: &GlossaryEntry) -> Result<bool> {
+        if !self.find_by_term(&candidate.term).await?.is_empty() {
+            return Ok(false);
+        }
+        self.upsert(candidate).await?;
+        Ok(true)
+    }
+}
+
+/// PostgreSQL glossary store (`glossary_terms` table)
+pub struct GlossaryStore {
+    pool: PgPool,
+}
+
+impl GlossaryStore {
+    /// Create from an existing pool
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct GlossaryRow {
+    id: Uuid,
+    term: String,
+    aliases: Vec<String>,
+    definition: String,
+    source: Option<String>,
+    document_id: Option<Uuid>,
+    access_level: String,
+    department: Option<String>,
+    required_roles: Vec<String>,
+    allowed_users: Vec<String>,
+    status: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<GlossaryRow> for GlossaryEntry {
+    fn from(row: GlossaryRow) -> Self {
+        Self {
+            id: row.id,
+            term: row.term,
+            aliases: row.aliases,
+            definition: row.definition,
+            source: row.source,
+            document_id: row.document_id,
+            acl: DocumentAcl {
+                access_level: match row.access_level.as_str() {
+                    "public" => AccessLevel::Public,
+                    "confidential" => AccessLevel::Confidential,
+                    "restricted" => AccessLevel::Restricted,
+                    _ => AccessLevel::Internal,
+                },
+                owner_id: None,
+                department: row.department,
+                required_roles: row.required_roles,
+                allowed_users: row.allowed_users,
+            },
+            status: row.status.parse().unwrap_or(GlossaryStatus::Candidate),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+const SELECT_ENTRY: &str = r#"
+    SELECT id, term, aliases, definition, source, document_id, access_level::text,
+           department, required_roles, allowed_users, status, created_at, updated_at
+    FROM glossary_terms
+"#;
+
+#[async_trait]
+impl GlossaryRepository for GlossaryStore {
+    async fn find_by_term(&self, term: &str) -> Result<Vec<GlossaryEntry>> {
+        let rows: Vec<GlossaryRow> = sqlx::query_as(&format!(
+            "{SELECT_ENTRY} WHERE $1 = ANY(lookup_keys) ORDER BY status, updated_at DESC"
+        ))
+        .bind(normalize_term(term))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Glossary lookup failed: {e}")))?;
+
+        Ok(rows.into_iter().map(GlossaryEntry::from).collect())
+    }
+
+    async fn list(
+        &self,
+        status: Option<GlossaryStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<GlossaryEntry>> {
+        let rows: Vec<GlossaryRow> = sqlx::query_as(&format!(
+            "{SELECT_ENTRY} WHERE ($1::text IS NULL OR status = $1)
+             ORDER BY lower(term) LIMIT $2 OFFSET $3"
+        ))
+        .bind(status.map(|s| s.as_str()))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to list glossary: {e}")))?;
+
+        Ok(rows.into_iter().map(GlossaryEntry::from).collect())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<GlossaryEntry>> {
+        let row: Option<GlossaryRow> = sqlx::query_as(&format!("{SELECT_ENTRY} WHERE id = $1"))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to get glossary entry: {e}")))?;
+
+        Ok(row.map(GlossaryEntry::from))
+    }
+
+    async fn upsert(&self, entry: &GlossaryEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO glossary_terms (
+                id, term, aliases, lookup_keys, definition, source, document_id,
+                access_level, department, required_roles, allowed_users, status,
+                created_at, updated_at
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7,
+                $8::access_level, $9, $10, $11, $12,
+                $13, NOW()
+            )
+            ON CONFLICT (id) DO UPDATE SET
+                term = EXCLUDED.term,
+                aliases = EXCLUDED.aliases,
+                lookup_keys = EXCLUDED.lookup_keys,
+                definition = EXCLUDED.definition,
+                source = EXCLUDED.source,
+                document_id = EXCLUDED.document_id,
+                access_level = EXCLUDED.access_level,
+                department = EXCLUDED.department,
+                required_roles = EXCLUDED.required_roles,
+                allowed_users = EXCLUDED.allowed_users,
+                status = EXCLUDED.status,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(entry.id)
+        .bind(&entry.term)
+        .bind(&entry.aliases)
+        .bind(entry.lookup_keys())
+        .bind(&entry.definition)
+        .bind(&entry.source)
+        .bind(entry.document_id)
+        .bind(entry.acl.access_level.to_string())
+        .bind(&entry.acl.department)
+        .bind(&entry.acl.required_roles)
+        .bind(&entry.acl.allowed_users)
+        .bind(entry.status.as_str())
+        .bind(entry.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to save glossary entry: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM glossary_terms WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                OtlError::DatabaseError(format!("Failed to delete glossary entry: {e}"))
+            })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_term() {
+        assert_eq!(normalize_term("  \"Annual   Leave\"? "), "annual leave");
+        assert_eq!(normalize_term("'연차휴가'"), "연차휴가");
+        assert_eq!(normalize_term("?!"), "");
+    }
+
+    #[test]
+    fn test_lookup_keys_include_aliases() {
+        let mut entry = GlossaryEntry::new(
+            "연차휴가",
+            "1년간 80% 이상 출근한 근로자에게 주어지는 유급휴가",
+        );
+        entry.aliases = vec![
+            "연차".to_string(),
+            " 연차휴가 ".to_string(),
+            "Annual Leave".to_string(),
+        ];
+
+        assert_eq!(
+            entry.lookup_keys(),
+            vec!["annual leave", "연차", "연차휴가"]
+        );
+        assert_eq!(entry.status, GlossaryStatus::Approved);
+    }
+}
@@ -0,0 +1,180 @@
+//! Chunk integrity
+//!
+//! Every stored chunk records the SHA-256 of its text when it is written
+//! (`document_chunks.content_hash`). An integrity check hashes the stored
+//! text again and compares it with the recorded hash and, when the original
+//! document is available, with the chunks re-derived from it using the
+//! chunker settings the document was ingested with. A chunk whose text no
+//! longer matches is corrupted: it stays in storage for inspection but is
+//! never cited.
+//!
+//! Author: hephaex@gmail.com
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+/// SHA-256 of a chunk's text, hex encoded
+pub fn content_hash(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Outcome of checking one chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStatus {
+    /// Stored text matches every reference hash
+    Ok,
+    /// Stored text differs from the recorded hash or the original
+    Corrupted,
+    /// The original has a chunk that is not stored
+    Missing,
+    /// No recorded hash and no original to compare with
+    Unverified,
+}
+
+/// Stored chunk to check
+#[derive(Debug, Clone)]
+pub struct StoredChunk {
+    pub chunk_index: u32,
+    pub content: String,
+    /// Hash recorded when the chunk was written
+    pub recorded_hash: Option<String>,
+}
+
+/// Integrity of one chunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkIntegrity {
+    pub chunk_index: u32,
+    pub status: ChunkStatus,
+    /// Hash of the stored text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stored_hash: Option<String>,
+    /// Hash recorded when the chunk was written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recorded_hash: Option<String>,
+    /// Hash of the chunk re-derived from the original
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_hash: Option<String>,
+}
+
+/// Check stored chunks against their recorded hashes and, if given, the
+/// chunks re-derived from the original document
+///
+/// With an original, a stored chunk past its last chunk is corrupted and a
+/// chunk of the original that is not stored is missing. Results are
+/// ordered by chunk index.
+pub fn check_chunks(stored: &[StoredChunk], original: Option<&[String]>) -> Vec<ChunkIntegrity> {
+    let original_len = original.map_or(0, <[String]>::len) as u32;
+    let indices: BTreeSet<u32> = stored
+        .iter()
+        .map(|c| c.chunk_index)
+        .chain(0..original_len)
+        .collect();
+
+    indices
+        .into_iter()
+        .map(|index| {
+            let original_hash = original
+                .and_then(|chunks| chunks.get(index as usize))
+                .map(|text| content_hash(text));
+            let Some(chunk) = stored.iter().find(|c| c.chunk_index == index) else {
+                return ChunkIntegrity {
+                    chunk_index: index,
+                    status: ChunkStatus::Missing,
+                    stored_hash: None,
+                    recorded_hash: None,
+                    original_hash,
+                };
+            };
+
+            let stored_hash = content_hash(&chunk.content);
+            let references: Vec<&String> = chunk
+                .recorded_hash
+                .iter()
+                .chain(original_hash.iter())
+                .collect();
+            let status = if original.is_some() && original_hash.is_none() {
+                ChunkStatus::Corrupted
+            } else if references.is_empty() {
+                ChunkStatus::Unverified
+            } else if references.iter().all(|hash| **hash == stored_hash) {
+                ChunkStatus::Ok
+            } else {
+                ChunkStatus::Corrupted
+            };
+            ChunkIntegrity {
+                chunk_index: index,
+                status,
+                stored_hash: Some(stored_hash),
+                recorded_hash: chunk.recorded_hash.clone(),
+                original_hash,
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(index: u32, content: &str, recorded: Option<&str>) -> StoredChunk {
+        StoredChunk {
+            chunk_index: index,
+            content: content.to_string(),
+            recorded_hash: recorded.map(content_hash),
+        }
+    }
+
+    fn statuses(report: &[ChunkIntegrity]) -> Vec<ChunkStatus> {
+        report.iter().map(|c| c.status).collect()
+    }
+
+    #[test]
+    fn test_check_against_recorded_hashes() {
+        let chunks = [
+            stored(0, "연차휴가는 15일", Some("연차휴가는 15일")),
+            stored(1, "병가는 연 60일", Some("병가는 연 30일")),
+            stored(2, "경조휴가", None),
+        ];
+        let report = check_chunks(&chunks, None);
+        assert_eq!(
+            statuses(&report),
+            [
+                ChunkStatus::Ok,
+                ChunkStatus::Corrupted,
+                ChunkStatus::Unverified
+            ]
+        );
+        assert_eq!(report[1].stored_hash, Some(content_hash("병가는 연 60일")));
+    }
+
+    #[test]
+    fn test_check_against_original() {
+        let original = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        // Chunk 1 matches its (rewritten) recorded hash but not the original
+        let chunks = [
+            stored(0, "a", Some("a")),
+            stored(1, "x", Some("x")),
+            stored(3, "d", None),
+        ];
+        let report = check_chunks(&chunks, Some(&original));
+        assert_eq!(
+            statuses(&report),
+            [
+                ChunkStatus::Ok,
+                ChunkStatus::Corrupted,
+                ChunkStatus::Missing,
+                ChunkStatus::Corrupted
+            ]
+        );
+        assert_eq!(report[2].original_hash, Some(content_hash("c")));
+        assert_eq!(content_hash("").len(), 64);
+    }
+}
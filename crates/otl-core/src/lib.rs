@@ -6,13 +6,50 @@
 //! - Common error types
 //! - Shared traits for search backends
 //! - Configuration management
+//! - Confidence calibration
 //! - Metadata storage (PostgreSQL)
+//! - Korean morphological analysis for keyword extraction
+//! - Synonym registry for query expansion
+//! - Document freshness (effective/review dates, superseded versions)
 
+pub mod blob;
+pub mod calibration;
 pub mod config;
+pub mod dedup;
+pub mod encryption;
+pub mod faq;
+pub mod freshness;
+pub mod glossary;
+pub mod integrity;
 pub mod metadata;
-
-pub use config::{AppConfig, ConfigError, DatabaseConfig, LlmConfig, LlmProvider, RagConfig};
+pub mod morph;
+pub mod persona;
+pub mod pipeline;
+pub mod space;
+pub mod synonyms;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+
+pub use blob::{BlobStore, FsBlobStore, S3BlobStore};
+pub use calibration::{
+    CalibrationCurve, CalibrationMethod, CalibrationSample, Calibrator, MIN_CALIBRATION_SAMPLES,
+};
+pub use config::{
+    AppConfig, BlobBackend, ConfigError, DatabaseConfig, EncryptionConfig, LlmConfig, LlmProvider,
+    PluginConfig, RagConfig, StorageConfig, VectorQuantization,
+};
+pub use encryption::{DataKey, KeyEncryptionKey, Keyring, MasterKey};
+pub use faq::{FaqEntry, FaqRepository, FaqStatus, FaqStore};
+pub use freshness::{FreshnessWarning, StaleReason};
+pub use glossary::{GlossaryEntry, GlossaryRepository, GlossaryStatus, GlossaryStore};
 pub use metadata::{MetadataRepository, MetadataStore};
+pub use morph::{
+    AnalyzerSettings, KoreanAnalyzer, LanguageSettings, Morpheme, Normalization, SharedAnalyzer,
+};
+pub use persona::{Persona, PromptRegistry};
+pub use pipeline::{PipelineDefinition, PipelineRunner, PipelineSet, StageKind};
+pub use space::{KnowledgeSpace, RetrievalScope, SpaceRegistry, SpaceRoute};
+pub use synonyms::{SynonymGroup, SynonymRegistry};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -51,6 +88,9 @@ pub enum OtlError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -66,7 +106,7 @@ pub type Result<T> = std::result::Result<T, OtlError>;
 /// Defines the security classification for documents:
 /// - `Public`: Anyone can access
 /// - `Internal`: Organization members only
-/// - `Confidential`: Specific departments/roles only
+/// - `Confidential`: Specific departments/roles (and users granted access)
 /// - `Restricted`: Named individuals only
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -104,7 +144,8 @@ pub struct DocumentAcl {
     /// Roles required to access this document
     pub required_roles: Vec<String>,
 
-    /// Specific users allowed access (for Restricted level)
+    /// Specific users allowed access (Restricted documents, and
+    /// Confidential ones outside the department or roles)
     pub allowed_users: Vec<String>,
 }
 
@@ -136,15 +177,19 @@ impl DocumentAcl {
 
                 let role_match = self.required_roles.iter().any(|r| user.roles.contains(r));
 
-                dept_match || role_match
+                dept_match || role_match || self.is_granted(user)
             }
             AccessLevel::Restricted => {
                 // Must be in allowed_users list
-                self.allowed_users.contains(&user.user_id)
-                    || self.owner_id.as_ref() == Some(&user.user_id)
+                self.is_granted(user)
             }
         }
     }
+
+    /// Whether the user owns the document or is named in `allowed_users`
+    fn is_granted(&self, user: &User) -> bool {
+        self.allowed_users.contains(&user.user_id) || self.owner_id.as_ref() == Some(&user.user_id)
+    }
 }
 
 /// User identity and permissions
@@ -355,6 +400,10 @@ pub struct SourceReference {
     /// Character offset in the document
     pub offset: Option<usize>,
 
+    /// Index of the stored chunk the result was read from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<u32>,
+
     /// Extraction confidence score
     pub confidence: f32,
 }
@@ -367,6 +416,7 @@ impl SourceReference {
             page: None,
             section: None,
             offset: None,
+            chunk_index: None,
             confidence: 1.0,
         }
     }
@@ -383,6 +433,12 @@ impl SourceReference {
         self
     }
 
+    /// Set the stored chunk index
+    pub fn with_chunk_index(mut self, chunk_index: u32) -> Self {
+        self.chunk_index = Some(chunk_index);
+        self
+    }
+
     /// Set confidence score
     pub fn with_confidence(mut self, confidence: f32) -> Self {
         self.confidence = confidence;
@@ -390,6 +446,105 @@ impl SourceReference {
     }
 }
 
+/// One piece of evidence supporting a triple
+///
+/// A triple extracted from several passages keeps one provenance record per
+/// passage, so reviewers can see every snippet behind it and how the
+/// extraction confidence evolved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Triple this evidence supports
+    pub triple_id: Uuid,
+
+    /// Where the evidence was found
+    pub source: SourceReference,
+
+    /// Supporting text snippet
+    pub snippet: Option<String>,
+
+    /// Extractor that produced the evidence (e.g. "rule", "llm", "hitl")
+    pub extractor: Option<String>,
+
+    /// Extraction confidence for this evidence
+    pub confidence: f32,
+
+    /// When the evidence was recorded
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl Provenance {
+    /// Create a provenance record from a source reference
+    pub fn new(triple_id: Uuid, source: SourceReference) -> Self {
+        Self {
+            triple_id,
+            confidence: source.confidence,
+            source,
+            snippet: None,
+            extractor: None,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    /// Set the supporting snippet
+    pub fn with_snippet(mut self, snippet: impl Into<String>) -> Self {
+        self.snippet = Some(snippet.into());
+        self
+    }
+
+    /// Set the extractor name
+    pub fn with_extractor(mut self, extractor: impl Into<String>) -> Self {
+        self.extractor = Some(extractor.into());
+        self
+    }
+}
+
+impl Triple {
+    /// Provenance record for the source this triple was created from
+    pub fn provenance(&self) -> Provenance {
+        let mut provenance = Provenance::new(self.id, self.source.clone());
+        provenance.confidence = self.confidence;
+        provenance.recorded_at = self.created_at;
+        provenance
+    }
+}
+
+/// Classes and predicates of the document structure graph
+///
+/// Documents, their sections and their chunks are stored as graph nodes
+/// alongside extracted entities: `SourceDocument -containsSection-> Section`,
+/// `Section -containsSection-> Section` for nested headings,
+/// `Section -hasChunk-> Chunk` and `Chunk -mentions-> Entity`.
+pub mod structure {
+    /// Class of document nodes (distinct from the `Document` entity type
+    /// the extractors use for certificates and forms)
+    pub const DOCUMENT_CLASS: &str = "SourceDocument";
+
+    /// Class of section nodes
+    pub const SECTION_CLASS: &str = "Section";
+
+    /// Class of chunk nodes
+    pub const CHUNK_CLASS: &str = "Chunk";
+
+    /// Document or section to a (sub)section
+    pub const CONTAINS_SECTION: &str = "containsSection";
+
+    /// Section (or document) to a chunk
+    pub const HAS_CHUNK: &str = "hasChunk";
+
+    /// Chunk to an entity extracted from it
+    pub const MENTIONS: &str = "mentions";
+
+    /// Whether a class belongs to the structure graph rather than the domain ontology
+    pub fn is_structure_class(class: &str) -> bool {
+        matches!(class, DOCUMENT_CLASS | SECTION_CLASS | CHUNK_CLASS)
+    }
+
+    /// Whether a predicate is a structure edge rather than an extracted relation
+    pub fn is_structure_predicate(predicate: &str) -> bool {
+        matches!(predicate, CONTAINS_SECTION | HAS_CHUNK | MENTIONS)
+    }
+}
+
 // ============================================================================
 // Document Models
 // ============================================================================
@@ -526,6 +681,8 @@ pub enum SearchResultType {
     Graph,
     /// Keyword search result
     Keyword,
+    /// Approved FAQ entry
+    Faq,
 }
 
 /// RAG query request
@@ -542,6 +699,97 @@ pub struct RagQuery {
 
     /// Filter by document IDs
     pub document_filter: Option<Vec<Uuid>>,
+
+    /// How the answer is produced
+    #[serde(default)]
+    pub answer_mode: AnswerMode,
+
+    /// Answer language override (detected from the question when unset)
+    #[serde(default)]
+    pub response_language: Option<Language>,
+
+    /// Record a retrieval trace in the response
+    #[serde(default)]
+    pub debug: bool,
+
+    /// Fail instead of answering without an unavailable retrieval backend
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Deadline in milliseconds, if shorter than the configured one
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+
+    /// Run reproducibly: LLM calls use temperature 0 and this seed, and the
+    /// trace carries a [`QueryRecording`] that can be replayed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Persona shaping the system instruction of the answer prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<Persona>,
+
+    /// Keep results from documents superseded by a newer version (for
+    /// historical questions)
+    #[serde(default)]
+    pub include_superseded: bool,
+
+    /// Knowledge spaces restricting the departments searched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<RetrievalScope>,
+}
+
+/// Supported query/answer languages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    /// Korean
+    #[default]
+    #[serde(rename = "ko")]
+    Korean,
+    /// English
+    #[serde(rename = "en")]
+    English,
+}
+
+impl Language {
+    /// ISO 639-1 language code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Korean => "ko",
+            Self::English => "en",
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = OtlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ko" | "kor" | "korean" => Ok(Self::Korean),
+            "en" | "eng" | "english" => Ok(Self::English),
+            other => Err(OtlError::ValidationError(format!(
+                "Unsupported language: {other}"
+            ))),
+        }
+    }
+}
+
+/// How a RAG answer is produced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerMode {
+    /// LLM-generated answer grounded in retrieved context
+    #[default]
+    Generative,
+    /// Top passages with highlighted sentences, no LLM call
+    Extractive,
 }
 
 impl RagQuery {
@@ -552,6 +800,15 @@ impl RagQuery {
             top_k: 10,
             min_score: None,
             document_filter: None,
+            answer_mode: AnswerMode::default(),
+            response_language: None,
+            debug: false,
+            strict: false,
+            timeout_ms: None,
+            seed: None,
+            persona: None,
+            include_superseded: false,
+            scope: None,
         }
     }
 
@@ -560,6 +817,62 @@ impl RagQuery {
         self.top_k = k;
         self
     }
+
+    /// Set answer mode
+    pub fn with_answer_mode(mut self, mode: AnswerMode) -> Self {
+        self.answer_mode = mode;
+        self
+    }
+
+    /// Force the answer language
+    pub fn with_response_language(mut self, language: Language) -> Self {
+        self.response_language = Some(language);
+        self
+    }
+
+    /// Record a retrieval trace in the response
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Fail the query when a retrieval backend is unavailable
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Finish within `ms` milliseconds (capped by the configured deadline)
+    pub fn with_timeout_ms(mut self, ms: u64) -> Self {
+        self.timeout_ms = Some(ms);
+        self
+    }
+
+    /// Run reproducibly with the given LLM seed, recording the run
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Answer with a persona's role and rules (`None` keeps the default
+    /// role)
+    pub fn with_persona(mut self, persona: Option<Persona>) -> Self {
+        self.persona = persona;
+        self
+    }
+
+    /// Keep results from superseded document versions
+    pub fn with_include_superseded(mut self, include: bool) -> Self {
+        self.include_superseded = include;
+        self
+    }
+
+    /// Search only the documents of some knowledge spaces (`None` searches
+    /// all readable documents)
+    pub fn with_scope(mut self, scope: Option<RetrievalScope>) -> Self {
+        self.scope = scope;
+        self
+    }
 }
 
 /// RAG response with answer and citations
@@ -576,6 +889,464 @@ pub struct RagResponse {
 
     /// Processing time in milliseconds
     pub processing_time_ms: u64,
+
+    /// Suggested follow-up questions
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+
+    /// Source passages with highlighted sentences (extractive mode)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub passages: Vec<ExtractedPassage>,
+
+    /// Ontology-conforming machine-readable answer (list/fact questions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_answer: Option<StructuredAnswer>,
+
+    /// Layout the answer was generated in
+    #[serde(default)]
+    pub format: AnswerFormat,
+
+    /// Moderation applied to the answer, if any sensitive-topic rule fired
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation: Option<ModerationDecision>,
+
+    /// Degradations the answer was produced under (e.g. skipped backends)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+
+    /// Restricted values masked in the context for lack of clearance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub withheld: Vec<WithheldTopic>,
+
+    /// Model that generated the answer (none for extractive and glossary
+    /// answers, or when the model name is unknown)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Knowledge spaces the context was retrieved from, ending with
+    /// `global` when the scoped search fell back (empty for a global search)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub searched_spaces: Vec<String>,
+
+    /// Retrieval trace (only when `RagQuery.debug` is set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<QueryTrace>,
+}
+
+/// Step-by-step record of how an answer was retrieved
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryTrace {
+    /// Candidates returned by the vector backend
+    pub vector_candidates: Vec<TraceCandidate>,
+
+    /// Candidates returned by the graph backend
+    pub graph_candidates: Vec<TraceCandidate>,
+
+    /// Candidates returned by the keyword backend
+    pub keyword_candidates: Vec<TraceCandidate>,
+
+    /// Approved FAQ entries matching the question
+    #[serde(default)]
+    pub faq_candidates: Vec<TraceCandidate>,
+
+    /// Backend failures (the query continues without the failed backend)
+    #[serde(default)]
+    pub backend_errors: Vec<String>,
+
+    /// Availability of each retrieval backend
+    #[serde(default)]
+    pub backend_health: Vec<BackendHealth>,
+
+    /// Candidates removed by ACL filtering
+    pub acl_filtered: Vec<TraceCandidate>,
+
+    /// Candidates after Reciprocal Rank Fusion, scored by RRF
+    pub fused: Vec<TraceCandidate>,
+
+    /// Context passed to answering, scored by the final reranker
+    pub reranked: Vec<TraceCandidate>,
+
+    /// Prompt sent to the LLM (generative mode)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+
+    /// Wall-clock time per pipeline stage, in execution order
+    pub timings: Vec<StageTiming>,
+
+    /// Everything the run read from backends (seeded queries only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recording: Option<QueryRecording>,
+}
+
+/// Inputs of a seeded query run, enough to replay it without live backends
+///
+/// Search results are kept in full (not as previews), along with every LLM
+/// exchange, embedding and document metadata lookup of the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRecording {
+    /// The query as run
+    pub query: RagQuery,
+
+    /// When the run started; replays rank and check freshness as of then
+    pub recorded_at: DateTime<Utc>,
+
+    /// What each retrieval backend returned (empty for glossary answers)
+    pub searches: Vec<RecordedSearch>,
+
+    /// Document metadata read for ranking boosts and freshness warnings
+    #[serde(default)]
+    pub documents: Vec<DocumentMetadata>,
+
+    /// Chunks reported corrupted, as `(document_id, chunk_index)`
+    #[serde(default)]
+    pub corrupted_chunks: Vec<(Uuid, u32)>,
+
+    /// Neighboring chunks read to expand contexts
+    #[serde(default)]
+    pub neighbor_chunks: Vec<DocumentChunk>,
+
+    /// Feedback prior multipliers other than 1, as
+    /// `(document_id, chunk_index, multiplier)`
+    #[serde(default)]
+    pub feedback_priors: Vec<(Uuid, Option<u32>, f32)>,
+
+    /// LLM calls in call order
+    #[serde(default)]
+    pub completions: Vec<RecordedCompletion>,
+
+    /// Embeddings computed while answering (compression, extractive scoring)
+    #[serde(default)]
+    pub embeddings: Vec<RecordedEmbedding>,
+
+    /// Final answer of the recorded run
+    #[serde(default)]
+    pub answer: String,
+}
+
+impl QueryRecording {
+    /// Recorded results of a backend
+    pub fn search(&self, backend: &SearchResultType) -> Option<&RecordedSearch> {
+        self.searches.iter().find(|s| s.backend == *backend)
+    }
+}
+
+/// Results of one retrieval backend in a recorded run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedSearch {
+    /// Backend
+    pub backend: SearchResultType,
+
+    /// Whether the backend was configured
+    pub configured: bool,
+
+    /// Results in backend order, with their scores
+    #[serde(default)]
+    pub results: Vec<SearchResult>,
+
+    /// Failure reason, if the backend failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RecordedSearch {
+    /// Record the outcome of a search
+    pub fn new(
+        backend: SearchResultType,
+        configured: bool,
+        outcome: &Result<Vec<SearchResult>>,
+    ) -> Self {
+        let (results, error) = match outcome {
+            Ok(results) => (results.clone(), None),
+            Err(OtlError::SearchError(message)) => (Vec::new(), Some(message.clone())),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        Self {
+            backend,
+            configured,
+            results,
+            error,
+        }
+    }
+
+    /// The search outcome as originally returned
+    ///
+    /// A recorded failure is returned as a `SearchError` with its message.
+    pub fn outcome(&self) -> Result<Vec<SearchResult>> {
+        match &self.error {
+            Some(error) => Err(OtlError::SearchError(error.clone())),
+            None => Ok(self.results.clone()),
+        }
+    }
+}
+
+/// One LLM call in a recorded run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCompletion {
+    /// Prompt sent
+    pub prompt: String,
+
+    /// Response received
+    pub response: String,
+}
+
+/// One embedding in a recorded run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEmbedding {
+    /// Embedded text
+    pub text: String,
+
+    /// Embedding vector
+    pub vector: Vec<f32>,
+}
+
+/// Search result as recorded in a query trace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceCandidate {
+    /// Source document
+    pub document_id: Uuid,
+
+    /// Backend that produced the result
+    pub result_type: SearchResultType,
+
+    /// Score at this stage
+    pub score: f32,
+
+    /// Stored chunk the result was read from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<u32>,
+
+    /// Leading part of the content (empty for results the user may not read)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub preview: String,
+}
+
+impl TraceCandidate {
+    /// Characters of content kept in the preview
+    pub const PREVIEW_CHARS: usize = 200;
+
+    /// Record a search result with the given score
+    pub fn new(result: &SearchResult, score: f32) -> Self {
+        Self {
+            score,
+            preview: result.content.chars().take(Self::PREVIEW_CHARS).collect(),
+            ..Self::withheld(result)
+        }
+    }
+
+    /// Record a result without any of its content
+    pub fn withheld(result: &SearchResult) -> Self {
+        Self {
+            document_id: result.source.document_id,
+            result_type: result.result_type.clone(),
+            score: result.score,
+            chunk_index: result.source.chunk_index,
+            preview: String::new(),
+        }
+    }
+}
+
+impl From<&SearchResult> for TraceCandidate {
+    fn from(result: &SearchResult) -> Self {
+        Self::new(result, result.score)
+    }
+}
+
+/// Duration of one pipeline stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageTiming {
+    /// Stage name (e.g. `vector_search`, `generation`)
+    pub stage: String,
+
+    /// Duration in milliseconds
+    pub duration_ms: f64,
+}
+
+/// Availability of one retrieval backend during a query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackendHealth {
+    /// Backend
+    pub backend: SearchResultType,
+
+    /// Whether the backend answered
+    pub status: BackendStatus,
+
+    /// Results returned
+    pub results: usize,
+
+    /// Time spent waiting for the backend, in milliseconds
+    pub duration_ms: f64,
+
+    /// Failure reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of one retrieval backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendStatus {
+    /// Searched successfully
+    Ok,
+    /// Failed; the query continued without it
+    Failed,
+    /// Not configured
+    Disabled,
+}
+
+/// Outcome of the post-generation moderation pass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModerationDecision {
+    /// Action taken on the answer
+    pub action: ModerationAction,
+
+    /// Names of the sensitive-topic rules that fired
+    pub rules: Vec<String>,
+
+    /// How the sensitive content was detected
+    pub detector: ModerationDetector,
+
+    /// Number of sentences replaced by the redaction marker
+    #[serde(default)]
+    pub redacted_sentences: usize,
+}
+
+/// Action taken by a sensitive-topic rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Replace the offending sentences and keep the rest of the answer
+    Redact,
+    /// Withhold the whole answer
+    Refuse,
+}
+
+/// Sensitive topic whose values were masked in a redacted answer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithheldTopic {
+    /// Name of the sensitive-topic rule
+    pub rule: String,
+
+    /// What the topic covers
+    #[serde(default)]
+    pub description: String,
+
+    /// Number of values masked
+    pub values: usize,
+
+    /// How to request access to the topic
+    pub access_request: String,
+}
+
+/// Detector that triggered a moderation decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationDetector {
+    /// Regex pattern of a rule
+    Pattern,
+    /// LLM classifier
+    Classifier,
+}
+
+/// Layout of an answer, chosen from the kind of question
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerFormat {
+    /// Free text
+    #[default]
+    Prose,
+    /// Numbered procedure steps
+    Steps,
+    /// Markdown comparison table
+    Table,
+    /// "Yes" or "No" verdict followed by the rationale
+    YesNo,
+}
+
+impl AnswerFormat {
+    /// Name used in API responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Prose => "prose",
+            Self::Steps => "steps",
+            Self::Table => "table",
+            Self::YesNo => "yes_no",
+        }
+    }
+}
+
+/// Machine-readable answer validated against the ontology
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StructuredAnswer {
+    /// A single fact about a subject
+    Fact {
+        /// What the fact is about
+        subject: String,
+        /// Fact value (number, string, boolean)
+        value: serde_json::Value,
+        /// Unit of the value, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+        /// Supporting citation indices
+        #[serde(default)]
+        citations: Vec<u32>,
+    },
+    /// A list of ontology entities
+    List {
+        /// Entities in answer order
+        items: Vec<StructuredItem>,
+    },
+}
+
+/// Entity in a structured list answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructuredItem {
+    /// Ontology class of the entity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+
+    /// Entity name
+    pub name: String,
+
+    /// Property values defined by the class
+    #[serde(default)]
+    pub properties: HashMap<String, serde_json::Value>,
+
+    /// Supporting citation indices
+    #[serde(default)]
+    pub citations: Vec<u32>,
+}
+
+/// Passage returned by extractive answering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedPassage {
+    /// Citation index matching `RagResponse.citations`
+    pub index: u32,
+
+    /// Full passage text
+    pub text: String,
+
+    /// Source reference
+    pub source: SourceReference,
+
+    /// Passage relevance score after reranking
+    pub score: f32,
+
+    /// Best matching sentences within the passage
+    pub highlights: Vec<SentenceHighlight>,
+}
+
+/// Sentence highlighted within an extracted passage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentenceHighlight {
+    /// Byte offset of the sentence start in the passage text
+    pub start: usize,
+
+    /// Byte offset of the sentence end in the passage text
+    pub end: usize,
+
+    /// Combined lexical/embedding sentence score
+    pub score: f32,
 }
 
 /// Citation for a claim in the answer
@@ -592,6 +1363,10 @@ pub struct Citation {
 
     /// Document title
     pub document_title: String,
+
+    /// Set when the cited document is past its review date or superseded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<FreshnessWarning>,
 }
 
 // ============================================================================
@@ -660,12 +1435,58 @@ pub trait SearchBackend: Send + Sync {
     fn name(&self) -> &str;
 }
 
+/// Graph access for entity-aware RAG retrieval
+///
+/// Lets the RAG orchestrator resolve question mentions to graph nodes and
+/// expand their neighborhood without depending on a concrete graph store.
+#[async_trait::async_trait]
+pub trait GraphContextBackend: Send + Sync {
+    /// Domain entities whose label occurs in `text`
+    async fn resolve_entities(&self, text: &str, limit: usize) -> Result<Vec<Entity>>;
+
+    /// Entities by ID (unknown IDs are skipped)
+    async fn get_entities(&self, ids: &[Uuid]) -> Result<Vec<Entity>>;
+
+    /// Relations with any of the given entities as subject or object
+    async fn incident_triples(&self, ids: &[Uuid]) -> Result<Vec<Triple>>;
+
+    /// Provenance records of the given triples
+    async fn provenance_for(&self, triple_ids: &[Uuid]) -> Result<Vec<Provenance>>;
+}
+
 /// Trait for LLM clients
 #[async_trait::async_trait]
 pub trait LlmClient: Send + Sync {
     /// Generate a response
     async fn generate(&self, prompt: &str) -> Result<String>;
 
+    /// Generate a response without serving it from a response cache
+    ///
+    /// Clients without a cache generate as usual.
+    async fn generate_uncached(&self, prompt: &str) -> Result<String> {
+        self.generate(prompt).await
+    }
+
+    /// Generate as deterministically as the backend allows
+    ///
+    /// Clients that support it sample with temperature 0 and `seed` and
+    /// bypass their response cache; the default generates uncached.
+    async fn generate_seeded(&self, prompt: &str, _seed: u64) -> Result<String> {
+        self.generate_uncached(prompt).await
+    }
+
+    /// Generate responses to several prompts, in prompt order
+    ///
+    /// Clients that can batch requests override this; the default generates
+    /// one prompt after another.
+    async fn generate_batch(&self, prompts: &[String]) -> Result<Vec<String>> {
+        let mut responses = Vec::with_capacity(prompts.len());
+        for prompt in prompts {
+            responses.push(self.generate(prompt).await?);
+        }
+        Ok(responses)
+    }
+
     /// Generate a streaming response
     async fn generate_stream(
         &self,
@@ -681,6 +1502,19 @@ pub trait LlmClient: Send + Sync {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_triple_provenance() {
+        let doc_id = Uuid::new_v4();
+        let source = SourceReference::new(doc_id).with_page(3);
+        let triple = Triple::new(Uuid::new_v4(), "requires", Uuid::new_v4(), source, 0.8);
+
+        let provenance = triple.provenance().with_snippet("병가 신청 시 진단서 제출");
+        assert_eq!(provenance.triple_id, triple.id);
+        assert_eq!(provenance.source.page, Some(3));
+        assert_eq!(provenance.confidence, 0.8);
+        assert_eq!(provenance.recorded_at, triple.created_at);
+    }
+
     #[test]
     fn test_acl_public_access() {
         let acl = DocumentAcl {
@@ -720,6 +1554,21 @@ mod tests {
         assert!(!acl.can_access(&regular_user));
     }
 
+    #[test]
+    fn test_acl_confidential_allowed_users() {
+        let acl = DocumentAcl {
+            access_level: AccessLevel::Confidential,
+            department: Some("인사팀".to_string()),
+            owner_id: Some("owner".to_string()),
+            allowed_users: vec!["auditor".to_string()],
+            ..Default::default()
+        };
+
+        assert!(acl.can_access(&User::internal("auditor", vec![])));
+        assert!(acl.can_access(&User::internal("owner", vec![])));
+        assert!(!acl.can_access(&User::internal("random", vec![])));
+    }
+
     #[test]
     fn test_acl_restricted_allowed_users() {
         let acl = DocumentAcl {
@@ -758,4 +1607,12 @@ mod tests {
         assert!(AccessLevel::Internal < AccessLevel::Confidential);
         assert!(AccessLevel::Confidential < AccessLevel::Restricted);
     }
+
+    #[test]
+    fn test_language_parsing() {
+        assert_eq!("EN".parse::<Language>().unwrap(), Language::English);
+        assert_eq!("korean".parse::<Language>().unwrap(), Language::Korean);
+        assert!("fr".parse::<Language>().is_err());
+        assert_eq!(serde_json::to_string(&Language::English).unwrap(), "\"en\"");
+    }
 }
@@ -6,13 +6,16 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::postgres::{PgPool, PgPoolOptions};
 use sqlx::FromRow;
+use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::encryption::{chunk_context, Keyring};
 use crate::{AccessLevel, DocumentAcl, DocumentChunk, DocumentMetadata, OtlError, Result};
 
 /// PostgreSQL metadata store
 pub struct MetadataStore {
     pool: PgPool,
+    keyring: Option<Arc<Keyring>>,
 }
 
 impl MetadataStore {
@@ -24,12 +27,24 @@ impl MetadataStore {
             .await
             .map_err(|e| OtlError::DatabaseError(format!("PostgreSQL connection failed: {e}")))?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            keyring: None,
+        })
     }
 
     /// Create from an existing pool
     pub fn from_pool(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            keyring: None,
+        }
+    }
+
+    /// Seal Restricted chunk content on write and open it on read
+    pub fn with_keyring(mut self, keyring: Arc<Keyring>) -> Self {
+        self.keyring = Some(keyring);
+        self
     }
 
     /// Get the connection pool
@@ -123,6 +138,9 @@ pub trait MetadataRepository: Send + Sync {
     /// Get document by ID
     async fn get_document(&self, id: Uuid) -> Result<Option<DocumentMetadata>>;
 
+    /// Get the documents with the given IDs (missing or deleted ones are skipped)
+    async fn get_documents(&self, ids: &[Uuid]) -> Result<Vec<DocumentMetadata>>;
+
     /// List documents with optional filters
     async fn list_documents(&self, limit: i64, offset: i64) -> Result<Vec<DocumentMetadata>>;
 
@@ -138,8 +156,16 @@ pub trait MetadataRepository: Send + Sync {
     /// Get chunks for a document
     async fn get_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>>;
 
+    /// Get the chunks at the given `(document_id, chunk_index)` positions
+    /// (missing ones are skipped)
+    async fn get_chunks_at(&self, positions: &[(Uuid, u32)]) -> Result<Vec<DocumentChunk>>;
+
     /// Update chunk with vector ID
     async fn update_chunk_vector_id(&self, chunk_id: Uuid, vector_id: &str) -> Result<()>;
+
+    /// Chunks of the given documents found corrupted by the last integrity
+    /// check, as `(document_id, chunk_index)`
+    async fn corrupted_chunks(&self, document_ids: &[Uuid]) -> Result<Vec<(Uuid, u32)>>;
 }
 
 #[async_trait]
@@ -200,6 +226,25 @@ impl MetadataRepository for MetadataStore {
         Ok(row.map(DocumentMetadata::from))
     }
 
+    async fn get_documents(&self, ids: &[Uuid]) -> Result<Vec<DocumentMetadata>> {
+        let rows: Vec<DocumentRow> = sqlx::query_as(
+            r#"
+            SELECT
+                id, title, file_path, file_type::text, file_size,
+                access_level::text, owner_id, department, required_roles, allowed_users,
+                metadata, created_at, updated_at
+            FROM documents
+            WHERE id = ANY($1) AND deleted_at IS NULL
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to get documents: {e}")))?;
+
+        Ok(rows.into_iter().map(DocumentMetadata::from).collect())
+    }
+
     async fn list_documents(&self, limit: i64, offset: i64) -> Result<Vec<DocumentMetadata>> {
         let rows: Vec<DocumentRow> = sqlx::query_as(
             r#"
@@ -273,19 +318,47 @@ impl MetadataRepository for MetadataStore {
     }
 
     async fn create_chunk(&self, chunk: &DocumentChunk) -> Result<Uuid> {
+        let content = match &self.keyring {
+            Some(keyring) => {
+                let access_level: Option<String> =
+                    sqlx::query_scalar("SELECT access_level::text FROM documents WHERE id = $1")
+                        .bind(chunk.document_id)
+                        .fetch_optional(&self.pool)
+                        .await
+                        .map_err(|e| {
+                            OtlError::DatabaseError(format!("Failed to fetch document: {e}"))
+                        })?;
+                let access_level = match access_level.as_deref() {
+                    Some("restricted") => AccessLevel::Restricted,
+                    _ => AccessLevel::Internal,
+                };
+                match keyring
+                    .content_key(&self.pool, chunk.document_id, access_level)
+                    .await?
+                {
+                    Some(key) => key.seal_text(
+                        &chunk.content,
+                        &chunk_context(chunk.document_id, chunk.chunk_index),
+                    )?,
+                    None => chunk.content.clone(),
+                }
+            }
+            None => chunk.content.clone(),
+        };
         let row: (Uuid,) = sqlx::query_as(
             r#"
             INSERT INTO document_chunks (
-                id, document_id, chunk_index, content,
+                id, document_id, chunk_index, content, content_hash,
                 page_number, section_name, vector_id
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING id
             "#,
         )
         .bind(chunk.id)
         .bind(chunk.document_id)
         .bind(chunk.chunk_index as i32)
-        .bind(&chunk.content)
+        .bind(&content)
+        .bind(crate::integrity::content_hash(&chunk.content))
         .bind(chunk.page_number.map(|n| n as i32))
         .bind(&chunk.section_name)
         .bind(&chunk.vector_id)
@@ -310,7 +383,61 @@ impl MetadataRepository for MetadataStore {
         .await
         .map_err(|e| OtlError::DatabaseError(format!("Failed to get chunks: {e}")))?;
 
-        Ok(rows.into_iter().map(DocumentChunk::from).collect())
+        let mut chunks: Vec<DocumentChunk> = rows.into_iter().map(DocumentChunk::from).collect();
+        if let Some(keyring) = &self.keyring {
+            keyring
+                .open_chunks(
+                    &self.pool,
+                    document_id,
+                    chunks.iter_mut().map(|c| (c.chunk_index, &mut c.content)),
+                )
+                .await?;
+        }
+        Ok(chunks)
+    }
+
+    async fn get_chunks_at(&self, positions: &[(Uuid, u32)]) -> Result<Vec<DocumentChunk>> {
+        if positions.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (document_ids, indices): (Vec<Uuid>, Vec<i32>) = positions
+            .iter()
+            .map(|(document_id, index)| (*document_id, *index as i32))
+            .unzip();
+        let rows: Vec<ChunkRow> = sqlx::query_as(
+            r#"
+            SELECT c.id, c.document_id, c.chunk_index, c.content, c.page_number,
+                   c.section_name, c.vector_id
+            FROM document_chunks c
+            JOIN UNNEST($1::uuid[], $2::int[]) AS p(document_id, chunk_index)
+              ON c.document_id = p.document_id AND c.chunk_index = p.chunk_index
+            ORDER BY c.document_id, c.chunk_index
+            "#,
+        )
+        .bind(&document_ids)
+        .bind(&indices)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to get chunks: {e}")))?;
+
+        let mut chunks: Vec<DocumentChunk> = rows.into_iter().map(DocumentChunk::from).collect();
+        if let Some(keyring) = &self.keyring {
+            let mut sealed_documents: Vec<Uuid> = chunks.iter().map(|c| c.document_id).collect();
+            sealed_documents.dedup();
+            for document_id in sealed_documents {
+                keyring
+                    .open_chunks(
+                        &self.pool,
+                        document_id,
+                        chunks
+                            .iter_mut()
+                            .filter(|c| c.document_id == document_id)
+                            .map(|c| (c.chunk_index, &mut c.content)),
+                    )
+                    .await?;
+            }
+        }
+        Ok(chunks)
     }
 
     async fn update_chunk_vector_id(&self, chunk_id: Uuid, vector_id: &str) -> Result<()> {
@@ -323,6 +450,22 @@ impl MetadataRepository for MetadataStore {
 
         Ok(())
     }
+
+    async fn corrupted_chunks(&self, document_ids: &[Uuid]) -> Result<Vec<(Uuid, u32)>> {
+        let rows: Vec<(Uuid, i32)> = sqlx::query_as(
+            "SELECT document_id, chunk_index FROM document_chunks
+             WHERE corrupted AND document_id = ANY($1)",
+        )
+        .bind(document_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to get corrupted chunks: {e}")))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(document_id, index)| (document_id, index as u32))
+            .collect())
+    }
 }
 
 #[cfg(test)]
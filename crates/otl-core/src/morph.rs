@@ -0,0 +1,795 @@
+//! Lightweight Korean morphological analysis
+//!
+//! Korean attaches particles (조사) and endings (어미) directly to the word,
+//! so whitespace tokenization turns "연차휴가는" and "연차휴가를" into
+//! different keywords. This module splits text into words, strips particles
+//! from nouns, recognizes predicates by their endings (lemmatizing
+//! 하다/되다 verbs to their noun) and drops stopwords.
+//!
+//! It is an embedded, dictionary-light analyzer: particles that are also the
+//! last syllable of a common noun ("휴가", "회의", "결과") are protected by a
+//! small built-in noun list, which callers can extend with domain nouns.
+//!
+//! Stopwords, the shortest keyword length and normalization rules are set
+//! per language with [`AnalyzerSettings`]: Korean rules apply to words
+//! containing Hangul, English rules to all other words. [`SharedAnalyzer`]
+//! lets query analysis and keyword search share settings that can be
+//! replaced at runtime.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::Language;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Part of speech assigned to a word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tag {
+    /// Korean noun (particles removed)
+    Noun,
+    /// Verb or adjective (lemma ends with 다)
+    Predicate,
+    /// Latin-script word
+    Foreign,
+    /// Number
+    Number,
+}
+
+/// A word with its lemma
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Morpheme {
+    /// Word as written
+    pub surface: String,
+    /// Dictionary form (noun without particles, predicate stem + 다)
+    pub lemma: String,
+    /// Part of speech
+    pub tag: Tag,
+}
+
+/// Particles, longest first so compound particles win
+const PARTICLES: &[&str] = &[
+    "에서부터",
+    "으로부터",
+    "에게서는",
+    "이라고",
+    "에서는",
+    "에서도",
+    "으로는",
+    "으로도",
+    "에게는",
+    "까지는",
+    "부터는",
+    "이라는",
+    "에서",
+    "에게",
+    "한테",
+    "으로",
+    "까지",
+    "부터",
+    "보다",
+    "처럼",
+    "같이",
+    "마다",
+    "이나",
+    "이란",
+    "라는",
+    "이며",
+    "에는",
+    "에도",
+    "와는",
+    "과는",
+    "은",
+    "는",
+    "이",
+    "가",
+    "을",
+    "를",
+    "의",
+    "에",
+    "와",
+    "과",
+    "로",
+    "도",
+    "만",
+    "나",
+    "란",
+];
+
+/// Endings of 하다/되다 predicates built on a noun ("신청하나요" -> 신청)
+const LIGHT_VERB_ENDINGS: &[&str] = &[
+    "하려면",
+    "하는지",
+    "하나요",
+    "합니까",
+    "합니다",
+    "했나요",
+    "해야",
+    "하면",
+    "하고",
+    "하는",
+    "하기",
+    "해서",
+    "하여",
+    "해요",
+    "할",
+    "한",
+    "되려면",
+    "되는지",
+    "되나요",
+    "됩니까",
+    "됩니다",
+    "되면",
+    "되는",
+    "되어",
+    "돼요",
+    "된",
+    "될",
+];
+
+/// Copula endings ("며칠인가요" -> 며칠)
+const COPULA_ENDINGS: &[&str] = &[
+    "인가요",
+    "입니까",
+    "입니다",
+    "인지",
+    "이에요",
+    "예요",
+    "이다",
+    "인데",
+    "이면",
+];
+
+/// Endings that mark a word as a predicate
+const PREDICATE_ENDINGS: &[&str] = &[
+    "습니까",
+    "습니다",
+    "나요",
+    "까요",
+    "세요",
+    "어요",
+    "아요",
+    "려면",
+    "으면",
+    "는지",
+    "는데",
+    "니까",
+    "어야",
+    "아야",
+    "고요",
+];
+
+/// Nouns whose last syllable looks like a particle
+const PROTECTED_NOUNS: &[&str] = &[
+    "휴가",
+    "병가",
+    "공가",
+    "평가",
+    "단가",
+    "대가",
+    "물가",
+    "추가",
+    "허가",
+    "인가",
+    "증가",
+    "국가",
+    "전문가",
+    "차이",
+    "나이",
+    "사이",
+    "길이",
+    "높이",
+    "넓이",
+    "회의",
+    "정의",
+    "주의",
+    "동의",
+    "합의",
+    "문의",
+    "논의",
+    "협의",
+    "심의",
+    "건의",
+    "이의",
+    "강의",
+    "의의",
+    "결의",
+    "제도",
+    "정도",
+    "한도",
+    "연도",
+    "년도",
+    "태도",
+    "지도",
+    "용도",
+    "경로",
+    "근로",
+    "진로",
+    "결과",
+    "효과",
+    "초과",
+    "통과",
+    "경과",
+    "부과",
+    "교과",
+    "기와",
+    "기한",
+    "권한",
+    "제한",
+    "시한",
+    "상한",
+    "하한",
+];
+
+/// Korean words that carry no search value: question words, pronouns and
+/// bound nouns
+const KOREAN_STOPWORDS: &[&str] = &[
+    "무엇",
+    "뭐",
+    "어떻게",
+    "어떤",
+    "언제",
+    "어디",
+    "누구",
+    "왜",
+    "얼마",
+    "얼마나",
+    "며칠",
+    "몇",
+    "것",
+    "수",
+    "등",
+    "및",
+    "때",
+    "관련",
+    "대한",
+    "대해",
+    "대해서",
+    "있나요",
+    "없나요",
+    "알려주세요",
+    "저",
+    "제",
+    "내",
+    "우리",
+    "그",
+    "이",
+    "좀",
+];
+
+/// English words that carry no search value
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "the", "a", "an", "is", "are", "was", "were", "what", "how", "do", "does", "can", "to", "for",
+    "of", "in", "on", "my", "when", "who", "why", "which", "and", "or", "i", "me", "it", "be",
+    "with", "about", "should", "there",
+];
+
+// ============================================================================
+// Settings
+// ============================================================================
+
+/// Normalization applied to a word before analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Lowercase letters
+    Lowercase,
+    /// Fold full-width forms ("ＨＲ", "１５") to ASCII
+    FoldWidth,
+    /// Compose decomposed Hangul jamo (NFD text, e.g. macOS file names)
+    /// into syllables
+    ComposeHangul,
+}
+
+/// Analyzer settings of one language
+///
+/// Unset fields use the language's built-in defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageSettings {
+    /// Stopwords added to the built-in list
+    pub stopwords: Vec<String>,
+
+    /// Use only `stopwords`, without the built-in list
+    pub replace_stopwords: bool,
+
+    /// Shortest keyword kept, in characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_token_length: Option<usize>,
+
+    /// Normalization rules, applied in order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalization: Option<Vec<Normalization>>,
+
+    /// Nouns kept whole although their last syllable looks like a particle
+    /// (Korean only)
+    pub nouns: Vec<String>,
+}
+
+/// Per-language analyzer settings
+///
+/// Korean settings apply to words containing Hangul, English settings to
+/// all other words (Latin script and numbers).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalyzerSettings {
+    /// Korean
+    pub ko: LanguageSettings,
+    /// English
+    pub en: LanguageSettings,
+}
+
+impl AnalyzerSettings {
+    /// Settings of a language
+    pub fn language(&self, language: Language) -> &LanguageSettings {
+        match language {
+            Language::Korean => &self.ko,
+            Language::English => &self.en,
+        }
+    }
+
+    /// Mutable settings of a language
+    pub fn language_mut(&mut self, language: Language) -> &mut LanguageSettings {
+        match language {
+            Language::Korean => &mut self.ko,
+            Language::English => &mut self.en,
+        }
+    }
+}
+
+/// Resolved rules of one language
+#[derive(Debug, Clone)]
+struct LanguageRules {
+    stopwords: HashSet<String>,
+    min_token_length: usize,
+    normalization: Vec<Normalization>,
+}
+
+impl LanguageRules {
+    fn new(settings: &LanguageSettings, language: Language) -> Self {
+        let (builtin, min_token_length, normalization): (&[&str], usize, &[Normalization]) =
+            match language {
+                Language::Korean => (
+                    KOREAN_STOPWORDS,
+                    1,
+                    &[Normalization::ComposeHangul, Normalization::FoldWidth],
+                ),
+                Language::English => (
+                    ENGLISH_STOPWORDS,
+                    2,
+                    &[Normalization::FoldWidth, Normalization::Lowercase],
+                ),
+            };
+
+        let mut stopwords: HashSet<String> = HashSet::new();
+        if !settings.replace_stopwords {
+            stopwords.extend(builtin.iter().map(|s| s.to_string()));
+        }
+        stopwords.extend(settings.stopwords.iter().map(|s| s.trim().to_string()));
+        Self {
+            stopwords,
+            min_token_length: settings.min_token_length.unwrap_or(min_token_length),
+            normalization: settings
+                .normalization
+                .clone()
+                .unwrap_or_else(|| normalization.to_vec()),
+        }
+    }
+
+    fn normalize(&self, word: &str) -> String {
+        let mut word = word.to_string();
+        for rule in &self.normalization {
+            word = match rule {
+                Normalization::Lowercase => word.to_lowercase(),
+                Normalization::FoldWidth => word.chars().map(fold_width).collect(),
+                Normalization::ComposeHangul => compose_hangul(&word),
+            };
+        }
+        word
+    }
+
+    fn keeps(&self, lemma: &str) -> bool {
+        !lemma.is_empty()
+            && !self.stopwords.contains(lemma)
+            && lemma.chars().count() >= self.min_token_length
+    }
+}
+
+// ============================================================================
+// Analyzer
+// ============================================================================
+
+/// Korean-aware keyword extractor
+#[derive(Debug, Clone)]
+pub struct KoreanAnalyzer {
+    protected: HashSet<String>,
+    ko: LanguageRules,
+    en: LanguageRules,
+}
+
+impl Default for KoreanAnalyzer {
+    fn default() -> Self {
+        Self::from_settings(&AnalyzerSettings::default())
+    }
+}
+
+impl KoreanAnalyzer {
+    /// Create an analyzer with the built-in noun and stopword lists
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an analyzer with per-language settings
+    pub fn from_settings(settings: &AnalyzerSettings) -> Self {
+        let protected = PROTECTED_NOUNS
+            .iter()
+            .map(|s| s.to_string())
+            .chain(settings.ko.nouns.iter().map(|s| s.trim().to_string()))
+            .filter(|noun| !noun.is_empty())
+            .collect();
+        Self {
+            protected,
+            ko: LanguageRules::new(&settings.ko, Language::Korean),
+            en: LanguageRules::new(&settings.en, Language::English),
+        }
+    }
+
+    /// Protect domain nouns whose last syllable looks like a particle
+    pub fn with_nouns<S: Into<String>>(mut self, nouns: impl IntoIterator<Item = S>) -> Self {
+        self.protected.extend(nouns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add stopwords (to both languages)
+    pub fn with_stopwords<S: Into<String>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        for word in words {
+            let word = word.into();
+            self.en.stopwords.insert(word.clone());
+            self.ko.stopwords.insert(word);
+        }
+        self
+    }
+
+    /// Split text into words, normalize and analyze each one
+    pub fn analyze(&self, text: &str) -> Vec<Morpheme> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|word| {
+                let normalized = self.word_rules(word).normalize(word);
+                self.analyze_word(word, &normalized)
+            })
+            .collect()
+    }
+
+    /// Search keywords: lemmatized nouns, foreign words and numbers without
+    /// stopwords, in order of first appearance
+    pub fn keywords(&self, text: &str) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.analyze(text)
+            .into_iter()
+            .filter(|m| m.tag != Tag::Predicate)
+            .filter(|m| self.tag_rules(m.tag).keeps(&m.lemma))
+            .map(|m| m.lemma)
+            .filter(|lemma| seen.insert(lemma.clone()))
+            .collect()
+    }
+
+    /// Rules for a word as written
+    fn word_rules(&self, word: &str) -> &LanguageRules {
+        if word.chars().any(|c| is_hangul(c) || is_hangul_jamo(c)) {
+            &self.ko
+        } else {
+            &self.en
+        }
+    }
+
+    /// Rules for an analyzed word
+    fn tag_rules(&self, tag: Tag) -> &LanguageRules {
+        match tag {
+            Tag::Noun | Tag::Predicate => &self.ko,
+            Tag::Foreign | Tag::Number => &self.en,
+        }
+    }
+
+    fn analyze_word(&self, surface: &str, word: &str) -> Morpheme {
+        let morpheme = |lemma: String, tag| Morpheme {
+            surface: surface.to_string(),
+            lemma,
+            tag,
+        };
+
+        if word.chars().all(|c| c.is_ascii_digit()) {
+            return morpheme(word.to_string(), Tag::Number);
+        }
+        if !word.chars().any(is_hangul) {
+            return morpheme(word.to_string(), Tag::Foreign);
+        }
+        if self.ko.stopwords.contains(word) || self.ends_with_protected(word) {
+            return morpheme(word.to_string(), Tag::Noun);
+        }
+
+        // Noun + 하다/되다 or copula: the noun is the lemma. A one-syllable
+        // stem is more likely a noun ending in 한/된 ("기한") than a verb.
+        for ending in LIGHT_VERB_ENDINGS.iter().chain(COPULA_ENDINGS) {
+            if let Some(stem) = strip(word, ending, 2) {
+                return morpheme(self.strip_particle(stem).to_string(), Tag::Noun);
+            }
+        }
+        for ending in PREDICATE_ENDINGS {
+            if let Some(stem) = strip(word, ending, 1) {
+                return morpheme(format!("{stem}다"), Tag::Predicate);
+            }
+        }
+
+        morpheme(self.strip_particle(word).to_string(), Tag::Noun)
+    }
+
+    /// Remove one trailing particle unless the word ends with a protected noun
+    fn strip_particle<'a>(&self, word: &'a str) -> &'a str {
+        if self.ends_with_protected(word) {
+            return word;
+        }
+        PARTICLES
+            .iter()
+            .find_map(|particle| {
+                strip(word, particle, 1).filter(|stem| fits_particle(stem, particle))
+            })
+            .unwrap_or(word)
+    }
+
+    fn ends_with_protected(&self, word: &str) -> bool {
+        self.protected
+            .iter()
+            .any(|noun| word.ends_with(noun.as_str()))
+    }
+}
+
+// ============================================================================
+// Shared Analyzer
+// ============================================================================
+
+/// Analyzer whose settings can be replaced while it is in use
+///
+/// Query analysis and keyword search hold the same instance, so a settings
+/// change applies to both from the next call.
+#[derive(Debug)]
+pub struct SharedAnalyzer {
+    inner: RwLock<(AnalyzerSettings, Arc<KoreanAnalyzer>)>,
+}
+
+impl SharedAnalyzer {
+    /// Create an analyzer with settings
+    pub fn new(settings: AnalyzerSettings) -> Self {
+        let analyzer = Arc::new(KoreanAnalyzer::from_settings(&settings));
+        Self {
+            inner: RwLock::new((settings, analyzer)),
+        }
+    }
+
+    /// Current analyzer
+    pub fn analyzer(&self) -> Arc<KoreanAnalyzer> {
+        self.inner.read().expect("analyzer lock poisoned").1.clone()
+    }
+
+    /// Current settings
+    pub fn settings(&self) -> AnalyzerSettings {
+        self.inner.read().expect("analyzer lock poisoned").0.clone()
+    }
+
+    /// Replace the settings
+    pub fn update(&self, settings: AnalyzerSettings) {
+        let analyzer = Arc::new(KoreanAnalyzer::from_settings(&settings));
+        *self.inner.write().expect("analyzer lock poisoned") = (settings, analyzer);
+    }
+
+    /// Keywords of a text with the current settings
+    pub fn keywords(&self, text: &str) -> Vec<String> {
+        self.analyzer().keywords(text)
+    }
+}
+
+impl Default for SharedAnalyzer {
+    fn default() -> Self {
+        Self::new(AnalyzerSettings::default())
+    }
+}
+
+/// Strip a suffix, keeping at least `min_chars` characters
+fn strip<'a>(word: &'a str, suffix: &str, min_chars: usize) -> Option<&'a str> {
+    word.strip_suffix(suffix)
+        .filter(|stem| stem.chars().count() >= min_chars)
+}
+
+/// Whether a particle agrees with the final consonant (받침) of the stem
+///
+/// 은/을/이/과/으로 follow a consonant, 는/를/가/와/로 follow a vowel
+/// (로 also follows ㄹ).
+fn fits_particle(stem: &str, particle: &str) -> bool {
+    let Some(last) = stem.chars().last() else {
+        return false;
+    };
+    let Some(coda) = final_consonant(last) else {
+        return true;
+    };
+    match particle {
+        "은" | "을" | "이" | "과" | "으로" => coda != 0,
+        "는" | "를" | "가" | "와" => coda == 0,
+        "로" => coda == 0 || coda == 8, // ㄹ
+        _ => true,
+    }
+}
+
+/// Index of the final consonant of a Hangul syllable (0 = none)
+fn final_consonant(c: char) -> Option<u32> {
+    let code = c as u32;
+    (0xAC00..=0xD7A3)
+        .contains(&code)
+        .then(|| (code - 0xAC00) % 28)
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '가'..='힣' | 'ㄱ'..='ㆎ')
+}
+
+/// Conjoining jamo, as found in decomposed (NFD) Hangul
+fn is_hangul_jamo(c: char) -> bool {
+    matches!(c, '\u{1100}'..='\u{11FF}')
+}
+
+/// Map a full-width ASCII form to its ASCII character
+fn fold_width(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '\u{3000}' => ' ',
+        _ => c,
+    }
+}
+
+/// Compose conjoining jamo sequences (initial + medial [+ final]) into
+/// Hangul syllables
+fn compose_hangul(word: &str) -> String {
+    const L_BASE: u32 = 0x1100;
+    const V_BASE: u32 = 0x1161;
+    const T_BASE: u32 = 0x11A7;
+
+    let chars: Vec<char> = word.chars().collect();
+    let mut composed = String::with_capacity(word.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let l = chars[i] as u32;
+        let v = chars.get(i + 1).map(|&c| c as u32);
+        match v {
+            Some(v) if (L_BASE..L_BASE + 19).contains(&l) && (V_BASE..V_BASE + 21).contains(&v) => {
+                let mut code = 0xAC00 + ((l - L_BASE) * 21 + (v - V_BASE)) * 28;
+                i += 2;
+                if let Some(t) = chars.get(i).map(|&c| c as u32) {
+                    if (T_BASE + 1..T_BASE + 28).contains(&t) {
+                        code += t - T_BASE;
+                        i += 1;
+                    }
+                }
+                composed.extend(char::from_u32(code));
+            }
+            _ => {
+                composed.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    composed
+}
+
+/// Keywords of a text with the default analyzer
+pub fn keywords(text: &str) -> Vec<String> {
+    KoreanAnalyzer::default().keywords(text)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_particles_are_stripped() {
+        assert_eq!(
+            keywords("연차휴가는 연차휴가를 연차휴가의"),
+            vec!["연차휴가".to_string()]
+        );
+        assert_eq!(keywords("병가를 쓰려면"), vec!["병가".to_string()]);
+        assert_eq!(keywords("인사팀에서는"), vec!["인사팀".to_string()]);
+    }
+
+    #[test]
+    fn test_protected_nouns_keep_their_last_syllable() {
+        assert_eq!(keywords("휴가 회의 결과"), vec!["휴가", "회의", "결과"]);
+        // 받침 agreement: "산" ends in a consonant, so 이 is a particle
+        assert_eq!(keywords("산이"), vec!["산".to_string()]);
+
+        let analyzer = KoreanAnalyzer::new().with_nouns(["보상휴무"]);
+        assert_eq!(analyzer.keywords("보상휴무"), vec!["보상휴무".to_string()]);
+    }
+
+    #[test]
+    fn test_question_is_reduced_to_keywords() {
+        assert_eq!(
+            keywords("연차휴가 신청 절차가 어떻게 되나요?"),
+            vec!["연차휴가", "신청", "절차"]
+        );
+        assert_eq!(keywords("병가는 며칠인가요?"), vec!["병가".to_string()]);
+        assert_eq!(keywords("휴가를 신청하려면"), vec!["휴가", "신청"]);
+        assert_eq!(
+            keywords("How do I apply for annual leave?"),
+            vec!["apply", "annual", "leave"]
+        );
+    }
+
+    #[test]
+    fn test_predicates_are_lemmatized() {
+        let morphemes = KoreanAnalyzer::new().analyze("받나요");
+        assert_eq!(morphemes[0].tag, Tag::Predicate);
+        assert_eq!(morphemes[0].lemma, "받다");
+
+        let morphemes = KoreanAnalyzer::new().analyze("신청합니다");
+        assert_eq!(morphemes[0].tag, Tag::Noun);
+        assert_eq!(morphemes[0].lemma, "신청");
+    }
+
+    #[test]
+    fn test_settings_apply_per_language() {
+        let settings: AnalyzerSettings = serde_json::from_value(serde_json::json!({
+            "ko": { "stopwords": ["신청"], "nouns": ["보상휴무"] },
+            "en": { "stopwords": ["leave"], "min_token_length": 3 }
+        }))
+        .unwrap();
+        let analyzer = KoreanAnalyzer::from_settings(&settings);
+
+        assert_eq!(
+            analyzer.keywords("보상휴무 신청 절차"),
+            vec!["보상휴무", "절차"]
+        );
+        assert_eq!(
+            analyzer.keywords("HR leave policy v2"),
+            vec!["policy".to_string()]
+        );
+
+        // Replacing the built-in list keeps only the configured stopwords
+        let mut settings = AnalyzerSettings::default();
+        settings.en.replace_stopwords = true;
+        settings.en.stopwords = vec!["annual".to_string()];
+        let analyzer = KoreanAnalyzer::from_settings(&settings);
+        assert_eq!(
+            analyzer.keywords("how to apply for annual leave"),
+            vec!["how", "to", "apply", "for", "leave"]
+        );
+    }
+
+    #[test]
+    fn test_normalization() {
+        // Full-width letters and decomposed Hangul match their usual forms
+        assert_eq!(keywords("ＨＲ팀 ＰＯＬＩＣＹ"), vec!["HR팀", "policy"]);
+        assert_eq!(keywords("\u{1112}\u{1172}\u{1100}\u{1161}"), vec!["휴가"]);
+
+        let mut settings = AnalyzerSettings::default();
+        settings.en.normalization = Some(vec![Normalization::FoldWidth]);
+        let analyzer = KoreanAnalyzer::from_settings(&settings);
+        assert_eq!(analyzer.keywords("ＨＲ Policy"), vec!["HR", "Policy"]);
+    }
+
+    #[test]
+    fn test_shared_analyzer_update() {
+        let shared = SharedAnalyzer::default();
+        assert_eq!(shared.keywords("연차휴가 절차"), vec!["연차휴가", "절차"]);
+
+        let mut settings = shared.settings();
+        settings.ko.stopwords.push("절차".to_string());
+        shared.update(settings.clone());
+        assert_eq!(
+            shared.keywords("연차휴가 절차"),
+            vec!["연차휴가".to_string()]
+        );
+        assert_eq!(shared.settings(), settings);
+    }
+}
@@ -0,0 +1,214 @@
+//! Answer personas
+//!
+//! The answer prompt opens with a fixed assistant role. A persona replaces
+//! that role and adds tone, refusal and source attribution rules, so each
+//! department can be answered in its own voice. Personas are kept in a
+//! [`PromptRegistry`] that can be changed at runtime and are selected by the
+//! department of the asking user.
+//!
+//! Author: hephaex@gmail.com
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// System prompt settings for the users of some departments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Persona {
+    /// Unique name (taken from the path when stored through the admin API)
+    #[serde(default)]
+    pub name: String,
+
+    /// Departments answered with this persona; a persona without
+    /// departments answers users no other persona matches
+    #[serde(default)]
+    pub departments: Vec<String>,
+
+    /// Assistant role replacing the default role line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+
+    /// Tone of the answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tone: Option<String>,
+
+    /// Questions to decline and how to decline them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refusal: Option<String>,
+
+    /// How the answer signs off its sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_signature: Option<String>,
+}
+
+impl Persona {
+    /// Create a persona that changes nothing, for all departments
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            departments: Vec::new(),
+            role: None,
+            tone: None,
+            refusal: None,
+            source_signature: None,
+        }
+    }
+
+    /// Whether the persona answers users no other persona matches
+    pub fn is_default(&self) -> bool {
+        self.departments.is_empty()
+    }
+
+    /// Whether the persona answers users of a department
+    pub fn applies_to(&self, department: &str) -> bool {
+        self.departments
+            .iter()
+            .any(|d| d.trim().eq_ignore_ascii_case(department.trim()))
+    }
+
+    /// Rules added after the role line: tone, refusal, source signature
+    pub fn rules(&self) -> impl Iterator<Item = &str> {
+        [&self.tone, &self.refusal, &self.source_signature]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|rule| !rule.trim().is_empty())
+    }
+}
+
+/// Thread-safe registry of personas, keyed by name
+#[derive(Debug, Default)]
+pub struct PromptRegistry {
+    personas: RwLock<BTreeMap<String, Persona>>,
+}
+
+impl PromptRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry from personas; later personas replace earlier ones
+    /// with the same name
+    pub fn from_personas(personas: impl IntoIterator<Item = Persona>) -> Self {
+        let registry = Self::new();
+        for persona in personas {
+            registry.upsert(persona);
+        }
+        registry
+    }
+
+    /// Add or replace a persona, returning the one it replaced
+    pub fn upsert(&self, persona: Persona) -> Option<Persona> {
+        self.personas
+            .write()
+            .expect("prompt registry poisoned")
+            .insert(persona.name.clone(), persona)
+    }
+
+    /// Remove a persona; returns whether it was registered
+    pub fn remove(&self, name: &str) -> bool {
+        self.personas
+            .write()
+            .expect("prompt registry poisoned")
+            .remove(name)
+            .is_some()
+    }
+
+    /// Persona by name
+    pub fn get(&self, name: &str) -> Option<Persona> {
+        let personas = self.personas.read().expect("prompt registry poisoned");
+        personas.get(name).cloned()
+    }
+
+    /// All personas, ordered by name
+    pub fn personas(&self) -> Vec<Persona> {
+        let personas = self.personas.read().expect("prompt registry poisoned");
+        personas.values().cloned().collect()
+    }
+
+    /// Number of personas
+    pub fn len(&self) -> usize {
+        self.personas
+            .read()
+            .expect("prompt registry poisoned")
+            .len()
+    }
+
+    /// Check if the registry has no personas
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Persona answering a user of `department`
+    ///
+    /// The first persona (by name) listing the department wins; users
+    /// without a department or without a matching persona get the first
+    /// default persona, if any.
+    pub fn select(&self, department: Option<&str>) -> Option<Persona> {
+        let personas = self.personas.read().expect("prompt registry poisoned");
+        department
+            .and_then(|department| personas.values().find(|p| p.applies_to(department)))
+            .or_else(|| personas.values().find(|p| p.is_default()))
+            .cloned()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> PromptRegistry {
+        PromptRegistry::from_personas([
+            Persona {
+                departments: vec!["인사팀".to_string(), "HR".to_string()],
+                role: Some("당신은 인사팀 규정 안내 담당자입니다.".to_string()),
+                refusal: Some(
+                    "개인 급여 문의는 답하지 말고 인사팀 담당자를 안내하세요.".to_string(),
+                ),
+                ..Persona::new("hr")
+            },
+            Persona {
+                tone: Some("존댓말로 간결하게 답변하세요.".to_string()),
+                ..Persona::new("default")
+            },
+        ])
+    }
+
+    #[test]
+    fn test_select_by_department_then_default() {
+        let registry = registry();
+
+        assert_eq!(registry.select(Some("인사팀")).unwrap().name, "hr");
+        assert_eq!(registry.select(Some("hr")).unwrap().name, "hr");
+        assert_eq!(registry.select(Some("재무팀")).unwrap().name, "default");
+        assert_eq!(registry.select(None).unwrap().name, "default");
+
+        assert!(registry.remove("default"));
+        assert!(registry.select(None).is_none());
+        assert!(PromptRegistry::new().select(Some("인사팀")).is_none());
+    }
+
+    #[test]
+    fn test_upsert_replaces_and_rules_skip_blank() {
+        let registry = registry();
+        let replaced = registry.upsert(Persona {
+            tone: Some("친근하게 답변하세요.".to_string()),
+            source_signature: Some("  ".to_string()),
+            ..Persona::new("default")
+        });
+
+        assert!(replaced.unwrap().tone.unwrap().contains("존댓말"));
+        assert_eq!(registry.len(), 2);
+        let persona = registry.get("default").unwrap();
+        assert_eq!(
+            persona.rules().collect::<Vec<_>>(),
+            vec!["친근하게 답변하세요."]
+        );
+        assert_eq!(registry.get("hr").unwrap().rules().count(), 1);
+    }
+}
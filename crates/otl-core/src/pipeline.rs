@@ -0,0 +1,435 @@
+//! Ingestion pipeline definitions
+//!
+//! Different document classes need different processing: scans need OCR,
+//! personnel records need PII redaction, a manual needs no extraction. A
+//! pipeline is an ordered list of stages with their configs, declared in
+//! YAML and selected per file by file type and collection:
+//!
+//! ```yaml
+//! pipelines:
+//!   - name: scans
+//!     match:
+//!       file_types: [png, jpg, tiff]
+//!     stages:
+//!       - stage: ocr
+//!       - stage: pii-redact
+//!       - stage: chunk
+//!         config: { chunk_size: 800 }
+//!       - stage: embed
+//!       - stage: index
+//!   - name: default
+//!     stages: [{ stage: parse }, { stage: chunk }, { stage: embed }, { stage: index }]
+//! ```
+//!
+//! The first pipeline whose match accepts a file is used; an empty match
+//! list accepts anything. [`PipelineRunner`] executes a definition with
+//! stage implementations supplied by the caller, so the definitions do not
+//! depend on any parser, extractor or store.
+//!
+//! Author: hephaex@gmail.com
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{OtlError, Result};
+
+/// Processing stage of an ingestion pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StageKind {
+    /// Parse the file with the parser for its type
+    Parse,
+    /// Read the file with OCR (if no earlier stage produced text)
+    Ocr,
+    /// Replace personal information in the document text
+    PiiRedact,
+    /// Split the document into chunks
+    Chunk,
+    /// Extract entities and relations into the verification queue
+    Extract,
+    /// Embed the chunks
+    Embed,
+    /// Store the document, its chunks and their vectors
+    Index,
+}
+
+impl StageKind {
+    /// Position in the processing order; stages of equal rank may come in
+    /// either order
+    fn rank(self) -> u8 {
+        match self {
+            Self::Parse => 0,
+            Self::Ocr => 1,
+            Self::PiiRedact => 2,
+            Self::Chunk => 3,
+            Self::Extract | Self::Embed => 4,
+            Self::Index => 5,
+        }
+    }
+}
+
+impl std::fmt::Display for StageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Parse => "parse",
+            Self::Ocr => "ocr",
+            Self::PiiRedact => "pii-redact",
+            Self::Chunk => "chunk",
+            Self::Extract => "extract",
+            Self::Embed => "embed",
+            Self::Index => "index",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One stage of a pipeline and its config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageDefinition {
+    pub stage: StageKind,
+    /// Stage-specific settings; omitted settings take their defaults
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub config: serde_json::Value,
+}
+
+impl StageDefinition {
+    pub fn new(stage: StageKind) -> Self {
+        Self {
+            stage,
+            config: serde_json::Value::Null,
+        }
+    }
+
+    /// Settings of the stage, defaults if none are given
+    pub fn settings<T: DeserializeOwned + Default>(&self) -> Result<T> {
+        if self.config.is_null() {
+            return Ok(T::default());
+        }
+        serde_json::from_value(self.config.clone()).map_err(|e| {
+            OtlError::ConfigError(format!("Invalid config of {} stage: {e}", self.stage))
+        })
+    }
+}
+
+/// Files a pipeline applies to; an empty list accepts any value
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineMatch {
+    /// File extensions, without the dot (case-insensitive)
+    #[serde(default)]
+    pub file_types: Vec<String>,
+    #[serde(default)]
+    pub collections: Vec<String>,
+}
+
+impl PipelineMatch {
+    fn accepts(&self, file_type: &str, collection: Option<&str>) -> bool {
+        let type_matches = self.file_types.is_empty()
+            || self
+                .file_types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(file_type));
+        let collection_matches = self.collections.is_empty()
+            || collection.is_some_and(|c| self.collections.iter().any(|m| m == c));
+        type_matches && collection_matches
+    }
+}
+
+/// Named sequence of stages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineDefinition {
+    pub name: String,
+    #[serde(default, rename = "match")]
+    pub matches: PipelineMatch,
+    pub stages: Vec<StageDefinition>,
+}
+
+impl PipelineDefinition {
+    pub fn new(name: impl Into<String>, stages: impl IntoIterator<Item = StageKind>) -> Self {
+        Self {
+            name: name.into(),
+            matches: PipelineMatch::default(),
+            stages: stages.into_iter().map(StageDefinition::new).collect(),
+        }
+    }
+
+    /// Restrict the pipeline to files of these types
+    pub fn for_file_types(mut self, file_types: &[&str]) -> Self {
+        self.matches.file_types = file_types.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Check that the stages can run in their order
+    ///
+    /// A pipeline reads the file first (parse and/or ocr), chunks before it
+    /// extracts or embeds, and ends by indexing the embedded chunks. Each
+    /// stage appears at most once.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| {
+            Err(OtlError::ConfigError(format!(
+                "Invalid pipeline {}: {message}",
+                self.name
+            )))
+        };
+        let kinds: Vec<StageKind> = self.stages.iter().map(|s| s.stage).collect();
+        if let Some(pair) = kinds.windows(2).find(|w| w[1].rank() < w[0].rank()) {
+            return invalid(format!("{} must come before {}", pair[1], pair[0]));
+        }
+        if let Some(kind) = kinds
+            .iter()
+            .enumerate()
+            .find_map(|(i, k)| kinds[..i].contains(k).then_some(k))
+        {
+            return invalid(format!("{kind} appears more than once"));
+        }
+        if !kinds
+            .iter()
+            .any(|k| matches!(k, StageKind::Parse | StageKind::Ocr))
+        {
+            return invalid("no parse or ocr stage reads the file".to_string());
+        }
+        for required in [StageKind::Chunk, StageKind::Embed, StageKind::Index] {
+            if !kinds.contains(&required) {
+                return invalid(format!("{required} stage is missing"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pipelines of an installation, in match order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineSet {
+    pub pipelines: Vec<PipelineDefinition>,
+}
+
+impl Default for PipelineSet {
+    /// Images are read with OCR, everything else is parsed
+    fn default() -> Self {
+        use StageKind::*;
+        Self {
+            pipelines: vec![
+                PipelineDefinition::new("scans", [Ocr, Chunk, Embed, Index])
+                    .for_file_types(&["png", "jpg", "jpeg", "tif", "tiff"]),
+                PipelineDefinition::new("default", [Parse, Chunk, Embed, Index]),
+            ],
+        }
+    }
+}
+
+impl PipelineSet {
+    /// Parse and validate pipeline definitions
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let set: Self = serde_yaml::from_str(yaml)
+            .map_err(|e| OtlError::ConfigError(format!("Invalid pipeline definitions: {e}")))?;
+        set.validate()?;
+        Ok(set)
+    }
+
+    /// Load pipeline definitions from a YAML file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            OtlError::ConfigError(format!("Failed to read {}: {e}", path.display()))
+        })?;
+        Self::from_yaml(&content)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (i, pipeline) in self.pipelines.iter().enumerate() {
+            if self.pipelines[..i].iter().any(|p| p.name == pipeline.name) {
+                return Err(OtlError::ConfigError(format!(
+                    "Pipeline {} is defined twice",
+                    pipeline.name
+                )));
+            }
+            pipeline.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Pipeline for a file of `file_type` (its extension) in `collection`
+    pub fn select(&self, file_type: &str, collection: Option<&str>) -> Option<&PipelineDefinition> {
+        self.pipelines
+            .iter()
+            .find(|p| p.matches.accepts(file_type, collection))
+    }
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+/// Implementation of a stage over the ingestion state `C`
+#[async_trait]
+pub trait Stage<C: Send>: Send + Sync {
+    async fn run(&self, context: &mut C) -> Result<()>;
+}
+
+/// Stages of a pipeline definition, ready to run
+pub struct PipelineRunner<C> {
+    name: String,
+    stages: Vec<(StageKind, Box<dyn Stage<C>>)>,
+}
+
+impl<C: Send> PipelineRunner<C> {
+    /// Instantiate the stages of a definition
+    ///
+    /// `build_stage` returns the implementation of a stage, or `None` to
+    /// leave it out (a dry run leaves out the stages that write).
+    pub fn build(
+        definition: &PipelineDefinition,
+        mut build_stage: impl FnMut(&StageDefinition) -> Result<Option<Box<dyn Stage<C>>>>,
+    ) -> Result<Self> {
+        let mut stages = Vec::with_capacity(definition.stages.len());
+        for stage in &definition.stages {
+            if let Some(implementation) = build_stage(stage)? {
+                stages.push((stage.stage, implementation));
+            }
+        }
+        Ok(Self {
+            name: definition.name.clone(),
+            stages,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Stages that will run, in order
+    pub fn stages(&self) -> impl Iterator<Item = StageKind> + '_ {
+        self.stages.iter().map(|(kind, _)| *kind)
+    }
+
+    /// Run the stages in order, stopping at the first failure
+    pub async fn run(&self, context: &mut C) -> Result<()> {
+        for (kind, stage) in &self.stages {
+            stage.run(context).await.map_err(|e| {
+                OtlError::Other(
+                    anyhow::Error::new(e)
+                        .context(format!("{kind} stage of pipeline {} failed", self.name)),
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIPELINES: &str = r#"
+pipelines:
+  - name: personnel
+    match:
+      collections: [personnel]
+    stages:
+      - stage: parse
+      - stage: pii-redact
+        config:
+          marker: "[개인정보]"
+      - stage: chunk
+      - stage: extract
+      - stage: embed
+      - stage: index
+  - name: default
+    stages: [{ stage: parse }, { stage: chunk }, { stage: embed }, { stage: index }]
+"#;
+
+    #[test]
+    fn test_parse_and_select() {
+        let set = PipelineSet::from_yaml(PIPELINES).unwrap();
+        let personnel = set.select("PDF", Some("personnel")).unwrap();
+        assert_eq!(personnel.name, "personnel");
+        assert_eq!(personnel.stages[1].stage, StageKind::PiiRedact);
+        assert_eq!(personnel.stages[1].config["marker"], "[개인정보]");
+        assert_eq!(set.select("pdf", None).unwrap().name, "default");
+
+        let defaults = PipelineSet::default();
+        assert_eq!(defaults.select("TIFF", None).unwrap().name, "scans");
+        assert_eq!(defaults.select("docx", None).unwrap().name, "default");
+        assert!(defaults.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_stage_order() {
+        use StageKind::*;
+        let check = |stages: Vec<StageKind>| {
+            PipelineDefinition::new("p", stages)
+                .validate()
+                .map_err(|e| e.to_string())
+        };
+
+        assert!(check(vec![Parse, Ocr, PiiRedact, Chunk, Embed, Extract, Index]).is_ok());
+        assert_eq!(
+            check(vec![Parse, Embed, Chunk, Index]).unwrap_err(),
+            "Configuration error: Invalid pipeline p: chunk must come before embed"
+        );
+        assert_eq!(
+            check(vec![Chunk, Embed, Index]).unwrap_err(),
+            "Configuration error: Invalid pipeline p: no parse or ocr stage reads the file"
+        );
+        assert_eq!(
+            check(vec![Parse, Chunk, Embed]).unwrap_err(),
+            "Configuration error: Invalid pipeline p: index stage is missing"
+        );
+        assert!(check(vec![Parse, Chunk, Chunk, Embed, Index]).is_err());
+        assert!(
+            PipelineSet::from_yaml("pipelines:\n  - name: x\n    stages: [{ stage: ner }]")
+                .is_err()
+        );
+    }
+
+    struct Push(&'static str);
+
+    #[async_trait]
+    impl Stage<Vec<&'static str>> for Push {
+        async fn run(&self, context: &mut Vec<&'static str>) -> Result<()> {
+            if self.0 == "fail" {
+                return Err(OtlError::ValidationError("no text".to_string()));
+            }
+            context.push(self.0);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runner_runs_stages_in_order() {
+        let definition = &PipelineSet::default().pipelines[1];
+        let runner = PipelineRunner::build(definition, |stage| {
+            Ok(match stage.stage {
+                StageKind::Index => None,
+                StageKind::Parse => Some(Box::new(Push("parse")) as Box<dyn Stage<_>>),
+                StageKind::Chunk => Some(Box::new(Push("chunk"))),
+                _ => Some(Box::new(Push("embed"))),
+            })
+        })
+        .unwrap();
+        assert_eq!(
+            runner.stages().collect::<Vec<_>>(),
+            vec![StageKind::Parse, StageKind::Chunk, StageKind::Embed]
+        );
+
+        let mut trace = Vec::new();
+        runner.run(&mut trace).await.unwrap();
+        assert_eq!(trace, vec!["parse", "chunk", "embed"]);
+
+        let failing = PipelineRunner::build(definition, |_| {
+            Ok(Some(Box::new(Push("fail")) as Box<dyn Stage<_>>))
+        })
+        .unwrap();
+        let error = failing.run(&mut trace).await.unwrap_err().to_string();
+        assert_eq!(error, "parse stage of pipeline default failed");
+
+        let settings: serde_json::Map<String, serde_json::Value> =
+            StageDefinition::new(StageKind::Chunk).settings().unwrap();
+        assert!(settings.is_empty());
+    }
+}
@@ -0,0 +1,438 @@
+//! Knowledge spaces
+//!
+//! A knowledge space groups the documents of some departments so that a
+//! question is answered from them alone, instead of from everything the
+//! user may read. A query searches the space it names (or everything for
+//! `global`); otherwise it is routed to the spaces whose topics appear in
+//! the question, then to the space listing the user's department in
+//! `default_for`. Routed and default searches fall back to all readable
+//! documents when the spaces hold nothing relevant. Spaces are kept in a
+//! [`SpaceRegistry`] that can be changed at runtime, like answer personas.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::{DocumentAcl, OtlError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// Reserved space name searching all readable documents
+pub const GLOBAL_SPACE: &str = "global";
+
+/// Documents of some departments, searched together
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnowledgeSpace {
+    /// Unique name (taken from the path when stored through the admin API)
+    #[serde(default)]
+    pub name: String,
+
+    /// Shown to users choosing a space
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Departments whose documents belong to the space
+    #[serde(default)]
+    pub departments: Vec<String>,
+
+    /// Also search documents without a department (company-wide rules)
+    #[serde(default = "default_include_unassigned")]
+    pub include_unassigned: bool,
+
+    /// User departments searching this space by default
+    #[serde(default)]
+    pub default_for: Vec<String>,
+
+    /// Question keywords routing queries to this space
+    #[serde(default)]
+    pub topics: Vec<String>,
+
+    /// Persona answering questions in this space, overriding the one
+    /// selected by the user's department
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+}
+
+fn default_include_unassigned() -> bool {
+    true
+}
+
+impl KnowledgeSpace {
+    /// Create a space of the given departments
+    pub fn new(name: impl Into<String>, departments: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            departments,
+            include_unassigned: true,
+            default_for: Vec::new(),
+            topics: Vec::new(),
+            persona: None,
+        }
+    }
+
+    /// Whether users of a department search this space by default
+    pub fn is_default_for(&self, department: &str) -> bool {
+        contains(&self.default_for, department)
+    }
+
+    /// Number of topics appearing in a lowercased question
+    fn topic_hits(&self, question: &str) -> usize {
+        self.topics
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty() && question.contains(t.as_str()))
+            .count()
+    }
+}
+
+fn contains(departments: &[String], department: &str) -> bool {
+    departments
+        .iter()
+        .any(|d| d.trim().eq_ignore_ascii_case(department.trim()))
+}
+
+/// Departments a query retrieves from, on top of the ACL check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetrievalScope {
+    /// Spaces the scope comes from
+    pub spaces: Vec<String>,
+
+    /// Departments whose documents are searched
+    pub departments: Vec<String>,
+
+    /// Also search documents without a department
+    pub include_unassigned: bool,
+
+    /// Search all readable documents when the scope admits no results
+    #[serde(default)]
+    pub fallback: bool,
+}
+
+impl RetrievalScope {
+    /// Scope covering the documents of all `spaces` (`None` without spaces)
+    pub fn of(spaces: &[KnowledgeSpace], fallback: bool) -> Option<Self> {
+        if spaces.is_empty() {
+            return None;
+        }
+        let mut departments: Vec<String> = Vec::new();
+        for department in spaces.iter().flat_map(|s| &s.departments) {
+            if !contains(&departments, department) {
+                departments.push(department.trim().to_string());
+            }
+        }
+        Some(Self {
+            spaces: spaces.iter().map(|s| s.name.clone()).collect(),
+            departments,
+            include_unassigned: spaces.iter().any(|s| s.include_unassigned),
+            fallback,
+        })
+    }
+
+    /// Whether a document with this ACL is inside the scope
+    pub fn admits(&self, acl: &DocumentAcl) -> bool {
+        match acl.department.as_deref().map(str::trim) {
+            Some(department) if !department.is_empty() => contains(&self.departments, department),
+            _ => self.include_unassigned,
+        }
+    }
+
+    /// Whether a document belongs to one of the scope's departments
+    /// (documents without a department never do)
+    pub fn holds(&self, acl: &DocumentAcl) -> bool {
+        acl.department
+            .as_deref()
+            .is_some_and(|department| contains(&self.departments, department))
+    }
+}
+
+/// Thread-safe registry of knowledge spaces, keyed by name
+#[derive(Debug, Default)]
+pub struct SpaceRegistry {
+    spaces: RwLock<BTreeMap<String, KnowledgeSpace>>,
+}
+
+impl SpaceRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry from spaces; later spaces replace earlier ones with
+    /// the same name
+    pub fn from_spaces(spaces: impl IntoIterator<Item = KnowledgeSpace>) -> Self {
+        let registry = Self::new();
+        for space in spaces {
+            registry.upsert(space);
+        }
+        registry
+    }
+
+    /// Add or replace a space, returning the one it replaced
+    pub fn upsert(&self, space: KnowledgeSpace) -> Option<KnowledgeSpace> {
+        self.spaces
+            .write()
+            .expect("space registry poisoned")
+            .insert(space.name.clone(), space)
+    }
+
+    /// Remove a space; returns whether it was registered
+    pub fn remove(&self, name: &str) -> bool {
+        self.spaces
+            .write()
+            .expect("space registry poisoned")
+            .remove(name)
+            .is_some()
+    }
+
+    /// Space by name
+    pub fn get(&self, name: &str) -> Option<KnowledgeSpace> {
+        let spaces = self.spaces.read().expect("space registry poisoned");
+        spaces.get(name).cloned()
+    }
+
+    /// All spaces, ordered by name
+    pub fn spaces(&self) -> Vec<KnowledgeSpace> {
+        let spaces = self.spaces.read().expect("space registry poisoned");
+        spaces.values().cloned().collect()
+    }
+
+    /// Number of spaces
+    pub fn len(&self) -> usize {
+        self.spaces.read().expect("space registry poisoned").len()
+    }
+
+    /// Check if the registry has no spaces
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Space searched by default by a user of `department`
+    ///
+    /// The first space (by name) listing the department in `default_for`
+    /// wins; users without a department or a default space search globally.
+    pub fn select_default(&self, department: Option<&str>) -> Option<KnowledgeSpace> {
+        let department = department?;
+        let spaces = self.spaces.read().expect("space registry poisoned");
+        spaces
+            .values()
+            .find(|s| s.is_default_for(department))
+            .cloned()
+    }
+
+    /// Spaces whose topics appear in the question, most matching topics
+    /// first
+    pub fn route(&self, question: &str) -> Vec<KnowledgeSpace> {
+        let question = question.to_lowercase();
+        let spaces = self.spaces.read().expect("space registry poisoned");
+        let mut routed: Vec<(usize, &KnowledgeSpace)> = spaces
+            .values()
+            .map(|s| (s.topic_hits(&question), s))
+            .filter(|(hits, _)| *hits > 0)
+            .collect();
+        routed.sort_by_key(|(hits, _)| std::cmp::Reverse(*hits));
+        routed.into_iter().map(|(_, s)| s.clone()).collect()
+    }
+
+    /// Spaces a query searches
+    ///
+    /// A requested space is searched alone and [`GLOBAL_SPACE`] searches
+    /// all readable documents; unknown names are rejected. Without a
+    /// request the question is routed by topic, then to the default space
+    /// of the user's department, and either may fall back to a global
+    /// search.
+    pub fn resolve(
+        &self,
+        requested: Option<&str>,
+        question: &str,
+        department: Option<&str>,
+    ) -> Result<SpaceRoute> {
+        match requested.map(str::trim).filter(|name| !name.is_empty()) {
+            Some(name) if name.eq_ignore_ascii_case(GLOBAL_SPACE) => Ok(SpaceRoute::default()),
+            Some(name) => {
+                let space = self.get(name).ok_or_else(|| {
+                    OtlError::ValidationError(format!("Unknown knowledge space: {name}"))
+                })?;
+                Ok(SpaceRoute {
+                    spaces: vec![space],
+                    fallback: false,
+                })
+            }
+            None => {
+                let mut spaces = self.route(question);
+                if spaces.is_empty() {
+                    spaces.extend(self.select_default(department));
+                }
+                Ok(SpaceRoute {
+                    spaces,
+                    fallback: true,
+                })
+            }
+        }
+    }
+}
+
+/// Spaces chosen for a query
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpaceRoute {
+    /// Spaces to search, best match first (none searches globally)
+    pub spaces: Vec<KnowledgeSpace>,
+
+    /// Search all readable documents when the spaces admit no results
+    pub fallback: bool,
+}
+
+impl SpaceRoute {
+    /// Retrieval restriction of the route (`None` searches globally)
+    pub fn scope(&self) -> Option<RetrievalScope> {
+        RetrievalScope::of(&self.spaces, self.fallback)
+    }
+
+    /// Persona of the best matching space naming one
+    pub fn persona(&self) -> Option<&str> {
+        self.spaces.iter().find_map(|s| s.persona.as_deref())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acl(department: Option<&str>) -> DocumentAcl {
+        DocumentAcl {
+            department: department.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn registry() -> SpaceRegistry {
+        SpaceRegistry::from_spaces([
+            KnowledgeSpace {
+                default_for: vec!["인사팀".to_string()],
+                persona: Some("hr".to_string()),
+                ..KnowledgeSpace::new("hr", vec!["인사팀".to_string(), "총무팀".to_string()])
+            },
+            KnowledgeSpace {
+                include_unassigned: false,
+                default_for: vec!["재무팀".to_string(), "Finance".to_string()],
+                ..KnowledgeSpace::new("finance", vec!["재무팀".to_string()])
+            },
+        ])
+    }
+
+    fn scope(registry: &SpaceRegistry, name: &str) -> RetrievalScope {
+        RetrievalScope::of(&[registry.get(name).unwrap()], false).unwrap()
+    }
+
+    #[test]
+    fn test_scope_admits_space_departments() {
+        let registry = registry();
+        let hr = scope(&registry, "hr");
+        assert!(hr.admits(&acl(Some("인사팀"))));
+        assert!(hr.admits(&acl(Some(" 총무팀 "))));
+        assert!(hr.admits(&acl(None)));
+        assert!(hr.admits(&acl(Some(""))));
+        assert!(hr.holds(&acl(Some("총무팀"))) && !hr.holds(&acl(None)));
+        assert!(!hr.admits(&acl(Some("재무팀"))));
+
+        let finance = scope(&registry, "finance");
+        assert!(finance.admits(&acl(Some("재무팀"))));
+        assert!(!finance.admits(&acl(None)));
+
+        let both = RetrievalScope::of(&registry.spaces(), true).unwrap();
+        assert_eq!(both.spaces, vec!["finance", "hr"]);
+        assert_eq!(both.departments.len(), 3);
+        assert!(both.admits(&acl(Some("재무팀"))) && both.admits(&acl(None)));
+        assert!(RetrievalScope::of(&[], true).is_none());
+    }
+
+    #[test]
+    fn test_select_default_by_department() {
+        let registry = registry();
+        assert_eq!(registry.select_default(Some("인사팀")).unwrap().name, "hr");
+        assert_eq!(
+            registry.select_default(Some("finance")).unwrap().name,
+            "finance"
+        );
+        assert!(registry.select_default(Some("영업팀")).is_none());
+        assert!(registry.select_default(None).is_none());
+
+        assert!(registry.remove("hr"));
+        assert!(registry.select_default(Some("인사팀")).is_none());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_requested_global_and_unknown() {
+        let registry = registry();
+        let route = registry
+            .resolve(Some("finance"), "연차 신청", Some("인사팀"))
+            .unwrap();
+        assert_eq!(route.spaces[0].name, "finance");
+        assert!(!route.fallback);
+        assert!(registry
+            .resolve(Some("GLOBAL"), "연차 신청", Some("인사팀"))
+            .unwrap()
+            .scope()
+            .is_none());
+        assert!(registry.resolve(Some("it"), "연차 신청", None).is_err());
+
+        let route = registry
+            .resolve(Some(" "), "회의실 예약", Some("인사팀"))
+            .unwrap();
+        assert_eq!(route.spaces[0].name, "hr");
+        assert!(route.fallback);
+        assert_eq!(route.persona(), Some("hr"));
+    }
+
+    #[test]
+    fn test_route_by_topic_before_department_default() {
+        let registry = registry();
+        registry.upsert(KnowledgeSpace {
+            topics: vec!["VPN".to_string(), "노트북".to_string()],
+            ..KnowledgeSpace::new("it", vec!["정보시스템팀".to_string()])
+        });
+        registry.upsert(KnowledgeSpace {
+            topics: vec!["연차".to_string(), "휴가".to_string()],
+            ..registry.get("hr").unwrap()
+        });
+
+        let route = registry
+            .resolve(None, "재택 근무 시 vpn 접속 방법은?", Some("인사팀"))
+            .unwrap();
+        assert_eq!(route.scope().unwrap().spaces, vec!["it"]);
+        assert!(route.persona().is_none());
+
+        let names = |question: &str| -> Vec<String> {
+            registry
+                .route(question)
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        };
+        assert_eq!(names("연차휴가 중 노트북 반납"), vec!["hr", "it"]);
+        assert!(names("법인카드 한도").is_empty());
+
+        let route = registry
+            .resolve(None, "법인카드 한도", Some("재무팀"))
+            .unwrap();
+        assert_eq!(route.scope().unwrap().spaces, vec!["finance"]);
+        assert!(registry
+            .resolve(None, "법인카드 한도", None)
+            .unwrap()
+            .scope()
+            .is_none());
+    }
+
+    #[test]
+    fn test_deserialize_defaults_include_unassigned() {
+        let space: KnowledgeSpace =
+            serde_json::from_str(r#"{"name": "legal", "departments": ["법무팀"]}"#).unwrap();
+        assert!(space.include_unassigned);
+        assert!(space.default_for.is_empty());
+        assert!(space.persona.is_none());
+    }
+}
@@ -0,0 +1,247 @@
+//! Synonym registry for query expansion
+//!
+//! Groups of interchangeable terms ("연차", "연차휴가", "연가") shared by the
+//! search backends, so a query using one form also matches documents and
+//! entities that use another. Groups are keyed by a canonical term and can
+//! be changed at runtime.
+//!
+//! Author: hephaex@gmail.com
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::RwLock;
+
+/// A canonical term and its synonyms
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SynonymGroup {
+    /// Preferred form of the term
+    pub canonical: String,
+
+    /// Other forms with the same meaning
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl SynonymGroup {
+    /// Create a group
+    pub fn new<S: Into<String>>(
+        canonical: impl Into<String>,
+        aliases: impl IntoIterator<Item = S>,
+    ) -> Self {
+        Self {
+            canonical: canonical.into(),
+            aliases: aliases.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Groups {
+    /// Canonical term -> aliases
+    groups: BTreeMap<String, Vec<String>>,
+    /// Lowercase term (canonical or alias) -> canonical term
+    lookup: HashMap<String, String>,
+}
+
+impl Groups {
+    fn detach(&mut self, term: &str) {
+        let Some(canonical) = self.lookup.remove(&term.to_lowercase()) else {
+            return;
+        };
+        if let Some(aliases) = self.groups.get_mut(&canonical) {
+            aliases.retain(|a| !a.eq_ignore_ascii_case(term));
+        }
+    }
+}
+
+/// Thread-safe registry of synonym groups
+#[derive(Debug, Default)]
+pub struct SynonymRegistry {
+    inner: RwLock<Groups>,
+}
+
+impl SynonymRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry from groups
+    pub fn from_groups(groups: impl IntoIterator<Item = SynonymGroup>) -> Self {
+        let registry = Self::new();
+        for group in groups {
+            registry.add(group);
+        }
+        registry
+    }
+
+    /// Add a group, merging it into an existing group with the same
+    /// canonical term. An alias registered elsewhere moves to this group.
+    pub fn add(&self, group: SynonymGroup) {
+        let mut inner = self.inner.write().expect("synonym registry poisoned");
+        let canonical = group.canonical.trim().to_string();
+        if canonical.is_empty() {
+            return;
+        }
+        if !inner.groups.contains_key(&canonical) {
+            inner.detach(&canonical);
+            inner.groups.insert(canonical.clone(), Vec::new());
+        }
+        inner
+            .lookup
+            .insert(canonical.to_lowercase(), canonical.clone());
+
+        for alias in group.aliases {
+            let alias = alias.trim().to_string();
+            if alias.is_empty() || alias.eq_ignore_ascii_case(&canonical) {
+                continue;
+            }
+            if inner.groups.contains_key(&alias) {
+                // A canonical term cannot also be an alias of another group
+                continue;
+            }
+            inner.detach(&alias);
+            inner.lookup.insert(alias.to_lowercase(), canonical.clone());
+            if let Some(aliases) = inner.groups.get_mut(&canonical) {
+                aliases.push(alias);
+            }
+        }
+    }
+
+    /// Remove a term. Removing a canonical term drops its whole group.
+    /// Returns whether the term was registered.
+    pub fn remove(&self, term: &str) -> bool {
+        let mut inner = self.inner.write().expect("synonym registry poisoned");
+        let Some(canonical) = inner.lookup.get(&term.to_lowercase()).cloned() else {
+            return false;
+        };
+        if canonical.eq_ignore_ascii_case(term) {
+            if let Some(aliases) = inner.groups.remove(&canonical) {
+                for alias in aliases {
+                    inner.lookup.remove(&alias.to_lowercase());
+                }
+            }
+            inner.lookup.remove(&canonical.to_lowercase());
+        } else {
+            inner.detach(term);
+        }
+        true
+    }
+
+    /// All groups, ordered by canonical term
+    pub fn groups(&self) -> Vec<SynonymGroup> {
+        let inner = self.inner.read().expect("synonym registry poisoned");
+        inner
+            .groups
+            .iter()
+            .map(|(canonical, aliases)| SynonymGroup::new(canonical.clone(), aliases.clone()))
+            .collect()
+    }
+
+    /// Number of groups
+    pub fn len(&self) -> usize {
+        self.inner
+            .read()
+            .expect("synonym registry poisoned")
+            .groups
+            .len()
+    }
+
+    /// Check if the registry has no groups
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Canonical form of a term, if it belongs to a group
+    pub fn canonical(&self, term: &str) -> Option<String> {
+        let inner = self.inner.read().expect("synonym registry poisoned");
+        inner.lookup.get(&term.to_lowercase()).cloned()
+    }
+
+    /// Other members of the term's group (empty for unknown terms)
+    pub fn synonyms(&self, term: &str) -> Vec<String> {
+        let inner = self.inner.read().expect("synonym registry poisoned");
+        let Some(canonical) = inner.lookup.get(&term.to_lowercase()) else {
+            return Vec::new();
+        };
+        std::iter::once(canonical)
+            .chain(inner.groups.get(canonical).into_iter().flatten())
+            .filter(|t| !t.eq_ignore_ascii_case(term))
+            .cloned()
+            .collect()
+    }
+
+    /// Synonyms of the given keywords that are not keywords themselves,
+    /// in keyword order and without duplicates
+    pub fn expand(&self, keywords: &[String]) -> Vec<String> {
+        let mut seen: HashSet<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
+        keywords
+            .iter()
+            .flat_map(|keyword| self.synonyms(keyword))
+            .filter(|term| seen.insert(term.to_lowercase()))
+            .collect()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> SynonymRegistry {
+        SynonymRegistry::from_groups([
+            SynonymGroup::new("연차", ["연차휴가", "연가"]),
+            SynonymGroup::new("인사팀", ["인사부", "HR팀"]),
+        ])
+    }
+
+    #[test]
+    fn test_expand_finds_group_members() {
+        let registry = registry();
+        assert_eq!(
+            registry.expand(&["연가".to_string(), "신청".to_string()]),
+            vec!["연차", "연차휴가"]
+        );
+        assert_eq!(registry.synonyms("hr팀"), vec!["인사팀", "인사부"]);
+        assert_eq!(registry.canonical("연차휴가").as_deref(), Some("연차"));
+        assert_eq!(
+            registry.expand(&["연차".to_string(), "연가".to_string()]),
+            vec!["연차휴가"]
+        );
+    }
+
+    #[test]
+    fn test_add_merges_and_moves_aliases() {
+        let registry = registry();
+        registry.add(SynonymGroup::new("연차", ["유급휴가"]));
+        registry.add(SynonymGroup::new("인사", ["인사부"]));
+
+        let groups = registry.groups();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            groups
+                .iter()
+                .find(|g| g.canonical == "연차")
+                .unwrap()
+                .aliases,
+            vec!["연차휴가", "연가", "유급휴가"]
+        );
+        assert_eq!(registry.canonical("인사부").as_deref(), Some("인사"));
+        assert_eq!(registry.synonyms("인사팀"), vec!["HR팀"]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let registry = registry();
+        assert!(registry.remove("연가"));
+        assert_eq!(registry.synonyms("연차"), vec!["연차휴가"]);
+
+        assert!(registry.remove("인사팀"));
+        assert!(registry.canonical("인사부").is_none());
+        assert!(!registry.remove("없는용어"));
+        assert_eq!(registry.len(), 1);
+    }
+}
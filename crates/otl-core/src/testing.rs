@@ -0,0 +1,411 @@
+//! In-memory fakes for tests
+//!
+//! Deterministic stand-ins for the search, LLM and metadata traits so
+//! orchestrator and handler tests run without Qdrant, SurrealDB, PostgreSQL
+//! or an LLM endpoint. Enabled with the `test-utils` feature.
+//!
+//! Author: hephaex@gmail.com
+
+use std::sync::{Mutex, RwLock};
+
+use futures::stream::{self, BoxStream, StreamExt};
+use uuid::Uuid;
+
+use crate::{
+    DocumentAcl, DocumentChunk, DocumentMetadata, LlmClient, MetadataRepository, OtlError, Result,
+    SearchBackend, SearchResult, SearchResultType, SourceReference,
+};
+
+// ============================================================================
+// Search Backend
+// ============================================================================
+
+/// Search backend over a fixed list of passages
+///
+/// A passage scores the fraction of query terms it contains, so results are
+/// reproducible; ties keep insertion order and passages without any query
+/// term are not returned.
+pub struct InMemorySearchBackend {
+    name: String,
+    result_type: SearchResultType,
+    passages: RwLock<Vec<SearchResult>>,
+    failure: Option<String>,
+}
+
+impl InMemorySearchBackend {
+    /// Create an empty backend returning results of `result_type`
+    pub fn new(name: impl Into<String>, result_type: SearchResultType) -> Self {
+        Self {
+            name: name.into(),
+            result_type,
+            passages: RwLock::new(Vec::new()),
+            failure: None,
+        }
+    }
+
+    /// Add a passage with the default (internal) ACL
+    pub fn with_passage(self, content: impl Into<String>, source: SourceReference) -> Self {
+        self.with_passage_acl(content, source, DocumentAcl::default())
+    }
+
+    /// Add a passage with an explicit ACL
+    pub fn with_passage_acl(
+        self,
+        content: impl Into<String>,
+        source: SourceReference,
+        acl: DocumentAcl,
+    ) -> Self {
+        self.insert(content, source, acl);
+        self
+    }
+
+    /// Fail every search with a `SearchError`, as an unreachable service would
+    pub fn failing(mut self, message: impl Into<String>) -> Self {
+        self.failure = Some(message.into());
+        self
+    }
+
+    /// Add a passage
+    pub fn insert(&self, content: impl Into<String>, source: SourceReference, acl: DocumentAcl) {
+        self.passages.write().unwrap().push(SearchResult {
+            content: content.into(),
+            score: 0.0,
+            source,
+            acl,
+            result_type: self.result_type.clone(),
+        });
+    }
+
+    /// Number of stored passages
+    pub fn len(&self) -> usize {
+        self.passages.read().unwrap().len()
+    }
+
+    /// Whether no passages are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Lowercased query terms without surrounding punctuation
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|t| {
+            t.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Fraction of `terms` contained in `content`
+fn term_overlap(terms: &[String], content: &str) -> f32 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let content = content.to_lowercase();
+    let matched = terms
+        .iter()
+        .filter(|t| content.contains(t.as_str()))
+        .count();
+    matched as f32 / terms.len() as f32
+}
+
+#[async_trait::async_trait]
+impl SearchBackend for InMemorySearchBackend {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        if let Some(message) = &self.failure {
+            return Err(OtlError::SearchError(format!("{}: {message}", self.name)));
+        }
+
+        let terms = query_terms(query);
+        let mut results: Vec<SearchResult> = self
+            .passages
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|passage| {
+                let score = term_overlap(&terms, &passage.content);
+                (score > 0.0).then(|| SearchResult {
+                    score,
+                    ..passage.clone()
+                })
+            })
+            .collect();
+        // Stable sort: equal scores stay in insertion order
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// ============================================================================
+// LLM Client
+// ============================================================================
+
+/// LLM client answering with canned responses
+///
+/// The first registered pattern contained in the prompt selects the
+/// response; prompts matching no pattern get the default response. Every
+/// prompt is recorded for assertions.
+pub struct MockLlmClient {
+    responses: Vec<(String, String)>,
+    default_response: String,
+    prompts: Mutex<Vec<String>>,
+}
+
+impl MockLlmClient {
+    /// Create a client answering every prompt with `default_response`
+    pub fn new(default_response: impl Into<String>) -> Self {
+        Self {
+            responses: Vec::new(),
+            default_response: default_response.into(),
+            prompts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answer prompts containing `pattern` with `response`
+    pub fn with_response(
+        mut self,
+        pattern: impl Into<String>,
+        response: impl Into<String>,
+    ) -> Self {
+        self.responses.push((pattern.into(), response.into()));
+        self
+    }
+
+    /// Prompts received so far, in call order
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().unwrap().clone()
+    }
+
+    /// Number of generation calls so far
+    pub fn call_count(&self) -> usize {
+        self.prompts.lock().unwrap().len()
+    }
+
+    fn respond(&self, prompt: &str) -> String {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        self.responses
+            .iter()
+            .find(|(pattern, _)| prompt.contains(pattern.as_str()))
+            .map_or(&self.default_response, |(_, response)| response)
+            .clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmClient for MockLlmClient {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        Ok(self.respond(prompt))
+    }
+
+    /// Stream the response word by word
+    async fn generate_stream(&self, prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
+        let chunks: Vec<Result<String>> = self
+            .respond(prompt)
+            .split_inclusive(' ')
+            .map(|chunk| Ok(chunk.to_string()))
+            .collect();
+        Ok(stream::iter(chunks).boxed())
+    }
+}
+
+// ============================================================================
+// Metadata Repository
+// ============================================================================
+
+/// Metadata repository over in-memory documents and chunks
+#[derive(Default)]
+pub struct InMemoryMetadataStore {
+    documents: RwLock<Vec<DocumentMetadata>>,
+    chunks: RwLock<Vec<DocumentChunk>>,
+}
+
+impl InMemoryMetadataStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a document
+    pub fn with_document(self, doc: DocumentMetadata) -> Self {
+        self.documents.write().unwrap().push(doc);
+        self
+    }
+
+    /// Add a chunk
+    pub fn with_chunk(self, chunk: DocumentChunk) -> Self {
+        self.chunks.write().unwrap().push(chunk);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl MetadataRepository for InMemoryMetadataStore {
+    async fn create_document(&self, doc: &DocumentMetadata) -> Result<Uuid> {
+        self.documents.write().unwrap().push(doc.clone());
+        Ok(doc.id)
+    }
+
+    async fn get_document(&self, id: Uuid) -> Result<Option<DocumentMetadata>> {
+        let documents = self.documents.read().unwrap();
+        Ok(documents.iter().find(|d| d.id == id).cloned())
+    }
+
+    async fn get_documents(&self, ids: &[Uuid]) -> Result<Vec<DocumentMetadata>> {
+        let documents = self.documents.read().unwrap();
+        Ok(documents
+            .iter()
+            .filter(|d| ids.contains(&d.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn list_documents(&self, limit: i64, offset: i64) -> Result<Vec<DocumentMetadata>> {
+        let documents = self.documents.read().unwrap();
+        Ok(documents
+            .iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_document(&self, doc: &DocumentMetadata) -> Result<()> {
+        let mut documents = self.documents.write().unwrap();
+        match documents.iter_mut().find(|d| d.id == doc.id) {
+            Some(stored) => {
+                *stored = doc.clone();
+                Ok(())
+            }
+            None => Err(OtlError::NotFound(format!("Document {}", doc.id))),
+        }
+    }
+
+    async fn delete_document(&self, id: Uuid) -> Result<()> {
+        self.documents.write().unwrap().retain(|d| d.id != id);
+        self.chunks.write().unwrap().retain(|c| c.document_id != id);
+        Ok(())
+    }
+
+    async fn create_chunk(&self, chunk: &DocumentChunk) -> Result<Uuid> {
+        self.chunks.write().unwrap().push(chunk.clone());
+        Ok(chunk.id)
+    }
+
+    async fn get_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>> {
+        let mut chunks: Vec<DocumentChunk> = self
+            .chunks
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|c| c.document_id == document_id)
+            .cloned()
+            .collect();
+        chunks.sort_by_key(|c| c.chunk_index);
+        Ok(chunks)
+    }
+
+    async fn get_chunks_at(&self, positions: &[(Uuid, u32)]) -> Result<Vec<DocumentChunk>> {
+        let chunks = self.chunks.read().unwrap();
+        Ok(chunks
+            .iter()
+            .filter(|c| positions.contains(&(c.document_id, c.chunk_index)))
+            .cloned()
+            .collect())
+    }
+
+    async fn update_chunk_vector_id(&self, chunk_id: Uuid, vector_id: &str) -> Result<()> {
+        let mut chunks = self.chunks.write().unwrap();
+        if let Some(chunk) = chunks.iter_mut().find(|c| c.id == chunk_id) {
+            chunk.vector_id = Some(vector_id.to_string());
+        }
+        Ok(())
+    }
+
+    async fn corrupted_chunks(&self, _document_ids: &[Uuid]) -> Result<Vec<(Uuid, u32)>> {
+        Ok(Vec::new())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backend() -> InMemorySearchBackend {
+        InMemorySearchBackend::new("memory", SearchResultType::Vector)
+            .with_passage("연차휴가는 15일이다", SourceReference::new(Uuid::new_v4()))
+            .with_passage("병가 신청 절차", SourceReference::new(Uuid::new_v4()))
+            .with_passage("연차휴가 신청 절차", SourceReference::new(Uuid::new_v4()))
+            .with_passage("출장 신청 절차", SourceReference::new(Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_search_scores_term_overlap() {
+        let results = backend().search("연차휴가 신청 절차?", 10).await.unwrap();
+
+        let contents: Vec<_> = results.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec![
+                "연차휴가 신청 절차",
+                "병가 신청 절차",
+                "출장 신청 절차",
+                "연차휴가는 15일이다"
+            ]
+        );
+        assert_eq!(results[0].score, 1.0);
+        assert!(results
+            .iter()
+            .all(|r| r.result_type == SearchResultType::Vector));
+
+        let top = backend().search("연차휴가 신청 절차?", 2).await.unwrap();
+        assert_eq!(top.len(), 2);
+        assert!(backend().search("퇴직금", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failing_backend() {
+        let err = backend()
+            .failing("connection refused")
+            .search("병가", 5)
+            .await;
+        assert!(matches!(err, Err(OtlError::SearchError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mock_llm_canned_responses() {
+        let llm = MockLlmClient::new("모르겠습니다")
+            .with_response("연차휴가", "연차휴가는 15일입니다 [출처: 1]");
+
+        assert_eq!(
+            llm.generate("질문: 연차휴가 일수는?").await.unwrap(),
+            "연차휴가는 15일입니다 [출처: 1]"
+        );
+        assert_eq!(
+            llm.generate("질문: 퇴직금은?").await.unwrap(),
+            "모르겠습니다"
+        );
+
+        let chunks: Vec<String> = llm
+            .generate_stream("연차휴가")
+            .await
+            .unwrap()
+            .map(|c| c.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks, vec!["연차휴가는 ", "15일입니다 ", "[출처: ", "1]"]);
+        assert_eq!(llm.call_count(), 3);
+        assert_eq!(llm.prompts()[1], "질문: 퇴직금은?");
+    }
+}
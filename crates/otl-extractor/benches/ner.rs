@@ -0,0 +1,48 @@
+//! Benchmarks of rule-based entity and relation extraction
+//!
+//! Run with `cargo bench -p otl-extractor --bench ner`.
+//!
+//! Author: hephaex@gmail.com
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use otl_extractor::ner::RuleBasedNer;
+use otl_extractor::relation::RuleBasedRe;
+use otl_extractor::{EntityExtractor, RelationExtractor};
+
+const PARAGRAPH: &str =
+    "인사팀 김철수 과장은 2024-03-01부터 연차휴가 15일과 병가 3일을 신청했으며, \
+팀장의 승인 후 인사위원회에 보고된다. 육아휴직은 최대 1년까지 사용할 수 있고 \
+휴직 기간 중 급여는 지급되지 않는다. 총무팀 이영희 대리는 출장비 정산 규정에 따라 \
+30일 이내에 증빙 서류를 제출해야 한다.\n";
+
+/// Korean HR text of about `kib` KiB
+fn korean_text(kib: usize) -> String {
+    let repeat = (kib * 1024).div_ceil(PARAGRAPH.len());
+    PARAGRAPH.repeat(repeat)
+}
+
+fn bench_ner(c: &mut Criterion) {
+    let ner = RuleBasedNer::new();
+    let re = RuleBasedRe::new();
+
+    let mut group = c.benchmark_group("ner");
+    group.sample_size(20);
+    for kib in [16, 256] {
+        let text = korean_text(kib);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::new("entities", kib), &text, |b, text| {
+            b.iter(|| ner.extract(black_box(text)).unwrap())
+        });
+    }
+    group.finish();
+
+    let text = korean_text(16);
+    let entities = ner.extract(&text).unwrap();
+    c.bench_function("relations/16", |b| {
+        b.iter(|| re.extract(black_box(&text), black_box(&entities)).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_ner);
+criterion_main!(benches);
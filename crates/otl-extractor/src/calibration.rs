@@ -0,0 +1,94 @@
+//! Calibrated extractors
+//!
+//! Wraps an entity or relation extractor so that the confidences it reports
+//! are passed through a [`Calibrator`] fitted from HITL review outcomes
+//! (see [`VerificationQueue`](crate::hitl::VerificationQueue)). Auto-approval
+//! thresholds then refer to observed precision instead of a hand-picked
+//! constant.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_core::{Calibrator, Result};
+
+use crate::{EntityExtractor, ExtractedEntity, ExtractedRelation, RelationExtractor};
+
+/// Extractor whose confidences are calibrated
+pub struct Calibrated<E> {
+    inner: E,
+    calibrator: Calibrator,
+}
+
+impl<E> Calibrated<E> {
+    /// Wrap an extractor with a calibrator
+    pub fn new(inner: E, calibrator: Calibrator) -> Self {
+        Self { inner, calibrator }
+    }
+
+    /// Calibrator applied to confidences
+    pub fn calibrator(&self) -> &Calibrator {
+        &self.calibrator
+    }
+
+    /// Replace the calibrator (e.g. after refitting)
+    pub fn set_calibrator(&mut self, calibrator: Calibrator) {
+        self.calibrator = calibrator;
+    }
+
+    /// The wrapped extractor
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+}
+
+impl<E: EntityExtractor> EntityExtractor for Calibrated<E> {
+    fn extract(&self, text: &str) -> Result<Vec<ExtractedEntity>> {
+        let mut entities = self.inner.extract(text)?;
+        for entity in &mut entities {
+            entity.confidence = self.calibrator.apply(entity.confidence);
+        }
+        Ok(entities)
+    }
+}
+
+impl<E: RelationExtractor> RelationExtractor for Calibrated<E> {
+    fn extract(&self, text: &str, entities: &[ExtractedEntity]) -> Result<Vec<ExtractedRelation>> {
+        let mut relations = self.inner.extract(text, entities)?;
+        for relation in &mut relations {
+            relation.confidence = self.calibrator.apply(relation.confidence);
+        }
+        Ok(relations)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ner::RuleBasedNer;
+
+    #[test]
+    fn test_calibrated_entity_extractor() {
+        let text = "연차휴가는 15일이 기본 부여됩니다.";
+        let raw = RuleBasedNer::new().extract(text).unwrap();
+
+        let calibrator = Calibrator::Isotonic {
+            points: vec![(0.0, 0.1), (1.0, 0.5)],
+        };
+        let ner = Calibrated::new(RuleBasedNer::new(), calibrator.clone());
+        let calibrated = ner.extract(text).unwrap();
+
+        assert_eq!(raw.len(), calibrated.len());
+        for (r, c) in raw.iter().zip(&calibrated) {
+            assert_eq!(r.text, c.text);
+            assert!((c.confidence - calibrator.apply(r.confidence)).abs() < 1e-6);
+            assert!(c.confidence <= 0.5);
+        }
+
+        let identity = Calibrated::new(RuleBasedNer::new(), Calibrator::Identity);
+        let unchanged = identity.extract(text).unwrap();
+        assert_eq!(unchanged[0].confidence, raw[0].confidence);
+    }
+}
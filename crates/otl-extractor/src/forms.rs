@@ -0,0 +1,580 @@
+//! Form templates
+//!
+//! Application forms (휴가신청서, 경비청구서, ...) have fixed layouts: a
+//! title, printed captions and labelled fields. A [`FormTemplate`] lists
+//! the phrases that identify a form type and, for each field, its printed
+//! label, the region of the form the label sits in and an optional value
+//! pattern:
+//!
+//! ```json
+//! {
+//!   "name": "leave_request",
+//!   "class": "LeaveRequest",
+//!   "markers": ["휴가신청서", "휴가 기간"],
+//!   "fields": [
+//!     {"property": "applicant", "label": "성명", "region": {"after": "신청인", "before": "대리인"},
+//!      "required": true, "relation": {"predicate": "submittedBy", "class": "Employee"}},
+//!     {"property": "leaveType", "label": "휴가종류", "aliases": ["휴가 구분"]},
+//!     {"property": "startDate", "label": "시작일", "pattern": "\\d{4}-\\d{2}-\\d{2}"}
+//!   ]
+//! }
+//! ```
+//!
+//! A [`FormRegistry`] recognizes which template a document follows by the
+//! share of its markers and labels found in the text. Labels and markers
+//! match with or without spaces between their characters, since forms
+//! often space out captions (`성 명`). A value is the text after its label
+//! (past `:` or a table cell border) up to the end of the cell, the line
+//! or the next label; a label alone on its line takes the next line.
+//!
+//! Values found at a fixed place in a known layout are reliable, so the
+//! items of a complete form get a high confidence. Forms missing a
+//! required field get a low one and are left to reviewers.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use otl_core::{OntologyClass, OtlError, Result};
+
+use crate::tabular::{relation_definition, MappedEntity};
+use crate::{ExtractedEntity, ExtractedRelation};
+
+/// Extractor name recorded for items taken from forms
+pub const FORM_EXTRACTOR: &str = "form";
+
+/// Share of markers and labels a document needs to be recognized
+pub const MIN_DETECTION_SCORE: f32 = 0.6;
+
+/// Confidence of items from a complete form
+const FORM_CONFIDENCE: f32 = 0.97;
+
+/// Confidence of items from a form missing required fields
+const INCOMPLETE_CONFIDENCE: f32 = 0.5;
+
+// ============================================================================
+// Templates
+// ============================================================================
+
+/// Layout of a form type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormTemplate {
+    /// Registry name
+    pub name: String,
+    /// Class of the entity a filled-in form becomes
+    pub class: String,
+    /// Phrases identifying the form (title, fixed captions)
+    pub markers: Vec<String>,
+    pub fields: Vec<FormField>,
+}
+
+/// Labelled field of a form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormField {
+    /// Property the value is stored in
+    pub property: String,
+    /// Printed label
+    pub label: String,
+    /// Other printings of the label
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Part of the form the label is looked for in (default: all of it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<FieldRegion>,
+    /// Regex a value must match; its first group (or the whole match) is
+    /// kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// The value names a related entity rather than a literal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation: Option<FieldRelation>,
+}
+
+/// Part of a form between two captions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldRegion {
+    /// Region starts after this caption (default: start of the form)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    /// Region ends at this caption (default: end of the form)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+}
+
+/// Relation a field value stands for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldRelation {
+    pub predicate: String,
+    /// Class of the named entity
+    pub class: String,
+}
+
+impl FormTemplate {
+    /// Check the template against the ontology
+    ///
+    /// The name, markers and fields must be present, patterns must compile
+    /// and relation fields must use a property of the form class whose
+    /// range admits the related class.
+    pub fn validate(&self, classes: &[OntologyClass]) -> Result<()> {
+        let classes: HashMap<&str, &OntologyClass> =
+            classes.iter().map(|c| (c.id.as_str(), c)).collect();
+        let mut problems = Vec::new();
+
+        if self.name.trim().is_empty() {
+            problems.push("name is empty".to_string());
+        }
+        if !classes.contains_key(self.class.as_str()) {
+            problems.push(format!("unknown class '{}'", self.class));
+        }
+        if self.markers.iter().all(|m| m.trim().is_empty()) {
+            problems.push("no markers".to_string());
+        }
+        if self.fields.is_empty() {
+            problems.push("no fields".to_string());
+        }
+        for field in &self.fields {
+            if let Err(problem) = self.check_field(&classes, field) {
+                problems.push(problem);
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(OtlError::ValidationError(format!(
+                "Invalid form template: {}",
+                problems.join("; ")
+            )))
+        }
+    }
+
+    fn check_field(
+        &self,
+        classes: &HashMap<&str, &OntologyClass>,
+        field: &FormField,
+    ) -> std::result::Result<(), String> {
+        if field.property.trim().is_empty() || field.label.trim().is_empty() {
+            return Err(format!(
+                "field '{}' needs a property and a label",
+                field.label
+            ));
+        }
+        if let Some(pattern) = &field.pattern {
+            Regex::new(pattern)
+                .map_err(|e| format!("invalid pattern for '{}': {e}", field.property))?;
+        }
+        let Some(relation) = &field.relation else {
+            return Ok(());
+        };
+        relation_definition(classes, &self.class, &relation.predicate, &relation.class)?;
+        Ok(())
+    }
+
+    /// Share of the template's markers and field labels found in `text`
+    ///
+    /// Zero when no marker is found, whatever the labels.
+    pub fn score(&self, text: &str) -> f32 {
+        let markers: Vec<&String> = self
+            .markers
+            .iter()
+            .filter(|m| !m.trim().is_empty())
+            .collect();
+        let found_markers = markers
+            .iter()
+            .filter(|m| find_spaced(text, m, 0..text.len()).is_some())
+            .count();
+        if found_markers == 0 {
+            return 0.0;
+        }
+        let found_labels = self
+            .fields
+            .iter()
+            .filter(|f| {
+                f.labels()
+                    .any(|label| find_spaced(text, label, 0..text.len()).is_some())
+            })
+            .count();
+        (found_markers + found_labels) as f32 / (markers.len() + self.fields.len()) as f32
+    }
+
+    /// Pull the field values of a filled-in form
+    ///
+    /// The form entity is named by the first marker found (the form title)
+    /// and carries the literal field values as properties; relation fields
+    /// add the named entities and their relations.
+    pub fn extract(&self, text: &str) -> FormExtraction {
+        let labels: Vec<&str> = self.fields.iter().flat_map(FormField::labels).collect();
+        let mut values = Vec::new();
+        let mut missing = Vec::new();
+        for field in &self.fields {
+            match find_value(text, field, &labels) {
+                Some(span) => values.push((field, span)),
+                None if field.required => missing.push(field.property.clone()),
+                None => {}
+            }
+        }
+        let confidence = if missing.is_empty() {
+            FORM_CONFIDENCE
+        } else {
+            INCOMPLETE_CONFIDENCE
+        };
+
+        let title = self
+            .markers
+            .iter()
+            .filter(|m| !m.trim().is_empty())
+            .find_map(|m| find_spaced(text, m, 0..text.len()))
+            .unwrap_or(0..0);
+        let form = span_entity(text, &self.class, title, confidence);
+
+        let mut properties = BTreeMap::new();
+        let mut entities = Vec::new();
+        let mut relations = Vec::new();
+        for (field, span) in values {
+            let value = text[span.clone()].to_string();
+            let Some(relation) = &field.relation else {
+                properties.insert(field.property.clone(), value);
+                continue;
+            };
+            let object = span_entity(text, &relation.class, span, confidence);
+            relations.push(ExtractedRelation {
+                subject: form.clone(),
+                predicate: relation.predicate.clone(),
+                object: object.clone(),
+                confidence,
+            });
+            entities.push(MappedEntity {
+                entity: object,
+                properties: BTreeMap::new(),
+            });
+        }
+        entities.insert(
+            0,
+            MappedEntity {
+                entity: form,
+                properties,
+            },
+        );
+
+        FormExtraction {
+            template: self.name.clone(),
+            entities,
+            relations,
+            missing,
+            confidence,
+        }
+    }
+}
+
+impl FormField {
+    /// Label and aliases
+    fn labels(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.label.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// Entity for a byte span of the form text
+fn span_entity(text: &str, class: &str, span: Range<usize>, confidence: f32) -> ExtractedEntity {
+    let char_start = text[..span.start].chars().count();
+    ExtractedEntity {
+        text: text[span.clone()].to_string(),
+        entity_type: class.to_string(),
+        start: span.start,
+        end: span.end,
+        char_start,
+        char_end: char_start + text[span].chars().count(),
+        confidence,
+    }
+}
+
+/// Regex matching a phrase with optional spaces between its characters
+fn spaced_regex(phrase: &str) -> Option<Regex> {
+    let chars: Vec<String> = phrase
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| regex::escape(&c.to_string()))
+        .collect();
+    if chars.is_empty() {
+        return None;
+    }
+    Regex::new(&chars.join("[ \\t]*")).ok()
+}
+
+/// First match of a spaced phrase within `range` of `text`
+fn find_spaced(text: &str, phrase: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let found = spaced_regex(phrase)?.find(&text[range.clone()])?;
+    Some(range.start + found.start()..range.start + found.end())
+}
+
+/// Byte range of the form a field's label is looked for in
+fn region_range(text: &str, region: Option<&FieldRegion>) -> Range<usize> {
+    let Some(region) = region else {
+        return 0..text.len();
+    };
+    let start = region
+        .after
+        .as_deref()
+        .and_then(|after| find_spaced(text, after, 0..text.len()))
+        .map_or(0, |found| found.end);
+    let end = region
+        .before
+        .as_deref()
+        .and_then(|before| find_spaced(text, before, start..text.len()))
+        .map_or(text.len(), |found| found.start);
+    start..end
+}
+
+/// Byte span of a field's value
+fn find_value(text: &str, field: &FormField, labels: &[&str]) -> Option<Range<usize>> {
+    let region = region_range(text, field.region.as_ref());
+    let label = field
+        .labels()
+        .filter_map(|label| find_spaced(text, label, region.clone()))
+        .min_by_key(|found| found.start)?;
+
+    let mut span = cell_value(text, label.end, region.end, labels);
+    if span.is_empty() {
+        // Label alone on its line: the value is on the next one
+        let next_line = text[label.end..region.end]
+            .find('\n')
+            .map(|i| label.end + i + 1)?;
+        span = cell_value(text, next_line, region.end, labels);
+    }
+    if span.is_empty() {
+        return None;
+    }
+
+    match &field.pattern {
+        Some(pattern) => {
+            let captures = Regex::new(pattern).ok()?.captures(&text[span.clone()])?;
+            let found = captures.get(1).or_else(|| captures.get(0))?;
+            Some(span.start + found.start()..span.start + found.end())
+        }
+        None => Some(span),
+    }
+}
+
+/// Trimmed value starting at `start`: past separators, up to the end of
+/// the cell, the line or the next label
+fn cell_value(text: &str, start: usize, limit: usize, labels: &[&str]) -> Range<usize> {
+    let rest = &text[start..limit];
+    let skipped = rest.len() - rest.trim_start_matches([':', '：', '|', ' ', '\t']).len();
+    let start = start + skipped;
+
+    let line = &text[start..limit];
+    let mut end = line.find(['\n', '|']).unwrap_or(line.len());
+    for label in labels {
+        if let Some(found) = find_spaced(line, label, 0..end) {
+            // Another field on the same line; a label at the very start is
+            // this field's empty value followed by the next field
+            end = end.min(found.start);
+        }
+    }
+
+    let value = &line[..end];
+    let trimmed = value.trim();
+    let offset = value.len() - value.trim_start().len();
+    start + offset..start + offset + trimmed.len()
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Recognized form type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormMatch<'a> {
+    pub template: &'a FormTemplate,
+    /// Share of markers and labels found
+    pub score: f32,
+}
+
+/// Templates a document is recognized against
+#[derive(Debug, Clone, Default)]
+pub struct FormRegistry {
+    templates: Vec<FormTemplate>,
+}
+
+impl FormRegistry {
+    pub fn new(templates: Vec<FormTemplate>) -> Self {
+        Self { templates }
+    }
+
+    pub fn templates(&self) -> &[FormTemplate] {
+        &self.templates
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FormTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// Best-scoring template reaching [`MIN_DETECTION_SCORE`]
+    ///
+    /// Ties go to the template registered first.
+    pub fn detect(&self, text: &str) -> Option<FormMatch<'_>> {
+        self.templates
+            .iter()
+            .map(|template| FormMatch {
+                template,
+                score: template.score(text),
+            })
+            .filter(|m| m.score >= MIN_DETECTION_SCORE)
+            .fold(None, |best: Option<FormMatch<'_>>, m| match best {
+                Some(best) if best.score >= m.score => Some(best),
+                _ => Some(m),
+            })
+    }
+}
+
+// ============================================================================
+// Results
+// ============================================================================
+
+/// Entities and relations of a filled-in form
+#[derive(Debug, Clone)]
+pub struct FormExtraction {
+    /// Template the form was read with
+    pub template: String,
+    /// Form entity first, then the entities named by relation fields
+    pub entities: Vec<MappedEntity>,
+    pub relations: Vec<ExtractedRelation>,
+    /// Required fields not found
+    pub missing: Vec<String>,
+    /// Confidence given to every item
+    pub confidence: f32,
+}
+
+impl FormExtraction {
+    /// Literal field values of the form entity
+    pub fn fields(&self) -> &BTreeMap<String, String> {
+        &self.entities[0].properties
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{Cardinality, DataType, PropertyDefinition};
+
+    const LEAVE_FORM: &str = "휴 가 신 청 서\n\
+        [신청인]\n\
+        성 명: 홍길동        소 속 | 인사팀\n\
+        휴가종류: 연차휴가\n\
+        시작일: 2026년 3월 2일 (2026-03-02)\n\
+        사유\n\
+        가족 여행\n\
+        [대리인]\n\
+        성 명: 김철수\n";
+
+    fn template() -> FormTemplate {
+        serde_json::from_value(serde_json::json!({
+            "name": "leave_request",
+            "class": "LeaveRequest",
+            "markers": ["휴가신청서", "신청인"],
+            "fields": [
+                {"property": "applicant", "label": "성명", "region": {"after": "신청인", "before": "대리인"},
+                 "required": true, "relation": {"predicate": "submittedBy", "class": "Employee"}},
+                {"property": "department", "label": "소속"},
+                {"property": "leaveType", "label": "휴가 종류", "aliases": ["휴가구분"]},
+                {"property": "startDate", "label": "시작일", "pattern": "\\d{4}-\\d{2}-\\d{2}"},
+                {"property": "reason", "label": "사유"},
+                {"property": "endDate", "label": "종료일"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_detect_and_extract_form() {
+        let expense = FormTemplate {
+            name: "expense_claim".to_string(),
+            class: "ExpenseClaim".to_string(),
+            markers: vec!["경비청구서".to_string()],
+            fields: template().fields,
+        };
+        let registry = FormRegistry::new(vec![expense, template()]);
+
+        let found = registry.detect(LEAVE_FORM).unwrap();
+        assert_eq!(found.template.name, "leave_request");
+        // Both markers and five of six labels
+        assert!((found.score - 7.0 / 8.0).abs() < 1e-6);
+        assert!(registry.detect("회의록\n참석자: 홍길동").is_none());
+
+        let form = found.template.extract(LEAVE_FORM);
+        assert!(form.missing.is_empty());
+        assert_eq!(form.confidence, FORM_CONFIDENCE);
+        let fields: Vec<(&str, &str)> = form
+            .fields()
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("department", "인사팀"),
+                ("leaveType", "연차휴가"),
+                ("reason", "가족 여행"),
+                ("startDate", "2026-03-02"),
+            ]
+        );
+
+        // The applicant comes from the 신청인 region, not the 대리인 one
+        assert_eq!(form.entities[0].entity.text, "휴 가 신 청 서");
+        assert_eq!(form.relations.len(), 1);
+        let applicant = &form.relations[0].object;
+        assert_eq!(applicant.text, "홍길동");
+        assert_eq!(applicant.entity_type, "Employee");
+        assert_eq!(&LEAVE_FORM[applicant.start..applicant.end], "홍길동");
+    }
+
+    #[test]
+    fn test_incomplete_form_and_validation() {
+        let form = template().extract("휴가신청서\n휴가종류: 병가\n");
+        assert_eq!(form.missing, vec!["applicant"]);
+        assert_eq!(form.confidence, INCOMPLETE_CONFIDENCE);
+        assert!(form.relations.is_empty());
+
+        let classes = vec![
+            OntologyClass {
+                id: "LeaveRequest".to_string(),
+                label: "휴가신청".to_string(),
+                description: None,
+                parent: None,
+                properties: vec![PropertyDefinition {
+                    name: "submittedBy".to_string(),
+                    data_type: DataType::ObjectReference("Employee".to_string()),
+                    cardinality: Cardinality::ZeroOrOne,
+                    range: Some("Employee".to_string()),
+                }],
+            },
+            OntologyClass {
+                id: "Employee".to_string(),
+                label: "직원".to_string(),
+                description: None,
+                parent: None,
+                properties: Vec::new(),
+            },
+        ];
+        assert!(template().validate(&classes).is_ok());
+
+        let mut bad = template();
+        bad.fields[0].relation = Some(FieldRelation {
+            predicate: "approvedBy".to_string(),
+            class: "Employee".to_string(),
+        });
+        bad.fields[3].pattern = Some("(".to_string());
+        let message = bad.validate(&classes).unwrap_err().to_string();
+        assert!(message.contains("LeaveRequest has no relation 'approvedBy'"));
+        assert!(message.contains("invalid pattern for 'startDate'"));
+    }
+}
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{ExtractedEntity, ExtractedRelation};
+use otl_core::CalibrationSample;
 
 // ============================================================================
 // Verification Status
@@ -272,6 +273,25 @@ impl VerificationQueue {
         self.relations.iter().find(|r| r.id == id)
     }
 
+    /// Calibration samples from human-reviewed entities
+    ///
+    /// Approved entities count as correct and rejected ones as incorrect;
+    /// auto-approved and pending items carry no human judgement and are skipped.
+    pub fn entity_calibration_samples(&self) -> Vec<CalibrationSample> {
+        self.entities
+            .iter()
+            .filter_map(|e| reviewed_sample(e.entity.confidence, e.status))
+            .collect()
+    }
+
+    /// Calibration samples from human-reviewed relations
+    pub fn relation_calibration_samples(&self) -> Vec<CalibrationSample> {
+        self.relations
+            .iter()
+            .filter_map(|r| reviewed_sample(r.relation.confidence, r.status))
+            .collect()
+    }
+
     /// Get statistics
     pub fn stats(&self) -> VerificationStats {
         VerificationStats {
@@ -311,6 +331,14 @@ impl VerificationQueue {
     }
 }
 
+fn reviewed_sample(confidence: f32, status: VerificationStatus) -> Option<CalibrationSample> {
+    match status {
+        VerificationStatus::Approved => Some(CalibrationSample::new(confidence, true)),
+        VerificationStatus::Rejected => Some(CalibrationSample::new(confidence, false)),
+        VerificationStatus::Pending | VerificationStatus::AutoApproved => None,
+    }
+}
+
 /// Verification statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerificationStats {
@@ -377,6 +405,8 @@ mod tests {
             entity_type: entity_type.to_string(),
             start: 0,
             end: text.len(),
+            char_start: 0,
+            char_end: text.chars().count(),
             confidence,
         }
     }
@@ -469,4 +499,26 @@ mod tests {
         assert_eq!(stats.auto_approved_entities, 1);
         assert_eq!(stats.pending_relations, 1);
     }
+
+    #[test]
+    fn test_calibration_samples_from_reviews() {
+        let mut queue = VerificationQueue::new().with_threshold(0.9);
+        let doc_id = Uuid::new_v4();
+
+        let approved = queue.add_entity(doc_id, create_entity("연차휴가", "AnnualLeave", 0.8));
+        let rejected = queue.add_entity(doc_id, create_entity("휴가", "AnnualLeave", 0.7));
+        queue.add_entity(doc_id, create_entity("병가", "SickLeave", 0.6)); // pending
+        queue.add_entity(doc_id, create_entity("진단서", "Document", 0.95)); // auto-approved
+        queue.approve_entity(approved, "reviewer@test.com", None);
+        queue.reject_entity(rejected, "reviewer@test.com", "Too generic");
+
+        assert_eq!(
+            queue.entity_calibration_samples(),
+            vec![
+                CalibrationSample::new(0.8, true),
+                CalibrationSample::new(0.7, false)
+            ]
+        );
+        assert!(queue.relation_calibration_samples().is_empty());
+    }
 }
@@ -8,15 +8,56 @@ use serde::{Deserialize, Serialize};
 use otl_core::Result;
 
 /// Extracted entity from text
+///
+/// `start`/`end` are byte offsets into the source text, suitable for slicing.
+/// `char_start`/`char_end` count characters and are what UIs should use for
+/// highlighting; see [`offsets`] for UTF-16 conversion.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractedEntity {
     pub text: String,
     pub entity_type: String,
+    /// Byte offset of the first character
     pub start: usize,
+    /// Byte offset past the last character
     pub end: usize,
+    /// Character offset of the first character
+    #[serde(default)]
+    pub char_start: usize,
+    /// Character offset past the last character
+    #[serde(default)]
+    pub char_end: usize,
     pub confidence: f32,
 }
 
+impl ExtractedEntity {
+    /// Create an entity from a byte range of the source text
+    ///
+    /// Returns `None` when the range is out of bounds or splits a character.
+    pub fn from_byte_span(
+        source: &str,
+        entity_type: impl Into<String>,
+        start: usize,
+        end: usize,
+        confidence: f32,
+    ) -> Option<Self> {
+        let (char_start, char_end) = offsets::byte_span_to_char(source, start, end)?;
+        Some(Self {
+            text: source[start..end].to_string(),
+            entity_type: entity_type.into(),
+            start,
+            end,
+            char_start,
+            char_end,
+            confidence,
+        })
+    }
+
+    /// UTF-16 code unit range of the entity in the source text
+    pub fn utf16_span(&self, source: &str) -> Option<(usize, usize)> {
+        offsets::byte_span_to_utf16(source, self.start, self.end)
+    }
+}
+
 /// Extracted relation between entities
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractedRelation {
@@ -36,8 +77,15 @@ pub trait RelationExtractor: Send + Sync {
     fn extract(&self, text: &str, entities: &[ExtractedEntity]) -> Result<Vec<ExtractedRelation>>;
 }
 
+pub mod calibration;
+pub mod forms;
 pub mod hitl;
 pub mod loader;
 pub mod metrics;
 pub mod ner;
+pub mod offsets;
+pub mod plugin;
 pub mod relation;
+pub mod tabular;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -7,7 +7,8 @@ use std::collections::HashMap;
 
 use uuid::Uuid;
 
-use otl_core::{Entity, SourceReference, Triple};
+use otl_core::structure::MENTIONS;
+use otl_core::{Entity, Provenance, SourceReference, Triple};
 
 use crate::hitl::{PendingEntity, PendingRelation, VerificationStatus};
 use crate::{ExtractedEntity, ExtractedRelation};
@@ -30,6 +31,14 @@ pub fn entity_to_core(extracted: &ExtractedEntity, document_id: Uuid) -> Entity
     entity
         .properties
         .insert("end".to_string(), serde_json::json!(extracted.end));
+    entity.properties.insert(
+        "char_start".to_string(),
+        serde_json::json!(extracted.char_start),
+    );
+    entity.properties.insert(
+        "char_end".to_string(),
+        serde_json::json!(extracted.char_end),
+    );
 
     entity
 }
@@ -63,6 +72,28 @@ pub fn relation_to_triple(
     )
 }
 
+/// Sentence of `text` containing both arguments of a relation
+///
+/// Uses the byte offsets of the subject and object; returns `None` if they
+/// do not point into `text`.
+pub fn relation_snippet(text: &str, relation: &ExtractedRelation) -> Option<String> {
+    let start = relation.subject.start.min(relation.object.start);
+    let end = relation.subject.end.max(relation.object.end);
+    text.get(start..end)?;
+
+    let is_break = |c: char| matches!(c, '.' | '?' | '!' | '\n' | '。');
+    let sentence_start = text[..start]
+        .rfind(is_break)
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let sentence_end = text[end..]
+        .find(is_break)
+        .map(|i| end + i + text[end + i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(text.len());
+
+    Some(text[sentence_start..sentence_end].trim().to_string())
+}
+
 // ============================================================================
 // Graph Loader
 // ============================================================================
@@ -102,6 +133,10 @@ pub struct GraphLoader {
     entities: Vec<Entity>,
     /// Prepared triples
     triples: Vec<Triple>,
+    /// Chunk -mentions-> entity edges for the document structure graph
+    mentions: Vec<Triple>,
+    /// Evidence for the prepared triples (one or more per triple)
+    provenance: Vec<Provenance>,
 }
 
 impl GraphLoader {
@@ -112,6 +147,8 @@ impl GraphLoader {
             entity_map: HashMap::new(),
             entities: Vec::new(),
             triples: Vec::new(),
+            mentions: Vec::new(),
+            provenance: Vec::new(),
         }
     }
 
@@ -139,19 +176,76 @@ impl GraphLoader {
             return None;
         }
 
-        Some(self.add_entity(&pending.entity))
+        let id = self.add_entity(&pending.entity);
+        if let Some(chunk_id) = pending.chunk_id {
+            self.add_mention(chunk_id, id, pending.entity.confidence);
+        }
+        Some(id)
+    }
+
+    /// Record that a chunk mentions an entity
+    pub fn add_mention(&mut self, chunk_id: Uuid, entity_id: Uuid, confidence: f32) {
+        if self
+            .mentions
+            .iter()
+            .any(|t| t.subject == chunk_id && t.object == entity_id)
+        {
+            return;
+        }
+        let source = SourceReference::new(self.document_id).with_confidence(confidence);
+        self.mentions.push(Triple::new(
+            chunk_id, MENTIONS, entity_id, source, confidence,
+        ));
     }
 
     /// Add an extracted relation
+    ///
+    /// A relation repeating an already prepared triple adds a provenance
+    /// record to that triple instead of a duplicate edge.
     pub fn add_relation(&mut self, relation: &ExtractedRelation) -> Option<Uuid> {
+        self.add_relation_with_snippet(relation, None)
+    }
+
+    /// Add an extracted relation, keeping the sentence it was found in
+    pub fn add_relation_from_text(
+        &mut self,
+        relation: &ExtractedRelation,
+        text: &str,
+    ) -> Option<Uuid> {
+        self.add_relation_with_snippet(relation, relation_snippet(text, relation))
+    }
+
+    fn add_relation_with_snippet(
+        &mut self,
+        relation: &ExtractedRelation,
+        snippet: Option<String>,
+    ) -> Option<Uuid> {
         // Look up subject and object entity IDs
-        let subject_id = self.entity_map.get(&relation.subject.text)?;
-        let object_id = self.entity_map.get(&relation.object.text)?;
+        let subject_id = *self.entity_map.get(&relation.subject.text)?;
+        let object_id = *self.entity_map.get(&relation.object.text)?;
+
+        let triple = relation_to_triple(relation, self.document_id, subject_id, object_id);
+        let mut provenance = triple.provenance();
+        provenance.source.offset =
+            Some(relation.subject.char_start.min(relation.object.char_start));
+        if let Some(snippet) = snippet {
+            provenance = provenance.with_snippet(snippet);
+        }
 
-        let triple = relation_to_triple(relation, self.document_id, *subject_id, *object_id);
-        let id = triple.id;
+        let existing = self.triples.iter().find(|t| {
+            t.subject == subject_id && t.predicate == triple.predicate && t.object == object_id
+        });
+        let id = match existing {
+            Some(t) => t.id,
+            None => {
+                let id = triple.id;
+                self.triples.push(triple);
+                id
+            }
+        };
 
-        self.triples.push(triple);
+        provenance.triple_id = id;
+        self.provenance.push(provenance);
         Some(id)
     }
 
@@ -176,6 +270,16 @@ impl GraphLoader {
         &self.triples
     }
 
+    /// Get the prepared chunk mentions
+    pub fn mentions(&self) -> &[Triple] {
+        &self.mentions
+    }
+
+    /// Get the evidence recorded for the prepared triples
+    pub fn provenance(&self) -> &[Provenance] {
+        &self.provenance
+    }
+
     /// Get the entity map
     pub fn entity_map(&self) -> &HashMap<String, Uuid> {
         &self.entity_map
@@ -191,6 +295,16 @@ impl GraphLoader {
         std::mem::take(&mut self.triples)
     }
 
+    /// Take ownership of chunk mentions
+    pub fn take_mentions(&mut self) -> Vec<Triple> {
+        std::mem::take(&mut self.mentions)
+    }
+
+    /// Take ownership of provenance records
+    pub fn take_provenance(&mut self) -> Vec<Provenance> {
+        std::mem::take(&mut self.provenance)
+    }
+
     /// Get load result summary
     pub fn result(&self) -> LoadResult {
         LoadResult {
@@ -247,6 +361,8 @@ mod tests {
             entity_type: entity_type.to_string(),
             start: 0,
             end: text.len(),
+            char_start: 0,
+            char_end: text.chars().count(),
             confidence: 0.9,
         }
     }
@@ -309,6 +425,51 @@ mod tests {
         assert_eq!(loader.triples().len(), 1);
     }
 
+    #[test]
+    fn test_repeated_relation_adds_provenance() {
+        let doc_id = Uuid::new_v4();
+        let mut loader = GraphLoader::new(doc_id);
+
+        let text = "휴가 규정. 병가 신청 시 진단서를 제출한다. 병가는 진단서가 필요하다.";
+        let entity_at = |name: &str, entity_type: &str, from: usize| {
+            let start = from + text[from..].find(name).unwrap();
+            ExtractedEntity::from_byte_span(text, entity_type, start, start + name.len(), 0.9)
+                .unwrap()
+        };
+
+        let first = create_relation(
+            entity_at("병가", "SickLeave", 0),
+            "requiresDocument",
+            entity_at("진단서", "Document", 0),
+        );
+        let second_from = text.find("병가는").unwrap();
+        let second = create_relation(
+            entity_at("병가", "SickLeave", second_from),
+            "requiresDocument",
+            entity_at("진단서", "Document", second_from),
+        );
+        loader.add_entity(&first.subject);
+        loader.add_entity(&first.object);
+
+        let id = loader.add_relation_from_text(&first, text).unwrap();
+        assert_eq!(loader.add_relation_from_text(&second, text), Some(id));
+
+        assert_eq!(loader.triples().len(), 1);
+        let snippets: Vec<_> = loader
+            .provenance()
+            .iter()
+            .map(|p| (p.triple_id, p.snippet.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            snippets,
+            vec![
+                (id, "병가 신청 시 진단서를 제출한다."),
+                (id, "병가는 진단서가 필요하다."),
+            ]
+        );
+        assert_eq!(loader.provenance()[0].source.offset, Some(7));
+    }
+
     #[test]
     fn test_load_approved_from_queue() {
         let mut queue = VerificationQueue::new().with_threshold(0.95);
@@ -323,7 +484,9 @@ mod tests {
             text: "진단서".to_string(),
             entity_type: "Document".to_string(),
             start: 0,
-            end: 6,
+            end: 9,
+            char_start: 0,
+            char_end: 3,
             confidence: 0.98, // Above threshold
         };
         queue.add_entity(doc_id, high_conf);
@@ -347,6 +510,35 @@ mod tests {
         assert_eq!(loader.entities().len(), 1); // Only auto-approved one
     }
 
+    #[test]
+    fn test_pending_entity_chunk_mentions() {
+        let doc_id = Uuid::new_v4();
+        let chunk_a = Uuid::new_v4();
+        let chunk_b = Uuid::new_v4();
+
+        let approved = |chunk_id| {
+            let mut pending = PendingEntity::new(doc_id, create_entity("연차휴가", "AnnualLeave"))
+                .with_chunk(chunk_id);
+            pending.status = VerificationStatus::Approved;
+            pending
+        };
+
+        let mut loader = GraphLoader::new(doc_id);
+        let id = loader.add_pending_entity(&approved(chunk_a)).unwrap();
+        loader.add_pending_entity(&approved(chunk_a));
+        loader.add_pending_entity(&approved(chunk_b));
+
+        assert_eq!(loader.entities().len(), 1);
+        let mentions: Vec<_> = loader.mentions().iter().map(|t| t.subject).collect();
+        assert_eq!(mentions, vec![chunk_a, chunk_b]);
+        assert!(loader
+            .mentions()
+            .iter()
+            .all(|t| t.predicate == MENTIONS && t.object == id));
+        // Mentions are structure edges, not extracted relations
+        assert_eq!(loader.result().relations_loaded, 0);
+    }
+
     #[test]
     fn test_load_result() {
         let doc_id = Uuid::new_v4();
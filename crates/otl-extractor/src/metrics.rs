@@ -362,6 +362,8 @@ mod tests {
             entity_type: entity_type.to_string(),
             start,
             end: start + text.len(),
+            char_start: start,
+            char_end: start + text.chars().count(),
             confidence: 0.9,
         }
     }
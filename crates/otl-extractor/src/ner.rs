@@ -10,8 +10,8 @@ use std::collections::{HashMap, HashSet};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use crate::{EntityExtractor, ExtractedEntity};
-use otl_core::Result;
+use crate::{offsets, EntityExtractor, ExtractedEntity};
+use otl_core::{Result, SynonymGroup};
 
 // ============================================================================
 // Entity Types for HR Domain
@@ -247,6 +247,18 @@ impl RuleBasedNer {
         self.add_term("재무팀", EntityType::Department, vec!["재무부", "경리팀"]);
     }
 
+    /// Dictionary terms with their aliases, for seeding a synonym registry
+    pub fn synonym_groups(&self) -> Vec<SynonymGroup> {
+        let mut groups: Vec<SynonymGroup> = self
+            .dictionary
+            .values()
+            .filter(|entry| !entry.aliases.is_empty())
+            .map(|entry| SynonymGroup::new(entry.term.clone(), entry.aliases.clone()))
+            .collect();
+        groups.sort_by(|a, b| a.canonical.cmp(&b.canonical));
+        groups
+    }
+
     /// Add a regex pattern
     fn add_pattern(&mut self, pattern: &str, entity_type: EntityType, confidence: f32) {
         if let Ok(regex) = Regex::new(pattern) {
@@ -278,15 +290,15 @@ impl RuleBasedNer {
         let mut entities = Vec::new();
 
         for (regex, entity_type, confidence) in &self.patterns {
-            for mat in regex.find_iter(text) {
-                entities.push(ExtractedEntity {
-                    text: mat.as_str().to_string(),
-                    entity_type: entity_type.to_string(),
-                    start: mat.start(),
-                    end: mat.end(),
-                    confidence: *confidence,
-                });
-            }
+            entities.extend(regex.find_iter(text).filter_map(|mat| {
+                ExtractedEntity::from_byte_span(
+                    text,
+                    entity_type.to_string(),
+                    mat.start(),
+                    mat.end(),
+                    *confidence,
+                )
+            }));
         }
 
         entities
@@ -295,33 +307,30 @@ impl RuleBasedNer {
     /// Extract entities using dictionary lookup
     fn extract_by_dictionary(&self, text: &str) -> Vec<ExtractedEntity> {
         let mut entities = Vec::new();
-        let text_lower = text.to_lowercase();
+        // Matches are found in the lowercased text and mapped back to `text`
+        let (text_lower, offsets) = offsets::lowercase_with_offsets(text);
+        let mut find = |term: &str, entity_type: &EntityType, confidence: f32| {
+            let term_lower = term.to_lowercase();
+            for (start, _) in text_lower.match_indices(&term_lower) {
+                let (start, end) = (offsets[start], offsets[start + term_lower.len()]);
+                entities.extend(ExtractedEntity::from_byte_span(
+                    text,
+                    entity_type.to_string(),
+                    start,
+                    end,
+                    confidence,
+                ));
+            }
+        };
 
         // Check each dictionary entry
         for (key, entry) in &self.dictionary {
             // Check main term
-            for (start, _) in text_lower.match_indices(&key.to_lowercase()) {
-                entities.push(ExtractedEntity {
-                    text: text[start..start + key.len()].to_string(),
-                    entity_type: entry.entity_type.to_string(),
-                    start,
-                    end: start + key.len(),
-                    confidence: 0.95,
-                });
-            }
+            find(key, &entry.entity_type, 0.95);
 
             // Check aliases
             for alias in &entry.aliases {
-                let alias_lower = alias.to_lowercase();
-                for (start, _) in text_lower.match_indices(&alias_lower) {
-                    entities.push(ExtractedEntity {
-                        text: text[start..start + alias.len()].to_string(),
-                        entity_type: entry.entity_type.to_string(),
-                        start,
-                        end: start + alias.len(),
-                        confidence: 0.9,
-                    });
-                }
+                find(alias, &entry.entity_type, 0.9);
             }
         }
 
@@ -461,18 +470,14 @@ impl LlmNer {
             .into_iter()
             .filter_map(|e| {
                 // Find the entity in original text
-                if let Some(start) = original_text.find(&e.text) {
-                    let len = e.text.len();
-                    Some(ExtractedEntity {
-                        text: e.text,
-                        entity_type: e.entity_type,
-                        start,
-                        end: start + len,
-                        confidence: e.confidence.unwrap_or(0.8),
-                    })
-                } else {
-                    None
-                }
+                let start = original_text.find(&e.text)?;
+                ExtractedEntity::from_byte_span(
+                    original_text,
+                    e.entity_type,
+                    start,
+                    start + e.text.len(),
+                    e.confidence.unwrap_or(0.8),
+                )
             })
             .collect()
     }
@@ -643,6 +648,48 @@ mod tests {
         assert!(types.contains(&"SickLeave") || types.contains(&"Document"));
     }
 
+    #[test]
+    fn test_synonym_groups_from_dictionary() {
+        let groups = RuleBasedNer::new().synonym_groups();
+        let annual = groups.iter().find(|g| g.canonical == "연차").unwrap();
+        assert!(annual.aliases.contains(&"연가".to_string()));
+        assert!(groups.iter().all(|g| !g.aliases.is_empty()));
+    }
+
+    #[test]
+    fn test_entity_char_offsets() {
+        let ner = RuleBasedNer::new();
+
+        let text = "İ 병가 신청시 진단서가 필요합니다.";
+        let entities = ner.extract(text).unwrap();
+
+        let document = entities.iter().find(|e| e.text == "진단서").unwrap();
+        assert_eq!(&text[document.start..document.end], "진단서");
+        assert_eq!((document.char_start, document.char_end), (9, 12));
+        let chars: String = text
+            .chars()
+            .skip(document.char_start)
+            .take(document.char_end - document.char_start)
+            .collect();
+        assert_eq!(chars, "진단서");
+    }
+
+    #[test]
+    fn test_dictionary_matches_ignore_unicode_case() {
+        let mut ner = RuleBasedNer::new();
+        ner.add_term("Équipe RH", EntityType::Department, vec!["ÉQUIPE-RH"]);
+
+        let text = "İ 문의는 équipe rh 또는 équipe-rh로 보내세요.";
+        let entities = ner.extract_by_dictionary(text);
+        let mut found: Vec<&str> = entities
+            .iter()
+            .filter(|e| e.entity_type == "Department")
+            .map(|e| &text[e.start..e.end])
+            .collect();
+        found.sort_unstable();
+        assert_eq!(found, ["équipe rh", "équipe-rh"]);
+    }
+
     #[test]
     fn test_entity_type_display() {
         assert_eq!(EntityType::AnnualLeave.to_string(), "AnnualLeave");
@@ -653,21 +700,11 @@ mod tests {
     fn test_hybrid_ner_merge() {
         let ner = HybridNer::new();
 
-        let rule_entities = vec![ExtractedEntity {
-            text: "연차".to_string(),
-            entity_type: "AnnualLeave".to_string(),
-            start: 0,
-            end: 2,
-            confidence: 0.9,
-        }];
-
-        let llm_entities = vec![ExtractedEntity {
-            text: "연차".to_string(),
-            entity_type: "AnnualLeave".to_string(),
-            start: 0,
-            end: 2,
-            confidence: 0.85,
-        }];
+        let rule_entities =
+            vec![ExtractedEntity::from_byte_span("연차", "AnnualLeave", 0, 6, 0.9).unwrap()];
+
+        let llm_entities =
+            vec![ExtractedEntity::from_byte_span("연차", "AnnualLeave", 0, 6, 0.85).unwrap()];
 
         let merged = ner.merge_entities(rule_entities, llm_entities);
         assert_eq!(merged.len(), 1);
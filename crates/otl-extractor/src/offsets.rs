@@ -0,0 +1,142 @@
+//! Text offset conversion
+//!
+//! Extractors work on byte offsets (what `str` slicing and `regex` use), but
+//! clients need character offsets to highlight Korean text and web clients
+//! index strings by UTF-16 code units. These helpers convert between the
+//! three. Conversions return `None` for offsets that are out of range or do
+//! not fall on a character boundary.
+//!
+//! Author: hephaex@gmail.com
+
+/// Convert a byte offset to a character (Unicode scalar) offset
+pub fn byte_to_char(text: &str, byte: usize) -> Option<usize> {
+    text.is_char_boundary(byte)
+        .then(|| text[..byte].chars().count())
+}
+
+/// Convert a character offset to a byte offset
+pub fn char_to_byte(text: &str, char_idx: usize) -> Option<usize> {
+    text.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .nth(char_idx)
+}
+
+/// Convert a byte offset to a UTF-16 code unit offset
+pub fn byte_to_utf16(text: &str, byte: usize) -> Option<usize> {
+    text.is_char_boundary(byte)
+        .then(|| text[..byte].chars().map(char::len_utf16).sum())
+}
+
+/// Convert a UTF-16 code unit offset to a byte offset
+pub fn utf16_to_byte(text: &str, utf16: usize) -> Option<usize> {
+    let mut units = 0;
+    for (i, c) in text.char_indices() {
+        if units == utf16 {
+            return Some(i);
+        }
+        units += c.len_utf16();
+        if units > utf16 {
+            return None;
+        }
+    }
+    (units == utf16).then_some(text.len())
+}
+
+/// Convert a byte range to a character range
+pub fn byte_span_to_char(text: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    if start > end {
+        return None;
+    }
+    let char_start = byte_to_char(text, start)?;
+    let char_end = char_start + text.get(start..end)?.chars().count();
+    Some((char_start, char_end))
+}
+
+/// Convert a byte range to a UTF-16 code unit range
+pub fn byte_span_to_utf16(text: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    if start > end {
+        return None;
+    }
+    let utf16_start = byte_to_utf16(text, start)?;
+    let utf16_end = utf16_start + text.get(start..end)?.encode_utf16().count();
+    Some((utf16_start, utf16_end))
+}
+
+/// Unicode-lowercase `text`, with the byte offset in `text` of every byte
+/// of the result
+///
+/// Lowercasing can change a character's length (`İ` becomes `i̇`), so
+/// offsets found in the lowercased text must be mapped back. The map has
+/// one more entry than the result, for its end.
+pub fn lowercase_with_offsets(text: &str) -> (String, Vec<usize>) {
+    let mut lower = String::with_capacity(text.len());
+    let mut map = Vec::with_capacity(text.len() + 1);
+    for (i, c) in text.char_indices() {
+        let before = lower.len();
+        lower.extend(c.to_lowercase());
+        map.resize(map.len() + lower.len() - before, i);
+    }
+    map.push(text.len());
+    (lower, map)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_char_roundtrip() {
+        let text = "연차휴가는 15일";
+        let byte = text.find("15").unwrap();
+
+        assert_eq!(byte, 16);
+        assert_eq!(byte_to_char(text, byte), Some(6));
+        assert_eq!(char_to_byte(text, 6), Some(byte));
+        assert_eq!(char_to_byte(text, 9), Some(text.len()));
+        assert_eq!(char_to_byte(text, 10), None);
+
+        // Inside a Hangul syllable
+        assert_eq!(byte_to_char(text, 1), None);
+        assert_eq!(byte_to_char(text, text.len() + 1), None);
+    }
+
+    #[test]
+    fn test_utf16_offsets() {
+        // The emoji is 4 bytes and 2 UTF-16 code units
+        let text = "휴가🌴 신청";
+        let byte = text.find("신청").unwrap();
+
+        assert_eq!(byte_to_utf16(text, byte), Some(5));
+        assert_eq!(utf16_to_byte(text, 5), Some(byte));
+        assert_eq!(utf16_to_byte(text, 3), None);
+        assert_eq!(utf16_to_byte(text, 7), Some(text.len()));
+    }
+
+    #[test]
+    fn test_span_conversion() {
+        let text = "병가 신청 시 진단서 제출";
+        let start = text.find("진단서").unwrap();
+        let end = start + "진단서".len();
+
+        assert_eq!(byte_span_to_char(text, start, end), Some((8, 11)));
+        assert_eq!(byte_span_to_utf16(text, start, end), Some((8, 11)));
+        assert_eq!(byte_span_to_char(text, start + 1, end), None);
+        assert_eq!(byte_span_to_char(text, end, start), None);
+    }
+
+    #[test]
+    fn test_lowercase_with_offsets() {
+        let text = "İ 연차 Leave";
+        let (lower, map) = lowercase_with_offsets(text);
+        assert_eq!(lower, "i\u{307} 연차 leave");
+        let start = lower.find("leave").unwrap();
+        let end = start + "leave".len();
+        assert_eq!(&text[map[start]..map[end]], "Leave");
+        assert_eq!(map.len(), lower.len() + 1);
+    }
+}
@@ -0,0 +1,382 @@
+//! Plugins for custom parsers and extractors
+//!
+//! A [`Plugin`] adds [`DocumentParser`], [`EntityExtractor`] and
+//! [`RelationExtractor`] implementations without changes to OTL itself, for
+//! example a parser for a proprietary document format or extractors for an
+//! organization's own entity types. Plugins are discovered when
+//! [`PluginHost::discover`] runs at startup:
+//!
+//! - native plugins are Rust crates linked into the binary that announce
+//!   themselves with [`submit_plugin!`]
+//! - WASM plugins are `.wasm` modules in the plugin directory, run in a
+//!   sandbox (only with the `wasm` feature; see [`crate::wasm`])
+//!
+//! ```ignore
+//! struct HwpPlugin;
+//!
+//! impl Plugin for HwpPlugin {
+//!     fn name(&self) -> &str {
+//!         "hwp"
+//!     }
+//!
+//!     fn register(&self, registrar: &mut PluginRegistrar) -> otl_core::Result<()> {
+//!         registrar.parser(HwpParser::new());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! otl_extractor::submit_plugin!(|| Box::new(HwpPlugin));
+//! ```
+//!
+//! Plugin parsers take precedence over the built-in parsers, and plugin
+//! extractors run after the built-in ones; see [`PluginHost::parser_registry`]
+//! and [`PluginHost::entity_extractor`].
+//!
+//! Author: hephaex@gmail.com
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use otl_core::{OtlError, Result};
+use otl_parser::{DocumentParser, ParserRegistry};
+
+use crate::{EntityExtractor, ExtractedEntity, ExtractedRelation, RelationExtractor};
+
+#[doc(hidden)]
+pub use inventory;
+
+/// Extension of OTL with parsers and extractors
+pub trait Plugin: Send + Sync {
+    /// Unique name of the plugin
+    fn name(&self) -> &str;
+
+    /// Register the plugin's implementations
+    fn register(&self, registrar: &mut PluginRegistrar) -> Result<()>;
+}
+
+/// Constructor of a native plugin, collected by [`submit_plugin!`]
+pub struct PluginConstructor(pub fn() -> Box<dyn Plugin>);
+
+inventory::collect!(PluginConstructor);
+
+/// Announce a native plugin to [`PluginHost::discover`]
+///
+/// Takes a `fn() -> Box<dyn Plugin>` (a non-capturing closure will do).
+#[macro_export]
+macro_rules! submit_plugin {
+    ($constructor:expr) => {
+        $crate::plugin::inventory::submit! {
+            $crate::plugin::PluginConstructor($constructor)
+        }
+    };
+}
+
+/// Collects the implementations a plugin registers
+#[derive(Default)]
+pub struct PluginRegistrar {
+    parsers: Vec<Arc<dyn DocumentParser>>,
+    entity_extractors: Vec<Arc<dyn EntityExtractor>>,
+    relation_extractors: Vec<Arc<dyn RelationExtractor>>,
+}
+
+impl PluginRegistrar {
+    pub fn parser(&mut self, parser: impl DocumentParser + 'static) {
+        self.parsers.push(Arc::new(parser));
+    }
+
+    pub fn entity_extractor(&mut self, extractor: impl EntityExtractor + 'static) {
+        self.entity_extractors.push(Arc::new(extractor));
+    }
+
+    pub fn relation_extractor(&mut self, extractor: impl RelationExtractor + 'static) {
+        self.relation_extractors.push(Arc::new(extractor));
+    }
+}
+
+/// Where a plugin was loaded from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PluginSource {
+    /// Linked into the binary
+    Native,
+    /// WASM module
+    Wasm { path: PathBuf },
+}
+
+/// A loaded plugin and what it registered
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub source: PluginSource,
+    /// File types and extensions of the plugin's parsers
+    pub parses: Vec<String>,
+    pub entity_extractors: usize,
+    pub relation_extractors: usize,
+}
+
+/// Loaded plugins and the implementations they registered
+#[derive(Default)]
+pub struct PluginHost {
+    plugins: Vec<PluginInfo>,
+    registered: PluginRegistrar,
+}
+
+impl PluginHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the native plugins linked into the binary and the WASM plugins
+    /// in `plugin_dir`
+    ///
+    /// WASM modules are skipped with a warning when the `wasm` feature is
+    /// disabled.
+    pub fn discover(plugin_dir: Option<&Path>) -> Result<Self> {
+        let mut host = Self::new();
+        for constructor in inventory::iter::<PluginConstructor> {
+            host.load((constructor.0)().as_ref(), PluginSource::Native)?;
+        }
+        if let Some(dir) = plugin_dir {
+            host.load_dir(dir)?;
+        }
+        Ok(host)
+    }
+
+    #[cfg(feature = "wasm")]
+    fn load_dir(&mut self, dir: &Path) -> Result<()> {
+        for path in crate::wasm::module_paths(dir)? {
+            let plugin = crate::wasm::WasmPlugin::load(&path)?;
+            self.load(&plugin, PluginSource::Wasm { path })?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn load_dir(&mut self, dir: &Path) -> Result<()> {
+        tracing::warn!(
+            "Ignoring plugin directory {}: built without WASM plugin support",
+            dir.display()
+        );
+        Ok(())
+    }
+
+    /// Register a plugin's implementations
+    pub fn load(&mut self, plugin: &dyn Plugin, source: PluginSource) -> Result<()> {
+        let name = plugin.name().to_string();
+        if self.plugins.iter().any(|p| p.name == name) {
+            return Err(OtlError::ConfigError(format!(
+                "Plugin {name} is loaded twice"
+            )));
+        }
+
+        let mut registrar = PluginRegistrar::default();
+        plugin.register(&mut registrar)?;
+        let parses = registrar
+            .parsers
+            .iter()
+            .flat_map(|p| {
+                p.supported_types()
+                    .iter()
+                    .map(|t| t.to_string())
+                    .chain(p.extensions().iter().cloned())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        tracing::info!(
+            plugin = %name,
+            parsers = registrar.parsers.len(),
+            entity_extractors = registrar.entity_extractors.len(),
+            relation_extractors = registrar.relation_extractors.len(),
+            "Plugin loaded"
+        );
+        self.plugins.push(PluginInfo {
+            name,
+            source,
+            parses,
+            entity_extractors: registrar.entity_extractors.len(),
+            relation_extractors: registrar.relation_extractors.len(),
+        });
+
+        self.registered.parsers.extend(registrar.parsers);
+        self.registered
+            .entity_extractors
+            .extend(registrar.entity_extractors);
+        self.registered
+            .relation_extractors
+            .extend(registrar.relation_extractors);
+        Ok(())
+    }
+
+    pub fn plugins(&self) -> &[PluginInfo] {
+        &self.plugins
+    }
+
+    /// Parsers of the plugins, ahead of the built-in parsers
+    pub fn parser_registry(&self) -> ParserRegistry {
+        let mut registry = ParserRegistry::new();
+        for parser in &self.registered.parsers {
+            registry.register(parser.clone());
+        }
+        registry.with_default_parsers()
+    }
+
+    /// `base` followed by the plugins' entity extractors
+    pub fn entity_extractor(&self, base: impl EntityExtractor + 'static) -> ChainedEntityExtractor {
+        let mut extractors: Vec<Arc<dyn EntityExtractor>> = vec![Arc::new(base)];
+        extractors.extend(self.registered.entity_extractors.iter().cloned());
+        ChainedEntityExtractor { extractors }
+    }
+
+    /// `base` followed by the plugins' relation extractors
+    pub fn relation_extractor(
+        &self,
+        base: impl RelationExtractor + 'static,
+    ) -> ChainedRelationExtractor {
+        let mut extractors: Vec<Arc<dyn RelationExtractor>> = vec![Arc::new(base)];
+        extractors.extend(self.registered.relation_extractors.iter().cloned());
+        ChainedRelationExtractor { extractors }
+    }
+}
+
+/// Entities of several extractors
+///
+/// An entity found by more than one extractor (same span and type) is kept
+/// once, with the highest confidence.
+pub struct ChainedEntityExtractor {
+    extractors: Vec<Arc<dyn EntityExtractor>>,
+}
+
+impl EntityExtractor for ChainedEntityExtractor {
+    fn extract(&self, text: &str) -> Result<Vec<ExtractedEntity>> {
+        let mut entities: Vec<ExtractedEntity> = Vec::new();
+        for extractor in &self.extractors {
+            for entity in extractor.extract(text)? {
+                let same = entities.iter_mut().find(|e| {
+                    e.start == entity.start
+                        && e.end == entity.end
+                        && e.entity_type == entity.entity_type
+                });
+                match same {
+                    Some(existing) => {
+                        existing.confidence = existing.confidence.max(entity.confidence)
+                    }
+                    None => entities.push(entity),
+                }
+            }
+        }
+        entities.sort_by_key(|e| e.start);
+        Ok(entities)
+    }
+}
+
+/// Relations of several extractors, in extractor order
+pub struct ChainedRelationExtractor {
+    extractors: Vec<Arc<dyn RelationExtractor>>,
+}
+
+impl RelationExtractor for ChainedRelationExtractor {
+    fn extract(&self, text: &str, entities: &[ExtractedEntity]) -> Result<Vec<ExtractedRelation>> {
+        let mut relations = Vec::new();
+        for extractor in &self.extractors {
+            relations.extend(extractor.extract(text, entities)?);
+        }
+        Ok(relations)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_parser::{FileType, ParsedDocument};
+
+    /// Parser of `.memo` files: the first line is the title
+    struct MemoParser {
+        extensions: Vec<String>,
+    }
+
+    impl DocumentParser for MemoParser {
+        fn parse(&self, path: &Path) -> otl_parser::Result<ParsedDocument> {
+            let text =
+                std::fs::read_to_string(path).map_err(|e| otl_parser::ParserError::IoError {
+                    path: path.display().to_string(),
+                    source: e,
+                })?;
+            let (title, body) = text.split_once('\n').unwrap_or((&text, ""));
+            let mut doc = ParsedDocument::new(path.display().to_string(), FileType::Unknown)
+                .with_content(body);
+            doc.metadata.title = Some(title.to_string());
+            Ok(doc)
+        }
+
+        fn supported_types(&self) -> &[FileType] {
+            &[]
+        }
+
+        fn extensions(&self) -> &[String] {
+            &self.extensions
+        }
+    }
+
+    /// Finds project codes such as "PRJ-2024"
+    struct ProjectCodes;
+
+    impl EntityExtractor for ProjectCodes {
+        fn extract(&self, text: &str) -> Result<Vec<ExtractedEntity>> {
+            Ok(text
+                .match_indices("PRJ-2024")
+                .filter_map(|(start, code)| {
+                    ExtractedEntity::from_byte_span(text, "Project", start, start + code.len(), 0.9)
+                })
+                .collect())
+        }
+    }
+
+    struct MemoPlugin;
+
+    impl Plugin for MemoPlugin {
+        fn name(&self) -> &str {
+            "memo"
+        }
+
+        fn register(&self, registrar: &mut PluginRegistrar) -> Result<()> {
+            registrar.parser(MemoParser {
+                extensions: vec!["memo".to_string()],
+            });
+            registrar.entity_extractor(ProjectCodes);
+            Ok(())
+        }
+    }
+
+    submit_plugin!(|| Box::new(MemoPlugin));
+
+    #[test]
+    fn test_discovered_plugin_parses_and_extracts() {
+        let host = PluginHost::discover(None).unwrap();
+        let info = &host.plugins()[0];
+        assert_eq!(info.name, "memo");
+        assert_eq!(info.source, PluginSource::Native);
+        assert_eq!(info.parses, vec!["memo".to_string()]);
+        assert_eq!(info.entity_extractors, 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("주간회의.memo");
+        std::fs::write(&path, "주간 회의\nPRJ-2024 일정 점검").unwrap();
+        let doc = host.parser_registry().parse(&path).unwrap();
+        assert_eq!(doc.metadata.title.as_deref(), Some("주간 회의"));
+
+        let extractor = host.entity_extractor(ProjectCodes);
+        let entities = extractor.extract(&doc.content).unwrap();
+        // Found by both extractors, kept once
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].text, "PRJ-2024");
+
+        let mut host = host;
+        assert!(host.load(&MemoPlugin, PluginSource::Native).is_err());
+    }
+}
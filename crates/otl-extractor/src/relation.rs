@@ -20,9 +20,11 @@ use otl_core::Result;
 pub enum RelationType {
     // Employment relations
     WorksIn,     // Employee -> Department
+    HasMember,   // Department -> Employee (inverse of WorksIn)
     HasPosition, // Employee -> Position
     HasGrade,    // Employee -> Grade
     ManagedBy,   // Employee -> Manager
+    Manages,     // Manager -> Employee (inverse of ManagedBy)
     ReportsTo,   // Employee -> Manager
 
     // Leave relations
@@ -32,9 +34,11 @@ pub enum RelationType {
     RequiresDocument, // LeaveType -> Document
 
     // Approval relations
-    HasStep,    // ApprovalProcess -> ApprovalStep
-    ApprovedBy, // ApprovalStep -> Role/Manager
-    NextStep,   // ApprovalStep -> ApprovalStep
+    HasStep,      // ApprovalProcess -> ApprovalStep
+    StepOf,       // ApprovalStep -> ApprovalProcess (inverse of HasStep)
+    ApprovedBy,   // ApprovalStep -> Role/Manager
+    NextStep,     // ApprovalStep -> ApprovalStep
+    PreviousStep, // ApprovalStep -> ApprovalStep (inverse of NextStep)
 
     // Document relations
     DefinedIn,  // Entity -> Regulation
@@ -51,17 +55,21 @@ impl RelationType {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::WorksIn => "worksIn",
+            Self::HasMember => "hasMember",
             Self::HasPosition => "hasPosition",
             Self::HasGrade => "hasGrade",
             Self::ManagedBy => "managedBy",
+            Self::Manages => "manages",
             Self::ReportsTo => "reportsTo",
             Self::RequestsLeave => "requestsLeave",
             Self::ApprovesLeave => "approvesLeave",
             Self::RequiresDuration => "requiresDuration",
             Self::RequiresDocument => "requiresDocument",
             Self::HasStep => "hasStep",
+            Self::StepOf => "stepOf",
             Self::ApprovedBy => "approvedBy",
             Self::NextStep => "nextStep",
+            Self::PreviousStep => "previousStep",
             Self::DefinedIn => "definedIn",
             Self::References => "references",
             Self::Requires => "requires",
@@ -74,17 +82,21 @@ impl RelationType {
     pub fn parse_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "worksin" => Some(Self::WorksIn),
+            "hasmember" => Some(Self::HasMember),
             "hasposition" => Some(Self::HasPosition),
             "hasgrade" => Some(Self::HasGrade),
             "managedby" => Some(Self::ManagedBy),
+            "manages" => Some(Self::Manages),
             "reportsto" => Some(Self::ReportsTo),
             "requestsleave" => Some(Self::RequestsLeave),
             "approvesleave" => Some(Self::ApprovesLeave),
             "requiresduration" => Some(Self::RequiresDuration),
             "requiresdocument" => Some(Self::RequiresDocument),
             "hasstep" => Some(Self::HasStep),
+            "stepof" => Some(Self::StepOf),
             "approvedby" => Some(Self::ApprovedBy),
             "nextstep" => Some(Self::NextStep),
+            "previousstep" => Some(Self::PreviousStep),
             "definedin" => Some(Self::DefinedIn),
             "references" => Some(Self::References),
             "requires" => Some(Self::Requires),
@@ -93,6 +105,40 @@ impl RelationType {
             _ => None,
         }
     }
+
+    /// Inverse relation: `a R b` holds exactly when `b R' a` holds
+    ///
+    /// Symmetric relations are their own inverse.
+    pub fn inverse(&self) -> Option<Self> {
+        match self {
+            Self::WorksIn => Some(Self::HasMember),
+            Self::HasMember => Some(Self::WorksIn),
+            Self::ManagedBy => Some(Self::Manages),
+            Self::Manages => Some(Self::ManagedBy),
+            Self::HasStep => Some(Self::StepOf),
+            Self::StepOf => Some(Self::HasStep),
+            Self::NextStep => Some(Self::PreviousStep),
+            Self::PreviousStep => Some(Self::NextStep),
+            Self::RelatedTo => Some(Self::RelatedTo),
+            _ => None,
+        }
+    }
+
+    /// Whether `a R b` implies `b R a`
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, Self::RelatedTo)
+    }
+
+    /// Whether this is the direction stored in the graph
+    ///
+    /// Inverse forms are accepted from extractors but rewritten by
+    /// [`canonicalize_relation`] so each fact is stored once.
+    pub fn is_canonical(&self) -> bool {
+        !matches!(
+            self,
+            Self::HasMember | Self::Manages | Self::StepOf | Self::PreviousStep
+        )
+    }
 }
 
 impl std::fmt::Display for RelationType {
@@ -101,10 +147,105 @@ impl std::fmt::Display for RelationType {
     }
 }
 
+// ============================================================================
+// Direction handling
+// ============================================================================
+
+/// Rewrite a relation in its canonical direction
+///
+/// Inverse predicates are flipped (`인사팀 hasMember 김철수` becomes
+/// `김철수 worksIn 인사팀`) and symmetric relations are ordered by text
+/// position. Unknown predicates are left as they are.
+pub fn canonicalize_relation(mut relation: ExtractedRelation) -> ExtractedRelation {
+    let Some(relation_type) = RelationType::parse_str(&relation.predicate) else {
+        return relation;
+    };
+
+    let flip = if relation_type.is_symmetric() {
+        relation.object.start < relation.subject.start
+    } else {
+        !relation_type.is_canonical()
+    };
+
+    if flip {
+        std::mem::swap(&mut relation.subject, &mut relation.object);
+        if let Some(inverse) = relation_type.inverse() {
+            relation.predicate = inverse.to_string();
+        }
+    }
+    relation
+}
+
+/// Grammatical role signalled by the particle right after an entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// Topic or subject marker (은/는/이/가/께서)
+    Subject,
+    /// Object marker (을/를)
+    Object,
+    /// Locative, dative or instrumental marker (에/에서/에게/으로/로 ...)
+    Adverbial,
+}
+
+const SUBJECT_PARTICLES: &[&str] = &["은", "는", "이", "가", "께서", "께서는"];
+const OBJECT_PARTICLES: &[&str] = &["을", "를"];
+const ADVERBIAL_PARTICLES: &[&str] = &[
+    "에",
+    "에서",
+    "에게",
+    "께",
+    "한테",
+    "으로",
+    "로",
+    "에는",
+    "에서는",
+    "에게는",
+    "으로는",
+    "로는",
+];
+
+/// Role of an entity from the particle attached to it, if any
+///
+/// Only a word consisting solely of a particle counts, so `가` in
+/// `휴가신청` or `이` in `이내` are not mistaken for markers.
+fn particle_role(text: &str, entity: &ExtractedEntity) -> Option<Role> {
+    let rest = text.get(entity.end..)?;
+    let end = rest
+        .find(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+        .unwrap_or(rest.len());
+    let particle = &rest[..end];
+
+    if SUBJECT_PARTICLES.contains(&particle) {
+        Some(Role::Subject)
+    } else if OBJECT_PARTICLES.contains(&particle) {
+        Some(Role::Object)
+    } else if ADVERBIAL_PARTICLES.contains(&particle) {
+        Some(Role::Adverbial)
+    } else {
+        None
+    }
+}
+
+/// Whether text contains a clause or sentence break
+fn has_clause_break(text: &str) -> bool {
+    text.contains([',', '.', ';', '\n', '。'])
+}
+
 // ============================================================================
 // Rule-based RE
 // ============================================================================
 
+/// How subject and object of a pattern are assigned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// Entity types fix the roles (subject and object types differ)
+    #[default]
+    ByType,
+    /// Both entities share a type: the one marked with a subject particle is
+    /// the subject, otherwise the earlier one
+    ByCues,
+}
+
 /// Pattern for extracting relations
 #[derive(Debug, Clone)]
 pub struct RelationPattern {
@@ -120,6 +261,8 @@ pub struct RelationPattern {
     pub max_distance: usize,
     /// Confidence score
     pub confidence: f32,
+    /// How subject and object are assigned
+    pub direction: Direction,
 }
 
 /// Rule-based relation extractor
@@ -220,9 +363,28 @@ impl RuleBasedRe {
             80,
             0.85,
         );
+        self.add_pattern(
+            "Employee",
+            "Manager",
+            RelationType::ReportsTo,
+            vec!["보고하", "보고한", "보고드"],
+            80,
+            0.80,
+        );
+        self.add_pattern(
+            "Manager",
+            "Manager",
+            RelationType::ReportsTo,
+            vec!["보고하", "보고한", "보고드"],
+            80,
+            0.80,
+        );
     }
 
     /// Add a relation pattern
+    ///
+    /// Patterns between two entities of the same type are resolved by
+    /// particle cues, since the types cannot tell subject from object.
     fn add_pattern(
         &mut self,
         subject_type: &str,
@@ -239,6 +401,11 @@ impl RuleBasedRe {
             keywords: keywords.iter().map(|s| s.to_string()).collect(),
             max_distance,
             confidence,
+            direction: if subject_type == object_type {
+                Direction::ByCues
+            } else {
+                Direction::ByType
+            },
         });
     }
 
@@ -255,6 +422,7 @@ impl RuleBasedRe {
     fn try_create_relation(
         &self,
         text: &str,
+        entities: &[ExtractedEntity],
         subject: &ExtractedEntity,
         object: &ExtractedEntity,
         pattern: &RelationPattern,
@@ -301,6 +469,16 @@ impl RuleBasedRe {
             return None;
         }
 
+        if !self.same_clause(text, entities, first, second) {
+            return None;
+        }
+
+        if pattern.direction == Direction::ByCues
+            && !self.is_grammatical_subject(text, subject, object)
+        {
+            return None;
+        }
+
         Some(ExtractedRelation {
             subject: subject.clone(),
             predicate: pattern.relation.to_string(),
@@ -309,6 +487,55 @@ impl RuleBasedRe {
         })
     }
 
+    /// Whether two entities (in text order) belong to the same clause
+    ///
+    /// A subject-marked entity of either type between them opens a new
+    /// clause (`김철수는 영업팀 소속이며, 이영희는 인사팀에서 ...`), as does a
+    /// clause break followed by a subject-marked second entity when the
+    /// first carries no particle tying it to the upcoming predicate.
+    fn same_clause(
+        &self,
+        text: &str,
+        entities: &[ExtractedEntity],
+        first: &ExtractedEntity,
+        second: &ExtractedEntity,
+    ) -> bool {
+        let intervening_subject = entities.iter().any(|e| {
+            e.start >= first.end
+                && e.end <= second.start
+                && (e.entity_type == first.entity_type || e.entity_type == second.entity_type)
+                && particle_role(text, e) == Some(Role::Subject)
+        });
+        if intervening_subject {
+            return false;
+        }
+
+        let between = text.get(first.end..second.start).unwrap_or_default();
+        !(has_clause_break(between)
+            && particle_role(text, second) == Some(Role::Subject)
+            && particle_role(text, first).is_none())
+    }
+
+    /// Whether `subject` rather than `object` is the grammatical subject
+    ///
+    /// Used when both entities have the same type. A subject particle decides
+    /// (`김과장은 박부장에게 보고한다`, `박부장에게 김과장이 보고한다`);
+    /// without one the earlier entity is taken as the subject.
+    fn is_grammatical_subject(
+        &self,
+        text: &str,
+        subject: &ExtractedEntity,
+        object: &ExtractedEntity,
+    ) -> bool {
+        let subject_marked = particle_role(text, subject) == Some(Role::Subject);
+        let object_marked = particle_role(text, object) == Some(Role::Subject);
+        match (subject_marked, object_marked) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => subject.start < object.start,
+        }
+    }
+
     /// Process a single pattern and find all matching relations
     fn process_pattern(
         &self,
@@ -332,7 +559,9 @@ impl RuleBasedRe {
         let mut relations = Vec::new();
         for subject in &subjects {
             for object in &objects {
-                if let Some(relation) = self.try_create_relation(text, subject, object, pattern) {
+                if let Some(relation) =
+                    self.try_create_relation(text, entities, subject, object, pattern)
+                {
                     relations.push(relation);
                 }
             }
@@ -361,7 +590,23 @@ impl Default for RuleBasedRe {
 
 impl RelationExtractor for RuleBasedRe {
     fn extract(&self, text: &str, entities: &[ExtractedEntity]) -> Result<Vec<ExtractedRelation>> {
-        let relations = self.find_pattern_relations(text, entities);
+        // Patterns for a relation and its inverse can match the same fact;
+        // keep one canonical relation with the highest confidence
+        let mut relations: Vec<ExtractedRelation> = Vec::new();
+        for relation in self.find_pattern_relations(text, entities) {
+            let relation = canonicalize_relation(relation);
+            let duplicate = relations.iter_mut().find(|r| {
+                r.predicate == relation.predicate
+                    && r.subject.start == relation.subject.start
+                    && r.object.start == relation.object.start
+            });
+            match duplicate {
+                Some(existing) => {
+                    existing.confidence = existing.confidence.max(relation.confidence)
+                }
+                None => relations.push(relation),
+            }
+        }
         Ok(relations)
     }
 }
@@ -433,8 +678,8 @@ impl LlmRe {
                 serde_json::json!({
                     "text": e.text,
                     "type": e.entity_type,
-                    "start": e.start,
-                    "end": e.end
+                    "start": e.char_start,
+                    "end": e.char_end
                 })
             })
             .collect();
@@ -463,12 +708,12 @@ impl LlmRe {
                 let subject = entities.iter().find(|e| e.text == r.subject)?;
                 let object = entities.iter().find(|e| e.text == r.object)?;
 
-                Some(ExtractedRelation {
+                Some(canonicalize_relation(ExtractedRelation {
                     subject: subject.clone(),
                     predicate: r.predicate,
                     object: object.clone(),
                     confidence: r.confidence.unwrap_or(0.8),
-                })
+                }))
             })
             .collect()
     }
@@ -605,6 +850,8 @@ mod tests {
             entity_type: entity_type.to_string(),
             start,
             end,
+            char_start: 0,
+            char_end: 0,
             confidence: 0.9,
         }
     }
@@ -706,4 +953,154 @@ mod tests {
         assert!(prompt.contains("Relation types to extract"));
         assert!(prompt.contains("연차휴가"));
     }
+
+    /// Entity at the first occurrence of `name` in `text`
+    fn entity_in(text: &str, name: &str, entity_type: &str) -> ExtractedEntity {
+        let start = text.find(name).unwrap();
+        ExtractedEntity::from_byte_span(text, entity_type, start, start + name.len(), 0.9).unwrap()
+    }
+
+    fn triples(relations: &[ExtractedRelation]) -> Vec<(&str, &str, &str)> {
+        let mut triples: Vec<_> = relations
+            .iter()
+            .map(|r| {
+                (
+                    r.subject.text.as_str(),
+                    r.predicate.as_str(),
+                    r.object.text.as_str(),
+                )
+            })
+            .collect();
+        triples.sort();
+        triples
+    }
+
+    #[test]
+    fn test_relation_inverse() {
+        for relation in [
+            RelationType::WorksIn,
+            RelationType::ManagedBy,
+            RelationType::HasStep,
+            RelationType::NextStep,
+        ] {
+            let inverse = relation.inverse().unwrap();
+            assert_eq!(inverse.inverse(), Some(relation));
+            assert!(relation.is_canonical());
+            assert!(!inverse.is_canonical());
+        }
+        assert_eq!(RelationType::RequiresDocument.inverse(), None);
+        assert!(RelationType::RelatedTo.is_symmetric());
+        assert_eq!(
+            RelationType::parse_str("hasMember"),
+            Some(RelationType::HasMember)
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_relation() {
+        let text = "인사팀에는 김철수가 소속되어 있다.";
+        let department = entity_in(text, "인사팀", "Department");
+        let employee = entity_in(text, "김철수", "Employee");
+
+        let relation = canonicalize_relation(ExtractedRelation {
+            subject: department.clone(),
+            predicate: "hasMember".to_string(),
+            object: employee.clone(),
+            confidence: 0.8,
+        });
+        assert_eq!(relation.subject.text, "김철수");
+        assert_eq!(relation.predicate, "worksIn");
+        assert_eq!(relation.object.text, "인사팀");
+
+        // Symmetric relations are ordered by position
+        let relation = canonicalize_relation(ExtractedRelation {
+            subject: employee,
+            predicate: "relatedTo".to_string(),
+            object: department,
+            confidence: 0.8,
+        });
+        assert_eq!(relation.subject.text, "인사팀");
+        assert_eq!(relation.predicate, "relatedTo");
+    }
+
+    #[test]
+    fn test_particle_role() {
+        let text = "김과장은 박부장에게 보고서를 휴가신청 이내";
+        let role = |name: &str| particle_role(text, &entity_in(text, name, "X"));
+
+        assert_eq!(role("김과장"), Some(Role::Subject));
+        assert_eq!(role("박부장"), Some(Role::Adverbial));
+        assert_eq!(role("보고서"), Some(Role::Object));
+        assert_eq!(role("휴가"), None);
+        assert_eq!(role("이내"), None);
+    }
+
+    #[test]
+    fn test_clause_boundaries_keep_pairs_apart() {
+        let re = RuleBasedRe::new();
+        let text = "김철수는 영업팀 소속이며, 이영희는 인사팀에서 근무한다.";
+        let entities = vec![
+            entity_in(text, "김철수", "Employee"),
+            entity_in(text, "영업팀", "Department"),
+            entity_in(text, "이영희", "Employee"),
+            entity_in(text, "인사팀", "Department"),
+        ];
+
+        let relations = re.extract(text, &entities).unwrap();
+        assert_eq!(
+            triples(&relations),
+            vec![
+                ("김철수", "worksIn", "영업팀"),
+                ("이영희", "worksIn", "인사팀")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_object_before_subject() {
+        let re = RuleBasedRe::new();
+        let text = "인사팀에는 김철수가 소속되어 있다.";
+        let entities = vec![
+            entity_in(text, "인사팀", "Department"),
+            entity_in(text, "김철수", "Employee"),
+        ];
+
+        let relations = re.extract(text, &entities).unwrap();
+        assert_eq!(triples(&relations), vec![("김철수", "worksIn", "인사팀")]);
+    }
+
+    #[test]
+    fn test_same_type_direction_from_particles() {
+        let re = RuleBasedRe::new();
+
+        for text in [
+            "김과장은 박부장에게 보고한다.",
+            "박부장에게 김과장이 보고한다.",
+        ] {
+            let entities = vec![
+                entity_in(text, "김과장", "Manager"),
+                entity_in(text, "박부장", "Manager"),
+            ];
+            let relations = re.extract(text, &entities).unwrap();
+            assert_eq!(
+                triples(&relations),
+                vec![("김과장", "reportsTo", "박부장")],
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_llm_re_canonicalizes_inverse() {
+        let re = LlmRe::new();
+        let text = "인사팀에는 김철수가 소속되어 있다.";
+        let entities = vec![
+            entity_in(text, "인사팀", "Department"),
+            entity_in(text, "김철수", "Employee"),
+        ];
+
+        let response = r#"[{"subject": "인사팀", "predicate": "hasMember", "object": "김철수"}]"#;
+        let relations = re.parse_response(response, &entities);
+        assert_eq!(triples(&relations), vec![("김철수", "worksIn", "인사팀")]);
+    }
 }
@@ -0,0 +1,425 @@
+//! WASM plugin host
+//!
+//! Runs plugins compiled to WebAssembly, so a parser for a proprietary
+//! format can be shipped as a single `.wasm` file without rebuilding OTL.
+//! Modules get no imports (no file, network or clock access), a memory
+//! limit and a fuel budget per call ([`WasmLimits`]), so a faulty plugin
+//! fails the call instead of the process.
+//!
+//! A module exports its linear memory as `memory` and:
+//!
+//! | Export | Signature | |
+//! |---|---|---|
+//! | `otl_alloc` | `(len: i32) -> i32` | buffer for input of `len` bytes |
+//! | `otl_manifest` | `() -> i64` | plugin manifest (JSON) |
+//! | `otl_parse` | `(ptr: i32, len: i32) -> i64` | optional: file bytes to document (JSON) |
+//! | `otl_extract_entities` | `(ptr: i32, len: i32) -> i64` | optional: UTF-8 text to entities (JSON) |
+//! | `otl_extract_relations` | `(ptr: i32, len: i32) -> i64` | optional: text and entities (JSON) to relations (JSON) |
+//!
+//! Outputs are returned as `ptr << 32 | len` of a buffer in the module's
+//! memory. The manifest is `{"name": "hwp", "extensions": ["hwp"]}`; a
+//! parsed document is `{"title": …, "content": …, "sections": [{"title": …,
+//! "content": …}]}`; entities are `[{"entity_type": …, "start": …, "end": …,
+//! "confidence": …}]` with byte offsets into the text; relation input is
+//! `{"text": …, "entities": […]}` and relations are `[{"subject": 0,
+//! "predicate": …, "object": 1, "confidence": …}]` with indices into the
+//! entities. Any output may instead be `{"error": "…"}`.
+//!
+//! Author: hephaex@gmail.com
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use wasmi::{
+    Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+use otl_core::{OtlError, Result};
+use otl_parser::{DocumentParser, DocumentSection, FileType, ParsedDocument, ParserError};
+
+use crate::plugin::{Plugin, PluginRegistrar};
+use crate::{EntityExtractor, ExtractedEntity, ExtractedRelation, RelationExtractor};
+
+/// Resources a module may use
+#[derive(Debug, Clone, Copy)]
+pub struct WasmLimits {
+    /// Linear memory a module may grow to, in bytes
+    pub memory: usize,
+    /// Fuel (roughly, instructions) per call
+    pub fuel_per_call: u64,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            memory: 256 * 1024 * 1024,
+            fuel_per_call: 1_000_000_000,
+        }
+    }
+}
+
+/// `.wasm` files in a directory, in name order
+pub fn module_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        OtlError::ConfigError(format!(
+            "Failed to read plugin directory {}: {e}",
+            dir.display()
+        ))
+    })?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|e| e == "wasm"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    name: String,
+    #[serde(default)]
+    extensions: Vec<String>,
+}
+
+/// Instance of a plugin module; calls are serialized
+struct WasmModule {
+    name: String,
+    fuel_per_call: u64,
+    state: Mutex<(Store<StoreLimits>, Instance, Memory)>,
+}
+
+impl WasmModule {
+    fn instantiate(name: &str, wasm: &[u8], limits: WasmLimits) -> Result<Self> {
+        let fail = |e: wasmi::Error| plugin_error(name, e);
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(fail)?;
+
+        let store_limits = StoreLimitsBuilder::new().memory_size(limits.memory).build();
+        let mut store = Store::new(&engine, store_limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(limits.fuel_per_call)
+            .map_err(|e| plugin_error(name, e))?;
+        // No imports: a module that needs any does not instantiate
+        let instance = Linker::<StoreLimits>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(fail)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| plugin_error(name, "no exported memory"))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            fuel_per_call: limits.fuel_per_call,
+            state: Mutex::new((store, instance, memory)),
+        })
+    }
+
+    fn exports(&self, function: &str) -> bool {
+        let state = self.state.lock().unwrap();
+        state.1.get_func(&state.0, function).is_some()
+    }
+
+    /// Call `function` with `input` and return its output buffer
+    fn call(&self, function: &str, input: Option<&[u8]>) -> Result<Vec<u8>> {
+        let fail = |e: wasmi::Error| plugin_error(&self.name, e);
+        let mut state = self.state.lock().unwrap();
+        let (store, instance, memory) = &mut *state;
+        store
+            .set_fuel(self.fuel_per_call)
+            .map_err(|e| plugin_error(&self.name, e))?;
+
+        let packed = match input {
+            Some(input) => {
+                let len = i32::try_from(input.len())
+                    .map_err(|_| plugin_error(&self.name, "input too large"))?;
+                let ptr = instance
+                    .get_typed_func::<i32, i32>(&*store, "otl_alloc")
+                    .and_then(|alloc| alloc.call(&mut *store, len))
+                    .map_err(fail)?;
+                memory
+                    .write(&mut *store, ptr as u32 as usize, input)
+                    .map_err(|e| plugin_error(&self.name, e))?;
+                instance
+                    .get_typed_func::<(i32, i32), i64>(&*store, function)
+                    .and_then(|f| f.call(&mut *store, (ptr, len)))
+                    .map_err(fail)?
+            }
+            None => instance
+                .get_typed_func::<(), i64>(&*store, function)
+                .and_then(|f| f.call(&mut *store, ()))
+                .map_err(fail)?,
+        };
+
+        let ptr = (packed as u64 >> 32) as usize;
+        let len = (packed as u64 & 0xffff_ffff) as usize;
+        let mut output = vec![0; len];
+        memory
+            .read(&*store, ptr, &mut output)
+            .map_err(|e| plugin_error(&self.name, e))?;
+        Ok(output)
+    }
+
+    /// Call `function` and decode its JSON output
+    fn call_json<T: DeserializeOwned>(&self, function: &str, input: Option<&[u8]>) -> Result<T> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Output<T> {
+            Error { error: String },
+            Ok(T),
+        }
+
+        let output = self.call(function, input)?;
+        match serde_json::from_slice(&output) {
+            Ok(Output::Ok(value)) => Ok(value),
+            Ok(Output::Error { error }) => Err(plugin_error(&self.name, error)),
+            Err(e) => Err(plugin_error(
+                &self.name,
+                format!("invalid output of {function}: {e}"),
+            )),
+        }
+    }
+}
+
+fn plugin_error(name: &str, error: impl std::fmt::Display) -> OtlError {
+    OtlError::Other(anyhow::anyhow!("WASM plugin {name}: {error}"))
+}
+
+/// Plugin backed by a WASM module
+pub struct WasmPlugin {
+    manifest: Manifest,
+    module: Arc<WasmModule>,
+}
+
+impl WasmPlugin {
+    pub fn load(path: &Path) -> Result<Self> {
+        let wasm = std::fs::read(path).map_err(|e| {
+            OtlError::ConfigError(format!("Failed to read plugin {}: {e}", path.display()))
+        })?;
+        let file_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("wasm");
+        Self::from_bytes(file_name, &wasm, WasmLimits::default())
+    }
+
+    /// Instantiate a module; `name` identifies it until its manifest is read
+    pub fn from_bytes(name: &str, wasm: &[u8], limits: WasmLimits) -> Result<Self> {
+        let module = WasmModule::instantiate(name, wasm, limits)?;
+        let manifest: Manifest = module.call_json("otl_manifest", None)?;
+        Ok(Self {
+            manifest,
+            module: Arc::new(module),
+        })
+    }
+}
+
+impl Plugin for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn register(&self, registrar: &mut PluginRegistrar) -> Result<()> {
+        if self.module.exports("otl_parse") && !self.manifest.extensions.is_empty() {
+            registrar.parser(WasmParser {
+                module: self.module.clone(),
+                extensions: self.manifest.extensions.clone(),
+            });
+        }
+        if self.module.exports("otl_extract_entities") {
+            registrar.entity_extractor(WasmEntityExtractor {
+                module: self.module.clone(),
+            });
+        }
+        if self.module.exports("otl_extract_relations") {
+            registrar.relation_extractor(WasmRelationExtractor {
+                module: self.module.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct WasmDocument {
+    #[serde(default)]
+    title: Option<String>,
+    content: String,
+    #[serde(default)]
+    sections: Vec<WasmSection>,
+}
+
+#[derive(Deserialize)]
+struct WasmSection {
+    #[serde(default)]
+    title: Option<String>,
+    content: String,
+}
+
+struct WasmParser {
+    module: Arc<WasmModule>,
+    extensions: Vec<String>,
+}
+
+impl DocumentParser for WasmParser {
+    fn parse(&self, path: &Path) -> otl_parser::Result<ParsedDocument> {
+        let bytes = std::fs::read(path).map_err(|e| ParserError::IoError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        let parsed: WasmDocument = self
+            .module
+            .call_json("otl_parse", Some(&bytes))
+            .map_err(|e| ParserError::PluginError(e.to_string()))?;
+
+        let mut doc = ParsedDocument::new(path.display().to_string(), FileType::Unknown)
+            .with_content(parsed.content);
+        doc.metadata.title = parsed.title;
+        doc.sections = parsed
+            .sections
+            .into_iter()
+            .map(|s| {
+                let section = DocumentSection::new(s.content);
+                match s.title {
+                    Some(title) => section.with_title(title),
+                    None => section,
+                }
+            })
+            .collect();
+        Ok(doc)
+    }
+
+    fn supported_types(&self) -> &[FileType] {
+        &[]
+    }
+
+    fn extensions(&self) -> &[String] {
+        &self.extensions
+    }
+}
+
+#[derive(Deserialize)]
+struct WasmEntity {
+    entity_type: String,
+    start: usize,
+    end: usize,
+    confidence: f32,
+}
+
+struct WasmEntityExtractor {
+    module: Arc<WasmModule>,
+}
+
+impl EntityExtractor for WasmEntityExtractor {
+    /// Spans that do not fit the text are dropped
+    fn extract(&self, text: &str) -> Result<Vec<ExtractedEntity>> {
+        let entities: Vec<WasmEntity> = self
+            .module
+            .call_json("otl_extract_entities", Some(text.as_bytes()))?;
+        Ok(entities
+            .into_iter()
+            .filter_map(|e| {
+                ExtractedEntity::from_byte_span(text, e.entity_type, e.start, e.end, e.confidence)
+            })
+            .collect())
+    }
+}
+
+#[derive(Deserialize)]
+struct WasmRelation {
+    subject: usize,
+    predicate: String,
+    object: usize,
+    confidence: f32,
+}
+
+struct WasmRelationExtractor {
+    module: Arc<WasmModule>,
+}
+
+impl RelationExtractor for WasmRelationExtractor {
+    /// Relations between unknown entity indices are dropped
+    fn extract(&self, text: &str, entities: &[ExtractedEntity]) -> Result<Vec<ExtractedRelation>> {
+        let input = serde_json::json!({ "text": text, "entities": entities });
+        let relations: Vec<WasmRelation> = self
+            .module
+            .call_json("otl_extract_relations", Some(input.to_string().as_bytes()))?;
+        Ok(relations
+            .into_iter()
+            .filter_map(|r| {
+                Some(ExtractedRelation {
+                    subject: entities.get(r.subject)?.clone(),
+                    predicate: r.predicate,
+                    object: entities.get(r.object)?.clone(),
+                    confidence: r.confidence,
+                })
+            })
+            .collect())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Module with its outputs in data segments: a manifest for `.memo`
+    /// files, a fixed document and one entity at bytes 0..3
+    const MODULE: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{\22name\22:\22memo\22,\22extensions\22:[\22memo\22]}")
+  (data (i32.const 64) "{\22title\22:\22회의\22,\22content\22:\22PRJ 일정\22}")
+  (data (i32.const 160) "[{\22entity_type\22:\22Project\22,\22start\22:0,\22end\22:3,\22confidence\22:0.9}]")
+  (func (export "otl_alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "otl_manifest") (result i64) (i64.const 37))
+  (func (export "otl_parse") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 64) (i64.const 32)) (i64.const 41)))
+  (func (export "otl_extract_entities") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 160) (i64.const 32)) (i64.const 62)))
+  (func (export "spin") (result i64) (loop (br 0)) (i64.const 0))
+)
+"#;
+
+    #[test]
+    fn test_wasm_plugin_parses_and_extracts() {
+        let wasm = wat::parse_str(MODULE).unwrap();
+        let limits = WasmLimits {
+            fuel_per_call: 100_000,
+            ..Default::default()
+        };
+        let plugin = WasmPlugin::from_bytes("memo", &wasm, limits).unwrap();
+        assert_eq!(plugin.name(), "memo");
+
+        let mut host = crate::plugin::PluginHost::new();
+        host.load(
+            &plugin,
+            crate::plugin::PluginSource::Wasm {
+                path: PathBuf::from("memo.wasm"),
+            },
+        )
+        .unwrap();
+        assert_eq!(host.plugins()[0].parses, vec!["memo".to_string()]);
+        assert_eq!(host.plugins()[0].relation_extractors, 0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.memo");
+        std::fs::write(&path, b"binary").unwrap();
+        let doc = host.parser_registry().parse(&path).unwrap();
+        assert_eq!(doc.metadata.title.as_deref(), Some("회의"));
+        assert_eq!(doc.content, "PRJ 일정");
+
+        let entities = plugin
+            .module
+            .call_json::<Vec<WasmEntity>>("otl_extract_entities", Some(doc.content.as_bytes()));
+        assert_eq!(entities.unwrap()[0].end, 3);
+
+        // Runaway plugins run out of fuel instead of hanging
+        assert!(plugin.module.call("spin", None).is_err());
+    }
+}
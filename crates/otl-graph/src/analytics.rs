@@ -0,0 +1,292 @@
+//! Knowledge graph analytics
+//!
+//! Health statistics over the whole graph: node and edge counts per class
+//! and predicate, orphaned entities, degree distribution and centrality,
+//! and document coverage (documents without any extracted triple).
+//!
+//! Structure nodes and edges (see [`otl_core::structure`]) are counted per
+//! class and predicate but excluded from orphan detection and degrees, so a
+//! chunk `mentions` edge does not make an entity look connected.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use otl_core::structure::{is_structure_class, is_structure_predicate, DOCUMENT_CLASS};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Default number of most central entities reported
+pub const DEFAULT_TOP_N: usize = 10;
+
+/// Maximum number of IDs listed for orphans and uncovered documents
+pub const MAX_LISTED: usize = 100;
+
+// ============================================================================
+// Inputs
+// ============================================================================
+
+/// Node as seen by the analytics (ID and class only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeSummary {
+    pub id: Uuid,
+    pub class: String,
+}
+
+/// Edge as seen by the analytics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeSummary {
+    pub subject: Uuid,
+    pub predicate: String,
+    pub object: Uuid,
+    /// Document the edge was extracted from
+    pub document_id: Option<Uuid>,
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+/// Entity ranked by degree centrality
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CentralNode {
+    pub id: Uuid,
+    pub class: String,
+    pub degree: usize,
+    /// Degree divided by the largest possible degree (n - 1)
+    pub centrality: f64,
+}
+
+/// Documents with and without extracted triples
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocumentCoverage {
+    pub total_documents: usize,
+    pub documents_with_triples: usize,
+    /// Share of documents with at least one triple (0.0 - 1.0)
+    pub coverage: f64,
+    /// Documents without triples (at most [`MAX_LISTED`])
+    pub uncovered: Vec<Uuid>,
+}
+
+/// Graph health report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphAnalytics {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub nodes_by_class: BTreeMap<String, usize>,
+    pub edges_by_predicate: BTreeMap<String, usize>,
+
+    /// Number of domain entities without any extracted relation
+    pub orphan_count: usize,
+    /// Orphaned entity IDs (at most [`MAX_LISTED`])
+    pub orphans: Vec<Uuid>,
+
+    /// Degree -> number of domain entities with that degree
+    pub degree_distribution: BTreeMap<usize, usize>,
+    pub average_degree: f64,
+    pub max_degree: usize,
+    pub most_central: Vec<CentralNode>,
+
+    pub coverage: DocumentCoverage,
+}
+
+// ============================================================================
+// Computation
+// ============================================================================
+
+/// Compute analytics for a graph
+pub fn analyze(nodes: &[NodeSummary], edges: &[EdgeSummary], top_n: usize) -> GraphAnalytics {
+    let mut nodes_by_class = BTreeMap::new();
+    for node in nodes {
+        *nodes_by_class.entry(node.class.clone()).or_insert(0) += 1;
+    }
+    let mut edges_by_predicate = BTreeMap::new();
+    for edge in edges {
+        *edges_by_predicate
+            .entry(edge.predicate.clone())
+            .or_insert(0) += 1;
+    }
+
+    // Degrees over extracted relations between domain entities
+    let domain: Vec<&NodeSummary> = nodes
+        .iter()
+        .filter(|n| !is_structure_class(&n.class))
+        .collect();
+    let mut degree: HashMap<Uuid, usize> = domain.iter().map(|n| (n.id, 0)).collect();
+    let relations: Vec<&EdgeSummary> = edges
+        .iter()
+        .filter(|e| !is_structure_predicate(&e.predicate))
+        .collect();
+    for edge in &relations {
+        for id in [edge.subject, edge.object] {
+            if let Some(d) = degree.get_mut(&id) {
+                *d += 1;
+            }
+        }
+    }
+
+    let mut degree_distribution = BTreeMap::new();
+    for d in degree.values() {
+        *degree_distribution.entry(*d).or_insert(0) += 1;
+    }
+
+    let orphaned: Vec<Uuid> = domain
+        .iter()
+        .filter(|n| degree[&n.id] == 0)
+        .map(|n| n.id)
+        .collect();
+
+    let total_degree: usize = degree.values().sum();
+    let average_degree = if domain.is_empty() {
+        0.0
+    } else {
+        total_degree as f64 / domain.len() as f64
+    };
+    let max_degree = degree.values().copied().max().unwrap_or(0);
+
+    let mut ranked: Vec<&NodeSummary> = domain
+        .iter()
+        .copied()
+        .filter(|n| degree[&n.id] > 0)
+        .collect();
+    ranked.sort_by(|a, b| degree[&b.id].cmp(&degree[&a.id]).then(a.id.cmp(&b.id)));
+    let denominator = domain.len().saturating_sub(1).max(1) as f64;
+    let most_central = ranked
+        .into_iter()
+        .take(top_n)
+        .map(|n| CentralNode {
+            id: n.id,
+            class: n.class.clone(),
+            degree: degree[&n.id],
+            centrality: degree[&n.id] as f64 / denominator,
+        })
+        .collect();
+
+    GraphAnalytics {
+        node_count: nodes.len(),
+        edge_count: edges.len(),
+        nodes_by_class,
+        edges_by_predicate,
+        orphan_count: orphaned.len(),
+        orphans: orphaned.into_iter().take(MAX_LISTED).collect(),
+        degree_distribution,
+        average_degree,
+        max_degree,
+        most_central,
+        coverage: document_coverage(nodes, &relations),
+    }
+}
+
+fn document_coverage(nodes: &[NodeSummary], relations: &[&EdgeSummary]) -> DocumentCoverage {
+    let covered: HashSet<Uuid> = relations.iter().filter_map(|e| e.document_id).collect();
+    let documents: Vec<Uuid> = nodes
+        .iter()
+        .filter(|n| n.class == DOCUMENT_CLASS)
+        .map(|n| n.id)
+        .collect();
+
+    let uncovered: Vec<Uuid> = documents
+        .iter()
+        .copied()
+        .filter(|id| !covered.contains(id))
+        .collect();
+    let documents_with_triples = documents.len() - uncovered.len();
+
+    DocumentCoverage {
+        total_documents: documents.len(),
+        documents_with_triples,
+        coverage: if documents.is_empty() {
+            0.0
+        } else {
+            documents_with_triples as f64 / documents.len() as f64
+        },
+        uncovered: uncovered.into_iter().take(MAX_LISTED).collect(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::structure::{CHUNK_CLASS, HAS_CHUNK, MENTIONS};
+
+    fn node(class: &str) -> NodeSummary {
+        NodeSummary {
+            id: Uuid::new_v4(),
+            class: class.to_string(),
+        }
+    }
+
+    fn edge(s: &NodeSummary, p: &str, o: &NodeSummary, doc: Option<Uuid>) -> EdgeSummary {
+        EdgeSummary {
+            subject: s.id,
+            predicate: p.to_string(),
+            object: o.id,
+            document_id: doc,
+        }
+    }
+
+    #[test]
+    fn test_analyze_counts_and_orphans() {
+        let doc_a = node(DOCUMENT_CLASS);
+        let doc_b = node(DOCUMENT_CLASS);
+        let chunk = node(CHUNK_CLASS);
+        let sick = node("SickLeave");
+        let cert = node("Document");
+        let approval = node("ApprovalProcess");
+        let orphan = node("LeaveType");
+
+        let nodes = vec![
+            doc_a.clone(),
+            doc_b.clone(),
+            chunk.clone(),
+            sick.clone(),
+            cert.clone(),
+            approval.clone(),
+            orphan.clone(),
+        ];
+        let edges = vec![
+            edge(&doc_a, HAS_CHUNK, &chunk, Some(doc_a.id)),
+            edge(&chunk, MENTIONS, &orphan, Some(doc_a.id)),
+            edge(&sick, "requiresDocument", &cert, Some(doc_a.id)),
+            edge(&sick, "requiresApproval", &approval, Some(doc_a.id)),
+        ];
+
+        let report = analyze(&nodes, &edges, DEFAULT_TOP_N);
+
+        assert_eq!(report.node_count, 7);
+        assert_eq!(report.edge_count, 4);
+        assert_eq!(report.nodes_by_class[DOCUMENT_CLASS], 2);
+        assert_eq!(report.edges_by_predicate[MENTIONS], 1);
+
+        // A mention alone does not connect an entity
+        assert_eq!(report.orphan_count, 1);
+        assert_eq!(report.orphans, vec![orphan.id]);
+
+        assert_eq!(report.max_degree, 2);
+        assert_eq!(report.degree_distribution[&0], 1);
+        assert_eq!(report.degree_distribution[&1], 2);
+        assert_eq!(report.degree_distribution[&2], 1);
+        assert!((report.average_degree - 1.0).abs() < 1e-9);
+
+        assert_eq!(report.most_central[0].id, sick.id);
+        assert!((report.most_central[0].centrality - 2.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(report.coverage.total_documents, 2);
+        assert_eq!(report.coverage.documents_with_triples, 1);
+        assert_eq!(report.coverage.uncovered, vec![doc_b.id]);
+        assert!((report.coverage.coverage - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_analyze_empty_graph() {
+        let report = analyze(&[], &[], DEFAULT_TOP_N);
+        assert_eq!(report.node_count, 0);
+        assert_eq!(report.average_degree, 0.0);
+        assert!(report.most_central.is_empty());
+        assert_eq!(report.coverage.coverage, 0.0);
+    }
+}
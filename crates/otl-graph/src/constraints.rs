@@ -0,0 +1,474 @@
+//! Ontology constraints and the conflict-resolution queue
+//!
+//! Properties can be declared functional (at most one object per subject,
+//! e.g. `maxDays`) or given a maximum cardinality. [`load_triple`] checks
+//! an incoming triple against the asserted edges of its subject before
+//! storing it: a triple that would exceed the limit is not stored but
+//! queued in the `graph_conflict` table together with its evidence and the
+//! edges it contradicts. A reviewer then keeps the existing edges, replaces
+//! them with the incoming triple, or keeps both ([`resolve`]).
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use otl_core::{OtlError, Provenance, Result, Triple};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::snapshot::EdgeSnapshot;
+use crate::SurrealDbStore;
+
+/// Maximum number of conflicts returned by one listing
+pub const MAX_LISTED: usize = 500;
+
+/// Cardinality limits declared by the ontology
+#[derive(Debug, Clone, Default)]
+pub struct ConstraintRules {
+    /// Property to the most objects one subject may have
+    max_objects: BTreeMap<String, usize>,
+}
+
+impl ConstraintRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a functional property (one object per subject)
+    pub fn with_functional(self, predicate: impl Into<String>) -> Self {
+        self.with_max_cardinality(predicate, 1)
+    }
+
+    /// Allow at most `max` objects per subject for a property
+    ///
+    /// The tighter of repeated declarations wins.
+    pub fn with_max_cardinality(mut self, predicate: impl Into<String>, max: usize) -> Self {
+        let limit = self.max_objects.entry(predicate.into()).or_insert(max);
+        *limit = (*limit).min(max);
+        self
+    }
+
+    /// Whether no property is constrained
+    pub fn is_empty(&self) -> bool {
+        self.max_objects.is_empty()
+    }
+
+    /// Most objects one subject may have for `predicate`, if limited
+    pub fn max_objects(&self, predicate: &str) -> Option<usize> {
+        self.max_objects.get(predicate).copied()
+    }
+
+    /// Check an incoming triple against the edges already stored
+    ///
+    /// `existing` are the edges of the triple's subject; edges with other
+    /// predicates, inferred edges and edges to the same object (which the
+    /// triple would merely confirm) never conflict.
+    pub fn check(&self, triple: &Triple, existing: &[EdgeSnapshot]) -> Option<Violation> {
+        let max = self.max_objects(&triple.predicate)?;
+        let subject = triple.subject.to_string();
+        let object = triple.object.to_string();
+
+        let mut conflicting: Vec<ConflictingEdge> = Vec::new();
+        for edge in existing {
+            if edge.inferred
+                || edge.subject != subject
+                || edge.predicate.as_deref() != Some(triple.predicate.as_str())
+            {
+                continue;
+            }
+            if edge.object == object {
+                return None;
+            }
+            if conflicting.iter().all(|c| c.object != edge.object) {
+                conflicting.push(ConflictingEdge {
+                    triple_id: edge.triple_id.clone(),
+                    object: edge.object.clone(),
+                    confidence: edge.confidence,
+                });
+            }
+        }
+        if conflicting.len() < max {
+            return None;
+        }
+
+        Some(Violation {
+            kind: if max == 1 {
+                ViolationKind::Functional
+            } else {
+                ViolationKind::Cardinality
+            },
+            predicate: triple.predicate.clone(),
+            subject,
+            max,
+            conflicting,
+        })
+    }
+}
+
+/// Which constraint an incoming triple breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    /// A functional property would get a second object
+    Functional,
+    /// A property would exceed its maximum cardinality
+    Cardinality,
+}
+
+/// Edge contradicted by an incoming triple
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConflictingEdge {
+    pub triple_id: Option<String>,
+    pub object: String,
+    pub confidence: Option<f32>,
+}
+
+/// A broken constraint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    pub predicate: String,
+    pub subject: String,
+    /// Most objects allowed per subject
+    pub max: usize,
+    /// Stored edges the triple would join
+    pub conflicting: Vec<ConflictingEdge>,
+}
+
+impl Violation {
+    /// Human-readable description
+    pub fn message(&self) -> String {
+        match self.kind {
+            ViolationKind::Functional => format!(
+                "'{}' is functional but {} already has object {}",
+                self.predicate,
+                self.subject,
+                self.conflicting
+                    .iter()
+                    .map(|c| c.object.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ViolationKind::Cardinality => format!(
+                "'{}' allows at most {} objects but {} already has {}",
+                self.predicate,
+                self.max,
+                self.subject,
+                self.conflicting.len()
+            ),
+        }
+    }
+}
+
+/// State of a queued conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStatus {
+    Open,
+    Resolved,
+}
+
+impl ConflictStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Resolved => "resolved",
+        }
+    }
+}
+
+/// How a reviewer settled a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// Discard the incoming triple
+    KeepExisting,
+    /// Delete the conflicting edges and store the incoming triple
+    Replace,
+    /// Store the incoming triple next to the existing edges
+    KeepBoth,
+}
+
+/// Triple held back by a constraint, awaiting a reviewer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphConflict {
+    /// Record ID (a UUID)
+    #[serde(default)]
+    pub id: String,
+    pub status: ConflictStatus,
+    /// The incoming triple
+    pub triple: Triple,
+    /// Evidence of the incoming triple
+    pub provenance: Vec<Provenance>,
+    pub violation: Violation,
+    pub message: String,
+    pub resolution: Option<Resolution>,
+    pub resolved_by: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of loading a triple
+#[derive(Debug, Clone)]
+pub enum TripleLoad {
+    /// Stored; the ID of the new or confirmed triple
+    Stored(Uuid),
+    /// Held back in the conflict queue
+    Queued(Box<GraphConflict>),
+}
+
+/// Store a triple unless it breaks a constraint
+///
+/// A conflicting triple is queued instead and nothing is written to the
+/// graph; see [`resolve`].
+pub async fn load_triple(
+    store: &SurrealDbStore,
+    rules: &ConstraintRules,
+    triple: &Triple,
+    provenance: &[Provenance],
+) -> Result<TripleLoad> {
+    if rules.max_objects(&triple.predicate).is_none() {
+        return store
+            .store_triple_with_provenance(triple, provenance)
+            .await
+            .map(TripleLoad::Stored);
+    }
+
+    let existing: Vec<EdgeSnapshot> = store
+        .client()
+        .query(
+            r#"
+            SELECT record::id(in) AS subject, record::id(out) AS object,
+                triple_id, predicate, confidence, document_id,
+                (inferred ?? false) AS inferred
+                FROM relates
+                WHERE in = type::thing("entity", $subject) AND predicate = $predicate;
+        "#,
+        )
+        .bind(("subject", triple.subject.to_string()))
+        .bind(("predicate", triple.predicate.clone()))
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Constraint check failed: {e}")))?
+        .take(0)
+        .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+    let Some(violation) = rules.check(triple, &existing) else {
+        return store
+            .store_triple_with_provenance(triple, provenance)
+            .await
+            .map(TripleLoad::Stored);
+    };
+
+    let conflict = GraphConflict {
+        id: Uuid::new_v4().to_string(),
+        status: ConflictStatus::Open,
+        triple: triple.clone(),
+        provenance: provenance.to_vec(),
+        message: violation.message(),
+        violation,
+        resolution: None,
+        resolved_by: None,
+        notes: None,
+        created_at: Utc::now(),
+        resolved_at: None,
+    };
+    save(store, &conflict).await?;
+    Ok(TripleLoad::Queued(Box::new(conflict)))
+}
+
+/// Write a conflict record
+async fn save(store: &SurrealDbStore, conflict: &GraphConflict) -> Result<()> {
+    let mut content = serde_json::to_value(conflict)
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to encode conflict: {e}")))?;
+    if let Some(fields) = content.as_object_mut() {
+        fields.remove("id");
+    }
+    store
+        .client()
+        .query("UPSERT type::thing('graph_conflict', $id) CONTENT $conflict")
+        .bind(("id", conflict.id.clone()))
+        .bind(("conflict", content))
+        .await
+        .and_then(surrealdb::Response::check)
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to record conflict: {e}")))?;
+    Ok(())
+}
+
+/// Queued conflicts, oldest first, optionally of one status
+pub async fn list(
+    store: &SurrealDbStore,
+    status: Option<ConflictStatus>,
+    limit: usize,
+) -> Result<Vec<GraphConflict>> {
+    let mut response = store
+        .client()
+        .query(
+            "SELECT *, record::id(id) AS id FROM graph_conflict \
+             WHERE $status = NONE OR status = $status \
+             ORDER BY created_at ASC LIMIT $limit",
+        )
+        .bind(("status", status.map(|s| s.as_str().to_string())))
+        .bind(("limit", limit.min(MAX_LISTED)))
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Conflict listing failed: {e}")))?;
+    response
+        .take(0)
+        .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))
+}
+
+/// A queued conflict
+pub async fn find(store: &SurrealDbStore, id: Uuid) -> Result<Option<GraphConflict>> {
+    let mut response = store
+        .client()
+        .query("SELECT *, record::id(id) AS id FROM type::thing('graph_conflict', $id)")
+        .bind(("id", id.to_string()))
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Conflict lookup failed: {e}")))?;
+    let found: Vec<GraphConflict> = response
+        .take(0)
+        .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+    Ok(found.into_iter().next())
+}
+
+/// Settle an open conflict and apply the decision to the graph
+pub async fn resolve(
+    store: &SurrealDbStore,
+    id: Uuid,
+    resolution: Resolution,
+    resolved_by: &str,
+    notes: Option<&str>,
+) -> Result<GraphConflict> {
+    let mut conflict = find(store, id)
+        .await?
+        .ok_or_else(|| OtlError::NotFound(format!("Graph conflict {id}")))?;
+    if conflict.status != ConflictStatus::Open {
+        return Err(OtlError::ValidationError(format!(
+            "Graph conflict {id} is already resolved"
+        )));
+    }
+
+    if resolution == Resolution::Replace {
+        let triple_ids: Vec<String> = conflict
+            .violation
+            .conflicting
+            .iter()
+            .filter_map(|c| c.triple_id.clone())
+            .collect();
+        store
+            .client()
+            .query(
+                "DELETE provenance WHERE triple_id INSIDE $triple_ids; \
+                 DELETE relates WHERE triple_id INSIDE $triple_ids;",
+            )
+            .bind(("triple_ids", triple_ids))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| {
+                OtlError::DatabaseError(format!("Failed to remove conflicting edges: {e}"))
+            })?;
+    }
+    if resolution != Resolution::KeepExisting {
+        store
+            .store_triple_with_provenance(&conflict.triple, &conflict.provenance)
+            .await?;
+    }
+
+    conflict.status = ConflictStatus::Resolved;
+    conflict.resolution = Some(resolution);
+    conflict.resolved_by = Some(resolved_by.to_string());
+    conflict.notes = notes.map(str::to_string);
+    conflict.resolved_at = Some(Utc::now());
+    save(store, &conflict).await?;
+    Ok(conflict)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::SourceReference;
+
+    fn edge(subject: Uuid, predicate: &str, object: Uuid) -> EdgeSnapshot {
+        EdgeSnapshot {
+            subject: subject.to_string(),
+            object: object.to_string(),
+            triple_id: Some(Uuid::new_v4().to_string()),
+            predicate: Some(predicate.to_string()),
+            confidence: Some(0.9),
+            document_id: None,
+            inferred: false,
+        }
+    }
+
+    #[test]
+    fn test_functional_and_cardinality_violations() {
+        let rules = ConstraintRules::new()
+            .with_functional("maxDays")
+            .with_max_cardinality("requires", 2);
+        let (leave, days_15, days_25) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let triple = |predicate: &str, object| {
+            Triple::new(
+                leave,
+                predicate,
+                object,
+                SourceReference::new(Uuid::new_v4()),
+                0.9,
+            )
+        };
+        let existing = vec![
+            edge(leave, "maxDays", days_15),
+            edge(leave, "requires", Uuid::new_v4()),
+        ];
+
+        // 연차 최대 15일 stored, 25일 arrives
+        let violation = rules.check(&triple("maxDays", days_25), &existing).unwrap();
+        assert_eq!(violation.kind, ViolationKind::Functional);
+        assert_eq!(violation.conflicting.len(), 1);
+        assert_eq!(violation.conflicting[0].object, days_15.to_string());
+        assert!(violation.message().contains("functional"));
+
+        // Repeating the stored value only confirms it
+        assert!(rules
+            .check(&triple("maxDays", days_15), &existing)
+            .is_none());
+        // Unconstrained properties never conflict
+        assert!(rules
+            .check(&triple("appliesTo", days_25), &existing)
+            .is_none());
+
+        // A second approval process fits, a third does not
+        assert!(rules
+            .check(&triple("requires", Uuid::new_v4()), &existing)
+            .is_none());
+        let mut more = existing.clone();
+        more.push(edge(leave, "requires", Uuid::new_v4()));
+        let violation = rules
+            .check(&triple("requires", Uuid::new_v4()), &more)
+            .unwrap();
+        assert_eq!(violation.kind, ViolationKind::Cardinality);
+        assert_eq!(violation.max, 2);
+
+        // Inferred edges are not asserted facts
+        let mut inferred = edge(leave, "maxDays", days_15);
+        inferred.inferred = true;
+        assert!(rules
+            .check(&triple("maxDays", days_25), &[inferred])
+            .is_none());
+    }
+
+    #[test]
+    fn test_tighter_cardinality_wins() {
+        let rules = ConstraintRules::new()
+            .with_max_cardinality("requires", 3)
+            .with_functional("requires")
+            .with_max_cardinality("requires", 2);
+        assert_eq!(rules.max_objects("requires"), Some(1));
+        assert_eq!(rules.max_objects("manages"), None);
+        assert!(ConstraintRules::new().is_empty());
+    }
+}
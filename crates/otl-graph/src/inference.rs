@@ -0,0 +1,456 @@
+//! Rule-based inference over the ontology
+//!
+//! Forward-chains the asserted relations of the graph with rules declared
+//! alongside the ontology:
+//!
+//! - **Transitivity**: `a partOf b` and `b partOf c` give `a partOf c`
+//! - **Inverses**: `a manages b` gives `b managedBy a` and vice versa
+//! - **Subclass inheritance**: a property declared on a class applies to
+//!   entities of its subclasses, so rules fire for them too
+//!
+//! Inferred triples are materialized as `relates` edges flagged
+//! `inferred = true` (see
+//! [`SurrealDbStore::materialize_inferences`](crate::SurrealDbStore::materialize_inferences)).
+//! They carry no provenance and never enter the review queue; they are
+//! recomputed from scratch on every run.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use otl_core::structure::is_structure_predicate;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::snapshot::EdgeSnapshot;
+
+/// Upper bound on inferred triples per run, against runaway closures
+pub const MAX_INFERRED: usize = 100_000;
+
+/// Inference rules declared by the ontology
+#[derive(Debug, Clone, Default)]
+pub struct InferenceRules {
+    transitive: BTreeSet<String>,
+    /// Property to its inverse, recorded in both directions
+    inverses: BTreeMap<String, String>,
+    /// Class to its parent class
+    parents: HashMap<String, String>,
+    /// Property to its (domain, range)
+    signatures: HashMap<String, (String, String)>,
+}
+
+impl InferenceRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a transitive property
+    pub fn with_transitive(mut self, predicate: impl Into<String>) -> Self {
+        self.transitive.insert(predicate.into());
+        self
+    }
+
+    /// Declare two properties as inverses of each other
+    pub fn with_inverse(
+        mut self,
+        predicate: impl Into<String>,
+        inverse: impl Into<String>,
+    ) -> Self {
+        let (predicate, inverse) = (predicate.into(), inverse.into());
+        self.inverses.insert(predicate.clone(), inverse.clone());
+        self.inverses.insert(inverse, predicate);
+        self
+    }
+
+    /// Declare `class` a subclass of `parent`
+    pub fn with_subclass(mut self, class: impl Into<String>, parent: impl Into<String>) -> Self {
+        self.parents.insert(class.into(), parent.into());
+        self
+    }
+
+    /// Declare the domain and range classes of a property
+    ///
+    /// Rules only derive a triple for this property when its subject is an
+    /// instance of the domain and its object of the range (or of their
+    /// subclasses). Undeclared properties are unrestricted.
+    pub fn with_signature(
+        mut self,
+        predicate: impl Into<String>,
+        domain: impl Into<String>,
+        range: impl Into<String>,
+    ) -> Self {
+        self.signatures
+            .insert(predicate.into(), (domain.into(), range.into()));
+        self
+    }
+
+    /// Whether no rule would ever fire
+    pub fn is_empty(&self) -> bool {
+        self.transitive.is_empty() && self.inverses.is_empty()
+    }
+
+    /// Whether `class` is `ancestor` or one of its subclasses
+    pub fn is_a(&self, class: &str, ancestor: &str) -> bool {
+        let mut current = Some(class);
+        // Bounded walk, in case the hierarchy has a cycle
+        for _ in 0..=self.parents.len() {
+            match current {
+                Some(c) if c == ancestor => return true,
+                Some(c) => current = self.parents.get(c).map(String::as_str),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Whether the ontology allows `predicate` between entities of these classes
+    fn conforms(&self, predicate: &str, subject: Option<&str>, object: Option<&str>) -> bool {
+        let Some((domain, range)) = self.signatures.get(predicate) else {
+            return true;
+        };
+        subject.map_or(true, |class| self.is_a(class, domain))
+            && object.map_or(true, |class| self.is_a(class, range))
+    }
+
+    /// Derive every triple entailed by `edges` and not already asserted
+    ///
+    /// `classes` maps entity IDs to their ontology class. Edges already
+    /// flagged inferred and structure edges are ignored. The confidence of
+    /// a derived triple is that of its weakest premise.
+    pub fn infer(&self, classes: &HashMap<String, String>, edges: &[EdgeSnapshot]) -> Inference {
+        let mut facts: Vec<Fact> = Vec::new();
+        let mut index: HashMap<(String, String, String), usize> = HashMap::new();
+        for edge in edges.iter().filter(|e| !e.inferred) {
+            let Some(predicate) = edge.predicate.as_deref() else {
+                continue;
+            };
+            if is_structure_predicate(predicate) {
+                continue;
+            }
+            let key = (
+                edge.subject.clone(),
+                predicate.to_string(),
+                edge.object.clone(),
+            );
+            if index.contains_key(&key) {
+                continue;
+            }
+            index.insert(key, facts.len());
+            facts.push(Fact {
+                triple_id: edge
+                    .triple_id
+                    .clone()
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+                subject: edge.subject.clone(),
+                predicate: predicate.to_string(),
+                object: edge.object.clone(),
+                confidence: edge.confidence.unwrap_or(1.0),
+                rule: None,
+                derived_from: Vec::new(),
+            });
+        }
+        let asserted = facts.len();
+        let class_of = |id: &str| classes.get(id).map(String::as_str);
+
+        // Semi-naive evaluation: each round only joins the facts derived in
+        // the previous one against everything known so far
+        let mut truncated = false;
+        let mut frontier = 0;
+        while frontier < facts.len() && !truncated {
+            let round_end = facts.len();
+            let derived: Vec<Derivation> = (frontier..round_end)
+                .flat_map(|i| self.derive(&facts, i, frontier))
+                .collect();
+
+            for Derivation {
+                subject,
+                predicate,
+                object,
+                rule,
+                premises,
+            } in derived
+            {
+                let key = (subject, predicate, object);
+                if key.0 == key.2 || index.contains_key(&key) {
+                    continue;
+                }
+                if !self.conforms(&key.1, class_of(&key.0), class_of(&key.2)) {
+                    continue;
+                }
+                if facts.len() - asserted >= MAX_INFERRED {
+                    truncated = true;
+                    break;
+                }
+                let confidence = premises
+                    .iter()
+                    .map(|&p| facts[p].confidence)
+                    .fold(1.0, f32::min);
+                let derived_from = premises
+                    .iter()
+                    .map(|&p| facts[p].triple_id.clone())
+                    .collect();
+                index.insert(key.clone(), facts.len());
+                let (subject, predicate, object) = key;
+                facts.push(Fact {
+                    triple_id: Uuid::new_v4().to_string(),
+                    subject,
+                    predicate,
+                    object,
+                    confidence,
+                    rule: Some(rule),
+                    derived_from,
+                });
+            }
+            frontier = round_end;
+        }
+
+        let triples: Vec<InferredTriple> = facts
+            .into_iter()
+            .skip(asserted)
+            .map(|f| InferredTriple {
+                triple_id: f.triple_id,
+                subject: f.subject,
+                predicate: f.predicate,
+                object: f.object,
+                confidence: f.confidence,
+                rule: f.rule.unwrap_or_default(),
+                derived_from: f.derived_from,
+            })
+            .collect();
+        Inference {
+            asserted,
+            triples,
+            truncated,
+        }
+    }
+
+    /// Consequences of `facts[i]` joined with the facts before it
+    ///
+    /// Pairs of two facts from the current round (`frontier..`) are only
+    /// joined with the later one first, so each pair is considered once.
+    fn derive(&self, facts: &[Fact], i: usize, frontier: usize) -> Vec<Derivation> {
+        let fact = &facts[i];
+        let mut derived = Vec::new();
+        if let Some(inverse) = self.inverses.get(&fact.predicate) {
+            derived.push(Derivation {
+                subject: fact.object.clone(),
+                predicate: inverse.clone(),
+                object: fact.subject.clone(),
+                rule: format!("inverse:{}", fact.predicate),
+                premises: vec![i],
+            });
+        }
+        if !self.transitive.contains(&fact.predicate) {
+            return derived;
+        }
+        let rule = format!("transitive:{}", fact.predicate);
+        for (j, other) in facts.iter().enumerate() {
+            if other.predicate != fact.predicate {
+                continue;
+            }
+            if other.subject == fact.object && other.object != fact.subject {
+                derived.push(Derivation {
+                    subject: fact.subject.clone(),
+                    predicate: fact.predicate.clone(),
+                    object: other.object.clone(),
+                    rule: rule.clone(),
+                    premises: vec![i, j],
+                });
+            }
+            if j < frontier && other.object == fact.subject && other.subject != fact.object {
+                derived.push(Derivation {
+                    subject: other.subject.clone(),
+                    predicate: fact.predicate.clone(),
+                    object: fact.object.clone(),
+                    rule: rule.clone(),
+                    premises: vec![j, i],
+                });
+            }
+        }
+        derived
+    }
+}
+
+/// A triple a rule derives from known facts
+struct Derivation {
+    subject: String,
+    predicate: String,
+    object: String,
+    rule: String,
+    /// Indexes of the premises among the known facts
+    premises: Vec<usize>,
+}
+
+/// A known triple during evaluation
+struct Fact {
+    triple_id: String,
+    subject: String,
+    predicate: String,
+    object: String,
+    confidence: f32,
+    rule: Option<String>,
+    derived_from: Vec<String>,
+}
+
+/// A triple derived by a rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InferredTriple {
+    pub triple_id: String,
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    pub confidence: f32,
+    /// Rule that fired, e.g. `transitive:partOf` or `inverse:manages`
+    pub rule: String,
+    /// Triple IDs of the premises
+    pub derived_from: Vec<String>,
+}
+
+/// Result of forward chaining
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inference {
+    /// Distinct asserted triples the rules ran over
+    pub asserted: usize,
+    pub triples: Vec<InferredTriple>,
+    /// Whether [`MAX_INFERRED`] cut the run short
+    pub truncated: bool,
+}
+
+/// Outcome of materializing inferred triples
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct InferenceReport {
+    pub asserted: usize,
+    pub inferred: usize,
+    /// Inferred edges of the previous run that were replaced
+    pub removed: usize,
+    /// Inferred triples per rule
+    pub by_rule: BTreeMap<String, usize>,
+    pub truncated: bool,
+}
+
+impl InferenceReport {
+    pub(crate) fn new(inference: &Inference, removed: usize) -> Self {
+        let mut by_rule = BTreeMap::new();
+        for triple in &inference.triples {
+            *by_rule.entry(triple.rule.clone()).or_insert(0) += 1;
+        }
+        Self {
+            asserted: inference.asserted,
+            inferred: inference.triples.len(),
+            removed,
+            by_rule,
+            truncated: inference.truncated,
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(triple_id: &str, subject: &str, predicate: &str, object: &str) -> EdgeSnapshot {
+        EdgeSnapshot {
+            subject: subject.to_string(),
+            object: object.to_string(),
+            triple_id: Some(triple_id.to_string()),
+            predicate: Some(predicate.to_string()),
+            confidence: Some(0.9),
+            document_id: Some("doc".to_string()),
+            inferred: false,
+        }
+    }
+
+    fn rules() -> InferenceRules {
+        InferenceRules::new()
+            .with_subclass("Team", "Department")
+            .with_signature("partOf", "Department", "Department")
+            .with_signature("manages", "Employee", "Department")
+            .with_signature("managedBy", "Department", "Employee")
+            .with_transitive("partOf")
+            .with_inverse("manages", "managedBy")
+    }
+
+    #[test]
+    fn test_transitive_and_inverse_inference() {
+        let classes: HashMap<String, String> = [
+            ("recruiting", "Team"),
+            ("hr", "Department"),
+            ("support", "Department"),
+            ("company", "Department"),
+            ("kim", "Employee"),
+            ("policy", "Policy"),
+        ]
+        .into_iter()
+        .map(|(id, class)| (id.to_string(), class.to_string()))
+        .collect();
+        let mut low = edge("t3", "support", "partOf", "company");
+        low.confidence = Some(0.6);
+        let edges = vec![
+            edge("t1", "recruiting", "partOf", "hr"),
+            edge("t2", "hr", "partOf", "support"),
+            low,
+            edge("t4", "kim", "manages", "recruiting"),
+            // Already asserted, not derived again
+            edge("t5", "recruiting", "partOf", "support"),
+            // Policies are not departments
+            edge("t6", "policy", "partOf", "recruiting"),
+            edge("t7", "chunk", "mentions", "hr"),
+        ];
+
+        let inference = rules().infer(&classes, &edges);
+        assert_eq!(inference.asserted, 6);
+        assert!(!inference.truncated);
+        let mut derived: Vec<_> = inference
+            .triples
+            .iter()
+            .map(|t| (t.subject.as_str(), t.predicate.as_str(), t.object.as_str()))
+            .collect();
+        derived.sort_unstable();
+        assert_eq!(
+            derived,
+            vec![
+                ("hr", "partOf", "company"),
+                ("recruiting", "managedBy", "kim"),
+                ("recruiting", "partOf", "company"),
+            ]
+        );
+
+        let managed = inference
+            .triples
+            .iter()
+            .find(|t| t.predicate == "managedBy")
+            .unwrap();
+        assert_eq!(managed.rule, "inverse:manages");
+        assert_eq!(managed.derived_from, vec!["t4".to_string()]);
+        let closure = inference
+            .triples
+            .iter()
+            .find(|t| t.subject == "hr")
+            .unwrap();
+        assert_eq!(closure.rule, "transitive:partOf");
+        assert_eq!(closure.confidence, 0.6);
+
+        // Previously inferred edges are not premises
+        let mut stale = edge("t8", "company", "partOf", "hr");
+        stale.inferred = true;
+        let again = rules().infer(&classes, &[edges, vec![stale]].concat());
+        assert_eq!(again.triples.len(), 3);
+    }
+
+    #[test]
+    fn test_subclass_hierarchy() {
+        let rules = rules().with_subclass("Squad", "Team");
+        assert!(rules.is_a("Squad", "Department"));
+        assert!(rules.is_a("Team", "Team"));
+        assert!(!rules.is_a("Department", "Team"));
+
+        let cyclic = InferenceRules::new()
+            .with_subclass("A", "B")
+            .with_subclass("B", "A");
+        assert!(!cyclic.is_a("A", "C"));
+    }
+}
@@ -9,10 +9,25 @@ use async_trait::async_trait;
 use otl_core::{Entity, Result, Triple};
 use uuid::Uuid;
 
+pub mod analytics;
+pub mod constraints;
+pub mod inference;
 pub mod search;
+pub mod snapshot;
+pub mod sparql;
+pub mod structure;
 pub mod surrealdb_store;
-
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+pub mod timeline;
+pub mod visualize;
+
+pub use analytics::GraphAnalytics;
+pub use constraints::ConstraintRules;
+pub use inference::{InferenceReport, InferenceRules};
 pub use search::GraphSearchBackend;
+pub use sparql::SparqlQuery;
+pub use structure::DocumentGraph;
 pub use surrealdb_store::SurrealDbStore;
 
 /// Trait for graph database operations
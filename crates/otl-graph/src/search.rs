@@ -10,9 +10,10 @@
 use async_trait::async_trait;
 use otl_core::{
     AccessLevel, DatabaseConfig, DocumentAcl, OtlError, Result, SearchBackend, SearchResult,
-    SearchResultType, SourceReference,
+    SearchResultType, SharedAnalyzer, SourceReference, SynonymRegistry,
 };
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use surrealdb::engine::remote::ws::{Client, Ws};
 use surrealdb::opt::auth::Root;
 use surrealdb::Surreal;
@@ -29,6 +30,10 @@ pub struct GraphSearchBackend {
     max_depth: u32,
     /// Maximum results per query
     max_results: usize,
+    /// Extracts entity keywords from the query
+    analyzer: Arc<SharedAnalyzer>,
+    /// Synonyms matched alongside the keywords (optional)
+    synonyms: Option<Arc<SynonymRegistry>>,
 }
 
 impl GraphSearchBackend {
@@ -65,6 +70,8 @@ impl GraphSearchBackend {
             client,
             max_depth: config.surrealdb_namespace.parse().unwrap_or(2),
             max_results: 20,
+            analyzer: Arc::new(SharedAnalyzer::default()),
+            synonyms: None,
         })
     }
 
@@ -80,6 +87,18 @@ impl GraphSearchBackend {
         self
     }
 
+    /// Set the analyzer used to extract keywords (e.g. with domain nouns)
+    pub fn with_analyzer(mut self, analyzer: Arc<SharedAnalyzer>) -> Self {
+        self.analyzer = analyzer;
+        self
+    }
+
+    /// Set the synonym registry used to widen entity matching
+    pub fn with_synonyms(mut self, synonyms: Arc<SynonymRegistry>) -> Self {
+        self.synonyms = Some(synonyms);
+        self
+    }
+
     /// Search for entities matching keywords
     async fn search_entities(&self, keywords: &[&str], limit: usize) -> Result<Vec<GraphNode>> {
         // Build search query - search in properties.text field
@@ -273,8 +292,13 @@ impl GraphSearchBackend {
 #[async_trait]
 impl SearchBackend for GraphSearchBackend {
     async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-        // Extract keywords from query
-        let keywords: Vec<&str> = query.split_whitespace().filter(|w| w.len() > 1).collect();
+        // Extract keywords from query (particles and endings removed)
+        let mut keywords = self.analyzer.keywords(query);
+        if let Some(synonyms) = &self.synonyms {
+            let expanded = synonyms.expand(&keywords);
+            keywords.extend(expanded);
+        }
+        let keywords: Vec<&str> = keywords.iter().map(String::as_str).collect();
 
         if keywords.is_empty() {
             return Ok(Vec::new());
@@ -0,0 +1,409 @@
+//! Graph snapshots for backup and restore
+//!
+//! A [`GraphSnapshot`] holds every entity, `relates` edge and provenance
+//! record of the graph in a plain serializable form, so it can be written
+//! to a backup archive and loaded back with
+//! [`SurrealDbStore::import_snapshot`](crate::SurrealDbStore::import_snapshot).
+//!
+//! Named snapshots are restore points kept inside SurrealDB: each is a copy
+//! of the graph in its own database of the namespace, listed in the
+//! `graph_snapshot` table of the live database. A named snapshot can be
+//! restored over the live graph or opened read-only to answer queries as of
+//! the moment it was taken.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use otl_core::{DatabaseConfig, OtlError, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::SurrealDbStore;
+
+/// Snapshot format written by this version
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// An entity record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntitySnapshot {
+    /// Record ID (the entity UUID)
+    pub id: String,
+    pub class: String,
+    pub properties: serde_json::Value,
+    pub source: serde_json::Value,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A `relates` edge between two entities
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeSnapshot {
+    /// Subject entity ID
+    pub subject: String,
+    /// Object entity ID
+    pub object: String,
+    pub triple_id: Option<String>,
+    pub predicate: Option<String>,
+    pub confidence: Option<f32>,
+    pub document_id: Option<String>,
+    /// Derived by an inference rule rather than extracted
+    #[serde(default)]
+    pub inferred: bool,
+}
+
+/// A provenance record supporting a triple
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvenanceSnapshot {
+    pub triple_id: String,
+    pub document_id: String,
+    pub page: Option<u32>,
+    pub section: Option<String>,
+    pub offset: Option<usize>,
+    pub snippet: Option<String>,
+    pub extractor: Option<String>,
+    pub confidence: f32,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Record counts of a graph or snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphCounts {
+    pub entities: u64,
+    pub edges: u64,
+    pub provenance: u64,
+}
+
+/// Full contents of the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    pub format_version: u32,
+    pub entities: Vec<EntitySnapshot>,
+    pub edges: Vec<EdgeSnapshot>,
+    pub provenance: Vec<ProvenanceSnapshot>,
+}
+
+impl GraphSnapshot {
+    pub fn new(
+        entities: Vec<EntitySnapshot>,
+        edges: Vec<EdgeSnapshot>,
+        provenance: Vec<ProvenanceSnapshot>,
+    ) -> Self {
+        Self {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            entities,
+            edges,
+            provenance,
+        }
+    }
+
+    pub fn counts(&self) -> GraphCounts {
+        GraphCounts {
+            entities: self.entities.len() as u64,
+            edges: self.edges.len() as u64,
+            provenance: self.provenance.len() as u64,
+        }
+    }
+
+    /// Referential problems in the snapshot
+    ///
+    /// Reports edges whose endpoints are missing and provenance records
+    /// whose triple has no edge. An empty list means the snapshot is
+    /// consistent.
+    pub fn problems(&self) -> Vec<String> {
+        let entity_ids: HashSet<&str> = self.entities.iter().map(|e| e.id.as_str()).collect();
+        let triple_ids: HashSet<&str> = self
+            .edges
+            .iter()
+            .filter_map(|e| e.triple_id.as_deref())
+            .collect();
+
+        let mut problems = Vec::new();
+        if self.format_version > SNAPSHOT_FORMAT_VERSION {
+            problems.push(format!(
+                "Unsupported snapshot format {} (expected at most {})",
+                self.format_version, SNAPSHOT_FORMAT_VERSION
+            ));
+        }
+        for edge in &self.edges {
+            for endpoint in [&edge.subject, &edge.object] {
+                if !entity_ids.contains(endpoint.as_str()) {
+                    problems.push(format!(
+                        "Edge {} references missing entity {}",
+                        edge.triple_id.as_deref().unwrap_or("-"),
+                        endpoint
+                    ));
+                }
+            }
+        }
+        for record in &self.provenance {
+            if !triple_ids.contains(record.triple_id.as_str()) {
+                problems.push(format!(
+                    "Provenance of document {} references missing triple {}",
+                    record.document_id, record.triple_id
+                ));
+            }
+        }
+        problems
+    }
+}
+
+// ============================================================================
+// Named snapshots
+// ============================================================================
+
+/// Longest snapshot name
+const MAX_NAME_LEN: usize = 64;
+
+/// Catalog entry of a named snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    /// SurrealDB database holding the copy
+    pub database: String,
+    pub counts: GraphCounts,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Check that a snapshot name is 1-64 ASCII letters, digits, `_` or `-`
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name.len() > MAX_NAME_LEN
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(OtlError::ValidationError(format!(
+            "Invalid snapshot name '{name}': use 1-{MAX_NAME_LEN} letters, digits, '_' or '-'"
+        )));
+    }
+    Ok(())
+}
+
+/// Database holding the named snapshot of a live database
+pub fn snapshot_database(live_database: &str, name: &str) -> String {
+    format!("{live_database}__snapshot__{name}")
+}
+
+/// Connection settings of the database holding a named snapshot
+pub fn snapshot_config(config: &DatabaseConfig, name: &str) -> DatabaseConfig {
+    DatabaseConfig {
+        surrealdb_database: snapshot_database(&config.surrealdb_database, name),
+        ..config.clone()
+    }
+}
+
+/// Named snapshots, newest first
+pub async fn list(live: &SurrealDbStore) -> Result<Vec<SnapshotInfo>> {
+    let mut response = live
+        .client()
+        .query(
+            "SELECT name, database, counts, note, created_at FROM graph_snapshot \
+             ORDER BY created_at DESC",
+        )
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Snapshot listing failed: {e}")))?;
+    response
+        .take(0)
+        .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))
+}
+
+/// Catalog entry of a named snapshot
+pub async fn find(live: &SurrealDbStore, name: &str) -> Result<Option<SnapshotInfo>> {
+    validate_name(name)?;
+    let mut response = live
+        .client()
+        .query(
+            "SELECT name, database, counts, note, created_at FROM type::thing('graph_snapshot', $name)",
+        )
+        .bind(("name", name.to_string()))
+        .await
+        .map_err(|e| OtlError::DatabaseError(format!("Snapshot lookup failed: {e}")))?;
+    let found: Vec<SnapshotInfo> = response
+        .take(0)
+        .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+    Ok(found.into_iter().next())
+}
+
+/// Copy the live graph into a new named snapshot
+pub async fn create(
+    live: &SurrealDbStore,
+    config: &DatabaseConfig,
+    name: &str,
+    note: Option<&str>,
+) -> Result<SnapshotInfo> {
+    validate_name(name)?;
+    if find(live, name).await?.is_some() {
+        return Err(OtlError::ValidationError(format!(
+            "Snapshot '{name}' already exists"
+        )));
+    }
+
+    let snapshot = live.export_snapshot().await?;
+    let target_config = snapshot_config(config, name);
+    let target = SurrealDbStore::new(&target_config).await?;
+    target.init_schema().await?;
+    target.import_snapshot(&snapshot).await?;
+
+    let info = SnapshotInfo {
+        name: name.to_string(),
+        database: target_config.surrealdb_database,
+        counts: snapshot.counts(),
+        note: note.map(str::to_string),
+        created_at: Utc::now(),
+    };
+    live.client()
+        .query("CREATE type::thing('graph_snapshot', $name) CONTENT $info")
+        .bind(("name", name.to_string()))
+        .bind(("info", info.clone()))
+        .await
+        .and_then(surrealdb::Response::check)
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to record snapshot: {e}")))?;
+    Ok(info)
+}
+
+/// Open the copy of a named snapshot
+pub async fn open(
+    live: &SurrealDbStore,
+    config: &DatabaseConfig,
+    name: &str,
+) -> Result<SurrealDbStore> {
+    if find(live, name).await?.is_none() {
+        return Err(OtlError::NotFound(format!("Graph snapshot '{name}'")));
+    }
+    SurrealDbStore::new(&snapshot_config(config, name)).await
+}
+
+/// Replace the live graph with a named snapshot
+///
+/// The snapshot is kept, so it can be restored again. Returns the restored
+/// contents.
+pub async fn restore(
+    live: &SurrealDbStore,
+    config: &DatabaseConfig,
+    name: &str,
+) -> Result<GraphSnapshot> {
+    let snapshot = open(live, config, name).await?.export_snapshot().await?;
+    let problems = snapshot.problems();
+    if !problems.is_empty() {
+        return Err(OtlError::ValidationError(format!(
+            "Snapshot '{name}' is inconsistent: {}",
+            problems.join("; ")
+        )));
+    }
+    live.import_snapshot(&snapshot).await?;
+    Ok(snapshot)
+}
+
+/// Remove a named snapshot and its database; returns whether it existed
+pub async fn delete(live: &SurrealDbStore, config: &DatabaseConfig, name: &str) -> Result<bool> {
+    if find(live, name).await?.is_none() {
+        return Ok(false);
+    }
+    let database = snapshot_database(&config.surrealdb_database, name);
+    live.client()
+        .query(format!("REMOVE DATABASE IF EXISTS `{database}`"))
+        .query("DELETE type::thing('graph_snapshot', $name)")
+        .bind(("name", name.to_string()))
+        .await
+        .and_then(surrealdb::Response::check)
+        .map_err(|e| OtlError::DatabaseError(format!("Failed to remove snapshot: {e}")))?;
+    Ok(true)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(id: &str) -> EntitySnapshot {
+        EntitySnapshot {
+            id: id.to_string(),
+            class: "Employee".to_string(),
+            properties: serde_json::json!({ "text": id }),
+            source: serde_json::json!({ "document_id": "doc" }),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn edge(triple_id: &str, subject: &str, object: &str) -> EdgeSnapshot {
+        EdgeSnapshot {
+            subject: subject.to_string(),
+            object: object.to_string(),
+            triple_id: Some(triple_id.to_string()),
+            predicate: Some("worksIn".to_string()),
+            confidence: Some(0.9),
+            document_id: Some("doc".to_string()),
+            inferred: false,
+        }
+    }
+
+    fn provenance(triple_id: &str) -> ProvenanceSnapshot {
+        ProvenanceSnapshot {
+            triple_id: triple_id.to_string(),
+            document_id: "doc".to_string(),
+            page: Some(1),
+            section: None,
+            offset: None,
+            snippet: Some("김철수는 인사팀에서 근무한다".to_string()),
+            extractor: None,
+            confidence: 0.9,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_consistent_snapshot_round_trips() {
+        let snapshot = GraphSnapshot::new(
+            vec![entity("a"), entity("b")],
+            vec![edge("t1", "a", "b")],
+            vec![provenance("t1")],
+        );
+        assert!(snapshot.problems().is_empty());
+        assert_eq!(
+            snapshot.counts(),
+            GraphCounts {
+                entities: 2,
+                edges: 1,
+                provenance: 1
+            }
+        );
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed: GraphSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+
+    #[test]
+    fn test_dangling_references_are_reported() {
+        let snapshot = GraphSnapshot::new(
+            vec![entity("a")],
+            vec![edge("t1", "a", "missing")],
+            vec![provenance("t2")],
+        );
+        let problems = snapshot.problems();
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("missing entity missing"));
+        assert!(problems[1].contains("missing triple t2"));
+    }
+
+    #[test]
+    fn test_snapshot_names() {
+        assert!(validate_name("before-bulk_2026").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("a b").is_err());
+        assert!(validate_name("x`; REMOVE NAMESPACE otl").is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LEN + 1)).is_err());
+
+        let config = DatabaseConfig::default();
+        assert_eq!(
+            snapshot_config(&config, "before-bulk").surrealdb_database,
+            "knowledge__snapshot__before-bulk"
+        );
+    }
+}
@@ -0,0 +1,969 @@
+//! SPARQL-lite
+//!
+//! A small SPARQL subset for read-only access to the knowledge graph:
+//!
+//! ```text
+//! PREFIX otl: <http://otl.local/ontology#>
+//! SELECT ?leave ?doc WHERE {
+//!     ?leave a otl:SickLeave .
+//!     ?leave otl:requiresDocument ?doc .
+//!     ?doc prop:text ?name .
+//!     FILTER(CONTAINS(?name, "진단서"))
+//! } LIMIT 10
+//! ```
+//!
+//! Supported: `PREFIX`, `SELECT [DISTINCT] ?vars | *`, basic graph patterns
+//! (triple patterns separated by `.`), `FILTER` with comparisons, `CONTAINS`
+//! and `&&`, and `LIMIT`. Predicates are `a` / `rdf:type` (entity class),
+//! names in the property namespace (`prop:`, entity properties) or any other
+//! IRI (relation predicates, by local name). Entities are written as
+//! `<urn:uuid:...>`.
+//!
+//! Each triple pattern compiles to one SurrealQL query over the `entity` or
+//! `relates` table; the patterns are then joined here. Callers filter the
+//! joined solutions (e.g. by document ACL) before [`project`] applies
+//! `SELECT`, `DISTINCT` and `LIMIT`.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::{BTreeMap, HashMap};
+
+use otl_core::{OtlError, Result};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Namespace of entity properties
+pub const PROPERTY_NS: &str = "http://otl.local/property#";
+
+/// Default namespace of ontology classes and relations
+pub const ONTOLOGY_NS: &str = "http://otl.local/ontology#";
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Result limit when the query has none
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Largest accepted LIMIT
+pub const MAX_LIMIT: usize = 1000;
+
+/// Maximum number of triple patterns per query
+pub const MAX_PATTERNS: usize = 8;
+
+/// Maximum number of intermediate solutions kept while joining
+pub const MAX_SOLUTIONS: usize = 10_000;
+
+// ============================================================================
+// Query Model
+// ============================================================================
+
+/// Subject or object of a triple pattern
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Var(String),
+    Entity(Uuid),
+    Literal(String),
+}
+
+/// Predicate of a triple pattern
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    /// `a` / `rdf:type`: the entity class
+    Type,
+    /// Relation stored as a `relates` edge
+    Relation(String),
+    /// Entity property value
+    Property(String),
+}
+
+/// One triple pattern of a basic graph pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriplePattern {
+    pub subject: Term,
+    pub predicate: Predicate,
+    pub object: Term,
+}
+
+/// Comparison operator in a FILTER
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// FILTER condition (all conditions must hold)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Compare {
+        var: String,
+        op: CompareOp,
+        value: String,
+    },
+    Contains {
+        var: String,
+        needle: String,
+    },
+}
+
+/// Parsed SPARQL-lite query
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparqlQuery {
+    /// Projected variables (`None` for `SELECT *`)
+    pub variables: Option<Vec<String>>,
+    pub distinct: bool,
+    pub patterns: Vec<TriplePattern>,
+    pub filters: Vec<Filter>,
+    pub limit: usize,
+}
+
+// ============================================================================
+// Results
+// ============================================================================
+
+/// Value bound to a variable
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SparqlValue {
+    Entity(Uuid),
+    Literal(String),
+}
+
+impl SparqlValue {
+    fn as_text(&self) -> String {
+        match self {
+            Self::Entity(id) => id.to_string(),
+            Self::Literal(s) => s.clone(),
+        }
+    }
+
+    fn matches(&self, term: &Term) -> bool {
+        match (self, term) {
+            (Self::Entity(a), Term::Entity(b)) => a == b,
+            (Self::Literal(a), Term::Literal(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Serialize for SparqlValue {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(2))?;
+        match self {
+            Self::Entity(id) => {
+                map.serialize_entry("type", "uri")?;
+                map.serialize_entry("value", &format!("urn:uuid:{id}"))?;
+            }
+            Self::Literal(s) => {
+                map.serialize_entry("type", "literal")?;
+                map.serialize_entry("value", s)?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// Variable bindings of one solution
+pub type Solution = BTreeMap<String, SparqlValue>;
+
+/// Query results in the SPARQL JSON results layout
+#[derive(Debug, Clone, Serialize)]
+pub struct SparqlResults {
+    pub head: SparqlHead,
+    pub results: SparqlBindings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SparqlHead {
+    pub vars: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SparqlBindings {
+    pub bindings: Vec<Solution>,
+}
+
+/// Entities bound anywhere in a set of solutions
+pub fn entity_ids(solutions: &[Solution]) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = solutions
+        .iter()
+        .flat_map(|s| s.values())
+        .filter_map(|v| match v {
+            SparqlValue::Entity(id) => Some(*id),
+            SparqlValue::Literal(_) => None,
+        })
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Var(String),
+    Iri(String),
+    Str(String),
+    Punct(&'static str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '{' | '}' | '(' | ')' | '.' | ',' | '*' => {
+                tokens.push(Token::Punct(match c {
+                    '{' => "{",
+                    '}' => "}",
+                    '(' => "(",
+                    ')' => ")",
+                    '.' => ".",
+                    ',' => ",",
+                    _ => "*",
+                }));
+                i += 1;
+            }
+            // `<` opens an IRI only when a `>` follows without whitespace;
+            // otherwise it is the less-than operator (`?d < 10`, `?d<10`)
+            '<' if iri_end(&chars[i..]).is_some() => {
+                let end = iri_end(&chars[i..]).unwrap_or_default();
+                tokens.push(Token::Iri(chars[i + 1..i + end].iter().collect()));
+                i += end + 1;
+            }
+            '<' | '>' | '=' | '!' | '&' => {
+                let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+                let op = match two.as_str() {
+                    "<=" => "<=",
+                    ">=" => ">=",
+                    "!=" => "!=",
+                    "&&" => "&&",
+                    _ => match c {
+                        '<' => "<",
+                        '>' => ">",
+                        '=' => "=",
+                        _ => return Err(parse_error(&format!("unexpected '{c}'"))),
+                    },
+                };
+                i += op.len();
+                tokens.push(Token::Punct(op));
+            }
+            '"' | '\'' => {
+                let (value, end) = read_string(&chars, i)?;
+                tokens.push(Token::Str(value));
+                i = end;
+            }
+            '?' | '$' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                if i == start {
+                    return Err(parse_error("empty variable name"));
+                }
+                tokens.push(Token::Var(chars[start..i].iter().collect()));
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '-' | ':'))
+                {
+                    i += 1;
+                }
+                // A trailing '.' ends the pattern, but '.' inside a number is kept
+                if i < chars.len()
+                    && chars[i] == '.'
+                    && chars[start..i].iter().all(|c| c.is_ascii_digit())
+                    && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())
+                {
+                    i += 1;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                if i == start {
+                    return Err(parse_error(&format!("unexpected '{c}'")));
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read a quoted string starting at `start`, returning it and the index after it
+fn read_string(chars: &[char], start: usize) -> Result<(String, usize)> {
+    let quote = chars[start];
+    let mut value = String::new();
+    let mut i = start + 1;
+    loop {
+        match chars.get(i) {
+            None => return Err(parse_error("unterminated string")),
+            Some('\\') => {
+                value.extend(chars.get(i + 1));
+                i += 2;
+            }
+            Some(&ch) if ch == quote => return Ok((value, i + 1)),
+            Some(&ch) => {
+                value.push(ch);
+                i += 1;
+            }
+        }
+    }
+}
+
+fn iri_end(chars: &[char]) -> Option<usize> {
+    let end = chars.iter().position(|&c| c == '>' || c.is_whitespace())?;
+    (end > 1 && chars[end] == '>' && chars[1] != '=').then_some(end)
+}
+
+fn parse_error(message: &str) -> OtlError {
+    OtlError::ValidationError(format!("SPARQL parse error: {message}"))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    prefixes: HashMap<String, String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| parse_error("unexpected end of query"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(parse_error(&format!("expected {keyword}")))
+        }
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<()> {
+        if self.is_punct(punct) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(parse_error(&format!("expected '{punct}'")))
+        }
+    }
+
+    /// Expand an IRI or prefixed name to a full IRI
+    fn expand(&self, token: &Token) -> Result<String> {
+        match token {
+            Token::Iri(iri) => Ok(iri.clone()),
+            Token::Word(word) => {
+                let (prefix, local) = word
+                    .split_once(':')
+                    .ok_or_else(|| parse_error(&format!("expected an IRI, found '{word}'")))?;
+                let ns = self
+                    .prefixes
+                    .get(prefix)
+                    .ok_or_else(|| parse_error(&format!("unknown prefix '{prefix}:'")))?;
+                Ok(format!("{ns}{local}"))
+            }
+            other => Err(parse_error(&format!("expected an IRI, found {other:?}"))),
+        }
+    }
+
+    fn parse_query(&mut self) -> Result<SparqlQuery> {
+        while self.is_keyword("PREFIX") {
+            self.pos += 1;
+            let name = match self.next()? {
+                Token::Word(w) if w.ends_with(':') => w.trim_end_matches(':').to_string(),
+                _ => return Err(parse_error("expected prefix name")),
+            };
+            let iri = match self.next()? {
+                Token::Iri(iri) => iri,
+                _ => return Err(parse_error("expected prefix IRI")),
+            };
+            self.prefixes.insert(name, iri);
+        }
+
+        self.expect_keyword("SELECT")?;
+        let distinct = self.is_keyword("DISTINCT");
+        if distinct {
+            self.pos += 1;
+        }
+
+        let variables = if self.is_punct("*") {
+            self.pos += 1;
+            None
+        } else {
+            let mut vars = Vec::new();
+            while let Some(Token::Var(v)) = self.peek() {
+                vars.push(v.clone());
+                self.pos += 1;
+            }
+            if vars.is_empty() {
+                return Err(parse_error("SELECT needs variables or *"));
+            }
+            Some(vars)
+        };
+
+        if self.is_keyword("WHERE") {
+            self.pos += 1;
+        }
+        self.expect_punct("{")?;
+
+        let mut patterns = Vec::new();
+        let mut filters = Vec::new();
+        while !self.is_punct("}") {
+            if self.is_keyword("FILTER") {
+                self.pos += 1;
+                self.parse_filter(&mut filters)?;
+            } else {
+                patterns.push(self.parse_pattern()?);
+            }
+            if self.is_punct(".") {
+                self.pos += 1;
+            }
+        }
+        self.expect_punct("}")?;
+
+        let limit = if self.is_keyword("LIMIT") {
+            self.pos += 1;
+            match self.next()? {
+                Token::Word(n) => n
+                    .parse::<usize>()
+                    .map_err(|_| parse_error("LIMIT must be a number"))?,
+                _ => return Err(parse_error("LIMIT must be a number")),
+            }
+        } else {
+            DEFAULT_LIMIT
+        };
+
+        if self.pos < self.tokens.len() {
+            return Err(parse_error("unexpected tokens after query"));
+        }
+        if patterns.is_empty() {
+            return Err(parse_error("WHERE needs at least one triple pattern"));
+        }
+        if patterns.len() > MAX_PATTERNS {
+            return Err(parse_error(&format!(
+                "at most {MAX_PATTERNS} triple patterns are supported"
+            )));
+        }
+
+        Ok(SparqlQuery {
+            variables,
+            distinct,
+            patterns,
+            filters,
+            limit: limit.min(MAX_LIMIT),
+        })
+    }
+
+    fn parse_pattern(&mut self) -> Result<TriplePattern> {
+        let subject = self.parse_term()?;
+
+        let predicate_token = self.next()?;
+        let predicate = match &predicate_token {
+            Token::Word(w) if w == "a" => Predicate::Type,
+            Token::Var(_) => {
+                return Err(parse_error("variable predicates are not supported"));
+            }
+            token => {
+                let iri = self.expand(token)?;
+                if iri == RDF_TYPE {
+                    Predicate::Type
+                } else if let Some(name) = iri.strip_prefix(PROPERTY_NS) {
+                    Predicate::Property(name.to_string())
+                } else {
+                    Predicate::Relation(local_name(&iri).to_string())
+                }
+            }
+        };
+
+        let object = if predicate == Predicate::Type {
+            match self.peek() {
+                Some(Token::Var(_)) => self.parse_term()?,
+                _ => {
+                    let token = self.next()?;
+                    Term::Literal(local_name(&self.expand(&token)?).to_string())
+                }
+            }
+        } else {
+            self.parse_term()?
+        };
+
+        Ok(TriplePattern {
+            subject,
+            predicate,
+            object,
+        })
+    }
+
+    fn parse_term(&mut self) -> Result<Term> {
+        match self.next()? {
+            Token::Var(v) => Ok(Term::Var(v)),
+            Token::Str(s) => Ok(Term::Literal(s)),
+            Token::Word(w) if w.parse::<f64>().is_ok() => Ok(Term::Literal(w)),
+            token => {
+                let iri = self.expand(&token)?;
+                let id = iri.strip_prefix("urn:uuid:").unwrap_or(local_name(&iri));
+                Uuid::parse_str(id)
+                    .map(Term::Entity)
+                    .map_err(|_| parse_error(&format!("expected an entity IRI, found <{iri}>")))
+            }
+        }
+    }
+
+    fn parse_filter(&mut self, filters: &mut Vec<Filter>) -> Result<()> {
+        self.expect_punct("(")?;
+        loop {
+            filters.push(self.parse_condition()?);
+            if self.is_punct("&&") {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        self.expect_punct(")")
+    }
+
+    fn parse_condition(&mut self) -> Result<Filter> {
+        if self.is_keyword("CONTAINS") {
+            self.pos += 1;
+            self.expect_punct("(")?;
+            let var = match self.next()? {
+                Token::Var(v) => v,
+                _ => return Err(parse_error("CONTAINS expects a variable")),
+            };
+            self.expect_punct(",")?;
+            let needle = match self.next()? {
+                Token::Str(s) => s,
+                _ => return Err(parse_error("CONTAINS expects a string")),
+            };
+            self.expect_punct(")")?;
+            return Ok(Filter::Contains { var, needle });
+        }
+
+        let var = match self.next()? {
+            Token::Var(v) => v,
+            _ => return Err(parse_error("FILTER expects a variable on the left")),
+        };
+        let op = match self.next()? {
+            Token::Punct("=") => CompareOp::Eq,
+            Token::Punct("!=") => CompareOp::Ne,
+            Token::Punct("<") => CompareOp::Lt,
+            Token::Punct("<=") => CompareOp::Le,
+            Token::Punct(">") => CompareOp::Gt,
+            Token::Punct(">=") => CompareOp::Ge,
+            _ => return Err(parse_error("expected a comparison operator")),
+        };
+        let value = match self.next()? {
+            Token::Str(s) | Token::Word(s) => s,
+            Token::Iri(iri) => iri.strip_prefix("urn:uuid:").unwrap_or(&iri).to_string(),
+            _ => return Err(parse_error("expected a value to compare with")),
+        };
+        Ok(Filter::Compare { var, op, value })
+    }
+}
+
+fn local_name(iri: &str) -> &str {
+    iri.rsplit(['#', '/', ':']).next().unwrap_or(iri)
+}
+
+/// Parse a SPARQL-lite query
+pub fn parse(query: &str) -> Result<SparqlQuery> {
+    let mut prefixes = HashMap::new();
+    prefixes.insert("otl".to_string(), ONTOLOGY_NS.to_string());
+    prefixes.insert("prop".to_string(), PROPERTY_NS.to_string());
+    prefixes.insert(
+        "rdf".to_string(),
+        "http://www.w3.org/1999/02/22-rdf-syntax-ns#".to_string(),
+    );
+
+    Parser {
+        tokens: tokenize(query)?,
+        pos: 0,
+        prefixes,
+    }
+    .parse_query()
+}
+
+// ============================================================================
+// Compilation
+// ============================================================================
+
+/// SurrealQL query for one triple pattern
+///
+/// Rows are `{ s, o }` with `s` the subject entity ID and `o` the object
+/// (entity ID for relations, class or property value otherwise).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledPattern {
+    pub query: String,
+    pub params: Vec<(&'static str, String)>,
+}
+
+impl TriplePattern {
+    /// Compile to SurrealQL, pushing constant terms into the WHERE clause
+    pub fn compile(&self) -> CompiledPattern {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+
+        let (select, table) = match &self.predicate {
+            Predicate::Type => {
+                if let Term::Literal(class) = &self.object {
+                    conditions.push("class = $class");
+                    params.push(("class", class.clone()));
+                }
+                ("record::id(id) AS s, class AS o", "entity")
+            }
+            Predicate::Relation(predicate) => {
+                conditions.push("predicate = $predicate");
+                params.push(("predicate", predicate.clone()));
+                if let Term::Entity(id) = &self.object {
+                    conditions.push("out = type::thing(\"entity\", $object)");
+                    params.push(("object", id.to_string()));
+                }
+                ("record::id(in) AS s, record::id(out) AS o", "relates")
+            }
+            Predicate::Property(property) => {
+                conditions.push("properties[$property] != NONE");
+                params.push(("property", property.clone()));
+                ("record::id(id) AS s, properties[$property] AS o", "entity")
+            }
+        };
+
+        if let Term::Entity(id) = &self.subject {
+            conditions.push(match self.predicate {
+                Predicate::Relation(_) => "in = type::thing(\"entity\", $subject)",
+                _ => "id = type::thing(\"entity\", $subject)",
+            });
+            params.push(("subject", id.to_string()));
+        }
+
+        let mut query = format!("SELECT {select} FROM {table}");
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+
+        CompiledPattern { query, params }
+    }
+
+    /// Convert a raw `{ s, o }` row into values
+    pub fn decode_row(&self, s: &str, o: &serde_json::Value) -> Option<(SparqlValue, SparqlValue)> {
+        let subject = SparqlValue::Entity(Uuid::parse_str(s).ok()?);
+        let object = match (&self.predicate, o) {
+            (Predicate::Relation(_), serde_json::Value::String(id)) => {
+                SparqlValue::Entity(Uuid::parse_str(id).ok()?)
+            }
+            (Predicate::Relation(_), _) => return None,
+            (_, serde_json::Value::String(s)) => SparqlValue::Literal(s.clone()),
+            (_, serde_json::Value::Null) => return None,
+            (_, other) => SparqlValue::Literal(other.to_string()),
+        };
+        Some((subject, object))
+    }
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+/// Join pattern rows into solutions and apply the filters
+///
+/// `rows[i]` holds the decoded rows of `query.patterns[i]`.
+pub fn evaluate(query: &SparqlQuery, rows: &[Vec<(SparqlValue, SparqlValue)>]) -> Vec<Solution> {
+    let mut solutions: Vec<Solution> = vec![Solution::new()];
+
+    for (pattern, pattern_rows) in query.patterns.iter().zip(rows) {
+        let mut next = Vec::new();
+        for solution in &solutions {
+            extend(solution, pattern, pattern_rows, &mut next);
+            if next.len() >= MAX_SOLUTIONS {
+                next.truncate(MAX_SOLUTIONS);
+                break;
+            }
+        }
+        solutions = next;
+        if solutions.is_empty() {
+            break;
+        }
+    }
+
+    solutions.retain(|s| query.filters.iter().all(|f| filter_holds(f, s)));
+    solutions
+}
+
+/// Push every extension of `solution` by a matching row of `pattern`
+fn extend(
+    solution: &Solution,
+    pattern: &TriplePattern,
+    rows: &[(SparqlValue, SparqlValue)],
+    out: &mut Vec<Solution>,
+) {
+    for (s, o) in rows {
+        let mut extended = solution.clone();
+        if bind(&mut extended, &pattern.subject, s) && bind(&mut extended, &pattern.object, o) {
+            out.push(extended);
+        }
+    }
+}
+
+fn bind(solution: &mut Solution, term: &Term, value: &SparqlValue) -> bool {
+    match term {
+        Term::Var(name) => match solution.get(name) {
+            Some(bound) => bound == value,
+            None => {
+                solution.insert(name.clone(), value.clone());
+                true
+            }
+        },
+        constant => value.matches(constant),
+    }
+}
+
+fn filter_holds(filter: &Filter, solution: &Solution) -> bool {
+    match filter {
+        Filter::Contains { var, needle } => solution
+            .get(var)
+            .is_some_and(|v| v.as_text().contains(needle.as_str())),
+        Filter::Compare { var, op, value } => {
+            let Some(bound) = solution.get(var) else {
+                return false;
+            };
+            let text = bound.as_text();
+            let ordering = match (text.parse::<f64>(), value.parse::<f64>()) {
+                (Ok(a), Ok(b)) => a.partial_cmp(&b),
+                _ => Some(text.as_str().cmp(value.as_str())),
+            };
+            let Some(ordering) = ordering else {
+                return false;
+            };
+            match op {
+                CompareOp::Eq => ordering.is_eq(),
+                CompareOp::Ne => ordering.is_ne(),
+                CompareOp::Lt => ordering.is_lt(),
+                CompareOp::Le => ordering.is_le(),
+                CompareOp::Gt => ordering.is_gt(),
+                CompareOp::Ge => ordering.is_ge(),
+            }
+        }
+    }
+}
+
+/// Apply SELECT, DISTINCT and LIMIT to (already filtered) solutions
+pub fn project(query: &SparqlQuery, solutions: Vec<Solution>) -> SparqlResults {
+    let vars = match &query.variables {
+        Some(vars) => vars.clone(),
+        None => {
+            let mut vars: Vec<String> = Vec::new();
+            let terms = query.patterns.iter().flat_map(|p| [&p.subject, &p.object]);
+            for term in terms {
+                match term {
+                    Term::Var(v) if !vars.contains(v) => vars.push(v.clone()),
+                    _ => {}
+                }
+            }
+            vars
+        }
+    };
+
+    let mut bindings: Vec<Solution> = Vec::new();
+    for solution in solutions {
+        let projected: Solution = solution
+            .into_iter()
+            .filter(|(k, _)| vars.contains(k))
+            .collect();
+        if query.distinct && bindings.contains(&projected) {
+            continue;
+        }
+        bindings.push(projected);
+        if bindings.len() >= query.limit {
+            break;
+        }
+    }
+
+    SparqlResults {
+        head: SparqlHead { vars },
+        results: SparqlBindings { bindings },
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let query = parse(
+            r#"
+            PREFIX hr: <http://example.org/hr#>
+            SELECT DISTINCT ?leave ?doc WHERE {
+                ?leave a otl:SickLeave .
+                ?leave hr:requiresDocument ?doc .
+                ?doc prop:text ?name .
+                FILTER(CONTAINS(?name, "진단서") && ?name != "x")
+            } LIMIT 5000
+        "#,
+        )
+        .unwrap();
+
+        assert!(query.distinct);
+        assert_eq!(
+            query.variables,
+            Some(vec!["leave".to_string(), "doc".to_string()])
+        );
+        assert_eq!(query.patterns.len(), 3);
+        assert_eq!(query.patterns[0].predicate, Predicate::Type);
+        assert_eq!(query.patterns[0].object, Term::Literal("SickLeave".into()));
+        assert_eq!(
+            query.patterns[1].predicate,
+            Predicate::Relation("requiresDocument".into())
+        );
+        assert_eq!(
+            query.patterns[2].predicate,
+            Predicate::Property("text".into())
+        );
+        assert_eq!(query.filters.len(), 2);
+        assert_eq!(query.limit, MAX_LIMIT);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("SELECT ?x WHERE { }").is_err());
+        assert!(parse("SELECT ?x WHERE { ?x ?p ?o }").is_err());
+        assert!(parse("SELECT ?x WHERE { ?x foo:bar ?o }").is_err());
+        assert!(parse("SELECT WHERE { ?x a otl:A }").is_err());
+        assert!(parse("SELECT ?x WHERE { ?x a otl:A } LIMIT many").is_err());
+        assert!(parse("SELECT ?x WHERE { ?x a otl:A } ORDER BY ?x").is_err());
+    }
+
+    #[test]
+    fn test_compile_pattern() {
+        let id = Uuid::new_v4();
+        let query = parse(&format!(
+            "SELECT * WHERE {{ <urn:uuid:{id}> otl:requiresDocument ?doc . ?doc a otl:Document }}"
+        ))
+        .unwrap();
+
+        let relation = query.patterns[0].compile();
+        assert_eq!(
+            relation.query,
+            "SELECT record::id(in) AS s, record::id(out) AS o FROM relates \
+             WHERE predicate = $predicate AND in = type::thing(\"entity\", $subject)"
+        );
+        assert_eq!(
+            relation.params,
+            vec![
+                ("predicate", "requiresDocument".to_string()),
+                ("subject", id.to_string())
+            ]
+        );
+
+        let class = query.patterns[1].compile();
+        assert_eq!(
+            class.query,
+            "SELECT record::id(id) AS s, class AS o FROM entity WHERE class = $class"
+        );
+    }
+
+    #[test]
+    fn test_evaluate_join_filter_project() {
+        let sick = Uuid::new_v4();
+        let annual = Uuid::new_v4();
+        let cert = Uuid::new_v4();
+        let form = Uuid::new_v4();
+        let e = SparqlValue::Entity;
+        let lit = |s: &str| SparqlValue::Literal(s.to_string());
+
+        let query = parse(
+            r#"SELECT ?leave ?name WHERE {
+                ?leave otl:requiresDocument ?doc .
+                ?doc prop:text ?name .
+                FILTER(CONTAINS(?name, "진단"))
+            }"#,
+        )
+        .unwrap();
+        let rows = vec![
+            vec![(e(sick), e(cert)), (e(annual), e(form))],
+            vec![(e(cert), lit("진단서")), (e(form), lit("휴가신청서"))],
+        ];
+
+        let solutions = evaluate(&query, &rows);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(entity_ids(&solutions).len(), 2);
+
+        let results = project(&query, solutions);
+        assert_eq!(results.head.vars, vec!["leave", "name"]);
+        assert_eq!(results.results.bindings[0]["leave"], e(sick));
+        assert_eq!(results.results.bindings[0]["name"], lit("진단서"));
+        assert!(!results.results.bindings[0].contains_key("doc"));
+
+        let json = serde_json::to_value(&results).unwrap();
+        assert_eq!(
+            json["results"]["bindings"][0]["leave"]["value"],
+            format!("urn:uuid:{sick}")
+        );
+        assert_eq!(json["results"]["bindings"][0]["name"]["type"], "literal");
+    }
+
+    #[test]
+    fn test_numeric_filter_and_limit() {
+        let lit = |s: &str| SparqlValue::Literal(s.to_string());
+        let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        let query =
+            parse("SELECT ?x WHERE { ?x prop:days ?d . FILTER(?d >= 10) } LIMIT 2").unwrap();
+        let rows = vec![ids
+            .iter()
+            .zip(["3", "10", "15", "9.5"])
+            .map(|(id, d)| (SparqlValue::Entity(*id), lit(d)))
+            .collect()];
+
+        let solutions = evaluate(&query, &rows);
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(project(&query, solutions).results.bindings.len(), 2);
+
+        let query = parse("SELECT ?x WHERE { ?x prop:days ?d . FILTER(?d<10) } LIMIT 1").unwrap();
+        let results = project(&query, evaluate(&query, &rows));
+        assert_eq!(results.results.bindings.len(), 1);
+    }
+}
@@ -0,0 +1,384 @@
+//! Document structure graph
+//!
+//! Builds graph nodes for a document's hierarchy so that answers can be
+//! traced from an extracted entity back to the section that cites it:
+//!
+//! ```text
+//! SourceDocument -containsSection-> Section -containsSection-> Section
+//!                                           -hasChunk-> Chunk -mentions-> Entity
+//! ```
+//!
+//! Chunk nodes reuse the [`DocumentChunk`] id and the document node reuses
+//! the document id, so vector hits and metadata rows map directly onto the
+//! graph.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::HashMap;
+
+use otl_core::structure::{
+    CHUNK_CLASS, CONTAINS_SECTION, DOCUMENT_CLASS, HAS_CHUNK, MENTIONS, SECTION_CLASS,
+};
+use otl_core::{DocumentChunk, Entity, Result, SourceReference, Triple};
+use uuid::Uuid;
+
+use crate::GraphStore;
+
+/// Maximum number of characters of chunk text kept on the chunk node
+const CHUNK_PREVIEW_CHARS: usize = 200;
+
+// ============================================================================
+// Document Graph
+// ============================================================================
+
+/// Structure graph of a single document
+#[derive(Debug, Clone)]
+pub struct DocumentGraph {
+    document: Entity,
+    sections: Vec<Entity>,
+    chunks: Vec<Entity>,
+    triples: Vec<Triple>,
+    /// Open sections by heading level, innermost last
+    open_sections: Vec<(u32, Uuid)>,
+    /// Section title -> section node ID (first occurrence wins)
+    section_by_title: HashMap<String, Uuid>,
+    /// Chunk node ID -> parent (section or document) node ID
+    chunk_parent: HashMap<Uuid, Uuid>,
+}
+
+impl DocumentGraph {
+    /// Create the graph with its document node
+    pub fn new(document_id: Uuid, title: impl Into<String>) -> Self {
+        let mut document = Entity::new(DOCUMENT_CLASS, SourceReference::new(document_id))
+            .with_property("name", title.into());
+        document.id = document_id;
+
+        Self {
+            document,
+            sections: Vec::new(),
+            chunks: Vec::new(),
+            triples: Vec::new(),
+            open_sections: Vec::new(),
+            section_by_title: HashMap::new(),
+            chunk_parent: HashMap::new(),
+        }
+    }
+
+    /// Document ID (also the document node ID)
+    pub fn document_id(&self) -> Uuid {
+        self.document.id
+    }
+
+    /// Add a section heading in document order
+    ///
+    /// The section is nested under the closest preceding section with a
+    /// lower level, or under the document if there is none.
+    pub fn add_section(&mut self, title: &str, level: u32, page: Option<u32>) -> Uuid {
+        while matches!(self.open_sections.last(), Some((open, _)) if *open >= level) {
+            self.open_sections.pop();
+        }
+        let parent = self
+            .open_sections
+            .last()
+            .map(|(_, id)| *id)
+            .unwrap_or(self.document.id);
+
+        let mut source = SourceReference::new(self.document.id).with_section(title);
+        if let Some(page) = page {
+            source = source.with_page(page);
+        }
+        let mut section = Entity::new(SECTION_CLASS, source)
+            .with_property("name", title)
+            .with_property("level", level)
+            .with_property("document_id", self.document.id.to_string());
+        if let Some(page) = page {
+            section = section.with_property("page", page);
+        }
+        let id = section.id;
+
+        self.triples
+            .push(self.structure_triple(parent, CONTAINS_SECTION, id));
+        self.sections.push(section);
+        self.open_sections.push((level, id));
+        self.section_by_title.entry(title.to_string()).or_insert(id);
+
+        id
+    }
+
+    /// Add a chunk under the section it names
+    ///
+    /// A chunk naming a section that was not added yet creates a top-level
+    /// section for it; a chunk without a section hangs off the document.
+    pub fn add_chunk(&mut self, chunk: &DocumentChunk) -> Uuid {
+        let parent = match chunk.section_name.as_deref() {
+            Some(name) => match self.section_by_title.get(name) {
+                Some(&id) => id,
+                None => self.add_section(name, 1, chunk.page_number),
+            },
+            None => self.document.id,
+        };
+
+        let mut source = SourceReference::new(self.document.id);
+        if let Some(page) = chunk.page_number {
+            source = source.with_page(page);
+        }
+        if let Some(section) = &chunk.section_name {
+            source = source.with_section(section.clone());
+        }
+
+        let preview: String = chunk.content.chars().take(CHUNK_PREVIEW_CHARS).collect();
+        let mut node = Entity::new(CHUNK_CLASS, source)
+            .with_property("chunk_index", chunk.chunk_index)
+            .with_property("text", preview)
+            .with_property("document_id", self.document.id.to_string());
+        if let Some(page) = chunk.page_number {
+            node = node.with_property("page", page);
+        }
+        if let Some(section) = &chunk.section_name {
+            node = node.with_property("section", section.clone());
+        }
+        if let Some(vector_id) = &chunk.vector_id {
+            node = node.with_property("vector_id", vector_id.clone());
+        }
+        node.id = chunk.id;
+
+        self.triples
+            .push(self.structure_triple(parent, HAS_CHUNK, chunk.id));
+        self.chunk_parent.insert(chunk.id, parent);
+        self.chunks.push(node);
+
+        chunk.id
+    }
+
+    /// Link a chunk to an entity extracted from it
+    pub fn add_mention(&mut self, chunk_id: Uuid, entity_id: Uuid, confidence: f32) -> Uuid {
+        let source = SourceReference::new(self.document.id).with_confidence(confidence);
+        let triple = Triple::new(chunk_id, MENTIONS, entity_id, source, confidence);
+        let id = triple.id;
+        self.triples.push(triple);
+        id
+    }
+
+    /// All nodes: the document, then sections, then chunks
+    pub fn entities(&self) -> impl Iterator<Item = &Entity> {
+        std::iter::once(&self.document)
+            .chain(self.sections.iter())
+            .chain(self.chunks.iter())
+    }
+
+    /// Section nodes in document order
+    pub fn sections(&self) -> &[Entity] {
+        &self.sections
+    }
+
+    /// Chunk nodes in insertion order
+    pub fn chunks(&self) -> &[Entity] {
+        &self.chunks
+    }
+
+    /// Structure and mention edges
+    pub fn triples(&self) -> &[Triple] {
+        &self.triples
+    }
+
+    /// Section containing a chunk (None if the chunk hangs off the document)
+    pub fn section_of(&self, chunk_id: Uuid) -> Option<&Entity> {
+        let parent = self.chunk_parent.get(&chunk_id)?;
+        self.sections.iter().find(|s| s.id == *parent)
+    }
+
+    /// Sections whose chunks mention an entity
+    pub fn sections_mentioning(&self, entity_id: Uuid) -> Vec<&Entity> {
+        let mut sections: Vec<&Entity> = Vec::new();
+        for triple in &self.triples {
+            if triple.predicate != MENTIONS || triple.object != entity_id {
+                continue;
+            }
+            if let Some(section) = self.section_of(triple.subject) {
+                if !sections.iter().any(|s| s.id == section.id) {
+                    sections.push(section);
+                }
+            }
+        }
+        sections
+    }
+
+    /// Store all nodes and edges
+    pub async fn store(&self, store: &dyn GraphStore) -> Result<()> {
+        for entity in self.entities() {
+            store.store_entity(entity).await?;
+        }
+        for triple in &self.triples {
+            store.store_triple(triple).await?;
+        }
+        Ok(())
+    }
+
+    fn structure_triple(&self, subject: Uuid, predicate: &str, object: Uuid) -> Triple {
+        Triple::new(
+            subject,
+            predicate,
+            object,
+            SourceReference::new(self.document.id),
+            1.0,
+        )
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    fn chunk(document_id: Uuid, index: u32, section: Option<&str>) -> DocumentChunk {
+        let mut chunk = DocumentChunk::new(document_id, index, format!("chunk {index}"));
+        chunk.section_name = section.map(str::to_string);
+        chunk
+    }
+
+    fn edges(graph: &DocumentGraph, predicate: &str) -> Vec<(Uuid, Uuid)> {
+        graph
+            .triples()
+            .iter()
+            .filter(|t| t.predicate == predicate)
+            .map(|t| (t.subject, t.object))
+            .collect()
+    }
+
+    #[test]
+    fn test_section_hierarchy() {
+        let doc_id = Uuid::new_v4();
+        let mut graph = DocumentGraph::new(doc_id, "인사규정");
+
+        let ch1 = graph.add_section("제1장 총칙", 1, Some(1));
+        let art1 = graph.add_section("제1조 목적", 2, Some(1));
+        let ch2 = graph.add_section("제2장 휴가", 1, Some(2));
+        let art5 = graph.add_section("제5조 연차휴가", 2, Some(2));
+
+        let contains = edges(&graph, CONTAINS_SECTION);
+        assert_eq!(
+            contains,
+            vec![(doc_id, ch1), (ch1, art1), (doc_id, ch2), (ch2, art5)]
+        );
+        assert_eq!(graph.document_id(), doc_id);
+        assert_eq!(graph.entities().count(), 5);
+        assert_eq!(graph.sections()[3].properties["level"], 2);
+    }
+
+    #[test]
+    fn test_chunks_attach_to_sections() {
+        let doc_id = Uuid::new_v4();
+        let mut graph = DocumentGraph::new(doc_id, "인사규정");
+        let leave = graph.add_section("제5조 연차휴가", 2, None);
+
+        let c0 = chunk(doc_id, 0, None);
+        let c1 = chunk(doc_id, 1, Some("제5조 연차휴가"));
+        let c2 = chunk(doc_id, 2, Some("부칙"));
+        graph.add_chunk(&c0);
+        graph.add_chunk(&c1);
+        graph.add_chunk(&c2);
+
+        assert!(graph.section_of(c0.id).is_none());
+        assert_eq!(graph.section_of(c1.id).unwrap().id, leave);
+        // Unknown section names become new sections
+        let appendix = graph.section_of(c2.id).unwrap();
+        assert_eq!(appendix.properties["name"], "부칙");
+
+        let has_chunk = edges(&graph, HAS_CHUNK);
+        assert!(has_chunk.contains(&(doc_id, c0.id)));
+        assert!(has_chunk.contains(&(leave, c1.id)));
+        assert_eq!(graph.chunks()[1].id, c1.id);
+    }
+
+    #[test]
+    fn test_entity_traces_back_to_section() {
+        let doc_id = Uuid::new_v4();
+        let mut graph = DocumentGraph::new(doc_id, "인사규정");
+        let leave = graph.add_section("제5조 연차휴가", 2, Some(3));
+        let sick = graph.add_section("제6조 병가", 2, Some(4));
+
+        let c1 = chunk(doc_id, 0, Some("제5조 연차휴가"));
+        let c2 = chunk(doc_id, 1, Some("제5조 연차휴가"));
+        let c3 = chunk(doc_id, 2, Some("제6조 병가"));
+        for c in [&c1, &c2, &c3] {
+            graph.add_chunk(c);
+        }
+
+        let annual_leave = Uuid::new_v4();
+        let approval = Uuid::new_v4();
+        graph.add_mention(c1.id, annual_leave, 0.9);
+        graph.add_mention(c2.id, annual_leave, 0.8);
+        graph.add_mention(c3.id, approval, 0.7);
+
+        let cited: Vec<Uuid> = graph
+            .sections_mentioning(annual_leave)
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(cited, vec![leave]);
+        assert_eq!(graph.sections_mentioning(approval)[0].id, sick);
+        assert!(graph.sections_mentioning(Uuid::new_v4()).is_empty());
+    }
+
+    #[derive(Default)]
+    struct RecordingStore {
+        entities: Mutex<Vec<Uuid>>,
+        triples: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl GraphStore for RecordingStore {
+        async fn store_entity(&self, entity: &Entity) -> Result<()> {
+            self.entities.lock().unwrap().push(entity.id);
+            Ok(())
+        }
+
+        async fn store_triple(&self, triple: &Triple) -> Result<()> {
+            self.triples.lock().unwrap().push(triple.predicate.clone());
+            Ok(())
+        }
+
+        async fn get_entity(&self, _id: Uuid) -> Result<Option<Entity>> {
+            Ok(None)
+        }
+
+        async fn find_by_class(&self, _class: &str, _limit: usize) -> Result<Vec<Entity>> {
+            Ok(Vec::new())
+        }
+
+        async fn traverse(&self, _start_id: Uuid, _depth: u32) -> Result<Vec<Entity>> {
+            Ok(Vec::new())
+        }
+
+        async fn query(&self, _query: &str) -> Result<Vec<Entity>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_writes_nodes_before_edges() {
+        let doc_id = Uuid::new_v4();
+        let mut graph = DocumentGraph::new(doc_id, "인사규정");
+        graph.add_section("제5조 연차휴가", 1, None);
+        let c = chunk(doc_id, 0, Some("제5조 연차휴가"));
+        graph.add_chunk(&c);
+        graph.add_mention(c.id, Uuid::new_v4(), 0.9);
+
+        let store = RecordingStore::default();
+        graph.store(&store).await.unwrap();
+
+        assert_eq!(
+            *store.entities.lock().unwrap(),
+            vec![doc_id, graph.sections()[0].id, c.id]
+        );
+        assert_eq!(
+            *store.triples.lock().unwrap(),
+            vec![CONTAINS_SECTION, HAS_CHUNK, MENTIONS]
+        );
+    }
+}
@@ -3,14 +3,30 @@
 //! Provides connection management and CRUD operations for
 //! entities and triples in SurrealDB.
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
-use otl_core::{DatabaseConfig, Entity, OtlError, Result, SourceReference, Triple};
+use otl_core::structure::{
+    is_structure_predicate, CHUNK_CLASS, DOCUMENT_CLASS, HAS_CHUNK, MENTIONS, SECTION_CLASS,
+};
+use otl_core::{
+    DatabaseConfig, Entity, GraphContextBackend, OtlError, Provenance, Result, SourceReference,
+    Triple,
+};
 use serde::{Deserialize, Serialize};
 use surrealdb::engine::remote::ws::{Client, Ws};
 use surrealdb::opt::auth::Root;
 use surrealdb::Surreal;
 use uuid::Uuid;
 
+use crate::analytics::{self, EdgeSummary, GraphAnalytics, NodeSummary};
+use crate::inference::{InferenceReport, InferenceRules};
+use crate::snapshot::{
+    EdgeSnapshot, EntitySnapshot, GraphCounts, GraphSnapshot, ProvenanceSnapshot,
+};
+use crate::sparql::{self, SparqlQuery};
+use crate::visualize::Neighborhood;
+
 /// SurrealDB graph store implementation
 pub struct SurrealDbStore {
     client: Surreal<Client>,
@@ -69,6 +85,9 @@ impl SurrealDbStore {
                 DEFINE FIELD created_at ON entity TYPE datetime DEFAULT time::now();
                 DEFINE FIELD updated_at ON entity TYPE datetime DEFAULT time::now();
                 DEFINE INDEX idx_entity_class ON entity FIELDS class;
+                DEFINE INDEX idx_relates_triple ON relates FIELDS triple_id;
+                DEFINE INDEX idx_provenance_triple ON provenance FIELDS triple_id;
+                DEFINE INDEX idx_graph_conflict_status ON graph_conflict FIELDS status;
             "#,
             )
             .await
@@ -76,6 +95,779 @@ impl SurrealDbStore {
 
         Ok(())
     }
+
+    /// Store a triple together with the evidence supporting it
+    ///
+    /// If an edge with the same subject, predicate and object already
+    /// exists, the provenance records are attached to it (and its
+    /// confidence raised to the strongest evidence) instead of creating a
+    /// duplicate edge. Returns the ID of the stored triple.
+    pub async fn store_triple_with_provenance(
+        &self,
+        triple: &Triple,
+        provenance: &[Provenance],
+    ) -> Result<Uuid> {
+        let existing: Option<String> = self
+            .client
+            .query(
+                r#"
+                SELECT VALUE triple_id FROM relates
+                WHERE in = type::thing("entity", $subject)
+                    AND out = type::thing("entity", $object)
+                    AND predicate = $predicate
+                    AND triple_id != NONE
+                LIMIT 1
+            "#,
+            )
+            .bind(("subject", triple.subject.to_string()))
+            .bind(("object", triple.object.to_string()))
+            .bind(("predicate", triple.predicate.clone()))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to look up triple: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        let max_confidence = provenance
+            .iter()
+            .map(|p| p.confidence)
+            .fold(triple.confidence, f32::max);
+
+        let triple_id = match existing.and_then(|id| Uuid::parse_str(&id).ok()) {
+            Some(id) => {
+                self.client
+                    .query(
+                        "UPDATE relates SET confidence = $confidence \
+                         WHERE triple_id = $triple_id AND confidence < $confidence; \
+                         UPDATE relates SET inferred = false \
+                         WHERE triple_id = $triple_id AND inferred = true;",
+                    )
+                    .bind(("triple_id", id.to_string()))
+                    .bind(("confidence", max_confidence))
+                    .await
+                    .map_err(|e| {
+                        OtlError::DatabaseError(format!("Failed to update triple: {e}"))
+                    })?;
+                id
+            }
+            None => {
+                self.client
+                    .query(
+                        r#"
+                        LET $from = type::thing("entity", $subject);
+                        LET $to = type::thing("entity", $object);
+                        RELATE $from->relates->$to SET
+                            triple_id = $triple_id,
+                            predicate = $predicate,
+                            confidence = $confidence,
+                            document_id = $document_id;
+                    "#,
+                    )
+                    .bind(("subject", triple.subject.to_string()))
+                    .bind(("object", triple.object.to_string()))
+                    .bind(("triple_id", triple.id.to_string()))
+                    .bind(("predicate", triple.predicate.clone()))
+                    .bind(("confidence", max_confidence))
+                    .bind(("document_id", triple.source.document_id.to_string()))
+                    .await
+                    .map_err(|e| OtlError::DatabaseError(format!("Failed to store triple: {e}")))?;
+                triple.id
+            }
+        };
+
+        for record in provenance {
+            let mut record = ProvenanceRecord::from(record);
+            record.triple_id = triple_id.to_string();
+            let _: Option<ProvenanceRecord> = self
+                .client
+                .create("provenance")
+                .content(record)
+                .await
+                .map_err(|e| OtlError::DatabaseError(format!("Failed to store provenance: {e}")))?;
+        }
+
+        Ok(triple_id)
+    }
+
+    /// Get a triple by ID
+    pub async fn get_triple(&self, id: Uuid) -> Result<Option<Triple>> {
+        let records: Vec<TripleRecord> = self
+            .client
+            .query(
+                r#"
+                SELECT triple_id, predicate, confidence,
+                    record::id(in) AS subject, record::id(out) AS object
+                FROM relates WHERE triple_id = $triple_id LIMIT 1
+            "#,
+            )
+            .bind(("triple_id", id.to_string()))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        let provenance = self.triple_provenance(id).await?;
+        Ok(records.into_iter().next().map(|r| {
+            let parse = |s: &str| Uuid::parse_str(s).unwrap_or_default();
+            let first = provenance.first();
+            let source = first
+                .map(|p| p.source.clone())
+                .unwrap_or_else(|| SourceReference::new(Uuid::nil()));
+            let mut triple = Triple::new(
+                parse(&r.subject),
+                r.predicate,
+                parse(&r.object),
+                source,
+                r.confidence,
+            );
+            triple.id = id;
+            if let Some(p) = first {
+                triple.created_at = p.recorded_at;
+            }
+            triple
+        }))
+    }
+
+    /// All evidence recorded for a triple, oldest first
+    pub async fn triple_provenance(&self, triple_id: Uuid) -> Result<Vec<Provenance>> {
+        let records: Vec<ProvenanceRecord> = self
+            .client
+            .query("SELECT * FROM provenance WHERE triple_id = $triple_id ORDER BY recorded_at ASC")
+            .bind(("triple_id", triple_id.to_string()))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(records.into_iter().map(Provenance::from).collect())
+    }
+
+    /// Compute graph health analytics over all nodes and edges
+    pub async fn analytics(&self, top_n: usize) -> Result<GraphAnalytics> {
+        let mut response = self
+            .client
+            .query(
+                r#"
+                SELECT record::id(id) AS id, class FROM entity;
+                SELECT record::id(in) AS subject, predicate, record::id(out) AS object,
+                    document_id FROM relates;
+            "#,
+            )
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?;
+
+        #[derive(Deserialize)]
+        struct NodeRow {
+            id: String,
+            class: String,
+        }
+
+        #[derive(Deserialize)]
+        struct EdgeRow {
+            subject: String,
+            predicate: Option<String>,
+            object: String,
+            document_id: Option<String>,
+        }
+
+        let node_rows: Vec<NodeRow> = response
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+        let edge_rows: Vec<EdgeRow> = response
+            .take(1)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        let nodes: Vec<NodeSummary> = node_rows
+            .into_iter()
+            .filter_map(|r| {
+                Some(NodeSummary {
+                    id: Uuid::parse_str(&r.id).ok()?,
+                    class: r.class,
+                })
+            })
+            .collect();
+        let edges: Vec<EdgeSummary> = edge_rows
+            .into_iter()
+            .filter_map(|r| {
+                Some(EdgeSummary {
+                    subject: Uuid::parse_str(&r.subject).ok()?,
+                    predicate: r.predicate.unwrap_or_else(|| "relates".to_string()),
+                    object: Uuid::parse_str(&r.object).ok()?,
+                    document_id: r.document_id.and_then(|d| Uuid::parse_str(&d).ok()),
+                })
+            })
+            .collect();
+
+        Ok(analytics::analyze(&nodes, &edges, top_n))
+    }
+
+    /// Sections whose chunks mention an entity
+    ///
+    /// Follows `Section -hasChunk-> Chunk -mentions-> Entity` edges of the
+    /// document structure graph (see [`crate::structure`]).
+    pub async fn find_citing_sections(&self, entity_id: Uuid) -> Result<Vec<Entity>> {
+        let records: Vec<EntityRecord> = self
+            .client
+            .query(
+                r#"
+                LET $chunks = (SELECT VALUE in FROM relates
+                    WHERE predicate = $mentions AND out = type::thing("entity", $entity));
+                SELECT * FROM entity WHERE class = $section AND id IN
+                    (SELECT VALUE in FROM relates WHERE predicate = $has_chunk AND out IN $chunks);
+            "#,
+            )
+            .bind(("entity", entity_id.to_string()))
+            .bind(("mentions", MENTIONS))
+            .bind(("has_chunk", HAS_CHUNK))
+            .bind(("section", SECTION_CLASS))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+            .take(1)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(records.into_iter().map(|r| r.into_entity(None)).collect())
+    }
+
+    /// Evaluate a SPARQL-lite query
+    ///
+    /// Returns the joined and filtered solutions before projection, so the
+    /// caller can drop solutions binding entities it may not see and then
+    /// call [`sparql::project`].
+    pub async fn sparql_solutions(&self, query: &SparqlQuery) -> Result<Vec<sparql::Solution>> {
+        #[derive(Deserialize)]
+        struct PatternRow {
+            s: String,
+            o: Option<serde_json::Value>,
+        }
+
+        let mut rows = Vec::with_capacity(query.patterns.len());
+        for pattern in &query.patterns {
+            let compiled = pattern.compile();
+            let mut request = self.client.query(compiled.query);
+            for (name, value) in compiled.params {
+                request = request.bind((name, value));
+            }
+            let records: Vec<PatternRow> = request
+                .await
+                .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+                .take(0)
+                .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+            rows.push(
+                records
+                    .into_iter()
+                    .filter_map(|r| {
+                        pattern.decode_row(&r.s, &r.o.unwrap_or(serde_json::Value::Null))
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        Ok(sparql::evaluate(query, &rows))
+    }
+
+    /// Source document of each given entity
+    pub async fn entity_documents(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Uuid>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        #[derive(Deserialize)]
+        struct DocumentRow {
+            id: String,
+            document_id: Option<String>,
+        }
+
+        let rows: Vec<DocumentRow> = self
+            .client
+            .query("SELECT record::id(id) AS id, source.document_id AS document_id FROM $ids")
+            .bind(("ids", entity_things(ids)))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| {
+                Some((
+                    Uuid::parse_str(&r.id).ok()?,
+                    Uuid::parse_str(&r.document_id?).ok()?,
+                ))
+            })
+            .collect())
+    }
+
+    /// Remove the graph records derived from a document
+    ///
+    /// Deletes the document's provenance records, the triples left without
+    /// any provenance, and the document's structure nodes (document,
+    /// sections, chunks) together with their edges. Entities and triples
+    /// also supported by other documents are kept. Returns the number of
+    /// records removed.
+    pub async fn purge_document(&self, document_id: Uuid) -> Result<usize> {
+        let document_id = document_id.to_string();
+        let structure_classes = [
+            otl_core::structure::DOCUMENT_CLASS,
+            otl_core::structure::SECTION_CLASS,
+            otl_core::structure::CHUNK_CLASS,
+        ];
+
+        let mut response = self
+            .client
+            .query(
+                r#"
+                LET $triples = array::distinct(
+                    SELECT VALUE triple_id FROM provenance WHERE document_id = $document_id
+                );
+                DELETE provenance WHERE document_id = $document_id RETURN BEFORE;
+                DELETE relates WHERE triple_id INSIDE $triples
+                    AND count(SELECT id FROM provenance WHERE triple_id = $parent.triple_id) = 0
+                    RETURN BEFORE;
+                DELETE entity WHERE source.document_id = $document_id
+                    AND class INSIDE $classes
+                    RETURN BEFORE;
+            "#,
+            )
+            .bind(("document_id", document_id))
+            .bind(("classes", structure_classes))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to purge document graph: {e}")))?;
+
+        /// Deleted record; only counted
+        #[derive(Deserialize)]
+        struct Removed {}
+
+        let mut removed = 0;
+        for statement in 1..=3 {
+            let records: Vec<Removed> = response
+                .take(statement)
+                .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+            removed += records.len();
+        }
+        Ok(removed)
+    }
+
+    /// Graph records of one document
+    ///
+    /// Contains the edges recorded for the document or supported by its
+    /// provenance, the document's provenance records, and the entities
+    /// sourced from the document together with the endpoints of its edges.
+    pub async fn document_snapshot(&self, document_id: Uuid) -> Result<GraphSnapshot> {
+        let mut response = self
+            .client
+            .query(
+                r#"
+                LET $triples = array::distinct(
+                    SELECT VALUE triple_id FROM provenance WHERE document_id = $document_id
+                );
+                SELECT record::id(in) AS subject, record::id(out) AS object,
+                    triple_id, predicate, confidence, document_id,
+                    (inferred ?? false) AS inferred
+                    FROM relates
+                    WHERE document_id = $document_id OR triple_id INSIDE $triples;
+                SELECT * FROM provenance WHERE document_id = $document_id ORDER BY recorded_at ASC;
+            "#,
+            )
+            .bind(("document_id", document_id.to_string()))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Graph export failed: {e}")))?;
+
+        let edges: Vec<EdgeSnapshot> = response
+            .take(1)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+        let provenance: Vec<ProvenanceSnapshot> = response
+            .take(2)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        let mut endpoints: Vec<Uuid> = edges
+            .iter()
+            .flat_map(|e| [&e.subject, &e.object])
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+        endpoints.sort_unstable();
+        endpoints.dedup();
+
+        let entities: Vec<EntitySnapshot> = self
+            .client
+            .query(
+                r#"
+                SELECT record::id(id) AS id, class, properties, source, created_at, updated_at
+                    FROM entity
+                    WHERE source.document_id = $document_id OR id INSIDE $endpoints;
+            "#,
+            )
+            .bind(("document_id", document_id.to_string()))
+            .bind(("endpoints", entity_things(&endpoints)))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Graph export failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(GraphSnapshot::new(entities, edges, provenance))
+    }
+
+    /// Graph records of one entity
+    ///
+    /// Contains the entity and the entities it is related to, the edges
+    /// touching it and the provenance records of those edges. Empty if the
+    /// entity does not exist.
+    pub async fn entity_snapshot(&self, entity_id: Uuid) -> Result<GraphSnapshot> {
+        let mut response = self
+            .client
+            .query(
+                r#"
+                LET $entity = type::thing("entity", $entity_id);
+                SELECT record::id(in) AS subject, record::id(out) AS object,
+                    triple_id, predicate, confidence, document_id,
+                    (inferred ?? false) AS inferred
+                    FROM relates
+                    WHERE in = $entity OR out = $entity;
+                LET $triples = array::distinct(
+                    SELECT VALUE triple_id FROM relates
+                    WHERE (in = $entity OR out = $entity) AND triple_id != NONE
+                );
+                SELECT * FROM provenance WHERE triple_id INSIDE $triples ORDER BY recorded_at ASC;
+            "#,
+            )
+            .bind(("entity_id", entity_id.to_string()))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Entity history query failed: {e}")))?;
+
+        let edges: Vec<EdgeSnapshot> = response
+            .take(1)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+        let provenance: Vec<ProvenanceSnapshot> = response
+            .take(3)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        let mut ids: Vec<Uuid> = edges
+            .iter()
+            .flat_map(|e| [&e.subject, &e.object])
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .chain([entity_id])
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let entities: Vec<EntitySnapshot> = self
+            .client
+            .query(
+                r#"
+                SELECT record::id(id) AS id, class, properties, source, created_at, updated_at
+                    FROM entity
+                    WHERE id INSIDE $ids;
+            "#,
+            )
+            .bind(("ids", entity_things(&ids)))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Entity history query failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(GraphSnapshot::new(entities, edges, provenance))
+    }
+
+    /// Entities within `depth` hops of an entity, for visualization
+    ///
+    /// Walks domain relations breadth first and keeps at most `max_nodes`
+    /// entities, nearest first (ties in ID order); the rest are counted as
+    /// omitted. Edges between the kept entities are included, provenance is
+    /// not. The snapshot is empty if the entity does not exist.
+    pub async fn neighborhood(
+        &self,
+        center: Uuid,
+        depth: u32,
+        max_nodes: usize,
+    ) -> Result<Neighborhood> {
+        const EDGE_FIELDS: &str = "record::id(in) AS subject, record::id(out) AS object, \
+             triple_id, predicate, confidence, document_id, (inferred ?? false) AS inferred";
+
+        let mut hops: HashMap<String, u32> = HashMap::from([(center.to_string(), 0)]);
+        let mut frontier = vec![center];
+        let mut omitted = 0;
+        for hop in 1..=depth {
+            if frontier.is_empty() {
+                break;
+            }
+            let edges: Vec<EdgeSnapshot> = self
+                .client
+                .query(format!(
+                    "SELECT {EDGE_FIELDS} FROM relates WHERE in INSIDE $ids OR out INSIDE $ids"
+                ))
+                .bind(("ids", entity_things(&frontier)))
+                .await
+                .map_err(|e| OtlError::DatabaseError(format!("Neighborhood query failed: {e}")))?
+                .take(0)
+                .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+            let mut reached: Vec<Uuid> = edges
+                .iter()
+                .filter(|e| !is_structure_predicate(e.predicate.as_deref().unwrap_or_default()))
+                .flat_map(|e| [&e.subject, &e.object])
+                .filter(|id| !hops.contains_key(id.as_str()))
+                .filter_map(|id| Uuid::parse_str(id).ok())
+                .collect();
+            reached.sort_unstable();
+            reached.dedup();
+
+            let room = max_nodes.saturating_sub(hops.len());
+            if reached.len() > room {
+                omitted += reached.len() - room;
+                reached.truncate(room);
+            }
+            for id in &reached {
+                hops.insert(id.to_string(), hop);
+            }
+            frontier = reached;
+        }
+
+        let ids: Vec<Uuid> = hops
+            .keys()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+        let mut response = self
+            .client
+            .query(
+                "SELECT record::id(id) AS id, class, properties, source, created_at, updated_at \
+                 FROM entity WHERE id INSIDE $ids",
+            )
+            .query(format!(
+                "SELECT {EDGE_FIELDS} FROM relates WHERE in INSIDE $ids AND out INSIDE $ids"
+            ))
+            .bind(("ids", entity_things(&ids)))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Neighborhood query failed: {e}")))?;
+        let entities: Vec<EntitySnapshot> = response
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+        let edges: Vec<EdgeSnapshot> = response
+            .take(1)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(Neighborhood {
+            center: center.to_string(),
+            snapshot: GraphSnapshot::new(entities, edges, Vec::new()),
+            hops,
+            omitted,
+        })
+    }
+
+    /// Record counts of the graph tables
+    pub async fn counts(&self) -> Result<GraphCounts> {
+        let mut response = self
+            .client
+            .query(
+                r#"
+                RETURN count(SELECT id FROM entity);
+                RETURN count(SELECT id FROM relates);
+                RETURN count(SELECT id FROM provenance);
+            "#,
+            )
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?;
+
+        let mut counts = [0u64; 3];
+        for (statement, count) in counts.iter_mut().enumerate() {
+            let value: Option<u64> = response
+                .take(statement)
+                .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+            *count = value.unwrap_or_default();
+        }
+        Ok(GraphCounts {
+            entities: counts[0],
+            edges: counts[1],
+            provenance: counts[2],
+        })
+    }
+
+    /// Read the whole graph into a snapshot
+    pub async fn export_snapshot(&self) -> Result<GraphSnapshot> {
+        let mut response = self
+            .client
+            .query(
+                r#"
+                SELECT record::id(id) AS id, class, properties, source, created_at, updated_at
+                    FROM entity;
+                SELECT record::id(in) AS subject, record::id(out) AS object,
+                    triple_id, predicate, confidence, document_id,
+                    (inferred ?? false) AS inferred
+                    FROM relates;
+                SELECT * FROM provenance ORDER BY recorded_at ASC;
+            "#,
+            )
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Graph export failed: {e}")))?;
+
+        let entities: Vec<EntitySnapshot> = response
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+        let edges: Vec<EdgeSnapshot> = response
+            .take(1)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+        let provenance: Vec<ProvenanceSnapshot> = response
+            .take(2)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(GraphSnapshot::new(entities, edges, provenance))
+    }
+
+    /// Replace the contents of the graph with a snapshot
+    ///
+    /// All existing entities, edges and provenance records are deleted
+    /// first. Records are written in batches, so a failure part way leaves
+    /// a partial graph; importing the snapshot again repairs it.
+    pub async fn import_snapshot(&self, snapshot: &GraphSnapshot) -> Result<()> {
+        const BATCH_SIZE: usize = 500;
+
+        self.client
+            .query("DELETE provenance; DELETE relates; DELETE entity;")
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to clear graph: {e}")))?;
+
+        for batch in snapshot.entities.chunks(BATCH_SIZE) {
+            self.client
+                .query(
+                    r#"
+                    FOR $entity IN $entities {
+                        CREATE type::thing("entity", $entity.id) CONTENT {
+                            class: $entity.class,
+                            properties: $entity.properties,
+                            source: $entity.source,
+                            created_at: <datetime> ($entity.created_at ?? time::now()),
+                            updated_at: <datetime> ($entity.updated_at ?? time::now())
+                        };
+                    };
+                "#,
+                )
+                .bind(("entities", batch.to_vec()))
+                .await
+                .and_then(surrealdb::Response::check)
+                .map_err(|e| OtlError::DatabaseError(format!("Failed to import entities: {e}")))?;
+        }
+
+        for batch in snapshot.edges.chunks(BATCH_SIZE) {
+            self.client
+                .query(
+                    r#"
+                    FOR $edge IN $edges {
+                        LET $from = type::thing("entity", $edge.subject);
+                        LET $to = type::thing("entity", $edge.object);
+                        RELATE $from->relates->$to SET
+                            triple_id = $edge.triple_id,
+                            predicate = $edge.predicate,
+                            confidence = $edge.confidence,
+                            document_id = $edge.document_id,
+                            inferred = $edge.inferred;
+                    };
+                "#,
+                )
+                .bind(("edges", batch.to_vec()))
+                .await
+                .and_then(surrealdb::Response::check)
+                .map_err(|e| OtlError::DatabaseError(format!("Failed to import edges: {e}")))?;
+        }
+
+        for batch in snapshot.provenance.chunks(BATCH_SIZE) {
+            self.client
+                .query(
+                    r#"
+                    FOR $record IN $records {
+                        CREATE provenance CONTENT $record;
+                    };
+                "#,
+                )
+                .bind(("records", batch.to_vec()))
+                .await
+                .and_then(surrealdb::Response::check)
+                .map_err(|e| {
+                    OtlError::DatabaseError(format!("Failed to import provenance: {e}"))
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Recompute the triples entailed by the ontology rules
+    ///
+    /// Runs `rules` over every asserted edge, deletes the inferred edges of
+    /// the previous run and stores the new ones flagged `inferred = true`
+    /// together with the rule and premises they come from. An extracted
+    /// triple that matches an inferred edge later takes the edge over (see
+    /// [`store_triple_with_provenance`](Self::store_triple_with_provenance)).
+    pub async fn materialize_inferences(&self, rules: &InferenceRules) -> Result<InferenceReport> {
+        const BATCH_SIZE: usize = 500;
+
+        #[derive(Deserialize)]
+        struct EntityClass {
+            id: String,
+            class: String,
+        }
+
+        let mut response = self
+            .client
+            .query(
+                r#"
+                SELECT record::id(id) AS id, class FROM entity;
+                SELECT record::id(in) AS subject, record::id(out) AS object,
+                    triple_id, predicate, confidence, document_id, false AS inferred
+                    FROM relates WHERE inferred != true;
+            "#,
+            )
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Inference query failed: {e}")))?;
+
+        let classes: Vec<EntityClass> = response
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+        let edges: Vec<EdgeSnapshot> = response
+            .take(1)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+        let classes: HashMap<String, String> =
+            classes.into_iter().map(|e| (e.id, e.class)).collect();
+
+        let inference = rules.infer(&classes, &edges);
+
+        let removed: Vec<serde_json::Value> = self
+            .client
+            .query("DELETE relates WHERE inferred = true RETURN BEFORE")
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to clear inferences: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        for batch in inference.triples.chunks(BATCH_SIZE) {
+            self.client
+                .query(
+                    r#"
+                    FOR $triple IN $triples {
+                        LET $from = type::thing("entity", $triple.subject);
+                        LET $to = type::thing("entity", $triple.object);
+                        RELATE $from->relates->$to SET
+                            triple_id = $triple.triple_id,
+                            predicate = $triple.predicate,
+                            confidence = $triple.confidence,
+                            inferred = true,
+                            rule = $triple.rule,
+                            derived_from = $triple.derived_from;
+                    };
+                "#,
+                )
+                .bind(("triples", batch.to_vec()))
+                .await
+                .and_then(surrealdb::Response::check)
+                .map_err(|e| OtlError::DatabaseError(format!("Failed to store inferences: {e}")))?;
+        }
+
+        Ok(InferenceReport::new(&inference, removed.len()))
+    }
+}
+
+/// Record IDs of entities, for binding as a query parameter
+fn entity_things(ids: &[Uuid]) -> Vec<surrealdb::sql::Thing> {
+    ids.iter()
+        .map(|id| surrealdb::sql::Thing::from(("entity", id.to_string().as_str())))
+        .collect()
 }
 
 /// Entity record for SurrealDB
@@ -89,6 +881,89 @@ struct EntityRecord {
     updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+impl EntityRecord {
+    /// Convert to a core entity, taking the ID from the record unless given
+    fn into_entity(self, id: Option<Uuid>) -> Entity {
+        let id = id
+            .or_else(|| {
+                self.id
+                    .as_ref()
+                    .and_then(|t| Uuid::parse_str(&t.id.to_raw()).ok())
+            })
+            .unwrap_or_default();
+        Entity {
+            id,
+            class: self.class,
+            properties: serde_json::from_value(self.properties).unwrap_or_default(),
+            source: SourceReference::new(
+                Uuid::parse_str(&self.source.document_id).unwrap_or_default(),
+            ),
+            created_at: self.created_at.unwrap_or_default(),
+            updated_at: self.updated_at.unwrap_or_default(),
+        }
+    }
+}
+
+/// Triple edge as selected from the `relates` table
+#[derive(Debug, Clone, Deserialize)]
+struct TripleRecord {
+    predicate: String,
+    confidence: f32,
+    subject: String,
+    object: String,
+}
+
+/// Provenance record for SurrealDB
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProvenanceRecord {
+    triple_id: String,
+    document_id: String,
+    page: Option<u32>,
+    section: Option<String>,
+    offset: Option<usize>,
+    snippet: Option<String>,
+    extractor: Option<String>,
+    confidence: f32,
+    recorded_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&Provenance> for ProvenanceRecord {
+    fn from(p: &Provenance) -> Self {
+        Self {
+            triple_id: p.triple_id.to_string(),
+            document_id: p.source.document_id.to_string(),
+            page: p.source.page,
+            section: p.source.section.clone(),
+            offset: p.source.offset,
+            snippet: p.snippet.clone(),
+            extractor: p.extractor.clone(),
+            confidence: p.confidence,
+            recorded_at: p.recorded_at,
+        }
+    }
+}
+
+impl From<ProvenanceRecord> for Provenance {
+    fn from(r: ProvenanceRecord) -> Self {
+        let source = SourceReference {
+            document_id: Uuid::parse_str(&r.document_id).unwrap_or_default(),
+            page: r.page,
+            section: r.section,
+            offset: r.offset,
+            chunk_index: None,
+            confidence: r.confidence,
+        };
+        Self {
+            triple_id: Uuid::parse_str(&r.triple_id).unwrap_or_default(),
+            source,
+            snippet: r.snippet,
+            extractor: r.extractor,
+            confidence: r.confidence,
+            recorded_at: r.recorded_at,
+        }
+    }
+}
+
 /// Source reference record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SourceRecord {
@@ -132,22 +1007,9 @@ impl super::GraphStore for SurrealDbStore {
     }
 
     async fn store_triple(&self, triple: &Triple) -> Result<()> {
-        let query = format!(
-            "RELATE entity:{}->relates->entity:{} SET predicate = $predicate, confidence = $confidence",
-            triple.subject, triple.object
-        );
-
-        let predicate = triple.predicate.clone();
-        let confidence = triple.confidence;
-
-        self.client
-            .query(&query)
-            .bind(("predicate", predicate))
-            .bind(("confidence", confidence))
+        self.store_triple_with_provenance(triple, &[triple.provenance()])
             .await
-            .map_err(|e| OtlError::DatabaseError(format!("Failed to store triple: {e}")))?;
-
-        Ok(())
+            .map(|_| ())
     }
 
     async fn get_entity(&self, id: Uuid) -> Result<Option<Entity>> {
@@ -157,16 +1019,7 @@ impl super::GraphStore for SurrealDbStore {
             .await
             .map_err(|e| OtlError::DatabaseError(format!("Failed to get entity: {e}")))?;
 
-        Ok(record.map(|r| Entity {
-            id,
-            class: r.class,
-            properties: serde_json::from_value(r.properties).unwrap_or_default(),
-            source: SourceReference::new(
-                Uuid::parse_str(&r.source.document_id).unwrap_or_default(),
-            ),
-            created_at: r.created_at.unwrap_or_default(),
-            updated_at: r.updated_at.unwrap_or_default(),
-        }))
+        Ok(record.map(|r| r.into_entity(Some(id))))
     }
 
     async fn find_by_class(&self, class: &str, limit: usize) -> Result<Vec<Entity>> {
@@ -181,25 +1034,7 @@ impl super::GraphStore for SurrealDbStore {
             .take(0)
             .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
 
-        Ok(records
-            .into_iter()
-            .map(|r| {
-                let id =
-                    r.id.as_ref()
-                        .and_then(|t| Uuid::parse_str(&t.id.to_string()).ok())
-                        .unwrap_or_default();
-                Entity {
-                    id,
-                    class: r.class,
-                    properties: serde_json::from_value(r.properties).unwrap_or_default(),
-                    source: SourceReference::new(
-                        Uuid::parse_str(&r.source.document_id).unwrap_or_default(),
-                    ),
-                    created_at: r.created_at.unwrap_or_default(),
-                    updated_at: r.updated_at.unwrap_or_default(),
-                }
-            })
-            .collect())
+        Ok(records.into_iter().map(|r| r.into_entity(None)).collect())
     }
 
     async fn traverse(&self, start_id: Uuid, depth: u32) -> Result<Vec<Entity>> {
@@ -228,3 +1063,119 @@ impl super::GraphStore for SurrealDbStore {
         Ok(Vec::new())
     }
 }
+
+#[async_trait]
+impl GraphContextBackend for SurrealDbStore {
+    async fn resolve_entities(&self, text: &str, limit: usize) -> Result<Vec<Entity>> {
+        let records: Vec<EntityRecord> = self
+            .client
+            .query(
+                r#"
+                SELECT * FROM entity
+                WHERE class NOT IN $structure
+                    AND type::is::string(properties.text)
+                    AND string::len(properties.text) > 1
+                    AND string::contains($text, properties.text)
+                ORDER BY source.confidence DESC
+                LIMIT $limit
+            "#,
+            )
+            .bind(("text", text.to_string()))
+            .bind(("structure", [DOCUMENT_CLASS, SECTION_CLASS, CHUNK_CLASS]))
+            .bind(("limit", limit))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(records.into_iter().map(|r| r.into_entity(None)).collect())
+    }
+
+    async fn get_entities(&self, ids: &[Uuid]) -> Result<Vec<Entity>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let records: Vec<EntityRecord> = self
+            .client
+            .query("SELECT * FROM $ids")
+            .bind(("ids", entity_things(ids)))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(records.into_iter().map(|r| r.into_entity(None)).collect())
+    }
+
+    async fn incident_triples(&self, ids: &[Uuid]) -> Result<Vec<Triple>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        #[derive(Deserialize)]
+        struct EdgeRow {
+            triple_id: Option<String>,
+            predicate: Option<String>,
+            confidence: Option<f32>,
+            document_id: Option<String>,
+            subject: String,
+            object: String,
+        }
+
+        let rows: Vec<EdgeRow> = self
+            .client
+            .query(
+                r#"
+                SELECT triple_id, predicate, confidence, document_id,
+                    record::id(in) AS subject, record::id(out) AS object
+                FROM relates WHERE in IN $ids OR out IN $ids
+            "#,
+            )
+            .bind(("ids", entity_things(ids)))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| {
+                let confidence = r.confidence.unwrap_or(0.5);
+                let document_id = r
+                    .document_id
+                    .and_then(|d| Uuid::parse_str(&d).ok())
+                    .unwrap_or_default();
+                let mut triple = Triple::new(
+                    Uuid::parse_str(&r.subject).ok()?,
+                    r.predicate.unwrap_or_else(|| "relates".to_string()),
+                    Uuid::parse_str(&r.object).ok()?,
+                    SourceReference::new(document_id).with_confidence(confidence),
+                    confidence,
+                );
+                if let Some(id) = r.triple_id.and_then(|t| Uuid::parse_str(&t).ok()) {
+                    triple.id = id;
+                }
+                Some(triple)
+            })
+            .collect())
+    }
+
+    async fn provenance_for(&self, triple_ids: &[Uuid]) -> Result<Vec<Provenance>> {
+        if triple_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<String> = triple_ids.iter().map(Uuid::to_string).collect();
+        let records: Vec<ProvenanceRecord> = self
+            .client
+            .query("SELECT * FROM provenance WHERE triple_id IN $ids ORDER BY recorded_at ASC")
+            .bind(("ids", ids))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Query failed: {e}")))?
+            .take(0)
+            .map_err(|e| OtlError::DatabaseError(format!("Result extraction failed: {e}")))?;
+
+        Ok(records.into_iter().map(Provenance::from).collect())
+    }
+}
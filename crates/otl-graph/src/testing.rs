@@ -0,0 +1,261 @@
+//! In-memory graph store for tests
+//!
+//! Keeps entities and triples in insertion order so graph-dependent code can
+//! be tested without SurrealDB. Enabled with the `test-utils` feature.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use otl_core::{Entity, GraphContextBackend, Provenance, Result, Triple};
+use uuid::Uuid;
+
+use crate::GraphStore;
+
+/// Graph store backed by vectors
+///
+/// Storing an entity or triple with a known ID replaces it. Provenance is
+/// derived from the stored triples, one record per triple.
+#[derive(Default)]
+pub struct InMemoryGraphStore {
+    entities: RwLock<Vec<Entity>>,
+    triples: RwLock<Vec<Triple>>,
+}
+
+impl InMemoryGraphStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All stored entities, in insertion order
+    pub fn entities(&self) -> Vec<Entity> {
+        self.entities.read().unwrap().clone()
+    }
+
+    /// All stored triples, in insertion order
+    pub fn triples(&self) -> Vec<Triple> {
+        self.triples.read().unwrap().clone()
+    }
+}
+
+/// Display label of an entity, as the SurrealDB store resolves mentions
+fn label(entity: &Entity) -> Option<&str> {
+    ["text", "name", "label"]
+        .iter()
+        .find_map(|key| entity.properties.get(*key)?.as_str())
+}
+
+#[async_trait]
+impl GraphStore for InMemoryGraphStore {
+    async fn store_entity(&self, entity: &Entity) -> Result<()> {
+        let mut entities = self.entities.write().unwrap();
+        match entities.iter_mut().find(|e| e.id == entity.id) {
+            Some(existing) => *existing = entity.clone(),
+            None => entities.push(entity.clone()),
+        }
+        Ok(())
+    }
+
+    async fn store_triple(&self, triple: &Triple) -> Result<()> {
+        let mut triples = self.triples.write().unwrap();
+        match triples.iter_mut().find(|t| t.id == triple.id) {
+            Some(existing) => *existing = triple.clone(),
+            None => triples.push(triple.clone()),
+        }
+        Ok(())
+    }
+
+    async fn get_entity(&self, id: Uuid) -> Result<Option<Entity>> {
+        Ok(self
+            .entities
+            .read()
+            .unwrap()
+            .iter()
+            .find(|e| e.id == id)
+            .cloned())
+    }
+
+    async fn find_by_class(&self, class: &str, limit: usize) -> Result<Vec<Entity>> {
+        Ok(self
+            .entities
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|e| e.class == class)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    /// Entities within `depth` hops in either direction, nearest first
+    async fn traverse(&self, start_id: Uuid, depth: u32) -> Result<Vec<Entity>> {
+        let triples = self.triples.read().unwrap();
+        let mut seen = HashSet::from([start_id]);
+        let mut queue = VecDeque::from([(start_id, 0)]);
+        let mut reached = Vec::new();
+
+        while let Some((id, hops)) = queue.pop_front() {
+            if hops == depth {
+                continue;
+            }
+            for triple in triples.iter() {
+                let next = match (triple.subject == id, triple.object == id) {
+                    (true, _) => triple.object,
+                    (_, true) => triple.subject,
+                    _ => continue,
+                };
+                if seen.insert(next) {
+                    reached.push(next);
+                    queue.push_back((next, hops + 1));
+                }
+            }
+        }
+
+        let entities = self.entities.read().unwrap();
+        Ok(reached
+            .iter()
+            .filter_map(|id| entities.iter().find(|e| e.id == *id).cloned())
+            .collect())
+    }
+
+    /// Entities whose label contains `query`; there is no query language
+    async fn query(&self, query: &str) -> Result<Vec<Entity>> {
+        Ok(self
+            .entities
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|e| label(e).is_some_and(|l| l.contains(query)))
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl GraphContextBackend for InMemoryGraphStore {
+    async fn resolve_entities(&self, text: &str, limit: usize) -> Result<Vec<Entity>> {
+        Ok(self
+            .entities
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|e| label(e).is_some_and(|l| l.chars().count() > 1 && text.contains(l)))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_entities(&self, ids: &[Uuid]) -> Result<Vec<Entity>> {
+        Ok(self
+            .entities
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|e| ids.contains(&e.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn incident_triples(&self, ids: &[Uuid]) -> Result<Vec<Triple>> {
+        Ok(self
+            .triples
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|t| ids.contains(&t.subject) || ids.contains(&t.object))
+            .cloned()
+            .collect())
+    }
+
+    async fn provenance_for(&self, triple_ids: &[Uuid]) -> Result<Vec<Provenance>> {
+        Ok(self
+            .triples
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|t| triple_ids.contains(&t.id))
+            .map(Triple::provenance)
+            .collect())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::SourceReference;
+
+    fn entity(class: &str, text: &str) -> Entity {
+        let mut entity = Entity::new(class, SourceReference::new(Uuid::new_v4()));
+        entity
+            .properties
+            .insert("text".to_string(), serde_json::json!(text));
+        entity
+    }
+
+    fn triple(subject: &Entity, predicate: &str, object: &Entity) -> Triple {
+        Triple::new(
+            subject.id,
+            predicate,
+            object.id,
+            SourceReference::new(Uuid::new_v4()),
+            0.9,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_traverse_by_depth() {
+        let store = InMemoryGraphStore::new();
+        let leave = entity("LeaveType", "연차휴가");
+        let approval = entity("ApprovalProcess", "부서장 승인");
+        let form = entity("Document", "휴가신청서");
+        for e in [&leave, &approval, &form] {
+            store.store_entity(e).await.unwrap();
+        }
+        store
+            .store_triple(&triple(&leave, "requires", &approval))
+            .await
+            .unwrap();
+        store
+            .store_triple(&triple(&approval, "uses", &form))
+            .await
+            .unwrap();
+
+        let ids = |entities: Vec<Entity>| entities.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(store.traverse(leave.id, 1).await.unwrap()),
+            vec![approval.id]
+        );
+        assert_eq!(
+            ids(store.traverse(form.id, 2).await.unwrap()),
+            vec![approval.id, leave.id]
+        );
+        assert!(store.traverse(leave.id, 0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_store_replaces_and_resolves() {
+        let store = InMemoryGraphStore::new();
+        let mut leave = entity("LeaveType", "연차휴가");
+        store.store_entity(&leave).await.unwrap();
+        leave
+            .properties
+            .insert("text".to_string(), serde_json::json!("병가"));
+        store.store_entity(&leave).await.unwrap();
+
+        assert_eq!(store.entities().len(), 1);
+        assert_eq!(store.find_by_class("LeaveType", 10).await.unwrap().len(), 1);
+        let resolved = store
+            .resolve_entities("병가는 며칠인가요?", 5)
+            .await
+            .unwrap();
+        assert_eq!(resolved[0].id, leave.id);
+        assert!(store.query("연차").await.unwrap().is_empty());
+    }
+}
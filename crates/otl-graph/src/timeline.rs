@@ -0,0 +1,304 @@
+//! Entity timelines
+//!
+//! Orders what the graph records about an entity into dated events: when
+//! the entity was created and last updated, when each relation was first
+//! extracted, when further documents confirmed it, and when every document
+//! supporting it was superseded by a newer version. Structure edges
+//! (sections, chunks, mentions) are left out.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use otl_core::structure::is_structure_predicate;
+use serde::Serialize;
+
+use crate::snapshot::GraphSnapshot;
+
+/// What happened to the entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    /// The entity was extracted for the first time
+    EntityCreated,
+    /// The entity's properties were last changed
+    EntityUpdated,
+    /// A relation was extracted for the first time
+    RelationAdded,
+    /// Another document supported an existing relation
+    RelationConfirmed,
+    /// Every document supporting a relation was superseded
+    RelationSuperseded,
+}
+
+impl TimelineEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EntityCreated => "entity_created",
+            Self::EntityUpdated => "entity_updated",
+            Self::RelationAdded => "relation_added",
+            Self::RelationConfirmed => "relation_confirmed",
+            Self::RelationSuperseded => "relation_superseded",
+        }
+    }
+}
+
+/// Dated event in an entity's history
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineEvent {
+    pub at: DateTime<Utc>,
+    pub kind: TimelineEventKind,
+    /// Document the event comes from
+    pub document_id: Option<String>,
+    pub triple_id: Option<String>,
+    pub predicate: Option<String>,
+    /// `outgoing` when the entity is the subject, `incoming` otherwise
+    pub direction: Option<&'static str>,
+    /// Entity at the other end of the relation
+    pub related_id: Option<String>,
+    pub confidence: Option<f32>,
+    /// Document replacing the superseded source
+    pub superseded_by: Option<String>,
+}
+
+impl TimelineEvent {
+    fn new(at: DateTime<Utc>, kind: TimelineEventKind) -> Self {
+        Self {
+            at,
+            kind,
+            document_id: None,
+            triple_id: None,
+            predicate: None,
+            direction: None,
+            related_id: None,
+            confidence: None,
+            superseded_by: None,
+        }
+    }
+}
+
+/// Supersession of a source document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Supersession {
+    /// Document replacing it
+    pub superseded_by: String,
+    /// When the supersession was detected
+    pub at: DateTime<Utc>,
+}
+
+/// Events of `entity_id` in `snapshot`, oldest first
+///
+/// `snapshot` holds the entity, the edges touching it and their provenance
+/// (see [`SurrealDbStore::entity_snapshot`](crate::SurrealDbStore::entity_snapshot));
+/// `superseded` maps superseded document IDs to their replacement.
+pub fn timeline(
+    entity_id: &str,
+    snapshot: &GraphSnapshot,
+    superseded: &HashMap<String, Supersession>,
+) -> Vec<TimelineEvent> {
+    let mut events = Vec::new();
+
+    if let Some(entity) = snapshot.entities.iter().find(|e| e.id == entity_id) {
+        let document_id = entity
+            .source
+            .get("document_id")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        if let Some(created_at) = entity.created_at {
+            let mut event = TimelineEvent::new(created_at, TimelineEventKind::EntityCreated);
+            event.document_id = document_id.clone();
+            events.push(event);
+        }
+        if let (Some(created_at), Some(updated_at)) = (entity.created_at, entity.updated_at) {
+            if updated_at - created_at > chrono::Duration::seconds(1) {
+                let mut event = TimelineEvent::new(updated_at, TimelineEventKind::EntityUpdated);
+                event.document_id = document_id;
+                events.push(event);
+            }
+        }
+    }
+
+    for edge in &snapshot.edges {
+        let predicate = edge.predicate.as_deref().unwrap_or_default();
+        if is_structure_predicate(predicate) {
+            continue;
+        }
+        let (direction, related_id) = if edge.subject == entity_id {
+            ("outgoing", &edge.object)
+        } else if edge.object == entity_id {
+            ("incoming", &edge.subject)
+        } else {
+            continue;
+        };
+        let relation_event = |at, kind, document_id: Option<&str>, confidence| {
+            let mut event = TimelineEvent::new(at, kind);
+            event.document_id = document_id.map(str::to_string);
+            event.triple_id = edge.triple_id.clone();
+            event.predicate = edge.predicate.clone();
+            event.direction = Some(direction);
+            event.related_id = Some(related_id.clone());
+            event.confidence = confidence;
+            event
+        };
+
+        let mut evidence: Vec<_> = snapshot
+            .provenance
+            .iter()
+            .filter(|p| edge.triple_id.as_deref() == Some(p.triple_id.as_str()))
+            .collect();
+        evidence.sort_by_key(|p| p.recorded_at);
+
+        let mut sources = BTreeSet::new();
+        for (i, record) in evidence.iter().enumerate() {
+            let first_from_document = sources.insert(record.document_id.as_str());
+            let kind = if i == 0 {
+                TimelineEventKind::RelationAdded
+            } else if first_from_document {
+                TimelineEventKind::RelationConfirmed
+            } else {
+                continue;
+            };
+            events.push(relation_event(
+                record.recorded_at,
+                kind,
+                Some(&record.document_id),
+                Some(record.confidence),
+            ));
+        }
+        if let Some(document_id) = &edge.document_id {
+            sources.insert(document_id.as_str());
+        }
+
+        // Superseded once the last of its sources is
+        let replaced: Option<Vec<&Supersession>> =
+            sources.iter().map(|doc| superseded.get(*doc)).collect();
+        if let Some(latest) = replaced
+            .filter(|r| !r.is_empty())
+            .and_then(|r| r.into_iter().max_by_key(|s| s.at))
+        {
+            let mut event = relation_event(
+                latest.at,
+                TimelineEventKind::RelationSuperseded,
+                None,
+                edge.confidence,
+            );
+            event.superseded_by = Some(latest.superseded_by.clone());
+            events.push(event);
+        }
+    }
+
+    events.sort_by_key(|event| event.at);
+    events
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{EdgeSnapshot, EntitySnapshot, ProvenanceSnapshot};
+    use chrono::TimeZone;
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, 9, 0, 0).unwrap()
+    }
+
+    fn edge(triple_id: &str, subject: &str, predicate: &str, object: &str) -> EdgeSnapshot {
+        EdgeSnapshot {
+            subject: subject.to_string(),
+            object: object.to_string(),
+            triple_id: Some(triple_id.to_string()),
+            predicate: Some(predicate.to_string()),
+            confidence: Some(0.9),
+            document_id: None,
+            inferred: false,
+        }
+    }
+
+    fn evidence(triple_id: &str, document_id: &str, day: u32) -> ProvenanceSnapshot {
+        ProvenanceSnapshot {
+            triple_id: triple_id.to_string(),
+            document_id: document_id.to_string(),
+            page: None,
+            section: None,
+            offset: None,
+            snippet: None,
+            extractor: Some("rule".to_string()),
+            confidence: 0.9,
+            recorded_at: at(day),
+        }
+    }
+
+    #[test]
+    fn test_timeline_events() {
+        let snapshot = GraphSnapshot::new(
+            vec![EntitySnapshot {
+                id: "leave".to_string(),
+                class: "LeaveType".to_string(),
+                properties: serde_json::json!({ "text": "육아휴직" }),
+                source: serde_json::json!({ "document_id": "rules-2025" }),
+                created_at: Some(at(1)),
+                updated_at: Some(at(1)),
+            }],
+            vec![
+                edge("t1", "leave", "requiresDocument", "form-a"),
+                edge("t2", "leave", "requiresDocument", "form-b"),
+                edge("t3", "chunk", "mentions", "leave"),
+                edge("t4", "hr", "manages", "leave"),
+            ],
+            vec![
+                evidence("t1", "rules-2025", 1),
+                evidence("t1", "rules-2025", 2),
+                evidence("t1", "guide", 3),
+                evidence("t2", "rules-2026", 10),
+                evidence("t3", "rules-2025", 1),
+                evidence("t4", "rules-2025", 4),
+                evidence("t4", "rules-2026", 10),
+            ],
+        );
+        let superseded = HashMap::from([(
+            "rules-2025".to_string(),
+            Supersession {
+                superseded_by: "rules-2026".to_string(),
+                at: at(11),
+            },
+        )]);
+
+        let events = timeline("leave", &snapshot, &superseded);
+        let summary: Vec<_> = events
+            .iter()
+            .map(|e| (e.kind, e.triple_id.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (TimelineEventKind::EntityCreated, None),
+                (TimelineEventKind::RelationAdded, Some("t1")),
+                (TimelineEventKind::RelationConfirmed, Some("t1")),
+                (TimelineEventKind::RelationAdded, Some("t4")),
+                (TimelineEventKind::RelationAdded, Some("t2")),
+                (TimelineEventKind::RelationConfirmed, Some("t4")),
+            ]
+        );
+        assert_eq!(events[3].direction, Some("incoming"));
+
+        // t1 loses its last source once the guide is superseded too
+        let mut superseded = superseded;
+        superseded.insert(
+            "guide".to_string(),
+            Supersession {
+                superseded_by: "guide-v2".to_string(),
+                at: at(12),
+            },
+        );
+        let events = timeline("leave", &snapshot, &superseded);
+        let last = events.last().unwrap();
+        assert_eq!(last.kind, TimelineEventKind::RelationSuperseded);
+        assert_eq!(last.triple_id.as_deref(), Some("t1"));
+        assert_eq!(last.superseded_by.as_deref(), Some("guide-v2"));
+        assert_eq!(last.at, at(12));
+    }
+}
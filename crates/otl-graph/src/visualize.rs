@@ -0,0 +1,374 @@
+//! Render-ready graph data
+//!
+//! Turns the neighborhood of an entity into nodes and edges a frontend can
+//! draw directly: each node carries a label, a color derived from its
+//! class, its hop distance from the center (for concentric layouts), its
+//! degree and a community cluster ID. Communities are found with the
+//! local moving phase of the Louvain method, visiting nodes in ID order so
+//! the same neighborhood always gets the same clusters.
+//!
+//! Structure nodes and edges (documents, sections, chunks, mentions) are
+//! left out.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use otl_core::structure::{is_structure_class, is_structure_predicate};
+use serde::Serialize;
+
+use crate::snapshot::GraphSnapshot;
+
+/// Default number of hops around the center
+pub const DEFAULT_DEPTH: u32 = 2;
+
+/// Deepest neighborhood served
+pub const MAX_DEPTH: u32 = 4;
+
+/// Default number of nodes returned
+pub const DEFAULT_MAX_NODES: usize = 200;
+
+/// Most nodes returned, whatever the request
+pub const MAX_NODES: usize = 1000;
+
+/// Community detection rounds before giving up on convergence
+const MAX_COMMUNITY_ROUNDS: usize = 20;
+
+/// Node colors, assigned by class
+const PALETTE: [&str; 12] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac", "#86bcb6", "#d37295",
+];
+
+/// Entities within a number of hops of a center entity
+#[derive(Debug, Clone)]
+pub struct Neighborhood {
+    pub center: String,
+    /// Entities and the edges between them (no provenance)
+    pub snapshot: GraphSnapshot,
+    /// Hop distance of each entity from the center
+    pub hops: HashMap<String, u32>,
+    /// Entities reached but left out by the node cap
+    pub omitted: usize,
+}
+
+/// Node of the rendered graph
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VisualNode {
+    pub id: String,
+    pub label: String,
+    pub class: String,
+    pub color: &'static str,
+    /// Community the node belongs to
+    pub cluster: usize,
+    /// Hops from the center (0 for the center itself)
+    pub depth: u32,
+    pub degree: usize,
+}
+
+/// Edge of the rendered graph
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VisualEdge {
+    pub id: Option<String>,
+    pub source: String,
+    pub target: String,
+    pub predicate: String,
+    pub confidence: Option<f32>,
+    pub inferred: bool,
+}
+
+/// Community of nodes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VisualCluster {
+    pub id: usize,
+    pub size: usize,
+    /// Most common class among the members
+    pub dominant_class: String,
+}
+
+/// Ready-to-render graph around an entity
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphView {
+    pub center: String,
+    pub nodes: Vec<VisualNode>,
+    pub edges: Vec<VisualEdge>,
+    /// Largest first; cluster IDs are positions in this list
+    pub clusters: Vec<VisualCluster>,
+    /// Class to node color, for legends
+    pub legend: BTreeMap<String, &'static str>,
+    /// Whether the node cap left entities out
+    pub truncated: bool,
+    pub omitted_nodes: usize,
+}
+
+/// Color of a class, stable across requests
+pub fn class_color(class: &str) -> &'static str {
+    // FNV-1a, so the color does not depend on the hasher's random state
+    let hash = class.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    PALETTE[(hash % PALETTE.len() as u64) as usize]
+}
+
+/// Build the rendered graph of a neighborhood
+pub fn render(neighborhood: &Neighborhood) -> GraphView {
+    let entities: Vec<_> = neighborhood
+        .snapshot
+        .entities
+        .iter()
+        .filter(|e| !is_structure_class(&e.class))
+        .collect();
+    // Nodes are numbered in ID order
+    let mut ids: Vec<&str> = entities.iter().map(|e| e.id.as_str()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let index: HashMap<&str, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let mut edges = Vec::new();
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); index.len()];
+    for edge in &neighborhood.snapshot.edges {
+        let predicate = edge.predicate.clone().unwrap_or_default();
+        if is_structure_predicate(&predicate) {
+            continue;
+        }
+        let (Some(&s), Some(&o)) = (
+            index.get(edge.subject.as_str()),
+            index.get(edge.object.as_str()),
+        ) else {
+            continue;
+        };
+        adjacency[s].push(o);
+        adjacency[o].push(s);
+        edges.push(VisualEdge {
+            id: edge.triple_id.clone(),
+            source: edge.subject.clone(),
+            target: edge.object.clone(),
+            predicate,
+            confidence: edge.confidence,
+            inferred: edge.inferred,
+        });
+    }
+
+    let labels = detect_communities(&adjacency);
+
+    // Number clusters by size, largest first
+    let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (node, label) in labels.iter().enumerate() {
+        members.entry(*label).or_default().push(node);
+    }
+    let mut groups: Vec<Vec<usize>> = members.into_values().collect();
+    groups.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+    let mut cluster_of = vec![0; ids.len()];
+    for (cluster, group) in groups.iter().enumerate() {
+        for &node in group {
+            cluster_of[node] = cluster;
+        }
+    }
+
+    let class_of: HashMap<&str, &str> = entities
+        .iter()
+        .map(|e| (e.id.as_str(), e.class.as_str()))
+        .collect();
+    let clusters = groups
+        .iter()
+        .enumerate()
+        .map(|(id, group)| {
+            let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for &node in group {
+                *counts.entry(class_of[ids[node]]).or_insert(0) += 1;
+            }
+            let dominant_class = counts
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+                .map(|(class, _)| class.to_string())
+                .unwrap_or_default();
+            VisualCluster {
+                id,
+                size: group.len(),
+                dominant_class,
+            }
+        })
+        .collect();
+
+    let mut legend = BTreeMap::new();
+    let mut seen = HashSet::new();
+    let mut nodes: Vec<VisualNode> = Vec::new();
+    for entity in &entities {
+        if !seen.insert(entity.id.as_str()) {
+            continue;
+        }
+        let i = index[entity.id.as_str()];
+        let color = class_color(&entity.class);
+        legend.insert(entity.class.clone(), color);
+        nodes.push(VisualNode {
+            id: entity.id.clone(),
+            label: entity_label(&entity.properties),
+            class: entity.class.clone(),
+            color,
+            cluster: cluster_of[i],
+            depth: neighborhood.hops.get(&entity.id).copied().unwrap_or(0),
+            degree: adjacency[i].len(),
+        });
+    }
+    nodes.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.id.cmp(&b.id)));
+
+    GraphView {
+        center: neighborhood.center.clone(),
+        nodes,
+        edges,
+        clusters,
+        legend,
+        truncated: neighborhood.omitted > 0,
+        omitted_nodes: neighborhood.omitted,
+    }
+}
+
+/// Display name of an entity
+fn entity_label(properties: &serde_json::Value) -> String {
+    ["text", "name", "label"]
+        .iter()
+        .find_map(|key| properties.get(key).and_then(|v| v.as_str()))
+        .unwrap_or("Unnamed")
+        .to_string()
+}
+
+/// Community of every node
+///
+/// Local moving phase of the Louvain method: each node in turn joins the
+/// neighboring community with the largest modularity gain, until no node
+/// moves.
+fn detect_communities(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let degree: Vec<f64> = adjacency.iter().map(|a| a.len() as f64).collect();
+    let two_m: f64 = degree.iter().sum();
+    let mut community: Vec<usize> = (0..adjacency.len()).collect();
+    if two_m == 0.0 {
+        return community;
+    }
+    // Sum of member degrees per community
+    let mut total = degree.clone();
+
+    for _ in 0..MAX_COMMUNITY_ROUNDS {
+        let mut moved = false;
+        for node in 0..adjacency.len() {
+            let current = community[node];
+            total[current] -= degree[node];
+            let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+            for &neighbor in adjacency[node].iter().filter(|&&n| n != node) {
+                *links.entry(community[neighbor]).or_insert(0.0) += 1.0;
+            }
+            let gain = |c: usize, k_in: f64| k_in - total[c] * degree[node] / two_m;
+
+            let mut best = current;
+            let mut best_gain = gain(current, links.get(&current).copied().unwrap_or(0.0));
+            for (&candidate, &k_in) in &links {
+                let candidate_gain = gain(candidate, k_in);
+                if candidate_gain > best_gain + f64::EPSILON {
+                    best = candidate;
+                    best_gain = candidate_gain;
+                }
+            }
+            total[best] += degree[node];
+            if best != current {
+                community[node] = best;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    community
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{EdgeSnapshot, EntitySnapshot};
+
+    fn entity(id: &str, class: &str) -> EntitySnapshot {
+        EntitySnapshot {
+            id: id.to_string(),
+            class: class.to_string(),
+            properties: serde_json::json!({ "text": id.to_uppercase() }),
+            source: serde_json::json!({ "document_id": "doc" }),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn edge(subject: &str, object: &str) -> EdgeSnapshot {
+        EdgeSnapshot {
+            subject: subject.to_string(),
+            object: object.to_string(),
+            triple_id: Some(format!("{subject}-{object}")),
+            predicate: Some("relatedTo".to_string()),
+            confidence: Some(0.9),
+            document_id: Some("doc".to_string()),
+            inferred: false,
+        }
+    }
+
+    #[test]
+    fn test_render_clusters_and_layout_hints() {
+        // Two triangles joined by the c-d bridge
+        let entities = vec![
+            entity("a", "Employee"),
+            entity("b", "Employee"),
+            entity("c", "Department"),
+            entity("d", "Policy"),
+            entity("e", "Regulation"),
+            entity("f", "Regulation"),
+            entity("chunk", "Chunk"),
+        ];
+        let mut edges: Vec<EdgeSnapshot> = [
+            ("a", "b"),
+            ("b", "c"),
+            ("a", "c"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "f"),
+            ("d", "f"),
+        ]
+        .iter()
+        .map(|(s, o)| edge(s, o))
+        .collect();
+        let mut mention = edge("chunk", "a");
+        mention.predicate = Some("mentions".to_string());
+        edges.push(mention);
+
+        let hops = [("c", 0), ("a", 1), ("b", 1), ("d", 1), ("e", 2), ("f", 2)]
+            .iter()
+            .map(|(id, hop)| (id.to_string(), *hop))
+            .collect();
+        let view = render(&Neighborhood {
+            center: "c".to_string(),
+            snapshot: GraphSnapshot::new(entities, edges, Vec::new()),
+            hops,
+            omitted: 3,
+        });
+
+        assert_eq!(view.nodes.len(), 6);
+        assert_eq!(view.edges.len(), 7);
+        assert_eq!(view.nodes[0].id, "c");
+        assert_eq!(view.nodes[0].label, "C");
+        assert_eq!(view.nodes[0].degree, 3);
+        assert!(view.truncated);
+        assert_eq!(view.omitted_nodes, 3);
+
+        let cluster = |id: &str| view.nodes.iter().find(|n| n.id == id).unwrap().cluster;
+        assert_eq!(view.clusters.len(), 2);
+        assert_eq!(cluster("a"), cluster("b"));
+        assert_eq!(cluster("a"), cluster("c"));
+        assert_eq!(cluster("d"), cluster("e"));
+        assert_eq!(cluster("d"), cluster("f"));
+        assert_ne!(cluster("a"), cluster("d"));
+        assert_eq!(view.clusters[cluster("e")].dominant_class, "Regulation");
+
+        assert_eq!(view.legend.len(), 4);
+        assert_eq!(view.legend["Employee"], class_color("Employee"));
+    }
+}
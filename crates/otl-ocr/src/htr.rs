@@ -0,0 +1,334 @@
+//! Handwritten text recognition
+//!
+//! Approval forms and the like are partly filled in by hand, which
+//! Tesseract reads poorly. [`HtrEngine`] calls an external handwritten text
+//! recognition (HTR) service over HTTP, and [`RoutedEngine`] reads each page
+//! with Tesseract first and sends only the text blocks it read with low
+//! confidence to that service, so printed text stays with Tesseract.
+//!
+//! The service receives the image file, the page and the regions to read
+//! (none for the whole page):
+//!
+//! ```text
+//! POST <HTR_URL>
+//! { "image": "<base64>", "page": 1,
+//!   "regions": [{ "left": 120, "top": 840, "width": 600, "height": 90 }] }
+//! ```
+//!
+//! and answers with the text of each region, in order (one for the whole
+//! page):
+//!
+//! ```text
+//! { "regions": [{ "text": "홍길동", "confidence": 0.91 }] }
+//! ```
+//!
+//! Author: hephaex@gmail.com
+
+use std::path::Path;
+use std::time::Duration;
+
+use base64::Engine as _;
+use otl_core::config::OcrConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    merge_pages, OcrEngine, OcrError, OcrResult, PageLayout, Region, Result, TesseractEngine,
+};
+
+/// Client of an HTR service
+pub struct HtrEngine {
+    url: String,
+    api_key: Option<String>,
+    timeout: Duration,
+}
+
+#[derive(Serialize)]
+struct HtrRequest<'a> {
+    image: String,
+    page: u32,
+    regions: &'a [Region],
+}
+
+#[derive(Deserialize)]
+struct HtrResponse {
+    regions: Vec<HtrRegion>,
+}
+
+#[derive(Deserialize)]
+struct HtrRegion {
+    text: String,
+    confidence: f32,
+}
+
+impl HtrEngine {
+    /// Create a client of the service at `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            api_key: None,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Authenticate with a bearer token
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Set the timeout of one request
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub(crate) fn from_config(url: &str, config: &OcrConfig) -> Self {
+        let engine = Self::new(url).with_timeout(Duration::from_secs(config.htr_timeout_secs));
+        match &config.htr_api_key {
+            Some(key) => engine.with_api_key(key),
+            None => engine,
+        }
+    }
+
+    /// Text and confidence of each of `regions` of the 1-based `page` of an
+    /// image file, in order; the whole page when `regions` is empty
+    pub fn recognize(
+        &self,
+        image_path: &Path,
+        page: u32,
+        regions: &[Region],
+    ) -> Result<Vec<(String, f32)>> {
+        let image = std::fs::read(image_path)?;
+        let request = HtrRequest {
+            image: base64::engine::general_purpose::STANDARD.encode(image),
+            page,
+            regions,
+        };
+        let response = self.post(&request)?;
+
+        let expected = regions.len().max(1);
+        if response.regions.len() != expected {
+            return Err(OcrError::ExecutionFailed(format!(
+                "HTR service returned {} regions for {expected}",
+                response.regions.len()
+            )));
+        }
+        Ok(response
+            .regions
+            .into_iter()
+            .map(|r| (r.text.trim().to_string(), r.confidence.clamp(0.0, 1.0)))
+            .collect())
+    }
+
+    /// Send a request to the service
+    ///
+    /// OCR engines are synchronous but also called from async ingestion
+    /// stages, where blocking on the caller's runtime would panic, so the
+    /// request runs on a thread and runtime of its own.
+    fn post(&self, request: &HtrRequest<'_>) -> Result<HtrResponse> {
+        let run = || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            runtime.block_on(self.send(request))
+        };
+        std::thread::scope(|scope| scope.spawn(run).join())
+            .map_err(|_| OcrError::ExecutionFailed("HTR request panicked".to_string()))?
+    }
+
+    async fn send(&self, request: &HtrRequest<'_>) -> Result<HtrResponse> {
+        let client = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(failed)?;
+        let mut builder = client.post(&self.url).json(request);
+        if let Some(key) = &self.api_key {
+            builder = builder.bearer_auth(key);
+        }
+        let response = builder
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(failed)?;
+        response.json::<HtrResponse>().await.map_err(failed)
+    }
+}
+
+fn failed(e: reqwest::Error) -> OcrError {
+    OcrError::ExecutionFailed(format!("HTR service: {e}"))
+}
+
+impl OcrEngine for HtrEngine {
+    fn extract_text(&self, image_path: &Path) -> Result<OcrResult> {
+        let (text, confidence) = self
+            .recognize(image_path, 1, &[])?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        Ok(OcrResult::new(text).with_confidence(confidence))
+    }
+
+    /// The service is checked by its first request
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "htr"
+    }
+}
+
+/// Tesseract for print, an HTR service for the blocks Tesseract reads with
+/// confidence below `handwriting_below`
+pub struct RoutedEngine {
+    printed: TesseractEngine,
+    handwriting: HtrEngine,
+    handwriting_below: f32,
+}
+
+impl RoutedEngine {
+    /// Route blocks below `handwriting_below` from `printed` to `handwriting`
+    pub fn new(printed: TesseractEngine, handwriting: HtrEngine, handwriting_below: f32) -> Self {
+        Self {
+            printed,
+            handwriting,
+            handwriting_below,
+        }
+    }
+}
+
+impl OcrEngine for RoutedEngine {
+    fn extract_text(&self, image_path: &Path) -> Result<OcrResult> {
+        let pages = self.extract_pages(image_path)?;
+        Ok(merge_pages(pages, &self.printed.config.language))
+    }
+
+    fn extract_pages(&self, image_path: &Path) -> Result<Vec<OcrResult>> {
+        self.printed
+            .read_layout(image_path)?
+            .into_iter()
+            .map(|page| {
+                let page = route(page, self.handwriting_below, |number, regions| {
+                    self.handwriting.recognize(image_path, number, regions)
+                })?;
+                Ok(page.into_result(&self.printed.config))
+            })
+            .collect()
+    }
+
+    fn is_available(&self) -> bool {
+        self.printed.is_available()
+    }
+
+    fn name(&self) -> &str {
+        "tesseract+htr"
+    }
+}
+
+/// Replace the text of the blocks of `page` read with confidence below
+/// `threshold` by what `recognize` reads in their regions
+///
+/// Blocks without a region (no layout row) stay as read.
+fn route(
+    mut page: PageLayout,
+    threshold: f32,
+    recognize: impl FnOnce(u32, &[Region]) -> Result<Vec<(String, f32)>>,
+) -> Result<PageLayout> {
+    let handwritten: Vec<usize> = page
+        .blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| b.confidence() < threshold && b.region.width > 0 && b.region.height > 0)
+        .map(|(i, _)| i)
+        .collect();
+    if handwritten.is_empty() {
+        return Ok(page);
+    }
+
+    let regions: Vec<Region> = handwritten.iter().map(|&i| page.blocks[i].region).collect();
+    let texts = recognize(page.number, &regions)?;
+    for (&i, (text, confidence)) in handwritten.iter().zip(texts) {
+        page.blocks[i].replace_text(text, confidence);
+    }
+    Ok(page)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TesseractConfig;
+
+    /// A printed block and a handwritten signature block Tesseract misread
+    const FORM: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t
+2\t1\t1\t0\t0\t0\t10\t10\t300\t20\t-1\t
+5\t1\t1\t1\t1\t1\t10\t10\t80\t20\t95\t결재자
+2\t1\t2\t0\t0\t0\t120\t400\t200\t60\t-1\t
+5\t1\t2\t1\t1\t1\t120\t400\t200\t60\t22\tㅎㄱ5
+";
+
+    #[test]
+    fn test_route_sends_low_confidence_blocks() {
+        let page = TesseractEngine::new().parse_tsv(FORM).remove(0);
+        let mut asked = Vec::new();
+        let page = route(page, 0.5, |number, regions| {
+            asked.push((number, regions.to_vec()));
+            Ok(vec![("홍길동".to_string(), 0.9)])
+        })
+        .unwrap();
+
+        assert_eq!(
+            asked,
+            vec![(
+                1,
+                vec![Region {
+                    left: 120,
+                    top: 400,
+                    width: 200,
+                    height: 60,
+                }]
+            )]
+        );
+        let result = page.into_result(&TesseractConfig::default());
+        assert_eq!(result.text, "결재자\n\n홍길동");
+        // (3 * 0.95 + 3 * 0.9) / 6
+        assert!((result.confidence - 0.925).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_route_keeps_confident_pages() {
+        let page = TesseractEngine::new().parse_tsv(FORM).remove(0);
+        let page = route(page, 0.2, |_, _| panic!("nothing to route")).unwrap();
+        assert_eq!(
+            page.into_result(&TesseractConfig::default()).text,
+            "결재자\n\nㅎㄱ5"
+        );
+    }
+
+    #[test]
+    fn test_request_shape() {
+        let regions = [Region {
+            left: 1,
+            top: 2,
+            width: 3,
+            height: 4,
+        }];
+        let request = HtrRequest {
+            image: "aGk=".to_string(),
+            page: 2,
+            regions: &regions,
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "image": "aGk=",
+                "page": 2,
+                "regions": [{ "left": 1, "top": 2, "width": 3, "height": 4 }],
+            })
+        );
+    }
+}
@@ -2,12 +2,40 @@
 //!
 //! Provides OCR capabilities for scanned documents using
 //! Tesseract or PaddleOCR backends.
+//!
+//! Tesseract is run with TSV output, which carries a confidence for every
+//! recognized word. The confidence of a page is the mean of its word
+//! confidences weighted by word length, so a page of short, certain
+//! particles and long, garbled words still scores low.
+//!
+//! Single pages of PDF files are rendered to images with poppler's
+//! `pdftoppm` first, which lets the PDF parser read the scanned pages of a
+//! mixed document with OCR (see [`otl_parser::pdf::PageOcr`]).
+//!
+//! Handwriting is left to an external recognition service ([`htr`]), to
+//! which the blocks Tesseract cannot read are routed.
+//!
+//! In table mode the word boxes of each page are also grouped into tables
+//! ([`table`]), which replace their words in the page text and come with
+//! the result as [`Table`]s anchored to their page.
+
+pub mod htr;
+pub mod paddle;
+pub(crate) mod table;
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::process::Command;
 
+use otl_core::config::OcrConfig;
+use otl_parser::pdf::RecognizedPage;
+use otl_parser::Table;
+use serde::Serialize;
 use thiserror::Error;
 
+pub use htr::{HtrEngine, RoutedEngine};
+pub use paddle::PaddleEngine;
+
 #[derive(Error, Debug)]
 pub enum OcrError {
     #[error("OCR engine not available: {0}")]
@@ -30,12 +58,14 @@ pub type Result<T> = std::result::Result<T, OcrError>;
 pub struct OcrResult {
     /// Extracted text content
     pub text: String,
-    /// Confidence score (0.0 - 1.0)
+    /// Confidence score (0.0 - 1.0); 0.0 for a page without recognized text
     pub confidence: f32,
     /// Page number
     pub page: u32,
     /// Detected language
     pub language: Option<String>,
+    /// Tables recognized on the page (table mode only)
+    pub tables: Vec<Table>,
 }
 
 impl OcrResult {
@@ -46,6 +76,7 @@ impl OcrResult {
             confidence: 1.0,
             page: 1,
             language: None,
+            tables: Vec::new(),
         }
     }
 
@@ -55,9 +86,12 @@ impl OcrResult {
         self
     }
 
-    /// Set page number
+    /// Set page number, which also anchors the tables
     pub fn with_page(mut self, page: u32) -> Self {
         self.page = page;
+        for table in &mut self.tables {
+            table.page = Some(page);
+        }
         self
     }
 
@@ -73,6 +107,11 @@ pub trait OcrEngine: Send + Sync {
     /// Extract text from an image file
     fn extract_text(&self, image_path: &Path) -> Result<OcrResult>;
 
+    /// Extract the text of each page of a (possibly multi-page) image file
+    fn extract_pages(&self, image_path: &Path) -> Result<Vec<OcrResult>> {
+        Ok(vec![self.extract_text(image_path)?])
+    }
+
     /// Extract text from multiple images (e.g., PDF pages)
     fn extract_text_batch(&self, image_paths: &[&Path]) -> Result<Vec<OcrResult>> {
         image_paths
@@ -110,6 +149,8 @@ pub struct TesseractConfig {
     pub executable_path: Option<String>,
     /// Additional tesseract arguments
     pub extra_args: Vec<String>,
+    /// Recover tables from the word boxes
+    pub tables: bool,
 }
 
 impl Default for TesseractConfig {
@@ -120,6 +161,7 @@ impl Default for TesseractConfig {
             oem: None,
             executable_path: None,
             extra_args: Vec::new(),
+            tables: false,
         }
     }
 }
@@ -150,6 +192,12 @@ impl TesseractConfig {
         self.oem = Some(oem);
         self
     }
+
+    /// Enable table recognition
+    pub fn with_tables(mut self, enabled: bool) -> Self {
+        self.tables = enabled;
+        self
+    }
 }
 
 /// Tesseract OCR engine wrapper
@@ -178,11 +226,12 @@ impl TesseractEngine {
             .unwrap_or("tesseract")
     }
 
-    /// Build command arguments
-    fn build_args(&self, image_path: &Path) -> Vec<String> {
+    /// Build command arguments writing `formats` (tesseract configs such
+    /// as "tsv") to `output`, a file base name or "stdout"
+    fn build_args(&self, image_path: &Path, output: &str, formats: &[&str]) -> Vec<String> {
         let mut args = vec![
             image_path.display().to_string(),
-            "stdout".to_string(), // Output to stdout
+            output.to_string(),
             "-l".to_string(),
             self.config.language.clone(),
         ];
@@ -198,32 +247,92 @@ impl TesseractEngine {
         }
 
         args.extend(self.config.extra_args.clone());
+        args.extend(formats.iter().map(|f| f.to_string()));
         args
     }
 
-    /// Parse confidence from tesseract hOCR output (if available)
-    fn parse_confidence(&self, _output: &str) -> f32 {
-        // Default confidence when not using hOCR
-        // Could be enhanced to use hOCR output for actual confidence
-        0.9
+    /// Pages of tesseract TSV output, with their text blocks
+    ///
+    /// Words are joined by spaces within a line, lines by newlines and
+    /// paragraphs and blocks by blank lines. Rows of other levels and words
+    /// without a confidence (`-1`) only delimit; block rows give the region
+    /// of their block.
+    pub(crate) fn parse_tsv(&self, tsv: &str) -> Vec<PageLayout> {
+        let mut pages: Vec<PageLayout> = Vec::new();
+        for row in tsv.lines().skip(1) {
+            let columns: Vec<&str> = row.splitn(12, '\t').collect();
+            let [level, page, block, paragraph, line, _, left, top, width, height, confidence, text] =
+                columns[..]
+            else {
+                continue;
+            };
+            let Ok(page) = page.parse::<u32>() else {
+                continue;
+            };
+            if pages.last().map(|p| p.number) != Some(page) {
+                pages.push(PageLayout::new(page));
+            }
+            let current = pages.last_mut().expect("page was just pushed");
+            if block == "0" {
+                continue;
+            }
+            if current.blocks.last().map(|b| b.number.as_str()) != Some(block) {
+                current.blocks.push(TextBlock::new(block));
+            }
+            let current = current.blocks.last_mut().expect("block was just pushed");
+            let number = |s: &str| s.trim().parse().unwrap_or(0);
+            let region = Region {
+                left: number(left),
+                top: number(top),
+                width: number(width),
+                height: number(height),
+            };
+            if level == "2" {
+                current.region = region;
+            }
+            let confidence: f32 = confidence.trim().parse().unwrap_or(-1.0);
+            let text = text.trim();
+            if level != "5" || confidence < 0.0 || text.is_empty() {
+                continue;
+            }
+            current.push_word((paragraph, line), region, text, confidence / 100.0);
+        }
+        pages
     }
-}
 
-impl Default for TesseractEngine {
-    fn default() -> Self {
-        Self::new()
+    /// Read the pages of an image file with their text blocks
+    pub(crate) fn read_layout(&self, image_path: &Path) -> Result<Vec<PageLayout>> {
+        // Word boxes with confidences instead of plain text
+        let tsv = self.run(image_path, "stdout", &["tsv"])?;
+        Ok(self.parse_tsv(&tsv))
     }
-}
 
-impl OcrEngine for TesseractEngine {
-    fn extract_text(&self, image_path: &Path) -> Result<OcrResult> {
+    /// Read an image file as hOCR (HTML with word boxes), along with the
+    /// result of each page from the same run
+    pub fn extract_hocr(&self, image_path: &Path) -> Result<(String, Vec<OcrResult>)> {
+        let dir = tempfile::tempdir()?;
+        let base = dir.path().join("page");
+        self.run(image_path, &base.display().to_string(), &["hocr", "tsv"])?;
+
+        let hocr = std::fs::read_to_string(base.with_extension("hocr"))?;
+        let tsv = std::fs::read_to_string(base.with_extension("tsv"))?;
+        let pages = self
+            .parse_tsv(&tsv)
+            .into_iter()
+            .map(|p| p.into_result(&self.config))
+            .collect();
+        Ok((hocr, pages))
+    }
+
+    /// Run tesseract on an image file, returning what it printed
+    fn run(&self, image_path: &Path, output: &str, formats: &[&str]) -> Result<String> {
         if !self.is_available() {
             return Err(OcrError::EngineNotAvailable(
                 "Tesseract is not installed or not in PATH".to_string(),
             ));
         }
 
-        let args = self.build_args(image_path);
+        let args = self.build_args(image_path, output, formats);
 
         let output = Command::new(self.executable())
             .args(&args)
@@ -237,15 +346,233 @@ impl OcrEngine for TesseractEngine {
             )));
         }
 
-        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        let confidence = self.parse_confidence(&text);
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
 
-        Ok(OcrResult {
-            text,
-            confidence,
-            page: 1,
-            language: Some(self.config.language.clone()),
-        })
+/// Rectangle of an image, in pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Region {
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Page read from TSV rows
+pub(crate) struct PageLayout {
+    pub number: u32,
+    pub blocks: Vec<TextBlock>,
+}
+
+impl PageLayout {
+    fn new(number: u32) -> Self {
+        Self {
+            number,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Result of the page: the texts of its blocks separated by blank
+    /// lines, and their confidences weighted by character count
+    ///
+    /// In table mode each table found among the words Tesseract read
+    /// (blocks read by another engine are left out) replaces its words
+    /// with its markdown, placed after the first block it starts in.
+    pub fn into_result(self, config: &TesseractConfig) -> OcrResult {
+        let chars: usize = self.blocks.iter().map(|b| b.chars()).sum();
+        let confidence = if chars == 0 {
+            0.0
+        } else {
+            self.blocks.iter().map(|b| b.weighted()).sum::<f32>() / chars as f32
+        };
+
+        let found = if config.tables {
+            self.tables()
+        } else {
+            Vec::new()
+        };
+        // Table of each claimed word, by (block, word)
+        let mut claimed: HashMap<(usize, usize), usize> = HashMap::new();
+        for (t, (_, words)) in found.iter().enumerate() {
+            claimed.extend(words.iter().map(|&word| (word, t)));
+        }
+
+        let mut text = Vec::new();
+        let mut placed = vec![false; found.len()];
+        for (b, block) in self.blocks.iter().enumerate() {
+            let free = block.text_where(|w| !claimed.contains_key(&(b, w)));
+            if !free.is_empty() {
+                text.push(free);
+            }
+            let tables = (0..block.words.len()).filter_map(|w| claimed.get(&(b, w)));
+            for &t in tables {
+                if !std::mem::replace(&mut placed[t], true) {
+                    text.push(found[t].0.to_markdown().trim_end().to_string());
+                }
+            }
+        }
+
+        let mut result = OcrResult::new(text.join("\n\n"))
+            .with_confidence(confidence)
+            .with_language(&config.language);
+        result.tables = found.into_iter().map(|(table, _)| table).collect();
+        result.with_page(self.number)
+    }
+
+    /// Tables among the words Tesseract read, with the (block, word)
+    /// positions of their words
+    fn tables(&self) -> Vec<(Table, Vec<(usize, usize)>)> {
+        let positions: Vec<(usize, usize)> = self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.replaced.is_none())
+            .flat_map(|(b, block)| (0..block.words.len()).map(move |w| (b, w)))
+            .collect();
+        let words: Vec<(Region, &str)> = positions
+            .iter()
+            .map(|&(b, w)| {
+                let word = &self.blocks[b].words[w];
+                (word.region, word.text.as_str())
+            })
+            .collect();
+        table::detect(&words, self.number)
+            .into_iter()
+            .map(|found| {
+                let words = found.words.iter().map(|&i| positions[i]).collect();
+                (found.table, words)
+            })
+            .collect()
+    }
+}
+
+/// Block of text Tesseract laid out as one unit, such as a paragraph or a
+/// form field
+pub(crate) struct TextBlock {
+    number: String,
+    pub region: Region,
+    words: Vec<Word>,
+    /// Text and confidence read by another engine in place of the words
+    replaced: Option<(String, f32)>,
+}
+
+/// Word read by Tesseract
+struct Word {
+    paragraph: String,
+    line: String,
+    region: Region,
+    text: String,
+    confidence: f32,
+}
+
+impl TextBlock {
+    fn new(number: &str) -> Self {
+        Self {
+            number: number.to_string(),
+            region: Region::default(),
+            words: Vec::new(),
+            replaced: None,
+        }
+    }
+
+    fn push_word(
+        &mut self,
+        (paragraph, line): (&str, &str),
+        region: Region,
+        text: &str,
+        confidence: f32,
+    ) {
+        self.words.push(Word {
+            paragraph: paragraph.to_string(),
+            line: line.to_string(),
+            region,
+            text: text.to_string(),
+            confidence: confidence.clamp(0.0, 1.0),
+        });
+    }
+
+    /// Text of the words at the indices `keep` accepts: words are joined by
+    /// spaces within a line, lines by newlines and paragraphs by blank lines
+    fn text_where(&self, keep: impl Fn(usize) -> bool) -> String {
+        if let Some((text, _)) = &self.replaced {
+            return text.clone();
+        }
+        let mut text = String::new();
+        let mut last: Option<&Word> = None;
+        for word in (0..self.words.len())
+            .filter(|&i| keep(i))
+            .map(|i| &self.words[i])
+        {
+            if let Some(last) = last {
+                let separator = if last.paragraph != word.paragraph {
+                    "\n\n"
+                } else if last.line != word.line {
+                    "\n"
+                } else {
+                    " "
+                };
+                text.push_str(separator);
+            }
+            text.push_str(&word.text);
+            last = Some(word);
+        }
+        text
+    }
+
+    /// Number of characters read
+    fn chars(&self) -> usize {
+        match &self.replaced {
+            Some((text, _)) => text.chars().filter(|c| !c.is_whitespace()).count(),
+            None => self.words.iter().map(|w| w.text.chars().count()).sum(),
+        }
+    }
+
+    /// Sum of the confidences of the characters read
+    fn weighted(&self) -> f32 {
+        match &self.replaced {
+            Some((_, confidence)) => confidence * self.chars() as f32,
+            None => self
+                .words
+                .iter()
+                .map(|w| w.confidence * w.text.chars().count() as f32)
+                .sum(),
+        }
+    }
+
+    /// Confidence of the block; 0.0 without recognized words
+    pub fn confidence(&self) -> f32 {
+        let chars = self.chars();
+        if chars == 0 {
+            return 0.0;
+        }
+        self.weighted() / chars as f32
+    }
+
+    /// Replace the text with one read by another engine
+    pub fn replace_text(&mut self, text: String, confidence: f32) {
+        self.replaced = Some((text, confidence.clamp(0.0, 1.0)));
+    }
+}
+
+impl Default for TesseractEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OcrEngine for TesseractEngine {
+    fn extract_text(&self, image_path: &Path) -> Result<OcrResult> {
+        let pages = self.extract_pages(image_path)?;
+        Ok(merge_pages(pages, &self.config.language))
+    }
+
+    fn extract_pages(&self, image_path: &Path) -> Result<Vec<OcrResult>> {
+        let pages = self.read_layout(image_path)?;
+        Ok(pages
+            .into_iter()
+            .map(|p| p.into_result(&self.config))
+            .collect())
     }
 
     fn is_available(&self) -> bool {
@@ -261,6 +588,34 @@ impl OcrEngine for TesseractEngine {
     }
 }
 
+/// One result of all pages: their texts separated by blank lines and their
+/// confidences weighted by text length
+fn merge_pages(pages: Vec<OcrResult>, language: &str) -> OcrResult {
+    let chars: usize = pages.iter().map(|p| p.text.chars().count()).sum();
+    let confidence = if chars == 0 {
+        0.0
+    } else {
+        pages
+            .iter()
+            .map(|p| p.confidence * p.text.chars().count() as f32)
+            .sum::<f32>()
+            / chars as f32
+    };
+    let mut text = Vec::new();
+    let mut tables = Vec::new();
+    for page in pages {
+        if !page.text.is_empty() {
+            text.push(page.text);
+        }
+        tables.extend(page.tables);
+    }
+    let mut result = OcrResult::new(text.join("\n\n"))
+        .with_confidence(confidence)
+        .with_language(language);
+    result.tables = tables;
+    result
+}
+
 // ============================================================================
 // OCR Manager
 // ============================================================================
@@ -286,6 +641,40 @@ impl OcrManager {
         manager
     }
 
+    /// Create an OCR manager from configuration
+    ///
+    /// With a handwriting recognition service configured, printed text is
+    /// read by Tesseract and the blocks it reads with low confidence by the
+    /// service; without Tesseract the service reads whole pages. Table
+    /// recognition needs Tesseract's word boxes.
+    pub fn from_config(config: &OcrConfig) -> Self {
+        let mut manager = Self {
+            engines: Vec::new(),
+        };
+        let tesseract =
+            TesseractEngine::with_config(TesseractConfig::default().with_tables(config.tables));
+        match (&config.htr_url, tesseract.is_available()) {
+            (Some(url), true) => manager.register(RoutedEngine::new(
+                tesseract,
+                HtrEngine::from_config(url, config),
+                config.handwriting_below,
+            )),
+            (Some(url), false) => manager.register(HtrEngine::from_config(url, config)),
+            (None, true) => manager.register(tesseract),
+            (None, false) => {}
+        }
+        manager
+    }
+
+    /// Create an OCR manager running `engine` only
+    pub fn with_engine<E: OcrEngine + 'static>(engine: E) -> Self {
+        let mut manager = Self {
+            engines: Vec::new(),
+        };
+        manager.register(engine);
+        manager
+    }
+
     /// Register an OCR engine
     pub fn register<E: OcrEngine + 'static>(&mut self, engine: E) {
         self.engines.push(Box::new(engine));
@@ -313,6 +702,17 @@ impl OcrManager {
         self.engines[0].extract_text(image_path)
     }
 
+    /// Extract the text of each page of an image file
+    pub fn extract_pages(&self, image_path: &Path) -> Result<Vec<OcrResult>> {
+        if self.engines.is_empty() {
+            return Err(OcrError::EngineNotAvailable(
+                "No OCR engines available".to_string(),
+            ));
+        }
+
+        self.engines[0].extract_pages(image_path)
+    }
+
     /// Extract text from multiple images
     pub fn extract_text_batch(&self, image_paths: &[&Path]) -> Result<Vec<OcrResult>> {
         if self.engines.is_empty() {
@@ -331,6 +731,80 @@ impl Default for OcrManager {
     }
 }
 
+// ============================================================================
+// PDF Pages
+// ============================================================================
+
+/// Renders PDF pages to images
+const PDFTOPPM: &str = "pdftoppm";
+
+/// Resolution of rendered pages; Tesseract reads best at about 300 DPI
+const RENDER_DPI: u32 = 300;
+
+impl OcrManager {
+    /// Whether pages of PDF files can be read: an engine is available and
+    /// `pdftoppm` is installed
+    pub fn can_read_pdf_pages(&self) -> bool {
+        self.is_available()
+            && Command::new(PDFTOPPM)
+                .arg("-v")
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+    }
+
+    /// Extract the text of the 1-based `pages` of a PDF file, in the order
+    /// given
+    pub fn extract_pdf_pages(&self, pdf: &[u8], pages: &[u32]) -> Result<Vec<OcrResult>> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("input.pdf");
+        std::fs::write(&input, pdf)?;
+
+        pages
+            .iter()
+            .map(|&page| {
+                let prefix = dir.path().join(format!("page-{page}"));
+                let output = Command::new(PDFTOPPM)
+                    .args(["-f", &page.to_string(), "-l", &page.to_string()])
+                    .args(["-r", &RENDER_DPI.to_string(), "-png", "-singlefile"])
+                    .arg(&input)
+                    .arg(&prefix)
+                    .output()
+                    .map_err(|e| OcrError::ImageProcessingFailed(e.to_string()))?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(OcrError::ImageProcessingFailed(format!(
+                        "pdftoppm failed on page {page}: {stderr}"
+                    )));
+                }
+
+                let result = self.extract_text(&prefix.with_extension("png"))?;
+                Ok(result.with_page(page))
+            })
+            .collect()
+    }
+}
+
+impl otl_parser::pdf::PageOcr for OcrManager {
+    fn recognize_pages(
+        &self,
+        pdf: &[u8],
+        pages: &[u32],
+    ) -> otl_parser::Result<Vec<RecognizedPage>> {
+        let results = self
+            .extract_pdf_pages(pdf, pages)
+            .map_err(|e| otl_parser::ParserError::OcrError(e.to_string()))?;
+        Ok(results
+            .into_iter()
+            .map(|r| RecognizedPage {
+                text: r.text,
+                confidence: r.confidence,
+                tables: r.tables,
+            })
+            .collect())
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -379,6 +853,67 @@ mod tests {
         assert_eq!(engine_with_config.config.language, "kor+eng");
     }
 
+    #[test]
+    fn test_tsv_pages_and_confidence() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t
+5\t1\t1\t1\t1\t1\t10\t10\t50\t20\t96.5\t휴가
+5\t1\t1\t1\t1\t2\t70\t10\t50\t20\t91\t신청은
+5\t1\t1\t1\t2\t1\t10\t40\t50\t20\t88\t부서장
+5\t1\t2\t1\t1\t1\t10\t90\t50\t20\t20\tx#7q
+1\t2\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t
+";
+        let engine = TesseractEngine::with_config(TesseractConfig::korean());
+        let pages: Vec<OcrResult> = engine
+            .parse_tsv(tsv)
+            .into_iter()
+            .map(|p| p.into_result(&engine.config))
+            .collect();
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].text, "휴가 신청은\n부서장\n\nx#7q");
+        assert_eq!(pages[0].page, 1);
+        // (2 * 0.965 + 3 * 0.91 + 3 * 0.88 + 4 * 0.2) / 12
+        assert!((pages[0].confidence - 0.675).abs() < 1e-4);
+        assert_eq!(pages[1].text, "");
+        assert_eq!(pages[1].confidence, 0.0);
+
+        let merged = merge_pages(pages, "kor+eng");
+        assert_eq!(merged.text, "휴가 신청은\n부서장\n\nx#7q");
+        assert!((merged.confidence - 0.675).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_table_mode_replaces_words_with_table() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t
+5\t1\t1\t1\t1\t1\t10\t10\t80\t20\t95\t연차현황
+5\t1\t2\t1\t1\t1\t10\t60\t40\t20\t90\t이름
+5\t1\t2\t1\t1\t2\t300\t60\t40\t20\t90\t잔여
+5\t1\t3\t1\t1\t1\t10\t100\t60\t20\t90\t홍길동
+5\t1\t4\t1\t1\t1\t300\t100\t40\t20\t90\t12
+";
+        let config = TesseractConfig::korean().with_tables(true);
+        let engine = TesseractEngine::with_config(config.clone());
+        let page = engine.parse_tsv(tsv).remove(0).into_result(&config);
+
+        assert_eq!(
+            page.text,
+            "연차현황\n\n| 이름 | 잔여 |\n| --- | --- |\n| 홍길동 | 12 |"
+        );
+        assert_eq!(page.tables.len(), 1);
+        assert_eq!(page.tables[0].rows, vec![vec!["홍길동", "12"]]);
+        assert_eq!(page.tables[0].page, Some(1));
+        assert_eq!(page.with_page(3).tables[0].page, Some(3));
+
+        let words = engine
+            .parse_tsv(tsv)
+            .remove(0)
+            .into_result(&TesseractConfig::korean());
+        assert_eq!(words.text, "연차현황\n\n이름 잔여\n\n홍길동\n\n12");
+        assert!(words.tables.is_empty());
+    }
+
     #[test]
     fn test_ocr_manager() {
         let manager = OcrManager::new();
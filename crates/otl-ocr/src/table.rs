@@ -0,0 +1,235 @@
+//! Table structure of scanned pages
+//!
+//! Tesseract reads a scanned table cell by cell in no useful order, so the
+//! table comes out of its text as loose words. The structure is recovered
+//! from the word boxes instead: words whose vertical centers lie within half
+//! a word height of each other form a row, and a row splits into cells at
+//! gaps wider than a word height. Consecutive rows of two or more cells make
+//! a table, whose columns are the overlapping horizontal extents of its
+//! cells; the first row is taken as the header.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_parser::Table;
+
+use crate::Region;
+
+/// Fewest rows, the header included, taken as a table
+const MIN_ROWS: usize = 2;
+
+/// Vertical gap between rows, in word heights, that ends a table
+const MAX_ROW_GAP: u32 = 3;
+
+/// Table found among the words of a page
+pub(crate) struct FoundTable {
+    pub table: Table,
+    /// Indices of the words in the table
+    pub words: Vec<usize>,
+}
+
+struct Row {
+    center: u32,
+    top: u32,
+    bottom: u32,
+    words: Vec<usize>,
+}
+
+struct Cell {
+    left: u32,
+    right: u32,
+    words: Vec<usize>,
+}
+
+/// Tables among the `words` of the 1-based `page`, top to bottom
+pub(crate) fn detect(words: &[(Region, &str)], page: u32) -> Vec<FoundTable> {
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let mut heights: Vec<u32> = words.iter().map(|(r, _)| r.height.max(1)).collect();
+    heights.sort_unstable();
+    let height = heights[heights.len() / 2];
+
+    let rows = rows(words, height);
+    let cells: Vec<Vec<Cell>> = rows.iter().map(|r| cells(words, r, height)).collect();
+
+    let mut tables = Vec::new();
+    let mut start = 0;
+    while start < rows.len() {
+        let mut end = start;
+        while end < rows.len()
+            && cells[end].len() >= 2
+            && (end == start
+                || rows[end].top.saturating_sub(rows[end - 1].bottom) <= MAX_ROW_GAP * height)
+        {
+            end += 1;
+        }
+        if end - start >= MIN_ROWS {
+            tables.extend(table(words, &cells[start..end], page));
+        }
+        start = end.max(start + 1);
+    }
+    tables
+}
+
+/// Words grouped into rows by their vertical centers, top to bottom
+fn rows(words: &[(Region, &str)], height: u32) -> Vec<Row> {
+    let center = |i: usize| words[i].0.top + words[i].0.height / 2;
+    let mut order: Vec<usize> = (0..words.len()).collect();
+    order.sort_by_key(|&i| (center(i), words[i].0.left));
+
+    let mut rows: Vec<Row> = Vec::new();
+    for i in order {
+        let region = words[i].0;
+        match rows.last_mut() {
+            Some(row) if center(i).abs_diff(row.center) <= height / 2 => {
+                row.top = row.top.min(region.top);
+                row.bottom = row.bottom.max(region.top + region.height);
+                row.words.push(i);
+            }
+            _ => rows.push(Row {
+                center: center(i),
+                top: region.top,
+                bottom: region.top + region.height,
+                words: vec![i],
+            }),
+        }
+    }
+    rows
+}
+
+/// Words of a row grouped into cells at wide gaps, left to right
+fn cells(words: &[(Region, &str)], row: &Row, height: u32) -> Vec<Cell> {
+    let mut order = row.words.clone();
+    order.sort_by_key(|&i| words[i].0.left);
+
+    let mut cells: Vec<Cell> = Vec::new();
+    for i in order {
+        let region = words[i].0;
+        let right = region.left + region.width;
+        match cells.last_mut() {
+            Some(cell) if region.left <= cell.right + height => {
+                cell.right = cell.right.max(right);
+                cell.words.push(i);
+            }
+            _ => cells.push(Cell {
+                left: region.left,
+                right,
+                words: vec![i],
+            }),
+        }
+    }
+    cells
+}
+
+/// Table of rows of cells, if they fall into two or more columns
+fn table(words: &[(Region, &str)], rows: &[Vec<Cell>], page: u32) -> Option<FoundTable> {
+    let mut spans: Vec<(u32, u32)> = rows.iter().flatten().map(|c| (c.left, c.right)).collect();
+    spans.sort_unstable();
+    let mut columns: Vec<(u32, u32)> = Vec::new();
+    for (left, right) in spans {
+        match columns.last_mut() {
+            Some(column) if left <= column.1 => column.1 = column.1.max(right),
+            _ => columns.push((left, right)),
+        }
+    }
+    if columns.len() < 2 {
+        return None;
+    }
+
+    let mut grid = rows.iter().map(|row| {
+        let mut line = vec![String::new(); columns.len()];
+        for cell in row {
+            let column = columns
+                .iter()
+                .position(|&(left, right)| (left..=right).contains(&cell.left))
+                .unwrap_or(0);
+            let text: Vec<&str> = cell.words.iter().map(|&i| words[i].1).collect();
+            if !line[column].is_empty() {
+                line[column].push(' ');
+            }
+            line[column].push_str(&text.join(" "));
+        }
+        line
+    });
+    let mut table = Table::new().with_headers(grid.next().unwrap_or_default());
+    for line in grid {
+        table.add_row(line);
+    }
+    table.page = Some(page);
+
+    Some(FoundTable {
+        table,
+        words: rows
+            .iter()
+            .flatten()
+            .flat_map(|c| c.words.iter().copied())
+            .collect(),
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(left: u32, top: u32, text: &str) -> (Region, &str) {
+        let region = Region {
+            left,
+            top,
+            width: 20 * text.chars().count() as u32,
+            height: 20,
+        };
+        (region, text)
+    }
+
+    #[test]
+    fn test_leave_table_is_recovered() {
+        let words = [
+            word(10, 10, "연차"),
+            word(60, 12, "현황"),
+            // Read in no particular order, with the header a little higher
+            word(300, 60, "잔여일수"),
+            word(10, 101, "홍길동"),
+            word(10, 58, "이름"),
+            word(150, 60, "부서"),
+            word(300, 100, "12"),
+            word(150, 99, "인사팀"),
+            word(10, 140, "김철수"),
+            word(150, 141, "총무"),
+            word(205, 141, "지원팀"),
+            word(300, 140, "3.5"),
+            word(10, 300, "끝."),
+        ];
+
+        let found = detect(&words, 2);
+        assert_eq!(found.len(), 1);
+        let table = &found[0].table;
+        assert_eq!(table.headers, vec!["이름", "부서", "잔여일수"]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["홍길동", "인사팀", "12"],
+                vec!["김철수", "총무 지원팀", "3.5"],
+            ]
+        );
+        assert_eq!(table.page, Some(2));
+        assert_eq!(found[0].words.len(), 10);
+        assert!(!found[0].words.contains(&0));
+    }
+
+    #[test]
+    fn test_running_text_is_no_table() {
+        let words = [
+            word(10, 10, "휴가는"),
+            word(80, 10, "미리"),
+            word(130, 10, "신청한다."),
+            word(10, 40, "승인은"),
+            word(80, 40, "부서장이"),
+            word(170, 40, "한다."),
+        ];
+        assert!(detect(&words, 1).is_empty());
+    }
+}
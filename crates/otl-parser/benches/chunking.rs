@@ -0,0 +1,54 @@
+//! Benchmarks of document chunking
+//!
+//! Run with `cargo bench -p otl-parser --bench chunking`.
+//!
+//! Author: hephaex@gmail.com
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use otl_parser::{chunk_document, ChunkConfig, DocumentSection, FileType, ParsedDocument};
+
+const ARTICLE: &str = concat!(
+    "제{n}조(연차휴가) ① 회사는 1년간 80퍼센트 이상 출근한 직원에게 15일의 유급휴가를 주어야 한다. ",
+    "② 계속하여 근로한 기간이 1년 미만인 직원에게는 1개월 개근 시 1일의 유급휴가를 주어야 한다.\n\n",
+    "③ 휴가는 직원이 청구한 시기에 주어야 하며, 사업 운영에 막대한 지장이 있는 경우에는 ",
+    "그 시기를 변경할 수 있다. ",
+    "Annual leave requests must be approved by the department head before the leave starts.\n\n",
+);
+
+/// Regulation-like document of `articles` articles, one section each
+fn regulation(articles: usize) -> ParsedDocument {
+    let mut doc = ParsedDocument::new("취업규칙.pdf", FileType::Pdf);
+    for n in 1..=articles {
+        let text = ARTICLE.replace("{n}", &n.to_string()).repeat(4);
+        doc.content.push_str(&text);
+        doc.sections
+            .push(DocumentSection::new(text).with_title(format!("제{n}조")));
+    }
+    doc
+}
+
+fn bench_chunk_document(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunk_document");
+    for articles in [100, 1_000] {
+        let doc = regulation(articles);
+        group.throughput(Throughput::Bytes(doc.content.len() as u64));
+
+        let by_section = ChunkConfig::default();
+        group.bench_with_input(BenchmarkId::new("sections", articles), &doc, |b, doc| {
+            b.iter(|| chunk_document(black_box(doc), &by_section))
+        });
+
+        let flat = ChunkConfig {
+            respect_sections: false,
+            ..ChunkConfig::default()
+        };
+        group.bench_with_input(BenchmarkId::new("flat", articles), &doc, |b, doc| {
+            b.iter(|| chunk_document(black_box(doc), &flat))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_chunk_document);
+criterion_main!(benches);
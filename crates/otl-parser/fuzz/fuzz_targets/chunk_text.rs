@@ -0,0 +1,31 @@
+//! Fuzz `chunk_text` with arbitrary text and chunk settings
+//!
+//! Author: hephaex@gmail.com
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use otl_parser::fuzzing::{check_chunks, chunk_text};
+use otl_parser::ChunkConfig;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    text: String,
+    chunk_size: u16,
+    overlap: u16,
+    min_chunk_size: u8,
+    respect_paragraphs: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let config = ChunkConfig {
+        chunk_size: usize::from(input.chunk_size).max(1),
+        overlap: usize::from(input.overlap),
+        min_chunk_size: usize::from(input.min_chunk_size),
+        respect_sections: true,
+        respect_paragraphs: input.respect_paragraphs,
+    };
+    let chunks = chunk_text(&input.text, &config);
+    check_chunks(&input.text, &config, &chunks);
+});
@@ -0,0 +1,32 @@
+//! Fuzz `find_break_point` with arbitrary text and positions
+//!
+//! Author: hephaex@gmail.com
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use otl_parser::fuzzing::{check_break_point, find_break_point};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    text: String,
+    start: usize,
+    target: usize,
+}
+
+fuzz_target!(|input: Input| {
+    let text = &input.text;
+    // The chunker passes character boundaries with start < target
+    let boundaries: Vec<usize> = (0..=text.len())
+        .filter(|&i| text.is_char_boundary(i))
+        .collect();
+    let index = input.target % boundaries.len();
+    if index == 0 {
+        return;
+    }
+    let target = boundaries[index];
+    let start = boundaries[input.start % index];
+    let end = find_break_point(text, start, target);
+    check_break_point(text, start, target, end);
+});
@@ -0,0 +1,13 @@
+//! Fuzz the PDF parser with arbitrary bytes
+//!
+//! Author: hephaex@gmail.com
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use otl_parser::pdf::PdfParser;
+
+fuzz_target!(|bytes: &[u8]| {
+    // Malformed input must fail with an error, never panic
+    let _ = PdfParser::new().parse_bytes(bytes, "fuzz.pdf");
+});
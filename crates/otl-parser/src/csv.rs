@@ -0,0 +1,88 @@
+//! CSV reader
+//!
+//! Reads comma-separated text into a [`Table`]: the first record is the
+//! header row and the rest are data rows. Fields may be quoted with `"`,
+//! with `""` for a literal quote, so quoted fields can hold commas and
+//! line breaks (RFC 4180). A leading byte order mark is ignored. Blank
+//! lines are kept as empty rows, so row positions match the file.
+
+use crate::{ParserError, Result, Table};
+
+/// Parse CSV text into a table
+pub fn parse_csv(text: &str) -> Result<Table> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut quote_line = 1;
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            (false, '"') if field.is_empty() => {
+                quoted = true;
+                quote_line = line;
+            }
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n' | '\r') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                line += 1;
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(ParserError::CorruptedFile(format!(
+            "unterminated quoted field at line {quote_line}"
+        )));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    let mut records = records.into_iter();
+    let mut table = Table::new().with_headers(records.next().unwrap_or_default());
+    for record in records {
+        table.add_row(record);
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let text = "\u{feff}사번,성명,비고\r\nE001,김철수,\"인사팀, 총무팀\"\r\n\r\nE002,\"이\"\"영희\"\"\",\"여러\n줄\"\n";
+        let table = parse_csv(text).unwrap();
+
+        assert_eq!(table.headers, vec!["사번", "성명", "비고"]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["E001", "김철수", "인사팀, 총무팀"],
+                vec![""],
+                vec!["E002", "이\"영희\"", "여러\n줄"],
+            ]
+        );
+
+        assert!(parse_csv("a,b\n\"open,1\n").is_err());
+    }
+}
@@ -32,6 +32,100 @@ impl DocxParser {
         self.preserve_formatting = enabled;
         self
     }
+
+    /// Parse a DOCX document held in memory
+    ///
+    /// `file_path` is only recorded in the result. Malformed input fails
+    /// with [`ParserError::DocxError`]; docx-rs panics on some corrupted
+    /// archives, which is reported the same way.
+    pub fn parse_bytes(&self, bytes: &[u8], file_path: &str) -> Result<ParsedDocument> {
+        let docx = std::panic::catch_unwind(|| read_docx(bytes))
+            .map_err(|_| ParserError::DocxError("corrupted DOCX archive".to_string()))?
+            .map_err(|e| ParserError::DocxError(e.to_string()))?;
+
+        let mut content = String::new();
+        let mut sections = Vec::new();
+        let mut tables = Vec::new();
+        let mut current_section_content = String::new();
+        let mut current_section_title: Option<String> = None;
+        let mut current_section_level = 1u8;
+
+        // Process document body
+        for child in docx.document.children {
+            match child {
+                docx_rs::DocumentChild::Paragraph(para) => {
+                    let para_text = Self::extract_paragraph_text(&para);
+                    let heading_level = Self::check_heading_style(&para);
+
+                    // Handle heading paragraphs
+                    let Some(level) = heading_level else {
+                        // Regular paragraph - add to current section
+                        current_section_content.push_str(&para_text);
+                        current_section_content.push('\n');
+                        content.push_str(&para_text);
+                        content.push('\n');
+                        continue;
+                    };
+
+                    // Skip empty headings
+                    if para_text.trim().is_empty() {
+                        content.push_str(&para_text);
+                        content.push('\n');
+                        continue;
+                    }
+
+                    // Save previous section and start new one
+                    Self::save_section_if_needed(
+                        &mut sections,
+                        &mut current_section_title,
+                        &mut current_section_content,
+                        current_section_level,
+                    );
+
+                    current_section_title = Some(para_text.trim().to_string());
+                    current_section_level = level;
+
+                    content.push_str(&para_text);
+                    content.push('\n');
+                }
+                docx_rs::DocumentChild::Table(tbl) => {
+                    let table = Self::process_table(&tbl);
+
+                    // Add table to content as markdown
+                    content.push_str(&table.to_markdown());
+                    content.push('\n');
+                    tables.push(table);
+                }
+                _ => {}
+            }
+        }
+
+        // Add final section
+        Self::save_section_if_needed(
+            &mut sections,
+            &mut current_section_title,
+            &mut current_section_content,
+            current_section_level,
+        );
+
+        // Try to get title from first heading
+        let title = sections.first().and_then(|s| s.title.clone());
+
+        let metadata = DocumentParseMetadata {
+            word_count: Some(content.split_whitespace().count() as u32),
+            title,
+            ..Default::default()
+        };
+
+        Ok(ParsedDocument {
+            file_path: file_path.to_string(),
+            file_type: FileType::Docx,
+            content,
+            sections,
+            tables,
+            metadata,
+        })
+    }
 }
 
 impl Default for DocxParser {
@@ -167,90 +261,7 @@ impl DocumentParser for DocxParser {
                 source: e,
             })?;
 
-        let docx = read_docx(&buf).map_err(|e| ParserError::DocxError(e.to_string()))?;
-
-        let mut content = String::new();
-        let mut sections = Vec::new();
-        let mut tables = Vec::new();
-        let mut current_section_content = String::new();
-        let mut current_section_title: Option<String> = None;
-        let mut current_section_level = 1u8;
-
-        // Process document body
-        for child in docx.document.children {
-            match child {
-                docx_rs::DocumentChild::Paragraph(para) => {
-                    let para_text = Self::extract_paragraph_text(&para);
-                    let heading_level = Self::check_heading_style(&para);
-
-                    // Handle heading paragraphs
-                    let Some(level) = heading_level else {
-                        // Regular paragraph - add to current section
-                        current_section_content.push_str(&para_text);
-                        current_section_content.push('\n');
-                        content.push_str(&para_text);
-                        content.push('\n');
-                        continue;
-                    };
-
-                    // Skip empty headings
-                    if para_text.trim().is_empty() {
-                        content.push_str(&para_text);
-                        content.push('\n');
-                        continue;
-                    }
-
-                    // Save previous section and start new one
-                    Self::save_section_if_needed(
-                        &mut sections,
-                        &mut current_section_title,
-                        &mut current_section_content,
-                        current_section_level,
-                    );
-
-                    current_section_title = Some(para_text.trim().to_string());
-                    current_section_level = level;
-
-                    content.push_str(&para_text);
-                    content.push('\n');
-                }
-                docx_rs::DocumentChild::Table(tbl) => {
-                    let table = Self::process_table(&tbl);
-
-                    // Add table to content as markdown
-                    content.push_str(&table.to_markdown());
-                    content.push('\n');
-                    tables.push(table);
-                }
-                _ => {}
-            }
-        }
-
-        // Add final section
-        Self::save_section_if_needed(
-            &mut sections,
-            &mut current_section_title,
-            &mut current_section_content,
-            current_section_level,
-        );
-
-        // Try to get title from first heading
-        let title = sections.first().and_then(|s| s.title.clone());
-
-        let metadata = DocumentParseMetadata {
-            word_count: Some(content.split_whitespace().count() as u32),
-            title,
-            ..Default::default()
-        };
-
-        Ok(ParsedDocument {
-            file_path: path.display().to_string(),
-            file_type: FileType::Docx,
-            content,
-            sections,
-            tables,
-            metadata,
-        })
+        self.parse_bytes(&buf, &path.display().to_string())
     }
 
     fn supported_types(&self) -> &[FileType] {
@@ -261,6 +272,7 @@ impl DocumentParser for DocxParser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_docx_parser_creation() {
@@ -277,4 +289,54 @@ mod tests {
         assert!(parser.can_parse(FileType::Docx));
         assert!(!parser.can_parse(FileType::Pdf));
     }
+
+    fn docx_bytes(paragraphs: &[String]) -> Vec<u8> {
+        let docx = paragraphs.iter().fold(docx_rs::Docx::new(), |docx, text| {
+            docx.add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text)),
+            )
+        });
+        let mut buf = std::io::Cursor::new(Vec::new());
+        docx.build().pack(&mut buf).unwrap();
+        buf.into_inner()
+    }
+
+    fn paragraphs() -> impl Strategy<Value = Vec<String>> {
+        prop::collection::vec(
+            "[가-힣a-zA-Z0-9]{1,10}( [가-힣a-zA-Z0-9]{1,10}){0,3}",
+            1..20,
+        )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_arbitrary_bytes_fail_cleanly(bytes in prop::collection::vec(any::<u8>(), 0..4096)) {
+            prop_assert!(DocxParser::new().parse_bytes(&bytes, "fuzz.docx").is_err());
+        }
+
+        #[test]
+        fn prop_corrupted_docx_does_not_panic(
+            paragraphs in paragraphs(),
+            damage in prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..16),
+        ) {
+            let mut bytes = docx_bytes(&paragraphs);
+            for (index, byte) in damage {
+                let i = index.index(bytes.len());
+                bytes[i] = byte;
+            }
+            let _ = DocxParser::new().parse_bytes(&bytes, "corrupted.docx");
+        }
+
+        #[test]
+        fn prop_paragraph_text_is_kept(paragraphs in paragraphs()) {
+            let doc = DocxParser::new()
+                .parse_bytes(&docx_bytes(&paragraphs), "generated.docx")
+                .unwrap();
+            for paragraph in &paragraphs {
+                prop_assert!(doc.content.contains(paragraph.as_str()));
+            }
+        }
+    }
 }
@@ -2,9 +2,10 @@
 //!
 //! Extracts data from Excel files (XLSX, XLS) as tables.
 
+use std::io::Cursor;
 use std::path::Path;
 
-use calamine::{open_workbook_auto, Data, Reader};
+use calamine::{open_workbook_auto, open_workbook_auto_from_rs, Data, Reader};
 
 use crate::{
     DocumentParseMetadata, DocumentParser, FileType, ParsedDocument, ParserError, Result, Table,
@@ -62,11 +63,7 @@ impl ExcelParser {
     }
 
     /// Process a single sheet and return the table
-    fn process_sheet(
-        &self,
-        sheet_name: &str,
-        range: calamine::Range<Data>,
-    ) -> (Table, String) {
+    fn process_sheet(&self, sheet_name: &str, range: calamine::Range<Data>) -> (Table, String) {
         let mut table = Table::new();
         table.caption = Some(sheet_name.to_string());
 
@@ -93,6 +90,41 @@ impl ExcelParser {
 
         (table, content)
     }
+
+    /// Read the sheets of an in-memory workbook as tables
+    ///
+    /// Each table is captioned with its sheet name. Unlike [`parse`], empty
+    /// rows are kept so that row positions match the sheet.
+    ///
+    /// [`parse`]: DocumentParser::parse
+    pub fn read_tables(&self, bytes: &[u8]) -> Result<Vec<Table>> {
+        let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes))
+            .map_err(|e| ParserError::ExcelError(e.to_string()))?;
+
+        let mut tables = Vec::new();
+        for sheet_name in workbook.sheet_names().to_vec() {
+            if let Some(filter) = &self.sheet_filter {
+                if !filter.contains(&sheet_name) {
+                    continue;
+                }
+            }
+            let Ok(range) = workbook.worksheet_range(&sheet_name) else {
+                continue;
+            };
+
+            let mut rows = range
+                .rows()
+                .map(|row| row.iter().map(Self::cell_to_string).collect::<Vec<_>>());
+            let mut table = Table::new();
+            table.caption = Some(sheet_name);
+            if self.first_row_header {
+                table.headers = rows.next().unwrap_or_default();
+            }
+            table.rows = rows.collect();
+            tables.push(table);
+        }
+        Ok(tables)
+    }
 }
 
 impl Default for ExcelParser {
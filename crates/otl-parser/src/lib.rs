@@ -4,6 +4,7 @@
 //! - PDF documents
 //! - Microsoft Word (DOCX)
 //! - Microsoft Excel (XLSX, XLS)
+//! - CSV tables
 //! - Markdown files
 //! - Plain text files
 //!
@@ -14,13 +15,17 @@
 use std::path::Path;
 use thiserror::Error;
 
+pub mod csv;
 pub mod docx;
 pub mod excel;
 pub mod pdf;
+pub mod pii;
+pub mod report;
 
 pub use docx::DocxParser;
 pub use excel::ExcelParser;
 pub use pdf::PdfParser;
+pub use report::IngestReport;
 
 // ============================================================================
 // Error Types
@@ -72,6 +77,10 @@ pub enum ParserError {
     /// Timeout during parsing
     #[error("Parsing timeout exceeded: {0}ms")]
     Timeout(u64),
+
+    /// Parser provided by a plugin failed
+    #[error("Plugin parser failed: {0}")]
+    PluginError(String),
 }
 
 pub type Result<T> = std::result::Result<T, ParserError>;
@@ -380,6 +389,17 @@ pub struct DocumentParseMetadata {
     /// Whether OCR was used
     pub ocr_applied: bool,
 
+    /// OCR confidence (0.0 - 1.0) of each scanned page, in page order
+    pub ocr_confidence: Vec<f32>,
+
+    /// 1-based page numbers of `ocr_confidence` when only some pages were
+    /// scanned; empty when it covers pages 1, 2, …
+    pub ocr_pages: Vec<u32>,
+
+    /// 1-based scanned pages left out of the text for their low OCR
+    /// confidence
+    pub ocr_excluded_pages: Vec<u32>,
+
     /// Additional custom metadata
     pub custom: std::collections::HashMap<String, String>,
 }
@@ -400,6 +420,30 @@ pub trait DocumentParser: Send + Sync {
     fn can_parse(&self, file_type: FileType) -> bool {
         self.supported_types().contains(&file_type)
     }
+
+    /// File extensions (without the dot) handled by this parser, for
+    /// formats without a [`FileType`]
+    fn extensions(&self) -> &[String] {
+        &[]
+    }
+}
+
+impl<P: DocumentParser + ?Sized> DocumentParser for std::sync::Arc<P> {
+    fn parse(&self, path: &Path) -> Result<ParsedDocument> {
+        (**self).parse(path)
+    }
+
+    fn supported_types(&self) -> &[FileType] {
+        (**self).supported_types()
+    }
+
+    fn can_parse(&self, file_type: FileType) -> bool {
+        (**self).can_parse(file_type)
+    }
+
+    fn extensions(&self) -> &[String] {
+        (**self).extensions()
+    }
 }
 
 // ============================================================================
@@ -407,7 +451,8 @@ pub trait DocumentParser: Send + Sync {
 // ============================================================================
 
 /// Configuration for document chunking
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ChunkConfig {
     /// Target chunk size in characters
     pub chunk_size: usize,
@@ -516,7 +561,9 @@ fn chunk_text(
     let mut start = 0;
 
     while start < text.len() {
-        let end = (start + config.chunk_size).min(text.len());
+        // At least one character per chunk, however small the chunk size
+        let end = floor_char_boundary(text, (start + config.chunk_size).min(text.len()))
+            .max(ceil_char_boundary(text, start + 1));
 
         // Find a good break point (end of sentence or paragraph)
         let actual_end = if config.respect_paragraphs {
@@ -543,21 +590,25 @@ fn chunk_text(
             break;
         }
 
-        start = if actual_end > config.overlap {
-            actual_end - config.overlap
-        } else {
-            actual_end
-        };
+        // Step back by at most the overlap, but always move forward
+        let next = ceil_char_boundary(text, actual_end.saturating_sub(config.overlap));
+        start = if next > start { next } else { actual_end };
     }
 
     chunks
 }
 
+/// Bytes searched for a break point on each side of the target position
+const BREAK_SEARCH_WINDOW: usize = 100;
+
 /// Find a good break point near the target position
-fn find_break_point(text: &str, _start: usize, target: usize) -> usize {
+///
+/// The break point lies after `start`, so every chunk is non-empty.
+fn find_break_point(text: &str, start: usize, target: usize) -> usize {
     // Search window
-    let search_start = if target > 100 { target - 100 } else { target };
-    let search_end = (target + 100).min(text.len());
+    let search_start =
+        floor_char_boundary(text, target.saturating_sub(BREAK_SEARCH_WINDOW).max(start));
+    let search_end = floor_char_boundary(text, (target + BREAK_SEARCH_WINDOW).min(text.len()));
 
     let search_text = &text[search_start..search_end];
 
@@ -582,6 +633,88 @@ fn find_break_point(text: &str, _start: usize, target: usize) -> usize {
     target.min(text.len())
 }
 
+/// Largest character boundary at or before a byte index
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+/// Smallest character boundary at or after a byte index
+fn ceil_char_boundary(text: &str, index: usize) -> usize {
+    (index.min(text.len())..=text.len())
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(text.len())
+}
+
+/// Chunker entry points and invariants for property tests and fuzz targets
+#[cfg(any(test, feature = "fuzzing"))]
+#[doc(hidden)]
+pub mod fuzzing {
+    use super::{ChunkConfig, TextChunk, BREAK_SEARCH_WINDOW};
+
+    /// Longest UTF-8 encoding of a character
+    const MAX_CHAR_LEN: usize = 4;
+
+    /// Chunk text without page or section
+    pub fn chunk_text(text: &str, config: &ChunkConfig) -> Vec<TextChunk> {
+        super::chunk_text(text, config, None, None)
+    }
+
+    /// Break point search as used by the chunker
+    pub fn find_break_point(text: &str, start: usize, target: usize) -> usize {
+        super::find_break_point(text, start, target)
+    }
+
+    /// Panic unless `chunks` of `text` hold the chunker invariants
+    ///
+    /// Chunks are non-empty slices of `text` at character boundaries, move
+    /// forward, overlap by at most `config.overlap` bytes and exceed the
+    /// chunk size by at most the break search window. Without a minimum
+    /// chunk size they cover the whole text.
+    pub fn check_chunks(text: &str, config: &ChunkConfig, chunks: &[TextChunk]) {
+        if text.len() <= config.chunk_size {
+            assert_eq!(chunks.len(), 1, "short text is one chunk");
+            assert_eq!(chunks[0].content, text);
+            return;
+        }
+
+        let max_len = config.chunk_size.max(MAX_CHAR_LEN) + BREAK_SEARCH_WINDOW;
+        for chunk in chunks {
+            let (start, end) = (chunk.start_offset, chunk.end_offset);
+            assert!(start < end && end <= text.len(), "bad range {start}..{end}");
+            assert_eq!(chunk.content, text[start..end]);
+            assert!(end - start <= max_len, "chunk {start}..{end} too long");
+        }
+
+        for pair in chunks.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            assert!(next.start_offset > prev.start_offset, "chunks move forward");
+            let overlap = prev.end_offset.saturating_sub(next.start_offset);
+            assert!(overlap <= config.overlap, "overlap {overlap} too large");
+            if config.min_chunk_size == 0 {
+                assert!(next.start_offset <= prev.end_offset, "gap between chunks");
+            }
+        }
+
+        if config.min_chunk_size == 0 {
+            assert_eq!(chunks.first().map(|c| c.start_offset), Some(0));
+            assert_eq!(chunks.last().map(|c| c.end_offset), Some(text.len()));
+        }
+    }
+
+    /// Panic unless `end` is a valid break point for `start..target`
+    pub fn check_break_point(text: &str, start: usize, target: usize, end: usize) {
+        assert!(text.is_char_boundary(end), "break {end} inside a character");
+        assert!(
+            start < end && end <= text.len(),
+            "break {end} outside {start}.."
+        );
+        assert!(end <= target + BREAK_SEARCH_WINDOW, "break {end} too far");
+    }
+}
+
 // ============================================================================
 // Parser Registry
 // ============================================================================
@@ -604,6 +737,12 @@ impl ParserRegistry {
         self.parsers.push(Box::new(parser));
     }
 
+    /// Register a parser ahead of those already registered, so it takes
+    /// precedence for its file types
+    pub fn register_first<P: DocumentParser + 'static>(&mut self, parser: P) {
+        self.parsers.insert(0, Box::new(parser));
+    }
+
     /// Find a parser for a file type
     pub fn find_parser(&self, file_type: FileType) -> Option<&dyn DocumentParser> {
         self.parsers
@@ -612,16 +751,33 @@ impl ParserRegistry {
             .map(|p| p.as_ref())
     }
 
+    /// Find a parser by file extension
+    pub fn find_parser_for_extension(&self, extension: &str) -> Option<&dyn DocumentParser> {
+        self.parsers
+            .iter()
+            .find(|p| {
+                p.extensions()
+                    .iter()
+                    .any(|e| e.eq_ignore_ascii_case(extension))
+            })
+            .map(|p| p.as_ref())
+    }
+
     /// Parse a file using the appropriate parser
+    ///
+    /// A parser registered for the file's extension takes precedence over
+    /// one for its file type.
     pub fn parse(&self, path: &Path) -> Result<ParsedDocument> {
+        let extension = path.extension().and_then(|e| e.to_str());
+        if let Some(parser) = extension.and_then(|e| self.find_parser_for_extension(e)) {
+            return parser.parse(path);
+        }
+
         let file_type = FileType::from_path(path);
 
         if file_type == FileType::Unknown {
             return Err(ParserError::UnsupportedFormat(
-                path.extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("none")
-                    .to_string(),
+                extension.unwrap_or("none").to_string(),
             ));
         }
 
@@ -642,12 +798,16 @@ impl Default for ParserRegistry {
 impl ParserRegistry {
     /// Create a registry with all default parsers registered
     pub fn with_defaults() -> Self {
-        let mut registry = Self::new();
-        registry.register(PlainTextParser);
-        registry.register(PdfParser::new());
-        registry.register(DocxParser::new());
-        registry.register(ExcelParser::new());
-        registry
+        Self::new().with_default_parsers()
+    }
+
+    /// Register the default parsers after the ones already registered
+    pub fn with_default_parsers(mut self) -> Self {
+        self.register(PlainTextParser);
+        self.register(PdfParser::new());
+        self.register(DocxParser::new());
+        self.register(ExcelParser::new());
+        self
     }
 }
 
@@ -683,6 +843,7 @@ impl DocumentParser for PlainTextParser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_file_type_detection() {
@@ -732,6 +893,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_chunking_korean_without_breaks() {
+        // No sentence or line breaks, so chunks end wherever the size limit
+        // falls, which is inside a three-byte character
+        let doc = ParsedDocument::new("규정.txt", FileType::PlainText)
+            .with_content("연차휴가유급휴가".repeat(100));
+
+        let config = ChunkConfig {
+            chunk_size: 200,
+            overlap: 50,
+            ..Default::default()
+        };
+
+        let chunks = chunk_document(&doc, &config);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.content.len() <= 200));
+        assert_eq!(chunks.last().unwrap().end_offset, doc.content.len());
+    }
+
     #[test]
     fn test_document_section_builder() {
         let section = DocumentSection::new("Content here")
@@ -743,4 +923,63 @@ mod tests {
         assert_eq!(section.level, 1);
         assert_eq!(section.start_page, Some(5));
     }
+
+    /// Text mixing Latin, Hangul, CJK, kana and emoji with the separators
+    /// the break point search looks for
+    fn multilingual_text() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            "[a-zA-Z0-9]{1,12}",
+            "[가-힣]{1,8}",
+            "[一-龥]{1,6}",
+            "[ぁ-ゖ]{1,6}",
+            "[😀-🙏]{1,3}",
+            prop::sample::select(vec![" ", ". ", "。", "! ", "? ", "\n", "\n\n"])
+                .prop_map(str::to_string),
+        ];
+        prop::collection::vec(piece, 0..400).prop_map(|pieces| pieces.concat())
+    }
+
+    fn chunk_config() -> impl Strategy<Value = ChunkConfig> {
+        (1usize..1500, 0usize..400, 0usize..200, any::<bool>()).prop_map(
+            |(chunk_size, overlap, min_chunk_size, respect_paragraphs)| ChunkConfig {
+                chunk_size,
+                overlap,
+                min_chunk_size,
+                respect_sections: true,
+                respect_paragraphs,
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn prop_chunks_hold_invariants(text in multilingual_text(), config in chunk_config()) {
+            let chunks = fuzzing::chunk_text(&text, &config);
+            fuzzing::check_chunks(&text, &config, &chunks);
+        }
+
+        #[test]
+        fn prop_chunks_cover_text(text in multilingual_text(), config in chunk_config()) {
+            let config = ChunkConfig { min_chunk_size: 0, ..config };
+            let chunks = fuzzing::chunk_text(&text, &config);
+            fuzzing::check_chunks(&text, &config, &chunks);
+        }
+
+        #[test]
+        fn prop_break_point_is_valid(
+            text in multilingual_text(),
+            start in any::<prop::sample::Index>(),
+            target in any::<prop::sample::Index>(),
+        ) {
+            let boundaries: Vec<usize> = (0..=text.len())
+                .filter(|&i| text.is_char_boundary(i))
+                .collect();
+            let index = target.index(boundaries.len());
+            prop_assume!(index > 0);
+            let (start, target) = (boundaries[start.index(index)], boundaries[index]);
+
+            let end = fuzzing::find_break_point(&text, start, target);
+            fuzzing::check_break_point(&text, start, target, end);
+        }
+    }
 }
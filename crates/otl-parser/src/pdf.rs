@@ -2,18 +2,49 @@
 //!
 //! Extracts text content from PDF files, handling multi-page documents
 //! and basic structure detection.
+//!
+//! Text is extracted page by page, and sections never span pages, so every
+//! section starts on the page its text is on. Many PDFs mix digital pages
+//! with scanned ones; given a [`PageOcr`], the parser reads the pages that
+//! have (next to) no text layer with OCR and keeps the text layer of the
+//! others. Tables the OCR recognizes on those pages are kept with the
+//! document.
 
 use std::path::Path;
+use std::sync::Arc;
 
 use crate::{
     DocumentParseMetadata, DocumentParser, DocumentSection, FileType, ParsedDocument, ParserError,
-    Result,
+    Result, Table,
 };
 
+/// Pages with fewer characters of text are taken to be scanned
+const MIN_PAGE_TEXT: usize = 16;
+
+/// Page read by a [`PageOcr`]
+#[derive(Debug, Clone, Default)]
+pub struct RecognizedPage {
+    /// Text of the page
+    pub text: String,
+    /// Confidence score (0.0 - 1.0)
+    pub confidence: f32,
+    /// Tables recognized on the page, anchored to it
+    pub tables: Vec<Table>,
+}
+
+/// OCR of single PDF pages, for pages without a text layer
+pub trait PageOcr: Send + Sync {
+    /// Each of the 1-based `pages` of `pdf` as read, in the order given
+    fn recognize_pages(&self, pdf: &[u8], pages: &[u32]) -> Result<Vec<RecognizedPage>>;
+}
+
 /// PDF document parser
 pub struct PdfParser {
     /// Whether to extract tables (experimental)
     pub extract_tables: bool,
+
+    /// OCR of pages without text; without it they stay empty
+    ocr: Option<Arc<dyn PageOcr>>,
 }
 
 impl PdfParser {
@@ -21,6 +52,7 @@ impl PdfParser {
     pub fn new() -> Self {
         Self {
             extract_tables: false,
+            ocr: None,
         }
     }
 
@@ -30,26 +62,113 @@ impl PdfParser {
         self
     }
 
-    /// Extract text from a PDF file
-    fn extract_text(&self, path: &Path) -> Result<(String, Option<u32>)> {
-        let bytes = std::fs::read(path).map_err(|e| ParserError::IoError {
-            path: path.display().to_string(),
-            source: e,
-        })?;
+    /// Read pages without text with `ocr`
+    pub fn with_ocr(mut self, ocr: Arc<dyn PageOcr>) -> Self {
+        self.ocr = Some(ocr);
+        self
+    }
+
+    /// Parse a PDF document held in memory
+    ///
+    /// `file_path` is only recorded in the result. Malformed input fails
+    /// with [`ParserError::PdfError`].
+    pub fn parse_bytes(&self, bytes: &[u8], file_path: &str) -> Result<ParsedDocument> {
+        let mut pages = self.extract_pages(bytes)?;
+
+        let mut metadata = DocumentParseMetadata {
+            page_count: (!pages.is_empty()).then_some(pages.len() as u32),
+            ..Default::default()
+        };
+        let mut tables = Vec::new();
+        if let Some(ocr) = &self.ocr {
+            tables =
+                self.recognize_scanned_pages(ocr.as_ref(), bytes, &mut pages, &mut metadata)?;
+        }
 
-        let text = pdf_extract::extract_text_from_mem(&bytes)
-            .map_err(|e| ParserError::PdfError(e.to_string()))?;
+        let sections = pages
+            .iter()
+            .zip(1u32..)
+            .flat_map(|(text, page)| {
+                self.parse_sections(text)
+                    .into_iter()
+                    .map(move |mut section| {
+                        section.start_page = Some(page);
+                        section
+                    })
+            })
+            .collect();
 
-        // Try to estimate page count from the extracted content
-        // This is a rough estimate based on form feed characters
-        let page_count = text.matches('\x0C').count() as u32;
-        let page_count = if page_count > 0 {
-            Some(page_count + 1)
-        } else {
-            None
+        let mut doc = ParsedDocument {
+            file_path: file_path.to_string(),
+            file_type: FileType::Pdf,
+            // Pages separated by form feeds, as pdf-extract separates them
+            content: pages.join("\x0C"),
+            sections,
+            tables,
+            metadata,
         };
 
-        Ok((text, page_count))
+        // Try to extract title from first section or first line
+        if let Some(first_section) = doc.sections.first() {
+            if let Some(title) = &first_section.title {
+                doc.metadata.title = Some(title.clone());
+            }
+        } else if let Some(first_line) = doc.content.lines().next() {
+            let trimmed = first_line.trim();
+            if !trimmed.is_empty() && trimmed.len() < 200 {
+                doc.metadata.title = Some(trimmed.to_string());
+            }
+        }
+
+        Ok(doc)
+    }
+
+    /// Extract the text of each page from PDF bytes
+    ///
+    /// pdf-extract panics on some malformed files; the panic is reported as
+    /// a [`ParserError::PdfError`] instead of taking down the caller.
+    fn extract_pages(&self, bytes: &[u8]) -> Result<Vec<String>> {
+        std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(bytes))
+            .map_err(|_| ParserError::PdfError("malformed PDF".to_string()))?
+            .map_err(|e| ParserError::PdfError(e.to_string()))
+    }
+
+    /// Replace the text of pages without a text layer by their OCR text
+    ///
+    /// The OCR text is kept only where it is longer than the text layer, so
+    /// a short digital page (a title page, say) keeps its own text. The
+    /// confidences of all recognized pages are recorded with their numbers;
+    /// the tables of the pages whose OCR text is kept are returned.
+    fn recognize_scanned_pages(
+        &self,
+        ocr: &dyn PageOcr,
+        bytes: &[u8],
+        pages: &mut [String],
+        metadata: &mut DocumentParseMetadata,
+    ) -> Result<Vec<Table>> {
+        let scanned: Vec<u32> = pages
+            .iter()
+            .zip(1u32..)
+            .filter(|(text, _)| text.trim().chars().count() < MIN_PAGE_TEXT)
+            .map(|(_, page)| page)
+            .collect();
+        if scanned.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut tables = Vec::new();
+        let recognized = ocr.recognize_pages(bytes, &scanned)?;
+        for (&page, read) in scanned.iter().zip(recognized) {
+            metadata.ocr_confidence.push(read.confidence);
+            let current = &mut pages[page as usize - 1];
+            if read.text.trim().chars().count() > current.trim().chars().count() {
+                *current = read.text;
+                tables.extend(read.tables);
+            }
+        }
+        metadata.ocr_applied = true;
+        metadata.ocr_pages = scanned;
+        Ok(tables)
     }
 
     /// Parse sections from extracted text
@@ -182,37 +301,12 @@ impl Default for PdfParser {
 
 impl DocumentParser for PdfParser {
     fn parse(&self, path: &Path) -> Result<ParsedDocument> {
-        let (text, page_count) = self.extract_text(path)?;
-
-        let sections = self.parse_sections(&text);
-
-        let metadata = DocumentParseMetadata {
-            page_count,
-            ..Default::default()
-        };
-
-        let mut doc = ParsedDocument {
-            file_path: path.display().to_string(),
-            file_type: FileType::Pdf,
-            content: text,
-            sections,
-            tables: Vec::new(),
-            metadata,
-        };
-
-        // Try to extract title from first section or first line
-        if let Some(first_section) = doc.sections.first() {
-            if let Some(title) = &first_section.title {
-                doc.metadata.title = Some(title.clone());
-            }
-        } else if let Some(first_line) = doc.content.lines().next() {
-            let trimmed = first_line.trim();
-            if !trimmed.is_empty() && trimmed.len() < 200 {
-                doc.metadata.title = Some(trimmed.to_string());
-            }
-        }
+        let bytes = std::fs::read(path).map_err(|e| ParserError::IoError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
 
-        Ok(doc)
+        self.parse_bytes(&bytes, &path.display().to_string())
     }
 
     fn supported_types(&self) -> &[FileType] {
@@ -223,6 +317,7 @@ impl DocumentParser for PdfParser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_pdf_parser_creation() {
@@ -253,10 +348,145 @@ mod tests {
         assert_eq!(parser.detect_heading_level("1.1.1 Details"), 3);
     }
 
+    /// OCR that reads every page as "Scanned page N", recording the pages
+    /// it was asked for
+    #[derive(Default)]
+    struct FakeOcr {
+        asked: std::sync::Mutex<Vec<u32>>,
+    }
+
+    impl PageOcr for FakeOcr {
+        fn recognize_pages(&self, _pdf: &[u8], pages: &[u32]) -> Result<Vec<RecognizedPage>> {
+            self.asked.lock().unwrap().extend_from_slice(pages);
+            Ok(pages
+                .iter()
+                .map(|&p| {
+                    let mut table = Table::new().with_headers(vec!["이름".to_string()]);
+                    table.page = Some(p);
+                    RecognizedPage {
+                        text: format!("Scanned page {p} about annual leave"),
+                        confidence: 0.8,
+                        tables: vec![table],
+                    }
+                })
+                .collect())
+        }
+    }
+
+    /// PDF whose pages show `texts`; an empty text leaves the page blank,
+    /// like a scanned page without a text layer
+    fn pdf_of_pages(texts: &[&str]) -> Vec<u8> {
+        use lopdf::content::{Content, Operation};
+        use lopdf::{dictionary, Document, Object, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let kids: Vec<Object> = texts
+            .iter()
+            .map(|text| {
+                let operations = if text.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![
+                        Operation::new("BT", vec![]),
+                        Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                        Operation::new("Td", vec![72.into(), 700.into()]),
+                        Operation::new("Tj", vec![Object::string_literal(*text)]),
+                        Operation::new("ET", vec![]),
+                    ]
+                };
+                let content = Content { operations };
+                let content_id =
+                    doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                })
+                .into()
+            })
+            .collect();
+        let pages = dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        };
+        doc.objects.insert(pages_id, Object::Dictionary(pages));
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_ocr_reads_only_pages_without_text() {
+        let bytes = pdf_of_pages(&[
+            "Article 1 Leave is requested in advance.",
+            "",
+            "Article 3 Unused leave is paid out.",
+        ]);
+        let ocr = Arc::new(FakeOcr::default());
+        let doc = PdfParser::new()
+            .with_ocr(ocr.clone())
+            .parse_bytes(&bytes, "mixed.pdf")
+            .unwrap();
+
+        assert_eq!(*ocr.asked.lock().unwrap(), vec![2]);
+        assert_eq!(doc.metadata.page_count, Some(3));
+        assert!(doc.metadata.ocr_applied);
+        assert_eq!(doc.metadata.ocr_confidence, vec![0.8]);
+        assert_eq!(doc.metadata.ocr_pages, vec![2]);
+
+        let pages: Vec<(Option<u32>, bool)> = doc
+            .sections
+            .iter()
+            .map(|s| (s.start_page, s.content.contains("Scanned page")))
+            .collect();
+        assert_eq!(
+            pages,
+            vec![(Some(1), false), (Some(2), true), (Some(3), false)]
+        );
+        assert!(doc.content.contains("Unused leave"));
+        assert_eq!(doc.tables.len(), 1);
+        assert_eq!(doc.tables[0].page, Some(2));
+
+        // Without OCR the scanned page stays empty
+        let doc = PdfParser::new().parse_bytes(&bytes, "mixed.pdf").unwrap();
+        assert!(!doc.metadata.ocr_applied);
+        assert!(!doc.content.contains("Scanned page"));
+    }
+
     #[test]
     fn test_supported_types() {
         let parser = PdfParser::new();
         assert!(parser.can_parse(FileType::Pdf));
         assert!(!parser.can_parse(FileType::Docx));
     }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_malformed_pdf_fails_cleanly(body in prop::collection::vec(any::<u8>(), 0..2048)) {
+            // Past the header check, so the bytes reach the object parser
+            let mut bytes = b"%PDF-1.4\n".to_vec();
+            bytes.extend(body);
+            let _ = PdfParser::new().parse_bytes(&bytes, "fuzz.pdf");
+        }
+    }
 }
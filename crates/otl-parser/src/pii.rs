@@ -0,0 +1,209 @@
+//! Redaction of personal information
+//!
+//! Replaces resident registration numbers, phone numbers, e-mail addresses
+//! and card numbers in parsed text with a marker before the text is chunked,
+//! embedded or shown to reviewers. Patterns follow Korean formats; digits
+//! glued to a match (a longer number) keep it from matching.
+//!
+//! Author: hephaex@gmail.com
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{DocumentSection, ParsedDocument};
+
+/// Marker that replaces redacted text by default
+pub const DEFAULT_PII_MARKER: &str = "[개인정보]";
+
+/// Kind of personal information
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    /// 주민등록번호 (and 외국인등록번호)
+    ResidentId,
+    /// Mobile and landline numbers
+    Phone,
+    Email,
+    CardNumber,
+}
+
+impl PiiKind {
+    pub const ALL: [PiiKind; 4] = [
+        PiiKind::ResidentId,
+        PiiKind::Phone,
+        PiiKind::Email,
+        PiiKind::CardNumber,
+    ];
+
+    fn pattern(self) -> &'static str {
+        match self {
+            Self::ResidentId => {
+                r"[0-9]{2}(?:0[1-9]|1[0-2])(?:0[1-9]|[12][0-9]|3[01])-?[1-8][0-9]{6}"
+            }
+            Self::Phone => r"0(?:1[016789]|2|[3-6][1-5]|70)[-. ]?[0-9]{3,4}[-. ]?[0-9]{4}",
+            Self::Email => r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+            Self::CardNumber => r"[0-9]{4}[- ][0-9]{4}[- ][0-9]{4}[- ][0-9]{4}",
+        }
+    }
+}
+
+/// Redaction settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PiiConfig {
+    /// Kinds to redact
+    pub kinds: Vec<PiiKind>,
+    pub marker: String,
+}
+
+impl Default for PiiConfig {
+    fn default() -> Self {
+        Self {
+            kinds: PiiKind::ALL.to_vec(),
+            marker: DEFAULT_PII_MARKER.to_string(),
+        }
+    }
+}
+
+/// Replaces personal information with a marker
+#[derive(Debug, Clone)]
+pub struct PiiRedactor {
+    patterns: Vec<Regex>,
+    marker: String,
+}
+
+impl PiiRedactor {
+    pub fn new(config: &PiiConfig) -> Self {
+        let patterns = config
+            .kinds
+            .iter()
+            .map(|kind| Regex::new(kind.pattern()).expect("PII patterns are valid"))
+            .collect();
+        Self {
+            patterns,
+            marker: config.marker.clone(),
+        }
+    }
+
+    /// Text with personal information replaced, and the number of
+    /// replacements
+    pub fn redact(&self, text: &str) -> (String, usize) {
+        let mut redacted = text.to_string();
+        let mut count = 0;
+        for pattern in &self.patterns {
+            let mut out = String::with_capacity(redacted.len());
+            let mut last = 0;
+            for m in pattern.find_iter(&redacted) {
+                if glued_to_digit(&redacted, m.start(), m.end()) {
+                    continue;
+                }
+                out.push_str(&redacted[last..m.start()]);
+                out.push_str(&self.marker);
+                last = m.end();
+                count += 1;
+            }
+            out.push_str(&redacted[last..]);
+            redacted = out;
+        }
+        (redacted, count)
+    }
+
+    /// Redact the text, sections and tables of a document in place;
+    /// returns the number of replacements
+    pub fn redact_document(&self, doc: &mut ParsedDocument) -> usize {
+        let mut count = self.redact_in_place(&mut doc.content);
+        count += self.redact_sections(&mut doc.sections);
+        for table in &mut doc.tables {
+            let cells = table
+                .caption
+                .iter_mut()
+                .chain(table.headers.iter_mut())
+                .chain(table.rows.iter_mut().flatten());
+            for cell in cells {
+                count += self.redact_in_place(cell);
+            }
+        }
+        count
+    }
+
+    fn redact_sections(&self, sections: &mut [DocumentSection]) -> usize {
+        sections
+            .iter_mut()
+            .map(|section| {
+                let title = section
+                    .title
+                    .as_mut()
+                    .map_or(0, |t| self.redact_in_place(t));
+                title
+                    + self.redact_in_place(&mut section.content)
+                    + self.redact_sections(&mut section.children)
+            })
+            .sum()
+    }
+
+    fn redact_in_place(&self, text: &mut String) -> usize {
+        let (redacted, count) = self.redact(text);
+        if count > 0 {
+            *text = redacted;
+        }
+        count
+    }
+}
+
+impl Default for PiiRedactor {
+    fn default() -> Self {
+        Self::new(&PiiConfig::default())
+    }
+}
+
+/// Whether a match is part of a longer number
+fn glued_to_digit(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    before.is_some_and(|c| c.is_ascii_digit()) || after.is_some_and(|c| c.is_ascii_digit())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileType;
+
+    #[test]
+    fn test_redact_korean_formats() {
+        let redactor = PiiRedactor::default();
+        let (text, count) = redactor.redact(
+            "홍길동(900101-1234567) 연락처 010-1234-5678, hong@example.co.kr 카드 1234-5678-9012-3456",
+        );
+        assert_eq!(
+            text,
+            "홍길동([개인정보]) 연락처 [개인정보], [개인정보] 카드 [개인정보]"
+        );
+        assert_eq!(count, 4);
+
+        // Digits glued to a match make it part of a longer number
+        let (text, count) = redactor.redact("문서번호 19900101123456789");
+        assert_eq!(count, 0);
+        assert_eq!(text, "문서번호 19900101123456789");
+    }
+
+    #[test]
+    fn test_redact_document() {
+        let config = PiiConfig {
+            kinds: vec![PiiKind::Email],
+            marker: "***".to_string(),
+        };
+        let mut doc = ParsedDocument::new("인사기록.txt", FileType::PlainText)
+            .with_content("담당: hr@example.com, 010-1111-2222");
+        doc.sections
+            .push(DocumentSection::new("문의 hr@example.com").with_title("제1조"));
+
+        let count = PiiRedactor::new(&config).redact_document(&mut doc);
+        assert_eq!(count, 2);
+        assert_eq!(doc.content, "담당: ***, 010-1111-2222");
+        assert_eq!(doc.sections[0].content, "문의 ***");
+    }
+}
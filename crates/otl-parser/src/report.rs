@@ -0,0 +1,385 @@
+//! Ingest report
+//!
+//! Summarizes how a document was chunked, so problems show up at ingest
+//! instead of as poor search results later: the token distribution of the
+//! chunks against the embedding model's input limit, chunks over that limit
+//! (only partly embedded), sections without text and the OCR confidence of
+//! scanned pages. Pages below [`LOW_OCR_CONFIDENCE`] are listed for
+//! re-scanning, with whether they were left out of the chunks. Token counts
+//! come from the caller, which knows the embedding model.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::{DocumentSection, ParsedDocument, TextChunk};
+use serde::{Deserialize, Serialize};
+
+/// Upper bounds of the token histogram buckets; larger chunks fall into a
+/// final open bucket
+pub const TOKEN_BUCKETS: [usize; 8] = [64, 128, 256, 512, 1024, 2048, 4096, 8192];
+
+/// OCR confidence below which a page is flagged
+pub const LOW_OCR_CONFIDENCE: f32 = 0.6;
+
+/// Report of one ingested document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IngestReport {
+    pub chunk_count: usize,
+    /// Input limit of the embedding model, in tokens
+    pub token_limit: usize,
+    pub tokens: TokenStats,
+    pub histogram: Vec<TokenBucket>,
+    /// Chunks the embedding model will truncate
+    pub over_limit: Vec<OverLimitChunk>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr: Option<OcrStats>,
+    #[serde(default)]
+    pub warnings: Vec<IngestWarning>,
+    /// Scanned pages to re-scan, in page order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rescan: Vec<RescanPage>,
+}
+
+/// Token count statistics of the chunks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenStats {
+    pub total: usize,
+    pub min: usize,
+    pub max: usize,
+    pub mean: f32,
+    pub p95: usize,
+}
+
+/// Chunks of at most `up_to` tokens (and more than the previous bucket)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBucket {
+    /// `None` for the bucket above the largest bound
+    pub up_to: Option<usize>,
+    pub count: usize,
+}
+
+/// Chunk over the embedding model's input limit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverLimitChunk {
+    pub chunk_index: u32,
+    pub tokens: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+/// OCR confidence distribution of scanned pages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrStats {
+    pub pages: usize,
+    pub min: f32,
+    pub mean: f32,
+    /// Pages per confidence decile (`[0.0, 0.1)` … `[0.9, 1.0]`)
+    pub deciles: [usize; 10],
+    /// 1-based pages below [`LOW_OCR_CONFIDENCE`]
+    pub low_confidence_pages: Vec<u32>,
+}
+
+/// Scanned page whose OCR confidence is too low to trust
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RescanPage {
+    /// 1-based page number
+    pub page: u32,
+    pub confidence: f32,
+    /// Left out of the chunks, so not searchable until re-scanned
+    #[serde(default)]
+    pub excluded: bool,
+}
+
+/// Problem found while ingesting a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IngestWarning {
+    /// The document produced no chunks
+    NoChunks,
+    /// A section has a heading but no text
+    EmptySection {
+        /// 0-based position of the section in the document
+        index: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+    },
+    /// Chunks over the embedding model's input limit
+    OverLimit { chunks: usize },
+    /// Scanned pages OCR had low confidence in
+    LowOcrConfidence { pages: usize },
+}
+
+impl IngestReport {
+    /// Report of chunk texts, with their sections if known
+    pub fn build<'a>(
+        chunks: impl IntoIterator<Item = (u32, &'a str, Option<&'a str>)>,
+        token_limit: usize,
+        count_tokens: impl Fn(&str) -> usize,
+    ) -> Self {
+        let mut counts = Vec::new();
+        let mut over_limit = Vec::new();
+        for (chunk_index, content, section) in chunks {
+            let tokens = count_tokens(content);
+            if tokens > token_limit {
+                over_limit.push(OverLimitChunk {
+                    chunk_index,
+                    tokens,
+                    section: section.map(str::to_string),
+                });
+            }
+            counts.push(tokens);
+        }
+
+        let mut warnings = Vec::new();
+        if counts.is_empty() {
+            warnings.push(IngestWarning::NoChunks);
+        }
+        if !over_limit.is_empty() {
+            warnings.push(IngestWarning::OverLimit {
+                chunks: over_limit.len(),
+            });
+        }
+
+        Self {
+            chunk_count: counts.len(),
+            token_limit,
+            histogram: histogram(&counts),
+            tokens: token_stats(counts),
+            over_limit,
+            ocr: None,
+            warnings,
+            rescan: Vec::new(),
+        }
+    }
+
+    /// Report of a parsed document and its chunks
+    pub fn for_document(
+        doc: &ParsedDocument,
+        chunks: &[TextChunk],
+        token_limit: usize,
+        count_tokens: impl Fn(&str) -> usize,
+    ) -> Self {
+        Self::build(
+            chunks
+                .iter()
+                .map(|c| (c.index, c.content.as_str(), c.section.as_deref())),
+            token_limit,
+            count_tokens,
+        )
+        .with_sections(&doc.sections)
+        .with_scanned_pages(&doc.metadata.ocr_confidence, &doc.metadata.ocr_pages)
+        .with_ocr_exclusions(&doc.metadata.ocr_excluded_pages)
+    }
+
+    /// Warn about sections without text
+    pub fn with_sections(mut self, sections: &[DocumentSection]) -> Self {
+        let empty = sections
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.content.trim().is_empty())
+            .map(|(index, s)| IngestWarning::EmptySection {
+                index,
+                title: s.title.clone(),
+            });
+        self.warnings.extend(empty);
+        self
+    }
+
+    /// Add the OCR confidence of each scanned page (none if not scanned)
+    pub fn with_ocr(self, confidences: &[f32]) -> Self {
+        self.with_scanned_pages(confidences, &[])
+    }
+
+    /// Add the OCR confidences of the scanned pages numbered `pages` (1, 2,
+    /// … where shorter), for documents where only some pages were scanned
+    pub fn with_scanned_pages(mut self, confidences: &[f32], pages: &[u32]) -> Self {
+        if confidences.is_empty() {
+            return self;
+        }
+        let numbered = confidences
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| (pages.get(i).copied().unwrap_or(i as u32 + 1), c));
+        let mut deciles = [0; 10];
+        for confidence in confidences {
+            let decile = (confidence.clamp(0.0, 1.0) * 10.0) as usize;
+            deciles[decile.min(9)] += 1;
+        }
+        self.rescan = numbered
+            .filter(|(_, c)| *c < LOW_OCR_CONFIDENCE)
+            .map(|(page, confidence)| RescanPage {
+                page,
+                confidence,
+                excluded: false,
+            })
+            .collect();
+        let low_confidence_pages: Vec<u32> = self.rescan.iter().map(|p| p.page).collect();
+        if !low_confidence_pages.is_empty() {
+            self.warnings.push(IngestWarning::LowOcrConfidence {
+                pages: low_confidence_pages.len(),
+            });
+        }
+        self.ocr = Some(OcrStats {
+            pages: confidences.len(),
+            min: confidences.iter().copied().fold(1.0, f32::min),
+            mean: confidences.iter().sum::<f32>() / confidences.len() as f32,
+            deciles,
+            low_confidence_pages,
+        });
+        self
+    }
+
+    /// Mark the re-scan pages left out of the chunks
+    pub fn with_ocr_exclusions(mut self, excluded_pages: &[u32]) -> Self {
+        for page in &mut self.rescan {
+            page.excluded = excluded_pages.contains(&page.page);
+        }
+        self
+    }
+}
+
+fn histogram(counts: &[usize]) -> Vec<TokenBucket> {
+    let mut buckets: Vec<TokenBucket> = TOKEN_BUCKETS
+        .iter()
+        .map(|&bound| TokenBucket {
+            up_to: Some(bound),
+            count: 0,
+        })
+        .chain(std::iter::once(TokenBucket {
+            up_to: None,
+            count: 0,
+        }))
+        .collect();
+    for &tokens in counts {
+        let bucket = TOKEN_BUCKETS
+            .iter()
+            .position(|&bound| tokens <= bound)
+            .unwrap_or(TOKEN_BUCKETS.len());
+        buckets[bucket].count += 1;
+    }
+    buckets
+}
+
+fn token_stats(mut counts: Vec<usize>) -> TokenStats {
+    if counts.is_empty() {
+        return TokenStats::default();
+    }
+    counts.sort_unstable();
+    let total: usize = counts.iter().sum();
+    // Nearest-rank percentile
+    let p95 = counts[(counts.len() * 95).div_ceil(100) - 1];
+    TokenStats {
+        total,
+        min: counts[0],
+        max: counts[counts.len() - 1],
+        mean: total as f32 / counts.len() as f32,
+        p95,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+
+    #[test]
+    fn test_report_of_chunks() {
+        let long = "휴가 ".repeat(600);
+        let chunks = [
+            (0, "연차 휴가 신청", Some("제1장")),
+            (1, long.as_str(), Some("제2장")),
+            (2, "", None),
+        ];
+        let report = IngestReport::build(chunks, 512, words);
+
+        assert_eq!(report.chunk_count, 3);
+        assert_eq!(report.tokens.total, 603);
+        assert_eq!((report.tokens.min, report.tokens.max), (0, 600));
+        assert_eq!(report.tokens.p95, 600);
+        assert_eq!(report.histogram[0].count, 2);
+        assert_eq!(report.histogram[4].up_to, Some(1024));
+        assert_eq!(report.histogram[4].count, 1);
+        assert_eq!(report.histogram.len(), TOKEN_BUCKETS.len() + 1);
+        assert_eq!(
+            report.over_limit,
+            vec![OverLimitChunk {
+                chunk_index: 1,
+                tokens: 600,
+                section: Some("제2장".to_string()),
+            }]
+        );
+        assert_eq!(
+            report.warnings,
+            vec![IngestWarning::OverLimit { chunks: 1 }]
+        );
+        assert!(report.ocr.is_none());
+    }
+
+    #[test]
+    fn test_sections_and_ocr_warnings() {
+        let sections = [
+            DocumentSection::new("본문").with_title("제1조"),
+            DocumentSection::new("  \n").with_title("제2조"),
+        ];
+        let report = IngestReport::build([], 512, words)
+            .with_sections(&sections)
+            .with_ocr(&[0.95, 0.4, 1.0]);
+
+        assert_eq!(
+            report.warnings,
+            vec![
+                IngestWarning::NoChunks,
+                IngestWarning::EmptySection {
+                    index: 1,
+                    title: Some("제2조".to_string()),
+                },
+                IngestWarning::LowOcrConfidence { pages: 1 },
+            ]
+        );
+        let ocr = report.ocr.as_ref().unwrap();
+        assert_eq!(ocr.pages, 3);
+        assert_eq!(ocr.min, 0.4);
+        assert_eq!(ocr.deciles[4], 1);
+        assert_eq!(ocr.deciles[9], 2);
+        assert_eq!(ocr.low_confidence_pages, vec![2]);
+        assert_eq!(
+            report.rescan,
+            vec![RescanPage {
+                page: 2,
+                confidence: 0.4,
+                excluded: false,
+            }]
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["warnings"][1]["kind"], "empty_section");
+    }
+
+    #[test]
+    fn test_excluded_rescan_pages() {
+        let report = IngestReport::build([(0, "본문", None)], 512, words)
+            .with_ocr(&[0.3, 0.9, 0.5])
+            .with_ocr_exclusions(&[1]);
+
+        let pages: Vec<(u32, bool)> = report.rescan.iter().map(|p| (p.page, p.excluded)).collect();
+        assert_eq!(pages, vec![(1, true), (3, false)]);
+
+        // Only pages 2 and 5 of a mixed document were scanned
+        let report = IngestReport::build([(0, "본문", None)], 512, words)
+            .with_scanned_pages(&[0.9, 0.2], &[2, 5]);
+        assert_eq!(report.ocr.as_ref().unwrap().low_confidence_pages, vec![5]);
+        assert_eq!(report.rescan[0].page, 5);
+
+        // Reports stored before re-scan pages were listed still read
+        let mut json = serde_json::to_value(&report).unwrap();
+        json.as_object_mut().unwrap().remove("rescan");
+        let stored: IngestReport = serde_json::from_value(json).unwrap();
+        assert!(stored.rescan.is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+//! Benchmarks of the in-memory cache layers
+//!
+//! Run with `cargo bench -p otl-rag --bench cache`.
+//!
+//! Author: hephaex@gmail.com
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use futures::future::join_all;
+use uuid::Uuid;
+
+use otl_core::{DocumentAcl, SearchResult, SearchResultType, SourceReference};
+use otl_rag::{EmbeddingCache, QueryCache};
+
+const ENTRIES: usize = 10_000;
+const DIMENSION: usize = 1536;
+/// Lookups per iteration
+const BATCH: usize = 1_000;
+/// Concurrent tasks in the contended benchmark
+const TASKS: usize = 8;
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(TASKS)
+        .build()
+        .unwrap()
+}
+
+fn text(i: usize) -> String {
+    format!("제{i}조 연차휴가는 1년간 80퍼센트 이상 출근한 직원에게 부여한다")
+}
+
+fn results() -> Vec<SearchResult> {
+    (0..5)
+        .map(|i| SearchResult {
+            content: text(i),
+            score: 1.0 / (i + 1) as f32,
+            source: SourceReference::new(Uuid::new_v4()),
+            acl: DocumentAcl::default(),
+            result_type: SearchResultType::Vector,
+        })
+        .collect()
+}
+
+/// Read `BATCH` cached entries starting at `offset`
+async fn read_batch(cache: &EmbeddingCache, offset: usize) {
+    for i in 0..BATCH {
+        black_box(cache.get(&text((offset + i) % ENTRIES)).await);
+    }
+}
+
+fn bench_embedding_cache(c: &mut Criterion) {
+    let rt = runtime();
+    let cache = EmbeddingCache::new();
+    rt.block_on(async {
+        for i in 0..ENTRIES {
+            cache.put(&text(i), vec![0.1; DIMENSION]).await;
+        }
+    });
+
+    let mut group = c.benchmark_group("embedding_cache");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("get_hit", |b| {
+        b.to_async(&rt).iter(|| async {
+            for i in 0..BATCH {
+                black_box(cache.get(&text(i * 7 % ENTRIES)).await);
+            }
+        })
+    });
+    group.bench_function("get_miss", |b| {
+        b.to_async(&rt).iter(|| async {
+            for i in 0..BATCH {
+                black_box(cache.get(&text(ENTRIES + i)).await);
+            }
+        })
+    });
+    group.bench_function("put", |b| {
+        b.to_async(&rt).iter(|| async {
+            for i in 0..BATCH {
+                cache.put(&text(i), vec![0.2; DIMENSION]).await;
+            }
+        })
+    });
+
+    group.throughput(Throughput::Elements((BATCH * TASKS) as u64));
+    group.bench_function("get_hit_contended", |b| {
+        b.to_async(&rt).iter(|| {
+            join_all((0..TASKS).map(|task| {
+                let cache = cache.clone();
+                tokio::spawn(async move { read_batch(&cache, task * BATCH).await })
+            }))
+        })
+    });
+    group.finish();
+}
+
+fn bench_query_cache(c: &mut Criterion) {
+    let rt = runtime();
+    let cache = QueryCache::new();
+    rt.block_on(async {
+        for i in 0..1_000 {
+            cache.put(&text(i), 5, 0.0, results()).await;
+        }
+    });
+
+    let mut group = c.benchmark_group("query_cache");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("get_hit", |b| {
+        b.to_async(&rt).iter(|| async {
+            for i in 0..BATCH {
+                black_box(cache.get(&text(i % 1_000), 5, 0.0).await);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_embedding_cache, bench_query_cache);
+criterion_main!(benches);
@@ -0,0 +1,61 @@
+//! Benchmarks of reciprocal rank fusion
+//!
+//! Run with `cargo bench -p otl-rag --bench fusion`.
+//!
+//! Author: hephaex@gmail.com
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use uuid::Uuid;
+
+use otl_core::{DocumentAcl, SearchResult, SearchResultType, SourceReference};
+use otl_rag::{fusion, RagConfig};
+
+/// `count` candidates split over vector, graph and keyword search; a third
+/// of the keyword and graph results repeat vector results
+fn candidates(count: usize) -> Vec<SearchResult> {
+    let kinds = [
+        SearchResultType::Vector,
+        SearchResultType::Graph,
+        SearchResultType::Keyword,
+    ];
+    (0..count)
+        .map(|i| {
+            let result_type = kinds[i % 3].clone();
+            let chunk = if result_type != SearchResultType::Vector && i % 9 < 3 {
+                i / 3 * 3
+            } else {
+                i
+            };
+            SearchResult {
+                content: format!("청크 {chunk}: 미사용 연차휴가는 수당으로 보상한다."),
+                score: 1.0 - (i as f32 / count as f32),
+                source: SourceReference::new(Uuid::from_u128(chunk as u128)),
+                acl: DocumentAcl::default(),
+                result_type,
+            }
+        })
+        .collect()
+}
+
+fn bench_merge_results(c: &mut Criterion) {
+    let config = RagConfig::default();
+    let mut group = c.benchmark_group("merge_results");
+    for count in [1_000, 10_000] {
+        let results = candidates(count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &results,
+            |b, results| {
+                b.iter_batched(
+                    || results.clone(),
+                    |results| fusion::merge_results(black_box(results), &config),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_merge_results);
+criterion_main!(benches);
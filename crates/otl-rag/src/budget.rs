@@ -0,0 +1,198 @@
+//! Query deadlines and stage budgets
+//!
+//! Every query gets a deadline of [`TimeoutBudgets::total`]. Each stage runs
+//! under its own budget, capped by the time left until the deadline:
+//! a search backend that overruns is skipped like a failed backend, and an
+//! LLM answer that overruns is replaced by the most relevant passages. The
+//! query as a whole fails only when the deadline passes outside those
+//! stages. Overruns are counted per stage for the metrics endpoint.
+//!
+//! Author: hephaex@gmail.com
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Time budgets of a query and its stages, in milliseconds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeoutBudgets {
+    /// Deadline of the whole query
+    pub total_ms: u64,
+
+    /// Budget of each search backend
+    pub search_ms: u64,
+
+    /// Budget of answer generation by the LLM
+    pub generation_ms: u64,
+
+    /// Budget of each optional LLM step (citation repair, structured answer)
+    pub refinement_ms: u64,
+}
+
+impl Default for TimeoutBudgets {
+    fn default() -> Self {
+        Self {
+            total_ms: 60_000,
+            search_ms: 5_000,
+            generation_ms: 45_000,
+            refinement_ms: 10_000,
+        }
+    }
+}
+
+/// Point in time a query must finish by
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    /// Deadline `total` from now
+    pub(crate) fn after(total: Duration) -> Self {
+        Self {
+            at: Instant::now() + total,
+        }
+    }
+
+    /// Time left until the deadline
+    pub(crate) fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    /// A stage budget of `ms`, capped by the time left
+    pub(crate) fn budget(&self, ms: u64) -> Duration {
+        Duration::from_millis(ms).min(self.remaining())
+    }
+}
+
+/// Pipeline stages with their own budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    VectorSearch,
+    GraphSearch,
+    KeywordSearch,
+    FaqSearch,
+    Generation,
+    Refinement,
+    /// The query deadline passed outside a budgeted stage
+    Query,
+}
+
+impl Stage {
+    /// All stages, in metric order
+    pub const ALL: [Stage; 7] = [
+        Stage::VectorSearch,
+        Stage::GraphSearch,
+        Stage::KeywordSearch,
+        Stage::FaqSearch,
+        Stage::Generation,
+        Stage::Refinement,
+        Stage::Query,
+    ];
+
+    /// Stage name in logs and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::VectorSearch => "vector_search",
+            Stage::GraphSearch => "graph_search",
+            Stage::KeywordSearch => "keyword_search",
+            Stage::FaqSearch => "faq_search",
+            Stage::Generation => "generation",
+            Stage::Refinement => "refinement",
+            Stage::Query => "query",
+        }
+    }
+}
+
+/// Budget overruns per stage since startup
+#[derive(Debug, Default)]
+pub struct TimeoutMetrics {
+    counts: [AtomicU64; Stage::ALL.len()],
+}
+
+impl TimeoutMetrics {
+    /// Count an overrun of `stage`
+    pub(crate) fn record(&self, stage: Stage) {
+        self.counts[stage as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Overruns of every stage
+    pub fn snapshot(&self) -> Vec<(Stage, u64)> {
+        Stage::ALL
+            .iter()
+            .map(|&stage| (stage, self.counts[stage as usize].load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Run `future` for at most `budget`; `None` (and a counted overrun of
+    /// `stage`) when it took longer
+    pub(crate) async fn within<F: Future>(
+        &self,
+        stage: Stage,
+        budget: Duration,
+        future: F,
+    ) -> Option<F::Output> {
+        match tokio::time::timeout(budget, future).await {
+            Ok(output) => Some(output),
+            Err(_) => {
+                tracing::warn!(
+                    "{} exceeded its {} ms budget",
+                    stage.as_str(),
+                    budget.as_millis()
+                );
+                self.record(stage);
+                None
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_is_capped_by_deadline() {
+        let deadline = Deadline::after(Duration::from_millis(200));
+
+        assert!(deadline.budget(50) <= Duration::from_millis(50));
+        assert!(deadline.budget(10_000) <= Duration::from_millis(200));
+        assert_eq!(Deadline::after(Duration::ZERO).budget(50), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_budgets_deserialize_with_defaults() {
+        let budgets: TimeoutBudgets = serde_json::from_str(r#"{"generation_ms": 5000}"#).unwrap();
+
+        assert_eq!(budgets.generation_ms, 5000);
+        assert_eq!(budgets.total_ms, TimeoutBudgets::default().total_ms);
+    }
+
+    #[tokio::test]
+    async fn test_within_counts_overruns() {
+        let metrics = TimeoutMetrics::default();
+
+        let fast = metrics
+            .within(Stage::VectorSearch, Duration::from_secs(1), async { 1 })
+            .await;
+        let slow = metrics
+            .within(
+                Stage::Generation,
+                Duration::from_millis(10),
+                tokio::time::sleep(Duration::from_secs(5)),
+            )
+            .await;
+
+        assert_eq!(fast, Some(1));
+        assert!(slow.is_none());
+        let counts = metrics.snapshot();
+        assert!(counts.contains(&(Stage::Generation, 1)));
+        assert!(counts.contains(&(Stage::VectorSearch, 0)));
+    }
+}
@@ -3,28 +3,58 @@
 //! Provides high-performance concurrent caching for:
 //! - Document embeddings (to avoid re-computing expensive embeddings)
 //! - Query results (to serve repeated queries quickly)
+//! - Generated answers (to skip the LLM for a repeated question over the
+//!   same contexts)
 //!
-//! Uses the moka crate for thread-safe, async-compatible LRU caching
-//! with TTL support.
+//! Embeddings can also be persisted to local disk so that they survive
+//! restarts (see [`EmbeddingStore`]).
+//!
+//! Entries live in a [`CacheBackend`]: per-process moka LRU caches with TTL
+//! by default, or Redis when several replicas should share one cache.
 //!
 //! Author: hephaex@gmail.com
 
-use moka::future::Cache;
-use otl_core::{Result, SearchResult};
+use crate::cache_backend::{CacheBackend, MemoryBackend, RedisBackend};
+use crate::embedding_store::EmbeddingStore;
+use crate::llm::LlmResponseCache;
+use async_trait::async_trait;
+use otl_core::{Citation, ExtractedPassage, OtlError, Result, SearchResult, StructuredAnswer};
+use otl_vector::embedding::EmbeddingClient;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
+use uuid::Uuid;
 
 // ============================================================================
 // Cache Configuration
 // ============================================================================
 
+/// Where cache entries are stored
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CacheBackendKind {
+    /// In-process caches, one per replica
+    #[default]
+    Memory,
+    /// Redis shared by all replicas
+    Redis {
+        /// Connection URL (`redis://host:6379/0`)
+        url: String,
+        /// Prefix of every key written by this deployment
+        key_prefix: String,
+    },
+}
+
 /// Configuration for cache behavior
 #[derive(Debug, Clone)]
 pub struct CacheConfig {
+    /// Storage backend shared by all caches
+    pub backend: CacheBackendKind,
+
     /// Maximum number of entries in embedding cache
     pub embedding_max_capacity: u64,
 
@@ -34,9 +64,28 @@ pub struct CacheConfig {
     /// Time-to-live for embedding cache entries (in seconds)
     pub embedding_ttl_seconds: u64,
 
+    /// Directory of the on-disk embedding store (no persistence when unset)
+    pub embedding_store_path: Option<PathBuf>,
+
+    /// Maximum number of embeddings kept on disk
+    pub embedding_store_max_entries: usize,
+
     /// Time-to-live for query cache entries (in seconds)
     pub query_ttl_seconds: u64,
 
+    /// Maximum number of entries in answer cache
+    pub answer_max_capacity: u64,
+
+    /// Time-to-live for answer cache entries (in seconds)
+    pub answer_ttl_seconds: u64,
+
+    /// Maximum number of entries in the LLM response cache
+    pub llm_max_capacity: u64,
+
+    /// Time-to-live for LLM response cache entries (in seconds, 0 disables
+    /// the cache)
+    pub llm_ttl_seconds: u64,
+
     /// Enable cache statistics collection
     pub enable_stats: bool,
 }
@@ -44,14 +93,26 @@ pub struct CacheConfig {
 impl Default for CacheConfig {
     fn default() -> Self {
         Self {
+            backend: CacheBackendKind::Memory,
             // 10k embeddings @ ~1.5KB each = ~15MB
             embedding_max_capacity: 10_000,
             // 1k query results @ ~10KB each = ~10MB
             query_max_capacity: 1_000,
             // Embeddings are stable, cache for 1 hour
             embedding_ttl_seconds: 3600,
+            // Persistence is opt-in; 100k embeddings @ ~6KB each = ~600MB on disk
+            embedding_store_path: None,
+            embedding_store_max_entries: 100_000,
             // Query results may change as documents are updated, cache for 5 minutes
             query_ttl_seconds: 300,
+            // 500 answers @ ~5KB each = ~2.5MB
+            answer_max_capacity: 500,
+            // Changed documents are invalidated explicitly, cache for 10 minutes
+            answer_ttl_seconds: 600,
+            // 10k responses @ ~2KB each = ~20MB
+            llm_max_capacity: 10_000,
+            // Responses depend only on the prompt, cache for a day
+            llm_ttl_seconds: 86_400,
             // Statistics enabled by default
             enable_stats: true,
         }
@@ -65,10 +126,13 @@ impl Default for CacheConfig {
 /// Cache for document embeddings
 ///
 /// Caches embedding vectors to avoid recomputing them for the same text.
-/// Thread-safe and suitable for async contexts.
+/// Thread-safe and suitable for async contexts. With a persistent store,
+/// writes go through to disk and misses fall back to it.
 #[derive(Clone)]
 pub struct EmbeddingCache {
-    cache: Cache<u64, Vec<f32>>,
+    backend: Arc<dyn CacheBackend<Vec<f32>>>,
+    store: Option<Arc<EmbeddingStore>>,
+    warm_capacity: usize,
     stats: Arc<CacheStats>,
 }
 
@@ -80,17 +144,40 @@ impl EmbeddingCache {
 
     /// Create a new embedding cache with custom configuration
     pub fn with_config(config: &CacheConfig) -> Self {
-        let cache = Cache::builder()
-            .max_capacity(config.embedding_max_capacity)
-            .time_to_live(Duration::from_secs(config.embedding_ttl_seconds))
-            .build();
-
         Self {
-            cache,
+            backend: build_backend(
+                config,
+                "embedding",
+                config.embedding_max_capacity,
+                config.embedding_ttl_seconds,
+            ),
+            store: open_embedding_store(config),
+            warm_capacity: config.embedding_max_capacity as usize,
             stats: Arc::new(CacheStats::new("embedding")),
         }
     }
 
+    /// Load the most recently used persisted embeddings into memory
+    ///
+    /// Returns the number of embeddings loaded (0 without a store).
+    pub async fn warm_load(&self) -> usize {
+        let Some(store) = &self.store else {
+            return 0;
+        };
+        let entries = store.recent(self.warm_capacity);
+        let loaded = entries.len();
+        for (key, embedding) in entries {
+            self.backend.insert(&key, embedding, &[]).await;
+        }
+        tracing::info!("Loaded {} of {} persisted embeddings", loaded, store.len());
+        loaded
+    }
+
+    /// Persistent store behind the cache, if configured
+    pub fn store(&self) -> Option<&Arc<EmbeddingStore>> {
+        self.store.as_ref()
+    }
+
     /// Get an embedding from cache
     ///
     /// # Arguments
@@ -99,8 +186,11 @@ impl EmbeddingCache {
     /// # Returns
     /// The cached embedding vector, or None if not in cache
     pub async fn get(&self, text: &str) -> Option<Vec<f32>> {
-        let key = hash_text(text);
-        let result = self.cache.get(&key).await;
+        let key = text_key(text);
+        let mut result = self.backend.get(&key).await;
+        if result.is_none() {
+            result = self.load_persisted(&key).await;
+        }
 
         if result.is_some() {
             self.stats.record_hit();
@@ -117,8 +207,13 @@ impl EmbeddingCache {
     /// * `text` - The text that was embedded
     /// * `embedding` - The embedding vector
     pub async fn put(&self, text: &str, embedding: Vec<f32>) {
-        let key = hash_text(text);
-        self.cache.insert(key, embedding).await;
+        let key = text_key(text);
+        if let Some(store) = &self.store {
+            if let Err(e) = store.put(&key, &embedding) {
+                tracing::warn!("Failed to persist embedding: {}", e);
+            }
+        }
+        self.backend.insert(&key, embedding, &[]).await;
         self.stats.record_write();
     }
 
@@ -127,8 +222,7 @@ impl EmbeddingCache {
     /// # Arguments
     /// * `text` - The text to check
     pub async fn contains(&self, text: &str) -> bool {
-        let key = hash_text(text);
-        self.cache.contains_key(&key)
+        self.backend.contains(&text_key(text)).await
     }
 
     /// Invalidate a specific embedding
@@ -136,16 +230,24 @@ impl EmbeddingCache {
     /// # Arguments
     /// * `text` - The text whose embedding to invalidate
     pub async fn invalidate(&self, text: &str) {
-        let key = hash_text(text);
-        self.cache.invalidate(&key).await;
+        let key = text_key(text);
+        if let Some(store) = &self.store {
+            if let Err(e) = store.remove(&key) {
+                tracing::warn!("Failed to remove persisted embedding: {}", e);
+            }
+        }
+        self.backend.invalidate(&key).await;
         self.stats.record_invalidation();
     }
 
-    /// Clear all cached embeddings
+    /// Clear all cached embeddings, including persisted ones
     pub async fn clear(&self) {
-        self.cache.invalidate_all();
-        // Wait for all pending invalidations to complete
-        self.cache.run_pending_tasks().await;
+        if let Some(store) = &self.store {
+            if let Err(e) = store.clear() {
+                tracing::warn!("Failed to clear persisted embeddings: {}", e);
+            }
+        }
+        self.backend.clear().await;
         self.stats.reset();
     }
 
@@ -156,12 +258,30 @@ impl EmbeddingCache {
 
     /// Get current cache size
     pub fn entry_count(&self) -> u64 {
-        self.cache.entry_count()
+        self.backend.entry_count()
     }
 
     /// Get weighted cache size (memory usage estimate)
     pub fn weighted_size(&self) -> u64 {
-        self.cache.weighted_size()
+        self.backend.weighted_size()
+    }
+}
+
+impl EmbeddingCache {
+    /// Read an embedding from the persistent store and keep it in memory
+    async fn load_persisted(&self, key: &str) -> Option<Vec<f32>> {
+        let store = self.store.as_ref()?;
+        match store.get(key) {
+            Ok(Some(embedding)) => {
+                self.backend.insert(key, embedding.clone(), &[]).await;
+                Some(embedding)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!("Failed to read persisted embedding: {}", e);
+                None
+            }
+        }
     }
 }
 
@@ -171,6 +291,87 @@ impl Default for EmbeddingCache {
     }
 }
 
+// ============================================================================
+// Cached Embedding Client
+// ============================================================================
+
+/// Embedding client that serves repeated texts from an [`EmbeddingCache`]
+pub struct CachedEmbeddingClient {
+    inner: Arc<dyn EmbeddingClient>,
+    cache: EmbeddingCache,
+    namespace: Option<String>,
+}
+
+impl CachedEmbeddingClient {
+    /// Wrap `inner` with `cache`
+    pub fn new(inner: Arc<dyn EmbeddingClient>, cache: EmbeddingCache) -> Self {
+        Self {
+            inner,
+            cache,
+            namespace: None,
+        }
+    }
+
+    /// Keep this client's entries apart from other models sharing the cache
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Cache key text of `text`
+    fn cache_text<'a>(&self, text: &'a str) -> std::borrow::Cow<'a, str> {
+        match &self.namespace {
+            Some(namespace) => format!("{namespace}\u{0}{text}").into(),
+            None => text.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingClient for CachedEmbeddingClient {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let key = self.cache_text(text);
+        if let Some(embedding) = self.cache.get(&key).await {
+            return Ok(embedding);
+        }
+        let embedding = self.inner.embed(text).await?;
+        self.cache.put(&key, embedding.clone()).await;
+        Ok(embedding)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        let mut missing = Vec::new();
+        for (i, text) in texts.iter().enumerate() {
+            let cached = self.cache.get(&self.cache_text(text)).await;
+            if cached.is_none() {
+                missing.push(i);
+            }
+            embeddings.push(cached);
+        }
+
+        if !missing.is_empty() {
+            let batch: Vec<String> = missing.iter().map(|&i| texts[i].clone()).collect();
+            let computed = self.inner.embed_batch(&batch).await?;
+            for (i, embedding) in missing.into_iter().zip(computed) {
+                self.cache
+                    .put(&self.cache_text(&texts[i]), embedding.clone())
+                    .await;
+                embeddings[i] = Some(embedding);
+            }
+        }
+
+        embeddings
+            .into_iter()
+            .map(|e| e.ok_or_else(|| OtlError::SearchError("Missing embedding in batch".into())))
+            .collect()
+    }
+
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+}
+
 // ============================================================================
 // Query Cache
 // ============================================================================
@@ -181,7 +382,7 @@ impl Default for EmbeddingCache {
 /// Thread-safe and suitable for async contexts.
 #[derive(Clone)]
 pub struct QueryCache {
-    cache: Cache<QueryKey, QueryCacheValue>,
+    backend: Arc<dyn CacheBackend<QueryCacheValue>>,
     stats: Arc<CacheStats>,
 }
 
@@ -207,6 +408,16 @@ impl QueryKey {
     }
 }
 
+impl std::fmt::Display for QueryKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:016x}:{}:{}",
+            self.query_hash, self.top_k, self.min_score_scaled
+        )
+    }
+}
+
 /// Cached query result value
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct QueryCacheValue {
@@ -224,13 +435,13 @@ impl QueryCache {
 
     /// Create a new query cache with custom configuration
     pub fn with_config(config: &CacheConfig) -> Self {
-        let cache = Cache::builder()
-            .max_capacity(config.query_max_capacity)
-            .time_to_live(Duration::from_secs(config.query_ttl_seconds))
-            .build();
-
         Self {
-            cache,
+            backend: build_backend(
+                config,
+                "query",
+                config.query_max_capacity,
+                config.query_ttl_seconds,
+            ),
             stats: Arc::new(CacheStats::new("query")),
         }
     }
@@ -250,8 +461,8 @@ impl QueryCache {
         top_k: usize,
         min_score: f32,
     ) -> Option<Vec<SearchResult>> {
-        let key = QueryKey::new(query, top_k, min_score);
-        let result = self.cache.get(&key).await;
+        let key = QueryKey::new(query, top_k, min_score).to_string();
+        let result = self.backend.get(&key).await;
 
         if result.is_some() {
             self.stats.record_hit();
@@ -270,12 +481,12 @@ impl QueryCache {
     /// * `min_score` - Minimum score threshold
     /// * `results` - The search results to cache
     pub async fn put(&self, query: &str, top_k: usize, min_score: f32, results: Vec<SearchResult>) {
-        let key = QueryKey::new(query, top_k, min_score);
+        let key = QueryKey::new(query, top_k, min_score).to_string();
         let value = QueryCacheValue {
             results,
             cached_at: std::time::SystemTime::now(),
         };
-        self.cache.insert(key, value).await;
+        self.backend.insert(&key, value, &[]).await;
         self.stats.record_write();
     }
 
@@ -286,8 +497,8 @@ impl QueryCache {
     /// * `top_k` - Number of results requested
     /// * `min_score` - Minimum score threshold
     pub async fn contains(&self, query: &str, top_k: usize, min_score: f32) -> bool {
-        let key = QueryKey::new(query, top_k, min_score);
-        self.cache.contains_key(&key)
+        let key = QueryKey::new(query, top_k, min_score).to_string();
+        self.backend.contains(&key).await
     }
 
     /// Invalidate a specific query
@@ -297,16 +508,14 @@ impl QueryCache {
     /// * `top_k` - Number of results requested
     /// * `min_score` - Minimum score threshold
     pub async fn invalidate(&self, query: &str, top_k: usize, min_score: f32) {
-        let key = QueryKey::new(query, top_k, min_score);
-        self.cache.invalidate(&key).await;
+        let key = QueryKey::new(query, top_k, min_score).to_string();
+        self.backend.invalidate(&key).await;
         self.stats.record_invalidation();
     }
 
     /// Clear all cached query results
     pub async fn clear(&self) {
-        self.cache.invalidate_all();
-        // Wait for all pending invalidations to complete
-        self.cache.run_pending_tasks().await;
+        self.backend.clear().await;
         self.stats.reset();
     }
 
@@ -317,12 +526,12 @@ impl QueryCache {
 
     /// Get current cache size
     pub fn entry_count(&self) -> u64 {
-        self.cache.entry_count()
+        self.backend.entry_count()
     }
 
     /// Get weighted cache size (memory usage estimate)
     pub fn weighted_size(&self) -> u64 {
-        self.cache.weighted_size()
+        self.backend.weighted_size()
     }
 }
 
@@ -332,6 +541,146 @@ impl Default for QueryCache {
     }
 }
 
+// ============================================================================
+// Answer Cache
+// ============================================================================
+
+/// Cache for generated answers
+///
+/// Retrieval still runs for every query; only generation is skipped. An
+/// answer is reused when the normalized question and the retrieved contexts
+/// match, so newly indexed or re-ranked documents miss the cache by
+/// themselves. Entries built from a document that changed are invalidated
+/// with [`AnswerCache::invalidate_document`].
+#[derive(Clone)]
+pub struct AnswerCache {
+    backend: Arc<dyn CacheBackend<CachedAnswer>>,
+    stats: Arc<CacheStats>,
+}
+
+/// Key for answer cache entries
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AnswerKey {
+    /// Hash of the normalized question and answer variant
+    question_hash: u64,
+    /// Hash of the retrieved contexts, in prompt order
+    context_fingerprint: u64,
+}
+
+impl AnswerKey {
+    /// Key for `question` answered from `contexts`
+    ///
+    /// `variant` separates answers that differ in form for the same question
+    /// and contexts (answer mode, language).
+    pub fn new(question: &str, variant: &str, contexts: &[SearchResult]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        normalize_question(question).hash(&mut hasher);
+        variant.hash(&mut hasher);
+        Self {
+            question_hash: hasher.finish(),
+            context_fingerprint: context_fingerprint(contexts),
+        }
+    }
+}
+
+impl std::fmt::Display for AnswerKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:016x}:{:016x}",
+            self.question_hash, self.context_fingerprint
+        )
+    }
+}
+
+/// A generated answer with the documents it was built from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedAnswer {
+    /// Answer text
+    pub answer: String,
+    /// Citations used in the answer
+    pub citations: Vec<Citation>,
+    /// Source passages (extractive mode)
+    pub passages: Vec<ExtractedPassage>,
+    /// Machine-readable answer, if one was generated
+    pub structured_answer: Option<StructuredAnswer>,
+    /// Model that generated the answer, if known
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Documents of the contexts the answer was generated from
+    pub document_ids: Vec<Uuid>,
+    /// Cache timestamp (for debugging/monitoring)
+    pub cached_at: std::time::SystemTime,
+}
+
+impl AnswerCache {
+    /// Create a new answer cache with default configuration
+    pub fn new() -> Self {
+        Self::with_config(&CacheConfig::default())
+    }
+
+    /// Create a new answer cache with custom configuration
+    pub fn with_config(config: &CacheConfig) -> Self {
+        Self {
+            backend: build_backend(
+                config,
+                "answer",
+                config.answer_max_capacity,
+                config.answer_ttl_seconds,
+            ),
+            stats: Arc::new(CacheStats::new("answer")),
+        }
+    }
+
+    /// Get a cached answer
+    pub async fn get(&self, key: &AnswerKey) -> Option<CachedAnswer> {
+        let result = self.backend.get(&key.to_string()).await;
+
+        if result.is_some() {
+            self.stats.record_hit();
+        } else {
+            self.stats.record_miss();
+        }
+
+        result
+    }
+
+    /// Store an answer
+    pub async fn put(&self, key: AnswerKey, answer: CachedAnswer) {
+        let tags: Vec<String> = answer.document_ids.iter().map(Uuid::to_string).collect();
+        self.backend.insert(&key.to_string(), answer, &tags).await;
+        self.stats.record_write();
+    }
+
+    /// Invalidate every answer generated from a document
+    pub async fn invalidate_document(&self, document_id: Uuid) {
+        self.backend.invalidate_tag(&document_id.to_string()).await;
+        self.stats.record_invalidation();
+    }
+
+    /// Clear all cached answers
+    pub async fn clear(&self) {
+        self.backend.clear().await;
+        self.stats.reset();
+    }
+
+    /// Get cache statistics
+    pub fn stats(&self) -> Arc<CacheStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Get current cache size
+    pub fn entry_count(&self) -> u64 {
+        self.backend.entry_count()
+    }
+}
+
+impl Default for AnswerCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // ============================================================================
 // Cache Statistics
 // ============================================================================
@@ -353,7 +702,7 @@ pub struct CacheStats {
 
 impl CacheStats {
     /// Create new cache statistics tracker
-    fn new(name: impl Into<String>) -> Self {
+    pub(crate) fn new(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             hits: AtomicU64::new(0),
@@ -364,17 +713,17 @@ impl CacheStats {
     }
 
     /// Record a cache hit
-    fn record_hit(&self) {
+    pub(crate) fn record_hit(&self) {
         self.hits.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Record a cache miss
-    fn record_miss(&self) {
+    pub(crate) fn record_miss(&self) {
         self.misses.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Record a cache write
-    fn record_write(&self) {
+    pub(crate) fn record_write(&self) {
         self.writes.fetch_add(1, Ordering::Relaxed);
     }
 
@@ -384,7 +733,7 @@ impl CacheStats {
     }
 
     /// Reset all statistics
-    fn reset(&self) {
+    pub(crate) fn reset(&self) {
         self.hits.store(0, Ordering::Relaxed);
         self.misses.store(0, Ordering::Relaxed);
         self.writes.store(0, Ordering::Relaxed);
@@ -483,6 +832,12 @@ pub struct RagCacheManager {
     pub embedding: EmbeddingCache,
     /// Query results cache
     pub query: QueryCache,
+    /// Generated answers cache
+    pub answer: AnswerCache,
+    /// LLM responses to non-RAG prompts
+    pub llm: LlmResponseCache,
+    /// Configuration the caches were built with
+    config: CacheConfig,
 }
 
 impl RagCacheManager {
@@ -496,18 +851,38 @@ impl RagCacheManager {
         Self {
             embedding: EmbeddingCache::with_config(config),
             query: QueryCache::with_config(config),
+            answer: AnswerCache::with_config(config),
+            llm: LlmResponseCache::with_config(config),
+            config: config.clone(),
         }
     }
 
+    /// Configuration the caches were built with
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
     /// Clear all caches
     pub async fn clear_all(&self) {
         self.embedding.clear().await;
         self.query.clear().await;
+        self.answer.clear().await;
+        self.llm.clear().await;
+    }
+
+    /// Drop cached answers generated from a changed or deleted document
+    pub async fn invalidate_document(&self, document_id: Uuid) {
+        self.answer.invalidate_document(document_id).await;
     }
 
     /// Get combined statistics for all caches
     pub fn all_stats(&self) -> Vec<CacheStatsReport> {
-        vec![self.embedding.stats().report(), self.query.stats().report()]
+        vec![
+            self.embedding.stats().report(),
+            self.query.stats().report(),
+            self.answer.stats().report(),
+            self.llm.stats().report(),
+        ]
     }
 
     /// Warm up the embedding cache with common queries
@@ -571,6 +946,74 @@ fn hash_text(text: &str) -> u64 {
     hasher.finish()
 }
 
+/// Cache key of a text
+fn text_key(text: &str) -> String {
+    format!("{:016x}", hash_text(text))
+}
+
+/// Open the configured embedding store
+///
+/// A store that cannot be opened disables persistence instead of failing.
+fn open_embedding_store(config: &CacheConfig) -> Option<Arc<EmbeddingStore>> {
+    let path = config.embedding_store_path.as_ref()?;
+    match EmbeddingStore::open(path, config.embedding_store_max_entries) {
+        Ok(store) => Some(Arc::new(store)),
+        Err(e) => {
+            tracing::error!("{e}; embeddings will not be persisted");
+            None
+        }
+    }
+}
+
+/// Create the backend of one cache
+///
+/// An unusable Redis URL falls back to the in-memory backend so a cache
+/// misconfiguration never keeps the pipeline from starting.
+pub(crate) fn build_backend<V>(
+    config: &CacheConfig,
+    name: &str,
+    max_capacity: u64,
+    ttl_seconds: u64,
+) -> Arc<dyn CacheBackend<V>>
+where
+    V: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    let ttl = Duration::from_secs(ttl_seconds);
+    if let CacheBackendKind::Redis { url, key_prefix } = &config.backend {
+        match RedisBackend::new(url, format!("{key_prefix}:{name}"), ttl) {
+            Ok(backend) => return Arc::new(backend),
+            Err(e) => tracing::error!("{e}; using an in-memory {name} cache"),
+        }
+    }
+    Arc::new(MemoryBackend::new(max_capacity, ttl))
+}
+
+/// Normalize a question for answer cache keys
+///
+/// Case, whitespace runs and trailing punctuation do not change the answer.
+fn normalize_question(question: &str) -> String {
+    question
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['?', '？', '.', '!'])
+        .trim_end()
+        .to_lowercase()
+}
+
+/// Hash the identity and content of contexts, in order
+fn context_fingerprint(contexts: &[SearchResult]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for context in contexts {
+        context.source.document_id.hash(&mut hasher);
+        context.source.page.hash(&mut hasher);
+        context.source.section.hash(&mut hasher);
+        context.source.offset.hash(&mut hasher);
+        context.content.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -614,6 +1057,60 @@ mod tests {
         assert_eq!(cache.stats().invalidations(), 1);
     }
 
+    #[tokio::test]
+    async fn test_embedding_cache_survives_restart_with_store() {
+        let path = std::env::temp_dir().join(format!("otl-embedding-cache-{}", Uuid::new_v4()));
+        let config = CacheConfig {
+            embedding_store_path: Some(path.clone()),
+            ..CacheConfig::default()
+        };
+
+        let cache = EmbeddingCache::with_config(&config);
+        cache.put("연차 규정", vec![0.1, 0.2]).await;
+        drop(cache);
+
+        // A new process starts with an empty memory cache
+        let cache = EmbeddingCache::with_config(&config);
+        assert_eq!(cache.warm_load().await, 1);
+        assert_eq!(cache.get("연차 규정").await, Some(vec![0.1, 0.2]));
+        drop(cache);
+        std::fs::remove_dir_all(path).ok();
+    }
+
+    /// Embeds every text as a single value
+    struct ConstantEmbedding(f32);
+
+    #[async_trait]
+    impl EmbeddingClient for ConstantEmbedding {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![self.0])
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| vec![self.0]).collect())
+        }
+
+        fn dimension(&self) -> usize {
+            1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_embedding_namespaces_do_not_mix() {
+        let cache = EmbeddingCache::new();
+        let old = CachedEmbeddingClient::new(Arc::new(ConstantEmbedding(1.0)), cache.clone());
+        let new = CachedEmbeddingClient::new(Arc::new(ConstantEmbedding(2.0)), cache.clone())
+            .with_namespace("bge-m3");
+
+        assert_eq!(old.embed("연차").await.unwrap(), vec![1.0]);
+        assert_eq!(new.embed("연차").await.unwrap(), vec![2.0]);
+        assert_eq!(
+            new.embed_batch(&["연차".to_string()]).await.unwrap(),
+            vec![vec![2.0]]
+        );
+        assert_eq!(old.embed("연차").await.unwrap(), vec![1.0]);
+    }
+
     #[tokio::test]
     async fn test_query_cache_basic() {
         let cache = QueryCache::new();
@@ -729,5 +1226,73 @@ mod tests {
         assert!(config.query_max_capacity > 0);
         assert!(config.embedding_ttl_seconds > 0);
         assert!(config.query_ttl_seconds > 0);
+        assert!(config.answer_ttl_seconds > 0);
+    }
+
+    fn context(document_id: Uuid, content: &str) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score: 1.0,
+            source: otl_core::SourceReference::new(document_id),
+            acl: otl_core::DocumentAcl::default(),
+            result_type: otl_core::SearchResultType::Vector,
+        }
+    }
+
+    fn cached(answer: &str, document_ids: Vec<Uuid>) -> CachedAnswer {
+        CachedAnswer {
+            answer: answer.to_string(),
+            citations: Vec::new(),
+            passages: Vec::new(),
+            structured_answer: None,
+            model: None,
+            document_ids,
+            cached_at: std::time::SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_answer_key_normalizes_question_and_fingerprints_contexts() {
+        let doc = Uuid::new_v4();
+        let contexts = vec![context(doc, "연차는 15일")];
+
+        let key = AnswerKey::new("연차는  며칠인가요?", "generative", &contexts);
+        assert_eq!(
+            key,
+            AnswerKey::new("연차는 며칠인가요", "generative", &contexts)
+        );
+        assert_ne!(
+            key,
+            AnswerKey::new("연차는 며칠인가요", "extractive", &contexts)
+        );
+        assert_ne!(
+            key,
+            AnswerKey::new(
+                "연차는 며칠인가요",
+                "generative",
+                &[context(doc, "연차는 20일")]
+            )
+        );
+        assert_eq!(
+            normalize_question("  What IS the Policy ? "),
+            "what is the policy"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_answer_cache_invalidates_by_document() {
+        let cache = AnswerCache::new();
+        let (doc_a, doc_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let key_a = AnswerKey::new("a", "", &[context(doc_a, "a")]);
+        let key_b = AnswerKey::new("b", "", &[context(doc_b, "b")]);
+
+        cache.put(key_a.clone(), cached("A", vec![doc_a])).await;
+        cache.put(key_b.clone(), cached("B", vec![doc_b])).await;
+        assert_eq!(cache.get(&key_a).await.unwrap().answer, "A");
+
+        cache.invalidate_document(doc_a).await;
+        assert!(cache.get(&key_a).await.is_none());
+        assert!(cache.get(&key_b).await.is_some());
+        assert_eq!(cache.stats().invalidations(), 1);
     }
 }
@@ -0,0 +1,338 @@
+//! Cache storage backends
+//!
+//! The typed caches in [`crate::cache`] keep their entries in a
+//! [`CacheBackend`]. The default backend is an in-process moka cache, which
+//! each API replica fills on its own; the Redis backend lets all replicas
+//! share embeddings, query results and answers.
+//!
+//! Author: hephaex@gmail.com
+
+use async_trait::async_trait;
+use moka::future::Cache;
+use otl_core::{OtlError, Result};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// Number of keys deleted per Redis command when clearing a namespace
+const REDIS_DELETE_BATCH: usize = 500;
+
+/// Key-value storage for one cache
+///
+/// Entries can carry tags (e.g. document IDs) so that every entry derived
+/// from the same source is invalidated together. Backend failures are
+/// logged and treated as misses; a cache never fails the caller.
+#[async_trait]
+pub trait CacheBackend<V>: Send + Sync {
+    /// Get a value
+    async fn get(&self, key: &str) -> Option<V>;
+
+    /// Store a value with its invalidation tags
+    async fn insert(&self, key: &str, value: V, tags: &[String]);
+
+    /// Check if a key is present
+    async fn contains(&self, key: &str) -> bool;
+
+    /// Remove a value
+    async fn invalidate(&self, key: &str);
+
+    /// Remove every value stored with `tag`
+    async fn invalidate_tag(&self, tag: &str);
+
+    /// Remove every value
+    async fn clear(&self);
+
+    /// Number of entries held by this process (0 for remote backends)
+    fn entry_count(&self) -> u64 {
+        0
+    }
+
+    /// Weighted size of the entries held by this process
+    fn weighted_size(&self) -> u64 {
+        0
+    }
+}
+
+// ============================================================================
+// In-memory Backend
+// ============================================================================
+
+#[derive(Clone)]
+struct MemoryEntry<V> {
+    value: V,
+    tags: Arc<[String]>,
+}
+
+/// Per-process LRU cache with TTL
+pub struct MemoryBackend<V> {
+    cache: Cache<String, MemoryEntry<V>>,
+}
+
+impl<V: Clone + Send + Sync + 'static> MemoryBackend<V> {
+    /// Create a cache holding at most `max_capacity` entries for `ttl`
+    pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+        let cache = Cache::builder()
+            .max_capacity(max_capacity)
+            .time_to_live(ttl)
+            .support_invalidation_closures()
+            .build();
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl<V: Clone + Send + Sync + 'static> CacheBackend<V> for MemoryBackend<V> {
+    async fn get(&self, key: &str) -> Option<V> {
+        self.cache.get(key).await.map(|entry| entry.value)
+    }
+
+    async fn insert(&self, key: &str, value: V, tags: &[String]) {
+        let entry = MemoryEntry {
+            value,
+            tags: tags.into(),
+        };
+        self.cache.insert(key.to_string(), entry).await;
+    }
+
+    async fn contains(&self, key: &str) -> bool {
+        self.cache.contains_key(key)
+    }
+
+    async fn invalidate(&self, key: &str) {
+        self.cache.invalidate(key).await;
+    }
+
+    async fn invalidate_tag(&self, tag: &str) {
+        let tag = tag.to_string();
+        let result = self
+            .cache
+            .invalidate_entries_if(move |_, entry| entry.tags.contains(&tag));
+        if let Err(e) = result {
+            tracing::warn!("Tag invalidation unavailable ({e}), clearing the cache");
+            self.cache.invalidate_all();
+        }
+        self.cache.run_pending_tasks().await;
+    }
+
+    async fn clear(&self) {
+        self.cache.invalidate_all();
+        // Wait for all pending invalidations to complete
+        self.cache.run_pending_tasks().await;
+    }
+
+    fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+
+    fn weighted_size(&self) -> u64 {
+        self.cache.weighted_size()
+    }
+}
+
+// ============================================================================
+// Redis Backend
+// ============================================================================
+
+/// Cache shared through Redis
+///
+/// Values are stored as JSON under `{namespace}:e:{key}` with the cache TTL.
+/// Each tag is a set `{namespace}:t:{tag}` of the keys stored with it. The
+/// connection is opened on first use and re-established automatically.
+pub struct RedisBackend<V> {
+    client: redis::Client,
+    connection: OnceCell<ConnectionManager>,
+    namespace: String,
+    ttl: Duration,
+    _value: PhantomData<fn() -> V>,
+}
+
+impl<V> RedisBackend<V> {
+    /// Create a backend for the Redis server at `url`
+    ///
+    /// Only the URL is checked here; the server is contacted on first use.
+    pub fn new(url: &str, namespace: impl Into<String>, ttl: Duration) -> Result<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| OtlError::ConfigError(format!("Invalid Redis URL: {e}")))?;
+        Ok(Self {
+            client,
+            connection: OnceCell::new(),
+            namespace: namespace.into(),
+            ttl,
+            _value: PhantomData,
+        })
+    }
+
+    async fn connection(&self) -> Option<ConnectionManager> {
+        let result = self
+            .connection
+            .get_or_try_init(|| self.client.get_connection_manager())
+            .await;
+        match result {
+            Ok(connection) => Some(connection.clone()),
+            Err(e) => {
+                tracing::warn!("Redis cache unavailable: {e}");
+                None
+            }
+        }
+    }
+
+    fn entry_key(&self, key: &str) -> String {
+        format!("{}:e:{}", self.namespace, key)
+    }
+
+    fn tag_key(&self, tag: &str) -> String {
+        format!("{}:t:{}", self.namespace, tag)
+    }
+
+    fn ttl_seconds(&self) -> u64 {
+        self.ttl.as_secs().max(1)
+    }
+}
+
+#[async_trait]
+impl<V> CacheBackend<V> for RedisBackend<V>
+where
+    V: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get(&self, key: &str) -> Option<V> {
+        let mut conn = self.connection().await?;
+        let bytes: Option<Vec<u8>> = conn
+            .get(self.entry_key(key))
+            .await
+            .map_err(|e| tracing::warn!("Redis cache read failed: {e}"))
+            .ok()?;
+        serde_json::from_slice(&bytes?)
+            .map_err(|e| tracing::warn!("Dropping undecodable cache entry {key}: {e}"))
+            .ok()
+    }
+
+    async fn insert(&self, key: &str, value: V, tags: &[String]) {
+        let Some(mut conn) = self.connection().await else {
+            return;
+        };
+        let bytes = match serde_json::to_vec(&value) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("Failed to encode cache entry {key}: {e}");
+                return;
+            }
+        };
+
+        let entry_key = self.entry_key(key);
+        let ttl = self.ttl_seconds();
+        let mut pipe = redis::pipe();
+        pipe.set_ex(&entry_key, bytes, ttl).ignore();
+        for tag in tags {
+            let tag_key = self.tag_key(tag);
+            pipe.sadd(&tag_key, &entry_key)
+                .ignore()
+                .expire(&tag_key, ttl as i64)
+                .ignore();
+        }
+        if let Err(e) = pipe.query_async::<()>(&mut conn).await {
+            tracing::warn!("Redis cache write failed: {e}");
+        }
+    }
+
+    async fn contains(&self, key: &str) -> bool {
+        let Some(mut conn) = self.connection().await else {
+            return false;
+        };
+        conn.exists(self.entry_key(key)).await.unwrap_or(false)
+    }
+
+    async fn invalidate(&self, key: &str) {
+        let Some(mut conn) = self.connection().await else {
+            return;
+        };
+        if let Err(e) = conn.del::<_, ()>(self.entry_key(key)).await {
+            tracing::warn!("Redis cache invalidation failed: {e}");
+        }
+    }
+
+    async fn invalidate_tag(&self, tag: &str) {
+        let Some(mut conn) = self.connection().await else {
+            return;
+        };
+        let tag_key = self.tag_key(tag);
+        let result: redis::RedisResult<()> = async {
+            let mut keys: Vec<String> = conn.smembers(&tag_key).await?;
+            keys.push(tag_key.clone());
+            for batch in keys.chunks(REDIS_DELETE_BATCH) {
+                conn.del::<_, ()>(batch).await?;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Redis cache invalidation of tag {tag} failed: {e}");
+        }
+    }
+
+    async fn clear(&self) {
+        let Some(mut conn) = self.connection().await else {
+            return;
+        };
+        let pattern = format!("{}:*", self.namespace);
+        let result: redis::RedisResult<()> = async {
+            let keys: Vec<String> = {
+                let mut iter = conn.scan_match::<_, String>(&pattern).await?;
+                let mut keys = Vec::new();
+                while let Some(key) = iter.next_item().await {
+                    keys.push(key);
+                }
+                keys
+            };
+            for batch in keys.chunks(REDIS_DELETE_BATCH) {
+                conn.del::<_, ()>(batch).await?;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Redis cache clear failed: {e}");
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_backend_invalidates_by_tag() {
+        let backend = MemoryBackend::new(100, Duration::from_secs(60));
+        backend.insert("a", 1, &["doc-1".to_string()]).await;
+        backend
+            .insert("b", 2, &["doc-1".to_string(), "doc-2".to_string()])
+            .await;
+        backend.insert("c", 3, &[]).await;
+
+        backend.invalidate_tag("doc-1").await;
+        assert_eq!(backend.get("a").await, None);
+        assert_eq!(backend.get("b").await, None);
+        assert_eq!(backend.get("c").await, Some(3));
+    }
+
+    #[test]
+    fn test_redis_backend_keys() {
+        let backend: RedisBackend<Vec<f32>> = RedisBackend::new(
+            "redis://127.0.0.1/",
+            "otl:embedding",
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        assert_eq!(backend.entry_key("ab12"), "otl:embedding:e:ab12");
+        assert_eq!(backend.tag_key("doc"), "otl:embedding:t:doc");
+        assert!(RedisBackend::<Vec<f32>>::new("not a url", "x", Duration::ZERO).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Citation verification
+//!
+//! The LLM cites contexts as `[출처: N]` / `[Source: N]`, but it can cite a
+//! number that never appeared in the prompt (a context dropped by the length
+//! budget or emptied by compression, or a hallucinated one). Markers are
+//! checked against the contexts actually shown, renumbered by first
+//! appearance so the answer and its citation list agree, and dangling
+//! markers are removed.
+//!
+//! Author: hephaex@gmail.com
+
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// An answer whose citation markers were checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckedCitations {
+    /// Answer with valid markers renumbered and dangling markers removed
+    pub answer: String,
+
+    /// Citations as (new number, zero-based position among the prompt
+    /// contexts), in order of first appearance
+    pub cited: Vec<(u32, usize)>,
+
+    /// Cited numbers that match no prompt context, in order of appearance
+    pub dangling: Vec<usize>,
+}
+
+fn marker_regex() -> &'static Regex {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    MARKER.get_or_init(|| {
+        Regex::new(r"(?i)(\s*)\[(출처|source):\s*(\d+)\]").expect("citation marker regex is valid")
+    })
+}
+
+/// Cited numbers that match none of the `available` prompt contexts
+pub fn dangling_citations(answer: &str, available: usize) -> Vec<usize> {
+    let mut dangling: Vec<usize> = Vec::new();
+    for caps in marker_regex().captures_iter(answer) {
+        let number = caps[3].parse::<usize>().unwrap_or(0);
+        if (number == 0 || number > available) && !dangling.contains(&number) {
+            dangling.push(number);
+        }
+    }
+    dangling
+}
+
+/// `answer` without citation markers
+pub fn strip_markers(answer: &str) -> String {
+    marker_regex().replace_all(answer, "").into_owned()
+}
+
+/// Check the citation markers of `answer` against `available` prompt
+/// contexts numbered from 1
+pub fn verify(answer: &str, available: usize) -> CheckedCitations {
+    let mut renumbered: HashMap<usize, u32> = HashMap::new();
+    let mut cited = Vec::new();
+
+    let rewritten = marker_regex().replace_all(answer, |caps: &Captures| {
+        let number = caps[3].parse::<usize>().unwrap_or(0);
+        if number == 0 || number > available {
+            return String::new();
+        }
+        let next = renumbered.len() as u32 + 1;
+        let index = *renumbered.entry(number).or_insert_with(|| {
+            cited.push((next, number - 1));
+            next
+        });
+        format!("{}[{}: {}]", &caps[1], &caps[2], index)
+    });
+
+    CheckedCitations {
+        answer: rewritten.into_owned(),
+        cited,
+        dangling: dangling_citations(answer, available),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_citations_are_renumbered_by_first_appearance() {
+        let checked = verify(
+            "연차는 15일입니다 [출처: 3]. 병가는 60일입니다 [출처: 1]. 다시 [출처: 3].",
+            3,
+        );
+        assert_eq!(
+            checked.answer,
+            "연차는 15일입니다 [출처: 1]. 병가는 60일입니다 [출처: 2]. 다시 [출처: 1]."
+        );
+        assert_eq!(checked.cited, vec![(1, 2), (2, 0)]);
+        assert!(checked.dangling.is_empty());
+    }
+
+    #[test]
+    fn test_dangling_citations_are_removed() {
+        let checked = verify(
+            "Annual leave is 15 days [Source: 7]. Sick leave is 60 days [source: 2].",
+            5,
+        );
+        assert_eq!(
+            checked.answer,
+            "Annual leave is 15 days. Sick leave is 60 days [source: 1]."
+        );
+        assert_eq!(checked.cited, vec![(1, 1)]);
+        assert_eq!(checked.dangling, vec![7]);
+        assert_eq!(
+            dangling_citations("[출처: 0] [출처: 6] [출처: 6]", 5),
+            vec![0, 6]
+        );
+    }
+}
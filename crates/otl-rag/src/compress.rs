@@ -0,0 +1,385 @@
+//! Context compression
+//!
+//! Shrinks the retrieved context before it goes into the prompt, in the
+//! spirit of LLMLingua. Every sentence of the reranked chunks is scored for
+//! salience (keyword coverage, similarity to the question, rank of its
+//! chunk); near-duplicate sentences across chunks are clustered and only the
+//! most salient member is kept; the remaining sentences are kept best-first
+//! until the character budget is met. Low-ranked chunks that survive can
+//! additionally be summarized by the LLM.
+//!
+//! Chunks are never removed, only emptied, so the `[N]` numbering of the
+//! prompt still matches the citations.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::extractive::{cosine_similarity, lexical_score, split_sentences};
+use crate::language::PromptTemplate;
+use otl_core::{LlmClient, SearchResult};
+use otl_vector::embedding::EmbeddingClient;
+use std::collections::HashSet;
+
+/// Cosine similarity above which two sentences count as duplicates
+pub const REDUNDANCY_THRESHOLD: f32 = 0.9;
+
+/// Character-bigram Jaccard similarity above which two sentences count as
+/// duplicates when no embeddings are available
+pub const LEXICAL_REDUNDANCY_THRESHOLD: f32 = 0.7;
+
+/// Chunks from this rank on (0-based) may be summarized by the LLM
+pub const SUMMARIZE_FROM_RANK: usize = 2;
+
+/// Weight of the chunk rank prior in the salience score
+const RANK_WEIGHT: f32 = 0.2;
+
+/// Salience bonus for the first sentence of a chunk (often a heading or topic sentence)
+const LEAD_BONUS: f32 = 0.05;
+
+/// Weight of keyword coverage when embedding similarity is also available
+const LEXICAL_WEIGHT: f32 = 0.5;
+
+/// What the compression stage did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompressionReport {
+    /// Context length before compression (bytes)
+    pub original_length: usize,
+    /// Context length after compression (bytes)
+    pub compressed_length: usize,
+    /// Sentences dropped as near-duplicates of a kept sentence
+    pub redundant_sentences: usize,
+    /// Sentences dropped to meet the budget
+    pub dropped_sentences: usize,
+    /// Chunks replaced by an LLM summary
+    pub summarized_chunks: usize,
+}
+
+impl CompressionReport {
+    /// Compressed length as a fraction of the original
+    pub fn ratio(&self) -> f32 {
+        if self.original_length == 0 {
+            1.0
+        } else {
+            self.compressed_length as f32 / self.original_length as f32
+        }
+    }
+}
+
+/// Context budget: `ratio` of the retrieved context, capped at the prompt limit
+pub fn context_budget(total_length: usize, ratio: f32, max_context_length: usize) -> usize {
+    ((total_length as f32 * ratio.clamp(0.0, 1.0)).ceil() as usize).min(max_context_length)
+}
+
+/// Sentence of a chunk with its salience
+#[derive(Debug, Clone)]
+struct Sentence {
+    chunk: usize,
+    start: usize,
+    end: usize,
+    salience: f32,
+}
+
+/// Compress `results` to at most `budget` bytes of content
+///
+/// Contexts already within budget are returned unchanged. Sentences are
+/// reassembled in their original order within each chunk.
+pub async fn compress_context(
+    question: &str,
+    keywords: &[String],
+    results: &[SearchResult],
+    budget: usize,
+    embedder: Option<&dyn EmbeddingClient>,
+) -> (Vec<SearchResult>, CompressionReport) {
+    let original_length: usize = results.iter().map(|r| r.content.len()).sum();
+    if original_length <= budget {
+        let report = CompressionReport {
+            original_length,
+            compressed_length: original_length,
+            ..Default::default()
+        };
+        return (results.to_vec(), report);
+    }
+
+    let mut sentences: Vec<Sentence> = Vec::new();
+    for (chunk, result) in results.iter().enumerate() {
+        for (position, (start, end)) in split_sentences(&result.content).into_iter().enumerate() {
+            sentences.push(Sentence {
+                chunk,
+                start,
+                end,
+                salience: RANK_WEIGHT / (1.0 + chunk as f32)
+                    + if position == 0 { LEAD_BONUS } else { 0.0 },
+            });
+        }
+    }
+    let texts: Vec<String> = sentences
+        .iter()
+        .map(|s| results[s.chunk].content[s.start..s.end].to_string())
+        .collect();
+
+    let embeddings = match embedder {
+        Some(embedder) if !texts.is_empty() => embed(question, &texts, embedder).await,
+        _ => None,
+    };
+    for (i, sentence) in sentences.iter_mut().enumerate() {
+        let lexical = lexical_score(&texts[i], keywords);
+        sentence.salience += match &embeddings {
+            Some((query, vectors)) => {
+                LEXICAL_WEIGHT * lexical
+                    + (1.0 - LEXICAL_WEIGHT) * cosine_similarity(query, &vectors[i]).clamp(0.0, 1.0)
+            }
+            None => lexical,
+        };
+    }
+
+    let bigrams: Vec<HashSet<(char, char)>> = texts.iter().map(|t| char_bigrams(t)).collect();
+    let similar = |a: usize, b: usize| match &embeddings {
+        Some((_, vectors)) => cosine_similarity(&vectors[a], &vectors[b]) >= REDUNDANCY_THRESHOLD,
+        None => jaccard(&bigrams[a], &bigrams[b]) >= LEXICAL_REDUNDANCY_THRESHOLD,
+    };
+
+    let mut order: Vec<usize> = (0..sentences.len()).collect();
+    order.sort_by(|&a, &b| sentences[b].salience.total_cmp(&sentences[a].salience));
+
+    let mut report = CompressionReport {
+        original_length,
+        ..Default::default()
+    };
+    let mut kept: Vec<usize> = Vec::new();
+    let mut used = 0;
+    for i in order {
+        if kept.iter().any(|&k| similar(i, k)) {
+            report.redundant_sentences += 1;
+            continue;
+        }
+        let length = sentences[i].end - sentences[i].start;
+        // The most salient sentence is kept even when it alone exceeds the budget
+        if used + length > budget && !kept.is_empty() {
+            report.dropped_sentences += 1;
+            continue;
+        }
+        used += length;
+        kept.push(i);
+    }
+
+    kept.sort_by_key(|&i| (sentences[i].chunk, sentences[i].start));
+    let mut compressed: Vec<SearchResult> = results
+        .iter()
+        .map(|r| SearchResult {
+            content: String::new(),
+            ..r.clone()
+        })
+        .collect();
+    for i in kept {
+        let content = &mut compressed[sentences[i].chunk].content;
+        if !content.is_empty() {
+            content.push(' ');
+        }
+        content.push_str(&texts[i]);
+    }
+
+    report.compressed_length = compressed.iter().map(|r| r.content.len()).sum();
+    (compressed, report)
+}
+
+/// Replace chunks from `from_rank` on by an LLM summary
+///
+/// Summaries that fail or are not shorter than the chunk are discarded.
+/// Returns the number of chunks summarized.
+pub async fn summarize_low_rank(
+    question: &str,
+    results: &mut [SearchResult],
+    from_rank: usize,
+    llm: &dyn LlmClient,
+    template: &PromptTemplate,
+) -> usize {
+    let targets: Vec<usize> = (from_rank..results.len())
+        .filter(|&i| !results[i].content.is_empty())
+        .collect();
+    let summaries = futures::future::join_all(targets.iter().map(|&i| {
+        let prompt = template.summarize_prompt(question, &results[i].content);
+        async move { llm.generate(&prompt).await }
+    }))
+    .await;
+
+    let mut summarized = 0;
+    for (i, summary) in targets.into_iter().zip(summaries) {
+        match summary {
+            Ok(summary) if !summary.trim().is_empty() => {
+                let summary = summary.trim();
+                if summary.len() < results[i].content.len() {
+                    results[i].content = summary.to_string();
+                    summarized += 1;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Chunk summarization failed: {}", e),
+        }
+    }
+    summarized
+}
+
+/// Question embedding and one embedding per sentence
+async fn embed(
+    question: &str,
+    sentences: &[String],
+    embedder: &dyn EmbeddingClient,
+) -> Option<(Vec<f32>, Vec<Vec<f32>>)> {
+    let mut texts = Vec::with_capacity(sentences.len() + 1);
+    texts.push(question.to_string());
+    texts.extend_from_slice(sentences);
+
+    match embedder.embed_batch(&texts).await {
+        Ok(mut embeddings) if embeddings.len() == texts.len() => {
+            let query = embeddings.remove(0);
+            Some((query, embeddings))
+        }
+        Ok(_) => {
+            tracing::warn!("Embedding batch size mismatch, compressing lexically");
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Sentence embedding failed, compressing lexically: {}", e);
+            None
+        }
+    }
+}
+
+pub(crate) fn char_bigrams(text: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_ascii_punctuation())
+        .flat_map(char::to_lowercase)
+        .collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+pub(crate) fn jaccard(a: &HashSet<(char, char)>, b: &HashSet<(char, char)>) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let intersection = a.intersection(b).count();
+    intersection as f32 / (a.len() + b.len() - intersection) as f32
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{DocumentAcl, Language, SearchResultType, SourceReference};
+    use uuid::Uuid;
+
+    fn result(content: &str) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score: 0.8,
+            source: SourceReference::new(Uuid::new_v4()),
+            acl: DocumentAcl::default(),
+            result_type: SearchResultType::Vector,
+        }
+    }
+
+    fn keywords(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    /// Answers every prompt with a fixed summary
+    struct SummaryLlm;
+
+    #[async_trait::async_trait]
+    impl LlmClient for SummaryLlm {
+        async fn generate(&self, prompt: &str) -> otl_core::Result<String> {
+            assert!(prompt.contains("Excerpt:"));
+            Ok("Sick leave needs a certificate.".to_string())
+        }
+
+        async fn generate_stream(
+            &self,
+            _prompt: &str,
+        ) -> otl_core::Result<futures::stream::BoxStream<'static, otl_core::Result<String>>>
+        {
+            Err(otl_core::OtlError::LlmError("not streaming".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_context_budget() {
+        assert_eq!(context_budget(1000, 0.5, 8000), 500);
+        assert_eq!(context_budget(1000, 1.0, 600), 600);
+        assert_eq!(context_budget(1000, 1.5, 8000), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_within_budget_is_unchanged() {
+        let results = vec![
+            result("연차휴가는 15일입니다."),
+            result("병가는 60일입니다."),
+        ];
+        let (compressed, report) = compress_context(
+            "연차휴가 일수",
+            &keywords(&["연차휴가"]),
+            &results,
+            1000,
+            None,
+        )
+        .await;
+
+        assert_eq!(compressed[0].content, results[0].content);
+        assert_eq!(compressed[1].content, results[1].content);
+        assert_eq!(report.ratio(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_drops_duplicates_and_low_salience_sentences() {
+        let results = vec![
+            result("연차휴가는 입사 1년 후 15일이 부여됩니다. 회사 식당은 12시에 엽니다."),
+            result("회사 소개 자료입니다. 연차휴가는 입사 1년 후 15일이 부여됩니다!"),
+            result("주차장은 지하 2층입니다. 연차휴가 신청은 인사시스템에서 합니다."),
+        ];
+        let total: usize = results.iter().map(|r| r.content.len()).sum();
+
+        let (compressed, report) = compress_context(
+            "연차휴가는 며칠인가요?",
+            &keywords(&["연차휴가는", "며칠인가요?"]),
+            &results,
+            total / 2,
+            None,
+        )
+        .await;
+
+        assert_eq!(compressed.len(), 3);
+        assert_eq!(
+            compressed[0].content,
+            "연차휴가는 입사 1년 후 15일이 부여됩니다."
+        );
+        // The duplicate in chunk 2 is gone; its chunk keeps its slot
+        assert!(!compressed[1].content.contains("15일"));
+        assert!(compressed[2].content.contains("인사시스템"));
+        assert!(!compressed
+            .iter()
+            .any(|r| r.content.contains("식당") || r.content.contains("주차장")));
+
+        assert_eq!(report.redundant_sentences, 1);
+        assert!(report.dropped_sentences >= 2);
+        assert!(report.compressed_length <= total / 2);
+        assert!(report.ratio() < 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_summarize_low_rank() {
+        let template = PromptTemplate::for_language(Language::English);
+        let long = "Employees on sick leave must submit a medical certificate issued by a \
+                    licensed doctor within three days of returning to work.";
+        let mut results = vec![result(long), result(long), result(long), result("")];
+
+        let summarized =
+            summarize_low_rank("Sick leave?", &mut results, 1, &SummaryLlm, template).await;
+
+        assert_eq!(summarized, 2);
+        assert_eq!(results[0].content, long);
+        assert_eq!(results[1].content, "Sick leave needs a certificate.");
+        assert_eq!(results[3].content, "");
+    }
+}
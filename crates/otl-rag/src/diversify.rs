@@ -0,0 +1,196 @@
+//! Result diversification
+//!
+//! Fusion often ranks several near-identical chunks of the same section at
+//! the top, so the context ends up repeating one passage. Maximal marginal
+//! relevance (MMR) picks results one at a time, trading relevance against
+//! similarity to what was already picked:
+//!
+//! `mmr = λ · relevance − (1 − λ) · max similarity to selected`
+//!
+//! Similarity is lexical (character bigram overlap), raised to a floor for
+//! chunks of the same document or section so document diversity is enforced
+//! even when the wording differs.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::compress::{char_bigrams, jaccard};
+use otl_core::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Diversification settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiversityOptions {
+    /// Relevance/diversity trade-off: 1.0 keeps the fused order, 0.0 picks
+    /// the most dissimilar results
+    pub lambda: f32,
+
+    /// Minimum similarity of two chunks from the same document
+    pub same_document_similarity: f32,
+
+    /// Minimum similarity of two chunks from the same section of a document
+    pub same_section_similarity: f32,
+}
+
+impl Default for DiversityOptions {
+    fn default() -> Self {
+        Self {
+            lambda: 0.7,
+            same_document_similarity: 0.3,
+            same_section_similarity: 0.6,
+        }
+    }
+}
+
+/// Select `k` results by maximal marginal relevance
+///
+/// Relevance is the fused score normalized by the best score. The returned
+/// results are in selection order and keep their scores.
+pub fn diversify(
+    results: Vec<SearchResult>,
+    k: usize,
+    options: &DiversityOptions,
+) -> Vec<SearchResult> {
+    if options.lambda >= 1.0 || results.len() <= 1 {
+        return results.into_iter().take(k).collect();
+    }
+
+    let max_score = results
+        .iter()
+        .map(|r| r.score)
+        .fold(f32::MIN, f32::max)
+        .max(f32::EPSILON);
+    let bigrams: Vec<HashSet<(char, char)>> =
+        results.iter().map(|r| char_bigrams(&r.content)).collect();
+
+    let mut selected: Vec<usize> = Vec::new();
+    let mut remaining: Vec<usize> = (0..results.len()).collect();
+    while selected.len() < k && !remaining.is_empty() {
+        let mmr = |i: usize| {
+            let redundancy = selected
+                .iter()
+                .map(|&j| similarity(&results[i], &results[j], &bigrams[i], &bigrams[j], options))
+                .fold(0.0, f32::max);
+            options.lambda * results[i].score / max_score - (1.0 - options.lambda) * redundancy
+        };
+        let (position, _) = remaining
+            .iter()
+            .enumerate()
+            .map(|(position, &i)| (position, mmr(i)))
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+            .expect("remaining is not empty");
+        selected.push(remaining.remove(position));
+    }
+
+    let mut results: Vec<Option<SearchResult>> = results.into_iter().map(Some).collect();
+    selected
+        .into_iter()
+        .filter_map(|i| results[i].take())
+        .collect()
+}
+
+fn similarity(
+    a: &SearchResult,
+    b: &SearchResult,
+    a_bigrams: &HashSet<(char, char)>,
+    b_bigrams: &HashSet<(char, char)>,
+    options: &DiversityOptions,
+) -> f32 {
+    let lexical = jaccard(a_bigrams, b_bigrams);
+    if a.source.document_id != b.source.document_id {
+        return lexical;
+    }
+    let floor = if a.source.section.is_some() && a.source.section == b.source.section {
+        options.same_section_similarity
+    } else {
+        options.same_document_similarity
+    };
+    lexical.max(floor)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{DocumentAcl, SearchResultType, SourceReference};
+    use uuid::Uuid;
+
+    fn result(content: &str, score: f32, source: SourceReference) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score,
+            source,
+            acl: DocumentAcl::default(),
+            result_type: SearchResultType::Vector,
+        }
+    }
+
+    #[test]
+    fn test_near_duplicates_are_pushed_down() {
+        let doc = Uuid::new_v4();
+        let results = vec![
+            result(
+                "연차휴가는 입사 1년 후 15일이 부여된다.",
+                1.0,
+                SourceReference::new(doc),
+            ),
+            result(
+                "연차휴가는 입사 1년 후 15일이 부여됩니다.",
+                0.95,
+                SourceReference::new(Uuid::new_v4()),
+            ),
+            result(
+                "병가는 연간 60일 이내에서 사용할 수 있다.",
+                0.8,
+                SourceReference::new(Uuid::new_v4()),
+            ),
+        ];
+
+        let diversified = diversify(results, 2, &DiversityOptions::default());
+        assert_eq!(diversified.len(), 2);
+        assert!(diversified[0].content.starts_with("연차휴가는"));
+        assert!(diversified[1].content.starts_with("병가는"));
+        assert_eq!(diversified[1].score, 0.8);
+    }
+
+    #[test]
+    fn test_same_section_chunks_are_spread_out() {
+        let doc = Uuid::new_v4();
+        let section = || SourceReference::new(doc).with_section("제3장 휴가");
+        let results = vec![
+            result("연차 사용 절차", 1.0, section()),
+            result("휴가 신청서 양식", 0.9, section()),
+            result(
+                "출장비 정산 기준",
+                0.75,
+                SourceReference::new(Uuid::new_v4()),
+            ),
+        ];
+
+        let diversified = diversify(results, 3, &DiversityOptions::default());
+        let order: Vec<_> = diversified.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(
+            order,
+            vec!["연차 사용 절차", "출장비 정산 기준", "휴가 신청서 양식"]
+        );
+    }
+
+    #[test]
+    fn test_lambda_one_keeps_fused_order() {
+        let results = vec![
+            result("a a a", 1.0, SourceReference::new(Uuid::new_v4())),
+            result("a a a", 0.9, SourceReference::new(Uuid::new_v4())),
+            result("b", 0.1, SourceReference::new(Uuid::new_v4())),
+        ];
+        let options = DiversityOptions {
+            lambda: 1.0,
+            ..DiversityOptions::default()
+        };
+        let diversified = diversify(results, 2, &options);
+        assert_eq!(diversified[1].score, 0.9);
+    }
+}
@@ -0,0 +1,204 @@
+//! Persistent embedding store
+//!
+//! In-memory embedding caches start empty after every deploy, so all chunk
+//! and query texts are embedded again at once. The store keeps embeddings on
+//! local disk (sled) behind the in-memory cache: writes go through to disk,
+//! memory misses fall back to it, and the most recently used entries are
+//! loaded into memory at startup. The store holds at most `max_entries`
+//! embeddings and evicts the least recently used ones beyond that.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_core::{OtlError, Result};
+use std::path::Path;
+
+/// Bytes of the access sequence number stored in front of each embedding
+const SEQ_LEN: usize = 8;
+
+/// Disk-backed embedding store with LRU eviction
+///
+/// Two trees: `entries` maps a cache key to its access sequence number and
+/// embedding, `recency` maps sequence numbers to keys in access order.
+pub struct EmbeddingStore {
+    db: sled::Db,
+    entries: sled::Tree,
+    recency: sled::Tree,
+    max_entries: usize,
+}
+
+impl EmbeddingStore {
+    /// Open (or create) the store at `path`
+    pub fn open(path: impl AsRef<Path>, max_entries: usize) -> Result<Self> {
+        let path = path.as_ref();
+        let db = sled::open(path).map_err(|e| {
+            OtlError::ConfigError(format!(
+                "Failed to open embedding store {}: {e}",
+                path.display()
+            ))
+        })?;
+        let entries = db.open_tree("entries").map_err(store_error)?;
+        let recency = db.open_tree("recency").map_err(store_error)?;
+        Ok(Self {
+            db,
+            entries,
+            recency,
+            max_entries,
+        })
+    }
+
+    /// Get an embedding, marking it as recently used
+    pub fn get(&self, key: &str) -> Result<Option<Vec<f32>>> {
+        let Some(record) = self.entries.get(key).map_err(store_error)? else {
+            return Ok(None);
+        };
+        let embedding = decode_embedding(&record[SEQ_LEN..]);
+        self.write(key, &record[..SEQ_LEN], &embedding)?;
+        Ok(Some(embedding))
+    }
+
+    /// Store an embedding, evicting the least recently used entries if full
+    pub fn put(&self, key: &str, embedding: &[f32]) -> Result<()> {
+        let previous = self.entries.get(key).map_err(store_error)?;
+        let previous_seq = previous.as_ref().map(|record| &record[..SEQ_LEN]);
+        self.write(key, previous_seq.unwrap_or_default(), embedding)?;
+        self.evict()
+    }
+
+    /// Remove an embedding
+    pub fn remove(&self, key: &str) -> Result<()> {
+        if let Some(record) = self.entries.remove(key).map_err(store_error)? {
+            self.recency
+                .remove(&record[..SEQ_LEN])
+                .map_err(store_error)?;
+        }
+        Ok(())
+    }
+
+    /// Remove every embedding
+    pub fn clear(&self) -> Result<()> {
+        self.entries.clear().map_err(store_error)?;
+        self.recency.clear().map_err(store_error)
+    }
+
+    /// Number of stored embeddings
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Up to `limit` embeddings, most recently used first
+    pub fn recent(&self, limit: usize) -> Vec<(String, Vec<f32>)> {
+        self.recency
+            .iter()
+            .rev()
+            .filter_map(|item| item.ok())
+            .filter_map(|(_, key)| {
+                let record = self.entries.get(&key).ok()??;
+                let key = String::from_utf8(key.to_vec()).ok()?;
+                Some((key, decode_embedding(&record[SEQ_LEN..])))
+            })
+            .take(limit)
+            .collect()
+    }
+
+    /// Flush pending writes to disk
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush().map(|_| ()).map_err(store_error)
+    }
+
+    /// Write an entry under a new sequence number, dropping `previous_seq`
+    fn write(&self, key: &str, previous_seq: &[u8], embedding: &[f32]) -> Result<()> {
+        let seq = self.db.generate_id().map_err(store_error)?.to_be_bytes();
+        let mut record = Vec::with_capacity(SEQ_LEN + embedding.len() * 4);
+        record.extend_from_slice(&seq);
+        for value in embedding {
+            record.extend_from_slice(&value.to_le_bytes());
+        }
+
+        if !previous_seq.is_empty() {
+            self.recency.remove(previous_seq).map_err(store_error)?;
+        }
+        self.recency
+            .insert(seq, key.as_bytes())
+            .map_err(store_error)?;
+        self.entries.insert(key, record).map_err(store_error)?;
+        Ok(())
+    }
+
+    fn evict(&self) -> Result<()> {
+        while self.entries.len() > self.max_entries {
+            let Some((_, key)) = self.recency.pop_min().map_err(store_error)? else {
+                break;
+            };
+            self.entries.remove(key).map_err(store_error)?;
+        }
+        Ok(())
+    }
+}
+
+fn store_error(e: sled::Error) -> OtlError {
+    OtlError::DatabaseError(format!("Embedding store error: {e}"))
+}
+
+fn decode_embedding(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(max_entries: usize) -> (EmbeddingStore, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("otl-embeddings-{}", uuid::Uuid::new_v4()));
+        (EmbeddingStore::open(&path, max_entries).unwrap(), path)
+    }
+
+    #[test]
+    fn test_store_round_trip_and_reopen() {
+        let (store, path) = temp_store(10);
+        store.put("a", &[0.5, -1.25]).unwrap();
+        assert_eq!(store.get("a").unwrap(), Some(vec![0.5, -1.25]));
+        assert_eq!(store.get("b").unwrap(), None);
+        store.flush().unwrap();
+        drop(store);
+
+        let store = EmbeddingStore::open(&path, 10).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.recent(5), vec![("a".to_string(), vec![0.5, -1.25])]);
+        drop(store);
+        std::fs::remove_dir_all(path).ok();
+    }
+
+    #[test]
+    fn test_store_evicts_least_recently_used() {
+        let (store, path) = temp_store(2);
+        store.put("a", &[1.0]).unwrap();
+        store.put("b", &[2.0]).unwrap();
+        // Reading "a" makes "b" the least recently used entry
+        store.get("a").unwrap();
+        store.put("c", &[3.0]).unwrap();
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get("b").unwrap(), None);
+        let keys: Vec<String> = store.recent(10).into_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec!["c", "a"]);
+
+        store.remove("c").unwrap();
+        assert_eq!(store.len(), 1);
+        store.clear().unwrap();
+        assert!(store.is_empty());
+        drop(store);
+        std::fs::remove_dir_all(path).ok();
+    }
+}
@@ -0,0 +1,156 @@
+//! Query cost estimation
+//!
+//! A dry run retrieves and packs the context of a question like a real
+//! query, then stops before generation. The prompt it would send is sized
+//! with the token estimates of [`otl_vector::tokens`] and priced with the
+//! per-model prices of [`CostModel`], so the cost of a configuration can be
+//! judged before it is enabled.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_core::BackendHealth;
+use otl_vector::TokenizerFamily;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Prompt tokens
+    pub input: f64,
+
+    /// Generated tokens
+    pub output: f64,
+}
+
+/// Model prices and expected answer length used to estimate query cost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostModel {
+    /// Prices by model name; unpriced models get no cost estimate
+    pub prices: BTreeMap<String, ModelPrice>,
+
+    /// Expected answer length in tokens
+    pub output_tokens: usize,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            prices: BTreeMap::new(),
+            output_tokens: 500,
+        }
+    }
+}
+
+impl CostModel {
+    /// Estimated cost in USD of one generation call with `prompt_tokens`
+    pub fn cost(&self, model: &str, prompt_tokens: usize) -> Option<f64> {
+        let price = self.prices.get(model)?;
+        let input = prompt_tokens as f64 * price.input;
+        let output = self.output_tokens as f64 * price.output;
+        Some((input + output) / 1_000_000.0)
+    }
+}
+
+/// Tokenizer family of a generation model, guessed from its name
+///
+/// OpenAI chat models use byte-level BPE; the open models served by
+/// Ollama and vLLM (Llama, Qwen, Gemma) are closer to SentencePiece.
+pub fn tokenizer_family(model: &str) -> TokenizerFamily {
+    let openai = ["gpt-", "chatgpt", "o1", "o3", "o4"];
+    if openai.iter().any(|prefix| model.starts_with(prefix)) {
+        TokenizerFamily::Cl100k
+    } else {
+        TokenizerFamily::SentencePiece
+    }
+}
+
+/// How a query would be answered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerPath {
+    /// LLM generation from the packed context
+    #[default]
+    Generation,
+    /// Passages with highlighted sentences, no LLM call
+    Extractive,
+    /// Approved glossary entry, no retrieval or LLM call
+    Glossary,
+}
+
+/// Dry-run estimate of a query
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryEstimate {
+    /// How the answer would be produced
+    pub path: AnswerPath,
+
+    /// Model that would generate the answer
+    pub model: Option<String>,
+
+    /// Availability and candidate count of each retrieval backend
+    pub backends: Vec<BackendHealth>,
+
+    /// Candidates removed by ACL filtering
+    pub acl_filtered: usize,
+
+    /// Candidates after fusion
+    pub fused: usize,
+
+    /// Contexts selected for answering
+    pub contexts: usize,
+
+    /// Contexts fitting the prompt budget
+    pub prompt_contexts: usize,
+
+    /// Prompt length in characters
+    pub prompt_chars: usize,
+
+    /// Estimated prompt tokens
+    pub prompt_tokens: usize,
+
+    /// Expected answer tokens
+    pub completion_tokens: usize,
+
+    /// Estimated cost of the generation call in USD
+    pub cost_usd: Option<f64>,
+
+    /// Time spent retrieving and packing the context, in milliseconds
+    pub retrieval_ms: u64,
+
+    /// Degradations the answer would be produced under
+    pub warnings: Vec<String>,
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_of_priced_models_only() {
+        let cost: CostModel = serde_json::from_str(
+            r#"{"prices": {"gpt-4o-mini": {"input": 0.15, "output": 0.6}}, "output_tokens": 1000}"#,
+        )
+        .unwrap();
+
+        // 2000 * 0.15 + 1000 * 0.6 per million tokens
+        let usd = cost.cost("gpt-4o-mini", 2000).unwrap();
+        assert!((usd - 0.0009).abs() < 1e-12);
+        assert!(cost.cost("qwen2.5:7b", 2000).is_none());
+        assert_eq!(CostModel::default().output_tokens, 500);
+    }
+
+    #[test]
+    fn test_tokenizer_family_by_model_name() {
+        assert_eq!(tokenizer_family("gpt-4o-mini"), TokenizerFamily::Cl100k);
+        assert_eq!(tokenizer_family("o3-mini"), TokenizerFamily::Cl100k);
+        assert_eq!(
+            tokenizer_family("Qwen/Qwen2.5-7B-Instruct"),
+            TokenizerFamily::SentencePiece
+        );
+    }
+}
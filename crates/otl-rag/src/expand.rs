@@ -0,0 +1,224 @@
+//! Context window expansion
+//!
+//! Chunks are cut at a fixed size, so a retrieved chunk often stops in the
+//! middle of a procedure. For intents that need the whole passage
+//! (procedural questions by default) each top result is widened with the
+//! chunks before and after it in its document, nearest first, while the
+//! context stays within `max_context_length`. Text repeated by the
+//! chunker's overlap is dropped at the seams.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::QueryIntent;
+use otl_core::encryption::is_sealed_text;
+use otl_core::{DocumentChunk, SearchResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Shortest repeated text treated as chunk overlap when stitching
+const MIN_OVERLAP: usize = 16;
+
+/// Context expansion settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpansionOptions {
+    /// Expand contexts at all
+    pub enabled: bool,
+
+    /// Most chunks added on each side of a result
+    pub window: u32,
+
+    /// Intents whose contexts are expanded
+    pub intents: Vec<QueryIntent>,
+}
+
+impl Default for ExpansionOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 1,
+            intents: vec![QueryIntent::Procedural],
+        }
+    }
+}
+
+impl ExpansionOptions {
+    /// Whether the contexts of a question with `intent` are expanded
+    pub fn applies(&self, intent: QueryIntent) -> bool {
+        self.enabled && self.window > 0 && self.intents.contains(&intent)
+    }
+}
+
+/// Stored chunk positions of `results`, as `(document_id, chunk_index)`
+fn positions(results: &[SearchResult]) -> impl Iterator<Item = (Uuid, u32)> + '_ {
+    results
+        .iter()
+        .filter_map(|r| Some((r.source.document_id, r.source.chunk_index?)))
+}
+
+/// Positions of the chunks within `window` of `results`, excluding chunks
+/// that are results themselves
+pub fn neighbor_positions(results: &[SearchResult], window: u32) -> Vec<(Uuid, u32)> {
+    let retrieved: HashSet<(Uuid, u32)> = positions(results).collect();
+    let mut neighbors = Vec::new();
+    for (document_id, index) in positions(results) {
+        for distance in 1..=window {
+            let sides = [index.checked_sub(distance), index.checked_add(distance)];
+            for neighbor in sides.into_iter().flatten() {
+                let position = (document_id, neighbor);
+                if !retrieved.contains(&position) && !neighbors.contains(&position) {
+                    neighbors.push(position);
+                }
+            }
+        }
+    }
+    neighbors
+}
+
+/// Stitch `neighbors` onto `results` in rank order while the total context
+/// stays within `budget` bytes; returns the number of chunks added
+///
+/// A result grows one chunk at a time, alternating the preceding and the
+/// following side, nearest first. A side stops at the first chunk that is
+/// missing, already used or over budget, so stitched text is always
+/// contiguous. Each chunk is stitched onto one result at most.
+pub fn expand(
+    results: &mut [SearchResult],
+    neighbors: &[DocumentChunk],
+    window: u32,
+    budget: usize,
+) -> usize {
+    let chunks: HashMap<(Uuid, u32), &str> = neighbors
+        .iter()
+        .filter(|c| !is_sealed_text(&c.content))
+        .map(|c| ((c.document_id, c.chunk_index), c.content.as_str()))
+        .collect();
+    let mut used: HashSet<(Uuid, u32)> = positions(results).collect();
+    let mut total: usize = results.iter().map(|r| r.content.len()).sum();
+    let mut added = 0;
+
+    for result in results.iter_mut() {
+        let Some(index) = result.source.chunk_index else {
+            continue;
+        };
+        let document_id = result.source.document_id;
+        // Preceding, then following side
+        let mut open = [true, true];
+        for distance in 1..=window {
+            for (side, open) in open.iter_mut().enumerate() {
+                if !*open {
+                    continue;
+                }
+                let neighbor = if side == 0 {
+                    index.checked_sub(distance)
+                } else {
+                    index.checked_add(distance)
+                };
+                let position = neighbor.map(|i| (document_id, i));
+                let text = position
+                    .filter(|p| !used.contains(p))
+                    .and_then(|p| chunks.get(&p));
+                let (Some(position), Some(text)) = (position, text) else {
+                    *open = false;
+                    continue;
+                };
+                let stitched = if side == 0 {
+                    stitch(text, &result.content)
+                } else {
+                    stitch(&result.content, text)
+                };
+                let grown = stitched.len().saturating_sub(result.content.len());
+                if total + grown > budget {
+                    *open = false;
+                    continue;
+                }
+                result.content = stitched;
+                total += grown;
+                used.insert(position);
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+/// Join two consecutive texts, dropping the overlap the chunker repeated
+fn stitch(left: &str, right: &str) -> String {
+    let overlap = overlap(left, right);
+    if overlap >= MIN_OVERLAP {
+        format!("{left}{}", &right[overlap..])
+    } else {
+        format!("{left}\n{right}")
+    }
+}
+
+/// Length in bytes of the longest suffix of `left` that starts `right`
+fn overlap(left: &str, right: &str) -> usize {
+    let longest = left.len().min(right.len());
+    (1..=longest)
+        .rev()
+        .filter(|&k| right.is_char_boundary(k))
+        .find(|&k| left.ends_with(&right[..k]))
+        .unwrap_or(0)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{DocumentAcl, SearchResultType, SourceReference};
+
+    fn result(document_id: Uuid, chunk_index: u32, content: &str) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score: 1.0,
+            source: SourceReference::new(document_id).with_chunk_index(chunk_index),
+            acl: DocumentAcl::default(),
+            result_type: SearchResultType::Vector,
+        }
+    }
+
+    #[test]
+    fn test_neighbors_skip_retrieved_chunks() {
+        let doc = Uuid::new_v4();
+        let results = [
+            result(doc, 0, "a"),
+            result(doc, 1, "b"),
+            result(doc, 5, "c"),
+        ];
+
+        assert_eq!(
+            neighbor_positions(&results, 1),
+            vec![(doc, 2), (doc, 4), (doc, 6)]
+        );
+    }
+
+    #[test]
+    fn test_expand_stitches_within_budget() {
+        let doc = Uuid::new_v4();
+        let step_two = "2. 부서장 승인을 받는다. 3. 인사시스템에 등록한다.";
+        let neighbors = [
+            DocumentChunk::new(doc, 3, "1. 휴가 신청서를 작성한다."),
+            // The chunker repeated the end of chunk 4 at the start of 5
+            DocumentChunk::new(doc, 5, format!("{step_two} 4. 결재 완료 후 휴가를 쓴다.")),
+        ];
+        let mut results = vec![result(doc, 4, step_two)];
+
+        let added = expand(&mut results, &neighbors, 1, 1000);
+        assert_eq!(added, 2);
+        assert_eq!(
+            results[0].content,
+            format!("1. 휴가 신청서를 작성한다.\n{step_two} 4. 결재 완료 후 휴가를 쓴다.")
+        );
+        assert_eq!(results[0].source.chunk_index, Some(4));
+
+        // Without room the result is left as retrieved
+        let mut results = vec![result(doc, 4, step_two)];
+        assert_eq!(expand(&mut results, &neighbors, 1, step_two.len() + 10), 0);
+        assert_eq!(results[0].content, step_two);
+    }
+}
@@ -0,0 +1,363 @@
+//! Extractive answering
+//!
+//! Answers a query without an LLM: the reranked passages are returned as-is
+//! and the sentences that best match the question are highlighted. Sentences
+//! are scored lexically (keyword coverage) and, when an embedding client is
+//! configured, by cosine similarity to the question embedding.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::language::PromptTemplate;
+use crate::suggest::topic_from_keyword;
+use otl_core::{ExtractedPassage, SearchResult, SentenceHighlight};
+use otl_vector::embedding::EmbeddingClient;
+
+/// Sentence scoring options for extractive answers
+#[derive(Debug, Clone)]
+pub struct ExtractiveOptions {
+    /// Weight of the lexical score (embedding score gets the remainder)
+    pub lexical_weight: f32,
+
+    /// Maximum highlighted sentences per passage
+    pub max_highlights: usize,
+}
+
+impl Default for ExtractiveOptions {
+    fn default() -> Self {
+        Self {
+            lexical_weight: 0.5,
+            max_highlights: 2,
+        }
+    }
+}
+
+/// Build extractive passages with highlighted sentences
+///
+/// Passages keep the order of `results` and are numbered from 1 so they line
+/// up with the citations of the response.
+pub async fn extract_passages(
+    question: &str,
+    keywords: &[String],
+    results: &[SearchResult],
+    embedder: Option<&dyn EmbeddingClient>,
+    options: &ExtractiveOptions,
+) -> Vec<ExtractedPassage> {
+    let spans: Vec<Vec<(usize, usize)>> = results
+        .iter()
+        .map(|r| split_sentences(&r.content))
+        .collect();
+
+    let sentences: Vec<String> = results
+        .iter()
+        .zip(&spans)
+        .flat_map(|(r, s)| {
+            s.iter()
+                .map(|&(start, end)| r.content[start..end].to_string())
+        })
+        .collect();
+
+    let embedding_scores = match embedder {
+        Some(embedder) if !sentences.is_empty() => {
+            embedding_scores(question, &sentences, embedder).await
+        }
+        _ => None,
+    };
+
+    let mut sentence_idx = 0;
+    results
+        .iter()
+        .zip(spans)
+        .enumerate()
+        .map(|(i, (result, spans))| {
+            let mut scored: Vec<SentenceHighlight> = spans
+                .into_iter()
+                .map(|(start, end)| {
+                    let lexical = lexical_score(&result.content[start..end], keywords);
+                    let score = match &embedding_scores {
+                        Some(scores) => {
+                            options.lexical_weight * lexical
+                                + (1.0 - options.lexical_weight) * scores[sentence_idx]
+                        }
+                        None => lexical,
+                    };
+                    sentence_idx += 1;
+                    SentenceHighlight { start, end, score }
+                })
+                .filter(|h| h.score > 0.0)
+                .collect();
+
+            scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+            scored.truncate(options.max_highlights);
+            scored.sort_by_key(|h| h.start);
+
+            ExtractedPassage {
+                index: (i + 1) as u32,
+                text: result.content.clone(),
+                source: result.source.clone(),
+                score: result.score,
+                highlights: scored,
+            }
+        })
+        .collect()
+}
+
+/// Render passages as a plain-text answer with `**highlighted**` sentences
+pub fn render_extractive_answer(
+    passages: &[ExtractedPassage],
+    template: &PromptTemplate,
+) -> String {
+    if passages.is_empty() {
+        return template.not_found.to_string();
+    }
+
+    passages
+        .iter()
+        .map(|passage| {
+            let mut text = String::with_capacity(passage.text.len() + 16);
+            let mut cursor = 0;
+            for h in &passage.highlights {
+                text.push_str(&passage.text[cursor..h.start]);
+                text.push_str("**");
+                text.push_str(&passage.text[h.start..h.end]);
+                text.push_str("**");
+                cursor = h.end;
+            }
+            text.push_str(&passage.text[cursor..]);
+            format!("{} {}", template.citation(passage.index), text.trim())
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Split text into trimmed sentence byte ranges
+pub(crate) fn split_sentences(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let boundary = match c {
+            '\n' | '。' => true,
+            '.' | '!' | '?' => chars.peek().map_or(true, |&(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if boundary {
+            let end = i + c.len_utf8();
+            push_trimmed(text, start, end, &mut spans);
+            start = end;
+        }
+    }
+    push_trimmed(text, start, text.len(), &mut spans);
+
+    spans
+}
+
+fn push_trimmed(text: &str, start: usize, end: usize, spans: &mut Vec<(usize, usize)>) {
+    let slice = &text[start..end];
+    let trimmed = slice.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+    let offset = start + (slice.len() - slice.trim_start().len());
+    spans.push((offset, offset + trimmed.len()));
+}
+
+/// Fraction of query keywords contained in the sentence
+pub(crate) fn lexical_score(sentence: &str, keywords: &[String]) -> f32 {
+    let topics: Vec<String> = keywords
+        .iter()
+        .map(|k| topic_from_keyword(k).to_lowercase())
+        .filter(|k| !k.is_empty())
+        .collect();
+    if topics.is_empty() {
+        return 0.0;
+    }
+
+    let sentence = sentence.to_lowercase();
+    let matched = topics
+        .iter()
+        .filter(|k| sentence.contains(k.as_str()))
+        .count();
+    matched as f32 / topics.len() as f32
+}
+
+/// Cosine similarity of each sentence to the question, clamped to 0..=1
+async fn embedding_scores(
+    question: &str,
+    sentences: &[String],
+    embedder: &dyn EmbeddingClient,
+) -> Option<Vec<f32>> {
+    let mut texts = Vec::with_capacity(sentences.len() + 1);
+    texts.push(question.to_string());
+    texts.extend_from_slice(sentences);
+
+    match embedder.embed_batch(&texts).await {
+        Ok(embeddings) if embeddings.len() == texts.len() => {
+            let (query, rest) = embeddings.split_first()?;
+            Some(
+                rest.iter()
+                    .map(|e| cosine_similarity(query, e).clamp(0.0, 1.0))
+                    .collect(),
+            )
+        }
+        Ok(_) => {
+            tracing::warn!("Embedding batch size mismatch, using lexical scores only");
+            None
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Sentence embedding failed, using lexical scores only: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{DocumentAcl, Language, SearchResultType, SourceReference};
+    use uuid::Uuid;
+
+    fn result(content: &str) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score: 0.8,
+            source: SourceReference::new(Uuid::new_v4()),
+            acl: DocumentAcl::default(),
+            result_type: SearchResultType::Vector,
+        }
+    }
+
+    /// Embeds texts as keyword presence vectors
+    struct KeywordEmbedder;
+
+    #[async_trait::async_trait]
+    impl EmbeddingClient for KeywordEmbedder {
+        async fn embed(&self, text: &str) -> otl_core::Result<Vec<f32>> {
+            Ok(["휴가", "승인"]
+                .iter()
+                .map(|k| if text.contains(k) { 1.0 } else { 0.0 })
+                .collect())
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> otl_core::Result<Vec<Vec<f32>>> {
+            let mut out = Vec::new();
+            for text in texts {
+                out.push(self.embed(text).await?);
+            }
+            Ok(out)
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        let text = "연차휴가는 15일입니다. 신청은 인사시스템에서 합니다!\n버전 1.2 참고";
+        let spans = split_sentences(text);
+        let sentences: Vec<&str> = spans.iter().map(|&(s, e)| &text[s..e]).collect();
+
+        assert_eq!(
+            sentences,
+            vec![
+                "연차휴가는 15일입니다.",
+                "신청은 인사시스템에서 합니다!",
+                "버전 1.2 참고"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexical_score() {
+        let keywords = vec!["연차휴가는".to_string(), "며칠".to_string()];
+        assert_eq!(lexical_score("연차휴가 일수는 15일", &keywords), 0.5);
+        assert_eq!(lexical_score("관련 없음", &keywords), 0.0);
+        assert_eq!(lexical_score("관련 없음", &[]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_extract_passages_lexical_only() {
+        let results = vec![result(
+            "회사 소개입니다. 연차휴가는 15일 부여됩니다. 병가는 별도입니다.",
+        )];
+        let keywords = vec!["연차휴가".to_string()];
+        let options = ExtractiveOptions::default();
+
+        let passages =
+            extract_passages("연차휴가 며칠?", &keywords, &results, None, &options).await;
+
+        assert_eq!(passages.len(), 1);
+        assert_eq!(passages[0].index, 1);
+        assert_eq!(passages[0].highlights.len(), 1);
+        let h = &passages[0].highlights[0];
+        assert_eq!(
+            &passages[0].text[h.start..h.end],
+            "연차휴가는 15일 부여됩니다."
+        );
+
+        let answer =
+            render_extractive_answer(&passages, PromptTemplate::for_language(Language::Korean));
+        assert_eq!(
+            answer,
+            "[출처: 1] 회사 소개입니다. **연차휴가는 15일 부여됩니다.** 병가는 별도입니다."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_passages_with_embeddings() {
+        let results = vec![result("팀장 승인이 필요합니다. 식당은 1층입니다.")];
+        let options = ExtractiveOptions {
+            lexical_weight: 0.0,
+            max_highlights: 2,
+        };
+
+        let passages = extract_passages(
+            "승인 절차",
+            &["절차".to_string()],
+            &results,
+            Some(&KeywordEmbedder),
+            &options,
+        )
+        .await;
+
+        let highlights = &passages[0].highlights;
+        assert_eq!(highlights.len(), 1);
+        assert_eq!(
+            &passages[0].text[highlights[0].start..highlights[0].end],
+            "팀장 승인이 필요합니다."
+        );
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let ko = PromptTemplate::for_language(Language::Korean);
+        let en = PromptTemplate::for_language(Language::English);
+        assert_eq!(
+            render_extractive_answer(&[], ko),
+            "해당 정보를 찾을 수 없습니다."
+        );
+        assert_eq!(
+            render_extractive_answer(&[], en),
+            "The information could not be found."
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Feedback-driven ranking priors
+//!
+//! Users mark the citations of an answer helpful or unhelpful. Every vote
+//! adds to the popularity of the cited chunk and of its document, and older
+//! votes count less, halving every `half_life_days`. In the fusion stage the
+//! popularity of a result's chunk and document becomes a score multiplier
+//! bounded by `max_boost`, so feedback nudges the ranking without
+//! overriding relevance.
+//!
+//! Author: hephaex@gmail.com
+
+use chrono::{DateTime, Utc};
+use otl_core::SourceReference;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// Feedback prior configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedbackConfig {
+    /// Apply feedback priors at all
+    pub enabled: bool,
+
+    /// Largest relative score change (0.1 = at most ±10%)
+    pub max_boost: f32,
+
+    /// Age in days at which a vote counts half
+    pub half_life_days: f32,
+
+    /// Net vote count giving half of `max_boost`
+    pub saturation: f32,
+}
+
+impl Default for FeedbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_boost: 0.1,
+            half_life_days: 30.0,
+            saturation: 5.0,
+        }
+    }
+}
+
+impl FeedbackConfig {
+    /// Weight of a vote cast `seconds` ago
+    fn decay(&self, seconds: i64) -> f32 {
+        if self.half_life_days <= 0.0 {
+            return 1.0;
+        }
+        let age_days = seconds.max(0) as f32 / 86_400.0;
+        0.5f32.powf(age_days / self.half_life_days)
+    }
+
+    /// Score multiplier for a net popularity
+    pub fn multiplier(&self, popularity: f32) -> f32 {
+        if !self.enabled || popularity == 0.0 {
+            return 1.0;
+        }
+        let saturation = self.saturation.max(f32::EPSILON);
+        1.0 + self.max_boost * popularity / (popularity.abs() + saturation)
+    }
+}
+
+/// A user's verdict on one cited chunk
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackVote {
+    /// Cited document
+    pub document_id: Uuid,
+
+    /// Cited chunk, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<u32>,
+
+    /// Whether the citation was helpful
+    pub helpful: bool,
+
+    /// When the vote was cast
+    pub at: DateTime<Utc>,
+}
+
+/// Net votes, decayed to `updated_at`
+#[derive(Debug, Clone, Copy)]
+struct Popularity {
+    score: f32,
+    updated_at: DateTime<Utc>,
+}
+
+impl Popularity {
+    fn at(&self, now: DateTime<Utc>, config: &FeedbackConfig) -> f32 {
+        self.score * config.decay((now - self.updated_at).num_seconds())
+    }
+
+    fn add(&mut self, value: f32, at: DateTime<Utc>, config: &FeedbackConfig) {
+        // Votes may arrive out of order when loaded from storage
+        if at >= self.updated_at {
+            self.score = self.at(at, config) + value;
+            self.updated_at = at;
+        } else {
+            self.score += value * config.decay((self.updated_at - at).num_seconds());
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Priors {
+    documents: HashMap<Uuid, Popularity>,
+    chunks: HashMap<(Uuid, u32), Popularity>,
+}
+
+/// Thread-safe document and chunk popularity learned from feedback
+#[derive(Debug, Default)]
+pub struct FeedbackPriors {
+    config: FeedbackConfig,
+    priors: RwLock<Priors>,
+}
+
+impl FeedbackPriors {
+    /// Create priors without votes
+    pub fn new(config: FeedbackConfig) -> Self {
+        Self {
+            config,
+            priors: RwLock::new(Priors::default()),
+        }
+    }
+
+    /// Configuration
+    pub fn config(&self) -> &FeedbackConfig {
+        &self.config
+    }
+
+    /// Add a vote to its chunk and document
+    pub fn record(&self, vote: &FeedbackVote) {
+        self.add(vote, if vote.helpful { 1.0 } else { -1.0 });
+    }
+
+    /// Take back a recorded vote, e.g. when the user changes it
+    pub fn withdraw(&self, vote: &FeedbackVote) {
+        self.add(vote, if vote.helpful { -1.0 } else { 1.0 });
+    }
+
+    fn add(&self, vote: &FeedbackVote, value: f32) {
+        let mut priors = self.priors.write().expect("feedback priors poisoned");
+        let empty = Popularity {
+            score: 0.0,
+            updated_at: vote.at,
+        };
+        priors
+            .documents
+            .entry(vote.document_id)
+            .or_insert(empty)
+            .add(value, vote.at, &self.config);
+        if let Some(index) = vote.chunk_index {
+            priors
+                .chunks
+                .entry((vote.document_id, index))
+                .or_insert(empty)
+                .add(value, vote.at, &self.config);
+        }
+    }
+
+    /// Decayed net votes of a chunk plus those of its document
+    pub fn popularity(
+        &self,
+        document_id: Uuid,
+        chunk_index: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> f32 {
+        let priors = self.priors.read().expect("feedback priors poisoned");
+        let document = priors
+            .documents
+            .get(&document_id)
+            .map_or(0.0, |p| p.at(now, &self.config));
+        let chunk = chunk_index
+            .and_then(|index| priors.chunks.get(&(document_id, index)))
+            .map_or(0.0, |p| p.at(now, &self.config));
+        document + chunk
+    }
+
+    /// Score multiplier of a result
+    pub fn multiplier(&self, source: &SourceReference, now: DateTime<Utc>) -> f32 {
+        if !self.config.enabled {
+            return 1.0;
+        }
+        let popularity = self.popularity(source.document_id, source.chunk_index, now);
+        self.config.multiplier(popularity)
+    }
+
+    /// Number of documents with votes
+    pub fn len(&self) -> usize {
+        self.priors
+            .read()
+            .expect("feedback priors poisoned")
+            .documents
+            .len()
+    }
+
+    /// Check if no votes were recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn vote(
+        document_id: Uuid,
+        chunk_index: Option<u32>,
+        helpful: bool,
+        at: DateTime<Utc>,
+    ) -> FeedbackVote {
+        FeedbackVote {
+            document_id,
+            chunk_index,
+            helpful,
+            at,
+        }
+    }
+
+    #[test]
+    fn test_votes_decay_with_half_life() {
+        let now = Utc::now();
+        let doc = Uuid::new_v4();
+        let priors = FeedbackPriors::new(FeedbackConfig::default());
+
+        priors.record(&vote(doc, Some(3), true, now - Duration::days(30)));
+        priors.record(&vote(doc, None, true, now));
+
+        // Document: 0.5 + 1, chunk: 0.5; out-of-order votes fold the same
+        assert!((priors.popularity(doc, Some(3), now) - 2.0).abs() < 1e-3);
+        assert!((priors.popularity(doc, Some(4), now) - 1.5).abs() < 1e-3);
+        priors.record(&vote(doc, None, false, now - Duration::days(60)));
+        assert!((priors.popularity(doc, None, now) - 1.25).abs() < 1e-3);
+        assert_eq!(priors.popularity(Uuid::new_v4(), Some(3), now), 0.0);
+        assert_eq!(priors.len(), 1);
+    }
+
+    #[test]
+    fn test_withdrawn_vote_cancels() {
+        let now = Utc::now();
+        let doc = Uuid::new_v4();
+        let priors = FeedbackPriors::new(FeedbackConfig::default());
+
+        let earlier = vote(doc, Some(1), true, now - Duration::days(30));
+        priors.record(&earlier);
+        priors.record(&vote(doc, Some(1), false, now));
+        priors.withdraw(&earlier);
+        assert!((priors.popularity(doc, Some(1), now) + 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_multiplier_is_bounded() {
+        let config = FeedbackConfig::default();
+
+        assert_eq!(config.multiplier(0.0), 1.0);
+        assert!((config.multiplier(5.0) - 1.05).abs() < 1e-6);
+        assert!(config.multiplier(1000.0) < 1.1);
+        assert!(config.multiplier(-1000.0) > 0.9);
+
+        let disabled = FeedbackConfig {
+            enabled: false,
+            ..FeedbackConfig::default()
+        };
+        assert_eq!(disabled.multiplier(5.0), 1.0);
+    }
+}
@@ -0,0 +1,358 @@
+//! Answer templates per intent
+//!
+//! Procedures read best as numbered steps, comparisons as tables and
+//! yes/no questions as a verdict followed by its rationale. The format is
+//! chosen from the expected [`AnswerType`], the answer prompt asks for it,
+//! and the generated answer is post-processed so that it holds even when
+//! the LLM drifts: bullet lists and sequenced sentences become numbered
+//! steps, and loose pipe-separated rows become a well-formed markdown table.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::extractive::split_sentences;
+use crate::AnswerType;
+use otl_core::AnswerFormat;
+
+/// Question words that rule out a yes/no question
+const WH_WORDS: &[&str] = &[
+    "무엇",
+    "뭐",
+    "뭔",
+    "어떻게",
+    "어떤",
+    "어디",
+    "언제",
+    "누구",
+    "누가",
+    "왜",
+    "몇",
+    "며칠",
+    "얼마",
+    "어느",
+    "what",
+    "how",
+    "which",
+    "where",
+    "when",
+    "who",
+    "why",
+];
+
+/// Korean endings of yes/no questions
+const YES_NO_ENDINGS: &[&str] = &["나요", "까요", "니까", "가요", "는지요", "죠"];
+
+/// English auxiliaries opening yes/no questions
+const AUXILIARIES: &[&str] = &[
+    "is", "are", "am", "was", "were", "do", "does", "did", "can", "could", "should", "shall",
+    "will", "would", "may", "must", "has", "have", "had",
+];
+
+/// Words marking a sentence as a step of a procedure
+const STEP_MARKERS: &[&str] = &[
+    "먼저",
+    "우선",
+    "다음으로",
+    "다음 단계",
+    "그다음",
+    "이후",
+    "그 후",
+    "그런 다음",
+    "마지막으로",
+    "끝으로",
+    "first",
+    "then",
+    "next",
+    "after that",
+    "finally",
+];
+
+/// Format of answers of an expected type
+pub fn answer_format(answer_type: &AnswerType) -> AnswerFormat {
+    match answer_type {
+        AnswerType::List => AnswerFormat::Steps,
+        AnswerType::Comparison => AnswerFormat::Table,
+        AnswerType::YesNo => AnswerFormat::YesNo,
+        _ => AnswerFormat::Prose,
+    }
+}
+
+/// Whether a question asks for a yes or no
+///
+/// Korean questions end in an interrogative ending, English ones open with
+/// an auxiliary; questions with a question word or offering alternatives
+/// are not yes/no questions.
+pub fn is_yes_no_question(question: &str) -> bool {
+    let lower = question.trim().to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let has_wh_word = WH_WORDS.iter().any(|wh| {
+        if wh.is_ascii() {
+            words.contains(wh)
+        } else {
+            lower.contains(wh)
+        }
+    });
+    if has_wh_word || lower.contains("아니면") || lower.contains("또는") || words.contains(&"or")
+    {
+        return false;
+    }
+
+    let stem = lower.trim_end_matches(['?', '.', '!', ' ']);
+    YES_NO_ENDINGS.iter().any(|ending| stem.ends_with(ending))
+        || words.first().is_some_and(|w| AUXILIARIES.contains(w))
+}
+
+/// Post-process a generated answer into its format
+pub fn apply(format: AnswerFormat, answer: &str) -> String {
+    match format {
+        AnswerFormat::Steps => number_steps(answer),
+        AnswerFormat::Table => assemble_table(answer),
+        AnswerFormat::YesNo | AnswerFormat::Prose => answer.to_string(),
+    }
+}
+
+// ============================================================================
+// Steps
+// ============================================================================
+
+/// Answer with its procedure as numbered steps
+///
+/// Answers already numbered are kept. Bullet items are numbered in order;
+/// otherwise the run of sentences from the first to the last step marker
+/// ("먼저", "다음으로", "마지막으로") is split into one step per sentence.
+pub fn number_steps(answer: &str) -> String {
+    let lines: Vec<&str> = answer.lines().collect();
+    if lines.iter().filter(|l| is_numbered(l)).count() >= 2 {
+        return answer.to_string();
+    }
+
+    if lines.iter().filter(|l| bullet_text(l).is_some()).count() >= 2 {
+        let mut step = 0;
+        return lines
+            .iter()
+            .map(|line| match bullet_text(line) {
+                Some(text) => {
+                    step += 1;
+                    format!("{step}. {text}")
+                }
+                None => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    let sentences = sentences(answer);
+    let marked: Vec<usize> = (0..sentences.len())
+        .filter(|&i| has_step_marker(&sentences[i]))
+        .collect();
+    let (Some(&first), Some(&last)) = (marked.first(), marked.last()) else {
+        return answer.to_string();
+    };
+    if marked.len() < 2 {
+        return answer.to_string();
+    }
+
+    let mut parts = Vec::new();
+    if first > 0 {
+        parts.push(sentences[..first].join(" "));
+    }
+    parts.push(
+        sentences[first..=last]
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!("{}. {s}", i + 1))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    if last + 1 < sentences.len() {
+        parts.push(sentences[last + 1..].join(" "));
+    }
+    parts.join("\n\n")
+}
+
+fn is_numbered(line: &str) -> bool {
+    let line = line.trim_start();
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && matches!(line[digits..].chars().next(), Some('.' | ')'))
+}
+
+fn bullet_text(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    ["- ", "* ", "• ", "· "]
+        .iter()
+        .find_map(|bullet| line.strip_prefix(bullet))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+/// Sentences of a text, with citations following a sentence kept on it
+fn sentences(text: &str) -> Vec<String> {
+    let mut sentences: Vec<String> = Vec::new();
+    for (start, end) in split_sentences(text) {
+        let mut sentence = &text[start..end];
+        while let (Some(previous), Some(close)) = (sentences.last_mut(), citation_end(sentence)) {
+            previous.push(' ');
+            previous.push_str(&sentence[..close]);
+            sentence = sentence[close..].trim_start();
+        }
+        if !sentence.is_empty() {
+            sentences.push(sentence.to_string());
+        }
+    }
+    sentences
+}
+
+/// End of a citation opening a sentence, if any
+fn citation_end(sentence: &str) -> Option<usize> {
+    sentence
+        .starts_with('[')
+        .then(|| sentence.find(']'))
+        .flatten()
+        .map(|close| close + 1)
+}
+
+fn has_step_marker(sentence: &str) -> bool {
+    let lower = sentence.to_lowercase();
+    STEP_MARKERS.iter().any(|marker| lower.starts_with(marker))
+}
+
+// ============================================================================
+// Tables
+// ============================================================================
+
+/// Answer with its pipe-separated rows assembled into markdown tables
+///
+/// Each run of two or more rows becomes a table: the first row is the
+/// header, a separator row is added after it, and short rows are padded to
+/// the widest row.
+pub fn assemble_table(answer: &str) -> String {
+    let lines: Vec<&str> = answer.lines().collect();
+    let mut output: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let mut rows = Vec::new();
+        let mut j = i;
+        while let Some(cells) = lines.get(j).and_then(|line| table_cells(line)) {
+            if !is_separator(&cells) {
+                rows.push(cells);
+            }
+            j += 1;
+        }
+        if rows.len() >= 2 {
+            output.extend(render_table(rows));
+            i = j;
+        } else {
+            output.push(lines[i].to_string());
+            i += 1;
+        }
+    }
+    output.join("\n")
+}
+
+fn table_cells(line: &str) -> Option<Vec<String>> {
+    let line = line.trim();
+    if !line.contains('|') {
+        return None;
+    }
+    let inner = line.strip_prefix('|').unwrap_or(line);
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    let cells: Vec<String> = inner.split('|').map(|c| c.trim().to_string()).collect();
+    (cells.len() >= 2).then_some(cells)
+}
+
+fn is_separator(cells: &[String]) -> bool {
+    cells.iter().all(|cell| {
+        let dashes = cell.trim_matches(':');
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    })
+}
+
+fn render_table(mut rows: Vec<Vec<String>>) -> Vec<String> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for row in &mut rows {
+        row.resize(columns, String::new());
+    }
+    let render = |cells: &[String]| format!("| {} |", cells.join(" | "));
+
+    let mut lines = vec![render(&rows[0])];
+    lines.push(render(&vec!["---".to_string(); columns]));
+    lines.extend(rows[1..].iter().map(|row| render(row)));
+    lines
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yes_no_questions() {
+        assert!(is_yes_no_question("재택근무 중에도 연차를 쓸 수 있나요?"));
+        assert!(is_yes_no_question("수습 기간에 경조휴가가 가능한가요"));
+        assert!(is_yes_no_question("Can I carry over unused leave?"));
+        assert!(!is_yes_no_question("연차휴가는 며칠인가요?"));
+        assert!(!is_yes_no_question("연차는 어떻게 신청하나요?"));
+        assert!(!is_yes_no_question("연차와 반차 중 어느 쪽이 유리한가요?"));
+        assert!(!is_yes_no_question("Is it paid or unpaid?"));
+        assert!(!is_yes_no_question("연차휴가 신청 절차"));
+    }
+
+    #[test]
+    fn test_number_steps_from_bullets_and_markers() {
+        let bullets = "신청 절차는 다음과 같습니다.\n- 인사시스템에 접속합니다.\n- 휴가 신청서를 작성합니다 [출처: 1]";
+        assert_eq!(
+            number_steps(bullets),
+            "신청 절차는 다음과 같습니다.\n1. 인사시스템에 접속합니다.\n2. 휴가 신청서를 작성합니다 [출처: 1]"
+        );
+
+        let prose = "연차 신청 방법입니다. 먼저 인사시스템에 접속합니다. [출처: 1] \
+                     다음으로 신청서를 작성합니다. 마지막으로 팀장 승인을 받습니다. 승인 후 확정됩니다.";
+        assert_eq!(
+            number_steps(prose),
+            "연차 신청 방법입니다.\n\n\
+             1. 먼저 인사시스템에 접속합니다. [출처: 1]\n\
+             2. 다음으로 신청서를 작성합니다.\n\
+             3. 마지막으로 팀장 승인을 받습니다.\n\n\
+             승인 후 확정됩니다."
+        );
+
+        let numbered = "1. 접속합니다.\n2. 작성합니다.";
+        assert_eq!(number_steps(numbered), numbered);
+        assert_eq!(
+            number_steps("인사시스템에서 신청합니다."),
+            "인사시스템에서 신청합니다."
+        );
+    }
+
+    #[test]
+    fn test_assemble_table() {
+        let answer = "비교 결과입니다.\n항목 | 연차 | 병가\n|---|---|\n| 일수 | 15일 |\n급여 | 유급 | 무급 [출처: 2]\n\n연차가 유리합니다.";
+        assert_eq!(
+            assemble_table(answer),
+            "비교 결과입니다.\n\
+             | 항목 | 연차 | 병가 |\n\
+             | --- | --- | --- |\n\
+             | 일수 | 15일 |  |\n\
+             | 급여 | 유급 | 무급 [출처: 2] |\n\n\
+             연차가 유리합니다."
+        );
+        assert_eq!(assemble_table("A | B 중 하나"), "A | B 중 하나");
+    }
+
+    #[test]
+    fn test_format_from_answer_type() {
+        assert_eq!(answer_format(&AnswerType::List), AnswerFormat::Steps);
+        assert_eq!(answer_format(&AnswerType::Comparison), AnswerFormat::Table);
+        assert_eq!(answer_format(&AnswerType::YesNo), AnswerFormat::YesNo);
+        assert_eq!(answer_format(&AnswerType::SingleFact), AnswerFormat::Prose);
+        assert_eq!(
+            apply(AnswerFormat::YesNo, "예. 가능합니다."),
+            "예. 가능합니다."
+        );
+    }
+}
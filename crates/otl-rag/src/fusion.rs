@@ -0,0 +1,156 @@
+//! Reciprocal Rank Fusion (RRF)
+//!
+//! Vector, graph and keyword search score results on different scales, so
+//! results are fused by rank instead: each list contributes
+//! `weight / (k + rank)` to a result, and results found by several searches
+//! (same content) add up their contributions.
+//!
+//! Fusion is deterministic: equal fused scores keep the order in which the
+//! results were first seen (vector, then graph, then keyword, each by score),
+//! so identical backend results always rank identically.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::HashMap;
+
+use otl_core::{SearchResult, SearchResultType};
+
+use crate::RagConfig;
+
+/// Merge results using Reciprocal Rank Fusion, best first
+///
+/// The fused score replaces each result's score; `rrf_k` and the per-search
+/// weights come from `config`.
+pub fn merge_results(results: Vec<SearchResult>, config: &RagConfig) -> Vec<SearchResult> {
+    // Fused results in first-seen order, indexed by content hash
+    let mut merged: Vec<SearchResult> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    // Sort results by score to get ranks (stable: ties keep backend order)
+    let mut vector_results: Vec<_> = results
+        .iter()
+        .filter(|r| r.result_type == SearchResultType::Vector)
+        .collect();
+    let mut graph_results: Vec<_> = results
+        .iter()
+        .filter(|r| r.result_type == SearchResultType::Graph)
+        .collect();
+    let mut keyword_results: Vec<_> = results
+        .iter()
+        .filter(|r| r.result_type == SearchResultType::Keyword)
+        .collect();
+
+    vector_results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    graph_results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    keyword_results.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    // Calculate RRF scores
+    let k = config.rrf_k;
+    let lists = [
+        (vector_results, config.vector_weight),
+        (graph_results, config.graph_weight),
+        (keyword_results, config.keyword_weight),
+    ];
+
+    for (list, weight) in lists {
+        for (rank, result) in list.into_iter().enumerate() {
+            let rrf_score = weight / (k + rank as f32 + 1.0);
+            let key = hash_content(&result.content);
+            match positions.get(&key) {
+                Some(&position) => merged[position].score += rrf_score,
+                None => {
+                    positions.insert(key, merged.len());
+                    merged.push(SearchResult {
+                        score: rrf_score,
+                        ..result.clone()
+                    });
+                }
+            }
+        }
+    }
+
+    // Sort by RRF score; the sort is stable, so ties stay in first-seen order
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+    merged
+}
+
+/// Simple hash for content deduplication
+fn hash_content(content: &str) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    // Hash first 100 chars for efficiency
+    content
+        .chars()
+        .take(100)
+        .collect::<String>()
+        .hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{DocumentAcl, SourceReference};
+    use uuid::Uuid;
+
+    fn result(content: &str, score: f32, result_type: SearchResultType) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score,
+            source: SourceReference::new(Uuid::new_v4()),
+            acl: DocumentAcl::default(),
+            result_type,
+        }
+    }
+
+    #[test]
+    fn test_content_hashing() {
+        let content1 = "This is some content for testing";
+        let content2 = "This is some content for testing";
+        let content3 = "Different content";
+
+        assert_eq!(hash_content(content1), hash_content(content2));
+        assert_ne!(hash_content(content1), hash_content(content3));
+    }
+
+    #[test]
+    fn test_ties_keep_first_seen_order() {
+        let results = vec![
+            result("병가", 0.5, SearchResultType::Keyword),
+            result("연차", 0.5, SearchResultType::Vector),
+            result("출장", 0.5, SearchResultType::Vector),
+        ];
+        let config = RagConfig {
+            keyword_weight: 1.0,
+            vector_weight: 1.0,
+            ..RagConfig::default()
+        };
+
+        for _ in 0..5 {
+            let merged = merge_results(results.clone(), &config);
+            let contents: Vec<_> = merged.iter().map(|r| r.content.as_str()).collect();
+            assert_eq!(contents, vec!["연차", "병가", "출장"]);
+        }
+    }
+
+    #[test]
+    fn test_results_found_twice_rank_first() {
+        let results = vec![
+            result("연차휴가 15일", 0.9, SearchResultType::Vector),
+            result("병가 신청 절차", 0.8, SearchResultType::Vector),
+            result("병가 신청 절차", 3.0, SearchResultType::Keyword),
+        ];
+
+        let merged = merge_results(results, &RagConfig::default());
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].content, "병가 신청 절차");
+        // 1.0 / 62 from vector search plus 0.8 / 61 from keyword search
+        assert!((merged[0].score - (1.0 / 62.0 + 0.8 / 61.0)).abs() < 1e-6);
+    }
+}
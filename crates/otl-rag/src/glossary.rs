@@ -0,0 +1,222 @@
+//! Glossary fast path for definitional questions
+//!
+//! "연차휴가란 무엇인가요?" or "What is a KPI?" asks for the definition of
+//! one term. When the glossary holds an approved entry for it that the user
+//! may read, the orchestrator answers from the entry and skips retrieval and
+//! generation. Otherwise a well-supported generated answer is proposed as a
+//! glossary candidate for admin review.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::citations;
+use crate::language::PromptTemplate;
+use otl_core::glossary::{normalize_term, GlossaryEntry};
+
+/// Longest term (in characters) considered for lookups and candidates
+const MAX_TERM_CHARS: usize = 40;
+
+/// Most words in a term
+const MAX_TERM_WORDS: usize = 4;
+
+/// Longest candidate definition (in characters)
+const MAX_DEFINITION_CHARS: usize = 500;
+
+/// Korean question endings after the term, longest first
+const KO_TAILS: &[&str] = &[
+    "무엇인가요",
+    "무엇입니까",
+    "무엇이에요",
+    "무엇인지",
+    "뭐예요",
+    "뭐에요",
+    "뭔가요",
+    "무엇",
+    "뭐야",
+    "뭐죠",
+    "뭐지",
+    "뭐",
+    "정의는",
+    "정의",
+    "뜻은",
+    "뜻",
+    "의미는",
+    "의미",
+];
+
+/// Korean particles between the term and the ending, longest first
+const KO_PARTICLES: &[&str] = &["이라는", "라는", "이란", "란", "의", "은", "는", "이", "가"];
+
+/// English question openings before the term
+const EN_PREFIXES: &[&str] = &[
+    "what is the meaning of ",
+    "what is the definition of ",
+    "definition of ",
+    "meaning of ",
+    "what is ",
+    "what's ",
+    "what are ",
+    "define ",
+];
+
+/// Terms a definitional question may be asking about, most likely first
+///
+/// Korean particles are ambiguous (`연차휴가` ends in `가`), so both the
+/// stripped and the unstripped form are returned for lookups.
+pub fn defined_terms(question: &str) -> Vec<String> {
+    let question = question
+        .trim()
+        .trim_end_matches(|c: char| c == '?' || c == '.' || c == '!' || c.is_whitespace());
+    let lower = question.to_lowercase();
+
+    let mut terms = Vec::new();
+    if let Some(prefix) = EN_PREFIXES.iter().find(|p| lower.starts_with(*p)) {
+        let term = lower[prefix.len()..].trim();
+        let term = term
+            .strip_prefix("a ")
+            .or_else(|| term.strip_prefix("an "))
+            .or_else(|| term.strip_prefix("the "))
+            .unwrap_or(term);
+        terms.push(term.to_string());
+    } else if let Some(term) = lower
+        .strip_prefix("what does ")
+        .and_then(|rest| rest.strip_suffix(" mean"))
+    {
+        terms.push(term.trim().to_string());
+    } else if let Some(tail) = KO_TAILS.iter().find(|t| question.ends_with(*t)) {
+        let head = question[..question.len() - tail.len()].trim_end();
+        if let Some(stripped) = KO_PARTICLES
+            .iter()
+            .find_map(|p| head.strip_suffix(p))
+            .filter(|s| !s.trim().is_empty())
+        {
+            terms.push(stripped.trim().to_string());
+        }
+        terms.push(head.to_string());
+    }
+
+    let mut seen = Vec::new();
+    terms
+        .into_iter()
+        .map(|t| normalize_term(&t))
+        .filter(|t| {
+            !t.is_empty()
+                && t.chars().count() <= MAX_TERM_CHARS
+                && t.split_whitespace().count() <= MAX_TERM_WORDS
+        })
+        .filter(|t| {
+            let new = !seen.contains(t);
+            seen.push(t.clone());
+            new
+        })
+        .collect()
+}
+
+/// Answer text for a glossary entry
+///
+/// With `cited`, the definition ends with the marker of the citation to
+/// the entry's source document (`[출처: 1]`).
+pub fn render_glossary_answer(
+    entry: &GlossaryEntry,
+    template: &PromptTemplate,
+    cited: bool,
+) -> String {
+    let mut answer = format!("{}: {}", entry.term, entry.definition.trim());
+    if cited {
+        answer.push(' ');
+        answer.push_str(&template.citation(1));
+    }
+    if let Some(source) = entry.source.as_deref().filter(|s| !s.trim().is_empty()) {
+        answer.push_str(&format!(
+            "\n\n({}: {})",
+            template.source_label,
+            source.trim()
+        ));
+    }
+    answer
+}
+
+/// Definition proposed from a generated answer
+///
+/// The first paragraph without citation markers, cut at a sentence end
+/// within the length limit. `None` when nothing usable remains.
+pub fn candidate_definition(answer: &str) -> Option<String> {
+    let paragraph = answer
+        .split("\n\n")
+        .map(str::trim)
+        .find(|p| !p.is_empty())?;
+    let text = citations::strip_markers(paragraph)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if text.chars().count() <= MAX_DEFINITION_CHARS {
+        return Some(text).filter(|t| !t.is_empty());
+    }
+    let cut: String = text.chars().take(MAX_DEFINITION_CHARS).collect();
+    let end = cut
+        .rfind(['.', '。', '!', '?'])
+        .map(|i| i + 1)
+        .unwrap_or(cut.len());
+    Some(cut[..end].trim().to_string()).filter(|t| !t.is_empty())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::Language;
+
+    #[test]
+    fn test_defined_terms_korean() {
+        assert_eq!(
+            defined_terms("연차휴가란 무엇인가요?"),
+            vec!["연차휴가", "연차휴가란"]
+        );
+        assert_eq!(
+            defined_terms("통상임금의 정의는?"),
+            vec!["통상임금", "통상임금의"]
+        );
+        assert_eq!(defined_terms("연차휴가 뭐야"), vec!["연차휴", "연차휴가"]);
+        assert!(defined_terms("연차휴가 신청 절차").is_empty());
+    }
+
+    #[test]
+    fn test_defined_terms_english() {
+        assert_eq!(defined_terms("What is a KPI?"), vec!["kpi"]);
+        assert_eq!(
+            defined_terms("what does onboarding mean"),
+            vec!["onboarding"]
+        );
+        assert_eq!(defined_terms("Define annual leave."), vec!["annual leave"]);
+        assert!(defined_terms("what is the process to request leave in the sales team").is_empty());
+    }
+
+    #[test]
+    fn test_render_glossary_answer() {
+        let mut entry =
+            GlossaryEntry::new("연차휴가", "1년간 80% 이상 출근한 근로자에게 주는 유급휴가");
+        entry.source = Some("근로기준법 제60조".to_string());
+        let template = PromptTemplate::for_language(Language::Korean);
+
+        assert_eq!(
+            render_glossary_answer(&entry, template, true),
+            "연차휴가: 1년간 80% 이상 출근한 근로자에게 주는 유급휴가 [출처: 1]\n\n(출처: 근로기준법 제60조)"
+        );
+    }
+
+    #[test]
+    fn test_candidate_definition_strips_citations() {
+        let answer = "연차휴가는 유급휴가입니다 [출처: 1]. 15일이 주어집니다[출처: 2][출처: 3].\n\n자세한 내용은 인사팀에 문의하세요.";
+        assert_eq!(
+            candidate_definition(answer).unwrap(),
+            "연차휴가는 유급휴가입니다. 15일이 주어집니다."
+        );
+
+        let long = "가".repeat(300) + ". " + &"나".repeat(300);
+        assert_eq!(candidate_definition(&long).unwrap(), "가".repeat(300) + ".");
+        assert!(candidate_definition("  [출처: 1] ").is_none());
+    }
+}
@@ -0,0 +1,659 @@
+//! Entity-aware graph retrieval
+//!
+//! The graph leg of the hybrid search: entities mentioned in the question
+//! are resolved to graph nodes, their neighborhood is expanded for
+//! `graph_depth` hops, and every relation found is rendered as a sentence
+//! together with the passage it was extracted from.
+//!
+//! Structure edges (`hasChunk`, `mentions`, ...) are never followed. Beyond
+//! the first hop only predicates the ontology declares for the class of the
+//! node being left are followed, so the expansion stays on ontology paths
+//! instead of fanning out over every extracted relation.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::{DetectedEntity, QueryAnalysis};
+use otl_core::structure::is_structure_predicate;
+use otl_core::{
+    AccessLevel, DocumentAcl, Entity, GraphContextBackend, OntologyClass, Provenance, Result,
+    SearchResult, SearchResultType, SourceReference, Triple,
+};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Maximum number of entities resolved from the question
+pub const MAX_SEEDS: usize = 10;
+
+/// Maximum number of new nodes expanded per hop
+pub const MAX_FRONTIER: usize = 50;
+
+/// Score multiplier per hop away from the resolved entities
+const HOP_DECAY: f32 = 0.8;
+
+/// Entity properties that are extraction bookkeeping, not content
+const HIDDEN_PROPERTIES: [&str; 3] = ["text", "start", "end"];
+
+// ============================================================================
+// Predicate Policy
+// ============================================================================
+
+/// Which predicates to follow from a node of a given class
+#[derive(Debug, Clone, Default)]
+pub struct PredicatePolicy {
+    /// Class -> object properties with the class (or an ancestor) as domain or range
+    by_class: HashMap<String, HashSet<String>>,
+}
+
+impl PredicatePolicy {
+    /// Build the policy from ontology classes
+    pub fn from_ontology(classes: &[OntologyClass]) -> Self {
+        let parents: HashMap<&str, &str> = classes
+            .iter()
+            .filter_map(|c| Some((c.id.as_str(), c.parent.as_deref()?)))
+            .collect();
+
+        let mut declared: HashMap<String, HashSet<String>> = HashMap::new();
+        for class in classes {
+            for property in class.properties.iter().filter(|p| p.range.is_some()) {
+                declared
+                    .entry(class.id.clone())
+                    .or_default()
+                    .insert(property.name.clone());
+                if let Some(range) = &property.range {
+                    declared
+                        .entry(range.clone())
+                        .or_default()
+                        .insert(property.name.clone());
+                }
+            }
+        }
+
+        // Subclasses inherit the predicates of their ancestors
+        let mut by_class = HashMap::new();
+        for class in classes {
+            let mut predicates = HashSet::new();
+            let mut current = Some(class.id.as_str());
+            let mut depth = 0;
+            while let Some(id) = current.filter(|_| depth <= classes.len()) {
+                predicates.extend(declared.get(id).into_iter().flatten().cloned());
+                current = parents.get(id).copied();
+                depth += 1;
+            }
+            by_class.insert(class.id.clone(), predicates);
+        }
+        for (class, predicates) in declared {
+            by_class.entry(class).or_insert(predicates);
+        }
+
+        Self { by_class }
+    }
+
+    /// Whether to follow `predicate` from a node of `class` on hop `hop` (1-based)
+    pub fn follows(&self, class: Option<&str>, predicate: &str, hop: u32) -> bool {
+        if is_structure_predicate(predicate) {
+            return false;
+        }
+        if hop <= 1 || self.by_class.is_empty() {
+            return true;
+        }
+        class
+            .and_then(|c| self.by_class.get(c))
+            .is_some_and(|predicates| predicates.contains(predicate))
+    }
+}
+
+// ============================================================================
+// Subgraph Expansion
+// ============================================================================
+
+/// Relation reached during expansion
+#[derive(Debug, Clone)]
+pub struct GraphFact {
+    pub triple: Triple,
+    /// Hop on which the relation was reached (1 = touches a resolved entity)
+    pub hop: u32,
+}
+
+impl GraphFact {
+    /// Ranking score: extraction confidence decayed by distance
+    pub fn score(&self) -> f32 {
+        self.triple.confidence * HOP_DECAY.powi(self.hop.saturating_sub(1) as i32)
+    }
+}
+
+/// Neighborhood of the entities resolved from a question
+#[derive(Debug, Clone, Default)]
+pub struct Subgraph {
+    /// Entities resolved from the question
+    pub seeds: Vec<Uuid>,
+    /// All entities loaded during expansion
+    pub entities: HashMap<Uuid, Entity>,
+    /// Relations in the order they were reached
+    pub facts: Vec<GraphFact>,
+}
+
+/// Resolve the question's entities to graph nodes
+///
+/// Detected entity mentions are resolved one by one (honoring their type
+/// when known); without any, entities whose label occurs in the question
+/// or matches a synonym of its keywords are used.
+pub async fn resolve_seeds(
+    backend: &dyn GraphContextBackend,
+    analysis: &QueryAnalysis,
+) -> Result<Vec<Entity>> {
+    let mut seeds: Vec<Entity> = Vec::new();
+
+    if analysis.detected_entities.is_empty() {
+        seeds = backend
+            .resolve_entities(&analysis.question, MAX_SEEDS)
+            .await?;
+        for synonym in &analysis.synonyms {
+            seeds.extend(backend.resolve_entities(synonym, MAX_SEEDS).await?);
+        }
+    } else {
+        for mention in &analysis.detected_entities {
+            let candidates = backend.resolve_entities(&mention.text, MAX_SEEDS).await?;
+            seeds.extend(
+                candidates
+                    .into_iter()
+                    .filter(|e| matches_mention(e, mention)),
+            );
+        }
+    }
+
+    let mut seen = HashSet::new();
+    seeds.retain(|e| seen.insert(e.id));
+    seeds.truncate(MAX_SEEDS);
+    Ok(seeds)
+}
+
+fn matches_mention(entity: &Entity, mention: &DetectedEntity) -> bool {
+    mention
+        .entity_type
+        .as_deref()
+        .map_or(true, |t| t == entity.class)
+}
+
+/// Expand the neighborhood of `seeds` for `depth` hops
+pub async fn expand(
+    backend: &dyn GraphContextBackend,
+    seeds: Vec<Entity>,
+    depth: u32,
+    policy: &PredicatePolicy,
+) -> Result<Subgraph> {
+    let mut subgraph = Subgraph {
+        seeds: seeds.iter().map(|e| e.id).collect(),
+        entities: seeds.into_iter().map(|e| (e.id, e)).collect(),
+        facts: Vec::new(),
+    };
+    let mut visited: HashSet<Uuid> = subgraph.seeds.iter().copied().collect();
+    let mut seen_triples = HashSet::new();
+    let mut frontier = subgraph.seeds.clone();
+
+    for hop in 1..=depth {
+        if frontier.is_empty() {
+            break;
+        }
+        let on_frontier: HashSet<Uuid> = frontier.iter().copied().collect();
+        let mut next = Vec::new();
+
+        for triple in backend.incident_triples(&frontier).await? {
+            let from = if on_frontier.contains(&triple.subject) {
+                triple.subject
+            } else {
+                triple.object
+            };
+            let class = subgraph.entities.get(&from).map(|e| e.class.as_str());
+            if !policy.follows(class, &triple.predicate, hop) || !seen_triples.insert(triple.id) {
+                continue;
+            }
+
+            for end in [triple.subject, triple.object] {
+                if visited.insert(end) {
+                    next.push(end);
+                }
+            }
+            subgraph.facts.push(GraphFact { triple, hop });
+        }
+
+        next.truncate(MAX_FRONTIER);
+        for entity in backend.get_entities(&next).await? {
+            subgraph.entities.insert(entity.id, entity);
+        }
+        frontier = next;
+    }
+
+    Ok(subgraph)
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Render the subgraph as natural-language search results
+///
+/// Each relation becomes one result, best first, citing the strongest
+/// evidence recorded for it. Resolved entities without any relation are
+/// described by their properties.
+pub fn render(subgraph: &Subgraph, provenance: &[Provenance], limit: usize) -> Vec<SearchResult> {
+    let mut evidence: HashMap<Uuid, &Provenance> = HashMap::new();
+    for record in provenance {
+        evidence
+            .entry(record.triple_id)
+            .and_modify(|best| {
+                if record.confidence > best.confidence {
+                    *best = record;
+                }
+            })
+            .or_insert(record);
+    }
+
+    let mut facts: Vec<&GraphFact> = subgraph.facts.iter().collect();
+    facts.sort_by(|a, b| b.score().total_cmp(&a.score()));
+
+    let mut results: Vec<SearchResult> = facts
+        .into_iter()
+        .map(|fact| {
+            let best = evidence.get(&fact.triple.id).copied();
+            let source = best
+                .map(|p| p.source.clone())
+                .unwrap_or_else(|| fact.triple.source.clone());
+            graph_result(describe_fact(subgraph, fact, best), fact.score(), source)
+        })
+        .collect();
+
+    let connected: HashSet<Uuid> = subgraph
+        .facts
+        .iter()
+        .flat_map(|f| [f.triple.subject, f.triple.object])
+        .collect();
+    for entity in subgraph
+        .seeds
+        .iter()
+        .filter(|id| !connected.contains(id))
+        .filter_map(|id| subgraph.entities.get(id))
+    {
+        results.push(graph_result(
+            describe_entity(entity),
+            entity.source.confidence,
+            entity.source.clone(),
+        ));
+    }
+
+    results.truncate(limit);
+    results
+}
+
+fn graph_result(content: String, score: f32, source: SourceReference) -> SearchResult {
+    SearchResult {
+        content,
+        score,
+        source,
+        acl: DocumentAcl {
+            access_level: AccessLevel::Internal,
+            ..Default::default()
+        },
+        result_type: SearchResultType::Graph,
+    }
+}
+
+/// `"병가" (LeaveType) requires document "진단서" (Document).` plus evidence
+fn describe_fact(subgraph: &Subgraph, fact: &GraphFact, evidence: Option<&Provenance>) -> String {
+    let node = |id: &Uuid| match subgraph.entities.get(id) {
+        Some(entity) => format!("\"{}\" ({})", entity_label(entity), entity.class),
+        None => format!("\"{id}\""),
+    };
+    let mut content = format!(
+        "{} {} {}.",
+        node(&fact.triple.subject),
+        predicate_phrase(&fact.triple.predicate),
+        node(&fact.triple.object)
+    );
+
+    if let Some(snippet) = evidence.and_then(|p| p.snippet.as_deref()) {
+        content.push_str(&format!("\nEvidence: \"{}\"", snippet.trim()));
+    }
+    if let Some(location) = evidence.and_then(|p| source_location(&p.source)) {
+        content.push_str(&format!(" ({location})"));
+    }
+    content
+}
+
+/// `"병가" (LeaveType): days: 10`
+fn describe_entity(entity: &Entity) -> String {
+    let mut properties: Vec<String> = entity
+        .properties
+        .iter()
+        .filter(|(k, _)| !HIDDEN_PROPERTIES.contains(&k.as_str()))
+        .map(|(k, v)| match v {
+            serde_json::Value::String(s) => format!("{k}: {s}"),
+            other => format!("{k}: {other}"),
+        })
+        .collect();
+    properties.sort();
+
+    let mut content = format!("\"{}\" ({})", entity_label(entity), entity.class);
+    if !properties.is_empty() {
+        content.push_str(": ");
+        content.push_str(&properties.join(", "));
+    }
+    content
+}
+
+fn entity_label(entity: &Entity) -> String {
+    ["text", "name", "label"]
+        .iter()
+        .find_map(|key| entity.properties.get(*key)?.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| entity.class.clone())
+}
+
+fn source_location(source: &SourceReference) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(section) = &source.section {
+        parts.push(section.clone());
+    }
+    if let Some(page) = source.page {
+        parts.push(format!("p. {page}"));
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// `requiresDocument` -> `requires document`
+fn predicate_phrase(predicate: &str) -> String {
+    let mut phrase = String::with_capacity(predicate.len() + 4);
+    for c in predicate.chars() {
+        if c.is_uppercase() && !phrase.is_empty() {
+            phrase.push(' ');
+        }
+        if c == '_' {
+            phrase.push(' ');
+        } else {
+            phrase.extend(c.to_lowercase());
+        }
+    }
+    phrase
+}
+
+/// Parse subject and object labels from a rendered relation
+pub(crate) fn parse_fact(content: &str) -> Option<(&str, &str)> {
+    let first_line = content.lines().next()?;
+    let mut quoted = first_line.split('"').skip(1).step_by(2);
+    let subject = quoted.next()?;
+    let object = quoted.next()?;
+    (!subject.is_empty() && !object.is_empty()).then_some((subject, object))
+}
+
+/// Parse the entity label from a rendered entity description
+pub(crate) fn parse_entity(content: &str) -> Option<&str> {
+    let first_line = content.lines().next()?;
+    let mut quoted = first_line.split('"').skip(1).step_by(2);
+    let label = quoted.next()?;
+    (quoted.next().is_none() && !label.is_empty()).then_some(label)
+}
+
+// ============================================================================
+// Retrieval
+// ============================================================================
+
+/// Entity-aware graph context for a query
+pub async fn retrieve(
+    backend: &dyn GraphContextBackend,
+    analysis: &QueryAnalysis,
+    policy: &PredicatePolicy,
+    depth: u32,
+    limit: usize,
+) -> Result<Vec<SearchResult>> {
+    let seeds = resolve_seeds(backend, analysis).await?;
+    if seeds.is_empty() {
+        return Ok(Vec::new());
+    }
+    tracing::debug!("Graph context: {} entities resolved", seeds.len());
+
+    let subgraph = expand(backend, seeds, depth, policy).await?;
+    let triple_ids: Vec<Uuid> = subgraph.facts.iter().map(|f| f.triple.id).collect();
+    let provenance = backend.provenance_for(&triple_ids).await?;
+    tracing::debug!(
+        "Graph context: {} relations within {} hops",
+        subgraph.facts.len(),
+        depth
+    );
+
+    Ok(render(&subgraph, &provenance, limit))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnswerType, QueryIntent};
+    use async_trait::async_trait;
+    use otl_core::structure::MENTIONS;
+    use otl_core::{Cardinality, DataType, Language, PropertyDefinition};
+
+    /// In-memory graph
+    #[derive(Default)]
+    struct MemoryGraph {
+        entities: Vec<Entity>,
+        triples: Vec<Triple>,
+        provenance: Vec<Provenance>,
+    }
+
+    impl MemoryGraph {
+        fn entity(&mut self, class: &str, label: &str) -> Uuid {
+            let mut entity = Entity::new(class, SourceReference::new(Uuid::new_v4()));
+            entity
+                .properties
+                .insert("text".to_string(), serde_json::json!(label));
+            let id = entity.id;
+            self.entities.push(entity);
+            id
+        }
+
+        fn relate(&mut self, subject: Uuid, predicate: &str, object: Uuid) -> Uuid {
+            let triple = Triple::new(
+                subject,
+                predicate,
+                object,
+                SourceReference::new(Uuid::new_v4()),
+                0.9,
+            );
+            let id = triple.id;
+            self.triples.push(triple);
+            id
+        }
+    }
+
+    #[async_trait]
+    impl GraphContextBackend for MemoryGraph {
+        async fn resolve_entities(&self, text: &str, limit: usize) -> Result<Vec<Entity>> {
+            Ok(self
+                .entities
+                .iter()
+                .filter(|e| text.contains(&entity_label(e)))
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+
+        async fn get_entities(&self, ids: &[Uuid]) -> Result<Vec<Entity>> {
+            Ok(self
+                .entities
+                .iter()
+                .filter(|e| ids.contains(&e.id))
+                .cloned()
+                .collect())
+        }
+
+        async fn incident_triples(&self, ids: &[Uuid]) -> Result<Vec<Triple>> {
+            Ok(self
+                .triples
+                .iter()
+                .filter(|t| ids.contains(&t.subject) || ids.contains(&t.object))
+                .cloned()
+                .collect())
+        }
+
+        async fn provenance_for(&self, triple_ids: &[Uuid]) -> Result<Vec<Provenance>> {
+            Ok(self
+                .provenance
+                .iter()
+                .filter(|p| triple_ids.contains(&p.triple_id))
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn analysis(question: &str) -> QueryAnalysis {
+        QueryAnalysis {
+            question: question.to_string(),
+            intent: QueryIntent::General,
+            intent_confidence: 1.0,
+            intent_source: crate::IntentSource::Rules,
+            detected_entities: Vec::new(),
+            keywords: Vec::new(),
+            synonyms: Vec::new(),
+            expected_answer_type: AnswerType::Unknown,
+            language: Language::Korean,
+        }
+    }
+
+    fn class(id: &str, properties: &[(&str, &str)]) -> OntologyClass {
+        OntologyClass {
+            id: id.to_string(),
+            label: id.to_string(),
+            description: None,
+            parent: None,
+            properties: properties
+                .iter()
+                .map(|(name, range)| PropertyDefinition {
+                    name: name.to_string(),
+                    data_type: DataType::ObjectReference(range.to_string()),
+                    cardinality: Cardinality::Many,
+                    range: Some(range.to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_predicate_policy() {
+        let mut annual = class("AnnualLeave", &[]);
+        annual.parent = Some("LeaveType".to_string());
+        let classes = vec![
+            class("LeaveType", &[("requires", "ApprovalProcess")]),
+            class("ApprovalProcess", &[]),
+            annual,
+        ];
+        let policy = PredicatePolicy::from_ontology(&classes);
+
+        assert!(!policy.follows(Some("LeaveType"), MENTIONS, 1));
+        assert!(policy.follows(Some("LeaveType"), "relatedTo", 1));
+        assert!(policy.follows(Some("LeaveType"), "requires", 2));
+        assert!(policy.follows(Some("AnnualLeave"), "requires", 2));
+        assert!(policy.follows(Some("ApprovalProcess"), "requires", 2));
+        assert!(!policy.follows(Some("LeaveType"), "relatedTo", 2));
+        assert!(!policy.follows(None, "requires", 2));
+
+        // Without an ontology every extracted relation is followed
+        assert!(PredicatePolicy::default().follows(None, "relatedTo", 3));
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_renders_subgraph_with_provenance() {
+        let mut graph = MemoryGraph::default();
+        let sick = graph.entity("LeaveType", "병가");
+        let approval = graph.entity("ApprovalProcess", "부서장 승인");
+        let manager = graph.entity("Position", "부서장");
+        let unrelated = graph.entity("Position", "인사팀장");
+        let chunk = graph.entity("Chunk", "chunk");
+        let requires = graph.relate(sick, "requires", approval);
+        graph.relate(approval, "approvedBy", manager);
+        graph.relate(manager, "reportsTo", unrelated);
+        graph.relate(chunk, MENTIONS, sick);
+        graph.provenance.push(
+            Provenance::new(
+                requires,
+                SourceReference::new(Uuid::new_v4())
+                    .with_page(3)
+                    .with_confidence(0.95),
+            )
+            .with_snippet("병가는 부서장 승인을 받아야 한다."),
+        );
+
+        let classes = vec![
+            class("LeaveType", &[("requires", "ApprovalProcess")]),
+            class("ApprovalProcess", &[("approvedBy", "Position")]),
+        ];
+        let policy = PredicatePolicy::from_ontology(&classes);
+
+        let results = retrieve(&graph, &analysis("병가 신청 방법은?"), &policy, 2, 10)
+            .await
+            .unwrap();
+
+        // requires (hop 1) and approvedBy (hop 2, declared); not reportsTo (hop 3)
+        // nor the structural mention
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|r| r.result_type == SearchResultType::Graph));
+        assert_eq!(
+            results[0].content,
+            "\"병가\" (LeaveType) requires \"부서장 승인\" (ApprovalProcess).\n\
+             Evidence: \"병가는 부서장 승인을 받아야 한다.\" (p. 3)"
+        );
+        assert_eq!(results[0].source.page, Some(3));
+        assert_eq!(
+            parse_fact(&results[0].content),
+            Some(("병가", "부서장 승인"))
+        );
+        assert!(results[1].content.contains("approved by"));
+        assert!(results[1].score < results[0].score);
+
+        let none = retrieve(&graph, &analysis("휴직 규정"), &policy, 2, 10)
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_seeds_resolve_through_synonyms() {
+        let mut graph = MemoryGraph::default();
+        let annual = graph.entity("LeaveType", "연차휴가");
+
+        let mut query = analysis("연가 신청");
+        assert!(resolve_seeds(&graph, &query).await.unwrap().is_empty());
+
+        query.synonyms = vec!["연차".to_string(), "연차휴가".to_string()];
+        let seeds = resolve_seeds(&graph, &query).await.unwrap();
+        assert_eq!(seeds.len(), 1);
+        assert_eq!(seeds[0].id, annual);
+    }
+
+    #[tokio::test]
+    async fn test_unconnected_seed_is_described() {
+        let mut graph = MemoryGraph::default();
+        graph.entity("LeaveType", "경조사휴가");
+        graph.entities[0]
+            .properties
+            .insert("days".to_string(), serde_json::json!(5));
+
+        let results = retrieve(
+            &graph,
+            &analysis("경조사휴가 일수"),
+            &PredicatePolicy::default(),
+            2,
+            10,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content, "\"경조사휴가\" (LeaveType): days: 5");
+        assert_eq!(parse_entity(&results[0].content), Some("경조사휴가"));
+        assert_eq!(parse_fact(&results[0].content), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Retrieval backend health
+//!
+//! The retrieval backends are searched concurrently. One that fails is
+//! skipped and the answer is built from the others; the skip is reported as
+//! a response warning and, for debug queries, as per-backend health in the
+//! trace. Strict queries fail on the first unavailable backend instead.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_core::{BackendHealth, BackendStatus, OtlError, Result, SearchResult, SearchResultType};
+use std::time::Duration;
+
+/// Name of a backend in warnings and errors
+fn backend_name(backend: &SearchResultType) -> &'static str {
+    match backend {
+        SearchResultType::Vector => "vector",
+        SearchResultType::Graph => "graph",
+        SearchResultType::Keyword => "keyword",
+        SearchResultType::Faq => "faq",
+    }
+}
+
+/// Health of a backend from its search outcome
+///
+/// `enabled` is false for optional backends that are not configured; they
+/// return no results without failing.
+pub(crate) fn check(
+    backend: SearchResultType,
+    enabled: bool,
+    results: &Result<Vec<SearchResult>>,
+    elapsed: Duration,
+) -> BackendHealth {
+    let (status, count, error) = match results {
+        _ if !enabled => (BackendStatus::Disabled, 0, None),
+        Ok(results) => (BackendStatus::Ok, results.len(), None),
+        Err(e) => {
+            tracing::warn!(
+                "{} search failed, continuing without it: {}",
+                backend_name(&backend),
+                e
+            );
+            (BackendStatus::Failed, 0, Some(e.to_string()))
+        }
+    };
+    BackendHealth {
+        backend,
+        status,
+        results: count,
+        duration_ms: elapsed.as_secs_f64() * 1000.0,
+        error,
+    }
+}
+
+/// Response warnings for the failed backends
+///
+/// Error details stay in the trace, which only privileged callers see.
+pub(crate) fn warnings(health: &[BackendHealth]) -> Vec<String> {
+    health
+        .iter()
+        .filter(|h| h.status == BackendStatus::Failed)
+        .map(|h| {
+            format!(
+                "{} search unavailable; the answer may be incomplete",
+                backend_name(&h.backend)
+            )
+        })
+        .collect()
+}
+
+/// Error failing a strict query, if a backend failed
+pub(crate) fn strict_error(health: &[BackendHealth]) -> Option<OtlError> {
+    health
+        .iter()
+        .find(|h| h.status == BackendStatus::Failed)
+        .map(|h| {
+            OtlError::SearchError(format!(
+                "{} search failed: {}",
+                backend_name(&h.backend),
+                h.error.as_deref().unwrap_or("unknown error")
+            ))
+        })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health() -> Vec<BackendHealth> {
+        let ms = Duration::from_millis(5);
+        vec![
+            check(SearchResultType::Vector, true, &Ok(Vec::new()), ms),
+            check(
+                SearchResultType::Graph,
+                true,
+                &Err(OtlError::DatabaseError("connection refused".into())),
+                ms,
+            ),
+            check(SearchResultType::Keyword, false, &Ok(Vec::new()), ms),
+        ]
+    }
+
+    #[test]
+    fn test_check_statuses() {
+        let health = health();
+
+        assert_eq!(health[0].status, BackendStatus::Ok);
+        assert_eq!(health[1].status, BackendStatus::Failed);
+        assert!(health[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("connection refused"));
+        assert_eq!(health[2].status, BackendStatus::Disabled);
+        assert_eq!(health[0].duration_ms, 5.0);
+    }
+
+    #[test]
+    fn test_warnings_name_failed_backends_only() {
+        let warnings = warnings(&health());
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("graph search unavailable"));
+        assert!(!warnings[0].contains("connection refused"));
+    }
+
+    #[test]
+    fn test_strict_error() {
+        let error = strict_error(&health()).unwrap();
+        assert!(matches!(error, OtlError::SearchError(ref m) if m.contains("graph")));
+
+        assert!(strict_error(&health()[..1]).is_none());
+    }
+}
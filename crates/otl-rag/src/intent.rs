@@ -0,0 +1,435 @@
+//! Query intent classification
+//!
+//! Intent drives the expected answer type, structured answers and follow-up
+//! suggestions. The keyword rules that used to be the only detector remain as
+//! [`rule_intent`], the fallback for any [`IntentClassifier`]. The built-in
+//! statistical classifier is a multinomial naive Bayes model over word and
+//! character-bigram features (bigrams make it robust to Korean particles
+//! without a morphological analyzer). It is trained from a labeled CSV and can
+//! keep learning from user feedback.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::QueryIntent;
+use async_trait::async_trait;
+use otl_core::{OtlError, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Confidence reported when a keyword rule matches
+pub const RULE_CONFIDENCE: f32 = 0.6;
+
+/// Confidence reported when no keyword rule matches
+pub const RULE_FALLBACK_CONFIDENCE: f32 = 0.3;
+
+// ============================================================================
+// Intent labels
+// ============================================================================
+
+impl QueryIntent {
+    /// All intents, in label order
+    pub const ALL: [QueryIntent; 6] = [
+        QueryIntent::Procedural,
+        QueryIntent::Factual,
+        QueryIntent::Comparative,
+        QueryIntent::Conditional,
+        QueryIntent::Definitional,
+        QueryIntent::General,
+    ];
+
+    /// Lowercase label used in training data
+    pub fn label(&self) -> &'static str {
+        match self {
+            QueryIntent::Procedural => "procedural",
+            QueryIntent::Factual => "factual",
+            QueryIntent::Comparative => "comparative",
+            QueryIntent::Conditional => "conditional",
+            QueryIntent::Definitional => "definitional",
+            QueryIntent::General => "general",
+        }
+    }
+}
+
+impl std::str::FromStr for QueryIntent {
+    type Err = OtlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let label = s.trim().to_lowercase();
+        QueryIntent::ALL
+            .into_iter()
+            .find(|intent| intent.label() == label)
+            .ok_or_else(|| OtlError::ValidationError(format!("Unknown intent: {s}")))
+    }
+}
+
+// ============================================================================
+// Classifier trait
+// ============================================================================
+
+/// Where the intent of a query analysis came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntentSource {
+    /// Keyword rules
+    Rules,
+    /// A trained classifier
+    Classifier,
+}
+
+/// Predicted intent with its confidence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntentPrediction {
+    /// Most likely intent
+    pub intent: QueryIntent,
+    /// Probability of the intent (0.0 - 1.0)
+    pub confidence: f32,
+}
+
+/// Pluggable query intent classifier
+#[async_trait]
+pub trait IntentClassifier: Send + Sync {
+    /// Predict the intent of a question; `None` when the classifier has no
+    /// opinion (e.g. it has not been trained yet)
+    async fn classify(&self, question: &str) -> Result<Option<IntentPrediction>>;
+
+    /// Learn from a labeled question, e.g. a user correcting the intent.
+    /// Classifiers that cannot learn online ignore it.
+    fn learn(&self, _question: &str, _intent: QueryIntent) {}
+
+    /// Classifier name for logs
+    fn name(&self) -> &str;
+}
+
+// ============================================================================
+// Keyword rules
+// ============================================================================
+
+/// Detect the intent with keyword rules
+pub fn rule_intent(question: &str) -> IntentPrediction {
+    let question_lower = question.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| question_lower.contains(w));
+
+    let intent = if has(&["어떻게", "절차", "방법", "how"]) {
+        QueryIntent::Procedural
+    } else if has(&["차이", "비교", "vs"]) {
+        QueryIntent::Comparative
+    } else if has(&["무엇", "뭐", "what is"]) {
+        QueryIntent::Definitional
+    } else if has(&["며칠", "몇", "언제"]) {
+        QueryIntent::Factual
+    } else if has(&["경우", "만약", "if"]) {
+        QueryIntent::Conditional
+    } else {
+        return IntentPrediction {
+            intent: QueryIntent::General,
+            confidence: RULE_FALLBACK_CONFIDENCE,
+        };
+    };
+
+    IntentPrediction {
+        intent,
+        confidence: RULE_CONFIDENCE,
+    }
+}
+
+/// Keyword rules as a classifier
+#[derive(Debug, Default)]
+pub struct RuleIntentClassifier;
+
+#[async_trait]
+impl IntentClassifier for RuleIntentClassifier {
+    async fn classify(&self, question: &str) -> Result<Option<IntentPrediction>> {
+        Ok(Some(rule_intent(question)))
+    }
+
+    fn name(&self) -> &str {
+        "rules"
+    }
+}
+
+// ============================================================================
+// Naive Bayes classifier
+// ============================================================================
+
+/// Word and character-bigram features of a question
+fn features(question: &str) -> Vec<String> {
+    let mut features = Vec::new();
+    for word in question
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let word = word.to_lowercase();
+        let chars: Vec<char> = word.chars().collect();
+        features.extend(
+            chars
+                .windows(2)
+                .map(|pair| format!("#{}{}", pair[0], pair[1])),
+        );
+        features.push(word);
+    }
+    features
+}
+
+/// Token counts per intent
+#[derive(Debug, Default)]
+struct NaiveBayesModel {
+    documents: HashMap<QueryIntent, u32>,
+    feature_counts: HashMap<QueryIntent, HashMap<String, u32>>,
+    feature_totals: HashMap<QueryIntent, u32>,
+    vocabulary: HashSet<String>,
+}
+
+impl NaiveBayesModel {
+    fn observe(&mut self, question: &str, intent: QueryIntent) {
+        *self.documents.entry(intent).or_default() += 1;
+        let counts = self.feature_counts.entry(intent).or_default();
+        for feature in features(question) {
+            *counts.entry(feature.clone()).or_default() += 1;
+            *self.feature_totals.entry(intent).or_default() += 1;
+            self.vocabulary.insert(feature);
+        }
+    }
+
+    fn predict(&self, question: &str) -> Option<IntentPrediction> {
+        let total_documents: u32 = self.documents.values().sum();
+        if total_documents == 0 {
+            return None;
+        }
+        let features = features(question);
+        let vocabulary = self.vocabulary.len().max(1) as f64;
+
+        // Log posterior per intent with Laplace smoothing
+        let scores: Vec<(QueryIntent, f64)> = self
+            .documents
+            .iter()
+            .map(|(intent, &documents)| {
+                let counts = &self.feature_counts[intent];
+                let total = f64::from(self.feature_totals.get(intent).copied().unwrap_or(0));
+                let prior = (f64::from(documents) / f64::from(total_documents)).ln();
+                let likelihood: f64 = features
+                    .iter()
+                    .filter(|f| self.vocabulary.contains(*f))
+                    .map(|f| {
+                        let count = f64::from(counts.get(f).copied().unwrap_or(0));
+                        ((count + 1.0) / (total + vocabulary)).ln()
+                    })
+                    .sum();
+                (*intent, prior + likelihood)
+            })
+            .collect();
+
+        let max = scores.iter().map(|(_, s)| *s).fold(f64::MIN, f64::max);
+        let normalizer: f64 = scores.iter().map(|(_, s)| (s - max).exp()).sum();
+        scores
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(intent, score)| IntentPrediction {
+                intent,
+                confidence: ((score - max).exp() / normalizer) as f32,
+            })
+    }
+}
+
+/// Multinomial naive Bayes intent classifier
+#[derive(Debug, Default)]
+pub struct NaiveBayesIntentClassifier {
+    model: RwLock<NaiveBayesModel>,
+}
+
+impl NaiveBayesIntentClassifier {
+    /// Train on labeled questions
+    pub fn train<S: AsRef<str>>(examples: impl IntoIterator<Item = (S, QueryIntent)>) -> Self {
+        let mut model = NaiveBayesModel::default();
+        for (question, intent) in examples {
+            model.observe(question.as_ref(), intent);
+        }
+        Self {
+            model: RwLock::new(model),
+        }
+    }
+
+    /// Train from CSV text with `question,intent` rows (header optional)
+    pub fn from_csv(text: &str) -> Result<Self> {
+        Ok(Self::train(parse_training_csv(text)?))
+    }
+
+    /// Train from a CSV file (see [`Self::from_csv`])
+    pub fn from_csv_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            OtlError::ValidationError(format!(
+                "Failed to read intent training data {}: {e}",
+                path.display()
+            ))
+        })?;
+        Self::from_csv(&text)
+    }
+
+    /// Number of training examples seen
+    pub fn examples(&self) -> u32 {
+        self.model
+            .read()
+            .map(|m| m.documents.values().sum())
+            .unwrap_or(0)
+    }
+}
+
+#[async_trait]
+impl IntentClassifier for NaiveBayesIntentClassifier {
+    async fn classify(&self, question: &str) -> Result<Option<IntentPrediction>> {
+        let model = self
+            .model
+            .read()
+            .map_err(|_| OtlError::SearchError("Intent model lock poisoned".to_string()))?;
+        Ok(model.predict(question))
+    }
+
+    fn learn(&self, question: &str, intent: QueryIntent) {
+        if let Ok(mut model) = self.model.write() {
+            model.observe(question, intent);
+        }
+    }
+
+    fn name(&self) -> &str {
+        "naive_bayes"
+    }
+}
+
+// ============================================================================
+// Training data
+// ============================================================================
+
+/// Parse `question,intent` CSV rows
+///
+/// Fields may be double-quoted (with `""` escaping a quote). A first row
+/// whose intent column is not a known intent is treated as a header; blank
+/// lines and lines starting with `#` are skipped.
+pub fn parse_training_csv(text: &str) -> Result<Vec<(String, QueryIntent)>> {
+    let mut examples = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        let (question, label) = match fields.as_slice() {
+            [question, label, ..] => (question.trim(), label.trim()),
+            _ => {
+                return Err(OtlError::ValidationError(format!(
+                    "Line {}: expected `question,intent`",
+                    number + 1
+                )))
+            }
+        };
+        match label.parse::<QueryIntent>() {
+            Ok(intent) if !question.is_empty() => examples.push((question.to_string(), intent)),
+            Ok(_) => {}
+            Err(_) if examples.is_empty() && number == 0 => {} // header
+            Err(e) => {
+                return Err(OtlError::ValidationError(format!(
+                    "Line {}: {e}",
+                    number + 1
+                )))
+            }
+        }
+    }
+    Ok(examples)
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRAINING: &str = "\
+question,intent
+연차휴가 신청은 어떻게 하나요?,procedural
+병가를 내려면 무엇을 제출해야 하나요?,procedural
+출장비 정산 절차를 알려주세요,procedural
+연차휴가는 며칠인가요?,factual
+경조휴가 일수가 몇 일이죠?,factual
+육아휴직 기간은 얼마나 되나요?,factual
+연차와 반차의 차이는?,comparative
+병가와 공가를 비교해 주세요,comparative
+\"Annual leave vs sick leave, which is longer?\",comparative
+";
+
+    #[test]
+    fn test_rule_intent() {
+        assert_eq!(
+            rule_intent("연차휴가 신청 절차가 어떻게 되나요?").intent,
+            QueryIntent::Procedural
+        );
+        assert_eq!(
+            rule_intent("연차는 며칠인가요?").intent,
+            QueryIntent::Factual
+        );
+        let general = rule_intent("인사팀 연락처");
+        assert_eq!(general.intent, QueryIntent::General);
+        assert_eq!(general.confidence, RULE_FALLBACK_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_parse_training_csv() {
+        let examples = parse_training_csv(TRAINING).unwrap();
+        assert_eq!(examples.len(), 9);
+        assert_eq!(
+            examples[8].0,
+            "Annual leave vs sick leave, which is longer?"
+        );
+        assert_eq!(examples[8].1, QueryIntent::Comparative);
+
+        let err = parse_training_csv("연차는 며칠?,factual\n반차는?,unknown").unwrap_err();
+        assert!(err.to_string().contains("Line 2"));
+    }
+
+    #[tokio::test]
+    async fn test_naive_bayes_classifies_unseen_questions() {
+        let classifier = NaiveBayesIntentClassifier::from_csv(TRAINING).unwrap();
+        assert_eq!(classifier.examples(), 9);
+
+        // Shares no rule keyword, only learned features
+        let prediction = classifier
+            .classify("육아휴직 일수는 얼마나 되나요?")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(prediction.intent, QueryIntent::Factual);
+        assert!(prediction.confidence > 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_naive_bayes_learns_from_feedback() {
+        let classifier = NaiveBayesIntentClassifier::default();
+        assert!(classifier.classify("휴가 규정").await.unwrap().is_none());
+
+        classifier.learn("휴가 규정 알려줘", QueryIntent::Definitional);
+        let prediction = classifier.classify("휴가 규정").await.unwrap().unwrap();
+        assert_eq!(prediction.intent, QueryIntent::Definitional);
+        assert_eq!(prediction.confidence, 1.0);
+    }
+}
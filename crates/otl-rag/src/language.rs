@@ -0,0 +1,471 @@
+//! Query language detection and localized prompt templates
+//!
+//! Questions arrive in Korean or English. The language is detected from the
+//! script of the question (Hangul vs. Latin letters) unless the caller forces
+//! one, and the matching template is used to build the LLM prompt so the
+//! answer comes back in the user's language.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::moderation::SensitiveTopicRule;
+use otl_core::{AnswerFormat, Language, Persona, WithheldTopic};
+
+/// Detect the language of a question
+///
+/// Korean wins ties and is the default for text without letters, since most
+/// source documents are Korean.
+pub fn detect_language(text: &str) -> Language {
+    let hangul = text.chars().filter(|c| is_hangul(*c)).count();
+    let latin = text.chars().filter(|c| c.is_ascii_alphabetic()).count();
+
+    // Hangul syllables carry roughly two to three Latin letters of content
+    if latin > hangul * 2 {
+        Language::English
+    } else {
+        Language::Korean
+    }
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '가'..='힣' | 'ㄱ'..='ㆎ' | 'ᄀ'..='ᇿ')
+}
+
+/// Localized prompt text for one language
+#[derive(Debug)]
+pub struct PromptTemplate {
+    /// Language of this template
+    pub language: Language,
+    /// Assistant role line
+    pub role: &'static str,
+    /// Restrict the answer to the given context
+    pub context_only: &'static str,
+    /// Citation format rule
+    pub citation_rule: &'static str,
+    /// What to say when the context lacks the answer
+    pub not_found_rule: &'static str,
+    /// Answer language rule
+    pub language_rule: &'static str,
+    /// Header for the ontology schema section
+    pub ontology_header: &'static str,
+    /// Label used in citations (`[label: N]`)
+    pub source_label: &'static str,
+    /// Numbered answering instructions
+    pub instructions: [&'static str; 4],
+    /// Answer used when nothing relevant was retrieved
+    pub not_found: &'static str,
+    /// Label for context documents in streaming prompts
+    pub document_label: &'static str,
+    /// Streaming prompt rule when no documents were found
+    pub concise_rule: &'static str,
+    /// Streaming prompt rule when documents were found
+    pub reference_rule: &'static str,
+    /// Streaming prompt rule against speculation
+    pub no_speculation_rule: &'static str,
+    /// Header for reference documents in streaming prompts
+    pub references_header: &'static str,
+    /// Label for the question
+    pub question_label: &'static str,
+    /// Label preceding the answer
+    pub answer_label: &'static str,
+    /// Instruction for summarizing a context excerpt during compression
+    pub summarize_rule: &'static str,
+    /// Label for the excerpt to summarize
+    pub excerpt_label: &'static str,
+    /// Answer shown when moderation withholds a response
+    pub moderation_refusal: &'static str,
+    /// Replacement for sentences removed by moderation
+    pub redacted_marker: &'static str,
+    /// Replacement for restricted values masked in the context
+    pub restricted_marker: &'static str,
+    /// Prompt rule keeping masked values masked
+    pub restricted_rule: &'static str,
+    /// Heading of the list of withheld topics appended to the answer
+    pub withheld_notice: &'static str,
+    /// Label for the departments and roles to ask for access
+    pub access_request_label: &'static str,
+    /// Whom to ask for access when a topic names nobody
+    pub access_request_fallback: &'static str,
+    /// Unit of the number of masked values
+    pub masked_values_label: &'static str,
+    /// Request to fix citations of contexts that were not in the prompt
+    pub citation_repair_rule: &'static str,
+    /// Label for the list of invalid citation numbers
+    pub invalid_citations_label: &'static str,
+    /// Request to phrase one question covering similar user questions
+    pub faq_question_rule: &'static str,
+    /// Request to summarize the changes between two document versions
+    pub compare_summary_rule: &'static str,
+    /// Label for sections of the older document version
+    pub old_version_label: &'static str,
+    /// Label for sections of the newer document version
+    pub new_version_label: &'static str,
+    /// Request to suggest an access level and department for a document
+    pub classification_rule: &'static str,
+    /// Label for the departments a document may belong to
+    pub departments_label: &'static str,
+    /// Layout rule for procedure answers
+    pub steps_rule: &'static str,
+    /// Layout rule for comparison answers
+    pub table_rule: &'static str,
+    /// Layout rule for yes/no answers
+    pub yes_no_rule: &'static str,
+}
+
+const KOREAN: PromptTemplate = PromptTemplate {
+    language: Language::Korean,
+    role: "당신은 조직의 지식 전문가입니다.",
+    context_only: "제공된 컨텍스트 정보만을 사용하여 질문에 답변하세요.",
+    citation_rule: "답변에 사용한 정보의 출처를 반드시 [출처: N] 형식으로 명시하세요.",
+    not_found_rule: "컨텍스트에 없는 정보는 \"해당 정보를 찾을 수 없습니다\"라고 답변하세요.",
+    language_rule: "반드시 한국어로 답변하세요.",
+    ontology_header: "온톨로지 스키마:",
+    source_label: "출처",
+    instructions: [
+        "컨텍스트를 주의 깊게 읽으세요.",
+        "질문에 직접 관련된 정보만 사용하세요.",
+        "답변 작성 시 [출처: N] 형식으로 인용하세요.",
+        "확실하지 않은 정보는 언급하지 마세요.",
+    ],
+    not_found: "해당 정보를 찾을 수 없습니다.",
+    document_label: "문서",
+    concise_rule: "질문에 대해 간결하고 정확하게 답변하세요.",
+    reference_rule: "아래 제공된 문서를 참고하여 질문에 답변하세요.",
+    no_speculation_rule: "문서에 없는 내용은 추측하지 마세요.",
+    references_header: "참고 문서",
+    question_label: "질문",
+    answer_label: "답변",
+    summarize_rule:
+        "다음 발췌문에서 질문에 답하는 데 필요한 사실만 남겨 짧게 요약하세요. 요약문만 출력하세요.",
+    excerpt_label: "발췌문",
+    moderation_refusal: "요청하신 답변에는 열람 권한이 없는 민감 정보가 포함되어 있어 제공할 수 없습니다. 인사팀에 문의하세요.",
+    redacted_marker: "[비공개 정보]",
+    restricted_marker: "[권한 필요]",
+    restricted_rule: "컨텍스트의 [권한 필요] 표시는 열람 권한이 없어 가려진 값입니다. 값을 추측하지 말고 답변에도 [권한 필요]로 표시하세요.",
+    withheld_notice: "열람 권한이 없어 일부 값을 [권한 필요]로 가렸습니다.",
+    access_request_label: "열람 권한 요청",
+    access_request_fallback: "관리자",
+    masked_values_label: "건",
+    citation_repair_rule: "위 답변이 컨텍스트에 없는 출처 번호를 인용했습니다. 내용은 유지하되 컨텍스트에 있는 출처 번호만 인용하도록 잘못된 인용을 고치거나 삭제하여 답변 전체를 다시 작성하세요. 답변만 출력하세요.",
+    invalid_citations_label: "잘못된 출처 번호",
+    faq_question_rule: "다음은 사용자들이 같은 내용을 여러 방식으로 물어본 질문입니다. 이 질문들을 대표하는 명확하고 자연스러운 질문 하나를 작성하세요. 질문만 한 줄로 출력하세요.",
+    compare_summary_rule: "다음은 문서의 이전 버전과 새 버전에서 달라진 부분입니다. 무엇이 추가, 삭제, 변경되었는지 항목별로 요약하세요. 각 항목에는 근거가 된 이전 버전과 새 버전의 출처를 [출처: N] 형식으로 모두 인용하세요.",
+    old_version_label: "이전 버전",
+    new_version_label: "새 버전",
+    classification_rule: "다음 문서의 보안 등급을 public(누구나 열람), internal(임직원 열람), confidential(특정 부서·역할만 열람), restricted(지정된 개인만 열람) 중에서 판단하고, 부서 목록에서 담당 부서를 고르세요. {\"access_level\": \"...\", \"department\": \"...\" 또는 null, \"confidence\": 0~1, \"reason\": \"...\"} 형식의 JSON만 출력하세요.",
+    departments_label: "부서 목록",
+    steps_rule: "절차는 \"1.\", \"2.\"처럼 번호를 붙인 단계로 나누어 한 줄에 한 단계씩 답변하세요.",
+    table_rule: "비교 대상을 열로, 비교 항목을 행으로 하는 마크다운 표로 답변하고, 표 아래에 차이를 한두 문장으로 요약하세요.",
+    yes_no_rule: "첫 문장을 \"예\" 또는 \"아니요\"로 시작해 결론을 밝히고 이어서 근거를 설명하세요. 컨텍스트로 판단할 수 없으면 \"판단할 수 없습니다\"로 시작하세요.",
+};
+
+const ENGLISH: PromptTemplate = PromptTemplate {
+    language: Language::English,
+    role: "You are the organization's knowledge expert.",
+    context_only: "Answer the question using only the provided context.",
+    citation_rule: "Always cite the sources you use in the form [Source: N].",
+    not_found_rule:
+        "If the context does not contain the answer, reply \"The information could not be found\".",
+    language_rule: "Answer in English, even when the context is written in another language.",
+    ontology_header: "Ontology schema:",
+    source_label: "Source",
+    instructions: [
+        "Read the context carefully.",
+        "Use only information directly relevant to the question.",
+        "Cite sources in the form [Source: N].",
+        "Do not mention information you are unsure about.",
+    ],
+    not_found: "The information could not be found.",
+    document_label: "Document",
+    concise_rule: "Answer the question concisely and accurately.",
+    reference_rule: "Answer the question using the documents below.",
+    no_speculation_rule: "Do not speculate beyond what the documents say.",
+    references_header: "Reference documents",
+    question_label: "Question",
+    answer_label: "Answer",
+    summarize_rule: "Summarize the excerpt below briefly, keeping only the facts needed to answer the question. Output only the summary.",
+    excerpt_label: "Excerpt",
+    moderation_refusal: "This answer contains sensitive information you are not authorized to view. Please contact HR.",
+    redacted_marker: "[redacted]",
+    restricted_marker: "[access required]",
+    restricted_rule: "Values shown as [access required] in the context are hidden because you lack clearance. Do not guess them; write [access required] in the answer instead.",
+    withheld_notice: "Some values were masked as [access required] because you lack clearance.",
+    access_request_label: "Request access from",
+    access_request_fallback: "an administrator",
+    masked_values_label: "values",
+    citation_repair_rule: "The answer above cites sources that are not in the context. Rewrite the whole answer, keeping its content, so that it only cites source numbers present in the context; fix or remove the other citations. Output only the answer.",
+    invalid_citations_label: "Invalid source numbers",
+    faq_question_rule: "The questions below ask the same thing in different words. Write one clear, natural question that represents them all. Output only the question, on one line.",
+    compare_summary_rule: "Below are the parts that differ between the old and the new version of a document. Summarize, item by item, what was added, removed or changed. Cite every old and new version source each item relies on in the form [Source: N].",
+    old_version_label: "Old version",
+    new_version_label: "New version",
+    classification_rule: "Classify the document below as public (anyone), internal (employees), confidential (specific departments or roles) or restricted (named individuals only), and pick the department that owns it from the list. Output only JSON of the form {\"access_level\": \"...\", \"department\": \"...\" or null, \"confidence\": 0-1, \"reason\": \"...\"}.",
+    departments_label: "Departments",
+    steps_rule: "Answer procedures as numbered steps (\"1.\", \"2.\", ...), one step per line.",
+    table_rule: "Answer with a markdown table with the compared items as columns and the aspects compared as rows, followed by a one or two sentence summary of the differences.",
+    yes_no_rule: "Start with \"Yes\" or \"No\" as the verdict, then explain the rationale. If the context does not settle it, start with \"It cannot be determined\".",
+};
+
+impl PromptTemplate {
+    /// Template for a language
+    pub fn for_language(language: Language) -> &'static PromptTemplate {
+        match language {
+            Language::Korean => &KOREAN,
+            Language::English => &ENGLISH,
+        }
+    }
+
+    /// Citation marker for a context index (e.g. `[출처: 1]`)
+    pub fn citation(&self, index: u32) -> String {
+        format!("[{}: {}]", self.source_label, index)
+    }
+
+    /// Follow-up to `prompt` asking the LLM to fix dangling citations
+    pub fn citation_repair_prompt(&self, prompt: &str, answer: &str, dangling: &[usize]) -> String {
+        let numbers: Vec<String> = dangling.iter().map(|n| n.to_string()).collect();
+        format!(
+            "{}\n{}:\n{}\n\n{}\n{}: {}\n",
+            prompt,
+            self.answer_label,
+            answer,
+            self.citation_repair_rule,
+            self.invalid_citations_label,
+            numbers.join(", ")
+        )
+    }
+
+    /// Prompt for summarizing one context excerpt with respect to a question
+    pub fn summarize_prompt(&self, question: &str, excerpt: &str) -> String {
+        format!(
+            "{}\n{}\n\n{}: {}\n\n{}:\n{}\n",
+            self.summarize_rule,
+            self.language_rule,
+            self.question_label,
+            question,
+            self.excerpt_label,
+            excerpt
+        )
+    }
+
+    /// Prompt asking for one canonical question covering `questions`
+    pub fn faq_question_prompt(&self, questions: &[String]) -> String {
+        let list: Vec<String> = questions.iter().map(|q| format!("- {q}")).collect();
+        format!(
+            "{}\n{}\n\n{}\n\n{}:",
+            self.faq_question_rule,
+            self.language_rule,
+            list.join("\n"),
+            self.question_label
+        )
+    }
+
+    /// Prompt asking for a summary of the changes between two document
+    /// versions; `sources` are (heading, text) pairs cited from 1
+    pub fn compare_summary_prompt(&self, sources: &[(String, String)]) -> String {
+        let blocks: Vec<String> = sources
+            .iter()
+            .enumerate()
+            .map(|(i, (heading, text))| {
+                format!("{} {}\n{}", self.citation(i as u32 + 1), heading, text)
+            })
+            .collect();
+        format!(
+            "{}\n{}\n{}\n\n{}\n\n{}:",
+            self.role,
+            self.compare_summary_rule,
+            self.language_rule,
+            blocks.join("\n\n"),
+            self.answer_label
+        )
+    }
+
+    /// Prompt asking for the access level and owning department of a
+    /// document excerpt
+    pub fn classification_prompt(&self, excerpt: &str, departments: &[String]) -> String {
+        format!(
+            "{}\n\n{}: {}\n\n{}:\n{}\n",
+            self.classification_rule,
+            self.departments_label,
+            departments.join(", "),
+            self.document_label,
+            excerpt
+        )
+    }
+
+    /// How to request access to the topic of a moderation rule
+    pub fn access_request(&self, rule: &SensitiveTopicRule) -> String {
+        let owners: Vec<&str> = rule
+            .allowed_departments
+            .iter()
+            .chain(&rule.allowed_roles)
+            .map(String::as_str)
+            .collect();
+        let owners = if owners.is_empty() {
+            self.access_request_fallback.to_string()
+        } else {
+            owners.join(", ")
+        };
+        format!("{}: {}", self.access_request_label, owners)
+    }
+
+    /// Notice appended to an answer whose context had values masked
+    pub fn withheld_notice(&self, withheld: &[WithheldTopic]) -> String {
+        let topics: Vec<String> = withheld
+            .iter()
+            .map(|topic| {
+                let name = if topic.description.is_empty() {
+                    &topic.rule
+                } else {
+                    &topic.description
+                };
+                format!(
+                    "- {} ({} {}). {}",
+                    name, topic.values, self.masked_values_label, topic.access_request
+                )
+            })
+            .collect();
+        format!("{}\n{}", self.withheld_notice, topics.join("\n"))
+    }
+
+    /// Prompt rule asking for an answer layout (none for prose)
+    pub fn format_rule(&self, format: AnswerFormat) -> Option<&'static str> {
+        match format {
+            AnswerFormat::Steps => Some(self.steps_rule),
+            AnswerFormat::Table => Some(self.table_rule),
+            AnswerFormat::YesNo => Some(self.yes_no_rule),
+            AnswerFormat::Prose => None,
+        }
+    }
+
+    /// Role line followed by the rules of a persona, whose role replaces
+    /// the default one
+    pub fn persona_role(&self, persona: Option<&Persona>) -> String {
+        let role = persona
+            .and_then(|p| p.role.as_deref())
+            .filter(|role| !role.trim().is_empty())
+            .unwrap_or(self.role);
+        std::iter::once(role)
+            .chain(persona.into_iter().flat_map(Persona::rules))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Prompt for streaming answers over optional reference documents
+    pub fn stream_prompt(
+        &self,
+        context: &str,
+        question: &str,
+        persona: Option<&Persona>,
+    ) -> String {
+        let role = self.persona_role(persona);
+        if context.is_empty() {
+            format!(
+                "{}\n{}\n{}\n\n{}: {}\n\n{}:",
+                role,
+                self.concise_rule,
+                self.language_rule,
+                self.question_label,
+                question,
+                self.answer_label
+            )
+        } else {
+            format!(
+                "{}\n{}\n{}\n{}\n\n=== {} ===\n{}\n\n=== {} ===\n{}\n\n{}:",
+                role,
+                self.reference_rule,
+                self.no_speculation_rule,
+                self.language_rule,
+                self.references_header,
+                context,
+                self.question_label,
+                question,
+                self.answer_label
+            )
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("연차휴가 신청 절차가 어떻게 되나요?"),
+            Language::Korean
+        );
+        assert_eq!(
+            detect_language("How do I apply for annual leave?"),
+            Language::English
+        );
+        assert_eq!(
+            detect_language("HR 규정에서 연차휴가 일수는?"),
+            Language::Korean
+        );
+        assert_eq!(detect_language("What is 연차?"), Language::English);
+        assert_eq!(detect_language("2024?"), Language::Korean);
+    }
+
+    #[test]
+    fn test_templates_cite_in_language() {
+        let ko = PromptTemplate::for_language(Language::Korean);
+        let en = PromptTemplate::for_language(Language::English);
+
+        assert_eq!(ko.citation(2), "[출처: 2]");
+        assert_eq!(en.citation(2), "[Source: 2]");
+        assert!(en.citation_rule.contains("[Source: N]"));
+    }
+
+    #[test]
+    fn test_stream_prompt() {
+        let en = PromptTemplate::for_language(Language::English);
+        let prompt = en.stream_prompt("[Document 1] Leave is 15 days", "How many days?", None);
+
+        assert!(prompt.contains("=== Reference documents ==="));
+        assert!(prompt.contains("Answer in English"));
+        assert!(prompt.ends_with("Answer:"));
+
+        let ko = PromptTemplate::for_language(Language::Korean);
+        let prompt = ko.stream_prompt("", "며칠인가요?", None);
+        assert!(prompt.contains("질문: 며칠인가요?"));
+        assert!(!prompt.contains("==="));
+    }
+
+    #[test]
+    fn test_format_rules() {
+        let ko = PromptTemplate::for_language(Language::Korean);
+        let en = PromptTemplate::for_language(Language::English);
+        let rule = |template: &PromptTemplate, format| template.format_rule(format).unwrap();
+
+        assert!(rule(ko, AnswerFormat::Steps).contains("번호"));
+        assert!(rule(ko, AnswerFormat::YesNo).starts_with("첫 문장을 \"예\""));
+        assert!(rule(en, AnswerFormat::Table).contains("markdown table"));
+        assert!(ko.format_rule(AnswerFormat::Prose).is_none());
+    }
+
+    #[test]
+    fn test_persona_role_replaces_default_role() {
+        let ko = PromptTemplate::for_language(Language::Korean);
+        assert_eq!(ko.persona_role(None), ko.role);
+
+        let persona = Persona {
+            role: Some("당신은 인사팀 규정 안내 담당자입니다.".to_string()),
+            tone: Some("존댓말로 간결하게 답변하세요.".to_string()),
+            ..Persona::new("hr")
+        };
+        let prompt = ko.stream_prompt("", "며칠인가요?", Some(&persona));
+        assert!(prompt
+            .starts_with("당신은 인사팀 규정 안내 담당자입니다.\n존댓말로 간결하게 답변하세요.\n"));
+        assert!(!prompt.contains(ko.role));
+
+        let tone_only = Persona {
+            role: None,
+            ..persona
+        };
+        assert!(ko.persona_role(Some(&tone_only)).starts_with(ko.role));
+    }
+}
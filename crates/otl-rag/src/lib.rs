@@ -10,19 +10,87 @@
 //!
 //! Author: hephaex@gmail.com
 
+use budget::{Deadline, Stage, TimeoutMetrics};
+use otl_core::faq::keyword_overlap;
 use otl_core::{
-    Citation, LlmClient, RagQuery, RagResponse, Result, SearchBackend, SearchResult,
-    SearchResultType, User,
+    AnswerFormat, AnswerMode, BackendHealth, Calibrator, Citation, DocumentChunk, DocumentMetadata,
+    ExtractedPassage, FaqRepository, GlossaryEntry, GlossaryRepository, GlossaryStatus,
+    GraphContextBackend, Language, LlmClient, MetadataRepository, ModerationAction,
+    ModerationDecision, ModerationDetector, OntologyClass, OtlError, Persona, QueryRecording,
+    RagQuery, RagResponse, RecordedSearch, Result, RetrievalScope, SearchBackend, SearchResult,
+    SearchResultType, SharedAnalyzer, SourceReference, StructuredAnswer, SynonymRegistry,
+    TraceCandidate, User,
 };
-use std::collections::HashMap;
+use otl_vector::embedding::EmbeddingClient;
+use otl_vector::TokenCounter;
+use replay::{Recorder, Reproduction};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use trace::Tracer;
+use uuid::Uuid;
 
+pub mod budget;
 pub mod cache;
+pub mod cache_backend;
+pub mod citations;
+pub mod compress;
+pub mod diversify;
+pub mod embedding_store;
+pub mod estimate;
+pub mod expand;
+pub mod extractive;
+pub mod feedback;
+pub mod format;
+pub mod fusion;
+pub mod glossary;
+pub mod graph_context;
+mod health;
+pub mod intent;
+pub mod language;
 pub mod llm;
+pub mod moderation;
+pub mod ranking;
+mod replay;
+pub mod routing;
+pub mod structured;
+pub mod suggest;
+mod trace;
+
+pub use budget::TimeoutBudgets;
+pub use cache::{
+    AnswerCache, AnswerKey, CacheBackendKind, CacheConfig, CacheStatsReport, CachedAnswer,
+    CachedEmbeddingClient, EmbeddingCache, QueryCache, RagCacheManager,
+};
+pub use cache_backend::{CacheBackend, MemoryBackend, RedisBackend};
+pub use compress::CompressionReport;
+pub use diversify::DiversityOptions;
+pub use embedding_store::EmbeddingStore;
+pub use estimate::{AnswerPath, CostModel, ModelPrice, QueryEstimate};
+pub use expand::ExpansionOptions;
+pub use extractive::ExtractiveOptions;
+pub use feedback::{FeedbackConfig, FeedbackPriors, FeedbackVote};
+pub use intent::{
+    IntentClassifier, IntentPrediction, IntentSource, NaiveBayesIntentClassifier,
+    RuleIntentClassifier,
+};
+pub use language::{detect_language, PromptTemplate};
+pub use llm::{
+    create_llm_client, CachedLlmClient, DisabledLlmClient, LlmResponseCache, OllamaClient,
+    OpenAiClient,
+};
+pub use moderation::{Masking, ModerationConfig, Moderator, SensitiveTopicRule};
+pub use ranking::RankingBoosts;
+pub use routing::{ModelRouting, ModelTier, RoutingRule};
+pub use suggest::suggest_related_questions;
+
+/// Most FAQ entries placed ahead of the fused document passages
+const MAX_FAQ_RESULTS: usize = 2;
 
-pub use cache::{CacheConfig, CacheStatsReport, EmbeddingCache, QueryCache, RagCacheManager};
-pub use llm::{create_llm_client, OllamaClient, OpenAiClient};
+/// Time allowed past the query deadline for the stages after generation
+/// (freshness, moderation) to finish a partial answer
+const DEADLINE_GRACE: Duration = Duration::from_secs(2);
 
 // ============================================================================
 // Configuration
@@ -61,8 +129,69 @@ pub struct RagConfig {
     /// Maximum context length for LLM (in characters)
     pub max_context_length: usize,
 
+    /// Fraction of the retrieved context kept in the prompt after compression
+    /// (1.0 only compresses contexts longer than `max_context_length`)
+    pub compression_ratio: f32,
+
+    /// Summarize low-ranked chunks with the LLM during compression
+    pub summarize_low_rank_chunks: bool,
+
     /// Include ontology schema in prompt
     pub include_ontology: bool,
+
+    /// Maximum number of follow-up question suggestions (0 disables)
+    pub max_suggestions: usize,
+
+    /// Sentence scoring for extractive answers
+    pub extractive: ExtractiveOptions,
+
+    /// Request ontology-conforming JSON for list and single-fact questions
+    pub structured_answers: bool,
+
+    /// Maps the raw retrieval-based confidence to a calibrated probability
+    pub confidence_calibrator: Calibrator,
+
+    /// Sensitive-topic rules applied to generated answers
+    pub moderation: ModerationConfig,
+
+    /// Minimum classifier confidence before the keyword rules take over
+    pub intent_min_confidence: f32,
+
+    /// Metadata boosts applied to fused scores (needs a metadata store)
+    pub ranking: RankingBoosts,
+
+    /// Maximal marginal relevance selection of the final top-k
+    pub diversity: DiversityOptions,
+
+    /// Neighbor chunks stitched onto the final contexts for intents that
+    /// need whole passages
+    pub context_expansion: ExpansionOptions,
+
+    /// Ask the LLM once to fix citations of contexts missing from the
+    /// prompt (they are dropped either way)
+    pub repair_dangling_citations: bool,
+
+    /// Minimum confidence of a definitional answer before it is proposed as
+    /// a glossary candidate (needs a glossary)
+    pub glossary_candidate_min_confidence: f32,
+
+    /// Minimum keyword overlap (Jaccard) between a question and an approved
+    /// FAQ entry for the entry to be retrieved (needs a FAQ store)
+    pub faq_min_similarity: f32,
+
+    /// Fail queries when a retrieval backend is unavailable instead of
+    /// answering from the remaining backends
+    pub strict_backends: bool,
+
+    /// Query deadline and stage budgets
+    pub timeouts: TimeoutBudgets,
+
+    /// Model tiers answering questions by intent and length (the default
+    /// LLM client answers everything when there are no rules)
+    pub model_routing: ModelRouting,
+
+    /// Model prices used by dry-run cost estimates
+    pub cost: CostModel,
 }
 
 impl Default for RagConfig {
@@ -78,7 +207,25 @@ impl Default for RagConfig {
             graph_weight: 1.5, // Slightly higher weight for graph results
             keyword_weight: 0.8,
             max_context_length: 8000,
+            compression_ratio: 1.0,
+            summarize_low_rank_chunks: false,
             include_ontology: true,
+            max_suggestions: 5,
+            extractive: ExtractiveOptions::default(),
+            structured_answers: true,
+            confidence_calibrator: Calibrator::Identity,
+            moderation: ModerationConfig::default(),
+            intent_min_confidence: 0.5,
+            ranking: RankingBoosts::default(),
+            diversity: DiversityOptions::default(),
+            context_expansion: ExpansionOptions::default(),
+            repair_dangling_citations: false,
+            glossary_candidate_min_confidence: 0.6,
+            faq_min_similarity: 0.5,
+            strict_backends: false,
+            timeouts: TimeoutBudgets::default(),
+            model_routing: ModelRouting::default(),
+            cost: CostModel::default(),
         }
     }
 }
@@ -96,18 +243,42 @@ pub struct QueryAnalysis {
     /// Detected intent
     pub intent: QueryIntent,
 
+    /// Confidence of the detected intent (0.0 - 1.0)
+    pub intent_confidence: f32,
+
+    /// Whether the intent came from the classifier or the keyword rules
+    pub intent_source: IntentSource,
+
     /// Entities detected in the question
     pub detected_entities: Vec<DetectedEntity>,
 
     /// Keywords extracted
     pub keywords: Vec<String>,
 
+    /// Synonyms of the keywords used to widen keyword and graph search
+    pub synonyms: Vec<String>,
+
     /// Expected answer type
     pub expected_answer_type: AnswerType,
+
+    /// Answer language (detected from the question unless overridden)
+    pub language: Language,
+}
+
+impl QueryAnalysis {
+    /// Keywords followed by their synonyms
+    pub fn search_terms(&self) -> Vec<&str> {
+        self.keywords
+            .iter()
+            .chain(&self.synonyms)
+            .map(String::as_str)
+            .collect()
+    }
 }
 
 /// Type of user intent
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum QueryIntent {
     /// Looking for a procedure/process
     Procedural,
@@ -147,6 +318,33 @@ pub enum AnswerType {
     Unknown,
 }
 
+/// Context retrieved for a question, before answering
+struct Retrieval {
+    /// Final top-k contexts, FAQ entries first
+    results: Vec<SearchResult>,
+
+    /// Graph results kept for follow-up suggestions
+    graph_context: Vec<SearchResult>,
+
+    /// Availability of each backend
+    backend_health: Vec<BackendHealth>,
+
+    /// Candidates removed by ACL filtering
+    acl_filtered: usize,
+
+    /// Candidates after fusion
+    fused: usize,
+
+    /// Degradations from unavailable backends
+    warnings: Vec<String>,
+
+    /// Restricted values masked per moderation rule
+    masked: Vec<(String, usize)>,
+
+    /// Knowledge spaces searched (`global` last after a fallback)
+    searched_spaces: Vec<String>,
+}
+
 // ============================================================================
 // RAG Orchestrator
 // ============================================================================
@@ -156,20 +354,68 @@ pub struct HybridRagOrchestrator {
     /// Vector search backend
     vector_store: Arc<dyn SearchBackend>,
 
-    /// Graph search backend
+    /// Graph search backend (keyword fallback when no graph context is set)
     graph_store: Arc<dyn SearchBackend>,
 
+    /// Graph access for entity-aware retrieval (optional)
+    graph_context: Option<Arc<dyn GraphContextBackend>>,
+
+    /// Trained intent classifier (keyword rules only when unset)
+    intent_classifier: Option<Arc<dyn IntentClassifier>>,
+
     /// Keyword search backend (optional)
     keyword_store: Option<Arc<dyn SearchBackend>>,
 
+    /// Extracts query keywords for the keyword and graph backends
+    keyword_analyzer: Arc<SharedAnalyzer>,
+
+    /// Synonyms added to the keywords (no expansion when unset)
+    synonyms: Option<Arc<SynonymRegistry>>,
+
+    /// Document metadata for ranking boosts (no boosts when unset)
+    metadata_store: Option<Arc<dyn MetadataRepository>>,
+
+    /// Answer cache (every answer is generated when unset)
+    cache: Option<Arc<RagCacheManager>>,
+
+    /// Curated definitions answering definitional questions (optional)
+    glossary: Option<Arc<dyn GlossaryRepository>>,
+
+    /// Approved FAQ entries, retrieved ahead of document passages (optional)
+    faq: Option<Arc<dyn FaqRepository>>,
+
     /// LLM client
     llm_client: Arc<dyn LlmClient>,
 
+    /// Model of the LLM client, reported with its answers (optional)
+    model_name: Option<String>,
+
+    /// LLM clients of the model tiers in `RagConfig::model_routing`
+    model_tiers: HashMap<String, Arc<dyn LlmClient>>,
+
+    /// Embedding client for extractive sentence scoring (optional)
+    embedding_client: Option<Arc<dyn EmbeddingClient>>,
+
     /// Configuration
     config: RagConfig,
 
     /// Ontology schema (for prompt context)
     ontology_schema: Option<String>,
+
+    /// Ontology classes used to validate structured answers
+    ontology_classes: Vec<OntologyClass>,
+
+    /// Post-generation moderation built from `config.moderation`
+    moderator: Moderator,
+
+    /// Popularity priors learned from citation feedback (optional)
+    feedback: Option<Arc<FeedbackPriors>>,
+
+    /// Stage budget overruns
+    timeout_metrics: TimeoutMetrics,
+
+    /// Set on copies recording or replaying a seeded run
+    reproduction: Option<Reproduction>,
 }
 
 impl HybridRagOrchestrator {
@@ -180,13 +426,34 @@ impl HybridRagOrchestrator {
         llm_client: Arc<dyn LlmClient>,
         config: RagConfig,
     ) -> Self {
+        let moderator = Moderator::new(&config.moderation).unwrap_or_else(|e| {
+            tracing::error!("{e}; falling back to the built-in moderation rules");
+            Moderator::new(&ModerationConfig::default())
+                .expect("built-in moderation rules are valid")
+        });
         Self {
             vector_store,
             graph_store,
+            graph_context: None,
+            intent_classifier: None,
             keyword_store: None,
+            keyword_analyzer: Arc::new(SharedAnalyzer::default()),
+            synonyms: None,
+            metadata_store: None,
+            cache: None,
+            glossary: None,
+            faq: None,
             llm_client,
+            model_name: None,
+            model_tiers: HashMap::new(),
+            embedding_client: None,
             config,
             ontology_schema: None,
+            ontology_classes: Vec::new(),
+            moderator,
+            feedback: None,
+            timeout_metrics: TimeoutMetrics::default(),
+            reproduction: None,
         }
     }
 
@@ -196,31 +463,750 @@ impl HybridRagOrchestrator {
         self
     }
 
+    /// Set the analyzer used to extract query keywords
+    ///
+    /// Share it with the keyword and graph backends so that settings
+    /// updates apply to queries and keyword matching alike.
+    pub fn with_keyword_analyzer(mut self, analyzer: Arc<SharedAnalyzer>) -> Self {
+        self.keyword_analyzer = analyzer;
+        self
+    }
+
+    /// Set the synonym registry used to expand query keywords
+    pub fn with_synonyms(mut self, synonyms: Arc<SynonymRegistry>) -> Self {
+        self.synonyms = Some(synonyms);
+        self
+    }
+
+    /// Set the document metadata source used for ranking boosts
+    pub fn with_metadata_store(mut self, store: Arc<dyn MetadataRepository>) -> Self {
+        self.metadata_store = Some(store);
+        self
+    }
+
+    /// Set the cache used to reuse answers to repeated questions
+    pub fn with_cache(mut self, cache: Arc<RagCacheManager>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Cache shared with document handlers for invalidation
+    pub fn cache(&self) -> Option<&Arc<RagCacheManager>> {
+        self.cache.as_ref()
+    }
+
+    /// Set the glossary used to answer definitional questions directly
+    pub fn with_glossary(mut self, glossary: Arc<dyn GlossaryRepository>) -> Self {
+        self.glossary = Some(glossary);
+        self
+    }
+
+    /// Set the FAQ store whose approved entries are retrieved first
+    pub fn with_faq(mut self, faq: Arc<dyn FaqRepository>) -> Self {
+        self.faq = Some(faq);
+        self
+    }
+
+    /// Set graph access used for entity-aware graph retrieval
+    pub fn with_graph_context(mut self, backend: Arc<dyn GraphContextBackend>) -> Self {
+        self.graph_context = Some(backend);
+        self
+    }
+
+    /// Copy of the orchestrator retrieving from another graph
+    ///
+    /// Used to answer against a graph snapshot. Vector and keyword search
+    /// are shared; the copy has no answer cache and its own timeout counts.
+    pub fn on_graph(
+        &self,
+        graph_store: Arc<dyn SearchBackend>,
+        graph_context: Option<Arc<dyn GraphContextBackend>>,
+    ) -> Self {
+        Self {
+            vector_store: self.vector_store.clone(),
+            graph_store,
+            graph_context,
+            intent_classifier: self.intent_classifier.clone(),
+            keyword_store: self.keyword_store.clone(),
+            keyword_analyzer: self.keyword_analyzer.clone(),
+            synonyms: self.synonyms.clone(),
+            metadata_store: self.metadata_store.clone(),
+            cache: None,
+            glossary: self.glossary.clone(),
+            faq: self.faq.clone(),
+            llm_client: self.llm_client.clone(),
+            model_name: self.model_name.clone(),
+            model_tiers: self.model_tiers.clone(),
+            embedding_client: self.embedding_client.clone(),
+            config: self.config.clone(),
+            ontology_schema: self.ontology_schema.clone(),
+            ontology_classes: self.ontology_classes.clone(),
+            moderator: self.moderator.clone(),
+            feedback: self.feedback.clone(),
+            timeout_metrics: TimeoutMetrics::default(),
+            reproduction: self.reproduction.clone(),
+        }
+    }
+
+    /// Copy of the orchestrator reproducing a run (see [`replay`])
+    ///
+    /// The LLM clients of all tiers and the embedding client are replaced
+    /// by `llm` and `embedding`; the copy has no answer cache and its own
+    /// timeout counts.
+    fn reproducing(
+        &self,
+        reproduction: Reproduction,
+        llm: impl Fn(&Arc<dyn LlmClient>) -> Arc<dyn LlmClient>,
+        embedding: Option<Arc<dyn EmbeddingClient>>,
+    ) -> Self {
+        Self {
+            vector_store: self.vector_store.clone(),
+            graph_store: self.graph_store.clone(),
+            graph_context: self.graph_context.clone(),
+            intent_classifier: self.intent_classifier.clone(),
+            keyword_store: self.keyword_store.clone(),
+            keyword_analyzer: self.keyword_analyzer.clone(),
+            synonyms: self.synonyms.clone(),
+            metadata_store: self.metadata_store.clone(),
+            cache: None,
+            glossary: self.glossary.clone(),
+            faq: self.faq.clone(),
+            llm_client: llm(&self.llm_client),
+            model_name: self.model_name.clone(),
+            model_tiers: self
+                .model_tiers
+                .iter()
+                .map(|(tier, client)| (tier.clone(), llm(client)))
+                .collect(),
+            embedding_client: embedding,
+            config: self.config.clone(),
+            ontology_schema: self.ontology_schema.clone(),
+            ontology_classes: self.ontology_classes.clone(),
+            moderator: self.moderator.clone(),
+            feedback: self.feedback.clone(),
+            timeout_metrics: TimeoutMetrics::default(),
+            reproduction: Some(reproduction),
+        }
+    }
+
+    /// Set the popularity priors applied after the ranking boosts
+    pub fn with_feedback(mut self, feedback: Arc<FeedbackPriors>) -> Self {
+        self.feedback = Some(feedback);
+        self
+    }
+
+    /// Set the classifier used to detect query intent
+    pub fn with_intent_classifier(mut self, classifier: Arc<dyn IntentClassifier>) -> Self {
+        self.intent_classifier = Some(classifier);
+        self
+    }
+
+    /// Set the model name of the LLM client, reported with its answers
+    pub fn with_model_name(mut self, model: impl Into<String>) -> Self {
+        self.model_name = Some(model.into());
+        self
+    }
+
+    /// Set the LLM client of a model tier named by the routing rules
+    pub fn with_model_tier(mut self, tier: impl Into<String>, client: Arc<dyn LlmClient>) -> Self {
+        self.model_tiers.insert(tier.into(), client);
+        self
+    }
+
+    /// LLM client answering a question and its model name
+    ///
+    /// A routed tier without a client falls back to the default client.
+    fn answer_model(&self, analysis: &QueryAnalysis) -> (&dyn LlmClient, Option<String>) {
+        let routing = &self.config.model_routing;
+        if let Some(tier) = routing.select(analysis.intent, &analysis.question) {
+            match self.model_tiers.get(tier) {
+                Some(client) => {
+                    tracing::debug!("Routing {:?} question to tier {}", analysis.intent, tier);
+                    let model = routing.tiers.get(tier).map(|t| t.model.clone());
+                    return (client.as_ref(), model);
+                }
+                None => tracing::warn!("Model tier {} is unavailable, using the default", tier),
+            }
+        }
+        (self.llm_client.as_ref(), self.model_name.clone())
+    }
+
+    /// Set embedding client used to score sentences in extractive mode
+    pub fn with_embedding_client(mut self, client: Arc<dyn EmbeddingClient>) -> Self {
+        self.embedding_client = Some(client);
+        self
+    }
+
     /// Set ontology schema for prompts
     pub fn with_ontology_schema(mut self, schema: impl Into<String>) -> Self {
         self.ontology_schema = Some(schema.into());
         self
     }
 
+    /// Set ontology classes for validating structured answers
+    pub fn with_ontology_classes(mut self, classes: Vec<OntologyClass>) -> Self {
+        self.ontology_classes = classes;
+        self
+    }
+
+    /// Orchestrator configuration
+    pub fn config(&self) -> &RagConfig {
+        &self.config
+    }
+
+    /// Sensitive-topic rules applied to answers and contexts
+    pub fn moderator(&self) -> &Moderator {
+        &self.moderator
+    }
+
+    /// Stage budget overruns since startup
+    pub fn timeout_metrics(&self) -> &TimeoutMetrics {
+        &self.timeout_metrics
+    }
+
     /// Execute a RAG query
+    ///
+    /// The query has `timeouts.total_ms`, or its own shorter `timeout_ms`,
+    /// to finish; see [`budget`] for how the stages share that time. A
+    /// query with a seed is run reproducibly and recorded, see [`replay`].
     pub async fn query(&self, query: &RagQuery, user: &User) -> Result<RagResponse> {
+        match query.seed {
+            Some(seed) => self.query_seeded(query, user, seed).await,
+            None => self.query_timed(query, user).await,
+        }
+    }
+
+    /// Re-run a recorded query without calling any backend
+    ///
+    /// Search results, LLM responses, embeddings and document metadata are
+    /// read from `recording`; everything else runs as usual for `user`.
+    /// The response always carries a trace, and warns when prompts or the
+    /// answer differ from the recorded run.
+    pub async fn replay(&self, recording: &QueryRecording, user: &User) -> Result<RagResponse> {
+        if recording.searches.is_empty() {
+            return Err(OtlError::ValidationError(
+                "recording has no retrieval to replay (answered from the glossary)".to_string(),
+            ));
+        }
+        let llm = Arc::new(replay::ReplayLlm::new(&recording.completions));
+        let embedding = (!recording.embeddings.is_empty()).then(|| {
+            Arc::new(replay::ReplayEmbedding::new(&recording.embeddings))
+                as Arc<dyn EmbeddingClient>
+        });
+        let mut run = self.reproducing(
+            Reproduction::Replay(Arc::new(recording.clone())),
+            |_| llm.clone(),
+            embedding,
+        );
+        run.glossary = None;
+
+        let query = RagQuery {
+            debug: true,
+            seed: None,
+            ..recording.query.clone()
+        };
+        let mut response = run.query_timed(&query, user).await?;
+        let mismatched = llm.mismatched();
+        if mismatched > 0 {
+            response.warnings.push(format!(
+                "{mismatched} LLM prompts differ from the recording; their responses were replayed in call order"
+            ));
+        }
+        if response.answer != recording.answer {
+            response
+                .warnings
+                .push("replayed answer differs from the recorded answer".to_string());
+        }
+        Ok(response)
+    }
+
+    /// Run a query with seeded generation, recording its inputs in the trace
+    async fn query_seeded(&self, query: &RagQuery, user: &User, seed: u64) -> Result<RagResponse> {
+        let recorder = Arc::new(Recorder::new(seed));
+        let embedding = self.embedding_client.clone().map(|client| {
+            Arc::new(replay::RecordingEmbedding::new(client, recorder.clone()))
+                as Arc<dyn EmbeddingClient>
+        });
+        let run = self.reproducing(
+            Reproduction::Record(recorder.clone()),
+            |client| Arc::new(replay::SeededLlm::new(client.clone(), recorder.clone())),
+            embedding,
+        );
+
+        let traced = RagQuery {
+            debug: true,
+            ..query.clone()
+        };
+        let mut response = run.query_timed(&traced, user).await?;
+        response
+            .trace
+            .get_or_insert_with(Default::default)
+            .recording = Some(recorder.finish(query, &response.answer));
+        Ok(response)
+    }
+
+    /// Run a query within its deadline
+    async fn query_timed(&self, query: &RagQuery, user: &User) -> Result<RagResponse> {
+        let total_ms = self.total_ms(query);
+        let total = Duration::from_millis(total_ms);
+        let deadline = Deadline::after(total);
+
+        self.timeout_metrics
+            .within(
+                Stage::Query,
+                total + DEADLINE_GRACE,
+                self.query_within(query, user, deadline),
+            )
+            .await
+            .unwrap_or_else(|| {
+                Err(OtlError::LlmError(format!(
+                    "query timed out after {total_ms} ms"
+                )))
+            })
+    }
+
+    /// Deadline of a query in milliseconds, capped by the configured total
+    fn total_ms(&self, query: &RagQuery) -> u64 {
+        query
+            .timeout_ms
+            .map_or(self.config.timeouts.total_ms, |ms| {
+                ms.min(self.config.timeouts.total_ms)
+            })
+    }
+
+    /// Estimate the prompt size and cost of a query without answering it
+    ///
+    /// Analysis, retrieval and context packing run as in [`Self::query`],
+    /// then generation is skipped, so no LLM call is made. The answer cache
+    /// is not consulted and low-ranked chunks are not summarized: the
+    /// estimate is the prompt of a cache miss.
+    pub async fn estimate(&self, query: &RagQuery, user: &User) -> Result<QueryEstimate> {
+        let start_time = Instant::now();
+        let deadline = Deadline::after(Duration::from_millis(self.total_ms(query)));
+        let mut tracer = Tracer::new(false);
+        let analysis = self.analyze_question(query).await?;
+
+        if analysis.intent == QueryIntent::Definitional
+            && self.glossary_entry(&analysis, user).await.is_some()
+        {
+            return Ok(QueryEstimate {
+                path: AnswerPath::Glossary,
+                retrieval_ms: start_time.elapsed().as_millis() as u64,
+                ..Default::default()
+            });
+        }
+
+        let retrieval = self
+            .retrieve(query, &analysis, user, deadline, &mut tracer)
+            .await?;
+        let mut estimate = QueryEstimate {
+            path: AnswerPath::Extractive,
+            contexts: retrieval.results.len(),
+            backends: retrieval.backend_health,
+            acl_filtered: retrieval.acl_filtered,
+            fused: retrieval.fused,
+            warnings: retrieval.warnings,
+            ..Default::default()
+        };
+
+        if query.answer_mode == AnswerMode::Generative {
+            let context = self
+                .compress_context(&query.question, &retrieval.results, &analysis, false)
+                .await;
+            let included = self.prompt_contexts(&context);
+            let prompt = self.build_prompt(
+                &query.question,
+                &context,
+                &included,
+                &analysis,
+                query.persona.as_ref(),
+            );
+            let (_, model) = self.answer_model(&analysis);
+            let family = estimate::tokenizer_family(model.as_deref().unwrap_or_default());
+            let prompt_tokens = TokenCounter::new(family, 0).count(&prompt);
+
+            estimate.path = AnswerPath::Generation;
+            estimate.prompt_contexts = included.len();
+            estimate.prompt_chars = prompt.chars().count();
+            estimate.prompt_tokens = prompt_tokens;
+            estimate.completion_tokens = self.config.cost.output_tokens;
+            estimate.cost_usd = model
+                .as_deref()
+                .and_then(|m| self.config.cost.cost(m, prompt_tokens));
+            estimate.model = model;
+        }
+
+        estimate.retrieval_ms = start_time.elapsed().as_millis() as u64;
+        Ok(estimate)
+    }
+
+    /// Run a search within `budget`; an overrun fails the backend
+    async fn bounded_search(
+        &self,
+        stage: Stage,
+        budget: Duration,
+        search: impl Future<Output = Result<Vec<SearchResult>>>,
+    ) -> Result<Vec<SearchResult>> {
+        self.timeout_metrics
+            .within(stage, budget, search)
+            .await
+            .unwrap_or_else(|| {
+                Err(OtlError::SearchError(format!(
+                    "timed out after {} ms",
+                    budget.as_millis()
+                )))
+            })
+    }
+
+    async fn query_within(
+        &self,
+        query: &RagQuery,
+        user: &User,
+        deadline: Deadline,
+    ) -> Result<RagResponse> {
         let start_time = Instant::now();
 
         tracing::info!("RAG query started");
+        let mut tracer = Tracer::new(query.debug);
 
         // 1. Analyze the question
-        let analysis = self.analyze_query(&query.question).await?;
-        tracing::debug!("Query analyzed: intent={:?}", analysis.intent);
+        let analysis = self.analyze_question(query).await?;
+        tracer.stage("analysis");
+
+        // Definitional questions with an approved glossary entry skip retrieval
+        if analysis.intent == QueryIntent::Definitional {
+            if let Some(entry) = self.glossary_entry(&analysis, user).await {
+                tracing::info!("Answer served from glossary entry {}", entry.id);
+                tracer.stage("glossary");
+                let mut response = self.glossary_response(&entry, &analysis, start_time);
+                self.attach_freshness_warnings(&mut response.citations)
+                    .await;
+                self.moderate_response(&mut response, user, analysis.language)
+                    .await;
+                tracer.stage("moderation");
+                response.trace = tracer.finish();
+                return Ok(response);
+            }
+        }
+
+        // 2-6. Retrieve, filter and rank the context
+        let Retrieval {
+            results: final_results,
+            graph_context,
+            mut warnings,
+            masked,
+            searched_spaces,
+            ..
+        } = self
+            .retrieve(query, &analysis, user, deadline, &mut tracer)
+            .await?;
+
+        // 7-8. Produce the answer and its citations, reusing an answer
+        // generated for the same question and contexts
+        let cache_key = self.answer_cache_key(query, &analysis, &final_results);
+        let cached = match (&self.cache, &cache_key) {
+            (Some(cache), Some(key)) => cache.answer.get(key).await,
+            _ => None,
+        };
+        let from_cache = cached.is_some();
+        // Set when generation overran its budget (never cached)
+        let mut partial = false;
+        // Model that generated the answer
+        let mut model = None;
+        // Layout the answer was asked for (generated answers only)
+        let answer_format = format::answer_format(&analysis.expected_answer_type);
+        let mut format = AnswerFormat::Prose;
+        let (answer, citations, passages, structured_answer) = match (cached, query.answer_mode) {
+            (Some(cached), _) => {
+                tracing::info!("Answer served from cache");
+                tracer.stage("answer_cache");
+                model = cached.model;
+                if query.answer_mode == AnswerMode::Generative {
+                    format = answer_format;
+                }
+                (
+                    cached.answer,
+                    cached.citations,
+                    cached.passages,
+                    cached.structured_answer,
+                )
+            }
+            (None, AnswerMode::Generative) => {
+                let context = self
+                    .compress_context(&query.question, &final_results, &analysis, true)
+                    .await;
+                tracer.stage("compression");
+
+                let included = self.prompt_contexts(&context);
+                let prompt = self.build_prompt(
+                    &query.question,
+                    &context,
+                    &included,
+                    &analysis,
+                    query.persona.as_ref(),
+                );
+                tracing::info!("Calling LLM with prompt length: {} chars", prompt.len());
+                let (llm, answer_model) = self.answer_model(&analysis);
+                let generated = self
+                    .timeout_metrics
+                    .within(
+                        Stage::Generation,
+                        deadline.budget(self.config.timeouts.generation_ms),
+                        // Answers have their own cache, invalidated with documents
+                        llm.generate_uncached(&prompt),
+                    )
+                    .await;
+                tracer.stage("generation");
+
+                match generated {
+                    Some(answer) => {
+                        let answer = answer?;
+                        model = answer_model;
+                        tracing::info!("LLM response received: {} chars", answer.len());
+                        let refinement_budget = deadline.budget(self.config.timeouts.refinement_ms);
+                        let answer = self
+                            .timeout_metrics
+                            .within(
+                                Stage::Refinement,
+                                refinement_budget,
+                                self.repair_citations(
+                                    answer.clone(),
+                                    &prompt,
+                                    included.len(),
+                                    analysis.language,
+                                ),
+                            )
+                            .await
+                            .unwrap_or(answer);
+                        let (answer, citations) =
+                            self.extract_citations(&answer, &final_results, &included);
+                        let answer = format::apply(answer_format, &answer);
+                        format = answer_format;
+                        tracer.stage("citations");
+                        tracer.record(|t| {
+                            t.reranked = trace::candidates(&final_results);
+                            t.prompt = Some(prompt);
+                        });
+                        let structured = self
+                            .timeout_metrics
+                            .within(
+                                Stage::Refinement,
+                                deadline.budget(self.config.timeouts.refinement_ms),
+                                self.generate_structured_answer(
+                                    &query.question,
+                                    &answer,
+                                    &final_results,
+                                    &analysis,
+                                ),
+                            )
+                            .await
+                            .flatten();
+                        tracer.stage("structured_answer");
+                        (answer, citations, Vec::new(), structured)
+                    }
+                    None => {
+                        // Early return with the passages the answer would
+                        // have been generated from
+                        partial = true;
+                        warnings.push(
+                            "answer generation timed out; showing the most relevant passages"
+                                .to_string(),
+                        );
+                        tracer.record(|t| t.prompt = Some(prompt));
+                        let (answer, citations, passages) = self
+                            .extractive_answer(
+                                &query.question,
+                                &analysis,
+                                &final_results,
+                                &mut tracer,
+                            )
+                            .await;
+                        (answer, citations, passages, None)
+                    }
+                }
+            }
+            (None, AnswerMode::Extractive) => {
+                let (answer, citations, passages) = self
+                    .extractive_answer(&query.question, &analysis, &final_results, &mut tracer)
+                    .await;
+                (answer, citations, passages, None)
+            }
+        };
 
+        if let (Some(cache), Some(key)) = (&self.cache, cache_key) {
+            if !from_cache && !partial {
+                let mut document_ids: Vec<Uuid> =
+                    final_results.iter().map(|r| r.source.document_id).collect();
+                document_ids.sort();
+                document_ids.dedup();
+                let entry = CachedAnswer {
+                    answer: answer.clone(),
+                    citations: citations.clone(),
+                    passages: passages.clone(),
+                    structured_answer: structured_answer.clone(),
+                    model: model.clone(),
+                    document_ids,
+                    cached_at: std::time::SystemTime::now(),
+                };
+                cache.answer.put(key, entry).await;
+            }
+        }
+
+        // 9. Suggest follow-up questions
+        let suggestions = self.suggest_follow_ups(&analysis, &final_results, &graph_context);
+        tracer.stage("suggestions");
+
+        let processing_time_ms = start_time.elapsed().as_millis() as u64;
+
+        let mut response = RagResponse {
+            answer,
+            citations,
+            confidence: self.calculate_confidence(&final_results),
+            processing_time_ms,
+            suggestions,
+            passages,
+            structured_answer,
+            format,
+            moderation: None,
+            warnings,
+            withheld: Vec::new(),
+            model,
+            searched_spaces,
+            trace: None,
+        };
+
+        // Freshness changes without the contexts changing, so it is checked
+        // after the answer cache
+        self.attach_freshness_warnings(&mut response.citations)
+            .await;
+
+        // 10. Moderate sensitive topics before anything leaves the pipeline
+        self.moderate_response(&mut response, user, analysis.language)
+            .await;
+        self.explain_withheld(&mut response, &masked, analysis.language);
+        tracer.stage("moderation");
+
+        if analysis.intent == QueryIntent::Definitional
+            && query.answer_mode == AnswerMode::Generative
+            && response.moderation.is_none()
+        {
+            self.propose_glossary_candidate(&analysis, &response, &final_results);
+        }
+
+        response.trace = tracer.finish();
+        Ok(response)
+    }
+
+    /// Analyze the question of a query, answering in the requested language
+    async fn analyze_question(&self, query: &RagQuery) -> Result<QueryAnalysis> {
+        let mut analysis = self.analyze_query(&query.question).await?;
+        if let Some(language) = query.response_language {
+            analysis.language = language;
+        }
+        tracing::debug!(
+            "Query analyzed: intent={:?} ({:.2}, {:?}), language={}",
+            analysis.intent,
+            analysis.intent_confidence,
+            analysis.intent_source,
+            analysis.language
+        );
+        Ok(analysis)
+    }
+
+    /// Search all backends, then filter by ACL, fuse, boost and diversify
+    /// the results into the context for answering
+    ///
+    /// Fails only for a strict query with an unavailable backend.
+    async fn retrieve(
+        &self,
+        query: &RagQuery,
+        analysis: &QueryAnalysis,
+        user: &User,
+        deadline: Deadline,
+        tracer: &mut Tracer,
+    ) -> Result<Retrieval> {
         // 2. Execute searches in parallel
         tracing::debug!("Executing parallel searches");
-        let (vector_results, graph_results, keyword_results) = tokio::join!(
-            self.vector_store
-                .search(&query.question, self.config.vector_top_k),
-            self.search_graph_context(&analysis),
-            self.search_keywords(&analysis)
-        );
+        let search_budget = deadline.budget(self.config.timeouts.search_ms);
+        let (
+            (vector_results, vector_time),
+            (graph_results, graph_time),
+            (keyword_results, keyword_time),
+            (faq_results, faq_time),
+        ) = match &self.reproduction {
+            Some(Reproduction::Replay(recording)) => replay::recorded_searches(recording),
+            _ => tokio::join!(
+                trace::timed(
+                    self.bounded_search(
+                        Stage::VectorSearch,
+                        search_budget,
+                        self.vector_store
+                            .search(&query.question, self.config.vector_top_k)
+                    )
+                ),
+                trace::timed(self.bounded_search(
+                    Stage::GraphSearch,
+                    search_budget,
+                    self.search_graph_context(analysis)
+                )),
+                trace::timed(self.bounded_search(
+                    Stage::KeywordSearch,
+                    search_budget,
+                    self.search_keywords(analysis)
+                )),
+                trace::timed(self.bounded_search(
+                    Stage::FaqSearch,
+                    search_budget,
+                    self.search_faq(analysis)
+                ))
+            ),
+        };
         tracing::debug!("Searches completed");
+        tracer.parallel_stages(&[
+            ("vector_search", vector_time),
+            ("graph_search", graph_time),
+            ("keyword_search", keyword_time),
+            ("faq_search", faq_time),
+        ]);
+        tracer.candidates(SearchResultType::Vector, &vector_results, user);
+        tracer.candidates(SearchResultType::Graph, &graph_results, user);
+        tracer.candidates(SearchResultType::Keyword, &keyword_results, user);
+        tracer.candidates(SearchResultType::Faq, &faq_results, user);
+
+        // A failed backend is skipped unless the query is strict
+        let keyword_enabled = self.is_configured(SearchResultType::Keyword);
+        let faq_enabled = self.is_configured(SearchResultType::Faq);
+        if let Some(Reproduction::Record(recorder)) = &self.reproduction {
+            recorder.searches(vec![
+                readable_search(SearchResultType::Vector, true, &vector_results, user),
+                readable_search(SearchResultType::Graph, true, &graph_results, user),
+                readable_search(
+                    SearchResultType::Keyword,
+                    keyword_enabled,
+                    &keyword_results,
+                    user,
+                ),
+                readable_search(SearchResultType::Faq, faq_enabled, &faq_results, user),
+            ]);
+        }
+        let backend_health = [
+            health::check(SearchResultType::Vector, true, &vector_results, vector_time),
+            health::check(SearchResultType::Graph, true, &graph_results, graph_time),
+            health::check(
+                SearchResultType::Keyword,
+                keyword_enabled,
+                &keyword_results,
+                keyword_time,
+            ),
+            health::check(SearchResultType::Faq, faq_enabled, &faq_results, faq_time),
+        ];
+        tracer.record(|t| t.backend_health = backend_health.to_vec());
+        if self.config.strict_backends || query.strict {
+            if let Some(e) = health::strict_error(&backend_health) {
+                return Err(e);
+            }
+        }
+        let warnings = health::warnings(&backend_health);
 
         // 3. Collect results
         let mut all_results = Vec::new();
@@ -240,219 +1226,889 @@ impl HybridRagOrchestrator {
             all_results.extend(results);
         }
 
-        // 4. ACL filtering
-        let filtered_results = self.filter_by_acl(all_results, user);
+        if let Ok(results) = faq_results {
+            tracing::debug!("FAQ search returned {} results", results.len());
+            all_results.extend(results);
+        }
+
+        // 4. ACL filtering, then drop results outside the knowledge space,
+        // text from corrupted chunks and superseded document versions
+        let (mut filtered_results, denied) = self.filter_by_acl(all_results, user);
+        let searched_spaces = match &query.scope {
+            Some(scope) => Self::restrict_to_scope(&mut filtered_results, scope),
+            None => Vec::new(),
+        };
+        self.withhold_corrupted_chunks(&mut filtered_results).await;
+        if !query.include_superseded {
+            self.exclude_superseded(&mut filtered_results).await;
+        }
         tracing::debug!("ACL filtered to {} results", filtered_results.len());
+        tracer.record(|t| t.acl_filtered = trace::withheld(&denied));
+        let acl_filtered = denied.len();
+        tracer.stage("acl_filter");
 
-        // 5. Merge and rank results using RRF
-        let merged_results = self.merge_results(filtered_results);
-        tracing::debug!("Merged to {} results", merged_results.len());
+        // Keep the graph neighborhood for follow-up suggestions
+        let graph_context: Vec<_> = filtered_results
+            .iter()
+            .filter(|r| r.result_type == SearchResultType::Graph)
+            .cloned()
+            .collect();
 
-        // 6. Take top-k
-        let final_results: Vec<_> = merged_results
+        // FAQ entries skip fusion and lead the context
+        let (mut final_results, filtered_results): (Vec<_>, Vec<_>) = filtered_results
             .into_iter()
-            .take(self.config.final_top_k)
-            .collect();
+            .partition(|r| r.result_type == SearchResultType::Faq);
+        final_results.truncate(self.config.final_top_k);
+
+        // 5. Merge and rank results using RRF, then boost by document metadata
+        let mut merged_results = self.merge_results(filtered_results);
+        self.apply_ranking_boosts(&mut merged_results, user).await;
+        self.apply_feedback_priors(&mut merged_results);
+        tracing::debug!("Merged to {} results", merged_results.len());
+        let fused = merged_results.len();
+        tracer.record(|t| t.fused = trace::candidates(&merged_results));
+
+        // 6. Take a diverse top-k
+        final_results.extend(diversify::diversify(
+            merged_results,
+            self.config.final_top_k - final_results.len(),
+            &self.config.diversity,
+        ));
         tracing::debug!("Final top-k: {} results", final_results.len());
+        tracer.stage("fusion");
 
-        // 7. Build prompt and generate response
-        let prompt = self.build_prompt(&query.question, &final_results, &analysis);
-        tracing::info!("Calling LLM with prompt length: {} chars", prompt.len());
-        let answer = self.llm_client.generate(&prompt).await?;
-        tracing::info!("LLM response received: {} chars", answer.len());
+        // 6a. Stitch neighboring chunks onto contexts cut mid-passage
+        if self.config.context_expansion.applies(analysis.intent) {
+            self.expand_contexts(&mut final_results).await;
+            tracer.stage("context_expansion");
+        }
 
-        // 8. Extract citations
-        let citations = self.extract_citations(&answer, &final_results);
+        // 6b. Mask restricted values the user may not see
+        let masked = self.mask_restricted(&mut final_results, user, analysis.language);
+
+        Ok(Retrieval {
+            results: final_results,
+            graph_context,
+            backend_health: backend_health.to_vec(),
+            acl_filtered,
+            fused,
+            warnings,
+            masked,
+            searched_spaces,
+        })
+    }
 
-        let processing_time_ms = start_time.elapsed().as_millis() as u64;
+    /// Approved glossary entry for the term a definitional question asks
+    /// about, if the user may read it
+    async fn glossary_entry(&self, analysis: &QueryAnalysis, user: &User) -> Option<GlossaryEntry> {
+        let glossary = self.glossary.as_ref()?;
+        for term in glossary::defined_terms(&analysis.question) {
+            match glossary.find_by_term(&term).await {
+                Ok(entries) => {
+                    let entry = entries
+                        .into_iter()
+                        .find(|e| e.status == GlossaryStatus::Approved && e.acl.can_access(user));
+                    if entry.is_some() {
+                        return entry;
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Glossary lookup failed: {e}");
+                    return None;
+                }
+            }
+        }
+        None
+    }
 
-        Ok(RagResponse {
-            answer,
+    /// Top passages of `results` with highlighted sentences, rendered as the
+    /// answer, and a citation per result
+    async fn extractive_answer(
+        &self,
+        question: &str,
+        analysis: &QueryAnalysis,
+        results: &[SearchResult],
+        tracer: &mut Tracer,
+    ) -> (String, Vec<Citation>, Vec<ExtractedPassage>) {
+        let passages = extractive::extract_passages(
+            question,
+            &analysis.keywords,
+            results,
+            self.embedding_client.as_deref(),
+            &self.config.extractive,
+        )
+        .await;
+        tracing::info!("Extractive answer with {} passages", passages.len());
+        tracer.record(|t| {
+            t.reranked = passages
+                .iter()
+                .filter_map(|p| {
+                    let result = results.get((p.index as usize).checked_sub(1)?)?;
+                    Some(TraceCandidate::new(result, p.score))
+                })
+                .collect();
+        });
+
+        let answer = extractive::render_extractive_answer(
+            &passages,
+            PromptTemplate::for_language(analysis.language),
+        );
+        let citations = results
+            .iter()
+            .enumerate()
+            .map(|(i, result)| citation_for(i + 1, result))
+            .collect();
+        tracer.stage("extraction");
+        (answer, citations, passages)
+    }
+
+    /// Response built from a glossary entry, citing its source document
+    fn glossary_response(
+        &self,
+        entry: &GlossaryEntry,
+        analysis: &QueryAnalysis,
+        start_time: Instant,
+    ) -> RagResponse {
+        let citations: Vec<Citation> = entry
+            .document_id
+            .map(|document_id| Citation {
+                index: 1,
+                text: entry.definition.chars().take(200).collect(),
+                source: SourceReference::new(document_id),
+                document_title: entry.source.clone().unwrap_or_else(|| entry.term.clone()),
+                freshness: None,
+            })
+            .into_iter()
+            .collect();
+
+        RagResponse {
+            answer: glossary::render_glossary_answer(
+                entry,
+                PromptTemplate::for_language(analysis.language),
+                !citations.is_empty(),
+            ),
             citations,
-            confidence: self.calculate_confidence(&final_results),
-            processing_time_ms,
-        })
+            confidence: 1.0,
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+            suggestions: self.suggest_follow_ups(analysis, &[], &[]),
+            passages: Vec::new(),
+            structured_answer: None,
+            format: AnswerFormat::Prose,
+            moderation: None,
+            warnings: Vec::new(),
+            withheld: Vec::new(),
+            model: None,
+            searched_spaces: Vec::new(),
+            trace: None,
+        }
     }
 
-    /// Analyze the query to extract intent, entities, and keywords
-    async fn analyze_query(&self, question: &str) -> Result<QueryAnalysis> {
-        // Simple rule-based analysis (can be enhanced with LLM)
-        let question_lower = question.to_lowercase();
-
-        // Detect intent
-        let intent = if question_lower.contains("어떻게")
-            || question_lower.contains("절차")
-            || question_lower.contains("방법")
-            || question_lower.contains("how")
-        {
-            QueryIntent::Procedural
-        } else if question_lower.contains("차이")
-            || question_lower.contains("비교")
-            || question_lower.contains("vs")
-        {
-            QueryIntent::Comparative
-        } else if question_lower.contains("무엇")
-            || question_lower.contains("뭐")
-            || question_lower.contains("what is")
-        {
-            QueryIntent::Definitional
-        } else if question_lower.contains("며칠")
-            || question_lower.contains("몇")
-            || question_lower.contains("언제")
-        {
-            QueryIntent::Factual
-        } else if question_lower.contains("경우")
-            || question_lower.contains("만약")
-            || question_lower.contains("if")
+    /// Propose a well-supported definitional answer as a glossary candidate
+    ///
+    /// The candidate carries the most restrictive ACL of the cited
+    /// passages. It is stored in the background and skipped when the term
+    /// already has an entry.
+    fn propose_glossary_candidate(
+        &self,
+        analysis: &QueryAnalysis,
+        response: &RagResponse,
+        results: &[SearchResult],
+    ) {
+        let Some(glossary) = self.glossary.clone() else {
+            return;
+        };
+        if response.citations.is_empty()
+            || response.confidence < self.config.glossary_candidate_min_confidence
         {
-            QueryIntent::Conditional
+            return;
+        }
+        let terms = glossary::defined_terms(&analysis.question);
+        let Some(phrase) = terms.last() else {
+            return;
+        };
+        let Some(definition) = glossary::candidate_definition(&response.answer) else {
+            return;
+        };
+
+        // The analyzer knows which trailing syllables are particles
+        let keywords = self.keyword_analyzer.keywords(phrase);
+        let term = if keywords.is_empty() {
+            terms[0].clone()
         } else {
-            QueryIntent::General
+            keywords.join(" ")
         };
 
+        let cited: Vec<Uuid> = response
+            .citations
+            .iter()
+            .map(|c| c.source.document_id)
+            .collect();
+        let acl = results
+            .iter()
+            .filter(|r| cited.contains(&r.source.document_id))
+            .map(|r| &r.acl)
+            .max_by_key(|acl| acl.access_level)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut candidate = GlossaryEntry::new(term, definition);
+        candidate.status = GlossaryStatus::Candidate;
+        candidate.acl = acl;
+        candidate.document_id = response.citations.first().map(|c| c.source.document_id);
+        candidate.source = response.citations.first().map(|c| c.document_title.clone());
+
+        tokio::spawn(async move {
+            match glossary.propose(&candidate).await {
+                Ok(true) => tracing::info!("Proposed glossary candidate: {}", candidate.term),
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to propose glossary candidate: {e}"),
+            }
+        });
+    }
+
+    /// Mask the values of sensitive topics the user has no clearance for
+    /// in the contexts, returning the number masked per rule
+    ///
+    /// Masked contexts fingerprint differently, so the answer cache keeps
+    /// answers for each clearance apart.
+    fn mask_restricted(
+        &self,
+        results: &mut [SearchResult],
+        user: &User,
+        language: Language,
+    ) -> Vec<(String, usize)> {
+        if !self.moderator.masks_context() {
+            return Vec::new();
+        }
+        let marker = PromptTemplate::for_language(language).restricted_marker;
+
+        let mut masked: Vec<(String, usize)> = Vec::new();
+        for result in results.iter_mut() {
+            let masking = self.moderator.mask(&result.content, user, marker);
+            if masking.values.is_empty() {
+                continue;
+            }
+            result.content = masking.text;
+            for (rule, count) in masking.values {
+                match masked.iter_mut().find(|(name, _)| *name == rule) {
+                    Some((_, total)) => *total += count,
+                    None => masked.push((rule, count)),
+                }
+            }
+        }
+        masked
+    }
+
+    /// Tell the user what was masked in the context and how to get access
+    ///
+    /// Refused answers already point to HR and get no notice.
+    fn explain_withheld(
+        &self,
+        response: &mut RagResponse,
+        masked: &[(String, usize)],
+        language: Language,
+    ) {
+        let refused = response
+            .moderation
+            .as_ref()
+            .is_some_and(|d| d.action == ModerationAction::Refuse);
+        if masked.is_empty() || refused {
+            return;
+        }
+        let template = PromptTemplate::for_language(language);
+        response.withheld = self.moderator.withheld(masked, template);
+        response.answer = format!(
+            "{}\n\n{}",
+            response.answer.trim_end(),
+            template.withheld_notice(&response.withheld)
+        );
+    }
+
+    /// Apply sensitive-topic rules to a finished response
+    ///
+    /// Citation snippets come straight from retrieval, so they are redacted
+    /// even when the answer itself is clean. Passages and structured answers
+    /// carry unredacted text and are dropped once the answer is moderated.
+    async fn moderate_response(&self, response: &mut RagResponse, user: &User, language: Language) {
+        if !self.moderator.is_enabled() {
+            return;
+        }
+        let template = PromptTemplate::for_language(language);
+
+        let moderation = self
+            .moderator
+            .moderate(&response.answer, user, self.llm_client.as_ref(), template)
+            .await;
+        let mut decision = moderation.decision;
+        response.answer = moderation.answer;
+
+        let mut snippet_rules: Vec<String> = Vec::new();
+        let mut snippet_sentences = 0;
+        for citation in &mut response.citations {
+            let redaction = self
+                .moderator
+                .redact(&citation.text, user, template.redacted_marker);
+            if redaction.sentences > 0 {
+                citation.text = redaction.text;
+                snippet_sentences += redaction.sentences;
+                snippet_rules.extend(redaction.rules);
+            }
+        }
+        snippet_rules.sort();
+        snippet_rules.dedup();
+        response
+            .suggestions
+            .retain(|s| !self.moderator.flags(s, user));
+
+        match decision.as_ref().map(|d| d.action) {
+            Some(ModerationAction::Refuse) => {
+                response.citations.clear();
+                response.passages.clear();
+                response.structured_answer = None;
+            }
+            Some(ModerationAction::Redact) => {
+                response.passages.clear();
+                response.structured_answer = None;
+            }
+            None if snippet_sentences > 0 => {
+                decision = Some(ModerationDecision {
+                    action: ModerationAction::Redact,
+                    rules: snippet_rules,
+                    detector: ModerationDetector::Pattern,
+                    redacted_sentences: snippet_sentences,
+                });
+            }
+            None => {}
+        }
+
+        if let Some(decision) = &decision {
+            tracing::info!(
+                target: "audit",
+                user_id = %user.user_id,
+                action = ?decision.action,
+                rules = ?decision.rules,
+                detector = ?decision.detector,
+                redacted_sentences = decision.redacted_sentences,
+                "Answer moderated"
+            );
+        }
+        response.moderation = decision;
+    }
+
+    /// Analyze the query to extract intent, entities, and keywords
+    async fn analyze_query(&self, question: &str) -> Result<QueryAnalysis> {
+        let (prediction, intent_source) = self.classify_intent(question).await;
+        let intent = prediction.intent;
+
         // Determine expected answer type
         let expected_answer_type = match intent {
             QueryIntent::Procedural => AnswerType::List,
             QueryIntent::Comparative => AnswerType::Comparison,
-            QueryIntent::Factual => AnswerType::SingleFact,
             QueryIntent::Definitional => AnswerType::Explanation,
+            _ if format::is_yes_no_question(question) => AnswerType::YesNo,
+            QueryIntent::Factual => AnswerType::SingleFact,
             _ => AnswerType::Unknown,
         };
 
-        // Extract keywords (simple whitespace tokenization, filter stopwords)
-        let stopwords = [
-            "은", "는", "이", "가", "를", "을", "의", "에", "와", "과", "the", "a", "is", "are",
-            "what", "how",
-        ];
-        let keywords: Vec<String> = question
-            .split_whitespace()
-            .filter(|w| w.len() > 1 && !stopwords.contains(&w.to_lowercase().as_str()))
-            .map(|s| s.to_string())
-            .collect();
+        // Extract keywords: lemmatized nouns without particles or stopwords
+        let keywords = self.keyword_analyzer.keywords(question);
+        let synonyms = self
+            .synonyms
+            .as_ref()
+            .map(|registry| registry.expand(&keywords))
+            .unwrap_or_default();
 
         Ok(QueryAnalysis {
             question: question.to_string(),
             intent,
+            intent_confidence: prediction.confidence,
+            intent_source,
             detected_entities: Vec::new(), // Would be populated by NER
             keywords,
+            synonyms,
             expected_answer_type,
+            language: detect_language(question),
         })
     }
 
+    /// Detect the intent, preferring a confident classifier over the rules
+    async fn classify_intent(&self, question: &str) -> (IntentPrediction, IntentSource) {
+        if let Some(classifier) = &self.intent_classifier {
+            match classifier.classify(question).await {
+                Ok(Some(prediction))
+                    if prediction.confidence >= self.config.intent_min_confidence =>
+                {
+                    return (prediction, IntentSource::Classifier);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Intent classifier {} failed: {e}", classifier.name()),
+            }
+        }
+        (intent::rule_intent(question), IntentSource::Rules)
+    }
+
     /// Search graph for context related to detected entities
+    ///
+    /// With graph access configured, entities in the question are resolved
+    /// and expanded for `graph_depth` hops (see [`graph_context`]); otherwise
+    /// the graph search backend is queried with the keywords.
     async fn search_graph_context(&self, analysis: &QueryAnalysis) -> Result<Vec<SearchResult>> {
-        // Use keywords as starting points for graph traversal
-        let query = analysis.keywords.join(" ");
-        self.graph_store
-            .search(&query, self.config.vector_top_k)
-            .await
+        let Some(backend) = &self.graph_context else {
+            let query = analysis.search_terms().join(" ");
+            return self
+                .graph_store
+                .search(&query, self.config.vector_top_k)
+                .await;
+        };
+
+        let policy = graph_context::PredicatePolicy::from_ontology(&self.ontology_classes);
+        graph_context::retrieve(
+            backend.as_ref(),
+            analysis,
+            &policy,
+            self.config.graph_depth,
+            self.config.vector_top_k,
+        )
+        .await
     }
 
     /// Search keywords if keyword store is available
     async fn search_keywords(&self, analysis: &QueryAnalysis) -> Result<Vec<SearchResult>> {
         if let Some(ref store) = self.keyword_store {
-            let query = analysis.keywords.join(" ");
+            let query = analysis.search_terms().join(" ");
             store.search(&query, self.config.keyword_top_k).await
         } else {
             Ok(Vec::new())
         }
     }
 
-    /// Filter results based on user's access permissions
-    fn filter_by_acl(&self, results: Vec<SearchResult>, user: &User) -> Vec<SearchResult> {
-        results
+    /// Approved FAQ entries sharing enough keywords with the question, best
+    /// first
+    ///
+    /// Entries cite their first source document; entries without citations
+    /// are skipped.
+    async fn search_faq(&self, analysis: &QueryAnalysis) -> Result<Vec<SearchResult>> {
+        let Some(ref faq) = self.faq else {
+            return Ok(Vec::new());
+        };
+        if analysis.keywords.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut results: Vec<SearchResult> = faq
+            .find_approved(&analysis.keywords)
+            .await?
             .into_iter()
-            .filter(|r| r.acl.can_access(user))
-            .collect()
+            .filter_map(|entry| {
+                let overlap = keyword_overlap(&entry.keywords, &analysis.keywords);
+                if overlap < self.config.faq_min_similarity {
+                    return None;
+                }
+                let source = entry.citations.first()?.source.clone();
+                Some(SearchResult {
+                    content: format!(
+                        "Q: {}\nA: {}",
+                        entry.question,
+                        citations::strip_markers(&entry.answer)
+                    ),
+                    score: overlap,
+                    source,
+                    acl: entry.acl,
+                    result_type: SearchResultType::Faq,
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(MAX_FAQ_RESULTS);
+        Ok(results)
+    }
+
+    /// Keep the results inside the knowledge spaces of a scope, returning
+    /// the spaces searched
+    ///
+    /// A scope allowing fallback keeps every result when none comes from
+    /// its departments, and `global` is added to the spaces searched.
+    fn restrict_to_scope(results: &mut Vec<SearchResult>, scope: &RetrievalScope) -> Vec<String> {
+        let mut searched = scope.spaces.clone();
+        if scope.fallback && !results.is_empty() && !results.iter().any(|r| scope.holds(&r.acl)) {
+            tracing::debug!(
+                "No results in spaces {}; searching all documents",
+                scope.spaces.join(", ")
+            );
+            searched.push(otl_core::space::GLOBAL_SPACE.to_string());
+            return searched;
+        }
+        let before = results.len();
+        results.retain(|r| scope.admits(&r.acl));
+        tracing::debug!(
+            "Excluded {} results outside spaces {}",
+            before - results.len(),
+            scope.spaces.join(", ")
+        );
+        searched
+    }
+
+    /// Split results into those the user may access and those denied
+    fn filter_by_acl(
+        &self,
+        results: Vec<SearchResult>,
+        user: &User,
+    ) -> (Vec<SearchResult>, Vec<SearchResult>) {
+        results.into_iter().partition(|r| r.acl.can_access(user))
     }
 
     /// Merge results using Reciprocal Rank Fusion (RRF)
     fn merge_results(&self, results: Vec<SearchResult>) -> Vec<SearchResult> {
-        // Group by content hash to handle duplicates
-        let mut score_map: HashMap<String, (f32, SearchResult)> = HashMap::new();
+        fusion::merge_results(results, &self.config)
+    }
 
-        // Sort results by score to get ranks
-        let mut vector_results: Vec<_> = results
-            .iter()
-            .filter(|r| r.result_type == SearchResultType::Vector)
-            .collect();
-        let mut graph_results: Vec<_> = results
-            .iter()
-            .filter(|r| r.result_type == SearchResultType::Graph)
-            .collect();
-        let mut keyword_results: Vec<_> = results
+    /// Answer cache key for a query, if its answer may be cached
+    ///
+    /// Debug queries bypass the cache so their trace covers generation.
+    fn answer_cache_key(
+        &self,
+        query: &RagQuery,
+        analysis: &QueryAnalysis,
+        contexts: &[SearchResult],
+    ) -> Option<AnswerKey> {
+        if self.cache.is_none() || query.debug || contexts.is_empty() {
+            return None;
+        }
+        // Personas change the prompt, so each gets its own answers
+        let variant = format!(
+            "{:?}/{}/{:?}",
+            query.answer_mode, analysis.language, query.persona
+        );
+        Some(AnswerKey::new(&query.question, &variant, contexts))
+    }
+
+    /// Multiply fused scores by the configured document metadata boosts
+    ///
+    /// Ranking falls back to the fused scores if metadata cannot be loaded.
+    async fn apply_ranking_boosts(&self, results: &mut [SearchResult], user: &User) {
+        if !self.config.ranking.enabled || results.is_empty() {
+            return;
+        }
+
+        let mut ids: Vec<Uuid> = results.iter().map(|r| r.source.document_id).collect();
+        ids.sort();
+        ids.dedup();
+        match self.documents(&ids).await {
+            Some(Ok(documents)) => {
+                let documents = documents.into_iter().map(|d| (d.id, d)).collect();
+                self.config
+                    .ranking
+                    .apply(results, &documents, user, self.now());
+            }
+            Some(Err(e)) => tracing::warn!("Skipping ranking boosts: {}", e),
+            None => {}
+        }
+    }
+
+    /// Multiply fused scores by the popularity priors learned from feedback
+    ///
+    /// Seeded runs record the multipliers applied; replays read them back.
+    fn apply_feedback_priors(&self, results: &mut [SearchResult]) {
+        let multipliers: Vec<f32> = match (&self.reproduction, &self.feedback) {
+            (Some(Reproduction::Replay(recording)), _) => results
+                .iter()
+                .map(|r| {
+                    let chunk = (r.source.document_id, r.source.chunk_index);
+                    recording
+                        .feedback_priors
+                        .iter()
+                        .find(|(d, c, _)| (*d, *c) == chunk)
+                        .map_or(1.0, |(_, _, multiplier)| *multiplier)
+                })
+                .collect(),
+            (_, Some(feedback)) if feedback.config().enabled && !feedback.is_empty() => {
+                let now = self.now();
+                results
+                    .iter()
+                    .map(|r| feedback.multiplier(&r.source, now))
+                    .collect()
+            }
+            _ => return,
+        };
+        if let Some(Reproduction::Record(recorder)) = &self.reproduction {
+            recorder.feedback_priors(results, &multipliers);
+        }
+        if multipliers.iter().all(|&m| m == 1.0) {
+            return;
+        }
+        for (result, multiplier) in results.iter_mut().zip(multipliers) {
+            result.score *= multiplier;
+        }
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+
+    /// Whether a retrieval backend is configured (as recorded when replaying)
+    fn is_configured(&self, backend: SearchResultType) -> bool {
+        if let Some(Reproduction::Replay(recording)) = &self.reproduction {
+            return recording.search(&backend).is_some_and(|s| s.configured);
+        }
+        match backend {
+            SearchResultType::Vector | SearchResultType::Graph => true,
+            SearchResultType::Keyword => self.keyword_store.is_some(),
+            SearchResultType::Faq => self.faq.is_some(),
+        }
+    }
+
+    /// Current time, or the time of the recorded run when reproducing one
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.reproduction
+            .as_ref()
+            .map_or_else(chrono::Utc::now, Reproduction::now)
+    }
+
+    /// Metadata of the given documents; `None` without a metadata store
+    ///
+    /// Seeded runs record the documents read; replays read the recording.
+    async fn documents(&self, ids: &[Uuid]) -> Option<Result<Vec<DocumentMetadata>>> {
+        let store = match (&self.reproduction, &self.metadata_store) {
+            (Some(Reproduction::Replay(recording)), _) => {
+                let documents = recording.documents.iter().filter(|d| ids.contains(&d.id));
+                return Some(Ok(documents.cloned().collect()));
+            }
+            (_, store) => store.as_ref()?,
+        };
+        let documents = store.get_documents(ids).await;
+        if let (Some(Reproduction::Record(recorder)), Ok(documents)) =
+            (&self.reproduction, &documents)
+        {
+            recorder.documents(documents);
+        }
+        Some(documents)
+    }
+
+    /// Corrupted chunks of the given documents; `None` without a metadata
+    /// store
+    ///
+    /// Seeded runs record the chunks read; replays read the recording.
+    async fn corrupted_chunks(&self, ids: &[Uuid]) -> Option<Result<Vec<(Uuid, u32)>>> {
+        let store = match (&self.reproduction, &self.metadata_store) {
+            (Some(Reproduction::Replay(recording)), _) => {
+                let chunks = recording.corrupted_chunks.iter();
+                return Some(Ok(chunks
+                    .filter(|(id, _)| ids.contains(id))
+                    .copied()
+                    .collect()));
+            }
+            (_, store) => store.as_ref()?,
+        };
+        let chunks = store.corrupted_chunks(ids).await;
+        if let (Some(Reproduction::Record(recorder)), Ok(chunks)) = (&self.reproduction, &chunks) {
+            recorder.corrupted_chunks(chunks);
+        }
+        Some(chunks)
+    }
+
+    /// Drop results read from chunks the last integrity check found
+    /// corrupted, so their text is neither answered from nor cited
+    ///
+    /// Results are kept if the corrupted chunks cannot be loaded.
+    async fn withhold_corrupted_chunks(&self, results: &mut Vec<SearchResult>) {
+        let mut ids: Vec<Uuid> = results
             .iter()
-            .filter(|r| r.result_type == SearchResultType::Keyword)
+            .filter(|r| r.source.chunk_index.is_some())
+            .map(|r| r.source.document_id)
             .collect();
-
-        vector_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        graph_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        keyword_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-
-        // Calculate RRF scores
-        let k = self.config.rrf_k;
-
-        for (rank, result) in vector_results.iter().enumerate() {
-            let rrf_score = self.config.vector_weight / (k + rank as f32 + 1.0);
-            let key = hash_content(&result.content);
-            score_map
-                .entry(key)
-                .and_modify(|(score, _)| *score += rrf_score)
-                .or_insert((rrf_score, (*result).clone()));
-        }
-
-        for (rank, result) in graph_results.iter().enumerate() {
-            let rrf_score = self.config.graph_weight / (k + rank as f32 + 1.0);
-            let key = hash_content(&result.content);
-            score_map
-                .entry(key)
-                .and_modify(|(score, _)| *score += rrf_score)
-                .or_insert((rrf_score, (*result).clone()));
-        }
-
-        for (rank, result) in keyword_results.iter().enumerate() {
-            let rrf_score = self.config.keyword_weight / (k + rank as f32 + 1.0);
-            let key = hash_content(&result.content);
-            score_map
-                .entry(key)
-                .and_modify(|(score, _)| *score += rrf_score)
-                .or_insert((rrf_score, (*result).clone()));
-        }
-
-        // Sort by RRF score and return
-        let mut merged: Vec<_> = score_map
-            .into_values()
-            .map(|(score, mut result)| {
-                result.score = score;
-                result
+        if ids.is_empty() {
+            return;
+        }
+        ids.sort();
+        ids.dedup();
+
+        let corrupted: HashSet<(Uuid, u32)> = match self.corrupted_chunks(&ids).await {
+            Some(Ok(chunks)) => chunks.into_iter().collect(),
+            Some(Err(e)) => {
+                tracing::warn!("Skipping chunk integrity filter: {}", e);
+                return;
+            }
+            None => return,
+        };
+        let before = results.len();
+        results.retain(|r| {
+            r.source.chunk_index.map_or(true, |index| {
+                !corrupted.contains(&(r.source.document_id, index))
             })
-            .collect();
+        });
+        if results.len() < before {
+            tracing::warn!(
+                "Withheld {} results read from corrupted chunks",
+                before - results.len()
+            );
+        }
+    }
+
+    /// Stitch the chunks around each context onto it within the context
+    /// budget
+    ///
+    /// Contexts are left as retrieved if the chunks cannot be loaded.
+    async fn expand_contexts(&self, results: &mut [SearchResult]) {
+        let window = self.config.context_expansion.window;
+        let positions = expand::neighbor_positions(results, window);
+        if positions.is_empty() {
+            return;
+        }
+        match self.neighbor_chunks(&positions).await {
+            Some(Ok(neighbors)) => {
+                let added =
+                    expand::expand(results, &neighbors, window, self.config.max_context_length);
+                tracing::debug!("Stitched {} neighboring chunks onto contexts", added);
+            }
+            Some(Err(e)) => tracing::warn!("Skipping context expansion: {}", e),
+            None => {}
+        }
+    }
+
+    /// Chunks at the given positions; `None` without a metadata store
+    ///
+    /// Seeded runs record the chunks read; replays read the recording.
+    async fn neighbor_chunks(
+        &self,
+        positions: &[(Uuid, u32)],
+    ) -> Option<Result<Vec<DocumentChunk>>> {
+        let store = match (&self.reproduction, &self.metadata_store) {
+            (Some(Reproduction::Replay(recording)), _) => {
+                let chunks = recording.neighbor_chunks.iter();
+                return Some(Ok(chunks
+                    .filter(|c| positions.contains(&(c.document_id, c.chunk_index)))
+                    .cloned()
+                    .collect()));
+            }
+            (_, store) => store.as_ref()?,
+        };
+        let chunks = store.get_chunks_at(positions).await;
+        if let (Some(Reproduction::Record(recorder)), Ok(chunks)) = (&self.reproduction, &chunks) {
+            recorder.neighbor_chunks(chunks);
+        }
+        Some(chunks)
+    }
+
+    /// Drop results from documents superseded by a newer version
+    ///
+    /// A document is superseded if it names a replacement or a newer version
+    /// of it was also retrieved (see [`otl_core::freshness::superseded`]).
+    /// Results are kept if metadata cannot be loaded.
+    async fn exclude_superseded(&self, results: &mut Vec<SearchResult>) {
+        if results.is_empty() {
+            return;
+        }
+        let mut ids: Vec<Uuid> = results.iter().map(|r| r.source.document_id).collect();
+        ids.sort();
+        ids.dedup();
+
+        let superseded = match self.documents(&ids).await {
+            Some(Ok(documents)) => otl_core::freshness::superseded(&documents),
+            Some(Err(e)) => {
+                tracing::warn!("Skipping superseded version filter: {}", e);
+                return;
+            }
+            None => return,
+        };
+        let before = results.len();
+        results.retain(|r| !superseded.contains(&r.source.document_id));
+        if results.len() < before {
+            tracing::debug!(
+                "Excluded {} results from superseded document versions",
+                before - results.len()
+            );
+        }
+    }
+
+    /// Warn about citations of documents past their review date or
+    /// superseded by a newer version
+    ///
+    /// Citations are left unmarked if metadata cannot be loaded.
+    async fn attach_freshness_warnings(&self, citations: &mut [Citation]) {
+        if citations.is_empty() {
+            return;
+        }
 
-        merged.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        merged
+        let mut ids: Vec<Uuid> = citations.iter().map(|c| c.source.document_id).collect();
+        ids.sort();
+        ids.dedup();
+        let documents: HashMap<Uuid, _> = match self.documents(&ids).await {
+            Some(Ok(documents)) => documents.into_iter().map(|d| (d.id, d)).collect(),
+            Some(Err(e)) => {
+                tracing::warn!("Skipping freshness warnings: {}", e);
+                return;
+            }
+            None => return,
+        };
+        let today = self.now().date_naive();
+        for citation in citations {
+            citation.freshness = documents
+                .get(&citation.source.document_id)
+                .and_then(|doc| otl_core::freshness::assess(doc, today));
+        }
     }
 
-    /// Build the LLM prompt with context
+    /// Positions of the contexts that fit the prompt budget
+    ///
+    /// Contexts emptied by compression are skipped. The prompt numbers the
+    /// included contexts consecutively from 1.
+    fn prompt_contexts(&self, results: &[SearchResult]) -> Vec<usize> {
+        let mut included = Vec::new();
+        let mut total_length = 0;
+        for (i, result) in results.iter().enumerate() {
+            if total_length + result.content.len() > self.config.max_context_length {
+                break;
+            }
+            if result.content.is_empty() {
+                continue;
+            }
+            total_length += result.content.len();
+            included.push(i);
+        }
+        included
+    }
+
+    /// Build the LLM prompt with the `included` contexts
     fn build_prompt(
         &self,
         question: &str,
         results: &[SearchResult],
-        _analysis: &QueryAnalysis,
+        included: &[usize],
+        analysis: &QueryAnalysis,
+        persona: Option<&Persona>,
     ) -> String {
+        let template = PromptTemplate::for_language(analysis.language);
         let mut prompt = String::new();
 
         // System instruction
         prompt.push_str("<s>\n");
-        prompt.push_str("당신은 조직의 지식 전문가입니다.\n");
-        prompt.push_str("제공된 컨텍스트 정보만을 사용하여 질문에 답변하세요.\n");
-        prompt.push_str("답변에 사용한 정보의 출처를 반드시 [출처: N] 형식으로 명시하세요.\n");
-        prompt
-            .push_str("컨텍스트에 없는 정보는 \"해당 정보를 찾을 수 없습니다\"라고 답변하세요.\n");
+        for line in [
+            template.persona_role(persona).as_str(),
+            template.context_only,
+            template.citation_rule,
+            template.not_found_rule,
+            template.language_rule,
+        ] {
+            prompt.push_str(line);
+            prompt.push('\n');
+        }
+        if let Some(rule) =
+            template.format_rule(format::answer_format(&analysis.expected_answer_type))
+        {
+            prompt.push_str(rule);
+            prompt.push('\n');
+        }
+        if included
+            .iter()
+            .any(|&i| results[i].content.contains(template.restricted_marker))
+        {
+            prompt.push_str(template.restricted_rule);
+            prompt.push('\n');
+        }
 
         // Include ontology schema if configured
         if self.config.include_ontology {
             if let Some(ref schema) = self.ontology_schema {
-                prompt.push_str("\n온톨로지 스키마:\n");
+                prompt.push('\n');
+                prompt.push_str(template.ontology_header);
+                prompt.push('\n');
                 prompt.push_str(schema);
             }
         }
@@ -461,17 +2117,16 @@ impl HybridRagOrchestrator {
 
         // Context
         prompt.push_str("<context>\n");
-        let mut total_length = 0;
-        for (i, result) in results.iter().enumerate() {
-            if total_length + result.content.len() > self.config.max_context_length {
-                break;
-            }
-
-            prompt.push_str(&format!("[{}] 출처: {:?}\n", i + 1, result.source));
+        for (number, &i) in included.iter().enumerate() {
+            let result = &results[i];
+            prompt.push_str(&format!(
+                "[{}] {}: {:?}\n",
+                number + 1,
+                template.source_label,
+                result.source
+            ));
             prompt.push_str(&result.content);
             prompt.push_str("\n\n");
-
-            total_length += result.content.len();
         }
         prompt.push_str("</context>\n\n");
 
@@ -482,50 +2137,184 @@ impl HybridRagOrchestrator {
 
         // Instructions
         prompt.push_str("<instructions>\n");
-        prompt.push_str("1. 컨텍스트를 주의 깊게 읽으세요.\n");
-        prompt.push_str("2. 질문에 직접 관련된 정보만 사용하세요.\n");
-        prompt.push_str("3. 답변 작성 시 [출처: N] 형식으로 인용하세요.\n");
-        prompt.push_str("4. 확실하지 않은 정보는 언급하지 마세요.\n");
+        for (i, instruction) in template.instructions.iter().enumerate() {
+            prompt.push_str(&format!("{}. {}\n", i + 1, instruction));
+        }
         prompt.push_str("</instructions>\n");
 
         prompt
     }
 
-    /// Extract citations from the generated answer
-    fn extract_citations(&self, answer: &str, results: &[SearchResult]) -> Vec<Citation> {
-        let mut citations = Vec::new();
+    /// Compress the retrieved context to the prompt budget (see [`compress`])
+    ///
+    /// `summarize` allows summarizing low-ranked chunks when configured.
+    async fn compress_context(
+        &self,
+        question: &str,
+        results: &[SearchResult],
+        analysis: &QueryAnalysis,
+        summarize: bool,
+    ) -> Vec<SearchResult> {
+        let total: usize = results.iter().map(|r| r.content.len()).sum();
+        let budget = compress::context_budget(
+            total,
+            self.config.compression_ratio,
+            self.config.max_context_length,
+        );
+        let (mut context, mut report) = compress::compress_context(
+            question,
+            &analysis.keywords,
+            results,
+            budget,
+            self.embedding_client.as_deref(),
+        )
+        .await;
+
+        if summarize && self.config.summarize_low_rank_chunks {
+            report.summarized_chunks = compress::summarize_low_rank(
+                question,
+                &mut context,
+                compress::SUMMARIZE_FROM_RANK,
+                self.llm_client.as_ref(),
+                PromptTemplate::for_language(analysis.language),
+            )
+            .await;
+            report.compressed_length = context.iter().map(|r| r.content.len()).sum();
+        }
 
-        // Find all [출처: N] patterns
-        let re = regex::Regex::new(r"\[출처:\s*(\d+)\]").unwrap_or_else(|_| {
-            // Fallback if regex fails
-            regex::Regex::new(r"\[(\d+)\]").unwrap()
-        });
+        tracing::debug!(
+            "Context compressed {} -> {} chars ({} redundant, {} dropped, {} summarized)",
+            report.original_length,
+            report.compressed_length,
+            report.redundant_sentences,
+            report.dropped_sentences,
+            report.summarized_chunks
+        );
+        context
+    }
 
-        for cap in re.captures_iter(answer) {
-            let Some(num_str) = cap.get(1) else {
-                continue;
-            };
-            let Ok(num) = num_str.as_str().parse::<usize>() else {
-                continue;
-            };
-            if num == 0 || num > results.len() {
-                continue;
+    /// Ask the LLM once to fix citations of contexts that were not in the
+    /// prompt
+    ///
+    /// Returns the original answer when repair is disabled, unnecessary or
+    /// fails; remaining dangling markers are dropped by `extract_citations`.
+    async fn repair_citations(
+        &self,
+        answer: String,
+        prompt: &str,
+        available: usize,
+        language: Language,
+    ) -> String {
+        let dangling = citations::dangling_citations(&answer, available);
+        if dangling.is_empty() {
+            return answer;
+        }
+        tracing::warn!(
+            "Answer cites {:?} but only {} contexts were provided",
+            dangling,
+            available
+        );
+        if !self.config.repair_dangling_citations {
+            return answer;
+        }
+
+        let template = PromptTemplate::for_language(language);
+        let repair_prompt = template.citation_repair_prompt(prompt, &answer, &dangling);
+        match self.llm_client.generate(&repair_prompt).await {
+            Ok(repaired)
+                if !repaired.trim().is_empty()
+                    && citations::dangling_citations(&repaired, available).is_empty() =>
+            {
+                repaired
+            }
+            Ok(_) => {
+                tracing::warn!("Citation repair still cites missing contexts, dropping them");
+                answer
             }
+            Err(e) => {
+                tracing::warn!("Citation repair failed, dropping dangling citations: {}", e);
+                answer
+            }
+        }
+    }
 
-            let result = &results[num - 1];
-            citations.push(Citation {
-                index: num as u32,
-                text: result.content.chars().take(200).collect(),
-                source: result.source.clone(),
-                document_title: format!("Document {:?}", result.source.document_id),
-            });
+    /// Verify the answer's citations against the prompt contexts
+    ///
+    /// `included` holds the positions in `results` of the contexts shown in
+    /// the prompt, in prompt order. Valid markers are renumbered by first
+    /// appearance and dangling ones are removed, so the returned answer and
+    /// citations agree.
+    fn extract_citations(
+        &self,
+        answer: &str,
+        results: &[SearchResult],
+        included: &[usize],
+    ) -> (String, Vec<Citation>) {
+        let checked = citations::verify(answer, included.len());
+        let citations = checked
+            .cited
+            .iter()
+            .map(|&(index, position)| citation_for(index as usize, &results[included[position]]))
+            .collect();
+        (checked.answer, citations)
+    }
+
+    /// Ask the LLM to restate a list/fact answer as ontology-validated JSON
+    ///
+    /// Failures are logged and yield `None`; the prose answer is unaffected.
+    async fn generate_structured_answer(
+        &self,
+        question: &str,
+        answer: &str,
+        results: &[SearchResult],
+        analysis: &QueryAnalysis,
+    ) -> Option<StructuredAnswer> {
+        if !self.config.structured_answers
+            || !structured::supports_structured_answer(&analysis.expected_answer_type)
+        {
+            return None;
         }
 
-        // Deduplicate by index
-        citations.sort_by_key(|c| c.index);
-        citations.dedup_by_key(|c| c.index);
+        let prompt = structured::build_structured_prompt(
+            question,
+            answer,
+            results,
+            &analysis.expected_answer_type,
+            &self.ontology_classes,
+            PromptTemplate::for_language(analysis.language),
+        );
+        let raw = match self.llm_client.generate(&prompt).await {
+            Ok(raw) => raw,
+            Err(e) => {
+                tracing::warn!("Structured answer generation failed: {}", e);
+                return None;
+            }
+        };
+
+        match structured::parse_structured_answer(
+            &raw,
+            &analysis.expected_answer_type,
+            &self.ontology_classes,
+            results.len(),
+        ) {
+            Ok(structured) => Some(structured),
+            Err(e) => {
+                tracing::warn!("Discarding structured answer: {}", e);
+                None
+            }
+        }
+    }
 
-        citations
+    /// Propose follow-up questions from the answer context and graph neighborhood
+    fn suggest_follow_ups(
+        &self,
+        analysis: &QueryAnalysis,
+        final_results: &[SearchResult],
+        graph_context: &[SearchResult],
+    ) -> Vec<String> {
+        let contexts: Vec<SearchResult> =
+            final_results.iter().chain(graph_context).cloned().collect();
+        suggest_related_questions(analysis, &contexts, self.config.max_suggestions)
     }
 
     /// Calculate overall confidence based on search results
@@ -538,23 +2327,35 @@ impl HybridRagOrchestrator {
         let avg_score: f32 = results.iter().map(|r| r.score).sum::<f32>() / results.len() as f32;
 
         // Normalize to 0-1 range (assuming RRF scores are typically < 1)
-        (avg_score * 10.0).min(1.0)
+        let raw = (avg_score * 10.0).min(1.0);
+        self.config.confidence_calibrator.apply(raw)
     }
 }
 
-/// Simple hash for content deduplication
-fn hash_content(content: &str) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-
-    let mut hasher = DefaultHasher::new();
-    // Hash first 100 chars for efficiency
-    content
-        .chars()
-        .take(100)
-        .collect::<String>()
-        .hash(&mut hasher);
-    format!("{:x}", hasher.finish())
+/// Record a backend's outcome without the results `user` may not read
+///
+/// Recordings are returned to debug callers in full, and ACL filtering runs
+/// before fusion, so dropping denied results does not change a replay.
+fn readable_search(
+    backend: SearchResultType,
+    configured: bool,
+    outcome: &Result<Vec<SearchResult>>,
+    user: &User,
+) -> RecordedSearch {
+    let mut search = RecordedSearch::new(backend, configured, outcome);
+    search.results.retain(|r| r.acl.can_access(user));
+    search
+}
+
+/// Build the citation for the `index`-th (1-based) context result
+fn citation_for(index: usize, result: &SearchResult) -> Citation {
+    Citation {
+        index: index as u32,
+        text: result.content.chars().take(200).collect(),
+        source: result.source.clone(),
+        document_title: format!("Document {:?}", result.source.document_id),
+        freshness: None,
+    }
 }
 
 // ============================================================================
@@ -697,16 +2498,63 @@ mod tests {
         assert_eq!(config.vector_top_k, 20);
         assert_eq!(config.graph_depth, 2);
         assert_eq!(config.final_top_k, 5);
+        assert_eq!(config.max_suggestions, 5);
         assert!(config.rrf_k > 0.0);
     }
 
     #[test]
-    fn test_content_hashing() {
-        let content1 = "This is some content for testing";
-        let content2 = "This is some content for testing";
-        let content3 = "Different content";
+    fn test_recorded_search_omits_denied_results() {
+        let result = |content: &str, access_level| SearchResult {
+            content: content.to_string(),
+            score: 0.8,
+            source: SourceReference::new(uuid::Uuid::new_v4()),
+            acl: otl_core::DocumentAcl {
+                access_level,
+                ..Default::default()
+            },
+            result_type: SearchResultType::Vector,
+        };
+        let outcome = Ok(vec![
+            result("연차휴가는 15일입니다.", otl_core::AccessLevel::Internal),
+            result("임원 징계 기록", otl_core::AccessLevel::Restricted),
+        ]);
+        let user = User::internal("lee", vec!["DEVELOPER".to_string()]);
+
+        let search = readable_search(SearchResultType::Vector, true, &outcome, &user);
+        assert_eq!(search.results.len(), 1);
+        assert_eq!(search.results[0].content, "연차휴가는 15일입니다.");
+    }
 
-        assert_eq!(hash_content(content1), hash_content(content2));
-        assert_ne!(hash_content(content1), hash_content(content3));
+    #[test]
+    fn test_restrict_to_scope_falls_back_to_global() {
+        let result = |department: Option<&str>| SearchResult {
+            content: "연차휴가는 입사일 기준으로 부여됩니다.".to_string(),
+            score: 0.8,
+            source: SourceReference::new(uuid::Uuid::new_v4()),
+            acl: otl_core::DocumentAcl {
+                department: department.map(str::to_string),
+                ..Default::default()
+            },
+            result_type: SearchResultType::Vector,
+        };
+        let hr = otl_core::KnowledgeSpace::new("hr", vec!["인사팀".to_string()]);
+        let scope = RetrievalScope::of(&[hr], true).unwrap();
+
+        let mut results = vec![result(Some("인사팀")), result(Some("재무팀")), result(None)];
+        let searched = HybridRagOrchestrator::restrict_to_scope(&mut results, &scope);
+        assert_eq!(searched, vec!["hr"]);
+        assert_eq!(results.len(), 2);
+
+        let mut results = vec![result(Some("재무팀")), result(None)];
+        let searched = HybridRagOrchestrator::restrict_to_scope(&mut results, &scope);
+        assert_eq!(searched, vec!["hr", "global"]);
+        assert_eq!(results.len(), 2);
+
+        let scope = RetrievalScope {
+            fallback: false,
+            ..scope
+        };
+        HybridRagOrchestrator::restrict_to_scope(&mut results, &scope);
+        assert_eq!(results.len(), 1);
     }
 }
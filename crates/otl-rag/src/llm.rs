@@ -3,19 +3,35 @@
 //! Provides abstraction for OpenAI and Ollama LLM APIs
 //! with support for both synchronous and streaming responses.
 //!
+//! [`warm_up_ollama`] checks that the configured Ollama models are pulled and
+//! loads them at startup, so the first query does not wait for a cold model.
+//!
+//! [`CachedLlmClient`] serves repeated prompts (summaries, classification,
+//! FAQ questions) from an [`LlmResponseCache`].
+//!
 //! Author: hephaex@gmail.com
 
+use crate::cache::{build_backend, CacheConfig, CacheStats};
+use crate::cache_backend::CacheBackend;
 use async_trait::async_trait;
 use futures::stream::{BoxStream, StreamExt};
 use otl_core::{LlmClient, LlmConfig, LlmProvider, OtlError, Result};
+use otl_vector::embedding::OllamaEmbedding;
+use otl_vector::EmbeddingClient;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 // ============================================================================
 // OpenAI Client
 // ============================================================================
 
 /// OpenAI API client
+///
+/// Also serves vLLM and other servers with an OpenAI-compatible API, see
+/// [`OpenAiClient::vllm_from_config`].
 pub struct OpenAiClient {
     client: Client,
     api_key: String,
@@ -23,6 +39,10 @@ pub struct OpenAiClient {
     model: String,
     max_tokens: u32,
     temperature: f32,
+    /// Requests in flight at once in [`LlmClient::generate_batch`]
+    batch_size: usize,
+    /// Server name in errors
+    name: &'static str,
 }
 
 #[derive(Debug, Serialize)]
@@ -31,6 +51,9 @@ struct OpenAiRequest {
     messages: Vec<Message>,
     max_tokens: u32,
     temperature: f32,
+    /// Sampling seed (honored by OpenAI and vLLM on a best-effort basis)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
 }
@@ -53,10 +76,15 @@ struct Choice {
     finish_reason: Option<String>,
 }
 
+/// Streamed choice; vLLM sends a role-only first delta, `null` content and
+/// `text` instead of a delta for completion models
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 struct StreamChoice {
-    delta: Delta,
+    #[serde(default)]
+    delta: Option<Delta>,
+    #[serde(default)]
+    text: Option<String>,
     finish_reason: Option<String>,
 }
 
@@ -65,9 +93,47 @@ struct Delta {
     content: Option<String>,
 }
 
+/// Streamed chunk; the final usage chunk of vLLM has no choices, and errors
+/// after the stream started arrive as an `error` chunk
 #[derive(Debug, Deserialize)]
 struct StreamResponse {
+    #[serde(default)]
     choices: Vec<StreamChoice>,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+/// Text of one line of a server-sent event stream, if any
+///
+/// Accepts `data:` with or without a space; comments, other fields, the
+/// `[DONE]` marker and chunks without text yield nothing.
+fn stream_chunk(line: &str) -> Option<Result<String>> {
+    let data = line.strip_prefix("data:")?.trim();
+    if data.is_empty() || data == "[DONE]" {
+        return None;
+    }
+    let parsed = match serde_json::from_str::<StreamResponse>(data) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to parse stream chunk: {} - {}",
+                data.chars().take(100).collect::<String>(),
+                e
+            );
+            return None;
+        }
+    };
+    if let Some(error) = parsed.error {
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return Some(Err(OtlError::LlmError(format!("Stream error: {message}"))));
+    }
+    let choice = parsed.choices.into_iter().next()?;
+    let text = choice.delta.and_then(|d| d.content).or(choice.text)?;
+    (!text.is_empty()).then_some(Ok(text))
 }
 
 impl OpenAiClient {
@@ -93,6 +159,8 @@ impl OpenAiClient {
             model: model.into(),
             max_tokens,
             temperature,
+            batch_size: 1,
+            name: "OpenAI",
         }
     }
 
@@ -108,22 +176,30 @@ impl OpenAiClient {
             .clone()
             .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
 
-        // Configure reqwest client with appropriate timeouts for LLM operations
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(300)) // 5 minutes total timeout
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .pool_idle_timeout(std::time::Duration::from_secs(90))
-            .build()
-            .unwrap_or_else(|_| Client::new());
+        Ok(Self::new(
+            api_key.clone(),
+            config.model.clone(),
+            config.max_tokens,
+            config.temperature,
+        )
+        .with_base_url(base_url)
+        .with_batch_size(config.batch_size))
+    }
 
-        Ok(Self {
-            client,
-            api_key: api_key.clone(),
-            base_url,
-            model: config.model.clone(),
-            max_tokens: config.max_tokens,
-            temperature: config.temperature,
-        })
+    /// Create a client of the vLLM server at `config.vllm_url`
+    ///
+    /// The API key is optional; without one no `Authorization` header is sent.
+    pub fn vllm_from_config(config: &LlmConfig) -> Self {
+        let mut client = Self::new(
+            config.vllm_api_key.clone().unwrap_or_default(),
+            config.model.clone(),
+            config.max_tokens,
+            config.temperature,
+        )
+        .with_base_url(config.vllm_url.trim_end_matches('/'))
+        .with_batch_size(config.batch_size);
+        client.name = "vLLM";
+        client
     }
 
     /// Set custom base URL (for Azure or compatible APIs)
@@ -131,11 +207,29 @@ impl OpenAiClient {
         self.base_url = url.into();
         self
     }
-}
 
-#[async_trait]
-impl LlmClient for OpenAiClient {
-    async fn generate(&self, prompt: &str) -> Result<String> {
+    /// Set the number of requests in flight at once for batched generation
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Chat completion request for `body`
+    fn completion_request(&self, body: &OpenAiRequest) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("Content-Type", "application/json")
+            .json(body);
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.header("Authorization", format!("Bearer {}", self.api_key))
+        }
+    }
+
+    /// Single chat completion with the given sampling settings
+    async fn complete(&self, prompt: &str, temperature: f32, seed: Option<u64>) -> Result<String> {
         let request = OpenAiRequest {
             model: self.model.clone(),
             messages: vec![Message {
@@ -143,23 +237,23 @@ impl LlmClient for OpenAiClient {
                 content: prompt.to_string(),
             }],
             max_tokens: self.max_tokens,
-            temperature: self.temperature,
+            temperature,
+            seed,
             stream: None,
         };
 
         let response = self
-            .client
-            .post(format!("{}/chat/completions", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
+            .completion_request(&request)
             .send()
             .await
             .map_err(|e| OtlError::LlmError(format!("Request failed: {e}")))?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(OtlError::LlmError(format!("OpenAI error: {error_text}")));
+            return Err(OtlError::LlmError(format!(
+                "{} error: {error_text}",
+                self.name
+            )));
         }
 
         let result: OpenAiResponse = response
@@ -173,8 +267,34 @@ impl LlmClient for OpenAiClient {
             .map(|c| c.message.content.clone())
             .ok_or_else(|| OtlError::LlmError("No response generated".to_string()))
     }
+}
+
+#[async_trait]
+impl LlmClient for OpenAiClient {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        self.complete(prompt, self.temperature, None).await
+    }
+
+    async fn generate_seeded(&self, prompt: &str, seed: u64) -> Result<String> {
+        self.complete(prompt, 0.0, Some(seed)).await
+    }
+
+    async fn generate_batch(&self, prompts: &[String]) -> Result<Vec<String>> {
+        // Requests of a batch are sent together; the server batches them
+        let mut responses = Vec::with_capacity(prompts.len());
+        for batch in prompts.chunks(self.batch_size) {
+            let mut requests = Vec::with_capacity(batch.len());
+            for prompt in batch {
+                requests.push(self.generate(prompt));
+            }
+            responses.extend(futures::future::try_join_all(requests).await?);
+        }
+        Ok(responses)
+    }
 
     async fn generate_stream(&self, prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
+        use tokio_util::codec::{FramedRead, LinesCodec};
+
         let request = OpenAiRequest {
             model: self.model.clone(),
             messages: vec![Message {
@@ -183,15 +303,12 @@ impl LlmClient for OpenAiClient {
             }],
             max_tokens: self.max_tokens,
             temperature: self.temperature,
+            seed: None,
             stream: Some(true),
         };
 
         let response = self
-            .client
-            .post(format!("{}/chat/completions", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
+            .completion_request(&request)
             .send()
             .await
             .map_err(|e| OtlError::LlmError(format!("Stream request failed: {e}")))?;
@@ -199,38 +316,23 @@ impl LlmClient for OpenAiClient {
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
             return Err(OtlError::LlmError(format!(
-                "OpenAI stream error: {error_text}"
+                "{} stream error: {error_text}",
+                self.name
             )));
         }
 
-        let stream = response.bytes_stream();
+        // Events may be split across network chunks, so frame by lines
+        let stream_reader = tokio_util::io::StreamReader::new(
+            response
+                .bytes_stream()
+                .map(|result| result.map_err(std::io::Error::other)),
+        );
+        let lines_stream =
+            FramedRead::new(stream_reader, LinesCodec::new_with_max_length(64 * 1024));
 
-        let mapped_stream = stream.filter_map(|result| async move {
+        let mapped_stream = lines_stream.filter_map(|result| async move {
             match result {
-                Ok(bytes) => {
-                    let text = String::from_utf8_lossy(&bytes);
-                    // Parse SSE format: data: {...}
-                    let mut content = String::new();
-                    for line in text.lines() {
-                        if let Some(data) = line.strip_prefix("data: ") {
-                            if data == "[DONE]" {
-                                continue;
-                            }
-                            if let Ok(parsed) = serde_json::from_str::<StreamResponse>(data) {
-                                if let Some(choice) = parsed.choices.first() {
-                                    if let Some(c) = &choice.delta.content {
-                                        content.push_str(c);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    if content.is_empty() {
-                        None
-                    } else {
-                        Some(Ok(content))
-                    }
-                }
+                Ok(line) => stream_chunk(&line),
                 Err(e) => Some(Err(OtlError::LlmError(format!("Stream error: {e}")))),
             }
         });
@@ -256,6 +358,15 @@ struct OllamaRequest {
     prompt: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<OllamaOptions>,
+}
+
+/// Sampling settings overriding the model defaults
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    temperature: f32,
+    seed: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -265,6 +376,30 @@ struct OllamaResponse {
     done: bool,
 }
 
+/// A model pulled into the Ollama server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    /// Size on disk in bytes
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub modified_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaModel>,
+}
+
+/// Whether the pulled model `pulled` is the configured model `wanted`
+///
+/// Ollama names untagged models `<name>:latest`.
+pub fn ollama_model_matches(pulled: &str, wanted: &str) -> bool {
+    pulled == wanted || (!wanted.contains(':') && pulled == format!("{wanted}:latest"))
+}
+
 impl OllamaClient {
     /// Create a new Ollama client
     pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
@@ -287,17 +422,47 @@ impl OllamaClient {
     pub fn from_config(config: &LlmConfig) -> Self {
         Self::new(config.ollama_url.clone(), config.model.clone())
     }
-}
 
-#[async_trait]
-impl LlmClient for OllamaClient {
-    async fn generate(&self, prompt: &str) -> Result<String> {
+    /// Models pulled into the server
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>> {
+        let response = self
+            .client
+            .get(format!("{}/api/tags", self.base_url))
+            .send()
+            .await
+            .map_err(|e| OtlError::LlmError(format!("Ollama request failed: {e}")))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(OtlError::LlmError(format!("Ollama error: {error_text}")));
+        }
+
+        let tags: OllamaTagsResponse = response
+            .json()
+            .await
+            .map_err(|e| OtlError::LlmError(format!("Failed to parse Ollama models: {e}")))?;
+        Ok(tags.models)
+    }
+
+    /// Whether `model` is pulled into the server
+    pub async fn is_pulled(&self, model: &str) -> Result<bool> {
+        Ok(self
+            .list_models()
+            .await?
+            .iter()
+            .any(|m| ollama_model_matches(&m.name, model)))
+    }
+
+    /// Single non-streaming generation, with model default sampling unless
+    /// `options` are given
+    async fn complete(&self, prompt: &str, options: Option<OllamaOptions>) -> Result<String> {
         tracing::info!("Ollama generate: sending request to {}", self.base_url);
 
         let request = OllamaRequest {
             model: self.model.clone(),
             prompt: prompt.to_string(),
             stream: Some(false),
+            options,
         };
 
         tracing::debug!("Ollama generate: request prepared");
@@ -332,6 +497,102 @@ impl LlmClient for OllamaClient {
         Ok(result.response)
     }
 
+    /// Load the model into memory without generating
+    pub async fn warm_up(&self) -> Result<()> {
+        // An empty prompt only loads the model
+        let request = OllamaRequest {
+            model: self.model.clone(),
+            prompt: String::new(),
+            stream: Some(false),
+            options: None,
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/generate", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| OtlError::LlmError(format!("Ollama request failed: {e}")))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(OtlError::LlmError(format!("Ollama error: {error_text}")));
+        }
+        Ok(())
+    }
+}
+
+/// Check that the generation and embedding models of `config` are pulled
+/// into its Ollama server and load them into memory
+///
+/// Problems are logged; the server starts either way.
+pub async fn warm_up_ollama(config: &LlmConfig) {
+    let client = OllamaClient::from_config(config);
+    let models = match client.list_models().await {
+        Ok(models) => models,
+        Err(e) => {
+            tracing::warn!("Ollama at {} is unreachable: {}", config.ollama_url, e);
+            return;
+        }
+    };
+    let pulled = |wanted: &str| {
+        let found = models.iter().any(|m| ollama_model_matches(&m.name, wanted));
+        if !found {
+            tracing::warn!(
+                "Ollama model {} is not pulled; run `ollama pull {}`",
+                wanted,
+                wanted
+            );
+        }
+        found
+    };
+
+    if pulled(&config.model) {
+        let started = std::time::Instant::now();
+        match client.warm_up().await {
+            Ok(()) => tracing::info!(
+                "Ollama model {} loaded in {:?}",
+                config.model,
+                started.elapsed()
+            ),
+            Err(e) => tracing::warn!("Failed to load Ollama model {}: {}", config.model, e),
+        }
+    }
+    if pulled(&config.embedding_model) {
+        let started = std::time::Instant::now();
+        match OllamaEmbedding::from_config(config).embed("warm-up").await {
+            Ok(_) => tracing::info!(
+                "Ollama embedding model {} loaded in {:?}",
+                config.embedding_model,
+                started.elapsed()
+            ),
+            Err(e) => tracing::warn!(
+                "Failed to load Ollama embedding model {}: {}",
+                config.embedding_model,
+                e
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for OllamaClient {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        self.complete(prompt, None).await
+    }
+
+    async fn generate_seeded(&self, prompt: &str, seed: u64) -> Result<String> {
+        self.complete(
+            prompt,
+            Some(OllamaOptions {
+                temperature: 0.0,
+                seed,
+            }),
+        )
+        .await
+    }
+
     async fn generate_stream(&self, prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
         use futures::stream::StreamExt;
         use tokio_util::codec::{FramedRead, LinesCodec};
@@ -342,6 +603,7 @@ impl LlmClient for OllamaClient {
             model: self.model.clone(),
             prompt: prompt.to_string(),
             stream: Some(true),
+            options: None,
         };
 
         let response = self
@@ -416,6 +678,202 @@ impl LlmClient for OllamaClient {
     }
 }
 
+// ============================================================================
+// Response Cache
+// ============================================================================
+
+/// Cache of LLM responses keyed by prompt and model settings
+///
+/// Entries are keyed by a hash of the prompt together with the identity of
+/// the client that produced them (see [`llm_cache_identity`]), so a model or
+/// sampling change never serves an old response.
+#[derive(Clone)]
+pub struct LlmResponseCache {
+    backend: Arc<dyn CacheBackend<String>>,
+    stats: Arc<CacheStats>,
+}
+
+impl LlmResponseCache {
+    /// Create a new response cache with default configuration
+    pub fn new() -> Self {
+        Self::with_config(&CacheConfig::default())
+    }
+
+    /// Create a new response cache with custom configuration
+    pub fn with_config(config: &CacheConfig) -> Self {
+        Self {
+            backend: build_backend(
+                config,
+                "llm",
+                config.llm_max_capacity,
+                config.llm_ttl_seconds,
+            ),
+            stats: Arc::new(CacheStats::new("llm")),
+        }
+    }
+
+    /// Cache key of `prompt` sent to the client `identity`
+    fn key(identity: &str, prompt: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        identity.hash(&mut hasher);
+        prompt.hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Get a cached response
+    pub async fn get(&self, identity: &str, prompt: &str) -> Option<String> {
+        let result = self.backend.get(&Self::key(identity, prompt)).await;
+
+        if result.is_some() {
+            self.stats.record_hit();
+        } else {
+            self.stats.record_miss();
+        }
+
+        result
+    }
+
+    /// Store a response
+    pub async fn put(&self, identity: &str, prompt: &str, response: String) {
+        self.backend
+            .insert(&Self::key(identity, prompt), response, &[])
+            .await;
+        self.stats.record_write();
+    }
+
+    /// Clear all cached responses
+    pub async fn clear(&self) {
+        self.backend.clear().await;
+        self.stats.reset();
+    }
+
+    /// Get cache statistics
+    pub fn stats(&self) -> Arc<CacheStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Get current cache size
+    pub fn entry_count(&self) -> u64 {
+        self.backend.entry_count()
+    }
+}
+
+impl Default for LlmResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Identity of the settings that shape a response: provider, model, token
+/// limit and temperature
+pub fn llm_cache_identity(config: &LlmConfig) -> String {
+    format!(
+        "{:?}:{}:{}:{}",
+        config.provider, config.model, config.max_tokens, config.temperature
+    )
+}
+
+/// LLM client that serves repeated prompts from an [`LlmResponseCache`]
+///
+/// Streams are never cached. [`LlmClient::generate_uncached`] skips the
+/// lookup and refreshes the entry.
+pub struct CachedLlmClient {
+    inner: Arc<dyn LlmClient>,
+    cache: LlmResponseCache,
+    identity: String,
+}
+
+impl CachedLlmClient {
+    /// Wrap `inner`, whose settings are described by `identity`
+    pub fn new(
+        inner: Arc<dyn LlmClient>,
+        cache: LlmResponseCache,
+        identity: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner,
+            cache,
+            identity: identity.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for CachedLlmClient {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        if let Some(response) = self.cache.get(&self.identity, prompt).await {
+            return Ok(response);
+        }
+        self.generate_uncached(prompt).await
+    }
+
+    async fn generate_uncached(&self, prompt: &str) -> Result<String> {
+        let response = self.inner.generate(prompt).await?;
+        self.cache
+            .put(&self.identity, prompt, response.clone())
+            .await;
+        Ok(response)
+    }
+
+    /// Seeded generations are neither served from nor stored in the cache
+    async fn generate_seeded(&self, prompt: &str, seed: u64) -> Result<String> {
+        self.inner.generate_seeded(prompt, seed).await
+    }
+
+    async fn generate_batch(&self, prompts: &[String]) -> Result<Vec<String>> {
+        let mut responses = Vec::with_capacity(prompts.len());
+        let mut missing = Vec::new();
+        for prompt in prompts {
+            let cached = self.cache.get(&self.identity, prompt).await;
+            if cached.is_none() {
+                missing.push(prompt.clone());
+            }
+            responses.push(cached);
+        }
+
+        let mut generated = self.inner.generate_batch(&missing).await?.into_iter();
+        for (prompt, response) in prompts.iter().zip(responses.iter_mut()) {
+            if response.is_none() {
+                let text = generated
+                    .next()
+                    .ok_or_else(|| OtlError::LlmError("No response generated".to_string()))?;
+                self.cache.put(&self.identity, prompt, text.clone()).await;
+                *response = Some(text);
+            }
+        }
+        Ok(responses.into_iter().flatten().collect())
+    }
+
+    async fn generate_stream(&self, prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
+        self.inner.generate_stream(prompt).await
+    }
+}
+
+// ============================================================================
+// Disabled Client
+// ============================================================================
+
+/// Placeholder for deployments without an LLM
+///
+/// Every generation call fails, so only extractive answers are available.
+#[derive(Debug, Default)]
+pub struct DisabledLlmClient;
+
+#[async_trait]
+impl LlmClient for DisabledLlmClient {
+    async fn generate(&self, _prompt: &str) -> Result<String> {
+        Err(OtlError::LlmError(
+            "No LLM configured; use extractive answer mode".to_string(),
+        ))
+    }
+
+    async fn generate_stream(&self, _prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
+        Err(OtlError::LlmError(
+            "No LLM configured; use extractive answer mode".to_string(),
+        ))
+    }
+}
+
 // ============================================================================
 // Factory function
 // ============================================================================
@@ -427,6 +885,7 @@ pub fn create_llm_client(config: &LlmConfig) -> Result<Box<dyn LlmClient>> {
             Ok(Box::new(OpenAiClient::from_config(config)?))
         }
         LlmProvider::Ollama => Ok(Box::new(OllamaClient::from_config(config))),
+        LlmProvider::Vllm => Ok(Box::new(OpenAiClient::vllm_from_config(config))),
     }
 }
 
@@ -444,9 +903,206 @@ mod tests {
         assert_eq!(client.model, "gpt-4o-mini");
     }
 
+    #[test]
+    fn test_vllm_client_from_config() {
+        let config = LlmConfig {
+            provider: LlmProvider::Vllm,
+            vllm_url: "http://vllm:8000/v1/".to_string(),
+            model: "Qwen/Qwen2.5-7B-Instruct".to_string(),
+            batch_size: 16,
+            ..Default::default()
+        };
+        let client = OpenAiClient::vllm_from_config(&config);
+
+        assert_eq!(client.base_url, "http://vllm:8000/v1");
+        assert!(client.api_key.is_empty());
+        assert_eq!(client.batch_size, 16);
+        assert_eq!(client.name, "vLLM");
+    }
+
+    #[test]
+    fn test_stream_chunk_formats() {
+        let text = |line: &str| stream_chunk(line).map(|r| r.unwrap());
+
+        // OpenAI
+        assert_eq!(
+            text(r#"data: {"choices":[{"delta":{"content":"Hel"},"finish_reason":null}]}"#),
+            Some("Hel".to_string())
+        );
+        // vLLM: no space after the colon, role-only first delta, usage chunk
+        assert_eq!(
+            text(r#"data:{"choices":[{"index":0,"delta":{"content":"lo"}}]}"#),
+            Some("lo".to_string())
+        );
+        assert_eq!(
+            text(r#"data: {"choices":[{"delta":{"role":"assistant","content":null}}]}"#),
+            None
+        );
+        assert_eq!(
+            text(r#"data: {"choices":[],"usage":{"total_tokens":9}}"#),
+            None
+        );
+        // Completion models stream `text`
+        assert_eq!(
+            text(r#"data: {"choices":[{"text":" world","finish_reason":null}]}"#),
+            Some(" world".to_string())
+        );
+        assert_eq!(text("data: [DONE]"), None);
+        assert_eq!(text(": ping"), None);
+        assert_eq!(text(""), None);
+    }
+
+    #[test]
+    fn test_stream_chunk_error() {
+        let error = stream_chunk(
+            r#"data: {"error":{"object":"error","message":"context length exceeded","code":400}}"#,
+        )
+        .unwrap();
+
+        assert!(
+            matches!(error, Err(OtlError::LlmError(m)) if m.contains("context length exceeded"))
+        );
+    }
+
     #[test]
     fn test_ollama_client_creation() {
         let client = OllamaClient::new("http://localhost:11434", "llama2");
         assert_eq!(client.model, "llama2");
     }
+
+    #[test]
+    fn test_ollama_model_matches() {
+        assert!(ollama_model_matches("llama3:latest", "llama3"));
+        assert!(ollama_model_matches("llama3:8b", "llama3:8b"));
+        assert!(!ollama_model_matches("llama3:8b", "llama3"));
+        assert!(!ollama_model_matches("llama3:latest", "llama3:8b"));
+    }
+
+    #[test]
+    fn test_parse_ollama_tags() {
+        let tags: OllamaTagsResponse = serde_json::from_str(
+            r#"{"models": [{"name": "nomic-embed-text:latest", "size": 274302450,
+                "modified_at": "2024-05-01T10:00:00Z", "digest": "0a109f422b47"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(tags.models.len(), 1);
+        assert_eq!(tags.models[0].name, "nomic-embed-text:latest");
+        assert_eq!(tags.models[0].size, 274_302_450);
+    }
+
+    /// Counts generations and echoes the prompt
+    #[derive(Default)]
+    struct CountingClient {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LlmClient for CountingClient {
+        async fn generate(&self, prompt: &str) -> Result<String> {
+            let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(format!("{prompt} #{n}"))
+        }
+
+        async fn generate_stream(
+            &self,
+            _prompt: &str,
+        ) -> Result<BoxStream<'static, Result<String>>> {
+            Err(OtlError::LlmError("not streaming".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_client_reuses_responses() {
+        let inner = Arc::new(CountingClient::default());
+        let cache = LlmResponseCache::new();
+        let client = CachedLlmClient::new(inner.clone(), cache.clone(), "openai:gpt-4o-mini");
+
+        assert_eq!(client.generate("summarize").await.unwrap(), "summarize #0");
+        assert_eq!(client.generate("summarize").await.unwrap(), "summarize #0");
+        assert_eq!(client.generate("classify").await.unwrap(), "classify #1");
+        assert_eq!(cache.stats().hits(), 1);
+
+        // Bypassing generates again and refreshes the entry
+        assert_eq!(
+            client.generate_uncached("summarize").await.unwrap(),
+            "summarize #2"
+        );
+        assert_eq!(client.generate("summarize").await.unwrap(), "summarize #2");
+    }
+
+    #[tokio::test]
+    async fn test_cached_client_batches_misses_only() {
+        let inner = Arc::new(CountingClient::default());
+        let client = CachedLlmClient::new(inner.clone(), LlmResponseCache::new(), "vllm:qwen");
+        client.generate("b").await.unwrap();
+
+        let prompts = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let responses = client.generate_batch(&prompts).await.unwrap();
+
+        assert_eq!(responses, vec!["a #1", "b #0", "c #2"]);
+        assert_eq!(client.generate("c").await.unwrap(), "c #2");
+    }
+
+    #[tokio::test]
+    async fn test_cached_client_separates_models() {
+        let inner = Arc::new(CountingClient::default());
+        let cache = LlmResponseCache::new();
+        let mini = CachedLlmClient::new(inner.clone(), cache.clone(), "openai:gpt-4o-mini");
+        let large = CachedLlmClient::new(inner.clone(), cache, "openai:gpt-4o");
+
+        mini.generate("summarize").await.unwrap();
+        assert_eq!(large.generate("summarize").await.unwrap(), "summarize #1");
+    }
+
+    #[tokio::test]
+    async fn test_cached_client_skips_cache_when_seeded() {
+        let inner = Arc::new(CountingClient::default());
+        let cache = LlmResponseCache::new();
+        let client = CachedLlmClient::new(inner.clone(), cache.clone(), "openai:gpt-4o-mini");
+        client.generate("summarize").await.unwrap();
+
+        assert_eq!(
+            client.generate_seeded("summarize", 7).await.unwrap(),
+            "summarize #1"
+        );
+        assert_eq!(client.generate("summarize").await.unwrap(), "summarize #0");
+    }
+
+    #[test]
+    fn test_seeded_requests_serialize_sampling() {
+        let openai = serde_json::to_value(OpenAiRequest {
+            model: "gpt-4o-mini".to_string(),
+            messages: Vec::new(),
+            max_tokens: 16,
+            temperature: 0.0,
+            seed: Some(42),
+            stream: None,
+        })
+        .unwrap();
+        assert_eq!(openai["seed"], 42);
+        assert!(openai.get("stream").is_none());
+
+        let ollama = serde_json::to_value(OllamaRequest {
+            model: "qwen2.5".to_string(),
+            prompt: "hi".to_string(),
+            stream: Some(false),
+            options: Some(OllamaOptions {
+                temperature: 0.0,
+                seed: 42,
+            }),
+        })
+        .unwrap();
+        assert_eq!(ollama["options"]["seed"], 42);
+        assert_eq!(ollama["options"]["temperature"], 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_client_fails() {
+        let client = DisabledLlmClient;
+        assert!(matches!(
+            client.generate("hi").await,
+            Err(OtlError::LlmError(_))
+        ));
+    }
 }
@@ -0,0 +1,672 @@
+//! Post-generation moderation
+//!
+//! ACL filtering keeps restricted documents out of retrieval, but a wrongly
+//! tagged document or an over-eager LLM can still put salary figures or
+//! disciplinary records into an answer. This pass runs on the finished
+//! response: sensitive-topic rules match sentences with regexes (optionally
+//! backed by an LLM classifier), and either redact the offending sentences or
+//! refuse the whole answer. Users holding one of a rule's allowed roles or
+//! departments are exempt from it.
+//!
+//! In redacted-answer mode (`mask_context`) the values a rule matches are
+//! also masked in the retrieved contexts before the prompt is built, so a
+//! user without clearance gets an answer that keeps the restricted values
+//! in place but hidden, along with what was withheld and whom to ask.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::extractive::split_sentences;
+use crate::language::PromptTemplate;
+use otl_core::{
+    LlmClient, ModerationAction, ModerationDecision, ModerationDetector, OtlError, Result, User,
+    WithheldTopic,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// A sensitive topic and what to do when an answer touches it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitiveTopicRule {
+    /// Rule name, reported in moderation decisions and audit logs
+    pub name: String,
+
+    /// What the topic covers (shown to the LLM classifier)
+    #[serde(default)]
+    pub description: String,
+
+    /// Regexes matched against each sentence of the answer
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    /// Action taken when the rule fires
+    pub action: ModerationAction,
+
+    /// Roles allowed to see the topic (case-insensitive)
+    #[serde(default)]
+    pub allowed_roles: Vec<String>,
+
+    /// Departments allowed to see the topic (case-insensitive)
+    #[serde(default)]
+    pub allowed_departments: Vec<String>,
+
+    /// How to request access, shown when values of the topic are masked
+    /// (defaults to the allowed departments and roles)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_request: Option<String>,
+}
+
+impl SensitiveTopicRule {
+    /// Whether the user may see content covered by this rule
+    pub fn exempts(&self, user: &User) -> bool {
+        let matches_any = |allowed: &[String], held: &[String]| {
+            allowed
+                .iter()
+                .any(|a| held.iter().any(|h| h.trim().eq_ignore_ascii_case(a.trim())))
+        };
+        matches_any(&self.allowed_roles, &user.roles)
+            || matches_any(&self.allowed_departments, &user.departments)
+    }
+}
+
+/// Moderation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationConfig {
+    /// Run the moderation pass
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Sensitive-topic rules
+    #[serde(default = "default_rules")]
+    pub rules: Vec<SensitiveTopicRule>,
+
+    /// Ask the LLM whether an answer that passed the regexes still discloses
+    /// a sensitive topic
+    #[serde(default)]
+    pub llm_classifier: bool,
+
+    /// Mask the values matched by the rules in the retrieved contexts of
+    /// users without clearance (redacted-answer mode)
+    #[serde(default)]
+    pub mask_context: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: default_rules(),
+            llm_classifier: false,
+            mask_context: false,
+        }
+    }
+}
+
+/// Built-in HR rules: salary amounts are redacted, individual disciplinary
+/// records refuse the answer. HR staff and administrators are exempt.
+pub fn default_rules() -> Vec<SensitiveTopicRule> {
+    let allowed_roles = vec!["ADMIN".to_string(), "HR_MANAGER".to_string()];
+    let allowed_departments = vec!["HR".to_string(), "인사팀".to_string()];
+
+    vec![
+        SensitiveTopicRule {
+            name: "salary".to_string(),
+            description: "Salary, bonus or other pay amounts of employees".to_string(),
+            patterns: vec![
+                r"(?i)(연봉|월급|급여|기본급|성과급|상여금|보너스|salary|wage|bonus)[^.\n]{0,40}?\d[\d,.]*\s*(억|만\s*원|천\s*원|원|krw|usd|won|dollars?)".to_string(),
+                r"(?i)\d[\d,.]*\s*(억|만\s*원|천\s*원|원|krw|usd|dollars?)[^.\n]{0,30}(연봉|월급|급여|salary|wage)".to_string(),
+                r"(?i)(salary|wage|bonus|연봉|월급|급여)[^.\n]{0,40}?\$\s?\d".to_string(),
+            ],
+            action: ModerationAction::Redact,
+            allowed_roles: allowed_roles.clone(),
+            allowed_departments: allowed_departments.clone(),
+            access_request: None,
+        },
+        SensitiveTopicRule {
+            name: "disciplinary".to_string(),
+            description: "Disciplinary actions or records of individual employees".to_string(),
+            patterns: vec![
+                r"(징계|감봉|정직|견책|파면|해임)\s*(이력|기록|내역|대상자)".to_string(),
+                r"(징계|감봉|정직|견책|파면|해임)\s*(처분|조치)?\s*(을|를)?\s*받았".to_string(),
+                r"(?i)disciplin\w*\s+(record|history|file)s?".to_string(),
+                r"(?i)\b(was|were|has been|have been)\s+(disciplined|suspended|dismissed|reprimanded)"
+                    .to_string(),
+            ],
+            action: ModerationAction::Refuse,
+            allowed_roles,
+            allowed_departments,
+            access_request: None,
+        },
+    ]
+}
+
+// ============================================================================
+// Moderator
+// ============================================================================
+
+/// Rule with compiled patterns
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: SensitiveTopicRule,
+    patterns: Vec<Regex>,
+}
+
+impl CompiledRule {
+    fn matches(&self, text: &str) -> bool {
+        self.patterns.iter().any(|p| p.is_match(text))
+    }
+}
+
+/// Text after redaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redaction {
+    /// Text with offending sentences replaced by the marker
+    pub text: String,
+    /// Rules that fired, in configuration order
+    pub rules: Vec<String>,
+    /// Number of sentences replaced
+    pub sentences: usize,
+}
+
+/// Text after masking restricted values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Masking {
+    /// Text with restricted values replaced by the marker
+    pub text: String,
+    /// Values masked per rule, in configuration order
+    pub values: Vec<(String, usize)>,
+}
+
+/// Result of moderating an answer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Moderation {
+    /// Answer to show the user
+    pub answer: String,
+    /// Decision taken, `None` when the answer passed unchanged
+    pub decision: Option<ModerationDecision>,
+}
+
+/// Applies sensitive-topic rules to generated answers
+#[derive(Debug, Clone)]
+pub struct Moderator {
+    enabled: bool,
+    llm_classifier: bool,
+    mask_context: bool,
+    rules: Vec<CompiledRule>,
+}
+
+impl Moderator {
+    /// Compile the rules of a configuration
+    pub fn new(config: &ModerationConfig) -> Result<Self> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                let patterns = rule
+                    .patterns
+                    .iter()
+                    .map(|p| {
+                        Regex::new(p).map_err(|e| {
+                            OtlError::ValidationError(format!(
+                                "Invalid pattern in moderation rule '{}': {e}",
+                                rule.name
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(CompiledRule {
+                    rule: rule.clone(),
+                    patterns,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            enabled: config.enabled,
+            llm_classifier: config.llm_classifier,
+            mask_context: config.mask_context,
+            rules,
+        })
+    }
+
+    /// Whether the moderation pass runs at all
+    pub fn is_enabled(&self) -> bool {
+        self.enabled && !self.rules.is_empty()
+    }
+
+    fn applicable<'a>(&'a self, user: &'a User) -> impl Iterator<Item = &'a CompiledRule> + 'a {
+        self.rules.iter().filter(move |r| !r.rule.exempts(user))
+    }
+
+    /// Whether any rule applies to the user, i.e. their answers may need
+    /// moderation
+    pub fn applies_to(&self, user: &User) -> bool {
+        self.is_enabled() && self.applicable(user).next().is_some()
+    }
+
+    /// Whether any rule that applies to the user matches the text
+    pub fn flags(&self, text: &str, user: &User) -> bool {
+        self.is_enabled() && self.applicable(user).any(|r| r.matches(text))
+    }
+
+    /// Replace every sentence matched by a rule that applies to the user,
+    /// regardless of the rule's action
+    pub fn redact(&self, text: &str, user: &User, marker: &str) -> Redaction {
+        let mut redaction = Redaction {
+            text: String::with_capacity(text.len()),
+            rules: Vec::new(),
+            sentences: 0,
+        };
+        if !self.is_enabled() {
+            redaction.text = text.to_string();
+            return redaction;
+        }
+
+        let rules: Vec<_> = self.applicable(user).collect();
+        let mut last = 0;
+        for (start, end) in split_sentences(text) {
+            let sentence = &text[start..end];
+            let fired: Vec<_> = rules.iter().filter(|r| r.matches(sentence)).collect();
+            if fired.is_empty() {
+                continue;
+            }
+            redaction.text.push_str(&text[last..start]);
+            redaction.text.push_str(marker);
+            last = end;
+            redaction.sentences += 1;
+            for rule in fired {
+                if !redaction.rules.contains(&rule.rule.name) {
+                    redaction.rules.push(rule.rule.name.clone());
+                }
+            }
+        }
+        redaction.text.push_str(&text[last..]);
+        redaction
+    }
+
+    /// Whether restricted values are masked in retrieved contexts
+    pub fn masks_context(&self) -> bool {
+        self.is_enabled() && self.mask_context
+    }
+
+    /// Replace the values matched by every rule that applies to the user
+    ///
+    /// Amounts and other numbers inside a match are masked and the words
+    /// around them kept ("연봉은 [권한 필요]"); a match without numbers is
+    /// masked whole. Text is returned unchanged when masking is off.
+    pub fn mask(&self, text: &str, user: &User, marker: &str) -> Masking {
+        let mut masking = Masking {
+            text: text.to_string(),
+            values: Vec::new(),
+        };
+        if !self.masks_context() {
+            return masking;
+        }
+
+        // (span, rule) of every value, earliest first
+        let mut spans: Vec<(Range<usize>, usize)> = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            if rule.rule.exempts(user) {
+                continue;
+            }
+            for found in rule.patterns.iter().flat_map(|p| p.find_iter(text)) {
+                let matched = found.range();
+                let values: Vec<_> = value_regex()
+                    .find_iter(&text[matched.clone()])
+                    .map(|v| matched.start + v.start()..matched.start + v.end())
+                    .collect();
+                if values.is_empty() {
+                    spans.push((matched, index));
+                } else {
+                    spans.extend(values.into_iter().map(|v| (v, index)));
+                }
+            }
+        }
+        if spans.is_empty() {
+            return masking;
+        }
+        spans.sort_by_key(|(span, _)| (span.start, std::cmp::Reverse(span.end)));
+
+        let mut counts = vec![0; self.rules.len()];
+        masking.text = String::with_capacity(text.len());
+        let mut last = 0;
+        for (span, index) in spans {
+            // Overlapping matches were masked by the earlier span
+            if span.start < last {
+                continue;
+            }
+            masking.text.push_str(&text[last..span.start]);
+            masking.text.push_str(marker);
+            last = span.end;
+            counts[index] += 1;
+        }
+        masking.text.push_str(&text[last..]);
+        masking.values = self
+            .rules
+            .iter()
+            .zip(counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(rule, count)| (rule.rule.name.clone(), count))
+            .collect();
+        masking
+    }
+
+    /// What masked values covered and whom to ask for access
+    pub fn withheld(
+        &self,
+        values: &[(String, usize)],
+        template: &PromptTemplate,
+    ) -> Vec<WithheldTopic> {
+        values
+            .iter()
+            .filter_map(|(name, count)| {
+                let rule = &self.rules.iter().find(|r| &r.rule.name == name)?.rule;
+                Some(WithheldTopic {
+                    rule: rule.name.clone(),
+                    description: rule.description.clone(),
+                    values: *count,
+                    access_request: rule
+                        .access_request
+                        .clone()
+                        .unwrap_or_else(|| template.access_request(rule)),
+                })
+            })
+            .collect()
+    }
+
+    /// Moderate a generated answer
+    ///
+    /// Pattern hits on a refusing rule withhold the answer; hits on redacting
+    /// rules only replace the matched sentences. When no pattern fires and the
+    /// classifier is enabled, the LLM gets the final word; since it cannot
+    /// point at sentences, a classifier hit always refuses.
+    pub async fn moderate(
+        &self,
+        answer: &str,
+        user: &User,
+        llm: &dyn LlmClient,
+        template: &PromptTemplate,
+    ) -> Moderation {
+        let unchanged = Moderation {
+            answer: answer.to_string(),
+            decision: None,
+        };
+        if !self.is_enabled() {
+            return unchanged;
+        }
+
+        let redaction = self.redact(answer, user, template.redacted_marker);
+        if !redaction.rules.is_empty() {
+            let refuse = self.applicable(user).any(|r| {
+                r.rule.action == ModerationAction::Refuse && redaction.rules.contains(&r.rule.name)
+            });
+            let (answer, action) = if refuse {
+                (
+                    template.moderation_refusal.to_string(),
+                    ModerationAction::Refuse,
+                )
+            } else {
+                (redaction.text, ModerationAction::Redact)
+            };
+            return Moderation {
+                answer,
+                decision: Some(ModerationDecision {
+                    action,
+                    rules: redaction.rules,
+                    detector: ModerationDetector::Pattern,
+                    redacted_sentences: if refuse { 0 } else { redaction.sentences },
+                }),
+            };
+        }
+
+        if !self.llm_classifier {
+            return unchanged;
+        }
+        match self.classify(answer, user, llm).await {
+            Some(rule) => Moderation {
+                answer: template.moderation_refusal.to_string(),
+                decision: Some(ModerationDecision {
+                    action: ModerationAction::Refuse,
+                    rules: vec![rule],
+                    detector: ModerationDetector::Classifier,
+                    redacted_sentences: 0,
+                }),
+            },
+            None => unchanged,
+        }
+    }
+
+    /// Ask the LLM which applicable topic, if any, the answer discloses
+    async fn classify(&self, answer: &str, user: &User, llm: &dyn LlmClient) -> Option<String> {
+        let rules: Vec<_> = self.applicable(user).map(|r| &r.rule).collect();
+        if rules.is_empty() {
+            return None;
+        }
+
+        let prompt = classifier_prompt(answer, &rules);
+        let response = match llm.generate(&prompt).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("Moderation classifier failed, keeping pattern verdict: {e}");
+                return None;
+            }
+        };
+        parse_classification(&response, &rules)
+    }
+}
+
+/// Amounts and other numbers, with their units
+fn value_regex() -> &'static Regex {
+    static VALUE: OnceLock<Regex> = OnceLock::new();
+    VALUE.get_or_init(|| {
+        Regex::new(
+            r"(?i)(?:[$₩]\s?)?\d[\d,.]*(?:\s*(?:억|만|천))*(?:\s*(?:원|krw|usd|won|dollars?))?",
+        )
+        .expect("value regex is valid")
+    })
+}
+
+fn classifier_prompt(answer: &str, rules: &[&SensitiveTopicRule]) -> String {
+    let topics: String = rules
+        .iter()
+        .map(|r| format!("- {}: {}\n", r.name, r.description))
+        .collect();
+    format!(
+        "You review answers of an HR assistant before they are shown to an employee.\n\
+         Does the answer below disclose any of these sensitive topics?\n{topics}\n\
+         Reply with the name of the disclosed topic only, or NONE.\n\nAnswer:\n{answer}\n"
+    )
+}
+
+fn parse_classification(response: &str, rules: &[&SensitiveTopicRule]) -> Option<String> {
+    let response = response.trim().to_lowercase();
+    if response.is_empty() || response.starts_with("none") {
+        return None;
+    }
+    rules
+        .iter()
+        .find(|r| response.contains(&r.name.to_lowercase()))
+        .map(|r| r.name.clone())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::Language;
+
+    /// Answers every prompt with a fixed verdict
+    struct VerdictLlm(&'static str);
+
+    #[async_trait::async_trait]
+    impl LlmClient for VerdictLlm {
+        async fn generate(&self, prompt: &str) -> otl_core::Result<String> {
+            assert!(prompt.contains("sensitive topics"));
+            Ok(self.0.to_string())
+        }
+
+        async fn generate_stream(
+            &self,
+            _prompt: &str,
+        ) -> otl_core::Result<futures::stream::BoxStream<'static, otl_core::Result<String>>>
+        {
+            Err(OtlError::LlmError("not streaming".to_string()))
+        }
+    }
+
+    fn employee() -> User {
+        User::internal("emp-1", vec!["EMPLOYEE".to_string()])
+    }
+
+    fn korean() -> &'static PromptTemplate {
+        PromptTemplate::for_language(Language::Korean)
+    }
+
+    #[test]
+    fn test_default_rules_compile() {
+        let moderator = Moderator::new(&ModerationConfig::default()).unwrap();
+        assert!(moderator.is_enabled());
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        let config = ModerationConfig {
+            rules: vec![SensitiveTopicRule {
+                name: "broken".to_string(),
+                description: String::new(),
+                patterns: vec!["(unclosed".to_string()],
+                action: ModerationAction::Redact,
+                allowed_roles: Vec::new(),
+                allowed_departments: Vec::new(),
+                access_request: None,
+            }],
+            ..Default::default()
+        };
+        assert!(Moderator::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_mask_keeps_words_around_restricted_values() {
+        let config = ModerationConfig {
+            mask_context: true,
+            ..Default::default()
+        };
+        let moderator = Moderator::new(&config).unwrap();
+        let text = "연차휴가는 15일이다. 과장 연봉은 6,500만원이고 징계 이력이 있다.";
+
+        let masking = moderator.mask(text, &employee(), "[권한 필요]");
+        assert_eq!(
+            masking.text,
+            "연차휴가는 15일이다. 과장 연봉은 [권한 필요]이고 [권한 필요]이 있다."
+        );
+        assert_eq!(
+            masking.values,
+            vec![("salary".to_string(), 1), ("disciplinary".to_string(), 1)]
+        );
+
+        let mut hr = employee();
+        hr.roles.push("HR_MANAGER".to_string());
+        assert_eq!(moderator.mask(text, &hr, "[권한 필요]").text, text);
+
+        let withheld = moderator.withheld(&masking.values, korean());
+        assert_eq!(
+            withheld[0].access_request,
+            "열람 권한 요청: HR, 인사팀, ADMIN, HR_MANAGER"
+        );
+        assert_eq!(withheld[1].values, 1);
+
+        let unmasked = Moderator::new(&ModerationConfig::default()).unwrap();
+        assert!(unmasked
+            .mask(text, &employee(), "[권한 필요]")
+            .values
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_salary_is_redacted_for_employees() {
+        let moderator = Moderator::new(&ModerationConfig::default()).unwrap();
+        let answer =
+            "연차휴가는 15일입니다 [출처: 1]. 김철수 과장의 연봉은 6,500만원입니다 [출처: 2].";
+
+        let moderation = moderator
+            .moderate(answer, &employee(), &VerdictLlm("NONE"), korean())
+            .await;
+
+        let decision = moderation.decision.unwrap();
+        assert_eq!(decision.action, ModerationAction::Redact);
+        assert_eq!(decision.rules, vec!["salary".to_string()]);
+        assert_eq!(decision.redacted_sentences, 1);
+        assert!(moderation.answer.starts_with("연차휴가는 15일입니다"));
+        assert!(moderation.answer.contains("[비공개 정보]"));
+        assert!(!moderation.answer.contains("6,500"));
+    }
+
+    #[tokio::test]
+    async fn test_hr_staff_are_exempt() {
+        let moderator = Moderator::new(&ModerationConfig::default()).unwrap();
+        let mut user = employee();
+        user.departments = vec!["인사팀".to_string()];
+        let answer = "김철수 과장의 연봉은 6,500만원입니다.";
+
+        let moderation = moderator
+            .moderate(answer, &user, &VerdictLlm("NONE"), korean())
+            .await;
+
+        assert!(moderation.decision.is_none());
+        assert_eq!(moderation.answer, answer);
+    }
+
+    #[tokio::test]
+    async fn test_disciplinary_record_refuses() {
+        let moderator = Moderator::new(&ModerationConfig::default()).unwrap();
+        let answer = "Policy allows appeals. John Doe was suspended in 2023.";
+
+        let moderation = moderator
+            .moderate(
+                answer,
+                &employee(),
+                &VerdictLlm("NONE"),
+                PromptTemplate::for_language(Language::English),
+            )
+            .await;
+
+        let decision = moderation.decision.unwrap();
+        assert_eq!(decision.action, ModerationAction::Refuse);
+        assert_eq!(decision.detector, ModerationDetector::Pattern);
+        assert!(moderation.answer.contains("not authorized"));
+    }
+
+    #[tokio::test]
+    async fn test_classifier_catches_what_patterns_miss() {
+        let config = ModerationConfig {
+            llm_classifier: true,
+            ..Default::default()
+        };
+        let moderator = Moderator::new(&config).unwrap();
+        let answer = "그 직원은 작년에 팀장 대비 두 배를 받았습니다.";
+
+        let flagged = moderator
+            .moderate(answer, &employee(), &VerdictLlm("salary"), korean())
+            .await;
+        let decision = flagged.decision.unwrap();
+        assert_eq!(decision.detector, ModerationDetector::Classifier);
+        assert_eq!(decision.rules, vec!["salary".to_string()]);
+
+        let clean = moderator
+            .moderate(answer, &employee(), &VerdictLlm("NONE"), korean())
+            .await;
+        assert!(clean.decision.is_none());
+    }
+}
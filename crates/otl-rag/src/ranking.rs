@@ -0,0 +1,234 @@
+//! Metadata-boosted ranking
+//!
+//! After rank fusion every chunk is scored on relevance alone. Ranking boosts
+//! multiply the fused score by document metadata: recently updated
+//! documents, the latest version of a document whose older versions were
+//! also retrieved, documents owned by the asking user's department and
+//! documents tagged as authoritative regulations.
+//!
+//! Versions and tags come from the document's custom metadata: `version`
+//! (number), `document_group` (shared by all versions, the title otherwise),
+//! `superseded_by` (see [`otl_core::freshness`]) and `tags` (list of strings).
+//!
+//! Author: hephaex@gmail.com
+
+use chrono::{DateTime, Utc};
+use otl_core::{freshness, DocumentMetadata, SearchResult, User};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Per-deployment ranking boost configuration
+///
+/// Multipliers of 1.0 disable the corresponding boost.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RankingBoosts {
+    /// Apply boosts at all
+    pub enabled: bool,
+
+    /// Extra weight for a document updated just now (decays with age)
+    pub recency_boost: f32,
+
+    /// Age in days at which the recency boost is halved
+    pub recency_half_life_days: f32,
+
+    /// Multiplier for older versions when a newer version was also retrieved
+    pub superseded_multiplier: f32,
+
+    /// Multiplier for documents owned by one of the user's departments
+    pub department_multiplier: f32,
+
+    /// Multiplier for documents carrying an authoritative tag
+    pub authoritative_multiplier: f32,
+
+    /// Tags marking authoritative documents
+    pub authoritative_tags: Vec<String>,
+}
+
+impl Default for RankingBoosts {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            recency_boost: 0.1,
+            recency_half_life_days: 365.0,
+            superseded_multiplier: 0.7,
+            department_multiplier: 1.15,
+            authoritative_multiplier: 1.2,
+            authoritative_tags: vec!["authoritative".to_string()],
+        }
+    }
+}
+
+impl RankingBoosts {
+    /// Multiply result scores by their documents' boosts and re-sort
+    ///
+    /// Results whose document has no metadata keep their score.
+    pub fn apply(
+        &self,
+        results: &mut [SearchResult],
+        documents: &HashMap<Uuid, DocumentMetadata>,
+        user: &User,
+        now: DateTime<Utc>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let superseded = freshness::superseded(documents.values());
+        for result in results.iter_mut() {
+            if let Some(doc) = documents.get(&result.source.document_id) {
+                let superseded = superseded.contains(&doc.id);
+                result.score *= self.multiplier(doc, user, now, superseded);
+            }
+        }
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+
+    /// Combined score multiplier of a document
+    pub fn multiplier(
+        &self,
+        doc: &DocumentMetadata,
+        user: &User,
+        now: DateTime<Utc>,
+        superseded: bool,
+    ) -> f32 {
+        let mut multiplier = 1.0;
+
+        if self.recency_boost > 0.0 && self.recency_half_life_days > 0.0 {
+            let age_days = (now - doc.updated_at).num_seconds().max(0) as f32 / 86_400.0;
+            multiplier *=
+                1.0 + self.recency_boost * 0.5f32.powf(age_days / self.recency_half_life_days);
+        }
+        if superseded {
+            multiplier *= self.superseded_multiplier;
+        }
+        let owned_by_user = doc.acl.department.as_ref().is_some_and(|dept| {
+            user.departments
+                .iter()
+                .any(|d| d.eq_ignore_ascii_case(dept))
+        });
+        if owned_by_user {
+            multiplier *= self.department_multiplier;
+        }
+        let authoritative = tags(doc).iter().any(|tag| {
+            self.authoritative_tags
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(tag))
+        });
+        if authoritative {
+            multiplier *= self.authoritative_multiplier;
+        }
+
+        multiplier
+    }
+}
+
+fn tags(doc: &DocumentMetadata) -> Vec<&str> {
+    doc.extra
+        .get("tags")
+        .and_then(|v| v.as_array())
+        .map(|tags| tags.iter().filter_map(|t| t.as_str()).collect())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use otl_core::{DocumentAcl, SearchResultType, SourceReference};
+
+    fn document(title: &str, age_days: i64, now: DateTime<Utc>) -> DocumentMetadata {
+        let mut doc = DocumentMetadata::new(title, format!("/docs/{title}.pdf"), "pdf");
+        doc.updated_at = now - Duration::days(age_days);
+        doc
+    }
+
+    fn result(document_id: Uuid, score: f32) -> SearchResult {
+        SearchResult {
+            content: document_id.to_string(),
+            score,
+            source: SourceReference::new(document_id),
+            acl: DocumentAcl::default(),
+            result_type: SearchResultType::Vector,
+        }
+    }
+
+    fn user(department: &str) -> User {
+        User {
+            departments: vec![department.to_string()],
+            ..User::anonymous()
+        }
+    }
+
+    #[test]
+    fn test_multiplier_combines_boosts() {
+        let now = Utc::now();
+        let boosts = RankingBoosts {
+            recency_boost: 0.0,
+            ..RankingBoosts::default()
+        };
+        let mut doc = document("휴가 규정", 0, now);
+        assert_eq!(boosts.multiplier(&doc, &user("인사팀"), now, false), 1.0);
+
+        doc.acl.department = Some("인사팀".to_string());
+        doc.extra
+            .insert("tags".to_string(), serde_json::json!(["authoritative"]));
+        let multiplier = boosts.multiplier(&doc, &user("인사팀"), now, false);
+        assert!((multiplier - 1.15 * 1.2).abs() < 1e-6);
+        assert!(boosts.multiplier(&doc, &user("재무팀"), now, true) < 1.0);
+
+        let recency = RankingBoosts::default();
+        let fresh = recency.multiplier(&document("a", 0, now), &user("x"), now, false);
+        let year_old = recency.multiplier(&document("a", 365, now), &user("x"), now, false);
+        assert!((fresh - 1.1).abs() < 1e-6);
+        assert!((year_old - 1.05).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_newer_version_outranks_superseded() {
+        let now = Utc::now();
+        let mut old = document("취업규칙", 400, now);
+        old.extra
+            .insert("version".to_string(), serde_json::json!(1));
+        let mut new = document("취업규칙", 10, now);
+        new.extra
+            .insert("version".to_string(), serde_json::json!("v2"));
+        let other = document("출장 규정", 400, now);
+
+        let documents: HashMap<Uuid, DocumentMetadata> = [&old, &new, &other]
+            .into_iter()
+            .map(|d| (d.id, d.clone()))
+            .collect();
+        let mut results = vec![
+            result(old.id, 0.030),
+            result(other.id, 0.029),
+            result(new.id, 0.028),
+            result(Uuid::new_v4(), 0.027),
+        ];
+
+        RankingBoosts::default().apply(&mut results, &documents, &user("인사팀"), now);
+
+        assert_eq!(results[0].source.document_id, new.id);
+        assert_eq!(results[1].source.document_id, other.id);
+        assert_eq!(results.last().unwrap().source.document_id, old.id);
+    }
+
+    #[test]
+    fn test_disabled_boosts_keep_order() {
+        let now = Utc::now();
+        let doc = document("a", 0, now);
+        let documents = HashMap::from([(doc.id, doc.clone())]);
+        let mut results = vec![result(Uuid::new_v4(), 0.5), result(doc.id, 0.4)];
+
+        let boosts = RankingBoosts {
+            enabled: false,
+            ..RankingBoosts::default()
+        };
+        boosts.apply(&mut results, &documents, &user("x"), now);
+        assert_eq!(results[1].score, 0.4);
+    }
+}
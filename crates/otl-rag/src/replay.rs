@@ -0,0 +1,429 @@
+//! Seeded query runs and their replay
+//!
+//! A query with a seed runs on a copy of the orchestrator whose LLM client
+//! samples with temperature 0 and the seed. The copy records what every
+//! retrieval backend returned, each prompt and response, the embeddings and
+//! the document metadata and chunks it read, and returns them as a [`QueryRecording`]
+//! in the trace.
+//!
+//! Replaying a recording runs the pipeline on a copy that reads all of this
+//! from the recording instead, so the run is reproduced without Qdrant,
+//! SurrealDB, PostgreSQL or the LLM. ACL filtering, fusion, prompting and
+//! moderation run as usual, so pipeline changes show up as prompts or
+//! answers that differ from the recorded ones.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use otl_core::{
+    DocumentChunk, DocumentMetadata, LlmClient, OtlError, QueryRecording, RagQuery,
+    RecordedCompletion, RecordedEmbedding, RecordedSearch, Result, SearchResult, SearchResultType,
+};
+use otl_vector::embedding::EmbeddingClient;
+use uuid::Uuid;
+
+/// How a copy of the orchestrator reproduces a run
+#[derive(Clone)]
+pub(crate) enum Reproduction {
+    /// Record everything read from backends
+    Record(Arc<Recorder>),
+    /// Read everything from a recording
+    Replay(Arc<QueryRecording>),
+}
+
+impl Reproduction {
+    /// Time the run ranks documents and checks freshness as of
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        match self {
+            Self::Record(recorder) => recorder.started_at,
+            Self::Replay(recording) => recording.recorded_at,
+        }
+    }
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Collects the inputs of a seeded run
+pub(crate) struct Recorder {
+    seed: u64,
+    started_at: DateTime<Utc>,
+    searches: Mutex<Vec<RecordedSearch>>,
+    documents: Mutex<Vec<DocumentMetadata>>,
+    corrupted_chunks: Mutex<Vec<(Uuid, u32)>>,
+    neighbor_chunks: Mutex<Vec<DocumentChunk>>,
+    feedback_priors: Mutex<Vec<(Uuid, Option<u32>, f32)>>,
+    completions: Mutex<Vec<RecordedCompletion>>,
+    embeddings: Mutex<Vec<RecordedEmbedding>>,
+}
+
+impl Recorder {
+    /// Start recording a run with `seed`
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            started_at: Utc::now(),
+            searches: Mutex::new(Vec::new()),
+            documents: Mutex::new(Vec::new()),
+            corrupted_chunks: Mutex::new(Vec::new()),
+            neighbor_chunks: Mutex::new(Vec::new()),
+            feedback_priors: Mutex::new(Vec::new()),
+            completions: Mutex::new(Vec::new()),
+            embeddings: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record the outcome of the retrieval backends
+    pub(crate) fn searches(&self, searches: Vec<RecordedSearch>) {
+        *self.searches.lock().unwrap() = searches;
+    }
+
+    /// Record document metadata that was read
+    pub(crate) fn documents(&self, documents: &[DocumentMetadata]) {
+        let mut recorded = self.documents.lock().unwrap();
+        for document in documents {
+            if !recorded.iter().any(|d| d.id == document.id) {
+                recorded.push(document.clone());
+            }
+        }
+    }
+
+    /// Record chunks reported corrupted
+    pub(crate) fn corrupted_chunks(&self, chunks: &[(Uuid, u32)]) {
+        let mut recorded = self.corrupted_chunks.lock().unwrap();
+        for chunk in chunks {
+            if !recorded.contains(chunk) {
+                recorded.push(*chunk);
+            }
+        }
+    }
+
+    /// Record neighboring chunks read to expand contexts
+    pub(crate) fn neighbor_chunks(&self, chunks: &[DocumentChunk]) {
+        let mut recorded = self.neighbor_chunks.lock().unwrap();
+        for chunk in chunks {
+            let position = (chunk.document_id, chunk.chunk_index);
+            if !recorded
+                .iter()
+                .any(|c| (c.document_id, c.chunk_index) == position)
+            {
+                recorded.push(chunk.clone());
+            }
+        }
+    }
+
+    /// Record feedback prior multipliers other than 1
+    pub(crate) fn feedback_priors(&self, results: &[SearchResult], multipliers: &[f32]) {
+        let mut recorded = self.feedback_priors.lock().unwrap();
+        for (result, &multiplier) in results.iter().zip(multipliers) {
+            let chunk = (result.source.document_id, result.source.chunk_index);
+            if multiplier != 1.0 && !recorded.iter().any(|(d, c, _)| (*d, *c) == chunk) {
+                recorded.push((chunk.0, chunk.1, multiplier));
+            }
+        }
+    }
+
+    fn completion(&self, prompt: &str, response: &str) {
+        self.completions.lock().unwrap().push(RecordedCompletion {
+            prompt: prompt.to_string(),
+            response: response.to_string(),
+        });
+    }
+
+    fn embedding(&self, text: &str, vector: &[f32]) {
+        let mut recorded = self.embeddings.lock().unwrap();
+        if !recorded.iter().any(|e| e.text == text) {
+            recorded.push(RecordedEmbedding {
+                text: text.to_string(),
+                vector: vector.to_vec(),
+            });
+        }
+    }
+
+    /// The recording of `query`, which was answered with `answer`
+    pub(crate) fn finish(&self, query: &RagQuery, answer: &str) -> QueryRecording {
+        QueryRecording {
+            query: query.clone(),
+            recorded_at: self.started_at,
+            searches: self.searches.lock().unwrap().clone(),
+            documents: self.documents.lock().unwrap().clone(),
+            corrupted_chunks: self.corrupted_chunks.lock().unwrap().clone(),
+            neighbor_chunks: self.neighbor_chunks.lock().unwrap().clone(),
+            feedback_priors: self.feedback_priors.lock().unwrap().clone(),
+            completions: self.completions.lock().unwrap().clone(),
+            embeddings: self.embeddings.lock().unwrap().clone(),
+            answer: answer.to_string(),
+        }
+    }
+}
+
+/// LLM client generating with the recorder's seed and recording every call
+///
+/// Every call bypasses response caches. Streaming is passed through
+/// unrecorded; the query pipeline does not stream.
+pub(crate) struct SeededLlm {
+    inner: Arc<dyn LlmClient>,
+    recorder: Arc<Recorder>,
+}
+
+impl SeededLlm {
+    pub(crate) fn new(inner: Arc<dyn LlmClient>, recorder: Arc<Recorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl LlmClient for SeededLlm {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        let response = self
+            .inner
+            .generate_seeded(prompt, self.recorder.seed)
+            .await?;
+        self.recorder.completion(prompt, &response);
+        Ok(response)
+    }
+
+    async fn generate_uncached(&self, prompt: &str) -> Result<String> {
+        self.generate(prompt).await
+    }
+
+    async fn generate_seeded(&self, prompt: &str, _seed: u64) -> Result<String> {
+        self.generate(prompt).await
+    }
+
+    async fn generate_stream(&self, prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
+        self.inner.generate_stream(prompt).await
+    }
+}
+
+/// Embedding client recording every embedding it returns
+pub(crate) struct RecordingEmbedding {
+    inner: Arc<dyn EmbeddingClient>,
+    recorder: Arc<Recorder>,
+}
+
+impl RecordingEmbedding {
+    pub(crate) fn new(inner: Arc<dyn EmbeddingClient>, recorder: Arc<Recorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl EmbeddingClient for RecordingEmbedding {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let vector = self.inner.embed(text).await?;
+        self.recorder.embedding(text, &vector);
+        Ok(vector)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let vectors = self.inner.embed_batch(texts).await?;
+        for (text, vector) in texts.iter().zip(&vectors) {
+            self.recorder.embedding(text, vector);
+        }
+        Ok(vectors)
+    }
+
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+}
+
+// ============================================================================
+// Replay
+// ============================================================================
+
+/// A search outcome and the time it took
+type TimedSearch = (Result<Vec<SearchResult>>, Duration);
+
+/// Recorded outcomes of the vector, graph, keyword and FAQ searches
+///
+/// A backend missing from the recording returns no results.
+pub(crate) fn recorded_searches(
+    recording: &QueryRecording,
+) -> (TimedSearch, TimedSearch, TimedSearch, TimedSearch) {
+    let outcome = |backend: SearchResultType| {
+        let results = recording
+            .search(&backend)
+            .map_or_else(|| Ok(Vec::new()), RecordedSearch::outcome);
+        (results, Duration::ZERO)
+    };
+    (
+        outcome(SearchResultType::Vector),
+        outcome(SearchResultType::Graph),
+        outcome(SearchResultType::Keyword),
+        outcome(SearchResultType::Faq),
+    )
+}
+
+/// LLM client answering with the responses of a recording
+///
+/// A prompt gets the unused recorded response to the identical prompt.
+/// Failing that, it gets the next unused response in call order and counts
+/// as a mismatch, so a replay continues after a prompt has changed.
+pub(crate) struct ReplayLlm {
+    completions: Vec<RecordedCompletion>,
+    used: Mutex<Vec<bool>>,
+    mismatched: AtomicUsize,
+}
+
+impl ReplayLlm {
+    pub(crate) fn new(completions: &[RecordedCompletion]) -> Self {
+        Self {
+            completions: completions.to_vec(),
+            used: Mutex::new(vec![false; completions.len()]),
+            mismatched: AtomicUsize::new(0),
+        }
+    }
+
+    /// Prompts that differed from every unused recorded prompt
+    pub(crate) fn mismatched(&self) -> usize {
+        self.mismatched.load(Ordering::Relaxed)
+    }
+
+    fn respond(&self, prompt: &str) -> Result<String> {
+        let mut used = self.used.lock().unwrap();
+        let unused = |i: &usize| !used[*i];
+        let index = match (0..self.completions.len())
+            .filter(unused)
+            .find(|&i| self.completions[i].prompt == prompt)
+        {
+            Some(index) => index,
+            None => {
+                let index = (0..self.completions.len()).find(unused).ok_or_else(|| {
+                    OtlError::LlmError("recording has no LLM response left to replay".into())
+                })?;
+                self.mismatched.fetch_add(1, Ordering::Relaxed);
+                index
+            }
+        };
+        used[index] = true;
+        Ok(self.completions[index].response.clone())
+    }
+}
+
+#[async_trait]
+impl LlmClient for ReplayLlm {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        self.respond(prompt)
+    }
+
+    async fn generate_stream(&self, _prompt: &str) -> Result<BoxStream<'static, Result<String>>> {
+        Err(OtlError::LlmError(
+            "streaming is not available when replaying".to_string(),
+        ))
+    }
+}
+
+/// Embedding client returning the embeddings of a recording
+///
+/// Texts that were not embedded in the recorded run fail, which the
+/// pipeline handles as an unavailable embedding endpoint.
+pub(crate) struct ReplayEmbedding {
+    vectors: HashMap<String, Vec<f32>>,
+    dimension: usize,
+}
+
+impl ReplayEmbedding {
+    pub(crate) fn new(embeddings: &[RecordedEmbedding]) -> Self {
+        Self {
+            vectors: embeddings
+                .iter()
+                .map(|e| (e.text.clone(), e.vector.clone()))
+                .collect(),
+            dimension: embeddings.first().map_or(0, |e| e.vector.len()),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingClient for ReplayEmbedding {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.vectors
+            .get(text)
+            .cloned()
+            .ok_or_else(|| OtlError::LlmError("no recorded embedding for text".to_string()))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for text in texts {
+            vectors.push(self.embed(text).await?);
+        }
+        Ok(vectors)
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completion(prompt: &str, response: &str) -> RecordedCompletion {
+        RecordedCompletion {
+            prompt: prompt.to_string(),
+            response: response.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_llm_matches_prompts_then_call_order() {
+        let llm = ReplayLlm::new(&[
+            completion("답변 생성", "연차휴가는 15일입니다 [출처: 1]"),
+            completion("검열", "SAFE"),
+        ]);
+
+        assert_eq!(llm.generate("검열").await.unwrap(), "SAFE");
+        assert_eq!(llm.mismatched(), 0);
+        assert_eq!(
+            llm.generate("바뀐 프롬프트").await.unwrap(),
+            "연차휴가는 15일입니다 [출처: 1]"
+        );
+        assert_eq!(llm.mismatched(), 1);
+        assert!(matches!(
+            llm.generate("검열").await,
+            Err(OtlError::LlmError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_recorded_failures_replay_as_errors() {
+        let recorder = Recorder::new(7);
+        recorder.searches(vec![
+            RecordedSearch::new(SearchResultType::Vector, true, &Ok(Vec::new())),
+            RecordedSearch::new(
+                SearchResultType::Keyword,
+                true,
+                &Err(OtlError::SearchError("index offline".to_string())),
+            ),
+        ]);
+        let recording = recorder.finish(&RagQuery::new("연차휴가").with_seed(7), "");
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let recording: QueryRecording = serde_json::from_str(&json).unwrap();
+        let (vector, graph, keyword, _) = recorded_searches(&recording);
+
+        assert!(vector.0.unwrap().is_empty());
+        assert!(graph.0.unwrap().is_empty());
+        match keyword.0 {
+            Err(OtlError::SearchError(message)) => assert_eq!(message, "index offline"),
+            other => panic!("expected a search error, got {other:?}"),
+        }
+        assert_eq!(recording.query.seed, Some(7));
+    }
+}
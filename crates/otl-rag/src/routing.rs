@@ -0,0 +1,175 @@
+//! Model routing
+//!
+//! Simple questions do not need the most capable model. Routing rules map
+//! the detected intent and the question length to a named model tier; the
+//! first matching rule wins, and questions no rule matches are answered by
+//! the default LLM client. The model that generated an answer is reported
+//! in `RagResponse.model`.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::QueryIntent;
+use otl_core::{LlmConfig, LlmProvider};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Model of a tier; settings left out are taken from the default LLM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelTier {
+    /// Provider serving the model
+    #[serde(default)]
+    pub provider: Option<LlmProvider>,
+
+    /// Model name
+    pub model: String,
+
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+impl ModelTier {
+    /// LLM settings of the tier on top of the default ones
+    pub fn llm_config(&self, base: &LlmConfig) -> LlmConfig {
+        LlmConfig {
+            provider: self.provider.unwrap_or(base.provider),
+            model: self.model.clone(),
+            max_tokens: self.max_tokens.unwrap_or(base.max_tokens),
+            temperature: self.temperature.unwrap_or(base.temperature),
+            ..base.clone()
+        }
+    }
+}
+
+/// Rule sending matching questions to a tier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    /// Intents the rule applies to (all when empty)
+    #[serde(default)]
+    pub intents: Vec<QueryIntent>,
+
+    /// Minimum question length in characters
+    #[serde(default)]
+    pub min_chars: Option<usize>,
+
+    /// Maximum question length in characters
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+
+    /// Tier answering matching questions
+    pub tier: String,
+}
+
+impl RoutingRule {
+    fn matches(&self, intent: QueryIntent, chars: usize) -> bool {
+        (self.intents.is_empty() || self.intents.contains(&intent))
+            && self.min_chars.map_or(true, |min| chars >= min)
+            && self.max_chars.map_or(true, |max| chars <= max)
+    }
+}
+
+/// Model tiers and the rules selecting them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelRouting {
+    pub tiers: BTreeMap<String, ModelTier>,
+    pub rules: Vec<RoutingRule>,
+}
+
+impl ModelRouting {
+    /// Check that every rule names a defined tier
+    pub fn validate(&self) -> Result<(), String> {
+        match self
+            .rules
+            .iter()
+            .find(|r| !self.tiers.contains_key(&r.tier))
+        {
+            Some(rule) => Err(format!("routing rule uses unknown tier '{}'", rule.tier)),
+            None => Ok(()),
+        }
+    }
+
+    /// Tier of the first rule matching the question, if any
+    pub fn select(&self, intent: QueryIntent, question: &str) -> Option<&str> {
+        let chars = question.chars().count();
+        self.rules
+            .iter()
+            .find(|r| r.matches(intent, chars))
+            .map(|r| r.tier.as_str())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routing() -> ModelRouting {
+        serde_json::from_str(
+            r#"{
+                "tiers": {
+                    "small": {"provider": "ollama", "model": "qwen2.5:3b"},
+                    "large": {"model": "gpt-4o", "max_tokens": 4096}
+                },
+                "rules": [
+                    {"intents": ["definitional", "factual"], "max_chars": 40, "tier": "small"},
+                    {"intents": ["comparative"], "tier": "large"},
+                    {"min_chars": 200, "tier": "large"}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_select_first_matching_rule() {
+        let routing = routing();
+
+        assert_eq!(
+            routing.select(QueryIntent::Definitional, "연차휴가란?"),
+            Some("small")
+        );
+        assert_eq!(
+            routing.select(QueryIntent::Comparative, "연차와 병가의 차이는?"),
+            Some("large")
+        );
+        assert_eq!(
+            routing.select(QueryIntent::Procedural, "출장 신청 절차는?"),
+            None
+        );
+        assert_eq!(routing.select(QueryIntent::Factual, &"가".repeat(41)), None);
+        assert_eq!(
+            routing.select(QueryIntent::Factual, &"가".repeat(200)),
+            Some("large")
+        );
+    }
+
+    #[test]
+    fn test_tier_llm_config() {
+        let routing = routing();
+        let base = LlmConfig::default();
+
+        let small = routing.tiers["small"].llm_config(&base);
+        assert_eq!(small.provider, LlmProvider::Ollama);
+        assert_eq!(small.model, "qwen2.5:3b");
+        assert_eq!(small.max_tokens, base.max_tokens);
+
+        let large = routing.tiers["large"].llm_config(&base);
+        assert_eq!(large.provider, base.provider);
+        assert_eq!(large.max_tokens, 4096);
+    }
+
+    #[test]
+    fn test_validate_unknown_tier() {
+        let mut routing = routing();
+        assert!(routing.validate().is_ok());
+
+        routing.rules[0].tier = "medium".to_string();
+        assert!(routing.validate().unwrap_err().contains("medium"));
+    }
+}
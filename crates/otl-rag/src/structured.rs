@@ -0,0 +1,321 @@
+//! Ontology-guided structured answers
+//!
+//! For list and single-fact questions the prose answer is hard to consume
+//! programmatically. After generation, the LLM is asked to restate the
+//! answer as JSON following a fixed schema; the result is parsed and
+//! validated against the ontology classes before it is returned.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::language::PromptTemplate;
+use crate::AnswerType;
+use otl_core::{OntologyClass, OtlError, Result, SearchResult, StructuredAnswer};
+use std::collections::HashSet;
+
+/// Maximum characters of each context passage included in the prompt
+const MAX_PASSAGE_CHARS: usize = 500;
+
+const LIST_SCHEMA: &str = r#"{"type": "list", "items": [{"class": "<ontology class>", "name": "<entity name>", "properties": {"<property>": <value>}, "citations": [<context number>]}]}"#;
+
+const FACT_SCHEMA: &str = r#"{"type": "fact", "subject": "<what the fact is about>", "value": <number | string | boolean>, "unit": "<unit or null>", "citations": [<context number>]}"#;
+
+/// Whether an answer type gets a structured answer
+pub fn supports_structured_answer(answer_type: &AnswerType) -> bool {
+    matches!(answer_type, AnswerType::List | AnswerType::SingleFact)
+}
+
+/// Build the prompt asking the LLM to restate an answer as schema JSON
+pub fn build_structured_prompt(
+    question: &str,
+    answer: &str,
+    results: &[SearchResult],
+    answer_type: &AnswerType,
+    classes: &[OntologyClass],
+    template: &PromptTemplate,
+) -> String {
+    let schema = match answer_type {
+        AnswerType::List => LIST_SCHEMA,
+        _ => FACT_SCHEMA,
+    };
+
+    let mut prompt = String::new();
+    prompt.push_str("<s>\n");
+    prompt.push_str("Restate the answer below as JSON that conforms to the schema.\n");
+    prompt.push_str("Output only the JSON object, without explanation or code fences.\n");
+    prompt.push_str("Use only information present in the answer and context.\n");
+    prompt.push_str(template.language_rule);
+    prompt.push_str("\n</s>\n\n");
+
+    prompt.push_str("<schema>\n");
+    prompt.push_str(schema);
+    prompt.push_str("\n</schema>\n\n");
+
+    if !classes.is_empty() && matches!(answer_type, AnswerType::List) {
+        prompt.push_str("<ontology>\n");
+        for class in classes {
+            let properties: Vec<&str> = class.properties.iter().map(|p| p.name.as_str()).collect();
+            prompt.push_str(&format!(
+                "- {} ({}): {}\n",
+                class.id,
+                class.label,
+                properties.join(", ")
+            ));
+        }
+        prompt.push_str("</ontology>\n\n");
+    }
+
+    prompt.push_str("<context>\n");
+    for (i, result) in results.iter().enumerate() {
+        let passage: String = result.content.chars().take(MAX_PASSAGE_CHARS).collect();
+        prompt.push_str(&format!("[{}] {}\n", i + 1, passage));
+    }
+    prompt.push_str("</context>\n\n");
+
+    prompt.push_str(&format!("<question>\n{question}\n</question>\n\n"));
+    prompt.push_str(&format!("<answer>\n{answer}\n</answer>\n"));
+
+    prompt
+}
+
+/// Parse and validate the LLM output for a structured answer
+///
+/// Items with a class unknown to the ontology are rejected; properties the
+/// class does not define are dropped. Citations outside `1..=num_contexts`
+/// are removed.
+pub fn parse_structured_answer(
+    raw: &str,
+    answer_type: &AnswerType,
+    classes: &[OntologyClass],
+    num_contexts: usize,
+) -> Result<StructuredAnswer> {
+    let json = extract_json_object(raw)
+        .ok_or_else(|| OtlError::ValidationError("No JSON object in LLM output".to_string()))?;
+    let mut answer: StructuredAnswer = serde_json::from_str(json)
+        .map_err(|e| OtlError::ValidationError(format!("Invalid structured answer: {e}")))?;
+
+    let valid_citation = |c: &u32| *c >= 1 && (*c as usize) <= num_contexts;
+
+    match (&mut answer, answer_type) {
+        (
+            StructuredAnswer::Fact {
+                subject,
+                value,
+                citations,
+                ..
+            },
+            AnswerType::SingleFact,
+        ) => {
+            if subject.trim().is_empty() {
+                return Err(OtlError::ValidationError(
+                    "Fact subject is empty".to_string(),
+                ));
+            }
+            if value.is_null() || value.is_object() || value.is_array() {
+                return Err(OtlError::ValidationError(
+                    "Fact value must be a number, string or boolean".to_string(),
+                ));
+            }
+            citations.retain(valid_citation);
+        }
+        (StructuredAnswer::List { items }, AnswerType::List) => {
+            if items.is_empty() {
+                return Err(OtlError::ValidationError(
+                    "List answer is empty".to_string(),
+                ));
+            }
+            for item in items.iter_mut() {
+                if item.name.trim().is_empty() {
+                    return Err(OtlError::ValidationError(
+                        "List item name is empty".to_string(),
+                    ));
+                }
+                item.citations.retain(valid_citation);
+
+                let Some(class_name) = item.class.as_deref() else {
+                    continue;
+                };
+                if classes.is_empty() {
+                    continue;
+                }
+                let class = find_class(classes, class_name).ok_or_else(|| {
+                    OtlError::ValidationError(format!("Unknown ontology class: {class_name}"))
+                })?;
+
+                let allowed = class_properties(classes, class);
+                item.properties.retain(|name, _| {
+                    let keep = allowed.contains(name.as_str());
+                    if !keep {
+                        tracing::debug!("Dropping property {} not defined on {}", name, class.id);
+                    }
+                    keep
+                });
+                item.class = Some(class.id.clone());
+            }
+        }
+        _ => {
+            return Err(OtlError::ValidationError(format!(
+                "Structured answer does not match expected type {answer_type:?}"
+            )));
+        }
+    }
+
+    Ok(answer)
+}
+
+/// Slice from the first `{` to the last `}` (strips prose and code fences)
+fn extract_json_object(raw: &str) -> Option<&str> {
+    let start = raw.find('{')?;
+    let end = raw.rfind('}')?;
+    (start < end).then(|| &raw[start..=end])
+}
+
+/// Find a class by ID or label
+fn find_class<'a>(classes: &'a [OntologyClass], name: &str) -> Option<&'a OntologyClass> {
+    classes
+        .iter()
+        .find(|c| c.id.eq_ignore_ascii_case(name) || c.label == name)
+}
+
+/// Property names defined on a class and its ancestors
+fn class_properties<'a>(
+    classes: &'a [OntologyClass],
+    class: &'a OntologyClass,
+) -> HashSet<&'a str> {
+    let mut names = HashSet::new();
+    let mut current = Some(class);
+    let mut visited = HashSet::new();
+
+    while let Some(c) = current {
+        if !visited.insert(c.id.as_str()) {
+            break;
+        }
+        names.extend(c.properties.iter().map(|p| p.name.as_str()));
+        current = c.parent.as_deref().and_then(|p| find_class(classes, p));
+    }
+
+    names
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{Cardinality, DataType, Language, PropertyDefinition};
+
+    fn classes() -> Vec<OntologyClass> {
+        let property = |name: &str| PropertyDefinition {
+            name: name.to_string(),
+            data_type: DataType::Integer,
+            cardinality: Cardinality::ZeroOrOne,
+            range: None,
+        };
+        vec![
+            OntologyClass {
+                id: "Benefit".to_string(),
+                label: "복리후생".to_string(),
+                description: None,
+                parent: None,
+                properties: vec![property("amount")],
+            },
+            OntologyClass {
+                id: "LeaveType".to_string(),
+                label: "휴가유형".to_string(),
+                description: None,
+                parent: Some("Benefit".to_string()),
+                properties: vec![property("days")],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_parse_list_answer() {
+        let raw = r#"```json
+{"type": "list", "items": [
+  {"class": "휴가유형", "name": "연차휴가", "properties": {"days": 15, "color": "red"}, "citations": [1, 9]},
+  {"name": "경조휴가"}
+]}
+```"#;
+
+        let answer = parse_structured_answer(raw, &AnswerType::List, &classes(), 2).unwrap();
+        let StructuredAnswer::List { items } = answer else {
+            panic!("expected list");
+        };
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].class.as_deref(), Some("LeaveType"));
+        assert_eq!(items[0].properties.len(), 1);
+        assert_eq!(items[0].properties["days"], serde_json::json!(15));
+        assert_eq!(items[0].citations, vec![1]);
+        assert!(items[1].class.is_none());
+    }
+
+    #[test]
+    fn test_inherited_properties_are_kept() {
+        let raw = r#"{"type": "list", "items": [{"class": "LeaveType", "name": "경조휴가", "properties": {"amount": 100000}}]}"#;
+
+        let answer = parse_structured_answer(raw, &AnswerType::List, &classes(), 1).unwrap();
+        let StructuredAnswer::List { items } = answer else {
+            panic!("expected list");
+        };
+        assert!(items[0].properties.contains_key("amount"));
+    }
+
+    #[test]
+    fn test_unknown_class_is_rejected() {
+        let raw = r#"{"type": "list", "items": [{"class": "Vehicle", "name": "차량"}]}"#;
+        assert!(parse_structured_answer(raw, &AnswerType::List, &classes(), 1).is_err());
+
+        // Without an ontology, classes are not checked
+        assert!(parse_structured_answer(raw, &AnswerType::List, &[], 1).is_ok());
+    }
+
+    #[test]
+    fn test_parse_fact_answer() {
+        let raw = r#"답변: {"type": "fact", "subject": "연차휴가 일수", "value": 15, "unit": "일", "citations": [1]}"#;
+
+        let answer = parse_structured_answer(raw, &AnswerType::SingleFact, &[], 3).unwrap();
+        assert_eq!(
+            answer,
+            StructuredAnswer::Fact {
+                subject: "연차휴가 일수".to_string(),
+                value: serde_json::json!(15),
+                unit: Some("일".to_string()),
+                citations: vec![1],
+            }
+        );
+    }
+
+    #[test]
+    fn test_type_mismatch_and_garbage_are_rejected() {
+        let fact = r#"{"type": "fact", "subject": "x", "value": 1}"#;
+        assert!(parse_structured_answer(fact, &AnswerType::List, &[], 1).is_err());
+
+        let nested = r#"{"type": "fact", "subject": "x", "value": {"a": 1}}"#;
+        assert!(parse_structured_answer(nested, &AnswerType::SingleFact, &[], 1).is_err());
+
+        assert!(parse_structured_answer("모르겠습니다", &AnswerType::List, &[], 1).is_err());
+    }
+
+    #[test]
+    fn test_structured_prompt_contains_schema_and_ontology() {
+        let template = PromptTemplate::for_language(Language::Korean);
+        let prompt = build_structured_prompt(
+            "휴가 종류는?",
+            "연차휴가와 경조휴가가 있습니다 [출처: 1]",
+            &[],
+            &AnswerType::List,
+            &classes(),
+            template,
+        );
+
+        assert!(prompt.contains(r#""type": "list""#));
+        assert!(prompt.contains("- LeaveType (휴가유형): days"));
+        assert!(prompt.contains(template.language_rule));
+
+        assert!(supports_structured_answer(&AnswerType::SingleFact));
+        assert!(!supports_structured_answer(&AnswerType::Explanation));
+    }
+}
@@ -0,0 +1,333 @@
+//! Related question suggestions
+//!
+//! Proposes follow-up questions after an answer has been generated, using
+//! the retrieved contexts (section names) and the graph neighborhood
+//! (entities and relations) returned by the graph search leg. Intent-based
+//! templates fill in when the retrieved context is too thin.
+//!
+//! Author: hephaex@gmail.com
+
+use crate::{graph_context, QueryAnalysis, QueryIntent};
+use otl_core::{Language, SearchResult, SearchResultType};
+use std::collections::HashSet;
+
+/// Minimum number of suggestions returned when a topic is available
+pub const MIN_SUGGESTIONS: usize = 3;
+
+/// Propose follow-up questions for an answered query
+///
+/// Candidates are gathered in order of specificity: graph relations, graph
+/// entities, document sections and finally intent templates. Duplicates and
+/// questions about entities already named in the question are skipped.
+pub fn suggest_related_questions(
+    analysis: &QueryAnalysis,
+    results: &[SearchResult],
+    max_suggestions: usize,
+) -> Vec<String> {
+    if max_suggestions == 0 {
+        return Vec::new();
+    }
+
+    let question = analysis.question.as_str();
+    let english = analysis.language == Language::English;
+    let mut candidates = Vec::new();
+
+    // 1. Graph neighborhood
+    for result in results
+        .iter()
+        .filter(|r| r.result_type == SearchResultType::Graph)
+    {
+        let relation =
+            graph_context::parse_fact(&result.content).or_else(|| parse_relation(&result.content));
+        let node_name = graph_context::parse_entity(&result.content)
+            .or_else(|| parse_node_name(&result.content));
+        if let Some((subject, object)) = relation {
+            if !(question.contains(subject) && question.contains(object)) {
+                candidates.push(if english {
+                    format!("How is {subject} related to {object}?")
+                } else {
+                    format!(
+                        "{subject}{} {object}의 관계는 무엇인가요?",
+                        josa_wa(subject)
+                    )
+                });
+            }
+        } else if let Some(name) = node_name {
+            if !question.contains(name) {
+                candidates.push(if english {
+                    format!("Tell me more about {name}.")
+                } else {
+                    format!("{name}에 대해 자세히 알려주세요.")
+                });
+            }
+        }
+    }
+
+    // 2. Sections of retrieved passages
+    for section in results.iter().filter_map(|r| r.source.section.as_deref()) {
+        let section = section.trim();
+        if !section.is_empty() && !question.contains(section) {
+            candidates.push(if english {
+                format!("What does {section} cover?")
+            } else {
+                format!("{section}에는 어떤 내용이 있나요?")
+            });
+        }
+    }
+
+    // 3. Intent templates on the main topic (English noun phrases end in their head)
+    let main_keyword = if english {
+        analysis.keywords.last()
+    } else {
+        analysis.keywords.first()
+    };
+    if let Some(topic) = main_keyword.map(|k| topic_from_keyword(k)) {
+        if !topic.is_empty() {
+            candidates.extend(intent_templates(&analysis.intent, analysis.language, topic));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|c| c != question && seen.insert(c.clone()))
+        .take(max_suggestions)
+        .collect()
+}
+
+/// Follow-up templates for a detected intent
+fn intent_templates(intent: &QueryIntent, language: Language, topic: &str) -> Vec<String> {
+    let templates: [&str; MIN_SUGGESTIONS] = match (language, intent) {
+        (Language::English, QueryIntent::Procedural) => [
+            "How long does {} take to process?",
+            "Which documents are required for {}?",
+            "Who approves {}?",
+        ],
+        (Language::English, QueryIntent::Factual) => [
+            "Are there exceptions for {}?",
+            "When did the {} rules last change?",
+            "What is the procedure for {}?",
+        ],
+        (Language::English, QueryIntent::Comparative) => [
+            "What should I consider when choosing {}?",
+            "Who is eligible for each {} option?",
+            "Can {} options be combined?",
+        ],
+        (Language::English, QueryIntent::Conditional) => [
+            "What happens if the {} conditions are not met?",
+            "Are there exceptions for {}?",
+            "What is the procedure for {}?",
+        ],
+        (Language::English, QueryIntent::Definitional) => [
+            "Who is eligible for {}?",
+            "What is the procedure for {}?",
+            "Which policy governs {}?",
+        ],
+        (Language::English, QueryIntent::General) => [
+            "What is the procedure for {}?",
+            "Which policy governs {}?",
+            "Are there exceptions for {}?",
+        ],
+        (Language::Korean, QueryIntent::Procedural) => [
+            "{} 처리에는 얼마나 걸리나요?",
+            "{} 신청 시 필요한 서류는 무엇인가요?",
+            "{} 승인권자는 누구인가요?",
+        ],
+        (Language::Korean, QueryIntent::Factual) => [
+            "{} 관련 예외 사항이 있나요?",
+            "{} 기준은 언제 변경되었나요?",
+            "{} 신청 절차는 어떻게 되나요?",
+        ],
+        (Language::Korean, QueryIntent::Comparative) => [
+            "{} 선택 시 고려해야 할 점은 무엇인가요?",
+            "{} 각각의 적용 대상은 누구인가요?",
+            "{} 중복 적용이 가능한가요?",
+        ],
+        (Language::Korean, QueryIntent::Conditional) => [
+            "{} 조건을 충족하지 못하면 어떻게 되나요?",
+            "{} 관련 예외 사항이 있나요?",
+            "{} 신청 절차는 어떻게 되나요?",
+        ],
+        (Language::Korean, QueryIntent::Definitional) => [
+            "{} 적용 대상은 누구인가요?",
+            "{} 신청 절차는 어떻게 되나요?",
+            "{} 관련 규정은 무엇인가요?",
+        ],
+        (Language::Korean, QueryIntent::General) => [
+            "{} 신청 절차는 어떻게 되나요?",
+            "{} 관련 규정은 무엇인가요?",
+            "{} 관련 예외 사항이 있나요?",
+        ],
+    };
+
+    templates
+        .iter()
+        .map(|t| t.replacen("{}", topic, 1))
+        .collect()
+}
+
+/// Strip trailing punctuation and common particles from a query keyword
+pub(crate) fn topic_from_keyword(keyword: &str) -> &str {
+    let word = keyword.trim_end_matches(|c: char| c.is_ascii_punctuation());
+    ["에서", "은", "는", "을", "를", "의"]
+        .iter()
+        .find_map(|p| word.strip_suffix(p).filter(|w| w.chars().count() >= 2))
+        .unwrap_or(word)
+}
+
+/// Parse `subject [predicate] object` graph relation content
+fn parse_relation(content: &str) -> Option<(&str, &str)> {
+    if content.starts_with('[') {
+        return None;
+    }
+    let open = content.find(" [")?;
+    let close = open + content[open..].find("] ")?;
+    let subject = content[..open].trim();
+    let object = content[close + 2..].trim();
+    (!subject.is_empty() && !object.is_empty()).then_some((subject, object))
+}
+
+/// Parse the entity name from `[Class] - name - key: value` node content
+fn parse_node_name(content: &str) -> Option<&str> {
+    let rest = content.strip_prefix('[')?;
+    let after_class = &rest[rest.find(']')? + 1..];
+    let name = after_class.strip_prefix(" - ")?.split(" - ").next()?.trim();
+    (!name.is_empty() && !name.contains(": ")).then_some(name)
+}
+
+/// Pick the comitative particle (와/과) for a Korean word
+fn josa_wa(word: &str) -> &'static str {
+    match word.chars().last() {
+        Some(c @ '가'..='힣') if (c as u32 - 0xAC00) % 28 != 0 => "과",
+        _ => "와",
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnswerType;
+    use otl_core::{DocumentAcl, SourceReference};
+    use uuid::Uuid;
+
+    fn analysis(question: &str, intent: QueryIntent, keywords: &[&str]) -> QueryAnalysis {
+        QueryAnalysis {
+            question: question.to_string(),
+            intent,
+            intent_confidence: 1.0,
+            intent_source: crate::IntentSource::Rules,
+            detected_entities: Vec::new(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            synonyms: Vec::new(),
+            expected_answer_type: AnswerType::Unknown,
+            language: Language::Korean,
+        }
+    }
+
+    fn result(content: &str, result_type: SearchResultType) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score: 0.5,
+            source: SourceReference::new(Uuid::new_v4()),
+            acl: DocumentAcl::default(),
+            result_type,
+        }
+    }
+
+    #[test]
+    fn test_parse_graph_content() {
+        assert_eq!(
+            parse_relation("연차휴가 [requiresApproval] 팀장"),
+            Some(("연차휴가", "팀장"))
+        );
+        assert_eq!(parse_relation("[LeaveType] - 병가"), None);
+        assert_eq!(
+            parse_node_name("[LeaveType] - 병가 - days: 60"),
+            Some("병가")
+        );
+        assert_eq!(parse_node_name("[LeaveType] - days: 60"), None);
+    }
+
+    #[test]
+    fn test_topic_from_keyword() {
+        assert_eq!(topic_from_keyword("연차휴가는"), "연차휴가");
+        assert_eq!(topic_from_keyword("병가를?"), "병가");
+        assert_eq!(topic_from_keyword("휴가"), "휴가");
+    }
+
+    #[test]
+    fn test_josa_selection() {
+        assert_eq!(josa_wa("팀장"), "과");
+        assert_eq!(josa_wa("휴가"), "와");
+        assert_eq!(josa_wa("HR"), "와");
+    }
+
+    #[test]
+    fn test_suggestions_from_graph_and_sections() {
+        let mut section_result = result("본문", SearchResultType::Vector);
+        section_result.source = section_result.source.with_section("제3장 휴가");
+
+        let results = vec![
+            result("연차휴가 [requiresApproval] 팀장", SearchResultType::Graph),
+            result("[LeaveType] - 병가", SearchResultType::Graph),
+            section_result,
+        ];
+        let analysis = analysis(
+            "연차휴가는 며칠인가요?",
+            QueryIntent::Factual,
+            &["연차휴가는", "며칠인가요?"],
+        );
+
+        let suggestions = suggest_related_questions(&analysis, &results, 5);
+        assert_eq!(suggestions.len(), 5);
+        assert_eq!(suggestions[0], "연차휴가와 팀장의 관계는 무엇인가요?");
+        assert_eq!(suggestions[1], "병가에 대해 자세히 알려주세요.");
+        assert_eq!(suggestions[2], "제3장 휴가에는 어떤 내용이 있나요?");
+        assert_eq!(suggestions[3], "연차휴가 관련 예외 사항이 있나요?");
+    }
+
+    #[test]
+    fn test_suggestions_fall_back_to_intent_templates() {
+        let analysis = analysis("출장 절차", QueryIntent::Procedural, &["출장", "절차"]);
+        let suggestions = suggest_related_questions(&analysis, &[], 5);
+
+        assert_eq!(suggestions.len(), MIN_SUGGESTIONS);
+        assert!(suggestions.iter().all(|s| s.starts_with("출장")));
+    }
+
+    #[test]
+    fn test_suggestions_follow_answer_language() {
+        let results = vec![result(
+            "annual leave [requiresApproval] manager",
+            SearchResultType::Graph,
+        )];
+        let mut analysis = analysis(
+            "How do I request leave?",
+            QueryIntent::Procedural,
+            &["request", "leave?"],
+        );
+        analysis.language = Language::English;
+
+        let suggestions = suggest_related_questions(&analysis, &results, 5);
+        assert_eq!(suggestions[0], "How is annual leave related to manager?");
+        assert_eq!(suggestions[1], "How long does leave take to process?");
+    }
+
+    #[test]
+    fn test_suggestions_skip_known_entities_and_respect_limit() {
+        let results = vec![
+            result("[LeaveType] - 병가", SearchResultType::Graph),
+            result("[LeaveType] - 병가", SearchResultType::Graph),
+        ];
+        let analysis = analysis("병가 신청", QueryIntent::General, &["병가"]);
+
+        let suggestions = suggest_related_questions(&analysis, &results, 2);
+        assert_eq!(suggestions.len(), 2);
+        assert!(!suggestions.iter().any(|s| s.contains("자세히")));
+        assert!(suggest_related_questions(&analysis, &results, 0).is_empty());
+    }
+}
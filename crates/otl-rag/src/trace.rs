@@ -0,0 +1,206 @@
+//! Retrieval tracing for debug queries
+//!
+//! When a query asks for it, the orchestrator records the candidates of every
+//! backend, what ACL filtering removed, the fused and reranked lists, the
+//! prompt and the time spent in each stage. Without debug the tracer is inert
+//! and nothing is cloned. Candidates the user may not read are traced by
+//! document, chunk and score only.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_core::{
+    QueryTrace, Result, SearchResult, SearchResultType, StageTiming, TraceCandidate, User,
+};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Run a future and measure how long it took
+pub(crate) async fn timed<F: Future>(future: F) -> (F::Output, Duration) {
+    let start = Instant::now();
+    let output = future.await;
+    (output, start.elapsed())
+}
+
+/// Collects a [`QueryTrace`] while a query runs
+pub(crate) struct Tracer {
+    trace: Option<QueryTrace>,
+    mark: Instant,
+}
+
+impl Tracer {
+    /// Create a tracer; a disabled tracer records nothing
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            trace: enabled.then(QueryTrace::default),
+            mark: Instant::now(),
+        }
+    }
+
+    /// Close the current stage, timing it from the previous mark
+    pub(crate) fn stage(&mut self, name: &str) {
+        let elapsed = self.mark.elapsed();
+        self.timing(name, elapsed);
+        self.mark = Instant::now();
+    }
+
+    /// Record stages that ran concurrently with their own durations, then
+    /// start the next stage
+    pub(crate) fn parallel_stages(&mut self, stages: &[(&str, Duration)]) {
+        for (name, elapsed) in stages {
+            self.timing(name, *elapsed);
+        }
+        self.mark = Instant::now();
+    }
+
+    fn timing(&mut self, name: &str, elapsed: Duration) {
+        if let Some(trace) = &mut self.trace {
+            trace.timings.push(StageTiming {
+                stage: name.to_string(),
+                duration_ms: elapsed.as_secs_f64() * 1000.0,
+            });
+        }
+    }
+
+    /// Record what a search backend returned, as seen by `user`
+    pub(crate) fn candidates(
+        &mut self,
+        backend: SearchResultType,
+        results: &Result<Vec<SearchResult>>,
+        user: &User,
+    ) {
+        let Some(trace) = &mut self.trace else {
+            return;
+        };
+        let list = match backend {
+            SearchResultType::Vector => &mut trace.vector_candidates,
+            SearchResultType::Graph => &mut trace.graph_candidates,
+            SearchResultType::Keyword => &mut trace.keyword_candidates,
+            SearchResultType::Faq => &mut trace.faq_candidates,
+        };
+        match results {
+            Ok(results) => *list = results.iter().map(|r| visible_to(r, user)).collect(),
+            Err(e) => trace.backend_errors.push(format!("{backend:?}: {e}")),
+        }
+    }
+
+    /// Edit the trace, if tracing is enabled
+    pub(crate) fn record(&mut self, f: impl FnOnce(&mut QueryTrace)) {
+        if let Some(trace) = &mut self.trace {
+            f(trace);
+        }
+    }
+
+    /// The recorded trace
+    pub(crate) fn finish(self) -> Option<QueryTrace> {
+        self.trace
+    }
+}
+
+/// Trace entries for a list of results, keeping their current scores
+pub(crate) fn candidates(results: &[SearchResult]) -> Vec<TraceCandidate> {
+    results.iter().map(TraceCandidate::from).collect()
+}
+
+/// Trace entries for results removed by ACL filtering, without content
+pub(crate) fn withheld(results: &[SearchResult]) -> Vec<TraceCandidate> {
+    results.iter().map(TraceCandidate::withheld).collect()
+}
+
+fn visible_to(result: &SearchResult, user: &User) -> TraceCandidate {
+    if result.acl.can_access(user) {
+        TraceCandidate::from(result)
+    } else {
+        TraceCandidate::withheld(result)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use otl_core::{DocumentAcl, OtlError, SourceReference};
+    use uuid::Uuid;
+
+    fn result(content: &str, result_type: SearchResultType) -> SearchResult {
+        SearchResult {
+            content: content.to_string(),
+            score: 0.5,
+            source: SourceReference::new(Uuid::new_v4()),
+            acl: DocumentAcl::default(),
+            result_type,
+        }
+    }
+
+    fn user() -> User {
+        User::internal("lee", vec!["DEVELOPER".to_string()])
+    }
+
+    #[test]
+    fn test_disabled_tracer_records_nothing() {
+        let mut tracer = Tracer::new(false);
+        tracer.stage("analysis");
+        tracer.candidates(
+            SearchResultType::Vector,
+            &Ok(vec![result("a", SearchResultType::Vector)]),
+            &User::anonymous(),
+        );
+        assert!(tracer.finish().is_none());
+    }
+
+    #[test]
+    fn test_tracer_records_candidates_errors_and_stages() {
+        let mut tracer = Tracer::new(true);
+        tracer.stage("analysis");
+        tracer.candidates(
+            SearchResultType::Graph,
+            &Ok(vec![result(&"가".repeat(500), SearchResultType::Graph)]),
+            &user(),
+        );
+        tracer.candidates(
+            SearchResultType::Keyword,
+            &Err(OtlError::SearchError("index offline".to_string())),
+            &user(),
+        );
+        tracer.parallel_stages(&[("vector_search", Duration::from_millis(12))]);
+
+        let trace = tracer.finish().unwrap();
+        assert_eq!(trace.graph_candidates.len(), 1);
+        assert_eq!(
+            trace.graph_candidates[0].preview.chars().count(),
+            TraceCandidate::PREVIEW_CHARS
+        );
+        assert_eq!(trace.backend_errors.len(), 1);
+        assert!(trace.backend_errors[0].contains("index offline"));
+        let stages: Vec<_> = trace.timings.iter().map(|t| t.stage.as_str()).collect();
+        assert_eq!(stages, vec!["analysis", "vector_search"]);
+        assert_eq!(trace.timings[1].duration_ms, 12.0);
+    }
+
+    #[test]
+    fn test_denied_candidates_carry_no_content() {
+        let mut restricted = result("임원 징계 기록", SearchResultType::Vector);
+        restricted.acl.access_level = otl_core::AccessLevel::Restricted;
+        restricted.source.chunk_index = Some(3);
+        let results = vec![
+            result("연차휴가는 15일입니다.", SearchResultType::Vector),
+            restricted,
+        ];
+
+        let mut tracer = Tracer::new(true);
+        tracer.candidates(SearchResultType::Vector, &Ok(results.clone()), &user());
+        tracer.record(|t| t.acl_filtered = withheld(&results[1..]));
+        let trace = tracer.finish().unwrap();
+
+        assert_eq!(trace.vector_candidates[0].preview, "연차휴가는 15일입니다.");
+        let denied = &trace.vector_candidates[1];
+        assert!(denied.preview.is_empty());
+        assert_eq!(denied.chunk_index, Some(3));
+        assert_eq!(denied.score, 0.5);
+        assert!(trace.acl_filtered[0].preview.is_empty());
+        let json = serde_json::to_value(&trace.acl_filtered[0]).unwrap();
+        assert!(json.get("preview").is_none());
+    }
+}
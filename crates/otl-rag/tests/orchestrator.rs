@@ -0,0 +1,362 @@
+//! Orchestrator tests against the in-memory fakes
+//!
+//! Runs the full hybrid RAG pipeline without Qdrant, SurrealDB or an LLM.
+//!
+//! Author: hephaex@gmail.com
+
+use std::sync::Arc;
+
+use otl_core::testing::{InMemoryMetadataStore, InMemorySearchBackend, MockLlmClient};
+use otl_core::{
+    AccessLevel, AnswerMode, DocumentAcl, DocumentChunk, DocumentMetadata, OtlError, Persona,
+    RagQuery, SearchResultType, SourceReference, User,
+};
+use otl_rag::{
+    AnswerPath, FeedbackConfig, FeedbackPriors, FeedbackVote, HybridRagOrchestrator,
+    ModerationConfig, RagConfig,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+const QUESTION: &str = "연차휴가 신청 절차가 어떻게 되나요?";
+
+fn vector_store(leave_doc: Uuid) -> InMemorySearchBackend {
+    InMemorySearchBackend::new("vector", SearchResultType::Vector)
+        .with_passage(
+            "연차휴가 신청 절차: 휴가 3일 전까지 부서장 승인을 받는다.",
+            SourceReference::new(leave_doc).with_section("제5조 연차휴가"),
+        )
+        .with_passage(
+            "출장 신청 절차: 출장 전 출장신청서를 제출한다.",
+            SourceReference::new(Uuid::new_v4()),
+        )
+}
+
+fn graph_store() -> InMemorySearchBackend {
+    InMemorySearchBackend::new("graph", SearchResultType::Graph)
+}
+
+fn orchestrator(vector: InMemorySearchBackend, llm: Arc<MockLlmClient>) -> HybridRagOrchestrator {
+    HybridRagOrchestrator::new(
+        Arc::new(vector),
+        Arc::new(graph_store()),
+        llm,
+        RagConfig::default(),
+    )
+}
+
+#[tokio::test]
+async fn test_generative_answer_cites_retrieved_passage() {
+    let leave_doc = Uuid::new_v4();
+    let llm = Arc::new(
+        MockLlmClient::new("관련 내용을 찾지 못했습니다.").with_response(
+            "부서장 승인",
+            "휴가 3일 전까지 부서장 승인을 받아야 합니다 [출처: 1]",
+        ),
+    );
+    let rag = orchestrator(vector_store(leave_doc), llm.clone());
+
+    let response = rag
+        .query(&RagQuery::new(QUESTION), &User::internal("u1", vec![]))
+        .await
+        .unwrap();
+
+    assert!(response.answer.contains("부서장 승인"));
+    assert_eq!(response.citations.len(), 1);
+    assert_eq!(response.citations[0].source.document_id, leave_doc);
+    assert!(llm.prompts().iter().any(|p| p.contains(QUESTION)));
+}
+
+#[tokio::test]
+async fn test_acl_hides_restricted_passages() {
+    let llm = Arc::new(MockLlmClient::new("관련 내용을 찾지 못했습니다."));
+    let vector = InMemorySearchBackend::new("vector", SearchResultType::Vector).with_passage_acl(
+        "연차휴가 신청 절차는 인사팀 내부 지침을 따른다.",
+        SourceReference::new(Uuid::new_v4()),
+        DocumentAcl {
+            access_level: AccessLevel::Restricted,
+            ..Default::default()
+        },
+    );
+    let rag = orchestrator(vector, llm.clone());
+
+    let response = rag
+        .query(&RagQuery::new(QUESTION), &User::internal("u1", vec![]))
+        .await
+        .unwrap();
+
+    assert!(response.citations.is_empty());
+    assert!(llm.prompts().iter().all(|p| !p.contains("내부 지침")));
+}
+
+#[tokio::test]
+async fn test_extractive_answer_skips_llm() {
+    let llm = Arc::new(MockLlmClient::new("사용되지 않는 응답"));
+    let rag = orchestrator(vector_store(Uuid::new_v4()), llm.clone());
+
+    let query = RagQuery::new(QUESTION).with_answer_mode(AnswerMode::Extractive);
+    let response = rag
+        .query(&query, &User::internal("u1", vec![]))
+        .await
+        .unwrap();
+
+    assert!(!response.passages.is_empty());
+    assert!(response.passages[0].text.contains("부서장 승인"));
+    assert_eq!(llm.call_count(), 0);
+}
+
+#[tokio::test]
+async fn test_failed_backend_is_skipped_unless_strict() {
+    let llm = Arc::new(MockLlmClient::new("관련 내용을 찾지 못했습니다."));
+    let vector = vector_store(Uuid::new_v4()).failing("connection refused");
+    let rag = orchestrator(vector, llm);
+    let user = User::internal("u1", vec![]);
+
+    let response = rag.query(&RagQuery::new(QUESTION), &user).await.unwrap();
+    assert!(!response.warnings.is_empty());
+
+    let strict = rag
+        .query(&RagQuery::new(QUESTION).with_strict(true), &user)
+        .await;
+    assert!(matches!(strict, Err(OtlError::SearchError(_))));
+}
+
+#[tokio::test]
+async fn test_seeded_run_replays_without_backends() {
+    let llm = Arc::new(
+        MockLlmClient::new("관련 내용을 찾지 못했습니다.").with_response(
+            "부서장 승인",
+            "휴가 3일 전까지 부서장 승인을 받아야 합니다 [출처: 1]",
+        ),
+    );
+    let rag = orchestrator(vector_store(Uuid::new_v4()), llm.clone());
+    let user = User::internal("u1", vec![]);
+
+    let recorded = rag
+        .query(&RagQuery::new(QUESTION).with_seed(42), &user)
+        .await
+        .unwrap();
+    let recording = recorded.trace.unwrap().recording.unwrap();
+    assert_eq!(recording.completions.len(), llm.call_count());
+    assert_eq!(recording.answer, recorded.answer);
+
+    // Every backend of the replaying orchestrator would fail or answer
+    // differently if it were called
+    let offline_llm = Arc::new(MockLlmClient::new("다른 답변"));
+    let offline = orchestrator(
+        vector_store(Uuid::new_v4()).failing("connection refused"),
+        offline_llm.clone(),
+    );
+    let replayed = offline.replay(&recording, &user).await.unwrap();
+
+    assert_eq!(replayed.answer, recorded.answer);
+    assert_eq!(replayed.citations.len(), recorded.citations.len());
+    assert!(replayed.warnings.is_empty(), "{:?}", replayed.warnings);
+    assert_eq!(offline_llm.call_count(), 0);
+    assert!(replayed.trace.unwrap().prompt.is_some());
+}
+
+#[tokio::test]
+async fn test_estimate_prices_prompt_without_llm_call() {
+    let llm = Arc::new(MockLlmClient::new("사용되지 않는 응답"));
+    let config = RagConfig {
+        cost: serde_json::from_str(
+            r#"{"prices": {"gpt-4o-mini": {"input": 0.15, "output": 0.6}}}"#,
+        )
+        .unwrap(),
+        ..Default::default()
+    };
+    let rag = HybridRagOrchestrator::new(
+        Arc::new(vector_store(Uuid::new_v4())),
+        Arc::new(graph_store()),
+        llm.clone(),
+        config,
+    )
+    .with_model_name("gpt-4o-mini");
+
+    let estimate = rag
+        .estimate(&RagQuery::new(QUESTION), &User::internal("u1", vec![]))
+        .await
+        .unwrap();
+
+    assert_eq!(estimate.path, AnswerPath::Generation);
+    assert_eq!(estimate.model.as_deref(), Some("gpt-4o-mini"));
+    assert_eq!(estimate.backends.len(), 4);
+    assert_eq!(estimate.contexts, 2);
+    assert!(estimate.prompt_tokens > 0);
+    assert!(estimate.cost_usd.unwrap() > 0.0);
+    assert_eq!(llm.call_count(), 0);
+}
+
+#[tokio::test]
+async fn test_persona_replaces_role_in_prompt() {
+    let llm = Arc::new(MockLlmClient::new("관련 내용을 찾지 못했습니다."));
+    let rag = orchestrator(vector_store(Uuid::new_v4()), llm.clone());
+    let persona = Persona {
+        departments: vec!["인사팀".to_string()],
+        role: Some("당신은 인사팀 규정 안내 담당자입니다.".to_string()),
+        source_signature: Some("답변 끝에 '— 인사팀 규정 안내'라고 서명하세요.".to_string()),
+        ..Persona::new("hr")
+    };
+
+    rag.query(
+        &RagQuery::new(QUESTION).with_persona(Some(persona)),
+        &User::internal("u1", vec![]),
+    )
+    .await
+    .unwrap();
+
+    let prompt = &llm.prompts()[0];
+    assert!(prompt.starts_with("<s>\n당신은 인사팀 규정 안내 담당자입니다.\n답변 끝에"));
+    assert!(!prompt.contains("조직의 지식 전문가"));
+}
+
+#[tokio::test]
+async fn test_restricted_values_are_masked_by_clearance() {
+    let vector = InMemorySearchBackend::new("vector", SearchResultType::Vector).with_passage(
+        "신입사원 연봉은 4,000만원이며 매년 3월에 조정한다.",
+        SourceReference::new(Uuid::new_v4()),
+    );
+    let llm = Arc::new(
+        MockLlmClient::new("신입사원 연봉은 4,000만원입니다 [출처: 1].").with_response(
+            "[권한 필요]",
+            "신입사원 연봉은 [권한 필요]입니다 [출처: 1].",
+        ),
+    );
+    let config = RagConfig {
+        moderation: ModerationConfig {
+            mask_context: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let rag = HybridRagOrchestrator::new(Arc::new(vector), Arc::new(graph_store()), llm, config);
+    let query = RagQuery::new("신입사원 연봉은 얼마인가요?");
+
+    let employee = User::internal("u1", vec!["EMPLOYEE".to_string()]);
+    let redacted = rag.query(&query, &employee).await.unwrap();
+    let mut hr = User::internal("u2", vec!["EMPLOYEE".to_string()]);
+    hr.departments = vec!["인사팀".to_string()];
+    let full = rag.query(&query, &hr).await.unwrap();
+
+    assert!(redacted.answer.contains("연봉은 [권한 필요]"));
+    assert!(!redacted.answer.contains("4,000"));
+    assert!(!redacted.citations[0].text.contains("4,000"));
+    assert_eq!(redacted.withheld.len(), 1);
+    assert_eq!(redacted.withheld[0].rule, "salary");
+    assert_eq!(redacted.withheld[0].values, 1);
+    assert!(redacted.withheld[0].access_request.contains("인사팀"));
+    assert!(redacted.answer.contains("열람 권한 요청"));
+
+    assert!(full.answer.contains("4,000만원"));
+    assert!(full.withheld.is_empty());
+}
+
+#[tokio::test]
+async fn test_helpful_feedback_lifts_document() {
+    let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+    let vector = InMemorySearchBackend::new("vector", SearchResultType::Vector)
+        .with_passage(
+            "연차휴가 신청 절차: 부서장 승인 후 인사시스템에 등록한다.",
+            SourceReference::new(first),
+        )
+        .with_passage(
+            "연차휴가 신청 절차 안내: 휴가 3일 전까지 신청서를 제출한다.",
+            SourceReference::new(second),
+        );
+    let llm = Arc::new(MockLlmClient::new("관련 내용을 찾지 못했습니다."));
+    let feedback = Arc::new(FeedbackPriors::new(FeedbackConfig::default()));
+    let rag = orchestrator(vector, llm.clone()).with_feedback(feedback.clone());
+    let user = User::internal("u1", vec![]);
+    let leading = |prompt: &str| {
+        let first_at = prompt.find("인사시스템").unwrap();
+        let second_at = prompt.find("신청서를 제출").unwrap();
+        if first_at < second_at {
+            first
+        } else {
+            second
+        }
+    };
+
+    rag.query(&RagQuery::new(QUESTION), &user).await.unwrap();
+    let before = leading(&llm.prompts()[0]);
+    let other = if before == first { second } else { first };
+    let answered = llm.call_count();
+
+    for _ in 0..10 {
+        feedback.record(&FeedbackVote {
+            document_id: other,
+            chunk_index: None,
+            helpful: true,
+            at: chrono::Utc::now(),
+        });
+    }
+    rag.query(&RagQuery::new(QUESTION), &user).await.unwrap();
+    assert_eq!(leading(&llm.prompts()[answered]), other);
+}
+
+#[tokio::test]
+async fn test_superseded_versions_are_excluded_unless_requested() {
+    let mut old = DocumentMetadata::new("취업규칙", "/docs/취업규칙_v1.pdf", "pdf");
+    old.extra.insert("version".to_string(), json!(1));
+    let mut new = DocumentMetadata::new("취업규칙", "/docs/취업규칙_v2.pdf", "pdf");
+    new.extra.insert("version".to_string(), json!(2));
+    let vector = InMemorySearchBackend::new("vector", SearchResultType::Vector)
+        .with_passage(
+            "연차휴가 신청 절차: 휴가 3일 전까지 서면으로 신청한다.",
+            SourceReference::new(old.id),
+        )
+        .with_passage(
+            "연차휴가 신청 절차: 휴가 전날까지 인사시스템으로 신청한다.",
+            SourceReference::new(new.id),
+        );
+    let llm = Arc::new(MockLlmClient::new("관련 내용을 찾지 못했습니다."));
+    let metadata = InMemoryMetadataStore::new()
+        .with_document(old.clone())
+        .with_document(new.clone());
+    let rag = orchestrator(vector, llm.clone()).with_metadata_store(Arc::new(metadata));
+    let user = User::internal("u1", vec![]);
+
+    rag.query(&RagQuery::new(QUESTION), &user).await.unwrap();
+    let prompt = &llm.prompts()[0];
+    assert!(prompt.contains("인사시스템으로 신청"));
+    assert!(!prompt.contains("서면으로 신청"));
+
+    let answered = llm.call_count();
+    rag.query(
+        &RagQuery::new(QUESTION).with_include_superseded(true),
+        &user,
+    )
+    .await
+    .unwrap();
+    assert!(llm.prompts()[answered].contains("서면으로 신청"));
+}
+
+#[tokio::test]
+async fn test_procedural_context_is_stitched_with_neighbor_chunks() {
+    let doc = Uuid::new_v4();
+    let vector = InMemorySearchBackend::new("vector", SearchResultType::Vector).with_passage(
+        "연차휴가 신청 절차 2단계: 부서장 승인을 받는다.",
+        SourceReference::new(doc).with_chunk_index(1),
+    );
+    let metadata = InMemoryMetadataStore::new()
+        .with_chunk(DocumentChunk::new(doc, 0, "1단계: 휴가 신청서를 작성한다."))
+        .with_chunk(DocumentChunk::new(doc, 2, "3단계: 인사시스템에 등록한다."))
+        .with_chunk(DocumentChunk::new(
+            doc,
+            3,
+            "부칙: 이 규정은 2024년부터 시행한다.",
+        ));
+    let llm = Arc::new(MockLlmClient::new("관련 내용을 찾지 못했습니다."));
+    let rag = orchestrator(vector, llm.clone()).with_metadata_store(Arc::new(metadata));
+
+    rag.query(&RagQuery::new(QUESTION), &User::internal("u1", vec![]))
+        .await
+        .unwrap();
+    let prompt = &llm.prompts()[0];
+    let first = prompt.find("1단계").unwrap();
+    let second = prompt.find("2단계").unwrap();
+    let third = prompt.find("3단계").unwrap();
+    assert!(first < second && second < third);
+    assert!(!prompt.contains("부칙"));
+}
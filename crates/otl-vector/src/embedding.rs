@@ -31,9 +31,13 @@ pub trait EmbeddingClient: Send + Sync {
 // ============================================================================
 
 /// OpenAI embedding API client
+///
+/// Also serves the embedding endpoint of vLLM and other OpenAI-compatible
+/// servers, see [`OpenAiEmbedding::vllm_from_config`].
 pub struct OpenAiEmbedding {
     client: Client,
     api_key: String,
+    base_url: String,
     model: String,
     dimension: usize,
 }
@@ -56,19 +60,35 @@ struct EmbeddingData {
 }
 
 impl OpenAiEmbedding {
-    /// Create a new OpenAI embedding client
-    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
-        let model = model.into();
-        let dimension = match model.as_str() {
+    /// Vector dimension produced by a model
+    pub fn model_dimension(model: &str) -> usize {
+        match model {
             "text-embedding-3-small" => 1536,
             "text-embedding-3-large" => 3072,
             "text-embedding-ada-002" => 1536,
             _ => 1536, // Default
-        };
+        }
+    }
+
+    /// Vector dimension produced by a model served by vLLM
+    pub fn vllm_model_dimension(model: &str) -> usize {
+        match model {
+            "BAAI/bge-m3" | "intfloat/multilingual-e5-large" => 1024,
+            "nomic-ai/nomic-embed-text-v1.5" => 768,
+            "sentence-transformers/all-MiniLM-L6-v2" => 384,
+            _ => Self::model_dimension(model),
+        }
+    }
+
+    /// Create a new OpenAI embedding client
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        let model = model.into();
+        let dimension = Self::model_dimension(&model);
 
         Self {
             client: Client::new(),
             api_key: api_key.into(),
+            base_url: "https://api.openai.com/v1".to_string(),
             model,
             dimension,
         }
@@ -83,6 +103,19 @@ impl OpenAiEmbedding {
 
         Ok(Self::new(api_key.clone(), config.embedding_model.clone()))
     }
+
+    /// Create a client of the vLLM server at `config.vllm_url`
+    ///
+    /// The API key is optional; without one no `Authorization` header is sent.
+    pub fn vllm_from_config(config: &LlmConfig) -> Self {
+        let mut client = Self::new(
+            config.vllm_api_key.clone().unwrap_or_default(),
+            config.embedding_model.clone(),
+        );
+        client.base_url = config.vllm_url.trim_end_matches('/').to_string();
+        client.dimension = Self::vllm_model_dimension(&client.model);
+        client
+    }
 }
 
 #[async_trait]
@@ -105,12 +138,16 @@ impl EmbeddingClient for OpenAiEmbedding {
             model: self.model.clone(),
         };
 
-        let response = self
+        let mut http_request = self
             .client
-            .post("https://api.openai.com/v1/embeddings")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+            .post(format!("{}/embeddings", self.base_url))
             .header("Content-Type", "application/json")
-            .json(&request)
+            .json(&request);
+        if !self.api_key.is_empty() {
+            http_request = http_request.header("Authorization", format!("Bearer {}", self.api_key));
+        }
+
+        let response = http_request
             .send()
             .await
             .map_err(|e| OtlError::LlmError(format!("Embedding request failed: {e}")))?;
@@ -163,15 +200,20 @@ struct OllamaEmbeddingResponse {
 }
 
 impl OllamaEmbedding {
-    /// Create a new Ollama embedding client
-    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
-        let model = model.into();
-        let dimension = match model.as_str() {
+    /// Vector dimension produced by a model
+    pub fn model_dimension(model: &str) -> usize {
+        match model {
             "nomic-embed-text" => 768,
             "mxbai-embed-large" => 1024,
             "all-minilm" => 384,
             _ => 768, // Default for most models
-        };
+        }
+    }
+
+    /// Create a new Ollama embedding client
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        let model = model.into();
+        let dimension = Self::model_dimension(&model);
 
         Self {
             client: Client::new(),
@@ -236,6 +278,20 @@ impl EmbeddingClient for OllamaEmbedding {
 // Factory function
 // ============================================================================
 
+/// Vector dimension of the configured embedding model
+///
+/// Same value as `create_embedding_client(config)?.dimension()`, without
+/// needing API credentials.
+pub fn embedding_dimension(config: &LlmConfig) -> usize {
+    match config.provider {
+        LlmProvider::OpenAI | LlmProvider::Azure => {
+            OpenAiEmbedding::model_dimension(&config.embedding_model)
+        }
+        LlmProvider::Ollama => OllamaEmbedding::model_dimension(&config.embedding_model),
+        LlmProvider::Vllm => OpenAiEmbedding::vllm_model_dimension(&config.embedding_model),
+    }
+}
+
 /// Create an embedding client from config
 pub fn create_embedding_client(config: &LlmConfig) -> Result<Box<dyn EmbeddingClient>> {
     match config.provider {
@@ -243,6 +299,7 @@ pub fn create_embedding_client(config: &LlmConfig) -> Result<Box<dyn EmbeddingCl
             Ok(Box::new(OpenAiEmbedding::from_config(config)?))
         }
         LlmProvider::Ollama => Ok(Box::new(OllamaEmbedding::from_config(config))),
+        LlmProvider::Vllm => Ok(Box::new(OpenAiEmbedding::vllm_from_config(config))),
     }
 }
 
@@ -271,4 +328,22 @@ mod tests {
         let client = OllamaEmbedding::new("http://localhost:11434", "mxbai-embed-large");
         assert_eq!(client.dimension(), 1024);
     }
+
+    #[test]
+    fn test_embedding_dimension_from_config() {
+        let mut config = LlmConfig {
+            embedding_model: "text-embedding-3-large".to_string(),
+            ..Default::default()
+        };
+        config.provider = LlmProvider::OpenAI;
+        assert_eq!(embedding_dimension(&config), 3072);
+
+        config.provider = LlmProvider::Ollama;
+        config.embedding_model = "all-minilm".to_string();
+        assert_eq!(embedding_dimension(&config), 384);
+
+        config.provider = LlmProvider::Vllm;
+        config.embedding_model = "BAAI/bge-m3".to_string();
+        assert_eq!(embedding_dimension(&config), 1024);
+    }
 }
@@ -11,9 +11,19 @@ use uuid::Uuid;
 
 pub mod embedding;
 pub mod qdrant_store;
+pub mod quantization;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+pub mod tokens;
 
-pub use embedding::{create_embedding_client, EmbeddingClient, OllamaEmbedding, OpenAiEmbedding};
-pub use qdrant_store::{QdrantStore, VectorSearchBackend};
+pub use embedding::{
+    create_embedding_client, embedding_dimension, EmbeddingClient, OllamaEmbedding, OpenAiEmbedding,
+};
+pub use qdrant_store::{
+    ChunkPoint, ChunkVector, NeighborChunk, QdrantStore, ReembedPage, VectorIndex,
+    VectorSearchBackend,
+};
+pub use tokens::{TokenCounter, TokenizerFamily};
 
 /// A vector with metadata
 #[derive(Debug, Clone)]
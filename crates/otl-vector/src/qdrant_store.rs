@@ -8,18 +8,23 @@
 use async_trait::async_trait;
 use otl_core::{
     AccessLevel, DatabaseConfig, DocumentAcl, OtlError, Result, SearchBackend, SearchResult,
-    SearchResultType, SourceReference,
+    SearchResultType, SourceReference, VectorQuantization,
 };
 use qdrant_client::qdrant::{
-    Condition, CreateCollectionBuilder, DeletePointsBuilder, Distance, Filter, PointStruct,
-    SearchPointsBuilder, UpsertPointsBuilder, VectorParamsBuilder,
+    point_id::PointIdOptions, quantization_config, quantization_config_diff, vector_output::Vector,
+    CompressionRatio, Condition, CountPointsBuilder, CreateCollectionBuilder, DeletePointsBuilder,
+    Distance, Filter, PointId, PointStruct, ProductQuantizationBuilder,
+    QuantizationSearchParamsBuilder, RecommendPointsBuilder, ScalarQuantizationBuilder,
+    ScoredPoint, ScrollPointsBuilder, SearchParamsBuilder, SearchPointsBuilder,
+    SetPayloadPointsBuilder, UpdateCollectionBuilder, UpsertPointsBuilder, VectorParamsBuilder,
 };
-use qdrant_client::Qdrant;
+use qdrant_client::{Payload, Qdrant};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
 use crate::embedding::EmbeddingClient;
+use crate::quantization;
 use crate::VectorStore;
 
 /// Qdrant vector store implementation
@@ -27,14 +32,27 @@ pub struct QdrantStore {
     client: Qdrant,
     collection: String,
     dimension: usize,
+    truncate_dim: Option<usize>,
+    quantization: VectorQuantization,
 }
 
-/// Vector search backend that wraps QdrantStore with an embedding client
-pub struct VectorSearchBackend {
+/// A collection together with the embedding model its vectors come from
+pub struct VectorIndex {
     store: QdrantStore,
     embedding_client: Arc<dyn EmbeddingClient>,
 }
 
+/// Vector search backend over the active [`VectorIndex`]
+///
+/// The active index can be replaced at runtime (see
+/// [`switch_to`](Self::switch_to)); searches and writes in flight keep
+/// the index they started with. While an embedding migration runs, a
+/// shadow index receives a copy of every write so it stays complete.
+pub struct VectorSearchBackend {
+    active: RwLock<Arc<VectorIndex>>,
+    shadow: RwLock<Option<Arc<VectorIndex>>>,
+}
+
 impl QdrantStore {
     /// Create a new Qdrant connection
     pub async fn new(config: &DatabaseConfig) -> Result<Self> {
@@ -46,9 +64,79 @@ impl QdrantStore {
             client,
             collection: config.qdrant_collection.clone(),
             dimension: config.vector_dimension,
+            truncate_dim: config.vector_truncate_dim,
+            quantization: config.vector_quantization,
         })
     }
 
+    /// Store on another collection of the same Qdrant server
+    pub fn with_collection(&self, collection: impl Into<String>) -> Self {
+        Self {
+            client: self.client.clone(),
+            collection: collection.into(),
+            dimension: self.dimension,
+            truncate_dim: self.truncate_dim,
+            quantization: self.quantization,
+        }
+    }
+
+    /// Collection name
+    pub fn collection(&self) -> &str {
+        &self.collection
+    }
+
+    /// Dimension of stored vectors, after truncation
+    pub fn stored_dimension(&self) -> usize {
+        self.truncate_dim
+            .map_or(self.dimension, |dim| dim.min(self.dimension))
+    }
+
+    /// Embedding as stored: truncated to the stored dimension if needed
+    fn prepare(&self, vector: &[f32]) -> Vec<f32> {
+        match self.truncate_dim {
+            Some(dim) if vector.len() > dim => quantization::truncate(vector, dim),
+            _ => vector.to_vec(),
+        }
+    }
+
+    /// Quantization config for creating or updating the collection
+    fn quantization_config(&self) -> Option<quantization_config::Quantization> {
+        match self.quantization {
+            VectorQuantization::None => None,
+            VectorQuantization::Scalar => Some(
+                ScalarQuantizationBuilder::default()
+                    .always_ram(true)
+                    .build()
+                    .into(),
+            ),
+            VectorQuantization::Product { compression } => {
+                let ratio = match compression {
+                    4 => CompressionRatio::X4,
+                    8 => CompressionRatio::X8,
+                    32 => CompressionRatio::X32,
+                    64 => CompressionRatio::X64,
+                    _ => CompressionRatio::X16,
+                };
+                Some(
+                    ProductQuantizationBuilder::new(ratio.into())
+                        .always_ram(true)
+                        .build()
+                        .into(),
+                )
+            }
+        }
+    }
+
+    /// Number of points in the collection
+    pub async fn count(&self) -> Result<u64> {
+        let response = self
+            .client
+            .count(CountPointsBuilder::new(&self.collection).exact(true))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to count vectors: {e}")))?;
+        Ok(response.result.map(|r| r.count).unwrap_or(0))
+    }
+
     /// Initialize collection (run once on setup)
     pub async fn init_collection(&self) -> Result<()> {
         // Check if collection exists
@@ -63,16 +151,33 @@ impl QdrantStore {
             .iter()
             .any(|c| c.name == self.collection);
 
+        let quantization = self.quantization_config();
         if !exists {
+            let mut request = CreateCollectionBuilder::new(&self.collection).vectors_config(
+                VectorParamsBuilder::new(self.stored_dimension() as u64, Distance::Cosine),
+            );
+            if let Some(quantization) = quantization {
+                request = request.quantization_config(quantization);
+            }
+            self.client.create_collection(request).await.map_err(|e| {
+                OtlError::DatabaseError(format!("Failed to create collection: {e}"))
+            })?;
+        } else if let Some(quantization) = quantization {
+            // Quantization can be added to an existing collection; a new
+            // dimension needs an embedding migration instead
+            let quantization: quantization_config_diff::Quantization = match quantization {
+                quantization_config::Quantization::Scalar(q) => q.into(),
+                quantization_config::Quantization::Product(q) => q.into(),
+                quantization_config::Quantization::Binary(q) => q.into(),
+            };
             self.client
-                .create_collection(
-                    CreateCollectionBuilder::new(&self.collection).vectors_config(
-                        VectorParamsBuilder::new(self.dimension as u64, Distance::Cosine),
-                    ),
+                .update_collection(
+                    UpdateCollectionBuilder::new(&self.collection)
+                        .quantization_config(quantization),
                 )
                 .await
                 .map_err(|e| {
-                    OtlError::DatabaseError(format!("Failed to create collection: {e}"))
+                    OtlError::DatabaseError(format!("Failed to update quantization: {e}"))
                 })?;
         }
 
@@ -91,20 +196,239 @@ struct VectorPayload {
     access_level: String,
     department: Option<String>,
     required_roles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    document_version: Option<f64>,
+    #[serde(default)]
+    superseded: bool,
 }
 
-#[async_trait]
-impl super::VectorStore for QdrantStore {
-    async fn store(&self, embedding: &super::EmbeddingVector) -> Result<()> {
+/// Convert a scored Qdrant point into a search result
+fn point_to_result(point: ScoredPoint) -> SearchResult {
+    let payload = point.payload;
+    let content = payload
+        .get("content")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_default();
+
+    let document_id = payload
+        .get("document_id")
+        .and_then(|v| v.as_str())
+        .and_then(|s| Uuid::parse_str(s).ok())
+        .unwrap_or_default();
+
+    let access_level = payload
+        .get("access_level")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "internal".to_string());
+
+    let mut source = SourceReference::new(document_id);
+    source.chunk_index = payload
+        .get("chunk_index")
+        .and_then(|v| v.as_integer())
+        .and_then(|i| u32::try_from(i).ok());
+
+    SearchResult {
+        content,
+        score: point.score,
+        source,
+        acl: DocumentAcl {
+            access_level: match access_level.as_str() {
+                "public" => AccessLevel::Public,
+                "confidential" => AccessLevel::Confidential,
+                "restricted" => AccessLevel::Restricted,
+                _ => AccessLevel::Internal,
+            },
+            ..Default::default()
+        },
+        result_type: SearchResultType::Vector,
+    }
+}
+
+/// A stored chunk close to another chunk in embedding space
+#[derive(Debug, Clone)]
+pub struct NeighborChunk {
+    /// Qdrant point ID of the neighbor
+    pub vector_id: String,
+
+    /// Chunk index within its document (from the payload)
+    pub chunk_index: Option<u32>,
+
+    /// Neighbor content, score, source and ACL
+    pub result: SearchResult,
+}
+
+impl QdrantStore {
+    /// Find the chunks nearest to a stored point, excluding the point itself
+    pub async fn neighbors(&self, vector_id: &str, limit: usize) -> Result<Vec<NeighborChunk>> {
+        let response = self
+            .client
+            .recommend(
+                RecommendPointsBuilder::new(&self.collection, limit as u64)
+                    .add_positive(vector_id.to_string())
+                    .with_payload(true),
+            )
+            .await
+            .map_err(|e| OtlError::SearchError(format!("Neighbor search failed: {e}")))?;
+
+        Ok(response
+            .result
+            .into_iter()
+            .map(|point| {
+                let vector_id = point_id_string(point.id.as_ref());
+                let chunk_index = point
+                    .payload
+                    .get("chunk_index")
+                    .and_then(|v| v.as_integer())
+                    .and_then(|i| u32::try_from(i).ok());
+                NeighborChunk {
+                    vector_id,
+                    chunk_index,
+                    result: point_to_result(point),
+                }
+            })
+            .collect())
+    }
+}
+
+/// A stored chunk vector
+#[derive(Debug, Clone)]
+pub struct ChunkVector {
+    /// Vector store point ID
+    pub vector_id: String,
+
+    /// Chunk index within its document (from the payload)
+    pub chunk_index: Option<u32>,
+
+    /// Embedding
+    pub vector: Vec<f32>,
+}
+
+impl QdrantStore {
+    /// All vectors stored for a document, in no particular order
+    pub async fn document_vectors(&self, document_id: Uuid) -> Result<Vec<ChunkVector>> {
+        const PAGE_SIZE: u32 = 256;
+
+        let filter = Filter::must([Condition::matches("document_id", document_id.to_string())]);
+        let mut vectors = Vec::new();
+        let mut offset: Option<PointId> = None;
+
+        loop {
+            let mut request = ScrollPointsBuilder::new(&self.collection)
+                .filter(filter.clone())
+                .with_payload(true)
+                .with_vectors(true)
+                .limit(PAGE_SIZE);
+            if let Some(offset) = offset.take() {
+                request = request.offset(offset);
+            }
+
+            let response = self
+                .client
+                .scroll(request)
+                .await
+                .map_err(|e| OtlError::DatabaseError(format!("Failed to read vectors: {e}")))?;
+
+            for point in response.result {
+                let Some(Vector::Dense(dense)) =
+                    point.vectors.as_ref().and_then(|v| v.get_vector())
+                else {
+                    continue;
+                };
+                vectors.push(ChunkVector {
+                    vector_id: point_id_string(point.id.as_ref()),
+                    chunk_index: point
+                        .payload
+                        .get("chunk_index")
+                        .and_then(|v| v.as_integer())
+                        .and_then(|i| u32::try_from(i).ok()),
+                    vector: dense.data,
+                });
+            }
+
+            match response.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(vectors)
+    }
+}
+
+impl QdrantStore {
+    /// Up to `limit` stored vectors, in storage order
+    pub async fn sample_vectors(&self, limit: usize) -> Result<Vec<Vec<f32>>> {
+        const PAGE_SIZE: usize = 256;
+
+        let mut vectors = Vec::with_capacity(limit);
+        let mut offset: Option<PointId> = None;
+        while vectors.len() < limit {
+            let mut request = ScrollPointsBuilder::new(&self.collection)
+                .with_payload(false)
+                .with_vectors(true)
+                .limit(PAGE_SIZE.min(limit - vectors.len()) as u32);
+            if let Some(offset) = offset.take() {
+                request = request.offset(offset);
+            }
+            let response = self
+                .client
+                .scroll(request)
+                .await
+                .map_err(|e| OtlError::DatabaseError(format!("Failed to read vectors: {e}")))?;
+
+            vectors.extend(response.result.into_iter().filter_map(|point| {
+                match point.vectors.as_ref().and_then(|v| v.get_vector()) {
+                    Some(Vector::Dense(dense)) => Some(dense.data),
+                    _ => None,
+                }
+            }));
+            match response.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+        Ok(vectors)
+    }
+}
+
+/// Point ID as a string (UUID or number)
+fn point_id_string(id: Option<&PointId>) -> String {
+    match id.and_then(|id| id.point_id_options.as_ref()) {
+        Some(PointIdOptions::Uuid(uuid)) => uuid.clone(),
+        Some(PointIdOptions::Num(num)) => num.to_string(),
+        None => String::new(),
+    }
+}
+
+/// A chunk vector with its location, access metadata and document version
+#[derive(Debug, Clone)]
+pub struct ChunkPoint {
+    pub embedding: super::EmbeddingVector,
+    pub page: Option<u32>,
+    pub section: Option<String>,
+    pub acl: DocumentAcl,
+    /// Version of the document (see [`otl_core::freshness::version`])
+    pub version: Option<f64>,
+    /// Whether a newer version of the document replaces it
+    pub superseded: bool,
+}
+
+impl ChunkPoint {
+    fn to_point(&self, vector: Vec<f32>) -> PointStruct {
+        let embedding = &self.embedding;
         let payload = VectorPayload {
             document_id: embedding.document_id.to_string(),
             chunk_index: embedding.chunk_index,
             content: embedding.content.clone(),
-            page: None,
-            section: None,
-            access_level: "internal".to_string(),
-            department: None,
-            required_roles: vec![],
+            page: self.page,
+            section: self.section.clone(),
+            access_level: self.acl.access_level.to_string(),
+            department: self.acl.department.clone(),
+            required_roles: self.acl.required_roles.clone(),
+            document_version: self.version,
+            superseded: self.superseded,
         };
 
         let payload_map: std::collections::HashMap<String, qdrant_client::qdrant::Value> =
@@ -117,14 +441,72 @@ impl super::VectorStore for QdrantStore {
                 .map(|(k, v)| (k, v.into()))
                 .collect();
 
-        let point = PointStruct::new(
-            embedding.id.to_string(),
-            embedding.vector.clone(),
-            payload_map,
-        );
+        PointStruct::new(embedding.id.to_string(), vector, payload_map)
+    }
+}
+
+impl QdrantStore {
+    /// Upsert chunk vectors in one request
+    pub async fn store_points(&self, points: &[ChunkPoint]) -> Result<()> {
+        if points.is_empty() {
+            return Ok(());
+        }
+        let points: Vec<PointStruct> = points
+            .iter()
+            .map(|p| p.to_point(self.prepare(&p.embedding.vector)))
+            .collect();
 
         self.client
-            .upsert_points(UpsertPointsBuilder::new(&self.collection, vec![point]))
+            .upsert_points(UpsertPointsBuilder::new(&self.collection, points))
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to upsert vectors: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Tag every vector of a document with its version and whether it is
+    /// superseded
+    pub async fn tag_version(
+        &self,
+        document_id: Uuid,
+        version: Option<f64>,
+        superseded: bool,
+    ) -> Result<()> {
+        let payload = Payload::try_from(serde_json::json!({
+            "document_version": version,
+            "superseded": superseded,
+        }))
+        .map_err(|e| OtlError::DatabaseError(format!("Invalid version payload: {e}")))?;
+        let filter = Filter::must([Condition::matches("document_id", document_id.to_string())]);
+
+        self.client
+            .set_payload(
+                SetPayloadPointsBuilder::new(&self.collection, payload).points_selector(filter),
+            )
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to tag vectors: {e}")))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl super::VectorStore for QdrantStore {
+    async fn store(&self, embedding: &super::EmbeddingVector) -> Result<()> {
+        let point = ChunkPoint {
+            embedding: embedding.clone(),
+            page: None,
+            section: None,
+            acl: DocumentAcl::default(),
+            version: None,
+            superseded: false,
+        };
+
+        self.client
+            .upsert_points(UpsertPointsBuilder::new(
+                &self.collection,
+                vec![point.to_point(self.prepare(&embedding.vector))],
+            ))
             .await
             .map_err(|e| OtlError::DatabaseError(format!("Failed to upsert vector: {e}")))?;
 
@@ -132,57 +514,25 @@ impl super::VectorStore for QdrantStore {
     }
 
     async fn search(&self, query_vector: &[f32], limit: usize) -> Result<Vec<SearchResult>> {
+        let mut request =
+            SearchPointsBuilder::new(&self.collection, self.prepare(query_vector), limit as u64)
+                .with_payload(true);
+        if self.quantization != VectorQuantization::None {
+            request = request.params(
+                SearchParamsBuilder::default().quantization(
+                    QuantizationSearchParamsBuilder::default()
+                        .rescore(true)
+                        .oversampling(quantization::QUANTIZATION_OVERSAMPLING),
+                ),
+            );
+        }
         let results = self
             .client
-            .search_points(
-                SearchPointsBuilder::new(&self.collection, query_vector.to_vec(), limit as u64)
-                    .with_payload(true),
-            )
+            .search_points(request)
             .await
             .map_err(|e| OtlError::SearchError(format!("Vector search failed: {e}")))?;
 
-        let search_results: Vec<SearchResult> = results
-            .result
-            .into_iter()
-            .map(|point| {
-                let payload = point.payload;
-                let content = payload
-                    .get("content")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .unwrap_or_default();
-
-                let document_id = payload
-                    .get("document_id")
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| Uuid::parse_str(s).ok())
-                    .unwrap_or_default();
-
-                let access_level = payload
-                    .get("access_level")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .unwrap_or_else(|| "internal".to_string());
-
-                SearchResult {
-                    content,
-                    score: point.score,
-                    source: SourceReference::new(document_id),
-                    acl: DocumentAcl {
-                        access_level: match access_level.as_str() {
-                            "public" => AccessLevel::Public,
-                            "confidential" => AccessLevel::Confidential,
-                            "restricted" => AccessLevel::Restricted,
-                            _ => AccessLevel::Internal,
-                        },
-                        ..Default::default()
-                    },
-                    result_type: SearchResultType::Vector,
-                }
-            })
-            .collect();
-
-        Ok(search_results)
+        Ok(results.result.into_iter().map(point_to_result).collect())
     }
 
     async fn delete_by_document(&self, document_id: Uuid) -> Result<u64> {
@@ -199,6 +549,178 @@ impl super::VectorStore for QdrantStore {
     }
 }
 
+// ============================================================================
+// VectorIndex Implementation
+// ============================================================================
+
+/// Points read from a collection by one re-embedding step
+const REEMBED_PAGE_SIZE: u32 = 128;
+
+/// Outcome of one [`VectorIndex::reembed_from`] step
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReembedPage {
+    /// Points re-embedded and stored
+    pub embedded: u64,
+    /// Points without content or whose embedding failed
+    pub failed: u64,
+    /// Offset of the next step (`None` when the source is exhausted)
+    pub next_offset: Option<String>,
+}
+
+/// Point ID from its string form
+fn parse_point_id(id: &str) -> PointId {
+    match id.parse::<u64>() {
+        Ok(num) => PointId::from(num),
+        Err(_) => PointId::from(id.to_string()),
+    }
+}
+
+impl VectorIndex {
+    /// Index over `store` whose vectors come from `embedding_client`
+    pub fn new(mut store: QdrantStore, embedding_client: Arc<dyn EmbeddingClient>) -> Self {
+        store.dimension = embedding_client.dimension();
+        Self {
+            store,
+            embedding_client,
+        }
+    }
+
+    /// Qdrant collection of the index
+    pub fn collection(&self) -> &str {
+        self.store.collection()
+    }
+
+    /// Vector store of the index
+    pub fn store(&self) -> &QdrantStore {
+        &self.store
+    }
+
+    /// Embedding client producing the index's vectors
+    pub fn embedding_client(&self) -> &Arc<dyn EmbeddingClient> {
+        &self.embedding_client
+    }
+
+    /// Create the collection if it does not exist
+    pub async fn init(&self) -> Result<()> {
+        self.store.init_collection().await
+    }
+
+    /// Embed a query and search the index
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let query_vector = self
+            .embedding_client
+            .embed(query)
+            .await
+            .map_err(|e| OtlError::SearchError(format!("Failed to embed query: {e}")))?;
+        self.store.search(&query_vector, limit).await
+    }
+
+    /// Embed `content` and store it under `id`
+    async fn index_point(
+        &self,
+        id: Uuid,
+        document_id: Uuid,
+        chunk_index: u32,
+        content: &str,
+    ) -> Result<()> {
+        let vector = self.embedding_client.embed(content).await?;
+        self.store
+            .store(&super::EmbeddingVector {
+                id,
+                vector,
+                document_id,
+                chunk_index,
+                content: content.to_string(),
+            })
+            .await
+    }
+
+    /// Copy one page of `source` into this index, re-embedding each point's
+    /// content with this index's model
+    ///
+    /// Point IDs and payloads are kept, so chunk `vector_id`s stay valid.
+    /// Start with `offset = None` and pass each returned `next_offset` on.
+    pub async fn reembed_from(
+        &self,
+        source: &QdrantStore,
+        offset: Option<&str>,
+    ) -> Result<ReembedPage> {
+        let mut request = ScrollPointsBuilder::new(&source.collection)
+            .with_payload(true)
+            .with_vectors(false)
+            .limit(REEMBED_PAGE_SIZE);
+        if let Some(offset) = offset {
+            request = request.offset(parse_point_id(offset));
+        }
+        let response = source
+            .client
+            .scroll(request)
+            .await
+            .map_err(|e| OtlError::DatabaseError(format!("Failed to read vectors: {e}")))?;
+
+        let mut page = ReembedPage {
+            next_offset: response
+                .next_page_offset
+                .as_ref()
+                .map(|id| point_id_string(Some(id))),
+            ..Default::default()
+        };
+
+        let mut points = Vec::new();
+        let mut contents = Vec::new();
+        for point in response.result {
+            let content = point
+                .payload
+                .get("content")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            match (point.id, content) {
+                (Some(id), Some(content)) if !content.is_empty() => {
+                    points.push((id, point.payload));
+                    contents.push(content);
+                }
+                _ => page.failed += 1,
+            }
+        }
+        if points.is_empty() {
+            return Ok(page);
+        }
+
+        // One bad text should not fail the whole page
+        let vectors = match self.embedding_client.embed_batch(&contents).await {
+            Ok(vectors) if vectors.len() == contents.len() => {
+                vectors.into_iter().map(Some).collect()
+            }
+            _ => {
+                let mut vectors = Vec::with_capacity(contents.len());
+                for content in &contents {
+                    vectors.push(self.embedding_client.embed(content).await.ok());
+                }
+                vectors
+            }
+        };
+
+        let mut structs = Vec::with_capacity(points.len());
+        for ((id, payload), vector) in points.into_iter().zip(vectors) {
+            match vector {
+                Some(vector) => {
+                    structs.push(PointStruct::new(id, self.store.prepare(&vector), payload))
+                }
+                None => page.failed += 1,
+            }
+        }
+        page.embedded = structs.len() as u64;
+        if !structs.is_empty() {
+            self.store
+                .client
+                .upsert_points(UpsertPointsBuilder::new(&self.store.collection, structs))
+                .await
+                .map_err(|e| OtlError::DatabaseError(format!("Failed to upsert vectors: {e}")))?;
+        }
+        Ok(page)
+    }
+}
+
 // ============================================================================
 // VectorSearchBackend Implementation
 // ============================================================================
@@ -206,9 +728,14 @@ impl super::VectorStore for QdrantStore {
 impl VectorSearchBackend {
     /// Create a new vector search backend
     pub fn new(store: QdrantStore, embedding_client: Arc<dyn EmbeddingClient>) -> Self {
+        Self::from_index(Arc::new(VectorIndex::new(store, embedding_client)))
+    }
+
+    /// Backend whose active index is `index`
+    pub fn from_index(index: Arc<VectorIndex>) -> Self {
         Self {
-            store,
-            embedding_client,
+            active: RwLock::new(index),
+            shadow: RwLock::new(None),
         }
     }
 
@@ -217,20 +744,71 @@ impl VectorSearchBackend {
         config: &DatabaseConfig,
         embedding_client: Arc<dyn EmbeddingClient>,
     ) -> Result<Self> {
-        let mut store = QdrantStore::new(config).await?;
-        // Override dimension with actual embedding client dimension
-        store.dimension = embedding_client.dimension();
+        let store = QdrantStore::new(config).await?;
         Ok(Self::new(store, embedding_client))
     }
 
+    /// Index serving searches
+    pub fn active(&self) -> Arc<VectorIndex> {
+        self.active.read().expect("vector index poisoned").clone()
+    }
+
+    /// Index receiving copies of writes, if any
+    pub fn shadow(&self) -> Option<Arc<VectorIndex>> {
+        self.shadow.read().expect("vector index poisoned").clone()
+    }
+
+    /// Start or stop copying writes to another index
+    pub fn set_shadow(&self, index: Option<Arc<VectorIndex>>) {
+        *self.shadow.write().expect("vector index poisoned") = index;
+    }
+
+    /// Serve searches and writes from `index` and return the previous one
+    ///
+    /// A shadow on the same collection is detached, since it is now active.
+    pub fn switch_to(&self, index: Arc<VectorIndex>) -> Arc<VectorIndex> {
+        let mut shadow = self.shadow.write().expect("vector index poisoned");
+        if shadow
+            .as_ref()
+            .is_some_and(|s| s.collection() == index.collection())
+        {
+            *shadow = None;
+        }
+        std::mem::replace(
+            &mut *self.active.write().expect("vector index poisoned"),
+            index,
+        )
+    }
+
+    /// Embedding client used for queries and indexing
+    pub fn embedding_client(&self) -> Arc<dyn EmbeddingClient> {
+        self.active().embedding_client.clone()
+    }
+
     /// Initialize the collection
     pub async fn init(&self) -> Result<()> {
-        self.store.init_collection().await
+        self.active().init().await
     }
 
     /// Store an embedding
+    ///
+    /// The shadow index, having another model, embeds the content itself.
     pub async fn store(&self, embedding: &super::EmbeddingVector) -> Result<()> {
-        self.store.store(embedding).await
+        self.active().store.store(embedding).await?;
+        if let Some(shadow) = self.shadow() {
+            if let Err(e) = shadow
+                .index_point(
+                    embedding.id,
+                    embedding.document_id,
+                    embedding.chunk_index,
+                    &embedding.content,
+                )
+                .await
+            {
+                tracing::warn!("Shadow index {} missed a write: {}", shadow.collection(), e);
+            }
+        }
+        Ok(())
     }
 
     /// Generate embedding and store a text chunk
@@ -240,7 +818,8 @@ impl VectorSearchBackend {
         chunk_index: u32,
         content: &str,
     ) -> Result<Uuid> {
-        let vector = self.embedding_client.embed(content).await?;
+        let active = self.active();
+        let vector = active.embedding_client.embed(content).await?;
         let id = Uuid::new_v4();
 
         let embedding = super::EmbeddingVector {
@@ -250,8 +829,16 @@ impl VectorSearchBackend {
             chunk_index,
             content: content.to_string(),
         };
+        active.store.store(&embedding).await?;
 
-        self.store(&embedding).await?;
+        if let Some(shadow) = self.shadow() {
+            if let Err(e) = shadow
+                .index_point(id, document_id, chunk_index, content)
+                .await
+            {
+                tracing::warn!("Shadow index {} missed a write: {}", shadow.collection(), e);
+            }
+        }
         Ok(id)
     }
 
@@ -261,27 +848,67 @@ impl VectorSearchBackend {
         query_vector: &[f32],
         limit: usize,
     ) -> Result<Vec<SearchResult>> {
-        self.store.search(query_vector, limit).await
+        self.active().store.search(query_vector, limit).await
     }
 
     /// Delete vectors by document ID
     pub async fn delete_by_document(&self, document_id: Uuid) -> Result<u64> {
-        self.store.delete_by_document(document_id).await
+        let deleted = self.active().store.delete_by_document(document_id).await?;
+        if let Some(shadow) = self.shadow() {
+            if let Err(e) = shadow.store.delete_by_document(document_id).await {
+                tracing::warn!(
+                    "Shadow index {} missed a delete: {}",
+                    shadow.collection(),
+                    e
+                );
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Tag the vectors of a document with its version and whether it is
+    /// superseded
+    pub async fn tag_version(
+        &self,
+        document_id: Uuid,
+        version: Option<f64>,
+        superseded: bool,
+    ) -> Result<()> {
+        self.active()
+            .store
+            .tag_version(document_id, version, superseded)
+            .await?;
+        if let Some(shadow) = self.shadow() {
+            if let Err(e) = shadow
+                .store
+                .tag_version(document_id, version, superseded)
+                .await
+            {
+                tracing::warn!(
+                    "Shadow index {} missed a version tag: {}",
+                    shadow.collection(),
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the chunks nearest to a stored chunk vector
+    pub async fn neighbors(&self, vector_id: &str, limit: usize) -> Result<Vec<NeighborChunk>> {
+        self.active().store.neighbors(vector_id, limit).await
+    }
+
+    /// All vectors stored for a document
+    pub async fn document_vectors(&self, document_id: Uuid) -> Result<Vec<ChunkVector>> {
+        self.active().store.document_vectors(document_id).await
     }
 }
 
 #[async_trait]
 impl SearchBackend for VectorSearchBackend {
     async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-        // Generate embedding for the query
-        let query_vector = self
-            .embedding_client
-            .embed(query)
-            .await
-            .map_err(|e| OtlError::SearchError(format!("Failed to embed query: {e}")))?;
-
-        // Search with the vector
-        self.store.search(&query_vector, limit).await
+        self.active().search(query, limit).await
     }
 
     fn name(&self) -> &str {
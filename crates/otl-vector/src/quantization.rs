@@ -0,0 +1,383 @@
+//! Vector compression
+//!
+//! Matryoshka-style dimension truncation of embeddings, and local
+//! simulations of the scalar and product quantization Qdrant applies to
+//! stored vectors. `otl vector bench` uses the simulations to report recall
+//! against memory for a sample of stored vectors before a collection is
+//! configured.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_core::VectorQuantization;
+
+/// Candidates fetched per requested result when searching quantized
+/// vectors; they are rescored with the original vectors
+pub const QUANTIZATION_OVERSAMPLING: f64 = 2.0;
+
+/// Quantile of values covered by the scalar quantization range
+const SCALAR_QUANTILE: f32 = 0.99;
+
+/// Centroids per product quantization subspace (one byte per code)
+const PQ_CENTROIDS: usize = 256;
+
+/// k-means iterations when fitting product quantization codebooks
+const PQ_ITERATIONS: usize = 10;
+
+// ============================================================================
+// Truncation
+// ============================================================================
+
+/// First `dimension` components of `vector`, scaled to unit length
+///
+/// Matryoshka embeddings keep most of their meaning in the leading
+/// dimensions; renormalizing keeps cosine and dot product scores aligned.
+pub fn truncate(vector: &[f32], dimension: usize) -> Vec<f32> {
+    let mut truncated = vector[..dimension.min(vector.len())].to_vec();
+    let norm = truncated.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        truncated.iter_mut().for_each(|x| *x /= norm);
+    }
+    truncated
+}
+
+/// Cosine similarity of two vectors of the same length
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+// ============================================================================
+// Quantizers
+// ============================================================================
+
+/// `i8` quantization over one value range shared by all dimensions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalarQuantizer {
+    min: f32,
+    step: f32,
+}
+
+impl ScalarQuantizer {
+    /// Range covering the central [`SCALAR_QUANTILE`] of all values
+    pub fn fit(vectors: &[Vec<f32>]) -> Self {
+        let mut values: Vec<f32> = vectors.iter().flatten().copied().collect();
+        if values.is_empty() {
+            return Self {
+                min: 0.0,
+                step: 1.0,
+            };
+        }
+        values.sort_by(f32::total_cmp);
+        let tail = ((1.0 - SCALAR_QUANTILE) / 2.0 * values.len() as f32) as usize;
+        let min = values[tail];
+        let max = values[values.len() - 1 - tail];
+        let step = if max > min { (max - min) / 255.0 } else { 1.0 };
+        Self { min, step }
+    }
+
+    /// Value `x` holds after quantization
+    pub fn reconstruct(&self, vector: &[f32]) -> Vec<f32> {
+        vector
+            .iter()
+            .map(|x| ((x - self.min) / self.step).round().clamp(0.0, 255.0) * self.step + self.min)
+            .collect()
+    }
+}
+
+/// Product quantization: each run of `sub_dimension` components is
+/// replaced by the nearest of [`PQ_CENTROIDS`] centroids
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductQuantizer {
+    sub_dimension: usize,
+    /// Centroids of each subspace
+    codebooks: Vec<Vec<Vec<f32>>>,
+}
+
+fn squared_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn nearest(centroids: &[Vec<f32>], point: &[f32]) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i, squared_distance(c, point)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// k-means over `points`, seeded with evenly spaced points
+fn kmeans(points: &[&[f32]], k: usize, iterations: usize) -> Vec<Vec<f32>> {
+    let k = k.min(points.len()).max(1);
+    let stride = points.len() / k;
+    let mut centroids: Vec<Vec<f32>> = (0..k).map(|i| points[i * stride].to_vec()).collect();
+    let dim = centroids[0].len();
+
+    for _ in 0..iterations {
+        let mut sums = vec![vec![0.0f32; dim]; k];
+        let mut counts = vec![0usize; k];
+        for point in points {
+            let c = nearest(&centroids, point);
+            counts[c] += 1;
+            sums[c].iter_mut().zip(*point).for_each(|(s, x)| *s += x);
+        }
+        for ((centroid, sum), count) in centroids.iter_mut().zip(sums).zip(counts) {
+            if count > 0 {
+                *centroid = sum.into_iter().map(|s| s / count as f32).collect();
+            }
+        }
+    }
+    centroids
+}
+
+impl ProductQuantizer {
+    /// Codebooks for `compression` (4 to 64: a byte per `compression / 4`
+    /// components, as in Qdrant)
+    pub fn fit(vectors: &[Vec<f32>], compression: u32) -> Self {
+        let sub_dimension = (compression as usize / 4).max(1);
+        let dimension = vectors.first().map(Vec::len).unwrap_or(0);
+        let codebooks = (0..dimension)
+            .step_by(sub_dimension)
+            .map(|start| {
+                let end = (start + sub_dimension).min(dimension);
+                let points: Vec<&[f32]> = vectors.iter().map(|v| &v[start..end]).collect();
+                kmeans(&points, PQ_CENTROIDS, PQ_ITERATIONS)
+            })
+            .collect();
+        Self {
+            sub_dimension,
+            codebooks,
+        }
+    }
+
+    /// Vector made of the centroids nearest to each subvector
+    pub fn reconstruct(&self, vector: &[f32]) -> Vec<f32> {
+        vector
+            .chunks(self.sub_dimension)
+            .zip(&self.codebooks)
+            .flat_map(|(sub, centroids)| centroids[nearest(centroids, sub)].clone())
+            .collect()
+    }
+}
+
+// ============================================================================
+// Benchmark
+// ============================================================================
+
+/// A storage setting to benchmark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchmarkCase {
+    /// Stored dimension (truncated if below the embedding dimension)
+    pub dimension: usize,
+    pub quantization: VectorQuantization,
+}
+
+/// Recall and memory of a [`BenchmarkCase`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+    pub case: BenchmarkCase,
+    /// Share of the exact top-k found by searching the compressed vectors
+    pub recall: f32,
+    /// Recall after rescoring oversampled candidates with the stored
+    /// (truncated, unquantized) vectors, as Qdrant does
+    pub rescored_recall: f32,
+    /// In-memory bytes per vector
+    pub bytes_per_vector: usize,
+}
+
+/// Indices of the `k` vectors of `base` most similar to `query`
+fn top_k(query: &[f32], base: &[Vec<f32>], k: usize) -> Vec<usize> {
+    let mut scored: Vec<(usize, f32)> = base
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (i, cosine(query, v)))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.into_iter().take(k).map(|(i, _)| i).collect()
+}
+
+/// Share of `exact` found in `approx`
+pub fn recall(exact: &[usize], approx: &[usize]) -> f32 {
+    if exact.is_empty() {
+        return 1.0;
+    }
+    let found = exact.iter().filter(|i| approx.contains(i)).count();
+    found as f32 / exact.len() as f32
+}
+
+/// Recall@k of each case against exact search over the full vectors
+///
+/// The default cases are every combination of the embedding dimension and
+/// its Matryoshka truncations with no, scalar and product quantization.
+pub fn benchmark(
+    base: &[Vec<f32>],
+    queries: &[Vec<f32>],
+    k: usize,
+    cases: &[BenchmarkCase],
+) -> Vec<BenchmarkResult> {
+    let exact: Vec<Vec<usize>> = queries.iter().map(|q| top_k(q, base, k)).collect();
+    let candidates = (k as f64 * QUANTIZATION_OVERSAMPLING).ceil() as usize;
+
+    cases
+        .iter()
+        .map(|case| {
+            let stored: Vec<Vec<f32>> = base.iter().map(|v| truncate(v, case.dimension)).collect();
+            let compressed: Vec<Vec<f32>> = match case.quantization {
+                VectorQuantization::None => stored.clone(),
+                VectorQuantization::Scalar => {
+                    let quantizer = ScalarQuantizer::fit(&stored);
+                    stored.iter().map(|v| quantizer.reconstruct(v)).collect()
+                }
+                VectorQuantization::Product { compression } => {
+                    let quantizer = ProductQuantizer::fit(&stored, compression);
+                    stored.iter().map(|v| quantizer.reconstruct(v)).collect()
+                }
+            };
+
+            let mut total = 0.0;
+            let mut rescored_total = 0.0;
+            for (query, exact) in queries.iter().zip(&exact) {
+                let query = truncate(query, case.dimension);
+                let found = top_k(&query, &compressed, candidates);
+                total += recall(exact, &found[..k.min(found.len())]);
+
+                let mut rescored: Vec<(usize, f32)> = found
+                    .into_iter()
+                    .map(|i| (i, cosine(&query, &stored[i])))
+                    .collect();
+                rescored.sort_by(|a, b| b.1.total_cmp(&a.1));
+                let rescored: Vec<usize> = rescored.into_iter().take(k).map(|(i, _)| i).collect();
+                rescored_total += recall(exact, &rescored);
+            }
+            let n = queries.len().max(1) as f32;
+
+            BenchmarkResult {
+                case: *case,
+                recall: total / n,
+                rescored_recall: rescored_total / n,
+                bytes_per_vector: case.quantization.bytes_per_vector(case.dimension),
+            }
+        })
+        .collect()
+}
+
+/// Every combination of the dimensions (the embedding dimension and common
+/// Matryoshka truncations below it) with no, scalar and product (x16)
+/// quantization
+pub fn default_cases(dimension: usize) -> Vec<BenchmarkCase> {
+    let mut dimensions = vec![dimension];
+    dimensions.extend([1024, 768, 512, 256].into_iter().filter(|&d| d < dimension));
+    dimensions
+        .into_iter()
+        .flat_map(|dimension| {
+            [
+                VectorQuantization::None,
+                VectorQuantization::Scalar,
+                VectorQuantization::Product {
+                    compression: VectorQuantization::DEFAULT_PRODUCT_COMPRESSION,
+                },
+            ]
+            .into_iter()
+            .map(move |quantization| BenchmarkCase {
+                dimension,
+                quantization,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random unit vectors
+    fn vectors(n: usize, dim: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                let v: Vec<f32> = (0..dim)
+                    .map(|_| {
+                        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                        ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+                    })
+                    .collect();
+                truncate(&v, dim)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_truncate_renormalizes() {
+        let v = truncate(&[3.0, 4.0, 12.0], 2);
+        assert_eq!(v, vec![0.6, 0.8]);
+        assert_eq!(truncate(&[1.0, 0.0], 8), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_scalar_quantizer_is_close() {
+        let data = vectors(50, 16, 7);
+        let quantizer = ScalarQuantizer::fit(&data);
+        for v in &data {
+            assert!(cosine(v, &quantizer.reconstruct(v)) > 0.99);
+        }
+    }
+
+    #[test]
+    fn test_product_quantizer_shapes() {
+        let data = vectors(40, 16, 3);
+        let quantizer = ProductQuantizer::fit(&data, 16);
+        assert_eq!(quantizer.codebooks.len(), 4);
+        // Fewer points than centroids: every point is its own centroid
+        for v in &data {
+            assert_eq!(&quantizer.reconstruct(v), v);
+        }
+    }
+
+    #[test]
+    fn test_benchmark_recall_and_memory() {
+        let base = vectors(200, 32, 11);
+        let queries = vectors(10, 32, 5);
+        let cases = [
+            BenchmarkCase {
+                dimension: 32,
+                quantization: VectorQuantization::None,
+            },
+            BenchmarkCase {
+                dimension: 32,
+                quantization: VectorQuantization::Scalar,
+            },
+            BenchmarkCase {
+                dimension: 8,
+                quantization: VectorQuantization::None,
+            },
+        ];
+        let results = benchmark(&base, &queries, 5, &cases);
+
+        assert_eq!(results[0].recall, 1.0);
+        assert_eq!(results[0].bytes_per_vector, 128);
+        assert!(results[1].rescored_recall >= results[1].recall);
+        assert!(results[1].rescored_recall > 0.9);
+        assert_eq!(results[1].bytes_per_vector, 32);
+        // Random vectors carry no Matryoshka structure
+        assert!(results[2].recall < 1.0);
+    }
+
+    #[test]
+    fn test_default_cases() {
+        let cases = default_cases(768);
+        assert_eq!(cases.len(), 9);
+        assert!(cases.iter().all(|c| c.dimension <= 768));
+        assert_eq!(cases[0].dimension, 768);
+    }
+}
@@ -0,0 +1,211 @@
+//! In-memory vector store and embedder for tests
+//!
+//! Lets ingestion and retrieval code run without Qdrant or an embedding
+//! endpoint. Enabled with the `test-utils` feature.
+//!
+//! Author: hephaex@gmail.com
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use otl_core::{DocumentAcl, Result, SearchResult, SearchResultType, SourceReference};
+use uuid::Uuid;
+
+use crate::embedding::EmbeddingClient;
+use crate::quantization::cosine;
+use crate::{EmbeddingVector, VectorStore};
+
+// ============================================================================
+// Vector Store
+// ============================================================================
+
+/// Vector store with exact cosine search
+///
+/// Results are ranked by cosine similarity; equal scores keep insertion
+/// order. Storing a vector with a known ID replaces it.
+#[derive(Default)]
+pub struct InMemoryVectorStore {
+    vectors: RwLock<Vec<EmbeddingVector>>,
+}
+
+impl InMemoryVectorStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored vectors
+    pub fn len(&self) -> usize {
+        self.vectors.read().unwrap().len()
+    }
+
+    /// Whether no vectors are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl VectorStore for InMemoryVectorStore {
+    async fn store(&self, embedding: &EmbeddingVector) -> Result<()> {
+        let mut vectors = self.vectors.write().unwrap();
+        match vectors.iter_mut().find(|v| v.id == embedding.id) {
+            Some(existing) => *existing = embedding.clone(),
+            None => vectors.push(embedding.clone()),
+        }
+        Ok(())
+    }
+
+    async fn search(&self, query_vector: &[f32], limit: usize) -> Result<Vec<SearchResult>> {
+        let mut results: Vec<SearchResult> = self
+            .vectors
+            .read()
+            .unwrap()
+            .iter()
+            .map(|v| SearchResult {
+                content: v.content.clone(),
+                score: cosine(query_vector, &v.vector),
+                source: SourceReference::new(v.document_id).with_chunk_index(v.chunk_index),
+                acl: DocumentAcl::default(),
+                result_type: SearchResultType::Vector,
+            })
+            .collect();
+        // Stable sort: equal scores stay in insertion order
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    async fn delete_by_document(&self, document_id: Uuid) -> Result<u64> {
+        let mut vectors = self.vectors.write().unwrap();
+        let before = vectors.len();
+        vectors.retain(|v| v.document_id != document_id);
+        Ok((before - vectors.len()) as u64)
+    }
+}
+
+// ============================================================================
+// Embedding Client
+// ============================================================================
+
+/// Deterministic bag-of-words embedder
+///
+/// Each lowercased word is hashed into one of `dimension` buckets and the
+/// counts are L2-normalized, so texts sharing words have a positive cosine
+/// similarity and identical texts embed identically.
+pub struct HashEmbedding {
+    dimension: usize,
+}
+
+impl HashEmbedding {
+    /// Create an embedder producing vectors of `dimension` values
+    pub fn new(dimension: usize) -> Self {
+        Self {
+            dimension: dimension.max(1),
+        }
+    }
+
+    fn vector(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0; self.dimension];
+        for word in text.split_whitespace() {
+            let mut hasher = DefaultHasher::new();
+            word.to_lowercase().hash(&mut hasher);
+            vector[(hasher.finish() % self.dimension as u64) as usize] += 1.0;
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+#[async_trait]
+impl EmbeddingClient for HashEmbedding {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.vector(text))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.vector(t)).collect())
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn store_chunk(
+        store: &InMemoryVectorStore,
+        embedder: &HashEmbedding,
+        document_id: Uuid,
+        chunk_index: u32,
+        content: &str,
+    ) {
+        store
+            .store(&EmbeddingVector {
+                id: Uuid::new_v4(),
+                vector: embedder.embed(content).await.unwrap(),
+                document_id,
+                chunk_index,
+                content: content.to_string(),
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_by_cosine() {
+        let embedder = HashEmbedding::new(64);
+        let store = InMemoryVectorStore::new();
+        let doc = Uuid::new_v4();
+        store_chunk(&store, &embedder, doc, 0, "출장 여비 정산").await;
+        store_chunk(&store, &embedder, doc, 1, "연차휴가 신청 절차").await;
+
+        let query = embedder.embed("연차휴가 신청 절차").await.unwrap();
+        let results = store.search(&query, 5).await.unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].content, "연차휴가 신청 절차");
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+        assert_eq!(results[0].source.chunk_index, Some(1));
+        assert_eq!(store.search(&query, 1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_document() {
+        let embedder = HashEmbedding::new(16);
+        let store = InMemoryVectorStore::new();
+        let (kept, deleted) = (Uuid::new_v4(), Uuid::new_v4());
+        store_chunk(&store, &embedder, kept, 0, "병가").await;
+        store_chunk(&store, &embedder, deleted, 0, "연차").await;
+        store_chunk(&store, &embedder, deleted, 1, "휴직").await;
+
+        assert_eq!(store.delete_by_document(deleted).await.unwrap(), 2);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_hash_embedding_is_deterministic() {
+        let embedder = HashEmbedding::new(32);
+        let a = embedder.embed("연차휴가 신청").await.unwrap();
+        let batch = embedder
+            .embed_batch(&["연차휴가 신청".to_string(), String::new()])
+            .await
+            .unwrap();
+
+        assert_eq!(a, batch[0]);
+        assert_eq!(a.len(), 32);
+        assert!(batch[1].iter().all(|x| *x == 0.0));
+    }
+}
@@ -0,0 +1,202 @@
+//! Token estimates for embedding models
+//!
+//! Embedding models truncate (or reject) input beyond their context length,
+//! so a chunk longer than that is only partly searchable. Shipping the real
+//! tokenizers of every supported model is not worth it for a size check;
+//! [`TokenCounter`] estimates token counts from the tokenizer family of the
+//! configured model instead:
+//!
+//! - runs of letters and digits cost one token per few characters
+//!   (BPE and WordPiece vocabularies cover common words whole)
+//! - Hangul and CJK characters cost a fixed fraction of a token each,
+//!   depending on how well the family's vocabulary covers them
+//! - punctuation and symbols cost one token each
+//!
+//! Estimates err on the high side so that a chunk reported within the limit
+//! is within it.
+//!
+//! Author: hephaex@gmail.com
+
+use otl_core::{LlmConfig, LlmProvider};
+use serde::{Deserialize, Serialize};
+
+/// Tokenizer family of an embedding model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerFamily {
+    /// Byte-level BPE of the OpenAI embedding models (`cl100k_base`)
+    Cl100k,
+    /// SentencePiece of XLM-RoBERTa based models (bge-m3, multilingual-e5)
+    SentencePiece,
+    /// WordPiece of BERT based models (MiniLM, nomic-embed-text)
+    WordPiece,
+}
+
+impl TokenizerFamily {
+    /// Characters per token in runs of letters and digits, in tenths
+    fn chars_per_token_tenths(self) -> usize {
+        match self {
+            Self::Cl100k => 40,
+            Self::SentencePiece => 35,
+            Self::WordPiece => 30,
+        }
+    }
+
+    /// Tokens per Hangul or CJK character, in tenths
+    fn cjk_char_tenths(self) -> usize {
+        match self {
+            Self::Cl100k => 12,
+            Self::SentencePiece => 7,
+            Self::WordPiece => 10,
+        }
+    }
+
+    /// Special tokens added around every input
+    fn special_tokens(self) -> usize {
+        match self {
+            Self::Cl100k => 0,
+            Self::SentencePiece | Self::WordPiece => 2,
+        }
+    }
+}
+
+/// Estimates token counts against an embedding model's input limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenCounter {
+    pub family: TokenizerFamily,
+    /// Maximum input tokens of the model
+    pub max_tokens: usize,
+}
+
+impl TokenCounter {
+    pub fn new(family: TokenizerFamily, max_tokens: usize) -> Self {
+        Self { family, max_tokens }
+    }
+
+    /// Counter for the embedding model of a config
+    pub fn for_config(config: &LlmConfig) -> Self {
+        let model = config.embedding_model.as_str();
+        match config.provider {
+            LlmProvider::OpenAI | LlmProvider::Azure => Self::openai(model),
+            LlmProvider::Ollama => Self::ollama(model),
+            LlmProvider::Vllm => Self::vllm(model),
+        }
+    }
+
+    fn openai(_model: &str) -> Self {
+        // text-embedding-3-small/-large and ada-002 share the limit
+        Self::new(TokenizerFamily::Cl100k, 8191)
+    }
+
+    fn ollama(model: &str) -> Self {
+        // Ollama model names may carry a tag ("nomic-embed-text:latest")
+        match model.split(':').next().unwrap_or(model) {
+            "nomic-embed-text" => Self::new(TokenizerFamily::WordPiece, 8192),
+            "mxbai-embed-large" => Self::new(TokenizerFamily::WordPiece, 512),
+            "all-minilm" => Self::new(TokenizerFamily::WordPiece, 256),
+            "bge-m3" => Self::new(TokenizerFamily::SentencePiece, 8192),
+            _ => Self::new(TokenizerFamily::WordPiece, 512),
+        }
+    }
+
+    fn vllm(model: &str) -> Self {
+        match model {
+            "BAAI/bge-m3" => Self::new(TokenizerFamily::SentencePiece, 8192),
+            "intfloat/multilingual-e5-large" => Self::new(TokenizerFamily::SentencePiece, 512),
+            "nomic-ai/nomic-embed-text-v1.5" => Self::new(TokenizerFamily::WordPiece, 8192),
+            "sentence-transformers/all-MiniLM-L6-v2" => Self::new(TokenizerFamily::WordPiece, 256),
+            _ => Self::openai(model),
+        }
+    }
+
+    /// Estimated number of tokens of a text
+    pub fn count(&self, text: &str) -> usize {
+        if text.trim().is_empty() {
+            return 0;
+        }
+        let chars_per_token = self.family.chars_per_token_tenths();
+        let per_cjk = self.family.cjk_char_tenths();
+        // Word runs cost whole tokens; CJK characters add up in tenths
+        let word_tokens = |len: usize| (len * 10).div_ceil(chars_per_token);
+
+        let mut tokens = 0;
+        let mut cjk_tenths = 0;
+        let mut word = 0;
+        for c in text.chars() {
+            if c.is_alphanumeric() && !is_cjk(c) {
+                word += 1;
+                continue;
+            }
+            tokens += word_tokens(word);
+            word = 0;
+            if is_cjk(c) {
+                cjk_tenths += per_cjk;
+            } else if !c.is_whitespace() {
+                tokens += 1;
+            }
+        }
+        tokens += word_tokens(word);
+
+        tokens + cjk_tenths.div_ceil(10) + self.family.special_tokens()
+    }
+
+    /// Whether a text exceeds the model's input limit
+    pub fn exceeds_limit(&self, text: &str) -> bool {
+        self.count(text) > self.max_tokens
+    }
+}
+
+/// Hangul, CJK ideographs and kana
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{AC00}'..='\u{D7A3}'     // Hangul syllables
+        | '\u{1100}'..='\u{11FF}'   // Hangul jamo
+        | '\u{3130}'..='\u{318F}'   // Hangul compatibility jamo
+        | '\u{3040}'..='\u{30FF}'   // Hiragana, katakana
+        | '\u{4E00}'..='\u{9FFF}'   // CJK unified ideographs
+        | '\u{3400}'..='\u{4DBF}'   // CJK extension A
+    )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_by_family() {
+        let cl100k = TokenCounter::new(TokenizerFamily::Cl100k, 8191);
+        let sentencepiece = TokenCounter::new(TokenizerFamily::SentencePiece, 512);
+
+        assert_eq!(cl100k.count(""), 0);
+        assert_eq!(cl100k.count("  \n"), 0);
+        // "annual" and "leave" are two words of at most 4 chars per token
+        assert_eq!(cl100k.count("annual leave."), 2 + 2 + 1);
+        // Five Hangul syllables
+        assert_eq!(cl100k.count("연차휴가는"), 6);
+        assert_eq!(sentencepiece.count("연차휴가는"), 4 + 2);
+        // Hangul tenths add up across words; the digit is a word of its own
+        assert_eq!(cl100k.count("연차휴가 2일"), 6 + 1);
+    }
+
+    #[test]
+    fn test_limit_of_configured_model() {
+        let mut config = LlmConfig {
+            provider: LlmProvider::Ollama,
+            embedding_model: "all-minilm:latest".to_string(),
+            ..Default::default()
+        };
+        let counter = TokenCounter::for_config(&config);
+        assert_eq!(counter.family, TokenizerFamily::WordPiece);
+        assert_eq!(counter.max_tokens, 256);
+        assert!(counter.exceeds_limit(&"휴가 ".repeat(200)));
+        assert!(!counter.exceeds_limit("연차휴가 신청 절차"));
+
+        config.provider = LlmProvider::OpenAI;
+        config.embedding_model = "text-embedding-3-small".to_string();
+        assert_eq!(TokenCounter::for_config(&config).max_tokens, 8191);
+    }
+}
@@ -0,0 +1,15 @@
+-- Document Lineage Schema
+-- Records which parser, OCR engine, chunker settings, embedding model and
+-- extraction model produced each document and chunk
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS document_lineage (
+    document_id UUID PRIMARY KEY,
+    lineage JSONB NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE document_lineage IS 'Processing lineage of ingested documents, served by GET /api/v1/documents/:id/lineage';
+COMMENT ON COLUMN document_lineage.lineage IS 'DocumentLineage JSON: parser, ocr_engine, chunker (with config_hash), embedding_model, extraction_model, chunks';
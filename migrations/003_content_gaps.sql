@@ -0,0 +1,27 @@
+-- Content Gap Triage Schema
+-- Queries that retrieved nothing or only low-confidence answers, grouped
+-- into topics by the admin API and resolved once covering documents exist
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS content_gaps (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    question TEXT NOT NULL,
+    keywords TEXT[] NOT NULL DEFAULT '{}',  -- Analyzer keywords, used for topic clustering
+    reason VARCHAR(20) NOT NULL,  -- no_results | low_confidence
+    confidence REAL NOT NULL DEFAULT 0.0,
+    user_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Resolution
+    resolved_at TIMESTAMPTZ,
+    resolved_by UUID,
+    resolved_documents UUID[] NOT NULL DEFAULT '{}',  -- Documents ingested to cover the gap
+    resolution_note TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_gaps_created ON content_gaps(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_content_gaps_open ON content_gaps(created_at DESC) WHERE resolved_at IS NULL;
+
+COMMENT ON TABLE content_gaps IS 'Content gap triage queue, served by GET /api/v1/admin/content-gaps';
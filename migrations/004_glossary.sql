@@ -0,0 +1,31 @@
+-- Glossary Schema
+-- Curated term definitions that answer definitional questions directly,
+-- plus candidates proposed from generated answers for admin review
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS glossary_terms (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    term VARCHAR(200) NOT NULL,
+    aliases TEXT[] NOT NULL DEFAULT '{}',
+    lookup_keys TEXT[] NOT NULL DEFAULT '{}',  -- Normalized term and aliases
+    definition TEXT NOT NULL,
+    source TEXT,  -- Document title, regulation or URL the definition comes from
+    document_id UUID REFERENCES documents(id) ON DELETE SET NULL,
+
+    -- Access control (same model as documents)
+    access_level access_level NOT NULL DEFAULT 'internal',
+    department VARCHAR(100),
+    required_roles TEXT[] NOT NULL DEFAULT '{}',
+    allowed_users TEXT[] NOT NULL DEFAULT '{}',
+
+    status VARCHAR(20) NOT NULL DEFAULT 'approved',  -- approved | candidate
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_glossary_terms_lookup ON glossary_terms USING GIN(lookup_keys);
+CREATE INDEX IF NOT EXISTS idx_glossary_terms_status ON glossary_terms(status);
+
+COMMENT ON TABLE glossary_terms IS 'Glossary entries, managed through /api/v1/admin/glossary';
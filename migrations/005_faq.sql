@@ -0,0 +1,38 @@
+-- FAQ Schema
+-- Query history keywords for clustering, and FAQ entries generated from
+-- frequent questions that wait for admin review
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-17
+
+ALTER TABLE query_stats ADD COLUMN IF NOT EXISTS keywords TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE TABLE IF NOT EXISTS faq_entries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    question TEXT NOT NULL,  -- Canonical question
+    answer TEXT NOT NULL,
+    citations JSONB NOT NULL DEFAULT '[]',
+    keywords TEXT[] NOT NULL DEFAULT '{}',  -- Cluster keywords, matched against query keywords
+    source_questions TEXT[] NOT NULL DEFAULT '{}',  -- Most asked questions of the cluster
+    query_count INTEGER NOT NULL DEFAULT 0,
+    confidence REAL NOT NULL DEFAULT 0.0,
+
+    -- Access control (most restrictive cited document)
+    access_level access_level NOT NULL DEFAULT 'internal',
+    department VARCHAR(100),
+    required_roles TEXT[] NOT NULL DEFAULT '{}',
+    allowed_users TEXT[] NOT NULL DEFAULT '{}',
+
+    -- Review
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',  -- pending | approved | rejected
+    reviewed_by UUID,
+    reviewed_at TIMESTAMPTZ,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_faq_entries_keywords ON faq_entries USING GIN(keywords);
+CREATE INDEX IF NOT EXISTS idx_faq_entries_status ON faq_entries(status, query_count DESC);
+
+COMMENT ON TABLE faq_entries IS 'FAQ entries, reviewed through /api/v1/admin/faq and served by GET /api/v1/faq';
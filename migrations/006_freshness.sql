@@ -0,0 +1,24 @@
+-- Freshness Schema
+-- Alerts raised by the scheduled freshness check for documents past their
+-- review date or superseded by a newer version
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS freshness_alerts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    reason VARCHAR(20) NOT NULL,  -- review_overdue | superseded
+    review_date DATE,  -- Review date that had passed
+    superseded_by UUID,  -- Newer version of the document
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    acknowledged_at TIMESTAMPTZ,
+    acknowledged_by UUID,
+    resolved_at TIMESTAMPTZ  -- Set when the condition no longer holds
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_freshness_alerts_open
+    ON freshness_alerts(document_id, reason) WHERE resolved_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_freshness_alerts_detected ON freshness_alerts(detected_at DESC);
+
+COMMENT ON TABLE freshness_alerts IS 'Stale document alerts, listed through /api/v1/admin/freshness/alerts';
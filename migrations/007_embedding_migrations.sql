@@ -0,0 +1,32 @@
+-- Embedding Migration Schema
+-- Re-embedding runs that move vector search to a new embedding model and
+-- Qdrant collection; the last switched run decides the active collection
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS embedding_migrations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    source_collection VARCHAR(255) NOT NULL,
+    target_collection VARCHAR(255) NOT NULL,
+    embedding_model VARCHAR(255) NOT NULL,
+    dimension INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running',  -- running | shadow_testing | ready | switched | failed
+
+    -- Progress
+    total_points BIGINT NOT NULL DEFAULT 0,
+    embedded_points BIGINT NOT NULL DEFAULT 0,
+    failed_points BIGINT NOT NULL DEFAULT 0,
+
+    auto_switch BOOLEAN NOT NULL DEFAULT FALSE,
+    shadow_report JSONB,  -- Retrieval overlap with the source collection
+    error TEXT,
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    switched_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_embedding_migrations_status ON embedding_migrations(status, switched_at DESC);
+
+COMMENT ON TABLE embedding_migrations IS 'Embedding model migrations, managed through /api/v1/admin/embeddings/migrations';
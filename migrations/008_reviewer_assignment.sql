@@ -0,0 +1,15 @@
+-- Reviewer Assignment Schema
+-- Extractions are assigned to reviewers and locked to the reviewer working
+-- on them; an expired lock returns the item to the pending queue
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-17
+
+ALTER TABLE extraction_queue ADD COLUMN IF NOT EXISTS assigned_to VARCHAR(100);
+ALTER TABLE extraction_queue ADD COLUMN IF NOT EXISTS assigned_at TIMESTAMPTZ;
+ALTER TABLE extraction_queue ADD COLUMN IF NOT EXISTS locked_by VARCHAR(100);
+ALTER TABLE extraction_queue ADD COLUMN IF NOT EXISTS lock_expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_extraction_assigned ON extraction_queue(assigned_to, status);
+CREATE INDEX IF NOT EXISTS idx_extraction_lock
+    ON extraction_queue(lock_expires_at) WHERE status = 'in_review';
@@ -0,0 +1,22 @@
+-- Multiple Review Schema
+-- Decisions of each reviewer on an extraction. Documents whose access level
+-- needs several approvals collect more than one; disagreeing decisions move
+-- the extraction to adjudication
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-18
+
+ALTER TYPE verification_status ADD VALUE IF NOT EXISTS 'adjudication';
+
+CREATE TABLE IF NOT EXISTS extraction_reviews (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    extraction_id UUID NOT NULL REFERENCES extraction_queue(id) ON DELETE CASCADE,
+    reviewer_id VARCHAR(100) NOT NULL,
+    decision VARCHAR(10) NOT NULL,  -- approve | reject
+    correction JSONB,  -- Correction proposed with an approval
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (extraction_id, reviewer_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_extraction_reviews_created ON extraction_reviews(created_at);
@@ -0,0 +1,29 @@
+-- Quality Audit Schema
+-- Auto-approved extractions are marked, a sample of them is audited, and
+-- the auto-approve threshold is raised when the audited error rate spikes
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-18
+
+ALTER TABLE extraction_queue ADD COLUMN IF NOT EXISTS auto_approved BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS extraction_audits (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    extraction_id UUID NOT NULL UNIQUE REFERENCES extraction_queue(id) ON DELETE CASCADE,
+    confidence REAL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',  -- pending | correct | incorrect
+    auditor_id VARCHAR(100),
+    notes TEXT,
+    sampled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    audited_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_extraction_audits_status ON extraction_audits(status, sampled_at);
+
+CREATE TABLE IF NOT EXISTS auto_approve_thresholds (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    threshold REAL NOT NULL,
+    reason TEXT,
+    changed_by VARCHAR(100),  -- NULL for automatic changes
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
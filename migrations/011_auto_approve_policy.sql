@@ -0,0 +1,15 @@
+-- Auto-approval Policy Schema
+-- Extractions record the extractor that produced them, and auto-approval
+-- rules per entity type, predicate, access level and extractor are versioned
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-18
+
+ALTER TABLE extraction_queue ADD COLUMN IF NOT EXISTS extractor VARCHAR(20);  -- rule | llm
+
+CREATE TABLE IF NOT EXISTS auto_approve_policies (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    rules JSONB NOT NULL DEFAULT '[]',
+    changed_by VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
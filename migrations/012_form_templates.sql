@@ -0,0 +1,13 @@
+-- Form Template Schema
+-- Admin-defined layouts of application forms, used to recognize a form
+-- type and extract its field values
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS form_templates (
+    name VARCHAR(100) PRIMARY KEY,
+    template JSONB NOT NULL,
+    updated_by VARCHAR(100),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,11 @@
+-- Chunk Integrity Schema
+-- Integrity checks compare stored chunk text with its recorded hash and the
+-- original document; corrupted chunks are kept but never cited
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-18
+
+ALTER TABLE document_chunks ADD COLUMN IF NOT EXISTS corrupted BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE document_chunks ADD COLUMN IF NOT EXISTS integrity_checked_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_chunks_corrupted ON document_chunks(document_id) WHERE corrupted;
@@ -0,0 +1,16 @@
+-- Document Key Schema
+-- Per-document data keys encrypting Restricted chunk content and stored
+-- originals, each wrapped by a key-encryption key (master key or KMS)
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS document_keys (
+    document_id UUID PRIMARY KEY,
+    key_id VARCHAR(100) NOT NULL,  -- Key-encryption key that wrapped it
+    algorithm VARCHAR(20) NOT NULL DEFAULT 'AES-256-GCM',
+    wrapped_key TEXT NOT NULL,     -- Base64 nonce, ciphertext and tag
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_keys_key_id ON document_keys(key_id);
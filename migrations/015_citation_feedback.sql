@@ -0,0 +1,21 @@
+-- Citation Feedback Schema
+-- Helpful / unhelpful votes on the citations of answered queries, folded
+-- into per-document and per-chunk popularity priors used for ranking
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-18
+
+CREATE TABLE IF NOT EXISTS citation_feedback (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    query_id UUID NOT NULL,  -- ID returned by POST /api/v1/query
+    user_id UUID,
+    document_id UUID NOT NULL,
+    chunk_index INTEGER,
+    helpful BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_citation_feedback_created ON citation_feedback(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_citation_feedback_document ON citation_feedback(document_id);
+
+COMMENT ON TABLE citation_feedback IS 'Citation votes, loaded into the ranking feedback priors at startup';
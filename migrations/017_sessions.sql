@@ -0,0 +1,11 @@
+-- Session Tracking Schema
+-- Each refresh token row is a login session; refreshing rotates the token
+-- in place and records when the session was last used
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-18
+
+ALTER TABLE refresh_tokens ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMP WITH TIME ZONE;
+
+COMMENT ON COLUMN refresh_tokens.device_info IS 'User agent the session was last used from';
+COMMENT ON COLUMN refresh_tokens.last_used_at IS 'Last login or token refresh of the session';
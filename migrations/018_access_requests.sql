@@ -0,0 +1,33 @@
+-- Document Access Request Schema
+-- Users denied by a document ACL ask for access; the owner, an editor of
+-- the owning department or an admin approves or denies the request. An
+-- approval adds the user to documents.allowed_users, until
+-- access_expires_at if set
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-19
+
+CREATE TABLE IF NOT EXISTS access_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    requester_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT,
+    requested_until TIMESTAMP WITH TIME ZONE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'approved', 'denied', 'expired')),
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_at TIMESTAMP WITH TIME ZONE,
+    decision_note TEXT,
+    access_expires_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- At most one open request per user and document
+CREATE UNIQUE INDEX IF NOT EXISTS idx_access_requests_pending
+    ON access_requests(document_id, requester_id) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_access_requests_requester ON access_requests(requester_id);
+CREATE INDEX IF NOT EXISTS idx_access_requests_expiry
+    ON access_requests(access_expires_at) WHERE status = 'approved';
+
+COMMENT ON COLUMN access_requests.requested_until IS 'Access end the requester asked for';
+COMMENT ON COLUMN access_requests.access_expires_at IS 'When the approved grant is withdrawn (NULL: never)';
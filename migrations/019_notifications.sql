@@ -0,0 +1,19 @@
+-- Notification Preferences Schema
+-- How each user is notified of review work, access requests, failed jobs
+-- and stale documents. Users without a row get every event through every
+-- channel
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-19
+
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    email_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    slack_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    slack_member_id VARCHAR(50),
+    muted_events TEXT[] NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON COLUMN notification_preferences.slack_member_id IS 'Slack member mentioned in channel notifications';
+COMMENT ON COLUMN notification_preferences.muted_events IS 'Events not notified: extractions_pending, access_requested, access_decided, job_failed, documents_stale';
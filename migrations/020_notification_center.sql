@@ -0,0 +1,27 @@
+-- In-App Notification Center Schema
+-- Every notification sent to a user is kept here so the web UI can show
+-- an unread count and a list. Read notifications are purged after
+-- NOTIFY_READ_RETENTION_DAYS
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-19
+
+CREATE TABLE IF NOT EXISTS user_notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event VARCHAR(50) NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    link TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_notifications_user
+    ON user_notifications(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_user_notifications_unread
+    ON user_notifications(user_id) WHERE read_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_user_notifications_read
+    ON user_notifications(read_at) WHERE read_at IS NOT NULL;
+
+COMMENT ON COLUMN user_notifications.link IS 'API path of what the notification is about';
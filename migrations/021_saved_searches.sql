@@ -0,0 +1,38 @@
+-- Saved Search Schema
+-- Users save queries and are notified when new documents match them. A
+-- background job scores the chunks of new documents against every enabled
+-- search; each match is recorded once so a document never notifies the
+-- same search twice
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-19
+
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(200) NOT NULL,
+    query TEXT NOT NULL,
+    keywords TEXT[] NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_matched_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_searches_user ON saved_searches(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_saved_searches_enabled ON saved_searches(created_at) WHERE enabled;
+
+CREATE TABLE IF NOT EXISTS saved_search_matches (
+    saved_search_id UUID NOT NULL REFERENCES saved_searches(id) ON DELETE CASCADE,
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    chunk_index INTEGER NOT NULL,
+    score REAL NOT NULL,
+    matched_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (saved_search_id, document_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_search_matches_recent
+    ON saved_search_matches(saved_search_id, matched_at DESC);
+
+COMMENT ON COLUMN saved_searches.keywords IS 'Words a matching chunk should contain (lowercase)';
+COMMENT ON COLUMN saved_search_matches.score IS 'Score of the best matching chunk';
@@ -0,0 +1,27 @@
+-- Chunk Deduplication Schema
+-- Each chunk gets a SimHash fingerprint of its normalized text and, when it
+-- near-duplicates an earlier chunk (boilerplate headers, repeated legal
+-- clauses), a link to that canonical chunk. Duplicates keep their own
+-- document and position for citations; the canonical chunk may be the only
+-- one embedded
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-19
+
+ALTER TABLE document_chunks
+    ADD COLUMN IF NOT EXISTS simhash BIGINT,
+    ADD COLUMN IF NOT EXISTS canonical_chunk_id UUID
+        REFERENCES document_chunks(id) ON DELETE SET NULL;
+
+-- Candidates are found by exact match on any 16-bit band of the fingerprint
+CREATE INDEX IF NOT EXISTS idx_chunks_simhash_band0
+    ON document_chunks (((simhash >> 48) & 65535)) WHERE simhash IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_chunks_simhash_band1
+    ON document_chunks (((simhash >> 32) & 65535)) WHERE simhash IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_chunks_simhash_band2
+    ON document_chunks (((simhash >> 16) & 65535)) WHERE simhash IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_chunks_simhash_band3
+    ON document_chunks ((simhash & 65535)) WHERE simhash IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_chunks_canonical
+    ON document_chunks(canonical_chunk_id) WHERE canonical_chunk_id IS NOT NULL;
@@ -0,0 +1,27 @@
+-- Document Classification Schema
+-- Each uploaded document gets a suggested access level and owning
+-- department from its content (rules, then the LLM when the rules are
+-- unsure). A suggestion that differs from the declared level with enough
+-- confidence is flagged for admin review; suggestions never change the ACL
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-19
+
+CREATE TABLE IF NOT EXISTS document_classifications (
+    document_id UUID PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    declared_access_level access_level NOT NULL,
+    declared_department VARCHAR(100),
+    suggested_access_level access_level NOT NULL,
+    suggested_department VARCHAR(100),
+    confidence REAL NOT NULL,
+    source VARCHAR(20) NOT NULL,           -- rules, llm
+    reasons TEXT[] NOT NULL DEFAULT '{}',
+    mismatch BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    reviewed_at TIMESTAMP WITH TIME ZONE,
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    review_note TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_classifications_open
+    ON document_classifications(created_at DESC) WHERE mismatch AND reviewed_at IS NULL;
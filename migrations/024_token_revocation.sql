@@ -0,0 +1,13 @@
+-- Forced Logout Schema
+-- Access tokens of a user issued at or before tokens_revoked_at are
+-- rejected; every API instance reloads the cutoffs periodically
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-19
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS tokens_revoked_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_users_tokens_revoked_at
+    ON users(tokens_revoked_at) WHERE tokens_revoked_at IS NOT NULL;
+
+COMMENT ON COLUMN users.tokens_revoked_at IS 'Access tokens issued at or before this time are rejected (forced logout)';
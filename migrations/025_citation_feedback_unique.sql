@@ -0,0 +1,18 @@
+-- Citation Feedback Deduplication
+-- One vote per user on each cited chunk of a query; a repeated vote
+-- replaces the earlier one instead of adding to it
+--
+-- Author: hephaex@gmail.com
+-- Date: 2026-10-19
+
+-- Keep only the latest of the votes already repeated
+DELETE FROM citation_feedback f
+USING citation_feedback later
+WHERE f.query_id = later.query_id
+  AND f.user_id IS NOT DISTINCT FROM later.user_id
+  AND f.document_id = later.document_id
+  AND f.chunk_index IS NOT DISTINCT FROM later.chunk_index
+  AND (f.created_at, f.id) < (later.created_at, later.id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_citation_feedback_vote
+    ON citation_feedback(query_id, user_id, document_id, chunk_index) NULLS NOT DISTINCT;
@@ -19,7 +19,8 @@ CREATE TYPE verification_status AS ENUM (
     'pending',
     'in_review',
     'approved',
-    'rejected'
+    'rejected',
+    'adjudication'
 );
 
 CREATE TYPE file_type AS ENUM (
@@ -80,7 +81,7 @@ CREATE TABLE document_chunks (
     document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
     chunk_index INTEGER NOT NULL,
     content TEXT NOT NULL,
-    content_hash VARCHAR(64),  -- For deduplication
+    content_hash VARCHAR(64),  -- SHA-256 of content when written
     
     -- Location info
     page_number INTEGER,
@@ -91,6 +92,14 @@ CREATE TABLE document_chunks (
     -- Vector store reference
     vector_id VARCHAR(100),  -- Qdrant point ID
     
+    -- Integrity check (content no longer matches its hash or the original)
+    corrupted BOOLEAN NOT NULL DEFAULT FALSE,
+    integrity_checked_at TIMESTAMPTZ,
+    
+    -- Near-duplicate detection (SimHash fingerprint, canonical copy)
+    simhash BIGINT,
+    canonical_chunk_id UUID REFERENCES document_chunks(id) ON DELETE SET NULL,
+    
     -- Timestamps
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     
@@ -99,6 +108,35 @@ CREATE TABLE document_chunks (
 
 CREATE INDEX idx_chunks_document ON document_chunks(document_id);
 CREATE INDEX idx_chunks_vector ON document_chunks(vector_id);
+CREATE INDEX idx_chunks_corrupted ON document_chunks(document_id) WHERE corrupted;
+CREATE INDEX idx_chunks_simhash_band0 ON document_chunks (((simhash >> 48) & 65535)) WHERE simhash IS NOT NULL;
+CREATE INDEX idx_chunks_simhash_band1 ON document_chunks (((simhash >> 32) & 65535)) WHERE simhash IS NOT NULL;
+CREATE INDEX idx_chunks_simhash_band2 ON document_chunks (((simhash >> 16) & 65535)) WHERE simhash IS NOT NULL;
+CREATE INDEX idx_chunks_simhash_band3 ON document_chunks ((simhash & 65535)) WHERE simhash IS NOT NULL;
+CREATE INDEX idx_chunks_canonical ON document_chunks(canonical_chunk_id) WHERE canonical_chunk_id IS NOT NULL;
+
+-- Data keys of encrypted (Restricted) documents, wrapped by a master key
+CREATE TABLE document_keys (
+    document_id UUID PRIMARY KEY,
+    key_id VARCHAR(100) NOT NULL,  -- Key-encryption key that wrapped it
+    algorithm VARCHAR(20) NOT NULL DEFAULT 'AES-256-GCM',
+    wrapped_key TEXT NOT NULL,     -- Base64 nonce, ciphertext and tag
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_document_keys_key_id ON document_keys(key_id);
+
+-- ==========================================================================
+-- Document Lineage Table
+-- ==========================================================================
+
+-- No foreign key: documents ingested through the API may have no row in
+-- documents. Removed by the purge job together with the document.
+CREATE TABLE document_lineage (
+    document_id UUID PRIMARY KEY,
+    lineage JSONB NOT NULL,  -- Parser, OCR, chunker, embedding and extraction models per document and chunk
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
 
 -- ==========================================================================
 -- Extraction Queue Table (for HITL verification)
@@ -112,6 +150,7 @@ CREATE TABLE extraction_queue (
     extracted_entities JSONB NOT NULL DEFAULT '[]',
     extracted_relations JSONB NOT NULL DEFAULT '[]',
     source_text TEXT,
+    extractor VARCHAR(20),  -- rule | llm | table | form
     
     -- Confidence
     confidence_score REAL DEFAULT 0.0,
@@ -120,19 +159,84 @@ CREATE TABLE extraction_queue (
     status verification_status NOT NULL DEFAULT 'pending',
     reviewer_id VARCHAR(100),
     review_notes TEXT,
+
+    -- Assignment and review lock
+    assigned_to VARCHAR(100),
+    assigned_at TIMESTAMPTZ,
+    locked_by VARCHAR(100),
+    lock_expires_at TIMESTAMPTZ,
     
     -- Timestamps
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     reviewed_at TIMESTAMPTZ,
     
     -- Priority (lower = higher priority)
-    priority INTEGER DEFAULT 100
+    priority INTEGER DEFAULT 100,
+
+    -- Approved without review by the auto-approval policy
+    auto_approved BOOLEAN NOT NULL DEFAULT FALSE
 );
 
 CREATE INDEX idx_extraction_status ON extraction_queue(status);
 CREATE INDEX idx_extraction_priority ON extraction_queue(priority, created_at);
 CREATE INDEX idx_extraction_document ON extraction_queue(document_id);
 CREATE INDEX idx_extraction_reviewer ON extraction_queue(reviewer_id);
+CREATE INDEX idx_extraction_assigned ON extraction_queue(assigned_to, status);
+CREATE INDEX idx_extraction_lock ON extraction_queue(lock_expires_at) WHERE status = 'in_review';
+
+-- Decisions of each reviewer; documents whose access level needs several
+-- approvals collect more than one per extraction
+CREATE TABLE extraction_reviews (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    extraction_id UUID NOT NULL REFERENCES extraction_queue(id) ON DELETE CASCADE,
+    reviewer_id VARCHAR(100) NOT NULL,
+    decision VARCHAR(10) NOT NULL,  -- approve | reject
+    correction JSONB,  -- Correction proposed with an approval
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (extraction_id, reviewer_id)
+);
+
+CREATE INDEX idx_extraction_reviews_created ON extraction_reviews(created_at);
+
+-- Auto-approved extractions sampled for quality audit
+CREATE TABLE extraction_audits (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    extraction_id UUID NOT NULL UNIQUE REFERENCES extraction_queue(id) ON DELETE CASCADE,
+    confidence REAL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',  -- pending | correct | incorrect
+    auditor_id VARCHAR(100),
+    notes TEXT,
+    sampled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    audited_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_extraction_audits_status ON extraction_audits(status, sampled_at);
+
+-- Auto-approve threshold changes; the newest row is in effect
+CREATE TABLE auto_approve_thresholds (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    threshold REAL NOT NULL,
+    reason TEXT,
+    changed_by VARCHAR(100),  -- NULL for automatic changes
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Auto-approval rules; the newest row is in effect
+CREATE TABLE auto_approve_policies (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    rules JSONB NOT NULL DEFAULT '[]',
+    changed_by VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Form layouts used to recognize form types and extract their fields
+CREATE TABLE form_templates (
+    name VARCHAR(100) PRIMARY KEY,
+    template JSONB NOT NULL,
+    updated_by VARCHAR(100),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
 
 -- ==========================================================================
 -- Users Table (for ACL reference)
@@ -185,6 +289,7 @@ CREATE TABLE query_stats (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     user_id VARCHAR(100),
     query_text TEXT NOT NULL,
+    keywords TEXT[] NOT NULL DEFAULT '{}',  -- Analyzer keywords, used for FAQ clustering
     
     -- Performance metrics
     total_time_ms INTEGER,
@@ -206,6 +311,155 @@ CREATE TABLE query_stats (
 CREATE INDEX idx_query_stats_user ON query_stats(user_id);
 CREATE INDEX idx_query_stats_created ON query_stats(created_at DESC);
 
+-- ==========================================================================
+-- Content Gaps Table (queries with empty or weak retrieval)
+-- ==========================================================================
+
+CREATE TABLE content_gaps (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    question TEXT NOT NULL,
+    keywords TEXT[] NOT NULL DEFAULT '{}',  -- Analyzer keywords, used for topic clustering
+    reason VARCHAR(20) NOT NULL,  -- no_results | low_confidence
+    confidence REAL NOT NULL DEFAULT 0.0,
+    user_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Resolution
+    resolved_at TIMESTAMPTZ,
+    resolved_by UUID,
+    resolved_documents UUID[] NOT NULL DEFAULT '{}',  -- Documents ingested to cover the gap
+    resolution_note TEXT
+);
+
+CREATE INDEX idx_content_gaps_created ON content_gaps(created_at DESC);
+CREATE INDEX idx_content_gaps_open ON content_gaps(created_at DESC) WHERE resolved_at IS NULL;
+
+-- ==========================================================================
+-- Glossary Table (curated term definitions)
+-- ==========================================================================
+
+CREATE TABLE glossary_terms (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    term VARCHAR(200) NOT NULL,
+    aliases TEXT[] NOT NULL DEFAULT '{}',
+    lookup_keys TEXT[] NOT NULL DEFAULT '{}',  -- Normalized term and aliases
+    definition TEXT NOT NULL,
+    source TEXT,  -- Document title, regulation or URL the definition comes from
+    document_id UUID REFERENCES documents(id) ON DELETE SET NULL,
+
+    -- Access control (same model as documents)
+    access_level access_level NOT NULL DEFAULT 'internal',
+    department VARCHAR(100),
+    required_roles TEXT[] NOT NULL DEFAULT '{}',
+    allowed_users TEXT[] NOT NULL DEFAULT '{}',
+
+    status VARCHAR(20) NOT NULL DEFAULT 'approved',  -- approved | candidate
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_glossary_terms_lookup ON glossary_terms USING GIN(lookup_keys);
+CREATE INDEX idx_glossary_terms_status ON glossary_terms(status);
+
+-- ==========================================================================
+-- FAQ Table (generated from frequent queries, reviewed by admins)
+-- ==========================================================================
+
+CREATE TABLE faq_entries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    question TEXT NOT NULL,  -- Canonical question
+    answer TEXT NOT NULL,
+    citations JSONB NOT NULL DEFAULT '[]',
+    keywords TEXT[] NOT NULL DEFAULT '{}',  -- Cluster keywords, matched against query keywords
+    source_questions TEXT[] NOT NULL DEFAULT '{}',  -- Most asked questions of the cluster
+    query_count INTEGER NOT NULL DEFAULT 0,
+    confidence REAL NOT NULL DEFAULT 0.0,
+
+    -- Access control (most restrictive cited document)
+    access_level access_level NOT NULL DEFAULT 'internal',
+    department VARCHAR(100),
+    required_roles TEXT[] NOT NULL DEFAULT '{}',
+    allowed_users TEXT[] NOT NULL DEFAULT '{}',
+
+    -- Review
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',  -- pending | approved | rejected
+    reviewed_by UUID,
+    reviewed_at TIMESTAMPTZ,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_faq_entries_keywords ON faq_entries USING GIN(keywords);
+CREATE INDEX idx_faq_entries_status ON faq_entries(status, query_count DESC);
+
+-- ==========================================================================
+-- Freshness Alerts Table (stale documents found by the freshness check)
+-- ==========================================================================
+
+CREATE TABLE freshness_alerts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    reason VARCHAR(20) NOT NULL,  -- review_overdue | superseded
+    review_date DATE,  -- Review date that had passed
+    superseded_by UUID,  -- Newer version of the document
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    acknowledged_at TIMESTAMPTZ,
+    acknowledged_by UUID,
+    resolved_at TIMESTAMPTZ  -- Set when the condition no longer holds
+);
+
+CREATE UNIQUE INDEX idx_freshness_alerts_open
+    ON freshness_alerts(document_id, reason) WHERE resolved_at IS NULL;
+CREATE INDEX idx_freshness_alerts_detected ON freshness_alerts(detected_at DESC);
+
+-- ==========================================================================
+-- Embedding Migrations Table (re-embedding into a new model's collection)
+-- ==========================================================================
+
+CREATE TABLE embedding_migrations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    source_collection VARCHAR(255) NOT NULL,
+    target_collection VARCHAR(255) NOT NULL,
+    embedding_model VARCHAR(255) NOT NULL,
+    dimension INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running',  -- running | shadow_testing | ready | switched | failed
+
+    -- Progress
+    total_points BIGINT NOT NULL DEFAULT 0,
+    embedded_points BIGINT NOT NULL DEFAULT 0,
+    failed_points BIGINT NOT NULL DEFAULT 0,
+
+    auto_switch BOOLEAN NOT NULL DEFAULT FALSE,
+    shadow_report JSONB,  -- Retrieval overlap with the source collection
+    error TEXT,
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    switched_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_embedding_migrations_status ON embedding_migrations(status, switched_at DESC);
+
+-- ==========================================================================
+-- Citation Feedback Table (helpful / unhelpful votes, ranking priors)
+-- ==========================================================================
+
+CREATE TABLE citation_feedback (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    query_id UUID NOT NULL,  -- ID returned by POST /api/v1/query
+    user_id UUID,
+    document_id UUID NOT NULL,
+    chunk_index INTEGER,
+    helpful BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_citation_feedback_created ON citation_feedback(created_at DESC);
+CREATE INDEX idx_citation_feedback_document ON citation_feedback(document_id);
+CREATE UNIQUE INDEX idx_citation_feedback_vote
+    ON citation_feedback(query_id, user_id, document_id, chunk_index) NULLS NOT DISTINCT;
+
 -- ==========================================================================
 -- Helper Functions
 -- ==========================================================================